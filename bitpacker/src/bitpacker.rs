@@ -2,7 +2,7 @@ use std::convert::TryInto;
 use std::io;
 use std::ops::{Range, RangeInclusive};
 
-use bitpacking::{BitPacker as ExternalBitPackerTrait, BitPacker1x};
+use crate::unpack_vec::{self, BLOCK_LEN};
 
 pub struct BitPacker {
     mini_buffer: u64,
@@ -151,7 +151,7 @@ impl BitUnpacker {
             }
         };
 
-        // We use an unrolled routine to decode 32 values at once.
+        // We use a SIMD routine, when available, to decode 32 values at once.
         // We therefore decompose our range of values to decode into three ranges:
         // - Entrance ramp: [start_idx, fast_track_start) (up to 31 values)
         // - Highway: [fast_track_start, fast_track_end) (a length multiple of 32s)
@@ -164,14 +164,14 @@ impl BitUnpacker {
 
         let highway_start: u32 = start_idx + entrance_ramp_len;
 
-        if highway_start + BitPacker1x::BLOCK_LEN as u32 > end_idx {
+        if highway_start + BLOCK_LEN as u32 > end_idx {
             // We don't have enough values to have even a single block of highway.
             // Let's just supply the values the simple way.
             get_batch_ramp(start_idx, output);
             return;
         }
 
-        let num_blocks: u32 = (end_idx - highway_start) / BitPacker1x::BLOCK_LEN as u32;
+        let num_blocks: u32 = (end_idx - highway_start) / BLOCK_LEN as u32;
 
         // Entrance ramp
         get_batch_ramp(start_idx, &mut output[..entrance_ramp_len as usize]);
@@ -180,16 +180,16 @@ impl BitUnpacker {
         let mut offset = (highway_start * self.num_bits) as usize / 8;
         let mut output_cursor = (highway_start - start_idx) as usize;
         for _ in 0..num_blocks {
-            offset += BitPacker1x.decompress(
-                &data[offset..],
-                &mut output[output_cursor..],
-                self.num_bits as u8,
-            );
-            output_cursor += 32;
+            let output_block: &mut [u32; BLOCK_LEN] = (&mut output
+                [output_cursor..output_cursor + BLOCK_LEN])
+                .try_into()
+                .unwrap();
+            offset += unpack_vec::unpack_block(&data[offset..], self.num_bits as u8, output_block);
+            output_cursor += BLOCK_LEN;
         }
 
         // Exit ramp
-        let highway_end = highway_start + num_blocks * BitPacker1x::BLOCK_LEN as u32;
+        let highway_end = highway_start + num_blocks * BLOCK_LEN as u32;
         get_batch_ramp(highway_end, &mut output[output_cursor..]);
     }
 
@@ -1,11 +1,13 @@
 mod bitpacker;
 mod blocked_bitpacker;
 mod filter_vec;
+mod unpack_vec;
 
 use std::cmp::Ordering;
 
 pub use crate::bitpacker::{BitPacker, BitUnpacker};
 pub use crate::blocked_bitpacker::BlockedBitpacker;
+pub use crate::unpack_vec::{unpack_block_4x, unpack_block_4x_sorted, BLOCK_4X_LEN};
 
 /// Computes the number of bits that will be used for bitpacking.
 ///
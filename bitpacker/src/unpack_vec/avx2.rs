@@ -0,0 +1,164 @@
+//! AVX2 decoding of blocks of bitpacked values.
+//!
+//! For the blocks of 32 values packed one after the other, each lane gathers the 4 bytes
+//! starting at the first byte of its value, shifts them right by the bit offset of the value
+//! within this byte, and masks the result. A value therefore needs to fit within these 4 bytes,
+//! which is the case for bit widths up to 25.
+//!
+//! For the blocks of 128 values packed in 4 interleaved lanes, the values at the same position
+//! in the 4 lanes share their bit offset, so that each half of a register decodes the 4 values
+//! of a position from the 16 bytes of the 32 bits words holding them.
+use std::arch::x86_64::{
+    __m128i, __m256i as DataType, _mm256_add_epi32 as op_add, _mm256_and_si256 as op_and,
+    _mm256_castsi128_si256, _mm256_i32gather_epi32 as gather, _mm256_inserti128_si256,
+    _mm256_or_si256 as op_or, _mm256_permutevar8x32_epi32 as permute, _mm256_set1_epi32 as set1,
+    _mm256_setr_epi32 as setr, _mm256_slli_si256 as left_shift_lanes,
+    _mm256_sllv_epi32 as left_shift_var, _mm256_srli_epi32 as right_shift_32,
+    _mm256_srlv_epi32 as right_shift_var, _mm256_storeu_si256 as store_unaligned, _mm_loadu_si128,
+};
+
+use super::{BLOCK_4X_LEN, BLOCK_LEN};
+
+const NUM_LANES: usize = 8;
+
+/// The largest bit width decoded by this kernel.
+const MAX_NUM_BITS: u8 = 25;
+
+/// Decodes the block of 32 values starting at the beginning of `data`, and returns the number
+/// of bytes of the block.
+///
+/// The blocks with a bit width greater than 25, or too close to the end of `data` for the
+/// gathers to stay within its bounds, are decoded with the scalar kernel.
+pub fn unpack_block(data: &[u8], num_bits: u8, output: &mut [u32; BLOCK_LEN]) -> usize {
+    let last_value_byte = (BLOCK_LEN - 1) * num_bits as usize / 8;
+    if num_bits == 0 || num_bits > MAX_NUM_BITS || last_value_byte + 4 > data.len() {
+        return super::scalar::unpack_block(data, num_bits, output);
+    }
+    unsafe { unpack_block_avx2(data.as_ptr(), num_bits, output) };
+    num_bits as usize * BLOCK_LEN / 8
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn unpack_block_avx2(data: *const u8, num_bits: u8, output: &mut [u32; BLOCK_LEN]) {
+    let num_bits = num_bits as i32;
+    let mask = set1(((1u32 << num_bits) - 1) as i32);
+    let lanes_num_bits = set1(num_bits * NUM_LANES as i32);
+    let mut bit_offsets: DataType = setr(
+        0,
+        num_bits,
+        2 * num_bits,
+        3 * num_bits,
+        4 * num_bits,
+        5 * num_bits,
+        6 * num_bits,
+        7 * num_bits,
+    );
+    let bit_shift_mask = set1(7);
+    for chunk in output.chunks_exact_mut(NUM_LANES) {
+        let byte_offsets = right_shift_32(bit_offsets, 3);
+        let words = gather(data as *const i32, byte_offsets, 1);
+        let shifts = op_and(bit_offsets, bit_shift_mask);
+        let vals = op_and(right_shift_var(words, shifts), mask);
+        store_unaligned(chunk.as_mut_ptr() as *mut DataType, vals);
+        bit_offsets = op_add(bit_offsets, lanes_num_bits);
+    }
+}
+
+/// Decodes the block of 128 values packed in 4 interleaved lanes starting at the beginning of
+/// `data`, and returns the number of bytes of the block.
+///
+/// If `offset` is given, the values are the deltas between consecutive values and are
+/// integrated starting from `offset`.
+pub fn unpack_block_4x(
+    data: &[u8],
+    num_bits: u8,
+    offset: Option<u32>,
+    output: &mut [u32; BLOCK_4X_LEN],
+) -> usize {
+    if num_bits == 0 || num_bits > 32 || data.len() < num_bits as usize * 16 {
+        return super::scalar::unpack_block_4x(data, num_bits, offset, output);
+    }
+    unsafe { unpack_block_4x_avx2(data.as_ptr(), num_bits, offset, output) };
+    num_bits as usize * 16
+}
+
+/// Returns the 16 bytes of the 32 bits words of the 4 lanes at `word_idx`.
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn load_words(data: *const u8, word_idx: usize) -> __m128i {
+    _mm_loadu_si128(data.add(word_idx * 16) as *const __m128i)
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn unpack_block_4x_avx2(
+    data: *const u8,
+    num_bits: u8,
+    offset: Option<u32>,
+    output: &mut [u32; BLOCK_4X_LEN],
+) {
+    let num_bits = num_bits as usize;
+    let mask = set1((u32::MAX >> (32 - num_bits)) as i32);
+    let low_half_last = setr(0, 0, 0, 0, 3, 3, 3, 3);
+    let high_half_mask = setr(0, 0, 0, 0, -1, -1, -1, -1);
+    let last = set1(7);
+    let mut previous = set1(offset.unwrap_or(0) as i32);
+    for (pair_idx, chunk) in output.chunks_exact_mut(8).enumerate() {
+        let mut words = [0usize; 2];
+        let mut next_words = [0usize; 2];
+        let mut shifts = [0i32; 2];
+        let mut next_shifts = [32i32; 2];
+        for half in 0..2 {
+            let bit_offset = (pair_idx * 2 + half) * num_bits;
+            words[half] = bit_offset / 32;
+            shifts[half] = (bit_offset % 32) as i32;
+            // The value overflows into the next word.
+            if bit_offset % 32 + num_bits > 32 {
+                next_words[half] = words[half] + 1;
+                next_shifts[half] = 32 - shifts[half];
+            } else {
+                // Shifting by 32 bits clears the lanes, any word in bounds does.
+                next_words[half] = words[half];
+            }
+        }
+        let low_words = _mm256_inserti128_si256(
+            _mm256_castsi128_si256(load_words(data, words[0])),
+            load_words(data, words[1]),
+            1,
+        );
+        let high_words = _mm256_inserti128_si256(
+            _mm256_castsi128_si256(load_words(data, next_words[0])),
+            load_words(data, next_words[1]),
+            1,
+        );
+        let shifts = setr(
+            shifts[0], shifts[0], shifts[0], shifts[0], shifts[1], shifts[1], shifts[1], shifts[1],
+        );
+        let next_shifts = setr(
+            next_shifts[0],
+            next_shifts[0],
+            next_shifts[0],
+            next_shifts[0],
+            next_shifts[1],
+            next_shifts[1],
+            next_shifts[1],
+            next_shifts[1],
+        );
+        let mut vals = op_and(
+            op_or(
+                right_shift_var(low_words, shifts),
+                left_shift_var(high_words, next_shifts),
+            ),
+            mask,
+        );
+        if offset.is_some() {
+            // Prefix sum within each half, then carried over from the low half to the high
+            // half, and from the previous pair.
+            vals = op_add(vals, left_shift_lanes(vals, 4));
+            vals = op_add(vals, left_shift_lanes(vals, 8));
+            vals = op_add(vals, op_and(permute(vals, low_half_last), high_half_mask));
+            vals = op_add(vals, previous);
+            previous = permute(vals, last);
+        }
+        store_unaligned(chunk.as_mut_ptr() as *mut DataType, vals);
+    }
+}
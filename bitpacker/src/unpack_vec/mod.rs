@@ -0,0 +1,352 @@
+#[cfg(target_arch = "x86_64")]
+mod avx2;
+#[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+mod neon;
+
+mod scalar;
+
+/// Number of values decoded at once by the kernels.
+pub(crate) const BLOCK_LEN: usize = 32;
+
+/// Number of values of the blocks packed in 4 interleaved lanes.
+pub const BLOCK_4X_LEN: usize = 128;
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[repr(u8)]
+enum UnpackImplPerInstructionSet {
+    #[cfg(target_arch = "x86_64")]
+    AVX2 = 0u8,
+    Scalar = 1u8,
+    #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+    Neon = 2u8,
+}
+
+impl UnpackImplPerInstructionSet {
+    #[inline]
+    pub fn is_available(&self) -> bool {
+        match *self {
+            #[cfg(target_arch = "x86_64")]
+            UnpackImplPerInstructionSet::AVX2 => is_x86_feature_detected!("avx2"),
+            #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+            UnpackImplPerInstructionSet::Neon => std::arch::is_aarch64_feature_detected!("neon"),
+            UnpackImplPerInstructionSet::Scalar => true,
+        }
+    }
+}
+
+// List of available implementation in preferred order.
+#[cfg(target_arch = "x86_64")]
+const IMPLS: [UnpackImplPerInstructionSet; 2] = [
+    UnpackImplPerInstructionSet::AVX2,
+    UnpackImplPerInstructionSet::Scalar,
+];
+
+#[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+const IMPLS: [UnpackImplPerInstructionSet; 2] = [
+    UnpackImplPerInstructionSet::Neon,
+    UnpackImplPerInstructionSet::Scalar,
+];
+
+#[cfg(not(any(
+    target_arch = "x86_64",
+    all(target_arch = "aarch64", target_endian = "little")
+)))]
+const IMPLS: [UnpackImplPerInstructionSet; 1] = [UnpackImplPerInstructionSet::Scalar];
+
+impl UnpackImplPerInstructionSet {
+    #[allow(unused_variables)]
+    #[inline]
+    fn from(code: u8) -> UnpackImplPerInstructionSet {
+        #[cfg(target_arch = "x86_64")]
+        if code == UnpackImplPerInstructionSet::AVX2 as u8 {
+            return UnpackImplPerInstructionSet::AVX2;
+        }
+        #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+        if code == UnpackImplPerInstructionSet::Neon as u8 {
+            return UnpackImplPerInstructionSet::Neon;
+        }
+        UnpackImplPerInstructionSet::Scalar
+    }
+
+    #[inline]
+    fn unpack_block(self, data: &[u8], num_bits: u8, output: &mut [u32; BLOCK_LEN]) -> usize {
+        match self {
+            #[cfg(target_arch = "x86_64")]
+            UnpackImplPerInstructionSet::AVX2 => avx2::unpack_block(data, num_bits, output),
+            #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+            UnpackImplPerInstructionSet::Neon => neon::unpack_block(data, num_bits, output),
+            UnpackImplPerInstructionSet::Scalar => scalar::unpack_block(data, num_bits, output),
+        }
+    }
+
+    #[inline]
+    fn unpack_block_4x(
+        self,
+        data: &[u8],
+        num_bits: u8,
+        offset: Option<u32>,
+        output: &mut [u32; BLOCK_4X_LEN],
+    ) -> usize {
+        match self {
+            #[cfg(target_arch = "x86_64")]
+            UnpackImplPerInstructionSet::AVX2 => {
+                avx2::unpack_block_4x(data, num_bits, offset, output)
+            }
+            #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+            UnpackImplPerInstructionSet::Neon => {
+                neon::unpack_block_4x(data, num_bits, offset, output)
+            }
+            UnpackImplPerInstructionSet::Scalar => {
+                scalar::unpack_block_4x(data, num_bits, offset, output)
+            }
+        }
+    }
+}
+
+#[inline]
+fn get_best_available_instruction_set() -> UnpackImplPerInstructionSet {
+    use std::sync::atomic::{AtomicU8, Ordering};
+    static INSTRUCTION_SET_BYTE: AtomicU8 = AtomicU8::new(u8::MAX);
+    let instruction_set_byte: u8 = INSTRUCTION_SET_BYTE.load(Ordering::Relaxed);
+    if instruction_set_byte == u8::MAX {
+        // Let's initialize the instruction set and cache it.
+        let instruction_set = IMPLS
+            .into_iter()
+            .find(UnpackImplPerInstructionSet::is_available)
+            .unwrap();
+        INSTRUCTION_SET_BYTE.store(instruction_set as u8, Ordering::Relaxed);
+        return instruction_set;
+    }
+    UnpackImplPerInstructionSet::from(instruction_set_byte)
+}
+
+/// Decodes the block of 32 values of `num_bits` bits starting at the beginning of `data`, and
+/// returns the number of bytes of the block.
+///
+/// `num_bits` must be <= 32, and `data` must hold the whole block.
+pub fn unpack_block(data: &[u8], num_bits: u8, output: &mut [u32; BLOCK_LEN]) -> usize {
+    get_best_available_instruction_set().unpack_block(data, num_bits, output)
+}
+
+/// Decodes the block of 128 values of `num_bits` bits packed in 4 interleaved lanes starting at
+/// the beginning of `data`, as packed by the `BitPacker4x` of the `bitpacking` crate, and
+/// returns the number of bytes of the block.
+///
+/// This is the layout of the blocks of the postings.
+///
+/// # Panics
+///
+/// Panics if `num_bits` is > 32, or if `data` does not hold the whole block.
+pub fn unpack_block_4x(data: &[u8], num_bits: u8, output: &mut [u32; BLOCK_4X_LEN]) -> usize {
+    get_best_available_instruction_set().unpack_block_4x(data, num_bits, None, output)
+}
+
+/// Decodes the block of 128 deltas of `num_bits` bits packed in 4 interleaved lanes starting at
+/// the beginning of `data`, as packed by `BitPacker4x::compress_sorted()` of the `bitpacking`
+/// crate, and returns the number of bytes of the block.
+///
+/// The values are integrated starting from `offset`, the value preceding the block.
+///
+/// # Panics
+///
+/// Panics if `num_bits` is > 32, or if `data` does not hold the whole block.
+pub fn unpack_block_4x_sorted(
+    offset: u32,
+    data: &[u8],
+    num_bits: u8,
+    output: &mut [u32; BLOCK_4X_LEN],
+) -> usize {
+    get_best_available_instruction_set().unpack_block_4x(data, num_bits, Some(offset), output)
+}
+
+#[cfg(test)]
+mod tests {
+    use bitpacking::{BitPacker as ExternalBitPackerTrait, BitPacker4x};
+
+    use super::*;
+    use crate::BitPacker;
+
+    fn pack(vals: &[u64], num_bits: u8) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut bitpacker = BitPacker::new();
+        for &val in vals {
+            bitpacker.write(val, num_bits, &mut data).unwrap();
+        }
+        bitpacker.flush(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_get_best_available_instruction_set() {
+        // We just make sure the function returns without crashing and returns the same result.
+        let instruction_set = get_best_available_instruction_set();
+        assert_eq!(get_best_available_instruction_set(), instruction_set);
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_instruction_set_to_code_from_code() {
+        for instruction_set in [
+            UnpackImplPerInstructionSet::AVX2,
+            UnpackImplPerInstructionSet::Scalar,
+        ] {
+            let code = instruction_set as u8;
+            assert_eq!(instruction_set, UnpackImplPerInstructionSet::from(code));
+        }
+    }
+
+    fn test_unpack_impl_all_num_bits(unpack_impl: UnpackImplPerInstructionSet) {
+        for num_bits in 0u8..=32u8 {
+            let max_val = (1u64 << num_bits) - 1;
+            let vals: Vec<u64> = (0..BLOCK_LEN as u64 * 2)
+                .map(|i| (i * 2_654_435_761) & max_val)
+                .collect();
+            let data = pack(&vals, num_bits);
+            let mut output = [0u32; BLOCK_LEN];
+            let num_bytes = unpack_impl.unpack_block(&data, num_bits, &mut output);
+            assert_eq!(num_bytes, num_bits as usize * 4);
+            assert!(output
+                .iter()
+                .map(|&val| val as u64)
+                .eq(vals[..BLOCK_LEN].iter().cloned()));
+            // The last block of the data is decoded as well.
+            unpack_impl.unpack_block(&data[num_bytes..], num_bits, &mut output);
+            assert!(output
+                .iter()
+                .map(|&val| val as u64)
+                .eq(vals[BLOCK_LEN..].iter().cloned()));
+        }
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn test_unpack_implementation_avx2() {
+        if UnpackImplPerInstructionSet::AVX2.is_available() {
+            test_unpack_impl_all_num_bits(UnpackImplPerInstructionSet::AVX2);
+        }
+    }
+
+    #[test]
+    #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+    fn test_unpack_implementation_neon() {
+        if UnpackImplPerInstructionSet::Neon.is_available() {
+            test_unpack_impl_all_num_bits(UnpackImplPerInstructionSet::Neon);
+        }
+    }
+
+    #[test]
+    fn test_unpack_implementation_scalar() {
+        test_unpack_impl_all_num_bits(UnpackImplPerInstructionSet::Scalar);
+    }
+
+    fn test_unpack_4x_impl_all_num_bits(unpack_impl: UnpackImplPerInstructionSet) {
+        let bitpacker = BitPacker4x::new();
+        for num_bits in 0u8..=32u8 {
+            let max_val = (1u64 << num_bits) - 1;
+            let vals: Vec<u32> = (0..BLOCK_4X_LEN as u64)
+                .map(|i| ((i * 2_654_435_761) & max_val) as u32)
+                .collect();
+            let mut data = vec![0u8; BLOCK_4X_LEN * 4];
+            let num_bytes = bitpacker.compress(&vals, &mut data, num_bits);
+            data.truncate(num_bytes);
+            let mut output = [0u32; BLOCK_4X_LEN];
+            assert_eq!(
+                unpack_impl.unpack_block_4x(&data, num_bits, None, &mut output),
+                num_bytes
+            );
+            assert_eq!(&output[..], &vals[..]);
+
+            // Deltas, integrated from the offset.
+            let offset = 1_000u32;
+            let sorted_vals: Vec<u32> = vals
+                .iter()
+                .scan(offset, |val, &delta| {
+                    *val = val.wrapping_add(delta);
+                    Some(*val)
+                })
+                .collect();
+            let mut data = vec![0u8; BLOCK_4X_LEN * 4];
+            let num_bytes = bitpacker.compress_sorted(offset, &sorted_vals, &mut data, num_bits);
+            data.truncate(num_bytes);
+            assert_eq!(
+                unpack_impl.unpack_block_4x(&data, num_bits, Some(offset), &mut output),
+                num_bytes
+            );
+            assert_eq!(&output[..], &sorted_vals[..]);
+        }
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn test_unpack_4x_implementation_avx2() {
+        if UnpackImplPerInstructionSet::AVX2.is_available() {
+            test_unpack_4x_impl_all_num_bits(UnpackImplPerInstructionSet::AVX2);
+        }
+    }
+
+    #[test]
+    #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+    fn test_unpack_4x_implementation_neon() {
+        if UnpackImplPerInstructionSet::Neon.is_available() {
+            test_unpack_4x_impl_all_num_bits(UnpackImplPerInstructionSet::Neon);
+        }
+    }
+
+    #[test]
+    fn test_unpack_4x_implementation_scalar() {
+        test_unpack_4x_impl_all_num_bits(UnpackImplPerInstructionSet::Scalar);
+    }
+
+    /// Checks that a kernel decodes the same values as the scalar kernel.
+    fn check_unpack_same_as_scalar(
+        unpack_impl: UnpackImplPerInstructionSet,
+        num_bits: u8,
+        vals: &[u32],
+        offset: u32,
+    ) {
+        let max_val = (1u64 << num_bits) - 1;
+        let vals: Vec<u32> = vals.iter().map(|&val| (val as u64 & max_val) as u32).collect();
+
+        let data = pack(
+            &vals[..BLOCK_LEN]
+                .iter()
+                .map(|&val| val as u64)
+                .collect::<Vec<u64>>(),
+            num_bits,
+        );
+        let mut output = [0u32; BLOCK_LEN];
+        let mut scalar_output = [0u32; BLOCK_LEN];
+        unpack_impl.unpack_block(&data, num_bits, &mut output);
+        UnpackImplPerInstructionSet::Scalar.unpack_block(&data, num_bits, &mut scalar_output);
+        assert_eq!(output, scalar_output);
+
+        let mut data = vec![0u8; BLOCK_4X_LEN * 4];
+        let num_bytes = BitPacker4x::new().compress(&vals, &mut data, num_bits);
+        for offset in [None, Some(offset)] {
+            let mut output = [0u32; BLOCK_4X_LEN];
+            let mut scalar_output = [0u32; BLOCK_4X_LEN];
+            unpack_impl.unpack_block_4x(&data[..num_bytes], num_bits, offset, &mut output);
+            UnpackImplPerInstructionSet::Scalar.unpack_block_4x(
+                &data[..num_bytes],
+                num_bits,
+                offset,
+                &mut scalar_output,
+            );
+            assert_eq!(output, scalar_output);
+        }
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_unpack_compare_with_scalar_impl_proptest(
+            num_bits in 0u8..=32u8,
+            vals in proptest::collection::vec(proptest::prelude::any::<u32>(), BLOCK_4X_LEN),
+            offset in proptest::prelude::any::<u32>()) {
+            for unpack_impl in IMPLS {
+                if unpack_impl.is_available() {
+                    check_unpack_same_as_scalar(unpack_impl, num_bits, &vals, offset);
+                }
+            }
+       }
+    }
+}
@@ -0,0 +1,122 @@
+//! NEON decoding of blocks of bitpacked values.
+//!
+//! For the blocks of 32 values packed one after the other, the 4 values decoded at once span at
+//! most 14 bytes for bit widths up to 25. A table lookup moves the 4 bytes starting at the first
+//! byte of each value into its lane, which is then shifted right by the bit offset of the value
+//! within this byte, and masked.
+//!
+//! For the blocks of 128 values packed in 4 interleaved lanes, the values at the same position
+//! in the 4 lanes share their bit offset, so that a register decodes the 4 values of a position
+//! from the 16 bytes of the 32 bits words holding them.
+use std::arch::aarch64::{
+    uint32x4_t as DataType, vaddq_u32 as op_add, vandq_u32 as op_and, vdupq_laneq_u32, vdupq_n_s32,
+    vdupq_n_u32 as set1, vextq_u32, vld1q_s32, vld1q_u8, vorrq_u32 as op_or,
+    vqtbl1q_u8 as table_lookup, vreinterpretq_u32_u8, vshlq_u32 as shift_var, vst1q_u32,
+};
+
+use super::{BLOCK_4X_LEN, BLOCK_LEN};
+
+const NUM_LANES: usize = 4;
+
+/// The largest bit width decoded by the kernel of the blocks of 32 values.
+const MAX_NUM_BITS: u8 = 25;
+
+/// Decodes the block of 32 values starting at the beginning of `data`, and returns the number
+/// of bytes of the block.
+///
+/// The blocks with a bit width greater than 25, or too close to the end of `data` for the
+/// 16 bytes loads to stay within its bounds, are decoded with the scalar kernel.
+pub fn unpack_block(data: &[u8], num_bits: u8, output: &mut [u32; BLOCK_LEN]) -> usize {
+    let last_load_byte = (BLOCK_LEN - NUM_LANES) * num_bits as usize / 8;
+    if num_bits == 0 || num_bits > MAX_NUM_BITS || last_load_byte + 16 > data.len() {
+        return super::scalar::unpack_block(data, num_bits, output);
+    }
+    unsafe { unpack_block_neon(data.as_ptr(), num_bits, output) };
+    num_bits as usize * BLOCK_LEN / 8
+}
+
+#[target_feature(enable = "neon")]
+unsafe fn unpack_block_neon(data: *const u8, num_bits: u8, output: &mut [u32; BLOCK_LEN]) {
+    let num_bits = num_bits as usize;
+    let mask = set1(u32::MAX >> (32 - num_bits));
+    for (chunk_idx, chunk) in output.chunks_exact_mut(NUM_LANES).enumerate() {
+        let first_bit_offset = chunk_idx * NUM_LANES * num_bits;
+        let first_byte = first_bit_offset / 8;
+        let mut byte_indexes = [0u8; 16];
+        let mut shifts = [0i32; NUM_LANES];
+        for lane in 0..NUM_LANES {
+            let bit_offset = first_bit_offset + lane * num_bits;
+            let byte = (bit_offset / 8 - first_byte) as u8;
+            for i in 0..4 {
+                byte_indexes[lane * 4 + i] = byte + i as u8;
+            }
+            // Negative shifts are right shifts.
+            shifts[lane] = -((bit_offset % 8) as i32);
+        }
+        let bytes = table_lookup(
+            vld1q_u8(data.add(first_byte)),
+            vld1q_u8(byte_indexes.as_ptr()),
+        );
+        let shifts = vld1q_s32(shifts.as_ptr());
+        let vals = op_and(shift_var(vreinterpretq_u32_u8(bytes), shifts), mask);
+        vst1q_u32(chunk.as_mut_ptr(), vals);
+    }
+}
+
+/// Decodes the block of 128 values packed in 4 interleaved lanes starting at the beginning of
+/// `data`, and returns the number of bytes of the block.
+///
+/// If `offset` is given, the values are the deltas between consecutive values and are
+/// integrated starting from `offset`.
+pub fn unpack_block_4x(
+    data: &[u8],
+    num_bits: u8,
+    offset: Option<u32>,
+    output: &mut [u32; BLOCK_4X_LEN],
+) -> usize {
+    if num_bits == 0 || num_bits > 32 || data.len() < num_bits as usize * 16 {
+        return super::scalar::unpack_block_4x(data, num_bits, offset, output);
+    }
+    unsafe { unpack_block_4x_neon(data.as_ptr(), num_bits, offset, output) };
+    num_bits as usize * 16
+}
+
+/// Returns the 32 bits words of the 4 lanes at `word_idx`.
+#[inline]
+#[target_feature(enable = "neon")]
+unsafe fn load_words(data: *const u8, word_idx: usize) -> DataType {
+    vreinterpretq_u32_u8(vld1q_u8(data.add(word_idx * 16)))
+}
+
+#[target_feature(enable = "neon")]
+unsafe fn unpack_block_4x_neon(
+    data: *const u8,
+    num_bits: u8,
+    offset: Option<u32>,
+    output: &mut [u32; BLOCK_4X_LEN],
+) {
+    let num_bits = num_bits as usize;
+    let mask = set1(u32::MAX >> (32 - num_bits));
+    let zero = set1(0);
+    let mut previous = set1(offset.unwrap_or(0));
+    for (position, chunk) in output.chunks_exact_mut(NUM_LANES).enumerate() {
+        let bit_offset = position * num_bits;
+        let word_idx = bit_offset / 32;
+        let shift = (bit_offset % 32) as i32;
+        // Negative shifts are right shifts.
+        let mut vals = shift_var(load_words(data, word_idx), vdupq_n_s32(-shift));
+        // The values overflow into the next word.
+        if bit_offset % 32 + num_bits > 32 {
+            let next_words = shift_var(load_words(data, word_idx + 1), vdupq_n_s32(32 - shift));
+            vals = op_or(vals, next_words);
+        }
+        vals = op_and(vals, mask);
+        if offset.is_some() {
+            vals = op_add(vals, vextq_u32(zero, vals, 3));
+            vals = op_add(vals, vextq_u32(zero, vals, 2));
+            vals = op_add(vals, previous);
+            previous = vdupq_laneq_u32(vals, 3);
+        }
+        vst1q_u32(chunk.as_mut_ptr(), vals);
+    }
+}
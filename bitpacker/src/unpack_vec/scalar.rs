@@ -0,0 +1,18 @@
+use bitpacking::{BitPacker as ExternalBitPackerTrait, BitPacker1x, BitPacker4x};
+
+pub fn unpack_block(data: &[u8], num_bits: u8, output: &mut [u32; super::BLOCK_LEN]) -> usize {
+    BitPacker1x.decompress(data, &mut output[..], num_bits)
+}
+
+pub fn unpack_block_4x(
+    data: &[u8],
+    num_bits: u8,
+    offset: Option<u32>,
+    output: &mut [u32; super::BLOCK_4X_LEN],
+) -> usize {
+    let bitpacker = BitPacker4x::new();
+    match offset {
+        Some(offset) => bitpacker.decompress_sorted(offset, data, &mut output[..], num_bits),
+        None => bitpacker.decompress(data, &mut output[..], num_bits),
+    }
+}
@@ -30,6 +30,14 @@ impl fmt::Debug for BytesColumn {
 }
 
 impl BytesColumn {
+    /// Creates a column from a dictionary and a column of ordinals of its terms.
+    pub fn new(dictionary: Arc<Dictionary<VoidSSTable>>, term_ord_column: Column<u64>) -> Self {
+        BytesColumn {
+            dictionary,
+            term_ord_column,
+        }
+    }
+
     /// Fills the given `output` buffer with the term associated to the ordinal `ord`.
     ///
     /// Returns `false` if the term does not exist (e.g. `term_ord` is greater or equal to the
@@ -8,8 +8,9 @@ use sstable::Dictionary;
 use crate::column::{BytesColumn, Column};
 use crate::column_index::{serialize_column_index, SerializableColumnIndex};
 use crate::column_values::{
-    load_u64_based_column_values, serialize_column_values_u128, serialize_u64_based_column_values,
-    CodecType, MonotonicallyMappableToU128, MonotonicallyMappableToU64,
+    load_column_values_with_format, serialize_column_values_u128,
+    serialize_column_values_with_format, ColumnValuesFormat, MonotonicallyMappableToU128,
+    MonotonicallyMappableToU64,
 };
 use crate::iterable::Iterable;
 use crate::StrColumn;
@@ -28,19 +29,19 @@ pub fn serialize_column_mappable_to_u128<T: MonotonicallyMappableToU128>(
 pub fn serialize_column_mappable_to_u64<T: MonotonicallyMappableToU64>(
     column_index: SerializableColumnIndex<'_>,
     column_values: &impl Iterable<T>,
+    values_format: &dyn ColumnValuesFormat,
     output: &mut impl Write,
 ) -> io::Result<()> {
     let column_index_num_bytes = serialize_column_index(column_index, output)?;
-    serialize_u64_based_column_values(
-        column_values,
-        &[CodecType::Bitpacked, CodecType::BlockwiseLinear],
-        output,
-    )?;
+    serialize_column_values_with_format(values_format, column_values, output)?;
     output.write_all(&column_index_num_bytes.to_le_bytes())?;
     Ok(())
 }
 
-pub fn open_column_u64<T: MonotonicallyMappableToU64>(bytes: OwnedBytes) -> io::Result<Column<T>> {
+pub fn open_column_u64<T: MonotonicallyMappableToU64>(
+    bytes: OwnedBytes,
+    values_format: Option<&dyn ColumnValuesFormat>,
+) -> io::Result<Column<T>> {
     let (body, column_index_num_bytes_payload) = bytes.rsplit(4);
     let column_index_num_bytes = u32::from_le_bytes(
         column_index_num_bytes_payload
@@ -50,7 +51,7 @@ pub fn open_column_u64<T: MonotonicallyMappableToU64>(bytes: OwnedBytes) -> io::
     );
     let (column_index_data, column_values_data) = body.split(column_index_num_bytes as usize);
     let column_index = crate::column_index::open_column_index(column_index_data)?;
-    let column_values = load_u64_based_column_values(column_values_data)?;
+    let column_values = load_column_values_with_format(values_format, column_values_data)?;
     Ok(Column {
         index: column_index,
         values: column_values,
@@ -76,19 +77,25 @@ pub fn open_column_u128<T: MonotonicallyMappableToU128>(
     })
 }
 
-pub fn open_column_bytes(data: OwnedBytes) -> io::Result<BytesColumn> {
+pub fn open_column_bytes(
+    data: OwnedBytes,
+    values_format: Option<&dyn ColumnValuesFormat>,
+) -> io::Result<BytesColumn> {
     let (body, dictionary_len_bytes) = data.rsplit(4);
     let dictionary_len = u32::from_le_bytes(dictionary_len_bytes.as_slice().try_into().unwrap());
     let (dictionary_bytes, column_bytes) = body.split(dictionary_len as usize);
     let dictionary = Arc::new(Dictionary::from_bytes(dictionary_bytes)?);
-    let term_ord_column = crate::column::open_column_u64::<u64>(column_bytes)?;
+    let term_ord_column = crate::column::open_column_u64::<u64>(column_bytes, values_format)?;
     Ok(BytesColumn {
         dictionary,
         term_ord_column,
     })
 }
 
-pub fn open_column_str(data: OwnedBytes) -> io::Result<StrColumn> {
-    let bytes_column = open_column_bytes(data)?;
+pub fn open_column_str(
+    data: OwnedBytes,
+    values_format: Option<&dyn ColumnValuesFormat>,
+) -> io::Result<StrColumn> {
+    let bytes_column = open_column_bytes(data, values_format)?;
     Ok(StrColumn::wrap(bytes_column))
 }
@@ -245,6 +245,14 @@ impl Set<RowId> for OptionalIndex {
 
 impl OptionalIndex {
     pub fn for_test(num_rows: RowId, row_ids: &[RowId]) -> OptionalIndex {
+        Self::for_row_ids(num_rows, row_ids)
+    }
+
+    /// Builds an optional index over `num_rows` rows, in which only the rows
+    /// in `row_ids` have a value.
+    ///
+    /// `row_ids` must be sorted.
+    pub fn for_row_ids(num_rows: RowId, row_ids: &[RowId]) -> OptionalIndex {
         assert!(row_ids
             .last()
             .copied()
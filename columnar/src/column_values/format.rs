@@ -0,0 +1,221 @@
+use std::io;
+use std::sync::Arc;
+
+use common::OwnedBytes;
+
+use crate::column_values::monotonic_mapping::{
+    StrictlyMonotonicMappingInverter, StrictlyMonotonicMappingToInternal,
+};
+use crate::column_values::{
+    load_u64_based_column_values, monotonic_map_column, serialize_u64_based_column_values,
+    CodecType,
+};
+use crate::iterable::Iterable;
+use crate::{ColumnValues, MonotonicallyMappableToU64};
+
+/// The format of the values of the columns mapped to `u64`, that is of all of the columns
+/// except the ip address columns.
+///
+/// The values are handed to the format after their monotonic mapping to `u64`, and the format
+/// needs to load them back in the same order. The column indexes, which map the rows to their
+/// values, and the dictionaries of the bytes and str columns are always written in their
+/// default format.
+pub trait ColumnValuesFormat: Send + Sync + 'static {
+    /// Serializes the values of a column.
+    ///
+    /// The values may be iterated over several times.
+    fn serialize(&self, vals: &dyn Iterable<u64>, wrt: &mut dyn io::Write) -> io::Result<()>;
+
+    /// Loads the values of a column serialized with [`ColumnValuesFormat::serialize()`].
+    fn load(&self, bytes: OwnedBytes) -> io::Result<Arc<dyn ColumnValues<u64>>>;
+}
+
+/// The default format of the column values, which picks the most compact of the bitpacked and
+/// the blockwise linear codecs for each column.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultColumnValuesFormat;
+
+impl ColumnValuesFormat for DefaultColumnValuesFormat {
+    fn serialize(&self, vals: &dyn Iterable<u64>, wrt: &mut dyn io::Write) -> io::Result<()> {
+        serialize_u64_based_column_values(
+            vals,
+            &[CodecType::Bitpacked, CodecType::BlockwiseLinear],
+            wrt,
+        )
+    }
+
+    fn load(&self, bytes: OwnedBytes) -> io::Result<Arc<dyn ColumnValues<u64>>> {
+        load_u64_based_column_values::<u64>(bytes)
+    }
+}
+
+struct MappedToU64<'a, T> {
+    vals: &'a dyn Iterable<T>,
+}
+
+impl<'a, T: MonotonicallyMappableToU64> Iterable<u64> for MappedToU64<'a, T> {
+    fn boxed_iter(&self) -> Box<dyn Iterator<Item = u64> + '_> {
+        Box::new(
+            self.vals
+                .boxed_iter()
+                .map(MonotonicallyMappableToU64::to_u64),
+        )
+    }
+}
+
+pub(crate) fn serialize_column_values_with_format<T: MonotonicallyMappableToU64>(
+    format: &dyn ColumnValuesFormat,
+    vals: &dyn Iterable<T>,
+    wrt: &mut dyn io::Write,
+) -> io::Result<()> {
+    format.serialize(&MappedToU64 { vals }, wrt)
+}
+
+/// Loads the values of a column, written with the default format if `format` is `None`.
+///
+/// The default format is special cased, so that the values of the default columns are not
+/// accessed through two levels of dynamic dispatch.
+pub(crate) fn load_column_values_with_format<T: MonotonicallyMappableToU64>(
+    format: Option<&dyn ColumnValuesFormat>,
+    bytes: OwnedBytes,
+) -> io::Result<Arc<dyn ColumnValues<T>>> {
+    let Some(format) = format else {
+        return load_u64_based_column_values::<T>(bytes);
+    };
+    let column_values = format.load(bytes)?;
+    Ok(Arc::new(monotonic_map_column(
+        column_values,
+        StrictlyMonotonicMappingInverter::from(StrictlyMonotonicMappingToInternal::<T>::new()),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::sync::Arc;
+
+    use common::OwnedBytes;
+
+    use super::ColumnValuesFormat;
+    use crate::iterable::Iterable;
+    use crate::{
+        ColumnValues, ColumnarReader, ColumnarWriter, DynamicColumn, MergeRowOrder, StackMergeOrder,
+    };
+
+    /// Stores the values as little-endian `u64`s.
+    struct PlainFormat;
+
+    struct PlainColumnValues {
+        data: OwnedBytes,
+    }
+
+    impl ColumnValues for PlainColumnValues {
+        fn get_val(&self, idx: u32) -> u64 {
+            let start = idx as usize * 8;
+            u64::from_le_bytes(self.data.as_slice()[start..start + 8].try_into().unwrap())
+        }
+
+        fn min_value(&self) -> u64 {
+            self.iter().min().unwrap_or(0)
+        }
+
+        fn max_value(&self) -> u64 {
+            self.iter().max().unwrap_or(0)
+        }
+
+        fn num_vals(&self) -> u32 {
+            (self.data.len() / 8) as u32
+        }
+    }
+
+    impl ColumnValuesFormat for PlainFormat {
+        fn serialize(&self, vals: &dyn Iterable<u64>, wrt: &mut dyn io::Write) -> io::Result<()> {
+            for val in vals.boxed_iter() {
+                wrt.write_all(&val.to_le_bytes())?;
+            }
+            Ok(())
+        }
+
+        fn load(&self, data: OwnedBytes) -> io::Result<Arc<dyn ColumnValues<u64>>> {
+            Ok(Arc::new(PlainColumnValues { data }))
+        }
+    }
+
+    fn build_columnar(
+        first_row: i64,
+        num_rows: u32,
+        values_format: Option<Arc<dyn ColumnValuesFormat>>,
+    ) -> ColumnarReader {
+        let mut columnar_writer = ColumnarWriter::default();
+        for row in 0..num_rows {
+            columnar_writer.record_numerical(row, "num", first_row + row as i64 * 1_000);
+            columnar_writer.record_str(row, "text", if row % 2 == 0 { "even" } else { "odd" });
+        }
+        let mut buffer = Vec::new();
+        if let Some(values_format) = values_format {
+            columnar_writer
+                .serialize_with_format(num_rows, None, values_format.as_ref(), &mut buffer)
+                .unwrap();
+            ColumnarReader::open_with_format(buffer, values_format).unwrap()
+        } else {
+            columnar_writer
+                .serialize(num_rows, None, &mut buffer)
+                .unwrap();
+            ColumnarReader::open(buffer).unwrap()
+        }
+    }
+
+    fn num_values(columnar: &ColumnarReader) -> Vec<i64> {
+        let column_handles = columnar.read_columns("num").unwrap();
+        let Ok(DynamicColumn::I64(column)) = column_handles[0].open() else {
+            panic!("expected an i64 column");
+        };
+        column.values.iter().collect()
+    }
+
+    fn text_values(columnar: &ColumnarReader) -> Vec<String> {
+        let column_handles = columnar.read_columns("text").unwrap();
+        let Ok(DynamicColumn::Str(column)) = column_handles[0].open() else {
+            panic!("expected a str column");
+        };
+        let mut text = String::new();
+        (0..columnar.num_rows())
+            .map(|row| {
+                let term_ord = column.term_ords(row).next().unwrap();
+                column.ord_to_str(term_ord, &mut text).unwrap();
+                text.clone()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_column_values_format() {
+        let plain_format: Arc<dyn ColumnValuesFormat> = Arc::new(PlainFormat);
+        let columnar = build_columnar(-2_000, 3, Some(plain_format.clone()));
+        assert_eq!(num_values(&columnar), vec![-2_000, -1_000, 0]);
+        assert_eq!(text_values(&columnar), vec!["even", "odd", "even"]);
+
+        // The columnars are merged with the format of the output, whatever their own format.
+        let default_columnar = build_columnar(5_000, 2, None);
+        let columnars = [&columnar, &default_columnar];
+        let stack_merge_order = StackMergeOrder::stack(&columnars[..]);
+        let mut buffer = Vec::new();
+        crate::merge_columnar_with_format(
+            &columnars[..],
+            &[],
+            MergeRowOrder::Stack(stack_merge_order),
+            plain_format.as_ref(),
+            &mut buffer,
+        )
+        .unwrap();
+        let merged_columnar = ColumnarReader::open_with_format(buffer, plain_format).unwrap();
+        assert_eq!(
+            num_values(&merged_columnar),
+            vec![-2_000, -1_000, 0, 5_000, 6_000]
+        );
+        assert_eq!(
+            text_values(&merged_columnar),
+            vec!["even", "odd", "even", "even", "odd"]
+        );
+    }
+}
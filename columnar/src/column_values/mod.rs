@@ -13,6 +13,7 @@ use std::sync::Arc;
 pub use monotonic_mapping::{MonotonicallyMappableToU64, StrictlyMonotonicFn};
 pub use monotonic_mapping_u128::MonotonicallyMappableToU128;
 
+mod format;
 mod merge;
 pub(crate) mod monotonic_mapping;
 pub(crate) mod monotonic_mapping_u128;
@@ -23,6 +24,8 @@ mod vec_column;
 
 mod monotonic_column;
 
+pub(crate) use format::{load_column_values_with_format, serialize_column_values_with_format};
+pub use format::{ColumnValuesFormat, DefaultColumnValuesFormat};
 pub(crate) use merge::MergedColumnValues;
 pub use stats::ColumnStats;
 pub use u128_based::{open_u128_mapped, serialize_column_values_u128};
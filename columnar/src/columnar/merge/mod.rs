@@ -12,7 +12,7 @@ pub use merge_mapping::{MergeRowOrder, ShuffleMergeOrder, StackMergeOrder};
 
 use super::writer::ColumnarSerializer;
 use crate::column::{serialize_column_mappable_to_u128, serialize_column_mappable_to_u64};
-use crate::column_values::MergedColumnValues;
+use crate::column_values::{ColumnValuesFormat, DefaultColumnValuesFormat, MergedColumnValues};
 use crate::columnar::merge::merge_dict_column::merge_bytes_or_str_column;
 use crate::columnar::writer::CompatibleNumericalTypes;
 use crate::columnar::ColumnarReader;
@@ -77,6 +77,26 @@ pub fn merge_columnar(
     required_columns: &[(String, ColumnType)],
     merge_row_order: MergeRowOrder,
     output: &mut impl io::Write,
+) -> io::Result<()> {
+    merge_columnar_with_format(
+        columnar_readers,
+        required_columns,
+        merge_row_order,
+        &DefaultColumnValuesFormat,
+        output,
+    )
+}
+
+/// Merge several columnar table together, like [`merge_columnar`], writing the column values
+/// with the given format.
+///
+/// The input columnars may have been written with other formats.
+pub fn merge_columnar_with_format(
+    columnar_readers: &[&ColumnarReader],
+    required_columns: &[(String, ColumnType)],
+    merge_row_order: MergeRowOrder,
+    values_format: &dyn ColumnValuesFormat,
+    output: &mut impl io::Write,
 ) -> io::Result<()> {
     let mut serializer = ColumnarSerializer::new(output);
     let num_rows_per_columnar = columnar_readers
@@ -93,6 +113,7 @@ pub fn merge_columnar(
             &num_rows_per_columnar,
             columns,
             &merge_row_order,
+            values_format,
             &mut column_serializer,
         )?;
         column_serializer.finalize()?;
@@ -117,6 +138,7 @@ fn merge_column(
     num_docs_per_column: &[u32],
     columns: Vec<Option<DynamicColumn>>,
     merge_row_order: &MergeRowOrder,
+    values_format: &dyn ColumnValuesFormat,
     wrt: &mut impl io::Write,
 ) -> io::Result<()> {
     match column_type {
@@ -148,7 +170,12 @@ fn merge_column(
                 column_values: &column_values[..],
                 merge_row_order,
             };
-            serialize_column_mappable_to_u64(merged_column_index, &merge_column_values, wrt)?;
+            serialize_column_mappable_to_u64(
+                merged_column_index,
+                &merge_column_values,
+                values_format,
+                wrt,
+            )?;
         }
         ColumnType::IpAddr => {
             let mut column_indexes: Vec<ColumnIndex> = Vec::with_capacity(columns.len());
@@ -201,7 +228,13 @@ fn merge_column(
             }
             let merged_column_index =
                 crate::column_index::merge_column_index(&column_indexes[..], merge_row_order);
-            merge_bytes_or_str_column(merged_column_index, &bytes_columns, merge_row_order, wrt)?;
+            merge_bytes_or_str_column(
+                merged_column_index,
+                &bytes_columns,
+                merge_row_order,
+                values_format,
+                wrt,
+            )?;
         }
     }
     Ok(())
@@ -7,6 +7,8 @@ mod writer;
 pub use column_type::{ColumnType, HasAssociatedColumnType};
 #[cfg(test)]
 pub(crate) use merge::ColumnTypeCategory;
-pub use merge::{merge_columnar, MergeRowOrder, ShuffleMergeOrder, StackMergeOrder};
+pub use merge::{
+    merge_columnar, merge_columnar_with_format, MergeRowOrder, ShuffleMergeOrder, StackMergeOrder,
+};
 pub use reader::ColumnarReader;
 pub use writer::ColumnarWriter;
@@ -1,9 +1,11 @@
+use std::sync::Arc;
 use std::{fmt, io, mem};
 
 use common::file_slice::FileSlice;
 use common::BinarySerializable;
 use sstable::{Dictionary, RangeSSTable};
 
+use crate::column_values::ColumnValuesFormat;
 use crate::columnar::{format_version, ColumnType};
 use crate::dynamic_column::DynamicColumnHandle;
 use crate::RowId;
@@ -19,6 +21,7 @@ pub struct ColumnarReader {
     column_dictionary: Dictionary<RangeSSTable>,
     column_data: FileSlice,
     num_rows: RowId,
+    values_format: Option<Arc<dyn ColumnValuesFormat>>,
 }
 
 impl fmt::Debug for ColumnarReader {
@@ -53,6 +56,7 @@ impl fmt::Debug for ColumnarReader {
 fn read_all_columns_in_stream(
     mut stream: sstable::Streamer<'_, RangeSSTable>,
     column_data: &FileSlice,
+    values_format: Option<&Arc<dyn ColumnValuesFormat>>,
 ) -> io::Result<Vec<DynamicColumnHandle>> {
     let mut results = Vec::new();
     while stream.advance() {
@@ -67,6 +71,7 @@ fn read_all_columns_in_stream(
         let dynamic_column_handle = DynamicColumnHandle {
             file_slice,
             column_type,
+            values_format: values_format.cloned(),
         };
         results.push(dynamic_column_handle);
     }
@@ -77,10 +82,25 @@ impl ColumnarReader {
     /// Opens a new Columnar file.
     pub fn open<F>(file_slice: F) -> io::Result<ColumnarReader>
     where FileSlice: From<F> {
-        Self::open_inner(file_slice.into())
+        Self::open_inner(file_slice.into(), None)
     }
 
-    fn open_inner(file_slice: FileSlice) -> io::Result<ColumnarReader> {
+    /// Opens a new Columnar file, of which the column values were written with the given
+    /// format.
+    pub fn open_with_format<F>(
+        file_slice: F,
+        values_format: Arc<dyn ColumnValuesFormat>,
+    ) -> io::Result<ColumnarReader>
+    where
+        FileSlice: From<F>,
+    {
+        Self::open_inner(file_slice.into(), Some(values_format))
+    }
+
+    fn open_inner(
+        file_slice: FileSlice,
+        values_format: Option<Arc<dyn ColumnValuesFormat>>,
+    ) -> io::Result<ColumnarReader> {
         let (file_slice_without_sstable_len, footer_slice) = file_slice
             .split_from_end(mem::size_of::<u64>() + 4 + format_version::VERSION_FOOTER_NUM_BYTES);
         let footer_bytes = footer_slice.read_bytes()?;
@@ -96,6 +116,7 @@ impl ColumnarReader {
             column_dictionary,
             column_data,
             num_rows,
+            values_format,
         })
     }
 
@@ -122,6 +143,7 @@ impl ColumnarReader {
             let column_handle = DynamicColumnHandle {
                 file_slice,
                 column_type,
+                values_format: self.values_format.clone(),
             };
             results.push((column_name, column_handle));
         }
@@ -156,7 +178,7 @@ impl ColumnarReader {
             .stream_for_column_range(column_name)
             .into_stream_async()
             .await?;
-        read_all_columns_in_stream(stream, &self.column_data)
+        read_all_columns_in_stream(stream, &self.column_data, self.values_format.as_ref())
     }
 
     /// Get all columns for the given column name.
@@ -165,7 +187,7 @@ impl ColumnarReader {
     /// different types.
     pub fn read_columns(&self, column_name: &str) -> io::Result<Vec<DynamicColumnHandle>> {
         let stream = self.stream_for_column_range(column_name).into_stream()?;
-        read_all_columns_in_stream(stream, &self.column_data)
+        read_all_columns_in_stream(stream, &self.column_data, self.values_format.as_ref())
     }
 
     /// Return the number of columns in the columnar.
@@ -14,7 +14,8 @@ use stacker::{Addr, ArenaHashMap, MemoryArena};
 
 use crate::column_index::SerializableColumnIndex;
 use crate::column_values::{
-    ColumnValues, MonotonicallyMappableToU128, MonotonicallyMappableToU64, VecColumn,
+    ColumnValues, ColumnValuesFormat, DefaultColumnValuesFormat, MonotonicallyMappableToU128,
+    MonotonicallyMappableToU64, VecColumn,
 };
 use crate::columnar::column_type::ColumnType;
 use crate::columnar::writer::column_writers::{
@@ -323,6 +324,22 @@ impl ColumnarWriter {
         num_docs: RowId,
         old_to_new_row_ids: Option<&[RowId]>,
         wrt: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        self.serialize_with_format(
+            num_docs,
+            old_to_new_row_ids,
+            &DefaultColumnValuesFormat,
+            wrt,
+        )
+    }
+
+    /// Serializes the columnar, writing the column values with the given format.
+    pub fn serialize_with_format(
+        &mut self,
+        num_docs: RowId,
+        old_to_new_row_ids: Option<&[RowId]>,
+        values_format: &dyn ColumnValuesFormat,
+        wrt: &mut dyn io::Write,
     ) -> io::Result<()> {
         let mut serializer = ColumnarSerializer::new(wrt);
         let mut columns: Vec<(&[u8], ColumnType, Addr)> = self
@@ -379,6 +396,7 @@ impl ColumnarWriter {
                             old_to_new_row_ids,
                             &mut symbol_byte_buffer,
                         ),
+                        values_format,
                         buffers,
                         &mut column_serializer,
                     )?;
@@ -426,6 +444,7 @@ impl ColumnarWriter {
                             old_to_new_row_ids,
                             &mut symbol_byte_buffer,
                         ),
+                        values_format,
                         buffers,
                         &mut column_serializer,
                     )?;
@@ -447,6 +466,7 @@ impl ColumnarWriter {
                             old_to_new_row_ids,
                             &mut symbol_byte_buffer,
                         ),
+                        values_format,
                         buffers,
                         &mut column_serializer,
                     )?;
@@ -466,6 +486,7 @@ impl ColumnarWriter {
                             old_to_new_row_ids,
                             &mut symbol_byte_buffer,
                         ),
+                        values_format,
                         buffers,
                         &mut column_serializer,
                     )?;
@@ -480,12 +501,14 @@ impl ColumnarWriter {
 
 // Serialize [Dictionary, Column, dictionary num bytes U32::LE]
 // Column: [Column Index, Column Values, column index num bytes U32::LE]
+#[allow(clippy::too_many_arguments)]
 fn serialize_bytes_or_str_column(
     cardinality: Cardinality,
     num_docs: RowId,
     sort_values_within_row: bool,
     dictionary_builder: &DictionaryBuilder,
     operation_it: impl Iterator<Item = ColumnOperation<UnorderedId>>,
+    values_format: &dyn ColumnValuesFormat,
     buffers: &mut SpareBuffers,
     wrt: impl io::Write,
 ) -> io::Result<()> {
@@ -515,6 +538,7 @@ fn serialize_bytes_or_str_column(
         sort_values_within_row,
         value_index_builders,
         u64_values,
+        values_format,
         &mut wrt,
     )?;
     wrt.write_all(&dictionary_num_bytes.to_le_bytes()[..])?;
@@ -526,6 +550,7 @@ fn serialize_numerical_column(
     num_docs: RowId,
     numerical_type: NumericalType,
     op_iterator: impl Iterator<Item = ColumnOperation<NumericalValue>>,
+    values_format: &dyn ColumnValuesFormat,
     buffers: &mut SpareBuffers,
     wrt: &mut impl io::Write,
 ) -> io::Result<()> {
@@ -543,6 +568,7 @@ fn serialize_numerical_column(
                 false,
                 value_index_builders,
                 u64_values,
+                values_format,
                 wrt,
             )?;
         }
@@ -554,6 +580,7 @@ fn serialize_numerical_column(
                 false,
                 value_index_builders,
                 u64_values,
+                values_format,
                 wrt,
             )?;
         }
@@ -565,6 +592,7 @@ fn serialize_numerical_column(
                 false,
                 value_index_builders,
                 u64_values,
+                values_format,
                 wrt,
             )?;
         }
@@ -576,6 +604,7 @@ fn serialize_bool_column(
     cardinality: Cardinality,
     num_docs: RowId,
     column_operations_it: impl Iterator<Item = ColumnOperation<bool>>,
+    values_format: &dyn ColumnValuesFormat,
     buffers: &mut SpareBuffers,
     wrt: &mut impl io::Write,
 ) -> io::Result<()> {
@@ -594,6 +623,7 @@ fn serialize_bool_column(
         false,
         value_index_builders,
         u64_values,
+        values_format,
         wrt,
     )?;
     Ok(())
@@ -679,6 +709,7 @@ fn sort_values_within_row_in_place(multivalued_index: &[RowId], values: &mut [u6
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn send_to_serialize_column_mappable_to_u64(
     op_iterator: impl Iterator<Item = ColumnOperation<u64>>,
     cardinality: Cardinality,
@@ -686,6 +717,7 @@ fn send_to_serialize_column_mappable_to_u64(
     sort_values_within_row: bool,
     value_index_builders: &mut PreallocatedIndexBuilders,
     values: &mut Vec<u64>,
+    values_format: &dyn ColumnValuesFormat,
     mut wrt: impl io::Write,
 ) -> io::Result<()>
 where
@@ -723,6 +755,7 @@ where
     crate::column::serialize_column_mappable_to_u64(
         serializable_column_index,
         &&values[..],
+        values_format,
         &mut wrt,
     )?;
     Ok(())
@@ -6,7 +6,7 @@ use common::file_slice::FileSlice;
 use common::{ByteCount, DateTime, HasLen, OwnedBytes};
 
 use crate::column::{BytesColumn, Column, StrColumn};
-use crate::column_values::{monotonic_map_column, StrictlyMonotonicFn};
+use crate::column_values::{monotonic_map_column, ColumnValuesFormat, StrictlyMonotonicFn};
 use crate::columnar::ColumnType;
 use crate::{Cardinality, ColumnIndex, NumericalType};
 
@@ -232,6 +232,8 @@ static_dynamic_conversions!(Column<Ipv6Addr>, IpAddr);
 pub struct DynamicColumnHandle {
     pub(crate) file_slice: FileSlice,
     pub(crate) column_type: ColumnType,
+    // The format of the column values, or `None` for the default format.
+    pub(crate) values_format: Option<Arc<dyn ColumnValuesFormat>>,
 }
 
 impl DynamicColumnHandle {
@@ -253,31 +255,44 @@ impl DynamicColumnHandle {
     /// FastValue.
     pub fn open_u64_lenient(&self) -> io::Result<Option<Column<u64>>> {
         let column_bytes = self.file_slice.read_bytes()?;
+        let values_format = self.values_format.as_deref();
         match self.column_type {
             ColumnType::Str | ColumnType::Bytes => {
-                let column: BytesColumn = crate::column::open_column_bytes(column_bytes)?;
+                let column: BytesColumn =
+                    crate::column::open_column_bytes(column_bytes, values_format)?;
                 Ok(Some(column.term_ord_column))
             }
             ColumnType::Bool => Ok(None),
             ColumnType::IpAddr => Ok(None),
             ColumnType::I64 | ColumnType::U64 | ColumnType::F64 | ColumnType::DateTime => {
-                let column = crate::column::open_column_u64::<u64>(column_bytes)?;
+                let column = crate::column::open_column_u64::<u64>(column_bytes, values_format)?;
                 Ok(Some(column))
             }
         }
     }
 
     fn open_internal(&self, column_bytes: OwnedBytes) -> io::Result<DynamicColumn> {
+        let values_format = self.values_format.as_deref();
         let dynamic_column: DynamicColumn = match self.column_type {
-            ColumnType::Bytes => crate::column::open_column_bytes(column_bytes)?.into(),
-            ColumnType::Str => crate::column::open_column_str(column_bytes)?.into(),
-            ColumnType::I64 => crate::column::open_column_u64::<i64>(column_bytes)?.into(),
-            ColumnType::U64 => crate::column::open_column_u64::<u64>(column_bytes)?.into(),
-            ColumnType::F64 => crate::column::open_column_u64::<f64>(column_bytes)?.into(),
-            ColumnType::Bool => crate::column::open_column_u64::<bool>(column_bytes)?.into(),
+            ColumnType::Bytes => {
+                crate::column::open_column_bytes(column_bytes, values_format)?.into()
+            }
+            ColumnType::Str => crate::column::open_column_str(column_bytes, values_format)?.into(),
+            ColumnType::I64 => {
+                crate::column::open_column_u64::<i64>(column_bytes, values_format)?.into()
+            }
+            ColumnType::U64 => {
+                crate::column::open_column_u64::<u64>(column_bytes, values_format)?.into()
+            }
+            ColumnType::F64 => {
+                crate::column::open_column_u64::<f64>(column_bytes, values_format)?.into()
+            }
+            ColumnType::Bool => {
+                crate::column::open_column_u64::<bool>(column_bytes, values_format)?.into()
+            }
             ColumnType::IpAddr => crate::column::open_column_u128::<Ipv6Addr>(column_bytes)?.into(),
             ColumnType::DateTime => {
-                crate::column::open_column_u64::<DateTime>(column_bytes)?.into()
+                crate::column::open_column_u64::<DateTime>(column_bytes, values_format)?.into()
             }
         };
         Ok(dynamic_column)
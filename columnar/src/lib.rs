@@ -23,14 +23,15 @@ mod value;
 
 pub use block_accessor::ColumnBlockAccessor;
 pub use column::{BytesColumn, Column, StrColumn};
-pub use column_index::ColumnIndex;
+pub use column_index::{ColumnIndex, OptionalIndex};
 pub use column_values::{
-    ColumnValues, EmptyColumnValues, MonotonicallyMappableToU128, MonotonicallyMappableToU64,
+    ColumnValues, ColumnValuesFormat, DefaultColumnValuesFormat, EmptyColumnValues, MonotonicallyMappableToU128, MonotonicallyMappableToU64,
 };
 pub use columnar::{
-    merge_columnar, ColumnType, ColumnarReader, ColumnarWriter, HasAssociatedColumnType,
-    MergeRowOrder, ShuffleMergeOrder, StackMergeOrder,
+    merge_columnar, merge_columnar_with_format, ColumnType, ColumnarReader, ColumnarWriter,
+    HasAssociatedColumnType, MergeRowOrder, ShuffleMergeOrder, StackMergeOrder,
 };
+pub use iterable::Iterable;
 use sstable::VoidSSTable;
 pub use value::{NumericalType, NumericalValue};
 
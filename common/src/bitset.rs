@@ -240,6 +240,15 @@ impl BitSet {
         self.intersect_update_with_iter(other.iter_tinysets());
     }
 
+    /// Removes the elements of another `BitSet` of the same max value.
+    pub fn difference_update(&mut self, other: &BitSet) {
+        self.len = 0;
+        for (left, right) in self.tinysets.iter_mut().zip(other.tinysets.iter().cloned()) {
+            *left = TinySet(left.0 & !right.0);
+            self.len += left.len() as u64;
+        }
+    }
+
     /// Intersect with tinysets
     fn intersect_update_with_iter(&mut self, other: impl Iterator<Item = TinySet>) {
         self.len = 0;
@@ -448,6 +457,23 @@ mod tests {
         assert_eq!(bitset.len(), 4);
     }
 
+    #[test]
+    fn test_bitset_difference() {
+        let mut left = BitSet::with_max_value(100);
+        let mut right = BitSet::with_max_value(100);
+        for el in [1, 2, 70] {
+            left.insert(el);
+        }
+        for el in [2, 3, 99] {
+            right.insert(el);
+        }
+        left.difference_update(&right);
+        assert_eq!(left.len(), 2);
+        assert!(left.contains(1));
+        assert!(!left.contains(2));
+        assert!(left.contains(70));
+    }
+
     #[test]
     fn test_bitset_intersect() {
         let bitset_serialized = {
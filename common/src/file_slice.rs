@@ -23,7 +23,11 @@ pub trait FileHandle: 'static + Send + Sync + HasLen + fmt::Debug {
     /// This method may panic if the range requested is invalid.
     fn read_bytes(&self, range: Range<usize>) -> io::Result<OwnedBytes>;
 
-    #[doc(hidden)]
+    /// Reads a slice of bytes asynchronously.
+    ///
+    /// File handles backed by a remote storage should implement it, so that reading the file
+    /// does not block the executor thread. By default, it returns an error of kind
+    /// [`io::ErrorKind::Unsupported`].
     async fn read_bytes_async(&self, _byte_range: Range<usize>) -> io::Result<OwnedBytes> {
         Err(io::Error::new(
             io::ErrorKind::Unsupported,
@@ -7,7 +7,7 @@ use super::segment_agg_result::{
     build_segment_agg_collector, AggregationLimits, SegmentAggregationCollector,
 };
 use crate::aggregation::agg_req_with_accessor::get_aggs_with_segment_accessor_and_validate;
-use crate::collector::{Collector, SegmentCollector};
+use crate::collector::{CacheableCollector, Collector, SegmentCollector};
 use crate::{DocId, SegmentReader, TantivyError};
 
 /// The default max bucket count, before the aggregation fails.
@@ -108,6 +108,17 @@ impl Collector for AggregationCollector {
     }
 }
 
+impl CacheableCollector for AggregationCollector {
+    fn cache_key(&self) -> String {
+        // Unlike the `Debug` representation of the hash maps of the request, its JSON
+        // representation has sorted keys.
+        let request = serde_json::to_value(&self.agg)
+            .map(|request| request.to_string())
+            .unwrap_or_else(|_| format!("{:?}", self.agg));
+        format!("AggregationCollector({request})")
+    }
+}
+
 fn merge_fruits(
     mut segment_fruits: Vec<crate::Result<IntermediateAggregationResults>>,
 ) -> crate::Result<IntermediateAggregationResults> {
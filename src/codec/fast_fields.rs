@@ -0,0 +1,80 @@
+use std::io;
+use std::sync::Arc;
+
+use columnar::{
+    ColumnType, ColumnValuesFormat, ColumnarReader, ColumnarWriter, MergeRowOrder, RowId,
+};
+
+use crate::directory::FileSlice;
+
+/// Encodes and decodes the fast fields of the segments, stored as a columnar.
+///
+/// By default, the columns are written with the [`ColumnValuesFormat`] returned by
+/// [`FastFieldsFormat::column_values_format()`], the other methods being overridden only to
+/// write the columnars entirely differently.
+pub trait FastFieldsFormat: Send + Sync + 'static {
+    /// The format of the values of the columns, or `None` for the default format.
+    fn column_values_format(&self) -> Option<Arc<dyn ColumnValuesFormat>> {
+        None
+    }
+
+    /// Serializes the columnar of a new segment, remapping its rows if `old_to_new_row_ids` is
+    /// given.
+    fn serialize(
+        &self,
+        columnar_writer: &mut ColumnarWriter,
+        num_docs: RowId,
+        old_to_new_row_ids: Option<&[RowId]>,
+        wrt: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        match self.column_values_format() {
+            Some(values_format) => columnar_writer.serialize_with_format(
+                num_docs,
+                old_to_new_row_ids,
+                values_format.as_ref(),
+                wrt,
+            ),
+            None => columnar_writer.serialize(num_docs, old_to_new_row_ids, wrt),
+        }
+    }
+
+    /// Merges the columnars of the merged segments, whatever their format, into the columnar of
+    /// the new segment.
+    fn merge(
+        &self,
+        columnar_readers: &[&ColumnarReader],
+        required_columns: &[(String, ColumnType)],
+        merge_row_order: MergeRowOrder,
+        mut wrt: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        match self.column_values_format() {
+            Some(values_format) => columnar::merge_columnar_with_format(
+                columnar_readers,
+                required_columns,
+                merge_row_order,
+                values_format.as_ref(),
+                &mut wrt,
+            ),
+            None => columnar::merge_columnar(
+                columnar_readers,
+                required_columns,
+                merge_row_order,
+                &mut wrt,
+            ),
+        }
+    }
+
+    /// Opens a columnar written by this format.
+    fn open(&self, file: FileSlice) -> io::Result<ColumnarReader> {
+        match self.column_values_format() {
+            Some(values_format) => ColumnarReader::open_with_format(file, values_format),
+            None => ColumnarReader::open(file),
+        }
+    }
+}
+
+/// The current format of the fast fields.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultFastFieldsFormat;
+
+impl FastFieldsFormat for DefaultFastFieldsFormat {}
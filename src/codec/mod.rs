@@ -0,0 +1,157 @@
+//! Codecs encode the postings, the term dictionaries and the fast fields of the segments.
+//!
+//! A [`Codec`] is made of a format for each of the following components of the segments:
+//! - a [`PostingsFormat`] for the doc ids and the term frequencies of the posting lists,
+//! - a [`TermDictionaryFormat`] for the term dictionaries,
+//! - a [`FastFieldsFormat`] for the columnar of the fast fields.
+//!
+//! The other components, e.g. the positions, the fieldnorms or the doc store, are always
+//! written in their default format.
+//!
+//! The codecs are registered by name in the [`CodecManager`] of the index, which contains the
+//! [`DefaultCodec`] writing the current formats. The codec writing the new segments is selected
+//! by [`IndexSettings::codec`](crate::IndexSettings::codec), and the name of the codec of each
+//! segment is recorded in its [`SegmentMeta`](crate::SegmentMeta), so that the segments are
+//! always read with the codec that wrote them. Experimental formats can therefore be A/B-tested
+//! on an index: the segments written before and after a change of codec, and the segments
+//! merged with the new codec, remain searchable together.
+//!
+//! The codecs of the segments of an index need to be registered before opening the readers or
+//! the writers of the index.
+//!
+//! ```rust
+//! use tantivy::codec::{Codec, CodecManager, DefaultPostingsFormat, PostingsFormat};
+//! use tantivy::schema::{Schema, TEXT};
+//! use tantivy::{doc, Index, IndexSettings};
+//!
+//! struct ExperimentalCodec;
+//!
+//! impl Codec for ExperimentalCodec {
+//!     fn name(&self) -> &str {
+//!         "experimental"
+//!     }
+//!
+//!     // Returns the postings format under test, while the term dictionaries and the fast
+//!     // fields keep their default format.
+//!     fn postings_format(&self) -> &dyn PostingsFormat {
+//!         &DefaultPostingsFormat
+//!     }
+//! }
+//!
+//! # fn main() -> tantivy::Result<()> {
+//! let mut schema_builder = Schema::builder();
+//! let title = schema_builder.add_text_field("title", TEXT);
+//! let settings = IndexSettings {
+//!     codec: "experimental".to_string(),
+//!     ..Default::default()
+//! };
+//! let codecs = CodecManager::default();
+//! codecs.register(ExperimentalCodec);
+//! let index = Index::builder()
+//!     .schema(schema_builder.build())
+//!     .settings(settings)
+//!     .codecs(codecs)
+//!     .create_in_ram()?;
+//! let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+//! index_writer.add_document(doc!(title => "The Old Man and the Sea"))?;
+//! index_writer.commit()?;
+//! let segment_metas = index.searchable_segment_metas()?;
+//! assert_eq!(segment_metas[0].codec(), "experimental");
+//! # Ok(())
+//! # }
+//! ```
+
+mod fast_fields;
+mod postings;
+mod term_dictionary;
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+pub use columnar::{ColumnValues, ColumnValuesFormat, DefaultColumnValuesFormat, Iterable};
+
+pub use self::fast_fields::{DefaultFastFieldsFormat, FastFieldsFormat};
+pub use self::postings::{
+    DefaultPostingsFormat, PostingsDecoder, PostingsEncoder, PostingsFormat, POSTINGS_BLOCK_LEN,
+};
+pub use self::term_dictionary::{
+    DefaultTermDictionaryFormat, TermDictionaryDecoder, TermDictionaryEncoder, TermDictionaryFormat,
+};
+
+/// The name of the [`DefaultCodec`].
+pub const DEFAULT_CODEC_NAME: &str = "default";
+
+/// A set of formats for the postings, the term dictionaries and the fast fields of the
+/// segments, registered by name in the [`CodecManager`] of an index.
+///
+/// All of the formats are the default ones by default, so that implementations only override
+/// the formats they experiment with.
+pub trait Codec: Send + Sync + 'static {
+    /// The name of the codec, recorded in the meta of the segments it writes.
+    fn name(&self) -> &str;
+
+    /// The format of the postings.
+    fn postings_format(&self) -> &dyn PostingsFormat {
+        &DefaultPostingsFormat
+    }
+
+    /// The format of the term dictionaries.
+    fn term_dictionary_format(&self) -> &dyn TermDictionaryFormat {
+        &DefaultTermDictionaryFormat
+    }
+
+    /// The format of the fast fields.
+    fn fast_fields_format(&self) -> &dyn FastFieldsFormat {
+        &DefaultFastFieldsFormat
+    }
+}
+
+/// The codec writing the current formats of the components.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultCodec;
+
+impl Codec for DefaultCodec {
+    fn name(&self) -> &str {
+        DEFAULT_CODEC_NAME
+    }
+}
+
+/// The codec manager serves as a store for the codecs of an index.
+///
+/// By default, it contains the [`DefaultCodec`].
+#[derive(Clone)]
+pub struct CodecManager {
+    codecs: Arc<RwLock<HashMap<String, Arc<dyn Codec>>>>,
+}
+
+impl CodecManager {
+    /// Registers a codec under its name, replacing the codec previously registered with the
+    /// same name, if any.
+    pub fn register<C: Codec>(&self, codec: C) {
+        self.codecs
+            .write()
+            .expect("Acquiring the lock should never fail")
+            .insert(codec.name().to_string(), Arc::new(codec));
+    }
+
+    /// Accessing a codec given its name.
+    pub fn get(&self, codec_name: &str) -> Option<Arc<dyn Codec>> {
+        self.codecs
+            .read()
+            .expect("Acquiring the lock should never fail")
+            .get(codec_name)
+            .cloned()
+    }
+}
+
+impl Default for CodecManager {
+    fn default() -> CodecManager {
+        let codec_manager = CodecManager {
+            codecs: Arc::new(RwLock::new(HashMap::new())),
+        };
+        codec_manager.register(DefaultCodec);
+        codec_manager
+    }
+}
@@ -0,0 +1,141 @@
+use std::io;
+
+use common::OwnedBytes;
+
+use crate::directory::FileSlice;
+use crate::fieldnorm::FieldNormReader;
+use crate::postings::compression::COMPRESSION_BLOCK_SIZE;
+use crate::postings::{BlockSegmentPostings, PostingsSerializer};
+use crate::schema::IndexRecordOption;
+use crate::{DocId, Score};
+
+/// The number of documents of the blocks of the posting lists.
+pub const POSTINGS_BLOCK_LEN: usize = COMPRESSION_BLOCK_SIZE;
+
+/// Encodes and decodes the posting lists, that is the doc ids and the term frequencies of the
+/// terms. The positions are always written in their default format.
+pub trait PostingsFormat: Send + Sync + 'static {
+    /// Creates the encoder of the posting lists of a field.
+    ///
+    /// The fieldnorm reader and the average fieldnorm of the field are given if the field has
+    /// fieldnorms, e.g. to compute the block max scores of the posting lists.
+    fn new_encoder(
+        &self,
+        record_option: IndexRecordOption,
+        fieldnorm_reader: Option<FieldNormReader>,
+        average_fieldnorm: Score,
+    ) -> Box<dyn PostingsEncoder>;
+
+    /// Opens the posting list of a term with `doc_freq` documents, written by an encoder of
+    /// this format.
+    ///
+    /// The formats other than the default one return the block postings of
+    /// [`BlockSegmentPostings::open_with_decoder()`].
+    fn open(
+        &self,
+        doc_freq: u32,
+        data: FileSlice,
+        record_option: IndexRecordOption,
+        requested_option: IndexRecordOption,
+    ) -> io::Result<BlockSegmentPostings>;
+}
+
+/// Encodes the posting lists of the terms of a field, one term after the other.
+pub trait PostingsEncoder {
+    /// Starts the posting list of a new term.
+    ///
+    /// `term_doc_freq` is the number of documents containing the term in the segment.
+    fn new_term(&mut self, term_doc_freq: u32);
+
+    /// Adds a document to the posting list of the current term.
+    ///
+    /// The documents are added in increasing order. The term frequency is 1 if the field does
+    /// not record the term frequencies, or if they are not serialized for the current term.
+    fn write_doc(&mut self, doc: DocId, term_freq: u32);
+
+    /// Writes the posting list of the current term, made of `doc_freq` documents, to `output`.
+    fn close_term(&mut self, doc_freq: u32, output: &mut dyn io::Write) -> io::Result<()>;
+}
+
+/// Decodes the posting lists written by the encoder of a [`PostingsFormat`], block by block.
+///
+/// A posting list is split into blocks of [`POSTINGS_BLOCK_LEN`] documents, except for its
+/// last block which holds fewer than [`POSTINGS_BLOCK_LEN`] documents, possibly none. The
+/// blocks past the last block are empty.
+pub trait PostingsDecoder: Send + Sync {
+    /// Resets the decoder on the posting list of a term with `doc_freq` documents, and positions
+    /// it on the first block.
+    fn reset(&mut self, doc_freq: u32, data: OwnedBytes) -> io::Result<()>;
+
+    /// Returns the index of the current block.
+    fn block_id(&self) -> usize;
+
+    /// Returns the last document of the current block, or `TERMINATED` if the current block is
+    /// the last block of the posting list.
+    ///
+    /// It is called before decoding the block, so it needs to be cheap.
+    fn last_doc_in_block(&self) -> DocId;
+
+    /// Moves to the next block.
+    fn advance(&mut self);
+
+    /// Moves to the first block whose last document is greater than or equal to `target`.
+    fn seek(&mut self, target: DocId) {
+        while self.last_doc_in_block() < target {
+            self.advance();
+        }
+    }
+
+    /// Returns the sum of the term frequencies of the blocks before the current block, that is
+    /// the offset of the positions of its first document.
+    ///
+    /// It is only called if the field records the positions.
+    fn position_offset(&self) -> u64;
+
+    /// Decodes the doc ids of the current block, as well as its term frequencies if
+    /// `term_freqs` is given, and returns the number of documents of the block.
+    fn decode_block(
+        &self,
+        doc_ids: &mut [DocId; POSTINGS_BLOCK_LEN],
+        term_freqs: Option<&mut [u32; POSTINGS_BLOCK_LEN]>,
+    ) -> usize;
+
+    /// Returns a boxed copy of the decoder, at the same block.
+    fn box_clone(&self) -> Box<dyn PostingsDecoder>;
+}
+
+impl Clone for Box<dyn PostingsDecoder> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+/// The current format of the posting lists, made of bitpacked blocks with a skip list, or of a
+/// bitset for the dense posting lists without term frequencies.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultPostingsFormat;
+
+impl PostingsFormat for DefaultPostingsFormat {
+    fn new_encoder(
+        &self,
+        record_option: IndexRecordOption,
+        fieldnorm_reader: Option<FieldNormReader>,
+        average_fieldnorm: Score,
+    ) -> Box<dyn PostingsEncoder> {
+        Box::new(PostingsSerializer::new(
+            average_fieldnorm,
+            record_option,
+            fieldnorm_reader,
+        ))
+    }
+
+    fn open(
+        &self,
+        doc_freq: u32,
+        data: FileSlice,
+        record_option: IndexRecordOption,
+        requested_option: IndexRecordOption,
+    ) -> io::Result<BlockSegmentPostings> {
+        BlockSegmentPostings::open(doc_freq, data, record_option, requested_option)
+    }
+}
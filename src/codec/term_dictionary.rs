@@ -0,0 +1,86 @@
+use std::io;
+
+use crate::directory::FileSlice;
+use crate::postings::TermInfo;
+use crate::termdict::{TermDictionary, TermDictionaryBuilder, TermOrdinal};
+
+/// Encodes and decodes the term dictionaries, which associate the sorted terms of a field to
+/// their [`TermInfo`].
+pub trait TermDictionaryFormat: Send + Sync + 'static {
+    /// Creates the encoder of the term dictionary of a field, written to `wrt`.
+    fn new_encoder<'a>(
+        &self,
+        wrt: &'a mut dyn io::Write,
+    ) -> io::Result<Box<dyn TermDictionaryEncoder + 'a>>;
+
+    /// Opens a term dictionary written by an encoder of this format.
+    ///
+    /// The formats other than the default one return the term dictionary of
+    /// [`TermDictionary::from_decoder()`].
+    fn open(&self, file: FileSlice) -> io::Result<TermDictionary>;
+}
+
+/// Encodes the term dictionary of a field.
+pub trait TermDictionaryEncoder {
+    /// Inserts a term and its term info. The terms are inserted in their lexicographical order.
+    fn insert(&mut self, key: &[u8], term_info: &TermInfo) -> io::Result<()>;
+
+    /// Finishes writing the term dictionary.
+    fn finish(self: Box<Self>) -> io::Result<()>;
+}
+
+/// Decodes a term dictionary written by the encoder of a [`TermDictionaryFormat`].
+///
+/// The terms are identified by their [`TermOrdinal`], that is their position in the sorted list
+/// of terms. The range queries, the automatons and the merges stream the terms by ordinal, from
+/// the ordinals given by [`TermDictionaryDecoder::seek()`].
+pub trait TermDictionaryDecoder: Send + Sync + 'static {
+    /// Returns the number of terms in the dictionary.
+    fn num_terms(&self) -> usize;
+
+    /// Returns the ordinal of the first term greater than or equal to `key`, or the number of
+    /// terms if there is none.
+    fn seek(&self, key: &[u8]) -> io::Result<TermOrdinal>;
+
+    /// Stores the term of the given ordinal in `bytes`, and returns false if there is no such
+    /// term.
+    fn ord_to_term(&self, term_ord: TermOrdinal, bytes: &mut Vec<u8>) -> io::Result<bool>;
+
+    /// Returns the term info of the term of the given ordinal.
+    fn term_info_from_ord(&self, term_ord: TermOrdinal) -> io::Result<TermInfo>;
+}
+
+/// The current format of the term dictionaries, an FST, or an SSTable if the `quickwit` feature
+/// is enabled.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultTermDictionaryFormat;
+
+struct DefaultTermDictionaryEncoder<'a> {
+    term_dictionary_builder: TermDictionaryBuilder<&'a mut dyn io::Write>,
+}
+
+impl<'a> TermDictionaryEncoder for DefaultTermDictionaryEncoder<'a> {
+    fn insert(&mut self, key: &[u8], term_info: &TermInfo) -> io::Result<()> {
+        self.term_dictionary_builder.insert(key, term_info)
+    }
+
+    fn finish(self: Box<Self>) -> io::Result<()> {
+        self.term_dictionary_builder.finish()?;
+        Ok(())
+    }
+}
+
+impl TermDictionaryFormat for DefaultTermDictionaryFormat {
+    fn new_encoder<'a>(
+        &self,
+        wrt: &'a mut dyn io::Write,
+    ) -> io::Result<Box<dyn TermDictionaryEncoder + 'a>> {
+        Ok(Box::new(DefaultTermDictionaryEncoder {
+            term_dictionary_builder: TermDictionaryBuilder::create(wrt)?,
+        }))
+    }
+
+    fn open(&self, file: FileSlice) -> io::Result<TermDictionary> {
+        TermDictionary::open(file)
+    }
+}
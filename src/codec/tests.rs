@@ -0,0 +1,525 @@
+use std::io::{self, Write};
+use std::sync::Arc;
+
+use common::{BinarySerializable, OwnedBytes};
+
+use super::{
+    Codec, CodecManager, ColumnValues, ColumnValuesFormat, FastFieldsFormat, Iterable,
+    PostingsDecoder, PostingsEncoder, PostingsFormat, TermDictionaryDecoder, TermDictionaryEncoder,
+    TermDictionaryFormat, DEFAULT_CODEC_NAME, POSTINGS_BLOCK_LEN,
+};
+use crate::collector::{Count, TopDocs};
+use crate::directory::{FileSlice, RamDirectory};
+use crate::fieldnorm::FieldNormReader;
+use crate::postings::{BlockSegmentPostings, TermInfo};
+use crate::query::{QueryParser, RegexQuery, TermQuery};
+use crate::schema::{IndexRecordOption, Schema, FAST, INDEXED, STRING, TEXT};
+use crate::termdict::{TermDictionary, TermMerger, TermOrdinal};
+use crate::{DocId, Index, IndexSettings, Score, Term, TERMINATED};
+
+/// Writes the blocks of the posting lists as little-endian `u32`s, the doc ids of each block
+/// being followed by its term frequencies.
+struct PlainPostingsFormat;
+
+struct PlainPostingsEncoder {
+    has_freq: bool,
+    doc_ids: Vec<DocId>,
+    term_freqs: Vec<u32>,
+}
+
+impl PostingsEncoder for PlainPostingsEncoder {
+    fn new_term(&mut self, _term_doc_freq: u32) {
+        self.doc_ids.clear();
+        self.term_freqs.clear();
+    }
+
+    fn write_doc(&mut self, doc: DocId, term_freq: u32) {
+        self.doc_ids.push(doc);
+        self.term_freqs.push(term_freq);
+    }
+
+    fn close_term(&mut self, doc_freq: u32, output: &mut dyn io::Write) -> io::Result<()> {
+        assert_eq!(doc_freq as usize, self.doc_ids.len());
+        let blocks = self
+            .doc_ids
+            .chunks(POSTINGS_BLOCK_LEN)
+            .zip(self.term_freqs.chunks(POSTINGS_BLOCK_LEN));
+        for (doc_ids, term_freqs) in blocks {
+            for doc in doc_ids {
+                output.write_all(&doc.to_le_bytes())?;
+            }
+            if self.has_freq {
+                for term_freq in term_freqs {
+                    output.write_all(&term_freq.to_le_bytes())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+struct PlainPostingsDecoder {
+    has_freq: bool,
+    data: OwnedBytes,
+    doc_freq: u32,
+    block_id: usize,
+    position_offset: u64,
+}
+
+impl PlainPostingsDecoder {
+    fn block_len(&self) -> usize {
+        (self.doc_freq as usize)
+            .saturating_sub(self.block_id * POSTINGS_BLOCK_LEN)
+            .min(POSTINGS_BLOCK_LEN)
+    }
+
+    fn read_block_vals(&self, offset: usize, output: &mut [u32]) {
+        if output.is_empty() {
+            return;
+        }
+        let num_u32s_per_doc = if self.has_freq { 2 } else { 1 };
+        let block_start = self.block_id * POSTINGS_BLOCK_LEN * num_u32s_per_doc * 4;
+        let bytes = &self.data.as_slice()[block_start + offset * 4..];
+        for (val, val_bytes) in output.iter_mut().zip(bytes.chunks_exact(4)) {
+            *val = u32::from_le_bytes(val_bytes.try_into().unwrap());
+        }
+    }
+}
+
+impl PostingsDecoder for PlainPostingsDecoder {
+    fn reset(&mut self, doc_freq: u32, data: OwnedBytes) -> io::Result<()> {
+        self.data = data;
+        self.doc_freq = doc_freq;
+        self.block_id = 0;
+        self.position_offset = 0;
+        Ok(())
+    }
+
+    fn block_id(&self) -> usize {
+        self.block_id
+    }
+
+    fn last_doc_in_block(&self) -> DocId {
+        if self.block_len() < POSTINGS_BLOCK_LEN {
+            return TERMINATED;
+        }
+        let mut last_doc = [0u32];
+        self.read_block_vals(POSTINGS_BLOCK_LEN - 1, &mut last_doc);
+        last_doc[0]
+    }
+
+    fn advance(&mut self) {
+        if self.has_freq {
+            let mut term_freqs = vec![0u32; self.block_len()];
+            self.read_block_vals(self.block_len(), &mut term_freqs);
+            self.position_offset += term_freqs
+                .iter()
+                .map(|&term_freq| term_freq as u64)
+                .sum::<u64>();
+        }
+        self.block_id += 1;
+    }
+
+    fn position_offset(&self) -> u64 {
+        self.position_offset
+    }
+
+    fn decode_block(
+        &self,
+        doc_ids: &mut [DocId; POSTINGS_BLOCK_LEN],
+        term_freqs: Option<&mut [u32; POSTINGS_BLOCK_LEN]>,
+    ) -> usize {
+        let block_len = self.block_len();
+        self.read_block_vals(0, &mut doc_ids[..block_len]);
+        if let Some(term_freqs) = term_freqs {
+            self.read_block_vals(block_len, &mut term_freqs[..block_len]);
+        }
+        block_len
+    }
+
+    fn box_clone(&self) -> Box<dyn PostingsDecoder> {
+        Box::new(self.clone())
+    }
+}
+
+impl PostingsFormat for PlainPostingsFormat {
+    fn new_encoder(
+        &self,
+        record_option: IndexRecordOption,
+        _fieldnorm_reader: Option<FieldNormReader>,
+        _average_fieldnorm: Score,
+    ) -> Box<dyn PostingsEncoder> {
+        Box::new(PlainPostingsEncoder {
+            has_freq: record_option.has_freq(),
+            doc_ids: Vec::new(),
+            term_freqs: Vec::new(),
+        })
+    }
+
+    fn open(
+        &self,
+        doc_freq: u32,
+        data: FileSlice,
+        record_option: IndexRecordOption,
+        requested_option: IndexRecordOption,
+    ) -> io::Result<BlockSegmentPostings> {
+        let postings_decoder = PlainPostingsDecoder {
+            has_freq: record_option.has_freq(),
+            data: OwnedBytes::empty(),
+            doc_freq: 0,
+            block_id: 0,
+            position_offset: 0,
+        };
+        BlockSegmentPostings::open_with_decoder(
+            doc_freq,
+            data,
+            Box::new(postings_decoder),
+            record_option,
+            requested_option,
+        )
+    }
+}
+
+/// Writes the number of terms, followed by each term and its term info.
+struct PlainTermDictionaryFormat;
+
+struct PlainTermDictionaryEncoder<'a> {
+    wrt: &'a mut dyn io::Write,
+    terms: Vec<(Vec<u8>, TermInfo)>,
+}
+
+impl<'a> TermDictionaryEncoder for PlainTermDictionaryEncoder<'a> {
+    fn insert(&mut self, key: &[u8], term_info: &TermInfo) -> io::Result<()> {
+        self.terms.push((key.to_vec(), term_info.clone()));
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> io::Result<()> {
+        (self.terms.len() as u64).serialize(self.wrt)?;
+        for (term, term_info) in &self.terms {
+            term.serialize(self.wrt)?;
+            term_info.serialize(self.wrt)?;
+        }
+        self.wrt.flush()
+    }
+}
+
+struct PlainTermDictionaryDecoder {
+    terms: Vec<(Vec<u8>, TermInfo)>,
+}
+
+impl TermDictionaryDecoder for PlainTermDictionaryDecoder {
+    fn num_terms(&self) -> usize {
+        self.terms.len()
+    }
+
+    fn seek(&self, key: &[u8]) -> io::Result<TermOrdinal> {
+        Ok(self
+            .terms
+            .partition_point(|(term, _)| term.as_slice() < key) as TermOrdinal)
+    }
+
+    fn ord_to_term(&self, term_ord: TermOrdinal, bytes: &mut Vec<u8>) -> io::Result<bool> {
+        let Some((term, _)) = self.terms.get(term_ord as usize) else {
+            return Ok(false);
+        };
+        bytes.extend_from_slice(term);
+        Ok(true)
+    }
+
+    fn term_info_from_ord(&self, term_ord: TermOrdinal) -> io::Result<TermInfo> {
+        self.terms
+            .get(term_ord as usize)
+            .map(|(_, term_info)| term_info.clone())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Unknown term ordinal"))
+    }
+}
+
+impl TermDictionaryFormat for PlainTermDictionaryFormat {
+    fn new_encoder<'a>(
+        &self,
+        wrt: &'a mut dyn io::Write,
+    ) -> io::Result<Box<dyn TermDictionaryEncoder + 'a>> {
+        Ok(Box::new(PlainTermDictionaryEncoder {
+            wrt,
+            terms: Vec::new(),
+        }))
+    }
+
+    fn open(&self, file: FileSlice) -> io::Result<TermDictionary> {
+        let bytes = file.read_bytes()?;
+        let mut reader = bytes.as_slice();
+        let num_terms = u64::deserialize(&mut reader)?;
+        let terms = (0..num_terms)
+            .map(|_| {
+                Ok((
+                    Vec::deserialize(&mut reader)?,
+                    TermInfo::deserialize(&mut reader)?,
+                ))
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(TermDictionary::from_decoder(Box::new(
+            PlainTermDictionaryDecoder { terms },
+        )))
+    }
+}
+
+/// Writes the values of the fast fields as little-endian `u64`s.
+struct PlainFastFieldsFormat;
+
+struct PlainColumnValuesFormat;
+
+struct PlainColumnValues {
+    data: OwnedBytes,
+}
+
+impl ColumnValues for PlainColumnValues {
+    fn get_val(&self, idx: u32) -> u64 {
+        let start = idx as usize * 8;
+        u64::from_le_bytes(self.data.as_slice()[start..start + 8].try_into().unwrap())
+    }
+
+    fn min_value(&self) -> u64 {
+        self.iter().min().unwrap_or(0)
+    }
+
+    fn max_value(&self) -> u64 {
+        self.iter().max().unwrap_or(0)
+    }
+
+    fn num_vals(&self) -> u32 {
+        (self.data.len() / 8) as u32
+    }
+}
+
+impl ColumnValuesFormat for PlainColumnValuesFormat {
+    fn serialize(&self, vals: &dyn Iterable<u64>, wrt: &mut dyn io::Write) -> io::Result<()> {
+        for val in vals.boxed_iter() {
+            wrt.write_all(&val.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn load(&self, data: OwnedBytes) -> io::Result<Arc<dyn ColumnValues<u64>>> {
+        Ok(Arc::new(PlainColumnValues { data }))
+    }
+}
+
+impl FastFieldsFormat for PlainFastFieldsFormat {
+    fn column_values_format(&self) -> Option<Arc<dyn ColumnValuesFormat>> {
+        Some(Arc::new(PlainColumnValuesFormat))
+    }
+}
+
+struct PlainCodec;
+
+impl Codec for PlainCodec {
+    fn name(&self) -> &str {
+        "plain"
+    }
+
+    fn postings_format(&self) -> &dyn PostingsFormat {
+        &PlainPostingsFormat
+    }
+
+    fn term_dictionary_format(&self) -> &dyn TermDictionaryFormat {
+        &PlainTermDictionaryFormat
+    }
+
+    fn fast_fields_format(&self) -> &dyn FastFieldsFormat {
+        &PlainFastFieldsFormat
+    }
+}
+
+fn plain_codecs() -> CodecManager {
+    let codecs = CodecManager::default();
+    codecs.register(PlainCodec);
+    codecs
+}
+
+const NUM_DOCS_PER_SEGMENT: u64 = 300;
+
+/// Creates an index with a segment written by each of the codecs.
+fn build_index(codec_names: &[&str], directory: RamDirectory) -> crate::Result<Index> {
+    let mut schema_builder = Schema::builder();
+    let text = schema_builder.add_text_field("text", TEXT);
+    let tag = schema_builder.add_text_field("tag", STRING);
+    let num = schema_builder.add_u64_field("num", FAST | INDEXED);
+    let mut index = Index::builder()
+        .schema(schema_builder.build())
+        .codecs(plain_codecs())
+        .open_or_create(directory)?;
+    for (segment_ord, codec_name) in codec_names.iter().enumerate() {
+        index.settings_mut().codec = codec_name.to_string();
+        let mut index_writer = index.writer_for_tests()?;
+        let first_doc = segment_ord as u64 * NUM_DOCS_PER_SEGMENT;
+        for i in first_doc..first_doc + NUM_DOCS_PER_SEGMENT {
+            let words = match i % 3 {
+                0 => "hello world",
+                1 => "world hello hello",
+                _ => "goodbye",
+            };
+            index_writer.add_document(doc!(
+                text => format!("{words}{}", " sea".repeat(i as usize % 7)),
+                tag => if i % 5 == 0 { "five" } else { "other" },
+                num => i,
+            ))?;
+        }
+        index_writer.commit()?;
+    }
+    Ok(index)
+}
+
+#[derive(Debug, PartialEq)]
+struct SearchResults {
+    hello_hits: Vec<(u64, Score)>,
+    top_scores: Vec<Score>,
+    counts: Vec<usize>,
+    num_sum: u64,
+    text_terms: Vec<String>,
+}
+
+fn search(index: &Index) -> crate::Result<SearchResults> {
+    let schema = index.schema();
+    let text = schema.get_field("text").unwrap();
+    let tag = schema.get_field("tag").unwrap();
+    let searcher = index.reader()?.searcher();
+    let num_value = |doc_address: crate::DocAddress| -> crate::Result<u64> {
+        let segment_reader = searcher.segment_reader(doc_address.segment_ord);
+        Ok(segment_reader
+            .fast_fields()
+            .u64("num")?
+            .first(doc_address.doc_id)
+            .unwrap())
+    };
+
+    let hello_query = TermQuery::new(
+        Term::from_field_text(text, "hello"),
+        IndexRecordOption::WithFreqs,
+    );
+    let mut hello_hits = searcher
+        .search(&hello_query, &TopDocs::with_limit(10_000))?
+        .into_iter()
+        .map(|(score, doc_address)| Ok((num_value(doc_address)?, score)))
+        .collect::<crate::Result<Vec<_>>>()?;
+    hello_hits.sort_by_key(|(num, _)| *num);
+
+    let query_parser = QueryParser::for_index(index, vec![text]);
+    let top_scores = searcher
+        .search(
+            &query_parser.parse_query("hello sea")?,
+            &TopDocs::with_limit(10),
+        )?
+        .into_iter()
+        .map(|(score, _)| score)
+        .collect();
+
+    let mut counts = Vec::new();
+    for query_str in [
+        "\"world hello\"",
+        "\"hello world sea\"",
+        "+hello +num:[100 TO 500]",
+        "+sea -world",
+        "num:[150 TO 450}",
+    ] {
+        counts.push(searcher.search(&query_parser.parse_query(query_str)?, &Count)?);
+    }
+    counts.push(searcher.search(&RegexQuery::from_pattern("go.*", text)?, &Count)?);
+    let tag_query = TermQuery::new(Term::from_field_text(tag, "five"), IndexRecordOption::Basic);
+    counts.push(searcher.search(&tag_query, &Count)?);
+
+    let mut num_sum = 0;
+    for segment_reader in searcher.segment_readers() {
+        let num_column = segment_reader.fast_fields().u64("num")?;
+        num_sum += num_column.values.iter().sum::<u64>();
+    }
+
+    let inverted_indexes = searcher
+        .segment_readers()
+        .iter()
+        .map(|segment_reader| segment_reader.inverted_index(text))
+        .collect::<crate::Result<Vec<_>>>()?;
+    let streams = inverted_indexes
+        .iter()
+        .map(|inverted_index| inverted_index.terms().stream())
+        .collect::<io::Result<Vec<_>>>()?;
+    let mut term_merger = TermMerger::new(streams);
+    let mut text_terms = Vec::new();
+    while term_merger.advance() {
+        text_terms.push(String::from_utf8(term_merger.key().to_vec()).unwrap());
+    }
+
+    Ok(SearchResults {
+        hello_hits,
+        top_scores,
+        counts,
+        num_sum,
+        text_terms,
+    })
+}
+
+fn segment_codec_names(index: &Index) -> crate::Result<Vec<String>> {
+    let mut codec_names: Vec<String> = index
+        .searchable_segment_metas()?
+        .iter()
+        .map(|segment_meta| segment_meta.codec().to_string())
+        .collect();
+    codec_names.sort();
+    Ok(codec_names)
+}
+
+#[test]
+fn test_codec_manager() {
+    let codecs = plain_codecs();
+    assert_eq!(codecs.get(DEFAULT_CODEC_NAME).unwrap().name(), "default");
+    assert_eq!(codecs.get("plain").unwrap().name(), "plain");
+    assert!(codecs.get("elias_fano").is_none());
+}
+
+#[test]
+fn test_codecs_of_the_segments() -> crate::Result<()> {
+    let default_index = build_index(&["default", "default"], RamDirectory::create())?;
+    let expected_results = search(&default_index)?;
+    assert_eq!(expected_results.num_sum, 599 * 600 / 2);
+
+    let plain_index = build_index(&["plain", "plain"], RamDirectory::create())?;
+    assert_eq!(segment_codec_names(&plain_index)?, vec!["plain", "plain"]);
+    assert_eq!(search(&plain_index)?, expected_results);
+
+    let directory = RamDirectory::create();
+    let index = build_index(&["default", "plain"], directory.clone())?;
+    assert_eq!(segment_codec_names(&index)?, vec!["default", "plain"]);
+    assert_eq!(search(&index)?, expected_results);
+
+    // The merged segment is written with the codec of the index.
+    let mut index_writer = index.writer_for_tests()?;
+    index_writer
+        .merge(&index.searchable_segment_ids()?)
+        .wait()?;
+    assert_eq!(segment_codec_names(&index)?, vec!["plain"]);
+    assert_eq!(search(&index)?, expected_results);
+
+    // The codec is required to read the segments.
+    let reopened_index = Index::open(directory)?;
+    assert_eq!(reopened_index.settings().codec, "plain");
+    assert!(reopened_index.reader().is_err());
+    reopened_index.codecs().register(PlainCodec);
+    assert_eq!(search(&reopened_index)?, expected_results);
+    Ok(())
+}
+
+#[test]
+fn test_unregistered_codec() {
+    let mut schema_builder = Schema::builder();
+    schema_builder.add_text_field("title", TEXT);
+    let settings = IndexSettings {
+        codec: "elias_fano".to_string(),
+        ..Default::default()
+    };
+    assert!(Index::builder()
+        .schema(schema_builder.build())
+        .settings(settings)
+        .create_in_ram()
+        .is_err());
+}
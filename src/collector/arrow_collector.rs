@@ -0,0 +1,850 @@
+use columnar::{BytesColumn, Column, StrColumn};
+
+use crate::collector::{Collector, SegmentCollector};
+use crate::fastfield::FastFieldNotAvailableError;
+use crate::schema::Type;
+use crate::{DateTime, DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+
+/// Name of the column of the segment ordinals of the hits, in an [`ArrowRecordBatch`].
+pub const SEGMENT_ORD_COLUMN: &str = "_segment_ord";
+/// Name of the column of the doc ids of the hits, in an [`ArrowRecordBatch`].
+pub const DOC_ID_COLUMN: &str = "_doc_id";
+/// Name of the column of the scores of the hits, in an [`ArrowRecordBatch`].
+pub const SCORE_COLUMN: &str = "_score";
+
+fn get_bit(bitmap: &[u8], i: usize) -> bool {
+    bitmap[i / 8] & (1 << (i % 8)) != 0
+}
+
+fn push_bit(bitmap: &mut Vec<u8>, len: usize, bit: bool) {
+    if len % 8 == 0 {
+        bitmap.push(0);
+    }
+    if bit {
+        bitmap[len / 8] |= 1 << (len % 8);
+    }
+}
+
+/// The values of an [`ArrowColumn`], laid out as the buffers of the Arrow array of the same
+/// data type.
+///
+/// The buffers can be handed over to an Arrow implementation without copying them, e.g. with
+/// `arrow::buffer::ScalarBuffer::from(values)` for the primitive types.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ArrowValues {
+    /// Arrow `UInt32`.
+    UInt32(Vec<u32>),
+    /// Arrow `UInt64`.
+    UInt64(Vec<u64>),
+    /// Arrow `Int64`.
+    Int64(Vec<i64>),
+    /// Arrow `Float32`.
+    Float32(Vec<f32>),
+    /// Arrow `Float64`.
+    Float64(Vec<f64>),
+    /// Arrow `Boolean`: a bitmap, in the least significant bit order.
+    Boolean(Vec<u8>),
+    /// Arrow `Timestamp(Nanosecond, None)`.
+    TimestampNanosecond(Vec<i64>),
+    /// Arrow `LargeUtf8`: the value `i` is `data[offsets[i]..offsets[i + 1]]`.
+    LargeUtf8 {
+        /// The `len + 1` offsets of the values in `data`.
+        offsets: Vec<i64>,
+        /// The concatenated values.
+        data: Vec<u8>,
+    },
+    /// Arrow `LargeBinary`: the value `i` is `data[offsets[i]..offsets[i + 1]]`.
+    LargeBinary {
+        /// The `len + 1` offsets of the values in `data`.
+        offsets: Vec<i64>,
+        /// The concatenated values.
+        data: Vec<u8>,
+    },
+}
+
+impl ArrowValues {
+    fn empty(value_type: Type) -> crate::Result<ArrowValues> {
+        Ok(match value_type {
+            Type::U64 => ArrowValues::UInt64(Vec::new()),
+            Type::I64 => ArrowValues::Int64(Vec::new()),
+            Type::F64 => ArrowValues::Float64(Vec::new()),
+            Type::Bool => ArrowValues::Boolean(Vec::new()),
+            Type::Date => ArrowValues::TimestampNanosecond(Vec::new()),
+            Type::Str => ArrowValues::LargeUtf8 {
+                offsets: vec![0],
+                data: Vec::new(),
+            },
+            Type::Bytes => ArrowValues::LargeBinary {
+                offsets: vec![0],
+                data: Vec::new(),
+            },
+            _ => {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "Fast fields of type {value_type:?} cannot be exported to Arrow"
+                )))
+            }
+        })
+    }
+}
+
+/// A column of an [`ArrowRecordBatch`], in the Arrow memory layout.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArrowColumn {
+    len: usize,
+    null_count: usize,
+    validity: Vec<u8>,
+    values: ArrowValues,
+}
+
+impl ArrowColumn {
+    fn new(values: ArrowValues) -> ArrowColumn {
+        ArrowColumn {
+            len: 0,
+            null_count: 0,
+            validity: Vec::new(),
+            values,
+        }
+    }
+
+    /// Creates a column of `len` values from the buffers of an Arrow array, typically to load
+    /// it with [`IndexWriter::add_record_batch()`](crate::IndexWriter::add_record_batch).
+    ///
+    /// Without a validity bitmap, all of the values are valid. Returns an error if the buffers
+    /// are too short for `len` values, or if the offsets are not increasing.
+    pub fn try_new(
+        len: usize,
+        values: ArrowValues,
+        validity: Option<Vec<u8>>,
+    ) -> crate::Result<ArrowColumn> {
+        let bitmap_len = (len + 7) / 8;
+        let is_valid = match &values {
+            ArrowValues::UInt32(values) => values.len() == len,
+            ArrowValues::UInt64(values) => values.len() == len,
+            ArrowValues::Int64(values) | ArrowValues::TimestampNanosecond(values) => {
+                values.len() == len
+            }
+            ArrowValues::Float32(values) => values.len() == len,
+            ArrowValues::Float64(values) => values.len() == len,
+            ArrowValues::Boolean(values) => values.len() >= bitmap_len,
+            ArrowValues::LargeUtf8 { offsets, data }
+            | ArrowValues::LargeBinary { offsets, data } => {
+                offsets.len() == len + 1
+                    && offsets[0] >= 0
+                    && offsets.windows(2).all(|window| window[0] <= window[1])
+                    && offsets[len] as usize <= data.len()
+            }
+        };
+        if !is_valid
+            || validity
+                .as_ref()
+                .map_or(false, |validity| validity.len() < bitmap_len)
+        {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The buffers of the column do not hold {len} values"
+            )));
+        }
+        let (validity, null_count) = match validity {
+            Some(validity) => {
+                let null_count = (0..len).filter(|&i| !get_bit(&validity, i)).count();
+                (validity, null_count)
+            }
+            None => {
+                let mut validity = vec![u8::MAX; bitmap_len];
+                if len % 8 != 0 {
+                    validity[len / 8] = (1u8 << (len % 8)) - 1;
+                }
+                (validity, 0)
+            }
+        };
+        Ok(ArrowColumn {
+            len,
+            null_count,
+            validity,
+            values,
+        })
+    }
+
+    /// Returns the number of values of the column, nulls included.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the column has no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of null values of the column.
+    pub fn null_count(&self) -> usize {
+        self.null_count
+    }
+
+    /// Returns the validity bitmap of the column, in the least significant bit order, or
+    /// `None` if the column has no null values.
+    pub fn validity(&self) -> Option<&[u8]> {
+        if self.null_count == 0 {
+            None
+        } else {
+            Some(&self.validity)
+        }
+    }
+
+    /// Returns true if the value `i` is not null.
+    pub fn is_valid(&self, i: usize) -> bool {
+        get_bit(&self.validity, i)
+    }
+
+    /// Returns the values of the column. The values at the null positions are unspecified.
+    pub fn values(&self) -> &ArrowValues {
+        &self.values
+    }
+
+    /// Consumes the column, and returns its validity bitmap and its values.
+    pub fn into_parts(self) -> (Option<Vec<u8>>, ArrowValues) {
+        let validity = if self.null_count == 0 {
+            None
+        } else {
+            Some(self.validity)
+        };
+        (validity, self.values)
+    }
+
+    fn push_validity(&mut self, is_valid: bool) {
+        push_bit(&mut self.validity, self.len, is_valid);
+        if !is_valid {
+            self.null_count += 1;
+        }
+        self.len += 1;
+    }
+
+    fn push_u32(&mut self, value: u32) {
+        if let ArrowValues::UInt32(values) = &mut self.values {
+            values.push(value);
+        }
+        self.push_validity(true);
+    }
+
+    fn push_f32(&mut self, value: f32) {
+        if let ArrowValues::Float32(values) = &mut self.values {
+            values.push(value);
+        }
+        self.push_validity(true);
+    }
+
+    fn push_null(&mut self) {
+        match &mut self.values {
+            ArrowValues::UInt32(values) => values.push(0),
+            ArrowValues::UInt64(values) => values.push(0),
+            ArrowValues::Int64(values) | ArrowValues::TimestampNanosecond(values) => values.push(0),
+            ArrowValues::Float32(values) => values.push(0.0),
+            ArrowValues::Float64(values) => values.push(0.0),
+            ArrowValues::Boolean(values) => push_bit(values, self.len, false),
+            ArrowValues::LargeUtf8 { offsets, data }
+            | ArrowValues::LargeBinary { offsets, data } => offsets.push(data.len() as i64),
+        }
+        self.push_validity(false);
+    }
+
+    fn append(&mut self, other: &ArrowColumn) {
+        match (&mut self.values, &other.values) {
+            (ArrowValues::UInt32(values), ArrowValues::UInt32(other_values)) => {
+                values.extend_from_slice(other_values)
+            }
+            (ArrowValues::UInt64(values), ArrowValues::UInt64(other_values)) => {
+                values.extend_from_slice(other_values)
+            }
+            (ArrowValues::Int64(values), ArrowValues::Int64(other_values))
+            | (
+                ArrowValues::TimestampNanosecond(values),
+                ArrowValues::TimestampNanosecond(other_values),
+            ) => values.extend_from_slice(other_values),
+            (ArrowValues::Float32(values), ArrowValues::Float32(other_values)) => {
+                values.extend_from_slice(other_values)
+            }
+            (ArrowValues::Float64(values), ArrowValues::Float64(other_values)) => {
+                values.extend_from_slice(other_values)
+            }
+            (ArrowValues::Boolean(values), ArrowValues::Boolean(other_values)) => {
+                for i in 0..other.len {
+                    push_bit(values, self.len + i, get_bit(other_values, i));
+                }
+            }
+            (
+                ArrowValues::LargeUtf8 { offsets, data },
+                ArrowValues::LargeUtf8 {
+                    offsets: other_offsets,
+                    data: other_data,
+                },
+            )
+            | (
+                ArrowValues::LargeBinary { offsets, data },
+                ArrowValues::LargeBinary {
+                    offsets: other_offsets,
+                    data: other_data,
+                },
+            ) => {
+                let base_offset = data.len() as i64;
+                offsets.extend(other_offsets[1..].iter().map(|offset| base_offset + offset));
+                data.extend_from_slice(other_data);
+            }
+            _ => panic!("Cannot append columns of different types"),
+        }
+        for i in 0..other.len {
+            self.push_validity(other.is_valid(i));
+        }
+    }
+}
+
+/// The hits of a search, along with their fast field values, as the columns of an Arrow
+/// record batch.
+///
+/// The rows are sorted by segment, then by doc id. The batch starts with the
+/// [`SEGMENT_ORD_COLUMN`] and [`DOC_ID_COLUMN`] columns, followed by the [`SCORE_COLUMN`]
+/// column if the scores were collected, and by the columns of the fast fields, in the order
+/// they were given to the [`ArrowCollector`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArrowRecordBatch {
+    num_rows: usize,
+    columns: Vec<(String, ArrowColumn)>,
+}
+
+impl ArrowRecordBatch {
+    /// Creates a batch from named columns, which must all have the same number of values.
+    pub fn try_new(columns: Vec<(String, ArrowColumn)>) -> crate::Result<ArrowRecordBatch> {
+        let num_rows = columns.first().map_or(0, |(_, column)| column.len());
+        if let Some((name, column)) = columns.iter().find(|(_, column)| column.len() != num_rows) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The column {name:?} has {} values, expected {num_rows}",
+                column.len()
+            )));
+        }
+        Ok(ArrowRecordBatch { num_rows, columns })
+    }
+
+    /// Returns the number of rows of the batch, that is the number of hits.
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    /// Returns the names and the columns of the batch.
+    pub fn columns(&self) -> &[(String, ArrowColumn)] {
+        &self.columns
+    }
+
+    /// Returns the column with the given name.
+    pub fn column(&self, name: &str) -> Option<&ArrowColumn> {
+        self.columns
+            .iter()
+            .find(|(column_name, _)| column_name == name)
+            .map(|(_, column)| column)
+    }
+
+    /// Consumes the batch, and returns its names and columns.
+    pub fn into_columns(self) -> Vec<(String, ArrowColumn)> {
+        self.columns
+    }
+}
+
+/// Materializes the hits of a search and the values of some of their fast fields into an
+/// [`ArrowRecordBatch`], column by column.
+///
+/// The values are stored in the memory layout of Arrow, so that they can be consumed by
+/// analytical engines such as DataFusion or Polars without converting each document.
+///
+/// The fast fields of type `u64`, `i64`, `f64`, `bool`, `date`, `str` and `bytes` are
+/// supported. A document without a value gets a null, and only the first value of the
+/// multivalued fields is kept.
+///
+/// ```rust
+/// use tantivy::collector::{ArrowCollector, ArrowValues};
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let price = schema_builder.add_u64_field("price", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+/// index_writer.add_document(doc!(price => 10u64))?;
+/// index_writer.add_document(doc!(price => 20u64))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let batch = searcher.search(&AllQuery, &ArrowCollector::new(["price"]))?;
+/// assert_eq!(batch.num_rows(), 2);
+/// let prices = batch.column("price").unwrap();
+/// assert_eq!(prices.values(), &ArrowValues::UInt64(vec![10, 20]));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ArrowCollector {
+    fast_fields: Vec<String>,
+    with_scores: bool,
+}
+
+impl ArrowCollector {
+    /// Creates a collector of the values of the given fast fields.
+    pub fn new<S: ToString>(fast_fields: impl IntoIterator<Item = S>) -> ArrowCollector {
+        ArrowCollector {
+            fast_fields: fast_fields
+                .into_iter()
+                .map(|fast_field| fast_field.to_string())
+                .collect(),
+            with_scores: false,
+        }
+    }
+
+    /// Also collects the scores of the hits, in the [`SCORE_COLUMN`] column.
+    #[must_use]
+    pub fn with_scores(mut self) -> ArrowCollector {
+        self.with_scores = true;
+        self
+    }
+}
+
+enum FastFieldColumn {
+    U64(Column<u64>),
+    I64(Column<i64>),
+    F64(Column<f64>),
+    Bool(Column<bool>),
+    Date(Column<DateTime>),
+    Str(StrColumn),
+    Bytes(BytesColumn),
+    Missing,
+}
+
+impl FastFieldColumn {
+    fn open(segment: &SegmentReader, field_name: &str) -> crate::Result<(Self, ArrowValues)> {
+        let field = segment.schema().get_field(field_name)?;
+        let field_entry = segment.schema().get_field_entry(field);
+        if !field_entry.is_fast() {
+            return Err(FastFieldNotAvailableError::new(field_entry).into());
+        }
+        let value_type = field_entry.field_type().value_type();
+        let values = ArrowValues::empty(value_type)?;
+        let fast_fields = segment.fast_fields();
+        let column = match value_type {
+            Type::U64 => fast_fields
+                .column_opt(field_name)?
+                .map(FastFieldColumn::U64),
+            Type::I64 => fast_fields
+                .column_opt(field_name)?
+                .map(FastFieldColumn::I64),
+            Type::F64 => fast_fields
+                .column_opt(field_name)?
+                .map(FastFieldColumn::F64),
+            Type::Bool => fast_fields
+                .column_opt(field_name)?
+                .map(FastFieldColumn::Bool),
+            Type::Date => fast_fields
+                .column_opt(field_name)?
+                .map(FastFieldColumn::Date),
+            Type::Str => fast_fields.str(field_name)?.map(FastFieldColumn::Str),
+            Type::Bytes => fast_fields.bytes(field_name)?.map(FastFieldColumn::Bytes),
+            _ => None,
+        };
+        Ok((column.unwrap_or(FastFieldColumn::Missing), values))
+    }
+
+    fn fill(&self, docs: &[DocId], column: &mut ArrowColumn) -> crate::Result<()> {
+        fn fill_primitive<T: PartialOrd + Copy + std::fmt::Debug + Send + Sync + 'static, V>(
+            docs: &[DocId],
+            fast_field_column: &Column<T>,
+            column: &mut ArrowColumn,
+            to_value: impl Fn(T) -> V,
+            values: impl Fn(&mut ArrowValues) -> Option<&mut Vec<V>>,
+        ) {
+            for &doc in docs {
+                match fast_field_column.first(doc) {
+                    Some(value) => {
+                        if let Some(values) = values(&mut column.values) {
+                            values.push(to_value(value));
+                        }
+                        column.push_validity(true);
+                    }
+                    None => column.push_null(),
+                }
+            }
+        }
+        match self {
+            FastFieldColumn::U64(fast_field_column) => fill_primitive(
+                docs,
+                fast_field_column,
+                column,
+                |value| value,
+                |values| {
+                    if let ArrowValues::UInt64(values) = values {
+                        Some(values)
+                    } else {
+                        None
+                    }
+                },
+            ),
+            FastFieldColumn::I64(fast_field_column) => fill_primitive(
+                docs,
+                fast_field_column,
+                column,
+                |value| value,
+                |values| {
+                    if let ArrowValues::Int64(values) = values {
+                        Some(values)
+                    } else {
+                        None
+                    }
+                },
+            ),
+            FastFieldColumn::F64(fast_field_column) => fill_primitive(
+                docs,
+                fast_field_column,
+                column,
+                |value| value,
+                |values| {
+                    if let ArrowValues::Float64(values) = values {
+                        Some(values)
+                    } else {
+                        None
+                    }
+                },
+            ),
+            FastFieldColumn::Date(fast_field_column) => fill_primitive(
+                docs,
+                fast_field_column,
+                column,
+                DateTime::into_timestamp_nanos,
+                |values| {
+                    if let ArrowValues::TimestampNanosecond(values) = values {
+                        Some(values)
+                    } else {
+                        None
+                    }
+                },
+            ),
+            FastFieldColumn::Bool(fast_field_column) => {
+                for &doc in docs {
+                    match fast_field_column.first(doc) {
+                        Some(value) => {
+                            if let ArrowValues::Boolean(values) = &mut column.values {
+                                push_bit(values, column.len, value);
+                            }
+                            column.push_validity(true);
+                        }
+                        None => column.push_null(),
+                    }
+                }
+            }
+            FastFieldColumn::Str(str_column) => {
+                let mut buffer = String::new();
+                for &doc in docs {
+                    match str_column.term_ords(doc).next() {
+                        Some(term_ord) => {
+                            buffer.clear();
+                            str_column.ord_to_str(term_ord, &mut buffer)?;
+                            push_bytes(column, buffer.as_bytes());
+                        }
+                        None => column.push_null(),
+                    }
+                }
+            }
+            FastFieldColumn::Bytes(bytes_column) => {
+                let mut buffer = Vec::new();
+                for &doc in docs {
+                    match bytes_column.term_ords(doc).next() {
+                        Some(term_ord) => {
+                            buffer.clear();
+                            bytes_column.ord_to_bytes(term_ord, &mut buffer)?;
+                            push_bytes(column, &buffer);
+                        }
+                        None => column.push_null(),
+                    }
+                }
+            }
+            FastFieldColumn::Missing => {
+                for _ in docs {
+                    column.push_null();
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn push_bytes(column: &mut ArrowColumn, bytes: &[u8]) {
+    if let ArrowValues::LargeUtf8 { offsets, data } | ArrowValues::LargeBinary { offsets, data } =
+        &mut column.values
+    {
+        data.extend_from_slice(bytes);
+        offsets.push(data.len() as i64);
+    }
+    column.push_validity(true);
+}
+
+/// The [`SegmentCollector`] of the [`ArrowCollector`].
+pub struct ArrowSegmentCollector {
+    segment_ord: SegmentOrdinal,
+    docs: Vec<DocId>,
+    scores: Option<Vec<Score>>,
+    fast_field_columns: Vec<(String, FastFieldColumn, ArrowValues)>,
+}
+
+impl SegmentCollector for ArrowSegmentCollector {
+    type Fruit = crate::Result<ArrowRecordBatch>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        self.docs.push(doc);
+        if let Some(scores) = &mut self.scores {
+            scores.push(score);
+        }
+    }
+
+    fn collect_block(&mut self, docs: &[DocId]) {
+        self.docs.extend_from_slice(docs);
+        if let Some(scores) = &mut self.scores {
+            scores.resize(self.docs.len(), 0.0);
+        }
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        let mut columns = Vec::with_capacity(self.fast_field_columns.len() + 3);
+        let mut segment_ords = ArrowColumn::new(ArrowValues::UInt32(Vec::new()));
+        let mut doc_ids = ArrowColumn::new(ArrowValues::UInt32(Vec::new()));
+        for &doc in &self.docs {
+            segment_ords.push_u32(self.segment_ord);
+            doc_ids.push_u32(doc);
+        }
+        columns.push((SEGMENT_ORD_COLUMN.to_string(), segment_ords));
+        columns.push((DOC_ID_COLUMN.to_string(), doc_ids));
+        if let Some(scores) = &self.scores {
+            let mut score_column = ArrowColumn::new(ArrowValues::Float32(Vec::new()));
+            for &score in scores {
+                score_column.push_f32(score);
+            }
+            columns.push((SCORE_COLUMN.to_string(), score_column));
+        }
+        for (field_name, fast_field_column, values) in self.fast_field_columns {
+            let mut column = ArrowColumn::new(values);
+            fast_field_column.fill(&self.docs, &mut column)?;
+            columns.push((field_name, column));
+        }
+        Ok(ArrowRecordBatch {
+            num_rows: self.docs.len(),
+            columns,
+        })
+    }
+}
+
+impl Collector for ArrowCollector {
+    type Fruit = ArrowRecordBatch;
+    type Child = ArrowSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_ord: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> crate::Result<ArrowSegmentCollector> {
+        let fast_field_columns = self
+            .fast_fields
+            .iter()
+            .map(|field_name| {
+                let (column, values) = FastFieldColumn::open(segment, field_name)?;
+                Ok((field_name.clone(), column, values))
+            })
+            .collect::<crate::Result<_>>()?;
+        Ok(ArrowSegmentCollector {
+            segment_ord,
+            docs: Vec::new(),
+            scores: self.with_scores.then(Vec::new),
+            fast_field_columns,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.with_scores
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_batches: Vec<crate::Result<ArrowRecordBatch>>,
+    ) -> crate::Result<ArrowRecordBatch> {
+        let mut merged_batch: Option<ArrowRecordBatch> = None;
+        for segment_batch in segment_batches {
+            let segment_batch = segment_batch?;
+            match &mut merged_batch {
+                None => merged_batch = Some(segment_batch),
+                Some(merged_batch) => {
+                    merged_batch.num_rows += segment_batch.num_rows;
+                    for ((_, column), (_, segment_column)) in
+                        merged_batch.columns.iter_mut().zip(&segment_batch.columns)
+                    {
+                        column.append(segment_column);
+                    }
+                }
+            }
+        }
+        Ok(merged_batch.unwrap_or_else(|| self.empty_batch()))
+    }
+}
+
+impl ArrowCollector {
+    /// The batch of a search over no segments, whose fast field columns are typed as `UInt64`
+    /// as their type is unknown.
+    fn empty_batch(&self) -> ArrowRecordBatch {
+        let mut columns = vec![
+            (
+                SEGMENT_ORD_COLUMN.to_string(),
+                ArrowColumn::new(ArrowValues::UInt32(Vec::new())),
+            ),
+            (
+                DOC_ID_COLUMN.to_string(),
+                ArrowColumn::new(ArrowValues::UInt32(Vec::new())),
+            ),
+        ];
+        if self.with_scores {
+            columns.push((
+                SCORE_COLUMN.to_string(),
+                ArrowColumn::new(ArrowValues::Float32(Vec::new())),
+            ));
+        }
+        for field_name in &self.fast_fields {
+            columns.push((
+                field_name.clone(),
+                ArrowColumn::new(ArrowValues::UInt64(Vec::new())),
+            ));
+        }
+        ArrowRecordBatch {
+            num_rows: 0,
+            columns,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArrowCollector, ArrowValues, DOC_ID_COLUMN, SCORE_COLUMN, SEGMENT_ORD_COLUMN};
+    use crate::indexer::NoMergePolicy;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, STRING};
+    use crate::{DateTime, Index, Term};
+
+    #[test]
+    fn test_arrow_collector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_text_field("category", STRING | FAST);
+        let price = schema_builder.add_f64_field("price", FAST);
+        let in_stock = schema_builder.add_bool_field("in_stock", FAST);
+        let date = schema_builder.add_date_field("date", FAST);
+        let payload = schema_builder.add_bytes_field("payload", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(
+            category => "book",
+            price => 10.5f64,
+            in_stock => true,
+            date => DateTime::from_timestamp_secs(1),
+            payload => vec![1u8, 2u8],
+        ))?;
+        index_writer.add_document(doc!(category => "toy", in_stock => false))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(category => "book", price => 3.0f64))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let collector =
+            ArrowCollector::new(["category", "price", "in_stock", "date", "payload"]).with_scores();
+        let batch = searcher.search(&AllQuery, &collector)?;
+        assert_eq!(batch.num_rows(), 3);
+        let column_names: Vec<&str> = batch
+            .columns()
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(
+            column_names,
+            [
+                SEGMENT_ORD_COLUMN,
+                DOC_ID_COLUMN,
+                SCORE_COLUMN,
+                "category",
+                "price",
+                "in_stock",
+                "date",
+                "payload"
+            ]
+        );
+        assert_eq!(
+            batch.column(SEGMENT_ORD_COLUMN).unwrap().values(),
+            &ArrowValues::UInt32(vec![0, 0, 1])
+        );
+        assert_eq!(
+            batch.column(DOC_ID_COLUMN).unwrap().values(),
+            &ArrowValues::UInt32(vec![0, 1, 0])
+        );
+        assert_eq!(
+            batch.column("category").unwrap().values(),
+            &ArrowValues::LargeUtf8 {
+                offsets: vec![0, 4, 7, 11],
+                data: b"booktoybook".to_vec()
+            }
+        );
+
+        let prices = batch.column("price").unwrap();
+        assert_eq!(prices.null_count(), 1);
+        assert_eq!(prices.validity(), Some(&[0b101u8][..]));
+        let ArrowValues::Float64(price_values) = prices.values() else {
+            panic!("price should be a Float64 column");
+        };
+        assert_eq!((price_values[0], price_values[2]), (10.5, 3.0));
+
+        let in_stock = batch.column("in_stock").unwrap();
+        assert_eq!(in_stock.validity(), Some(&[0b011u8][..]));
+        assert_eq!(in_stock.values(), &ArrowValues::Boolean(vec![0b001]));
+
+        let dates = batch.column("date").unwrap();
+        assert!(dates.is_valid(0) && !dates.is_valid(1));
+        let ArrowValues::TimestampNanosecond(date_values) = dates.values() else {
+            panic!("date should be a Timestamp column");
+        };
+        assert_eq!(date_values[0], 1_000_000_000);
+
+        let payloads = batch.column("payload").unwrap();
+        assert_eq!(payloads.null_count(), 2);
+        assert_eq!(
+            payloads.values(),
+            &ArrowValues::LargeBinary {
+                offsets: vec![0, 2, 2, 2],
+                data: vec![1, 2]
+            }
+        );
+
+        // Only the matching documents are materialized.
+        let query = TermQuery::new(
+            Term::from_field_text(category, "toy"),
+            IndexRecordOption::Basic,
+        );
+        let batch = searcher.search(&query, &ArrowCollector::new(["in_stock"]))?;
+        assert_eq!(batch.num_rows(), 1);
+        assert!(batch.column(SCORE_COLUMN).is_none());
+        assert_eq!(batch.column("in_stock").unwrap().null_count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_arrow_collector_requires_fast_fields() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!())?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert!(searcher
+            .search(&AllQuery, &ArrowCollector::new(["title"]))
+            .is_err());
+        assert!(searcher
+            .search(&AllQuery, &ArrowCollector::new(["missing"]))
+            .is_err());
+        Ok(())
+    }
+}
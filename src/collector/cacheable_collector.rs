@@ -0,0 +1,30 @@
+use super::{Collector, Count, DocSetCollector, TopDocs};
+
+/// A collector whose results can be kept in the result cache of an
+/// [`IndexReader`](crate::IndexReader), with
+/// [`Searcher::search_cached`](crate::Searcher::search_cached).
+pub trait CacheableCollector: Collector {
+    /// Returns a key identifying the results of the collector.
+    ///
+    /// Two collectors with the same key must produce the same results for the same query and
+    /// the same searcher.
+    fn cache_key(&self) -> String;
+}
+
+impl CacheableCollector for Count {
+    fn cache_key(&self) -> String {
+        "Count".to_string()
+    }
+}
+
+impl CacheableCollector for DocSetCollector {
+    fn cache_key(&self) -> String {
+        "DocSetCollector".to_string()
+    }
+}
+
+impl CacheableCollector for TopDocs {
+    fn cache_key(&self) -> String {
+        format!("{self:?}")
+    }
+}
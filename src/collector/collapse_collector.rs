@@ -0,0 +1,461 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use columnar::{Column, ColumnType, StrColumn};
+
+use super::top_collector::TopSegmentCollector;
+use crate::collector::{Collector, SegmentCollector};
+use crate::fastfield::FastFieldNotAvailableError;
+use crate::{DocAddress, DocId, Order, Score, SegmentOrdinal, SegmentReader};
+
+/// The column types a [`CollapseCollector`] can order the inner hits by.
+const INNER_HITS_COLUMN_TYPES: &[ColumnType] = &[
+    ColumnType::U64,
+    ColumnType::I64,
+    ColumnType::F64,
+    ColumnType::Bool,
+    ColumnType::DateTime,
+];
+
+/// `CollapseCollector` groups the collected documents by the value of a fast field, and returns
+/// the groups of the best scoring documents, along with up to `n` inner hits per group.
+///
+/// The groups are ranked by the best score of their documents. The inner hits of a group are its
+/// documents sorted by an [`InnerHitsOrder`]: the best variants of a product by score, or the
+/// newest messages of a thread by date, are returned by the same search as the groups.
+///
+/// The field can be a `str` or a `u64` fast field. The documents without a value for the field
+/// are not collected, and the documents with several values are grouped by their first value.
+///
+/// All of the groups of a segment are kept until the fruits of the segments are merged, so that
+/// the memory usage grows with the number of distinct values of the field among the matching
+/// documents.
+///
+/// ```rust
+/// use tantivy::collector::{CollapseCollector, CollapseKey, InnerHitsOrder};
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, FAST, STRING, TEXT};
+/// use tantivy::{doc, Index, Order};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let thread = schema_builder.add_text_field("thread", STRING | FAST);
+/// let body = schema_builder.add_text_field("body", TEXT);
+/// let date = schema_builder.add_u64_field("date", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+/// index_writer.add_document(doc!(thread => "a", body => "tantivy", date => 1u64))?;
+/// index_writer.add_document(doc!(thread => "a", body => "tantivy rocks", date => 2u64))?;
+/// index_writer.add_document(doc!(thread => "a", body => "tantivy", date => 3u64))?;
+/// index_writer.add_document(doc!(thread => "b", body => "tantivy tantivy", date => 4u64))?;
+/// index_writer.commit()?;
+/// let searcher = index.reader()?.searcher();
+///
+/// let query = QueryParser::for_index(&index, vec![body]).parse_query("tantivy")?;
+/// let collector = CollapseCollector::for_field("thread", 10)
+///     .with_inner_hits(2, InnerHitsOrder::by_fast_field("date", Order::Desc));
+/// let groups = searcher.search(&query, &collector)?;
+/// assert_eq!(groups.len(), 2);
+/// assert_eq!(groups[0].key, CollapseKey::Str("b".to_string()));
+/// assert_eq!(groups[1].key, CollapseKey::Str("a".to_string()));
+/// // The two newest messages of the thread `a`.
+/// assert_eq!(groups[1].inner_hits.len(), 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct CollapseCollector {
+    field: String,
+    limit: usize,
+    num_inner_hits: usize,
+    inner_hits_order: InnerHitsOrder,
+}
+
+/// The order of the inner hits of the groups of a [`CollapseCollector`].
+#[derive(Clone, Debug)]
+pub enum InnerHitsOrder {
+    /// The best scoring documents first.
+    Score,
+    /// The documents sorted by the first value of a numerical, bool or date fast field.
+    ///
+    /// The documents without a value for the field come last.
+    FastField {
+        /// The name of the fast field.
+        field: String,
+        /// The order of the values.
+        order: Order,
+    },
+}
+
+impl InnerHitsOrder {
+    /// Orders the inner hits by the first value of the fast field `field`.
+    pub fn by_fast_field(field: impl ToString, order: Order) -> InnerHitsOrder {
+        InnerHitsOrder::FastField {
+            field: field.to_string(),
+            order,
+        }
+    }
+}
+
+/// The value of the field grouping the documents of a [`CollapsedGroup`].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CollapseKey {
+    /// The value of a `str` fast field.
+    Str(String),
+    /// The value of a `u64` fast field.
+    U64(u64),
+}
+
+/// A group of documents sharing the same value for the field of a [`CollapseCollector`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct CollapsedGroup {
+    /// The value shared by the documents of the group.
+    pub key: CollapseKey,
+    /// The best score of the documents of the group.
+    pub score: Score,
+    /// The inner hits of the group, with their score, in the [`InnerHitsOrder`] of the
+    /// collector.
+    pub inner_hits: Vec<(Score, DocAddress)>,
+}
+
+impl CollapseCollector {
+    /// Creates a collector returning the `limit` best groups of the documents sharing the same
+    /// value for the fast field `field`.
+    ///
+    /// By default, each group has one inner hit: its best scoring document.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is 0.
+    pub fn for_field(field: impl ToString, limit: usize) -> CollapseCollector {
+        assert!(limit >= 1, "Limit must be strictly greater than 0.");
+        CollapseCollector {
+            field: field.to_string(),
+            limit,
+            num_inner_hits: 1,
+            inner_hits_order: InnerHitsOrder::Score,
+        }
+    }
+
+    /// Returns up to `num_inner_hits` documents per group, in the given order.
+    pub fn with_inner_hits(
+        mut self,
+        num_inner_hits: usize,
+        inner_hits_order: InnerHitsOrder,
+    ) -> CollapseCollector {
+        self.num_inner_hits = num_inner_hits;
+        self.inner_hits_order = inner_hits_order;
+        self
+    }
+
+    fn key_column(&self, segment: &SegmentReader) -> crate::Result<KeyColumn> {
+        if let Some(str_column) = segment.fast_fields().str(&self.field)? {
+            return Ok(KeyColumn::Str(str_column));
+        }
+        if let Some(u64_column) = segment.fast_fields().column_opt::<u64>(&self.field)? {
+            return Ok(KeyColumn::U64(u64_column));
+        }
+        // The column is missing from the segments without any value for the field.
+        let schema = segment.schema();
+        let is_fast = schema
+            .get_field(&self.field)
+            .map(|field| schema.get_field_entry(field).is_fast())
+            .unwrap_or(false);
+        if !is_fast {
+            return Err(FastFieldNotAvailableError {
+                field_name: self.field.clone(),
+            }
+            .into());
+        }
+        Ok(KeyColumn::Missing)
+    }
+}
+
+impl Collector for CollapseCollector {
+    type Fruit = Vec<CollapsedGroup>;
+    type Child = CollapseSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        let key_column = self.key_column(segment)?;
+        let sort_column_opt = match &self.inner_hits_order {
+            InnerHitsOrder::Score => None,
+            InnerHitsOrder::FastField { field, order } => {
+                let column_opt = segment
+                    .fast_fields()
+                    .u64_lenient_for_type(Some(INNER_HITS_COLUMN_TYPES), field)?
+                    .map(|(column, _column_type)| column);
+                Some((column_opt, order.clone()))
+            }
+        };
+        Ok(CollapseSegmentCollector {
+            segment_ord: segment_local_id,
+            key_column,
+            sort_column_opt,
+            num_inner_hits: self.num_inner_hits,
+            groups: HashMap::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<crate::Result<Vec<SegmentCollapsedGroup>>>,
+    ) -> crate::Result<Vec<CollapsedGroup>> {
+        let mut groups: HashMap<CollapseKey, SegmentCollapsedGroup> = HashMap::new();
+        let segment_groups = segment_fruits
+            .into_iter()
+            .collect::<crate::Result<Vec<_>>>()?
+            .into_iter()
+            .flatten();
+        for segment_group in segment_groups {
+            match groups.get_mut(&segment_group.key) {
+                Some(group) => {
+                    group.score = group.score.max(segment_group.score);
+                    group.inner_hits.extend(segment_group.inner_hits);
+                }
+                None => {
+                    groups.insert(segment_group.key.clone(), segment_group);
+                }
+            }
+        }
+        let mut groups: Vec<SegmentCollapsedGroup> = groups.into_values().collect();
+        groups.sort_by(|left, right| {
+            right
+                .score
+                .partial_cmp(&left.score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| left.key.cmp(&right.key))
+        });
+        groups.truncate(self.limit);
+        Ok(groups
+            .into_iter()
+            .map(|mut group| {
+                // Best inner hits first, and ascending doc addresses on ties.
+                group.inner_hits.sort_by(|left, right| {
+                    right
+                        .0
+                        .partial_cmp(&left.0)
+                        .unwrap_or(Ordering::Equal)
+                        .then_with(|| left.1.cmp(&right.1))
+                });
+                group.inner_hits.truncate(self.num_inner_hits);
+                CollapsedGroup {
+                    key: group.key,
+                    score: group.score,
+                    inner_hits: group
+                        .inner_hits
+                        .into_iter()
+                        .map(|((_sort_key, score), doc_address)| (score, doc_address))
+                        .collect(),
+                }
+            })
+            .collect())
+    }
+}
+
+enum KeyColumn {
+    Str(StrColumn),
+    U64(Column<u64>),
+    Missing,
+}
+
+/// The inner hits are collected by a sort key, the higher the better, and then by score.
+type InnerHitFeature = (u64, Score);
+
+struct SegmentGroup {
+    score: Score,
+    inner_hits: TopSegmentCollector<InnerHitFeature>,
+}
+
+/// A group of the documents of a segment, as harvested by a [`CollapseSegmentCollector`].
+pub struct SegmentCollapsedGroup {
+    key: CollapseKey,
+    score: Score,
+    inner_hits: Vec<(InnerHitFeature, DocAddress)>,
+}
+
+pub struct CollapseSegmentCollector {
+    segment_ord: SegmentOrdinal,
+    key_column: KeyColumn,
+    sort_column_opt: Option<(Option<Column<u64>>, Order)>,
+    num_inner_hits: usize,
+    // term ord or value -> group
+    groups: HashMap<u64, SegmentGroup>,
+}
+
+impl CollapseSegmentCollector {
+    fn sort_key(&self, doc: DocId, score: Score) -> u64 {
+        match &self.sort_column_opt {
+            None => common::f64_to_u64(score as f64),
+            Some((column_opt, order)) => {
+                let value_opt = column_opt.as_ref().and_then(|column| column.first(doc));
+                match (value_opt, order) {
+                    (Some(value), Order::Desc) => value,
+                    (Some(value), Order::Asc) => u64::MAX - value,
+                    // The documents without a value come last.
+                    (None, _) => 0,
+                }
+            }
+        }
+    }
+}
+
+impl SegmentCollector for CollapseSegmentCollector {
+    type Fruit = crate::Result<Vec<SegmentCollapsedGroup>>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let key_opt = match &self.key_column {
+            KeyColumn::Str(str_column) => str_column.ords().first(doc),
+            KeyColumn::U64(column) => column.first(doc),
+            KeyColumn::Missing => None,
+        };
+        let Some(key) = key_opt else {
+            return;
+        };
+        let sort_key = self.sort_key(doc, score);
+        let segment_ord = self.segment_ord;
+        let num_inner_hits = self.num_inner_hits;
+        let group = self.groups.entry(key).or_insert_with(|| SegmentGroup {
+            score,
+            inner_hits: TopSegmentCollector::new(segment_ord, num_inner_hits),
+        });
+        group.score = group.score.max(score);
+        group.inner_hits.collect(doc, (sort_key, score));
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        let mut term = String::new();
+        let mut segment_groups = Vec::with_capacity(self.groups.len());
+        for (key, group) in self.groups {
+            let key = match &self.key_column {
+                KeyColumn::Str(str_column) => {
+                    term.clear();
+                    if !str_column.ord_to_str(key, &mut term)? {
+                        continue;
+                    }
+                    CollapseKey::Str(term.clone())
+                }
+                KeyColumn::U64(_) | KeyColumn::Missing => CollapseKey::U64(key),
+            };
+            segment_groups.push(SegmentCollapsedGroup {
+                key,
+                score: group.score,
+                inner_hits: group.inner_hits.harvest(),
+            });
+        }
+        Ok(segment_groups)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CollapseCollector, CollapseKey, InnerHitsOrder};
+    use crate::query::{AllQuery, QueryParser};
+    use crate::schema::{Schema, FAST, STORED, STRING, TEXT};
+    use crate::{doc, DocAddress, Index, Order};
+
+    #[test]
+    fn test_collapse_collector_inner_hits() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let product = schema_builder.add_text_field("product", STRING | FAST);
+        let title = schema_builder.add_text_field("title", TEXT);
+        let price = schema_builder.add_f64_field("price", FAST | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(product => "shoe", title => "red", price => 30.0f64))?;
+        index_writer.add_document(doc!(product => "shoe", title => "red red", price => 20.0f64))?;
+        index_writer.add_document(doc!(product => "hat", title => "blue", price => 5.0f64))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(product => "shoe", title => "red", price => 10.0f64))?;
+        index_writer.add_document(doc!(product => "hat", title => "red", price => 15.0f64))?;
+        // Not collected, without any product.
+        index_writer.add_document(doc!(title => "red"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        // The order of the segments is not deterministic.
+        let price_of = |doc_address: DocAddress| -> crate::Result<f64> {
+            Ok(searcher
+                .doc(doc_address)?
+                .get_first(price)
+                .unwrap()
+                .as_f64()
+                .unwrap())
+        };
+
+        let query = QueryParser::for_index(&index, vec![title]).parse_query("red")?;
+        let by_score = CollapseCollector::for_field("product", 10);
+        let groups = searcher.search(&query, &by_score)?;
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].key, CollapseKey::Str("shoe".to_string()));
+        assert_eq!(groups[0].inner_hits.len(), 1);
+        assert_eq!(price_of(groups[0].inner_hits[0].1)?, 20.0);
+        assert_eq!(groups[0].score, groups[0].inner_hits[0].0);
+        assert_eq!(groups[1].key, CollapseKey::Str("hat".to_string()));
+
+        let cheapest = CollapseCollector::for_field("product", 1)
+            .with_inner_hits(2, InnerHitsOrder::by_fast_field("price", Order::Asc));
+        let groups = searcher.search(&query, &cheapest)?;
+        assert_eq!(groups.len(), 1);
+        let inner_prices: Vec<f64> = groups[0]
+            .inner_hits
+            .iter()
+            .map(|(_score, doc_address)| price_of(*doc_address))
+            .collect::<crate::Result<_>>()?;
+        assert_eq!(inner_prices, vec![10.0, 20.0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_collapse_collector_u64_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let thread = schema_builder.add_u64_field("thread", FAST);
+        let date = schema_builder.add_date_field("date", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for (thread_id, timestamp) in [(1u64, 10), (1, 30), (2, 20), (1, 20)] {
+            index_writer.add_document(doc!(
+                thread => thread_id,
+                date => crate::DateTime::from_timestamp_secs(timestamp),
+            ))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let newest = CollapseCollector::for_field("thread", 10)
+            .with_inner_hits(2, InnerHitsOrder::by_fast_field("date", Order::Desc));
+        let mut groups = searcher.search(&AllQuery, &newest)?;
+        groups.sort_by(|left, right| left.key.cmp(&right.key));
+        assert_eq!(groups[0].key, CollapseKey::U64(1));
+        let inner_docs: Vec<DocAddress> = groups[0]
+            .inner_hits
+            .iter()
+            .map(|(_score, doc_address)| *doc_address)
+            .collect();
+        assert_eq!(
+            inner_docs,
+            vec![DocAddress::new(0, 1), DocAddress::new(0, 3)]
+        );
+        assert_eq!(groups[1].key, CollapseKey::U64(2));
+        assert_eq!(groups[1].inner_hits.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_collapse_collector_not_fast() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests().unwrap();
+        index_writer.add_document(doc!(title => "hello")).unwrap();
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let collector = CollapseCollector::for_field("title", 10);
+        assert!(searcher.search(&AllQuery, &collector).is_err());
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::BTreeMap;
+
+use super::facet_collector::FacetSegmentCollector;
+use crate::collector::{Collector, FacetCollector, FacetCounts, SegmentCollector};
+use crate::query::{EnableScoring, Query, Scorer, TermSetQuery, Weight};
+use crate::schema::Facet;
+use crate::{DocId, DocSet, Score, Searcher, SegmentOrdinal, SegmentReader, Term};
+
+/// Runs a query restricted by facet drill-downs, and computes, for each drilled down
+/// dimension, the facet counts the query would have if the drill-down of this dimension
+/// were not applied.
+///
+/// A dimension is a facet whose children are displayed to the user, e.g. `/color`, and the
+/// drill-down of a dimension matches the documents having any of the facets selected in this
+/// dimension, e.g. `/color/red` or `/color/blue`. The hits match the query and the drill-downs
+/// of all of the dimensions, while the counts of a dimension show how many documents the user
+/// would get by changing the selection of this dimension.
+///
+/// All of the counts are computed in one pass over the documents matching the query.
+///
+/// ```rust
+/// use tantivy::collector::{Count, DrillSideways};
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Facet, FacetOptions, Schema};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let facet = schema_builder.add_facet_field("facet", FacetOptions::default());
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+/// index_writer.add_document(doc!(facet => Facet::from("/color/red")))?;
+/// index_writer.add_document(doc!(facet => Facet::from("/color/blue")))?;
+/// index_writer.commit()?;
+/// let searcher = index.reader()?.searcher();
+///
+/// let mut drill_sideways = DrillSideways::for_field("facet");
+/// drill_sideways.add_drill_down("/color", "/color/red");
+/// let result = drill_sideways.search(&searcher, &AllQuery, &Count)?;
+/// assert_eq!(result.hits, 1);
+/// let color_counts = result.facet_counts("/color").unwrap();
+/// assert_eq!(color_counts.get("/color").count(), 2);
+/// # Ok(())
+/// # }
+/// ```
+pub struct DrillSideways {
+    field_name: String,
+    // dimension -> selected facets
+    dimensions: BTreeMap<Facet, Vec<Facet>>,
+}
+
+impl DrillSideways {
+    /// Creates a `DrillSideways` on the facets of a specific facet `Field`.
+    ///
+    /// This function does not check whether the field
+    /// is of the proper type.
+    pub fn for_field(field_name: impl ToString) -> DrillSideways {
+        DrillSideways {
+            field_name: field_name.to_string(),
+            dimensions: BTreeMap::new(),
+        }
+    }
+
+    /// Selects a facet in a dimension.
+    ///
+    /// The documents having any of the facets selected in a dimension, or one of their
+    /// descendants, match the drill-down of the dimension.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the facet is not a descendant of the dimension.
+    pub fn add_drill_down<D, T>(&mut self, dimension_from: D, facet_from: T)
+    where
+        Facet: From<D>,
+        Facet: From<T>,
+    {
+        let dimension = Facet::from(dimension_from);
+        let facet = Facet::from(facet_from);
+        assert!(
+            dimension.is_prefix_of(&facet),
+            "The facet {facet} is not a descendant of the dimension {dimension}."
+        );
+        self.dimensions.entry(dimension).or_default().push(facet);
+    }
+
+    /// Runs the query with the drill-downs applied, collecting its hits with the collector,
+    /// and computes the drill sideways facet counts of each dimension.
+    pub fn search<C: Collector>(
+        &self,
+        searcher: &Searcher,
+        query: &dyn Query,
+        collector: &C,
+    ) -> crate::Result<DrillSidewaysResult<C::Fruit>> {
+        let field = searcher.schema().get_field(&self.field_name)?;
+        let mut dimension_weights = Vec::with_capacity(self.dimensions.len());
+        let mut facet_collectors = Vec::with_capacity(self.dimensions.len());
+        for (dimension, facets) in &self.dimensions {
+            let terms = facets.iter().map(|facet| Term::from_facet(field, facet));
+            let drill_down_query = TermSetQuery::new(terms);
+            dimension_weights
+                .push(drill_down_query.weight(EnableScoring::disabled_from_searcher(searcher))?);
+            let mut facet_collector = FacetCollector::for_field(&self.field_name);
+            facet_collector.add_facet(dimension.clone());
+            facet_collectors.push(facet_collector);
+        }
+        let drill_sideways_collector = DrillSidewaysCollector {
+            collector,
+            dimension_weights,
+            facet_collectors,
+        };
+        let (hits, facet_counts) = searcher.search(query, &drill_sideways_collector)?;
+        Ok(DrillSidewaysResult {
+            hits,
+            facet_counts: self.dimensions.keys().cloned().zip(facet_counts).collect(),
+        })
+    }
+}
+
+/// The result of a [`DrillSideways`] search.
+pub struct DrillSidewaysResult<TFruit> {
+    /// The fruit of the collector, on the documents matching the query and all of the
+    /// drill-downs.
+    pub hits: TFruit,
+    // dimension -> counts without the drill-down of the dimension
+    facet_counts: BTreeMap<Facet, FacetCounts>,
+}
+
+impl<TFruit> DrillSidewaysResult<TFruit> {
+    /// Returns the counts of the children of a dimension, on the documents matching the query
+    /// and the drill-downs of all of the other dimensions.
+    ///
+    /// Returns `None` if no facet was selected in the dimension.
+    pub fn facet_counts<T>(&self, dimension_from: T) -> Option<&FacetCounts>
+    where Facet: From<T> {
+        self.facet_counts.get(&Facet::from(dimension_from))
+    }
+}
+
+struct DrillSidewaysCollector<'a, TCollector> {
+    collector: &'a TCollector,
+    dimension_weights: Vec<Box<dyn Weight>>,
+    facet_collectors: Vec<FacetCollector>,
+}
+
+impl<'a, TCollector: Collector> Collector for DrillSidewaysCollector<'a, TCollector> {
+    type Fruit = (TCollector::Fruit, Vec<FacetCounts>);
+
+    type Child = DrillSidewaysSegmentCollector<TCollector::Child>;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        let segment_collector = self.collector.for_segment(segment_local_id, segment)?;
+        let dimension_scorers = self
+            .dimension_weights
+            .iter()
+            .map(|weight| weight.scorer(segment, 1.0))
+            .collect::<crate::Result<_>>()?;
+        let facet_segment_collectors = self
+            .facet_collectors
+            .iter()
+            .map(|facet_collector| facet_collector.for_segment(segment_local_id, segment))
+            .collect::<crate::Result<_>>()?;
+        Ok(DrillSidewaysSegmentCollector {
+            segment_collector,
+            dimension_scorers,
+            facet_segment_collectors,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.collector.requires_scoring()
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<<Self::Child as SegmentCollector>::Fruit>,
+    ) -> crate::Result<Self::Fruit> {
+        let mut hits_fruits = Vec::with_capacity(segment_fruits.len());
+        let mut facet_counts_fruits: Vec<Vec<FacetCounts>> = self
+            .facet_collectors
+            .iter()
+            .map(|_| Vec::with_capacity(segment_fruits.len()))
+            .collect();
+        for (hits_fruit, segment_facet_counts) in segment_fruits {
+            hits_fruits.push(hits_fruit);
+            for (dimension_ord, facet_counts) in segment_facet_counts.into_iter().enumerate() {
+                facet_counts_fruits[dimension_ord].push(facet_counts);
+            }
+        }
+        let hits = self.collector.merge_fruits(hits_fruits)?;
+        let facet_counts = self
+            .facet_collectors
+            .iter()
+            .zip(facet_counts_fruits)
+            .map(|(facet_collector, fruits)| facet_collector.merge_fruits(fruits))
+            .collect::<crate::Result<_>>()?;
+        Ok((hits, facet_counts))
+    }
+}
+
+struct DrillSidewaysSegmentCollector<TSegmentCollector> {
+    segment_collector: TSegmentCollector,
+    dimension_scorers: Vec<Box<dyn Scorer>>,
+    facet_segment_collectors: Vec<FacetSegmentCollector>,
+}
+
+impl<TSegmentCollector: SegmentCollector> SegmentCollector
+    for DrillSidewaysSegmentCollector<TSegmentCollector>
+{
+    type Fruit = (TSegmentCollector::Fruit, Vec<FacetCounts>);
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        // The documents are collected in increasing order, so the drill-down scorers only
+        // have to move forward.
+        let mut missed_dimension_ord: Option<usize> = None;
+        for (dimension_ord, scorer) in self.dimension_scorers.iter_mut().enumerate() {
+            let mut scorer_doc = scorer.doc();
+            if scorer_doc < doc {
+                scorer_doc = scorer.seek(doc);
+            }
+            if scorer_doc != doc {
+                if missed_dimension_ord.is_some() {
+                    // The document misses two drill-downs, and is not counted anywhere.
+                    return;
+                }
+                missed_dimension_ord = Some(dimension_ord);
+            }
+        }
+        match missed_dimension_ord {
+            Some(dimension_ord) => self.facet_segment_collectors[dimension_ord].collect(doc, score),
+            None => {
+                self.segment_collector.collect(doc, score);
+                for facet_segment_collector in &mut self.facet_segment_collectors {
+                    facet_segment_collector.collect(doc, score);
+                }
+            }
+        }
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        let facet_counts = self
+            .facet_segment_collectors
+            .into_iter()
+            .map(SegmentCollector::harvest)
+            .collect();
+        (self.segment_collector.harvest(), facet_counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DrillSideways;
+    use crate::collector::{Count, FacetCounts};
+    use crate::query::{QueryParser, TermQuery};
+    use crate::schema::{Facet, FacetOptions, IndexRecordOption, Schema, STRING};
+    use crate::{doc, Index, Term};
+
+    fn counts<'a>(facet_counts: &'a FacetCounts, dimension: &str) -> Vec<(&'a Facet, u64)> {
+        facet_counts.get(dimension).collect()
+    }
+
+    #[test]
+    fn test_drill_sideways() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let kind = schema_builder.add_text_field("kind", STRING);
+        let facet = schema_builder.add_facet_field("facet", FacetOptions::default());
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for (color, size) in [
+            ("red", "small"),
+            ("red", "large"),
+            ("blue", "small"),
+            ("green", "large"),
+        ] {
+            index_writer.add_document(doc!(
+                kind => "shirt",
+                facet => Facet::from(&format!("/color/{color}")),
+                facet => Facet::from(&format!("/size/{size}")),
+            ))?;
+        }
+        index_writer.commit()?;
+        index_writer.add_document(doc!(
+            kind => "shirt",
+            facet => Facet::from("/color/red"),
+            facet => Facet::from("/size/small"),
+        ))?;
+        index_writer.add_document(doc!(kind => "hat", facet => Facet::from("/color/red")))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(kind, "shirt"),
+            IndexRecordOption::Basic,
+        );
+
+        let mut drill_sideways = DrillSideways::for_field("facet");
+        drill_sideways.add_drill_down("/color", "/color/red");
+        drill_sideways.add_drill_down("/size", "/size/small");
+        let result = drill_sideways.search(&searcher, &query, &Count)?;
+        // The red and small shirts.
+        assert_eq!(result.hits, 2);
+        // The small shirts by color.
+        assert_eq!(
+            counts(result.facet_counts("/color").unwrap(), "/color"),
+            vec![
+                (&Facet::from("/color/blue"), 1),
+                (&Facet::from("/color/red"), 2)
+            ]
+        );
+        // The red shirts by size.
+        assert_eq!(
+            counts(result.facet_counts("/size").unwrap(), "/size"),
+            vec![
+                (&Facet::from("/size/large"), 1),
+                (&Facet::from("/size/small"), 2)
+            ]
+        );
+        assert!(result.facet_counts("/material").is_none());
+
+        // Selecting several facets of a dimension matches either of them.
+        let mut drill_sideways = DrillSideways::for_field("facet");
+        drill_sideways.add_drill_down("/color", "/color/red");
+        drill_sideways.add_drill_down("/color", "/color/green");
+        let query = QueryParser::for_index(&index, vec![kind]).parse_query("shirt")?;
+        let result = drill_sideways.search(&searcher, query.as_ref(), &Count)?;
+        assert_eq!(result.hits, 4);
+        assert_eq!(
+            counts(result.facet_counts("/color").unwrap(), "/color").len(),
+            3
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_drill_sideways_requires_descendant() {
+        let mut drill_sideways = DrillSideways::for_field("facet");
+        drill_sideways.add_drill_down("/color", "/size/small");
+    }
+}
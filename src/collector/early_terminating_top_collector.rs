@@ -0,0 +1,306 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use columnar::{Cardinality, ColumnType, ColumnValues, MonotonicallyMappableToU64};
+
+use crate::collector::top_collector::{TopCollector, TopSegmentCollector};
+use crate::collector::top_score_collector::check_fast_field_type;
+use crate::collector::{Collector, SegmentCollector};
+use crate::fastfield::{FastFieldNotAvailableError, FastValue};
+use crate::query::Weight;
+use crate::{DocAddress, DocId, DocSet, Score, SegmentOrdinal, SegmentReader, TERMINATED};
+
+/// Maps the `u64` representation of a value to the key the documents are sorted by.
+type SortKeyFn = fn(u64) -> f32;
+
+/// The number of documents matching a query, as counted by a collector that can stop collecting
+/// a segment early.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HitCount {
+    /// All of the matching documents were counted.
+    Exact(usize),
+    /// The collection stopped early, and at least this number of documents match.
+    LowerBound(usize),
+}
+
+impl HitCount {
+    /// Returns the number of documents counted.
+    pub fn count(&self) -> usize {
+        match *self {
+            HitCount::Exact(count) | HitCount::LowerBound(count) => count,
+        }
+    }
+
+    /// Returns true if all of the matching documents were counted.
+    pub fn is_exact(&self) -> bool {
+        matches!(self, HitCount::Exact(_))
+    }
+}
+
+/// Collects the top K documents by a fast field, and stops collecting the segments sorted by
+/// this field as soon as the remaining documents cannot make it into the top K.
+///
+/// See [`TopDocs::order_by_fast_field_with_hit_count`](crate::collector::TopDocs).
+pub(crate) struct EarlyTerminatingTopCollector<TFastValue> {
+    field: String,
+    collector: TopCollector<u64>,
+    fast_value: PhantomData<TFastValue>,
+}
+
+impl<TFastValue: FastValue> EarlyTerminatingTopCollector<TFastValue> {
+    pub fn new(field: String, collector: TopCollector<u64>) -> Self {
+        EarlyTerminatingTopCollector {
+            field,
+            collector,
+            fast_value: PhantomData,
+        }
+    }
+
+    /// Returns the function computing the sort key of the values of the sort column, if the
+    /// documents of the segment are sorted by it in the descending order.
+    ///
+    /// With a single value per document, the documents are sorted by the `f32` conversion of
+    /// their value, both when the segment is written and when segments are merged. The sort
+    /// keys are therefore non-increasing along the doc ids.
+    fn sort_key_fn(&self, reader: &SegmentReader) -> crate::Result<Option<SortKeyFn>> {
+        let Some(sort_by_field) = reader.sort_by_field() else {
+            return Ok(None);
+        };
+        if sort_by_field.field != self.field || sort_by_field.order.is_asc() {
+            return Ok(None);
+        }
+        let Some((column, column_type)) = reader.fast_fields().u64_lenient(&self.field)? else {
+            return Ok(None);
+        };
+        if column.get_cardinality() != Cardinality::Full {
+            return Ok(None);
+        }
+        let sort_key_fn: SortKeyFn = match column_type {
+            ColumnType::U64 => |val| val as f32,
+            ColumnType::I64 => |val| i64::from_u64(val) as f32,
+            ColumnType::F64 => |val| f64::from_u64(val) as f32,
+            _ => return Ok(None),
+        };
+        Ok(Some(sort_key_fn))
+    }
+}
+
+impl<TFastValue: FastValue> Collector for EarlyTerminatingTopCollector<TFastValue> {
+    type Fruit = (Vec<(TFastValue, DocAddress)>, HitCount);
+
+    type Child = EarlyTerminatingTopSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        check_fast_field_type::<TFastValue>(&self.field, segment)?;
+        let (sort_column, _sort_column_type) = segment
+            .fast_fields()
+            .u64_lenient(&self.field)?
+            .ok_or_else(|| FastFieldNotAvailableError {
+                field_name: self.field.clone(),
+            })?;
+        Ok(EarlyTerminatingTopSegmentCollector {
+            top_collector: self.collector.for_segment(segment_local_id, segment),
+            sort_column: sort_column.first_or_default_col(0u64),
+            num_hits: 0,
+            terminated_early: false,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<(Vec<(u64, DocAddress)>, HitCount)>,
+    ) -> crate::Result<Self::Fruit> {
+        let mut num_hits = 0;
+        let mut is_exact = true;
+        let mut top_docs = Vec::with_capacity(segment_fruits.len());
+        for (segment_top_docs, hit_count) in segment_fruits {
+            num_hits += hit_count.count();
+            is_exact &= hit_count.is_exact();
+            top_docs.push(segment_top_docs);
+        }
+        let top_docs = self
+            .collector
+            .merge_fruits(top_docs)?
+            .into_iter()
+            .map(|(val, doc_address)| (TFastValue::from_u64(val), doc_address))
+            .collect();
+        let hit_count = if is_exact {
+            HitCount::Exact(num_hits)
+        } else {
+            HitCount::LowerBound(num_hits)
+        };
+        Ok((top_docs, hit_count))
+    }
+
+    fn collect_segment(
+        &self,
+        weight: &dyn Weight,
+        segment_ord: u32,
+        reader: &SegmentReader,
+    ) -> crate::Result<<Self::Child as SegmentCollector>::Fruit> {
+        let mut segment_collector = self.for_segment(segment_ord, reader)?;
+        let sort_key_fn_opt = self.sort_key_fn(reader)?;
+        let alive_bitset_opt = reader.alive_bitset();
+        let mut scorer = weight.scorer(reader, 1.0)?;
+        let mut doc = scorer.doc();
+        while doc != TERMINATED {
+            if alive_bitset_opt.map_or(true, |alive_bitset| alive_bitset.is_alive(doc)) {
+                if let Some(sort_key_fn) = sort_key_fn_opt {
+                    if segment_collector.cannot_compete(doc, sort_key_fn) {
+                        segment_collector.terminated_early = true;
+                        break;
+                    }
+                }
+                segment_collector.collect(doc, 0.0);
+            }
+            doc = scorer.advance();
+        }
+        Ok(segment_collector.harvest())
+    }
+}
+
+/// Segment collector associated with the [`EarlyTerminatingTopCollector`].
+pub(crate) struct EarlyTerminatingTopSegmentCollector {
+    top_collector: TopSegmentCollector<u64>,
+    sort_column: Arc<dyn ColumnValues>,
+    num_hits: usize,
+    terminated_early: bool,
+}
+
+impl EarlyTerminatingTopSegmentCollector {
+    /// Returns true if the top K documents are collected, and neither `doc` nor the documents
+    /// sorted after it can replace any of them.
+    fn cannot_compete(&self, doc: DocId, sort_key_fn: SortKeyFn) -> bool {
+        let Some(threshold) = self.top_collector.threshold() else {
+            return false;
+        };
+        sort_key_fn(self.sort_column.get_val(doc)) < sort_key_fn(threshold)
+    }
+}
+
+impl SegmentCollector for EarlyTerminatingTopSegmentCollector {
+    type Fruit = (Vec<(u64, DocAddress)>, HitCount);
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        self.num_hits += 1;
+        let val = self.sort_column.get_val(doc);
+        self.top_collector.collect(doc, val);
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        let hit_count = if self.terminated_early {
+            HitCount::LowerBound(self.num_hits)
+        } else {
+            HitCount::Exact(self.num_hits)
+        };
+        (self.top_collector.harvest(), hit_count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HitCount;
+    use crate::collector::TopDocs;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, STRING};
+    use crate::{doc, DocAddress, Index, IndexSettings, IndexSortByField, Order, Term};
+
+    fn create_index(sort_order: Option<Order>) -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_text_field("category", STRING);
+        let price = schema_builder.add_i64_field("price", FAST);
+        let settings = IndexSettings {
+            sort_by_field: sort_order.map(|order| IndexSortByField {
+                field: "price".to_string(),
+                order,
+            }),
+            ..Default::default()
+        };
+        let index = Index::builder()
+            .schema(schema_builder.build())
+            .settings(settings)
+            .create_in_ram()?;
+        let mut index_writer = index.writer_for_tests()?;
+        for i in 0..50i64 {
+            let category_val = if i % 2 == 0 { "even" } else { "odd" };
+            index_writer.add_document(doc!(category => category_val, price => i - 25))?;
+        }
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    #[test]
+    fn test_early_termination_on_sorted_index() -> crate::Result<()> {
+        let index = create_index(Some(Order::Desc))?;
+        let searcher = index.reader()?.searcher();
+        let collector = TopDocs::with_limit(3).order_by_fast_field_with_hit_count::<i64>("price");
+        let (top_docs, hit_count) = searcher.search(&AllQuery, &collector)?;
+        assert_eq!(
+            top_docs,
+            vec![
+                (24, DocAddress::new(0, 0)),
+                (23, DocAddress::new(0, 1)),
+                (22, DocAddress::new(0, 2))
+            ]
+        );
+        assert_eq!(hit_count, HitCount::LowerBound(3));
+
+        let category = index.schema().get_field("category")?;
+        let query = TermQuery::new(
+            Term::from_field_text(category, "odd"),
+            IndexRecordOption::Basic,
+        );
+        let collector = TopDocs::with_limit(2)
+            .and_offset(1)
+            .order_by_fast_field_with_hit_count::<i64>("price");
+        let (top_docs, hit_count) = searcher.search(&query, &collector)?;
+        let prices: Vec<i64> = top_docs.into_iter().map(|(price, _)| price).collect();
+        assert_eq!(prices, vec![22, 20]);
+        assert_eq!(hit_count, HitCount::LowerBound(3));
+
+        // The whole segment is collected when the top docs are the last ones.
+        let all_collector =
+            TopDocs::with_limit(50).order_by_fast_field_with_hit_count::<i64>("price");
+        let (top_docs, hit_count) = searcher.search(&AllQuery, &all_collector)?;
+        assert_eq!(top_docs.len(), 50);
+        assert_eq!(hit_count, HitCount::Exact(50));
+
+        // Merged segments are sorted as well.
+        let price = index.schema().get_field("price")?;
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(category => "odd", price => 100i64))?;
+        index_writer.commit()?;
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let (top_docs, hit_count) = searcher.search(&query, &collector)?;
+        let prices: Vec<i64> = top_docs.into_iter().map(|(price, _)| price).collect();
+        assert_eq!(prices, vec![24, 22]);
+        assert_eq!(hit_count, HitCount::LowerBound(3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_early_termination_without_matching_sort() -> crate::Result<()> {
+        for sort_order in [None, Some(Order::Asc)] {
+            let index = create_index(sort_order)?;
+            let searcher = index.reader()?.searcher();
+            let collector =
+                TopDocs::with_limit(3).order_by_fast_field_with_hit_count::<i64>("price");
+            let (top_docs, hit_count) = searcher.search(&AllQuery, &collector)?;
+            let prices: Vec<i64> = top_docs.into_iter().map(|(price, _)| price).collect();
+            assert_eq!(prices, vec![24, 23, 22]);
+            assert_eq!(hit_count, HitCount::Exact(50));
+        }
+        Ok(())
+    }
+}
@@ -168,6 +168,8 @@ fn facet_depth(facet_bytes: &[u8]) -> usize {
 pub struct FacetCollector {
     field_name: String,
     facets: BTreeSet<Facet>,
+    // facet -> number of children kept in the `FacetCounts`
+    top_ks: BTreeMap<Facet, usize>,
 }
 
 pub struct FacetSegmentCollector {
@@ -190,6 +192,7 @@ impl FacetCollector {
         FacetCollector {
             field_name: field_name.to_string(),
             facets: BTreeSet::default(),
+            top_ks: BTreeMap::default(),
         }
     }
 
@@ -217,6 +220,21 @@ impl FacetCollector {
         }
         self.facets.insert(facet);
     }
+
+    /// Adds a facet for which only the `k` children with the highest counts are kept.
+    ///
+    /// This works like [`FacetCollector::add_facet`], but the resulting [`FacetCounts`] only
+    /// hold the top `k` children of the facet, ties being broken by facet order. The sum of the
+    /// counts of the other children is available with [`FacetCounts::other_count`].
+    ///
+    /// This keeps the results small for high cardinality facets, when only the most frequent
+    /// values are displayed.
+    pub fn add_facet_with_top_k<T>(&mut self, facet_from: T, k: usize)
+    where Facet: From<T> {
+        let facet = Facet::from(facet_from);
+        self.add_facet::<Facet>(facet.clone());
+        self.top_ks.insert(facet, k);
+    }
 }
 
 fn compress_mapping(mapping: &[(u64, usize)]) -> (Vec<usize>, Vec<(u64, usize)>) {
@@ -277,7 +295,14 @@ impl Collector for FacetCollector {
                 *(facet_counts.entry(facet).or_insert(0)) += count;
             }
         }
-        Ok(FacetCounts { facet_counts })
+        let mut facet_counts = FacetCounts {
+            facet_counts,
+            other_counts: BTreeMap::new(),
+        };
+        for (facet, &k) in &self.top_ks {
+            facet_counts.retain_top_k(facet, k);
+        }
+        Ok(facet_counts)
     }
 }
 
@@ -386,7 +411,10 @@ impl SegmentCollector for FacetSegmentCollector {
                 }
             }
         }
-        FacetCounts { facet_counts }
+        FacetCounts {
+            facet_counts,
+            other_counts: BTreeMap::new(),
+        }
     }
 }
 
@@ -394,6 +422,8 @@ impl SegmentCollector for FacetSegmentCollector {
 /// the facet counts for all the segments.
 pub struct FacetCounts {
     facet_counts: BTreeMap<Facet, u64>,
+    // facet -> sum of the counts of the children dropped by `retain_top_k`
+    other_counts: BTreeMap<Facet, u64>,
 }
 
 pub struct FacetChildIterator<'a> {
@@ -428,6 +458,38 @@ impl FacetCounts {
         FacetChildIterator { underlying }
     }
 
+    /// Returns the sum of the counts of the children of the facet which were not kept, because
+    /// the facet was added with [`FacetCollector::add_facet_with_top_k`].
+    ///
+    /// A document having several of these children is counted once for each of them.
+    pub fn other_count<T>(&self, facet_from: T) -> u64
+    where Facet: From<T> {
+        let facet = Facet::from(facet_from);
+        self.other_counts.get(&facet).copied().unwrap_or(0)
+    }
+
+    /// Only keeps the `k` children of the facet with the highest counts.
+    fn retain_top_k(&mut self, facet: &Facet, k: usize) {
+        let mut children: Vec<(Facet, u64)> = self
+            .get(facet.clone())
+            .map(|(child, count)| (child.clone(), count))
+            .collect();
+        if children.len() <= k {
+            return;
+        }
+        children.sort_by(|(left_facet, left_count), (right_facet, right_count)| {
+            right_count
+                .cmp(left_count)
+                .then_with(|| left_facet.cmp(right_facet))
+        });
+        let mut other_count = 0u64;
+        for (child, count) in children.drain(k..) {
+            self.facet_counts.remove(&child);
+            other_count += count;
+        }
+        self.other_counts.insert(facet.clone(), other_count);
+    }
+
     /// Returns a vector of top `k` facets with their counts, sorted highest-to-lowest by counts.
     /// See the documentation for [`FacetCollector`] for a usage example.
     pub fn top_k<T>(&self, facet: T, k: usize) -> Vec<(&Facet, u64)>
@@ -755,6 +817,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_facet_collector_add_facet_with_top_k() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let facet_field = schema_builder.add_facet_field("facet", FacetOptions::default());
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for (color, count) in [("red", 3), ("blue", 5), ("green", 1), ("black", 3)] {
+            for _ in 0..count {
+                index_writer.add_document(doc!(
+                    facet_field => Facet::from(&format!("/color/{color}")),
+                    facet_field => Facet::from("/size/small"),
+                ))?;
+            }
+            // The counts are merged over several segments.
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let mut facet_collector = FacetCollector::for_field("facet");
+        facet_collector.add_facet_with_top_k("/color", 2);
+        facet_collector.add_facet_with_top_k("/size", 2);
+        let counts = searcher.search(&AllQuery, &facet_collector)?;
+        let colors: Vec<(&Facet, u64)> = counts.get("/color").collect();
+        assert_eq!(
+            colors,
+            vec![
+                (&Facet::from("/color/black"), 3),
+                (&Facet::from("/color/blue"), 5),
+            ]
+        );
+        assert_eq!(counts.other_count("/color"), 4);
+        assert_eq!(counts.get("/size").count(), 1);
+        assert_eq!(counts.other_count("/size"), 0);
+        Ok(())
+    }
+
     #[test]
     fn test_facet_collector_topk_tie_break() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
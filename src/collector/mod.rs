@@ -81,16 +81,39 @@
 //!
 //! See the `custom_collector` example.
 
+use std::ops::Range;
+
 use downcast_rs::impl_downcast;
 
-use crate::{DocId, Score, SegmentOrdinal, SegmentReader};
+use crate::docset::BUFFER_LEN;
+use crate::{DocId, DocSet, Executor, Score, SegmentOrdinal, SegmentReader};
+
+mod arrow_collector;
+pub use self::arrow_collector::{
+    ArrowCollector, ArrowColumn, ArrowRecordBatch, ArrowSegmentCollector, ArrowValues,
+    DOC_ID_COLUMN, SCORE_COLUMN, SEGMENT_ORD_COLUMN,
+};
+
+mod cacheable_collector;
+pub use self::cacheable_collector::CacheableCollector;
+
+mod collapse_collector;
+pub use self::collapse_collector::{
+    CollapseCollector, CollapseKey, CollapsedGroup, InnerHitsOrder,
+};
 
 mod count_collector;
 pub use self::count_collector::Count;
 
+mod early_terminating_top_collector;
+pub use self::early_terminating_top_collector::HitCount;
+
 mod histogram_collector;
 pub use histogram_collector::HistogramCollector;
 
+mod range_facet_collector;
+pub use self::range_facet_collector::RangeFacetCollector;
+
 mod multi_collector;
 pub use self::multi_collector::{FruitHandle, MultiCollector, MultiFruit};
 
@@ -106,8 +129,15 @@ mod tweak_score_top_collector;
 pub use self::tweak_score_top_collector::{ScoreSegmentTweaker, ScoreTweaker};
 mod facet_collector;
 pub use self::facet_collector::{FacetCollector, FacetCounts};
+mod string_facet_collector;
+pub use self::string_facet_collector::{
+    StringFacetCollector, StringFacetCounts, StringFacetCountsIter,
+};
 use crate::query::Weight;
 
+mod drill_sideways;
+pub use self::drill_sideways::{DrillSideways, DrillSidewaysResult};
+
 mod docset_collector;
 pub use self::docset_collector::DocSetCollector;
 
@@ -202,6 +232,64 @@ pub trait Collector: Sync + Send {
 
         Ok(segment_collector.harvest())
     }
+
+    /// Creates a segment collector and collects the documents of the segment whose doc ids are
+    /// within `doc_range`, without computing their scores.
+    ///
+    /// The fruits of the ranges of a segment are merged by
+    /// [`merge_fruits`](Collector::merge_fruits) as if they were the fruits of different
+    /// segments, with the same segment ordinal.
+    fn collect_segment_range(
+        &self,
+        weight: &dyn Weight,
+        segment_ord: u32,
+        reader: &SegmentReader,
+        doc_range: Range<DocId>,
+    ) -> crate::Result<<Self::Child as SegmentCollector>::Fruit> {
+        let mut segment_collector = self.for_segment(segment_ord, reader)?;
+        let mut scorer = weight.scorer(reader, 1.0)?;
+        let mut doc = scorer.doc();
+        if doc < doc_range.start {
+            doc = scorer.seek(doc_range.start);
+        }
+        let alive_bitset = reader.alive_bitset();
+        let mut buffer = [0u32; BUFFER_LEN];
+        let mut len = 0;
+        // `TERMINATED` is beyond the end of any range.
+        while doc < doc_range.end {
+            if alive_bitset.map_or(true, |alive_bitset| alive_bitset.is_alive(doc)) {
+                buffer[len] = doc;
+                len += 1;
+                if len == BUFFER_LEN {
+                    segment_collector.collect_block(&buffer);
+                    len = 0;
+                }
+            }
+            doc = scorer.advance();
+        }
+        segment_collector.collect_block(&buffer[..len]);
+        Ok(segment_collector.harvest())
+    }
+
+    /// Collects all of the segments of a search with the executor, and merges their fruits.
+    ///
+    /// Collectors can override this method to share some state between the collection of the
+    /// segments of a search. For instance, [`TopDocs`] shares the score of the K-th best document
+    /// found so far, so that the segments skip the documents which cannot compete.
+    fn collect_segments(
+        &self,
+        weight: &dyn Weight,
+        segment_readers: &[SegmentReader],
+        executor: &Executor,
+    ) -> crate::Result<Self::Fruit> {
+        let fruits = executor.map(
+            |(segment_ord, segment_reader)| {
+                self.collect_segment(weight, segment_ord as u32, segment_reader)
+            },
+            segment_readers.iter().enumerate(),
+        )?;
+        self.merge_fruits(fruits)
+    }
 }
 
 impl<TSegmentCollector: SegmentCollector> SegmentCollector for Option<TSegmentCollector> {
@@ -0,0 +1,245 @@
+use std::ops::{Bound, RangeBounds};
+
+use columnar::Column;
+
+use crate::collector::{Collector, SegmentCollector};
+use crate::fastfield::{FastFieldNotAvailableError, FastValue};
+use crate::schema::Type;
+use crate::{DocId, Score, SegmentOrdinal, SegmentReader};
+
+/// `RangeFacetCollector` counts the collected documents whose values of a numeric or date fast
+/// field fall within each of a list of ranges, e.g. the price ranges `0..10`, `10..50` and
+/// `50..`.
+///
+/// The fruit is a `Vec<u64>` holding the count of each range, in the order the ranges were
+/// added. The ranges may overlap: a document is counted once in each of the ranges containing
+/// one of its values.
+///
+/// Combine it with another collector in a tuple to get the counts alongside the hits:
+///
+/// ```rust
+/// use tantivy::collector::{RangeFacetCollector, TopDocs};
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let price = schema_builder.add_u64_field("price", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+/// for price_val in [5u64, 12, 30, 70] {
+///     index_writer.add_document(doc!(price => price_val))?;
+/// }
+/// index_writer.commit()?;
+/// let searcher = index.reader()?.searcher();
+///
+/// let mut range_facet_collector = RangeFacetCollector::for_field("price");
+/// range_facet_collector.add_range(0u64..10);
+/// range_facet_collector.add_range(10u64..50);
+/// range_facet_collector.add_range(50u64..);
+/// let (top_docs, counts) =
+///     searcher.search(&AllQuery, &(TopDocs::with_limit(2), range_facet_collector))?;
+/// assert_eq!(top_docs.len(), 2);
+/// assert_eq!(counts, vec![1, 2, 1]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct RangeFacetCollector {
+    field: String,
+    value_type: Option<Type>,
+    // The ranges, on the u64 representation of the values.
+    ranges: Vec<(Bound<u64>, Bound<u64>)>,
+}
+
+fn map_bound<TFastValue: FastValue>(bound: Bound<&TFastValue>) -> Bound<u64> {
+    match bound {
+        Bound::Included(value) => Bound::Included(value.to_u64()),
+        Bound::Excluded(value) => Bound::Excluded(value.to_u64()),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+impl RangeFacetCollector {
+    /// Creates a collector counting the values of the fast field `field`.
+    ///
+    /// This function does not check whether the field
+    /// is of the proper type.
+    pub fn for_field(field: impl ToString) -> RangeFacetCollector {
+        RangeFacetCollector {
+            field: field.to_string(),
+            value_type: None,
+            ranges: Vec::new(),
+        }
+    }
+
+    /// Adds a range, whose count is at the next position of the fruit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the type of the values of the range differs from the type of the ranges
+    /// added before.
+    pub fn add_range<TFastValue: FastValue>(&mut self, range: impl RangeBounds<TFastValue>) {
+        let value_type = TFastValue::to_type();
+        assert_eq!(
+            *self.value_type.get_or_insert(value_type),
+            value_type,
+            "The ranges of a RangeFacetCollector must all have the same type."
+        );
+        self.ranges
+            .push((map_bound(range.start_bound()), map_bound(range.end_bound())));
+    }
+}
+
+impl Collector for RangeFacetCollector {
+    type Fruit = Vec<u64>;
+    type Child = RangeFacetSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        let column_opt = segment.fast_fields().u64_lenient(&self.field)?;
+        if column_opt.is_none() {
+            // The column is missing from the segments without any value for the field.
+            let schema = segment.schema();
+            let is_fast = schema
+                .get_field(&self.field)
+                .map(|field| schema.get_field_entry(field).is_fast())
+                .unwrap_or(false);
+            if !is_fast {
+                return Err(FastFieldNotAvailableError {
+                    field_name: self.field.clone(),
+                }
+                .into());
+            }
+        }
+        Ok(RangeFacetSegmentCollector {
+            column_opt: column_opt.map(|(column, _column_type)| column),
+            ranges: self.ranges.clone(),
+            counts: vec![0; self.ranges.len()],
+            counted: vec![false; self.ranges.len()],
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_counts: Vec<Vec<u64>>) -> crate::Result<Vec<u64>> {
+        let mut counts = vec![0u64; self.ranges.len()];
+        for segment_counts in segment_counts {
+            for (count, segment_count) in counts.iter_mut().zip(segment_counts) {
+                *count += segment_count;
+            }
+        }
+        Ok(counts)
+    }
+}
+
+pub struct RangeFacetSegmentCollector {
+    column_opt: Option<Column<u64>>,
+    ranges: Vec<(Bound<u64>, Bound<u64>)>,
+    counts: Vec<u64>,
+    // Whether the current document was already counted in each range.
+    counted: Vec<bool>,
+}
+
+impl SegmentCollector for RangeFacetSegmentCollector {
+    type Fruit = Vec<u64>;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        let Some(column) = &self.column_opt else {
+            return;
+        };
+        self.counted.fill(false);
+        for value in column.values_for_doc(doc) {
+            for (range_ord, range) in self.ranges.iter().enumerate() {
+                if !self.counted[range_ord] && range.contains(&value) {
+                    self.counted[range_ord] = true;
+                    self.counts[range_ord] += 1;
+                }
+            }
+        }
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        self.counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RangeFacetCollector;
+    use crate::collector::Count;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, FAST, INDEXED};
+    use crate::{doc, DateTime, Index};
+
+    #[test]
+    fn test_range_facet_collector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let temperature = schema_builder.add_f64_field("temperature", FAST);
+        let tags = schema_builder.add_i64_field("tags", FAST);
+        let date = schema_builder.add_date_field("date", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(
+            temperature => -3.5f64,
+            tags => -1i64,
+            tags => 5i64,
+            tags => 6i64,
+            date => DateTime::from_timestamp_secs(100),
+        ))?;
+        index_writer.add_document(doc!(temperature => 12.0f64, tags => 2i64))?;
+        index_writer.commit()?;
+        // A segment without any date.
+        index_writer.add_document(doc!(temperature => 25.0f64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let mut collector = RangeFacetCollector::for_field("temperature");
+        collector.add_range(..0.0f64);
+        collector.add_range(0.0f64..=12.0);
+        collector.add_range(10.0f64..);
+        assert_eq!(searcher.search(&AllQuery, &collector)?, vec![1, 1, 2]);
+
+        // A document is counted once per range, even with several values in it.
+        let mut collector = RangeFacetCollector::for_field("tags");
+        collector.add_range(0i64..10);
+        collector.add_range(-10i64..0);
+        assert_eq!(searcher.search(&AllQuery, &collector)?, vec![2, 1]);
+
+        let mut collector = RangeFacetCollector::for_field("date");
+        collector.add_range(DateTime::from_timestamp_secs(0)..DateTime::from_timestamp_secs(200));
+        let (count, date_counts) = searcher.search(&AllQuery, &(Count, collector))?;
+        assert_eq!(count, 3);
+        assert_eq!(date_counts, vec![1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_range_facet_collector_requires_fast_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let num = schema_builder.add_u64_field("num", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(num => 1u64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let mut collector = RangeFacetCollector::for_field("num");
+        collector.add_range(0u64..);
+        assert!(searcher.search(&AllQuery, &collector).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_range_facet_collector_mixed_types() {
+        let mut collector = RangeFacetCollector::for_field("num");
+        collector.add_range(0u64..10);
+        collector.add_range(10i64..);
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::{btree_map, BTreeMap};
+
+use columnar::StrColumn;
+
+use crate::collector::{Collector, SegmentCollector};
+use crate::fastfield::FastFieldNotAvailableError;
+use crate::{DocId, Score, SegmentOrdinal, SegmentReader, TERMINATED};
+
+/// `StringFacetCollector` counts the collected documents for each of the values of a string
+/// fast field.
+///
+/// Unlike the [`FacetCollector`](crate::collector::FacetCollector), the values are plain
+/// strings, without any hierarchy: tags, authors or statuses can be faceted as they are
+/// indexed, without encoding them as [`Facet`](crate::schema::Facet) paths.
+///
+/// A document having a value several times is counted once for this value.
+///
+/// ```rust
+/// use tantivy::collector::StringFacetCollector;
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, FAST, STRING};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let tag = schema_builder.add_text_field("tag", STRING | FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+/// index_writer.add_document(doc!(tag => "rust", tag => "search"))?;
+/// index_writer.add_document(doc!(tag => "rust"))?;
+/// index_writer.commit()?;
+/// let searcher = index.reader()?.searcher();
+///
+/// let counts = searcher.search(&AllQuery, &StringFacetCollector::for_field("tag"))?;
+/// assert_eq!(counts.get("rust"), 2);
+/// assert_eq!(counts.top_k(1), vec![("rust", 2)]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct StringFacetCollector {
+    field: String,
+}
+
+impl StringFacetCollector {
+    /// Creates a collector counting the values of the string fast field `field`.
+    pub fn for_field(field: impl ToString) -> StringFacetCollector {
+        StringFacetCollector {
+            field: field.to_string(),
+        }
+    }
+}
+
+impl Collector for StringFacetCollector {
+    type Fruit = StringFacetCounts;
+    type Child = StringFacetSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        let str_column_opt = segment.fast_fields().str(&self.field)?;
+        if str_column_opt.is_none() {
+            // The column is missing from the segments without any value for the field.
+            let schema = segment.schema();
+            let is_fast = schema
+                .get_field(&self.field)
+                .map(|field| schema.get_field_entry(field).is_fast())
+                .unwrap_or(false);
+            if !is_fast {
+                return Err(FastFieldNotAvailableError {
+                    field_name: self.field.clone(),
+                }
+                .into());
+            }
+        }
+        let num_terms = str_column_opt
+            .as_ref()
+            .map(|str_column| str_column.dictionary().num_terms())
+            .unwrap_or(0);
+        Ok(StringFacetSegmentCollector {
+            str_column_opt,
+            counts: vec![0; num_terms],
+            last_docs: vec![TERMINATED; num_terms],
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_counts: Vec<crate::Result<StringFacetCounts>>,
+    ) -> crate::Result<StringFacetCounts> {
+        let mut counts: BTreeMap<String, u64> = BTreeMap::new();
+        for segment_counts in segment_counts {
+            for (value, count) in segment_counts?.counts {
+                *counts.entry(value).or_insert(0) += count;
+            }
+        }
+        Ok(StringFacetCounts { counts })
+    }
+}
+
+pub struct StringFacetSegmentCollector {
+    str_column_opt: Option<StrColumn>,
+    // term ord -> count
+    counts: Vec<u64>,
+    // term ord -> last doc counted, to count the values repeated in a document once
+    last_docs: Vec<DocId>,
+}
+
+impl SegmentCollector for StringFacetSegmentCollector {
+    type Fruit = crate::Result<StringFacetCounts>;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        let Some(str_column) = &self.str_column_opt else {
+            return;
+        };
+        for term_ord in str_column.term_ords(doc) {
+            let term_ord = term_ord as usize;
+            if self.last_docs[term_ord] != doc {
+                self.last_docs[term_ord] = doc;
+                self.counts[term_ord] += 1;
+            }
+        }
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        let mut counts = BTreeMap::new();
+        let Some(str_column) = &self.str_column_opt else {
+            return Ok(StringFacetCounts { counts });
+        };
+        for (term_ord, count) in self.counts.iter().copied().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            let mut value = String::new();
+            if str_column.ord_to_str(term_ord as u64, &mut value)? {
+                counts.insert(value, count);
+            }
+        }
+        Ok(StringFacetCounts { counts })
+    }
+}
+
+/// The counts of the values of a string fast field, computed by a [`StringFacetCollector`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StringFacetCounts {
+    counts: BTreeMap<String, u64>,
+}
+
+impl StringFacetCounts {
+    /// Returns the number of documents having the value.
+    pub fn get(&self, value: &str) -> u64 {
+        self.counts.get(value).copied().unwrap_or(0)
+    }
+
+    /// Returns the number of distinct values.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Returns true if no document has a value.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Returns an iterator over the values and their counts, sorted by value.
+    pub fn iter(&self) -> StringFacetCountsIter<'_> {
+        StringFacetCountsIter {
+            underlying: self.counts.iter(),
+        }
+    }
+
+    /// Returns the `k` values with the highest counts, sorted highest-to-lowest by count, and
+    /// then by value.
+    pub fn top_k(&self, k: usize) -> Vec<(&str, u64)> {
+        let mut values: Vec<(&str, u64)> = self.iter().collect();
+        values.sort_by(|(left_value, left_count), (right_value, right_count)| {
+            right_count
+                .cmp(left_count)
+                .then_with(|| left_value.cmp(right_value))
+        });
+        values.truncate(k);
+        values
+    }
+}
+
+/// An iterator over the values of [`StringFacetCounts`] and their counts.
+pub struct StringFacetCountsIter<'a> {
+    underlying: btree_map::Iter<'a, String, u64>,
+}
+
+impl<'a> Iterator for StringFacetCountsIter<'a> {
+    type Item = (&'a str, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.underlying
+            .next()
+            .map(|(value, count)| (value.as_str(), *count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StringFacetCollector;
+    use crate::collector::Count;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, STRING};
+    use crate::{doc, Index, Term};
+
+    #[test]
+    fn test_string_facet_collector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let author = schema_builder.add_text_field("author", STRING | FAST);
+        let status = schema_builder.add_text_field("status", STRING | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(author => "ann", author => "bob", status => "draft"))?;
+        index_writer.add_document(doc!(author => "ann", author => "ann", status => "draft"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(author => "carl/jr", status => "published"))?;
+        // A segment without any author.
+        index_writer.commit()?;
+        index_writer.add_document(doc!(status => "published"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let counts = searcher.search(&AllQuery, &StringFacetCollector::for_field("author"))?;
+        assert_eq!(
+            counts.iter().collect::<Vec<_>>(),
+            vec![("ann", 2), ("bob", 1), ("carl/jr", 1)]
+        );
+        assert_eq!(counts.top_k(2), vec![("ann", 2), ("bob", 1)]);
+        assert_eq!(counts.get("dan"), 0);
+
+        let query = TermQuery::new(
+            Term::from_field_text(status, "draft"),
+            IndexRecordOption::Basic,
+        );
+        let (count, counts) =
+            searcher.search(&query, &(Count, StringFacetCollector::for_field("author")))?;
+        assert_eq!(count, 2);
+        assert_eq!(counts.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_string_facet_collector_requires_fast_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tag => "rust"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert!(searcher
+            .search(&AllQuery, &StringFacetCollector::for_field("tag"))
+            .is_err());
+        Ok(())
+    }
+}
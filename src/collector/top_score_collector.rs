@@ -1,20 +1,61 @@
+use std::cmp::Reverse;
 use std::collections::BinaryHeap;
 use std::fmt;
 use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU32, Ordering as AtomicOrdering};
 use std::sync::Arc;
 
-use columnar::ColumnValues;
+use columnar::{Column, ColumnValues};
 
 use super::Collector;
 use crate::collector::custom_score_top_collector::CustomScoreTopCollector;
+use crate::collector::early_terminating_top_collector::EarlyTerminatingTopCollector;
 use crate::collector::top_collector::{ComparableDoc, TopCollector, TopSegmentCollector};
 use crate::collector::tweak_score_top_collector::TweakedScoreTopCollector;
 use crate::collector::{
-    CustomScorer, CustomSegmentScorer, ScoreSegmentTweaker, ScoreTweaker, SegmentCollector,
+    CustomScorer, CustomSegmentScorer, HitCount, ScoreSegmentTweaker, ScoreTweaker,
+    SegmentCollector,
 };
 use crate::fastfield::{FastFieldNotAvailableError, FastValue};
 use crate::query::Weight;
-use crate::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+use crate::schema::{FieldType, GeoPoint, Type};
+use crate::{DocAddress, DocId, Executor, Score, SegmentOrdinal, SegmentReader, TantivyError};
+
+/// Checks that `field` is a fast field of the type `TFastValue`, or a runtime field if
+/// `TFastValue` is `f64`.
+pub(crate) fn check_fast_field_type<TFastValue: FastValue>(
+    field: &str,
+    segment: &SegmentReader,
+) -> crate::Result<()> {
+    if segment.fast_fields().runtime_field(field).is_some() {
+        let schema_type = TFastValue::to_type();
+        if schema_type != Type::F64 {
+            return Err(TantivyError::SchemaError(format!(
+                "Runtime field {:?} is of type {schema_type:?}!=F64",
+                field
+            )));
+        }
+        return Ok(());
+    }
+    let schema = segment.schema();
+    let field = schema.get_field(field)?;
+    let field_entry = schema.get_field_entry(field);
+    if !field_entry.is_fast() {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {:?} is not a fast field.",
+            field_entry.name()
+        )));
+    }
+    let schema_type = TFastValue::to_type();
+    let requested_type = field_entry.field_type().value_type();
+    if schema_type != requested_type {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {:?} is of type {schema_type:?}!={requested_type:?}",
+            field_entry.name()
+        )));
+    }
+    Ok(())
+}
 
 struct FastFieldConvertCollector<
     TCollector: Collector<Fruit = Vec<(u64, DocAddress)>>,
@@ -39,23 +80,7 @@ where
         segment_local_id: crate::SegmentOrdinal,
         segment: &SegmentReader,
     ) -> crate::Result<Self::Child> {
-        let schema = segment.schema();
-        let field = schema.get_field(&self.field)?;
-        let field_entry = schema.get_field_entry(field);
-        if !field_entry.is_fast() {
-            return Err(TantivyError::SchemaError(format!(
-                "Field {:?} is not a fast field.",
-                field_entry.name()
-            )));
-        }
-        let schema_type = TFastValue::to_type();
-        let requested_type = field_entry.field_type().value_type();
-        if schema_type != requested_type {
-            return Err(TantivyError::SchemaError(format!(
-                "Field {:?} is of type {schema_type:?}!={requested_type:?}",
-                field_entry.name()
-            )));
-        }
+        check_fast_field_type::<TFastValue>(&self.field, segment)?;
         self.collector.for_segment(segment_local_id, segment)
     }
 
@@ -163,6 +188,92 @@ impl CustomScorer<u64> for ScorerByField {
     }
 }
 
+struct GeoDistanceSegmentScorer {
+    column_opt: Option<Column<u64>>,
+    origin: GeoPoint,
+}
+
+impl CustomSegmentScorer<Reverse<f64>> for GeoDistanceSegmentScorer {
+    fn score(&mut self, doc: DocId) -> Reverse<f64> {
+        let distance = self
+            .column_opt
+            .as_ref()
+            .and_then(|column| {
+                column
+                    .values_for_doc(doc)
+                    .map(|val| self.origin.distance(&GeoPoint::from_u64(val)))
+                    .min_by(f64::total_cmp)
+            })
+            .unwrap_or(f64::INFINITY);
+        // The closest documents have the highest score.
+        Reverse(distance)
+    }
+}
+
+struct ScorerByGeoDistance {
+    field: String,
+    origin: GeoPoint,
+}
+
+impl CustomScorer<Reverse<f64>> for ScorerByGeoDistance {
+    type Child = GeoDistanceSegmentScorer;
+
+    fn segment_scorer(&self, segment_reader: &SegmentReader) -> crate::Result<Self::Child> {
+        let schema = segment_reader.schema();
+        let field = schema.get_field(&self.field)?;
+        let field_entry = schema.get_field_entry(field);
+        if !matches!(field_entry.field_type(), FieldType::GeoPoint(_)) {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not a geo point field.",
+                field_entry.name()
+            )));
+        }
+        let column_opt = segment_reader
+            .fast_fields()
+            .column_opt::<u64>(&self.field)?;
+        Ok(GeoDistanceSegmentScorer {
+            column_opt,
+            origin: self.origin,
+        })
+    }
+}
+
+struct GeoDistanceConvertCollector<TCollector: Collector<Fruit = Vec<(Reverse<f64>, DocAddress)>>> {
+    collector: TCollector,
+}
+
+impl<TCollector> Collector for GeoDistanceConvertCollector<TCollector>
+where TCollector: Collector<Fruit = Vec<(Reverse<f64>, DocAddress)>>
+{
+    type Fruit = Vec<(f64, DocAddress)>;
+
+    type Child = TCollector::Child;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        self.collector.for_segment(segment_local_id, segment)
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.collector.requires_scoring()
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<<Self::Child as SegmentCollector>::Fruit>,
+    ) -> crate::Result<Self::Fruit> {
+        let raw_result = self.collector.merge_fruits(segment_fruits)?;
+        let transformed_result = raw_result
+            .into_iter()
+            .map(|(Reverse(distance), doc_address)| (distance, doc_address))
+            .collect::<Vec<_>>();
+        Ok(transformed_result)
+    }
+}
+
 impl TopDocs {
     /// Creates a top score collector, with a number of documents equal to "limit".
     ///
@@ -384,6 +495,119 @@ impl TopDocs {
         }
     }
 
+    /// Set top-K to rank documents by a given fast field, and count the matching documents.
+    ///
+    /// The documents are ranked as with
+    /// [`.order_by_fast_field(...)`](TopDocs::order_by_fast_field).
+    ///
+    /// If the index is sorted by the same field in the descending order (see
+    /// [`IndexSettings::sort_by_field`](crate::IndexSettings::sort_by_field)), the collection of
+    /// a segment stops as soon as its remaining documents cannot make it into the top-K. The hit
+    /// count is then a lower bound of the number of matching documents.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tantivy::collector::{HitCount, TopDocs};
+    /// use tantivy::query::AllQuery;
+    /// use tantivy::schema::{Schema, FAST};
+    /// use tantivy::{doc, Index, IndexSettings, IndexSortByField, Order};
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// let mut schema_builder = Schema::builder();
+    /// let timestamp = schema_builder.add_u64_field("timestamp", FAST);
+    /// let settings = IndexSettings {
+    ///     sort_by_field: Some(IndexSortByField {
+    ///         field: "timestamp".to_string(),
+    ///         order: Order::Desc,
+    ///     }),
+    ///     ..Default::default()
+    /// };
+    /// let index = Index::builder()
+    ///     .schema(schema_builder.build())
+    ///     .settings(settings)
+    ///     .create_in_ram()?;
+    /// let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+    /// for i in 0..100u64 {
+    ///     index_writer.add_document(doc!(timestamp => i))?;
+    /// }
+    /// index_writer.commit()?;
+    ///
+    /// let searcher = index.reader()?.searcher();
+    /// let latest = TopDocs::with_limit(2).order_by_fast_field_with_hit_count::<u64>("timestamp");
+    /// let (top_docs, hit_count) = searcher.search(&AllQuery, &latest)?;
+    /// assert_eq!(top_docs[0].0, 99);
+    /// assert_eq!(top_docs[1].0, 98);
+    /// assert_eq!(hit_count, HitCount::LowerBound(2));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn order_by_fast_field_with_hit_count<TFastValue>(
+        self,
+        fast_field: impl ToString,
+    ) -> impl Collector<Fruit = (Vec<(TFastValue, DocAddress)>, HitCount)>
+    where
+        TFastValue: FastValue,
+    {
+        EarlyTerminatingTopCollector::new(fast_field.to_string(), self.0.into_tscore())
+    }
+
+    /// Set top-K to rank documents by their distance to `origin`, closest first.
+    ///
+    /// The distance, in meters, is computed from the fast field of the geo point field during
+    /// the collection, and returned alongside each document. A document with several points is
+    /// ranked by its closest point, and a document without any point is ranked last, with an
+    /// infinite distance.
+    ///
+    /// If the field does not exist or is not a geo point field, an error will be returned at the
+    /// moment of search.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tantivy::collector::TopDocs;
+    /// use tantivy::query::AllQuery;
+    /// use tantivy::schema::{GeoPoint, GeoPointOptions, Schema};
+    /// use tantivy::{doc, DocAddress, Index};
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// let mut schema_builder = Schema::builder();
+    /// let location = schema_builder.add_geo_point_field("location", GeoPointOptions::default());
+    /// let index = Index::create_in_ram(schema_builder.build());
+    /// let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+    /// // The Tower of London, the Eiffel tower and the Arc de Triomphe.
+    /// index_writer.add_document(doc!(location => GeoPoint { lat: 51.5081, lon: -0.0759 }))?;
+    /// index_writer.add_document(doc!(location => GeoPoint { lat: 48.8584, lon: 2.2945 }))?;
+    /// index_writer.add_document(doc!(location => GeoPoint { lat: 48.8738, lon: 2.2950 }))?;
+    /// index_writer.commit()?;
+    ///
+    /// let searcher = index.reader()?.searcher();
+    /// let notre_dame = GeoPoint { lat: 48.8530, lon: 2.3499 };
+    /// let top_docs = TopDocs::with_limit(2).order_by_geo_distance("location", notre_dame);
+    /// let closest_docs: Vec<(f64, DocAddress)> = searcher.search(&AllQuery, &top_docs)?;
+    /// assert_eq!(closest_docs.len(), 2);
+    /// assert_eq!(closest_docs[0].1, DocAddress::new(0, 1));
+    /// assert_eq!(closest_docs[1].1, DocAddress::new(0, 2));
+    /// assert!((closest_docs[0].0 - 4_100.0).abs() < 100.0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn order_by_geo_distance(
+        self,
+        field: impl ToString,
+        origin: GeoPoint,
+    ) -> impl Collector<Fruit = Vec<(f64, DocAddress)>> {
+        GeoDistanceConvertCollector {
+            collector: CustomScoreTopCollector::new(
+                ScorerByGeoDistance {
+                    field: field.to_string(),
+                    origin,
+                },
+                self.0.into_tscore(),
+            ),
+        }
+    }
+
     /// Ranks the documents using a custom score.
     ///
     /// This method offers a convenient way to tweak or replace
@@ -637,49 +861,68 @@ impl Collector for TopDocs {
         segment_ord: u32,
         reader: &SegmentReader,
     ) -> crate::Result<<Self::Child as SegmentCollector>::Fruit> {
+        self.collect_segment_with_threshold(weight, segment_ord, reader, &SharedThreshold::new())
+    }
+
+    fn collect_segments(
+        &self,
+        weight: &dyn Weight,
+        segment_readers: &[SegmentReader],
+        executor: &Executor,
+    ) -> crate::Result<Self::Fruit> {
+        let shared_threshold = SharedThreshold::new();
+        let fruits = executor.map(
+            |(segment_ord, segment_reader)| {
+                self.collect_segment_with_threshold(
+                    weight,
+                    segment_ord as u32,
+                    segment_reader,
+                    &shared_threshold,
+                )
+            },
+            segment_readers.iter().enumerate(),
+        )?;
+        self.merge_fruits(fruits)
+    }
+}
+
+impl TopDocs {
+    /// Collects the top documents of a segment, skipping the documents scoring below the
+    /// threshold shared with the other segments of the search.
+    ///
+    /// The documents scoring as much as the shared threshold are still collected, since they
+    /// can win the tie against the documents of the segments of higher ordinals.
+    fn collect_segment_with_threshold(
+        &self,
+        weight: &dyn Weight,
+        segment_ord: u32,
+        reader: &SegmentReader,
+        shared_threshold: &SharedThreshold,
+    ) -> crate::Result<Vec<(Score, DocAddress)>> {
         let heap_len = self.0.limit + self.0.offset;
         let mut heap: BinaryHeap<ComparableDoc<Score, DocId>> = BinaryHeap::with_capacity(heap_len);
-
-        if let Some(alive_bitset) = reader.alive_bitset() {
-            let mut threshold = Score::MIN;
-            weight.for_each_pruning(threshold, reader, &mut |doc, score| {
-                if alive_bitset.is_deleted(doc) {
-                    return threshold;
-                }
-                let heap_item = ComparableDoc {
-                    feature: score,
-                    doc,
-                };
-                if heap.len() < heap_len {
-                    heap.push(heap_item);
-                    if heap.len() == heap_len {
-                        threshold = heap.peek().map(|el| el.feature).unwrap_or(Score::MIN);
-                    }
-                    return threshold;
-                }
+        let alive_bitset_opt = reader.alive_bitset();
+        let mut local_threshold = Score::MIN;
+        let initial_threshold = shared_threshold.pruning_threshold(local_threshold);
+        weight.for_each_pruning(initial_threshold, reader, &mut |doc, score| {
+            if alive_bitset_opt.map_or(false, |alive_bitset| alive_bitset.is_deleted(doc)) {
+                return shared_threshold.pruning_threshold(local_threshold);
+            }
+            let heap_item = ComparableDoc {
+                feature: score,
+                doc,
+            };
+            if heap.len() < heap_len {
+                heap.push(heap_item);
+            } else {
                 *heap.peek_mut().unwrap() = heap_item;
-                threshold = heap.peek().map(|el| el.feature).unwrap_or(Score::MIN);
-                threshold
-            })?;
-        } else {
-            weight.for_each_pruning(Score::MIN, reader, &mut |doc, score| {
-                let heap_item = ComparableDoc {
-                    feature: score,
-                    doc,
-                };
-                if heap.len() < heap_len {
-                    heap.push(heap_item);
-                    // TODO the threshold is suboptimal for heap.len == heap_len
-                    if heap.len() == heap_len {
-                        return heap.peek().map(|el| el.feature).unwrap_or(Score::MIN);
-                    } else {
-                        return Score::MIN;
-                    }
-                }
-                *heap.peek_mut().unwrap() = heap_item;
-                heap.peek().map(|el| el.feature).unwrap_or(Score::MIN)
-            })?;
-        }
+            }
+            if heap.len() == heap_len {
+                local_threshold = heap.peek().map(|el| el.feature).unwrap_or(Score::MIN);
+                shared_threshold.raise(local_threshold);
+            }
+            shared_threshold.pruning_threshold(local_threshold)
+        })?;
 
         let fruit = heap
             .into_sorted_vec()
@@ -698,6 +941,42 @@ impl Collector for TopDocs {
     }
 }
 
+/// The score of the K-th best document found so far by any of the segments of a search.
+struct SharedThreshold(AtomicU32);
+
+impl SharedThreshold {
+    fn new() -> SharedThreshold {
+        SharedThreshold(AtomicU32::new(Score::MIN.to_bits()))
+    }
+
+    fn raise(&self, score: Score) {
+        let _ = self
+            .0
+            .fetch_update(AtomicOrdering::Relaxed, AtomicOrdering::Relaxed, |bits| {
+                (score > Score::from_bits(bits)).then(|| score.to_bits())
+            });
+    }
+
+    /// Returns the threshold to prune the documents of a segment with: its documents must score
+    /// more than its own K-th best document, and at least as much as the shared threshold.
+    fn pruning_threshold(&self, local_threshold: Score) -> Score {
+        let shared_threshold = Score::from_bits(self.0.load(AtomicOrdering::Relaxed));
+        local_threshold.max(next_score_down(shared_threshold))
+    }
+}
+
+/// Returns the greatest score strictly lower than `score`.
+fn next_score_down(score: Score) -> Score {
+    if score.is_nan() || score == Score::NEG_INFINITY {
+        return score;
+    }
+    if score == 0.0 {
+        return -Score::from_bits(1);
+    }
+    let bits = score.to_bits();
+    Score::from_bits(if score > 0.0 { bits - 1 } else { bits + 1 })
+}
+
 /// Segment Collector associated with `TopDocs`.
 pub struct TopScoreSegmentCollector(TopSegmentCollector<Score>);
 
@@ -717,11 +996,11 @@ impl SegmentCollector for TopScoreSegmentCollector {
 mod tests {
     use super::TopDocs;
     use crate::collector::Collector;
-    use crate::query::{AllQuery, Query, QueryParser};
-    use crate::schema::{Field, Schema, FAST, STORED, TEXT};
+    use crate::query::{AllQuery, EnableScoring, Query, QueryParser};
+    use crate::schema::{Field, GeoPoint, GeoPointOptions, Schema, FAST, STORED, TEXT};
     use crate::time::format_description::well_known::Rfc3339;
     use crate::time::OffsetDateTime;
-    use crate::{DateTime, DocAddress, DocId, Index, IndexWriter, Score, SegmentReader};
+    use crate::{DateTime, DocAddress, DocId, Executor, Index, IndexWriter, Score, SegmentReader};
 
     fn make_index() -> crate::Result<Index> {
         let mut schema_builder = Schema::builder();
@@ -744,6 +1023,61 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_top_collector_shared_threshold() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for segment in 0..4 {
+            for i in 0..50 {
+                let text = match (segment + i) % 5 {
+                    0 => "a",
+                    1 => "a a b",
+                    2 => "b c",
+                    3 => "a b c d e f",
+                    _ => "c",
+                };
+                index_writer.add_document(doc!(text_field => text))?;
+            }
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 4);
+        let executor = Executor::multi_thread(4, "top-docs-test")?;
+        let query = QueryParser::for_index(&index, vec![text_field]).parse_query("a b")?;
+        let all_docs = searcher.search(&query, &TopDocs::with_limit(1_000))?;
+        for (limit, offset) in [(1, 0), (5, 0), (10, 3), (60, 0)] {
+            let top_docs = TopDocs::with_limit(limit).and_offset(offset);
+            let expected: Vec<_> = all_docs.iter().skip(offset).take(limit).cloned().collect();
+            assert_eq!(searcher.search(&query, &top_docs)?, expected);
+            let enable_scoring = EnableScoring::enabled_from_searcher(&searcher);
+            assert_eq!(
+                searcher.search_with_executor(&query, &top_docs, &executor, enable_scoring)?,
+                expected
+            );
+        }
+
+        // On a tie, the documents of the first segments win, whatever segment is collected first.
+        let enable_scoring = EnableScoring::enabled_from_searcher(&searcher);
+        let top_docs = searcher.search_with_executor(
+            &AllQuery,
+            &TopDocs::with_limit(3),
+            &executor,
+            enable_scoring,
+        )?;
+        let doc_addresses: Vec<DocAddress> = top_docs.into_iter().map(|(_, addr)| addr).collect();
+        assert_eq!(
+            doc_addresses,
+            vec![
+                DocAddress::new(0, 0),
+                DocAddress::new(0, 1),
+                DocAddress::new(0, 2)
+            ]
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_top_collector_not_at_capacity_without_offset() -> crate::Result<()> {
         let index = make_index()?;
@@ -1092,6 +1426,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_top_docs_order_by_geo_distance() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let location = schema_builder.add_geo_point_field("location", GeoPointOptions::default());
+        let id = schema_builder.add_u64_field("id", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc!(location => GeoPoint { lat: 0.0, lon: 3.0 }))?;
+            index_writer.add_document(doc!(id => 1u64))?;
+            index_writer.add_document(doc!(location => GeoPoint { lat: 0.0, lon: 1.0 }))?;
+            index_writer.commit()?;
+            // A document with two points is ranked by its closest point.
+            index_writer.add_document(doc!(
+                location => GeoPoint { lat: 0.0, lon: 10.0 },
+                location => GeoPoint { lat: 0.0, lon: -2.0 },
+            ))?;
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let origin = GeoPoint { lat: 0.0, lon: 0.0 };
+        let collector = TopDocs::with_limit(10).order_by_geo_distance("location", origin);
+        let top_docs: Vec<(f64, DocAddress)> = searcher.search(&AllQuery, &collector)?;
+        let doc_addresses: Vec<DocAddress> = top_docs.iter().map(|(_, addr)| *addr).collect();
+        assert_eq!(
+            doc_addresses,
+            vec![
+                DocAddress::new(0, 2),
+                DocAddress::new(1, 0),
+                DocAddress::new(0, 0),
+                DocAddress::new(0, 1),
+            ]
+        );
+        let one_degree = origin.distance(&GeoPoint { lat: 0.0, lon: 1.0 });
+        for ((distance, _), expected) in top_docs.iter().zip([1.0, 2.0, 3.0]) {
+            assert!((distance / one_degree - expected).abs() < 1e-6);
+        }
+        assert_eq!(top_docs[3].0, f64::INFINITY);
+
+        let collector = TopDocs::with_limit(1)
+            .and_offset(1)
+            .order_by_geo_distance("location", origin);
+        let top_docs = searcher.search(&AllQuery, &collector)?;
+        assert_eq!(top_docs.len(), 1);
+        assert_eq!(top_docs[0].1, DocAddress::new(1, 0));
+
+        let collector = TopDocs::with_limit(1).order_by_geo_distance("id", origin);
+        assert!(searcher.search(&AllQuery, &collector).is_err());
+        Ok(())
+    }
+
     fn index(
         query: &str,
         query_field: Field,
@@ -0,0 +1,343 @@
+//! The archive format of [`Index::export()`] and [`Index::import()`].
+//!
+//! An archive is a single stream holding a commit of an index:
+//!
+//! ```text
+//! magic: [u8; 8] | version: u32 | compressor id: u8
+//! meta.json: Block
+//! num files: u32
+//! for each file: path: Block | num bytes: u64 | footer crc: u32 | content: Block*
+//! ```
+//!
+//! A `Block` is `len: u32 | crc32: u32 | bytes`. The content of each file is split into chunks
+//! of 1 MiB, compressed independently, the last chunk being shorter.
+//!
+//! The `meta.json` file holds the schema and the settings of the index, which makes the archive
+//! self-describing. The files are written with their footers, whose checksums are validated on
+//! import, on top of the checksums of the blocks.
+
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use common::{BinarySerializable, HasLen};
+
+use crate::core::snapshot::write_meta;
+use crate::core::{Index, IndexMeta, SegmentMetaInventory};
+use crate::directory::{Directory, ManagedDirectory};
+use crate::error::DataCorruption;
+use crate::store::{Compressor, Decompressor};
+use crate::TantivyError;
+
+const ARCHIVE_MAGIC: [u8; 8] = *b"TANTIVYA";
+const ARCHIVE_VERSION: u32 = 1;
+/// Number of bytes of the chunks the files are split into.
+const ARCHIVE_CHUNK_LEN: usize = 1 << 20;
+// Bounds on the lengths of the blocks, which are checked before allocating them.
+const MAX_META_LEN: usize = 1 << 28;
+const MAX_PATH_LEN: usize = 1 << 10;
+const MAX_COMPRESSED_CHUNK_LEN: usize = 2 * ARCHIVE_CHUNK_LEN;
+
+fn corruption(comment: impl ToString) -> TantivyError {
+    DataCorruption::comment_only(comment).into()
+}
+
+fn write_block(writer: &mut dyn io::Write, data: &[u8]) -> io::Result<()> {
+    (data.len() as u32).serialize(writer)?;
+    crc32fast::hash(data).serialize(writer)?;
+    writer.write_all(data)
+}
+
+fn read_block_into(
+    mut reader: &mut dyn io::Read,
+    max_len: usize,
+    data: &mut Vec<u8>,
+) -> crate::Result<()> {
+    let len = u32::deserialize(&mut reader)? as usize;
+    let crc = u32::deserialize(&mut reader)?;
+    if len > max_len {
+        return Err(corruption(format!(
+            "Block of {len} bytes, exceeding the maximum of {max_len} bytes"
+        )));
+    }
+    data.resize(len, 0u8);
+    reader.read_exact(data)?;
+    if crc32fast::hash(data) != crc {
+        return Err(corruption("Block checksum mismatch"));
+    }
+    Ok(())
+}
+
+fn read_string(reader: &mut dyn io::Read, max_len: usize) -> crate::Result<String> {
+    let mut data = Vec::new();
+    read_block_into(reader, max_len, &mut data)?;
+    String::from_utf8(data).map_err(|_| corruption("Invalid utf8 string"))
+}
+
+fn decompressor_from_id(id: u8) -> crate::Result<Decompressor> {
+    let compressor = match id {
+        0 => Compressor::None,
+        1 => Compressor::Lz4,
+        2 => Compressor::Brotli,
+        3 => Compressor::Snappy,
+        4 => Compressor::Zstd(Default::default()),
+        _ => return Err(corruption(format!("Unknown compressor id {id}"))),
+    };
+    if !compressor.is_available() {
+        return Err(TantivyError::InvalidArgument(format!(
+            "The archive is compressed with {compressor:?}, whose feature flag is not enabled"
+        )));
+    }
+    Ok(Decompressor::from(compressor))
+}
+
+pub(crate) fn export_index(
+    index: &Index,
+    writer: &mut dyn io::Write,
+    compressor: Compressor,
+) -> crate::Result<()> {
+    if !compressor.is_available() {
+        return Err(TantivyError::InvalidArgument(format!(
+            "The feature flag of {compressor:?} is not enabled"
+        )));
+    }
+    let snapshot = index.snapshot()?;
+    let manifest = snapshot.manifest();
+    writer.write_all(&ARCHIVE_MAGIC)?;
+    ARCHIVE_VERSION.serialize(writer)?;
+    Decompressor::from(compressor).get_id().serialize(writer)?;
+    write_block(writer, manifest.meta().as_bytes())?;
+    (manifest.files().len() as u32).serialize(writer)?;
+    let mut chunk = vec![0u8; ARCHIVE_CHUNK_LEN];
+    let mut compressed_chunk = Vec::new();
+    for (path, file) in manifest.files() {
+        let path_str = path.to_str().ok_or_else(|| {
+            TantivyError::InvalidArgument(format!("{path:?} is not a valid utf8 path"))
+        })?;
+        write_block(writer, path_str.as_bytes())?;
+        file.num_bytes.serialize(writer)?;
+        file.crc.serialize(writer)?;
+        let mut file_reader = snapshot.open_file(path)?;
+        let mut remaining = file.num_bytes;
+        while remaining > 0 {
+            let chunk_len = remaining.min(ARCHIVE_CHUNK_LEN as u64) as usize;
+            io::Read::read_exact(&mut file_reader, &mut chunk[..chunk_len])?;
+            compressor.compress_into(&chunk[..chunk_len], &mut compressed_chunk)?;
+            write_block(writer, &compressed_chunk)?;
+            remaining -= chunk_len as u64;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads the content of a file of an archive, chunk by chunk.
+struct ArchiveFileReader<'a> {
+    reader: &'a mut dyn io::Read,
+    decompressor: Decompressor,
+    remaining: u64,
+    compressed_chunk: Vec<u8>,
+    chunk: Vec<u8>,
+    offset: usize,
+}
+
+impl<'a> io::Read for ArchiveFileReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.offset == self.chunk.len() {
+            if self.remaining == 0 {
+                return Ok(0);
+            }
+            let chunk_len = self.remaining.min(ARCHIVE_CHUNK_LEN as u64) as usize;
+            read_block_into(
+                self.reader,
+                MAX_COMPRESSED_CHUNK_LEN,
+                &mut self.compressed_chunk,
+            )
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+            self.decompressor
+                .decompress_into(&self.compressed_chunk, &mut self.chunk)?;
+            if self.chunk.len() != chunk_len {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Chunk of {} bytes, expected {chunk_len} bytes",
+                        self.chunk.len()
+                    ),
+                ));
+            }
+            self.remaining -= chunk_len as u64;
+            self.offset = 0;
+        }
+        let num_bytes = buf.len().min(self.chunk.len() - self.offset);
+        buf[..num_bytes].copy_from_slice(&self.chunk[self.offset..self.offset + num_bytes]);
+        self.offset += num_bytes;
+        Ok(num_bytes)
+    }
+}
+
+fn is_valid_file(
+    directory: &ManagedDirectory,
+    path: &Path,
+    num_bytes: u64,
+    crc: u32,
+) -> crate::Result<bool> {
+    Ok(
+        directory.open_read_with_footer(path)?.len() as u64 == num_bytes
+            && directory.footer_crc(path)? == crc
+            && directory.validate_checksum(path)?,
+    )
+}
+
+pub(crate) fn import_index(
+    mut reader: &mut dyn io::Read,
+    directory: Box<dyn Directory>,
+) -> crate::Result<Index> {
+    if Index::exists(&*directory)? {
+        return Err(TantivyError::IndexAlreadyExists);
+    }
+    let mut magic = [0u8; ARCHIVE_MAGIC.len()];
+    reader.read_exact(&mut magic)?;
+    if magic != ARCHIVE_MAGIC {
+        return Err(corruption("Not an index archive"));
+    }
+    let version = u32::deserialize(&mut reader)?;
+    if version != ARCHIVE_VERSION {
+        return Err(corruption(format!(
+            "Unsupported archive version {version}, expected {ARCHIVE_VERSION}"
+        )));
+    }
+    let decompressor = decompressor_from_id(u8::deserialize(&mut reader)?)?;
+    let meta = read_string(reader, MAX_META_LEN)?;
+    let index_meta = IndexMeta::deserialize(&meta, &SegmentMetaInventory::default())
+        .map_err(|err| corruption(format!("Invalid meta.json: {err}")))?;
+    // Only the files of the segments are imported, which also rules out the paths escaping the
+    // directory.
+    let segment_files: HashSet<PathBuf> = index_meta
+        .segments
+        .iter()
+        .flat_map(|segment_meta| segment_meta.list_files())
+        .collect();
+
+    let managed_directory = ManagedDirectory::wrap(directory.box_clone())?;
+    let num_files = u32::deserialize(&mut reader)?;
+    for _ in 0..num_files {
+        let path = PathBuf::from(read_string(reader, MAX_PATH_LEN)?);
+        if !segment_files.contains(&path) {
+            return Err(corruption(format!(
+                "{path:?} is not a file of the segments of the archive"
+            )));
+        }
+        let num_bytes = u64::deserialize(&mut reader)?;
+        let crc = u32::deserialize(&mut reader)?;
+        let mut file_reader = ArchiveFileReader {
+            reader: &mut *reader,
+            decompressor,
+            remaining: num_bytes,
+            compressed_chunk: Vec::new(),
+            chunk: Vec::new(),
+            offset: 0,
+        };
+        managed_directory.write_with_footer(&path, &mut file_reader)?;
+        if !is_valid_file(&managed_directory, &path, num_bytes, crc)? {
+            managed_directory.delete_if_exists(&path)?;
+            return Err(DataCorruption::new(
+                path,
+                "The imported file does not match its checksum".to_string(),
+            )
+            .into());
+        }
+    }
+    write_meta(&managed_directory, &meta)?;
+    Index::open(directory)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::directory::RamDirectory;
+    use crate::indexer::NoMergePolicy;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, STORED, TEXT};
+    use crate::store::Compressor;
+    use crate::{Document, Index, TantivyError, Term};
+
+    fn test_index() -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(text => "hello happy tax payer"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text => "hello"))?;
+        index_writer.add_document(doc!(text => "goodbye"))?;
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    fn test_export_import(compressor: Compressor) -> crate::Result<()> {
+        let index = test_index()?;
+        let mut archive = Vec::new();
+        index.export_with_compressor(&mut archive, compressor)?;
+
+        let imported_index = Index::import(&mut Cursor::new(&archive), RamDirectory::create())?;
+        assert_eq!(imported_index.schema(), index.schema());
+        assert_eq!(
+            imported_index.searchable_segment_ids()?,
+            index.searchable_segment_ids()?
+        );
+        let searcher = imported_index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 3);
+        let text = imported_index.schema().get_field("text").unwrap();
+        let query = TermQuery::new(
+            Term::from_field_text(text, "hello"),
+            IndexRecordOption::Basic,
+        );
+        let top_docs = searcher.search(&query, &crate::collector::TopDocs::with_limit(2))?;
+        assert_eq!(top_docs.len(), 2);
+        let doc: Document = searcher.doc(top_docs[0].1)?;
+        assert!(doc.get_first(text).is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_import_uncompressed() -> crate::Result<()> {
+        test_export_import(Compressor::None)
+    }
+
+    #[test]
+    #[cfg(feature = "lz4-compression")]
+    fn test_export_import_lz4() -> crate::Result<()> {
+        test_export_import(Compressor::Lz4)
+    }
+
+    #[test]
+    fn test_import_rejects_corrupted_archives() -> crate::Result<()> {
+        let index = test_index()?;
+        let mut archive = Vec::new();
+        index.export(&mut archive)?;
+        for offset in [0, 20, archive.len() / 2, archive.len() - 1] {
+            let mut corrupted_archive = archive.clone();
+            corrupted_archive[offset] ^= 1;
+            let directory = RamDirectory::create();
+            assert!(
+                Index::import(&mut Cursor::new(&corrupted_archive), directory.clone()).is_err()
+            );
+            assert!(!Index::exists(&directory)?);
+        }
+        let truncated_archive = &archive[..archive.len() - 1];
+        assert!(
+            Index::import(&mut Cursor::new(truncated_archive), RamDirectory::create()).is_err()
+        );
+
+        // The archive is not imported over an existing index.
+        let directory = RamDirectory::create();
+        Index::import(&mut Cursor::new(&archive), directory.clone())?;
+        assert!(matches!(
+            Index::import(&mut Cursor::new(&archive), directory),
+            Err(TantivyError::IndexAlreadyExists)
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::collector::Collector;
+use crate::query::{BooleanQuery, ConstScoreQuery, EnableScoring, Occur, Query, Weight};
+use crate::schema::{Document, Field, Schema};
+use crate::{DocAddress, DocId, DocSet, Searcher, SegmentOrdinal, SegmentReader, TantivyError};
+
+/// Wraps a [`Searcher`] so that only the documents matching a mandatory filter query are
+/// visible, and optionally only some of their fields, e.g. to serve several tenants from one
+/// index.
+///
+/// - [`FilteredSearcher::search`] and [`FilteredSearcher::count`] only match the documents
+///   matching the filter. The filter does not change the scores of the documents.
+/// - [`FilteredSearcher::doc`] and [`FilteredSearcher::docs`] return an error for the documents
+///   not matching the filter, and drop the stored fields outside of the allow-list.
+/// - The collectors, and therefore the [aggregations](crate::aggregation), get segment readers
+///   whose fast fields outside of the allow-list are hidden, as if they were not fast.
+///
+/// The BM25 statistics used for scoring are the statistics of the whole index.
+#[derive(Clone)]
+pub struct FilteredSearcher {
+    searcher: Searcher,
+    filter: Arc<dyn Query>,
+    filter_weight: Arc<dyn Weight>,
+    allowed_fields: Option<Arc<Vec<Field>>>,
+    segment_readers: Vec<SegmentReader>,
+}
+
+impl FilteredSearcher {
+    /// Creates a `FilteredSearcher` showing the documents of `searcher` matching `filter`.
+    pub fn new(searcher: Searcher, filter: Box<dyn Query>) -> crate::Result<FilteredSearcher> {
+        let filter_weight: Arc<dyn Weight> = filter
+            .weight(EnableScoring::disabled_from_searcher(&searcher))?
+            .into();
+        let segment_readers = searcher.segment_readers().to_vec();
+        Ok(FilteredSearcher {
+            searcher,
+            filter: filter.into(),
+            filter_weight,
+            allowed_fields: None,
+            segment_readers,
+        })
+    }
+
+    /// Only shows the stored fields and the fast fields in `allowed_fields`.
+    pub fn with_allowed_fields(mut self, allowed_fields: Vec<Field>) -> FilteredSearcher {
+        let allowed_fields = Arc::new(allowed_fields);
+        self.segment_readers = self
+            .searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| segment_reader.with_allowed_fast_fields(allowed_fields.clone()))
+            .collect();
+        self.allowed_fields = Some(allowed_fields);
+        self
+    }
+
+    /// Returns the schema of the index.
+    pub fn schema(&self) -> &Schema {
+        self.searcher.schema()
+    }
+
+    /// Returns the segment readers, with the fast fields outside of the allow-list hidden.
+    pub fn segment_readers(&self) -> &[SegmentReader] {
+        &self.segment_readers
+    }
+
+    /// Returns true if the document is alive and matches the filter.
+    pub fn is_visible(&self, doc_address: DocAddress) -> crate::Result<bool> {
+        let Some(segment_reader) = self.segment_readers.get(doc_address.segment_ord as usize)
+        else {
+            return Ok(false);
+        };
+        if doc_address.doc_id >= segment_reader.max_doc()
+            || segment_reader.is_deleted(doc_address.doc_id)
+        {
+            return Ok(false);
+        }
+        let mut scorer = self.filter_weight.scorer(segment_reader, 1.0)?;
+        Ok(scorer.seek(doc_address.doc_id) == doc_address.doc_id)
+    }
+
+    /// Fetches a visible document, keeping only the stored fields of the allow-list.
+    ///
+    /// Returns an error if the document does not match the filter.
+    pub fn doc(&self, doc_address: DocAddress) -> crate::Result<Document> {
+        if !self.is_visible(doc_address)? {
+            return Err(not_visible_error(doc_address));
+        }
+        match &self.allowed_fields {
+            Some(allowed_fields) => self.searcher.doc_with_fields(doc_address, allowed_fields),
+            None => self.searcher.doc(doc_address),
+        }
+    }
+
+    /// Fetches the visible documents of the given [`DocAddress`]es, returned in the same order.
+    /// See [`Searcher::docs`].
+    ///
+    /// Returns an error if one of the documents does not match the filter.
+    pub fn docs(&self, doc_addresses: &[DocAddress]) -> crate::Result<Vec<Document>> {
+        let mut doc_ids_by_segment: BTreeMap<SegmentOrdinal, Vec<DocId>> = BTreeMap::new();
+        for doc_address in doc_addresses {
+            doc_ids_by_segment
+                .entry(doc_address.segment_ord)
+                .or_default()
+                .push(doc_address.doc_id);
+        }
+        for (segment_ord, mut doc_ids) in doc_ids_by_segment {
+            let Some(segment_reader) = self.segment_readers.get(segment_ord as usize) else {
+                return Err(not_visible_error(DocAddress::new(segment_ord, doc_ids[0])));
+            };
+            doc_ids.sort_unstable();
+            doc_ids.dedup();
+            let mut scorer = self.filter_weight.scorer(segment_reader, 1.0)?;
+            for doc_id in doc_ids {
+                if doc_id >= segment_reader.max_doc()
+                    || segment_reader.is_deleted(doc_id)
+                    || scorer.seek(doc_id) != doc_id
+                {
+                    return Err(not_visible_error(DocAddress::new(segment_ord, doc_id)));
+                }
+            }
+        }
+        let docs = self.searcher.docs(doc_addresses)?;
+        let Some(allowed_fields) = &self.allowed_fields else {
+            return Ok(docs);
+        };
+        Ok(docs
+            .into_iter()
+            .map(|doc| {
+                doc.into_iter()
+                    .filter(|field_value| allowed_fields.contains(&field_value.field()))
+                    .collect::<Vec<_>>()
+                    .into()
+            })
+            .collect())
+    }
+
+    /// Runs a query on the documents matching the filter. See [`Searcher::search`].
+    pub fn search<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+    ) -> crate::Result<C::Fruit> {
+        let filtered_query = self.filtered_query(query);
+        let enabled_scoring = if collector.requires_scoring() {
+            EnableScoring::enabled_from_searcher(&self.searcher)
+        } else {
+            EnableScoring::disabled_from_searcher(&self.searcher)
+        };
+        let weight = filtered_query.weight(enabled_scoring)?;
+        let executor = self.searcher.index().search_executor();
+        collector.collect_segments(weight.as_ref(), &self.segment_readers, executor)
+    }
+
+    /// Returns the number of documents matching both the query and the filter.
+    pub fn count(&self, query: &dyn Query) -> crate::Result<usize> {
+        let weight = self
+            .filtered_query(query)
+            .weight(EnableScoring::disabled_from_searcher(&self.searcher))?;
+        let mut count = 0;
+        for segment_reader in &self.segment_readers {
+            count += weight.count(segment_reader)? as usize;
+        }
+        Ok(count)
+    }
+
+    fn filtered_query(&self, query: &dyn Query) -> BooleanQuery {
+        BooleanQuery::new(vec![
+            (Occur::Must, query.box_clone()),
+            (
+                Occur::Must,
+                Box::new(ConstScoreQuery::new(self.filter.box_clone(), 0.0)),
+            ),
+        ])
+    }
+}
+
+fn not_visible_error(doc_address: DocAddress) -> TantivyError {
+    TantivyError::InvalidArgument(format!(
+        "Document {doc_address:?} is not visible to the filtered searcher"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FilteredSearcher;
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::agg_result::AggregationResults;
+    use crate::aggregation::AggregationCollector;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, STORED, STRING, TEXT};
+    use crate::{doc, DocAddress, Index, Term};
+
+    #[test]
+    fn test_filtered_searcher() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING);
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let secret = schema_builder.add_u64_field("secret", FAST | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tenant => "a", title => "hello a", secret => 1u64))?;
+        index_writer.add_document(doc!(tenant => "b", title => "hello b", secret => 2u64))?;
+        index_writer.add_document(doc!(tenant => "a", title => "bye a", secret => 3u64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let filter = TermQuery::new(Term::from_field_text(tenant, "a"), IndexRecordOption::Basic);
+        let filtered_searcher =
+            FilteredSearcher::new(searcher, Box::new(filter))?.with_allowed_fields(vec![title]);
+
+        let hello = TermQuery::new(
+            Term::from_field_text(title, "hello"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(filtered_searcher.count(&AllQuery)?, 2);
+        assert_eq!(filtered_searcher.search(&AllQuery, &Count)?, 2);
+        let top_docs = filtered_searcher.search(&hello, &TopDocs::with_limit(10))?;
+        assert_eq!(top_docs.len(), 1);
+        let doc_address = top_docs[0].1;
+        let doc = filtered_searcher.doc(doc_address)?;
+        assert_eq!(doc.get_first(title).unwrap().as_text(), Some("hello a"));
+        assert!(doc.get_first(secret).is_none());
+        assert_eq!(filtered_searcher.docs(&[doc_address])?, vec![doc]);
+
+        let hidden_doc_address = DocAddress::new(0, 1);
+        assert!(!filtered_searcher.is_visible(hidden_doc_address)?);
+        assert!(filtered_searcher.doc(hidden_doc_address).is_err());
+        assert!(filtered_searcher
+            .docs(&[doc_address, hidden_doc_address])
+            .is_err());
+
+        let segment_reader = &filtered_searcher.segment_readers()[0];
+        assert!(segment_reader.fast_fields().u64("secret").is_err());
+        let aggregations: Aggregations = serde_json::from_value(serde_json::json!({
+            "secret_stats": { "stats": { "field": "secret" } }
+        }))
+        .unwrap();
+        let collector = AggregationCollector::from_aggs(aggregations, Default::default());
+        let aggregation_results: AggregationResults =
+            filtered_searcher.search(&AllQuery, &collector)?;
+        let aggregation_results = serde_json::to_value(aggregation_results).unwrap();
+        assert_eq!(aggregation_results["secret_stats"]["count"], 0);
+        Ok(())
+    }
+}
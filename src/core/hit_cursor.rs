@@ -0,0 +1,302 @@
+use columnar::{BytesColumn, Column, StrColumn};
+
+use crate::fastfield::FastFieldNotAvailableError;
+use crate::query::{EnableScoring, Query, Scorer, Weight};
+use crate::schema::{Type, Value};
+use crate::{DateTime, DocAddress, DocId, DocSet, Score, Searcher, SegmentReader, TERMINATED};
+
+/// The options of a [`HitCursor`]: whether the hits are scored, and the fast fields whose
+/// values are read for each hit.
+#[derive(Clone, Debug, Default)]
+pub struct HitCursorOptions {
+    fast_fields: Vec<String>,
+    with_scores: bool,
+}
+
+impl HitCursorOptions {
+    /// Also computes the scores of the hits.
+    #[must_use]
+    pub fn with_scores(mut self) -> HitCursorOptions {
+        self.with_scores = true;
+        self
+    }
+
+    /// Also reads the first value of the given fast fields for each hit.
+    #[must_use]
+    pub fn with_fast_fields<S: ToString>(
+        mut self,
+        fast_fields: impl IntoIterator<Item = S>,
+    ) -> HitCursorOptions {
+        self.fast_fields.extend(
+            fast_fields
+                .into_iter()
+                .map(|fast_field| fast_field.to_string()),
+        );
+        self
+    }
+}
+
+/// A document matching the query of a [`HitCursor`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct CursorHit {
+    /// The address of the document.
+    pub doc_address: DocAddress,
+    /// The score of the document, if the cursor computes the scores.
+    pub score: Option<Score>,
+    /// The first value of each of the fast fields of the cursor, in the order of the options,
+    /// or `None` if the document has no value for the field.
+    pub fast_field_values: Vec<Option<Value>>,
+}
+
+/// An iterator over the documents matching a query, in the order of their [`DocAddress`].
+///
+/// Unlike a collector, the cursor only moves forward as it is consumed: exporting millions of
+/// hits does not require to hold them all in memory, and a slow consumer naturally slows down
+/// the search.
+///
+/// The cursor holds its own [`Searcher`], so that it can be moved to another thread.
+/// See [`Searcher::hit_cursor`].
+pub struct HitCursor {
+    searcher: Searcher,
+    weight: Box<dyn Weight>,
+    options: HitCursorOptions,
+    segment_ord: usize,
+    segment_cursor: Option<SegmentCursor>,
+}
+
+struct SegmentCursor {
+    scorer: Box<dyn Scorer>,
+    columns: Vec<FastFieldColumn>,
+}
+
+impl HitCursor {
+    pub(crate) fn new(
+        searcher: &Searcher,
+        query: &dyn Query,
+        options: HitCursorOptions,
+    ) -> crate::Result<HitCursor> {
+        let schema = searcher.schema();
+        for fast_field in &options.fast_fields {
+            let field_entry = schema.get_field_entry(schema.get_field(fast_field)?);
+            if !field_entry.is_fast() {
+                return Err(FastFieldNotAvailableError::new(field_entry).into());
+            }
+        }
+        let enable_scoring = if options.with_scores {
+            EnableScoring::enabled_from_searcher(searcher)
+        } else {
+            EnableScoring::disabled_from_searcher(searcher)
+        };
+        let weight = query.weight(enable_scoring)?;
+        Ok(HitCursor {
+            searcher: searcher.clone(),
+            weight,
+            options,
+            segment_ord: 0,
+            segment_cursor: None,
+        })
+    }
+
+    fn open_segment_cursor(&self, segment_reader: &SegmentReader) -> crate::Result<SegmentCursor> {
+        let scorer = self.weight.scorer(segment_reader, 1.0)?;
+        let columns = self
+            .options
+            .fast_fields
+            .iter()
+            .map(|fast_field| FastFieldColumn::open(segment_reader, fast_field))
+            .collect::<crate::Result<_>>()?;
+        Ok(SegmentCursor { scorer, columns })
+    }
+}
+
+impl Iterator for HitCursor {
+    type Item = crate::Result<CursorHit>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.segment_ord < self.searcher.segment_readers().len() {
+            let segment_reader = self.searcher.segment_reader(self.segment_ord as u32);
+            if self.segment_cursor.is_none() {
+                match self.open_segment_cursor(segment_reader) {
+                    Ok(segment_cursor) => self.segment_cursor = Some(segment_cursor),
+                    Err(err) => {
+                        // The cursor ends after an error.
+                        self.segment_ord = self.searcher.segment_readers().len();
+                        return Some(Err(err));
+                    }
+                }
+            }
+            let segment_cursor = self.segment_cursor.as_mut()?;
+            let mut doc = segment_cursor.scorer.doc();
+            if let Some(alive_bitset) = segment_reader.alive_bitset() {
+                while doc != TERMINATED && alive_bitset.is_deleted(doc) {
+                    doc = segment_cursor.scorer.advance();
+                }
+            }
+            if doc == TERMINATED {
+                self.segment_cursor = None;
+                self.segment_ord += 1;
+                continue;
+            }
+            let score = if self.options.with_scores {
+                Some(segment_cursor.scorer.score())
+            } else {
+                None
+            };
+            let fast_field_values = segment_cursor
+                .columns
+                .iter()
+                .map(|column| column.first_value(doc))
+                .collect();
+            segment_cursor.scorer.advance();
+            return Some(Ok(CursorHit {
+                doc_address: DocAddress::new(self.segment_ord as u32, doc),
+                score,
+                fast_field_values,
+            }));
+        }
+        None
+    }
+}
+
+enum FastFieldColumn {
+    U64(Column<u64>),
+    I64(Column<i64>),
+    F64(Column<f64>),
+    Bool(Column<bool>),
+    Date(Column<DateTime>),
+    Str(StrColumn),
+    Bytes(BytesColumn),
+    Missing,
+}
+
+impl FastFieldColumn {
+    fn open(segment: &SegmentReader, field_name: &str) -> crate::Result<Self> {
+        let field = segment.schema().get_field(field_name)?;
+        let value_type = segment
+            .schema()
+            .get_field_entry(field)
+            .field_type()
+            .value_type();
+        let fast_fields = segment.fast_fields();
+        let column = match value_type {
+            Type::U64 => fast_fields
+                .column_opt(field_name)?
+                .map(FastFieldColumn::U64),
+            Type::I64 => fast_fields
+                .column_opt(field_name)?
+                .map(FastFieldColumn::I64),
+            Type::F64 => fast_fields
+                .column_opt(field_name)?
+                .map(FastFieldColumn::F64),
+            Type::Bool => fast_fields
+                .column_opt(field_name)?
+                .map(FastFieldColumn::Bool),
+            Type::Date => fast_fields
+                .column_opt(field_name)?
+                .map(FastFieldColumn::Date),
+            Type::Str => fast_fields.str(field_name)?.map(FastFieldColumn::Str),
+            Type::Bytes => fast_fields.bytes(field_name)?.map(FastFieldColumn::Bytes),
+            _ => None,
+        };
+        Ok(column.unwrap_or(FastFieldColumn::Missing))
+    }
+
+    fn first_value(&self, doc: DocId) -> Option<Value> {
+        match self {
+            FastFieldColumn::U64(column) => column.first(doc).map(Value::U64),
+            FastFieldColumn::I64(column) => column.first(doc).map(Value::I64),
+            FastFieldColumn::F64(column) => column.first(doc).map(Value::F64),
+            FastFieldColumn::Bool(column) => column.first(doc).map(Value::Bool),
+            FastFieldColumn::Date(column) => column.first(doc).map(Value::Date),
+            FastFieldColumn::Str(column) => {
+                let term_ord = column.term_ords(doc).next()?;
+                let mut text = String::new();
+                column.ord_to_str(term_ord, &mut text).ok()?;
+                Some(Value::Str(text))
+            }
+            FastFieldColumn::Bytes(column) => {
+                let term_ord = column.term_ords(doc).next()?;
+                let mut bytes = Vec::new();
+                column.ord_to_bytes(term_ord, &mut bytes).ok()?;
+                Some(Value::Bytes(bytes))
+            }
+            FastFieldColumn::Missing => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HitCursorOptions;
+    use crate::query::{AllQuery, QueryParser};
+    use crate::schema::{Schema, Value, FAST, INDEXED, STRING, TEXT};
+    use crate::{doc, DocAddress, Index, Term};
+
+    #[test]
+    fn test_hit_cursor() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let id = schema_builder.add_u64_field("id", FAST | INDEXED);
+        let tag = schema_builder.add_text_field("tag", STRING | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "hello", id => 0u64, tag => "a"))?;
+        index_writer.add_document(doc!(text => "hello world", id => 1u64))?;
+        index_writer.add_document(doc!(text => "other", id => 2u64, tag => "c"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text => "hello", id => 3u64, tag => "d"))?;
+        index_writer.delete_term(Term::from_field_u64(id, 0));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let old_segment_ord = searcher
+            .segment_readers()
+            .iter()
+            .position(|segment_reader| segment_reader.max_doc() == 3)
+            .unwrap() as u32;
+
+        let query = QueryParser::for_index(&index, vec![text]).parse_query("hello")?;
+        let options = HitCursorOptions::default()
+            .with_scores()
+            .with_fast_fields(["id", "tag"]);
+        let hits = searcher
+            .hit_cursor(query.as_ref(), options)?
+            .collect::<crate::Result<Vec<_>>>()?;
+        assert_eq!(hits.len(), 2);
+        let hit = hits
+            .iter()
+            .find(|hit| hit.doc_address == DocAddress::new(old_segment_ord, 1))
+            .unwrap();
+        assert!(hit.score.unwrap() > 0.0);
+        assert_eq!(hit.fast_field_values, vec![Some(Value::U64(1)), None]);
+        let hit = hits
+            .iter()
+            .find(|hit| hit.doc_address.segment_ord != old_segment_ord)
+            .unwrap();
+        assert_eq!(
+            hit.fast_field_values,
+            vec![Some(Value::U64(3)), Some(Value::Str("d".to_string()))]
+        );
+
+        // Without scores, the hits come in the order of their doc addresses.
+        let doc_addresses: Vec<DocAddress> = searcher
+            .hit_cursor(&AllQuery, HitCursorOptions::default())?
+            .map(|hit| hit.map(|hit| (hit.score, hit.doc_address)))
+            .collect::<crate::Result<Vec<_>>>()?
+            .into_iter()
+            .map(|(score, doc_address)| {
+                assert!(score.is_none());
+                doc_address
+            })
+            .collect();
+        assert_eq!(doc_addresses.len(), 3);
+        assert!(doc_addresses.windows(2).all(|pair| pair[0] < pair[1]));
+
+        assert!(searcher
+            .hit_cursor(
+                &AllQuery,
+                HitCursorOptions::default().with_fast_fields(["text"])
+            )
+            .is_err());
+        Ok(())
+    }
+}
@@ -1,25 +1,32 @@
-use std::collections::HashSet;
-use std::fmt;
+use std::collections::{BTreeMap, HashSet};
 #[cfg(feature = "mmap")]
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::{fmt, io};
 
+use super::archive::{export_index, import_index};
+use super::index_check::{check_index, IndexCheckReport};
 use super::segment::Segment;
 use super::IndexSettings;
+use crate::codec::CodecManager;
 use crate::core::single_segment_index_writer::SingleSegmentIndexWriter;
 use crate::core::{
-    Executor, IndexMeta, SegmentId, SegmentMeta, SegmentMetaInventory, META_FILEPATH,
+    Executor, IndexMeta, IndexSnapshot, SegmentId, SegmentMeta, SegmentMetaInventory, META_FILEPATH,
 };
 use crate::directory::error::OpenReadError;
 #[cfg(feature = "mmap")]
 use crate::directory::MmapDirectory;
 use crate::directory::{Directory, ManagedDirectory, RamDirectory, INDEX_WRITER_LOCK};
 use crate::error::{DataCorruption, TantivyError};
+use crate::fastfield::RuntimeField;
 use crate::indexer::index_writer::{MAX_NUM_THREAD, MEMORY_ARENA_NUM_BYTES_MIN};
 use crate::indexer::segment_updater::save_metas;
-use crate::reader::{IndexReader, IndexReaderBuilder};
+use crate::indexer::MemoryBudget;
+use crate::metrics::MetricsRecorder;
+use crate::reader::{IndexReader, IndexReaderBuilder, NrtSegments};
 use crate::schema::{Field, FieldType, Schema};
+use crate::store::Compressor;
 use crate::tokenizer::{TextAnalyzer, TokenizerManager};
 use crate::IndexWriter;
 
@@ -57,6 +64,7 @@ fn load_metas(
 fn save_new_metas(
     schema: Schema,
     index_settings: IndexSettings,
+    tokenizers: &TokenizerManager,
     directory: &dyn Directory,
 ) -> crate::Result<()> {
     save_metas(
@@ -66,6 +74,8 @@ fn save_new_metas(
             schema,
             opstamp: 0u64,
             payload: None,
+            metadata: BTreeMap::new(),
+            tokenizers: tokenizers.configs(),
         },
         directory,
     )?;
@@ -108,6 +118,7 @@ pub struct IndexBuilder {
     index_settings: IndexSettings,
     tokenizer_manager: TokenizerManager,
     fast_field_tokenizer_manager: TokenizerManager,
+    codec_manager: CodecManager,
 }
 impl Default for IndexBuilder {
     fn default() -> Self {
@@ -122,6 +133,7 @@ impl IndexBuilder {
             index_settings: IndexSettings::default(),
             tokenizer_manager: TokenizerManager::default(),
             fast_field_tokenizer_manager: TokenizerManager::default(),
+            codec_manager: CodecManager::default(),
         }
     }
 
@@ -151,6 +163,12 @@ impl IndexBuilder {
         self
     }
 
+    /// Set the codecs.
+    pub fn codecs(mut self, codecs: CodecManager) -> Self {
+        self.codec_manager = codecs;
+        self
+    }
+
     /// Creates a new index using the [`RamDirectory`].
     ///
     /// The index will be allocated in anonymous memory.
@@ -217,14 +235,32 @@ impl IndexBuilder {
     }
 
     /// Opens or creates a new index in the provided directory
+    ///
+    /// If the index exists, the schema passed to the builder needs to either be
+    /// equal to the schema of the index, or to only add new fields to it
+    /// (see [`Schema::is_extension_of`]). In the latter case, the index is opened with
+    /// the new schema, which will be persisted at the next commit.
     pub fn open_or_create<T: Into<Box<dyn Directory>>>(self, dir: T) -> crate::Result<Index> {
         let dir = dir.into();
         if !Index::exists(&*dir)? {
             return self.create(dir);
         }
         let mut index = Index::open(dir)?;
+        // The tokenizers persisted in the index meta are kept, unless the builder
+        // registers a tokenizer with the same name.
+        for (tokenizer_name, config) in index.tokenizers().configs() {
+            if self.tokenizer_manager.get(&tokenizer_name).is_none() {
+                self.tokenizer_manager
+                    .register_config(&tokenizer_name, config)?;
+            }
+        }
         index.set_tokenizers(self.tokenizer_manager.clone());
-        if index.schema() == self.get_expect_schema()? {
+        index.set_codecs(self.codec_manager.clone());
+        let expected_schema = self.get_expect_schema()?;
+        if index.schema() == expected_schema {
+            Ok(index)
+        } else if expected_schema.is_extension_of(&index.schema) {
+            index.schema = expected_schema;
             Ok(index)
         } else {
             Err(TantivyError::SchemaError(
@@ -234,6 +270,24 @@ impl IndexBuilder {
     }
 
     fn validate(&self) -> crate::Result<()> {
+        let docstore_compression = self.index_settings.docstore_compression;
+        if !docstore_compression.is_available() {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Docstore compressor {docstore_compression:?} is not available. The corresponding \
+                 feature flag needs to be enabled."
+            )));
+        }
+        let codec_name = &self.index_settings.codec;
+        if self.codec_manager.get(codec_name).is_none() {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Codec {codec_name:?} is not registered."
+            )));
+        }
+        if self.index_settings.docstore_blocksize == 0 {
+            return Err(TantivyError::InvalidArgument(
+                "Docstore block size needs to be strictly positive".to_string(),
+            ));
+        }
         if let Some(schema) = self.schema.as_ref() {
             if let Some(sort_by_field) = self.index_settings.sort_by_field.as_ref() {
                 let schema_field = schema.get_field(&sort_by_field.field).map_err(|_| {
@@ -269,6 +323,7 @@ impl IndexBuilder {
         save_new_metas(
             self.get_expect_schema()?,
             self.index_settings.clone(),
+            &self.tokenizer_manager,
             &directory,
         )?;
         let mut metas = IndexMeta::with_schema(self.get_expect_schema()?);
@@ -276,6 +331,7 @@ impl IndexBuilder {
         let mut index = Index::open_from_metas(directory, &metas, SegmentMetaInventory::default());
         index.set_tokenizers(self.tokenizer_manager);
         index.set_fast_field_tokenizers(self.fast_field_tokenizer_manager);
+        index.set_codecs(self.codec_manager);
         Ok(index)
     }
 }
@@ -287,9 +343,13 @@ pub struct Index {
     schema: Schema,
     settings: IndexSettings,
     executor: Arc<Executor>,
+    metrics_recorder: Option<Arc<dyn MetricsRecorder>>,
     tokenizers: TokenizerManager,
     fast_field_tokenizers: TokenizerManager,
+    codecs: CodecManager,
+    runtime_fields: Vec<RuntimeField>,
     inventory: SegmentMetaInventory,
+    nrt_segments: NrtSegments,
 }
 
 impl Index {
@@ -321,6 +381,19 @@ impl Index {
         Ok(())
     }
 
+    /// Sets the recorder receiving the metrics of the operations of the index.
+    ///
+    /// Like the executor, the recorder is shared by the writers and the readers created from
+    /// now on. See the [`metrics`](crate::metrics) module.
+    pub fn set_metrics_recorder(&mut self, metrics_recorder: Arc<dyn MetricsRecorder>) {
+        self.metrics_recorder = Some(metrics_recorder);
+    }
+
+    /// Accessor to the metrics recorder, if any.
+    pub fn metrics_recorder(&self) -> Option<&dyn MetricsRecorder> {
+        self.metrics_recorder.as_deref()
+    }
+
     /// Replace the default single thread search executor pool
     /// by a thread pool with as many threads as there are CPUs on the system.
     pub fn set_default_multithread_executor(&mut self) -> crate::Result<()> {
@@ -402,8 +475,12 @@ impl Index {
             schema,
             tokenizers: TokenizerManager::default(),
             fast_field_tokenizers: TokenizerManager::default(),
+            codecs: CodecManager::default(),
+            runtime_fields: Vec::new(),
             executor: Arc::new(Executor::single_thread()),
+            metrics_recorder: None,
             inventory,
+            nrt_segments: NrtSegments::default(),
         }
     }
 
@@ -427,6 +504,45 @@ impl Index {
         &self.fast_field_tokenizers
     }
 
+    /// Setter for the codec manager.
+    pub fn set_codecs(&mut self, codecs: CodecManager) {
+        self.codecs = codecs;
+    }
+
+    /// Accessor for the codec manager.
+    ///
+    /// The codecs of the segments need to be registered before opening the readers or the
+    /// writers of the index.
+    pub fn codecs(&self) -> &CodecManager {
+        &self.codecs
+    }
+
+    /// Registers a runtime field, computed at query time from the fast fields.
+    ///
+    /// The runtime field is available to the segment readers opened after its
+    /// registration. Returns an error if its name is already used by a field
+    /// of the schema or by another runtime field.
+    pub fn register_runtime_field(&mut self, runtime_field: RuntimeField) -> crate::Result<()> {
+        let name = runtime_field.name();
+        if self.schema.get_field(name).is_ok()
+            || self
+                .runtime_fields
+                .iter()
+                .any(|registered_field| registered_field.name() == name)
+        {
+            return Err(TantivyError::SchemaError(format!(
+                "Runtime field {name:?} conflicts with an existing field"
+            )));
+        }
+        self.runtime_fields.push(runtime_field);
+        Ok(())
+    }
+
+    /// Accessor for the runtime fields of the index.
+    pub fn runtime_fields(&self) -> &[RuntimeField] {
+        &self.runtime_fields
+    }
+
     /// Get the tokenizer associated with a specific field.
     pub fn tokenizer_for_field(&self, field: Field) -> crate::Result<TextAnalyzer> {
         let field_entry = self.schema.get_field_entry(field);
@@ -488,13 +604,20 @@ impl Index {
         self.inventory.all()
     }
 
+    /// Returns the segments published for the near real time readers.
+    pub(crate) fn nrt_segments(&self) -> &NrtSegments {
+        &self.nrt_segments
+    }
+
     /// Creates a new segment_meta (Advanced user only).
     ///
     /// As long as the `SegmentMeta` lives, the files associated with the
     /// `SegmentMeta` are guaranteed to not be garbage collected, regardless of
     /// whether the segment is recorded as part of the index or not.
     pub fn new_segment_meta(&self, segment_id: SegmentId, max_doc: u32) -> SegmentMeta {
-        self.inventory.new_segment_meta(segment_id, max_doc)
+        self.inventory
+            .new_segment_meta(segment_id, max_doc)
+            .with_codec(&self.settings.codec)
     }
 
     /// Open the index using the provided directory
@@ -504,6 +627,11 @@ impl Index {
         let inventory = SegmentMetaInventory::default();
         let metas = load_metas(&directory, &inventory)?;
         let index = Index::open_from_metas(directory, &metas, inventory);
+        for (tokenizer_name, config) in &metas.tokenizers {
+            index
+                .tokenizers()
+                .register_config(tokenizer_name, config.clone())?;
+        }
         Ok(index)
     }
 
@@ -512,6 +640,59 @@ impl Index {
         load_metas(self.directory(), &self.inventory)
     }
 
+    /// Takes a snapshot of the last commit of the index, which keeps its files alive until it
+    /// is dropped, to back it up.
+    ///
+    /// See [`IndexSnapshot`].
+    pub fn snapshot(&self) -> crate::Result<IndexSnapshot> {
+        IndexSnapshot::new(self)
+    }
+
+    /// Writes the last commit of the index as a single archive, which [`Index::import()`] turns
+    /// back into an index, possibly on another machine.
+    ///
+    /// The archive holds the files of the segments, along with the `meta.json` file and its
+    /// schema, each chunk of it being checksummed. The index is exported from a snapshot, so
+    /// that it can be written to in the meantime. The files are not compressed: see
+    /// [`Index::export_with_compressor()`].
+    pub fn export<W: io::Write>(&self, mut writer: W) -> crate::Result<()> {
+        export_index(self, &mut writer, Compressor::None)
+    }
+
+    /// Writes the last commit of the index as a single archive, as [`Index::export()`] does,
+    /// compressing the files with the given compressor.
+    ///
+    /// The compressor is recorded in the archive, which can only be imported with its feature
+    /// flag enabled.
+    pub fn export_with_compressor<W: io::Write>(
+        &self,
+        mut writer: W,
+        compressor: Compressor,
+    ) -> crate::Result<()> {
+        export_index(self, &mut writer, compressor)
+    }
+
+    /// Imports an archive written by [`Index::export()`] into a directory, and opens the
+    /// imported index.
+    ///
+    /// The checksums of the archive are validated as it is read, and an error is returned if
+    /// they do not match, or if the directory already holds an index. The `meta.json` file is
+    /// written last: if the import fails, the directory does not hold an index, though it may
+    /// keep some of the imported files.
+    pub fn import<R: io::Read, T: Into<Box<dyn Directory>>>(
+        mut reader: R,
+        directory: T,
+    ) -> crate::Result<Index> {
+        import_index(&mut reader, directory.into())
+    }
+
+    /// Reads the metadata attached to the last commit.
+    ///
+    /// See [`PreparedCommit::set_metadata()`](crate::PreparedCommit::set_metadata).
+    pub fn commit_metadata(&self) -> crate::Result<BTreeMap<String, String>> {
+        Ok(self.load_metas()?.metadata)
+    }
+
     /// Open a new index writer. Attempts to acquire a lockfile.
     ///
     /// The lockfile should be deleted on drop, but it is possible
@@ -555,6 +736,45 @@ impl Index {
             self,
             num_threads,
             memory_arena_in_bytes_per_thread,
+            None,
+            directory_lock,
+        )
+    }
+
+    /// Same as [`writer_with_num_threads()`](Index::writer_with_num_threads), but the memory
+    /// is taken from a [`MemoryBudget`] shared with the other writers created with this budget.
+    ///
+    /// Each thread may use up to `memory_budget.num_bytes() / num_threads` bytes, and the
+    /// indexing workers of all of the writers flush their segments as soon as they use the whole
+    /// budget overall.
+    ///
+    /// # Errors
+    /// Same as [`writer_with_num_threads()`](Index::writer_with_num_threads).
+    pub fn writer_with_memory_budget(
+        &self,
+        num_threads: usize,
+        memory_budget: &MemoryBudget,
+    ) -> crate::Result<IndexWriter> {
+        let directory_lock = self
+            .directory
+            .acquire_lock(&INDEX_WRITER_LOCK)
+            .map_err(|err| {
+                TantivyError::LockFailure(
+                    err,
+                    Some(
+                        "Failed to acquire index lock. If you are using a regular directory, this \
+                         means there is already an `IndexWriter` working on this `Directory`, in \
+                         this process or in a different process."
+                            .to_string(),
+                    ),
+                )
+            })?;
+        let memory_arena_in_bytes_per_thread = memory_budget.num_bytes() / num_threads;
+        IndexWriter::new(
+            self,
+            num_threads,
+            memory_arena_in_bytes_per_thread,
+            Some(memory_budget.clone()),
             directory_lock,
         )
     }
@@ -621,9 +841,7 @@ impl Index {
 
     /// Creates a new segment.
     pub fn new_segment(&self) -> Segment {
-        let segment_meta = self
-            .inventory
-            .new_segment_meta(SegmentId::generate_random(), 0);
+        let segment_meta = self.new_segment_meta(SegmentId::generate_random(), 0);
         self.segment(segment_meta)
     }
 
@@ -652,6 +870,21 @@ impl Index {
             .collect())
     }
 
+    /// Checks the integrity of the searchable segments of the index, and reports the problems
+    /// found in each of them.
+    ///
+    /// The checksums of the files of the segments are verified, every postings list is decoded,
+    /// the number of documents of the fieldnorms, of the fast fields and of the doc store is
+    /// compared with the one of the segment, and every document of the store is decoded.
+    ///
+    /// This reads the whole index, and is meant to be run offline, e.g. before serving an index
+    /// restored from a backup. The errors and panics of the readers are reported as problems of
+    /// their segment, and the corrupt segments can then be dropped with
+    /// [`IndexWriter::drop_segments()`](crate::IndexWriter::drop_segments).
+    pub fn check_index(&self) -> crate::Result<IndexCheckReport> {
+        check_index(self)
+    }
+
     /// Returns the set of corrupted files
     pub fn validate_checksum(&self) -> crate::Result<HashSet<PathBuf>> {
         let managed_files = self.directory.list_managed_files();
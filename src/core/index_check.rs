@@ -0,0 +1,300 @@
+use std::any::Any;
+use std::collections::HashSet;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+
+use columnar::ColumnIndex;
+
+use crate::core::{Index, SegmentMeta, SegmentReader};
+use crate::postings::Postings;
+use crate::{DocId, DocSet, SegmentId, TERMINATED};
+
+/// The problems found in the segments of an index by [`Index::check_index`].
+#[derive(Clone, Debug, Default)]
+pub struct IndexCheckReport {
+    /// The checks of the searchable segments.
+    pub segments: Vec<SegmentCheckReport>,
+}
+
+impl IndexCheckReport {
+    /// Returns true if no problem was found.
+    pub fn is_ok(&self) -> bool {
+        self.segments.iter().all(SegmentCheckReport::is_ok)
+    }
+
+    /// Returns the ids of the segments in which a problem was found.
+    ///
+    /// These segments can be dropped with
+    /// [`IndexWriter::drop_segments`](crate::IndexWriter::drop_segments).
+    pub fn corrupt_segment_ids(&self) -> Vec<SegmentId> {
+        self.segments
+            .iter()
+            .filter(|segment_check| !segment_check.is_ok())
+            .map(|segment_check| segment_check.segment_id)
+            .collect()
+    }
+}
+
+/// The problems found in one segment by [`Index::check_index`].
+#[derive(Clone, Debug)]
+pub struct SegmentCheckReport {
+    /// The id of the segment.
+    pub segment_id: SegmentId,
+    /// The number of documents of the segment, including the deleted ones.
+    pub max_doc: DocId,
+    /// The description of each problem found.
+    pub problems: Vec<String>,
+}
+
+impl SegmentCheckReport {
+    /// Returns true if no problem was found.
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+fn panic_message(panic_payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = panic_payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic_payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Runs one of the checks, turning its errors and panics into problems: corrupted data can
+/// make the readers panic rather than return an error.
+fn run_check(
+    check_name: &str,
+    problems: &mut Vec<String>,
+    check: impl FnOnce(&mut Vec<String>) -> crate::Result<()>,
+) {
+    match panic::catch_unwind(AssertUnwindSafe(|| check(problems))) {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => problems.push(format!("{check_name}: {err}")),
+        Err(panic_payload) => problems.push(format!(
+            "{check_name}: panicked with {:?}",
+            panic_message(panic_payload)
+        )),
+    }
+}
+
+fn check_postings(segment_reader: &SegmentReader, problems: &mut Vec<String>) -> crate::Result<()> {
+    let max_doc = segment_reader.max_doc();
+    for (field, field_entry) in segment_reader.schema().fields() {
+        let Some(record_option) = field_entry.field_type().get_index_record_option() else {
+            continue;
+        };
+        let inverted_index = segment_reader.inverted_index(field)?;
+        let mut term_stream = inverted_index.terms().stream()?;
+        while term_stream.advance() {
+            let term_info = term_stream.value();
+            let mut postings =
+                inverted_index.read_postings_from_terminfo(term_info, record_option)?;
+            let mut num_docs = 0u32;
+            let mut previous_doc: Option<DocId> = None;
+            let mut doc = postings.doc();
+            while doc != TERMINATED {
+                if doc >= max_doc || previous_doc.map_or(false, |previous_doc| doc <= previous_doc)
+                {
+                    problems.push(format!(
+                        "The postings of a term of the field {:?} have the invalid doc id {doc}",
+                        field_entry.name()
+                    ));
+                    break;
+                }
+                if record_option.has_freq() && postings.term_freq() == 0 {
+                    problems.push(format!(
+                        "The postings of a term of the field {:?} have a zero term frequency",
+                        field_entry.name()
+                    ));
+                    break;
+                }
+                previous_doc = Some(doc);
+                num_docs += 1;
+                doc = postings.advance();
+            }
+            if num_docs != term_info.doc_freq {
+                problems.push(format!(
+                    "The postings of a term of the field {:?} hold {num_docs} documents, while \
+                     the term dictionary records {}",
+                    field_entry.name(),
+                    term_info.doc_freq
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_fieldnorms(
+    segment_reader: &SegmentReader,
+    problems: &mut Vec<String>,
+) -> crate::Result<()> {
+    let max_doc = segment_reader.max_doc();
+    for (field, field_entry) in segment_reader.schema().fields() {
+        if !field_entry.is_indexed() || !field_entry.has_fieldnorms() {
+            continue;
+        }
+        let Some(fieldnorm_reader) = segment_reader.fieldnorms_readers().get_field(field)? else {
+            continue;
+        };
+        if fieldnorm_reader.num_docs() != max_doc {
+            problems.push(format!(
+                "The fieldnorms of the field {:?} have {} documents instead of {max_doc}",
+                field_entry.name(),
+                fieldnorm_reader.num_docs()
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn check_fast_fields(
+    segment_reader: &SegmentReader,
+    problems: &mut Vec<String>,
+) -> crate::Result<()> {
+    let max_doc = segment_reader.max_doc();
+    let columnar = segment_reader.fast_fields().columnar();
+    if columnar.num_rows() != max_doc {
+        problems.push(format!(
+            "The fast fields have {} documents instead of {max_doc}",
+            columnar.num_rows()
+        ));
+    }
+    for (column_name, column_handle) in columnar.list_columns()? {
+        let column = column_handle.open()?;
+        let num_docs = match column.column_index() {
+            ColumnIndex::Empty { num_docs } => *num_docs,
+            ColumnIndex::Full => column.num_values(),
+            ColumnIndex::Optional(optional_index) => optional_index.num_docs(),
+            ColumnIndex::Multivalued(multivalued_index) => multivalued_index.num_docs(),
+        };
+        if num_docs != max_doc {
+            problems.push(format!(
+                "The fast field column {column_name:?} has {num_docs} documents instead of \
+                 {max_doc}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn check_store(segment_reader: &SegmentReader, problems: &mut Vec<String>) -> crate::Result<()> {
+    let max_doc = segment_reader.max_doc();
+    let store_reader = segment_reader.get_store_reader(1)?;
+    let mut num_docs = 0u32;
+    for doc_res in store_reader.iter(None) {
+        doc_res?;
+        num_docs += 1;
+    }
+    if num_docs != max_doc {
+        problems.push(format!(
+            "The doc store has {num_docs} documents instead of {max_doc}"
+        ));
+    }
+    Ok(())
+}
+
+fn check_segment(
+    index: &Index,
+    segment_meta: &SegmentMeta,
+    managed_files: &HashSet<PathBuf>,
+) -> SegmentCheckReport {
+    let mut problems = Vec::new();
+    let mut segment_files: Vec<PathBuf> = segment_meta
+        .list_files()
+        .into_iter()
+        .filter(|path| managed_files.contains(path))
+        .collect();
+    segment_files.sort();
+    for path in &segment_files {
+        match index.directory().validate_checksum(path) {
+            Ok(true) => {}
+            Ok(false) => problems.push(format!("The checksum of the file {path:?} is invalid")),
+            Err(err) => problems.push(format!("The file {path:?} cannot be read: {err}")),
+        }
+    }
+    let segment = index.segment(segment_meta.clone());
+    let mut segment_reader_opt = None;
+    run_check("Opening the segment", &mut problems, |_| {
+        segment_reader_opt = Some(SegmentReader::open(&segment)?);
+        Ok(())
+    });
+    if let Some(segment_reader) = segment_reader_opt {
+        run_check("Checking the postings", &mut problems, |problems| {
+            check_postings(&segment_reader, problems)
+        });
+        run_check("Checking the fieldnorms", &mut problems, |problems| {
+            check_fieldnorms(&segment_reader, problems)
+        });
+        run_check("Checking the fast fields", &mut problems, |problems| {
+            check_fast_fields(&segment_reader, problems)
+        });
+        run_check("Checking the doc store", &mut problems, |problems| {
+            check_store(&segment_reader, problems)
+        });
+    }
+    SegmentCheckReport {
+        segment_id: segment_meta.id(),
+        max_doc: segment_meta.max_doc(),
+        problems,
+    }
+}
+
+/// Checks the integrity of the searchable segments of the index.
+pub(crate) fn check_index(index: &Index) -> crate::Result<IndexCheckReport> {
+    let managed_files = index.directory().list_managed_files();
+    let segments = index
+        .searchable_segment_metas()?
+        .iter()
+        .map(|segment_meta| check_segment(index, segment_meta, &managed_files))
+        .collect();
+    Ok(IndexCheckReport { segments })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::directory::Directory;
+    use crate::schema::{Schema, FAST, STORED, TEXT};
+    use crate::{doc, Index, SegmentComponent};
+
+    #[test]
+    fn test_check_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT | STORED);
+        let num = schema_builder.add_u64_field("num", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "hello world", num => 1u64))?;
+        index_writer.add_document(doc!(text => "hello"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text => "another segment", num => 2u64))?;
+        index_writer.commit()?;
+        let report = index.check_index()?;
+        assert_eq!(report.segments.len(), 2);
+        assert!(report.is_ok());
+
+        // Corrupts the doc store of one of the segments.
+        let segment_meta = index.searchable_segment_metas()?[0].clone();
+        let store_path = segment_meta.relative_path(SegmentComponent::Store);
+        let mut store_bytes = index
+            .directory()
+            .open_read(&store_path)?
+            .read_bytes()?
+            .to_vec();
+        store_bytes[0] ^= 0xFF;
+        index.directory().atomic_write(&store_path, &store_bytes)?;
+
+        let report = index.check_index()?;
+        assert!(!report.is_ok());
+        assert_eq!(report.corrupt_segment_ids(), vec![segment_meta.id()]);
+
+        index_writer.drop_segments(&report.corrupt_segment_ids())?;
+        index_writer.commit()?;
+        assert_eq!(index.searchable_segment_metas()?.len(), 1);
+        assert!(index.check_index()?.is_ok());
+        Ok(())
+    }
+}
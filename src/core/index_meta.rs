@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt;
 use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
@@ -7,9 +7,11 @@ use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 
 use super::SegmentComponent;
+use crate::codec::DEFAULT_CODEC_NAME;
 use crate::core::SegmentId;
 use crate::schema::Schema;
 use crate::store::Compressor;
+use crate::tokenizer::TextAnalyzerConfig;
 use crate::{Inventory, Opstamp, TrackedObject};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -39,6 +41,7 @@ impl SegmentMetaInventory {
             max_doc,
             include_temp_doc_store: Arc::new(AtomicBool::new(true)),
             deletes: None,
+            codec: default_codec_name(),
         };
         SegmentMeta::from(self.inventory.track(inner))
     }
@@ -103,6 +106,11 @@ impl SegmentMeta {
             .unwrap_or(0u32)
     }
 
+    /// Returns the name of the [`Codec`](crate::codec::Codec) which wrote the segment.
+    pub fn codec(&self) -> &str {
+        &self.tracked.codec
+    }
+
     /// Returns the list of files that
     /// are required for the segment meta.
     /// Note: Some of the returned files may not exist depending on the state of the segment.
@@ -141,6 +149,11 @@ impl SegmentMeta {
             SegmentComponent::TempStore => ".store.temp".to_string(),
             SegmentComponent::FastFields => ".fast".to_string(),
             SegmentComponent::FieldNorms => ".fieldnorm".to_string(),
+            SegmentComponent::Points => ".points".to_string(),
+            SegmentComponent::TermVectors => ".tv".to_string(),
+            SegmentComponent::Payloads => ".pay".to_string(),
+            SegmentComponent::Completions => ".compl".to_string(),
+            SegmentComponent::Hnsw => ".hnsw".to_string(),
             SegmentComponent::Delete => format!(".{}.del", self.delete_opstamp().unwrap_or(0)),
         });
         PathBuf::from(path)
@@ -187,6 +200,21 @@ impl SegmentMeta {
             max_doc,
             deletes: None,
             include_temp_doc_store: Arc::new(AtomicBool::new(true)),
+            codec: inner_meta.codec.clone(),
+        });
+        SegmentMeta { tracked }
+    }
+
+    /// Updates the name of the codec writing the segment.
+    ///
+    /// This method is only used when creating a new segment.
+    pub(crate) fn with_codec(self, codec: &str) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            segment_id: inner_meta.segment_id,
+            max_doc: inner_meta.max_doc,
+            deletes: inner_meta.deletes.clone(),
+            include_temp_doc_store: Arc::new(AtomicBool::new(true)),
+            codec: codec.to_string(),
         });
         SegmentMeta { tracked }
     }
@@ -207,6 +235,7 @@ impl SegmentMeta {
             max_doc: inner_meta.max_doc,
             include_temp_doc_store: Arc::new(AtomicBool::new(true)),
             deletes: Some(delete_meta),
+            codec: inner_meta.codec.clone(),
         });
         SegmentMeta { tracked }
     }
@@ -222,11 +251,24 @@ struct InnerSegmentMeta {
     #[serde(skip)]
     #[serde(default = "default_temp_store")]
     pub(crate) include_temp_doc_store: Arc<AtomicBool>,
+    /// The name of the codec which wrote the segment.
+    #[serde(default = "default_codec_name")]
+    #[serde(skip_serializing_if = "is_default_codec_name")]
+    codec: String,
 }
 fn default_temp_store() -> Arc<AtomicBool> {
     Arc::new(AtomicBool::new(false))
 }
 
+/// Must be a function to be compatible with serde defaults
+fn default_codec_name() -> String {
+    DEFAULT_CODEC_NAME.to_string()
+}
+
+fn is_default_codec_name(codec_name: &str) -> bool {
+    codec_name == DEFAULT_CODEC_NAME
+}
+
 impl InnerSegmentMeta {
     pub fn track(self, inventory: &SegmentMetaInventory) -> SegmentMeta {
         SegmentMeta {
@@ -254,6 +296,10 @@ pub struct IndexSettings {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sort_by_field: Option<IndexSortByField>,
     /// The `Compressor` used to compress the doc store.
+    ///
+    /// `Compressor::None` gives the fastest access to stored documents, while
+    /// `Compressor::Zstd` with a high compression level gives the smallest doc store.
+    /// The compressor needs its feature flag (e.g. `zstd-compression`) to be enabled.
     #[serde(default)]
     pub docstore_compression: Compressor,
     /// If set to true, docstore compression will happen on a dedicated thread.
@@ -264,7 +310,18 @@ pub struct IndexSettings {
     pub docstore_compress_dedicated_thread: bool,
     #[serde(default = "default_docstore_blocksize")]
     /// The size of each block that will be compressed and written to disk
+    ///
+    /// Larger blocks compress better, but a whole block needs to be decompressed to
+    /// access a single document.
     pub docstore_blocksize: usize,
+    /// The name of the [`Codec`](crate::codec::Codec) writing the postings, the term
+    /// dictionaries and the fast fields of the new segments.
+    ///
+    /// The codec needs to be registered in the [`CodecManager`](crate::codec::CodecManager) of
+    /// the index. (defaults: the [`DefaultCodec`](crate::codec::DefaultCodec))
+    #[serde(default = "default_codec_name")]
+    #[serde(skip_serializing_if = "is_default_codec_name")]
+    pub codec: String,
 }
 
 /// Must be a function to be compatible with serde defaults
@@ -279,6 +336,7 @@ impl Default for IndexSettings {
             docstore_compression: Compressor::default(),
             docstore_blocksize: default_docstore_blocksize(),
             docstore_compress_dedicated_thread: true,
+            codec: default_codec_name(),
         }
     }
 }
@@ -339,6 +397,18 @@ pub struct IndexMeta {
     /// This payload is entirely unused by tantivy.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub payload: Option<String>,
+    /// Key-value metadata associated with the last commit.
+    ///
+    /// Like the payload, this metadata is entirely unused by tantivy.
+    /// It is carried over by merges, and replaced entirely by the next commit.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+    /// Configurations of the tokenizers registered using
+    /// [`TokenizerManager::register_config()`](crate::tokenizer::TokenizerManager::register_config).
+    ///
+    /// These tokenizers are registered again when the index is opened.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tokenizers: BTreeMap<String, TextAnalyzerConfig>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -350,6 +420,10 @@ struct UntrackedIndexMeta {
     pub opstamp: Opstamp,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub payload: Option<String>,
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    #[serde(default)]
+    pub tokenizers: BTreeMap<String, TextAnalyzerConfig>,
 }
 
 impl UntrackedIndexMeta {
@@ -364,6 +438,8 @@ impl UntrackedIndexMeta {
             schema: self.schema,
             opstamp: self.opstamp,
             payload: self.payload,
+            metadata: self.metadata,
+            tokenizers: self.tokenizers,
         }
     }
 }
@@ -381,6 +457,8 @@ impl IndexMeta {
             schema,
             opstamp: 0u64,
             payload: None,
+            metadata: BTreeMap::new(),
+            tokenizers: BTreeMap::new(),
         }
     }
 
@@ -407,7 +485,9 @@ impl fmt::Debug for IndexMeta {
 #[cfg(test)]
 mod tests {
 
-    use super::IndexMeta;
+    use std::collections::BTreeMap;
+
+    use super::{IndexMeta, SegmentMetaInventory};
     use crate::core::index_meta::UntrackedIndexMeta;
     use crate::schema::{Schema, TEXT};
     use crate::store::{Compressor, ZstdCompressor};
@@ -432,6 +512,8 @@ mod tests {
             schema,
             opstamp: 0u64,
             payload: None,
+            metadata: BTreeMap::new(),
+            tokenizers: BTreeMap::new(),
         };
         let json = serde_json::ser::to_string(&index_metas).expect("serialization failed");
         assert_eq!(
@@ -463,11 +545,14 @@ mod tests {
                 }),
                 docstore_blocksize: 1_000_000,
                 docstore_compress_dedicated_thread: true,
+                codec: "default".to_string(),
             },
             segments: Vec::new(),
             schema,
             opstamp: 0u64,
             payload: None,
+            metadata: BTreeMap::new(),
+            tokenizers: BTreeMap::new(),
         };
         let json = serde_json::ser::to_string(&index_metas).expect("serialization failed");
         assert_eq!(
@@ -481,6 +566,31 @@ mod tests {
         assert_eq!(index_metas.opstamp, deser_meta.opstamp);
     }
 
+    #[test]
+    fn test_serialize_metas_codec() {
+        // meta.json written before the codecs, without any codec name.
+        let json = r#"{"index_settings":{"docstore_compression":"lz4","docstore_blocksize":16384},"segments":[{"segment_id":"b3a7c25e-bb6c-4c4b-8f58-a3d3b1f1ce65","max_doc":5,"deletes":null}],"schema":[],"opstamp":0}"#;
+        let inventory = SegmentMetaInventory::default();
+        let mut index_metas = IndexMeta::deserialize(json, &inventory).unwrap();
+        assert_eq!(index_metas.index_settings.codec, "default");
+        assert_eq!(index_metas.segments[0].codec(), "default");
+
+        // The default codec is not serialized, so that the meta.json remains readable by the
+        // versions without codecs.
+        assert_eq!(serde_json::to_string(&index_metas).unwrap(), json);
+
+        index_metas.index_settings.codec = "plain".to_string();
+        index_metas.segments[0] = index_metas.segments[0].clone().with_codec("plain");
+        let json = serde_json::to_string(&index_metas).unwrap();
+        assert_eq!(
+            json,
+            r#"{"index_settings":{"docstore_compression":"lz4","docstore_blocksize":16384,"codec":"plain"},"segments":[{"segment_id":"b3a7c25e-bb6c-4c4b-8f58-a3d3b1f1ce65","max_doc":5,"deletes":null,"codec":"plain"}],"schema":[],"opstamp":0}"#
+        );
+        let index_metas = IndexMeta::deserialize(&json, &inventory).unwrap();
+        assert_eq!(index_metas.index_settings.codec, "plain");
+        assert_eq!(index_metas.segments[0].codec(), "plain");
+    }
+
     #[test]
     fn test_serialize_metas_invalid_comp() {
         let json = r#"{"index_settings":{"sort_by_field":{"field":"text","order":"Asc"},"docstore_compression":"zsstd","docstore_blocksize":1000000},"segments":[],"schema":[{"name":"text","type":"text","options":{"indexing":{"record":"position","fieldnorms":true,"tokenizer":"default"},"stored":false,"fast":false}}],"opstamp":0}"#;
@@ -512,7 +622,8 @@ mod tests {
                 sort_by_field: None,
                 docstore_compression: Compressor::default(),
                 docstore_compress_dedicated_thread: true,
-                docstore_blocksize: 16_384
+                docstore_blocksize: 16_384,
+                codec: "default".to_string(),
             }
         );
         {
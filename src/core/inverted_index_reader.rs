@@ -1,7 +1,9 @@
 use std::io;
+use std::sync::Arc;
 
 use common::BinarySerializable;
 
+use crate::codec::{Codec, DefaultCodec};
 use crate::directory::FileSlice;
 use crate::positions::PositionReader;
 use crate::postings::{BlockSegmentPostings, SegmentPostings, TermInfo};
@@ -26,6 +28,7 @@ pub struct InvertedIndexReader {
     positions_file_slice: FileSlice,
     record_option: IndexRecordOption,
     total_num_tokens: u64,
+    codec: Arc<dyn Codec>,
 }
 
 impl InvertedIndexReader {
@@ -35,6 +38,7 @@ impl InvertedIndexReader {
         postings_file_slice: FileSlice,
         positions_file_slice: FileSlice,
         record_option: IndexRecordOption,
+        codec: Arc<dyn Codec>,
     ) -> io::Result<InvertedIndexReader> {
         let (total_num_tokens_slice, postings_body) = postings_file_slice.split(8);
         let total_num_tokens = u64::deserialize(&mut total_num_tokens_slice.read_bytes()?)?;
@@ -44,6 +48,7 @@ impl InvertedIndexReader {
             positions_file_slice,
             record_option,
             total_num_tokens,
+            codec,
         })
     }
 
@@ -56,6 +61,7 @@ impl InvertedIndexReader {
             positions_file_slice: FileSlice::empty(),
             record_option,
             total_num_tokens: 0u64,
+            codec: Arc::new(DefaultCodec),
         }
     }
 
@@ -78,7 +84,8 @@ impl InvertedIndexReader {
     ///
     /// # Warning
     ///
-    /// This does not reset the positions list.
+    /// This does not reset the positions list, and the block postings need to be
+    /// read from a segment written with the same codec.
     pub fn reset_block_postings_from_terminfo(
         &self,
         term_info: &TermInfo,
@@ -118,7 +125,7 @@ impl InvertedIndexReader {
         let postings_data = self
             .postings_file_slice
             .slice(term_info.postings_range.clone());
-        BlockSegmentPostings::open(
+        self.codec.postings_format().open(
             term_info.doc_freq,
             postings_data,
             self.record_option,
@@ -139,7 +146,8 @@ impl InvertedIndexReader {
 
         let block_postings = self.read_block_postings_from_terminfo(term_info, option)?;
         let position_reader = {
-            if option.has_positions() {
+            // The terms exceeding the maximum document frequency of the field have no positions.
+            if option.has_positions() && !term_info.positions_range.is_empty() {
                 let positions_data = self
                     .positions_file_slice
                     .read_bytes_slice(term_info.positions_range.clone())?;
@@ -149,10 +157,17 @@ impl InvertedIndexReader {
                 None
             }
         };
-        Ok(SegmentPostings::from_block_postings(
-            block_postings,
-            position_reader,
-        ))
+        if self.record_option.has_offsets() {
+            Ok(SegmentPostings::from_block_postings_with_offsets(
+                block_postings,
+                position_reader,
+            ))
+        } else {
+            Ok(SegmentPostings::from_block_postings(
+                block_postings,
+                position_reader,
+            ))
+        }
     }
 
     /// Returns the total number of tokens recorded for all documents
@@ -198,6 +213,31 @@ impl InvertedIndexReader {
             .map(|term_info| term_info.doc_freq)
             .unwrap_or(0u32))
     }
+
+    /// Returns the total number of occurrences of the term in all the documents,
+    /// or `None` if the field does not record term frequencies.
+    ///
+    /// This requires decoding the whole posting list of the term.
+    pub fn total_term_freq(&self, term: &Term) -> io::Result<Option<u64>> {
+        if !self.record_option.has_freq() {
+            return Ok(None);
+        }
+        let Some(mut block_postings) =
+            self.read_block_postings(term, IndexRecordOption::WithFreqs)?
+        else {
+            return Ok(Some(0u64));
+        };
+        let mut total_term_freq = 0u64;
+        while !block_postings.docs().is_empty() {
+            total_term_freq += block_postings
+                .freqs()
+                .iter()
+                .map(|&term_freq| u64::from(term_freq))
+                .sum::<u64>();
+            block_postings.advance();
+        }
+        Ok(Some(total_term_freq))
+    }
 }
 
 #[cfg(feature = "quickwit")]
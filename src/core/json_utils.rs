@@ -6,10 +6,12 @@ use rustc_hash::FxHashMap;
 use crate::fastfield::FastValue;
 use crate::postings::{IndexingContext, IndexingPosition, PostingsWriter};
 use crate::schema::term::{JSON_PATH_SEGMENT_SEP, JSON_PATH_SEGMENT_SEP_STR};
-use crate::schema::{Field, Type, DATE_TIME_PRECISION_INDEXED};
+use crate::schema::{
+    Field, JsonObjectOptions, JsonPathMapping, JsonPathTemplate, Type, DATE_TIME_PRECISION_INDEXED,
+};
 use crate::time::format_description::well_known::Rfc3339;
 use crate::time::{OffsetDateTime, UtcOffset};
-use crate::tokenizer::TextAnalyzer;
+use crate::tokenizer::{RawTokenizer, TextAnalyzer, TokenStream, Tokenizer};
 use crate::{DateTime, DocId, Term};
 
 /// This object is a map storing the last position for a given path for the current document
@@ -68,12 +70,13 @@ pub(crate) fn index_json_values<'a>(
     doc: DocId,
     json_values: impl Iterator<Item = crate::Result<&'a serde_json::Map<String, serde_json::Value>>>,
     text_analyzer: &TextAnalyzer,
-    expand_dots_enabled: bool,
+    json_options: &JsonObjectOptions,
     term_buffer: &mut Term,
     postings_writer: &mut dyn PostingsWriter,
     ctx: &mut IndexingContext,
 ) -> crate::Result<()> {
-    let mut json_term_writer = JsonTermWriter::wrap(term_buffer, expand_dots_enabled);
+    let mut json_term_writer =
+        JsonTermWriter::wrap(term_buffer, json_options.is_expand_dots_enabled());
     let mut positions_per_path: IndexingPositionsPerPath = Default::default();
     for json_value_res in json_values {
         let json_value = json_value_res?;
@@ -81,6 +84,7 @@ pub(crate) fn index_json_values<'a>(
             doc,
             json_value,
             text_analyzer,
+            json_options,
             &mut json_term_writer,
             postings_writer,
             ctx,
@@ -90,10 +94,12 @@ pub(crate) fn index_json_values<'a>(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn index_json_object(
     doc: DocId,
     json_value: &serde_json::Map<String, serde_json::Value>,
     text_analyzer: &TextAnalyzer,
+    json_options: &JsonObjectOptions,
     json_term_writer: &mut JsonTermWriter,
     postings_writer: &mut dyn PostingsWriter,
     ctx: &mut IndexingContext,
@@ -105,6 +111,7 @@ fn index_json_object(
             doc,
             json_value,
             text_analyzer,
+            json_options,
             json_term_writer,
             postings_writer,
             ctx,
@@ -114,15 +121,29 @@ fn index_json_object(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn index_json_value(
     doc: DocId,
     json_value: &serde_json::Value,
     text_analyzer: &TextAnalyzer,
+    json_options: &JsonObjectOptions,
     json_term_writer: &mut JsonTermWriter,
     postings_writer: &mut dyn PostingsWriter,
     ctx: &mut IndexingContext,
     positions_per_path: &mut IndexingPositionsPerPath,
 ) {
+    let mapping = if json_value.is_array() || json_value.is_object() {
+        // Templates apply to the leaves of the json object.
+        JsonPathMapping::Dynamic
+    } else {
+        json_options
+            .template_for_path(json_term_writer.path())
+            .map(JsonPathTemplate::mapping)
+            .unwrap_or_default()
+    };
+    if mapping == JsonPathMapping::Ignore {
+        return;
+    }
     match json_value {
         serde_json::Value::Null => {}
         serde_json::Value::Bool(val_bool) => {
@@ -139,24 +160,33 @@ fn index_json_value(
             }
             postings_writer.subscribe(doc, 0u32, json_term_writer.term(), ctx);
         }
-        serde_json::Value::String(text) => match infer_type_from_str(text) {
-            TextOrDateTime::Text(text) => {
-                let mut token_stream = text_analyzer.token_stream(text);
+        serde_json::Value::String(text) => match infer_type_from_str(text, mapping) {
+            Some(TextOrDateTime::Text(text)) => {
+                let mut raw_token_stream;
+                let mut analyzer_token_stream;
+                let token_stream: &mut dyn TokenStream = if mapping == JsonPathMapping::Raw {
+                    raw_token_stream = RawTokenizer.token_stream(text);
+                    &mut raw_token_stream
+                } else {
+                    analyzer_token_stream = text_analyzer.token_stream(text);
+                    &mut *analyzer_token_stream
+                };
                 // TODO make sure the chain position works out.
                 json_term_writer.close_path_and_set_type(Type::Str);
                 let indexing_position = positions_per_path.get_position(json_term_writer.term());
                 postings_writer.index_text(
                     doc,
-                    &mut *token_stream,
+                    token_stream,
                     json_term_writer.term_buffer,
                     ctx,
                     indexing_position,
                 );
             }
-            TextOrDateTime::DateTime(dt) => {
+            Some(TextOrDateTime::DateTime(dt)) => {
                 json_term_writer.set_fast_value(DateTime::from_utc(dt));
                 postings_writer.subscribe(doc, 0u32, json_term_writer.term(), ctx);
             }
+            None => {}
         },
         serde_json::Value::Array(arr) => {
             for val in arr {
@@ -164,6 +194,7 @@ fn index_json_value(
                     doc,
                     val,
                     text_analyzer,
+                    json_options,
                     json_term_writer,
                     postings_writer,
                     ctx,
@@ -176,6 +207,7 @@ fn index_json_value(
                 doc,
                 map,
                 text_analyzer,
+                json_options,
                 json_term_writer,
                 postings_writer,
                 ctx,
@@ -185,18 +217,31 @@ fn index_json_value(
     }
 }
 
-enum TextOrDateTime<'a> {
+pub(crate) enum TextOrDateTime<'a> {
     Text(&'a str),
     DateTime(OffsetDateTime),
 }
 
-fn infer_type_from_str(text: &str) -> TextOrDateTime {
-    match OffsetDateTime::parse(text, &Rfc3339) {
-        Ok(dt) => {
-            let dt_utc = dt.to_offset(UtcOffset::UTC);
-            TextOrDateTime::DateTime(dt_utc)
+/// Infers the type of a json string, given the mapping of its path.
+///
+/// Returns `None` if the string should be ignored.
+pub(crate) fn infer_type_from_str(
+    text: &str,
+    mapping: JsonPathMapping,
+) -> Option<TextOrDateTime<'_>> {
+    match mapping {
+        JsonPathMapping::Dynamic | JsonPathMapping::Date => {
+            match OffsetDateTime::parse(text, &Rfc3339) {
+                Ok(dt) => {
+                    let dt_utc = dt.to_offset(UtcOffset::UTC);
+                    Some(TextOrDateTime::DateTime(dt_utc))
+                }
+                Err(_) if mapping == JsonPathMapping::Dynamic => Some(TextOrDateTime::Text(text)),
+                Err(_) => None,
+            }
         }
-        Err(_) => TextOrDateTime::Text(text),
+        JsonPathMapping::Raw | JsonPathMapping::Text => Some(TextOrDateTime::Text(text)),
+        JsonPathMapping::Ignore => None,
     }
 }
 
@@ -385,7 +430,6 @@ impl<'a> JsonTermWriter<'a> {
     }
 
     /// Returns the json path of the term being currently built.
-    #[cfg(test)]
     pub(crate) fn path(&self) -> &[u8] {
         let end_of_path = self.path_stack.last().cloned().unwrap_or(1);
         &self.term().serialized_value_bytes()[..end_of_path - 1]
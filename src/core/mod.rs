@@ -1,32 +1,47 @@
+mod archive;
 mod executor;
+mod filtered_searcher;
+mod hit_cursor;
 pub mod index;
+mod index_check;
 mod index_meta;
 mod inverted_index_reader;
 #[doc(hidden)]
 pub mod json_utils;
+mod multi_searcher;
+mod replication;
 pub mod searcher;
 mod segment;
 mod segment_component;
 mod segment_id;
 mod segment_reader;
+mod shard_router;
 mod single_segment_index_writer;
+mod snapshot;
 
 use std::path::Path;
 
 use once_cell::sync::Lazy;
 
 pub use self::executor::Executor;
+pub use self::filtered_searcher::FilteredSearcher;
+pub use self::hit_cursor::{CursorHit, HitCursor, HitCursorOptions};
 pub use self::index::{Index, IndexBuilder};
+pub use self::index_check::{IndexCheckReport, SegmentCheckReport};
 pub use self::index_meta::{
     IndexMeta, IndexSettings, IndexSortByField, Order, SegmentMeta, SegmentMetaInventory,
 };
 pub use self::inverted_index_reader::InvertedIndexReader;
-pub use self::searcher::{Searcher, SearcherGeneration};
+pub use self::multi_searcher::MultiSearcher;
+pub use self::replication::IndexReplica;
+pub use self::searcher::{FieldStatistics, Searcher, SearcherGeneration, TermStatistics};
 pub use self::segment::Segment;
 pub use self::segment_component::SegmentComponent;
 pub use self::segment_id::SegmentId;
 pub use self::segment_reader::SegmentReader;
+pub use self::shard_router::ShardRouter;
 pub use self::single_segment_index_writer::SingleSegmentIndexWriter;
+pub use self::snapshot::{IndexSnapshot, SnapshotFile, SnapshotManifest};
 
 /// The meta file contains all the information about the list of segments and the schema
 /// of the index.
@@ -0,0 +1,353 @@
+use crate::collector::Collector;
+use crate::query::{Bm25StatisticsProvider, EnableScoring, Query};
+use crate::schema::{Document, Field, Schema, Term, Value};
+use crate::{DocAddress, Searcher, SegmentOrdinal, SegmentReader, ShardRouter, TantivyError};
+
+/// Searches several indexes sharing the same schema as if they were one, e.g. the indexes of
+/// the days of a time partitioned layout.
+///
+/// The segments of the searchers are numbered one after the other: the [`DocAddress`]es
+/// returned by the collectors use these global segment ordinals, and can be mapped back to the
+/// index they belong to with [`MultiSearcher::index_doc_address`].
+///
+/// The BM25 statistics are combined over all of the indexes, so that the scores of the
+/// documents of different indexes are comparable.
+#[derive(Clone)]
+pub struct MultiSearcher {
+    searchers: Vec<Searcher>,
+    segment_readers: Vec<SegmentReader>,
+    // The global ordinal of the first segment of each searcher.
+    segment_ord_offsets: Vec<SegmentOrdinal>,
+}
+
+impl MultiSearcher {
+    /// Creates a `MultiSearcher` over the given searchers.
+    ///
+    /// Returns an error if there are no searchers, or if their schemas differ.
+    pub fn new(searchers: Vec<Searcher>) -> crate::Result<MultiSearcher> {
+        let Some(first_searcher) = searchers.first() else {
+            return Err(TantivyError::InvalidArgument(
+                "A multi searcher requires at least one searcher".to_string(),
+            ));
+        };
+        let target_schema = first_searcher.schema();
+        if searchers
+            .iter()
+            .skip(1)
+            .any(|searcher| searcher.schema() != target_schema)
+        {
+            return Err(TantivyError::InvalidArgument(
+                "Attempt to search different schema indices".to_string(),
+            ));
+        }
+        let mut segment_readers = Vec::new();
+        let mut segment_ord_offsets = Vec::with_capacity(searchers.len());
+        for searcher in &searchers {
+            segment_ord_offsets.push(segment_readers.len() as SegmentOrdinal);
+            segment_readers.extend(searcher.segment_readers().iter().cloned());
+        }
+        Ok(MultiSearcher {
+            searchers,
+            segment_readers,
+            segment_ord_offsets,
+        })
+    }
+
+    /// Returns the searchers, in the order of their index ordinals.
+    pub fn searchers(&self) -> &[Searcher] {
+        &self.searchers
+    }
+
+    /// Returns the schema shared by the indexes.
+    pub fn schema(&self) -> &Schema {
+        self.searchers[0].schema()
+    }
+
+    /// Returns the segment readers of all of the indexes, indexed by their global segment
+    /// ordinal.
+    pub fn segment_readers(&self) -> &[SegmentReader] {
+        &self.segment_readers
+    }
+
+    /// Returns the overall number of documents of the indexes.
+    pub fn num_docs(&self) -> u64 {
+        self.searchers.iter().map(Searcher::num_docs).sum()
+    }
+
+    /// Returns the overall number of documents containing the given term.
+    pub fn doc_freq(&self, term: &Term) -> crate::Result<u64> {
+        let mut doc_freq = 0u64;
+        for searcher in &self.searchers {
+            doc_freq += searcher.doc_freq(term)?;
+        }
+        Ok(doc_freq)
+    }
+
+    /// Maps a [`DocAddress`] using a global segment ordinal to the ordinal of its index,
+    /// and its address within the searcher of this index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the segment ordinal is out of bounds.
+    pub fn index_doc_address(&self, doc_address: DocAddress) -> (usize, DocAddress) {
+        assert!(
+            (doc_address.segment_ord as usize) < self.segment_readers.len(),
+            "Segment ordinal {} out of bounds",
+            doc_address.segment_ord
+        );
+        let index_ord = self
+            .segment_ord_offsets
+            .partition_point(|&offset| offset <= doc_address.segment_ord)
+            - 1;
+        let local_doc_address = DocAddress::new(
+            doc_address.segment_ord - self.segment_ord_offsets[index_ord],
+            doc_address.doc_id,
+        );
+        (index_ord, local_doc_address)
+    }
+
+    /// Fetches a document from the store of its index, given a [`DocAddress`] using a global
+    /// segment ordinal.
+    pub fn doc(&self, doc_address: DocAddress) -> crate::Result<Document> {
+        let (index_ord, local_doc_address) = self.index_doc_address(doc_address);
+        self.searchers[index_ord].doc(local_doc_address)
+    }
+
+    /// Runs a query on the segments of all of the indexes, and merges the fruits of the
+    /// collector.
+    ///
+    /// The weight of the query is created once, with the schema of the first searcher and the
+    /// BM25 statistics combined over all of the searchers. The segments are searched on the
+    /// executor of the first index.
+    pub fn search<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+    ) -> crate::Result<C::Fruit> {
+        let first_searcher = &self.searchers[0];
+        let enabled_scoring = if collector.requires_scoring() {
+            EnableScoring::enabled_from_statistics_provider(self, first_searcher)
+        } else {
+            EnableScoring::disabled_from_searcher(first_searcher)
+        };
+        let weight = query.weight(enabled_scoring)?;
+        let executor = first_searcher.index().search_executor();
+        collector.collect_segments(weight.as_ref(), &self.segment_readers, executor)
+    }
+
+    /// Same as [`search(...)`](MultiSearcher::search), but only searching the shards of the
+    /// given routing values.
+    ///
+    /// The searchers are expected to be the shards of the router, in the order of their shard
+    /// ordinals. The [`DocAddress`]es returned by the collector use the global segment ordinals
+    /// of the `MultiSearcher`, and the BM25 statistics are still combined over all of the
+    /// shards, so that the scores are the same as those of a search of all of the shards.
+    ///
+    /// The query is expected to only match documents of the given routing values, e.g. by
+    /// filtering on the routing field: the other documents of the targeted shards are
+    /// collected too.
+    pub fn search_routed<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+        router: &ShardRouter,
+        routing_values: &[Value],
+    ) -> crate::Result<C::Fruit> {
+        if router.num_shards() != self.searchers.len() {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The router has {} shards, but the multi searcher has {} searchers",
+                router.num_shards(),
+                self.searchers.len()
+            )));
+        }
+        let shards = router.shards_for_values(routing_values)?;
+        let first_searcher = &self.searchers[0];
+        let enabled_scoring = if collector.requires_scoring() {
+            EnableScoring::enabled_from_statistics_provider(self, first_searcher)
+        } else {
+            EnableScoring::disabled_from_searcher(first_searcher)
+        };
+        let weight = query.weight(enabled_scoring)?;
+        let executor = first_searcher.index().search_executor();
+        let segments = shards.into_iter().flat_map(|shard| {
+            let first_segment_ord = self.segment_ord_offsets[shard];
+            let num_segments = self.searchers[shard].segment_readers().len() as SegmentOrdinal;
+            first_segment_ord..first_segment_ord + num_segments
+        });
+        let fruits = executor.map(
+            |segment_ord| {
+                collector.collect_segment(
+                    weight.as_ref(),
+                    segment_ord,
+                    &self.segment_readers[segment_ord as usize],
+                )
+            },
+            segments,
+        )?;
+        collector.merge_fruits(fruits)
+    }
+}
+
+impl Bm25StatisticsProvider for MultiSearcher {
+    fn total_num_tokens(&self, field: Field) -> crate::Result<u64> {
+        let mut total_num_tokens = 0u64;
+        for searcher in &self.searchers {
+            total_num_tokens += searcher.total_num_tokens(field)?;
+        }
+        Ok(total_num_tokens)
+    }
+
+    fn total_num_docs(&self) -> crate::Result<u64> {
+        let mut total_num_docs = 0u64;
+        for searcher in &self.searchers {
+            total_num_docs += searcher.total_num_docs()?;
+        }
+        Ok(total_num_docs)
+    }
+
+    fn doc_freq(&self, term: &Term) -> crate::Result<u64> {
+        self.doc_freq(term)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MultiSearcher;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{AllQuery, QueryParser, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, Value, STORED, STRING, TEXT};
+    use crate::{doc, DocAddress, Index, Searcher, ShardRouter, Term};
+
+    fn create_searcher(schema: &Schema, texts: &[&[&str]]) -> crate::Result<Searcher> {
+        let text = schema.get_field("text")?;
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer = index.writer_for_tests()?;
+        for segment_texts in texts {
+            for text_val in *segment_texts {
+                index_writer.add_document(doc!(text => *text_val))?;
+            }
+            index_writer.commit()?;
+        }
+        Ok(index.reader()?.searcher())
+    }
+
+    #[test]
+    fn test_multi_searcher() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT | STORED);
+        let schema = schema_builder.build();
+        let day1 = create_searcher(&schema, &[&["a b", "b"], &["c"]])?;
+        let day2 = create_searcher(&schema, &[&["a", "b c"]])?;
+        let day3 = create_searcher(&schema, &[&["a"]])?;
+        let all = create_searcher(&schema, &[&["a b", "b", "c", "a", "b c", "a"]])?;
+        let multi_searcher = MultiSearcher::new(vec![day1, day2, day3])?;
+        assert_eq!(multi_searcher.segment_readers().len(), 4);
+        assert_eq!(multi_searcher.num_docs(), 6);
+
+        let query = QueryParser::for_index(all.index(), vec![text]).parse_query("a")?;
+        assert_eq!(multi_searcher.search(query.as_ref(), &Count)?, 3);
+        let top_docs = multi_searcher.search(query.as_ref(), &TopDocs::with_limit(10))?;
+        let expected_top_docs = all.search(query.as_ref(), &TopDocs::with_limit(10))?;
+        // The scores are the ones of a single index holding all of the documents.
+        let scores: Vec<_> = top_docs.iter().map(|(score, _)| *score).collect();
+        let expected_scores: Vec<_> = expected_top_docs.iter().map(|(score, _)| *score).collect();
+        assert_eq!(scores, expected_scores);
+        for (_, doc_address) in top_docs {
+            let doc = multi_searcher.doc(doc_address)?;
+            assert!(doc
+                .get_first(text)
+                .unwrap()
+                .as_text()
+                .unwrap()
+                .contains('a'));
+        }
+
+        assert_eq!(
+            multi_searcher.index_doc_address(DocAddress::new(1, 0)),
+            (0, DocAddress::new(1, 0))
+        );
+        assert_eq!(
+            multi_searcher.index_doc_address(DocAddress::new(2, 1)),
+            (1, DocAddress::new(0, 1))
+        );
+        assert_eq!(
+            multi_searcher.index_doc_address(DocAddress::new(3, 0)),
+            (2, DocAddress::new(0, 0))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_searcher_search_routed() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING | STORED);
+        let text = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let router = ShardRouter::new(tenant, 3);
+        let indexes: Vec<Index> = (0..3)
+            .map(|_| Index::create_in_ram(schema.clone()))
+            .collect();
+        let mut index_writers = indexes
+            .iter()
+            .map(Index::writer_for_tests)
+            .collect::<crate::Result<Vec<_>>>()?;
+        for tenant_id in 0..10 {
+            let doc = doc!(tenant => format!("tenant{tenant_id}"), text => "hello");
+            index_writers[router.shard_for_document(&doc)?].add_document(doc)?;
+        }
+        for index_writer in &mut index_writers {
+            index_writer.commit()?;
+        }
+        let searchers = indexes
+            .iter()
+            .map(|index| Ok(index.reader()?.searcher()))
+            .collect::<crate::Result<Vec<_>>>()?;
+        let multi_searcher = MultiSearcher::new(searchers)?;
+        let query = TermQuery::new(
+            Term::from_field_text(tenant, "tenant7"),
+            IndexRecordOption::Basic,
+        );
+        let routing_values = [Value::from("tenant7")];
+        let top_docs = multi_searcher.search_routed(
+            &query,
+            &TopDocs::with_limit(10),
+            &router,
+            &routing_values,
+        )?;
+        assert_eq!(
+            top_docs,
+            multi_searcher.search(&query, &TopDocs::with_limit(10))?
+        );
+        let doc = multi_searcher.doc(top_docs[0].1)?;
+        assert_eq!(doc.get_first(tenant).unwrap().as_text(), Some("tenant7"));
+        // Only the documents of the shard of `tenant7` are searched.
+        let count = multi_searcher.search_routed(&AllQuery, &Count, &router, &routing_values)?;
+        let shard = router.shard_for_value(&routing_values[0])?;
+        assert_eq!(count as u64, multi_searcher.searchers()[shard].num_docs());
+        assert!(multi_searcher
+            .search_routed(
+                &AllQuery,
+                &Count,
+                &ShardRouter::new(tenant, 2),
+                &routing_values
+            )
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_searcher_requires_same_schema() -> crate::Result<()> {
+        assert!(MultiSearcher::new(Vec::new()).is_err());
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let mut other_schema_builder = Schema::builder();
+        other_schema_builder.add_text_field("text", TEXT);
+        other_schema_builder.add_u64_field("other", STORED);
+        let other_schema = other_schema_builder.build();
+        let searcher = create_searcher(&schema, &[&["a"]])?;
+        let other_searcher = create_searcher(&other_schema, &[&["a"]])?;
+        assert!(MultiSearcher::new(vec![searcher, other_searcher]).is_err());
+        Ok(())
+    }
+}
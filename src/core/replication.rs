@@ -0,0 +1,247 @@
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use common::HasLen;
+
+use crate::core::snapshot::write_meta;
+use crate::core::{SnapshotManifest, META_FILEPATH};
+use crate::directory::{Directory, GarbageCollectionResult, ManagedDirectory};
+use crate::error::DataCorruption;
+use crate::TantivyError;
+
+/// The replica side of a replication of an index, receiving the files of the commits of a
+/// primary index and applying them to its directory.
+///
+/// The primary takes an [`IndexSnapshot`](crate::IndexSnapshot) of each commit to replicate,
+/// and sends its [`SnapshotManifest`] to the replica. The replica asks for its
+/// [`IndexReplica::missing_files()`], which the primary streams with
+/// [`IndexSnapshot::open_file()`](crate::IndexSnapshot::open_file) and the replica writes
+/// with [`IndexReplica::receive_file()`], checking their checksums. The replica then moves to
+/// the commit with [`IndexReplica::apply()`], which writes its `meta.json` atomically: the
+/// readers of the replica directory see the commit in full, or not at all.
+///
+/// ```rust
+/// use tantivy::directory::RamDirectory;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index, IndexReplica};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+/// index_writer.add_document(doc!(title => "The Old Man and the Sea"))?;
+/// index_writer.commit()?;
+///
+/// let replica_directory = RamDirectory::create();
+/// let replica = IndexReplica::open(replica_directory.clone())?;
+///
+/// // On the primary.
+/// let snapshot = index.snapshot()?;
+/// let manifest = snapshot.manifest();
+/// // On the replica, with the files sent over the network.
+/// for path in replica.missing_files(manifest)? {
+///     replica.receive_file(manifest, path, &mut snapshot.open_file(path)?)?;
+/// }
+/// replica.apply(manifest)?;
+///
+/// let replica_index = Index::open(replica_directory)?;
+/// assert_eq!(replica_index.reader()?.searcher().num_docs(), 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct IndexReplica {
+    directory: ManagedDirectory,
+}
+
+impl IndexReplica {
+    /// Opens a replica in a directory, which may be empty or hold a previous replicated
+    /// commit.
+    ///
+    /// No index writer must be opened on the directory.
+    pub fn open<T: Into<Box<dyn Directory>>>(directory: T) -> crate::Result<IndexReplica> {
+        Ok(IndexReplica {
+            directory: ManagedDirectory::wrap(directory.into())?,
+        })
+    }
+
+    fn has_file(&self, path: &Path, num_bytes: u64, crc: u32) -> crate::Result<bool> {
+        if !self.directory.exists(path)? {
+            return Ok(false);
+        }
+        let file = self.directory.open_read_with_footer(path)?;
+        Ok(file.len() as u64 == num_bytes && self.directory.footer_crc(path)? == crc)
+    }
+
+    /// Returns the files of the commit the replica does not have yet.
+    pub fn missing_files<'a>(
+        &self,
+        manifest: &'a SnapshotManifest,
+    ) -> crate::Result<Vec<&'a Path>> {
+        let mut missing_files = Vec::new();
+        for (path, file) in manifest.files() {
+            if !self.has_file(path, file.num_bytes, file.crc)? {
+                missing_files.push(path.as_path());
+            }
+        }
+        Ok(missing_files)
+    }
+
+    /// Writes a file of the commit, as streamed by
+    /// [`IndexSnapshot::open_file()`](crate::IndexSnapshot::open_file).
+    ///
+    /// Returns an error, and removes the file, if its size or its checksum do not match the
+    /// manifest, or if its content does not match its checksum.
+    pub fn receive_file(
+        &self,
+        manifest: &SnapshotManifest,
+        path: &Path,
+        data: &mut dyn io::Read,
+    ) -> crate::Result<()> {
+        let file = manifest.files().get(path).ok_or_else(|| {
+            TantivyError::InvalidArgument(format!("{path:?} is not a file of the commit"))
+        })?;
+        self.directory.write_with_footer(path, data)?;
+        let is_valid = self.has_file(path, file.num_bytes, file.crc)?
+            && self.directory.validate_checksum(path)?;
+        if !is_valid {
+            self.directory.delete_if_exists(path)?;
+            return Err(DataCorruption::new(
+                path.to_path_buf(),
+                "The received file does not match the manifest of the commit.".to_string(),
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Moves the replica to the commit, writing its `meta.json` atomically, once all of its
+    /// files were received.
+    pub fn apply(&self, manifest: &SnapshotManifest) -> crate::Result<()> {
+        let missing_files = self.missing_files(manifest)?;
+        if !missing_files.is_empty() {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Cannot apply the commit {}, the replica is missing {:?}",
+                manifest.opstamp(),
+                missing_files
+            )));
+        }
+        write_meta(&self.directory, manifest.meta())
+    }
+
+    /// Deletes the files of the replica which are not used by the given commit, typically the
+    /// last one applied.
+    ///
+    /// The searchers opened on the replica before the commit may still be using these files.
+    /// As for the garbage collection of an index writer, the files which cannot be deleted,
+    /// for instance as they are memory mapped on Windows, are deleted by the next call.
+    pub fn garbage_collect(
+        &self,
+        manifest: &SnapshotManifest,
+    ) -> crate::Result<GarbageCollectionResult> {
+        let mut living_files: HashSet<PathBuf> = manifest.files().keys().cloned().collect();
+        living_files.insert(META_FILEPATH.to_path_buf());
+        self.directory.clone().garbage_collect(|| living_files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::IndexReplica;
+    use crate::directory::RamDirectory;
+    use crate::indexer::NoMergePolicy;
+    use crate::schema::{Schema, INDEXED};
+    use crate::{Directory, Index, IndexSnapshot, ReloadPolicy};
+
+    fn replicate(snapshot: &IndexSnapshot, replica: &IndexReplica) -> crate::Result<usize> {
+        let manifest = snapshot.manifest();
+        let missing_files = replica.missing_files(manifest)?;
+        for path in &missing_files {
+            replica.receive_file(manifest, path, &mut snapshot.open_file(path)?)?;
+        }
+        replica.apply(manifest)?;
+        Ok(missing_files.len())
+    }
+
+    #[test]
+    fn test_index_replica() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(id => 1u64))?;
+        index_writer.commit()?;
+
+        let replica_directory = RamDirectory::create();
+        let replica = IndexReplica::open(replica_directory.clone())?;
+        let first_snapshot = index.snapshot()?;
+        let num_files = replicate(&first_snapshot, &replica)?;
+        assert_eq!(num_files, first_snapshot.manifest().files().len());
+        let replica_index = Index::open(replica_directory.clone())?;
+        let replica_reader = replica_index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        assert_eq!(replica_reader.searcher().num_docs(), 1);
+
+        // Only the files of the new segment are sent.
+        index_writer.add_document(doc!(id => 2u64))?;
+        index_writer.commit()?;
+        let second_snapshot = index.snapshot()?;
+        let num_new_files = second_snapshot
+            .manifest()
+            .new_files_since(first_snapshot.manifest())
+            .count();
+        assert_eq!(replicate(&second_snapshot, &replica)?, num_new_files);
+        replica_reader.reload()?;
+        assert_eq!(replica_reader.searcher().num_docs(), 2);
+
+        // After a merge, the files of the merged segments are deleted from the replica.
+        index_writer
+            .merge(&index.searchable_segment_ids()?)
+            .wait()?;
+        let merged_snapshot = index.snapshot()?;
+        replicate(&merged_snapshot, &replica)?;
+        let gc_result = replica.garbage_collect(merged_snapshot.manifest())?;
+        assert!(!gc_result.deleted_files.is_empty());
+        for path in second_snapshot.manifest().files().keys() {
+            let is_living = merged_snapshot.manifest().files().contains_key(path);
+            assert_eq!(replica_directory.exists(path)?, is_living);
+        }
+        replica_reader.reload()?;
+        assert_eq!(replica_reader.searcher().segment_readers().len(), 1);
+        assert_eq!(replica_reader.searcher().num_docs(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_replica_rejects_corrupted_files() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id => 1u64))?;
+        index_writer.commit()?;
+        let snapshot = index.snapshot()?;
+        let manifest = snapshot.manifest();
+
+        let replica_directory = RamDirectory::create();
+        let replica = IndexReplica::open(replica_directory.clone())?;
+        let path = replica.missing_files(manifest)?[0];
+        let mut data = Vec::new();
+        std::io::copy(&mut snapshot.open_file(path)?, &mut data)?;
+        data[0] ^= 1;
+        assert!(replica
+            .receive_file(manifest, path, &mut Cursor::new(&data))
+            .is_err());
+        assert!(!replica_directory.exists(path)?);
+        assert!(replica.apply(manifest).is_err());
+        assert!(!replica_directory.exists(std::path::Path::new("meta.json"))?);
+        Ok(())
+    }
+}
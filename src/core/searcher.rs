@@ -1,14 +1,32 @@
-use std::collections::BTreeMap;
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Instant;
 use std::{fmt, io};
 
-use crate::collector::Collector;
-use crate::core::{Executor, SegmentReader};
-use crate::query::{Bm25StatisticsProvider, EnableScoring, Query};
-use crate::schema::{Document, Schema, Term};
+use serde::{Deserialize, Serialize};
+
+use crate::collector::{CacheableCollector, Collector, SegmentCollector};
+use crate::core::{Executor, HitCursor, HitCursorOptions, SegmentReader};
+use crate::fastfield::{FastFieldDocReader, GlobalOrdinals};
+use crate::metrics::{enter_span, QueryPhase};
+use crate::postings::compression::COMPRESSION_BLOCK_SIZE;
+use crate::query::profile::profile_query;
+use crate::query::{
+    Bm25StatisticsProvider, EnableScoring, Query, Scorer, SearchProfile, SegmentCollectionProfile,
+    Weight,
+};
+use crate::reader::{ResultCache, ResultCacheKey, WarmUpComponents};
+use crate::schema::{Document, Field, IndexRecordOption, Schema, Term};
 use crate::space_usage::SearcherSpaceUsage;
 use crate::store::{CacheStats, StoreReader};
-use crate::{DocAddress, Index, Opstamp, SegmentId, TrackedObject};
+use crate::termdict::MergedTermDictionary;
+use crate::{
+    DocAddress, DocId, DocSet, Index, Opstamp, SegmentId, SegmentOrdinal, TantivyError,
+    TrackedObject, TERMINATED,
+};
 
 /// Identifies the searcher generation accessed by a [`Searcher`].
 ///
@@ -59,6 +77,30 @@ impl SearcherGeneration {
     }
 }
 
+/// Statistics of a term, aggregated over all of the segments of a [`Searcher`].
+///
+/// Like the statistics used for BM25 scoring, they include the deleted documents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TermStatistics {
+    /// The number of documents containing the term.
+    pub doc_freq: u64,
+    /// The total number of occurrences of the term in all the documents,
+    /// or `None` if the field does not record term frequencies.
+    pub total_term_freq: Option<u64>,
+}
+
+/// Statistics of an indexed field, aggregated over all of the segments of a [`Searcher`].
+///
+/// Like the statistics used for BM25 scoring, they include the deleted documents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldStatistics {
+    /// The number of documents.
+    pub doc_count: u64,
+    /// The sum of the lengths of the field in all of the documents, i.e. its total
+    /// number of tokens.
+    pub total_num_tokens: u64,
+}
+
 /// Holds a list of `SegmentReader`s ready for search.
 ///
 /// It guarantees that the `Segment` will not be removed before
@@ -88,6 +130,107 @@ impl Searcher {
         store_reader.get(doc_address.doc_id)
     }
 
+    /// Fetches the documents of the given [`DocAddress`]es, returned in the same order.
+    ///
+    /// The addresses are grouped by segment and by block of the store, so that each block is
+    /// decompressed only once. This is cheaper than calling [`Searcher::doc`] for each of the
+    /// hits of a search.
+    pub fn docs(&self, doc_addresses: &[DocAddress]) -> crate::Result<Vec<Document>> {
+        let mut docs: Vec<Option<Document>> = vec![None; doc_addresses.len()];
+        for (segment_ord, positions) in positions_by_segment(doc_addresses) {
+            let doc_ids: Vec<DocId> = positions
+                .iter()
+                .map(|&pos| doc_addresses[pos].doc_id)
+                .collect();
+            let store_reader = &self.inner.store_readers[segment_ord as usize];
+            let segment_docs = store_reader.get_many(&doc_ids)?;
+            for (pos, doc) in positions.into_iter().zip(segment_docs) {
+                docs[pos] = Some(doc);
+            }
+        }
+        Ok(docs.into_iter().flatten().collect())
+    }
+
+    /// Fetches documents in an asynchronous manner. Async version of [`Searcher::docs`].
+    pub async fn docs_async(&self, doc_addresses: &[DocAddress]) -> crate::Result<Vec<Document>> {
+        let mut docs: Vec<Option<Document>> = vec![None; doc_addresses.len()];
+        for (segment_ord, positions) in positions_by_segment(doc_addresses) {
+            let doc_ids: Vec<DocId> = positions
+                .iter()
+                .map(|&pos| doc_addresses[pos].doc_id)
+                .collect();
+            let store_reader = &self.inner.store_readers[segment_ord as usize];
+            let segment_docs = store_reader.get_many_async(&doc_ids).await?;
+            for (pos, doc) in positions.into_iter().zip(segment_docs) {
+                docs[pos] = Some(doc);
+            }
+        }
+        Ok(docs.into_iter().flatten().collect())
+    }
+
+    /// Fetches a document from tantivy's store given a [`DocAddress`], keeping only the
+    /// values of the given fields.
+    ///
+    /// The values of the other fields are skipped without being decoded, which makes
+    /// this cheaper than [`Searcher::doc`] when the document has large stored fields
+    /// that are not needed.
+    pub fn doc_with_fields(
+        &self,
+        doc_address: DocAddress,
+        fields: &[Field],
+    ) -> crate::Result<Document> {
+        let store_reader = &self.inner.store_readers[doc_address.segment_ord as usize];
+        store_reader.get_with_fields(doc_address.doc_id, fields)
+    }
+
+    /// Rebuilds the documents of the given [`DocAddress`]es from the fast fields, keeping only
+    /// the values of the given fields. The documents are returned in the same order.
+    ///
+    /// Unlike [`Searcher::docs`], this does not decompress any block of the doc store, which is
+    /// much cheaper for analytics-style queries fetching a few numeric fields of many hits. The
+    /// values are read from the columns of the fast fields: the text values are normalized by
+    /// the fast field tokenizer, the dates are truncated to the precision of the fast field, and
+    /// the values of a multivalued field are not necessarily in the order in which they were
+    /// added.
+    ///
+    /// Returns an error if one of the fields is not a fast field, or is a JSON, dense vector or
+    /// geo point field.
+    pub fn docs_from_fast_fields(
+        &self,
+        doc_addresses: &[DocAddress],
+        fields: &[Field],
+    ) -> crate::Result<Vec<Document>> {
+        for &field in fields {
+            let field_entry = self.schema().get_field_entry(field);
+            if !field_entry.is_fast() {
+                return Err(TantivyError::SchemaError(format!(
+                    "Field {:?} is not a fast field",
+                    field_entry.name()
+                )));
+            }
+        }
+        let mut docs: Vec<Option<Document>> = vec![None; doc_addresses.len()];
+        for (segment_ord, positions) in positions_by_segment(doc_addresses) {
+            let segment_reader = self.segment_reader(segment_ord);
+            let doc_reader = FastFieldDocReader::open(segment_reader, fields)?;
+            for pos in positions {
+                docs[pos] = Some(doc_reader.get(doc_addresses[pos].doc_id)?);
+            }
+        }
+        Ok(docs.into_iter().flatten().collect())
+    }
+
+    /// Rebuilds a document from the fast fields, keeping only the values of the given fields.
+    /// See [`Searcher::docs_from_fast_fields`].
+    pub fn doc_from_fast_fields(
+        &self,
+        doc_address: DocAddress,
+        fields: &[Field],
+    ) -> crate::Result<Document> {
+        let segment_reader = self.segment_reader(doc_address.segment_ord);
+        FastFieldDocReader::open(segment_reader, fields)?.get(doc_address.doc_id)
+    }
+
     /// The cache stats for the underlying store reader.
     ///
     /// Aggregates the sum for each segment store reader.
@@ -102,12 +245,67 @@ impl Searcher {
     }
 
     /// Fetches a document in an asynchronous manner.
-    #[cfg(feature = "quickwit")]
+    ///
+    /// The document store is read with [`FileHandle::read_bytes_async`], which the
+    /// directory of the index needs to support.
+    ///
+    /// [`FileHandle::read_bytes_async`]: crate::directory::FileHandle::read_bytes_async
     pub async fn doc_async(&self, doc_address: DocAddress) -> crate::Result<Document> {
         let store_reader = &self.inner.store_readers[doc_address.segment_ord as usize];
         store_reader.get_async(doc_address.doc_id).await
     }
 
+    /// Fetches a document in an asynchronous manner, keeping only the values of the given
+    /// fields.
+    pub async fn doc_with_fields_async(
+        &self,
+        doc_address: DocAddress,
+        fields: &[Field],
+    ) -> crate::Result<Document> {
+        let store_reader = &self.inner.store_readers[doc_address.segment_ord as usize];
+        store_reader
+            .get_with_fields_async(doc_address.doc_id, fields)
+            .await
+    }
+
+    /// Returns the address of the first alive document containing the given term.
+    ///
+    /// This looks the term up in the term dictionary of each segment, without building a query
+    /// nor a collector, and is meant for fields holding a unique key of the documents.
+    pub fn doc_address_by_term(&self, term: &Term) -> crate::Result<Option<DocAddress>> {
+        for (segment_ord, segment_reader) in self.inner.segment_readers.iter().enumerate() {
+            let inverted_index = segment_reader.inverted_index(term.field())?;
+            let Some(mut postings) =
+                inverted_index.read_postings(term, IndexRecordOption::Basic)?
+            else {
+                continue;
+            };
+            let alive_bitset_opt = segment_reader.alive_bitset();
+            let mut doc = postings.doc();
+            while doc != TERMINATED {
+                if alive_bitset_opt.map_or(true, |alive_bitset| alive_bitset.is_alive(doc)) {
+                    return Ok(Some(DocAddress::new(segment_ord as u32, doc)));
+                }
+                doc = postings.advance();
+            }
+        }
+        Ok(None)
+    }
+
+    /// Fetches the first alive document containing the given term, e.g. the document of a
+    /// primary key.
+    ///
+    /// See [`Searcher::doc_address_by_term`]. The documents added since the last commit are
+    /// only visible to the searchers of the
+    /// [near real time](crate::IndexReaderBuilder::near_real_time) readers, once the writer is
+    /// [refreshed](crate::IndexWriter::refresh).
+    pub fn get_by_term(&self, term: &Term) -> crate::Result<Option<Document>> {
+        let Some(doc_address) = self.doc_address_by_term(term)? else {
+            return Ok(None);
+        };
+        self.doc(doc_address).map(Some)
+    }
+
     /// Access the schema associated with the index of this searcher.
     pub fn schema(&self) -> &Schema {
         &self.inner.schema
@@ -134,6 +332,37 @@ impl Searcher {
         Ok(total_doc_freq)
     }
 
+    /// Returns the statistics of the given term, aggregated over all of the segments.
+    ///
+    /// These statistics can be shared between several indexes, e.g. to compute
+    /// consistent idfs in a distributed search.
+    pub fn term_statistics(&self, term: &Term) -> crate::Result<TermStatistics> {
+        let mut doc_freq = 0u64;
+        let mut total_term_freq = Some(0u64);
+        for segment_reader in &self.inner.segment_readers {
+            let inverted_index = segment_reader.inverted_index(term.field())?;
+            doc_freq += u64::from(inverted_index.doc_freq(term)?);
+            if total_term_freq.is_some() {
+                let segment_total_term_freq = inverted_index.total_term_freq(term)?;
+                total_term_freq = total_term_freq
+                    .zip(segment_total_term_freq)
+                    .map(|(left, right)| left + right);
+            }
+        }
+        Ok(TermStatistics {
+            doc_freq,
+            total_term_freq,
+        })
+    }
+
+    /// Returns the statistics of the given field, aggregated over all of the segments.
+    pub fn field_statistics(&self, field: Field) -> crate::Result<FieldStatistics> {
+        Ok(FieldStatistics {
+            doc_count: self.total_num_docs()?,
+            total_num_tokens: self.total_num_tokens(field)?,
+        })
+    }
+
     /// Return the overall number of documents containing
     /// the given term in an asynchronous manner.
     #[cfg(feature = "quickwit")]
@@ -147,6 +376,21 @@ impl Searcher {
         Ok(total_doc_freq)
     }
 
+    /// Returns the term dictionaries of the field in all of the segments, which can be streamed
+    /// as one sorted and deduplicated dictionary, optionally filtered by an automaton.
+    ///
+    /// This is useful to list the terms of a field along with their overall doc frequency, e.g.
+    /// to build the candidates of a query expansion.
+    pub fn merged_term_dictionary(&self, field: Field) -> crate::Result<MergedTermDictionary> {
+        let inverted_indexes = self
+            .inner
+            .segment_readers
+            .iter()
+            .map(|segment_reader| segment_reader.inverted_index(field))
+            .collect::<crate::Result<Vec<_>>>()?;
+        Ok(MergedTermDictionary::new(inverted_indexes))
+    }
+
     /// Return the list of segment readers
     pub fn segment_readers(&self) -> &[SegmentReader] {
         &self.inner.segment_readers
@@ -157,6 +401,17 @@ impl Searcher {
         &self.inner.segment_readers[segment_ord as usize]
     }
 
+    /// Loads the given components of all of the segments.
+    ///
+    /// See [`SegmentReader::warm_up()`], and the [`ComponentWarmer`](crate::ComponentWarmer)
+    /// to warm up the new segments on each reload of an [`IndexReader`](crate::IndexReader).
+    pub fn warm_up(&self, components: &WarmUpComponents) -> crate::Result<()> {
+        for segment_reader in &self.inner.segment_readers {
+            segment_reader.warm_up(components)?;
+        }
+        Ok(())
+    }
+
     /// Runs a query on the segment readers wrapped by the searcher.
     ///
     /// Search works as follows :
@@ -179,6 +434,161 @@ impl Searcher {
         self.search_with_statistics_provider(query, collector, self)
     }
 
+    /// Same as [`search(...)`](Searcher::search), but collecting the documents in a future.
+    ///
+    /// The segments are collected one after the other, and the future yields back to the
+    /// executor after each block of documents, so that a long search does not hog the thread
+    /// of an async runtime. With the `quickwit` feature, the postings of the terms of the query
+    /// are first loaded with the async reads of the [`Directory`](crate::Directory).
+    ///
+    /// The documents are pushed one by one to the segment collectors: the specialized
+    /// collection of some collectors, such as the pruning of the documents which cannot make
+    /// it to the top of [`TopDocs`](crate::collector::TopDocs), does not apply.
+    pub async fn search_async<C>(&self, query: &dyn Query, collector: &C) -> crate::Result<C::Fruit>
+    where
+        C: Collector,
+        C::Child: Send,
+    {
+        #[cfg(feature = "quickwit")]
+        self.warm_up_query_terms(query).await?;
+        let enabled_scoring = if collector.requires_scoring() {
+            EnableScoring::enabled_from_searcher(self)
+        } else {
+            EnableScoring::disabled_from_searcher(self)
+        };
+        let weight = query.weight(enabled_scoring)?;
+        let mut segment_fruits = Vec::with_capacity(self.segment_readers().len());
+        for (segment_ord, segment_reader) in self.segment_readers().iter().enumerate() {
+            let segment_fruit = collect_segment_async(
+                collector,
+                weight.as_ref(),
+                segment_ord as SegmentOrdinal,
+                segment_reader,
+            )
+            .await?;
+            segment_fruits.push(segment_fruit);
+        }
+        collector.merge_fruits(segment_fruits)
+    }
+
+    /// Loads the postings of the terms of the query in all of the segments, with the async
+    /// reads of the directory.
+    #[cfg(feature = "quickwit")]
+    async fn warm_up_query_terms(&self, query: &dyn Query) -> crate::Result<()> {
+        let mut terms: Vec<(&Term, bool)> = Vec::new();
+        query.query_terms(&mut |term, need_positions| terms.push((term, need_positions)));
+        for segment_reader in self.segment_readers() {
+            for &(term, need_positions) in &terms {
+                let inverted_index = segment_reader.inverted_index(term.field())?;
+                inverted_index.warm_postings(term, need_positions).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a cursor over the documents matching the query, in the order of their
+    /// [`DocAddress`], optionally with their scores and the values of some fast fields.
+    ///
+    /// The documents are only searched as the cursor is consumed. See [`HitCursor`].
+    pub fn hit_cursor(
+        &self,
+        query: &dyn Query,
+        options: HitCursorOptions,
+    ) -> crate::Result<HitCursor> {
+        HitCursor::new(self, query, options)
+    }
+
+    /// Returns the number of documents matching the query.
+    ///
+    /// The documents are not scored, and the segments count their matching documents with
+    /// [`Weight::count`](crate::query::Weight::count), which can rely on the doc frequencies of the
+    /// terms, or on set operations for boolean queries, rather than visiting every document.
+    pub fn count(&self, query: &dyn Query) -> crate::Result<usize> {
+        query.count(self)
+    }
+
+    /// Same as [`search(...)`](Searcher::search), but also returns the profile of the search.
+    ///
+    /// The profile holds, for each node of the query and each segment, the time spent creating
+    /// its weight and scorers, the time spent scoring and the number of documents scored and
+    /// skipped, as well as the time spent collecting each segment.
+    ///
+    /// Profiling has a cost of its own, and the profiled search does not go through the
+    /// specialized code paths of the scorers, so that it is slower than the plain search.
+    pub fn search_with_profile<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+    ) -> crate::Result<(C::Fruit, SearchProfile)> {
+        let (profiled_query, recorder) = profile_query(query);
+        let enabled_scoring = if collector.requires_scoring() {
+            EnableScoring::enabled_from_searcher(self)
+        } else {
+            EnableScoring::disabled_from_searcher(self)
+        };
+        let weight = profiled_query.weight(enabled_scoring)?;
+        let executor = self.inner.index.search_executor();
+        let segment_fruits_and_durations = executor.map(
+            |(segment_ord, segment_reader)| {
+                let start = Instant::now();
+                let segment_fruit = collector.collect_segment(
+                    weight.as_ref(),
+                    segment_ord as u32,
+                    segment_reader,
+                )?;
+                Ok((segment_fruit, start.elapsed()))
+            },
+            self.segment_readers().iter().enumerate(),
+        )?;
+        let mut segment_fruits = Vec::with_capacity(segment_fruits_and_durations.len());
+        let mut collection = Vec::with_capacity(segment_fruits_and_durations.len());
+        for (segment_ord, (segment_fruit, duration)) in
+            segment_fruits_and_durations.into_iter().enumerate()
+        {
+            segment_fruits.push(segment_fruit);
+            collection.push(SegmentCollectionProfile {
+                segment_ord: segment_ord as u32,
+                collection: duration,
+            });
+        }
+        let fruit = collector.merge_fruits(segment_fruits)?;
+        drop(weight);
+        let profile = SearchProfile {
+            query: recorder.profile(self.segment_readers()),
+            collection,
+        };
+        Ok((fruit, profile))
+    }
+
+    /// Same as [`search(...)`](Searcher::search), but going through the result cache of the
+    /// [`IndexReader`](crate::IndexReader), if it has one.
+    ///
+    /// The results are identified by the generation of the searcher, the `Debug` representation
+    /// of the query and the [`cache_key`](CacheableCollector::cache_key) of the collector. The
+    /// queries whose `Debug` representation does not identify them should not be cached.
+    ///
+    /// See [`IndexReaderBuilder::result_cache_num_entries`](crate::IndexReaderBuilder::result_cache_num_entries).
+    pub fn search_cached<C>(&self, query: &dyn Query, collector: &C) -> crate::Result<C::Fruit>
+    where
+        C: CacheableCollector,
+        C::Fruit: Clone + Sync,
+    {
+        let Some(result_cache) = &self.inner.result_cache else {
+            return self.search(query, collector);
+        };
+        let key = ResultCacheKey {
+            generation_id: self.generation().generation_id(),
+            query: format!("{query:?}"),
+            collector: collector.cache_key(),
+        };
+        if let Some(fruit) = result_cache.get::<C::Fruit>(&key) {
+            return Ok(fruit);
+        }
+        let fruit = self.search(query, collector)?;
+        result_cache.put(key, fruit.clone());
+        Ok(fruit)
+    }
+
     /// Same as [`search(...)`](Searcher::search) but allows specifying
     /// a [Bm25StatisticsProvider].
     ///
@@ -218,17 +628,102 @@ impl Searcher {
         executor: &Executor,
         enabled_scoring: EnableScoring,
     ) -> crate::Result<C::Fruit> {
+        let _span_guard = enter_span!("tantivy.search");
+        let metrics_recorder = self.index().metrics_recorder();
+        let start = Instant::now();
         let weight = query.weight(enabled_scoring)?;
+        if let Some(metrics_recorder) = metrics_recorder {
+            metrics_recorder.record_query_phase(QueryPhase::Weight, start.elapsed());
+        }
+        let start = Instant::now();
+        let fruit =
+            collector.collect_segments(weight.as_ref(), self.segment_readers(), executor)?;
+        if let Some(metrics_recorder) = metrics_recorder {
+            metrics_recorder.record_query_phase(QueryPhase::Collect, start.elapsed());
+        }
+        Ok(fruit)
+    }
+
+    /// Same as [`search_with_executor(...)`](Searcher::search_with_executor), but also splitting
+    /// the segments into ranges of at most `split_size` doc ids, searched as separate tasks.
+    ///
+    /// The threads of the executor pick the tasks as they become idle, so that one large segment
+    /// does not keep the whole search on one thread.
+    ///
+    /// This only applies to the collectors which do not require scoring, such as
+    /// [`Count`](crate::collector::Count), [`DocSetCollector`](crate::collector::DocSetCollector)
+    /// or the [aggregations](crate::aggregation). The collectors requiring scoring are run segment
+    /// by segment, as by `search_with_executor`.
+    ///
+    /// The scorer of the query is created for each range: the queries building the set of their
+    /// matching documents upfront, such as the regex queries, do it for each range. To keep this
+    /// cost bounded, `split_size` is raised to at least 16,384 doc ids, and rounded up to a
+    /// multiple of the postings block size so that the ranges do not split a block.
+    pub fn search_with_split_segments<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+        executor: &Executor,
+        split_size: DocId,
+    ) -> crate::Result<C::Fruit> {
+        if collector.requires_scoring() {
+            let enabled_scoring = EnableScoring::enabled_from_searcher(self);
+            return self.search_with_executor(query, collector, executor, enabled_scoring);
+        }
+        if split_size == 0 {
+            return Err(TantivyError::InvalidArgument(
+                "The split size must be strictly positive".to_string(),
+            ));
+        }
+        let split_size = clamp_split_size(split_size);
+        let weight = query.weight(EnableScoring::disabled_from_searcher(self))?;
         let segment_readers = self.segment_readers();
+        let tasks = segment_readers
+            .iter()
+            .enumerate()
+            .flat_map(|(segment_ord, segment_reader)| {
+                let max_doc = segment_reader.max_doc();
+                (0..max_doc).step_by(split_size as usize).map(move |start| {
+                    let end = start.saturating_add(split_size).min(max_doc);
+                    (segment_ord as u32, segment_reader, start..end)
+                })
+            });
         let fruits = executor.map(
-            |(segment_ord, segment_reader)| {
-                collector.collect_segment(weight.as_ref(), segment_ord as u32, segment_reader)
+            |(segment_ord, segment_reader, doc_range)| {
+                collector.collect_segment_range(
+                    weight.as_ref(),
+                    segment_ord,
+                    segment_reader,
+                    doc_range,
+                )
             },
-            segment_readers.iter().enumerate(),
+            tasks,
         )?;
         collector.merge_fruits(fruits)
     }
 
+    /// Returns the [`GlobalOrdinals`] of a `str` fast field, mapping the term ordinals of the
+    /// segments of the searcher to ordinals shared by all of them.
+    ///
+    /// They are built on the first call, and cached by the searcher: the searchers of an
+    /// [`IndexReader`](crate::IndexReader) share them until it reloads. A
+    /// [`GlobalOrdinalsWarmer`](crate::GlobalOrdinalsWarmer) builds them before a reload
+    /// publishes the new searcher.
+    pub fn global_ordinals(&self, field_name: &str) -> crate::Result<Arc<GlobalOrdinals>> {
+        if let Some(global_ordinals) = self.inner.global_ordinals.lock().unwrap().get(field_name) {
+            return Ok(global_ordinals.clone());
+        }
+        let global_ordinals = Arc::new(GlobalOrdinals::build(self.segment_readers(), field_name)?);
+        Ok(self
+            .inner
+            .global_ordinals
+            .lock()
+            .unwrap()
+            .entry(field_name.to_string())
+            .or_insert(global_ordinals)
+            .clone())
+    }
+
     /// Summarize total space usage of this searcher.
     pub fn space_usage(&self) -> io::Result<SearcherSpaceUsage> {
         let mut space_usage = SearcherSpaceUsage::new();
@@ -239,6 +734,75 @@ impl Searcher {
     }
 }
 
+/// The number of documents collected by [`Searcher::search_async`] between two yields to the
+/// executor.
+const ASYNC_COLLECT_BLOCK_LEN: usize = 1_024;
+
+/// A future yielding back once to the executor.
+struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+async fn collect_segment_async<C>(
+    collector: &C,
+    weight: &dyn Weight,
+    segment_ord: SegmentOrdinal,
+    segment_reader: &SegmentReader,
+) -> crate::Result<<C::Child as SegmentCollector>::Fruit>
+where
+    C: Collector,
+    C::Child: Send,
+{
+    let mut segment_collector = collector.for_segment(segment_ord, segment_reader)?;
+    let mut scorer = weight.scorer(segment_reader, 1.0)?;
+    let requires_scoring = collector.requires_scoring();
+    let alive_bitset = segment_reader.alive_bitset();
+    let mut num_docs_in_block = 0;
+    let mut doc = scorer.doc();
+    while doc != TERMINATED {
+        if alive_bitset.map_or(true, |alive_bitset| alive_bitset.is_alive(doc)) {
+            let score = if requires_scoring {
+                scorer.score()
+            } else {
+                0.0
+            };
+            segment_collector.collect(doc, score);
+        }
+        num_docs_in_block += 1;
+        if num_docs_in_block == ASYNC_COLLECT_BLOCK_LEN {
+            num_docs_in_block = 0;
+            YieldNow { yielded: false }.await;
+        }
+        doc = scorer.advance();
+    }
+    Ok(segment_collector.harvest())
+}
+
+/// Groups the positions of the doc addresses by segment ordinal.
+fn positions_by_segment(doc_addresses: &[DocAddress]) -> BTreeMap<u32, Vec<usize>> {
+    let mut positions_by_segment: BTreeMap<u32, Vec<usize>> = BTreeMap::new();
+    for (pos, doc_address) in doc_addresses.iter().enumerate() {
+        positions_by_segment
+            .entry(doc_address.segment_ord)
+            .or_default()
+            .push(pos);
+    }
+    positions_by_segment
+}
+
 impl From<Arc<SearcherInner>> for Searcher {
     fn from(inner: Arc<SearcherInner>) -> Self {
         Searcher { inner }
@@ -255,6 +819,8 @@ pub(crate) struct SearcherInner {
     segment_readers: Vec<SegmentReader>,
     store_readers: Vec<StoreReader>,
     generation: TrackedObject<SearcherGeneration>,
+    result_cache: Option<Arc<ResultCache>>,
+    global_ordinals: Mutex<HashMap<String, Arc<GlobalOrdinals>>>,
 }
 
 impl SearcherInner {
@@ -265,6 +831,7 @@ impl SearcherInner {
         segment_readers: Vec<SegmentReader>,
         generation: TrackedObject<SearcherGeneration>,
         doc_store_cache_num_blocks: usize,
+        result_cache: Option<Arc<ResultCache>>,
     ) -> io::Result<SearcherInner> {
         assert_eq!(
             &segment_readers
@@ -285,10 +852,22 @@ impl SearcherInner {
             segment_readers,
             store_readers,
             generation,
+            result_cache,
+            global_ordinals: Mutex::default(),
         })
     }
 }
 
+/// Minimum number of doc ids in the ranges of
+/// [`search_with_split_segments(...)`](Searcher::search_with_split_segments).
+const MIN_SPLIT_SIZE: DocId = 128 * COMPRESSION_BLOCK_SIZE as DocId;
+
+fn clamp_split_size(split_size: DocId) -> DocId {
+    let block_size = COMPRESSION_BLOCK_SIZE as DocId;
+    let num_blocks = (split_size.max(MIN_SPLIT_SIZE) - 1) / block_size + 1;
+    num_blocks.saturating_mul(block_size)
+}
+
 impl fmt::Debug for Searcher {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let segment_ids = self
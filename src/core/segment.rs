@@ -1,12 +1,14 @@
 use std::fmt;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use super::SegmentComponent;
+use crate::codec::Codec;
 use crate::core::{Index, SegmentId, SegmentMeta};
 use crate::directory::error::{OpenReadError, OpenWriteError};
 use crate::directory::{Directory, FileSlice, WritePtr};
 use crate::schema::Schema;
-use crate::Opstamp;
+use crate::{Opstamp, TantivyError};
 
 /// A segment is a piece of the index.
 #[derive(Clone)]
@@ -75,6 +77,15 @@ impl Segment {
         self.meta.relative_path(component)
     }
 
+    /// Returns the codec which wrote the segment, or an error if it is not registered in the
+    /// [`CodecManager`](crate::codec::CodecManager) of the index.
+    pub(crate) fn codec(&self) -> crate::Result<Arc<dyn Codec>> {
+        let codec_name = self.meta.codec();
+        self.index.codecs().get(codec_name).ok_or_else(|| {
+            TantivyError::InvalidArgument(format!("Codec {codec_name:?} is not registered."))
+        })
+    }
+
     /// Open one of the component file for a *regular* read.
     pub fn open_read(&self, component: SegmentComponent) -> Result<FileSlice, OpenReadError> {
         let path = self.relative_path(component);
@@ -4,7 +4,7 @@ use std::slice;
 /// Each component is stored in its own file,
 /// using the pattern `segment_uuid`.`component_extension`,
 /// except the delete component that takes an `segment_uuid`.`delete_opstamp`.`component_extension`
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum SegmentComponent {
     /// Postings (or inverted list). Sorted lists of document ids, associated with terms
     Postings,
@@ -27,12 +27,25 @@ pub enum SegmentComponent {
     /// Bitset describing which document of the segment is alive.
     /// (It was representing deleted docs but changed to represent alive docs from v0.17)
     Delete,
+    /// Sorted numeric values of the fields with a points index, used to
+    /// answer range queries.
+    Points,
+    /// Terms, positions and offsets of each document, for the fields storing term vectors.
+    TermVectors,
+    /// Payloads of the tokens of each document, for the fields storing payloads.
+    Payloads,
+    /// Completions of the values of the completion fields, used to suggest completions of a
+    /// prefix.
+    Completions,
+    /// HNSW graphs of the vectors of the dense vector fields, used to search their
+    /// approximate nearest neighbours.
+    Hnsw,
 }
 
 impl SegmentComponent {
     /// Iterates through the components.
     pub fn iterator() -> slice::Iter<'static, SegmentComponent> {
-        static SEGMENT_COMPONENTS: [SegmentComponent; 8] = [
+        static SEGMENT_COMPONENTS: [SegmentComponent; 13] = [
             SegmentComponent::Postings,
             SegmentComponent::Positions,
             SegmentComponent::FastFields,
@@ -41,6 +54,11 @@ impl SegmentComponent {
             SegmentComponent::Store,
             SegmentComponent::TempStore,
             SegmentComponent::Delete,
+            SegmentComponent::Points,
+            SegmentComponent::TermVectors,
+            SegmentComponent::Payloads,
+            SegmentComponent::Completions,
+            SegmentComponent::Hnsw,
         ];
         SEGMENT_COMPONENTS.iter()
     }
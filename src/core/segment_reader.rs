@@ -4,16 +4,23 @@ use std::{fmt, io};
 
 use fail::fail_point;
 
+use crate::codec::Codec;
 use crate::core::{InvertedIndexReader, Segment, SegmentComponent, SegmentId};
+use crate::directory::error::OpenReadError;
 use crate::directory::{CompositeFile, FileSlice};
 use crate::error::DataCorruption;
 use crate::fastfield::{intersect_alive_bitsets, AliveBitSet, FacetReader, FastFieldReaders};
 use crate::fieldnorm::{FieldNormReader, FieldNormReaders};
+use crate::payload::{DocPayloads, PayloadReaders};
+use crate::points::PointsReaders;
+use crate::reader::{warm_file_slice, WarmUpComponents};
 use crate::schema::{Field, IndexRecordOption, Schema, Type};
 use crate::space_usage::SegmentSpaceUsage;
 use crate::store::StoreReader;
-use crate::termdict::TermDictionary;
-use crate::{DocId, Opstamp};
+use crate::suggest::CompletionReaders;
+use crate::termvector::{TermVector, TermVectorReaders};
+use crate::vector::HnswReaders;
+use crate::{DocId, IndexSortByField, Opstamp};
 
 /// Entry point to access all of the datastructures of the `Segment`
 ///
@@ -40,10 +47,17 @@ pub struct SegmentReader {
     positions_composite: CompositeFile,
     fast_fields_readers: FastFieldReaders,
     fieldnorm_readers: FieldNormReaders,
+    points_readers: PointsReaders,
+    term_vector_readers: TermVectorReaders,
+    payload_readers: PayloadReaders,
+    completion_readers: CompletionReaders,
+    hnsw_readers: HnswReaders,
 
     store_file: FileSlice,
     alive_bitset_opt: Option<AliveBitSet>,
     schema: Schema,
+    sort_by_field: Option<IndexSortByField>,
+    codec: Arc<dyn Codec>,
 }
 
 impl SegmentReader {
@@ -64,6 +78,12 @@ impl SegmentReader {
         &self.schema
     }
 
+    /// Returns the field and the order the documents of this segment are sorted by, as
+    /// configured in the [`IndexSettings`](crate::IndexSettings) of the index.
+    pub fn sort_by_field(&self) -> Option<&IndexSortByField> {
+        self.sort_by_field.as_ref()
+    }
+
     /// Return the number of documents that have been
     /// deleted in the segment.
     pub fn num_deleted_docs(&self) -> DocId {
@@ -89,12 +109,27 @@ impl SegmentReader {
         &self.fast_fields_readers
     }
 
+    /// Returns a copy of the segment reader whose fast fields outside of `allowed_fields` are
+    /// hidden.
+    pub(crate) fn with_allowed_fast_fields(
+        &self,
+        allowed_fields: Arc<Vec<Field>>,
+    ) -> SegmentReader {
+        let mut segment_reader = self.clone();
+        segment_reader.fast_fields_readers = segment_reader
+            .fast_fields_readers
+            .with_allowed_fields(allowed_fields);
+        segment_reader
+    }
+
     /// Accessor to the `FacetReader` associated with a given `Field`.
     pub fn facet_reader(&self, field_name: &str) -> crate::Result<FacetReader> {
         let schema = self.schema();
         let field = schema.get_field(field_name)?;
         let field_entry = schema.get_field_entry(field);
-        if field_entry.field_type().value_type() != Type::Facet {
+        if field_entry.field_type().value_type() != Type::Facet
+            || self.fast_fields().is_hidden(field)
+        {
             return Err(crate::TantivyError::SchemaError(format!(
                 "`{field_name}` is not a facet field.`"
             )));
@@ -128,6 +163,69 @@ impl SegmentReader {
         &self.fieldnorm_readers
     }
 
+    /// Accessor to the points of the fields with a points index.
+    pub fn points_readers(&self) -> &PointsReaders {
+        &self.points_readers
+    }
+
+    /// Accessor to the term vectors of the fields storing term vectors.
+    pub fn term_vector_readers(&self) -> &TermVectorReaders {
+        &self.term_vector_readers
+    }
+
+    /// Returns the [term vector](crate::termvector) of the given document for the given field.
+    ///
+    /// Returns an error if the field does not store term vectors.
+    pub fn term_vector(&self, doc: DocId, field: Field) -> crate::Result<TermVector> {
+        let field_entry = self.schema.get_field_entry(field);
+        if !field_entry.has_term_vectors() {
+            return Err(crate::TantivyError::SchemaError(format!(
+                "Field {:?} does not store term vectors",
+                field_entry.name()
+            )));
+        }
+        // Segments created before the field was added to the schema have no term vectors
+        // for it.
+        match self.term_vector_readers.get_field(field)? {
+            Some(term_vector_reader) => term_vector_reader.term_vector(doc),
+            None => Ok(TermVector::default()),
+        }
+    }
+
+    /// Accessor to the [completions](crate::suggest) of the completion fields.
+    pub fn completion_readers(&self) -> &CompletionReaders {
+        &self.completion_readers
+    }
+
+    /// Accessor to the [HNSW graphs](crate::vector) of the dense vector fields.
+    pub fn hnsw_readers(&self) -> &HnswReaders {
+        &self.hnsw_readers
+    }
+
+    /// Accessor to the payloads of the fields storing payloads.
+    pub fn payload_readers(&self) -> &PayloadReaders {
+        &self.payload_readers
+    }
+
+    /// Returns the [payloads](crate::payload) of the tokens of the given document for the
+    /// given field.
+    ///
+    /// Returns an error if the field does not store payloads.
+    pub fn payloads(&self, doc: DocId, field: Field) -> crate::Result<DocPayloads> {
+        let field_entry = self.schema.get_field_entry(field);
+        if !field_entry.has_payloads() {
+            return Err(crate::TantivyError::SchemaError(format!(
+                "Field {:?} does not store payloads",
+                field_entry.name()
+            )));
+        }
+        // Segments created before the field was added to the schema have no payloads for it.
+        match self.payload_readers.get_field(field)? {
+            Some(payload_reader) => payload_reader.doc_payloads(doc),
+            None => Ok(DocPayloads::default()),
+        }
+    }
+
     /// Accessor to the segment's [`StoreReader`](crate::store::StoreReader).
     ///
     /// `cache_num_blocks` sets the number of decompressed blocks to be cached in an LRU.
@@ -146,6 +244,7 @@ impl SegmentReader {
         segment: &Segment,
         custom_bitset: Option<AliveBitSet>,
     ) -> crate::Result<SegmentReader> {
+        let codec = segment.codec()?;
         let termdict_file = segment.open_read(SegmentComponent::Terms)?;
         let termdict_composite = CompositeFile::open(&termdict_file)?;
 
@@ -167,10 +266,44 @@ impl SegmentReader {
         let schema = segment.schema();
 
         let fast_fields_data = segment.open_read(SegmentComponent::FastFields)?;
-        let fast_fields_readers = FastFieldReaders::open(fast_fields_data, schema.clone())?;
+        let fast_fields_readers = FastFieldReaders::open_with_format(
+            fast_fields_data,
+            schema.clone(),
+            codec.fast_fields_format(),
+        )?
+        .with_runtime_fields(segment.index().runtime_fields().to_vec());
         let fieldnorm_data = segment.open_read(SegmentComponent::FieldNorms)?;
         let fieldnorm_readers = FieldNormReaders::open(fieldnorm_data)?;
 
+        // Segments created before points, term vectors, payloads, completions and HNSW
+        // graphs were introduced do not have these files.
+        let points_readers = match open_optional_component(segment, SegmentComponent::Points)? {
+            Some(points_file) => PointsReaders::open(points_file)?,
+            None => PointsReaders::empty(),
+        };
+
+        let term_vector_readers =
+            match open_optional_component(segment, SegmentComponent::TermVectors)? {
+                Some(term_vectors_file) => TermVectorReaders::open(term_vectors_file)?,
+                None => TermVectorReaders::empty(),
+            };
+
+        let payload_readers = match open_optional_component(segment, SegmentComponent::Payloads)? {
+            Some(payloads_file) => PayloadReaders::open(payloads_file)?,
+            None => PayloadReaders::empty(),
+        };
+
+        let completion_readers =
+            match open_optional_component(segment, SegmentComponent::Completions)? {
+                Some(completions_file) => CompletionReaders::open(completions_file)?,
+                None => CompletionReaders::empty(),
+            };
+
+        let hnsw_readers = match open_optional_component(segment, SegmentComponent::Hnsw)? {
+            Some(hnsw_file) => HnswReaders::open(hnsw_file)?,
+            None => HnswReaders::empty(),
+        };
+
         let original_bitset = if segment.meta().has_deletes() {
             let alive_doc_file_slice = segment.open_read(SegmentComponent::Delete)?;
             let alive_doc_data = alive_doc_file_slice.read_bytes()?;
@@ -195,12 +328,19 @@ impl SegmentReader {
             postings_composite,
             fast_fields_readers,
             fieldnorm_readers,
+            points_readers,
+            term_vector_readers,
+            payload_readers,
+            completion_readers,
+            hnsw_readers,
             segment_id: segment.id(),
             delete_opstamp: segment.meta().delete_opstamp(),
             store_file,
             alive_bitset_opt,
             positions_composite,
             schema,
+            sort_by_field: segment.index().settings().sort_by_field.clone(),
+            codec,
         })
     }
 
@@ -266,10 +406,11 @@ impl SegmentReader {
         })?;
 
         let inv_idx_reader = Arc::new(InvertedIndexReader::new(
-            TermDictionary::open(termdict_file)?,
+            self.codec.term_dictionary_format().open(termdict_file)?,
             postings_file,
             positions_file,
             record_option,
+            Arc::clone(&self.codec),
         )?);
 
         // by releasing the lock in between, we may end up opening the inverting index
@@ -282,6 +423,45 @@ impl SegmentReader {
         Ok(inv_idx_reader)
     }
 
+    /// Loads the given components of the segment, so that the first searches do not have to
+    /// read them from the directory, or page fault on the memory mapped files.
+    ///
+    /// The fields and fast fields the segment does not have are ignored.
+    pub fn warm_up(&self, components: &WarmUpComponents) -> crate::Result<()> {
+        for field in &components.term_dictionaries {
+            if let Some(termdict_file) = self.termdict_composite.open_read(*field) {
+                warm_file_slice(&termdict_file)?;
+            }
+        }
+        for field in &components.postings {
+            if let Some(postings_file) = self.postings_composite.open_read(*field) {
+                warm_file_slice(&postings_file)?;
+            }
+            if let Some(positions_file) = self.positions_composite.open_read(*field) {
+                warm_file_slice(&positions_file)?;
+            }
+        }
+        for field_name in &components.fast_fields {
+            for column_handle in self
+                .fast_fields_readers
+                .columnar()
+                .read_columns(field_name)?
+            {
+                warm_file_slice(column_handle.file_slice())?;
+            }
+        }
+        for field in &components.fieldnorms {
+            if let Some(fieldnorm_file) = self.fieldnorm_readers.get_inner_file().open_read(*field)
+            {
+                warm_file_slice(&fieldnorm_file)?;
+            }
+        }
+        if components.store_index {
+            warm_file_slice(&StoreReader::skip_index_file(self.store_file.clone())?)?;
+        }
+        Ok(())
+    }
+
     /// Returns the segment id
     pub fn segment_id(&self) -> SegmentId {
         self.segment_id
@@ -323,6 +503,11 @@ impl SegmentReader {
             self.positions_composite.space_usage(),
             self.fast_fields_readers.space_usage(self.schema())?,
             self.fieldnorm_readers.space_usage(),
+            self.points_readers.space_usage(),
+            self.term_vector_readers.space_usage(),
+            self.payload_readers.space_usage(),
+            self.completion_readers.space_usage(),
+            self.hnsw_readers.space_usage(),
             self.get_store_reader(0)?.space_usage(),
             self.alive_bitset_opt
                 .as_ref()
@@ -332,6 +517,19 @@ impl SegmentReader {
     }
 }
 
+/// Opens a component file, or returns `None` if the segment was created before the component was
+/// introduced. The other errors are propagated.
+fn open_optional_component(
+    segment: &Segment,
+    component: SegmentComponent,
+) -> crate::Result<Option<FileSlice>> {
+    match segment.open_read(component) {
+        Ok(file) => Ok(Some(file)),
+        Err(OpenReadError::FileDoesNotExist(_)) => Ok(None),
+        Err(open_read_error) => Err(open_read_error.into()),
+    }
+}
+
 fn intersect_alive_bitset(
     left_opt: Option<AliveBitSet>,
     right_opt: Option<AliveBitSet>,
@@ -356,8 +554,8 @@ impl fmt::Debug for SegmentReader {
 #[cfg(test)]
 mod test {
     use crate::core::Index;
-    use crate::schema::{Schema, Term, STORED, TEXT};
-    use crate::DocId;
+    use crate::schema::{NumericOptions, Schema, Term, STORED, TEXT};
+    use crate::{DocId, Directory, SegmentComponent, SegmentReader};
 
     #[test]
     fn test_num_alive() -> crate::Result<()> {
@@ -416,4 +614,21 @@ mod test {
         assert_eq!(vec![0u32, 2u32], docs);
         Ok(())
     }
+
+    #[test]
+    fn test_open_segment_with_unreadable_points() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let num = schema_builder.add_u64_field("num", NumericOptions::default().set_points());
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(num => 1u64))?;
+        index_writer.commit()?;
+        let segment = index.searchable_segments()?.pop().unwrap();
+        assert!(SegmentReader::open(&segment).is_ok());
+
+        let points_path = segment.relative_path(SegmentComponent::Points);
+        index.directory().atomic_write(&points_path, b"corrupted")?;
+        assert!(SegmentReader::open(&segment).is_err());
+        Ok(())
+    }
 }
@@ -0,0 +1,151 @@
+use murmurhash32::murmurhash2;
+
+use crate::schema::{Document, Field, Value};
+use crate::TantivyError;
+
+/// Routes the documents of a sharded index to one of `num_shards` sub-indexes, given the value
+/// of their routing field, e.g. a tenant or a user id.
+///
+/// The shard of a value is the murmur2 hash of its bytes, modulo the number of shards:
+/// - `str` values are hashed as their UTF-8 bytes,
+/// - `u64` and `i64` values as their 8 big-endian bytes,
+/// - `bytes` values as they are.
+///
+/// The hash does not depend on the platform nor on the version of tantivy, so that the shard
+/// of a document can be computed again to update or delete it. Changing the number of shards
+/// moves most of the documents to a different shard.
+///
+/// The documents sharing a routing value are all in the same shard: the searches targeting
+/// a routing value only need to search this shard, see
+/// [`MultiSearcher::search_routed`](crate::MultiSearcher::search_routed).
+///
+/// ```rust
+/// use tantivy::schema::{Schema, Value, STRING};
+/// use tantivy::{doc, ShardRouter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let tenant = schema_builder.add_text_field("tenant", STRING);
+/// let router = ShardRouter::new(tenant, 4);
+/// let shard = router.shard_for_document(&doc!(tenant => "acme"))?;
+/// assert!(shard < 4);
+/// assert_eq!(router.shard_for_value(&Value::from("acme"))?, shard);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ShardRouter {
+    routing_field: Field,
+    num_shards: usize,
+}
+
+impl ShardRouter {
+    /// Creates a router over `num_shards` shards, routing the documents by the value of
+    /// `routing_field`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_shards` is 0.
+    pub fn new(routing_field: Field, num_shards: usize) -> ShardRouter {
+        assert!(
+            num_shards >= 1,
+            "The number of shards must be strictly positive."
+        );
+        ShardRouter {
+            routing_field,
+            num_shards,
+        }
+    }
+
+    /// Returns the field the documents are routed by.
+    pub fn routing_field(&self) -> Field {
+        self.routing_field
+    }
+
+    /// Returns the number of shards.
+    pub fn num_shards(&self) -> usize {
+        self.num_shards
+    }
+
+    /// Returns the shard of the given routing value bytes.
+    pub fn shard_for_bytes(&self, bytes: &[u8]) -> usize {
+        murmurhash2(bytes) as usize % self.num_shards
+    }
+
+    /// Returns the shard of the given routing value.
+    ///
+    /// Returns an error if the value is not a `str`, `u64`, `i64` or `bytes` value.
+    pub fn shard_for_value(&self, value: &Value) -> crate::Result<usize> {
+        let shard = match value {
+            Value::Str(text) => self.shard_for_bytes(text.as_bytes()),
+            Value::U64(val) => self.shard_for_bytes(&val.to_be_bytes()),
+            Value::I64(val) => self.shard_for_bytes(&val.to_be_bytes()),
+            Value::Bytes(bytes) => self.shard_for_bytes(bytes),
+            _ => {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "Unsupported routing value {value:?}"
+                )));
+            }
+        };
+        Ok(shard)
+    }
+
+    /// Returns the shard of the document, given the value of its routing field.
+    ///
+    /// Returns an error if the document does not have exactly one value for the routing field.
+    pub fn shard_for_document(&self, doc: &Document) -> crate::Result<usize> {
+        let mut values = doc.get_all(self.routing_field);
+        match (values.next(), values.next()) {
+            (Some(value), None) => self.shard_for_value(value),
+            _ => Err(TantivyError::InvalidArgument(
+                "A routed document requires exactly one value for the routing field".to_string(),
+            )),
+        }
+    }
+
+    /// Returns the sorted and deduplicated shards of the given routing values.
+    pub fn shards_for_values(&self, values: &[Value]) -> crate::Result<Vec<usize>> {
+        let mut shards = values
+            .iter()
+            .map(|value| self.shard_for_value(value))
+            .collect::<crate::Result<Vec<usize>>>()?;
+        shards.sort_unstable();
+        shards.dedup();
+        Ok(shards)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ShardRouter;
+    use crate::doc;
+    use crate::schema::{Schema, Value, STRING};
+
+    #[test]
+    fn test_shard_router_is_stable() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING);
+        let router = ShardRouter::new(tenant, 8);
+        // The shards must not change across versions, or the routed documents are lost.
+        assert_eq!(router.shard_for_value(&Value::from("acme"))?, 4);
+        assert_eq!(router.shard_for_value(&Value::from("globex"))?, 6);
+        assert_eq!(router.shard_for_value(&Value::U64(42))?, 2);
+        assert_eq!(router.shard_for_value(&Value::I64(-42))?, 6);
+        assert!(router.shard_for_value(&Value::F64(1.0)).is_err());
+
+        assert_eq!(router.shard_for_document(&doc!(tenant => "acme"))?, 4);
+        assert!(router.shard_for_document(&doc!()).is_err());
+        assert!(router
+            .shard_for_document(&doc!(tenant => "acme", tenant => "globex"))
+            .is_err());
+        assert_eq!(
+            router.shards_for_values(&[
+                Value::from("globex"),
+                Value::from("acme"),
+                Value::from("acme")
+            ])?,
+            vec![4, 6]
+        );
+        Ok(())
+    }
+}
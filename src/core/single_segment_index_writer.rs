@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use crate::indexer::operation::AddOperation;
 use crate::indexer::segment_updater::save_metas;
 use crate::indexer::SegmentWriter;
@@ -43,6 +45,8 @@ impl SingleSegmentIndexWriter {
             schema: index.schema(),
             opstamp: 0,
             payload: None,
+            metadata: BTreeMap::new(),
+            tokenizers: index.tokenizers().configs(),
         };
         save_metas(&index_meta, index.directory())?;
         index.directory().sync_directory()?;
@@ -0,0 +1,323 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use common::HasLen;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{Index, IndexMeta, META_FILEPATH};
+use crate::directory::{Directory, FileSlice, ManagedDirectory, META_LOCK};
+use crate::{Opstamp, TantivyError};
+
+/// Size of the reads of the files streamed by [`IndexSnapshot::open_file()`].
+const STREAM_BUFFER_LEN: usize = 1 << 20;
+
+/// A file of an [`IndexSnapshot`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotFile {
+    /// The number of bytes of the file, footer included.
+    pub num_bytes: u64,
+    /// The CRC32 checksum of the file, without its footer, as recorded in the footer.
+    pub crc: u32,
+}
+
+/// The list of the files of an [`IndexSnapshot`], along with its `meta.json`.
+///
+/// The manifest of a backup can be serialized, and given to the next backup to only copy the
+/// files that were created in between.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    opstamp: Opstamp,
+    files: BTreeMap<PathBuf, SnapshotFile>,
+    meta: String,
+}
+
+impl SnapshotManifest {
+    /// Returns the opstamp of the commit of the snapshot.
+    pub fn opstamp(&self) -> Opstamp {
+        self.opstamp
+    }
+
+    /// Returns the files of the segments of the snapshot.
+    ///
+    /// The `meta.json` file is not included, as it is kept in the manifest.
+    pub fn files(&self) -> &BTreeMap<PathBuf, SnapshotFile> {
+        &self.files
+    }
+
+    /// Returns the files of this snapshot which are not in a previous one.
+    ///
+    /// The files of a segment never change once written, so these are the only files to copy
+    /// for an incremental backup.
+    pub fn new_files_since<'a>(
+        &'a self,
+        previous: &'a SnapshotManifest,
+    ) -> impl Iterator<Item = &'a Path> + 'a {
+        self.files
+            .iter()
+            .filter(move |(path, file)| previous.files.get(*path) != Some(file))
+            .map(|(path, _)| path.as_path())
+    }
+
+    /// Restores the snapshot from a backup directory into the target directory, and opens the
+    /// restored index.
+    ///
+    /// The backup directory is typically the target of [`IndexSnapshot::backup()`]. It may
+    /// hold the files of several snapshots: only the files of this one are copied, and the
+    /// `meta.json` of the snapshot is written last. No index writer must be opened on the
+    /// target directory during the restore.
+    pub fn restore<T: Into<Box<dyn Directory>>>(
+        &self,
+        backup_directory: &dyn Directory,
+        target_directory: T,
+    ) -> crate::Result<Index> {
+        let backup_directory = ManagedDirectory::wrap(backup_directory.box_clone())?;
+        let target_directory: Box<dyn Directory> = target_directory.into();
+        let managed_target_directory = ManagedDirectory::wrap(target_directory.box_clone())?;
+        for path in self.files.keys() {
+            managed_target_directory.copy_file_from(&backup_directory, path)?;
+        }
+        write_meta(&managed_target_directory, &self.meta)?;
+        Index::open(target_directory)
+    }
+
+    /// Returns the content of the `meta.json` file of the snapshot.
+    pub(crate) fn meta(&self) -> &str {
+        &self.meta
+    }
+}
+
+pub(crate) fn write_meta(directory: &ManagedDirectory, meta: &str) -> crate::Result<()> {
+    directory.sync_directory()?;
+    directory.atomic_write(&META_FILEPATH, meta.as_bytes())?;
+    Ok(())
+}
+
+/// A commit point of an index, whose files are kept alive for as long as the snapshot is.
+///
+/// The garbage collection of the index writer does not delete the files of the segments of a
+/// snapshot, even if they are merged or their documents are deleted in the meantime. The
+/// snapshot can therefore be copied while the index is being written to, which copying the
+/// directory of a live index does not allow, as its `meta.json` may refer to different
+/// segments by the time all of the files are copied.
+///
+/// The files are only protected from the writers of the same [`Index`] instance, or of one of
+/// its clones: the snapshot must be taken on the index the writer was created from.
+///
+/// ```rust
+/// use tantivy::directory::RamDirectory;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+/// index_writer.add_document(doc!(title => "The Old Man and the Sea"))?;
+/// index_writer.commit()?;
+///
+/// let backup_directory = RamDirectory::create();
+/// let manifest = index.snapshot()?.backup(&backup_directory, None)?;
+///
+/// index_writer.add_document(doc!(title => "Of Mice and Men"))?;
+/// index_writer.commit()?;
+/// // Only copies the new segment.
+/// let manifest = index.snapshot()?.backup(&backup_directory, Some(&manifest))?;
+///
+/// let restored_index = manifest.restore(&backup_directory, RamDirectory::create())?;
+/// assert_eq!(restored_index.reader()?.searcher().num_docs(), 2);
+/// # Ok(())
+/// # }
+/// ```
+pub struct IndexSnapshot {
+    index: Index,
+    // Keeps the segments of the snapshot in the inventory of the index, which protects their
+    // files from the garbage collection.
+    _index_meta: IndexMeta,
+    manifest: SnapshotManifest,
+}
+
+impl IndexSnapshot {
+    pub(crate) fn new(index: &Index) -> crate::Result<IndexSnapshot> {
+        // Prevents the segment files from getting deleted before the segments are tracked.
+        let _meta_lock = index.directory().acquire_lock(&META_LOCK)?;
+        let index_meta = index.load_metas()?;
+        let mut files = BTreeMap::new();
+        for segment_meta in &index_meta.segments {
+            for path in segment_meta.list_files() {
+                if index.directory().exists(&path)? {
+                    let num_bytes = index.directory().open_read_with_footer(&path)?.len() as u64;
+                    let crc = index.directory().footer_crc(&path)?;
+                    files.insert(path, SnapshotFile { num_bytes, crc });
+                }
+            }
+        }
+        let mut meta = serde_json::to_string_pretty(&index_meta)?;
+        meta.push('\n');
+        let manifest = SnapshotManifest {
+            opstamp: index_meta.opstamp,
+            files,
+            meta,
+        };
+        Ok(IndexSnapshot {
+            index: index.clone(),
+            _index_meta: index_meta,
+            manifest,
+        })
+    }
+
+    /// Returns the manifest of the snapshot.
+    pub fn manifest(&self) -> &SnapshotManifest {
+        &self.manifest
+    }
+
+    /// Opens a file of the snapshot as a stream of its bytes, footer included, typically to send
+    /// it to an [`IndexReplica`](crate::IndexReplica).
+    pub fn open_file(&self, path: &Path) -> crate::Result<impl io::Read + Send> {
+        if !self.manifest.files.contains_key(path) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "{path:?} is not a file of the snapshot"
+            )));
+        }
+        let file_slice = self.index.directory().open_read_with_footer(path)?;
+        Ok(io::BufReader::with_capacity(
+            STREAM_BUFFER_LEN,
+            FileSliceReader {
+                file_slice,
+                offset: 0,
+            },
+        ))
+    }
+
+    /// Copies the snapshot to the target directory, which becomes a copy of the index at this
+    /// commit point, and returns the manifest of the snapshot.
+    ///
+    /// Given the manifest of a previous backup to the same directory, only the files created
+    /// since are copied. The files of the previous backups are kept, so that any of them can
+    /// be restored with [`SnapshotManifest::restore()`]. The `meta.json` is written last, so
+    /// that an interrupted backup leaves the previous one readable.
+    pub fn backup(
+        &self,
+        target_directory: &dyn Directory,
+        previous: Option<&SnapshotManifest>,
+    ) -> crate::Result<SnapshotManifest> {
+        let target_directory = ManagedDirectory::wrap(target_directory.box_clone())?;
+        let paths: Vec<&Path> = match previous {
+            Some(previous) => self.manifest.new_files_since(previous).collect(),
+            None => self.manifest.files.keys().map(PathBuf::as_path).collect(),
+        };
+        for path in paths {
+            target_directory.copy_file_from(self.index.directory(), path)?;
+        }
+        write_meta(&target_directory, &self.manifest.meta)?;
+        Ok(self.manifest.clone())
+    }
+}
+
+struct FileSliceReader {
+    file_slice: FileSlice,
+    offset: usize,
+}
+
+impl io::Read for FileSliceReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let end = (self.offset + buf.len()).min(self.file_slice.len());
+        let bytes = self.file_slice.read_bytes_slice(self.offset..end)?;
+        buf[..bytes.len()].copy_from_slice(bytes.as_slice());
+        self.offset = end;
+        Ok(bytes.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SnapshotManifest;
+    use crate::directory::RamDirectory;
+    use crate::indexer::NoMergePolicy;
+    use crate::schema::{Schema, INDEXED};
+    use crate::{Directory, Index};
+
+    #[test]
+    fn test_snapshot_keeps_files_alive() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(id => 1u64))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(id => 2u64))?;
+        index_writer.commit()?;
+        let snapshot = index.snapshot()?;
+        let manifest = snapshot.manifest().clone();
+        assert!(!manifest.files().is_empty());
+
+        index_writer
+            .merge(&index.searchable_segment_ids()?)
+            .wait()?;
+        index_writer.garbage_collect_files().wait()?;
+        for path in manifest.files().keys() {
+            assert!(index.directory().exists(path)?);
+        }
+
+        // The snapshot can still be backed up after the merge.
+        let backup_directory = RamDirectory::create();
+        snapshot.backup(&backup_directory, None)?;
+        let restored = manifest.restore(&backup_directory, RamDirectory::create())?;
+        assert_eq!(restored.searchable_segment_ids()?.len(), 2);
+        assert_eq!(restored.reader()?.searcher().num_docs(), 2);
+
+        drop(snapshot);
+        index_writer.garbage_collect_files().wait()?;
+        for path in manifest.files().keys() {
+            assert!(!index.directory().exists(path)?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_incremental_backup() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(id => 1u64))?;
+        index_writer.commit()?;
+
+        let backup_directory = RamDirectory::create();
+        let first_manifest = index.snapshot()?.backup(&backup_directory, None)?;
+        index_writer.add_document(doc!(id => 2u64))?;
+        index_writer.commit()?;
+        let second_snapshot = index.snapshot()?;
+        let new_files: Vec<_> = second_snapshot
+            .manifest()
+            .new_files_since(&first_manifest)
+            .collect();
+        assert!(!new_files.is_empty());
+        assert!(new_files.len() < second_snapshot.manifest().files().len());
+        for path in &new_files {
+            assert!(!backup_directory.exists(path)?);
+        }
+        let second_manifest = second_snapshot.backup(&backup_directory, Some(&first_manifest))?;
+
+        // The backup directory is an index at the last backup.
+        assert_eq!(
+            Index::open(backup_directory.clone())?
+                .reader()?
+                .searcher()
+                .num_docs(),
+            2
+        );
+        // The manifests survive serialization, and any backup can be restored.
+        let first_manifest: SnapshotManifest =
+            serde_json::from_str(&serde_json::to_string(&first_manifest)?)?;
+        let restored = first_manifest.restore(&backup_directory, RamDirectory::create())?;
+        assert_eq!(restored.reader()?.searcher().num_docs(), 1);
+        let restored = second_manifest.restore(&backup_directory, RamDirectory::create())?;
+        assert_eq!(restored.reader()?.searcher().num_docs(), 2);
+        assert_eq!(restored.load_metas()?.opstamp, second_manifest.opstamp());
+        Ok(())
+    }
+}
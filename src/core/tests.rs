@@ -2,8 +2,13 @@ use crate::collector::Count;
 use crate::directory::{RamDirectory, WatchCallback};
 use crate::indexer::NoMergePolicy;
 use crate::query::TermQuery;
-use crate::schema::{Field, IndexRecordOption, Schema, INDEXED, STRING, TEXT};
-use crate::tokenizer::TokenizerManager;
+use crate::schema::{
+    Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, INDEXED, STORED, STRING, TEXT,
+};
+use crate::store::{Compressor, ZstdCompressor};
+use crate::tokenizer::{
+    RawTokenizer, TextAnalyzerConfig, TokenFilterConfig, TokenizerConfig, TokenizerManager,
+};
 use crate::{
     Directory, Document, Index, IndexBuilder, IndexReader, IndexSettings, ReloadPolicy, SegmentId,
     Term,
@@ -38,6 +43,54 @@ fn test_set_tokenizer_manager() {
     assert!(index.tokenizers().get("raw").is_none());
 }
 
+#[test]
+fn test_tokenizer_config_persisted() -> crate::Result<()> {
+    let directory = RamDirectory::create();
+    let mut schema_builder = Schema::builder();
+    let body = schema_builder.add_text_field(
+        "body",
+        TextOptions::default()
+            .set_indexing_options(TextFieldIndexing::default().set_tokenizer("custom")),
+    );
+    let schema = schema_builder.build();
+    let config = TextAnalyzerConfig {
+        char_filters: Vec::new(),
+        tokenizer: TokenizerConfig::Whitespace,
+        filters: vec![TokenFilterConfig::LowerCaser],
+    };
+    {
+        let index = Index::create(directory.clone(), schema.clone(), IndexSettings::default())?;
+        index
+            .tokenizers()
+            .register_config("custom", config.clone())?;
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(body=>"Hello-World"))?;
+        index_writer.commit()?;
+    }
+    let index = Index::open(directory.clone())?;
+    assert_eq!(index.tokenizers().configs().get("custom"), Some(&config));
+    let mut token_stream = index
+        .tokenizers()
+        .get("custom")
+        .unwrap()
+        .token_stream("Hello-World");
+    assert_eq!(token_stream.next().unwrap().text, "hello-world");
+    let searcher = index.reader()?.searcher();
+    let term_query = TermQuery::new(
+        Term::from_field_text(body, "hello-world"),
+        IndexRecordOption::Basic,
+    );
+    assert_eq!(searcher.search(&term_query, &Count)?, 1);
+
+    // Tokenizers registered without a configuration are not persisted, and
+    // replace a persisted tokenizer with the same name.
+    index.tokenizers().register("custom", RawTokenizer);
+    assert!(index.tokenizers().configs().is_empty());
+    let index = Index::open_or_create(directory.clone(), schema)?;
+    assert_eq!(index.tokenizers().configs().get("custom"), Some(&config));
+    Ok(())
+}
+
 #[test]
 fn test_index_exists() {
     let directory: Box<dyn Directory> = Box::new(RamDirectory::create());
@@ -108,6 +161,50 @@ fn open_or_create_exists_but_schema_does_not_match() {
     );
 }
 
+#[test]
+fn open_or_create_with_extended_schema() -> crate::Result<()> {
+    let directory = RamDirectory::create();
+    let mut schema_builder = Schema::builder();
+    let title = schema_builder.add_text_field("title", TEXT);
+    let schema = schema_builder.build();
+    {
+        let index = Index::create(directory.clone(), schema, IndexSettings::default())?;
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title=>"hello"))?;
+        index_writer.commit()?;
+    }
+    let mut schema_builder = Schema::builder();
+    schema_builder.add_text_field("title", TEXT);
+    let body = schema_builder.add_text_field("body", TEXT);
+    let extended_schema = schema_builder.build();
+    assert!(!extended_schema.is_extension_of(&throw_away_schema()));
+    let index = Index::open_or_create(directory.clone(), extended_schema.clone())?;
+    assert_eq!(index.schema(), extended_schema);
+    // The upgraded schema is only persisted at the next commit.
+    assert_eq!(Index::open(directory.clone())?.schema().num_fields(), 1);
+    let mut index_writer = index.writer_for_tests()?;
+    index_writer.add_document(doc!(title=>"hello", body=>"world"))?;
+    index_writer.commit()?;
+    assert_eq!(Index::open(directory.clone())?.schema(), extended_schema);
+
+    let segment_ids = index.searchable_segment_ids()?;
+    index_writer.merge(&segment_ids).wait()?;
+    index_writer.wait_merging_threads()?;
+    let searcher = index.reader()?.searcher();
+    assert_eq!(searcher.segment_readers().len(), 1);
+    let title_query = TermQuery::new(
+        Term::from_field_text(title, "hello"),
+        IndexRecordOption::WithFreqs,
+    );
+    assert_eq!(searcher.search(&title_query, &Count)?, 2);
+    let body_query = TermQuery::new(
+        Term::from_field_text(body, "world"),
+        IndexRecordOption::WithFreqs,
+    );
+    assert_eq!(searcher.search(&body_query, &Count)?, 1);
+    Ok(())
+}
+
 fn throw_away_schema() -> Schema {
     let mut schema_builder = Schema::builder();
     let _ = schema_builder.add_u64_field("num_likes", INDEXED);
@@ -275,6 +372,56 @@ fn garbage_collect_works_as_intended() -> crate::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_index_docstore_compression_settings() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+    let schema = schema_builder.build();
+    let zstd_settings = IndexSettings {
+        docstore_compression: Compressor::Zstd(ZstdCompressor::with_compression_level(9)),
+        docstore_blocksize: 100_000,
+        ..Default::default()
+    };
+    let zstd_index = Index::builder()
+        .schema(schema.clone())
+        .settings(zstd_settings)
+        .create_in_ram();
+    assert_eq!(zstd_index.is_ok(), cfg!(feature = "zstd-compression"));
+
+    let invalid_settings = IndexSettings {
+        docstore_blocksize: 0,
+        ..Default::default()
+    };
+    assert!(Index::builder()
+        .schema(schema.clone())
+        .settings(invalid_settings)
+        .create_in_ram()
+        .is_err());
+
+    let settings = IndexSettings {
+        docstore_compression: Compressor::None,
+        docstore_blocksize: 1_000,
+        ..Default::default()
+    };
+    let index = Index::builder()
+        .schema(schema)
+        .settings(settings.clone())
+        .create_in_ram()?;
+    let mut index_writer = index.writer_for_tests()?;
+    for _ in 0..100 {
+        index_writer.add_document(doc!(text_field=>"a rather long text, repeated"))?;
+    }
+    index_writer.commit()?;
+    assert_eq!(index.load_metas()?.index_settings, settings);
+    let searcher = index.reader()?.searcher();
+    let doc = searcher.doc(crate::DocAddress::new(0, 99))?;
+    assert_eq!(
+        doc.get_first(text_field).and_then(|val| val.as_text()),
+        Some("a rather long text, repeated")
+    );
+    Ok(())
+}
+
 #[test]
 fn test_single_segment_index_writer() -> crate::Result<()> {
     let mut schema_builder = Schema::builder();
@@ -344,3 +491,188 @@ fn test_merging_segment_update_docfreq() {
     let term_info = inv_index.get_term_info(&term).unwrap().unwrap();
     assert_eq!(term_info.doc_freq, 12);
 }
+
+#[test]
+fn test_search_with_split_segments() -> crate::Result<()> {
+    use crate::collector::{DocSetCollector, TopDocs};
+    use crate::core::Executor;
+    use crate::query::RegexQuery;
+
+    let mut schema_builder = Schema::builder();
+    let text_field = schema_builder.add_text_field("text", STRING);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer = index.writer_for_tests()?;
+    for i in 0..40_000u64 {
+        let text = if i % 3 == 0 { "fizz" } else { "buzz" };
+        index_writer.add_document(doc!(text_field => text))?;
+    }
+    index_writer.commit()?;
+    index_writer.delete_term(Term::from_field_text(text_field, "buzz"));
+    index_writer.add_document(doc!(text_field => "fizz"))?;
+    index_writer.commit()?;
+
+    let searcher = index.reader()?.searcher();
+    let executor = Executor::multi_thread(3, "test-split-")?;
+    let term_query = TermQuery::new(
+        Term::from_field_text(text_field, "fizz"),
+        IndexRecordOption::Basic,
+    );
+    let regex_query = RegexQuery::from_pattern("f.*", text_field)?;
+    // The split sizes below the minimum are raised to it.
+    for split_size in [1, 7, 16_384, 20_000, 100_000] {
+        let count =
+            searcher.search_with_split_segments(&term_query, &Count, &executor, split_size)?;
+        assert_eq!(count, 13_335);
+        let docs = searcher.search_with_split_segments(
+            &regex_query,
+            &DocSetCollector,
+            &executor,
+            split_size,
+        )?;
+        assert_eq!(docs, searcher.search(&regex_query, &DocSetCollector)?);
+    }
+    // The collectors requiring scores are run segment by segment.
+    let top_docs =
+        searcher.search_with_split_segments(&term_query, &TopDocs::with_limit(3), &executor, 10)?;
+    assert_eq!(
+        top_docs,
+        searcher.search(&term_query, &TopDocs::with_limit(3))?
+    );
+    assert!(searcher
+        .search_with_split_segments(&term_query, &Count, &executor, 0)
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_get_by_term() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let id_field = schema_builder.add_text_field("id", STRING | STORED);
+    let text_field = schema_builder.add_text_field("text", STORED);
+    let index = Index::create_in_ram(schema_builder.build());
+    let nrt_reader: IndexReader = index.reader_builder().near_real_time(true).try_into()?;
+    let mut index_writer = index.writer_for_tests()?;
+    index_writer.add_document(doc!(id_field => "1", text_field => "first"))?;
+    index_writer.add_document(doc!(id_field => "2", text_field => "second"))?;
+    index_writer.commit()?;
+    index_writer.delete_term(Term::from_field_text(id_field, "1"));
+    index_writer.add_document(doc!(id_field => "1", text_field => "updated"))?;
+    index_writer.commit()?;
+
+    let searcher = index.reader()?.searcher();
+    let get_text = |searcher: &crate::Searcher, id: &str| {
+        searcher
+            .get_by_term(&Term::from_field_text(id_field, id))
+            .unwrap()
+            .map(|doc| {
+                doc.get_first(text_field)
+                    .unwrap()
+                    .as_text()
+                    .unwrap()
+                    .to_string()
+            })
+    };
+    assert_eq!(get_text(&searcher, "1").as_deref(), Some("updated"));
+    assert_eq!(get_text(&searcher, "2").as_deref(), Some("second"));
+    assert_eq!(get_text(&searcher, "3"), None);
+    let doc_address = searcher
+        .doc_address_by_term(&Term::from_field_text(id_field, "1"))?
+        .unwrap();
+    let doc = searcher.doc(doc_address)?;
+    assert_eq!(
+        doc.get_first(text_field).unwrap().as_text(),
+        Some("updated")
+    );
+
+    // The refreshed documents are visible to the near real time readers.
+    index_writer.add_document(doc!(id_field => "3", text_field => "third"))?;
+    index_writer.refresh()?;
+    assert_eq!(
+        get_text(&nrt_reader.searcher(), "3").as_deref(),
+        Some("third")
+    );
+    assert_eq!(get_text(&searcher, "3"), None);
+    Ok(())
+}
+
+#[test]
+fn test_searcher_docs() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let text_field = schema_builder.add_text_field("text", STRING | STORED);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer = index.writer_for_tests()?;
+    for i in 0..100 {
+        index_writer.add_document(doc!(text_field => format!("doc{i}")))?;
+    }
+    index_writer.commit()?;
+    index_writer.add_document(doc!(text_field => "last"))?;
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+    let last_segment_ord = searcher
+        .segment_readers()
+        .iter()
+        .position(|segment_reader| segment_reader.max_doc() == 1)
+        .unwrap() as u32;
+    let doc_addresses = vec![
+        crate::DocAddress::new(1 - last_segment_ord, 42),
+        crate::DocAddress::new(last_segment_ord, 0),
+        crate::DocAddress::new(1 - last_segment_ord, 3),
+        crate::DocAddress::new(1 - last_segment_ord, 42),
+    ];
+    let texts: Vec<String> = searcher
+        .docs(&doc_addresses)?
+        .iter()
+        .map(|doc| {
+            doc.get_first(text_field)
+                .unwrap()
+                .as_text()
+                .unwrap()
+                .to_string()
+        })
+        .collect();
+    assert_eq!(texts, vec!["doc42", "last", "doc3", "doc42"]);
+    let docs_async = futures::executor::block_on(searcher.docs_async(&doc_addresses))?;
+    assert_eq!(docs_async.len(), 4);
+    assert!(searcher.docs(&[])?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_search_async() -> crate::Result<()> {
+    use crate::collector::TopDocs;
+    let mut schema_builder = Schema::builder();
+    let text_field = schema_builder.add_text_field("text", TEXT);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer = index.writer_for_tests()?;
+    for i in 0..3_000u32 {
+        let text = if i % 3 == 0 { "a b" } else { "a" };
+        index_writer.add_document(doc!(text_field => text))?;
+        if i == 1_500 {
+            index_writer.commit()?;
+        }
+    }
+    index_writer.delete_term(Term::from_field_text(text_field, "b"));
+    index_writer.add_document(doc!(text_field => "b b"))?;
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+    let query = TermQuery::new(
+        Term::from_field_text(text_field, "b"),
+        IndexRecordOption::WithFreqs,
+    );
+
+    fn assert_send<T: Send>(value: T) -> T {
+        value
+    }
+    let collector = (Count, TopDocs::with_limit(3));
+    let search_future = assert_send(searcher.search_async(&query, &collector));
+    let (count, top_docs) = futures::executor::block_on(search_future)?;
+    assert_eq!(count, 1);
+    assert_eq!(top_docs, searcher.search(&query, &TopDocs::with_limit(3))?);
+    let all_query = TermQuery::new(
+        Term::from_field_text(text_field, "a"),
+        IndexRecordOption::Basic,
+    );
+    let count = futures::executor::block_on(searcher.search_async(&all_query, &Count))?;
+    assert_eq!(count, 2_000);
+    Ok(())
+}
@@ -0,0 +1,641 @@
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::{fmt, io, result};
+
+use async_trait::async_trait;
+use common::HasLen;
+use lru::LruCache;
+
+use crate::core::{SegmentComponent, SegmentMeta};
+use crate::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
+use crate::directory::{
+    Directory, DirectoryLock, FileHandle, Lock, OwnedBytes, WatchCallback, WatchHandle, WritePtr,
+};
+
+/// Default size of the blocks of the [`CachingDirectory`]: 64 KiB.
+pub const DEFAULT_CACHE_BLOCK_SIZE: usize = 64 << 10;
+
+/// Policy used to pick the blocks evicted from the cache of a [`CachingDirectory`] once its
+/// capacity is reached.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Evicts the least recently used block.
+    #[default]
+    Lru,
+    /// Evicts the least frequently used block, the least recently used one in case of a tie.
+    Lfu,
+}
+
+/// Statistics of the block cache of a [`CachingDirectory`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BlockCacheStats {
+    /// The number of blocks in the cache.
+    pub num_blocks: usize,
+    /// The number of bytes of the blocks in the cache.
+    pub num_bytes: usize,
+    /// The number of blocks read from the cache.
+    pub cache_hits: usize,
+    /// The number of blocks read from the underlying directory.
+    pub cache_misses: usize,
+}
+
+/// Identifies a block: the id of its file, and its ordinal within the file.
+type BlockKey = (u64, usize);
+
+/// Blocks ordered by their number of accesses, then their last access.
+struct LfuCache {
+    blocks: HashMap<BlockKey, (OwnedBytes, u64, u64)>,
+    eviction_order: BTreeSet<(u64, u64, BlockKey)>,
+    tick: u64,
+}
+
+impl LfuCache {
+    fn new() -> LfuCache {
+        LfuCache {
+            blocks: HashMap::new(),
+            eviction_order: BTreeSet::new(),
+            tick: 0,
+        }
+    }
+
+    fn get(&mut self, key: &BlockKey) -> Option<OwnedBytes> {
+        self.tick += 1;
+        let (block, num_accesses, last_access) = self.blocks.get_mut(key)?;
+        self.eviction_order
+            .remove(&(*num_accesses, *last_access, *key));
+        *num_accesses += 1;
+        *last_access = self.tick;
+        self.eviction_order
+            .insert((*num_accesses, *last_access, *key));
+        Some(block.clone())
+    }
+
+    fn put(&mut self, key: BlockKey, block: OwnedBytes) -> Option<OwnedBytes> {
+        self.tick += 1;
+        let previous_block = self.remove(&key);
+        self.blocks.insert(key, (block, 1, self.tick));
+        self.eviction_order.insert((1, self.tick, key));
+        previous_block
+    }
+
+    fn remove(&mut self, key: &BlockKey) -> Option<OwnedBytes> {
+        let (block, num_accesses, last_access) = self.blocks.remove(key)?;
+        self.eviction_order
+            .remove(&(num_accesses, last_access, *key));
+        Some(block)
+    }
+
+    fn pop_evicted(&mut self) -> Option<OwnedBytes> {
+        let (_, _, key) = self.eviction_order.iter().next().copied()?;
+        self.remove(&key)
+    }
+}
+
+enum Blocks {
+    Lru(LruCache<BlockKey, OwnedBytes>),
+    Lfu(LfuCache),
+}
+
+impl Blocks {
+    fn len(&self) -> usize {
+        match self {
+            Blocks::Lru(cache) => cache.len(),
+            Blocks::Lfu(cache) => cache.blocks.len(),
+        }
+    }
+
+    fn get(&mut self, key: &BlockKey) -> Option<OwnedBytes> {
+        match self {
+            Blocks::Lru(cache) => cache.get(key).cloned(),
+            Blocks::Lfu(cache) => cache.get(key),
+        }
+    }
+
+    fn put(&mut self, key: BlockKey, block: OwnedBytes) -> Option<OwnedBytes> {
+        match self {
+            Blocks::Lru(cache) => cache.put(key, block),
+            Blocks::Lfu(cache) => cache.put(key, block),
+        }
+    }
+
+    fn pop_evicted(&mut self) -> Option<OwnedBytes> {
+        match self {
+            Blocks::Lru(cache) => cache.pop_lru().map(|(_, block)| block),
+            Blocks::Lfu(cache) => cache.pop_evicted(),
+        }
+    }
+}
+
+struct BlockCache {
+    block_size: usize,
+    capacity: usize,
+    blocks: Mutex<(Blocks, usize)>,
+    cache_hits: AtomicUsize,
+    cache_misses: AtomicUsize,
+}
+
+impl BlockCache {
+    fn new(block_size: usize, capacity: usize, eviction_policy: EvictionPolicy) -> BlockCache {
+        let blocks = match eviction_policy {
+            EvictionPolicy::Lru => Blocks::Lru(LruCache::unbounded()),
+            EvictionPolicy::Lfu => Blocks::Lfu(LfuCache::new()),
+        };
+        BlockCache {
+            block_size,
+            capacity,
+            blocks: Mutex::new((blocks, 0)),
+            cache_hits: Default::default(),
+            cache_misses: Default::default(),
+        }
+    }
+
+    fn get(&self, key: &BlockKey) -> Option<OwnedBytes> {
+        let block_opt = self.blocks.lock().unwrap().0.get(key);
+        if block_opt.is_some() {
+            self.cache_hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.cache_misses.fetch_add(1, Ordering::Relaxed);
+        }
+        block_opt
+    }
+
+    fn put(&self, key: BlockKey, block: OwnedBytes) {
+        if block.len() > self.capacity {
+            return;
+        }
+        let mut guard = self.blocks.lock().unwrap();
+        let (blocks, num_bytes) = &mut *guard;
+        *num_bytes += block.len();
+        if let Some(previous_block) = blocks.put(key, block) {
+            *num_bytes -= previous_block.len();
+        }
+        while *num_bytes > self.capacity {
+            let Some(evicted_block) = blocks.pop_evicted() else {
+                break;
+            };
+            *num_bytes -= evicted_block.len();
+        }
+    }
+
+    fn stats(&self) -> BlockCacheStats {
+        let guard = self.blocks.lock().unwrap();
+        BlockCacheStats {
+            num_blocks: guard.0.len(),
+            num_bytes: guard.1,
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            cache_misses: self.cache_misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+struct InnerCachingDirectory {
+    underlying: Box<dyn Directory>,
+    cache: BlockCache,
+    // Files are write-once, but a path may be deleted and written again: each version of a file
+    // gets its own id, so that the blocks of a previous version are never read.
+    file_ids: RwLock<HashMap<PathBuf, u64>>,
+    next_file_id: AtomicU64,
+}
+
+impl InnerCachingDirectory {
+    fn file_id(&self, path: &Path) -> u64 {
+        if let Some(file_id) = self.file_ids.read().unwrap().get(path) {
+            return *file_id;
+        }
+        *self
+            .file_ids
+            .write()
+            .unwrap()
+            .entry(path.to_path_buf())
+            .or_insert_with(|| self.next_file_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn forget_file(&self, path: &Path) {
+        self.file_ids.write().unwrap().remove(path);
+    }
+}
+
+/// A Directory wrapping a slow or remote directory, such as an
+/// [`ObjectStoreDirectory`](crate::directory::ObjectStoreDirectory), with a read-through block
+/// cache kept in memory.
+///
+/// Files are read by blocks of a fixed size, aligned on the block size, which are kept in the
+/// cache until its capacity is reached, so that the searches reading the same parts of a file
+/// do not read them again from the underlying directory. Consecutive missing blocks are read
+/// with a single read.
+///
+/// The hot files, such as the term dictionaries, can be loaded in the cache ahead of the first
+/// search with [`CachingDirectory::warm_up()`].
+///
+/// Writes, as well as [`Directory::atomic_read()`], are passed through to the underlying
+/// directory.
+#[derive(Clone)]
+pub struct CachingDirectory {
+    inner: Arc<InnerCachingDirectory>,
+}
+
+impl fmt::Debug for CachingDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CachingDirectory({:?})", self.inner.underlying)
+    }
+}
+
+impl CachingDirectory {
+    /// Wraps a directory with a block cache of `capacity` bytes, with blocks of
+    /// [`DEFAULT_CACHE_BLOCK_SIZE`] bytes and an LRU eviction.
+    pub fn new(underlying: impl Into<Box<dyn Directory>>, capacity: usize) -> CachingDirectory {
+        CachingDirectory::with_eviction_policy(
+            underlying,
+            capacity,
+            DEFAULT_CACHE_BLOCK_SIZE,
+            EvictionPolicy::default(),
+        )
+    }
+
+    /// Wraps a directory with a block cache of `capacity` bytes, with blocks of `block_size`
+    /// bytes evicted with the given policy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the block size is 0.
+    pub fn with_eviction_policy(
+        underlying: impl Into<Box<dyn Directory>>,
+        capacity: usize,
+        block_size: usize,
+        eviction_policy: EvictionPolicy,
+    ) -> CachingDirectory {
+        assert!(block_size > 0, "The block size must be positive.");
+        CachingDirectory {
+            inner: Arc::new(InnerCachingDirectory {
+                underlying: underlying.into(),
+                cache: BlockCache::new(block_size, capacity, eviction_policy),
+                file_ids: Default::default(),
+                next_file_id: Default::default(),
+            }),
+        }
+    }
+
+    /// Loads all of the blocks of a file in the cache.
+    pub fn warm_up(&self, path: &Path) -> crate::Result<()> {
+        let file_handle = self.get_file_handle(path)?;
+        file_handle.read_bytes(0..file_handle.len())?;
+        Ok(())
+    }
+
+    /// Loads the given components of the segments in the cache, typically the
+    /// [`SegmentComponent::Terms`] of the searchable segments, given by
+    /// [`Index::searchable_segment_metas()`](crate::Index::searchable_segment_metas).
+    ///
+    /// The components a segment does not have are ignored.
+    pub fn warm_up_segments(
+        &self,
+        segment_metas: &[SegmentMeta],
+        components: &[SegmentComponent],
+    ) -> crate::Result<()> {
+        for segment_meta in segment_metas {
+            for component in components {
+                let path = segment_meta.relative_path(*component);
+                if self.exists(&path)? {
+                    self.warm_up(&path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the statistics of the block cache.
+    pub fn cache_stats(&self) -> BlockCacheStats {
+        self.inner.cache.stats()
+    }
+}
+
+impl Directory for CachingDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+        let underlying = self.inner.underlying.get_file_handle(path)?;
+        Ok(Arc::new(CachingFileHandle {
+            underlying,
+            file_id: self.inner.file_id(path),
+            directory: self.inner.clone(),
+        }))
+    }
+
+    fn delete(&self, path: &Path) -> result::Result<(), DeleteError> {
+        self.inner.forget_file(path);
+        self.inner.underlying.delete(path)
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        self.inner.underlying.exists(path)
+    }
+
+    fn open_write(&self, path: &Path) -> result::Result<WritePtr, OpenWriteError> {
+        self.inner.forget_file(path);
+        self.inner.underlying.open_write(path)
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        self.inner.underlying.atomic_read(path)
+    }
+
+    fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.inner.forget_file(path);
+        self.inner.underlying.atomic_write(path, data)
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> result::Result<DirectoryLock, LockError> {
+        self.inner.underlying.acquire_lock(lock)
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        self.inner.underlying.watch(watch_callback)
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        self.inner.underlying.sync_directory()
+    }
+}
+
+struct CachingFileHandle {
+    underlying: Arc<dyn FileHandle>,
+    file_id: u64,
+    directory: Arc<InnerCachingDirectory>,
+}
+
+impl fmt::Debug for CachingFileHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CachingFileHandle({:?})", self.underlying)
+    }
+}
+
+impl HasLen for CachingFileHandle {
+    fn len(&self) -> usize {
+        self.underlying.len()
+    }
+}
+
+impl CachingFileHandle {
+    fn block_range(&self, block_ord: usize) -> Range<usize> {
+        let block_size = self.directory.cache.block_size;
+        let start = block_ord * block_size;
+        start..(start + block_size).min(self.len())
+    }
+
+    /// Returns the ordinals of the blocks overlapping the range of bytes, and those of the
+    /// blocks found in the cache.
+    fn cached_blocks(&self, range: &Range<usize>) -> (Range<usize>, Vec<Option<OwnedBytes>>) {
+        let block_size = self.directory.cache.block_size;
+        let block_ords = range.start / block_size..(range.end - 1) / block_size + 1;
+        let blocks = block_ords
+            .clone()
+            .map(|block_ord| self.directory.cache.get(&(self.file_id, block_ord)))
+            .collect();
+        (block_ords, blocks)
+    }
+
+    /// Returns the ranges of bytes of the runs of consecutive missing blocks, each of which is
+    /// read with a single read, along with the ranges of their ordinals.
+    fn missing_runs(
+        &self,
+        block_ords: &Range<usize>,
+        blocks: &[Option<OwnedBytes>],
+    ) -> Vec<(Range<usize>, Range<usize>)> {
+        let mut missing_runs = Vec::new();
+        let mut missing_start_opt: Option<usize> = None;
+        for (block_ord, block_opt) in block_ords.clone().zip(blocks) {
+            if block_opt.is_none() {
+                missing_start_opt.get_or_insert(block_ord);
+            } else if let Some(missing_start) = missing_start_opt.take() {
+                missing_runs.push(missing_start..block_ord);
+            }
+        }
+        if let Some(missing_start) = missing_start_opt {
+            missing_runs.push(missing_start..block_ords.end);
+        }
+        missing_runs
+            .into_iter()
+            .map(|run| {
+                let byte_range =
+                    self.block_range(run.start).start..self.block_range(run.end - 1).end;
+                (run, byte_range)
+            })
+            .collect()
+    }
+
+    /// Splits the data read for a run of missing blocks, and puts the blocks in the cache.
+    fn put_missing_blocks(
+        &self,
+        block_ords: &Range<usize>,
+        blocks: &mut [Option<OwnedBytes>],
+        run: Range<usize>,
+        data: OwnedBytes,
+    ) {
+        let start = self.block_range(run.start).start;
+        for block_ord in run {
+            let block_range = self.block_range(block_ord);
+            let block = data.slice(block_range.start - start..block_range.end - start);
+            self.directory
+                .cache
+                .put((self.file_id, block_ord), block.clone());
+            blocks[block_ord - block_ords.start] = Some(block);
+        }
+    }
+
+    fn assemble(
+        &self,
+        range: Range<usize>,
+        block_ords: Range<usize>,
+        blocks: Vec<Option<OwnedBytes>>,
+    ) -> OwnedBytes {
+        let offset = range.start - block_ords.start * self.directory.cache.block_size;
+        let mut blocks: Vec<OwnedBytes> = blocks.into_iter().flatten().collect();
+        if blocks.len() == 1 {
+            return blocks.pop().unwrap().slice(offset..offset + range.len());
+        }
+        let mut data = Vec::with_capacity(range.len());
+        for block in &blocks {
+            data.extend_from_slice(block.as_slice());
+        }
+        OwnedBytes::new(data).slice(offset..offset + range.len())
+    }
+}
+
+#[async_trait]
+impl FileHandle for CachingFileHandle {
+    fn read_bytes(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        if range.is_empty() {
+            return Ok(OwnedBytes::empty());
+        }
+        let (block_ords, mut blocks) = self.cached_blocks(&range);
+        for (run, byte_range) in self.missing_runs(&block_ords, &blocks) {
+            let data = self.underlying.read_bytes(byte_range)?;
+            self.put_missing_blocks(&block_ords, &mut blocks, run, data);
+        }
+        Ok(self.assemble(range, block_ords, blocks))
+    }
+
+    async fn read_bytes_async(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        if range.is_empty() {
+            return Ok(OwnedBytes::empty());
+        }
+        let (block_ords, mut blocks) = self.cached_blocks(&range);
+        for (run, byte_range) in self.missing_runs(&block_ords, &blocks) {
+            let data = self.underlying.read_bytes_async(byte_range).await?;
+            self.put_missing_blocks(&block_ords, &mut blocks, run, data);
+        }
+        Ok(self.assemble(range, block_ords, blocks))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::path::Path;
+
+    use futures::executor::block_on;
+
+    use super::{CachingDirectory, EvictionPolicy};
+    use crate::collector::Count;
+    use crate::core::SegmentComponent;
+    use crate::directory::{RamDirectory, TerminatingWrite};
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, TEXT};
+    use crate::{Directory, Index, Term};
+
+    fn write_file(directory: &dyn Directory, path: &Path, data: &[u8]) -> crate::Result<()> {
+        let mut write = directory.open_write(path)?;
+        write.write_all(data)?;
+        write.terminate()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_caching_directory_read_blocks() -> crate::Result<()> {
+        let directory = CachingDirectory::with_eviction_policy(
+            RamDirectory::default(),
+            1_000,
+            10,
+            EvictionPolicy::Lru,
+        );
+        let path = Path::new("file");
+        let data: Vec<u8> = (0..=255u8).collect();
+        write_file(&directory, path, &data)?;
+        let file = directory.open_read(path)?;
+        assert_eq!(file.read_bytes_slice(3..7)?.as_slice(), &data[3..7]);
+        assert_eq!(directory.cache_stats().cache_misses, 1);
+        assert_eq!(file.read_bytes_slice(5..9)?.as_slice(), &data[5..9]);
+        assert_eq!(directory.cache_stats().cache_hits, 1);
+        // The missing blocks around a cached block.
+        assert_eq!(file.read_bytes_slice(0..31)?.as_slice(), &data[0..31]);
+        assert_eq!(file.read_bytes_slice(250..256)?.as_slice(), &data[250..256]);
+        assert_eq!(file.read_bytes()?.as_slice(), &data[..]);
+        let stats = directory.cache_stats();
+        assert_eq!(stats.num_blocks, 26);
+        assert_eq!(stats.num_bytes, 256);
+
+        // A file written again at the same path does not read the blocks of the previous one.
+        directory.delete(path).unwrap();
+        write_file(&directory, path, b"new content")?;
+        assert_eq!(
+            directory.open_read(path)?.read_bytes()?.as_slice(),
+            b"new content"
+        );
+        Ok(())
+    }
+
+    fn test_caching_directory_eviction_aux(eviction_policy: EvictionPolicy) -> crate::Result<()> {
+        let directory = CachingDirectory::with_eviction_policy(
+            RamDirectory::default(),
+            30,
+            10,
+            eviction_policy,
+        );
+        let path = Path::new("file");
+        let data: Vec<u8> = (0..100u8).collect();
+        write_file(&directory, path, &data)?;
+        let file = directory.open_read(path)?;
+        for block_ord in [0, 0, 0, 1, 2, 3, 1] {
+            let start = block_ord * 10;
+            assert_eq!(
+                file.read_bytes_slice(start..start + 10)?.as_slice(),
+                &data[start..start + 10]
+            );
+        }
+        let stats = directory.cache_stats();
+        assert_eq!(stats.num_blocks, 3);
+        assert_eq!(stats.num_bytes, 30);
+        let num_misses = stats.cache_misses;
+        // The block 0 is the least recently used one, but the most frequently used one.
+        file.read_bytes_slice(0..10)?;
+        let is_block_0_cached = directory.cache_stats().cache_misses == num_misses;
+        assert_eq!(is_block_0_cached, eviction_policy == EvictionPolicy::Lfu);
+        Ok(())
+    }
+
+    #[test]
+    fn test_caching_directory_read_async() -> crate::Result<()> {
+        let directory = CachingDirectory::with_eviction_policy(
+            RamDirectory::default(),
+            1_000,
+            10,
+            EvictionPolicy::Lru,
+        );
+        let path = Path::new("file");
+        let data: Vec<u8> = (0..100u8).collect();
+        write_file(&directory, path, &data)?;
+        let file = directory.open_read(path)?;
+        let bytes = block_on(file.read_bytes_slice_async(15..42))?;
+        assert_eq!(bytes.as_slice(), &data[15..42]);
+        assert_eq!(directory.cache_stats().cache_misses, 4);
+        let bytes = block_on(file.read_bytes_slice_async(5..35))?;
+        assert_eq!(bytes.as_slice(), &data[5..35]);
+        let stats = directory.cache_stats();
+        assert_eq!((stats.cache_hits, stats.cache_misses), (3, 5));
+        Ok(())
+    }
+
+    #[test]
+    fn test_caching_directory_lru() -> crate::Result<()> {
+        test_caching_directory_eviction_aux(EvictionPolicy::Lru)
+    }
+
+    #[test]
+    fn test_caching_directory_lfu() -> crate::Result<()> {
+        test_caching_directory_eviction_aux(EvictionPolicy::Lfu)
+    }
+
+    #[test]
+    fn test_caching_directory_warm_up() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let ram_directory = RamDirectory::default();
+        {
+            let index = Index::create(ram_directory.clone(), schema, Default::default())?;
+            let mut index_writer = index.writer_for_tests()?;
+            for i in 0..100 {
+                index_writer.add_document(doc!(text => format!("hello {i}")))?;
+            }
+            index_writer.commit()?;
+        }
+        let directory = CachingDirectory::new(ram_directory, 10_000_000);
+        let index = Index::open(directory.clone())?;
+        let segment_metas = index.searchable_segment_metas()?;
+        directory.warm_up_segments(
+            &segment_metas,
+            &[SegmentComponent::Terms, SegmentComponent::Points],
+        )?;
+        let num_blocks = directory.cache_stats().num_blocks;
+        assert!(num_blocks > 0);
+        let searcher = index.reader()?.searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(text, "hello"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 100);
+        assert!(directory.cache_stats().cache_hits > 0);
+        Ok(())
+    }
+}
@@ -100,12 +100,16 @@ fn retry_policy(is_blocking: bool) -> RetryPolicy {
 /// Write-once read many (WORM) abstraction for where
 /// tantivy's data should be stored.
 ///
-/// There are currently two implementations of `Directory`
+/// There are currently four implementations of `Directory`
 ///
 /// - The [`MMapDirectory`][crate::directory::MmapDirectory], this
 /// should be your default choice.
+/// - The [`PreadDirectory`][crate::directory::PreadDirectory], which
+///   reads the files with positional reads and a bounded page cache.
 /// - The [`RamDirectory`][crate::directory::RamDirectory], which
 /// should be used mostly for tests.
+/// - The [`ObjectStoreDirectory`][crate::directory::ObjectStoreDirectory], which
+///   stores the files in an object storage such as S3.
 pub trait Directory: DirectoryClone + fmt::Debug + Send + Sync + 'static {
     /// Opens a file and returns a boxed `FileHandle`.
     ///
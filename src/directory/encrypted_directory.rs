@@ -0,0 +1,742 @@
+use std::io::{self, BufWriter, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::{fmt, mem};
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use async_trait::async_trait;
+use common::HasLen;
+
+use crate::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
+use crate::directory::{
+    AntiCallToken, Directory, DirectoryLock, FileHandle, Lock, OwnedBytes, TerminatingWrite,
+    WatchCallback, WatchHandle, WritePtr,
+};
+
+/// Default size of the plaintext blocks encrypted by the [`EncryptedDirectory`]: 64 KiB.
+pub const DEFAULT_ENCRYPTION_BLOCK_SIZE: usize = 64 << 10;
+
+const MAGIC: [u8; 4] = *b"TENC";
+const FORMAT_VERSION: u8 = 1;
+// Magic number, format version, key id, nonce prefix and block size.
+const HEADER_LEN: usize = 4 + 1 + 4 + 8 + 4;
+const TAG_LEN: usize = 16;
+// Length of the encrypted block index, at the end of the file.
+const FOOTER_LEN: usize = 8;
+
+/// A 256-bit AES key.
+#[derive(Clone)]
+pub struct EncryptionKey([u8; 32]);
+
+impl From<[u8; 32]> for EncryptionKey {
+    fn from(key: [u8; 32]) -> EncryptionKey {
+        EncryptionKey(key)
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EncryptionKey(..)")
+    }
+}
+
+/// Provides the keys of an [`EncryptedDirectory`].
+///
+/// Each file records the id of the key it was encrypted with, so that the keys can be rotated:
+/// new files are encrypted with the current key, while the files encrypted with a previous key
+/// remain readable as long as the provider returns it.
+///
+/// An [`EncryptionKey`] is itself a provider of a single key, of id 0.
+pub trait KeyProvider: Send + Sync + 'static {
+    /// Returns the id of the key with which new files are encrypted, and the key.
+    fn current_key(&self) -> io::Result<(u32, EncryptionKey)>;
+
+    /// Returns the key of the given id.
+    fn key(&self, key_id: u32) -> io::Result<EncryptionKey>;
+}
+
+impl KeyProvider for EncryptionKey {
+    fn current_key(&self) -> io::Result<(u32, EncryptionKey)> {
+        Ok((0, self.clone()))
+    }
+
+    fn key(&self, key_id: u32) -> io::Result<EncryptionKey> {
+        if key_id != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Unknown encryption key id {key_id}"),
+            ));
+        }
+        Ok(self.clone())
+    }
+}
+
+/// The header of an encrypted file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Header {
+    key_id: u32,
+    nonce_prefix: [u8; 8],
+    block_size: usize,
+}
+
+impl Header {
+    fn serialize(&self) -> [u8; HEADER_LEN] {
+        let mut header = [0u8; HEADER_LEN];
+        header[..4].copy_from_slice(&MAGIC);
+        header[4] = FORMAT_VERSION;
+        header[5..9].copy_from_slice(&self.key_id.to_le_bytes());
+        header[9..17].copy_from_slice(&self.nonce_prefix);
+        header[17..21].copy_from_slice(&(self.block_size as u32).to_le_bytes());
+        header
+    }
+
+    fn deserialize(header: &[u8]) -> io::Result<Header> {
+        if header.len() < HEADER_LEN || header[..4] != MAGIC || header[4] != FORMAT_VERSION {
+            return Err(invalid_data("Not an encrypted file".to_string()));
+        }
+        let block_size = u32::from_le_bytes(header[17..21].try_into().unwrap()) as usize;
+        if block_size == 0 {
+            return Err(invalid_data("Invalid block size 0".to_string()));
+        }
+        Ok(Header {
+            key_id: u32::from_le_bytes(header[5..9].try_into().unwrap()),
+            nonce_prefix: header[9..17].try_into().unwrap(),
+            block_size,
+        })
+    }
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Encrypts and decrypts the blocks of a file.
+///
+/// Each block is encrypted with AES-256-GCM, with a nonce made of the nonce prefix of the file and
+/// the block ordinal. The block ordinal and whether the block is the last one are authenticated,
+/// so that the blocks of a file can neither be reordered nor truncated.
+///
+/// The last block of a file is its block index, listing the plaintext length of the other blocks.
+struct BlockCipher {
+    header: Header,
+    cipher: Aes256Gcm,
+}
+
+impl BlockCipher {
+    fn new(header: Header, key: &EncryptionKey) -> BlockCipher {
+        BlockCipher {
+            header,
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key.0)),
+        }
+    }
+
+    fn nonce_and_aad(&self, block_ord: usize, is_last: bool) -> ([u8; 12], [u8; 9]) {
+        let mut nonce = [0u8; 12];
+        nonce[..8].copy_from_slice(&self.header.nonce_prefix);
+        nonce[8..].copy_from_slice(&(block_ord as u32).to_le_bytes());
+        let mut aad = [0u8; 9];
+        aad[..8].copy_from_slice(&(block_ord as u64).to_le_bytes());
+        aad[8] = is_last as u8;
+        (nonce, aad)
+    }
+
+    fn encrypt(&self, block_ord: usize, is_last: bool, block: &[u8]) -> io::Result<Vec<u8>> {
+        if block_ord > u32::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "Too many blocks in an encrypted file",
+            ));
+        }
+        let (nonce, aad) = self.nonce_and_aad(block_ord, is_last);
+        let payload = Payload {
+            msg: block,
+            aad: &aad,
+        };
+        self.cipher
+            .encrypt(Nonce::from_slice(&nonce), payload)
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "Failed to encrypt a block"))
+    }
+
+    fn decrypt(&self, block_ord: usize, is_last: bool, block: &[u8]) -> io::Result<Vec<u8>> {
+        let (nonce, aad) = self.nonce_and_aad(block_ord, is_last);
+        let payload = Payload {
+            msg: block,
+            aad: &aad,
+        };
+        self.cipher
+            .decrypt(Nonce::from_slice(&nonce), payload)
+            .map_err(|_| invalid_data(format!("Failed to decrypt the block {block_ord}")))
+    }
+
+    /// Returns the encrypted block index, followed by its length, which end the file.
+    fn encrypt_block_index(&self, block_lens: &[u32]) -> io::Result<Vec<u8>> {
+        let block_index: Vec<u8> = block_lens
+            .iter()
+            .flat_map(|block_len| block_len.to_le_bytes())
+            .collect();
+        let mut encrypted_index = self.encrypt(block_lens.len(), true, &block_index)?;
+        let encrypted_index_len = encrypted_index.len() as u64;
+        encrypted_index.extend_from_slice(&encrypted_index_len.to_le_bytes());
+        Ok(encrypted_index)
+    }
+}
+
+/// A Directory wrapping another directory, in which all files are encrypted at rest.
+///
+/// The files are split into blocks, each encrypted with AES-256-GCM and a key given by a
+/// [`KeyProvider`]. Range reads only read and decrypt the blocks they overlap, so that the
+/// files are read on demand, as with the underlying directory, rather than decrypted as a whole.
+///
+/// Flushing a writer writes the pending data as a possibly partial block. The block index of a
+/// file is only written once its writer is terminated, and the file is not readable before.
+///
+/// This directory requires the `encryption` feature.
+#[derive(Clone)]
+pub struct EncryptedDirectory {
+    underlying: Box<dyn Directory>,
+    key_provider: Arc<dyn KeyProvider>,
+    block_size: usize,
+}
+
+impl fmt::Debug for EncryptedDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EncryptedDirectory({:?})", self.underlying)
+    }
+}
+
+impl EncryptedDirectory {
+    /// Wraps a directory, encrypting its files with the keys of the key provider.
+    pub fn new(
+        underlying: impl Into<Box<dyn Directory>>,
+        key_provider: impl KeyProvider,
+    ) -> EncryptedDirectory {
+        EncryptedDirectory {
+            underlying: underlying.into(),
+            key_provider: Arc::new(key_provider),
+            block_size: DEFAULT_ENCRYPTION_BLOCK_SIZE,
+        }
+    }
+
+    /// Sets the maximum size of the plaintext blocks of the new files.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the block size is 0 or does not fit in a `u32`.
+    #[must_use]
+    pub fn block_size(mut self, block_size: usize) -> EncryptedDirectory {
+        assert!(
+            block_size > 0 && block_size <= u32::MAX as usize,
+            "The block size must be positive and fit in a u32."
+        );
+        self.block_size = block_size;
+        self
+    }
+
+    fn new_block_cipher(&self) -> io::Result<BlockCipher> {
+        let (key_id, key) = self.key_provider.current_key()?;
+        let mut nonce_prefix = [0u8; 8];
+        OsRng.fill_bytes(&mut nonce_prefix);
+        let header = Header {
+            key_id,
+            nonce_prefix,
+            block_size: self.block_size,
+        };
+        Ok(BlockCipher::new(header, &key))
+    }
+
+    fn open_block_cipher(&self, header: &[u8]) -> io::Result<BlockCipher> {
+        let header = Header::deserialize(header)?;
+        let key = self.key_provider.key(header.key_id)?;
+        Ok(BlockCipher::new(header, &key))
+    }
+
+    fn open_encrypted_file(
+        &self,
+        underlying: Arc<dyn FileHandle>,
+    ) -> io::Result<EncryptedFileHandle> {
+        let header_len = HEADER_LEN.min(underlying.len());
+        let header = underlying.read_bytes(0..header_len)?;
+        let block_cipher = self.open_block_cipher(header.as_slice())?;
+        EncryptedFileHandle::open(underlying, block_cipher)
+    }
+}
+
+impl Directory for EncryptedDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+        let underlying = self.underlying.get_file_handle(path)?;
+        let file_handle = self
+            .open_encrypted_file(underlying)
+            .map_err(|io_error| OpenReadError::wrap_io_error(io_error, path.to_path_buf()))?;
+        Ok(Arc::new(file_handle))
+    }
+
+    fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+        self.underlying.delete(path)
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        self.underlying.exists(path)
+    }
+
+    fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+        let block_cipher = self
+            .new_block_cipher()
+            .map_err(|io_error| OpenWriteError::wrap_io_error(io_error, path.to_path_buf()))?;
+        let mut underlying = self.underlying.open_write(path)?;
+        underlying
+            .write_all(&block_cipher.header.serialize())
+            .map_err(|io_error| OpenWriteError::wrap_io_error(io_error, path.to_path_buf()))?;
+        Ok(BufWriter::new(Box::new(EncryptedWriter {
+            path: path.to_path_buf(),
+            underlying,
+            block_cipher,
+            buffer: Vec::new(),
+            block_lens: Vec::new(),
+            is_terminated: false,
+        })))
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        let wrap_io_error = |io_error| OpenReadError::wrap_io_error(io_error, path.to_path_buf());
+        let data = self.underlying.atomic_read(path)?;
+        let file_handle = self
+            .open_encrypted_file(Arc::new(OwnedBytes::new(data)))
+            .map_err(wrap_io_error)?;
+        let plaintext = file_handle
+            .read_bytes(0..file_handle.len())
+            .map_err(wrap_io_error)?;
+        Ok(plaintext.as_slice().to_vec())
+    }
+
+    fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        let block_cipher = self.new_block_cipher()?;
+        let mut encrypted_data = block_cipher.header.serialize().to_vec();
+        let mut block_lens = Vec::new();
+        for (block_ord, block) in data.chunks(self.block_size).enumerate() {
+            encrypted_data.extend(block_cipher.encrypt(block_ord, false, block)?);
+            block_lens.push(block.len() as u32);
+        }
+        encrypted_data.extend(block_cipher.encrypt_block_index(&block_lens)?);
+        self.underlying.atomic_write(path, &encrypted_data)
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        self.underlying.acquire_lock(lock)
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        self.underlying.watch(watch_callback)
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        self.underlying.sync_directory()
+    }
+}
+
+struct EncryptedFileHandle {
+    underlying: Arc<dyn FileHandle>,
+    block_cipher: BlockCipher,
+    // Start offsets of the blocks in the plaintext, followed by the length of the plaintext.
+    plaintext_offsets: Vec<usize>,
+    // Start offsets of the blocks in the underlying file, followed by the offset of the block
+    // index.
+    encrypted_offsets: Vec<usize>,
+}
+
+impl fmt::Debug for EncryptedFileHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EncryptedFileHandle({:?})", self.underlying)
+    }
+}
+
+impl HasLen for EncryptedFileHandle {
+    fn len(&self) -> usize {
+        *self.plaintext_offsets.last().unwrap()
+    }
+}
+
+impl EncryptedFileHandle {
+    /// Reads and decrypts the block index of the file.
+    fn open(
+        underlying: Arc<dyn FileHandle>,
+        block_cipher: BlockCipher,
+    ) -> io::Result<EncryptedFileHandle> {
+        let truncated = || invalid_data("Truncated encrypted file".to_string());
+        let footer_start = underlying
+            .len()
+            .checked_sub(FOOTER_LEN)
+            .filter(|&footer_start| footer_start >= HEADER_LEN)
+            .ok_or_else(truncated)?;
+        let footer = underlying.read_bytes(footer_start..underlying.len())?;
+        let encrypted_index_len = u64::from_le_bytes(footer.as_slice().try_into().unwrap());
+        let encrypted_index_start = usize::try_from(encrypted_index_len)
+            .ok()
+            .and_then(|encrypted_index_len| footer_start.checked_sub(encrypted_index_len))
+            .filter(|&encrypted_index_start| encrypted_index_start >= HEADER_LEN)
+            .ok_or_else(truncated)?;
+        let index_len = (footer_start - encrypted_index_start)
+            .checked_sub(TAG_LEN)
+            .filter(|index_len| index_len % 4 == 0)
+            .ok_or_else(truncated)?;
+        let num_blocks = index_len / 4;
+        let encrypted_index = underlying.read_bytes(encrypted_index_start..footer_start)?;
+        let block_index = block_cipher.decrypt(num_blocks, true, encrypted_index.as_slice())?;
+        let mut plaintext_offsets = Vec::with_capacity(num_blocks + 1);
+        let mut encrypted_offsets = Vec::with_capacity(num_blocks + 1);
+        plaintext_offsets.push(0);
+        encrypted_offsets.push(HEADER_LEN);
+        for block_len in block_index.chunks_exact(4) {
+            let block_len = u32::from_le_bytes(block_len.try_into().unwrap()) as usize;
+            if block_len == 0 || block_len > block_cipher.header.block_size {
+                return Err(invalid_data(format!("Invalid block length {block_len}")));
+            }
+            plaintext_offsets.push(plaintext_offsets.last().unwrap() + block_len);
+            encrypted_offsets.push(encrypted_offsets.last().unwrap() + block_len + TAG_LEN);
+        }
+        if encrypted_offsets.last() != Some(&encrypted_index_start) {
+            return Err(invalid_data(
+                "The block index does not match the encrypted file".to_string(),
+            ));
+        }
+        Ok(EncryptedFileHandle {
+            underlying,
+            block_cipher,
+            plaintext_offsets,
+            encrypted_offsets,
+        })
+    }
+
+    /// Returns the range of the ordinals of the blocks overlapping the range of plaintext bytes.
+    fn block_ords(&self, range: &Range<usize>) -> Range<usize> {
+        let first_block_ord = self
+            .plaintext_offsets
+            .partition_point(|&offset| offset <= range.start)
+            - 1;
+        let end_block_ord = self
+            .plaintext_offsets
+            .partition_point(|&offset| offset < range.end);
+        first_block_ord..end_block_ord
+    }
+
+    /// Returns the range of the encrypted blocks overlapping the range of plaintext bytes.
+    fn encrypted_range(&self, range: &Range<usize>) -> Range<usize> {
+        let block_ords = self.block_ords(range);
+        self.encrypted_offsets[block_ords.start]..self.encrypted_offsets[block_ords.end]
+    }
+
+    /// Decrypts the encrypted blocks overlapping the range of plaintext bytes.
+    fn decrypt_range(
+        &self,
+        range: Range<usize>,
+        encrypted_blocks: OwnedBytes,
+    ) -> io::Result<OwnedBytes> {
+        let block_ords = self.block_ords(&range);
+        let blocks_start = self.encrypted_offsets[block_ords.start];
+        let mut plaintext = Vec::with_capacity(encrypted_blocks.len());
+        for block_ord in block_ords.clone() {
+            let block_range = self.encrypted_offsets[block_ord] - blocks_start
+                ..self.encrypted_offsets[block_ord + 1] - blocks_start;
+            let block = encrypted_blocks
+                .as_slice()
+                .get(block_range)
+                .ok_or_else(|| invalid_data(format!("Truncated encrypted block {block_ord}")))?;
+            plaintext.extend(self.block_cipher.decrypt(block_ord, false, block)?);
+        }
+        let offset = range.start - self.plaintext_offsets[block_ords.start];
+        Ok(OwnedBytes::new(plaintext).slice(offset..offset + range.len()))
+    }
+}
+
+#[async_trait]
+impl FileHandle for EncryptedFileHandle {
+    fn read_bytes(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        if range.is_empty() {
+            return Ok(OwnedBytes::empty());
+        }
+        let encrypted_blocks = self.underlying.read_bytes(self.encrypted_range(&range))?;
+        self.decrypt_range(range, encrypted_blocks)
+    }
+
+    async fn read_bytes_async(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        if range.is_empty() {
+            return Ok(OwnedBytes::empty());
+        }
+        let encrypted_blocks = self
+            .underlying
+            .read_bytes_async(self.encrypted_range(&range))
+            .await?;
+        self.decrypt_range(range, encrypted_blocks)
+    }
+}
+
+/// Writer associated with the [`EncryptedDirectory`].
+///
+/// The plaintext is buffered until a full block is available, or the writer is flushed.
+/// Terminating the writer writes the block index of the file.
+struct EncryptedWriter {
+    path: PathBuf,
+    underlying: WritePtr,
+    block_cipher: BlockCipher,
+    buffer: Vec<u8>,
+    // Plaintext length of the blocks written so far.
+    block_lens: Vec<u32>,
+    is_terminated: bool,
+}
+
+impl EncryptedWriter {
+    fn write_block(&mut self, block: &[u8]) -> io::Result<()> {
+        let block_ord = self.block_lens.len();
+        let encrypted_block = self.block_cipher.encrypt(block_ord, false, block)?;
+        self.underlying.write_all(&encrypted_block)?;
+        self.block_lens.push(block.len() as u32);
+        Ok(())
+    }
+
+    fn write_pending_block(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let block = mem::take(&mut self.buffer);
+        self.write_block(&block)
+    }
+}
+
+impl Write for EncryptedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let block_size = self.block_cipher.header.block_size;
+        self.buffer.extend_from_slice(buf);
+        while self.buffer.len() >= block_size {
+            let remaining = self.buffer.split_off(block_size);
+            let block = mem::replace(&mut self.buffer, remaining);
+            self.write_block(&block)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_pending_block()?;
+        self.underlying.flush()
+    }
+}
+
+impl TerminatingWrite for EncryptedWriter {
+    fn terminate_ref(&mut self, token: AntiCallToken) -> io::Result<()> {
+        self.write_pending_block()?;
+        let encrypted_index = self.block_cipher.encrypt_block_index(&self.block_lens)?;
+        self.underlying.write_all(&encrypted_index)?;
+        self.is_terminated = true;
+        self.underlying.terminate_ref(token)
+    }
+}
+
+impl Drop for EncryptedWriter {
+    fn drop(&mut self) {
+        // Lock files are never terminated, nor written to.
+        if !self.is_terminated && (!self.block_lens.is_empty() || !self.buffer.is_empty()) {
+            warn!(
+                "The writer of {:?} got dropped before being terminated: the file is truncated.",
+                self.path
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::path::Path;
+
+    use common::HasLen;
+    use futures::executor::block_on;
+
+    use super::{EncryptedDirectory, EncryptionKey, KeyProvider, FOOTER_LEN, HEADER_LEN, TAG_LEN};
+    use crate::collector::Count;
+    use crate::directory::{RamDirectory, TerminatingWrite};
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, TEXT};
+    use crate::{Directory, Index, Term};
+
+    fn key(byte: u8) -> EncryptionKey {
+        EncryptionKey::from([byte; 32])
+    }
+
+    #[test]
+    fn test_encrypted_directory_range_reads() -> crate::Result<()> {
+        let ram_directory = RamDirectory::default();
+        let directory = EncryptedDirectory::new(ram_directory.clone(), key(1)).block_size(10);
+        let data: Vec<u8> = (0..=255u8).collect();
+        for len in [0, 1, 10, 11, 20, 256] {
+            let path_str = format!("file{len}");
+            let path = Path::new(&path_str);
+            let mut write = directory.open_write(path)?;
+            write.write_all(&data[..len])?;
+            write.terminate()?;
+            let file = directory.open_read(path)?;
+            assert_eq!(file.len(), len);
+            assert_eq!(file.read_bytes()?.as_slice(), &data[..len]);
+            for start in 0..len {
+                for end in start..=len.min(start + 25) {
+                    assert_eq!(
+                        file.read_bytes_slice(start..end)?.as_slice(),
+                        &data[start..end]
+                    );
+                }
+            }
+        }
+        // The underlying files are encrypted.
+        let raw_file = ram_directory
+            .open_read(Path::new("file256"))?
+            .read_bytes()?;
+        assert!(!raw_file
+            .as_slice()
+            .windows(10)
+            .any(|window| window == &data[100..110]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypted_directory_read_async() -> crate::Result<()> {
+        let directory = EncryptedDirectory::new(RamDirectory::default(), key(1)).block_size(10);
+        let path = Path::new("file");
+        let data: Vec<u8> = (0..100u8).collect();
+        let mut write = directory.open_write(path)?;
+        write.write_all(&data)?;
+        write.terminate()?;
+        let file = directory.open_read(path)?;
+        for (start, end) in [(0, 100), (15, 42), (99, 100), (20, 20)] {
+            let bytes = block_on(file.read_bytes_slice_async(start..end))?;
+            assert_eq!(bytes.as_slice(), &data[start..end]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypted_directory_flush() -> crate::Result<()> {
+        let ram_directory = RamDirectory::default();
+        let directory = EncryptedDirectory::new(ram_directory.clone(), key(1)).block_size(10);
+        let path = Path::new("file");
+        let data: Vec<u8> = (0..100u8).collect();
+        let mut write = directory.open_write(path)?;
+        write.write_all(&data[..15])?;
+        write.flush()?;
+        // The partial block is written on flush.
+        assert_eq!(
+            ram_directory.open_read(path)?.len(),
+            HEADER_LEN + 10 + 5 + 2 * TAG_LEN
+        );
+        write.write_all(&data[15..47])?;
+        write.flush()?;
+        write.flush()?;
+        write.write_all(&data[47..])?;
+        write.terminate()?;
+        let file = directory.open_read(path)?;
+        assert_eq!(file.len(), 100);
+        for start in 0..100 {
+            for end in start..=100 {
+                assert_eq!(
+                    file.read_bytes_slice(start..end)?.as_slice(),
+                    &data[start..end]
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypted_directory_atomic_write() -> crate::Result<()> {
+        let ram_directory = RamDirectory::default();
+        let directory = EncryptedDirectory::new(ram_directory.clone(), key(1)).block_size(4);
+        let path = Path::new("meta.json");
+        directory.atomic_write(path, b"some metadata")?;
+        assert_eq!(directory.atomic_read(path)?, b"some metadata");
+        assert_ne!(ram_directory.atomic_read(path)?, b"some metadata");
+        directory.atomic_write(path, b"")?;
+        assert_eq!(directory.atomic_read(path)?, b"");
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypted_directory_tampering() -> crate::Result<()> {
+        let ram_directory = RamDirectory::default();
+        let directory = EncryptedDirectory::new(ram_directory.clone(), key(1)).block_size(4);
+        let path = Path::new("meta.json");
+        directory.atomic_write(path, b"some metadata")?;
+        let encrypted = ram_directory.atomic_read(path)?;
+
+        for flipped_byte in [
+            HEADER_LEN,
+            encrypted.len() - FOOTER_LEN - 1,
+            encrypted.len() - 1,
+        ] {
+            let mut flipped = encrypted.clone();
+            flipped[flipped_byte] ^= 1;
+            ram_directory.atomic_write(path, &flipped)?;
+            assert!(directory.atomic_read(path).is_err());
+        }
+
+        // Dropping the block index.
+        let block_index_len = TAG_LEN + 4 * 4 + FOOTER_LEN;
+        let truncated = &encrypted[..encrypted.len() - block_index_len];
+        ram_directory.atomic_write(path, truncated)?;
+        assert!(directory.atomic_read(path).is_err());
+
+        ram_directory.atomic_write(path, &encrypted)?;
+        let other_key_directory = EncryptedDirectory::new(ram_directory, key(2));
+        assert!(other_key_directory.atomic_read(path).is_err());
+        Ok(())
+    }
+
+    struct RotatingKeyProvider;
+
+    impl KeyProvider for RotatingKeyProvider {
+        fn current_key(&self) -> std::io::Result<(u32, EncryptionKey)> {
+            Ok((2, key(2)))
+        }
+
+        fn key(&self, key_id: u32) -> std::io::Result<EncryptionKey> {
+            Ok(key(key_id as u8))
+        }
+    }
+
+    #[test]
+    fn test_encrypted_directory_key_rotation() -> crate::Result<()> {
+        let ram_directory = RamDirectory::default();
+        let old_directory = EncryptedDirectory::new(ram_directory.clone(), key(0));
+        old_directory.atomic_write(Path::new("old"), b"old data")?;
+        let directory = EncryptedDirectory::new(ram_directory, RotatingKeyProvider);
+        directory.atomic_write(Path::new("new"), b"new data")?;
+        assert_eq!(directory.atomic_read(Path::new("old"))?, b"old data");
+        assert_eq!(directory.atomic_read(Path::new("new"))?, b"new data");
+        assert!(old_directory.atomic_read(Path::new("new")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypted_directory_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let ram_directory = RamDirectory::default();
+        let directory = EncryptedDirectory::new(ram_directory.clone(), key(7)).block_size(100);
+        let index = Index::create(directory, schema, Default::default())?;
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            for i in 0..100 {
+                index_writer.add_document(doc!(text => format!("hello {i}")))?;
+            }
+            index_writer.commit()?;
+        }
+        assert!(Index::open(ram_directory.clone()).is_err());
+        let index = Index::open(EncryptedDirectory::new(ram_directory, key(7)))?;
+        let searcher = index.reader()?.searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(text, "hello"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 100);
+        Ok(())
+    }
+}
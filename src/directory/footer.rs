@@ -163,6 +163,7 @@ mod tests {
 
     use crate::directory::footer::{Footer, FOOTER_MAGIC_NUMBER};
     use crate::directory::{FileSlice, OwnedBytes};
+    use crate::INDEX_FORMAT_VERSION;
 
     #[test]
     fn test_deserialize_footer() {
@@ -174,6 +175,25 @@ mod tests {
         let (footer_deser, _body) = Footer::extract_footer(fileslice).unwrap();
         assert_eq!(footer_deser.crc(), footer.crc());
     }
+    #[test]
+    fn test_footer_is_compatible() {
+        let footer_with_format_version = |index_format_version: u32| {
+            let mut footer = Footer::new(123);
+            footer.version.index_format_version = index_format_version;
+            footer
+        };
+        assert_eq!(Footer::new(123).version.index_format_version, 6);
+        assert!(footer_with_format_version(3).is_compatible().is_err());
+        assert!(footer_with_format_version(4).is_compatible().is_ok());
+        assert!(footer_with_format_version(5).is_compatible().is_ok());
+        assert!(footer_with_format_version(INDEX_FORMAT_VERSION)
+            .is_compatible()
+            .is_ok());
+        assert!(footer_with_format_version(INDEX_FORMAT_VERSION + 1)
+            .is_compatible()
+            .is_err());
+    }
+
     #[test]
     fn test_deserialize_footer_missing_magic_byte() {
         let mut buf: Vec<u8> = vec![];
@@ -0,0 +1,498 @@
+use std::collections::HashMap;
+use std::io::{self, BufWriter, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use std::{fmt, result};
+
+use async_trait::async_trait;
+use common::HasLen;
+
+use crate::core::{SegmentComponent, MANAGED_FILEPATH, META_FILEPATH};
+use crate::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
+use crate::directory::{
+    AntiCallToken, Directory, DirectoryLock, FileHandle, Lock, OwnedBytes, TerminatingWrite,
+    WatchCallback, WatchHandle, WritePtr,
+};
+
+/// Number of buckets of a [`LatencyHistogram`].
+pub const NUM_LATENCY_BUCKETS: usize = 24;
+
+/// The kind of a file of an index, used to break down the IO statistics of an
+/// [`InstrumentedDirectory`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FileKind {
+    /// A component of a segment.
+    Segment(SegmentComponent),
+    /// The `meta.json` and `.managed.json` files.
+    Meta,
+    /// Any other file, such as the lock files.
+    Other,
+}
+
+impl FileKind {
+    /// Returns the kind of the file at the given path, relative to the root of the index.
+    pub fn for_path(path: &Path) -> FileKind {
+        if path == *META_FILEPATH || path == *MANAGED_FILEPATH {
+            return FileKind::Meta;
+        }
+        let Some(file_name) = path.file_name().and_then(|file_name| file_name.to_str()) else {
+            return FileKind::Other;
+        };
+        // Segment files are named `segment_uuid.extension`.
+        let Some((_, extension)) = file_name.split_once('.') else {
+            return FileKind::Other;
+        };
+        let component = match extension {
+            "idx" => SegmentComponent::Postings,
+            "pos" => SegmentComponent::Positions,
+            "term" => SegmentComponent::Terms,
+            "store" => SegmentComponent::Store,
+            "store.temp" => SegmentComponent::TempStore,
+            "fast" => SegmentComponent::FastFields,
+            "fieldnorm" => SegmentComponent::FieldNorms,
+            "points" => SegmentComponent::Points,
+            "tv" => SegmentComponent::TermVectors,
+            "pay" => SegmentComponent::Payloads,
+            "compl" => SegmentComponent::Completions,
+            "hnsw" => SegmentComponent::Hnsw,
+            // The delete files are named `segment_uuid.delete_opstamp.del`.
+            _ if extension.ends_with(".del") => SegmentComponent::Delete,
+            _ => return FileKind::Other,
+        };
+        FileKind::Segment(component)
+    }
+
+    fn all() -> impl Iterator<Item = FileKind> {
+        SegmentComponent::iterator()
+            .copied()
+            .map(FileKind::Segment)
+            .chain([FileKind::Meta, FileKind::Other])
+    }
+}
+
+/// Histogram of the latencies of the reads of an [`InstrumentedDirectory`].
+///
+/// The bucket `i` counts the latencies lower than `2^i` microseconds, and at least
+/// `2^(i-1)` microseconds. The last bucket counts all of the latencies above.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    counts: [u64; NUM_LATENCY_BUCKETS],
+}
+
+impl LatencyHistogram {
+    fn bucket(latency: Duration) -> usize {
+        let micros = latency.as_micros();
+        let bucket = (u128::BITS - micros.leading_zeros()) as usize;
+        bucket.min(NUM_LATENCY_BUCKETS - 1)
+    }
+
+    /// Returns the upper bound of the latencies counted in a bucket, or `None` for the last
+    /// bucket.
+    pub fn bucket_upper_bound(bucket: usize) -> Option<Duration> {
+        if bucket + 1 >= NUM_LATENCY_BUCKETS {
+            return None;
+        }
+        Some(Duration::from_micros(1 << bucket))
+    }
+
+    /// Returns the number of latencies in each bucket.
+    pub fn counts(&self) -> &[u64; NUM_LATENCY_BUCKETS] {
+        &self.counts
+    }
+
+    /// Returns the number of latencies recorded.
+    pub fn num_samples(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Returns the upper bound of the bucket of the `quantile` of the latencies, between 0 and
+    /// 1, e.g. `0.99` for the 99th percentile.
+    ///
+    /// Returns `None` if no latency was recorded, and [`Duration::MAX`] if the quantile falls
+    /// in the last bucket.
+    pub fn quantile(&self, quantile: f64) -> Option<Duration> {
+        let num_samples = self.num_samples();
+        if num_samples == 0 {
+            return None;
+        }
+        let rank = ((quantile.clamp(0.0, 1.0) * num_samples as f64).ceil() as u64).max(1);
+        let mut num_below = 0;
+        for (bucket, count) in self.counts.iter().enumerate() {
+            num_below += count;
+            if num_below >= rank {
+                return Some(Self::bucket_upper_bound(bucket).unwrap_or(Duration::MAX));
+            }
+        }
+        Some(Duration::MAX)
+    }
+}
+
+/// IO statistics of the files of a given [`FileKind`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FileKindIoStats {
+    /// The number of reads.
+    pub num_reads: u64,
+    /// The number of bytes read.
+    pub bytes_read: u64,
+    /// The latencies of the reads.
+    pub read_latencies: LatencyHistogram,
+    /// The number of files written, counted once they are terminated.
+    pub num_files_written: u64,
+    /// The number of bytes written.
+    pub bytes_written: u64,
+}
+
+#[derive(Default)]
+struct IoCounters {
+    num_reads: AtomicU64,
+    bytes_read: AtomicU64,
+    read_latencies: [AtomicU64; NUM_LATENCY_BUCKETS],
+    num_files_written: AtomicU64,
+    bytes_written: AtomicU64,
+}
+
+impl IoCounters {
+    fn record_read(&self, num_bytes: usize, latency: Duration) {
+        self.num_reads.fetch_add(1, Ordering::Relaxed);
+        self.bytes_read
+            .fetch_add(num_bytes as u64, Ordering::Relaxed);
+        self.read_latencies[LatencyHistogram::bucket(latency)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn record_write(&self, num_bytes: usize) {
+        self.bytes_written
+            .fetch_add(num_bytes as u64, Ordering::Relaxed);
+    }
+
+    fn record_file_written(&self) {
+        self.num_files_written.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn stats(&self) -> FileKindIoStats {
+        let mut read_latencies = LatencyHistogram::default();
+        for (count, counter) in read_latencies.counts.iter_mut().zip(&self.read_latencies) {
+            *count = counter.load(Ordering::Relaxed);
+        }
+        FileKindIoStats {
+            num_reads: self.num_reads.load(Ordering::Relaxed),
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+            read_latencies,
+            num_files_written: self.num_files_written.load(Ordering::Relaxed),
+            bytes_written: self.bytes_written.load(Ordering::Relaxed),
+        }
+    }
+
+    fn reset(&self) {
+        let counters = [
+            &self.num_reads,
+            &self.bytes_read,
+            &self.num_files_written,
+            &self.bytes_written,
+        ];
+        for counter in counters.into_iter().chain(&self.read_latencies) {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// A Directory wrapping another directory, recording the IO statistics of its files by
+/// [`FileKind`]: the number of reads, the bytes read and written, and a histogram of the
+/// latencies of the reads.
+///
+/// These statistics tell whether the searches are bound on reading the term dictionaries,
+/// the postings or the doc store, for instance. The reads are only timed as seen by tantivy:
+/// the reads of a memory mapped file are cheap, while its page faults when the bytes are
+/// later accessed are not measured.
+#[derive(Clone)]
+pub struct InstrumentedDirectory {
+    underlying: Arc<dyn Directory>,
+    counters: Arc<HashMap<FileKind, IoCounters>>,
+}
+
+impl fmt::Debug for InstrumentedDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "InstrumentedDirectory({:?})", self.underlying)
+    }
+}
+
+impl InstrumentedDirectory {
+    /// Wraps a directory, recording the IO statistics of its files.
+    pub fn new(underlying: impl Into<Box<dyn Directory>>) -> InstrumentedDirectory {
+        let counters = FileKind::all()
+            .map(|file_kind| (file_kind, IoCounters::default()))
+            .collect();
+        InstrumentedDirectory {
+            underlying: Arc::from(underlying.into()),
+            counters: Arc::new(counters),
+        }
+    }
+
+    fn counters(&self, path: &Path) -> &IoCounters {
+        &self.counters[&FileKind::for_path(path)]
+    }
+
+    /// Returns the IO statistics of the kinds of files read or written since the creation of
+    /// the directory, or since the last call to [`InstrumentedDirectory::reset_io_stats()`].
+    pub fn io_stats(&self) -> HashMap<FileKind, FileKindIoStats> {
+        self.counters
+            .iter()
+            .map(|(file_kind, counters)| (*file_kind, counters.stats()))
+            .filter(|(_, stats)| *stats != FileKindIoStats::default())
+            .collect()
+    }
+
+    /// Resets all of the IO statistics.
+    pub fn reset_io_stats(&self) {
+        for counters in self.counters.values() {
+            counters.reset();
+        }
+    }
+}
+
+impl Directory for InstrumentedDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+        let underlying = self.underlying.get_file_handle(path)?;
+        Ok(Arc::new(InstrumentedFileHandle {
+            underlying,
+            file_kind: FileKind::for_path(path),
+            counters: self.counters.clone(),
+        }))
+    }
+
+    fn delete(&self, path: &Path) -> result::Result<(), DeleteError> {
+        self.underlying.delete(path)
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        self.underlying.exists(path)
+    }
+
+    fn open_write(&self, path: &Path) -> result::Result<WritePtr, OpenWriteError> {
+        let underlying = self.underlying.open_write(path)?;
+        Ok(BufWriter::new(Box::new(InstrumentedWriter {
+            underlying,
+            file_kind: FileKind::for_path(path),
+            counters: self.counters.clone(),
+        })))
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        let start = Instant::now();
+        let data = self.underlying.atomic_read(path)?;
+        self.counters(path).record_read(data.len(), start.elapsed());
+        Ok(data)
+    }
+
+    fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.underlying.atomic_write(path, data)?;
+        let counters = self.counters(path);
+        counters.record_write(data.len());
+        counters.record_file_written();
+        Ok(())
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> result::Result<DirectoryLock, LockError> {
+        self.underlying.acquire_lock(lock)
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        self.underlying.watch(watch_callback)
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        self.underlying.sync_directory()
+    }
+}
+
+struct InstrumentedFileHandle {
+    underlying: Arc<dyn FileHandle>,
+    file_kind: FileKind,
+    counters: Arc<HashMap<FileKind, IoCounters>>,
+}
+
+impl InstrumentedFileHandle {
+    fn record_read(&self, num_bytes: usize, latency: Duration) {
+        self.counters[&self.file_kind].record_read(num_bytes, latency);
+    }
+}
+
+impl fmt::Debug for InstrumentedFileHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "InstrumentedFileHandle({:?})", self.underlying)
+    }
+}
+
+impl HasLen for InstrumentedFileHandle {
+    fn len(&self) -> usize {
+        self.underlying.len()
+    }
+}
+
+#[async_trait]
+impl FileHandle for InstrumentedFileHandle {
+    fn read_bytes(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        let start = Instant::now();
+        let data = self.underlying.read_bytes(range)?;
+        self.record_read(data.len(), start.elapsed());
+        Ok(data)
+    }
+
+    async fn read_bytes_async(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        let start = Instant::now();
+        let data = self.underlying.read_bytes_async(range).await?;
+        self.record_read(data.len(), start.elapsed());
+        Ok(data)
+    }
+}
+
+struct InstrumentedWriter {
+    underlying: WritePtr,
+    file_kind: FileKind,
+    counters: Arc<HashMap<FileKind, IoCounters>>,
+}
+
+impl Write for InstrumentedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let num_bytes = self.underlying.write(buf)?;
+        self.counters[&self.file_kind].record_write(num_bytes);
+        Ok(num_bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.underlying.flush()
+    }
+}
+
+impl TerminatingWrite for InstrumentedWriter {
+    fn terminate_ref(&mut self, token: AntiCallToken) -> io::Result<()> {
+        self.underlying.terminate_ref(token)?;
+        self.counters[&self.file_kind].record_file_written();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::path::Path;
+    use std::time::Duration;
+
+    use super::{FileKind, InstrumentedDirectory, LatencyHistogram};
+    use crate::collector::TopDocs;
+    use crate::core::SegmentComponent;
+    use crate::directory::{RamDirectory, TerminatingWrite};
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, STORED, TEXT};
+    use crate::{Directory, Index, IndexSettings, Term};
+
+    #[test]
+    fn test_file_kind_for_path() {
+        let uuid = "00000000000000000000000000000000";
+        let file_kind = |name: String| FileKind::for_path(Path::new(&name));
+        assert_eq!(FileKind::for_path(Path::new("meta.json")), FileKind::Meta);
+        assert_eq!(
+            FileKind::for_path(Path::new(".managed.json")),
+            FileKind::Meta
+        );
+        assert_eq!(
+            file_kind(format!("{uuid}.term")),
+            FileKind::Segment(SegmentComponent::Terms)
+        );
+        assert_eq!(
+            file_kind(format!("{uuid}.store.temp")),
+            FileKind::Segment(SegmentComponent::TempStore)
+        );
+        assert_eq!(
+            file_kind(format!("{uuid}.12.del")),
+            FileKind::Segment(SegmentComponent::Delete)
+        );
+        assert_eq!(
+            FileKind::for_path(Path::new(".tantivy-writer.lock")),
+            FileKind::Other
+        );
+    }
+
+    #[test]
+    fn test_latency_histogram_quantile() {
+        let mut histogram = LatencyHistogram::default();
+        assert_eq!(histogram.quantile(0.5), None);
+        for latency in [0, 3, 3, 100] {
+            histogram.counts[LatencyHistogram::bucket(Duration::from_micros(latency))] += 1;
+        }
+        assert_eq!(histogram.num_samples(), 4);
+        assert_eq!(histogram.counts()[0], 1);
+        assert_eq!(histogram.counts()[2], 2);
+        assert_eq!(histogram.quantile(0.5), Some(Duration::from_micros(4)));
+        assert_eq!(histogram.quantile(1.0), Some(Duration::from_micros(128)));
+        histogram.counts[LatencyHistogram::bucket(Duration::from_secs(3_600))] += 1;
+        assert_eq!(histogram.quantile(1.0), Some(Duration::MAX));
+    }
+
+    #[test]
+    fn test_instrumented_directory_file_io() -> crate::Result<()> {
+        let directory = InstrumentedDirectory::new(RamDirectory::default());
+        let path = Path::new("file");
+        let mut write = directory.open_write(path)?;
+        write.write_all(&[1u8; 100])?;
+        write.terminate()?;
+        let file = directory.open_read(path)?;
+        file.read_bytes_slice(10..30)?;
+        file.read_bytes_slice(0..100)?;
+        directory.atomic_write(Path::new("meta.json"), b"{}")?;
+
+        let io_stats = directory.io_stats();
+        assert_eq!(io_stats.len(), 2);
+        let other_stats = &io_stats[&FileKind::Other];
+        assert_eq!(other_stats.num_reads, 2);
+        assert_eq!(other_stats.bytes_read, 120);
+        assert_eq!(other_stats.read_latencies.num_samples(), 2);
+        assert_eq!(other_stats.num_files_written, 1);
+        assert_eq!(other_stats.bytes_written, 100);
+        assert_eq!(io_stats[&FileKind::Meta].bytes_written, 2);
+
+        directory.reset_io_stats();
+        assert!(directory.io_stats().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_instrumented_directory_search() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT | STORED);
+        let directory = InstrumentedDirectory::new(RamDirectory::default());
+        let index = Index::create(
+            directory.clone(),
+            schema_builder.build(),
+            IndexSettings::default(),
+        )?;
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "hello happy tax payer"))?;
+        index_writer.add_document(doc!(text => "goodbye"))?;
+        index_writer.commit()?;
+        let writes = directory.io_stats();
+        assert!(writes[&FileKind::Segment(SegmentComponent::Store)].bytes_written > 0);
+        assert!(writes[&FileKind::Segment(SegmentComponent::Terms)].num_files_written > 0);
+
+        let searcher = index.reader()?.searcher();
+        directory.reset_io_stats();
+        let query = TermQuery::new(
+            Term::from_field_text(text, "hello"),
+            IndexRecordOption::Basic,
+        );
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(1))?;
+        searcher.doc(top_docs[0].1)?;
+        let reads = directory.io_stats();
+        for component in [
+            SegmentComponent::Terms,
+            SegmentComponent::Postings,
+            SegmentComponent::Store,
+        ] {
+            assert!(reads[&FileKind::Segment(component)].num_reads > 0);
+        }
+        assert!(!reads.contains_key(&FileKind::Meta));
+        Ok(())
+    }
+}
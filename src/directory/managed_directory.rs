@@ -4,14 +4,15 @@ use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock, RwLockWriteGuard};
 use std::{io, result};
 
+use common::HasLen;
 use crc32fast::Hasher;
 
 use crate::core::MANAGED_FILEPATH;
 use crate::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
 use crate::directory::footer::{Footer, FooterProxy};
 use crate::directory::{
-    DirectoryLock, FileHandle, FileSlice, GarbageCollectionResult, Lock, WatchCallback,
-    WatchHandle, WritePtr, META_LOCK,
+    DirectoryLock, FileHandle, FileSlice, GarbageCollectionResult, Lock, TerminatingWrite,
+    WatchCallback, WatchHandle, WritePtr, META_LOCK,
 };
 use crate::error::DataCorruption;
 use crate::Directory;
@@ -256,6 +257,62 @@ impl ManagedDirectory {
         Ok(footer.crc() == crc)
     }
 
+    /// Opens a file for read, including its footer.
+    pub(crate) fn open_read_with_footer(
+        &self,
+        path: &Path,
+    ) -> result::Result<FileSlice, OpenReadError> {
+        self.directory.open_read(path)
+    }
+
+    /// Returns the checksum of a managed file, as recorded in its footer.
+    pub(crate) fn footer_crc(&self, path: &Path) -> crate::Result<u32> {
+        let (footer, _) = Footer::extract_footer(self.directory.open_read(path)?)?;
+        Ok(footer.crc())
+    }
+
+    /// Writes a file as is, footer included, and registers it as managed.
+    ///
+    /// A file already at this path is replaced.
+    pub(crate) fn write_with_footer(
+        &self,
+        path: &Path,
+        data: &mut dyn io::Read,
+    ) -> crate::Result<()> {
+        self.delete_if_exists(path)?;
+        self.register_file_as_managed(path)?;
+        let mut write = self.directory.open_write(path)?;
+        io::copy(data, &mut write)?;
+        write.terminate()?;
+        Ok(())
+    }
+
+    /// Copies a file of another managed directory as is, footer included, and registers it as
+    /// managed.
+    ///
+    /// A file of the same size at this path is assumed to be a copy already, as the files
+    /// of a segment never change, and is left as is. A file of a different size is replaced.
+    pub(crate) fn copy_file_from(
+        &self,
+        source: &ManagedDirectory,
+        path: &Path,
+    ) -> crate::Result<()> {
+        let file = source.directory.open_read(path)?;
+        if self.directory.exists(path)? && self.directory.open_read(path)?.len() == file.len() {
+            return Ok(());
+        }
+        self.write_with_footer(path, &mut file.read_bytes()?)
+    }
+
+    pub(crate) fn delete_if_exists(&self, path: &Path) -> crate::Result<()> {
+        match self.directory.delete(path) {
+            Ok(()) | Err(DeleteError::FileDoesNotExist(_)) => Ok(()),
+            Err(DeleteError::IoError { io_error, .. }) => {
+                Err(crate::TantivyError::IoError(io_error))
+            }
+        }
+    }
+
     /// List all managed files
     pub fn list_managed_files(&self) -> HashSet<PathBuf> {
         let managed_paths = self
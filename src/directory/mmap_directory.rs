@@ -261,7 +261,7 @@ impl MmapDirectory {
 
     /// Joins a relative_path to the directory `root_path`
     /// to create a proper complete `filepath`.
-    fn resolve_path(&self, relative_path: &Path) -> PathBuf {
+    pub(crate) fn resolve_path(&self, relative_path: &Path) -> PathBuf {
         self.inner.root_path.join(relative_path)
     }
 
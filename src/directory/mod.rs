@@ -7,14 +7,21 @@ mod directory;
 mod directory_lock;
 mod file_watcher;
 mod footer;
+mod instrumented_directory;
 mod managed_directory;
+mod object_store_directory;
+#[cfg(feature = "mmap")]
+mod pread_directory;
 mod ram_directory;
 mod watch_event_router;
 
 /// Errors specific to the directory module.
 pub mod error;
 
+mod caching_directory;
 mod composite_file;
+#[cfg(feature = "encryption")]
+mod encrypted_directory;
 
 use std::io::BufWriter;
 use std::path::PathBuf;
@@ -22,9 +29,25 @@ use std::path::PathBuf;
 pub use common::file_slice::{FileHandle, FileSlice};
 pub use common::{AntiCallToken, OwnedBytes, TerminatingWrite};
 
+pub use self::caching_directory::{
+    BlockCacheStats, CachingDirectory, EvictionPolicy, DEFAULT_CACHE_BLOCK_SIZE,
+};
 pub(crate) use self::composite_file::{CompositeFile, CompositeWrite};
 pub use self::directory::{Directory, DirectoryClone, DirectoryLock};
 pub use self::directory_lock::{Lock, INDEX_WRITER_LOCK, META_LOCK};
+#[cfg(feature = "encryption")]
+pub use self::encrypted_directory::{
+    EncryptedDirectory, EncryptionKey, KeyProvider, DEFAULT_ENCRYPTION_BLOCK_SIZE,
+};
+pub use self::instrumented_directory::{
+    FileKind, FileKindIoStats, InstrumentedDirectory, LatencyHistogram, NUM_LATENCY_BUCKETS,
+};
+pub use self::object_store_directory::{
+    MultipartUpload, ObjectStore, ObjectStoreDirectory, ObjectVersion, PutMode, PutOutcome,
+    RamObjectStore, DEFAULT_PART_SIZE,
+};
+#[cfg(feature = "mmap")]
+pub use self::pread_directory::{PreadDirectory, DEFAULT_PAGE_CACHE_CAPACITY};
 pub use self::ram_directory::RamDirectory;
 pub use self::watch_event_router::{WatchCallback, WatchCallbackList, WatchHandle};
 
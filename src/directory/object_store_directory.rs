@@ -0,0 +1,649 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, BufWriter, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::{fmt, mem};
+
+use async_trait::async_trait;
+use common::HasLen;
+
+use crate::core::META_FILEPATH;
+use crate::directory::error::{DeleteError, OpenReadError, OpenWriteError};
+use crate::directory::{
+    AntiCallToken, Directory, FileHandle, OwnedBytes, TerminatingWrite, WatchCallback,
+    WatchCallbackList, WatchHandle, WritePtr,
+};
+
+/// Default size of the parts of a multipart upload: 8 MiB.
+pub const DEFAULT_PART_SIZE: usize = 8 << 20;
+
+/// Opaque version of an object, such as an ETag or a generation number.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ObjectVersion(pub String);
+
+/// How an object should be written by [`ObjectStore::put()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PutMode {
+    /// Writes the object, whether it exists or not.
+    Overwrite,
+    /// Writes the object only if it does not exist.
+    Create,
+    /// Writes the object only if it exists, with the given version.
+    Update(ObjectVersion),
+}
+
+/// Outcome of an [`ObjectStore::put()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PutOutcome {
+    /// The object was written, and has the given version.
+    Written(ObjectVersion),
+    /// The [`PutMode`] condition did not hold, and the object was not written.
+    ConditionFailed,
+}
+
+/// A multipart upload of an object, started with [`ObjectStore::create_multipart_upload()`].
+///
+/// The object only becomes visible once the upload is completed.
+pub trait MultipartUpload: Send + Sync {
+    /// Uploads a part of the object. The parts are numbered from 0, and all of them but the
+    /// last one have the same size.
+    fn upload_part(&mut self, part_num: usize, data: Vec<u8>) -> io::Result<()>;
+
+    /// Assembles the uploaded parts, in the order of their number, into the object.
+    fn complete(self: Box<Self>) -> io::Result<()>;
+
+    /// Discards the uploaded parts.
+    fn abort(self: Box<Self>) -> io::Result<()>;
+}
+
+/// Minimal interface of an object storage, such as S3, GCS or Azure Blob Storage, used by the
+/// [`ObjectStoreDirectory`].
+///
+/// Tantivy does not ship a client for any cloud provider: implementing this trait is typically
+/// a thin wrapper around the provider's SDK.
+#[async_trait]
+pub trait ObjectStore: fmt::Debug + Send + Sync + 'static {
+    /// Returns the length of the object, or `None` if it does not exist.
+    fn head(&self, key: &str) -> io::Result<Option<usize>>;
+
+    /// Returns the content of the object and its version, or `None` if it does not exist.
+    fn get(&self, key: &str) -> io::Result<Option<(OwnedBytes, ObjectVersion)>>;
+
+    /// Returns a range of bytes of the object.
+    fn get_range(&self, key: &str, range: Range<usize>) -> io::Result<OwnedBytes>;
+
+    /// Returns a range of bytes of the object asynchronously.
+    ///
+    /// The default implementation calls the blocking [`ObjectStore::get_range()`]: stores with
+    /// an async client should override it, so that the async searches do not block the executor.
+    async fn get_range_async(&self, key: &str, range: Range<usize>) -> io::Result<OwnedBytes> {
+        self.get_range(key, range)
+    }
+
+    /// Writes the object, atomically, if the condition of `mode` holds.
+    fn put(&self, key: &str, data: Vec<u8>, mode: PutMode) -> io::Result<PutOutcome>;
+
+    /// Starts a multipart upload of the object.
+    fn create_multipart_upload(&self, key: &str) -> io::Result<Box<dyn MultipartUpload>>;
+
+    /// Deletes the object, and returns `false` if it did not exist.
+    fn delete(&self, key: &str) -> io::Result<bool>;
+}
+
+struct InnerObjectStoreDirectory {
+    store: Arc<dyn ObjectStore>,
+    prefix: String,
+    // Versions of the files written with `atomic_write`, as last read or written by this
+    // directory.
+    versions: RwLock<HashMap<PathBuf, ObjectVersion>>,
+    watch_router: WatchCallbackList,
+}
+
+/// A Directory storing the files of an index as the objects of an [`ObjectStore`].
+///
+/// - Files are read with range requests, on demand.
+/// - Files are buffered in memory while being written. When they grow larger than the part size,
+///   they are sent with a multipart upload, and they only become visible once terminated.
+/// - Files written with [`Directory::atomic_write()`], such as `meta.json`, are written with
+///   conditional puts: the write fails if the object was modified by someone else since this
+///   directory last read or wrote it.
+///
+/// [`Directory::watch()`] only reports the commits made through this directory: readers in
+/// another process need to be reloaded manually.
+#[derive(Clone)]
+pub struct ObjectStoreDirectory {
+    inner: Arc<InnerObjectStoreDirectory>,
+    part_size: usize,
+}
+
+impl fmt::Debug for ObjectStoreDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ObjectStoreDirectory({:?})", self.inner.prefix)
+    }
+}
+
+impl ObjectStoreDirectory {
+    /// Creates a directory storing its files under `prefix` in the object store.
+    pub fn new(store: impl ObjectStore, prefix: impl ToString) -> ObjectStoreDirectory {
+        ObjectStoreDirectory {
+            inner: Arc::new(InnerObjectStoreDirectory {
+                store: Arc::new(store),
+                prefix: prefix.to_string().trim_end_matches('/').to_string(),
+                versions: Default::default(),
+                watch_router: Default::default(),
+            }),
+            part_size: DEFAULT_PART_SIZE,
+        }
+    }
+
+    /// Sets the size of the parts of the multipart uploads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the part size is 0.
+    #[must_use]
+    pub fn part_size(mut self, part_size: usize) -> ObjectStoreDirectory {
+        assert!(part_size > 0, "The part size must be positive.");
+        self.part_size = part_size;
+        self
+    }
+
+    fn key(&self, path: &Path) -> String {
+        let path = path.to_string_lossy();
+        if self.inner.prefix.is_empty() {
+            path.into_owned()
+        } else {
+            format!("{}/{}", self.inner.prefix, path)
+        }
+    }
+
+    fn store(&self) -> &dyn ObjectStore {
+        &*self.inner.store
+    }
+}
+
+impl Directory for ObjectStoreDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+        let key = self.key(path);
+        let len = self
+            .store()
+            .head(&key)
+            .map_err(|io_error| OpenReadError::wrap_io_error(io_error, path.to_path_buf()))?
+            .ok_or_else(|| OpenReadError::FileDoesNotExist(path.to_path_buf()))?;
+        Ok(Arc::new(ObjectFileHandle {
+            store: self.inner.store.clone(),
+            key,
+            len,
+        }))
+    }
+
+    fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+        let existed =
+            self.store()
+                .delete(&self.key(path))
+                .map_err(|io_error| DeleteError::IoError {
+                    io_error: Arc::new(io_error),
+                    filepath: path.to_path_buf(),
+                })?;
+        if !existed {
+            return Err(DeleteError::FileDoesNotExist(path.to_path_buf()));
+        }
+        self.inner.versions.write().unwrap().remove(path);
+        Ok(())
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        let len_opt = self
+            .store()
+            .head(&self.key(path))
+            .map_err(|io_error| OpenReadError::wrap_io_error(io_error, path.to_path_buf()))?;
+        Ok(len_opt.is_some())
+    }
+
+    fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+        let key = self.key(path);
+        // Creating the empty object right away is what makes a directory lock exclusive.
+        match self.store().put(&key, Vec::new(), PutMode::Create) {
+            Ok(PutOutcome::Written(_)) => {}
+            Ok(PutOutcome::ConditionFailed) => {
+                return Err(OpenWriteError::FileAlreadyExists(path.to_path_buf()));
+            }
+            Err(io_error) => {
+                return Err(OpenWriteError::wrap_io_error(io_error, path.to_path_buf()))
+            }
+        }
+        Ok(BufWriter::new(Box::new(ObjectWriter {
+            store: self.inner.store.clone(),
+            path: path.to_path_buf(),
+            key,
+            part_size: self.part_size,
+            buffer: Vec::new(),
+            upload_opt: None,
+            num_parts: 0,
+            is_flushed: true,
+        })))
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        let (data, version) = self
+            .store()
+            .get(&self.key(path))
+            .map_err(|io_error| OpenReadError::wrap_io_error(io_error, path.to_path_buf()))?
+            .ok_or_else(|| OpenReadError::FileDoesNotExist(path.to_path_buf()))?;
+        self.inner
+            .versions
+            .write()
+            .unwrap()
+            .insert(path.to_path_buf(), version);
+        Ok(data.as_slice().to_vec())
+    }
+
+    fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        let mut versions = self.inner.versions.write().unwrap();
+        let mode = match versions.get(path) {
+            Some(version) => PutMode::Update(version.clone()),
+            None => PutMode::Create,
+        };
+        match self.store().put(&self.key(path), data.to_vec(), mode)? {
+            PutOutcome::Written(version) => {
+                versions.insert(path.to_path_buf(), version);
+            }
+            PutOutcome::ConditionFailed => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("{path:?} was modified concurrently by another writer."),
+                ));
+            }
+        }
+        drop(versions);
+        if path == *META_FILEPATH {
+            drop(self.inner.watch_router.broadcast());
+        }
+        Ok(())
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        Ok(self.inner.watch_router.subscribe(watch_callback))
+    }
+}
+
+struct ObjectFileHandle {
+    store: Arc<dyn ObjectStore>,
+    key: String,
+    len: usize,
+}
+
+impl fmt::Debug for ObjectFileHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ObjectFileHandle({:?}, len={})", self.key, self.len)
+    }
+}
+
+impl HasLen for ObjectFileHandle {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+#[async_trait]
+impl FileHandle for ObjectFileHandle {
+    fn read_bytes(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        if range.is_empty() {
+            return Ok(OwnedBytes::empty());
+        }
+        self.store.get_range(&self.key, range)
+    }
+
+    async fn read_bytes_async(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        if range.is_empty() {
+            return Ok(OwnedBytes::empty());
+        }
+        self.store.get_range_async(&self.key, range).await
+    }
+}
+
+/// Writer associated with the [`ObjectStoreDirectory`].
+///
+/// The data is buffered until it reaches the part size. From then on, it is sent with a
+/// multipart upload, completed when the writer is terminated.
+struct ObjectWriter {
+    store: Arc<dyn ObjectStore>,
+    path: PathBuf,
+    key: String,
+    part_size: usize,
+    buffer: Vec<u8>,
+    upload_opt: Option<Box<dyn MultipartUpload>>,
+    num_parts: usize,
+    is_flushed: bool,
+}
+
+impl ObjectWriter {
+    fn upload_full_parts(&mut self) -> io::Result<()> {
+        while self.buffer.len() >= self.part_size {
+            let remaining = self.buffer.split_off(self.part_size);
+            let part = mem::replace(&mut self.buffer, remaining);
+            self.upload_part(part)?;
+        }
+        Ok(())
+    }
+
+    fn upload_part(&mut self, part: Vec<u8>) -> io::Result<()> {
+        let upload = match &mut self.upload_opt {
+            Some(upload) => upload,
+            None => self
+                .upload_opt
+                .insert(self.store.create_multipart_upload(&self.key)?),
+        };
+        upload.upload_part(self.num_parts, part)?;
+        self.num_parts += 1;
+        Ok(())
+    }
+}
+
+impl Write for ObjectWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.is_flushed = false;
+        self.buffer.extend_from_slice(buf);
+        self.upload_full_parts()?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // Once the multipart upload is started, the data is only visible after `terminate`.
+        if self.upload_opt.is_none() && !self.is_flushed {
+            self.store
+                .put(&self.key, self.buffer.clone(), PutMode::Overwrite)?;
+        }
+        self.is_flushed = true;
+        Ok(())
+    }
+}
+
+impl TerminatingWrite for ObjectWriter {
+    fn terminate_ref(&mut self, _: AntiCallToken) -> io::Result<()> {
+        if self.upload_opt.is_none() {
+            return self.flush();
+        }
+        if !self.buffer.is_empty() {
+            let last_part = mem::take(&mut self.buffer);
+            self.upload_part(last_part)?;
+        }
+        self.is_flushed = true;
+        if let Some(upload) = self.upload_opt.take() {
+            upload.complete()?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ObjectWriter {
+    fn drop(&mut self) {
+        if let Some(upload) = self.upload_opt.take() {
+            warn!(
+                "The writer of {:?} got dropped before being terminated. Aborting its upload.",
+                self.path
+            );
+            if let Err(io_error) = upload.abort() {
+                warn!(
+                    "Failed to abort the upload of {:?}: {io_error:?}",
+                    self.path
+                );
+            }
+        } else if !self.is_flushed {
+            warn!(
+                "You forgot to flush {:?} before its writer got Drop. Do not rely on drop.",
+                self.path
+            );
+        }
+    }
+}
+
+#[derive(Default)]
+struct InnerRamObjectStore {
+    objects: RwLock<HashMap<String, (OwnedBytes, u64)>>,
+    generation: AtomicU64,
+}
+
+impl InnerRamObjectStore {
+    fn insert(&self, key: &str, data: Vec<u8>) -> ObjectVersion {
+        let generation = self.generation.fetch_add(1, Ordering::Relaxed) + 1;
+        self.objects
+            .write()
+            .unwrap()
+            .insert(key.to_string(), (OwnedBytes::new(data), generation));
+        ObjectVersion(generation.to_string())
+    }
+}
+
+/// An [`ObjectStore`] keeping its objects in anonymous memory.
+///
+/// It is mainly meant for unit testing.
+#[derive(Clone, Default)]
+pub struct RamObjectStore {
+    inner: Arc<InnerRamObjectStore>,
+}
+
+impl fmt::Debug for RamObjectStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RamObjectStore")
+    }
+}
+
+impl RamObjectStore {
+    /// Returns the keys of the objects, sorted.
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.inner.objects.read().unwrap().keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+}
+
+impl ObjectStore for RamObjectStore {
+    fn head(&self, key: &str) -> io::Result<Option<usize>> {
+        let objects = self.inner.objects.read().unwrap();
+        Ok(objects.get(key).map(|(data, _)| data.len()))
+    }
+
+    fn get(&self, key: &str) -> io::Result<Option<(OwnedBytes, ObjectVersion)>> {
+        let objects = self.inner.objects.read().unwrap();
+        Ok(objects
+            .get(key)
+            .map(|(data, generation)| (data.clone(), ObjectVersion(generation.to_string()))))
+    }
+
+    fn get_range(&self, key: &str, range: Range<usize>) -> io::Result<OwnedBytes> {
+        let objects = self.inner.objects.read().unwrap();
+        let (data, _) = objects
+            .get(key)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, key.to_string()))?;
+        if range.end > data.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Range {range:?} is out of the {} bytes of {key:?}",
+                    data.len()
+                ),
+            ));
+        }
+        Ok(data.slice(range))
+    }
+
+    fn put(&self, key: &str, data: Vec<u8>, mode: PutMode) -> io::Result<PutOutcome> {
+        // Holding the lock makes checking the condition and writing atomic.
+        let mut objects = self.inner.objects.write().unwrap();
+        let condition_holds = match (&mode, objects.get(key)) {
+            (PutMode::Overwrite, _) => true,
+            (PutMode::Create, existing) => existing.is_none(),
+            (PutMode::Update(version), Some((_, generation))) => {
+                version.0 == generation.to_string()
+            }
+            (PutMode::Update(_), None) => false,
+        };
+        if !condition_holds {
+            return Ok(PutOutcome::ConditionFailed);
+        }
+        let generation = self.inner.generation.fetch_add(1, Ordering::Relaxed) + 1;
+        objects.insert(key.to_string(), (OwnedBytes::new(data), generation));
+        Ok(PutOutcome::Written(ObjectVersion(generation.to_string())))
+    }
+
+    fn create_multipart_upload(&self, key: &str) -> io::Result<Box<dyn MultipartUpload>> {
+        Ok(Box::new(RamMultipartUpload {
+            store: self.inner.clone(),
+            key: key.to_string(),
+            parts: BTreeMap::new(),
+        }))
+    }
+
+    fn delete(&self, key: &str) -> io::Result<bool> {
+        Ok(self.inner.objects.write().unwrap().remove(key).is_some())
+    }
+}
+
+struct RamMultipartUpload {
+    store: Arc<InnerRamObjectStore>,
+    key: String,
+    parts: BTreeMap<usize, Vec<u8>>,
+}
+
+impl MultipartUpload for RamMultipartUpload {
+    fn upload_part(&mut self, part_num: usize, data: Vec<u8>) -> io::Result<()> {
+        self.parts.insert(part_num, data);
+        Ok(())
+    }
+
+    fn complete(self: Box<Self>) -> io::Result<()> {
+        let data: Vec<u8> = self.parts.into_values().flatten().collect();
+        self.store.insert(&self.key, data);
+        Ok(())
+    }
+
+    fn abort(self: Box<Self>) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::path::Path;
+
+    use common::HasLen;
+    use futures::executor::block_on;
+
+    use super::{ObjectStore, ObjectStoreDirectory, RamObjectStore};
+    use crate::collector::Count;
+    use crate::directory::{CachingDirectory, TerminatingWrite};
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, STORED, TEXT};
+    use crate::{Directory, DocAddress, Index, Term};
+
+    #[test]
+    fn test_object_store_directory_multipart_upload() -> crate::Result<()> {
+        let store = RamObjectStore::default();
+        let directory = ObjectStoreDirectory::new(store.clone(), "indexes/test/").part_size(4);
+        let path = Path::new("segment.idx");
+        let mut write = directory.open_write(path)?;
+        assert_eq!(store.keys(), vec!["indexes/test/segment.idx".to_string()]);
+        write.write_all(b"hello, ")?;
+        write.flush()?;
+        // The multipart upload is not completed yet.
+        assert_eq!(directory.open_read(path)?.len(), 0);
+        write.write_all(b"object store")?;
+        write.terminate()?;
+        let file = directory.open_read(path)?;
+        assert_eq!(file.read_bytes()?.as_slice(), b"hello, object store");
+        assert_eq!(file.slice(7..13).read_bytes()?.as_slice(), b"object");
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_store_directory_unterminated_upload() -> crate::Result<()> {
+        let store = RamObjectStore::default();
+        let directory = ObjectStoreDirectory::new(store, "").part_size(2);
+        let path = Path::new("segment.idx");
+        {
+            let mut write = directory.open_write(path)?;
+            write.write_all(b"12345")?;
+        }
+        assert_eq!(directory.open_read(path)?.len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_store_directory_conditional_atomic_write() -> crate::Result<()> {
+        let store = RamObjectStore::default();
+        let directory = ObjectStoreDirectory::new(store.clone(), "index");
+        let other_directory = ObjectStoreDirectory::new(store.clone(), "index");
+        let path = Path::new("meta.json");
+        directory.atomic_write(path, b"1")?;
+        // The other directory did not read the file before writing it.
+        assert!(other_directory.atomic_write(path, b"2").is_err());
+        assert_eq!(other_directory.atomic_read(path)?, b"1");
+        other_directory.atomic_write(path, b"2")?;
+        // The first directory now has a stale version.
+        assert!(directory.atomic_write(path, b"3").is_err());
+        assert_eq!(directory.atomic_read(path)?, b"2");
+        directory.atomic_write(path, b"3")?;
+        assert_eq!(store.get("index/meta.json")?.unwrap().0.as_slice(), b"3");
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_store_directory_doc_async() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT | STORED);
+        let schema = schema_builder.build();
+        let directory = CachingDirectory::new(
+            ObjectStoreDirectory::new(RamObjectStore::default(), "index"),
+            1_000_000,
+        );
+        let index = Index::create(directory, schema, Default::default())?;
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc!(text => "hello"))?;
+            index_writer.add_document(doc!(text => "hello again"))?;
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let doc = block_on(searcher.doc_async(DocAddress::new(0, 1)))?;
+        assert_eq!(
+            doc.get_first(text).and_then(|value| value.as_text()),
+            Some("hello again")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_store_directory_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let store = RamObjectStore::default();
+        let directory = ObjectStoreDirectory::new(store.clone(), "index").part_size(100);
+        let index = Index::create(directory, schema, Default::default())?;
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            for i in 0..100 {
+                index_writer.add_document(doc!(text => format!("hello {i}")))?;
+            }
+            index_writer.commit()?;
+            index_writer.add_document(doc!(text => "hello again"))?;
+            index_writer.commit()?;
+        }
+        let index = Index::open(ObjectStoreDirectory::new(store, "index"))?;
+        let searcher = index.reader()?.searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(text, "hello"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 101);
+        Ok(())
+    }
+}
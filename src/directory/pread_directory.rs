@@ -0,0 +1,314 @@
+use std::fs::File;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::{fmt, io, result};
+
+use common::HasLen;
+
+use crate::directory::error::{
+    DeleteError, LockError, OpenDirectoryError, OpenReadError, OpenWriteError,
+};
+use crate::directory::{
+    BlockCacheStats, CachingDirectory, Directory, DirectoryLock, EvictionPolicy, FileHandle, Lock,
+    MmapDirectory, OwnedBytes, WatchCallback, WatchHandle, WritePtr, DEFAULT_CACHE_BLOCK_SIZE,
+};
+
+/// Default capacity of the page cache of a [`PreadDirectory`]: 256 MiB.
+pub const DEFAULT_PAGE_CACHE_CAPACITY: usize = 256 << 20;
+
+/// Reads the files with positional reads, and delegates everything else to a
+/// [`MmapDirectory`] over the same path, which never maps the files as they are not read
+/// through it.
+#[derive(Clone, Debug)]
+struct PositionalReadDirectory {
+    mmap_directory: MmapDirectory,
+}
+
+impl Directory for PositionalReadDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+        let full_path = self.mmap_directory.resolve_path(path);
+        let file = File::open(&full_path).map_err(|io_error| {
+            if io_error.kind() == io::ErrorKind::NotFound {
+                OpenReadError::FileDoesNotExist(path.to_path_buf())
+            } else {
+                OpenReadError::wrap_io_error(io_error, path.to_path_buf())
+            }
+        })?;
+        let len = file
+            .metadata()
+            .map_err(|io_error| OpenReadError::wrap_io_error(io_error, path.to_path_buf()))?
+            .len() as usize;
+        Ok(Arc::new(PreadFileHandle {
+            path: path.to_path_buf(),
+            file,
+            len,
+        }))
+    }
+
+    fn delete(&self, path: &Path) -> result::Result<(), DeleteError> {
+        self.mmap_directory.delete(path)
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        self.mmap_directory.exists(path)
+    }
+
+    fn open_write(&self, path: &Path) -> result::Result<WritePtr, OpenWriteError> {
+        self.mmap_directory.open_write(path)
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        self.mmap_directory.atomic_read(path)
+    }
+
+    fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.mmap_directory.atomic_write(path, data)
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> result::Result<DirectoryLock, LockError> {
+        self.mmap_directory.acquire_lock(lock)
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        self.mmap_directory.watch(watch_callback)
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        self.mmap_directory.sync_directory()
+    }
+}
+
+struct PreadFileHandle {
+    path: PathBuf,
+    file: File,
+    len: usize,
+}
+
+impl fmt::Debug for PreadFileHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PreadFileHandle({:?})", self.path)
+    }
+}
+
+impl HasLen for PreadFileHandle {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl FileHandle for PreadFileHandle {
+    fn read_bytes(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        if range.end > self.len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "Range {range:?} is out of the bounds of {:?}, of length {}.",
+                    self.path, self.len
+                ),
+            ));
+        }
+        let mut data = vec![0u8; range.len()];
+        read_exact_at(&self.file, &mut data, range.start as u64)?;
+        Ok(OwnedBytes::new(data))
+    }
+}
+
+#[cfg(unix)]
+fn read_exact_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<()> {
+    use std::os::unix::fs::FileExt;
+    file.read_exact_at(buf, offset)
+}
+
+#[cfg(windows)]
+fn read_exact_at(file: &File, mut buf: &mut [u8], mut offset: u64) -> io::Result<()> {
+    use std::os::windows::fs::FileExt;
+    while !buf.is_empty() {
+        match file.seek_read(buf, offset) {
+            Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+            Ok(num_bytes) => {
+                buf = &mut buf[num_bytes..];
+                offset += num_bytes as u64;
+            }
+            Err(io_error) if io_error.kind() == io::ErrorKind::Interrupted => {}
+            Err(io_error) => return Err(io_error),
+        }
+    }
+    Ok(())
+}
+
+/// A Directory storing the files in a directory of the file system, like the
+/// [`MmapDirectory`], but reading them with positional reads (`pread`) instead of mapping them
+/// in memory.
+///
+/// When the memory is scarce, reading a memory mapped file may page fault at any time, which
+/// makes for unpredictable latencies, and cannot be timed. The `PreadDirectory` issues explicit
+/// reads instead, and keeps the blocks it reads in a page cache of a fixed capacity, so that the
+/// memory used by the index is bounded and the slow reads happen in known places.
+///
+/// The files are written, locked and watched as in the [`MmapDirectory`].
+#[derive(Clone)]
+pub struct PreadDirectory {
+    root_path: PathBuf,
+    page_cache: CachingDirectory,
+}
+
+impl fmt::Debug for PreadDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PreadDirectory({:?})", self.root_path)
+    }
+}
+
+impl PreadDirectory {
+    fn new(mmap_directory: MmapDirectory, page_cache_capacity: usize) -> PreadDirectory {
+        let root_path = mmap_directory.resolve_path(Path::new(""));
+        let page_cache = CachingDirectory::with_eviction_policy(
+            PositionalReadDirectory { mmap_directory },
+            page_cache_capacity,
+            DEFAULT_CACHE_BLOCK_SIZE,
+            EvictionPolicy::Lru,
+        );
+        PreadDirectory {
+            root_path,
+            page_cache,
+        }
+    }
+
+    /// Creates a new PreadDirectory in a temporary directory.
+    ///
+    /// This is mostly useful to test the PreadDirectory itself.
+    /// For your unit tests, prefer the RamDirectory.
+    pub fn create_from_tempdir() -> Result<PreadDirectory, OpenDirectoryError> {
+        let mmap_directory = MmapDirectory::create_from_tempdir()?;
+        Ok(PreadDirectory::new(
+            mmap_directory,
+            DEFAULT_PAGE_CACHE_CAPACITY,
+        ))
+    }
+
+    /// Opens a PreadDirectory in a directory, with a page cache of
+    /// [`DEFAULT_PAGE_CACHE_CAPACITY`] bytes.
+    ///
+    /// Returns an error if the `directory_path` does not
+    /// exist or if it is not a directory.
+    pub fn open<P: AsRef<Path>>(directory_path: P) -> Result<PreadDirectory, OpenDirectoryError> {
+        PreadDirectory::open_with_page_cache(directory_path, DEFAULT_PAGE_CACHE_CAPACITY)
+    }
+
+    /// Opens a PreadDirectory in a directory, with a page cache of `page_cache_capacity` bytes.
+    ///
+    /// With a capacity of 0, every read reaches the file system.
+    pub fn open_with_page_cache<P: AsRef<Path>>(
+        directory_path: P,
+        page_cache_capacity: usize,
+    ) -> Result<PreadDirectory, OpenDirectoryError> {
+        let mmap_directory = MmapDirectory::open(directory_path)?;
+        Ok(PreadDirectory::new(mmap_directory, page_cache_capacity))
+    }
+
+    /// Returns the statistics of the page cache.
+    pub fn page_cache_stats(&self) -> BlockCacheStats {
+        self.page_cache.cache_stats()
+    }
+}
+
+impl Directory for PreadDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+        self.page_cache.get_file_handle(path)
+    }
+
+    fn delete(&self, path: &Path) -> result::Result<(), DeleteError> {
+        self.page_cache.delete(path)
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        self.page_cache.exists(path)
+    }
+
+    fn open_write(&self, path: &Path) -> result::Result<WritePtr, OpenWriteError> {
+        self.page_cache.open_write(path)
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        self.page_cache.atomic_read(path)
+    }
+
+    fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.page_cache.atomic_write(path, data)
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> result::Result<DirectoryLock, LockError> {
+        self.page_cache.acquire_lock(lock)
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        self.page_cache.watch(watch_callback)
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        self.page_cache.sync_directory()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::path::Path;
+
+    use common::HasLen;
+
+    use super::PreadDirectory;
+    use crate::collector::Count;
+    use crate::directory::TerminatingWrite;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, TEXT};
+    use crate::{Directory, Index, IndexSettings, Term};
+
+    #[test]
+    fn test_pread_directory_read() -> crate::Result<()> {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let directory = PreadDirectory::open_with_page_cache(tempdir.path(), 1 << 20)?;
+        let path = Path::new("file");
+        let data: Vec<u8> = (0..100_000u32).map(|i| i as u8).collect();
+        let mut write = directory.open_write(path)?;
+        write.write_all(&data)?;
+        write.terminate()?;
+        assert_eq!(std::fs::read(tempdir.path().join(path))?, data);
+
+        let file = directory.open_read(path)?;
+        assert_eq!(file.len(), data.len());
+        assert_eq!(
+            file.read_bytes_slice(70_000..70_010)?.as_slice(),
+            &data[70_000..70_010]
+        );
+        assert_eq!(file.read_bytes()?.as_slice(), &data[..]);
+        let stats = directory.page_cache_stats();
+        assert_eq!((stats.cache_hits, stats.cache_misses), (1, 2));
+        assert!(directory.open_read(Path::new("missing")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pread_directory_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let tempdir = tempfile::TempDir::new().unwrap();
+        {
+            let directory = PreadDirectory::open_with_page_cache(tempdir.path(), 0)?;
+            let index = Index::create(directory, schema.clone(), IndexSettings::default())?;
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc!(text => "hello"))?;
+            index_writer.add_document(doc!(text => "goodbye"))?;
+            index_writer.commit()?;
+        }
+        let index = Index::open(PreadDirectory::open(tempdir.path())?)?;
+        let searcher = index.reader()?.searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(text, "hello"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::fs::{self, File};
 use std::io::{self, BufWriter, Cursor, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
@@ -8,13 +9,17 @@ use common::HasLen;
 use fail::fail_point;
 
 use super::FileHandle;
-use crate::core::META_FILEPATH;
-use crate::directory::error::{DeleteError, OpenReadError, OpenWriteError};
+use crate::core::{MANAGED_FILEPATH, META_FILEPATH};
+use crate::directory::error::{DeleteError, OpenDirectoryError, OpenReadError, OpenWriteError};
 use crate::directory::{
     AntiCallToken, Directory, FileSlice, TerminatingWrite, WatchCallback, WatchCallbackList,
-    WatchHandle, WritePtr,
+    WatchHandle, WritePtr, INDEX_WRITER_LOCK, META_LOCK,
 };
 
+/// Suffix of the temporary files written by [`RamDirectory::persist_to()`] before being
+/// renamed to their final path.
+const PERSIST_TMP_SUFFIX: &str = ".tantivy-tmp";
+
 /// Writer associated with the [`RamDirectory`].
 ///
 /// The Writer just writes a buffer.
@@ -171,6 +176,74 @@ impl RamDirectory {
         }
         Ok(())
     }
+
+    /// Writes a snapshot of all of the files of the [`RamDirectory`] in the directory at the
+    /// given path of the file system, creating it if necessary.
+    ///
+    /// Each file is written to a temporary file, synced and then renamed, and `meta.json`
+    /// and `.managed.json` are written last. An index persisted over a previous snapshot
+    /// is therefore either the old one or the new one, even if the process crashes.
+    /// Files of the target directory which are not in the [`RamDirectory`] are left
+    /// untouched, and the lock files are not persisted.
+    pub fn persist_to(&self, dir: &Path) -> crate::Result<()> {
+        fs::create_dir_all(dir)?;
+        let rlock = self.fs.read().unwrap();
+        let is_last = |path: &Path| path == *META_FILEPATH || path == *MANAGED_FILEPATH;
+        let (last_files, files): (Vec<_>, Vec<_>) = rlock
+            .fs
+            .iter()
+            .filter(|(path, _)| {
+                **path != INDEX_WRITER_LOCK.filepath && **path != META_LOCK.filepath
+            })
+            .partition(|(path, _)| is_last(path));
+        for (path, file) in files.into_iter().chain(last_files) {
+            let full_path = dir.join(path);
+            if let Some(parent) = full_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut tmp_path = full_path.clone().into_os_string();
+            tmp_path.push(PERSIST_TMP_SUFFIX);
+            let tmp_path = PathBuf::from(tmp_path);
+            let mut tmp_file = File::create(&tmp_path)?;
+            tmp_file.write_all(file.read_bytes()?.as_slice())?;
+            tmp_file.sync_data()?;
+            fs::rename(&tmp_path, &full_path)?;
+        }
+        #[cfg(not(windows))]
+        File::open(dir)?.sync_data()?;
+        Ok(())
+    }
+
+    /// Creates a [`RamDirectory`] holding a copy of the files at the root of the directory
+    /// at the given path of the file system, typically written by
+    /// [`RamDirectory::persist_to()`].
+    ///
+    /// The temporary files left over by an interrupted [`RamDirectory::persist_to()`] are
+    /// ignored.
+    pub fn load_from(dir: &Path) -> crate::Result<RamDirectory> {
+        if !dir.exists() {
+            return Err(OpenDirectoryError::DoesNotExist(dir.to_path_buf()).into());
+        }
+        if !dir.is_dir() {
+            return Err(OpenDirectoryError::NotADirectory(dir.to_path_buf()).into());
+        }
+        let mut inner = InnerDirectory::default();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            let file_name = PathBuf::from(entry.file_name());
+            if file_name.to_string_lossy().ends_with(PERSIST_TMP_SUFFIX) {
+                continue;
+            }
+            let data = fs::read(entry.path())?;
+            inner.write(file_name, &data);
+        }
+        Ok(RamDirectory {
+            fs: Arc::new(RwLock::new(inner)),
+        })
+    }
 }
 
 impl Directory for RamDirectory {
@@ -252,7 +325,8 @@ mod tests {
     use std::path::Path;
 
     use super::RamDirectory;
-    use crate::Directory;
+    use crate::schema::{Schema, TEXT};
+    use crate::{Directory, Index, IndexSettings};
 
     #[test]
     fn test_persist() {
@@ -271,6 +345,58 @@ mod tests {
         assert_eq!(directory_copy.atomic_read(path_seq).unwrap(), msg_seq);
     }
 
+    #[test]
+    fn test_persist_to_and_load_from() -> crate::Result<()> {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let snapshot_path = tempdir.path().join("snapshot");
+        let directory = RamDirectory::create();
+        directory.atomic_write(Path::new("atomic"), b"atomic is the way")?;
+        let mut wrt = directory.open_write(Path::new("seq")).unwrap();
+        wrt.write_all(b"sequential is the way")?;
+        wrt.flush()?;
+        directory.persist_to(&snapshot_path)?;
+
+        directory.atomic_write(Path::new("atomic"), b"overwritten")?;
+        directory.delete(Path::new("seq")).unwrap();
+        directory.persist_to(&snapshot_path)?;
+        std::fs::write(snapshot_path.join("seq.tantivy-tmp"), b"partial")?;
+
+        let loaded = RamDirectory::load_from(&snapshot_path)?;
+        assert_eq!(loaded.atomic_read(Path::new("atomic"))?, b"overwritten");
+        // Files deleted from the ram directory are left in the snapshot.
+        assert_eq!(
+            loaded.atomic_read(Path::new("seq"))?,
+            b"sequential is the way"
+        );
+        assert!(!loaded.exists(Path::new("seq.tantivy-tmp"))?);
+        assert!(RamDirectory::load_from(&tempdir.path().join("missing")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_persist_to_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let ram_directory = RamDirectory::create();
+        let index = Index::create(
+            ram_directory.clone(),
+            schema_builder.build(),
+            IndexSettings::default(),
+        )?;
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "hello"))?;
+        index_writer.commit()?;
+        let tempdir = tempfile::TempDir::new().unwrap();
+        ram_directory.persist_to(tempdir.path())?;
+        assert!(!tempdir.path().join(".tantivy-writer.lock").exists());
+        drop(index_writer);
+
+        let loaded_index = Index::open(RamDirectory::load_from(tempdir.path())?)?;
+        let searcher = loaded_index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 1);
+        Ok(())
+    }
+
     #[test]
     fn test_ram_directory_deep_clone() {
         let dir = RamDirectory::default();
@@ -106,6 +106,17 @@ pub enum TantivyError {
     /// e.g. a datastructure is incorrectly inititalized.
     #[error("Internal error: '{0}'")]
     InternalError(String),
+    /// The operation was cancelled before it could complete.
+    #[error("The operation was cancelled")]
+    Cancelled,
+    /// The version of a document differs from the version expected by a conditional update.
+    #[error("Version conflict: expected version {expected}, found version {actual}")]
+    VersionConflict {
+        /// The version expected by the update.
+        expected: u64,
+        /// The current version of the document.
+        actual: u64,
+    },
 }
 
 impl From<io::Error> for TantivyError {
@@ -0,0 +1,213 @@
+use std::fmt::Debug;
+use std::net::Ipv6Addr;
+
+use columnar::{BytesColumn, Column, StrColumn};
+
+use crate::schema::{Document, Facet, Field, FieldType, Value};
+use crate::{DateTime, DocId, SegmentReader, TantivyError};
+
+/// The column of a field, given its type.
+enum FieldColumn {
+    U64(Option<Column<u64>>),
+    I64(Option<Column<i64>>),
+    F64(Option<Column<f64>>),
+    Bool(Option<Column<bool>>),
+    Date(Option<Column<DateTime>>),
+    IpAddr(Option<Column<Ipv6Addr>>),
+    Str(Option<StrColumn>),
+    Facet(Option<StrColumn>),
+    Bytes(Option<BytesColumn>),
+}
+
+/// Rebuilds the documents of a segment from the fast fields, rather than from the doc store.
+///
+/// The columns are opened once, so that fetching many documents does not decompress any
+/// block of the doc store. The values are those of the fast fields: the text values are
+/// normalized by the fast field tokenizer, the dates are truncated to the precision of the
+/// fast field, and the values of a multivalued field are not necessarily in the order in which
+/// they were added.
+pub(crate) struct FastFieldDocReader {
+    field_columns: Vec<(Field, FieldColumn)>,
+}
+
+impl FastFieldDocReader {
+    /// Opens the columns of the given fields.
+    ///
+    /// Returns an error if one of the fields is not a fast field, or is a JSON, dense vector or
+    /// geo point field.
+    pub fn open(segment_reader: &SegmentReader, fields: &[Field]) -> crate::Result<Self> {
+        let schema = segment_reader.schema();
+        let fast_fields = segment_reader.fast_fields();
+        let mut field_columns = Vec::with_capacity(fields.len());
+        for &field in fields {
+            let field_entry = schema.get_field_entry(field);
+            let field_name = field_entry.name();
+            if !field_entry.is_fast() {
+                return Err(TantivyError::SchemaError(format!(
+                    "Field {field_name:?} is not a fast field"
+                )));
+            }
+            let field_column = match field_entry.field_type() {
+                FieldType::U64(_) => FieldColumn::U64(fast_fields.column_opt(field_name)?),
+                FieldType::I64(_) => FieldColumn::I64(fast_fields.column_opt(field_name)?),
+                FieldType::F64(_) => FieldColumn::F64(fast_fields.column_opt(field_name)?),
+                FieldType::Bool(_) => FieldColumn::Bool(fast_fields.column_opt(field_name)?),
+                FieldType::Date(_) => FieldColumn::Date(fast_fields.column_opt(field_name)?),
+                FieldType::IpAddr(_) => FieldColumn::IpAddr(fast_fields.column_opt(field_name)?),
+                FieldType::Str(_) => FieldColumn::Str(fast_fields.str(field_name)?),
+                FieldType::Facet(_) => FieldColumn::Facet(fast_fields.str(field_name)?),
+                FieldType::Bytes(_) => FieldColumn::Bytes(fast_fields.bytes(field_name)?),
+                FieldType::JsonObject(_) | FieldType::DenseVector(_) | FieldType::GeoPoint(_) => {
+                    return Err(TantivyError::SchemaError(format!(
+                        "The documents cannot be rebuilt from the fast field {field_name:?}"
+                    )));
+                }
+            };
+            field_columns.push((field, field_column));
+        }
+        Ok(FastFieldDocReader { field_columns })
+    }
+
+    /// Rebuilds the document `doc_id` from the columns of the fields.
+    pub fn get(&self, doc_id: DocId) -> crate::Result<Document> {
+        let mut document = Document::default();
+        for (field, field_column) in &self.field_columns {
+            let field = *field;
+            match field_column {
+                FieldColumn::U64(column) => add_values(&mut document, field, column, doc_id),
+                FieldColumn::I64(column) => add_values(&mut document, field, column, doc_id),
+                FieldColumn::F64(column) => add_values(&mut document, field, column, doc_id),
+                FieldColumn::Bool(column) => add_values(&mut document, field, column, doc_id),
+                FieldColumn::Date(column) => add_values(&mut document, field, column, doc_id),
+                FieldColumn::IpAddr(column) => add_values(&mut document, field, column, doc_id),
+                FieldColumn::Str(Some(str_column)) => {
+                    for term_ord in str_column.term_ords(doc_id) {
+                        let mut text = String::new();
+                        str_column.ord_to_str(term_ord, &mut text)?;
+                        document.add_text(field, text);
+                    }
+                }
+                FieldColumn::Facet(Some(facet_column)) => {
+                    for term_ord in facet_column.term_ords(doc_id) {
+                        let mut encoded_facet = String::new();
+                        facet_column.ord_to_str(term_ord, &mut encoded_facet)?;
+                        document.add_facet(field, Facet::from_encoded_string(encoded_facet));
+                    }
+                }
+                FieldColumn::Bytes(Some(bytes_column)) => {
+                    for term_ord in bytes_column.term_ords(doc_id) {
+                        let mut bytes = Vec::new();
+                        bytes_column.ord_to_bytes(term_ord, &mut bytes)?;
+                        document.add_bytes(field, bytes);
+                    }
+                }
+                FieldColumn::Str(None) | FieldColumn::Facet(None) | FieldColumn::Bytes(None) => {}
+            }
+        }
+        Ok(document)
+    }
+}
+
+fn add_values<T>(document: &mut Document, field: Field, column: &Option<Column<T>>, doc_id: DocId)
+where
+    T: PartialOrd + Copy + Debug + Send + Sync + 'static + Into<Value>,
+{
+    if let Some(column) = column {
+        for value in column.values_for_doc(doc_id) {
+            document.add_field_value(field, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv6Addr;
+
+    use crate::schema::{Facet, FacetOptions, Schema, FAST, STORED, STRING};
+    use crate::{doc, DateTime, DocAddress, Index};
+
+    #[test]
+    fn test_docs_from_fast_fields() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", FAST);
+        let delta = schema_builder.add_i64_field("delta", FAST);
+        let price = schema_builder.add_f64_field("price", FAST);
+        let available = schema_builder.add_bool_field("available", FAST);
+        let date = schema_builder.add_date_field("date", FAST);
+        let ip = schema_builder.add_ip_addr_field("ip", FAST);
+        let tag = schema_builder.add_text_field("tag", STRING | FAST);
+        let category = schema_builder.add_facet_field("category", FacetOptions::default());
+        let payload = schema_builder.add_bytes_field("payload", FAST);
+        let title = schema_builder.add_text_field("title", STRING | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let ip_addr = Ipv6Addr::from(1u128);
+        index_writer.add_document(doc!(
+            id => 1u64,
+            id => 2u64,
+            delta => -3i64,
+            price => 1.5f64,
+            available => true,
+            date => DateTime::from_timestamp_secs(1_000),
+            ip => ip_addr,
+            tag => "red",
+            category => Facet::from("/shoes/boots"),
+            payload => vec![1u8, 2u8],
+            title => "boots",
+        ))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(id => 3u64, title => "sandals"))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let fields = [
+            id, delta, price, available, date, ip, tag, category, payload,
+        ];
+        let doc_addresses: Vec<DocAddress> = (0..2)
+            .map(|segment_ord| DocAddress::new(segment_ord, 0))
+            .collect();
+        let docs = searcher.docs_from_fast_fields(&doc_addresses, &fields)?;
+        let (boots, sandals) = if docs[0].get_first(id).unwrap().as_u64() == Some(1) {
+            (&docs[0], &docs[1])
+        } else {
+            (&docs[1], &docs[0])
+        };
+        assert_eq!(
+            boots
+                .get_all(id)
+                .flat_map(|value| value.as_u64())
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(boots.get_first(delta).unwrap().as_i64(), Some(-3));
+        assert_eq!(boots.get_first(price).unwrap().as_f64(), Some(1.5));
+        assert_eq!(boots.get_first(available).unwrap().as_bool(), Some(true));
+        assert_eq!(
+            boots.get_first(date).unwrap().as_date(),
+            Some(DateTime::from_timestamp_secs(1_000))
+        );
+        assert_eq!(boots.get_first(ip).unwrap().as_ip_addr(), Some(ip_addr));
+        assert_eq!(boots.get_first(tag).unwrap().as_text(), Some("red"));
+        assert_eq!(
+            boots.get_first(category).unwrap().as_facet(),
+            Some(&Facet::from("/shoes/boots"))
+        );
+        assert_eq!(
+            boots.get_first(payload).unwrap().as_bytes(),
+            Some(&[1u8, 2u8][..])
+        );
+        assert!(boots.get_first(title).is_none());
+        assert_eq!(sandals.len(), 1);
+        assert_eq!(sandals.get_first(id).unwrap().as_u64(), Some(3));
+
+        assert_eq!(
+            &searcher.doc_from_fast_fields(doc_addresses[0], &fields)?,
+            &docs[0]
+        );
+        assert!(searcher
+            .docs_from_fast_fields(&doc_addresses, &[id, title])
+            .is_err());
+        assert!(searcher.docs_from_fast_fields(&[], &[title]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use columnar::StrColumn;
+
+use crate::{SegmentOrdinal, SegmentReader};
+
+/// Maps the term ordinals of the segments of a `str` fast field to global ordinals, shared by
+/// all of the segments of a [`Searcher`](crate::Searcher).
+///
+/// The global ordinals follow the lexicographic order of the terms, so that comparing or
+/// grouping the values of different segments does not require to fetch their terms.
+///
+/// The global ordinals of a searcher are built lazily, and cached until the searcher is dropped.
+/// See [`Searcher::global_ordinals`](crate::Searcher::global_ordinals).
+pub struct GlobalOrdinals {
+    term_bytes: Vec<u8>,
+    term_offsets: Vec<usize>,
+    segment_ord_to_global_ords: Vec<Vec<u64>>,
+}
+
+impl GlobalOrdinals {
+    /// Builds the global ordinals of the `str` fast field, by merging the dictionaries of the
+    /// segments.
+    pub(crate) fn build(
+        segment_readers: &[SegmentReader],
+        field_name: &str,
+    ) -> crate::Result<GlobalOrdinals> {
+        let str_columns: Vec<Option<StrColumn>> = segment_readers
+            .iter()
+            .map(|segment_reader| segment_reader.fast_fields().str(field_name))
+            .collect::<crate::Result<_>>()?;
+        let mut streams = Vec::with_capacity(str_columns.len());
+        let mut segment_ord_to_global_ords = Vec::with_capacity(str_columns.len());
+        for str_column_opt in &str_columns {
+            let Some(str_column) = str_column_opt else {
+                streams.push(None);
+                segment_ord_to_global_ords.push(Vec::new());
+                continue;
+            };
+            streams.push(Some(str_column.dictionary().stream()?));
+            segment_ord_to_global_ords.push(Vec::with_capacity(str_column.num_terms()));
+        }
+        let mut heap: BinaryHeap<Reverse<(Vec<u8>, usize)>> = BinaryHeap::new();
+        for (segment_ord, stream_opt) in streams.iter_mut().enumerate() {
+            if let Some(stream) = stream_opt {
+                if stream.advance() {
+                    heap.push(Reverse((stream.key().to_vec(), segment_ord)));
+                }
+            }
+        }
+        let mut term_bytes = Vec::new();
+        let mut term_offsets = vec![0];
+        while let Some(Reverse((term, segment_ord))) = heap.pop() {
+            let num_terms = term_offsets.len() - 1;
+            if num_terms == 0 || term_bytes[term_offsets[num_terms - 1]..] != term[..] {
+                term_bytes.extend_from_slice(&term);
+                term_offsets.push(term_bytes.len());
+            }
+            let global_ord = (term_offsets.len() - 2) as u64;
+            segment_ord_to_global_ords[segment_ord].push(global_ord);
+            let stream = streams[segment_ord].as_mut().unwrap();
+            if stream.advance() {
+                heap.push(Reverse((stream.key().to_vec(), segment_ord)));
+            }
+        }
+        Ok(GlobalOrdinals {
+            term_bytes,
+            term_offsets,
+            segment_ord_to_global_ords,
+        })
+    }
+
+    /// Returns the number of distinct terms across the segments.
+    pub fn num_terms(&self) -> usize {
+        self.term_offsets.len() - 1
+    }
+
+    /// Returns the global ordinal of a term ordinal of a segment.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the term ordinal does not belong to the column of the segment.
+    pub fn global_ord(&self, segment_ord: SegmentOrdinal, term_ord: u64) -> u64 {
+        self.segment_ord_to_global_ords[segment_ord as usize][term_ord as usize]
+    }
+
+    /// Returns the global ordinals of the term ordinals of a segment, indexed by term ordinal.
+    ///
+    /// The slice is empty if the segment does not have the column.
+    pub fn segment_global_ords(&self, segment_ord: SegmentOrdinal) -> &[u64] {
+        &self.segment_ord_to_global_ords[segment_ord as usize]
+    }
+
+    /// Returns the bytes of the term of a global ordinal.
+    pub fn term(&self, global_ord: u64) -> &[u8] {
+        let global_ord = global_ord as usize;
+        &self.term_bytes[self.term_offsets[global_ord]..self.term_offsets[global_ord + 1]]
+    }
+
+    /// Returns the term of a global ordinal as a string, or `None` if it is not valid UTF-8.
+    pub fn term_str(&self, global_ord: u64) -> Option<&str> {
+        std::str::from_utf8(self.term(global_ord)).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::{Schema, FAST, STRING};
+    use crate::{doc, Index};
+
+    #[test]
+    fn test_global_ordinals() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let color = schema_builder.add_text_field("color", STRING | FAST);
+        let other = schema_builder.add_text_field("other", STRING | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(color => "red", color => "blue"))?;
+        index_writer.add_document(doc!(color => "green"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(other => "x"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(color => "blue", color => "yellow"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+
+        let global_ordinals = searcher.global_ordinals("color")?;
+        let terms: Vec<&str> = (0..global_ordinals.num_terms() as u64)
+            .map(|global_ord| global_ordinals.term_str(global_ord).unwrap())
+            .collect();
+        assert_eq!(terms, vec!["blue", "green", "red", "yellow"]);
+        for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+            let segment_ord = segment_ord as u32;
+            let Some(str_column) = segment_reader.fast_fields().str("color")? else {
+                assert!(global_ordinals.segment_global_ords(segment_ord).is_empty());
+                continue;
+            };
+            let mut term = Vec::new();
+            for term_ord in 0..str_column.num_terms() as u64 {
+                str_column.ord_to_bytes(term_ord, &mut term)?;
+                let global_ord = global_ordinals.global_ord(segment_ord, term_ord);
+                assert_eq!(global_ordinals.term(global_ord), &term[..]);
+            }
+        }
+        // The global ordinals are cached by the searcher.
+        assert!(std::sync::Arc::ptr_eq(
+            &global_ordinals,
+            &searcher.global_ordinals("color")?
+        ));
+        assert_eq!(searcher.global_ordinals("missing")?.num_terms(), 0);
+        Ok(())
+    }
+}
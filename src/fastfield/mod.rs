@@ -23,17 +23,25 @@ pub use columnar::Column;
 use columnar::MonotonicallyMappableToU64;
 
 pub use self::alive_bitset::{intersect_alive_bitsets, write_alive_bitset, AliveBitSet};
+pub(crate) use self::doc_reader::FastFieldDocReader;
 pub use self::error::{FastFieldNotAvailableError, Result};
 pub use self::facet_reader::FacetReader;
+pub use self::global_ordinals::GlobalOrdinals;
 pub use self::readers::FastFieldReaders;
+#[cfg(feature = "lucene")]
+pub(crate) use self::runtime_field::ComputedColumnValues;
+pub use self::runtime_field::RuntimeField;
 pub use self::writer::FastFieldsWriter;
 use crate::schema::Type;
 use crate::DateTime;
 
 mod alive_bitset;
+mod doc_reader;
 mod error;
 mod facet_reader;
+mod global_ordinals;
 mod readers;
+mod runtime_field;
 mod writer;
 
 /// Trait for types that are allowed for fast fields:
@@ -8,8 +8,10 @@ use columnar::{
 };
 use common::ByteCount;
 
+use crate::codec::FastFieldsFormat;
 use crate::core::json_utils::encode_column_name;
 use crate::directory::FileSlice;
+use crate::fastfield::RuntimeField;
 use crate::schema::{Field, FieldEntry, FieldType, Schema};
 use crate::space_usage::{FieldUsage, PerFieldSpaceUsage};
 use crate::TantivyError;
@@ -22,12 +24,61 @@ use crate::TantivyError;
 pub struct FastFieldReaders {
     columnar: Arc<ColumnarReader>,
     schema: Schema,
+    runtime_fields: Vec<RuntimeField>,
+    // The fields whose columns can be read, or `None` if all of them can.
+    allowed_fields: Option<Arc<Vec<Field>>>,
 }
 
 impl FastFieldReaders {
+    #[cfg(test)]
     pub(crate) fn open(fast_field_file: FileSlice, schema: Schema) -> io::Result<FastFieldReaders> {
-        let columnar = Arc::new(ColumnarReader::open(fast_field_file)?);
-        Ok(FastFieldReaders { columnar, schema })
+        Self::open_with_format(
+            fast_field_file,
+            schema,
+            &crate::codec::DefaultFastFieldsFormat,
+        )
+    }
+
+    /// Opens the fast fields written with the given format.
+    pub(crate) fn open_with_format(
+        fast_field_file: FileSlice,
+        schema: Schema,
+        fast_fields_format: &dyn FastFieldsFormat,
+    ) -> io::Result<FastFieldReaders> {
+        let columnar = Arc::new(fast_fields_format.open(fast_field_file)?);
+        Ok(FastFieldReaders {
+            columnar,
+            schema,
+            runtime_fields: Vec::new(),
+            allowed_fields: None,
+        })
+    }
+
+    /// Makes the given runtime fields available as fast fields.
+    pub(crate) fn with_runtime_fields(mut self, runtime_fields: Vec<RuntimeField>) -> Self {
+        self.runtime_fields = runtime_fields;
+        self
+    }
+
+    /// Hides the columns of the fields outside of `allowed_fields`, as if they were not fast.
+    pub(crate) fn with_allowed_fields(mut self, allowed_fields: Arc<Vec<Field>>) -> Self {
+        self.allowed_fields = Some(allowed_fields);
+        self
+    }
+
+    /// Returns true if the columns of the field are hidden.
+    pub(crate) fn is_hidden(&self, field: Field) -> bool {
+        match &self.allowed_fields {
+            Some(allowed_fields) => !allowed_fields.contains(&field),
+            None => false,
+        }
+    }
+
+    /// Returns the runtime field with the given name, if any.
+    pub fn runtime_field(&self, field_name: &str) -> Option<&RuntimeField> {
+        self.runtime_fields
+            .iter()
+            .find(|runtime_field| runtime_field.name() == field_name)
     }
 
     fn resolve_field(&self, column_name: &str) -> crate::Result<Option<String>> {
@@ -91,6 +142,9 @@ impl FastFieldReaders {
         else{
             return Ok(None);
         };
+        if self.is_hidden(field) {
+            return Ok(None);
+        }
         let field_entry: &FieldEntry = self.schema.get_field_entry(field);
         if !field_entry.is_fast() {
             return Err(TantivyError::InvalidArgument(format!(
@@ -120,6 +174,12 @@ impl FastFieldReaders {
         T: HasAssociatedColumnType,
         DynamicColumn: Into<Option<Column<T>>>,
     {
+        if let Some(runtime_field) = self.runtime_field(field_name) {
+            if T::column_type() != ColumnType::F64 {
+                return Ok(None);
+            }
+            return Ok(DynamicColumn::F64(runtime_field.open(self)?).into());
+        }
         let Some(dynamic_column_handle) = self.dynamic_column_handle(field_name, T::column_type())?
         else {
             return Ok(None);
@@ -255,6 +315,18 @@ impl FastFieldReaders {
         &self,
         type_white_list_opt: Option<&[ColumnType]>,
         field_name: &str,
+    ) -> crate::Result<Option<(Column<u64>, ColumnType)>> {
+        if let Some(runtime_field) = self.runtime_field(field_name) {
+            return self.runtime_field_u64_lenient(runtime_field, type_white_list_opt);
+        }
+        self.columnar_u64_lenient_for_type(type_white_list_opt, field_name)
+    }
+
+    /// Same as `u64_lenient_for_type`, ignoring runtime fields.
+    pub(crate) fn columnar_u64_lenient_for_type(
+        &self,
+        type_white_list_opt: Option<&[ColumnType]>,
+        field_name: &str,
     ) -> crate::Result<Option<(Column<u64>, ColumnType)>> {
         let Some(resolved_field_name) = self.resolve_field(field_name)? else {
             return Ok(None);
@@ -284,6 +356,11 @@ impl FastFieldReaders {
         field_name: &str,
     ) -> crate::Result<Vec<(Column<u64>, ColumnType)>> {
         let mut columns_and_types = Vec::new();
+        if let Some(runtime_field) = self.runtime_field(field_name) {
+            columns_and_types
+                .extend(self.runtime_field_u64_lenient(runtime_field, type_white_list_opt)?);
+            return Ok(columns_and_types);
+        }
         let Some(resolved_field_name) = self.resolve_field(field_name)? else {
             return Ok(columns_and_types);
         };
@@ -300,6 +377,20 @@ impl FastFieldReaders {
         Ok(columns_and_types)
     }
 
+    fn runtime_field_u64_lenient(
+        &self,
+        runtime_field: &RuntimeField,
+        type_white_list_opt: Option<&[ColumnType]>,
+    ) -> crate::Result<Option<(Column<u64>, ColumnType)>> {
+        if let Some(type_white_list) = type_white_list_opt {
+            if !type_white_list.contains(&ColumnType::F64) {
+                return Ok(None);
+            }
+        }
+        let column = runtime_field.open_u64_lenient(self)?;
+        Ok(Some((column, ColumnType::F64)))
+    }
+
     /// Returns the `u64` column used to represent any `u64`-mapped typed (i64, u64, f64, DateTime).
     ///
     /// Returns Ok(None) for empty columns
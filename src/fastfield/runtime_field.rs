@@ -0,0 +1,259 @@
+use std::fmt;
+use std::sync::Arc;
+
+use columnar::{
+    Column, ColumnIndex, ColumnType, ColumnValues, MonotonicallyMappableToU64, OptionalIndex,
+};
+use tantivy_bitpacker::minmax;
+
+use crate::fastfield::FastFieldReaders;
+use crate::DocId;
+
+/// Column types that can be used as the input of a runtime field.
+const RUNTIME_FIELD_INPUT_TYPES: &[ColumnType] = &[
+    ColumnType::U64,
+    ColumnType::I64,
+    ColumnType::F64,
+    ColumnType::Bool,
+];
+
+type ComputeFn = dyn Fn(&[f64]) -> f64 + Send + Sync;
+
+/// A runtime field is a virtual `f64` fast field, computed at query time from
+/// the fast fields of the documents.
+///
+/// Once registered on the [`Index`](crate::Index), a runtime field can be accessed
+/// using [`FastFieldReaders::f64`], sorted on using
+/// [`TopDocs::order_by_fast_field`](crate::collector::TopDocs::order_by_fast_field), and
+/// used in aggregations just like a regular fast field.
+///
+/// A document only has a value for a runtime field if it has a value for all of
+/// the input fields. For multivalued input fields, the first value is used.
+///
+/// The values are computed for all of the documents of a segment, every time the
+/// column of the runtime field is opened for that segment.
+///
+/// ```rust
+/// use tantivy::fastfield::RuntimeField;
+///
+/// let rate = 0.92;
+/// let price_eur = RuntimeField::new("price_eur", &["price_usd"], move |inputs| inputs[0] * rate);
+/// assert_eq!(price_eur.name(), "price_eur");
+/// ```
+#[derive(Clone)]
+pub struct RuntimeField {
+    name: String,
+    input_fields: Vec<String>,
+    compute: Arc<ComputeFn>,
+}
+
+impl fmt::Debug for RuntimeField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RuntimeField")
+            .field("name", &self.name)
+            .field("input_fields", &self.input_fields)
+            .finish()
+    }
+}
+
+impl RuntimeField {
+    /// Creates a new runtime field.
+    ///
+    /// `compute` is called with the values of the `input_fields`, in the same order,
+    /// converted to `f64`. Input fields need to be numerical or bool fast fields.
+    pub fn new<F>(name: &str, input_fields: &[&str], compute: F) -> RuntimeField
+    where F: Fn(&[f64]) -> f64 + Send + Sync + 'static {
+        RuntimeField {
+            name: name.to_string(),
+            input_fields: input_fields
+                .iter()
+                .map(|input_field| input_field.to_string())
+                .collect(),
+            compute: Arc::new(compute),
+        }
+    }
+
+    /// Returns the name of the runtime field.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the names of the fast fields the runtime field is computed from.
+    pub fn input_fields(&self) -> &[String] {
+        &self.input_fields
+    }
+
+    /// Computes the column of the runtime field for a segment.
+    pub(crate) fn open(&self, fast_field_readers: &FastFieldReaders) -> crate::Result<Column<f64>> {
+        let (index, values) = self.compute_values(fast_field_readers)?;
+        Ok(Column {
+            index,
+            values: Arc::new(ComputedColumnValues::from(values)),
+        })
+    }
+
+    /// Computes the column of the runtime field for a segment, mapped to `u64`.
+    pub(crate) fn open_u64_lenient(
+        &self,
+        fast_field_readers: &FastFieldReaders,
+    ) -> crate::Result<Column<u64>> {
+        let (index, values) = self.compute_values(fast_field_readers)?;
+        let values: Vec<u64> = values.into_iter().map(f64::to_u64).collect();
+        Ok(Column {
+            index,
+            values: Arc::new(ComputedColumnValues::from(values)),
+        })
+    }
+
+    fn compute_values(
+        &self,
+        fast_field_readers: &FastFieldReaders,
+    ) -> crate::Result<(ColumnIndex, Vec<f64>)> {
+        let num_docs = fast_field_readers.columnar().num_rows();
+        let mut input_columns = Vec::with_capacity(self.input_fields.len());
+        for input_field in &self.input_fields {
+            let Some(input_column) = fast_field_readers
+                .columnar_u64_lenient_for_type(Some(RUNTIME_FIELD_INPUT_TYPES), input_field)?
+            else {
+                return Ok((ColumnIndex::Empty { num_docs }, Vec::new()));
+            };
+            input_columns.push(input_column);
+        }
+        let mut doc_ids: Vec<DocId> = Vec::new();
+        let mut values: Vec<f64> = Vec::new();
+        let mut inputs: Vec<f64> = vec![0.0; input_columns.len()];
+        'docs: for doc_id in 0..num_docs {
+            for (input, (input_column, column_type)) in inputs.iter_mut().zip(&input_columns) {
+                let Some(input_val) = input_column.first(doc_id) else {
+                    continue 'docs;
+                };
+                *input = to_f64(input_val, *column_type);
+            }
+            doc_ids.push(doc_id);
+            values.push((self.compute)(&inputs));
+        }
+        let index = if doc_ids.len() == num_docs as usize {
+            ColumnIndex::Full
+        } else {
+            ColumnIndex::Optional(OptionalIndex::for_row_ids(num_docs, &doc_ids))
+        };
+        Ok((index, values))
+    }
+}
+
+fn to_f64(val: u64, column_type: ColumnType) -> f64 {
+    match column_type {
+        ColumnType::I64 => i64::from_u64(val) as f64,
+        ColumnType::F64 => f64::from_u64(val),
+        _ => val as f64,
+    }
+}
+
+/// Column values held in memory, as computed for a runtime field.
+pub(crate) struct ComputedColumnValues<T> {
+    values: Vec<T>,
+    min_value: T,
+    max_value: T,
+}
+
+impl<T: Copy + PartialOrd + Default> From<Vec<T>> for ComputedColumnValues<T> {
+    fn from(values: Vec<T>) -> Self {
+        let (min_value, max_value) = minmax(values.iter().copied()).unwrap_or_default();
+        ComputedColumnValues {
+            values,
+            min_value,
+            max_value,
+        }
+    }
+}
+
+impl<T: Copy + PartialOrd + Send + Sync + fmt::Debug> ColumnValues<T> for ComputedColumnValues<T> {
+    fn get_val(&self, idx: u32) -> T {
+        self.values[idx as usize]
+    }
+
+    fn min_value(&self) -> T {
+        self.min_value
+    }
+
+    fn max_value(&self) -> T {
+        self.max_value
+    }
+
+    fn num_vals(&self) -> u32 {
+        self.values.len() as u32
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = T> + '_> {
+        Box::new(self.values.iter().copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::RuntimeField;
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::agg_result::AggregationResults;
+    use crate::aggregation::AggregationCollector;
+    use crate::collector::TopDocs;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, FAST};
+    use crate::Index;
+
+    #[test]
+    fn test_runtime_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let price = schema_builder.add_f64_field("price", FAST);
+        let quantity = schema_builder.add_u64_field("quantity", FAST);
+        let mut index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc!(price=>10.0f64, quantity=>2u64))?;
+            index_writer.add_document(doc!(price=>20.0f64, quantity=>1u64))?;
+            index_writer.add_document(doc!(price=>5.0f64))?;
+            index_writer.add_document(doc!(price=>1.5f64, quantity=>4u64))?;
+            index_writer.commit()?;
+        }
+        index.register_runtime_field(RuntimeField::new(
+            "total",
+            &["price", "quantity"],
+            |inputs| inputs[0] * inputs[1],
+        ))?;
+        assert!(index
+            .register_runtime_field(RuntimeField::new("price", &[], |_| 0.0))
+            .is_err());
+        let searcher = index.reader()?.searcher();
+
+        let total_column = searcher.segment_reader(0).fast_fields().f64("total")?;
+        let totals: Vec<Option<f64>> = (0..4).map(|doc| total_column.first(doc)).collect();
+        assert_eq!(totals, vec![Some(20.0), Some(20.0), None, Some(6.0)]);
+
+        let top_docs = searcher.search(
+            &AllQuery,
+            &TopDocs::with_limit(2).order_by_fast_field::<f64>("total"),
+        )?;
+        assert_eq!(top_docs[0].0, 20.0);
+        assert_eq!(top_docs[1].0, 20.0);
+        assert!(searcher
+            .search(
+                &AllQuery,
+                &TopDocs::with_limit(2).order_by_fast_field::<u64>("total"),
+            )
+            .is_err());
+
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "total_stats": { "stats": { "field": "total" } }
+        }))
+        .unwrap();
+        let collector = AggregationCollector::from_aggs(agg_req, Default::default());
+        let agg_res: AggregationResults = searcher.search(&AllQuery, &collector)?;
+        let res: serde_json::Value = serde_json::to_value(agg_res)?;
+        assert_eq!(
+            res["total_stats"],
+            json!({"avg": 15.333333333333334, "count": 3, "max": 20.0, "min": 6.0, "sum": 46.0})
+        );
+        Ok(())
+    }
+}
@@ -4,11 +4,16 @@ use columnar::{ColumnarWriter, NumericalValue};
 use common::replace_in_place;
 use tokenizer_api::Token;
 
+use crate::codec::{DefaultFastFieldsFormat, FastFieldsFormat};
+use crate::core::json_utils::{infer_type_from_str, TextOrDateTime};
 use crate::indexer::doc_id_mapping::DocIdMapping;
 use crate::schema::term::{JSON_PATH_SEGMENT_SEP, JSON_PATH_SEGMENT_SEP_STR};
-use crate::schema::{value_type_to_column_type, Document, FieldType, Schema, Type, Value};
+use crate::schema::{
+    dense_vector_to_bytes, value_type_to_column_type, Document, FieldType, JsonObjectOptions,
+    JsonPathMapping, JsonPathTemplate, Schema, Type, Value,
+};
 use crate::tokenizer::{TextAnalyzer, TokenizerManager};
-use crate::{DateTimePrecision, DocId, TantivyError};
+use crate::{DateTime, DateTimePrecision, DocId, TantivyError};
 
 /// Only index JSON down to a depth of 20.
 /// This is mostly to guard us from a stack overflow triggered by malicious input.
@@ -20,10 +25,12 @@ pub struct FastFieldsWriter {
     fast_field_names: Vec<Option<String>>, //< TODO see if we can hash the field name hash too.
     per_field_tokenizer: Vec<Option<TextAnalyzer>>,
     date_precisions: Vec<DateTimePrecision>,
-    expand_dots: Vec<bool>,
+    json_options: Vec<Option<JsonObjectOptions>>,
     num_docs: DocId,
     // Buffer that we recycle to avoid allocation.
     json_path_buffer: String,
+    // Buffer that we recycle to avoid allocation.
+    bytes_buffer: Vec<u8>,
 }
 
 impl FastFieldsWriter {
@@ -45,7 +52,7 @@ impl FastFieldsWriter {
             std::iter::repeat_with(DateTimePrecision::default)
                 .take(schema.num_fields())
                 .collect();
-        let mut expand_dots = vec![false; schema.num_fields()];
+        let mut json_options: Vec<Option<JsonObjectOptions>> = vec![None; schema.num_fields()];
         let mut per_field_tokenizer: Vec<Option<TextAnalyzer>> = vec![None; schema.num_fields()];
         // TODO see other types
         for (field_id, field_entry) in schema.fields() {
@@ -67,8 +74,7 @@ impl FastFieldsWriter {
                     per_field_tokenizer[field_id.field_id() as usize] = Some(text_analyzer);
                 }
 
-                expand_dots[field_id.field_id() as usize] =
-                    json_object_options.is_expand_dots_enabled();
+                json_options[field_id.field_id() as usize] = Some(json_object_options.clone());
             }
             if let FieldType::Str(text_options) = field_entry.field_type() {
                 if let Some(tokenizer_name) = text_options.get_fast_field_tokenizer_name() {
@@ -96,8 +102,9 @@ impl FastFieldsWriter {
             per_field_tokenizer,
             num_docs: 0u32,
             date_precisions,
-            expand_dots,
+            json_options,
             json_path_buffer: String::new(),
+            bytes_buffer: Vec::new(),
         })
     }
 
@@ -197,7 +204,11 @@ impl FastFieldsWriter {
                         );
                     }
                     Value::JsonObject(json_obj) => {
-                        let expand_dots = self.expand_dots[field_value.field().field_id() as usize];
+                        let Some(json_options) =
+                            &self.json_options[field_value.field().field_id() as usize]
+                        else {
+                            continue;
+                        };
                         self.json_path_buffer.clear();
                         self.json_path_buffer.push_str(field_name);
 
@@ -207,7 +218,7 @@ impl FastFieldsWriter {
                         record_json_obj_to_columnar_writer(
                             doc_id,
                             json_obj,
-                            expand_dots,
+                            json_options,
                             JSON_DEPTH_LIMIT,
                             &mut self.json_path_buffer,
                             &mut self.columnar_writer,
@@ -218,6 +229,22 @@ impl FastFieldsWriter {
                         self.columnar_writer
                             .record_ip_addr(doc_id, field_name.as_str(), *ip_addr);
                     }
+                    Value::DenseVector(vector) => {
+                        self.bytes_buffer.clear();
+                        dense_vector_to_bytes(vector, &mut self.bytes_buffer);
+                        self.columnar_writer.record_bytes(
+                            doc_id,
+                            field_name.as_str(),
+                            &self.bytes_buffer,
+                        );
+                    }
+                    Value::GeoPoint(geo_point) => {
+                        self.columnar_writer.record_numerical(
+                            doc_id,
+                            field_name.as_str(),
+                            NumericalValue::from(geo_point.to_u64()),
+                        );
+                    }
                 }
             }
         }
@@ -228,15 +255,29 @@ impl FastFieldsWriter {
     /// Serializes all of the `FastFieldWriter`s by pushing them in
     /// order to the fast field serializer.
     pub fn serialize(
+        self,
+        wrt: &mut dyn io::Write,
+        doc_id_map_opt: Option<&DocIdMapping>,
+    ) -> io::Result<()> {
+        self.serialize_with_format(wrt, doc_id_map_opt, &DefaultFastFieldsFormat)
+    }
+
+    /// Serializes the fast fields like [`FastFieldsWriter::serialize()`], with the given format.
+    pub(crate) fn serialize_with_format(
         mut self,
         wrt: &mut dyn io::Write,
         doc_id_map_opt: Option<&DocIdMapping>,
+        fast_fields_format: &dyn FastFieldsFormat,
     ) -> io::Result<()> {
         let num_docs = self.num_docs;
         let old_to_new_row_ids =
             doc_id_map_opt.map(|doc_id_mapping| doc_id_mapping.old_to_new_ids());
-        self.columnar_writer
-            .serialize(num_docs, old_to_new_row_ids, wrt)?;
+        fast_fields_format.serialize(
+            &mut self.columnar_writer,
+            num_docs,
+            old_to_new_row_ids,
+            wrt,
+        )?;
         Ok(())
     }
 }
@@ -256,10 +297,19 @@ fn columnar_numerical_value(json_number: &serde_json::Number) -> Option<Numerica
     None
 }
 
+/// Returns the json path of a column, without the name of the field it belongs to.
+fn strip_field_name(column_path: &str) -> &[u8] {
+    column_path
+        .split_once(JSON_PATH_SEGMENT_SEP_STR)
+        .map(|(_field_name, json_path)| json_path)
+        .unwrap_or_default()
+        .as_bytes()
+}
+
 fn record_json_obj_to_columnar_writer(
     doc: DocId,
     json_obj: &serde_json::Map<String, serde_json::Value>,
-    expand_dots: bool,
+    json_options: &JsonObjectOptions,
     remaining_depth_limit: usize,
     json_path_buffer: &mut String,
     columnar_writer: &mut columnar::ColumnarWriter,
@@ -271,7 +321,7 @@ fn record_json_obj_to_columnar_writer(
             json_path_buffer.push_str(JSON_PATH_SEGMENT_SEP_STR);
         }
         json_path_buffer.push_str(key);
-        if expand_dots {
+        if json_options.is_expand_dots_enabled() {
             // This might include the separation byte, which is ok because it is not a dot.
             let appended_segment = &mut json_path_buffer[len_path..];
             // The unsafe below is safe as long as b'.' and JSON_PATH_SEGMENT_SEP are
@@ -284,7 +334,7 @@ fn record_json_obj_to_columnar_writer(
         record_json_value_to_columnar_writer(
             doc,
             child,
-            expand_dots,
+            json_options,
             remaining_depth_limit,
             json_path_buffer,
             columnar_writer,
@@ -298,7 +348,7 @@ fn record_json_obj_to_columnar_writer(
 fn record_json_value_to_columnar_writer(
     doc: DocId,
     json_val: &serde_json::Value,
-    expand_dots: bool,
+    json_options: &JsonObjectOptions,
     mut remaining_depth_limit: usize,
     json_path_writer: &mut String,
     columnar_writer: &mut columnar::ColumnarWriter,
@@ -308,6 +358,20 @@ fn record_json_value_to_columnar_writer(
         return;
     }
     remaining_depth_limit -= 1;
+    let mapping = if json_val.is_array() || json_val.is_object() {
+        // Templates apply to the leaves of the json object.
+        JsonPathMapping::Dynamic
+    } else {
+        let template = json_options.template_for_path(strip_field_name(json_path_writer));
+        let is_fast = template
+            .and_then(JsonPathTemplate::fast)
+            .unwrap_or_else(|| json_options.is_fast_by_default());
+        let mapping = template.map(JsonPathTemplate::mapping).unwrap_or_default();
+        if !is_fast || mapping == JsonPathMapping::Ignore {
+            return;
+        }
+        mapping
+    };
     match json_val {
         serde_json::Value::Null => {
             // TODO handle null
@@ -320,22 +384,36 @@ fn record_json_value_to_columnar_writer(
                 columnar_writer.record_numerical(doc, json_path_writer.as_str(), numerical_value);
             }
         }
-        serde_json::Value::String(text) => {
-            if let Some(text_analyzer) = tokenizer {
-                let mut token_stream = text_analyzer.token_stream(text);
-                token_stream.process(&mut |token| {
-                    columnar_writer.record_str(doc, json_path_writer.as_str(), &token.text);
-                })
-            } else {
+        serde_json::Value::String(text) => match mapping {
+            JsonPathMapping::Date => {
+                if let Some(TextOrDateTime::DateTime(dt)) = infer_type_from_str(text, mapping) {
+                    columnar_writer.record_datetime(
+                        doc,
+                        json_path_writer.as_str(),
+                        DateTime::from_utc(dt),
+                    );
+                }
+            }
+            JsonPathMapping::Raw => {
                 columnar_writer.record_str(doc, json_path_writer.as_str(), text);
             }
-        }
+            _ => {
+                if let Some(text_analyzer) = tokenizer {
+                    let mut token_stream = text_analyzer.token_stream(text);
+                    token_stream.process(&mut |token| {
+                        columnar_writer.record_str(doc, json_path_writer.as_str(), &token.text);
+                    })
+                } else {
+                    columnar_writer.record_str(doc, json_path_writer.as_str(), text);
+                }
+            }
+        },
         serde_json::Value::Array(arr) => {
             for el in arr {
                 record_json_value_to_columnar_writer(
                     doc,
                     el,
-                    expand_dots,
+                    json_options,
                     remaining_depth_limit,
                     json_path_writer,
                     columnar_writer,
@@ -347,7 +425,7 @@ fn record_json_value_to_columnar_writer(
             record_json_obj_to_columnar_writer(
                 doc,
                 json_obj,
-                expand_dots,
+                json_options,
                 remaining_depth_limit,
                 json_path_writer,
                 columnar_writer,
@@ -363,6 +441,7 @@ mod tests {
 
     use super::record_json_value_to_columnar_writer;
     use crate::fastfield::writer::JSON_DEPTH_LIMIT;
+    use crate::schema::{JsonObjectOptions, FAST};
     use crate::DocId;
 
     fn test_columnar_from_jsons_aux(
@@ -371,11 +450,15 @@ mod tests {
     ) -> ColumnarReader {
         let mut columnar_writer = ColumnarWriter::default();
         let mut json_path = String::new();
+        let mut json_options = JsonObjectOptions::from(FAST);
+        if expand_dots {
+            json_options = json_options.set_expand_dots_enabled();
+        }
         for (doc, json_doc) in json_docs.iter().enumerate() {
             record_json_value_to_columnar_writer(
                 doc as u32,
                 json_doc,
-                expand_dots,
+                &json_options,
                 JSON_DEPTH_LIMIT,
                 &mut json_path,
                 &mut columnar_writer,
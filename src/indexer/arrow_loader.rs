@@ -0,0 +1,276 @@
+use crate::collector::{
+    ArrowColumn, ArrowRecordBatch, ArrowValues, DOC_ID_COLUMN, SCORE_COLUMN, SEGMENT_ORD_COLUMN,
+};
+use crate::schema::{Field, Schema, Type};
+use crate::{DateTime, Document, TantivyError};
+
+fn type_mismatch(column_name: &str, values: &ArrowValues, value_type: Type) -> TantivyError {
+    let data_type = match values {
+        ArrowValues::UInt32(_) => "UInt32",
+        ArrowValues::UInt64(_) => "UInt64",
+        ArrowValues::Int64(_) => "Int64",
+        ArrowValues::Float32(_) => "Float32",
+        ArrowValues::Float64(_) => "Float64",
+        ArrowValues::Boolean(_) => "Boolean",
+        ArrowValues::TimestampNanosecond(_) => "Timestamp",
+        ArrowValues::LargeUtf8 { .. } => "LargeUtf8",
+        ArrowValues::LargeBinary { .. } => "LargeBinary",
+    };
+    TantivyError::InvalidArgument(format!(
+        "The {data_type} column {column_name:?} cannot be loaded into a field of type \
+         {value_type:?}"
+    ))
+}
+
+fn out_of_range(column_name: &str, value: impl std::fmt::Debug, value_type: Type) -> TantivyError {
+    TantivyError::InvalidArgument(format!(
+        "The value {value:?} of the column {column_name:?} does not fit in a field of type \
+         {value_type:?}"
+    ))
+}
+
+/// Adds the values of a column to the documents, one row per document.
+fn load_column(
+    column_name: &str,
+    column: &ArrowColumn,
+    field: Field,
+    value_type: Type,
+    docs: &mut [Document],
+) -> crate::Result<()> {
+    let rows = (0..column.len()).filter(|&row| column.is_valid(row));
+    match (column.values(), value_type) {
+        (ArrowValues::UInt32(values), Type::U64) => {
+            for row in rows {
+                docs[row].add_u64(field, u64::from(values[row]));
+            }
+        }
+        (ArrowValues::UInt32(values), Type::I64) => {
+            for row in rows {
+                docs[row].add_i64(field, i64::from(values[row]));
+            }
+        }
+        (ArrowValues::UInt32(values), Type::F64) => {
+            for row in rows {
+                docs[row].add_f64(field, f64::from(values[row]));
+            }
+        }
+        (ArrowValues::UInt64(values), Type::U64) => {
+            for row in rows {
+                docs[row].add_u64(field, values[row]);
+            }
+        }
+        (ArrowValues::UInt64(values), Type::I64) => {
+            for row in rows {
+                let value = i64::try_from(values[row])
+                    .map_err(|_| out_of_range(column_name, values[row], value_type))?;
+                docs[row].add_i64(field, value);
+            }
+        }
+        (ArrowValues::Int64(values), Type::I64) => {
+            for row in rows {
+                docs[row].add_i64(field, values[row]);
+            }
+        }
+        (ArrowValues::Int64(values), Type::U64) => {
+            for row in rows {
+                let value = u64::try_from(values[row])
+                    .map_err(|_| out_of_range(column_name, values[row], value_type))?;
+                docs[row].add_u64(field, value);
+            }
+        }
+        (ArrowValues::Float32(values), Type::F64) => {
+            for row in rows {
+                docs[row].add_f64(field, f64::from(values[row]));
+            }
+        }
+        (ArrowValues::Float64(values), Type::F64) => {
+            for row in rows {
+                docs[row].add_f64(field, values[row]);
+            }
+        }
+        (ArrowValues::Boolean(values), Type::Bool) => {
+            for row in rows {
+                docs[row].add_bool(field, values[row / 8] & (1 << (row % 8)) != 0);
+            }
+        }
+        (ArrowValues::TimestampNanosecond(values), Type::Date) => {
+            for row in rows {
+                docs[row].add_date(field, DateTime::from_timestamp_nanos(values[row]));
+            }
+        }
+        (ArrowValues::LargeUtf8 { offsets, data }, Type::Str) => {
+            for row in rows {
+                let bytes = &data[offsets[row] as usize..offsets[row + 1] as usize];
+                let text = std::str::from_utf8(bytes).map_err(|_| {
+                    TantivyError::InvalidArgument(format!(
+                        "The value of the row {row} of the column {column_name:?} is not valid \
+                         utf8"
+                    ))
+                })?;
+                docs[row].add_text(field, text);
+            }
+        }
+        (ArrowValues::LargeBinary { offsets, data }, Type::Bytes) => {
+            for row in rows {
+                let bytes = &data[offsets[row] as usize..offsets[row + 1] as usize];
+                docs[row].add_bytes(field, bytes);
+            }
+        }
+        (values, value_type) => return Err(type_mismatch(column_name, values, value_type)),
+    }
+    Ok(())
+}
+
+/// Converts the rows of a batch into documents, column by column.
+///
+/// The columns are loaded into the fields of the same name. The columns of the hits of an
+/// [`ArrowCollector`](crate::collector::ArrowCollector) are skipped, unless the schema has
+/// fields of the same name.
+pub(crate) fn record_batch_to_documents(
+    schema: &Schema,
+    batch: &ArrowRecordBatch,
+) -> crate::Result<Vec<Document>> {
+    let mut docs = vec![Document::default(); batch.num_rows()];
+    for (column_name, column) in batch.columns() {
+        let field = match schema.get_field(column_name) {
+            Ok(field) => field,
+            Err(_)
+                if [SEGMENT_ORD_COLUMN, DOC_ID_COLUMN, SCORE_COLUMN]
+                    .contains(&column_name.as_str()) =>
+            {
+                continue
+            }
+            Err(err) => return Err(err),
+        };
+        let value_type = schema.get_field_entry(field).field_type().value_type();
+        load_column(column_name, column, field, value_type, &mut docs)?;
+    }
+    Ok(docs)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::{ArrowCollector, ArrowColumn, ArrowRecordBatch, ArrowValues, Count};
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, INDEXED, STORED, STRING};
+    use crate::{DateTime, Document, Index, Term};
+
+    #[test]
+    fn test_add_record_batch() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", INDEXED | FAST);
+        let name = schema_builder.add_text_field("name", STRING | STORED);
+        schema_builder.add_f64_field("score", FAST);
+        schema_builder.add_bool_field("active", FAST);
+        let date = schema_builder.add_date_field("date", INDEXED | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+
+        let batch = ArrowRecordBatch::try_new(vec![
+            (
+                "id".to_string(),
+                ArrowColumn::try_new(3, ArrowValues::Int64(vec![1, 2, 3]), None)?,
+            ),
+            (
+                "name".to_string(),
+                ArrowColumn::try_new(
+                    3,
+                    ArrowValues::LargeUtf8 {
+                        offsets: vec![0, 5, 5, 8],
+                        data: b"alicebob".to_vec(),
+                    },
+                    Some(vec![0b101]),
+                )?,
+            ),
+            (
+                "score".to_string(),
+                ArrowColumn::try_new(3, ArrowValues::Float32(vec![0.5, 1.5, 2.5]), None)?,
+            ),
+            (
+                "active".to_string(),
+                ArrowColumn::try_new(3, ArrowValues::Boolean(vec![0b011]), None)?,
+            ),
+            (
+                "date".to_string(),
+                ArrowColumn::try_new(
+                    3,
+                    ArrowValues::TimestampNanosecond(vec![0, 1_000_000_000, 0]),
+                    Some(vec![0b010]),
+                )?,
+            ),
+        ])?;
+        index_writer.add_record_batch(&batch)?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 3);
+        let query = TermQuery::new(Term::from_field_u64(id, 3), IndexRecordOption::Basic);
+        let (_, doc_address) =
+            searcher.search(&query, &crate::collector::TopDocs::with_limit(1))?[0];
+        let doc: Document = searcher.doc(doc_address)?;
+        assert_eq!(doc.get_first(name).unwrap().as_text(), Some("bob"));
+
+        // Round trip through the ArrowCollector.
+        let exported = searcher.search(
+            &AllQuery,
+            &ArrowCollector::new(["id", "score", "active", "date"]),
+        )?;
+        assert_eq!(
+            exported.column("id").unwrap().values(),
+            &ArrowValues::UInt64(vec![1, 2, 3])
+        );
+        assert_eq!(
+            exported.column("score").unwrap().values(),
+            &ArrowValues::Float64(vec![0.5, 1.5, 2.5])
+        );
+        assert_eq!(exported.column("date").unwrap().null_count(), 2);
+        let other_index = Index::create_in_ram(index.schema());
+        let mut other_index_writer = other_index.writer_for_tests()?;
+        other_index_writer.add_record_batch(&exported)?;
+        other_index_writer.commit()?;
+        let other_searcher = other_index.reader()?.searcher();
+        let query = TermQuery::new(
+            Term::from_field_date(date, DateTime::from_timestamp_secs(1)),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(other_searcher.search(&query, &Count)?, 1);
+        assert_eq!(other_searcher.search(&AllQuery, &Count)?, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_record_batch_errors() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field("id", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let index_writer = index.writer_for_tests()?;
+        let batch = |name: &str, values: ArrowValues| {
+            ArrowRecordBatch::try_new(vec![(
+                name.to_string(),
+                ArrowColumn::try_new(1, values, None)?,
+            )])
+        };
+        assert!(index_writer
+            .add_record_batch(&batch("id", ArrowValues::Int64(vec![-1]))?)
+            .is_err());
+        assert!(index_writer
+            .add_record_batch(&batch("id", ArrowValues::Float64(vec![1.0]))?)
+            .is_err());
+        assert!(index_writer
+            .add_record_batch(&batch("missing", ArrowValues::UInt64(vec![1]))?)
+            .is_err());
+        assert!(ArrowColumn::try_new(2, ArrowValues::UInt64(vec![1]), None).is_err());
+        assert!(ArrowRecordBatch::try_new(vec![
+            (
+                "a".to_string(),
+                ArrowColumn::try_new(1, ArrowValues::UInt64(vec![1]), None)?
+            ),
+            (
+                "b".to_string(),
+                ArrowColumn::try_new(0, ArrowValues::UInt64(vec![]), None)?
+            ),
+        ])
+        .is_err());
+        Ok(())
+    }
+}
@@ -1,14 +1,21 @@
 use std::ops::Range;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
 use std::thread::JoinHandle;
+use std::time::Instant;
 
 use common::BitSet;
 use smallvec::smallvec;
 
+use super::arrow_loader::record_batch_to_documents;
+use super::indexing_buffer_spill::IndexingBufferSpill;
+use super::memory_budget::MemoryBudget;
 use super::operation::{AddOperation, UserOperation};
+#[cfg(feature = "parquet")]
+use super::parquet_loader::ParquetBatchReader;
 use super::segment_updater::SegmentUpdater;
 use super::{AddBatch, AddBatchReceiver, AddBatchSender, PreparedCommit};
+use crate::collector::ArrowRecordBatch;
 use crate::core::{Index, Segment, SegmentComponent, SegmentId, SegmentMeta, SegmentReader};
 use crate::directory::{DirectoryLock, GarbageCollectionResult, TerminatingWrite};
 use crate::error::TantivyError;
@@ -17,10 +24,16 @@ use crate::indexer::delete_queue::{DeleteCursor, DeleteQueue};
 use crate::indexer::doc_opstamp_mapping::DocToOpstampMapping;
 use crate::indexer::index_writer_status::IndexWriterStatus;
 use crate::indexer::operation::DeleteOperation;
+use crate::indexer::segment_writer::{check_dense_vectors, resolve_copy_to_fields};
 use crate::indexer::stamper::Stamper;
-use crate::indexer::{MergePolicy, SegmentEntry, SegmentWriter};
+use crate::indexer::version_tracker::{VersionTracker, ABSENT_VERSION};
+use crate::indexer::{
+    MergeEventCallback, MergeEventHandle, MergeHandle, MergePolicy, SegmentEntry, SegmentWriter,
+};
+use crate::metrics::enter_span;
 use crate::query::{EnableScoring, Query, TermQuery};
-use crate::schema::{Document, IndexRecordOption, Term};
+use crate::schema::{Document, Field, IndexRecordOption, Term};
+use crate::suggest::resolve_completion_fields;
 use crate::{FutureResult, Opstamp};
 
 // Size of the margin for the `memory_arena`. A segment is closed when the remaining memory
@@ -58,6 +71,9 @@ pub struct IndexWriter {
     index: Index,
 
     memory_arena_in_bytes_per_thread: usize,
+    memory_budget: Option<MemoryBudget>,
+    indexing_buffer_spill: Arc<RwLock<Option<IndexingBufferSpill>>>,
+    version_tracker: Mutex<Option<VersionTracker>>,
 
     workers_join_handle: Vec<JoinHandle<crate::Result<()>>>,
 
@@ -165,12 +181,24 @@ pub(crate) fn advance_deletes(
 
 fn index_documents(
     memory_budget: usize,
+    shared_memory_budget: Option<&MemoryBudget>,
+    indexing_buffer_spill: Option<&IndexingBufferSpill>,
     segment: Segment,
     grouped_document_iterator: &mut dyn Iterator<Item = AddBatch>,
     segment_updater: &mut SegmentUpdater,
     mut delete_cursor: DeleteCursor,
 ) -> crate::Result<()> {
-    let mut segment_writer = SegmentWriter::for_segment(memory_budget, segment.clone())?;
+    let mut segment_writer = if let Some(indexing_buffer_spill) = indexing_buffer_spill {
+        SegmentWriter::for_segment_with_spill(
+            memory_budget,
+            segment.clone(),
+            indexing_buffer_spill,
+        )?
+    } else {
+        SegmentWriter::for_segment(memory_budget, segment.clone())?
+    };
+    // Released once the segment writer is finalized.
+    let mut memory_reservation = shared_memory_budget.map(MemoryBudget::reserve);
     for document_group in grouped_document_iterator {
         for doc in document_group {
             segment_writer.add_document(doc)?;
@@ -183,6 +211,30 @@ fn index_documents(
             );
             break;
         }
+        // The spilled pages do not use RAM, but the addresses of the arenas are 32 bits.
+        if mem_usage + segment_writer.spilled_num_bytes() >= MEMORY_ARENA_NUM_BYTES_MAX {
+            info!(
+                "Spilled buffer limit reached, flushing segment with maxdoc={}.",
+                segment_writer.max_doc()
+            );
+            break;
+        }
+        if let (Some(memory_reservation), Some(shared_memory_budget)) =
+            (memory_reservation.as_mut(), shared_memory_budget)
+        {
+            let overall_mem_usage = memory_reservation.set_num_bytes(mem_usage);
+            if overall_mem_usage
+                >= shared_memory_budget
+                    .num_bytes()
+                    .saturating_sub(MARGIN_IN_BYTES)
+            {
+                info!(
+                    "Shared memory budget reached, flushing segment with maxdoc={}.",
+                    segment_writer.max_doc()
+                );
+                break;
+            }
+        }
     }
 
     if !segment_updater.is_alive() {
@@ -195,7 +247,11 @@ fn index_documents(
     // the worker thread.
     assert!(max_doc > 0);
 
+    let _span_guard = enter_span!("tantivy.flush_segment", max_doc);
+    let start = Instant::now();
+
     let doc_opstamps: Vec<Opstamp> = segment_writer.finalize()?;
+    drop(memory_reservation);
 
     let segment_with_max_doc = segment.with_max_doc(max_doc);
 
@@ -206,6 +262,9 @@ fn index_documents(
     // update segment_updater inventory to remove tempstore
     let segment_entry = SegmentEntry::new(meta, delete_cursor, alive_bitset_opt);
     segment_updater.schedule_add_segment(segment_entry).wait()?;
+    if let Some(metrics_recorder) = segment_with_max_doc.index().metrics_recorder() {
+        metrics_recorder.record_segment_flush(max_doc, start.elapsed());
+    }
     Ok(())
 }
 
@@ -265,6 +324,7 @@ impl IndexWriter {
         index: &Index,
         num_threads: usize,
         memory_arena_in_bytes_per_thread: usize,
+        memory_budget: Option<MemoryBudget>,
         directory_lock: DirectoryLock,
     ) -> crate::Result<IndexWriter> {
         if memory_arena_in_bytes_per_thread < MEMORY_ARENA_NUM_BYTES_MIN {
@@ -280,6 +340,9 @@ impl IndexWriter {
             );
             return Err(TantivyError::InvalidArgument(err_msg));
         }
+        // Checking the schema upfront, rather than failing in the indexing workers.
+        resolve_copy_to_fields(&index.schema())?;
+        resolve_completion_fields(&index.schema())?;
         let (document_sender, document_receiver): (AddBatchSender, AddBatchReceiver) =
             crossbeam_channel::bounded(PIPELINE_MAX_SIZE_IN_DOCS);
 
@@ -296,6 +359,9 @@ impl IndexWriter {
             _directory_lock: Some(directory_lock),
 
             memory_arena_in_bytes_per_thread,
+            memory_budget,
+            indexing_buffer_spill: Arc::default(),
+            version_tracker: Mutex::new(None),
             index: index.clone(),
             index_writer_status: IndexWriterStatus::from(document_receiver),
             operation_sender: document_sender,
@@ -362,6 +428,17 @@ impl IndexWriter {
             .wait()
     }
 
+    /// Drops the given segments, e.g. the corrupt segments reported by
+    /// [`Index::check_index()`](crate::Index::check_index), along with all of their documents.
+    ///
+    /// The segments are removed from the index at the next commit, and are restored by a
+    /// rollback.
+    pub fn drop_segments(&self, segment_ids: &[SegmentId]) -> crate::Result<()> {
+        self.segment_updater
+            .schedule_remove_segments(segment_ids.to_vec())
+            .wait()
+    }
+
     /// Creates a new segment.
     ///
     /// This method is useful only for users trying to do complex
@@ -397,6 +474,8 @@ impl IndexWriter {
         let mut delete_cursor = self.delete_queue.cursor();
 
         let mem_budget = self.memory_arena_in_bytes_per_thread;
+        let shared_memory_budget = self.memory_budget.clone();
+        let indexing_buffer_spill = self.indexing_buffer_spill.clone();
         let index = self.index.clone();
         let join_handle: JoinHandle<crate::Result<()>> = thread::Builder::new()
             .name(format!("thrd-tantivy-index{}", self.worker_id))
@@ -424,8 +503,12 @@ impl IndexWriter {
                         return Ok(());
                     }
 
+                    let segment_indexing_buffer_spill =
+                        indexing_buffer_spill.read().unwrap().clone();
                     index_documents(
                         mem_budget,
+                        shared_memory_budget.as_ref(),
+                        segment_indexing_buffer_spill.as_ref(),
                         index.new_segment(),
                         &mut document_iterator,
                         &mut segment_updater,
@@ -448,6 +531,16 @@ impl IndexWriter {
         self.segment_updater.set_merge_policy(merge_policy);
     }
 
+    /// Sets whether the segments started from now on spill their indexing buffers to temporary
+    /// files. See [`IndexingBufferSpill`].
+    pub fn set_indexing_buffer_spill(&self, indexing_buffer_spill: Option<IndexingBufferSpill>) {
+        *self.indexing_buffer_spill.write().unwrap() = indexing_buffer_spill;
+    }
+
+    fn indexing_buffer_spill(&self) -> Option<IndexingBufferSpill> {
+        self.indexing_buffer_spill.read().unwrap().clone()
+    }
+
     fn start_workers(&mut self) -> crate::Result<()> {
         for _ in 0..self.num_threads {
             self.add_indexing_worker()?;
@@ -499,6 +592,9 @@ impl IndexWriter {
     pub fn delete_all_documents(&self) -> crate::Result<Opstamp> {
         // Delete segments
         self.segment_updater.remove_all_segments();
+        if let Some(version_tracker) = self.version_tracker.lock().unwrap().as_mut() {
+            version_tracker.delete_all();
+        }
         // Return new stamp - reverted stamp
         self.stamper.revert(self.committed_opstamp);
         Ok(self.committed_opstamp)
@@ -515,6 +611,23 @@ impl IndexWriter {
         segment_updater.start_merge(merge_operation)
     }
 
+    /// Returns handles on the merge operations that are currently running.
+    ///
+    /// The handles make it possible to follow the progress of these merges
+    /// and to cancel them.
+    pub fn running_merges(&self) -> Vec<MergeHandle> {
+        self.segment_updater.running_merges()
+    }
+
+    /// Subscribes to the `MergeEvent`s emitted when a merge starts or ends.
+    ///
+    /// The callback is called on the merge thread, and should therefore
+    /// return quickly. It stays subscribed until all of the clones of
+    /// the returned `MergeEventHandle` are dropped.
+    pub fn subscribe_merge_events(&self, callback: MergeEventCallback) -> MergeEventHandle {
+        self.segment_updater.subscribe_merge_events(callback)
+    }
+
     /// Closes the current document channel send.
     /// and replace all the channels by new ones.
     ///
@@ -555,8 +668,13 @@ impl IndexWriter {
             &self.index,
             self.num_threads,
             self.memory_arena_in_bytes_per_thread,
+            self.memory_budget.clone(),
             directory_lock,
         )?;
+        new_index_writer.set_indexing_buffer_spill(self.indexing_buffer_spill());
+        if let Some(version_field) = self.version_field() {
+            new_index_writer.set_version_field(version_field)?;
+        }
 
         // the current `self` is dropped right away because of this call.
         //
@@ -564,6 +682,9 @@ impl IndexWriter {
         // should terminate.
         *self = new_index_writer;
 
+        // The near real time readers go back to the segments of the last commit.
+        drop(self.index.nrt_segments().publish(None));
+
         // Drains the document receiver pipeline :
         // Workers don't need to index the pending documents.
         //
@@ -576,6 +697,83 @@ impl IndexWriter {
         Ok(self.committed_opstamp)
     }
 
+    /// Makes the indexing workers flush their current segment, regardless of its size, and
+    /// restarts them.
+    fn flush_indexing_workers(&mut self) -> crate::Result<()> {
+        // this will drop the current document channel
+        // and recreate a new one.
+        self.recreate_document_channel();
+
+        let former_workers_join_handle = std::mem::take(&mut self.workers_join_handle);
+
+        for worker_handle in former_workers_join_handle {
+            let indexing_worker_result = worker_handle
+                .join()
+                .map_err(|e| TantivyError::ErrorInThread(format!("{e:?}")))?;
+            indexing_worker_result?;
+            self.add_indexing_worker()?;
+        }
+        Ok(())
+    }
+
+    /// Same as [`flush_indexing_workers()`](IndexWriter::flush_indexing_workers), but waiting
+    /// for the workers to terminate from another thread, without blocking the caller.
+    async fn flush_indexing_workers_async(&mut self) -> crate::Result<()> {
+        self.recreate_document_channel();
+
+        let former_workers_join_handle = std::mem::take(&mut self.workers_join_handle);
+        let num_workers = former_workers_join_handle.len();
+        let (join_future, join_sender) =
+            FutureResult::create("Failed to wait for the indexing workers.");
+        thread::Builder::new()
+            .name("thrd-tantivy-flush".to_string())
+            .spawn(move || {
+                let mut join_result = Ok(());
+                for worker_handle in former_workers_join_handle {
+                    let indexing_worker_result = worker_handle
+                        .join()
+                        .map_err(|e| TantivyError::ErrorInThread(format!("{e:?}")))
+                        .and_then(|indexing_worker_result| indexing_worker_result);
+                    if join_result.is_ok() {
+                        join_result = indexing_worker_result;
+                    }
+                }
+                let _ = join_sender.send(join_result);
+            })?;
+        join_future.await?;
+        for _ in 0..num_workers {
+            self.add_indexing_worker()?;
+        }
+        Ok(())
+    }
+
+    /// Makes the pending changes searchable by the
+    /// [near real time](crate::IndexReaderBuilder::near_real_time) readers, without committing
+    /// them.
+    ///
+    /// Like [`prepare_commit()`](IndexWriter::prepare_commit), this flushes the segments of the
+    /// indexing workers, and applies the pending deletes to a copy of the segments. No
+    /// `meta.json` is written however: the changes are not persisted, and are lost in case of a
+    /// crash or of a [`rollback()`](IndexWriter::rollback) if no commit follows.
+    ///
+    /// The near real time readers with the [`ReloadPolicy::OnCommit`](crate::ReloadPolicy)
+    /// policy are reloaded before this method returns. The opstamp of the last operation made
+    /// searchable is returned.
+    pub fn refresh(&mut self) -> crate::Result<Opstamp> {
+        info!("Refreshing");
+        self.flush_indexing_workers()?;
+        let refresh_opstamp = self.stamper.stamp();
+        let segment_metas = self
+            .segment_updater
+            .schedule_refresh(refresh_opstamp)
+            .wait()?;
+        self.index
+            .nrt_segments()
+            .publish(Some(segment_metas))
+            .wait()?;
+        Ok(refresh_opstamp)
+    }
+
     /// Prepares a commit.
     ///
     /// Calling `prepare_commit()` will cut the indexing
@@ -609,21 +807,21 @@ impl IndexWriter {
         // This will move uncommitted segments to the state of
         // committed segments.
         info!("Preparing commit");
+        self.flush_indexing_workers()?;
+        let commit_opstamp = self.stamper.stamp();
+        let prepared_commit = PreparedCommit::new(self, commit_opstamp);
+        info!("Prepared commit {}", commit_opstamp);
+        Ok(prepared_commit)
+    }
 
-        // this will drop the current document channel
-        // and recreate a new one.
-        self.recreate_document_channel();
-
-        let former_workers_join_handle = std::mem::take(&mut self.workers_join_handle);
-
-        for worker_handle in former_workers_join_handle {
-            let indexing_worker_result = worker_handle
-                .join()
-                .map_err(|e| TantivyError::ErrorInThread(format!("{e:?}")))?;
-            indexing_worker_result?;
-            self.add_indexing_worker()?;
-        }
-
+    /// Same as [`prepare_commit()`](IndexWriter::prepare_commit), but returning a future which
+    /// does not block the thread polling it while the indexing workers flush their segments.
+    ///
+    /// Together with [`PreparedCommit::commit_future()`], this makes it possible to commit from
+    /// an async runtime without stalling it.
+    pub async fn prepare_commit_async(&mut self) -> crate::Result<PreparedCommit<'_>> {
+        info!("Preparing commit");
+        self.flush_indexing_workers_async().await?;
         let commit_opstamp = self.stamper.stamp();
         let prepared_commit = PreparedCommit::new(self, commit_opstamp);
         info!("Prepared commit {}", commit_opstamp);
@@ -647,6 +845,16 @@ impl IndexWriter {
         self.prepare_commit()?.commit()
     }
 
+    /// Commits all of the pending changes, in a future.
+    ///
+    /// This is the async version of [`commit()`](IndexWriter::commit): the indexing workers
+    /// flush their segments, and the segments are synced and published in the `meta.json` file
+    /// on other threads, while the future does not block the thread polling it. The future
+    /// resolves to the `opstamp` of the commit once it is persisted.
+    pub async fn commit_async(&mut self) -> crate::Result<Opstamp> {
+        self.prepare_commit_async().await?.commit_future().await
+    }
+
     pub(crate) fn segment_updater(&self) -> &SegmentUpdater {
         &self.segment_updater
     }
@@ -660,11 +868,17 @@ impl IndexWriter {
     /// Like adds, the deletion itself will be visible
     /// only after calling `commit()`.
     pub fn delete_term(&self, term: Term) -> Opstamp {
-        let query = TermQuery::new(term, IndexRecordOption::Basic);
+        let mut version_tracker = self.version_tracker.lock().unwrap();
+        let query = TermQuery::new(term.clone(), IndexRecordOption::Basic);
         // For backward compatibility, if Term is invalid for the index, do nothing but return an
         // Opstamp
-        self.delete_query(Box::new(query))
-            .unwrap_or_else(|_| self.stamper.stamp())
+        let opstamp = self
+            .delete_query(Box::new(query))
+            .unwrap_or_else(|_| self.stamper.stamp());
+        if let Some(version_tracker) = version_tracker.as_mut() {
+            version_tracker.set_version(term, ABSENT_VERSION, opstamp);
+        }
+        opstamp
     }
 
     /// Delete all documents matching a given query.
@@ -688,6 +902,104 @@ impl IndexWriter {
         Ok(opstamp)
     }
 
+    /// Sets the `u64` fast field holding the versions of the documents updated with
+    /// [`IndexWriter::update_document_if()`].
+    ///
+    /// Returns an error if the field is not a `u64` fast field.
+    pub fn set_version_field(&self, version_field: Field) -> crate::Result<()> {
+        let version_tracker = VersionTracker::new(
+            &self.index,
+            &self.segment_updater.load_meta(),
+            version_field,
+        )?;
+        *self.version_tracker.lock().unwrap() = Some(version_tracker);
+        Ok(())
+    }
+
+    /// Returns the version field set with [`IndexWriter::set_version_field()`].
+    pub fn version_field(&self) -> Option<Field> {
+        self.version_tracker
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(VersionTracker::version_field)
+    }
+
+    /// Returns the current version of the document identified by `term`, including the
+    /// uncommitted updates, or 0 if there is no such document.
+    ///
+    /// Returns an error if no version field is set.
+    pub fn document_version(&self, term: &Term) -> crate::Result<u64> {
+        let mut version_tracker = self.version_tracker.lock().unwrap();
+        let version_tracker = version_tracker
+            .as_mut()
+            .ok_or_else(missing_version_field_error)?;
+        version_tracker.current_version(&self.index, &self.segment_updater.load_meta(), term)
+    }
+
+    /// Replaces the document identified by `term` by `document`, provided the current version
+    /// of the document is `expected_version`, and returns the new version of the document.
+    ///
+    /// The expected version 0 means that there must not be any document identified by `term`.
+    /// The versions are stored in the version field set with
+    /// [`IndexWriter::set_version_field()`]: the writer overwrites the values of this field in
+    /// `document` with the new version, which is the opstamp of the update.
+    ///
+    /// Returns a [`TantivyError::VersionConflict`] if the document was updated, deleted or
+    /// created since `expected_version` was read, so that concurrent updates of the same
+    /// document do not overwrite each other silently. The versions only account for the
+    /// conditional updates and for [`IndexWriter::delete_term()`]: documents added with
+    /// [`IndexWriter::add_document()`] keep the version of their version field, if any.
+    ///
+    /// Like adds and deletes, the update is visible to readers only after calling `commit()`.
+    pub fn update_document_if(
+        &self,
+        term: Term,
+        expected_version: u64,
+        document: Document,
+    ) -> crate::Result<u64> {
+        let mut version_tracker = self.version_tracker.lock().unwrap();
+        let version_tracker = version_tracker
+            .as_mut()
+            .ok_or_else(missing_version_field_error)?;
+        let schema = self.index.schema();
+        let actual_version = version_tracker.current_version(
+            &self.index,
+            &self.segment_updater.load_meta(),
+            &term,
+        )?;
+        if actual_version != expected_version {
+            return Err(TantivyError::VersionConflict {
+                expected: expected_version,
+                actual: actual_version,
+            });
+        }
+        let version_field = version_tracker.version_field();
+        let mut document: Document = document
+            .into_iter()
+            .filter(|field_value| field_value.field() != version_field)
+            .collect::<Vec<_>>()
+            .into();
+        check_dense_vectors(&schema, &document)?;
+        let query = TermQuery::new(term.clone(), IndexRecordOption::Basic);
+        let weight = query.weight(EnableScoring::disabled_from_schema(&schema))?;
+
+        let (_, stamps) = self.get_batch_opstamps(2);
+        let delete_opstamp = stamps.start;
+        let version = delete_opstamp + 1;
+        document.add_u64(version_field, version);
+        self.delete_queue.push(DeleteOperation {
+            opstamp: delete_opstamp,
+            target: weight,
+        });
+        self.send_add_documents_batch(smallvec![AddOperation {
+            opstamp: version,
+            document
+        }])?;
+        version_tracker.set_version(term, version, version);
+        Ok(version)
+    }
+
     /// Returns the opstamp of the last successful commit.
     ///
     /// This is, for instance, the opstamp the index will
@@ -707,11 +1019,52 @@ impl IndexWriter {
     /// be used by the client to align commits with its own
     /// document queue.
     pub fn add_document(&self, document: Document) -> crate::Result<Opstamp> {
+        check_dense_vectors(&self.index.schema(), &document)?;
         let opstamp = self.stamper.stamp();
         self.send_add_documents_batch(smallvec![AddOperation { opstamp, document }])?;
         Ok(opstamp)
     }
 
+    /// Adds the rows of a columnar batch as documents, e.g. to bulk load a dataset read from a
+    /// Parquet file as Arrow arrays.
+    ///
+    /// Each column is loaded into the field of the same name, column by column. The integer and
+    /// floating point columns are loaded into the numeric fields they fit in, the timestamps
+    /// into date fields, the strings into text fields and the binaries into bytes fields. The
+    /// null values are skipped.
+    ///
+    /// The documents are added as a single group of operations: see [`IndexWriter::run()`].
+    pub fn add_record_batch(&self, batch: &ArrowRecordBatch) -> crate::Result<Opstamp> {
+        let schema = self.index.schema();
+        let documents = record_batch_to_documents(&schema, batch)?;
+        for document in &documents {
+            check_dense_vectors(&schema, document)?;
+        }
+        self.run(documents.into_iter().map(UserOperation::Add))
+    }
+
+    /// Bulk loads the rows of a Parquet file as documents.
+    ///
+    /// The columns having a field of the same name in the schema are read batch after batch,
+    /// and each batch is loaded like [`IndexWriter::add_record_batch()`] does. The other columns
+    /// are not read. Only the flat, non-repeated columns can be loaded: the integers, floating
+    /// points, booleans, dates, timestamps, strings and binaries.
+    ///
+    /// Each batch is added as a group of operations, and the opstamp of the last one is
+    /// returned.
+    #[cfg(feature = "parquet")]
+    pub fn add_parquet_file<P: AsRef<std::path::Path>>(&self, path: P) -> crate::Result<Opstamp> {
+        let mut parquet_reader = ParquetBatchReader::open(path.as_ref(), &self.index.schema())?;
+        let mut opstamp = None;
+        while let Some(batch) = parquet_reader.next_batch()? {
+            opstamp = Some(self.add_record_batch(&batch)?);
+        }
+        match opstamp {
+            Some(opstamp) => Ok(opstamp),
+            None => self.run(Vec::new()),
+        }
+    }
+
     /// Gets a range of stamps from the stamper and "pops" the last stamp
     /// from the range returning a tuple of the last optstamp and the popped
     /// range.
@@ -758,6 +1111,9 @@ impl IndexWriter {
         for (user_op, opstamp) in user_operations_it.zip(stamps) {
             match user_op {
                 UserOperation::Delete(term) => {
+                    if let Some(version_tracker) = self.version_tracker.lock().unwrap().as_mut() {
+                        version_tracker.set_version(term.clone(), ABSENT_VERSION, opstamp);
+                    }
                     let query = TermQuery::new(term, IndexRecordOption::Basic);
                     let weight =
                         query.weight(EnableScoring::disabled_from_schema(&self.index.schema()))?;
@@ -768,6 +1124,7 @@ impl IndexWriter {
                     self.delete_queue.push(delete_operation);
                 }
                 UserOperation::Add(document) => {
+                    check_dense_vectors(&self.index.schema(), &document)?;
                     let add_operation = AddOperation { opstamp, document };
                     adds.push(add_operation);
                 }
@@ -778,6 +1135,13 @@ impl IndexWriter {
     }
 
     fn send_add_documents_batch(&self, add_ops: AddBatch) -> crate::Result<()> {
+        let num_docs = add_ops.len();
+        let _span_guard = enter_span!("tantivy.add_documents", num_docs);
+        if let Some(metrics_recorder) = self.index.metrics_recorder() {
+            if num_docs > 0 {
+                metrics_recorder.record_add_documents(num_docs);
+            }
+        }
         if self.index_writer_status.is_alive() && self.operation_sender.send(add_ops).is_ok() {
             Ok(())
         } else {
@@ -786,6 +1150,13 @@ impl IndexWriter {
     }
 }
 
+fn missing_version_field_error() -> TantivyError {
+    TantivyError::InvalidArgument(
+        "Conditional updates require a version field, see `IndexWriter::set_version_field`"
+            .to_string(),
+    )
+}
+
 impl Drop for IndexWriter {
     fn drop(&mut self) {
         self.segment_updater.kill();
@@ -807,7 +1178,7 @@ mod tests {
     use proptest::strategy::Strategy;
 
     use super::super::operation::UserOperation;
-    use crate::collector::TopDocs;
+    use crate::collector::{Count, TopDocs};
     use crate::directory::error::LockError;
     use crate::error::*;
     use crate::indexer::NoMergePolicy;
@@ -818,7 +1189,8 @@ mod tests {
     };
     use crate::store::DOCSTORE_CACHE_CAPACITY;
     use crate::{
-        DateTime, DocAddress, Index, IndexSettings, IndexSortByField, Order, ReloadPolicy, Term,
+        DateTime, DocAddress, Index, IndexReader, IndexSettings, IndexSortByField, Order,
+        ReloadPolicy, Term,
     };
 
     const LOREM: &str = "Doc Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do \
@@ -844,6 +1216,39 @@ mod tests {
         assert_eq!(batch_opstamp1, 2u64);
     }
 
+    #[test]
+    fn test_commit_async() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => "a"))?;
+        index_writer.add_document(doc!(text_field => "b"))?;
+
+        fn assert_send<T: Send>(value: T) -> T {
+            value
+        }
+        let commit_future = assert_send(index_writer.commit_async());
+        let opstamp = futures::executor::block_on(commit_future)?;
+        assert_eq!(index.load_metas()?.opstamp, opstamp);
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 2);
+
+        // The indexing workers are restarted after the commit.
+        index_writer.add_document(doc!(text_field => "c"))?;
+        let mut prepared_commit = futures::executor::block_on(index_writer.prepare_commit_async())?;
+        prepared_commit.set_payload("async");
+        futures::executor::block_on(prepared_commit.commit_future())?;
+        assert_eq!(index.load_metas()?.payload.as_deref(), Some("async"));
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 3);
+        Ok(())
+    }
+
     #[test]
     fn test_no_need_to_rewrite_delete_file_if_no_new_deletes() {
         let mut schema_builder = schema::Schema::builder();
@@ -950,6 +1355,76 @@ mod tests {
         assert_eq!(batch_opstamp2, 1u64);
     }
 
+    #[test]
+    fn test_update_document_if() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let version = schema_builder.add_u64_field("version", FAST | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let id_term = Term::from_field_text(id, "1");
+        assert!(index_writer
+            .update_document_if(id_term.clone(), 0, doc!(id => "1"))
+            .is_err());
+        assert!(index_writer.set_version_field(title).is_err());
+        index_writer.set_version_field(version)?;
+        assert_eq!(index_writer.version_field(), Some(version));
+
+        // The expected version 0 creates the document if it does not exist.
+        let version1 = index_writer.update_document_if(
+            id_term.clone(),
+            0,
+            doc!(id => "1", title => "first", version => 1000u64),
+        )?;
+        assert!(matches!(
+            index_writer.update_document_if(id_term.clone(), 0, doc!(id => "1")),
+            Err(TantivyError::VersionConflict { expected: 0, actual }) if actual == version1
+        ));
+        let version2 = index_writer.update_document_if(
+            id_term.clone(),
+            version1,
+            doc!(id => "1", title => "second"),
+        )?;
+        assert!(version2 > version1);
+        index_writer.commit()?;
+
+        assert_eq!(index_writer.document_version(&id_term)?, version2);
+        assert!(matches!(
+            index_writer.update_document_if(id_term.clone(), version1, doc!(id => "1")),
+            Err(TantivyError::VersionConflict { .. })
+        ));
+        let version3 = index_writer.update_document_if(
+            id_term.clone(),
+            version2,
+            doc!(id => "1", title => "third"),
+        )?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let top_docs = searcher.search(
+            &TermQuery::new(id_term.clone(), IndexRecordOption::Basic),
+            &TopDocs::with_limit(10),
+        )?;
+        assert_eq!(top_docs.len(), 1);
+        let doc = searcher.doc(top_docs[0].1)?;
+        assert_eq!(doc.get_first(title).unwrap().as_text(), Some("third"));
+        assert_eq!(doc.get_all(version).count(), 1);
+        assert_eq!(doc.get_first(version).unwrap().as_u64(), Some(version3));
+
+        index_writer.delete_term(id_term.clone());
+        assert_eq!(index_writer.document_version(&id_term)?, 0);
+        index_writer.rollback()?;
+        assert_eq!(index_writer.version_field(), Some(version));
+        assert_eq!(index_writer.document_version(&id_term)?, version3);
+        index_writer.delete_all_documents()?;
+        assert_eq!(index_writer.document_version(&id_term)?, 0);
+        index_writer.update_document_if(id_term.clone(), 0, doc!(id => "1"))?;
+        index_writer.commit()?;
+        assert_eq!(index.reader()?.searcher().num_docs(), 1);
+        Ok(())
+    }
+
     #[test]
     fn test_lockfile_stops_duplicates() {
         let schema_builder = schema::Schema::builder();
@@ -1040,6 +1515,55 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_refresh_near_real_time_reader() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let nrt_reader = index.reader_builder().near_real_time(true).try_into()?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let num_docs_containing = |reader: &IndexReader, s: &str| {
+            let term = Term::from_field_text(text_field, s);
+            let query = TermQuery::new(term, IndexRecordOption::Basic);
+            reader.searcher().search(&query, &Count).unwrap()
+        };
+
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field=>"a"))?;
+        index_writer.commit()?;
+        nrt_reader.reload()?;
+        reader.reload()?;
+        index_writer.add_document(doc!(text_field=>"b"))?;
+        index_writer.delete_term(Term::from_field_text(text_field, "a"));
+        index_writer.refresh()?;
+        // The near real time reader is reloaded by the refresh.
+        assert_eq!(num_docs_containing(&nrt_reader, "a"), 0);
+        assert_eq!(num_docs_containing(&nrt_reader, "b"), 1);
+        reader.reload()?;
+        assert_eq!(num_docs_containing(&reader, "a"), 1);
+        assert_eq!(num_docs_containing(&reader, "b"), 0);
+
+        // The refreshed changes are dropped by a rollback.
+        index_writer.rollback()?;
+        nrt_reader.reload()?;
+        assert_eq!(num_docs_containing(&nrt_reader, "a"), 1);
+        assert_eq!(num_docs_containing(&nrt_reader, "b"), 0);
+
+        index_writer.add_document(doc!(text_field=>"c"))?;
+        index_writer.refresh()?;
+        assert_eq!(num_docs_containing(&nrt_reader, "c"), 1);
+        index_writer.add_document(doc!(text_field=>"c"))?;
+        index_writer.commit()?;
+        nrt_reader.reload()?;
+        reader.reload()?;
+        assert_eq!(num_docs_containing(&nrt_reader, "c"), 2);
+        assert_eq!(num_docs_containing(&reader, "c"), 2);
+        Ok(())
+    }
+
     #[test]
     fn test_merge_on_empty_segments_single_segment() -> crate::Result<()> {
         let mut schema_builder = schema::Schema::builder();
@@ -1188,6 +1712,40 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_commit_metadata() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => "a"))?;
+        {
+            let mut prepared_commit = index_writer.prepare_commit()?;
+            prepared_commit.set_metadata("source", "kafka");
+            prepared_commit.set_metadata("offset", "12");
+            prepared_commit.commit()?;
+        }
+        index_writer.add_document(doc!(text_field => "b"))?;
+        {
+            let mut prepared_commit = index_writer.prepare_commit()?;
+            prepared_commit.set_metadata("offset", "13");
+            prepared_commit.commit()?;
+        }
+        let commit_metadata = index.commit_metadata()?;
+        assert_eq!(commit_metadata.len(), 1);
+        assert_eq!(commit_metadata["offset"], "13");
+
+        // Merges keep the metadata of the last commit.
+        let segment_ids = index.searchable_segment_ids()?;
+        assert_eq!(segment_ids.len(), 2);
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+        assert_eq!(index.searchable_segment_ids()?.len(), 1);
+        assert_eq!(index.commit_metadata()?, commit_metadata);
+        Ok(())
+    }
+
     #[test]
     fn test_prepare_but_rollback() -> crate::Result<()> {
         let mut schema_builder = schema::Schema::builder();
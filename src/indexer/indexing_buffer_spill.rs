@@ -0,0 +1,87 @@
+use std::io;
+use std::path::PathBuf;
+
+use stacker::MemoryArena;
+
+/// Lets the segments of an [`IndexWriter`](crate::IndexWriter) spill their indexing buffers to
+/// memory-mapped temporary files.
+///
+/// The terms and the postings recorders of a segment being built are stored in memory arenas.
+/// Once one of them holds `threshold_num_bytes` bytes in RAM, its following pages are mapped
+/// from a temporary file created in `directory`, and are paged in and out by the operating
+/// system. The spilled pages are not accounted in the memory budget of the writer, so that very
+/// large segments can be built on a machine with little memory, at the cost of some speed.
+///
+/// A segment is still flushed before its arenas reach their 4GB capacity.
+///
+/// Spilling requires the `mmap` feature: without it, the indexing workers fail to create their
+/// segments.
+///
+/// See [`IndexWriter::set_indexing_buffer_spill()`](crate::IndexWriter::set_indexing_buffer_spill).
+#[derive(Clone, Debug)]
+pub struct IndexingBufferSpill {
+    threshold_num_bytes: usize,
+    directory: PathBuf,
+}
+
+impl IndexingBufferSpill {
+    /// Spills the pages of the arenas beyond `threshold_num_bytes` bytes to temporary files of
+    /// `directory`.
+    pub fn new(threshold_num_bytes: usize, directory: impl Into<PathBuf>) -> IndexingBufferSpill {
+        IndexingBufferSpill {
+            threshold_num_bytes,
+            directory: directory.into(),
+        }
+    }
+
+    /// Creates an arena spilling to a new temporary file.
+    #[cfg(feature = "mmap")]
+    pub(crate) fn spilling_arena(&self) -> io::Result<MemoryArena> {
+        let file = tempfile::tempfile_in(&self.directory)?;
+        Ok(MemoryArena::with_spill_file(self.threshold_num_bytes, file))
+    }
+
+    #[cfg(not(feature = "mmap"))]
+    pub(crate) fn spilling_arena(&self) -> io::Result<MemoryArena> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "Spilling the indexing buffers to {:?} requires the `mmap` feature.",
+                self.directory
+            ),
+        ))
+    }
+}
+
+#[cfg(all(test, feature = "mmap"))]
+mod tests {
+    use super::IndexingBufferSpill;
+    use crate::collector::Count;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, TEXT};
+    use crate::{doc, Index, Term};
+
+    #[test]
+    fn test_indexing_buffer_spill() -> crate::Result<()> {
+        let spill_dir = tempfile::TempDir::new()?;
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+        index_writer.set_indexing_buffer_spill(Some(IndexingBufferSpill::new(0, spill_dir.path())));
+        for i in 0..20_000u32 {
+            index_writer.add_document(doc!(text => format!("term{i} common")))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.search(&AllQuery, &Count)?, 20_000);
+        let query = TermQuery::new(
+            Term::from_field_text(text, "term1234"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        // The temporary files are removed with the segment writers.
+        assert_eq!(std::fs::read_dir(spill_dir.path())?.count(), 0);
+        Ok(())
+    }
+}
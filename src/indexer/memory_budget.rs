@@ -0,0 +1,143 @@
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// A memory budget shared by several [`IndexWriter`](crate::IndexWriter)s, e.g. the writers of
+/// the indexes of the tenants of a process.
+///
+/// Each indexing worker of the writers created with
+/// [`Index::writer_with_memory_budget()`](crate::Index::writer_with_memory_budget) accounts the
+/// memory used by the segment it is building in the budget. When the overall memory used
+/// reaches the budget, the worker noticing it flushes its segment, releasing its memory, so
+/// that the writers do not collectively use more than the budget.
+///
+/// The budget is a cheap handle: its clones share the same accounting.
+#[derive(Clone)]
+pub struct MemoryBudget {
+    inner: Arc<MemoryBudgetInner>,
+}
+
+struct MemoryBudgetInner {
+    num_bytes: usize,
+    used_num_bytes: AtomicUsize,
+}
+
+impl MemoryBudget {
+    /// Creates a budget of `num_bytes` bytes.
+    pub fn new(num_bytes: usize) -> MemoryBudget {
+        MemoryBudget {
+            inner: Arc::new(MemoryBudgetInner {
+                num_bytes,
+                used_num_bytes: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Returns the size of the budget in bytes.
+    pub fn num_bytes(&self) -> usize {
+        self.inner.num_bytes
+    }
+
+    /// Returns the number of bytes currently used by the segments being built by the writers
+    /// sharing the budget.
+    pub fn used_num_bytes(&self) -> usize {
+        self.inner.used_num_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Creates a reservation accounting the memory used by one segment writer.
+    pub(crate) fn reserve(&self) -> MemoryReservation {
+        MemoryReservation {
+            memory_budget: self.clone(),
+            num_bytes: 0,
+        }
+    }
+}
+
+impl fmt::Debug for MemoryBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryBudget")
+            .field("num_bytes", &self.num_bytes())
+            .field("used_num_bytes", &self.used_num_bytes())
+            .finish()
+    }
+}
+
+/// The part of a [`MemoryBudget`] used by one segment writer, released on drop.
+pub(crate) struct MemoryReservation {
+    memory_budget: MemoryBudget,
+    num_bytes: usize,
+}
+
+impl MemoryReservation {
+    /// Updates the number of bytes used by the segment writer, and returns the number of
+    /// bytes used overall.
+    pub(crate) fn set_num_bytes(&mut self, num_bytes: usize) -> usize {
+        let used_num_bytes = &self.memory_budget.inner.used_num_bytes;
+        let overall_num_bytes = if num_bytes >= self.num_bytes {
+            let delta = num_bytes - self.num_bytes;
+            used_num_bytes.fetch_add(delta, Ordering::Relaxed) + delta
+        } else {
+            let delta = self.num_bytes - num_bytes;
+            used_num_bytes.fetch_sub(delta, Ordering::Relaxed) - delta
+        };
+        self.num_bytes = num_bytes;
+        overall_num_bytes
+    }
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.set_num_bytes(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemoryBudget;
+    use crate::collector::Count;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, TEXT};
+    use crate::{doc, Index};
+
+    #[test]
+    fn test_memory_reservations() {
+        let memory_budget = MemoryBudget::new(100);
+        let mut reservation = memory_budget.reserve();
+        assert_eq!(reservation.set_num_bytes(30), 30);
+        let mut other_reservation = memory_budget.clone().reserve();
+        assert_eq!(other_reservation.set_num_bytes(50), 80);
+        assert_eq!(reservation.set_num_bytes(10), 60);
+        drop(other_reservation);
+        assert_eq!(memory_budget.used_num_bytes(), 10);
+        drop(reservation);
+        assert_eq!(memory_budget.used_num_bytes(), 0);
+    }
+
+    #[test]
+    fn test_shared_memory_budget() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let memory_budget = MemoryBudget::new(8_000_000);
+        let index1 = Index::create_in_ram(schema.clone());
+        let index2 = Index::create_in_ram(schema);
+        let mut index_writer1 = index1.writer_with_memory_budget(1, &memory_budget)?;
+        let mut index_writer2 = index2.writer_with_memory_budget(1, &memory_budget)?;
+        for i in 0..20_000u32 {
+            let text_val = format!("term{i} common");
+            index_writer1.add_document(doc!(text => text_val.clone()))?;
+            index_writer2.add_document(doc!(text => text_val))?;
+        }
+        index_writer1.commit()?;
+        index_writer2.commit()?;
+        assert_eq!(memory_budget.used_num_bytes(), 0);
+
+        let searcher1 = index1.reader()?.searcher();
+        let searcher2 = index2.reader()?.searcher();
+        assert_eq!(searcher1.search(&AllQuery, &Count)?, 20_000);
+        assert_eq!(searcher2.search(&AllQuery, &Count)?, 20_000);
+        // The writers flushed their segments to stay within the shared budget.
+        assert!(searcher1.segment_readers().len() + searcher2.segment_readers().len() > 2);
+        Ok(())
+    }
+}
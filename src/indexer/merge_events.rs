@@ -0,0 +1,82 @@
+use std::sync::{Arc, RwLock, Weak};
+
+use crate::indexer::MergeHandle;
+use crate::{SegmentMeta, TantivyError};
+
+/// Event emitted over the lifetime of a merge operation.
+#[derive(Clone, Debug)]
+pub enum MergeEvent {
+    /// The merge started on a merge thread.
+    Started(MergeHandle),
+    /// The merge succeeded and its result was registered in the index.
+    ///
+    /// `merged_segment` is `None` if all of the merged documents were deleted.
+    Finished {
+        /// Handle of the merge operation.
+        handle: MergeHandle,
+        /// Meta of the resulting segment.
+        merged_segment: Option<SegmentMeta>,
+    },
+    /// The merge was cancelled through its `MergeHandle`.
+    Cancelled(MergeHandle),
+    /// The merge failed.
+    Failed {
+        /// Handle of the merge operation.
+        handle: MergeHandle,
+        /// Error that caused the merge to fail.
+        error: TantivyError,
+    },
+}
+
+/// Cloneable wrapper for callbacks registered to receive `MergeEvent`s.
+#[derive(Clone)]
+pub struct MergeEventCallback(Arc<dyn Fn(&MergeEvent) + Sync + Send>);
+
+impl MergeEventCallback {
+    /// Wraps a `Fn(&MergeEvent)` to create a `MergeEventCallback`.
+    pub fn new<F: Fn(&MergeEvent) + Sync + Send + 'static>(op: F) -> Self {
+        MergeEventCallback(Arc::new(op))
+    }
+
+    fn call(&self, event: &MergeEvent) {
+        self.0(event)
+    }
+}
+
+/// Controls how long a merge event callback stays subscribed.
+///
+/// After all the clones of `MergeEventHandle` are dropped, the associated callback
+/// will not be called anymore.
+#[must_use = "This `MergeEventHandle` controls the lifetime of the subscription and should \
+              therefore be used."]
+#[derive(Clone)]
+pub struct MergeEventHandle(#[allow(dead_code)] Arc<MergeEventCallback>);
+
+/// Registers merge event callbacks and dispatches events to them.
+#[derive(Default)]
+pub(crate) struct MergeEventCallbackList {
+    callbacks: RwLock<Vec<Weak<MergeEventCallback>>>,
+}
+
+impl MergeEventCallbackList {
+    pub fn subscribe(&self, callback: MergeEventCallback) -> MergeEventHandle {
+        let callback_arc = Arc::new(callback);
+        self.callbacks
+            .write()
+            .unwrap()
+            .push(Arc::downgrade(&callback_arc));
+        MergeEventHandle(callback_arc)
+    }
+
+    /// Calls all of the live callbacks, in the calling thread.
+    pub fn broadcast(&self, event: MergeEvent) {
+        let callbacks: Vec<Arc<MergeEventCallback>> = {
+            let mut callbacks_wlock = self.callbacks.write().unwrap();
+            callbacks_wlock.retain(|callback| callback.strong_count() > 0);
+            callbacks_wlock.iter().filter_map(Weak::upgrade).collect()
+        };
+        for callback in callbacks {
+            callback.call(&event);
+        }
+    }
+}
@@ -1,7 +1,9 @@
 use std::collections::HashSet;
 use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::sync::Arc;
 
-use crate::{Inventory, Opstamp, SegmentId, TrackedObject};
+use crate::{Inventory, Opstamp, SegmentId, TantivyError, TrackedObject};
 
 #[derive(Default)]
 pub(crate) struct MergeOperationInventory(Inventory<InnerMergeOperation>);
@@ -24,6 +26,14 @@ impl MergeOperationInventory {
         }
         segment_in_merge
     }
+
+    /// Returns a handle for each of the merge operations currently tracked.
+    pub fn handles(&self) -> Vec<MergeHandle> {
+        self.list()
+            .iter()
+            .map(|merge_op| merge_op.handle())
+            .collect()
+    }
 }
 
 /// A `MergeOperation` has two roles.
@@ -46,6 +56,16 @@ pub struct MergeOperation {
 pub(crate) struct InnerMergeOperation {
     target_opstamp: Opstamp,
     segment_ids: Vec<SegmentId>,
+    state: Arc<MergeState>,
+}
+
+impl InnerMergeOperation {
+    fn handle(&self) -> MergeHandle {
+        MergeHandle {
+            segment_ids: self.segment_ids.clone(),
+            state: self.state.clone(),
+        }
+    }
 }
 
 impl MergeOperation {
@@ -57,6 +77,7 @@ impl MergeOperation {
         let inner_merge_operation = InnerMergeOperation {
             target_opstamp,
             segment_ids,
+            state: Arc::default(),
         };
         MergeOperation {
             inner: inventory.track(inner_merge_operation),
@@ -70,4 +91,157 @@ impl MergeOperation {
     pub fn segment_ids(&self) -> &[SegmentId] {
         &self.inner.segment_ids[..]
     }
+
+    /// Returns a handle that makes it possible to follow the progress of
+    /// this merge operation, or to cancel it.
+    pub fn handle(&self) -> MergeHandle {
+        self.inner.handle()
+    }
+
+    pub(crate) fn state(&self) -> &Arc<MergeState> {
+        &self.inner.state
+    }
+}
+
+/// Phase a merge operation is currently in.
+///
+/// A merge goes through these phases in order.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MergePhase {
+    /// The merge has not started yet, or is applying pending deletes.
+    Pending,
+    /// Fieldnorms are being merged.
+    FieldNorms,
+    /// Postings (terms, doc ids, positions) are being merged.
+    Postings,
+    /// Stored fields are being merged.
+    Store,
+    /// Fast fields are being merged.
+    FastFields,
+    /// All data structures have been written.
+    Done,
+}
+
+impl MergePhase {
+    fn from_code(code: u8) -> MergePhase {
+        match code {
+            1 => MergePhase::FieldNorms,
+            2 => MergePhase::Postings,
+            3 => MergePhase::Store,
+            4 => MergePhase::FastFields,
+            5 => MergePhase::Done,
+            _ => MergePhase::Pending,
+        }
+    }
+
+    fn to_code(self) -> u8 {
+        match self {
+            MergePhase::Pending => 0,
+            MergePhase::FieldNorms => 1,
+            MergePhase::Postings => 2,
+            MergePhase::Store => 3,
+            MergePhase::FastFields => 4,
+            MergePhase::Done => 5,
+        }
+    }
+}
+
+/// Snapshot of the progress of a merge operation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MergeProgress {
+    /// Phase the merge is currently in.
+    pub phase: MergePhase,
+    /// Number of documents of the resulting segment that have been
+    /// written to the doc store so far.
+    pub docs_processed: u64,
+    /// Number of documents of the resulting segment.
+    ///
+    /// This is `0` until the merge has started.
+    pub total_docs: u64,
+}
+
+/// State shared between a merge operation (and the thread running it)
+/// and its `MergeHandle`s.
+#[derive(Default)]
+pub(crate) struct MergeState {
+    phase: AtomicU8,
+    docs_processed: AtomicU64,
+    total_docs: AtomicU64,
+    cancelled: AtomicBool,
+}
+
+impl MergeState {
+    pub fn set_phase(&self, phase: MergePhase) -> crate::Result<()> {
+        self.phase.store(phase.to_code(), Ordering::Relaxed);
+        self.check_cancelled()
+    }
+
+    pub fn set_total_docs(&self, total_docs: u64) {
+        self.total_docs.store(total_docs, Ordering::Relaxed);
+    }
+
+    pub fn record_docs_processed(&self, num_docs: u64) {
+        self.docs_processed.fetch_add(num_docs, Ordering::Relaxed);
+    }
+
+    /// Returns an error if the merge was cancelled.
+    #[inline]
+    pub fn check_cancelled(&self) -> crate::Result<()> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err(TantivyError::Cancelled);
+        }
+        Ok(())
+    }
+}
+
+/// Handle on a merge operation.
+///
+/// It makes it possible to follow the progress of a merge and to cancel it.
+/// Holding a handle does not prevent the merge from terminating.
+#[derive(Clone)]
+pub struct MergeHandle {
+    segment_ids: Vec<SegmentId>,
+    state: Arc<MergeState>,
+}
+
+impl MergeHandle {
+    /// Returns the ids of the segments being merged.
+    pub fn segment_ids(&self) -> &[SegmentId] {
+        &self.segment_ids[..]
+    }
+
+    /// Returns a snapshot of the progress of the merge.
+    pub fn progress(&self) -> MergeProgress {
+        MergeProgress {
+            phase: MergePhase::from_code(self.state.phase.load(Ordering::Relaxed)),
+            docs_processed: self.state.docs_processed.load(Ordering::Relaxed),
+            total_docs: self.state.total_docs.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Requests the cancellation of the merge.
+    ///
+    /// The merge stops at its next checkpoint, and the segments being
+    /// merged are left untouched. The files written so far are eventually
+    /// removed by the garbage collector.
+    ///
+    /// Cancelling a merge that is already done has no effect.
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true if the cancellation of the merge was requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+impl std::fmt::Debug for MergeHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MergeHandle")
+            .field("segment_ids", &self.segment_ids)
+            .field("progress", &self.progress())
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
 }
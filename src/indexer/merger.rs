@@ -1,13 +1,14 @@
 use std::sync::Arc;
 
 use columnar::{
-    ColumnType, ColumnValues, ColumnarReader, MergeRowOrder, RowAddr, ShuffleMergeOrder,
+    Column, ColumnType, ColumnValues, ColumnarReader, MergeRowOrder, RowAddr, ShuffleMergeOrder,
     StackMergeOrder,
 };
 use common::ReadOnlyBitSet;
 use itertools::Itertools;
 use measure_time::debug_time;
 
+use crate::codec::FastFieldsFormat;
 use crate::core::{Segment, SegmentReader};
 use crate::directory::WritePtr;
 use crate::docset::{DocSet, TERMINATED};
@@ -15,11 +16,17 @@ use crate::error::DataCorruption;
 use crate::fastfield::{AliveBitSet, FastFieldNotAvailableError};
 use crate::fieldnorm::{FieldNormReader, FieldNormReaders, FieldNormsSerializer, FieldNormsWriter};
 use crate::indexer::doc_id_mapping::{MappingType, SegmentDocIdMapping};
-use crate::indexer::SegmentSerializer;
+use crate::indexer::merge_operation::MergeState;
+use crate::indexer::{MergePhase, SegmentSerializer};
+use crate::payload::{PayloadReader, PayloadsSerializer};
+use crate::points::PointsSerializer;
 use crate::postings::{InvertedIndexSerializer, Postings, SegmentPostings};
-use crate::schema::{value_type_to_column_type, Field, FieldType, Schema};
+use crate::schema::{dense_vector_from_bytes, value_type_to_column_type, Field, FieldType, Schema};
 use crate::store::StoreWriter;
+use crate::suggest::{CompletionReader, CompletionsSerializer};
 use crate::termdict::{TermMerger, TermOrdinal};
+use crate::termvector::{TermVectorReader, TermVectorsSerializer};
+use crate::vector::{HnswBuilder, HnswSerializer};
 use crate::{
     DocAddress, DocId, IndexSettings, IndexSortByField, InvertedIndexReader, Order,
     SegmentComponent, SegmentOrdinal,
@@ -85,6 +92,7 @@ pub struct IndexMerger {
     schema: Schema,
     pub(crate) readers: Vec<SegmentReader>,
     max_doc: u32,
+    merge_state: Arc<MergeState>,
 }
 
 struct DeltaComputer {
@@ -203,9 +211,16 @@ impl IndexMerger {
             schema,
             readers,
             max_doc,
+            merge_state: Arc::default(),
         })
     }
 
+    /// Sets the state through which the progress of the merge is reported,
+    /// and through which the merge can be cancelled.
+    pub(crate) fn set_merge_state(&mut self, merge_state: Arc<MergeState>) {
+        self.merge_state = merge_state;
+    }
+
     fn sort_readers_by_min_sort_field(
         readers: Vec<SegmentReader>,
         sort_by_field: &IndexSortByField,
@@ -242,8 +257,15 @@ impl IndexMerger {
             let fieldnorms_readers: Vec<FieldNormReader> = self
                 .readers
                 .iter()
-                .map(|reader| reader.get_fieldnorms_reader(field))
-                .collect::<Result<_, _>>()?;
+                .map(|reader| {
+                    // Segments created before the field was added to the schema
+                    // have no fieldnorms for it.
+                    Ok(reader
+                        .fieldnorms_readers()
+                        .get_field(field)?
+                        .unwrap_or_else(|| FieldNormReader::constant(reader.max_doc(), 0)))
+                })
+                .collect::<crate::Result<_>>()?;
             for old_doc_addr in doc_id_mapping.iter_old_doc_addrs() {
                 let fieldnorms_reader = &fieldnorms_readers[old_doc_addr.segment_ord as usize];
                 let fieldnorm_id = fieldnorms_reader.fieldnorm_id(old_doc_addr.doc_id);
@@ -255,8 +277,202 @@ impl IndexMerger {
         Ok(())
     }
 
+    fn write_points(
+        &self,
+        mut points_serializer: PointsSerializer,
+        doc_id_mapping: &SegmentDocIdMapping,
+    ) -> crate::Result<()> {
+        let fields: Vec<Field> = self
+            .schema
+            .fields()
+            .filter(|(_, field_entry)| field_entry.has_points())
+            .map(|(field, _)| field)
+            .collect();
+        if !fields.is_empty() {
+            // For each segment, maps the old doc ids to the doc ids of the merged segment.
+            // Deleted documents do not have a new doc id.
+            let mut new_doc_ids: Vec<Vec<Option<DocId>>> = self
+                .readers
+                .iter()
+                .map(|reader| vec![None; reader.max_doc() as usize])
+                .collect();
+            for (new_doc_id, old_doc_addr) in doc_id_mapping.iter_old_doc_addrs().enumerate() {
+                new_doc_ids[old_doc_addr.segment_ord as usize][old_doc_addr.doc_id as usize] =
+                    Some(new_doc_id as DocId);
+            }
+            let mut points: Vec<(u64, DocId)> = Vec::new();
+            for field in fields {
+                points.clear();
+                for (reader, segment_new_doc_ids) in self.readers.iter().zip(&new_doc_ids) {
+                    let Some(points_reader) = reader.points_readers().get_field(field)? else {
+                        continue;
+                    };
+                    points.extend(points_reader.iter().filter_map(|(val, old_doc_id)| {
+                        segment_new_doc_ids[old_doc_id as usize].map(|new_doc_id| (val, new_doc_id))
+                    }));
+                }
+                points.sort_unstable();
+                points_serializer.serialize_field(field, &points)?;
+            }
+        }
+        points_serializer.close()?;
+        Ok(())
+    }
+
+    fn write_term_vectors(
+        &self,
+        mut term_vectors_serializer: TermVectorsSerializer,
+        doc_id_mapping: &SegmentDocIdMapping,
+    ) -> crate::Result<()> {
+        for (field, field_entry) in self.schema.fields() {
+            if !field_entry.has_term_vectors() {
+                continue;
+            }
+            // Segments created before the field was added to the schema
+            // have no term vectors for it.
+            let term_vector_readers: Vec<Option<TermVectorReader>> = self
+                .readers
+                .iter()
+                .map(|reader| reader.term_vector_readers().get_field(field))
+                .collect::<crate::Result<_>>()?;
+            let docs = doc_id_mapping.iter_old_doc_addrs().map(|old_doc_addr| {
+                term_vector_readers[old_doc_addr.segment_ord as usize]
+                    .as_ref()
+                    .map(|term_vector_reader| term_vector_reader.doc_bytes(old_doc_addr.doc_id))
+                    .unwrap_or(&[])
+            });
+            term_vectors_serializer.serialize_field(field, docs)?;
+        }
+        term_vectors_serializer.close()?;
+        Ok(())
+    }
+
+    fn write_payloads(
+        &self,
+        mut payloads_serializer: PayloadsSerializer,
+        doc_id_mapping: &SegmentDocIdMapping,
+    ) -> crate::Result<()> {
+        for (field, field_entry) in self.schema.fields() {
+            if !field_entry.has_payloads() {
+                continue;
+            }
+            // Segments created before the field was added to the schema
+            // have no payloads for it.
+            let payload_readers: Vec<Option<PayloadReader>> = self
+                .readers
+                .iter()
+                .map(|reader| reader.payload_readers().get_field(field))
+                .collect::<crate::Result<_>>()?;
+            let docs = doc_id_mapping.iter_old_doc_addrs().map(|old_doc_addr| {
+                payload_readers[old_doc_addr.segment_ord as usize]
+                    .as_ref()
+                    .map(|payload_reader| payload_reader.doc_bytes(old_doc_addr.doc_id))
+                    .unwrap_or(&[])
+            });
+            payloads_serializer.serialize_field(field, docs)?;
+        }
+        payloads_serializer.close()?;
+        Ok(())
+    }
+
+    fn write_completions(
+        &self,
+        mut completions_serializer: CompletionsSerializer,
+        doc_id_mapping: &SegmentDocIdMapping,
+    ) -> crate::Result<()> {
+        // The new document id of each document of each segment, or `None` if it is deleted.
+        let mut new_doc_ids: Vec<Vec<Option<DocId>>> = self
+            .readers
+            .iter()
+            .map(|reader| vec![None; reader.max_doc() as usize])
+            .collect();
+        for (new_doc_id, old_doc_addr) in doc_id_mapping.iter_old_doc_addrs().enumerate() {
+            new_doc_ids[old_doc_addr.segment_ord as usize][old_doc_addr.doc_id as usize] =
+                Some(new_doc_id as DocId);
+        }
+        for (field, field_entry) in self.schema.fields() {
+            if field_entry.completion_options().is_none() {
+                continue;
+            }
+            // Segments created before the field was added to the schema
+            // have no completions for it.
+            let completion_readers: Vec<Option<CompletionReader>> = self
+                .readers
+                .iter()
+                .map(|reader| reader.completion_readers().get_field(field))
+                .collect::<crate::Result<_>>()?;
+            let mut completions: Vec<(Vec<u8>, u64)> = Vec::new();
+            for (completion_reader, segment_new_doc_ids) in
+                completion_readers.iter().zip(&new_doc_ids)
+            {
+                let Some(completion_reader) = completion_reader else {
+                    continue;
+                };
+                completion_reader.for_each_key(|key, weight, old_doc_id| {
+                    if let Some(new_doc_id) = segment_new_doc_ids[old_doc_id as usize] {
+                        let mut key = key.to_vec();
+                        key.extend_from_slice(&new_doc_id.to_be_bytes());
+                        completions.push((key, weight));
+                    }
+                });
+            }
+            completions.sort_unstable();
+            completions_serializer.serialize_field(field, &completions)?;
+        }
+        completions_serializer.close()?;
+        Ok(())
+    }
+
+    fn write_hnsw(
+        &self,
+        mut hnsw_serializer: HnswSerializer,
+        doc_id_mapping: &SegmentDocIdMapping,
+    ) -> crate::Result<()> {
+        for (field, field_entry) in self.schema.fields() {
+            let FieldType::DenseVector(dense_vector_options) = field_entry.field_type() else {
+                continue;
+            };
+            let Some(hnsw_options) = dense_vector_options.hnsw_options() else {
+                continue;
+            };
+            // The graph is rebuilt from the vectors of the fast field columns, decoded once
+            // per distinct vector.
+            let mut segment_vectors = Vec::with_capacity(self.readers.len());
+            for reader in &self.readers {
+                let Some(bytes_column) = reader.fast_fields().bytes(field_entry.name())? else {
+                    segment_vectors.push(None);
+                    continue;
+                };
+                let mut vectors: Vec<Vec<f32>> = Vec::with_capacity(bytes_column.num_terms());
+                let mut stream = bytes_column.dictionary().stream()?;
+                while let Some((vector_bytes, _)) = stream.next() {
+                    let mut vector = Vec::new();
+                    dense_vector_from_bytes(vector_bytes, &mut vector);
+                    vectors.push(vector);
+                }
+                let ords: Column<u64> = bytes_column.ords().clone();
+                segment_vectors.push(Some((ords, vectors)));
+            }
+            let mut builder = HnswBuilder::new(*hnsw_options, dense_vector_options.dimension());
+            for (new_doc_id, old_doc_addr) in doc_id_mapping.iter_old_doc_addrs().enumerate() {
+                let Some((ords, vectors)) = &segment_vectors[old_doc_addr.segment_ord as usize]
+                else {
+                    continue;
+                };
+                for ord in ords.values_for_doc(old_doc_addr.doc_id) {
+                    builder.add_vector(new_doc_id as DocId, &vectors[ord as usize]);
+                }
+            }
+            builder.build();
+            hnsw_serializer.serialize_field(field, &builder)?;
+        }
+        hnsw_serializer.close()?;
+        Ok(())
+    }
+
     fn write_fast_fields(
         &self,
+        fast_fields_format: &dyn FastFieldsFormat,
         fast_field_wrt: &mut WritePtr,
         doc_id_mapping: SegmentDocIdMapping,
     ) -> crate::Result<()> {
@@ -268,7 +484,7 @@ impl IndexMerger {
             .map(|reader| reader.fast_fields().columnar())
             .collect();
         let merge_row_order = convert_to_merge_order(&columnars[..], doc_id_mapping);
-        columnar::merge_columnar(
+        fast_fields_format.merge(
             &columnars[..],
             &required_columns,
             merge_row_order,
@@ -453,6 +669,7 @@ impl IndexMerger {
     ) -> crate::Result<()> {
         debug_time!("write-postings-for-field");
         let mut positions_buffer: Vec<u32> = Vec::with_capacity(1_000);
+        let mut offsets_buffer: Vec<(u32, u32)> = Vec::new();
         let mut delta_computer = DeltaComputer::new();
 
         let mut max_term_ords: Vec<TermOrdinal> = Vec::new();
@@ -519,10 +736,14 @@ impl IndexMerger {
         let mut doc_id_and_positions = vec![];
 
         while merged_terms.advance() {
+            self.merge_state.check_cancelled()?;
             segment_postings_containing_the_term.clear();
             let term_bytes: &[u8] = merged_terms.key();
 
             let mut total_doc_freq = 0;
+            // The terms which exceeded the maximum document frequency of the field in one of
+            // the segments have lost their positions, and stay without them.
+            let mut has_positions = true;
 
             // Let's compute the list of non-empty posting lists
             for (segment_ord, term_info) in merged_terms.current_segment_ords_and_term_infos() {
@@ -538,6 +759,8 @@ impl IndexMerger {
                 };
                 if doc_freq > 0u32 {
                     total_doc_freq += doc_freq;
+                    has_positions &= !(segment_postings_option.has_positions()
+                        && term_info.positions_range.is_empty());
                     segment_postings_containing_the_term.push((segment_ord, segment_postings));
                 }
             }
@@ -552,7 +775,12 @@ impl IndexMerger {
                 continue;
             }
 
-            field_serializer.new_term(term_bytes, total_doc_freq)?;
+            if has_positions {
+                field_serializer.new_term(term_bytes, total_doc_freq)?;
+            } else {
+                field_serializer
+                    .new_term_without_freqs_and_positions(term_bytes, total_doc_freq)?;
+            }
 
             // We can now serialize this postings, by pushing each document to the
             // postings serializer.
@@ -569,6 +797,7 @@ impl IndexMerger {
                         // there is at least one document.
                         let term_freq = segment_postings.term_freq();
                         segment_postings.positions(&mut positions_buffer);
+                        segment_postings.offsets(&mut offsets_buffer);
                         // if doc_id_mapping exists, the doc_ids are reordered, they are
                         // not just stacked. The field serializer expects monotonically increasing
                         // doc_ids, so we collect and sort them first, before writing.
@@ -581,10 +810,16 @@ impl IndexMerger {
                                 remapped_doc_id,
                                 term_freq,
                                 positions_buffer.to_vec(),
+                                offsets_buffer.to_vec(),
                             ));
                         } else {
                             let delta_positions = delta_computer.compute_delta(&positions_buffer);
-                            field_serializer.write_doc(remapped_doc_id, term_freq, delta_positions);
+                            field_serializer.write_doc_with_offsets(
+                                remapped_doc_id,
+                                term_freq,
+                                delta_positions,
+                                &offsets_buffer,
+                            );
                         }
                     }
 
@@ -592,11 +827,16 @@ impl IndexMerger {
                 }
             }
             if !doc_id_mapping.is_trivial() {
-                doc_id_and_positions.sort_unstable_by_key(|&(doc_id, _, _)| doc_id);
+                doc_id_and_positions.sort_unstable_by_key(|&(doc_id, _, _, _)| doc_id);
 
-                for (doc_id, term_freq, positions) in &doc_id_and_positions {
+                for (doc_id, term_freq, positions, offsets) in &doc_id_and_positions {
                     let delta_positions = delta_computer.compute_delta(positions);
-                    field_serializer.write_doc(*doc_id, *term_freq, delta_positions);
+                    field_serializer.write_doc_with_offsets(
+                        *doc_id,
+                        *term_freq,
+                        delta_positions,
+                        offsets,
+                    );
                 }
                 doc_id_and_positions.clear();
             }
@@ -652,10 +892,12 @@ impl IndexMerger {
                 .collect();
 
             for old_doc_addr in doc_id_mapping.iter_old_doc_addrs() {
+                self.merge_state.check_cancelled()?;
                 let doc_bytes_it = &mut document_iterators[old_doc_addr.segment_ord as usize];
                 if let Some(doc_bytes_res) = doc_bytes_it.next() {
                     let doc_bytes = doc_bytes_res?;
                     store_writer.store_bytes(&doc_bytes)?;
+                    self.merge_state.record_docs_processed(1);
                 } else {
                     return Err(DataCorruption::comment_only(format!(
                         "unexpected missing document in docstore on merge, doc address \
@@ -686,11 +928,16 @@ impl IndexMerger {
                     || store_reader.decompressor() != store_writer.compressor().into()
                 {
                     for doc_bytes_res in store_reader.iter_raw(reader.alive_bitset()) {
+                        self.merge_state.check_cancelled()?;
                         let doc_bytes = doc_bytes_res?;
                         store_writer.store_bytes(&doc_bytes)?;
+                        self.merge_state.record_docs_processed(1);
                     }
                 } else {
+                    self.merge_state.check_cancelled()?;
                     store_writer.stack(store_reader)?;
+                    self.merge_state
+                        .record_docs_processed(u64::from(reader.num_docs()));
                 }
             }
         }
@@ -703,6 +950,7 @@ impl IndexMerger {
     /// # Returns
     /// The number of documents in the resulting segment.
     pub fn write(&self, mut serializer: SegmentSerializer) -> crate::Result<u32> {
+        self.merge_state.set_total_docs(u64::from(self.max_doc));
         let doc_id_mapping = if let Some(sort_by_field) = self.index_settings.sort_by_field.as_ref()
         {
             // If the documents are already sorted and stackable, we ignore the mapping and execute
@@ -716,6 +964,7 @@ impl IndexMerger {
             self.get_doc_id_from_concatenated_data()?
         };
         debug!("write-fieldnorms");
+        self.merge_state.set_phase(MergePhase::FieldNorms)?;
         if let Some(fieldnorms_serializer) = serializer.extract_fieldnorms_serializer() {
             self.write_fieldnorms(fieldnorms_serializer, &doc_id_mapping)?;
         }
@@ -724,6 +973,7 @@ impl IndexMerger {
             .segment()
             .open_read(SegmentComponent::FieldNorms)?;
         let fieldnorm_readers = FieldNormReaders::open(fieldnorm_data)?;
+        self.merge_state.set_phase(MergePhase::Postings)?;
         self.write_postings(
             serializer.get_postings_serializer(),
             fieldnorm_readers,
@@ -731,12 +981,36 @@ impl IndexMerger {
         )?;
 
         debug!("write-storagefields");
+        self.merge_state.set_phase(MergePhase::Store)?;
         self.write_storable_fields(serializer.get_store_writer(), &doc_id_mapping)?;
+        if let Some(term_vectors_serializer) = serializer.extract_term_vectors_serializer() {
+            self.write_term_vectors(term_vectors_serializer, &doc_id_mapping)?;
+        }
+        if let Some(payloads_serializer) = serializer.extract_payloads_serializer() {
+            self.write_payloads(payloads_serializer, &doc_id_mapping)?;
+        }
+        if let Some(completions_serializer) = serializer.extract_completions_serializer() {
+            self.write_completions(completions_serializer, &doc_id_mapping)?;
+        }
+        if let Some(hnsw_serializer) = serializer.extract_hnsw_serializer() {
+            self.write_hnsw(hnsw_serializer, &doc_id_mapping)?;
+        }
         debug!("write-fastfields");
-        self.write_fast_fields(serializer.get_fast_field_write(), doc_id_mapping)?;
+        self.merge_state.set_phase(MergePhase::FastFields)?;
+        if let Some(points_serializer) = serializer.extract_points_serializer() {
+            self.write_points(points_serializer, &doc_id_mapping)?;
+        }
+        let codec = serializer.segment().codec()?;
+        self.write_fast_fields(
+            codec.fast_fields_format(),
+            serializer.get_fast_field_write(),
+            doc_id_mapping,
+        )?;
 
         debug!("close-serializer");
         serializer.close()?;
+        // Setting the phase is the last checkpoint: the merge can still be cancelled here.
+        self.merge_state.set_phase(MergePhase::Done)?;
         Ok(self.max_doc)
     }
 }
@@ -1,3 +1,4 @@
+mod arrow_loader;
 pub mod delete_queue;
 
 pub mod doc_id_mapping;
@@ -5,13 +6,19 @@ mod doc_opstamp_mapping;
 mod flat_map_with_buffer;
 pub mod index_writer;
 mod index_writer_status;
+mod indexing_buffer_spill;
 mod log_merge_policy;
+mod memory_budget;
+mod merge_events;
 mod merge_operation;
 pub mod merge_policy;
 pub mod merger;
 mod merger_sorted_index_test;
 pub mod operation;
+#[cfg(feature = "parquet")]
+mod parquet_loader;
 pub mod prepared_commit;
+mod reindex;
 mod segment_entry;
 mod segment_manager;
 mod segment_register;
@@ -19,15 +26,20 @@ pub mod segment_serializer;
 pub mod segment_updater;
 mod segment_writer;
 mod stamper;
+mod version_tracker;
 
 use crossbeam_channel as channel;
 use smallvec::SmallVec;
 
 pub use self::index_writer::IndexWriter;
+pub use self::indexing_buffer_spill::IndexingBufferSpill;
 pub use self::log_merge_policy::LogMergePolicy;
-pub use self::merge_operation::MergeOperation;
+pub use self::memory_budget::MemoryBudget;
+pub use self::merge_events::{MergeEvent, MergeEventCallback, MergeEventHandle};
+pub use self::merge_operation::{MergeHandle, MergeOperation, MergePhase, MergeProgress};
 pub use self::merge_policy::{MergeCandidate, MergePolicy, NoMergePolicy};
 pub use self::prepared_commit::PreparedCommit;
+pub use self::reindex::{reindex, ReindexProgress};
 pub use self::segment_entry::SegmentEntry;
 pub use self::segment_manager::SegmentManager;
 pub use self::segment_serializer::SegmentSerializer;
@@ -0,0 +1,626 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use parquet::basic::{ConvertedType, LogicalType, TimeUnit, Type as PhysicalType};
+use parquet::column::reader::{ColumnReader, ColumnReaderImpl};
+use parquet::data_type::DataType;
+use parquet::errors::ParquetError;
+use parquet::file::reader::FileReader;
+use parquet::file::serialized_reader::SerializedFileReader;
+use parquet::schema::types::ColumnDescriptor;
+
+use crate::collector::{ArrowColumn, ArrowRecordBatch, ArrowValues};
+use crate::error::DataCorruption;
+use crate::schema::Schema;
+use crate::TantivyError;
+
+/// Maximum number of rows read from the columns of a row group at once.
+const BATCH_NUM_ROWS: usize = 8_192;
+
+const NANOS_PER_DAY: i64 = 86_400_000_000_000;
+
+/// How the values of a Parquet column are mapped onto an [`ArrowValues`].
+#[derive(Clone, Copy, Debug)]
+enum ColumnType {
+    Boolean,
+    Int32,
+    UInt32,
+    Int64,
+    UInt64,
+    Float,
+    Double,
+    /// Days since the epoch.
+    Date,
+    /// Timestamps counted in units of the given number of nanoseconds.
+    Timestamp(i64),
+    Int96Timestamp,
+    Utf8,
+    Binary,
+}
+
+impl ColumnType {
+    fn of(descriptor: &ColumnDescriptor) -> Option<ColumnType> {
+        let logical_type = descriptor.logical_type();
+        let converted_type = descriptor.converted_type();
+        let column_type = match descriptor.physical_type() {
+            PhysicalType::BOOLEAN => ColumnType::Boolean,
+            PhysicalType::INT32 => match (logical_type, converted_type) {
+                (Some(LogicalType::Date), _) | (_, ConvertedType::DATE) => ColumnType::Date,
+                (
+                    Some(LogicalType::Integer {
+                        is_signed: false, ..
+                    }),
+                    _,
+                )
+                | (_, ConvertedType::UINT_8 | ConvertedType::UINT_16 | ConvertedType::UINT_32) => {
+                    ColumnType::UInt32
+                }
+                (Some(LogicalType::Integer { .. }) | None, ConvertedType::NONE)
+                | (_, ConvertedType::INT_8 | ConvertedType::INT_16 | ConvertedType::INT_32) => {
+                    ColumnType::Int32
+                }
+                _ => return None,
+            },
+            PhysicalType::INT64 => match (logical_type, converted_type) {
+                (Some(LogicalType::Timestamp { unit, .. }), _) => match unit {
+                    TimeUnit::MILLIS(_) => ColumnType::Timestamp(1_000_000),
+                    TimeUnit::MICROS(_) => ColumnType::Timestamp(1_000),
+                    TimeUnit::NANOS(_) => ColumnType::Timestamp(1),
+                },
+                (_, ConvertedType::TIMESTAMP_MILLIS) => ColumnType::Timestamp(1_000_000),
+                (_, ConvertedType::TIMESTAMP_MICROS) => ColumnType::Timestamp(1_000),
+                (
+                    Some(LogicalType::Integer {
+                        is_signed: false, ..
+                    }),
+                    _,
+                )
+                | (_, ConvertedType::UINT_64) => ColumnType::UInt64,
+                (Some(LogicalType::Integer { .. }) | None, ConvertedType::NONE)
+                | (_, ConvertedType::INT_64) => ColumnType::Int64,
+                _ => return None,
+            },
+            PhysicalType::INT96 => ColumnType::Int96Timestamp,
+            PhysicalType::FLOAT => ColumnType::Float,
+            PhysicalType::DOUBLE => ColumnType::Double,
+            PhysicalType::BYTE_ARRAY => match (logical_type, converted_type) {
+                (Some(LogicalType::String | LogicalType::Enum | LogicalType::Json), _)
+                | (_, ConvertedType::UTF8 | ConvertedType::ENUM | ConvertedType::JSON) => {
+                    ColumnType::Utf8
+                }
+                (Some(LogicalType::Decimal { .. }), _) | (_, ConvertedType::DECIMAL) => {
+                    return None
+                }
+                _ => ColumnType::Binary,
+            },
+            PhysicalType::FIXED_LEN_BYTE_ARRAY => match (logical_type, converted_type) {
+                (Some(LogicalType::Decimal { .. } | LogicalType::Float16), _)
+                | (_, ConvertedType::DECIMAL) => return None,
+                _ => ColumnType::Binary,
+            },
+        };
+        Some(column_type)
+    }
+}
+
+/// A column chunk of the current row group.
+struct ColumnChunk {
+    name: String,
+    column_type: ColumnType,
+    max_def_level: i16,
+    reader: ColumnReader,
+}
+
+/// Reads the columns of a Parquet file into batches of rows.
+///
+/// Only the columns having a field of the same name in the schema are read. The columns are
+/// read value by value in batches of at most [`BATCH_NUM_ROWS`] rows, and each batch maps onto
+/// an [`ArrowRecordBatch`] that can be loaded with
+/// [`IndexWriter::add_record_batch()`](crate::IndexWriter::add_record_batch).
+pub(crate) struct ParquetBatchReader {
+    path: PathBuf,
+    file_reader: SerializedFileReader<File>,
+    /// The columns read, as `(column index, column type)`.
+    columns: Vec<(usize, ColumnType)>,
+    next_row_group: usize,
+    column_chunks: Vec<ColumnChunk>,
+    num_remaining_rows: usize,
+}
+
+impl ParquetBatchReader {
+    /// Opens a Parquet file, and checks that the columns having a field of the same name in
+    /// the schema can be loaded.
+    pub fn open(path: &Path, schema: &Schema) -> crate::Result<ParquetBatchReader> {
+        let file = File::open(path)?;
+        let file_reader =
+            SerializedFileReader::new(file).map_err(|err| parquet_error(path, err))?;
+        let mut columns = Vec::new();
+        for (column_ord, descriptor) in file_reader
+            .metadata()
+            .file_metadata()
+            .schema_descr()
+            .columns()
+            .iter()
+            .enumerate()
+        {
+            let column_path = descriptor.path().parts();
+            if schema.get_field(&column_path[0]).is_err() {
+                continue;
+            }
+            if column_path.len() > 1 || descriptor.max_rep_level() > 0 {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "The column {:?} is nested or repeated, only the flat columns can be loaded",
+                    descriptor.path().string()
+                )));
+            }
+            let column_type = ColumnType::of(descriptor).ok_or_else(|| {
+                TantivyError::InvalidArgument(format!(
+                    "The column {:?} has the unsupported type {:?} ({:?})",
+                    descriptor.name(),
+                    descriptor.physical_type(),
+                    descriptor
+                        .logical_type()
+                        .map_or(descriptor.converted_type().to_string(), |logical_type| {
+                            format!("{logical_type:?}")
+                        })
+                ))
+            })?;
+            columns.push((column_ord, column_type));
+        }
+        Ok(ParquetBatchReader {
+            path: path.to_path_buf(),
+            file_reader,
+            columns,
+            next_row_group: 0,
+            column_chunks: Vec::new(),
+            num_remaining_rows: 0,
+        })
+    }
+
+    /// Returns the next batch of rows, or `None` once all of the rows were read.
+    pub fn next_batch(&mut self) -> crate::Result<Option<ArrowRecordBatch>> {
+        while self.num_remaining_rows == 0 {
+            if self.next_row_group == self.file_reader.num_row_groups() {
+                return Ok(None);
+            }
+            self.open_row_group(self.next_row_group)
+                .map_err(|err| parquet_error(&self.path, err))?;
+            self.next_row_group += 1;
+        }
+        let num_rows = self.num_remaining_rows.min(BATCH_NUM_ROWS);
+        self.num_remaining_rows -= num_rows;
+        let mut columns = Vec::with_capacity(self.column_chunks.len());
+        for column_chunk in &mut self.column_chunks {
+            let column = read_column(column_chunk, num_rows).map_err(|err| match err {
+                ReadError::Parquet(err) => parquet_error(&self.path, err),
+                ReadError::Tantivy(err) => err,
+            })?;
+            columns.push((column_chunk.name.clone(), column));
+        }
+        ArrowRecordBatch::try_new(columns).map(Some)
+    }
+
+    fn open_row_group(&mut self, row_group_ord: usize) -> parquet::errors::Result<()> {
+        let row_group_reader = self.file_reader.get_row_group(row_group_ord)?;
+        let row_group_metadata = row_group_reader.metadata();
+        let num_rows = usize::try_from(row_group_metadata.num_rows())
+            .map_err(|_| ParquetError::General("Negative number of rows".to_string()))?;
+        let mut column_chunks = Vec::with_capacity(self.columns.len());
+        for &(column_ord, column_type) in &self.columns {
+            let descriptor = row_group_metadata.column(column_ord).column_descr();
+            column_chunks.push(ColumnChunk {
+                name: descriptor.name().to_string(),
+                column_type,
+                max_def_level: descriptor.max_def_level(),
+                reader: row_group_reader.get_column_reader(column_ord)?,
+            });
+        }
+        self.column_chunks = column_chunks;
+        self.num_remaining_rows = num_rows;
+        Ok(())
+    }
+}
+
+fn parquet_error(path: &Path, err: ParquetError) -> TantivyError {
+    DataCorruption::new(
+        path.to_path_buf(),
+        format!("Failed to read the Parquet file: {err}"),
+    )
+    .into()
+}
+
+enum ReadError {
+    Parquet(ParquetError),
+    Tantivy(TantivyError),
+}
+
+impl From<ParquetError> for ReadError {
+    fn from(err: ParquetError) -> ReadError {
+        ReadError::Parquet(err)
+    }
+}
+
+impl From<TantivyError> for ReadError {
+    fn from(err: TantivyError) -> ReadError {
+        ReadError::Tantivy(err)
+    }
+}
+
+/// The values of the non-null rows, and the validity bitmap of the rows.
+type ValuesAndValidity<T> = (Vec<T>, Option<Vec<u8>>);
+
+/// Reads the values of the next `num_rows` rows of a column chunk.
+///
+/// Returns the values of the non-null rows, and the validity bitmap of the rows if the column
+/// is optional.
+fn read_values<T: DataType>(
+    reader: &mut ColumnReaderImpl<T>,
+    max_def_level: i16,
+    num_rows: usize,
+) -> parquet::errors::Result<ValuesAndValidity<T::T>> {
+    let mut values = Vec::with_capacity(num_rows);
+    let mut def_levels = Vec::new();
+    let (num_records, _, _) = reader.read_records(
+        num_rows,
+        (max_def_level > 0).then_some(&mut def_levels),
+        None,
+        &mut values,
+    )?;
+    if num_records != num_rows {
+        return Err(ParquetError::General(format!(
+            "Expected {num_rows} rows, got {num_records}"
+        )));
+    }
+    if max_def_level == 0 {
+        return Ok((values, None));
+    }
+    let mut validity = vec![0u8; (num_rows + 7) / 8];
+    for (row, &def_level) in def_levels.iter().enumerate() {
+        if def_level == max_def_level {
+            validity[row / 8] |= 1 << (row % 8);
+        }
+    }
+    Ok((values, Some(validity)))
+}
+
+/// Spreads the values of the non-null rows over all of the rows, the null rows getting the
+/// default value.
+fn spread<T, V: Default>(
+    values: Vec<T>,
+    validity: Option<&[u8]>,
+    num_rows: usize,
+    mut convert: impl FnMut(T) -> Result<V, ReadError>,
+) -> Result<Vec<V>, ReadError> {
+    let mut values = values.into_iter();
+    let mut spread_values = Vec::with_capacity(num_rows);
+    for row in 0..num_rows {
+        let is_valid = validity.map_or(true, |validity| validity[row / 8] & (1 << (row % 8)) != 0);
+        let value = if is_valid {
+            let value = values
+                .next()
+                .ok_or_else(|| ParquetError::General("Missing values".to_string()))?;
+            convert(value)?
+        } else {
+            V::default()
+        };
+        spread_values.push(value);
+    }
+    Ok(spread_values)
+}
+
+/// Appends the bytes of each row to a buffer, and returns the offsets of the rows in the
+/// buffer.
+fn spread_bytes<'a>(
+    mut values: impl Iterator<Item = &'a [u8]>,
+    validity: Option<&[u8]>,
+    num_rows: usize,
+) -> Result<(Vec<i64>, Vec<u8>), ReadError> {
+    let mut offsets = Vec::with_capacity(num_rows + 1);
+    let mut data = Vec::new();
+    offsets.push(0);
+    for row in 0..num_rows {
+        let is_valid = validity.map_or(true, |validity| validity[row / 8] & (1 << (row % 8)) != 0);
+        if is_valid {
+            let value = values
+                .next()
+                .ok_or_else(|| ParquetError::General("Missing values".to_string()))?;
+            data.extend_from_slice(value);
+        }
+        offsets.push(data.len() as i64);
+    }
+    Ok((offsets, data))
+}
+
+fn read_column(column_chunk: &mut ColumnChunk, num_rows: usize) -> Result<ArrowColumn, ReadError> {
+    let max_def_level = column_chunk.max_def_level;
+    let to_timestamp_nanos = |value: i64, nanos_per_unit: i64| {
+        value.checked_mul(nanos_per_unit).ok_or_else(|| {
+            ReadError::Tantivy(TantivyError::InvalidArgument(format!(
+                "The timestamp {value} of the column {:?} is out of range",
+                column_chunk.name
+            )))
+        })
+    };
+    let (values, validity) = match (&mut column_chunk.reader, column_chunk.column_type) {
+        (ColumnReader::BoolColumnReader(reader), ColumnType::Boolean) => {
+            let (values, validity) = read_values(reader, max_def_level, num_rows)?;
+            let values = spread(values, validity.as_deref(), num_rows, Ok)?;
+            let mut bitmap = vec![0u8; (num_rows + 7) / 8];
+            for (row, value) in values.into_iter().enumerate() {
+                if value {
+                    bitmap[row / 8] |= 1 << (row % 8);
+                }
+            }
+            (ArrowValues::Boolean(bitmap), validity)
+        }
+        (ColumnReader::Int32ColumnReader(reader), column_type) => {
+            let (values, validity) = read_values(reader, max_def_level, num_rows)?;
+            let validity_ref = validity.as_deref();
+            let values = match column_type {
+                ColumnType::UInt32 => {
+                    ArrowValues::UInt32(spread(values, validity_ref, num_rows, |value| {
+                        Ok(value as u32)
+                    })?)
+                }
+                ColumnType::Date => ArrowValues::TimestampNanosecond(spread(
+                    values,
+                    validity_ref,
+                    num_rows,
+                    |value| to_timestamp_nanos(i64::from(value), NANOS_PER_DAY),
+                )?),
+                _ => ArrowValues::Int64(spread(values, validity_ref, num_rows, |value| {
+                    Ok(i64::from(value))
+                })?),
+            };
+            (values, validity)
+        }
+        (ColumnReader::Int64ColumnReader(reader), column_type) => {
+            let (values, validity) = read_values(reader, max_def_level, num_rows)?;
+            let validity_ref = validity.as_deref();
+            let values = match column_type {
+                ColumnType::UInt64 => {
+                    ArrowValues::UInt64(spread(values, validity_ref, num_rows, |value| {
+                        Ok(value as u64)
+                    })?)
+                }
+                ColumnType::Timestamp(nanos_per_unit) => ArrowValues::TimestampNanosecond(spread(
+                    values,
+                    validity_ref,
+                    num_rows,
+                    |value| to_timestamp_nanos(value, nanos_per_unit),
+                )?),
+                _ => ArrowValues::Int64(spread(values, validity_ref, num_rows, Ok)?),
+            };
+            (values, validity)
+        }
+        (ColumnReader::Int96ColumnReader(reader), _) => {
+            let (values, validity) = read_values(reader, max_def_level, num_rows)?;
+            let values = spread(values, validity.as_deref(), num_rows, |value| {
+                Ok(value.to_nanos())
+            })?;
+            (ArrowValues::TimestampNanosecond(values), validity)
+        }
+        (ColumnReader::FloatColumnReader(reader), _) => {
+            let (values, validity) = read_values(reader, max_def_level, num_rows)?;
+            let values = spread(values, validity.as_deref(), num_rows, Ok)?;
+            (ArrowValues::Float32(values), validity)
+        }
+        (ColumnReader::DoubleColumnReader(reader), _) => {
+            let (values, validity) = read_values(reader, max_def_level, num_rows)?;
+            let values = spread(values, validity.as_deref(), num_rows, Ok)?;
+            (ArrowValues::Float64(values), validity)
+        }
+        (ColumnReader::ByteArrayColumnReader(reader), column_type) => {
+            let (values, validity) = read_values(reader, max_def_level, num_rows)?;
+            let (offsets, data) = spread_bytes(
+                values.iter().map(|value| value.data()),
+                validity.as_deref(),
+                num_rows,
+            )?;
+            let values = match column_type {
+                ColumnType::Utf8 => ArrowValues::LargeUtf8 { offsets, data },
+                _ => ArrowValues::LargeBinary { offsets, data },
+            };
+            (values, validity)
+        }
+        (ColumnReader::FixedLenByteArrayColumnReader(reader), _) => {
+            let (values, validity) = read_values(reader, max_def_level, num_rows)?;
+            let (offsets, data) = spread_bytes(
+                values.iter().map(|value| value.data()),
+                validity.as_deref(),
+                num_rows,
+            )?;
+            (ArrowValues::LargeBinary { offsets, data }, validity)
+        }
+        (_, column_type) => {
+            return Err(ParquetError::General(format!(
+                "The column chunk does not match the column type {column_type:?}"
+            ))
+            .into())
+        }
+    };
+    Ok(ArrowColumn::try_new(num_rows, values, validity)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::sync::Arc;
+
+    use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int32Type, Int64Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{AllQuery, RangeQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, INDEXED, STORED, STRING};
+    use crate::{DateTime, Document, Index, Term};
+
+    const NUM_ROWS: usize = 20_000;
+
+    fn write_parquet_file(path: &std::path::Path, message_type: &str) {
+        let parquet_schema = Arc::new(parse_message_type(message_type).unwrap());
+        let properties = Arc::new(
+            WriterProperties::builder()
+                .set_max_row_group_size(12_000)
+                .build(),
+        );
+        let file = File::create(path).unwrap();
+        let mut writer = SerializedFileWriter::new(file, parquet_schema, properties).unwrap();
+        for row_group_rows in [0..12_000, 12_000..NUM_ROWS] {
+            let mut row_group_writer = writer.next_row_group().unwrap();
+            // id: required int64
+            let mut column_writer = row_group_writer.next_column().unwrap().unwrap();
+            let ids: Vec<i64> = row_group_rows.clone().map(|row| row as i64).collect();
+            column_writer
+                .typed::<Int64Type>()
+                .write_batch(&ids, None, None)
+                .unwrap();
+            column_writer.close().unwrap();
+            // name: optional utf8, null on the odd rows.
+            let mut column_writer = row_group_writer.next_column().unwrap().unwrap();
+            let def_levels: Vec<i16> = row_group_rows
+                .clone()
+                .map(|row| (row % 2 == 0) as i16)
+                .collect();
+            let names: Vec<ByteArray> = row_group_rows
+                .clone()
+                .filter(|row| row % 2 == 0)
+                .map(|row| ByteArray::from(format!("name{row}").as_str()))
+                .collect();
+            column_writer
+                .typed::<ByteArrayType>()
+                .write_batch(&names, Some(&def_levels), None)
+                .unwrap();
+            column_writer.close().unwrap();
+            // price: required double
+            let mut column_writer = row_group_writer.next_column().unwrap().unwrap();
+            let prices: Vec<f64> = row_group_rows.clone().map(|row| row as f64 / 2.0).collect();
+            column_writer
+                .typed::<DoubleType>()
+                .write_batch(&prices, None, None)
+                .unwrap();
+            column_writer.close().unwrap();
+            // day: optional date, only set on the first row of the row group.
+            let mut column_writer = row_group_writer.next_column().unwrap().unwrap();
+            let def_levels: Vec<i16> = row_group_rows
+                .clone()
+                .map(|row| (row % 12_000 == 0) as i16)
+                .collect();
+            column_writer
+                .typed::<Int32Type>()
+                .write_batch(&[1], Some(&def_levels), None)
+                .unwrap();
+            column_writer.close().unwrap();
+            // ignored: required int32, without field in the schema.
+            let mut column_writer = row_group_writer.next_column().unwrap().unwrap();
+            let values: Vec<i32> = row_group_rows.map(|row| row as i32).collect();
+            column_writer
+                .typed::<Int32Type>()
+                .write_batch(&values, None, None)
+                .unwrap();
+            column_writer.close().unwrap();
+            row_group_writer.close().unwrap();
+        }
+        writer.close().unwrap();
+    }
+
+    #[test]
+    fn test_add_parquet_file() -> crate::Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let path = temp_dir.path().join("data.parquet");
+        write_parquet_file(
+            &path,
+            "message schema {
+                REQUIRED INT64 id;
+                OPTIONAL BYTE_ARRAY name (UTF8);
+                REQUIRED DOUBLE price;
+                OPTIONAL INT32 day (DATE);
+                REQUIRED INT32 ignored;
+            }",
+        );
+
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", INDEXED | FAST);
+        let name = schema_builder.add_text_field("name", STRING | STORED);
+        schema_builder.add_f64_field("price", INDEXED | FAST);
+        let day = schema_builder.add_date_field("day", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_parquet_file(&path)?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), NUM_ROWS as u64);
+        let query = TermQuery::new(Term::from_field_u64(id, 12_346), IndexRecordOption::Basic);
+        let (_, doc_address) = searcher.search(&query, &TopDocs::with_limit(1))?[0];
+        let doc: Document = searcher.doc(doc_address)?;
+        assert_eq!(doc.get_first(name).unwrap().as_text(), Some("name12346"));
+        let query = TermQuery::new(Term::from_field_u64(id, 12_347), IndexRecordOption::Basic);
+        let (_, doc_address) = searcher.search(&query, &TopDocs::with_limit(1))?[0];
+        let doc: Document = searcher.doc(doc_address)?;
+        assert!(doc.get_first(name).is_none());
+        let query = RangeQuery::new_f64("price".to_string(), 0.0..10.0);
+        assert_eq!(searcher.search(&query, &Count)?, 20);
+        let query = TermQuery::new(
+            Term::from_field_date(day, DateTime::from_timestamp_secs(86_400)),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 2);
+        assert_eq!(searcher.search(&AllQuery, &Count)?, NUM_ROWS);
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_parquet_file_errors() -> crate::Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field("id", INDEXED);
+        schema_builder.add_text_field("name", STRING);
+        schema_builder.add_u64_field("tags", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let index_writer = index.writer_for_tests()?;
+
+        // The string column cannot be loaded into a u64 field.
+        let path = temp_dir.path().join("mismatch.parquet");
+        write_parquet_file(
+            &path,
+            "message schema {
+                REQUIRED INT64 other;
+                OPTIONAL BYTE_ARRAY id (UTF8);
+                REQUIRED DOUBLE price;
+                OPTIONAL INT32 day (DATE);
+                REQUIRED INT32 ignored;
+            }",
+        );
+        assert!(matches!(
+            index_writer.add_parquet_file(&path),
+            Err(crate::TantivyError::InvalidArgument(_))
+        ));
+
+        // The repeated columns are not supported.
+        let path = temp_dir.path().join("repeated.parquet");
+        let parquet_schema =
+            Arc::new(parse_message_type("message schema { REPEATED INT64 tags; }").unwrap());
+        let writer = SerializedFileWriter::new(
+            File::create(&path)?,
+            parquet_schema,
+            Arc::new(WriterProperties::builder().build()),
+        )
+        .unwrap();
+        writer.close().unwrap();
+        assert!(matches!(
+            index_writer.add_parquet_file(&path),
+            Err(crate::TantivyError::InvalidArgument(_))
+        ));
+
+        let path = temp_dir.path().join("invalid.parquet");
+        std::fs::write(&path, b"not a parquet file")?;
+        assert!(matches!(
+            index_writer.add_parquet_file(&path),
+            Err(crate::TantivyError::DataCorruption(_))
+        ));
+        Ok(())
+    }
+}
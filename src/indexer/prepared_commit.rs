@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use super::IndexWriter;
 use crate::{FutureResult, Opstamp};
 
@@ -5,6 +7,7 @@ use crate::{FutureResult, Opstamp};
 pub struct PreparedCommit<'a> {
     index_writer: &'a mut IndexWriter,
     payload: Option<String>,
+    metadata: BTreeMap<String, String>,
     opstamp: Opstamp,
 }
 
@@ -13,6 +16,7 @@ impl<'a> PreparedCommit<'a> {
         PreparedCommit {
             index_writer,
             payload: None,
+            metadata: BTreeMap::new(),
             opstamp,
         }
     }
@@ -27,6 +31,15 @@ impl<'a> PreparedCommit<'a> {
         self.payload = Some(payload.to_string())
     }
 
+    /// Adds a key-value pair to the metadata of the commit.
+    ///
+    /// The metadata is persisted in the `meta.json` file, and can be read back
+    /// using [`Index::commit_metadata()`](crate::Index::commit_metadata).
+    /// Setting the same key twice overwrites the previous value.
+    pub fn set_metadata(&mut self, key: &str, value: &str) {
+        self.metadata.insert(key.to_string(), value.to_string());
+    }
+
     /// Rollbacks any change.
     pub fn abort(self) -> crate::Result<Opstamp> {
         self.index_writer.rollback()
@@ -45,8 +58,10 @@ impl<'a> PreparedCommit<'a> {
     /// At this point deletes have not been flushed yet.
     pub fn commit_future(self) -> FutureResult<Opstamp> {
         info!("committing {}", self.opstamp);
-        self.index_writer
-            .segment_updater()
-            .schedule_commit(self.opstamp, self.payload)
+        self.index_writer.segment_updater().schedule_commit(
+            self.opstamp,
+            self.payload,
+            self.metadata,
+        )
     }
 }
@@ -0,0 +1,187 @@
+use std::collections::BTreeSet;
+
+use crate::schema::{Field, Schema};
+use crate::{IndexWriter, Searcher, SegmentId, TantivyError};
+
+/// The key of the commit metadata listing the source segments already reindexed.
+const REINDEXED_SEGMENTS_METADATA_KEY: &str = "tantivy.reindexed_segments";
+
+/// The number of documents and segments processed by a call to [`reindex`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReindexProgress {
+    /// The number of segments reindexed by the call.
+    pub num_segments_reindexed: usize,
+    /// The number of segments skipped because a previous call already reindexed them.
+    pub num_segments_skipped: usize,
+    /// The number of documents reindexed by the call.
+    pub num_docs_reindexed: u64,
+}
+
+/// Maps the fields of the source schema to the fields of the target schema with the same
+/// name, checking that their values have the same type.
+fn map_fields(source_schema: &Schema, target_schema: &Schema) -> crate::Result<Vec<Option<Field>>> {
+    source_schema
+        .fields()
+        .map(|(_, source_field_entry)| {
+            let Ok(target_field) = target_schema.get_field(source_field_entry.name()) else {
+                return Ok(None);
+            };
+            let target_field_entry = target_schema.get_field_entry(target_field);
+            let source_type = source_field_entry.field_type().value_type();
+            let target_type = target_field_entry.field_type().value_type();
+            if source_type != target_type {
+                return Err(TantivyError::SchemaError(format!(
+                    "The field {:?} cannot be reindexed from {source_type:?} to {target_type:?}",
+                    source_field_entry.name()
+                )));
+            }
+            Ok(Some(target_field))
+        })
+        .collect()
+}
+
+fn parse_reindexed_segments(metadata_value: &str) -> crate::Result<BTreeSet<SegmentId>> {
+    metadata_value
+        .split(',')
+        .filter(|segment_id| !segment_id.is_empty())
+        .map(|segment_id| {
+            SegmentId::from_uuid_string(segment_id).map_err(|err| {
+                TantivyError::InternalError(format!(
+                    "Invalid segment id {segment_id:?} in the reindex metadata: {err:?}"
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Rewrites the documents of the `source_searcher` into the index of the `target_writer`,
+/// one segment at a time, using their stored fields as the source.
+///
+/// This makes it possible to change the schema or the tokenizers of an index without
+/// replaying the corpus from its original system of record: the target index is created
+/// with the new schema and tokenizers, and the documents are indexed again from the source
+/// index.
+///
+/// The values of a field are copied to the field of the target schema with the same name. The
+/// fields missing from the target schema are dropped, and the fields which are not stored
+/// in the source index are left empty. An error is returned if a field has a different value
+/// type in the target schema.
+///
+/// The target writer commits after each source segment, and records the source segments
+/// reindexed so far under the `tantivy.reindexed_segments` key of the
+/// [commit metadata](crate::Index::commit_metadata). If the reindexing is interrupted, calling
+/// `reindex` again with a searcher of the same source segments resumes it after the last
+/// committed segment. The source index should therefore not be modified, nor merged, until the
+/// reindexing completes.
+pub fn reindex(
+    source_searcher: &Searcher,
+    target_writer: &mut IndexWriter,
+) -> crate::Result<ReindexProgress> {
+    let source_schema = source_searcher.schema();
+    let target_schema = target_writer.index().schema();
+    let field_mapping = map_fields(source_schema, &target_schema)?;
+    let commit_metadata = target_writer.index().commit_metadata()?;
+    let mut reindexed_segments = match commit_metadata.get(REINDEXED_SEGMENTS_METADATA_KEY) {
+        Some(metadata_value) => parse_reindexed_segments(metadata_value)?,
+        None => BTreeSet::new(),
+    };
+    let mut progress = ReindexProgress::default();
+    for segment_reader in source_searcher.segment_readers() {
+        if reindexed_segments.contains(&segment_reader.segment_id()) {
+            progress.num_segments_skipped += 1;
+            continue;
+        }
+        let store_reader = segment_reader.get_store_reader(1)?;
+        for doc_res in store_reader.iter(segment_reader.alive_bitset()) {
+            let source_doc = doc_res?;
+            let mut target_doc = crate::Document::new();
+            for field_value in source_doc.field_values() {
+                if let Some(target_field) = field_mapping[field_value.field().field_id() as usize] {
+                    target_doc.add_field_value(target_field, field_value.value().clone());
+                }
+            }
+            target_writer.add_document(target_doc)?;
+            progress.num_docs_reindexed += 1;
+        }
+        reindexed_segments.insert(segment_reader.segment_id());
+        let metadata_value = reindexed_segments
+            .iter()
+            .map(SegmentId::uuid_string)
+            .collect::<Vec<_>>()
+            .join(",");
+        let mut prepared_commit = target_writer.prepare_commit()?;
+        prepared_commit.set_metadata(REINDEXED_SEGMENTS_METADATA_KEY, &metadata_value);
+        prepared_commit.commit()?;
+        progress.num_segments_reindexed += 1;
+    }
+    Ok(progress)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{reindex, ReindexProgress};
+    use crate::collector::Count;
+    use crate::query::QueryParser;
+    use crate::schema::{Schema, STORED, STRING, TEXT};
+    use crate::{doc, Index, Term};
+
+    #[test]
+    fn test_reindex() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STRING | STORED);
+        let body = schema_builder.add_text_field("body", STRING | STORED);
+        schema_builder.add_u64_field("dropped", STORED);
+        let source_index = Index::create_in_ram(schema_builder.build());
+        let mut source_writer = source_index.writer_for_tests()?;
+        source_writer.add_document(doc!(title => "Hello World", body => "First Body"))?;
+        source_writer.add_document(doc!(title => "Deleted", body => "Body"))?;
+        source_writer.commit()?;
+        source_writer.add_document(doc!(title => "Second", body => "Hello Again"))?;
+        source_writer.delete_term(Term::from_field_text(title, "Deleted"));
+        source_writer.commit()?;
+        let source_searcher = source_index.reader()?.searcher();
+        assert_eq!(source_searcher.segment_readers().len(), 2);
+
+        // The target tokenizes the text fields.
+        let mut target_schema_builder = Schema::builder();
+        let target_title = target_schema_builder.add_text_field("title", TEXT | STORED);
+        let target_body = target_schema_builder.add_text_field("body", TEXT | STORED);
+        let target_index = Index::create_in_ram(target_schema_builder.build());
+        let mut target_writer = target_index.writer_for_tests()?;
+        let progress = reindex(&source_searcher, &mut target_writer)?;
+        assert_eq!(
+            progress,
+            ReindexProgress {
+                num_segments_reindexed: 2,
+                num_segments_skipped: 0,
+                num_docs_reindexed: 2,
+            }
+        );
+
+        let target_searcher = target_index.reader()?.searcher();
+        assert_eq!(target_searcher.num_docs(), 2);
+        let query_parser = QueryParser::for_index(&target_index, vec![target_title, target_body]);
+        let query = query_parser.parse_query("hello")?;
+        assert_eq!(target_searcher.search(query.as_ref(), &Count)?, 2);
+
+        // Reindexing again resumes after the reindexed segments.
+        let progress = reindex(&source_searcher, &mut target_writer)?;
+        assert_eq!(progress.num_segments_skipped, 2);
+        assert_eq!(progress.num_docs_reindexed, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reindex_incompatible_field_type() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("field", STRING | STORED);
+        let source_index = Index::create_in_ram(schema_builder.build());
+        let mut target_schema_builder = Schema::builder();
+        target_schema_builder.add_u64_field("field", STORED);
+        let target_index = Index::create_in_ram(target_schema_builder.build());
+        let mut target_writer = target_index.writer_for_tests()?;
+        let source_searcher = source_index.reader()?.searcher();
+        assert!(reindex(&source_searcher, &mut target_writer).is_err());
+        Ok(())
+    }
+}
@@ -134,6 +134,15 @@ impl SegmentManager {
         registers_lock.uncommitted.clear();
     }
 
+    /// Removes the given segments, committed or uncommitted.
+    pub(crate) fn remove_segments(&self, segment_ids: &[SegmentId]) {
+        let mut registers_lock = self.write();
+        for segment_id in segment_ids {
+            registers_lock.committed.remove_segment(segment_id);
+            registers_lock.uncommitted.remove_segment(segment_id);
+        }
+    }
+
     pub fn commit(&self, segment_entries: Vec<SegmentEntry>) {
         let mut registers_lock = self.write();
         registers_lock.committed.clear();
@@ -3,8 +3,13 @@ use common::TerminatingWrite;
 use crate::core::{Segment, SegmentComponent};
 use crate::directory::WritePtr;
 use crate::fieldnorm::FieldNormsSerializer;
+use crate::payload::PayloadsSerializer;
+use crate::points::PointsSerializer;
 use crate::postings::InvertedIndexSerializer;
 use crate::store::StoreWriter;
+use crate::suggest::CompletionsSerializer;
+use crate::termvector::TermVectorsSerializer;
+use crate::vector::HnswSerializer;
 
 /// Segment serializer is in charge of laying out on disk
 /// the data accumulated and sorted by the `SegmentWriter`.
@@ -13,6 +18,11 @@ pub struct SegmentSerializer {
     pub(crate) store_writer: StoreWriter,
     fast_field_write: WritePtr,
     fieldnorms_serializer: Option<FieldNormsSerializer>,
+    points_serializer: Option<PointsSerializer>,
+    term_vectors_serializer: Option<TermVectorsSerializer>,
+    payloads_serializer: Option<PayloadsSerializer>,
+    completions_serializer: Option<CompletionsSerializer>,
+    hnsw_serializer: Option<HnswSerializer>,
     postings_serializer: InvertedIndexSerializer,
 }
 
@@ -53,12 +63,32 @@ impl SegmentSerializer {
         let fieldnorms_write = segment.open_write(SegmentComponent::FieldNorms)?;
         let fieldnorms_serializer = FieldNormsSerializer::from_write(fieldnorms_write)?;
 
+        let points_write = segment.open_write(SegmentComponent::Points)?;
+        let points_serializer = PointsSerializer::from_write(points_write)?;
+
+        let term_vectors_write = segment.open_write(SegmentComponent::TermVectors)?;
+        let term_vectors_serializer = TermVectorsSerializer::from_write(term_vectors_write)?;
+
+        let payloads_write = segment.open_write(SegmentComponent::Payloads)?;
+        let payloads_serializer = PayloadsSerializer::from_write(payloads_write)?;
+
+        let completions_write = segment.open_write(SegmentComponent::Completions)?;
+        let completions_serializer = CompletionsSerializer::from_write(completions_write)?;
+
+        let hnsw_write = segment.open_write(SegmentComponent::Hnsw)?;
+        let hnsw_serializer = HnswSerializer::from_write(hnsw_write)?;
+
         let postings_serializer = InvertedIndexSerializer::open(&mut segment)?;
         Ok(SegmentSerializer {
             segment,
             store_writer,
             fast_field_write,
             fieldnorms_serializer: Some(fieldnorms_serializer),
+            points_serializer: Some(points_serializer),
+            term_vectors_serializer: Some(term_vectors_serializer),
+            payloads_serializer: Some(payloads_serializer),
+            completions_serializer: Some(completions_serializer),
+            hnsw_serializer: Some(hnsw_serializer),
             postings_serializer,
         })
     }
@@ -93,6 +123,41 @@ impl SegmentSerializer {
         self.fieldnorms_serializer.take()
     }
 
+    /// Extract the points serializer.
+    ///
+    /// Note the points serializer can only be extracted once.
+    pub fn extract_points_serializer(&mut self) -> Option<PointsSerializer> {
+        self.points_serializer.take()
+    }
+
+    /// Extract the term vectors serializer.
+    ///
+    /// Note the term vectors serializer can only be extracted once.
+    pub fn extract_term_vectors_serializer(&mut self) -> Option<TermVectorsSerializer> {
+        self.term_vectors_serializer.take()
+    }
+
+    /// Extract the payloads serializer.
+    ///
+    /// Note the payloads serializer can only be extracted once.
+    pub fn extract_payloads_serializer(&mut self) -> Option<PayloadsSerializer> {
+        self.payloads_serializer.take()
+    }
+
+    /// Extract the completions serializer.
+    ///
+    /// Note the completions serializer can only be extracted once.
+    pub fn extract_completions_serializer(&mut self) -> Option<CompletionsSerializer> {
+        self.completions_serializer.take()
+    }
+
+    /// Extract the HNSW serializer.
+    ///
+    /// Note the HNSW serializer can only be extracted once.
+    pub fn extract_hnsw_serializer(&mut self) -> Option<HnswSerializer> {
+        self.hnsw_serializer.take()
+    }
+
     /// Accessor to the `StoreWriter`.
     pub fn get_store_writer(&mut self) -> &mut StoreWriter {
         &mut self.store_writer
@@ -103,6 +168,21 @@ impl SegmentSerializer {
         if let Some(fieldnorms_serializer) = self.extract_fieldnorms_serializer() {
             fieldnorms_serializer.close()?;
         }
+        if let Some(points_serializer) = self.extract_points_serializer() {
+            points_serializer.close()?;
+        }
+        if let Some(term_vectors_serializer) = self.extract_term_vectors_serializer() {
+            term_vectors_serializer.close()?;
+        }
+        if let Some(payloads_serializer) = self.extract_payloads_serializer() {
+            payloads_serializer.close()?;
+        }
+        if let Some(completions_serializer) = self.extract_completions_serializer() {
+            completions_serializer.close()?;
+        }
+        if let Some(hnsw_serializer) = self.extract_hnsw_serializer() {
+            hnsw_serializer.close()?;
+        }
         self.fast_field_write.terminate()?;
         self.postings_serializer.close()?;
         self.store_writer.close()?;
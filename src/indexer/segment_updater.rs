@@ -1,10 +1,11 @@
 use std::borrow::BorrowMut;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::io::Write;
 use std::ops::Deref;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
+use std::time::Instant;
 
 use fail::fail_point;
 use rayon::{ThreadPool, ThreadPoolBuilder};
@@ -17,15 +18,19 @@ use crate::directory::{Directory, DirectoryClone, GarbageCollectionResult};
 use crate::fastfield::AliveBitSet;
 use crate::indexer::delete_queue::DeleteCursor;
 use crate::indexer::index_writer::advance_deletes;
-use crate::indexer::merge_operation::MergeOperationInventory;
+use crate::indexer::merge_events::{
+    MergeEvent, MergeEventCallback, MergeEventCallbackList, MergeEventHandle,
+};
+use crate::indexer::merge_operation::{MergeOperationInventory, MergeState};
 use crate::indexer::merger::IndexMerger;
 use crate::indexer::segment_manager::SegmentsStatus;
 use crate::indexer::stamper::Stamper;
 use crate::indexer::{
-    DefaultMergePolicy, MergeCandidate, MergeOperation, MergePolicy, SegmentEntry,
+    DefaultMergePolicy, MergeCandidate, MergeHandle, MergeOperation, MergePolicy, SegmentEntry,
     SegmentSerializer,
 };
-use crate::{FutureResult, Opstamp};
+use crate::metrics::enter_span;
+use crate::{FutureResult, Opstamp, TantivyError};
 
 const NUM_MERGE_THREADS: usize = 4;
 
@@ -91,6 +96,7 @@ fn merge(
     index: &Index,
     mut segment_entries: Vec<SegmentEntry>,
     target_opstamp: Opstamp,
+    merge_state: &Arc<MergeState>,
 ) -> crate::Result<Option<SegmentEntry>> {
     let num_docs = segment_entries
         .iter()
@@ -99,12 +105,16 @@ fn merge(
     if num_docs == 0 {
         return Ok(None);
     }
+    let num_segments = segment_entries.len();
+    let _span_guard = enter_span!("tantivy.merge", num_segments, num_docs);
+    let start = Instant::now();
 
     // first we need to apply deletes to our segment.
     let merged_segment = index.new_segment();
 
     // First we apply all of the delete to the merged segment, up to the target opstamp.
     for segment_entry in &mut segment_entries {
+        merge_state.check_cancelled()?;
         let segment = index.segment(segment_entry.meta().clone());
         advance_deletes(segment, segment_entry, target_opstamp)?;
     }
@@ -117,8 +127,9 @@ fn merge(
         .collect();
 
     // An IndexMerger is like a "view" of our merged segments.
-    let merger: IndexMerger =
+    let mut merger: IndexMerger =
         IndexMerger::open(index.schema(), index.settings().clone(), &segments[..])?;
+    merger.set_merge_state(merge_state.clone());
 
     // ... we just serialize this index merger in our new segment to merge the segments.
     let segment_serializer = SegmentSerializer::for_segment(merged_segment.clone(), true)?;
@@ -128,6 +139,9 @@ fn merge(
     let merged_segment_id = merged_segment.id();
 
     let segment_meta = index.new_segment_meta(merged_segment_id, num_docs);
+    if let Some(metrics_recorder) = index.metrics_recorder() {
+        metrics_recorder.record_merge(num_segments, num_docs, start.elapsed());
+    }
     Ok(Some(SegmentEntry::new(segment_meta, delete_cursor, None)))
 }
 
@@ -250,6 +264,8 @@ pub fn merge_filtered_segments<T: Into<Box<dyn Directory>>>(
         schema: target_schema,
         opstamp: 0u64,
         payload: Some(stats),
+        metadata: BTreeMap::new(),
+        tokenizers: segments[0].index().tokenizers().configs(),
     };
 
     // save the meta.json
@@ -275,6 +291,7 @@ pub(crate) struct InnerSegmentUpdater {
     killed: AtomicBool,
     stamper: Stamper,
     merge_operations: MergeOperationInventory,
+    merge_event_callbacks: MergeEventCallbackList,
 }
 
 impl SegmentUpdater {
@@ -314,6 +331,7 @@ impl SegmentUpdater {
             killed: AtomicBool::new(false),
             stamper,
             merge_operations: Default::default(),
+            merge_event_callbacks: Default::default(),
         })))
     }
 
@@ -352,6 +370,16 @@ impl SegmentUpdater {
         })
     }
 
+    pub(crate) fn schedule_remove_segments(&self, segment_ids: Vec<SegmentId>) -> FutureResult<()> {
+        let segment_updater = self.clone();
+        self.schedule_task(move || {
+            segment_updater
+                .segment_manager
+                .remove_segments(&segment_ids);
+            Ok(())
+        })
+    }
+
     /// Orders `SegmentManager` to remove all segments
     pub(crate) fn remove_all_segments(&self) {
         self.segment_manager.remove_all_segments();
@@ -382,6 +410,7 @@ impl SegmentUpdater {
         &self,
         opstamp: Opstamp,
         commit_message: Option<String>,
+        commit_metadata: BTreeMap<String, String>,
     ) -> crate::Result<()> {
         if self.is_alive() {
             let index = &self.index;
@@ -408,6 +437,8 @@ impl SegmentUpdater {
                 schema: index.schema(),
                 opstamp,
                 payload: commit_message,
+                metadata: commit_metadata,
+                tokenizers: index.tokenizers().configs(),
             };
             // TODO add context to the error.
             save_metas(&index_meta, directory.box_clone().borrow_mut())?;
@@ -440,23 +471,44 @@ impl SegmentUpdater {
         &self,
         opstamp: Opstamp,
         payload: Option<String>,
+        metadata: BTreeMap<String, String>,
     ) -> FutureResult<Opstamp> {
         let segment_updater: SegmentUpdater = self.clone();
         self.schedule_task(move || {
+            let _span_guard = enter_span!("tantivy.commit", opstamp);
+            let start = Instant::now();
             let segment_entries = segment_updater.purge_deletes(opstamp)?;
             segment_updater.segment_manager.commit(segment_entries);
-            segment_updater.save_metas(opstamp, payload)?;
+            segment_updater.save_metas(opstamp, payload, metadata)?;
+            // The near real time readers go back to the segments of the `meta.json` file.
+            drop(segment_updater.index.nrt_segments().publish(None));
+            if let Some(metrics_recorder) = segment_updater.index.metrics_recorder() {
+                metrics_recorder.record_commit(opstamp, start.elapsed());
+            }
             let _ = garbage_collect_files(segment_updater.clone());
             segment_updater.consider_merge_options();
             Ok(opstamp)
         })
     }
 
+    /// Returns the committed and uncommitted segments, with the deletes up to the target opstamp
+    /// applied, without committing them.
+    pub(crate) fn schedule_refresh(&self, opstamp: Opstamp) -> FutureResult<Vec<SegmentMeta>> {
+        let segment_updater: SegmentUpdater = self.clone();
+        self.schedule_task(move || {
+            let segment_entries = segment_updater.purge_deletes(opstamp)?;
+            Ok(segment_entries
+                .iter()
+                .map(|segment_entry| segment_entry.meta().clone())
+                .collect())
+        })
+    }
+
     fn store_meta(&self, index_meta: &IndexMeta) {
         *self.active_index_meta.write().unwrap() = Arc::new(index_meta.clone());
     }
 
-    fn load_meta(&self) -> Arc<IndexMeta> {
+    pub(crate) fn load_meta(&self) -> Arc<IndexMeta> {
         self.active_index_meta.read().unwrap().clone()
     }
 
@@ -516,15 +568,44 @@ impl SegmentUpdater {
             // Its lifetime is used to track how many merging thread are currently running,
             // as well as which segment is currently in merge and therefore should not be
             // candidate for another merge.
+            let merge_handle = merge_operation.handle();
+            segment_updater
+                .merge_event_callbacks
+                .broadcast(MergeEvent::Started(merge_handle.clone()));
             match merge(
                 &segment_updater.index,
                 segment_entries,
                 merge_operation.target_opstamp(),
+                merge_operation.state(),
             ) {
                 Ok(after_merge_segment_entry) => {
                     let res = segment_updater.end_merge(merge_operation, after_merge_segment_entry);
+                    let event = match &res {
+                        Ok(merged_segment) => MergeEvent::Finished {
+                            handle: merge_handle,
+                            merged_segment: merged_segment.clone(),
+                        },
+                        Err(error) => MergeEvent::Failed {
+                            handle: merge_handle,
+                            error: error.clone(),
+                        },
+                    };
+                    segment_updater.merge_event_callbacks.broadcast(event);
                     let _send_result = merging_future_send.send(res);
                 }
+                Err(TantivyError::Cancelled) => {
+                    info!(
+                        "Merge of {:?} was cancelled by the user",
+                        merge_operation.segment_ids()
+                    );
+                    // `merge_operation` is dropped here, so that the segments are available
+                    // for merge again before subscribers get notified.
+                    drop(merge_operation);
+                    segment_updater
+                        .merge_event_callbacks
+                        .broadcast(MergeEvent::Cancelled(merge_handle));
+                    let _send_result = merging_future_send.send(Err(TantivyError::Cancelled));
+                }
                 Err(merge_error) => {
                     warn!(
                         "Merge of {:?} was cancelled: {:?}",
@@ -534,6 +615,12 @@ impl SegmentUpdater {
                     if cfg!(test) {
                         panic!("{merge_error:?}");
                     }
+                    segment_updater
+                        .merge_event_callbacks
+                        .broadcast(MergeEvent::Failed {
+                            handle: merge_handle,
+                            error: merge_error.clone(),
+                        });
                     let _send_result = merging_future_send.send(Err(merge_error));
                 }
             }
@@ -542,6 +629,17 @@ impl SegmentUpdater {
         scheduled_result
     }
 
+    /// Returns handles on the merge operations that are currently running or about to run.
+    pub fn running_merges(&self) -> Vec<MergeHandle> {
+        self.merge_operations.handles()
+    }
+
+    /// Registers a callback that gets called on the merge thread whenever a merge
+    /// starts or ends.
+    pub fn subscribe_merge_events(&self, callback: MergeEventCallback) -> MergeEventHandle {
+        self.merge_event_callbacks.subscribe(callback)
+    }
+
     pub(crate) fn get_mergeable_segments(&self) -> (Vec<SegmentMeta>, Vec<SegmentMeta>) {
         let merge_segment_ids: HashSet<SegmentId> = self.merge_operations.segment_in_merge();
         self.segment_manager
@@ -629,8 +727,11 @@ impl SegmentUpdater {
                     .end_merge(merge_operation.segment_ids(), after_merge_segment_entry)?;
 
                 if segments_status == SegmentsStatus::Committed {
-                    segment_updater
-                        .save_metas(previous_metas.opstamp, previous_metas.payload.clone())?;
+                    segment_updater.save_metas(
+                        previous_metas.opstamp,
+                        previous_metas.payload.clone(),
+                        previous_metas.metadata.clone(),
+                    )?;
                 }
 
                 segment_updater.consider_merge_options();
@@ -666,6 +767,8 @@ impl SegmentUpdater {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::{Arc, Mutex};
+
     use super::merge_indices;
     use crate::collector::TopDocs;
     use crate::directory::RamDirectory;
@@ -673,9 +776,10 @@ mod tests {
     use crate::indexer::merge_policy::tests::MergeWheneverPossible;
     use crate::indexer::merger::IndexMerger;
     use crate::indexer::segment_updater::merge_filtered_segments;
+    use crate::indexer::{MergeEvent, MergeEventCallback, MergePhase, NoMergePolicy};
     use crate::query::QueryParser;
     use crate::schema::*;
-    use crate::{Directory, DocAddress, Index, Segment};
+    use crate::{Directory, DocAddress, Index, Segment, SegmentId, TantivyError};
 
     #[test]
     fn test_delete_during_merge() -> crate::Result<()> {
@@ -815,6 +919,86 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_cancel_merge() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for _ in 0..3 {
+            index_writer.add_document(doc!(text_field=>"a"))?;
+            index_writer.add_document(doc!(text_field=>"b"))?;
+            index_writer.commit()?;
+        }
+        let segment_ids: Vec<SegmentId> = index.searchable_segment_ids()?;
+        assert_eq!(segment_ids.len(), 3);
+
+        let events: Arc<Mutex<Vec<MergeEvent>>> = Default::default();
+        let events_clone = events.clone();
+        let _handle = index_writer.subscribe_merge_events(MergeEventCallback::new(move |event| {
+            if let MergeEvent::Started(merge_handle) = event {
+                merge_handle.cancel();
+            }
+            events_clone.lock().unwrap().push(event.clone());
+        }));
+        let merge_res = index_writer.merge(&segment_ids).wait();
+        assert!(matches!(merge_res, Err(TantivyError::Cancelled)));
+        assert_eq!(index.searchable_segment_ids()?.len(), 3);
+        assert!(index_writer.running_merges().is_empty());
+        {
+            let events = events.lock().unwrap();
+            assert_eq!(events.len(), 2);
+            assert!(matches!(&events[0], MergeEvent::Started(_)));
+            let MergeEvent::Cancelled(merge_handle) = &events[1] else {
+                panic!("expected a cancelled event");
+            };
+            assert!(merge_handle.is_cancelled());
+            assert_eq!(merge_handle.segment_ids(), &segment_ids[..]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_progress_and_events() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for _ in 0..3 {
+            index_writer.add_document(doc!(text_field=>"a"))?;
+            index_writer.add_document(doc!(text_field=>"b"))?;
+            index_writer.commit()?;
+        }
+        let segment_ids: Vec<SegmentId> = index.searchable_segment_ids()?;
+
+        let events: Arc<Mutex<Vec<MergeEvent>>> = Default::default();
+        let events_clone = events.clone();
+        let _handle = index_writer.subscribe_merge_events(MergeEventCallback::new(move |event| {
+            events_clone.lock().unwrap().push(event.clone());
+        }));
+        let merged_segment = index_writer.merge(&segment_ids).wait()?.unwrap();
+        assert_eq!(merged_segment.num_docs(), 6);
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], MergeEvent::Started(_)));
+        let MergeEvent::Finished {
+            handle,
+            merged_segment: Some(finished_segment),
+        } = &events[1]
+        else {
+            panic!("expected a finished event");
+        };
+        assert_eq!(finished_segment.id(), merged_segment.id());
+        let progress = handle.progress();
+        assert_eq!(progress.phase, MergePhase::Done);
+        assert_eq!(progress.docs_processed, 6);
+        assert_eq!(progress.total_docs, 6);
+        assert!(!handle.is_cancelled());
+        Ok(())
+    }
+
     #[test]
     fn test_remove_all_segments() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
@@ -8,13 +8,24 @@ use crate::core::Segment;
 use crate::fastfield::FastFieldsWriter;
 use crate::fieldnorm::{FieldNormReaders, FieldNormsWriter};
 use crate::indexer::segment_serializer::SegmentSerializer;
+use crate::indexer::IndexingBufferSpill;
+use crate::payload::PayloadsWriter;
+use crate::points::PointsWriter;
 use crate::postings::{
     compute_table_memory_size, serialize_postings, IndexingContext, IndexingPosition,
     PerFieldPostingsWriter, PostingsWriter,
 };
-use crate::schema::{FieldEntry, FieldType, Schema, Term, Value, DATE_TIME_PRECISION_INDEXED};
+use crate::schema::{
+    Field, FieldEntry, FieldType, Schema, Term, Value, DATE_TIME_PRECISION_INDEXED,
+};
 use crate::store::{StoreReader, StoreWriter};
-use crate::tokenizer::{FacetTokenizer, PreTokenizedStream, TextAnalyzer, Tokenizer};
+use crate::suggest::CompletionsWriter;
+use crate::termvector::TermVectorsWriter;
+use crate::tokenizer::{
+    FacetTokenizer, PreTokenizedStream, PreTokenizedString, TextAnalyzer, Token, Tokenizer,
+    MAX_TOKEN_LEN,
+};
+use crate::vector::HnswWriter;
 use crate::{DocId, Document, Opstamp, SegmentComponent};
 
 /// Computes the initial size of the hash table.
@@ -36,6 +47,38 @@ fn compute_initial_table_size(per_thread_memory_budget: usize) -> crate::Result<
         })
 }
 
+/// Resolves the `copy_to` targets of every field of the schema.
+///
+/// Returns an error if one of the targets does not exist, or is not an indexed text field.
+pub(crate) fn resolve_copy_to_fields(schema: &Schema) -> crate::Result<Vec<Vec<Field>>> {
+    schema
+        .fields()
+        .map(|(_, field_entry)| {
+            let FieldType::Str(text_options) = field_entry.field_type() else {
+                return Ok(Vec::new());
+            };
+            text_options
+                .copy_to()
+                .iter()
+                .map(|target_field_name| {
+                    let target_field = schema.get_field(target_field_name)?;
+                    let target_field_type = schema.get_field_entry(target_field).field_type();
+                    if !matches!(target_field_type, FieldType::Str(target_text_options)
+                        if target_text_options.get_indexing_options().is_some())
+                    {
+                        return Err(crate::TantivyError::SchemaError(format!(
+                            "Field {:?} is copied to {target_field_name:?}, which is not an \
+                             indexed text field",
+                            field_entry.name()
+                        )));
+                    }
+                    Ok(target_field)
+                })
+                .collect()
+        })
+        .collect()
+}
+
 fn remap_doc_opstamps(
     opstamps: Vec<Opstamp>,
     doc_id_mapping_opt: Option<&DocIdMapping>,
@@ -50,6 +93,32 @@ fn remap_doc_opstamps(
     }
 }
 
+/// Checks that the values of the dense vector fields of a document are vectors with the
+/// dimension of their field.
+pub(crate) fn check_dense_vectors(schema: &Schema, document: &Document) -> crate::Result<()> {
+    for field_value in document.field_values() {
+        let field_entry = schema.get_field_entry(field_value.field());
+        let FieldType::DenseVector(dense_vector_options) = field_entry.field_type() else {
+            continue;
+        };
+        let Some(vector) = field_value.value().as_dense_vector() else {
+            return Err(crate::TantivyError::SchemaError(format!(
+                "Expected a DenseVector for field {:?}",
+                field_entry.name()
+            )));
+        };
+        if vector.len() != dense_vector_options.dimension() {
+            return Err(crate::TantivyError::SchemaError(format!(
+                "Expected a vector with {} dimensions for field {:?}, got {}",
+                dense_vector_options.dimension(),
+                field_entry.name(),
+                vector.len()
+            )));
+        }
+    }
+    Ok(())
+}
+
 /// A `SegmentWriter` is in charge of creating segment index from a
 /// set of documents.
 ///
@@ -62,8 +131,15 @@ pub struct SegmentWriter {
     pub(crate) segment_serializer: SegmentSerializer,
     pub(crate) fast_field_writers: FastFieldsWriter,
     pub(crate) fieldnorms_writer: FieldNormsWriter,
+    pub(crate) points_writer: PointsWriter,
+    pub(crate) term_vectors_writer: TermVectorsWriter,
+    pub(crate) payloads_writer: PayloadsWriter,
+    pub(crate) completions_writer: CompletionsWriter,
+    pub(crate) hnsw_writer: HnswWriter,
     pub(crate) doc_opstamps: Vec<Opstamp>,
     per_field_text_analyzers: Vec<TextAnalyzer>,
+    per_field_copy_to: Vec<Vec<Field>>,
+    default_values: Vec<(Field, Value)>,
     term_buffer: Term,
     schema: Schema,
 }
@@ -81,13 +157,41 @@ impl SegmentWriter {
     pub fn for_segment(
         memory_budget_in_bytes: usize,
         segment: Segment,
+    ) -> crate::Result<SegmentWriter> {
+        let table_size = compute_initial_table_size(memory_budget_in_bytes)?;
+        SegmentWriter::with_indexing_context(IndexingContext::new(table_size), segment)
+    }
+
+    /// Same as [`SegmentWriter::for_segment()`], but the memory arenas spill to temporary files.
+    pub(crate) fn for_segment_with_spill(
+        memory_budget_in_bytes: usize,
+        segment: Segment,
+        spill: &IndexingBufferSpill,
+    ) -> crate::Result<SegmentWriter> {
+        let table_size = compute_initial_table_size(memory_budget_in_bytes)?;
+        SegmentWriter::with_indexing_context(
+            IndexingContext::with_spill(table_size, spill)?,
+            segment,
+        )
+    }
+
+    fn with_indexing_context(
+        ctx: IndexingContext,
+        segment: Segment,
     ) -> crate::Result<SegmentWriter> {
         let schema = segment.schema();
         let tokenizer_manager = segment.index().tokenizers().clone();
         let tokenizer_manager_fast_field = segment.index().fast_field_tokenizer().clone();
-        let table_size = compute_initial_table_size(memory_budget_in_bytes)?;
         let segment_serializer = SegmentSerializer::for_segment(segment, false)?;
         let per_field_postings_writers = PerFieldPostingsWriter::for_schema(&schema);
+        let per_field_copy_to = resolve_copy_to_fields(&schema)?;
+        let default_values = schema
+            .fields()
+            .filter_map(|(field, field_entry)| {
+                let default_value = field_entry.default_value()?;
+                Some((field, default_value.clone()))
+            })
+            .collect();
         let per_field_text_analyzers = schema
             .fields()
             .map(|(_, field_entry): (_, &FieldEntry)| {
@@ -108,9 +212,14 @@ impl SegmentWriter {
             .collect();
         Ok(SegmentWriter {
             max_doc: 0,
-            ctx: IndexingContext::new(table_size),
+            ctx,
             per_field_postings_writers,
             fieldnorms_writer: FieldNormsWriter::for_schema(&schema),
+            points_writer: PointsWriter::for_schema(&schema),
+            term_vectors_writer: TermVectorsWriter::for_schema(&schema),
+            payloads_writer: PayloadsWriter::for_schema(&schema),
+            completions_writer: CompletionsWriter::for_schema(&schema)?,
+            hnsw_writer: HnswWriter::for_schema(&schema),
             segment_serializer,
             fast_field_writers: FastFieldsWriter::from_schema_and_tokenizer_manager(
                 &schema,
@@ -118,6 +227,8 @@ impl SegmentWriter {
             )?,
             doc_opstamps: Vec::with_capacity(1_000),
             per_field_text_analyzers,
+            per_field_copy_to,
+            default_values,
             term_buffer: Term::with_capacity(16),
             schema,
         })
@@ -129,6 +240,8 @@ impl SegmentWriter {
     /// be used afterwards.
     pub fn finalize(mut self) -> crate::Result<Vec<u64>> {
         self.fieldnorms_writer.fill_up_to_max_doc(self.max_doc);
+        self.term_vectors_writer.fill_up_to_max_doc(self.max_doc);
+        self.payloads_writer.fill_up_to_max_doc(self.max_doc);
         let mapping: Option<DocIdMapping> = self
             .segment_serializer
             .segment()
@@ -143,6 +256,11 @@ impl SegmentWriter {
             self.ctx,
             self.fast_field_writers,
             &self.fieldnorms_writer,
+            self.points_writer,
+            &self.term_vectors_writer,
+            &self.payloads_writer,
+            &self.completions_writer,
+            &self.hnsw_writer,
             self.segment_serializer,
             mapping.as_ref(),
         )?;
@@ -153,19 +271,39 @@ impl SegmentWriter {
     pub fn mem_usage(&self) -> usize {
         self.ctx.mem_usage()
             + self.fieldnorms_writer.mem_usage()
+            + self.points_writer.mem_usage()
+            + self.term_vectors_writer.mem_usage()
+            + self.payloads_writer.mem_usage()
+            + self.completions_writer.mem_usage()
+            + self.hnsw_writer.mem_usage()
             + self.fast_field_writers.mem_usage()
             + self.segment_serializer.mem_usage()
     }
 
+    /// Returns the number of bytes of the indexing buffers spilled to temporary files.
+    pub fn spilled_num_bytes(&self) -> usize {
+        self.ctx.spilled_num_bytes()
+    }
+
     fn index_document(&mut self, doc: &Document) -> crate::Result<()> {
         let doc_id = self.max_doc;
+        let per_field_copy_to = &self.per_field_copy_to;
+        // Values of fields with a `copy_to` option are also indexed as values of the target
+        // fields.
         let vals_grouped_by_field = doc
             .field_values()
             .iter()
-            .sorted_by_key(|el| el.field())
-            .group_by(|el| el.field());
+            .flat_map(|field_value| {
+                let field = field_value.field();
+                let copy_to_fields = &per_field_copy_to[field.field_id() as usize];
+                std::iter::once(field)
+                    .chain(copy_to_fields.iter().copied())
+                    .map(move |target_field| (target_field, field_value.value()))
+            })
+            .sorted_by_key(|(field, _)| *field)
+            .group_by(|(field, _)| *field);
         for (field, field_values) in &vals_grouped_by_field {
-            let values = field_values.map(|field_value| field_value.value());
+            let values = field_values.map(|(_, value)| value);
             let field_entry = self.schema.get_field_entry(field);
             let make_schema_error = || {
                 crate::TantivyError::SchemaError(format!(
@@ -184,6 +322,7 @@ impl SegmentWriter {
             term_buffer.clear_with_field_and_type(field_entry.field_type().value_type(), field);
 
             match field_entry.field_type() {
+                FieldType::DenseVector(_) | FieldType::GeoPoint(_) => {}
                 FieldType::Facet(_) => {
                     for value in values {
                         let facet = value.as_facet().ok_or_else(make_schema_error)?;
@@ -201,20 +340,49 @@ impl SegmentWriter {
                 }
                 FieldType::Str(_) => {
                     let mut indexing_position = IndexingPosition::default();
+                    let has_term_vectors = field_entry.has_term_vectors();
+                    let has_payloads = field_entry.has_payloads();
+                    let mut field_tokens: Vec<Token> = Vec::new();
                     for value in values {
-                        let mut token_stream = match value {
-                            Value::PreTokStr(tok_str) => {
-                                PreTokenizedStream::from(tok_str.clone()).into()
-                            }
+                        let (mut token_stream, text_len) = match value {
+                            Value::PreTokStr(tok_str) => (
+                                PreTokenizedStream::from(tok_str.clone()).into(),
+                                tok_str.text.len(),
+                            ),
                             Value::Str(ref text) => {
                                 let text_analyzer =
                                     &self.per_field_text_analyzers[field.field_id() as usize];
-                                text_analyzer.token_stream(text)
+                                (text_analyzer.token_stream(text), text.len())
                             }
                             _ => {
                                 continue;
                             }
                         };
+                        if has_term_vectors || has_payloads {
+                            // The tokens are collected once, to be recorded in the term vector
+                            // and the payloads, and then indexed.
+                            let mut tokens: Vec<Token> = Vec::new();
+                            token_stream.process(&mut |token: &Token| tokens.push(token.clone()));
+                            field_tokens.extend(
+                                tokens
+                                    .iter()
+                                    .filter(|token| token.text.len() <= MAX_TOKEN_LEN)
+                                    .map(|token| Token {
+                                        position: indexing_position.end_position as usize
+                                            + token.position,
+                                        offset_from: indexing_position.offset_base as usize
+                                            + token.offset_from,
+                                        offset_to: indexing_position.offset_base as usize
+                                            + token.offset_to,
+                                        ..token.clone()
+                                    }),
+                            );
+                            token_stream = PreTokenizedStream::from(PreTokenizedString {
+                                text: String::new(),
+                                tokens,
+                            })
+                            .into();
+                        }
 
                         assert!(term_buffer.is_empty());
                         postings_writer.index_text(
@@ -224,11 +392,19 @@ impl SegmentWriter {
                             ctx,
                             &mut indexing_position,
                         );
+                        indexing_position.offset_base += text_len as u32;
                     }
                     if field_entry.has_fieldnorms() {
                         self.fieldnorms_writer
                             .record(doc_id, field, indexing_position.num_tokens);
                     }
+                    if has_term_vectors {
+                        self.term_vectors_writer
+                            .record(doc_id, field, &field_tokens);
+                    }
+                    if has_payloads {
+                        self.payloads_writer.record(doc_id, field, &field_tokens);
+                    }
                 }
                 FieldType::U64(_) => {
                     let mut num_vals = 0;
@@ -311,7 +487,7 @@ impl SegmentWriter {
                         doc_id,
                         json_values_it,
                         text_analyzer,
-                        json_options.is_expand_dots_enabled(),
+                        json_options,
                         term_buffer,
                         postings_writer,
                         ctx,
@@ -338,9 +514,20 @@ impl SegmentWriter {
     ///
     /// As a user, you should rather use `IndexWriter`'s add_document.
     pub fn add_document(&mut self, add_operation: AddOperation) -> crate::Result<()> {
-        let AddOperation { document, opstamp } = add_operation;
+        let AddOperation {
+            mut document,
+            opstamp,
+        } = add_operation;
+        for (field, default_value) in &self.default_values {
+            if document.get_first(*field).is_none() {
+                document.add_field_value(*field, default_value.clone());
+            }
+        }
         self.doc_opstamps.push(opstamp);
         self.fast_field_writers.add_document(&document)?;
+        self.points_writer.add_document(self.max_doc, &document);
+        self.completions_writer.record(self.max_doc, &document);
+        self.hnsw_writer.record(self.max_doc, &document);
         self.index_document(&document)?;
         let doc_writer = self.segment_serializer.get_store_writer();
         doc_writer.store(&document, &self.schema)?;
@@ -374,11 +561,17 @@ impl SegmentWriter {
 /// to the `SegmentSerializer`.
 ///
 /// `doc_id_map` is used to map to the new doc_id order.
+#[allow(clippy::too_many_arguments)]
 fn remap_and_write(
     per_field_postings_writers: &PerFieldPostingsWriter,
     ctx: IndexingContext,
     fast_field_writers: FastFieldsWriter,
     fieldnorms_writer: &FieldNormsWriter,
+    points_writer: PointsWriter,
+    term_vectors_writer: &TermVectorsWriter,
+    payloads_writer: &PayloadsWriter,
+    completions_writer: &CompletionsWriter,
+    hnsw_writer: &HnswWriter,
     mut serializer: SegmentSerializer,
     doc_id_map: Option<&DocIdMapping>,
 ) -> crate::Result<()> {
@@ -398,7 +591,27 @@ fn remap_and_write(
         serializer.get_postings_serializer(),
     )?;
     debug!("fastfield-serialize");
-    fast_field_writers.serialize(serializer.get_fast_field_write(), doc_id_map)?;
+    let codec = serializer.segment().codec()?;
+    fast_field_writers.serialize_with_format(
+        serializer.get_fast_field_write(),
+        doc_id_map,
+        codec.fast_fields_format(),
+    )?;
+    if let Some(points_serializer) = serializer.extract_points_serializer() {
+        points_writer.serialize(points_serializer, doc_id_map)?;
+    }
+    if let Some(term_vectors_serializer) = serializer.extract_term_vectors_serializer() {
+        term_vectors_writer.serialize(term_vectors_serializer, doc_id_map)?;
+    }
+    if let Some(payloads_serializer) = serializer.extract_payloads_serializer() {
+        payloads_writer.serialize(payloads_serializer, doc_id_map)?;
+    }
+    if let Some(completions_serializer) = serializer.extract_completions_serializer() {
+        completions_writer.serialize(completions_serializer, doc_id_map)?;
+    }
+    if let Some(hnsw_serializer) = serializer.extract_hnsw_serializer() {
+        hnsw_writer.serialize(hnsw_serializer, doc_id_map)?;
+    }
 
     // finalize temp docstore and create version, which reflects the doc_id_map
     if let Some(doc_id_map) = doc_id_map {
@@ -439,18 +652,22 @@ mod tests {
     use std::path::Path;
 
     use super::compute_initial_table_size;
-    use crate::collector::Count;
+    use crate::collector::{Count, TopDocs};
     use crate::core::json_utils::JsonTermWriter;
     use crate::directory::RamDirectory;
     use crate::postings::TermInfo;
-    use crate::query::PhraseQuery;
-    use crate::schema::{IndexRecordOption, Schema, Type, STORED, STRING, TEXT};
+    use crate::query::{PhraseQuery, QueryParser};
+    use crate::schema::{
+        IndexRecordOption, JsonObjectOptions, JsonPathMapping, JsonPathTemplate, Schema,
+        TextFieldIndexing, TextOptions, Type, FAST, STORED, STRING, TEXT,
+    };
     use crate::store::{Compressor, StoreReader, StoreWriter};
     use crate::time::format_description::well_known::Rfc3339;
     use crate::time::OffsetDateTime;
     use crate::tokenizer::{PreTokenizedString, Token};
     use crate::{
-        DateTime, Directory, DocAddress, DocSet, Document, Index, Postings, Term, TERMINATED,
+        DateTime, Directory, DocAddress, DocSet, Document, Index, Postings, TantivyError, Term,
+        TERMINATED,
     };
 
     #[test]
@@ -476,6 +693,8 @@ mod tests {
                 position: 0,
                 text: String::from("A"),
                 position_length: 1,
+                keyword: false,
+                payload: Vec::new(),
             }],
         };
 
@@ -745,6 +964,98 @@ mod tests {
         assert_eq!(postings.advance(), TERMINATED);
     }
 
+    #[test]
+    fn test_default_values() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let status = schema_builder.add_text_field("status", STRING | STORED);
+        let rank = schema_builder.add_i64_field("rank", FAST);
+        schema_builder.set_default_value(status, "unknown");
+        schema_builder.set_default_value(rank, -1i64);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title=>"first", status=>"active", rank=>3i64))?;
+        index_writer.add_document(doc!(title=>"second"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![title]);
+        let query = query_parser.parse_query("status:unknown").unwrap();
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(2))?;
+        assert_eq!(top_docs.len(), 1);
+        let doc = searcher.doc(top_docs[0].1)?;
+        assert_eq!(
+            doc.get_first(title).and_then(|val| val.as_text()),
+            Some("second")
+        );
+        assert_eq!(
+            doc.get_first(status).and_then(|val| val.as_text()),
+            Some("unknown")
+        );
+        let rank_column = searcher.segment_reader(0).fast_fields().i64("rank")?;
+        assert_eq!(rank_column.first(0), Some(3));
+        assert_eq!(rank_column.first(1), Some(-1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_templates() {
+        let mut schema_builder = Schema::builder();
+        let json_options = JsonObjectOptions::from(TEXT)
+            .add_template(JsonPathTemplate::new("*.ts", JsonPathMapping::Date).set_fast(true))
+            .add_template(JsonPathTemplate::new("tags.*", JsonPathMapping::Raw))
+            .add_template(JsonPathTemplate::new("secret", JsonPathMapping::Ignore));
+        let json_field = schema_builder.add_json_field("json", json_options);
+        let schema = schema_builder.build();
+        let json_val: serde_json::Map<String, serde_json::Value> = serde_json::from_str(
+            r#"{
+                "event": {"ts": "2023-01-01T00:00:00Z", "label": "an event"},
+                "tags": {"color": "Light Blue"},
+                "secret": "hidden"
+            }"#,
+        )
+        .unwrap();
+        let index = Index::create_in_ram(schema);
+        let mut writer = index.writer_for_tests().unwrap();
+        writer.add_document(doc!(json_field=>json_val)).unwrap();
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let segment_reader = searcher.segment_reader(0u32);
+        let inv_index = segment_reader.inverted_index(json_field).unwrap();
+        let has_term = |path: &[&str], text: &str| {
+            let mut term = Term::with_type_and_field(Type::Json, json_field);
+            let mut json_term_writer = JsonTermWriter::wrap(&mut term, false);
+            for segment in path {
+                json_term_writer.push_path_segment(segment);
+            }
+            json_term_writer.set_str(text);
+            inv_index
+                .get_term_info(json_term_writer.term())
+                .unwrap()
+                .is_some()
+        };
+        assert!(has_term(&["tags", "color"], "Light Blue"));
+        assert!(!has_term(&["tags", "color"], "light"));
+        assert!(has_term(&["event", "label"], "event"));
+        assert!(!has_term(&["secret"], "hidden"));
+
+        let ts =
+            DateTime::from_utc(OffsetDateTime::parse("2023-01-01T00:00:00Z", &Rfc3339).unwrap());
+        let mut term = Term::with_type_and_field(Type::Json, json_field);
+        let mut json_term_writer = JsonTermWriter::wrap(&mut term, false);
+        json_term_writer.push_path_segment("event");
+        json_term_writer.push_path_segment("ts");
+        json_term_writer.set_fast_value(ts);
+        assert!(inv_index
+            .get_term_info(json_term_writer.term())
+            .unwrap()
+            .is_some());
+
+        let fast_fields = segment_reader.fast_fields();
+        let ts_column = fast_fields.date("json.event.ts").unwrap();
+        assert_eq!(ts_column.first(0), Some(ts));
+        assert!(fast_fields.str("json.event.label").unwrap().is_none());
+    }
+
     #[test]
     fn test_position_overlapping_path() {
         // This test checks that we do not end up detecting phrase query due
@@ -828,6 +1139,8 @@ mod tests {
                 position: 0,
                 text: "rollercoaster".to_string(),
                 position_length: 2,
+                keyword: false,
+                payload: Vec::new(),
             }],
         };
         doc.add_pre_tokenized_text(text, tokens.clone());
@@ -868,6 +1181,8 @@ mod tests {
                     position: 0,
                     text: "long_token".to_string(),
                     position_length: 3,
+                    keyword: false,
+                    payload: Vec::new(),
                 },
                 Token {
                     offset_from: 0,
@@ -875,6 +1190,8 @@ mod tests {
                     position: 1,
                     text: "short".to_string(),
                     position_length: 1,
+                    keyword: false,
+                    payload: Vec::new(),
                 },
             ],
         };
@@ -898,4 +1215,74 @@ mod tests {
         postings.positions(&mut positions);
         assert_eq!(positions, &[4]); //< as opposed to 3 if we had a position length of 1.
     }
+
+    #[test]
+    fn test_copy_to() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", (TEXT | STORED).set_copy_to("all"));
+        let body =
+            schema_builder.add_text_field("body", TextOptions::from(STORED).set_copy_to("all"));
+        let all = schema_builder.add_text_field("all", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_for_tests().unwrap();
+        index_writer
+            .add_document(doc!(title=>"Hello", body=>"happy tax payer"))
+            .unwrap();
+        index_writer.commit().unwrap();
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+        let seg_reader = searcher.segment_reader(0);
+        let inv_index = seg_reader.inverted_index(all).unwrap();
+        let term = Term::from_field_text(all, "payer");
+        let mut postings = inv_index
+            .read_postings(&term, IndexRecordOption::WithFreqsAndPositions)
+            .unwrap()
+            .unwrap();
+        assert_eq!(postings.doc(), 0u32);
+        let mut positions = Vec::new();
+        postings.positions(&mut positions);
+        assert_eq!(positions, &[4]);
+        assert!(inv_index
+            .read_postings(
+                &Term::from_field_text(all, "hello"),
+                IndexRecordOption::Basic
+            )
+            .unwrap()
+            .is_some());
+        // The source field is not indexed.
+        assert!(seg_reader
+            .inverted_index(body)
+            .unwrap()
+            .read_postings(
+                &Term::from_field_text(body, "payer"),
+                IndexRecordOption::Basic
+            )
+            .unwrap()
+            .is_none());
+        let fieldnorm_reader = seg_reader.get_fieldnorms_reader(all).unwrap();
+        assert_eq!(fieldnorm_reader.fieldnorm(0), 4);
+        // The copied values are not stored.
+        let doc = searcher.doc(DocAddress::new(0, 0)).unwrap();
+        assert!(doc.get_first(all).is_none());
+        assert_eq!(doc.get_all(title).count(), 1);
+    }
+
+    #[test]
+    fn test_copy_to_requires_indexed_text_field() {
+        let mut schema_builder = Schema::builder();
+        let text_indexing = TextFieldIndexing::default();
+        schema_builder.add_text_field(
+            "title",
+            TextOptions::default()
+                .set_indexing_options(text_indexing)
+                .set_copy_to("all"),
+        );
+        schema_builder.add_text_field("all", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        assert!(matches!(
+            index.writer_for_tests(),
+            Err(TantivyError::SchemaError(_))
+        ));
+    }
 }
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use crate::core::IndexMeta;
+use crate::schema::{Field, IndexRecordOption, Term, Type};
+use crate::{DocSet, Index, Opstamp, SegmentReader, TantivyError, TERMINATED};
+
+/// The version of the documents which do not exist.
+pub(crate) const ABSENT_VERSION: u64 = 0;
+
+/// A version set since the last commit.
+#[derive(Clone, Copy)]
+struct PendingVersion {
+    version: u64,
+    opstamp: Opstamp,
+}
+
+/// Tracks the versions of the documents updated with
+/// [`IndexWriter::update_document_if()`](crate::IndexWriter::update_document_if).
+///
+/// The versions of the documents are stored in a `u64` fast field. The versions set since the
+/// last commit are kept in memory, while the versions of the committed documents are read from
+/// the fast field of the committed segments.
+pub(crate) struct VersionTracker {
+    version_field: Field,
+    pending_versions: HashMap<Term, PendingVersion>,
+    // The opstamp of the last commit, and the readers of its segments.
+    commit_opstamp: Opstamp,
+    committed_segment_readers: Vec<SegmentReader>,
+    // Set by `delete_all_documents()`: the committed documents are deleted by the next commit.
+    committed_documents_deleted: bool,
+}
+
+impl VersionTracker {
+    pub(crate) fn new(
+        index: &Index,
+        committed_meta: &IndexMeta,
+        version_field: Field,
+    ) -> crate::Result<VersionTracker> {
+        let schema = index.schema();
+        let field_entry = schema.get_field_entry(version_field);
+        if field_entry.field_type().value_type() != Type::U64 || !field_entry.is_fast() {
+            return Err(TantivyError::SchemaError(format!(
+                "The version field {:?} must be a u64 fast field",
+                field_entry.name()
+            )));
+        }
+        Ok(VersionTracker {
+            version_field,
+            pending_versions: HashMap::new(),
+            commit_opstamp: committed_meta.opstamp,
+            committed_segment_readers: open_segment_readers(index, committed_meta)?,
+            committed_documents_deleted: false,
+        })
+    }
+
+    pub(crate) fn version_field(&self) -> Field {
+        self.version_field
+    }
+
+    /// Returns the current version of the document identified by `term`, or
+    /// [`ABSENT_VERSION`] if there is none.
+    pub(crate) fn current_version(
+        &mut self,
+        index: &Index,
+        committed_meta: &IndexMeta,
+        term: &Term,
+    ) -> crate::Result<u64> {
+        self.sync_committed_segment_readers(index, committed_meta)?;
+        if let Some(pending_version) = self.pending_versions.get(term) {
+            return Ok(pending_version.version);
+        }
+        if self.committed_documents_deleted {
+            return Ok(ABSENT_VERSION);
+        }
+        let version_field_name = index
+            .schema()
+            .get_field_name(self.version_field)
+            .to_string();
+        let mut current_version = ABSENT_VERSION;
+        for segment_reader in &self.committed_segment_readers {
+            let inverted_index = segment_reader.inverted_index(term.field())?;
+            let Some(mut postings) =
+                inverted_index.read_postings(term, IndexRecordOption::Basic)?
+            else {
+                continue;
+            };
+            let version_column = segment_reader.fast_fields().u64(&version_field_name)?;
+            let mut doc = postings.doc();
+            while doc != TERMINATED {
+                if !segment_reader.is_deleted(doc) {
+                    if let Some(version) = version_column.first(doc) {
+                        current_version = current_version.max(version);
+                    }
+                }
+                doc = postings.advance();
+            }
+        }
+        Ok(current_version)
+    }
+
+    /// Records the version of the document identified by `term`, set by the operation of the
+    /// given opstamp.
+    pub(crate) fn set_version(&mut self, term: Term, version: u64, opstamp: Opstamp) {
+        self.pending_versions
+            .insert(term, PendingVersion { version, opstamp });
+    }
+
+    /// Forgets the pending versions, as all of the documents are deleted.
+    pub(crate) fn delete_all(&mut self) {
+        self.pending_versions.clear();
+        self.committed_documents_deleted = true;
+    }
+
+    /// Opens the readers of the segments of the last commit, if they changed, and forgets the
+    /// pending versions which are now committed.
+    fn sync_committed_segment_readers(
+        &mut self,
+        index: &Index,
+        committed_meta: &IndexMeta,
+    ) -> crate::Result<()> {
+        // The segments also change, for the same commit, when they are merged.
+        if self.commit_opstamp == committed_meta.opstamp
+            && self
+                .committed_segment_readers
+                .iter()
+                .map(SegmentReader::segment_id)
+                .eq(committed_meta.segments.iter().map(|meta| meta.id()))
+        {
+            return Ok(());
+        }
+        self.committed_segment_readers = open_segment_readers(index, committed_meta)?;
+        if self.commit_opstamp != committed_meta.opstamp {
+            self.commit_opstamp = committed_meta.opstamp;
+            self.pending_versions
+                .retain(|_, pending_version| pending_version.opstamp > self.commit_opstamp);
+            self.committed_documents_deleted = false;
+        }
+        Ok(())
+    }
+}
+
+fn open_segment_readers(
+    index: &Index,
+    committed_meta: &IndexMeta,
+) -> crate::Result<Vec<SegmentReader>> {
+    committed_meta
+        .segments
+        .iter()
+        .map(|segment_meta| SegmentReader::open(&index.segment(segment_meta.clone())))
+        .collect()
+}
@@ -144,10 +144,16 @@ pub mod error;
 pub mod tokenizer;
 
 pub mod aggregation;
+pub mod codec;
 pub mod collector;
 pub mod directory;
 pub mod fastfield;
 pub mod fieldnorm;
+#[cfg(feature = "lucene")]
+pub mod lucene;
+pub mod metrics;
+pub mod payload;
+pub mod points;
 pub mod positions;
 pub mod postings;
 
@@ -155,14 +161,24 @@ pub mod postings;
 pub mod query;
 pub mod schema;
 pub mod space_usage;
+#[cfg(feature = "sql")]
+pub mod sql;
 pub mod store;
+pub mod suggest;
 pub mod termdict;
+pub mod termvector;
+pub mod vector;
 
 mod reader;
 
-pub use self::reader::{IndexReader, IndexReaderBuilder, ReloadPolicy, Warmer};
+pub use self::reader::{
+    ComponentWarmer, GlobalOrdinalsWarmer, IndexReader, IndexReaderBuilder, ReloadPolicy,
+    SegmentChange, SegmentChangeCallback, SegmentChangeHandle, WarmUpComponents, Warmer,
+};
 mod snippet;
-pub use self::snippet::{Snippet, SnippetGenerator};
+pub use self::snippet::{
+    FieldHighlight, Fragmenter, Highlighter, SentenceFragmenter, Snippet, SnippetGenerator,
+};
 
 mod docset;
 use std::fmt;
@@ -176,20 +192,29 @@ pub use self::docset::{DocSet, TERMINATED};
 #[doc(hidden)]
 pub use crate::core::json_utils;
 pub use crate::core::{
-    Executor, Index, IndexBuilder, IndexMeta, IndexSettings, IndexSortByField, InvertedIndexReader,
-    Order, Searcher, SearcherGeneration, Segment, SegmentComponent, SegmentId, SegmentMeta,
-    SegmentReader, SingleSegmentIndexWriter,
+    CursorHit, Executor, FieldStatistics, FilteredSearcher, HitCursor, HitCursorOptions, Index,
+    IndexBuilder, IndexCheckReport, IndexMeta, IndexReplica, IndexSettings, IndexSnapshot,
+    IndexSortByField, InvertedIndexReader, MultiSearcher, Order, Searcher, SearcherGeneration,
+    Segment, SegmentCheckReport, SegmentComponent, SegmentId, SegmentMeta, SegmentReader,
+    ShardRouter, SingleSegmentIndexWriter, SnapshotFile, SnapshotManifest, TermStatistics,
 };
 pub use crate::directory::Directory;
 pub use crate::indexer::operation::UserOperation;
-pub use crate::indexer::{merge_filtered_segments, merge_indices, IndexWriter, PreparedCommit};
+pub use crate::indexer::{
+    merge_filtered_segments, merge_indices, reindex, IndexWriter, IndexingBufferSpill,
+    MemoryBudget, MergeEvent, MergeEventCallback, MergeEventHandle, MergeHandle, MergePhase,
+    MergeProgress, PreparedCommit, ReindexProgress,
+};
 pub use crate::postings::Postings;
 #[allow(deprecated)]
 pub use crate::schema::DatePrecision;
 pub use crate::schema::{DateOptions, DateTimePrecision, Document, Term};
 
 /// Index format version.
-const INDEX_FORMAT_VERSION: u32 = 5;
+///
+/// Version 6 serializes the dense posting lists without term frequencies as bitsets, which
+/// the versions reading the format 5 would decode as garbled blocks.
+const INDEX_FORMAT_VERSION: u32 = 6;
 
 pub use memmap2::Advice;
 
@@ -312,7 +337,9 @@ pub mod tests {
     use crate::merge_policy::NoMergePolicy;
     use crate::query::BooleanQuery;
     use crate::schema::*;
-    use crate::{DateTime, DocAddress, Index, Postings, ReloadPolicy};
+    use crate::{
+        DateTime, DocAddress, FieldStatistics, Index, Postings, ReloadPolicy, TermStatistics,
+    };
 
     pub fn fixed_size_test<O: BinarySerializable + FixedSize + Default>() {
         let mut buffer = Vec::new();
@@ -856,6 +883,51 @@ pub mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_term_and_field_statistics() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let string_field = schema_builder.add_text_field("string", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field=>"a b a", string_field=>"a"))?;
+        index_writer.add_document(doc!(text_field=>"a c"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text_field=>"a a a a", string_field=>"a"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        assert_eq!(
+            searcher.term_statistics(&Term::from_field_text(text_field, "a"))?,
+            TermStatistics {
+                doc_freq: 3,
+                total_term_freq: Some(7),
+            }
+        );
+        assert_eq!(
+            searcher.term_statistics(&Term::from_field_text(text_field, "d"))?,
+            TermStatistics {
+                doc_freq: 0,
+                total_term_freq: Some(0),
+            }
+        );
+        assert_eq!(
+            searcher.term_statistics(&Term::from_field_text(string_field, "a"))?,
+            TermStatistics {
+                doc_freq: 2,
+                total_term_freq: None,
+            }
+        );
+        assert_eq!(
+            searcher.field_statistics(text_field)?,
+            FieldStatistics {
+                doc_count: 3,
+                total_num_tokens: 9,
+            }
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_doc_macro() {
         let mut schema_builder = Schema::builder();
@@ -0,0 +1,251 @@
+//! Decompression algorithms of Lucene: its LZ4 variant, the compression of the lowercase ASCII
+//! suffixes of the terms dictionary, and DEFLATE with a preset dictionary.
+
+use super::data_input::DataInput;
+
+/// Minimum length of the matches of Lucene's LZ4.
+const LZ4_MIN_MATCH: usize = 4;
+
+/// Decompresses `decompressed_len` bytes compressed by Lucene's `LZ4.compress()`, and appends
+/// them to `output`.
+///
+/// The matches may refer to the bytes already in `output`, which Lucene uses as a preset
+/// dictionary.
+pub(crate) fn lz4_decompress(
+    input: &mut DataInput,
+    decompressed_len: usize,
+    output: &mut Vec<u8>,
+) -> crate::Result<()> {
+    let end = output.len() + decompressed_len;
+    output.reserve(decompressed_len);
+    // Like Lucene, reads at least one sequence, even when `decompressed_len` is 0.
+    loop {
+        let token = input.read_byte()?;
+        let literal_len = read_lz4_len(input, token >> 4)?;
+        output.extend_from_slice(input.read_bytes(literal_len)?);
+        if output.len() >= end {
+            break;
+        }
+        let match_dec = input.read_u16()? as usize;
+        let match_len = read_lz4_len(input, token & 0x0f)? + LZ4_MIN_MATCH;
+        if match_dec == 0 || match_dec > output.len() {
+            return Err(input.corruption("Invalid LZ4 match offset"));
+        }
+        // The match may overlap the bytes it copies.
+        let match_start = output.len() - match_dec;
+        for i in match_start..match_start + match_len {
+            output.push(output[i]);
+        }
+        if output.len() >= end {
+            break;
+        }
+    }
+    if output.len() != end {
+        return Err(input.corruption("LZ4 block longer than expected"));
+    }
+    Ok(())
+}
+
+fn read_lz4_len(input: &mut DataInput, token_len: u8) -> crate::Result<usize> {
+    let mut len = token_len as usize;
+    if token_len == 0x0f {
+        loop {
+            let byte = input.read_byte()?;
+            len += byte as usize;
+            if byte != 0xff {
+                break;
+            }
+        }
+    }
+    Ok(len)
+}
+
+/// Decompresses `len` bytes compressed by Lucene's `LowercaseAsciiCompression`, which packs 4
+/// bytes of the `[0x1F, 0x3F)` and `[0x5F, 0x7F)` ranges in 3 bytes, and records the other bytes
+/// as exceptions.
+pub(crate) fn lowercase_ascii_decompress(
+    input: &mut DataInput,
+    len: usize,
+) -> crate::Result<Vec<u8>> {
+    let saved = len >> 2;
+    let compressed_len = len - saved;
+    let mut output = input.read_bytes(compressed_len)?.to_vec();
+    output.resize(len, 0);
+    for i in 0..saved {
+        output[compressed_len + i] = ((output[i] & 0xc0) >> 2)
+            | ((output[saved + i] & 0xc0) >> 4)
+            | ((output[2 * saved + i] & 0xc0) >> 6);
+    }
+    for byte in output.iter_mut() {
+        let b = *byte;
+        *byte = ((b & 0x1f) | 0x20 | ((b & 0x20) << 1)).wrapping_sub(1);
+    }
+    let num_exceptions = input.read_vint()?;
+    let mut i = 0;
+    for _ in 0..num_exceptions {
+        i += input.read_byte()? as usize;
+        let byte = input.read_byte()?;
+        *output
+            .get_mut(i)
+            .ok_or_else(|| input.corruption("Invalid exception offset"))? = byte;
+    }
+    Ok(output)
+}
+
+/// Decompresses a raw DEFLATE stream, as written by Java's `Deflater` with the `nowrap` option
+/// and the preset dictionary `dictionary`, and appends the decompressed bytes to `output`.
+pub(crate) fn inflate_with_dictionary(
+    input: &mut DataInput,
+    compressed: &[u8],
+    dictionary: &[u8],
+    output: &mut Vec<u8>,
+) -> crate::Result<()> {
+    // miniz does not support preset dictionaries for raw streams: the dictionary is prepended
+    // as uncompressed blocks instead, which end on a byte boundary.
+    let mut stream = Vec::with_capacity(dictionary.len() + compressed.len() + 8);
+    for chunk in dictionary.chunks(u16::MAX as usize) {
+        let chunk_len = chunk.len() as u16;
+        stream.push(0);
+        stream.extend_from_slice(&chunk_len.to_le_bytes());
+        stream.extend_from_slice(&(!chunk_len).to_le_bytes());
+        stream.extend_from_slice(chunk);
+    }
+    stream.extend_from_slice(compressed);
+    let decompressed = miniz_oxide::inflate::decompress_to_vec(&stream)
+        .map_err(|err| input.corruption(&format!("Invalid DEFLATE stream: {err:?}")))?;
+    output.extend_from_slice(&decompressed[dictionary.len()..]);
+    Ok(())
+}
+
+#[cfg(all(test, feature = "mmap"))]
+pub(crate) mod tests {
+    use std::path::Path;
+
+    use super::super::data_input::DataInput;
+    use super::{inflate_with_dictionary, lowercase_ascii_decompress, lz4_decompress};
+
+    /// Compresses `data` with literals only, which is valid LZ4.
+    pub fn lz4_literals(data: &[u8]) -> Vec<u8> {
+        let mut output = Vec::new();
+        let len = data.len();
+        if len >= 15 {
+            output.push(0xf0);
+            let mut remaining = len - 15;
+            while remaining >= 255 {
+                output.push(255);
+                remaining -= 255;
+            }
+            output.push(remaining as u8);
+        } else {
+            output.push((len as u8) << 4);
+        }
+        output.extend_from_slice(data);
+        output
+    }
+
+    #[test]
+    fn test_lz4_decompress_with_matches() -> crate::Result<()> {
+        // "abcabcabcabc!" : 3 literals, a match of 9 bytes at offset 3, and 1 literal.
+        let mut compressed = vec![0x35];
+        compressed.extend_from_slice(b"abc");
+        compressed.extend_from_slice(&3u16.to_le_bytes());
+        compressed.push(0x10);
+        compressed.push(b'!');
+        let path = Path::new("test");
+        let mut output = b"dict".to_vec();
+        lz4_decompress(&mut DataInput::new(path, &compressed), 13, &mut output)?;
+        assert_eq!(output, b"dictabcabcabcabc!");
+
+        // A match referring to the preset dictionary.
+        let mut compressed = vec![0x00];
+        compressed.extend_from_slice(&4u16.to_le_bytes());
+        compressed.push(0x00);
+        let mut output = b"dict".to_vec();
+        lz4_decompress(&mut DataInput::new(path, &compressed), 4, &mut output)?;
+        assert_eq!(output, b"dictdict");
+
+        let mut input = DataInput::new(path, &[0x00, 0x10, b'!']);
+        let mut output = Vec::new();
+        lz4_decompress(&mut input, 0, &mut output)?;
+        lz4_decompress(&mut input, 1, &mut output)?;
+        assert_eq!(output, b"!");
+
+        let literals = lz4_literals(&[7u8; 300]);
+        let mut output = Vec::new();
+        lz4_decompress(&mut DataInput::new(path, &literals), 300, &mut output)?;
+        assert_eq!(output, vec![7u8; 300]);
+        // The match offset goes beyond the start of the output.
+        let mut output = Vec::new();
+        assert!(lz4_decompress(&mut DataInput::new(path, &compressed), 4, &mut output).is_err());
+        Ok(())
+    }
+
+    /// Compresses `data` like Lucene's `LowercaseAsciiCompression.compress()`.
+    pub fn lowercase_ascii_compress(data: &[u8]) -> Vec<u8> {
+        let len = data.len();
+        let mut tmp: Vec<u8> = data
+            .iter()
+            .map(|&byte| {
+                let b = byte.wrapping_add(1);
+                (b & 0x1f) | ((b & 0x40) >> 1)
+            })
+            .collect();
+        let saved = len >> 2;
+        let compressed_len = len - saved;
+        for i in 0..saved {
+            let packed = tmp[compressed_len + i];
+            tmp[i] |= (packed & 0x30) << 2;
+            tmp[saved + i] |= (packed & 0x0c) << 4;
+            tmp[2 * saved + i] |= (packed & 0x03) << 6;
+        }
+        let mut output = tmp[..compressed_len].to_vec();
+        let exceptions: Vec<usize> = (0..len)
+            .filter(|&i| {
+                let high_bits = data[i].wrapping_add(1) & !0x1f;
+                high_bits != 0x20 && high_bits != 0x60
+            })
+            .collect();
+        output.push(exceptions.len() as u8);
+        let mut previous = 0;
+        for i in exceptions {
+            output.push((i - previous) as u8);
+            output.push(data[i]);
+            previous = i;
+        }
+        output
+    }
+
+    #[test]
+    fn test_lowercase_ascii_decompress() -> crate::Result<()> {
+        let data = b"lowercase_terms-042Z\xc3\xa9";
+        let compressed = lowercase_ascii_compress(data);
+        assert!(compressed.len() < data.len() + 8);
+        let decompressed = lowercase_ascii_decompress(
+            &mut DataInput::new(Path::new("test"), &compressed),
+            data.len(),
+        )?;
+        assert_eq!(decompressed, data);
+        Ok(())
+    }
+
+    #[test]
+    fn test_inflate_with_dictionary() -> crate::Result<()> {
+        let dictionary = b"the quick brown fox";
+        // Compressed by zlib with `dictionary` as preset dictionary.
+        let compressed = [
+            43, 193, 20, 82, 200, 42, 205, 45, 40, 86, 200, 47, 75, 45, 82, 192, 34, 13, 0,
+        ];
+        let mut output = Vec::new();
+        inflate_with_dictionary(
+            &mut DataInput::new(Path::new("test"), &[]),
+            &compressed,
+            dictionary,
+            &mut output,
+        )?;
+        assert_eq!(
+            output,
+            b"the quick brown fox jumps over the quick brown fox"
+        );
+        Ok(())
+    }
+}
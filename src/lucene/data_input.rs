@@ -0,0 +1,374 @@
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use common::OwnedBytes;
+
+use crate::error::DataCorruption;
+use crate::TantivyError;
+
+/// Magic number starting the header of every Lucene file.
+pub(crate) const CODEC_MAGIC: u32 = 0x3fd7_6c17;
+/// Magic number starting the footer of every Lucene file.
+pub(crate) const FOOTER_MAGIC: u32 = !CODEC_MAGIC;
+/// Length of the footer: magic, checksum algorithm id and checksum.
+pub(crate) const FOOTER_LEN: usize = 16;
+/// Length of the ids of the segments and of the commits.
+pub(crate) const ID_LEN: usize = 16;
+
+/// Reads the primitives of Lucene's `DataInput`, as written by Lucene 9.x.
+///
+/// Since Lucene 9, the integers are little endian, except for those of the headers and of the
+/// footers of the files, which stayed big endian.
+pub(crate) struct DataInput<'a> {
+    path: &'a Path,
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> DataInput<'a> {
+    /// Validates the footer of the file, and returns a `DataInput` over its content.
+    pub fn open(path: &'a Path, data: &'a [u8]) -> crate::Result<DataInput<'a>> {
+        let mut input = DataInput::new(path, data);
+        if data.len() < FOOTER_LEN {
+            return Err(input.corruption("File too short to contain a footer"));
+        }
+        let content_len = data.len() - FOOTER_LEN;
+        input.offset = content_len;
+        if input.read_be_u32()? != FOOTER_MAGIC {
+            return Err(input.corruption("Invalid footer magic number"));
+        }
+        if input.read_be_u32()? != 0 {
+            return Err(input.corruption("Unknown checksum algorithm"));
+        }
+        let checksum = input.read_be_u64()?;
+        if checksum != u64::from(crc32fast::hash(&data[..data.len() - 8])) {
+            return Err(input.corruption("Checksum mismatch"));
+        }
+        input.data = &data[..content_len];
+        input.offset = 0;
+        Ok(input)
+    }
+
+    /// Returns a `DataInput` over content whose footer was already validated.
+    pub fn new(path: &'a Path, data: &'a [u8]) -> DataInput<'a> {
+        DataInput {
+            path,
+            data,
+            offset: 0,
+        }
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn seek(&mut self, offset: usize) -> crate::Result<()> {
+        if offset > self.data.len() {
+            return Err(self.corruption(&format!("Seek beyond the end of file to {offset}")));
+        }
+        self.offset = offset;
+        Ok(())
+    }
+
+    pub fn skip(&mut self, len: usize) -> crate::Result<()> {
+        self.read_bytes(len).map(|_| ())
+    }
+
+    pub fn corruption(&self, comment: &str) -> TantivyError {
+        DataCorruption::new(
+            PathBuf::from(self.path),
+            format!("{comment} at offset {}", self.offset),
+        )
+        .into()
+    }
+
+    pub fn read_bytes(&mut self, len: usize) -> crate::Result<&'a [u8]> {
+        let data = self.data;
+        let bytes = self
+            .offset
+            .checked_add(len)
+            .and_then(|end| data.get(self.offset..end))
+            .ok_or_else(|| self.corruption("Unexpected end of file"))?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> crate::Result<[u8; N]> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.read_bytes(N)?);
+        Ok(array)
+    }
+
+    pub fn read_byte(&mut self) -> crate::Result<u8> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    pub fn read_be_u32(&mut self) -> crate::Result<u32> {
+        Ok(u32::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_be_u64(&mut self) -> crate::Result<u64> {
+        Ok(u64::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_u16(&mut self) -> crate::Result<u16> {
+        Ok(u16::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_i16(&mut self) -> crate::Result<i16> {
+        Ok(i16::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_i32(&mut self) -> crate::Result<i32> {
+        Ok(i32::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_i64(&mut self) -> crate::Result<i64> {
+        Ok(i64::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_vlong(&mut self) -> crate::Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(self.corruption("Invalid variable length integer"))
+    }
+
+    pub fn read_vint(&mut self) -> crate::Result<u32> {
+        u32::try_from(self.read_vlong()?)
+            .map_err(|_| self.corruption("Invalid variable length integer"))
+    }
+
+    /// Reads a zig-zag encoded variable length integer.
+    pub fn read_zint(&mut self) -> crate::Result<i32> {
+        let value = self.read_vint()?;
+        Ok((value >> 1) as i32 ^ -((value & 1) as i32))
+    }
+
+    /// Reads a length prefixed array of bytes, Lucene's `BytesRef`.
+    pub fn read_bytes_ref(&mut self) -> crate::Result<&'a [u8]> {
+        let len = self.read_vint()? as usize;
+        self.read_bytes(len)
+    }
+
+    pub fn read_string(&mut self) -> crate::Result<String> {
+        let len = self.read_vint()? as usize;
+        let bytes = self.read_bytes(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| self.corruption("Invalid utf8 string"))
+    }
+
+    pub fn read_set_of_strings(&mut self) -> crate::Result<Vec<String>> {
+        let len = self.read_vint()?;
+        (0..len).map(|_| self.read_string()).collect()
+    }
+
+    pub fn read_map_of_strings(&mut self) -> crate::Result<BTreeMap<String, String>> {
+        let len = self.read_vint()?;
+        (0..len)
+            .map(|_| Ok((self.read_string()?, self.read_string()?)))
+            .collect()
+    }
+
+    /// Returns the codec name of the header starting at the current offset, without consuming
+    /// it.
+    pub fn peek_codec(&self) -> crate::Result<String> {
+        let mut input = DataInput::new(self.path, self.data);
+        input.offset = self.offset;
+        input.read_be_u32()?;
+        input.read_string()
+    }
+
+    /// Reads the header written by Lucene's `CodecUtil.writeHeader()`, checking its codec name
+    /// and its version, and returns the version.
+    pub fn read_header(
+        &mut self,
+        codec: &str,
+        versions: RangeInclusive<u32>,
+    ) -> crate::Result<u32> {
+        if self.read_be_u32()? != CODEC_MAGIC {
+            return Err(self.corruption("Invalid header magic number"));
+        }
+        let actual_codec = self.read_string()?;
+        if actual_codec != codec {
+            return Err(self.corruption(&format!("Codec {actual_codec:?}, expected {codec:?}")));
+        }
+        let version = self.read_be_u32()?;
+        if !versions.contains(&version) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "{:?} has the version {version} of the codec {codec:?}, only the versions \
+                 {versions:?} of Lucene 9.x are supported",
+                self.path
+            )));
+        }
+        Ok(version)
+    }
+
+    /// Reads the header written by Lucene's `CodecUtil.writeIndexHeader()`, checking its codec
+    /// name and its version, and returns the version, the id and the suffix of the file.
+    pub fn read_index_header(
+        &mut self,
+        codec: &str,
+        versions: RangeInclusive<u32>,
+    ) -> crate::Result<(u32, [u8; ID_LEN], String)> {
+        let version = self.read_header(codec, versions)?;
+        let id = self.read_array()?;
+        let suffix_len = self.read_byte()? as usize;
+        let suffix = self.read_bytes(suffix_len)?;
+        let suffix =
+            String::from_utf8(suffix.to_vec()).map_err(|_| self.corruption("Invalid suffix"))?;
+        Ok((version, id, suffix))
+    }
+
+    /// Reads the index header of a file of a segment, checking that its id and its suffix are
+    /// those of the segment, and returns its version.
+    pub fn read_segment_header(
+        &mut self,
+        codec: &str,
+        versions: RangeInclusive<u32>,
+        segment_id: [u8; ID_LEN],
+        segment_suffix: &str,
+    ) -> crate::Result<u32> {
+        let (version, id, suffix) = self.read_index_header(codec, versions)?;
+        if id != segment_id {
+            return Err(self.corruption("The file does not belong to the segment"));
+        }
+        if suffix != segment_suffix {
+            return Err(self.corruption(&format!("Suffix {suffix:?}, expected {segment_suffix:?}")));
+        }
+        Ok(version)
+    }
+}
+
+/// A file of a segment, whose footer and checksum were validated when it was opened.
+#[derive(Clone)]
+pub(crate) struct LuceneFile {
+    path: PathBuf,
+    data: OwnedBytes,
+}
+
+impl LuceneFile {
+    /// Validates the footer of the file, and strips it.
+    pub fn open(path: PathBuf, data: OwnedBytes) -> crate::Result<LuceneFile> {
+        let content_len = DataInput::open(&path, data.as_slice())?.data.len();
+        Ok(LuceneFile {
+            path,
+            data: data.slice(0..content_len),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the content of the file, without its footer.
+    pub fn data(&self) -> &OwnedBytes {
+        &self.data
+    }
+
+    /// Returns a `DataInput` over the content of the file, starting at the given offset.
+    pub fn input_at(&self, offset: u64) -> crate::Result<DataInput<'_>> {
+        let mut input = DataInput::new(&self.path, self.data.as_slice());
+        let offset = usize::try_from(offset).map_err(|_| input.corruption("Invalid offset"))?;
+        input.seek(offset)?;
+        Ok(input)
+    }
+
+    /// Returns a `DataInput` over the content of the file.
+    pub fn input(&self) -> DataInput<'_> {
+        DataInput::new(&self.path, self.data.as_slice())
+    }
+
+    /// Returns the `len` bytes of the file starting at `offset`.
+    pub fn slice(&self, offset: u64, len: u64) -> crate::Result<OwnedBytes> {
+        let range = usize::try_from(offset)
+            .ok()
+            .zip(usize::try_from(len).ok())
+            .and_then(|(offset, len)| Some(offset..offset.checked_add(len)?))
+            .filter(|range| range.end <= self.data.len())
+            .ok_or_else(|| {
+                self.input()
+                    .corruption(&format!("Invalid slice {offset}+{len}"))
+            })?;
+        Ok(self.data.slice(range))
+    }
+}
+
+#[cfg(all(test, feature = "mmap"))]
+pub(crate) mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{CODEC_MAGIC, FOOTER_MAGIC, ID_LEN};
+
+    /// Writes the primitives read by `DataInput`, to build Lucene files in the tests.
+    #[derive(Default)]
+    pub struct DataOutput {
+        pub data: Vec<u8>,
+    }
+
+    impl DataOutput {
+        pub fn write_i32(&mut self, value: i32) {
+            self.data.extend_from_slice(&value.to_le_bytes());
+        }
+
+        pub fn write_i64(&mut self, value: i64) {
+            self.data.extend_from_slice(&value.to_le_bytes());
+        }
+
+        pub fn write_vlong(&mut self, mut value: u64) {
+            while value >= 0x80 {
+                self.data.push((value as u8) | 0x80);
+                value >>= 7;
+            }
+            self.data.push(value as u8);
+        }
+
+        pub fn write_string(&mut self, value: &str) {
+            self.write_vlong(value.len() as u64);
+            self.data.extend_from_slice(value.as_bytes());
+        }
+
+        pub fn write_set_of_strings(&mut self, values: &[&str]) {
+            self.write_vlong(values.len() as u64);
+            for value in values {
+                self.write_string(value);
+            }
+        }
+
+        pub fn write_map_of_strings(&mut self, values: &BTreeMap<&str, &str>) {
+            self.write_vlong(values.len() as u64);
+            for (key, value) in values {
+                self.write_string(key);
+                self.write_string(value);
+            }
+        }
+
+        pub fn write_index_header(
+            &mut self,
+            codec: &str,
+            version: u32,
+            id: [u8; ID_LEN],
+            suffix: &str,
+        ) {
+            self.data.extend_from_slice(&CODEC_MAGIC.to_be_bytes());
+            self.write_string(codec);
+            self.data.extend_from_slice(&version.to_be_bytes());
+            self.data.extend_from_slice(&id);
+            self.data.push(suffix.len() as u8);
+            self.data.extend_from_slice(suffix.as_bytes());
+        }
+
+        pub fn finish(mut self) -> Vec<u8> {
+            self.data.extend_from_slice(&FOOTER_MAGIC.to_be_bytes());
+            self.data.extend_from_slice(&0u32.to_be_bytes());
+            let checksum = u64::from(crc32fast::hash(&self.data));
+            self.data.extend_from_slice(&checksum.to_be_bytes());
+            self.data
+        }
+    }
+}
@@ -0,0 +1,1259 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use columnar::{BytesColumn, Column, ColumnIndex, ColumnValues, Dictionary, OptionalIndex};
+use common::OwnedBytes;
+
+use super::compression::lz4_decompress;
+use super::data_input::{DataInput, LuceneFile, ID_LEN};
+use super::field_infos::LuceneFieldInfo;
+use super::packed_ints::{DirectMonotonicMeta, DirectMonotonicReader, DirectReader};
+use super::segment_files::SegmentFiles;
+use crate::fastfield::ComputedColumnValues;
+use crate::DocId;
+
+const DATA_EXTENSION: &str = "dvd";
+const DATA_CODEC: &str = "Lucene90DocValuesData";
+const META_EXTENSION: &str = "dvm";
+const META_CODEC: &str = "Lucene90DocValuesMetadata";
+const VERSION: u32 = 0;
+
+const NUMERIC: u8 = 0;
+const BINARY: u8 = 1;
+const SORTED: u8 = 2;
+const SORTED_SET: u8 = 3;
+const SORTED_NUMERIC: u8 = 4;
+
+/// The terms dictionaries are compressed in blocks of 64 terms.
+const TERMS_DICT_BLOCK_SHIFT: u32 = 6;
+/// `IndexedDISI.MAX_ARRAY_LENGTH`: the blocks with more documents are bitmaps.
+const DISI_MAX_ARRAY_LENGTH: u32 = 4095;
+const DISI_BLOCK_SHIFT: u32 = 16;
+const DISI_DENSE_BLOCK_LONGS: usize = 1024;
+
+/// The location of the `IndexedDISI` of the documents having a value.
+#[derive(Clone, Copy)]
+struct DocsWithFieldEntry {
+    /// -2 if no document has a value, -1 if all the documents have a value.
+    offset: i64,
+    length: i64,
+    dense_rank_power: i8,
+}
+
+impl DocsWithFieldEntry {
+    fn read(input: &mut DataInput) -> crate::Result<DocsWithFieldEntry> {
+        let offset = input.read_i64()?;
+        let length = input.read_i64()?;
+        let _jump_table_entry_count = input.read_i16()?;
+        let dense_rank_power = input.read_byte()? as i8;
+        if dense_rank_power != -1 && !(7..=15).contains(&dense_rank_power) {
+            return Err(input.corruption("Invalid dense rank power"));
+        }
+        Ok(DocsWithFieldEntry {
+            offset,
+            length,
+            dense_rank_power,
+        })
+    }
+}
+
+/// The documents having a value.
+enum DocsWithField {
+    None,
+    All,
+    Some(Vec<DocId>),
+}
+
+struct NumericEntry {
+    docs_with_field: DocsWithFieldEntry,
+    num_values: u64,
+    /// The distinct values, when the packed integers are their indexes.
+    table: Option<Vec<i64>>,
+    /// The values are split in blocks of `1 << block_shift` values, each with its own number of
+    /// bits per value.
+    block_shift: Option<u32>,
+    bits_per_value: u32,
+    min_value: i64,
+    gcd: i64,
+    values_offset: u64,
+    values_length: u64,
+}
+
+impl NumericEntry {
+    fn read(input: &mut DataInput) -> crate::Result<NumericEntry> {
+        let docs_with_field = DocsWithFieldEntry::read(input)?;
+        let num_values = read_u64(input)?;
+        let table_size = input.read_i32()?;
+        if table_size > 256 {
+            return Err(input.corruption("Invalid table size"));
+        }
+        let table = if table_size >= 0 {
+            Some(
+                (0..table_size)
+                    .map(|_| input.read_i64())
+                    .collect::<crate::Result<_>>()?,
+            )
+        } else {
+            None
+        };
+        let block_shift = if table_size < -1 {
+            Some((-2 - table_size) as u32)
+        } else {
+            None
+        };
+        // 0xFF when the values are split in blocks.
+        let bits_per_value = u32::from(input.read_byte()?);
+        if block_shift.is_none() && bits_per_value > 64 {
+            return Err(input.corruption("Invalid number of bits per value"));
+        }
+        let min_value = input.read_i64()?;
+        let gcd = input.read_i64()?;
+        let values_offset = read_u64(input)?;
+        let values_length = read_u64(input)?;
+        let _value_jump_table_offset = input.read_i64()?;
+        Ok(NumericEntry {
+            docs_with_field,
+            num_values,
+            table,
+            block_shift,
+            bits_per_value,
+            min_value,
+            gcd,
+            values_offset,
+            values_length,
+        })
+    }
+}
+
+/// The index of the first value of each document with values, followed by the number of
+/// values.
+struct AddressesEntry {
+    meta: DirectMonotonicMeta,
+    offset: u64,
+    length: u64,
+}
+
+impl AddressesEntry {
+    fn read(input: &mut DataInput, num_docs_with_field: u64) -> crate::Result<AddressesEntry> {
+        let offset = read_u64(input)?;
+        let block_shift = input.read_vint()?;
+        let meta = DirectMonotonicMeta::read(input, num_docs_with_field + 1, block_shift)?;
+        let length = read_u64(input)?;
+        Ok(AddressesEntry {
+            meta,
+            offset,
+            length,
+        })
+    }
+}
+
+struct SortedNumericEntry {
+    numeric: NumericEntry,
+    num_docs_with_field: u64,
+    /// `None` if every document with values has a single value.
+    addresses: Option<AddressesEntry>,
+}
+
+impl SortedNumericEntry {
+    fn read(input: &mut DataInput) -> crate::Result<SortedNumericEntry> {
+        let numeric = NumericEntry::read(input)?;
+        let num_docs_with_field = read_count(input)?;
+        let addresses = if num_docs_with_field != numeric.num_values {
+            Some(AddressesEntry::read(input, num_docs_with_field)?)
+        } else {
+            None
+        };
+        Ok(SortedNumericEntry {
+            numeric,
+            num_docs_with_field,
+            addresses,
+        })
+    }
+}
+
+struct BinaryEntry {
+    data_offset: u64,
+    data_length: u64,
+    docs_with_field: DocsWithFieldEntry,
+    num_docs_with_field: u64,
+    min_length: u64,
+    /// `None` if all the values have the same length.
+    addresses: Option<AddressesEntry>,
+}
+
+impl BinaryEntry {
+    fn read(input: &mut DataInput) -> crate::Result<BinaryEntry> {
+        let data_offset = read_u64(input)?;
+        let data_length = read_u64(input)?;
+        let docs_with_field = DocsWithFieldEntry::read(input)?;
+        let num_docs_with_field = read_count(input)?;
+        let min_length = read_count(input)?;
+        let max_length = read_count(input)?;
+        let addresses = if min_length < max_length {
+            Some(AddressesEntry::read(input, num_docs_with_field)?)
+        } else {
+            None
+        };
+        Ok(BinaryEntry {
+            data_offset,
+            data_length,
+            docs_with_field,
+            num_docs_with_field,
+            min_length,
+            addresses,
+        })
+    }
+}
+
+struct TermsDictEntry {
+    num_terms: u64,
+    /// The offsets of the blocks of 64 terms.
+    addresses_meta: DirectMonotonicMeta,
+    data_offset: u64,
+    data_length: u64,
+    addresses_offset: u64,
+    addresses_length: u64,
+}
+
+impl TermsDictEntry {
+    fn read(input: &mut DataInput) -> crate::Result<TermsDictEntry> {
+        let num_terms = input.read_vlong()?;
+        let block_shift = input.read_i32()? as u32;
+        let num_blocks = (num_terms + (1 << TERMS_DICT_BLOCK_SHIFT) - 1) >> TERMS_DICT_BLOCK_SHIFT;
+        let addresses_meta = DirectMonotonicMeta::read(input, num_blocks, block_shift)?;
+        let _max_term_length = input.read_i32()?;
+        let _max_block_length = input.read_i32()?;
+        let data_offset = read_u64(input)?;
+        let data_length = read_u64(input)?;
+        let addresses_offset = read_u64(input)?;
+        let addresses_length = read_u64(input)?;
+        // The index of the terms, to seek by term, which is not needed to read all the terms.
+        let index_shift = input.read_i32()? as u32;
+        if index_shift >= 64 {
+            return Err(input.corruption("Invalid terms index shift"));
+        }
+        let index_size = (num_terms + (1 << index_shift) - 1) >> index_shift;
+        DirectMonotonicMeta::read(input, index_size + 1, block_shift)?;
+        for _ in 0..4 {
+            let _index_offset_or_length = input.read_i64()?;
+        }
+        Ok(TermsDictEntry {
+            num_terms,
+            addresses_meta,
+            data_offset,
+            data_length,
+            addresses_offset,
+            addresses_length,
+        })
+    }
+}
+
+enum DocValuesEntry {
+    Numeric(NumericEntry),
+    Binary(BinaryEntry),
+    Sorted(NumericEntry, TermsDictEntry),
+    SortedSet(SortedNumericEntry, TermsDictEntry),
+    SortedNumeric(SortedNumericEntry),
+}
+
+fn read_u64(input: &mut DataInput) -> crate::Result<u64> {
+    let value = input.read_i64()?;
+    u64::try_from(value).map_err(|_| input.corruption("Negative offset or count"))
+}
+
+fn read_count(input: &mut DataInput) -> crate::Result<u64> {
+    let value = input.read_i32()?;
+    u64::try_from(value).map_err(|_| input.corruption("Negative count"))
+}
+
+/// Reads the doc values of the fields written by a `Lucene90DocValuesFormat`, from its `.dvm`
+/// and `.dvd` files.
+///
+/// The doc values are decoded in memory, as tantivy columns.
+pub(crate) struct DocValuesReader {
+    data_file: LuceneFile,
+    entries: HashMap<u32, DocValuesEntry>,
+    max_doc: u32,
+}
+
+impl DocValuesReader {
+    pub fn open(
+        files: &SegmentFiles,
+        segment_id: [u8; ID_LEN],
+        suffix: &str,
+        field_infos: &[LuceneFieldInfo],
+        max_doc: u32,
+    ) -> crate::Result<DocValuesReader> {
+        let meta_file = files.open_file(suffix, META_EXTENSION)?;
+        let mut input = meta_file.input();
+        input.read_segment_header(META_CODEC, VERSION..=VERSION, segment_id, suffix)?;
+        let mut entries = HashMap::new();
+        loop {
+            let number = input.read_i32()?;
+            if number == -1 {
+                break;
+            }
+            if !field_infos
+                .iter()
+                .any(|field_info| i64::from(field_info.number()) == i64::from(number))
+            {
+                return Err(input.corruption(&format!("Unknown field number {number}")));
+            }
+            let entry = match input.read_byte()? {
+                NUMERIC => DocValuesEntry::Numeric(NumericEntry::read(&mut input)?),
+                BINARY => DocValuesEntry::Binary(BinaryEntry::read(&mut input)?),
+                SORTED => DocValuesEntry::Sorted(
+                    NumericEntry::read(&mut input)?,
+                    TermsDictEntry::read(&mut input)?,
+                ),
+                SORTED_SET => match input.read_byte()? {
+                    // A single value per document.
+                    0 => DocValuesEntry::Sorted(
+                        NumericEntry::read(&mut input)?,
+                        TermsDictEntry::read(&mut input)?,
+                    ),
+                    1 => DocValuesEntry::SortedSet(
+                        SortedNumericEntry::read(&mut input)?,
+                        TermsDictEntry::read(&mut input)?,
+                    ),
+                    _ => return Err(input.corruption("Invalid sorted set marker")),
+                },
+                SORTED_NUMERIC => {
+                    DocValuesEntry::SortedNumeric(SortedNumericEntry::read(&mut input)?)
+                }
+                _ => return Err(input.corruption("Invalid doc values type")),
+            };
+            entries.insert(number as u32, entry);
+        }
+        let data_file = files.open_file(suffix, DATA_EXTENSION)?;
+        data_file
+            .input()
+            .read_segment_header(DATA_CODEC, VERSION..=VERSION, segment_id, suffix)?;
+        Ok(DocValuesReader {
+            data_file,
+            entries,
+            max_doc,
+        })
+    }
+
+    /// Returns the column of the numeric or sorted numeric doc values of a field.
+    pub fn numeric_column(&self, number: u32) -> crate::Result<Option<Column<i64>>> {
+        let column = match self.entries.get(&number) {
+            Some(DocValuesEntry::Numeric(entry)) => {
+                let docs = self.read_docs_with_field(&entry.docs_with_field, entry.num_values)?;
+                let values = self.read_numeric_values(entry)?;
+                self.single_valued_column(docs, values)?
+            }
+            Some(DocValuesEntry::SortedNumeric(entry)) => {
+                let values = self.read_numeric_values(&entry.numeric)?;
+                self.multi_valued_column(entry, values)?
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(column))
+    }
+
+    /// Returns the column of the sorted, sorted set or binary doc values of a field.
+    pub fn bytes_column(&self, number: u32) -> crate::Result<Option<BytesColumn>> {
+        let (dictionary, term_ord_column) = match self.entries.get(&number) {
+            Some(DocValuesEntry::Sorted(entry, terms_dict_entry)) => {
+                let terms = self.read_terms(terms_dict_entry)?;
+                let docs = self.read_docs_with_field(&entry.docs_with_field, entry.num_values)?;
+                let ords = self.read_ords(entry, terms.len())?;
+                (terms, self.single_valued_column(docs, ords)?)
+            }
+            Some(DocValuesEntry::SortedSet(entry, terms_dict_entry)) => {
+                let terms = self.read_terms(terms_dict_entry)?;
+                let ords = self.read_ords(&entry.numeric, terms.len())?;
+                (terms, self.multi_valued_column(entry, ords)?)
+            }
+            Some(DocValuesEntry::Binary(entry)) => {
+                let docs =
+                    self.read_docs_with_field(&entry.docs_with_field, entry.num_docs_with_field)?;
+                let values = self.read_binary_values(entry)?;
+                // tantivy's bytes columns are dictionary encoded.
+                let mut terms = values.clone();
+                terms.sort_unstable();
+                terms.dedup();
+                let ords = values
+                    .iter()
+                    .map(|value| terms.binary_search(value).unwrap_or_default() as u64)
+                    .collect();
+                (terms, self.single_valued_column(docs, ords)?)
+            }
+            _ => return Ok(None),
+        };
+        let dictionary = self.build_dictionary(&dictionary)?;
+        Ok(Some(BytesColumn::new(
+            Arc::new(dictionary),
+            term_ord_column,
+        )))
+    }
+
+    /// Returns the documents having doc values for a field.
+    pub fn docs_with_values(&self, number: u32) -> crate::Result<Vec<DocId>> {
+        let (docs_with_field, num_docs_with_field) = match self.entries.get(&number) {
+            Some(DocValuesEntry::Numeric(entry)) | Some(DocValuesEntry::Sorted(entry, _)) => {
+                (&entry.docs_with_field, entry.num_values)
+            }
+            Some(DocValuesEntry::SortedNumeric(entry))
+            | Some(DocValuesEntry::SortedSet(entry, _)) => {
+                (&entry.numeric.docs_with_field, entry.num_docs_with_field)
+            }
+            Some(DocValuesEntry::Binary(entry)) => {
+                (&entry.docs_with_field, entry.num_docs_with_field)
+            }
+            None => return Ok(Vec::new()),
+        };
+        Ok(
+            match self.read_docs_with_field(docs_with_field, num_docs_with_field)? {
+                DocsWithField::None => Vec::new(),
+                DocsWithField::All => (0..self.max_doc).collect(),
+                DocsWithField::Some(docs) => docs,
+            },
+        )
+    }
+
+    fn corruption(&self, comment: &str) -> crate::TantivyError {
+        self.data_file.input().corruption(comment)
+    }
+
+    fn single_valued_column<T>(
+        &self,
+        docs: DocsWithField,
+        values: Vec<T>,
+    ) -> crate::Result<Column<T>>
+    where
+        T: Copy + PartialOrd + Default + Send + Sync + std::fmt::Debug + 'static,
+    {
+        let (index, num_values) = match docs {
+            DocsWithField::None => (
+                ColumnIndex::Empty {
+                    num_docs: self.max_doc,
+                },
+                0,
+            ),
+            DocsWithField::All => (ColumnIndex::Full, self.max_doc as usize),
+            DocsWithField::Some(docs) => (
+                ColumnIndex::Optional(OptionalIndex::for_row_ids(self.max_doc, &docs)),
+                docs.len(),
+            ),
+        };
+        if values.len() != num_values {
+            return Err(self.corruption("The number of values does not match the documents"));
+        }
+        Ok(Column {
+            index,
+            values: Arc::new(ComputedColumnValues::from(values)),
+        })
+    }
+
+    fn multi_valued_column<T>(
+        &self,
+        entry: &SortedNumericEntry,
+        values: Vec<T>,
+    ) -> crate::Result<Column<T>>
+    where
+        T: Copy + PartialOrd + Default + Send + Sync + std::fmt::Debug + 'static,
+    {
+        let docs = match self
+            .read_docs_with_field(&entry.numeric.docs_with_field, entry.num_docs_with_field)?
+        {
+            DocsWithField::None => Vec::new(),
+            DocsWithField::All => (0..self.max_doc).collect(),
+            DocsWithField::Some(docs) => docs,
+        };
+        let num_docs_with_field = docs.len() as u64;
+        let addresses = match &entry.addresses {
+            Some(addresses) => addresses
+                .meta
+                .reader(self.data_file.slice(addresses.offset, addresses.length)?)
+                .to_vec(),
+            None => (0..=num_docs_with_field as i64).collect(),
+        };
+        if addresses.len() as u64 != num_docs_with_field + 1
+            || addresses.first() != Some(&0)
+            || addresses.windows(2).any(|window| window[0] > window[1])
+            || addresses.last() != Some(&(values.len() as i64))
+            || values.len() > u32::MAX as usize
+        {
+            return Err(self.corruption("Invalid addresses of the values"));
+        }
+        // The index of the first value of each document, followed by the number of values.
+        let mut start_offsets = Vec::with_capacity(self.max_doc as usize + 1);
+        let mut docs_with_field = docs.iter().zip(&addresses[1..]).peekable();
+        let mut start_offset = 0;
+        for doc in 0..self.max_doc {
+            start_offsets.push(start_offset);
+            if let Some((_, &end_offset)) =
+                docs_with_field.next_if(|(&doc_with_field, _)| doc_with_field == doc)
+            {
+                start_offset = end_offset as u32;
+            }
+        }
+        start_offsets.push(values.len() as u32);
+        let start_index_column: Arc<dyn ColumnValues<u32>> =
+            Arc::new(ComputedColumnValues::from(start_offsets));
+        Ok(Column {
+            index: ColumnIndex::Multivalued(start_index_column.into()),
+            values: Arc::new(ComputedColumnValues::from(values)),
+        })
+    }
+
+    /// Decodes the `IndexedDISI` of the documents having a value: blocks of 65536 documents,
+    /// stored as a list of doc ids, as a bitmap, or implicitly when they are all present.
+    fn read_docs_with_field(
+        &self,
+        entry: &DocsWithFieldEntry,
+        num_docs_with_field: u64,
+    ) -> crate::Result<DocsWithField> {
+        match entry.offset {
+            -2 => return Ok(DocsWithField::None),
+            -1 => return Ok(DocsWithField::All),
+            _ => {}
+        }
+        let data = self
+            .data_file
+            .slice(entry.offset as u64, entry.length as u64)?;
+        let mut input = DataInput::new(self.data_file.path(), data.as_slice());
+        let num_docs_with_field = num_docs_with_field.min(u64::from(self.max_doc)) as usize;
+        let mut docs = Vec::with_capacity(num_docs_with_field);
+        while docs.len() < num_docs_with_field {
+            let block_base = u32::from(input.read_u16()?) << DISI_BLOCK_SHIFT;
+            let num_docs_in_block = u32::from(input.read_u16()?) + 1;
+            let block_start = docs.len();
+            if num_docs_in_block <= DISI_MAX_ARRAY_LENGTH {
+                for _ in 0..num_docs_in_block {
+                    docs.push(block_base | u32::from(input.read_u16()?));
+                }
+            } else if num_docs_in_block == 1 << DISI_BLOCK_SHIFT {
+                docs.extend(block_base..block_base + num_docs_in_block);
+            } else {
+                if entry.dense_rank_power != -1 {
+                    // The rank of the words of the bitmap, which is not needed to iterate.
+                    input.skip(2 * ((1 << DISI_BLOCK_SHIFT) >> entry.dense_rank_power))?;
+                }
+                for word_ord in 0..DISI_DENSE_BLOCK_LONGS as u32 {
+                    let mut word = input.read_i64()? as u64;
+                    while word != 0 {
+                        docs.push(block_base | (word_ord * 64) | word.trailing_zeros());
+                        word &= word - 1;
+                    }
+                }
+                if docs.len() - block_start != num_docs_in_block as usize {
+                    return Err(input.corruption("Invalid number of documents in dense block"));
+                }
+            }
+        }
+        if docs.len() != num_docs_with_field
+            || docs.windows(2).any(|window| window[0] >= window[1])
+            || docs.last().map_or(false, |&doc| doc >= self.max_doc)
+        {
+            return Err(input.corruption("Invalid documents with values"));
+        }
+        Ok(DocsWithField::Some(docs))
+    }
+
+    fn read_numeric_values(&self, entry: &NumericEntry) -> crate::Result<Vec<i64>> {
+        let num_values = usize::try_from(entry.num_values)
+            .ok()
+            .filter(|&num_values| num_values <= u32::MAX as usize)
+            .ok_or_else(|| self.corruption("Too many values"))?;
+        if entry.bits_per_value == 0 {
+            return Ok(vec![entry.min_value; num_values]);
+        }
+        let data = self
+            .data_file
+            .slice(entry.values_offset, entry.values_length)?;
+        let mut values = Vec::with_capacity(num_values);
+        if let Some(block_shift) = entry.block_shift {
+            if block_shift >= 32 {
+                return Err(self.corruption("Invalid block shift"));
+            }
+            // Each block has its own number of bits per value, and its own minimum value.
+            let mut input = DataInput::new(self.data_file.path(), data.as_slice());
+            while values.len() < num_values {
+                let block_len = (num_values - values.len()).min(1 << block_shift);
+                let bits_per_value = u32::from(input.read_byte()?);
+                if bits_per_value > 64 {
+                    return Err(input.corruption("Invalid number of bits per value"));
+                }
+                let delta = input.read_i64()?;
+                if bits_per_value == 0 {
+                    values.resize(values.len() + block_len, delta);
+                    continue;
+                }
+                let length = input.read_i32()? as usize;
+                let start = input.offset();
+                input.skip(length)?;
+                let reader = DirectReader::new(data.slice(start..start + length), bits_per_value);
+                values.extend((0..block_len as u64).map(|index| {
+                    entry
+                        .gcd
+                        .wrapping_mul(reader.get(index) as i64)
+                        .wrapping_add(delta)
+                }));
+            }
+            return Ok(values);
+        }
+        let reader = DirectReader::new(data, entry.bits_per_value);
+        for index in 0..num_values as u64 {
+            let packed = reader.get(index);
+            let value = match &entry.table {
+                Some(table) => *table
+                    .get(packed as usize)
+                    .ok_or_else(|| self.corruption("Invalid index in the table of values"))?,
+                None => entry
+                    .min_value
+                    .wrapping_add(entry.gcd.wrapping_mul(packed as i64)),
+            };
+            values.push(value);
+        }
+        Ok(values)
+    }
+
+    fn read_ords(&self, entry: &NumericEntry, num_terms: usize) -> crate::Result<Vec<u64>> {
+        self.read_numeric_values(entry)?
+            .into_iter()
+            .map(|ord| {
+                u64::try_from(ord)
+                    .ok()
+                    .filter(|&ord| ord < num_terms as u64)
+                    .ok_or_else(|| self.corruption("Invalid term ordinal"))
+            })
+            .collect()
+    }
+
+    fn read_binary_values(&self, entry: &BinaryEntry) -> crate::Result<Vec<Vec<u8>>> {
+        let data = self.data_file.slice(entry.data_offset, entry.data_length)?;
+        let addresses: Vec<u64> = match &entry.addresses {
+            Some(addresses) => addresses
+                .meta
+                .reader(self.data_file.slice(addresses.offset, addresses.length)?)
+                .to_vec()
+                .into_iter()
+                .map(|address| address as u64)
+                .collect(),
+            None => (0..=entry.num_docs_with_field)
+                .map(|doc_ord| doc_ord * entry.min_length)
+                .collect(),
+        };
+        addresses
+            .windows(2)
+            .map(|window| {
+                data.as_slice()
+                    .get(window[0] as usize..window[1] as usize)
+                    .map(<[u8]>::to_vec)
+                    .ok_or_else(|| self.corruption("Invalid address of binary value"))
+            })
+            .collect()
+    }
+
+    /// Decodes all the terms of a terms dictionary: blocks of 64 terms, whose first term is
+    /// stored as is, and whose other terms are prefix compressed and then compressed with LZ4,
+    /// with the first term as a dictionary.
+    fn read_terms(&self, entry: &TermsDictEntry) -> crate::Result<Vec<Vec<u8>>> {
+        let data = self.data_file.slice(entry.data_offset, entry.data_length)?;
+        let block_offsets: DirectMonotonicReader = entry.addresses_meta.reader(
+            self.data_file
+                .slice(entry.addresses_offset, entry.addresses_length)?,
+        );
+        let num_terms =
+            usize::try_from(entry.num_terms).map_err(|_| self.corruption("Too many terms"))?;
+        let mut terms = Vec::with_capacity(num_terms);
+        let path = self.data_file.path();
+        for block in 0..block_offsets.num_values() {
+            let mut input = DataInput::new(path, data.as_slice());
+            let block_offset = usize::try_from(block_offsets.get(block))
+                .map_err(|_| input.corruption("Invalid offset of block of terms"))?;
+            input.seek(block_offset)?;
+            let mut term = input.read_bytes_ref()?.to_vec();
+            let num_terms_in_block = (num_terms - terms.len()).min(1 << TERMS_DICT_BLOCK_SHIFT);
+            terms.push(term.clone());
+            if num_terms_in_block == 1 {
+                continue;
+            }
+            let block_len = input.read_vint()? as usize;
+            let mut buffer = term.clone();
+            lz4_decompress(&mut input, block_len, &mut buffer)?;
+            let mut block_input = DataInput::new(path, &buffer[term.len()..]);
+            for _ in 1..num_terms_in_block {
+                let token = block_input.read_byte()?;
+                let mut prefix_len = usize::from(token & 0x0f);
+                let mut suffix_len = usize::from(token >> 4) + 1;
+                if prefix_len == 15 {
+                    prefix_len += block_input.read_vint()? as usize;
+                }
+                if suffix_len == 16 {
+                    suffix_len += block_input.read_vint()? as usize;
+                }
+                if prefix_len > term.len() {
+                    return Err(block_input.corruption("Invalid prefix length"));
+                }
+                term.truncate(prefix_len);
+                term.extend_from_slice(block_input.read_bytes(suffix_len)?);
+                terms.push(term.clone());
+            }
+        }
+        if terms.len() != num_terms {
+            return Err(self.corruption("Invalid number of terms"));
+        }
+        Ok(terms)
+    }
+
+    fn build_dictionary(&self, terms: &[Vec<u8>]) -> crate::Result<Dictionary> {
+        if terms.windows(2).any(|window| window[0] >= window[1]) {
+            return Err(self.corruption("The terms are not sorted"));
+        }
+        let mut builder = <Dictionary>::builder(Vec::new())?;
+        for term in terms {
+            builder.insert(term, &())?;
+        }
+        Ok(Dictionary::from_bytes(OwnedBytes::new(builder.finish()?))?)
+    }
+}
+
+#[cfg(all(test, feature = "mmap"))]
+pub(crate) mod tests {
+    use super::super::compression::tests::lz4_literals;
+    use super::super::data_input::tests::DataOutput;
+    use super::super::field_infos::tests::{write_field_infos, TestField};
+    use super::super::field_infos::{read_field_infos, LuceneDocValuesType, LuceneIndexOptions};
+    use super::super::packed_ints::tests::{direct_monotonic_write, direct_write};
+    use super::super::segment_files::SegmentFiles;
+    use super::DocValuesReader;
+    use crate::lucene::data_input::LuceneFile;
+    use crate::DocId;
+
+    /// The doc values of a field of a test segment, by doc id.
+    pub enum TestDocValues {
+        Numeric(Vec<Option<i64>>),
+        Binary(Vec<Option<Vec<u8>>>),
+        Sorted(Vec<Option<String>>),
+        SortedSet(Vec<Vec<String>>),
+        SortedNumeric(Vec<Vec<i64>>),
+    }
+
+    /// Returns the number of bits per value supported by the `DirectWriter` for values up to
+    /// `max_value`.
+    fn bits_required(max_value: u64) -> u32 {
+        let bits = 64 - max_value.leading_zeros();
+        [1, 2, 4, 8, 12, 16, 20, 24, 28, 32, 40, 48, 56, 64]
+            .into_iter()
+            .find(|&supported_bits| supported_bits >= bits)
+            .unwrap()
+    }
+
+    fn write_packed(values: &[u64], meta: &mut DataOutput, data: &mut DataOutput) -> u32 {
+        let bits_per_value = bits_required(values.iter().copied().max().unwrap_or(0));
+        direct_write(values, bits_per_value, &mut data.data);
+        meta.data.push(bits_per_value as u8);
+        bits_per_value
+    }
+
+    fn write_docs_with_field(
+        docs: &[DocId],
+        max_doc: u32,
+        meta: &mut DataOutput,
+        data: &mut DataOutput,
+    ) {
+        if docs.is_empty() || docs.len() == max_doc as usize {
+            meta.write_i64(if docs.is_empty() { -2 } else { -1 });
+            meta.write_i64(0);
+            meta.data.extend_from_slice(&(-1i16).to_le_bytes());
+            meta.data.push(-1i8 as u8);
+            return;
+        }
+        let offset = data.data.len();
+        let mut start = 0;
+        while start < docs.len() {
+            let block = docs[start] >> 16;
+            let len = docs[start..]
+                .iter()
+                .take_while(|&&doc| doc >> 16 == block)
+                .count();
+            let block_docs = &docs[start..start + len];
+            data.data.extend_from_slice(&(block as u16).to_le_bytes());
+            data.data
+                .extend_from_slice(&((len - 1) as u16).to_le_bytes());
+            if len <= 4095 {
+                for &doc in block_docs {
+                    data.data.extend_from_slice(&(doc as u16).to_le_bytes());
+                }
+            } else if len < 65536 {
+                // The rank of the words, with a dense rank power of 9.
+                data.data.extend_from_slice(&[0u8; 2 * (65536 >> 9)]);
+                let mut words = [0u64; 1024];
+                for &doc in block_docs {
+                    words[(doc & 0xffff) as usize / 64] |= 1 << (doc % 64);
+                }
+                for word in words {
+                    data.write_i64(word as i64);
+                }
+            }
+            start += len;
+        }
+        // The block of `NO_MORE_DOCS`.
+        for value in [0x7fffu16, 0, 0xffff] {
+            data.data.extend_from_slice(&value.to_le_bytes());
+        }
+        meta.write_i64(offset as i64);
+        meta.write_i64((data.data.len() - offset) as i64);
+        meta.data.extend_from_slice(&(-1i16).to_le_bytes());
+        meta.data.push(9);
+    }
+
+    /// Writes numeric values like `Lucene90DocValuesConsumer.writeValues()`: as a constant, as
+    /// indexes in a table of up to 4 distinct values, in blocks of 64 values when there are at
+    /// least 200 values, or else as multiples of their greatest common divisor.
+    fn write_numeric(
+        docs: &[DocId],
+        values: &[i64],
+        max_doc: u32,
+        meta: &mut DataOutput,
+        data: &mut DataOutput,
+    ) {
+        write_docs_with_field(docs, max_doc, meta, data);
+        meta.write_i64(values.len() as i64);
+        let min_value = values.iter().copied().min().unwrap_or(0);
+        let max_value = values.iter().copied().max().unwrap_or(0);
+        let gcd = values.iter().fold(0u64, |gcd, &value| {
+            let (mut a, mut b) = (gcd, value.wrapping_sub(min_value) as u64);
+            while b != 0 {
+                (a, b) = (b, a % b);
+            }
+            a
+        });
+        let mut table = values.to_vec();
+        table.sort_unstable();
+        table.dedup();
+        let offset = data.data.len();
+        if min_value == max_value {
+            meta.write_i32(-1);
+            meta.data.push(0);
+            meta.write_i64(min_value);
+            meta.write_i64(1);
+        } else if table.len() <= 4 {
+            meta.write_i32(table.len() as i32);
+            for &value in &table {
+                meta.write_i64(value);
+            }
+            let indexes: Vec<u64> = values
+                .iter()
+                .map(|value| table.binary_search(value).unwrap() as u64)
+                .collect();
+            write_packed(&indexes, meta, data);
+            meta.write_i64(min_value);
+            meta.write_i64(1);
+        } else if values.len() >= 200 {
+            meta.write_i32(-2 - 6);
+            meta.data.push(0xff);
+            meta.write_i64(min_value);
+            meta.write_i64(gcd as i64);
+            for block in values.chunks(64) {
+                let block_min = block.iter().copied().min().unwrap();
+                let deltas: Vec<u64> = block
+                    .iter()
+                    .map(|&value| value.wrapping_sub(block_min) as u64 / gcd)
+                    .collect();
+                if deltas.iter().all(|&delta| delta == 0) {
+                    data.data.push(0);
+                    data.write_i64(block_min);
+                    continue;
+                }
+                let mut packed = DataOutput::default();
+                write_packed(&deltas, data, &mut packed);
+                data.write_i64(block_min);
+                data.write_i32(packed.data.len() as i32);
+                data.data.extend_from_slice(&packed.data);
+            }
+        } else {
+            meta.write_i32(-1);
+            let deltas: Vec<u64> = values
+                .iter()
+                .map(|&value| value.wrapping_sub(min_value) as u64 / gcd)
+                .collect();
+            write_packed(&deltas, meta, data);
+            meta.write_i64(min_value);
+            meta.write_i64(gcd as i64);
+        }
+        meta.write_i64(offset as i64);
+        meta.write_i64((data.data.len() - offset) as i64);
+        meta.write_i64(-1);
+    }
+
+    fn write_addresses(addresses: &[i64], meta: &mut DataOutput, data: &mut DataOutput) {
+        meta.write_i64(data.data.len() as i64);
+        meta.write_vlong(2);
+        let (addresses_meta, addresses_data) = direct_monotonic_write(addresses, 2);
+        meta.data.extend_from_slice(&addresses_meta.data);
+        data.data.extend_from_slice(&addresses_data);
+        meta.write_i64(addresses_data.len() as i64);
+    }
+
+    fn write_sorted_numeric(values: &[Vec<i64>], meta: &mut DataOutput, data: &mut DataOutput) {
+        let docs: Vec<DocId> = (0..values.len() as DocId)
+            .filter(|&doc| !values[doc as usize].is_empty())
+            .collect();
+        let flat_values: Vec<i64> = values.iter().flatten().copied().collect();
+        write_numeric(&docs, &flat_values, values.len() as u32, meta, data);
+        meta.write_i32(docs.len() as i32);
+        if docs.len() != flat_values.len() {
+            let mut addresses = vec![0];
+            for &doc in &docs {
+                addresses.push(addresses.last().unwrap() + values[doc as usize].len() as i64);
+            }
+            write_addresses(&addresses, meta, data);
+        }
+    }
+
+    /// Writes the terms in blocks of 64 terms, like
+    /// `Lucene90DocValuesConsumer.addTermsDict()`.
+    fn write_terms_dict(terms: &[&str], meta: &mut DataOutput, data: &mut DataOutput) {
+        meta.write_vlong(terms.len() as u64);
+        let terms_offset = data.data.len();
+        let mut block_offsets = Vec::new();
+        for block in terms.chunks(64) {
+            block_offsets.push((data.data.len() - terms_offset) as i64);
+            data.write_string(block[0]);
+            if block.len() == 1 {
+                continue;
+            }
+            let mut suffixes = DataOutput::default();
+            for pair in block.windows(2) {
+                let (previous, term) = (pair[0].as_bytes(), pair[1].as_bytes());
+                let prefix_len = previous
+                    .iter()
+                    .zip(term)
+                    .take_while(|(left, right)| left == right)
+                    .count();
+                let suffix_len = term.len() - prefix_len;
+                suffixes
+                    .data
+                    .push((prefix_len.min(15) | (suffix_len - 1).min(15) << 4) as u8);
+                if prefix_len >= 15 {
+                    suffixes.write_vlong((prefix_len - 15) as u64);
+                }
+                if suffix_len > 15 {
+                    suffixes.write_vlong((suffix_len - 16) as u64);
+                }
+                suffixes.data.extend_from_slice(&term[prefix_len..]);
+            }
+            data.write_vlong(suffixes.data.len() as u64);
+            data.data.extend_from_slice(&lz4_literals(&suffixes.data));
+        }
+        let terms_len = data.data.len() - terms_offset;
+        meta.write_i32(2);
+        let (addresses_meta, addresses_data) = direct_monotonic_write(&block_offsets, 2);
+        meta.data.extend_from_slice(&addresses_meta.data);
+        meta.write_i32(terms.iter().map(|term| term.len()).max().unwrap_or(0) as i32);
+        meta.write_i32(0);
+        meta.write_i64(terms_offset as i64);
+        meta.write_i64(terms_len as i64);
+        meta.write_i64(data.data.len() as i64);
+        meta.write_i64(addresses_data.len() as i64);
+        data.data.extend_from_slice(&addresses_data);
+        // The index of the terms, which is not read.
+        let index_shift = 10;
+        meta.write_i32(index_shift);
+        let index_size = (terms.len() + (1 << index_shift) - 1) >> index_shift;
+        let (index_meta, _) = direct_monotonic_write(&vec![0; index_size + 1], 2);
+        meta.data.extend_from_slice(&index_meta.data);
+        for _ in 0..4 {
+            meta.write_i64(0);
+        }
+    }
+
+    /// Returns the sorted distinct terms, and the ordinals of the terms of the documents.
+    fn term_ords(values: &[Vec<String>]) -> (Vec<&str>, Vec<Vec<i64>>) {
+        let mut terms: Vec<&str> = values.iter().flatten().map(String::as_str).collect();
+        terms.sort_unstable();
+        terms.dedup();
+        let ords = values
+            .iter()
+            .map(|doc_terms| {
+                let mut ords: Vec<i64> = doc_terms
+                    .iter()
+                    .map(|term| terms.binary_search(&term.as_str()).unwrap() as i64)
+                    .collect();
+                ords.sort_unstable();
+                ords
+            })
+            .collect();
+        (terms, ords)
+    }
+
+    /// Writes the `.dvm` and `.dvd` files of the doc values of fields, given by field number,
+    /// like the `Lucene90DocValuesFormat`, with the `Lucene90_0` suffix.
+    pub fn write_doc_values(
+        segment_id: [u8; 16],
+        fields: &[(u32, TestDocValues)],
+        max_doc: u32,
+    ) -> Vec<(&'static str, Vec<u8>)> {
+        let suffix = "Lucene90_0";
+        let mut meta = DataOutput::default();
+        meta.write_index_header("Lucene90DocValuesMetadata", 0, segment_id, suffix);
+        let mut data = DataOutput::default();
+        data.write_index_header("Lucene90DocValuesData", 0, segment_id, suffix);
+        for (number, doc_values) in fields {
+            meta.write_i32(*number as i32);
+            match doc_values {
+                TestDocValues::Numeric(values) => {
+                    meta.data.push(0);
+                    let docs: Vec<DocId> = (0..max_doc)
+                        .filter(|&doc| values[doc as usize].is_some())
+                        .collect();
+                    let values: Vec<i64> = values.iter().flatten().copied().collect();
+                    write_numeric(&docs, &values, max_doc, &mut meta, &mut data);
+                }
+                TestDocValues::Binary(values) => {
+                    meta.data.push(1);
+                    let docs: Vec<DocId> = (0..max_doc)
+                        .filter(|&doc| values[doc as usize].is_some())
+                        .collect();
+                    let values: Vec<&Vec<u8>> = values.iter().flatten().collect();
+                    meta.write_i64(data.data.len() as i64);
+                    let len: usize = values.iter().map(|value| value.len()).sum();
+                    meta.write_i64(len as i64);
+                    for value in &values {
+                        data.data.extend_from_slice(value);
+                    }
+                    write_docs_with_field(&docs, max_doc, &mut meta, &mut data);
+                    meta.write_i32(docs.len() as i32);
+                    let min_len = values.iter().map(|value| value.len()).min().unwrap_or(0);
+                    let max_len = values.iter().map(|value| value.len()).max().unwrap_or(0);
+                    meta.write_i32(min_len as i32);
+                    meta.write_i32(max_len as i32);
+                    if min_len < max_len {
+                        let mut addresses = vec![0];
+                        for value in &values {
+                            addresses.push(addresses.last().unwrap() + value.len() as i64);
+                        }
+                        write_addresses(&addresses, &mut meta, &mut data);
+                    }
+                }
+                TestDocValues::Sorted(values) => {
+                    meta.data.push(2);
+                    let values: Vec<Vec<String>> = values
+                        .iter()
+                        .map(|value| value.iter().cloned().collect())
+                        .collect();
+                    let (terms, ords) = term_ords(&values);
+                    let docs: Vec<DocId> = (0..max_doc)
+                        .filter(|&doc| !ords[doc as usize].is_empty())
+                        .collect();
+                    let ords: Vec<i64> = ords.into_iter().flatten().collect();
+                    write_numeric(&docs, &ords, max_doc, &mut meta, &mut data);
+                    write_terms_dict(&terms, &mut meta, &mut data);
+                }
+                TestDocValues::SortedSet(values) => {
+                    meta.data.push(3);
+                    meta.data.push(1);
+                    let (terms, ords) = term_ords(values);
+                    write_sorted_numeric(&ords, &mut meta, &mut data);
+                    write_terms_dict(&terms, &mut meta, &mut data);
+                }
+                TestDocValues::SortedNumeric(values) => {
+                    meta.data.push(4);
+                    write_sorted_numeric(values, &mut meta, &mut data);
+                }
+            }
+        }
+        meta.write_i32(-1);
+        vec![("dvm", meta.finish()), ("dvd", data.finish())]
+    }
+
+    /// Returns doc values of every type, whose documents with values are stored in sparse,
+    /// dense and full blocks when `max_doc` is greater than 65536.
+    pub fn sample_doc_values(max_doc: u32) -> Vec<(u32, TestDocValues)> {
+        let docs = 0..max_doc as i64;
+        vec![
+            (
+                0,
+                TestDocValues::Numeric(
+                    docs.clone()
+                        .map(|doc| (doc % 2 == 0).then_some(doc * 3 - 1000))
+                        .collect(),
+                ),
+            ),
+            (
+                1,
+                TestDocValues::Numeric(
+                    docs.clone()
+                        .map(|doc| (doc < 100).then_some([5, 7, -2][doc as usize % 3]))
+                        .collect(),
+                ),
+            ),
+            (
+                2,
+                TestDocValues::Numeric(docs.clone().map(|_| Some(42)).collect()),
+            ),
+            (
+                3,
+                TestDocValues::Numeric(
+                    docs.clone()
+                        .map(|doc| (doc % 1000 == 0).then_some(doc * 10 + 3))
+                        .collect(),
+                ),
+            ),
+            (
+                4,
+                TestDocValues::SortedNumeric(
+                    docs.clone()
+                        .map(|doc| {
+                            if doc < 300 {
+                                (doc..doc + doc % 3).collect()
+                            } else {
+                                Vec::new()
+                            }
+                        })
+                        .collect(),
+                ),
+            ),
+            (
+                5,
+                TestDocValues::Sorted(
+                    docs.clone()
+                        .map(|doc| (doc % 7 == 0).then(|| format!("term{:03}", doc % 100)))
+                        .collect(),
+                ),
+            ),
+            (
+                6,
+                TestDocValues::SortedSet(
+                    docs.clone()
+                        .map(|doc| {
+                            if doc < 50 {
+                                (0..doc % 4)
+                                    .map(|i| format!("tag{}", doc % 5 + i))
+                                    .collect()
+                            } else {
+                                Vec::new()
+                            }
+                        })
+                        .collect(),
+                ),
+            ),
+            (
+                7,
+                TestDocValues::Binary(
+                    docs.map(|doc| {
+                        (doc < 20 && doc != 3).then(|| vec![doc as u8; (doc % 4) as usize])
+                    })
+                    .collect(),
+                ),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_doc_values() -> crate::Result<()> {
+        let doc_values_types = [
+            LuceneDocValuesType::Numeric,
+            LuceneDocValuesType::Numeric,
+            LuceneDocValuesType::Numeric,
+            LuceneDocValuesType::Numeric,
+            LuceneDocValuesType::SortedNumeric,
+            LuceneDocValuesType::Sorted,
+            LuceneDocValuesType::SortedSet,
+            LuceneDocValuesType::Binary,
+        ];
+        let fields: Vec<TestField> = ["a", "b", "c", "d", "e", "f", "g", "h"]
+            .into_iter()
+            .zip(doc_values_types)
+            .map(|(name, doc_values_type)| TestField {
+                name,
+                index_options: LuceneIndexOptions::None,
+                has_payloads: false,
+                doc_values_type,
+                is_soft_deletes_field: false,
+            })
+            .collect();
+        let segment_id = [9u8; 16];
+        let max_doc = 70_000;
+        let tempdir = tempfile::TempDir::new().unwrap();
+        for (extension, data) in write_doc_values(segment_id, &sample_doc_values(max_doc), max_doc)
+        {
+            let path = tempdir.path().join(format!("_0_Lucene90_0.{extension}"));
+            std::fs::write(path, data).unwrap();
+        }
+        let field_infos = read_field_infos(
+            &LuceneFile::open(
+                "_0.fnm".into(),
+                common::OwnedBytes::new(write_field_infos(segment_id, &fields, "Lucene90")),
+            )?,
+            segment_id,
+            "",
+        )?;
+        let files = SegmentFiles::open(tempdir.path(), "_0", segment_id, false)?;
+        let reader =
+            DocValuesReader::open(&files, segment_id, "Lucene90_0", &field_infos, max_doc)?;
+
+        let column = reader.numeric_column(0)?.unwrap();
+        for doc in [0, 1, 2, 64, 65_534, 65_535, 65_536, 69_998, 69_999] {
+            let expected = (doc % 2 == 0).then_some(i64::from(doc) * 3 - 1000);
+            assert_eq!(column.first(doc), expected, "{doc}");
+        }
+        assert_eq!(reader.docs_with_values(0)?.len(), 35_000);
+        let column = reader.numeric_column(1)?.unwrap();
+        assert_eq!(
+            (0..5).map(|doc| column.first(doc)).collect::<Vec<_>>(),
+            [Some(5), Some(7), Some(-2), Some(5), Some(7)]
+        );
+        assert_eq!(column.first(100), None);
+        let column = reader.numeric_column(2)?.unwrap();
+        assert_eq!(
+            (column.first(0), column.first(69_999)),
+            (Some(42), Some(42))
+        );
+        let column = reader.numeric_column(3)?.unwrap();
+        assert_eq!(column.first(69_000), Some(690_003));
+        assert_eq!(column.first(69_001), None);
+        let column = reader.numeric_column(4)?.unwrap();
+        for doc in [0, 1, 2, 3, 299, 300] {
+            let expected: Vec<i64> = if doc < 300 {
+                (i64::from(doc)..i64::from(doc) + i64::from(doc % 3)).collect()
+            } else {
+                Vec::new()
+            };
+            assert_eq!(column.values_for_doc(doc).collect::<Vec<_>>(), expected);
+        }
+        assert!(reader.bytes_column(4)?.is_none());
+
+        let mut buffer = Vec::new();
+        let mut terms_of_doc = |column: &columnar::BytesColumn, doc: DocId| -> Vec<String> {
+            column
+                .term_ords(doc)
+                .map(|ord| {
+                    buffer.clear();
+                    assert!(column.ord_to_bytes(ord, &mut buffer).unwrap());
+                    String::from_utf8(buffer.clone()).unwrap()
+                })
+                .collect()
+        };
+        let column = reader.bytes_column(5)?.unwrap();
+        assert_eq!(column.num_terms(), 100);
+        assert_eq!(terms_of_doc(&column, 0), ["term000"]);
+        assert_eq!(terms_of_doc(&column, 69_993), ["term093"]);
+        assert!(terms_of_doc(&column, 69_994).is_empty());
+        let column = reader.bytes_column(6)?.unwrap();
+        assert_eq!(terms_of_doc(&column, 7), ["tag2", "tag3", "tag4"]);
+        assert!(terms_of_doc(&column, 8).is_empty());
+        assert_eq!(terms_of_doc(&column, 49), ["tag4"]);
+        let column = reader.bytes_column(7)?.unwrap();
+        assert_eq!(column.num_terms(), 15);
+        let mut value = Vec::new();
+        for doc in 0..21 {
+            let ords: Vec<u64> = column.term_ords(doc).collect();
+            if doc >= 20 || doc == 3 {
+                assert!(ords.is_empty());
+                continue;
+            }
+            assert!(column.ord_to_bytes(ords[0], &mut value)?);
+            assert_eq!(value, vec![doc as u8; (doc % 4) as usize]);
+        }
+        assert!(reader.numeric_column(7)?.is_none());
+        assert!(reader.numeric_column(8)?.is_none());
+        Ok(())
+    }
+}
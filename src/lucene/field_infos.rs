@@ -0,0 +1,282 @@
+use std::collections::BTreeMap;
+
+use super::data_input::{LuceneFile, ID_LEN};
+use crate::schema::IndexRecordOption;
+
+const FIELD_INFOS_CODEC_90: &str = "Lucene90FieldInfos";
+const FIELD_INFOS_CODEC_94: &str = "Lucene94FieldInfos";
+/// `Lucene94FieldInfosFormat.FORMAT_PARENT_FIELD`, the last version written by Lucene 9.x.
+const FIELD_INFOS_VERSION_94: u32 = 1;
+
+const STORE_PAYLOADS: u8 = 0x4;
+const SOFT_DELETES_FIELD: u8 = 0x8;
+
+const POSTINGS_FORMAT_ATTRIBUTE: &str = "PerFieldPostingsFormat.format";
+const POSTINGS_SUFFIX_ATTRIBUTE: &str = "PerFieldPostingsFormat.suffix";
+const DOC_VALUES_FORMAT_ATTRIBUTE: &str = "PerFieldDocValuesFormat.format";
+const DOC_VALUES_SUFFIX_ATTRIBUTE: &str = "PerFieldDocValuesFormat.suffix";
+
+/// What is indexed in the postings of a field of a Lucene segment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LuceneIndexOptions {
+    /// The field is not indexed.
+    None,
+    /// Only the doc ids are indexed.
+    Docs,
+    /// The doc ids and the term frequencies are indexed.
+    DocsAndFreqs,
+    /// The doc ids, the term frequencies and the positions are indexed.
+    DocsAndFreqsAndPositions,
+    /// The doc ids, the term frequencies, the positions and the offsets are indexed.
+    DocsAndFreqsAndPositionsAndOffsets,
+}
+
+impl LuceneIndexOptions {
+    /// Returns the tantivy equivalent of the options, or `None` if the field is not indexed.
+    pub fn index_record_option(self) -> Option<IndexRecordOption> {
+        match self {
+            LuceneIndexOptions::None => None,
+            LuceneIndexOptions::Docs => Some(IndexRecordOption::Basic),
+            LuceneIndexOptions::DocsAndFreqs => Some(IndexRecordOption::WithFreqs),
+            LuceneIndexOptions::DocsAndFreqsAndPositions
+            | LuceneIndexOptions::DocsAndFreqsAndPositionsAndOffsets => {
+                Some(IndexRecordOption::WithFreqsAndPositions)
+            }
+        }
+    }
+
+    pub(crate) fn has_freqs(self) -> bool {
+        self >= LuceneIndexOptions::DocsAndFreqs
+    }
+
+    pub(crate) fn has_positions(self) -> bool {
+        self >= LuceneIndexOptions::DocsAndFreqsAndPositions
+    }
+
+    pub(crate) fn has_offsets(self) -> bool {
+        self >= LuceneIndexOptions::DocsAndFreqsAndPositionsAndOffsets
+    }
+}
+
+/// The type of the doc values of a field of a Lucene segment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LuceneDocValuesType {
+    /// The field has no doc values.
+    None,
+    /// A `long` per document.
+    Numeric,
+    /// Bytes per document.
+    Binary,
+    /// A term per document, from the sorted terms of the field.
+    Sorted,
+    /// A set of terms per document, from the sorted terms of the field.
+    SortedSet,
+    /// A sorted list of `long`s per document.
+    SortedNumeric,
+}
+
+/// The description of a field of a Lucene segment.
+#[derive(Clone, Debug)]
+pub struct LuceneFieldInfo {
+    name: String,
+    number: u32,
+    index_options: LuceneIndexOptions,
+    has_payloads: bool,
+    doc_values_type: LuceneDocValuesType,
+    doc_values_gen: i64,
+    is_soft_deletes_field: bool,
+    attributes: BTreeMap<String, String>,
+}
+
+impl LuceneFieldInfo {
+    /// Returns the name of the field.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the number of the field, which identifies it in the files of the segment.
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+
+    /// Returns what is indexed in the postings of the field.
+    pub fn index_options(&self) -> LuceneIndexOptions {
+        self.index_options
+    }
+
+    /// Returns true if the positions of the field have payloads.
+    pub fn has_payloads(&self) -> bool {
+        self.has_payloads
+    }
+
+    /// Returns the type of the doc values of the field.
+    pub fn doc_values_type(&self) -> LuceneDocValuesType {
+        self.doc_values_type
+    }
+
+    /// Returns true if the documents with doc values for this field are soft deleted.
+    pub fn is_soft_deletes_field(&self) -> bool {
+        self.is_soft_deletes_field
+    }
+
+    /// Returns the attributes of the field, set by the formats which wrote it.
+    pub fn attributes(&self) -> &BTreeMap<String, String> {
+        &self.attributes
+    }
+
+    pub(crate) fn doc_values_gen(&self) -> i64 {
+        self.doc_values_gen
+    }
+
+    /// Returns the name of the postings format of the field, and the suffix of its files.
+    pub(crate) fn postings_format(&self) -> Option<(&str, String)> {
+        self.per_field_format(POSTINGS_FORMAT_ATTRIBUTE, POSTINGS_SUFFIX_ATTRIBUTE)
+    }
+
+    /// Returns the name of the doc values format of the field, and the suffix of its files.
+    pub(crate) fn doc_values_format(&self) -> Option<(&str, String)> {
+        self.per_field_format(DOC_VALUES_FORMAT_ATTRIBUTE, DOC_VALUES_SUFFIX_ATTRIBUTE)
+    }
+
+    fn per_field_format(&self, format_key: &str, suffix_key: &str) -> Option<(&str, String)> {
+        let format = self.attributes.get(format_key)?;
+        let suffix = self.attributes.get(suffix_key)?;
+        Some((format, format!("{format}_{suffix}")))
+    }
+}
+
+/// Reads the `.fnm` file of a segment, written by the `Lucene90FieldInfosFormat` or by the
+/// `Lucene94FieldInfosFormat`.
+pub(crate) fn read_field_infos(
+    file: &LuceneFile,
+    segment_id: [u8; ID_LEN],
+    suffix: &str,
+) -> crate::Result<Vec<LuceneFieldInfo>> {
+    let mut input = file.input();
+    let codec = input.peek_codec()?;
+    let has_vector_encoding = codec == FIELD_INFOS_CODEC_94;
+    if has_vector_encoding {
+        input.read_segment_header(
+            FIELD_INFOS_CODEC_94,
+            0..=FIELD_INFOS_VERSION_94,
+            segment_id,
+            suffix,
+        )?;
+    } else {
+        input.read_segment_header(FIELD_INFOS_CODEC_90, 0..=0, segment_id, suffix)?;
+    }
+    let num_fields = input.read_vint()?;
+    let mut field_infos: Vec<LuceneFieldInfo> = Vec::new();
+    for _ in 0..num_fields {
+        let name = input.read_string()?;
+        let number = input.read_vint()?;
+        let bits = input.read_byte()?;
+        let index_options = match input.read_byte()? {
+            0 => LuceneIndexOptions::None,
+            1 => LuceneIndexOptions::Docs,
+            2 => LuceneIndexOptions::DocsAndFreqs,
+            3 => LuceneIndexOptions::DocsAndFreqsAndPositions,
+            4 => LuceneIndexOptions::DocsAndFreqsAndPositionsAndOffsets,
+            _ => return Err(input.corruption("Invalid index options")),
+        };
+        let doc_values_type = match input.read_byte()? {
+            0 => LuceneDocValuesType::None,
+            1 => LuceneDocValuesType::Numeric,
+            2 => LuceneDocValuesType::Binary,
+            3 => LuceneDocValuesType::Sorted,
+            4 => LuceneDocValuesType::SortedSet,
+            5 => LuceneDocValuesType::SortedNumeric,
+            _ => return Err(input.corruption("Invalid doc values type")),
+        };
+        let doc_values_gen = input.read_i64()?;
+        let attributes = input.read_map_of_strings()?;
+        let point_dimension_count = input.read_vint()?;
+        if point_dimension_count != 0 {
+            let _point_index_dimension_count = input.read_vint()?;
+            let _point_num_bytes = input.read_vint()?;
+        }
+        let _vector_dimension = input.read_vint()?;
+        if has_vector_encoding {
+            let _vector_encoding = input.read_byte()?;
+        }
+        let _vector_similarity_function = input.read_byte()?;
+        if field_infos
+            .iter()
+            .any(|field_info| field_info.number == number || field_info.name == name)
+        {
+            return Err(input.corruption(&format!("Duplicate field {name:?}")));
+        }
+        field_infos.push(LuceneFieldInfo {
+            name,
+            number,
+            index_options,
+            has_payloads: bits & STORE_PAYLOADS != 0,
+            doc_values_type,
+            doc_values_gen,
+            is_soft_deletes_field: bits & SOFT_DELETES_FIELD != 0,
+            attributes,
+        });
+    }
+    Ok(field_infos)
+}
+
+#[cfg(all(test, feature = "mmap"))]
+pub(crate) mod tests {
+    use std::collections::BTreeMap;
+
+    use super::super::data_input::tests::DataOutput;
+    use super::{LuceneDocValuesType, LuceneIndexOptions};
+
+    /// A field of a test segment.
+    #[derive(Clone, Copy)]
+    pub struct TestField {
+        pub name: &'static str,
+        pub index_options: LuceneIndexOptions,
+        pub has_payloads: bool,
+        pub doc_values_type: LuceneDocValuesType,
+        pub is_soft_deletes_field: bool,
+    }
+
+    /// Writes field infos like the `Lucene94FieldInfosFormat`, with the postings of the fields
+    /// in the files of `postings_format` with the suffix `0`, and their doc values in the
+    /// `Lucene90_0` files.
+    pub fn write_field_infos(
+        segment_id: [u8; 16],
+        fields: &[TestField],
+        postings_format: &str,
+    ) -> Vec<u8> {
+        let mut output = DataOutput::default();
+        output.write_index_header("Lucene94FieldInfos", 1, segment_id, "");
+        output.write_vlong(fields.len() as u64);
+        for (number, field) in fields.iter().enumerate() {
+            output.write_string(field.name);
+            output.write_vlong(number as u64);
+            let mut bits = 0;
+            if field.has_payloads {
+                bits |= 0x4;
+            }
+            if field.is_soft_deletes_field {
+                bits |= 0x8;
+            }
+            output.data.push(bits);
+            output.data.push(field.index_options as u8);
+            output.data.push(field.doc_values_type as u8);
+            output.write_i64(-1);
+            let mut attributes = BTreeMap::new();
+            if field.index_options != LuceneIndexOptions::None {
+                attributes.insert("PerFieldPostingsFormat.format", postings_format);
+                attributes.insert("PerFieldPostingsFormat.suffix", "0");
+            }
+            if field.doc_values_type != LuceneDocValuesType::None {
+                attributes.insert("PerFieldDocValuesFormat.format", "Lucene90");
+                attributes.insert("PerFieldDocValuesFormat.suffix", "0");
+            }
+            output.write_map_of_strings(&attributes);
+            // No points, and no vectors.
+            output.write_vlong(0);
+            output.write_vlong(0);
+            output.data.extend_from_slice(&[0, 0]);
+        }
+        output.finish()
+    }
+}
@@ -0,0 +1,539 @@
+//! Reads the commits and the segments of Lucene 9.x indexes, to migrate them to tantivy.
+//!
+//! [`LuceneIndex::open()`] reads the last commit of a Lucene index: its segments, their number
+//! of documents and of deleted documents, and their files. The checksums of the files read are
+//! validated.
+//!
+//! [`LuceneIndex::segment_readers()`] then reads the segments written by the `Lucene90` family
+//! of codecs, up to Lucene 9.11, with the default postings and doc values formats:
+//! - the terms and the postings of the indexed fields, as [`LucenePostings`] implementing
+//!   tantivy's [`Postings`](crate::Postings),
+//! - the stored fields of the documents, as [`NamedFieldDocument`](crate::schema::NamedFieldDocument)s,
+//! - the doc values of the fields, as tantivy columns,
+//! - the deleted documents, hard or soft deleted, as an [`AliveBitSet`](crate::fastfield::AliveBitSet).
+//!
+//! The points and the vectors of the segments are not read.
+//!
+//! This module requires the `lucene` feature flag.
+
+mod compression;
+mod data_input;
+mod doc_values;
+mod field_infos;
+mod packed_ints;
+mod postings;
+mod segment_files;
+mod segment_reader;
+mod stored_fields;
+mod terms;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use self::data_input::{DataInput, ID_LEN};
+pub use self::field_infos::{LuceneDocValuesType, LuceneFieldInfo, LuceneIndexOptions};
+pub use self::postings::LucenePostings;
+pub use self::segment_reader::{LuceneInvertedIndexReader, LuceneSegmentReader};
+pub use self::terms::{LuceneTermInfo, LuceneTermStreamer};
+use crate::error::DataCorruption;
+use crate::TantivyError;
+
+const SEGMENTS_PREFIX: &str = "segments_";
+const SEGMENTS_CODEC: &str = "segments";
+/// `SegmentInfos.VERSION_86`, written by every Lucene 9.x release.
+const SEGMENTS_VERSION: u32 = 10;
+const SEGMENT_INFO_EXTENSION: &str = "si";
+const SEGMENT_INFO_CODEC: &str = "Lucene90SegmentInfo";
+const SEGMENT_INFO_VERSION: u32 = 0;
+
+/// A version of Lucene.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LuceneVersion {
+    /// Major version.
+    pub major: u32,
+    /// Minor version.
+    pub minor: u32,
+    /// Bugfix version.
+    pub bugfix: u32,
+}
+
+/// A segment of a [`LuceneIndex`].
+#[derive(Clone, Debug)]
+pub struct LuceneSegment {
+    name: String,
+    id: [u8; ID_LEN],
+    codec: String,
+    version: LuceneVersion,
+    max_doc: u32,
+    num_hard_deleted_docs: u32,
+    num_soft_deleted_docs: u32,
+    /// The generation of the `.liv` file of the hard deletes.
+    del_gen: Option<u64>,
+    /// The generation of the `.fnm` file of the field infos, if the doc values of the segment
+    /// were updated.
+    field_infos_gen: Option<u64>,
+    is_compound_file: bool,
+    files: Vec<String>,
+}
+
+impl LuceneSegment {
+    /// Returns the name of the segment, which prefixes the names of its files.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the unique id of the segment.
+    pub fn id(&self) -> [u8; ID_LEN] {
+        self.id
+    }
+
+    /// Returns the name of the codec the segment was written with.
+    pub fn codec(&self) -> &str {
+        &self.codec
+    }
+
+    /// Returns the version of Lucene the segment was written with.
+    pub fn version(&self) -> LuceneVersion {
+        self.version
+    }
+
+    /// Returns the number of documents of the segment, deleted documents included.
+    pub fn max_doc(&self) -> u32 {
+        self.max_doc
+    }
+
+    /// Returns the number of deleted documents of the segment, hard or soft deleted.
+    pub fn num_deleted_docs(&self) -> u32 {
+        self.num_hard_deleted_docs
+            .saturating_add(self.num_soft_deleted_docs)
+    }
+
+    /// Returns the number of documents of the segment which are not deleted.
+    ///
+    /// Returns an error if the segment has more deleted documents than documents.
+    pub fn num_docs(&self) -> crate::Result<u32> {
+        self.max_doc
+            .checked_sub(self.num_deleted_docs())
+            .ok_or_else(|| {
+                DataCorruption::new(
+                    self.segment_info_path(),
+                    format!(
+                        "{} documents are deleted out of {}",
+                        self.num_deleted_docs(),
+                        self.max_doc
+                    ),
+                )
+                .into()
+            })
+    }
+
+    /// Returns true if the files of the segment are packed in a compound file.
+    pub fn is_compound_file(&self) -> bool {
+        self.is_compound_file
+    }
+
+    /// Returns the files of the segment, as listed in its segment info.
+    ///
+    /// The files of the deletes and of the doc values updates, written after the segment, are
+    /// not included.
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+
+    fn segment_info_path(&self) -> PathBuf {
+        PathBuf::from(format!("{}.{SEGMENT_INFO_EXTENSION}", self.name))
+    }
+}
+
+/// The last commit of a Lucene 9.x index.
+///
+/// See the [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct LuceneIndex {
+    directory_path: PathBuf,
+    generation: u64,
+    version: LuceneVersion,
+    index_created_version_major: u32,
+    segments: Vec<LuceneSegment>,
+    user_data: BTreeMap<String, String>,
+}
+
+impl LuceneIndex {
+    /// Reads the last commit of the Lucene index stored in a directory of the file system.
+    ///
+    /// Returns an error if the directory does not hold a Lucene index, if the index was not
+    /// written by Lucene 9.x, or if its files are corrupted.
+    pub fn open<P: AsRef<Path>>(directory_path: P) -> crate::Result<LuceneIndex> {
+        let directory_path = directory_path.as_ref();
+        let generation = last_commit_generation(directory_path)?.ok_or_else(|| {
+            TantivyError::InvalidArgument(format!(
+                "{directory_path:?} does not contain a Lucene index"
+            ))
+        })?;
+        let segments_path = PathBuf::from(segments_file_name(generation));
+        let data = fs::read(directory_path.join(&segments_path))?;
+        let mut input = DataInput::open(&segments_path, &data)?;
+        let (_, _, suffix) =
+            input.read_index_header(SEGMENTS_CODEC, SEGMENTS_VERSION..=SEGMENTS_VERSION)?;
+        if suffix != to_radix_36(generation) {
+            return Err(input.corruption("The generation does not match the file name"));
+        }
+        let version = read_vint_version(&mut input)?;
+        let index_created_version_major = input.read_vint()?;
+        let _version = input.read_i64()?;
+        let _counter = input.read_vlong()?;
+        let num_segments = read_count(&mut input)?;
+        if num_segments > 0 {
+            let _min_segment_version = read_vint_version(&mut input)?;
+        }
+        let mut segments = Vec::with_capacity(num_segments);
+        for _ in 0..num_segments {
+            let name = input.read_string()?;
+            let mut id = [0u8; ID_LEN];
+            id.copy_from_slice(input.read_bytes(ID_LEN)?);
+            let codec = input.read_string()?;
+            let del_gen = u64::try_from(input.read_i64()?).ok();
+            let num_hard_deleted_docs = read_count(&mut input)? as u32;
+            let field_infos_gen = u64::try_from(input.read_i64()?).ok();
+            // The generations of the doc values updates are in the field infos.
+            let _doc_values_gen = input.read_i64()?;
+            let num_soft_deleted_docs = read_count(&mut input)? as u32;
+            match input.read_byte()? {
+                0 => {}
+                1 => {
+                    let _commit_id = input.read_bytes(ID_LEN)?;
+                }
+                _ => return Err(input.corruption("Invalid commit id marker")),
+            }
+            let _field_infos_files = input.read_set_of_strings()?;
+            let num_doc_values_updates = read_count(&mut input)?;
+            for _ in 0..num_doc_values_updates {
+                let _field = input.read_i32()?;
+                let _files = input.read_set_of_strings()?;
+            }
+            let mut segment = read_segment_info(directory_path, &name, id)?;
+            segment.codec = codec;
+            segment.num_hard_deleted_docs = num_hard_deleted_docs;
+            segment.num_soft_deleted_docs = num_soft_deleted_docs;
+            segment.del_gen = del_gen;
+            segment.field_infos_gen = field_infos_gen;
+            segment.num_docs()?;
+            segments.push(segment);
+        }
+        let user_data = input.read_map_of_strings()?;
+        Ok(LuceneIndex {
+            directory_path: directory_path.to_path_buf(),
+            generation,
+            version,
+            index_created_version_major,
+            segments,
+            user_data,
+        })
+    }
+
+    /// Returns the generation of the commit, which names its `segments_N` file.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns the version of Lucene which wrote the commit.
+    pub fn version(&self) -> LuceneVersion {
+        self.version
+    }
+
+    /// Returns the major version of Lucene which created the index.
+    pub fn index_created_version_major(&self) -> u32 {
+        self.index_created_version_major
+    }
+
+    /// Returns the segments of the commit.
+    pub fn segments(&self) -> &[LuceneSegment] {
+        &self.segments
+    }
+
+    /// Returns the number of documents of the index which are not deleted.
+    pub fn num_docs(&self) -> crate::Result<u64> {
+        let mut num_docs = 0;
+        for segment in &self.segments {
+            num_docs += u64::from(segment.num_docs()?);
+        }
+        Ok(num_docs)
+    }
+
+    /// Opens the readers of the segments of the commit.
+    ///
+    /// Returns an error if a segment was written with an unsupported format, or if its files
+    /// are corrupted.
+    pub fn segment_readers(&self) -> crate::Result<Vec<LuceneSegmentReader>> {
+        self.segments
+            .iter()
+            .map(|segment| LuceneSegmentReader::open(&self.directory_path, segment))
+            .collect()
+    }
+
+    /// Returns the user data of the commit.
+    pub fn user_data(&self) -> &BTreeMap<String, String> {
+        &self.user_data
+    }
+}
+
+fn segments_file_name(generation: u64) -> String {
+    format!("{SEGMENTS_PREFIX}{}", to_radix_36(generation))
+}
+
+fn to_radix_36(mut value: u64) -> String {
+    const DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    let mut digits = Vec::new();
+    loop {
+        digits.push(DIGITS[(value % 36) as usize]);
+        value /= 36;
+        if value == 0 {
+            break;
+        }
+    }
+    digits.reverse();
+    String::from_utf8(digits).unwrap()
+}
+
+/// Returns the generation of the last `segments_N` file of the directory.
+fn last_commit_generation(directory_path: &Path) -> crate::Result<Option<u64>> {
+    let mut last_generation = None;
+    for entry in fs::read_dir(directory_path)? {
+        let file_name = entry?.file_name();
+        let Some(generation) = file_name
+            .to_str()
+            .and_then(|file_name| file_name.strip_prefix(SEGMENTS_PREFIX))
+            .and_then(|generation| u64::from_str_radix(generation, 36).ok())
+        else {
+            continue;
+        };
+        last_generation = last_generation.max(Some(generation));
+    }
+    Ok(last_generation)
+}
+
+fn read_count(input: &mut DataInput) -> crate::Result<usize> {
+    let count = input.read_i32()?;
+    usize::try_from(count).map_err(|_| input.corruption("Negative count"))
+}
+
+fn read_vint_version(input: &mut DataInput) -> crate::Result<LuceneVersion> {
+    Ok(LuceneVersion {
+        major: input.read_vint()?,
+        minor: input.read_vint()?,
+        bugfix: input.read_vint()?,
+    })
+}
+
+fn read_int_version(input: &mut DataInput) -> crate::Result<LuceneVersion> {
+    let mut read_component = || {
+        let component = input.read_i32()?;
+        u32::try_from(component).map_err(|_| input.corruption("Invalid version"))
+    };
+    Ok(LuceneVersion {
+        major: read_component()?,
+        minor: read_component()?,
+        bugfix: read_component()?,
+    })
+}
+
+/// Reads the `.si` file of a segment, written by the `Lucene90SegmentInfoFormat`.
+fn read_segment_info(
+    directory_path: &Path,
+    name: &str,
+    id: [u8; ID_LEN],
+) -> crate::Result<LuceneSegment> {
+    let path = PathBuf::from(format!("{name}.{SEGMENT_INFO_EXTENSION}"));
+    let data = fs::read(directory_path.join(&path))?;
+    let mut input = DataInput::open(&path, &data)?;
+    let (_, segment_info_id, _) = input.read_index_header(
+        SEGMENT_INFO_CODEC,
+        SEGMENT_INFO_VERSION..=SEGMENT_INFO_VERSION,
+    )?;
+    if segment_info_id != id {
+        return Err(input.corruption("The segment id does not match the commit"));
+    }
+    let version = read_int_version(&mut input)?;
+    match input.read_byte()? {
+        0 => {}
+        1 => {
+            let _min_version = read_int_version(&mut input)?;
+        }
+        _ => return Err(input.corruption("Invalid minimum version marker")),
+    }
+    let max_doc = read_count(&mut input)? as u32;
+    let is_compound_file = match input.read_byte()? as i8 {
+        1 => true,
+        -1 => false,
+        _ => return Err(input.corruption("Invalid compound file marker")),
+    };
+    let _diagnostics = input.read_map_of_strings()?;
+    let files = input.read_set_of_strings()?;
+    Ok(LuceneSegment {
+        name: name.to_string(),
+        id,
+        codec: String::new(),
+        version,
+        max_doc,
+        num_hard_deleted_docs: 0,
+        num_soft_deleted_docs: 0,
+        del_gen: None,
+        field_infos_gen: None,
+        is_compound_file,
+        files,
+    })
+}
+
+#[cfg(all(test, feature = "mmap"))]
+pub(crate) mod tests {
+    use std::collections::BTreeMap;
+    use std::path::Path;
+
+    use super::data_input::tests::DataOutput;
+    use super::{to_radix_36, LuceneIndex, LuceneVersion};
+
+    /// A segment of a test commit.
+    pub struct TestSegment {
+        pub name: &'static str,
+        pub id: [u8; 16],
+        /// The generation of the `.liv` file, or -1.
+        pub del_gen: i64,
+        pub num_deleted_docs: i32,
+        pub num_soft_deleted_docs: i32,
+    }
+
+    impl TestSegment {
+        pub fn new(name: &'static str, id: [u8; 16], num_deleted_docs: i32) -> TestSegment {
+            TestSegment {
+                name,
+                id,
+                del_gen: -1,
+                num_deleted_docs,
+                num_soft_deleted_docs: 0,
+            }
+        }
+    }
+
+    pub fn write_segment_info(
+        directory_path: &Path,
+        name: &str,
+        id: [u8; 16],
+        max_doc: i32,
+        is_compound_file: bool,
+    ) {
+        let mut output = DataOutput::default();
+        output.write_index_header("Lucene90SegmentInfo", 0, id, "");
+        for component in [9, 7, 0] {
+            output.write_i32(component);
+        }
+        output.data.push(0);
+        output.write_i32(max_doc);
+        output
+            .data
+            .push(if is_compound_file { 1 } else { -1i8 as u8 });
+        output.write_map_of_strings(&BTreeMap::from([("source", "flush")]));
+        output.write_set_of_strings(&[&format!("{name}.cfs"), &format!("{name}.cfe")]);
+        output.write_map_of_strings(&BTreeMap::new());
+        // The sort of the segment, which is not read.
+        output.write_vlong(0);
+        std::fs::write(directory_path.join(format!("{name}.si")), output.finish()).unwrap();
+    }
+
+    pub fn write_segments(directory_path: &Path, generation: u64, segments: &[TestSegment]) {
+        let mut output = DataOutput::default();
+        output.write_index_header("segments", 10, [7u8; 16], &to_radix_36(generation));
+        for component in [9, 7, 0] {
+            output.write_vlong(component);
+        }
+        output.write_vlong(9);
+        output.write_i64(generation as i64);
+        output.write_vlong(segments.len() as u64);
+        output.write_i32(segments.len() as i32);
+        if !segments.is_empty() {
+            for component in [9, 7, 0] {
+                output.write_vlong(component);
+            }
+        }
+        for segment in segments {
+            output.write_string(segment.name);
+            output.data.extend_from_slice(&segment.id);
+            output.write_string("Lucene95");
+            output.write_i64(segment.del_gen);
+            output.write_i32(segment.num_deleted_docs);
+            output.write_i64(-1);
+            output.write_i64(-1);
+            output.write_i32(segment.num_soft_deleted_docs);
+            output.data.push(0);
+            output.write_set_of_strings(&[]);
+            output.write_i32(0);
+        }
+        output.write_map_of_strings(&BTreeMap::from([("source", "test")]));
+        let file_name = format!("segments_{}", to_radix_36(generation));
+        std::fs::write(directory_path.join(file_name), output.finish()).unwrap();
+    }
+
+    #[test]
+    fn test_open_lucene_index() -> crate::Result<()> {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        write_segment_info(tempdir.path(), "_0", [0u8; 16], 10, true);
+        write_segment_info(tempdir.path(), "_1", [1u8; 16], 5, true);
+        write_segments(tempdir.path(), 1, &[TestSegment::new("_0", [0u8; 16], 0)]);
+        write_segments(
+            tempdir.path(),
+            37,
+            &[
+                TestSegment::new("_0", [0u8; 16], 3),
+                TestSegment::new("_1", [1u8; 16], 0),
+            ],
+        );
+
+        let index = LuceneIndex::open(tempdir.path())?;
+        assert_eq!(index.generation(), 37);
+        let version = LuceneVersion {
+            major: 9,
+            minor: 7,
+            bugfix: 0,
+        };
+        assert_eq!(index.version(), version);
+        assert_eq!(index.index_created_version_major(), 9);
+        assert_eq!(index.user_data()["source"], "test");
+        assert_eq!(index.num_docs()?, 12);
+        let segment = &index.segments()[0];
+        assert_eq!(segment.name(), "_0");
+        assert_eq!(segment.codec(), "Lucene95");
+        assert_eq!(segment.version(), version);
+        assert_eq!((segment.max_doc(), segment.num_deleted_docs()), (10, 3));
+        assert!(segment.is_compound_file());
+        assert_eq!(segment.files(), &["_0.cfs", "_0.cfe"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_lucene_index_errors() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        assert!(LuceneIndex::open(tempdir.path()).is_err());
+
+        write_segment_info(tempdir.path(), "_0", [0u8; 16], 10, true);
+        write_segments(tempdir.path(), 1, &[TestSegment::new("_0", [0u8; 16], 0)]);
+        assert!(LuceneIndex::open(tempdir.path()).is_ok());
+        // The id of the segment does not match its segment info.
+        write_segments(tempdir.path(), 2, &[TestSegment::new("_0", [1u8; 16], 0)]);
+        assert!(LuceneIndex::open(tempdir.path()).is_err());
+        // More deleted documents than documents.
+        let mut segment = TestSegment::new("_0", [0u8; 16], 6);
+        segment.num_soft_deleted_docs = 5;
+        write_segments(tempdir.path(), 3, &[segment]);
+        assert!(matches!(
+            LuceneIndex::open(tempdir.path()),
+            Err(crate::TantivyError::DataCorruption(_))
+        ));
+        // A corrupted byte fails the checksum.
+        write_segments(tempdir.path(), 4, &[TestSegment::new("_0", [0u8; 16], 0)]);
+        let path = tempdir.path().join("segments_4");
+        let mut data = std::fs::read(&path).unwrap();
+        data[40] ^= 1;
+        std::fs::write(&path, data).unwrap();
+        assert!(LuceneIndex::open(tempdir.path()).is_err());
+    }
+}
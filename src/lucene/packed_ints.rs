@@ -0,0 +1,567 @@
+//! Decoders of the packed integers of Lucene: the blocks of 128 integers of the postings, and
+//! the `DirectReader` and `DirectMonotonicReader` arrays of the stored fields and of the doc
+//! values.
+
+use common::OwnedBytes;
+
+use super::data_input::DataInput;
+
+/// Number of integers of the blocks of the postings.
+pub(crate) const BLOCK_SIZE: usize = 128;
+
+/// Mask keeping the `bits` lowest bits of each of the lanes of `lane_bits` bits of a `u64`.
+fn lane_mask(lane_bits: u32, bits: u32) -> u64 {
+    if bits == 0 {
+        return 0;
+    }
+    let lane = (1u64 << bits) - 1;
+    (0..64 / lane_bits).fold(0, |mask, lane_ord| mask | lane << (lane_ord * lane_bits))
+}
+
+/// Decodes a block of 128 integers of `bits_per_value` bits, packed by Lucene's `ForUtil`.
+///
+/// `ForUtil` first collapses the integers in lanes of 8, 16 or 32 bits of `u64`s, depending on
+/// `bits_per_value`, and then packs these `u64`s, shifting all the lanes at once.
+pub(crate) fn for_decode(
+    input: &mut DataInput,
+    bits_per_value: u32,
+    output: &mut [u32; BLOCK_SIZE],
+) -> crate::Result<()> {
+    if !(1..=32).contains(&bits_per_value) {
+        return Err(input.corruption(&format!("Invalid number of bits {bits_per_value}")));
+    }
+    let lane_bits = match bits_per_value {
+        1..=8 => 8,
+        9..=16 => 16,
+        _ => 32,
+    };
+    let num_longs = BLOCK_SIZE * lane_bits as usize / 64;
+    let num_longs_per_shift = 2 * bits_per_value as usize;
+    let mut packed = [0u64; 64];
+    for long in &mut packed[..num_longs_per_shift] {
+        *long = input.read_i64()? as u64;
+    }
+    let mut longs = [0u64; 64];
+    let mut idx = 0;
+    let mask = lane_mask(lane_bits, bits_per_value);
+    let mut shift = lane_bits as i32 - bits_per_value as i32;
+    while shift >= 0 {
+        for &packed_long in &packed[..num_longs_per_shift] {
+            longs[idx] = (packed_long >> shift) & mask;
+            idx += 1;
+        }
+        shift -= bits_per_value as i32;
+    }
+    // The remaining bits of the lanes of the packed `u64`s hold the last collapsed `u64`s.
+    let remaining_bits_per_long = (shift + bits_per_value as i32) as u32;
+    let mask_remaining_bits_per_long = lane_mask(lane_bits, remaining_bits_per_long);
+    let mut packed_idx = 0;
+    let mut remaining_bits_per_value = bits_per_value;
+    while idx < num_longs {
+        if remaining_bits_per_value >= remaining_bits_per_long {
+            remaining_bits_per_value -= remaining_bits_per_long;
+            longs[idx] |=
+                (packed[packed_idx] & mask_remaining_bits_per_long) << remaining_bits_per_value;
+            packed_idx += 1;
+            if remaining_bits_per_value == 0 {
+                idx += 1;
+                remaining_bits_per_value = bits_per_value;
+            }
+        } else {
+            let mask1 = lane_mask(lane_bits, remaining_bits_per_value);
+            let mask2 = lane_mask(
+                lane_bits,
+                remaining_bits_per_long - remaining_bits_per_value,
+            );
+            longs[idx] |= (packed[packed_idx]
+                >> (remaining_bits_per_long - remaining_bits_per_value))
+                & mask1;
+            idx += 1;
+            remaining_bits_per_value += bits_per_value - remaining_bits_per_long;
+            longs[idx] |= (packed[packed_idx] & mask2) << remaining_bits_per_value;
+            packed_idx += 1;
+        }
+    }
+    // Expands the lanes: the lane `i` of the collapsed `u64` `j` is the integer
+    // `i * num_longs + j`, the first lanes being the most significant bits.
+    let lanes_per_long = 64 / lane_bits as usize;
+    let lane_mask = lane_mask(64, lane_bits);
+    for (j, &long) in longs[..num_longs].iter().enumerate() {
+        for i in 0..lanes_per_long {
+            let shift = lane_bits as usize * (lanes_per_long - 1 - i);
+            output[i * num_longs + j] = ((long >> shift) & lane_mask) as u32;
+        }
+    }
+    Ok(())
+}
+
+/// Decodes a block of 128 deltas, encoded by Lucene's `ForDeltaUtil`, and adds their prefix sum
+/// to `base`.
+pub(crate) fn for_delta_decode(
+    input: &mut DataInput,
+    base: u32,
+    output: &mut [u32; BLOCK_SIZE],
+) -> crate::Result<()> {
+    let bits_per_value = input.read_byte()?;
+    if bits_per_value == 0 {
+        // All the deltas are 1.
+        output.fill(1);
+    } else {
+        for_decode(input, u32::from(bits_per_value), output)?;
+    }
+    prefix_sum(input, base, output)
+}
+
+/// Decodes a block of 128 deltas, encoded by Lucene's `PForUtil`, and adds their prefix sum to
+/// `base`, as `PForUtil.decodeAndPrefixSum()`.
+pub(crate) fn pfor_delta_decode(
+    input: &mut DataInput,
+    base: u32,
+    output: &mut [u32; BLOCK_SIZE],
+) -> crate::Result<()> {
+    pfor_decode(input, output)?;
+    prefix_sum(input, base, output)
+}
+
+fn prefix_sum(input: &DataInput, base: u32, output: &mut [u32; BLOCK_SIZE]) -> crate::Result<()> {
+    let mut sum = base;
+    for value in output.iter_mut() {
+        sum = sum
+            .checked_add(*value)
+            .ok_or_else(|| input.corruption("Doc id overflow"))?;
+        *value = sum;
+    }
+    Ok(())
+}
+
+/// Decodes a block of 128 integers, encoded by Lucene's `PForUtil`: the integers are packed with
+/// `ForUtil`, and up to 7 exceptions patch their high bits.
+pub(crate) fn pfor_decode(
+    input: &mut DataInput,
+    output: &mut [u32; BLOCK_SIZE],
+) -> crate::Result<()> {
+    let token = input.read_byte()?;
+    let bits_per_value = u32::from(token & 0x1f);
+    let num_exceptions = token >> 5;
+    if bits_per_value == 0 {
+        let value =
+            u32::try_from(input.read_vlong()?).map_err(|_| input.corruption("Invalid integer"))?;
+        output.fill(value);
+    } else {
+        for_decode(input, bits_per_value, output)?;
+    }
+    for _ in 0..num_exceptions {
+        let index = input.read_byte()? as usize;
+        let patch = u32::from(input.read_byte()?);
+        let value = output
+            .get_mut(index)
+            .ok_or_else(|| input.corruption("Invalid exception index"))?;
+        *value |= u32::try_from(u64::from(patch) << bits_per_value)
+            .map_err(|_| input.corruption("Invalid exception"))?;
+    }
+    Ok(())
+}
+
+/// Reads the integers packed by Lucene's `DirectWriter`: a little endian stream of integers of
+/// `bits_per_value` bits.
+#[derive(Clone)]
+pub(crate) struct DirectReader {
+    data: OwnedBytes,
+    bits_per_value: u32,
+}
+
+impl DirectReader {
+    pub fn new(data: OwnedBytes, bits_per_value: u32) -> DirectReader {
+        DirectReader {
+            data,
+            bits_per_value,
+        }
+    }
+
+    pub fn get(&self, index: u64) -> u64 {
+        read_packed(self.data.as_slice(), self.bits_per_value, index)
+    }
+}
+
+fn read_packed(data: &[u8], bits_per_value: u32, index: u64) -> u64 {
+    if bits_per_value == 0 {
+        return 0;
+    }
+    let bit_offset = index * u64::from(bits_per_value);
+    let byte_offset = (bit_offset / 8) as usize;
+    // The last values may be read past the end of the data, when `DirectWriter` did not pad it.
+    let mut bytes = [0u8; 8];
+    if let Some(data) = data.get(byte_offset..) {
+        let len = data.len().min(8);
+        bytes[..len].copy_from_slice(&data[..len]);
+    }
+    let value = u64::from_le_bytes(bytes) >> (bit_offset % 8);
+    if bits_per_value == 64 {
+        value
+    } else {
+        value & ((1u64 << bits_per_value) - 1)
+    }
+}
+
+/// The metadata of a block of a `DirectMonotonicReader`.
+#[derive(Clone, Copy)]
+struct MonotonicBlock {
+    min: i64,
+    avg: f32,
+    offset: u64,
+    bits_per_value: u32,
+}
+
+/// Reads the integers written by Lucene's `DirectMonotonicWriter`: the integers of each block
+/// are encoded as their deviation from a linear function.
+#[derive(Clone)]
+pub(crate) struct DirectMonotonicReader {
+    blocks: Vec<MonotonicBlock>,
+    block_shift: u32,
+    num_values: u64,
+    data: OwnedBytes,
+}
+
+/// The metadata of a `DirectMonotonicReader`, written in the metadata files of the formats.
+#[derive(Clone)]
+pub(crate) struct DirectMonotonicMeta {
+    blocks: Vec<MonotonicBlock>,
+    block_shift: u32,
+    num_values: u64,
+}
+
+impl DirectMonotonicMeta {
+    /// Reads the metadata of `num_values` integers, in blocks of `1 << block_shift` integers.
+    pub fn read(
+        input: &mut DataInput,
+        num_values: u64,
+        block_shift: u32,
+    ) -> crate::Result<DirectMonotonicMeta> {
+        if !(2..=22).contains(&block_shift) {
+            return Err(input.corruption(&format!("Invalid block shift {block_shift}")));
+        }
+        let num_blocks = (num_values + (1 << block_shift) - 1) >> block_shift;
+        let mut blocks = Vec::new();
+        for _ in 0..num_blocks {
+            let min = input.read_i64()?;
+            let avg = f32::from_bits(input.read_i32()? as u32);
+            let offset = input.read_i64()? as u64;
+            let bits_per_value = u32::from(input.read_byte()?);
+            if bits_per_value > 64 {
+                return Err(input.corruption("Invalid number of bits"));
+            }
+            blocks.push(MonotonicBlock {
+                min,
+                avg,
+                offset,
+                bits_per_value,
+            });
+        }
+        Ok(DirectMonotonicMeta {
+            blocks,
+            block_shift,
+            num_values,
+        })
+    }
+
+    /// Returns the reader of the integers, whose blocks are stored in `data`.
+    pub fn reader(&self, data: OwnedBytes) -> DirectMonotonicReader {
+        DirectMonotonicReader {
+            blocks: self.blocks.clone(),
+            block_shift: self.block_shift,
+            num_values: self.num_values,
+            data,
+        }
+    }
+}
+
+impl DirectMonotonicReader {
+    pub fn num_values(&self) -> u64 {
+        self.num_values
+    }
+
+    pub fn get(&self, index: u64) -> i64 {
+        let block = &self.blocks[(index >> self.block_shift) as usize];
+        let index_in_block = index & ((1 << self.block_shift) - 1);
+        let data = self
+            .data
+            .as_slice()
+            .get(block.offset as usize..)
+            .unwrap_or_default();
+        let delta = read_packed(data, block.bits_per_value, index_in_block);
+        // Lucene multiplies a `float` by a `long`, which is computed in single precision.
+        let expected = (block.avg * index_in_block as f32) as i64;
+        block.min.wrapping_add(expected).wrapping_add(delta as i64)
+    }
+
+    /// Returns all the integers.
+    pub fn to_vec(&self) -> Vec<i64> {
+        (0..self.num_values).map(|index| self.get(index)).collect()
+    }
+}
+
+#[cfg(all(test, feature = "mmap"))]
+pub(crate) mod tests {
+    use std::path::Path;
+
+    use common::OwnedBytes;
+
+    use super::super::data_input::tests::DataOutput;
+    use super::super::data_input::DataInput;
+    use super::{
+        for_decode, for_delta_decode, lane_mask, pfor_decode, pfor_delta_decode,
+        DirectMonotonicMeta, DirectReader, BLOCK_SIZE,
+    };
+
+    /// Packs a block of integers like Lucene's `ForUtil.encode()`.
+    pub fn for_encode(values: &[u32; BLOCK_SIZE], bits_per_value: u32, output: &mut DataOutput) {
+        let (lane_bits, num_longs) = match bits_per_value {
+            1..=8 => (8u32, 16),
+            9..=16 => (16, 32),
+            _ => (32, 64),
+        };
+        let lanes_per_long = 64 / lane_bits as usize;
+        let mut longs = [0u64; 64];
+        for (j, long) in longs[..num_longs].iter_mut().enumerate() {
+            for i in 0..lanes_per_long {
+                let shift = lane_bits as usize * (lanes_per_long - 1 - i);
+                *long |= u64::from(values[i * num_longs + j]) << shift;
+            }
+        }
+        let num_longs_per_shift = 2 * bits_per_value as usize;
+        let mut packed = [0u64; 64];
+        let mut idx = 0;
+        let mut shift = lane_bits as i32 - bits_per_value as i32;
+        while shift >= 0 {
+            for packed_long in &mut packed[..num_longs_per_shift] {
+                *packed_long |= longs[idx] << shift;
+                idx += 1;
+            }
+            shift -= bits_per_value as i32;
+        }
+        let remaining_bits_per_long = (shift + bits_per_value as i32) as u32;
+        let mask_remaining_bits_per_long = lane_mask(lane_bits, remaining_bits_per_long);
+        let mut packed_idx = 0;
+        let mut remaining_bits_per_value = bits_per_value;
+        while idx < num_longs {
+            if remaining_bits_per_value >= remaining_bits_per_long {
+                remaining_bits_per_value -= remaining_bits_per_long;
+                packed[packed_idx] |=
+                    (longs[idx] >> remaining_bits_per_value) & mask_remaining_bits_per_long;
+                packed_idx += 1;
+                if remaining_bits_per_value == 0 {
+                    idx += 1;
+                    remaining_bits_per_value = bits_per_value;
+                }
+            } else {
+                let mask1 = lane_mask(lane_bits, remaining_bits_per_value);
+                let mask2 = lane_mask(
+                    lane_bits,
+                    remaining_bits_per_long - remaining_bits_per_value,
+                );
+                packed[packed_idx] |=
+                    (longs[idx] & mask1) << (remaining_bits_per_long - remaining_bits_per_value);
+                idx += 1;
+                remaining_bits_per_value =
+                    bits_per_value - remaining_bits_per_long + remaining_bits_per_value;
+                packed[packed_idx] |= (longs[idx] >> remaining_bits_per_value) & mask2;
+                packed_idx += 1;
+            }
+        }
+        for &packed_long in &packed[..num_longs_per_shift] {
+            output.write_i64(packed_long as i64);
+        }
+    }
+
+    fn bits_required(values: &[u32]) -> u32 {
+        let max = values.iter().fold(0, |max, &value| max | value);
+        32 - max.leading_zeros()
+    }
+
+    /// Encodes the deltas of a block like Lucene's `ForDeltaUtil.encodeDeltas()`.
+    pub fn for_delta_encode(deltas: &[u32; BLOCK_SIZE], output: &mut DataOutput) {
+        if deltas.iter().all(|&delta| delta == 1) {
+            output.data.push(0);
+        } else {
+            let bits_per_value = bits_required(deltas);
+            output.data.push(bits_per_value as u8);
+            for_encode(deltas, bits_per_value, output);
+        }
+    }
+
+    /// Encodes a block like Lucene's `PForUtil.encode()`, patching the largest value if it needs
+    /// more bits than the others.
+    pub fn pfor_encode(values: &[u32; BLOCK_SIZE], output: &mut DataOutput) {
+        if values.iter().all(|&value| value == values[0]) {
+            output.data.push(0);
+            output.write_vlong(u64::from(values[0]));
+            return;
+        }
+        let (max_index, _) = values
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, &value)| value)
+            .unwrap();
+        let mut others = *values;
+        others[max_index] = 0;
+        let max_bits = bits_required(values);
+        let bits_per_value = bits_required(&others)
+            .max(max_bits.saturating_sub(8))
+            .max(1);
+        if bits_per_value == max_bits {
+            output.data.push(bits_per_value as u8);
+            for_encode(values, bits_per_value, output);
+        } else {
+            output.data.push((1 << 5) | bits_per_value as u8);
+            let mut patched = *values;
+            patched[max_index] &= (1 << bits_per_value) - 1;
+            for_encode(&patched, bits_per_value, output);
+            output.data.push(max_index as u8);
+            output
+                .data
+                .push((values[max_index] >> bits_per_value) as u8);
+        }
+    }
+
+    #[test]
+    fn test_for_decode_all_bits_per_value() -> crate::Result<()> {
+        for bits_per_value in 1..=32 {
+            let mut values = [0u32; BLOCK_SIZE];
+            for (i, value) in values.iter_mut().enumerate() {
+                *value = (i as u32).wrapping_mul(2_654_435_761) >> (32 - bits_per_value);
+            }
+            let mut output = DataOutput::default();
+            for_encode(&values, bits_per_value, &mut output);
+            assert_eq!(output.data.len(), 16 * bits_per_value as usize);
+            let mut decoded = [0u32; BLOCK_SIZE];
+            let mut input = DataInput::new(Path::new("test"), &output.data);
+            for_decode(&mut input, bits_per_value, &mut decoded)?;
+            assert_eq!(decoded, values, "bits_per_value={bits_per_value}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_for_decode_layout() -> crate::Result<()> {
+        // With 8 bits per value, the lanes are the bytes of the `u64`s, the first integers in
+        // the most significant bytes.
+        let mut data = Vec::new();
+        for j in 0..16u64 {
+            let long = (0..8u64).fold(0u64, |long, i| long | (i * 16 + j) << (56 - 8 * i));
+            data.extend_from_slice(&long.to_le_bytes());
+        }
+        let mut decoded = [0u32; BLOCK_SIZE];
+        for_decode(
+            &mut DataInput::new(Path::new("test"), &data),
+            8,
+            &mut decoded,
+        )?;
+        let expected: Vec<u32> = (0..BLOCK_SIZE as u32).collect();
+        assert_eq!(&decoded[..], &expected[..]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pfor_and_for_delta_decode() -> crate::Result<()> {
+        let mut values = [3u32; BLOCK_SIZE];
+        values[17] = 1000;
+        values[18] = 1;
+        let mut deltas = [1u32; BLOCK_SIZE];
+        let mut output = DataOutput::default();
+        pfor_encode(&values, &mut output);
+        pfor_encode(&[5u32; BLOCK_SIZE], &mut output);
+        for_delta_encode(&deltas, &mut output);
+        deltas[3] = 40;
+        for_delta_encode(&deltas, &mut output);
+
+        let mut input = DataInput::new(Path::new("test"), &output.data);
+        let mut decoded = [0u32; BLOCK_SIZE];
+        pfor_decode(&mut input, &mut decoded)?;
+        assert_eq!(decoded, values);
+        pfor_decode(&mut input, &mut decoded)?;
+        assert_eq!(decoded, [5u32; BLOCK_SIZE]);
+        for_delta_decode(&mut input, 10, &mut decoded)?;
+        assert_eq!((decoded[0], decoded[127]), (11, 138));
+        for_delta_decode(&mut input, 10, &mut decoded)?;
+        assert_eq!(&decoded[..5], &[11, 12, 13, 53, 54]);
+        assert_eq!(input.offset(), output.data.len());
+
+        let mut output = DataOutput::default();
+        pfor_encode(&deltas, &mut output);
+        let mut input = DataInput::new(Path::new("test"), &output.data);
+        pfor_delta_decode(&mut input, 10, &mut decoded)?;
+        assert_eq!(&decoded[..5], &[11, 12, 13, 53, 54]);
+        Ok(())
+    }
+
+    /// Packs integers like Lucene's `DirectWriter`.
+    pub fn direct_write(values: &[u64], bits_per_value: u32, output: &mut Vec<u8>) {
+        let num_bits = values.len() * bits_per_value as usize;
+        let start = output.len();
+        output.resize(start + (num_bits + 7) / 8, 0);
+        for (index, &value) in values.iter().enumerate() {
+            for bit in 0..bits_per_value as usize {
+                if value >> bit & 1 == 1 {
+                    let bit_offset = index * bits_per_value as usize + bit;
+                    output[start + bit_offset / 8] |= 1 << (bit_offset % 8);
+                }
+            }
+        }
+    }
+
+    /// Writes integers like Lucene's `DirectMonotonicWriter`, returning the metadata and the
+    /// data.
+    pub fn direct_monotonic_write(values: &[i64], block_shift: u32) -> (DataOutput, Vec<u8>) {
+        let mut meta = DataOutput::default();
+        let mut data = Vec::new();
+        for block in values.chunks(1 << block_shift) {
+            let avg = (block[block.len() - 1] - block[0]) as f32 / (block.len() - 1).max(1) as f32;
+            let deltas: Vec<i64> = block
+                .iter()
+                .enumerate()
+                .map(|(i, value)| value - (avg * i as f32) as i64)
+                .collect();
+            let min = *deltas.iter().min().unwrap();
+            let deltas: Vec<u64> = deltas.iter().map(|delta| (delta - min) as u64).collect();
+            let max = deltas.iter().fold(0, |max, delta| max | delta);
+            meta.write_i64(min);
+            meta.write_i32(avg.to_bits() as i32);
+            meta.write_i64(data.len() as i64);
+            let bits_per_value = [0, 1, 2, 4, 8, 12, 16, 20, 24, 28, 32, 40, 48, 56, 64]
+                .into_iter()
+                .find(|&bits| bits == 64 || max >> bits == 0)
+                .unwrap();
+            meta.data.push(bits_per_value as u8);
+            direct_write(&deltas, bits_per_value, &mut data);
+        }
+        (meta, data)
+    }
+
+    #[test]
+    fn test_direct_reader() {
+        for bits_per_value in [1, 2, 4, 8, 12, 16, 20, 24, 28, 32, 40, 48, 56, 64] {
+            let values: Vec<u64> = (0..100u64)
+                .map(|i| i.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> (64 - bits_per_value))
+                .collect();
+            let mut data = Vec::new();
+            direct_write(&values, bits_per_value, &mut data);
+            let reader = DirectReader::new(OwnedBytes::new(data), bits_per_value);
+            for (index, &value) in values.iter().enumerate() {
+                assert_eq!(reader.get(index as u64), value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_direct_monotonic_reader() -> crate::Result<()> {
+        let values: Vec<i64> = (0..100i64).map(|i| i * 1000 + (i * 37) % 101).collect();
+        let (meta, data) = direct_monotonic_write(&values, 4);
+        let mut input = DataInput::new(Path::new("test"), &meta.data);
+        let meta_len = meta.data.len();
+        let meta = DirectMonotonicMeta::read(&mut input, values.len() as u64, 4)?;
+        assert_eq!(input.offset(), meta_len);
+        let reader = meta.reader(OwnedBytes::new(data));
+        assert_eq!(reader.to_vec(), values);
+        Ok(())
+    }
+}
@@ -0,0 +1,405 @@
+use super::data_input::{DataInput, LuceneFile, ID_LEN};
+use super::packed_ints::{for_delta_decode, pfor_decode, pfor_delta_decode, BLOCK_SIZE};
+use super::segment_files::SegmentFiles;
+use super::terms::{FieldTerms, LuceneTermInfo};
+use crate::docset::{DocSet, TERMINATED};
+use crate::postings::Postings;
+use crate::DocId;
+
+const DOC_EXTENSION: &str = "doc";
+const POS_EXTENSION: &str = "pos";
+const POSTINGS_VERSION: u32 = 0;
+
+/// The postings formats of Lucene 9.x.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PostingsFormat {
+    /// The `Lucene90PostingsFormat`, whose blocks of doc ids are encoded with `PForUtil`.
+    Lucene90,
+    /// The `Lucene99PostingsFormat` of Lucene 9.9 to 9.11, whose blocks of doc ids are encoded
+    /// with `ForDeltaUtil`.
+    Lucene99,
+}
+
+impl PostingsFormat {
+    /// Returns the postings format named `name` in the attributes of the fields.
+    pub fn from_name(name: &str) -> Option<PostingsFormat> {
+        match name {
+            "Lucene90" => Some(PostingsFormat::Lucene90),
+            "Lucene99" => Some(PostingsFormat::Lucene99),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the codec of the `kind` file of the postings writer.
+    pub fn codec(self, kind: &str) -> String {
+        let name = match self {
+            PostingsFormat::Lucene90 => "Lucene90",
+            PostingsFormat::Lucene99 => "Lucene99",
+        };
+        format!("{name}PostingsWriter{kind}")
+    }
+}
+
+/// Reads the postings of the terms from the `.doc` and `.pos` files of a postings format.
+///
+/// The `.pay` file, holding the payloads and the offsets of the positions in full blocks, is
+/// not read: tantivy has no equivalent.
+pub(crate) struct PostingsReader {
+    format: PostingsFormat,
+    doc_file: LuceneFile,
+    pos_file: Option<LuceneFile>,
+}
+
+impl PostingsReader {
+    pub fn open(
+        files: &SegmentFiles,
+        segment_id: [u8; ID_LEN],
+        suffix: &str,
+        format: PostingsFormat,
+        has_positions: bool,
+    ) -> crate::Result<PostingsReader> {
+        let doc_file = files.open_file(suffix, DOC_EXTENSION)?;
+        doc_file.input().read_segment_header(
+            &format.codec("Doc"),
+            POSTINGS_VERSION..=POSTINGS_VERSION,
+            segment_id,
+            suffix,
+        )?;
+        let pos_file = if has_positions {
+            let pos_file = files.open_file(suffix, POS_EXTENSION)?;
+            pos_file.input().read_segment_header(
+                &format.codec("Pos"),
+                POSTINGS_VERSION..=POSTINGS_VERSION,
+                segment_id,
+                suffix,
+            )?;
+            Some(pos_file)
+        } else {
+            None
+        };
+        Ok(PostingsReader {
+            format,
+            doc_file,
+            pos_file,
+        })
+    }
+
+    /// Decodes the postings of a term of `terms`, with its positions if `with_positions` is
+    /// true and the field has positions.
+    pub fn read_postings(
+        &self,
+        terms: &FieldTerms,
+        term_info: &LuceneTermInfo,
+        max_doc: u32,
+        with_positions: bool,
+    ) -> crate::Result<LucenePostings> {
+        let has_freqs = terms.index_options.has_freqs();
+        let (docs, term_freqs) = if let Some(doc) = term_info.singleton_doc {
+            let term_freq = if has_freqs {
+                u32::try_from(term_info.total_term_freq).unwrap_or(u32::MAX)
+            } else {
+                1
+            };
+            (vec![doc], vec![term_freq])
+        } else {
+            self.read_docs(term_info, has_freqs)?
+        };
+        let input = self.doc_file.input_at(term_info.doc_start_offset)?;
+        if docs.len() != term_info.doc_freq as usize
+            || docs.windows(2).any(|docs| docs[0] >= docs[1])
+            || docs.last().map_or(false, |&doc| doc >= max_doc)
+        {
+            return Err(input.corruption("Invalid doc ids"));
+        }
+        if has_freqs
+            && term_freqs
+                .iter()
+                .map(|&term_freq| u64::from(term_freq))
+                .sum::<u64>()
+                != term_info.total_term_freq
+        {
+            return Err(input.corruption("The term frequencies do not sum to the total"));
+        }
+        let positions = if with_positions && terms.index_options.has_positions() {
+            let has_offsets = terms.index_options.has_offsets();
+            self.read_positions(term_info, &term_freqs, terms.has_payloads, has_offsets)?
+        } else {
+            Vec::new()
+        };
+        Ok(LucenePostings {
+            docs,
+            term_freqs,
+            positions,
+            cursor: 0,
+            positions_start: 0,
+        })
+    }
+
+    /// Decodes the doc ids and the term frequencies written by
+    /// `Lucene90PostingsWriter.startDoc()`: blocks of 128 doc id deltas, followed by 128 term
+    /// frequencies, and the remaining docs as variable length integers.
+    fn read_docs(
+        &self,
+        term_info: &LuceneTermInfo,
+        has_freqs: bool,
+    ) -> crate::Result<(Vec<DocId>, Vec<u32>)> {
+        let mut input = self.doc_file.input_at(term_info.doc_start_offset)?;
+        let doc_freq = term_info.doc_freq as usize;
+        let mut docs = Vec::with_capacity(doc_freq);
+        let mut term_freqs = Vec::with_capacity(doc_freq);
+        let mut block = [0u32; BLOCK_SIZE];
+        let mut last_doc = 0;
+        for _ in 0..doc_freq / BLOCK_SIZE {
+            match self.format {
+                PostingsFormat::Lucene90 => pfor_delta_decode(&mut input, last_doc, &mut block)?,
+                PostingsFormat::Lucene99 => for_delta_decode(&mut input, last_doc, &mut block)?,
+            }
+            docs.extend_from_slice(&block);
+            last_doc = block[BLOCK_SIZE - 1];
+            if has_freqs {
+                pfor_decode(&mut input, &mut block)?;
+                term_freqs.extend_from_slice(&block);
+            } else {
+                term_freqs.resize(docs.len(), 1);
+            }
+        }
+        for _ in 0..doc_freq % BLOCK_SIZE {
+            let code = input.read_vint()?;
+            let (delta, term_freq) = if !has_freqs {
+                (code, 1)
+            } else if code & 1 != 0 {
+                (code >> 1, 1)
+            } else {
+                (code >> 1, input.read_vint()?)
+            };
+            last_doc = last_doc
+                .checked_add(delta)
+                .ok_or_else(|| input.corruption("Doc id overflow"))?;
+            docs.push(last_doc);
+            term_freqs.push(term_freq);
+        }
+        Ok((docs, term_freqs))
+    }
+
+    /// Decodes the positions of a term: blocks of 128 position deltas, and the remaining
+    /// positions as variable length integers, interleaved with their payloads and offsets.
+    fn read_positions(
+        &self,
+        term_info: &LuceneTermInfo,
+        term_freqs: &[u32],
+        has_payloads: bool,
+        has_offsets: bool,
+    ) -> crate::Result<Vec<u32>> {
+        let pos_file = self.pos_file.as_ref().ok_or_else(|| {
+            self.doc_file
+                .input()
+                .corruption("The segment has positions, but no positions file")
+        })?;
+        let mut input = pos_file.input_at(term_info.pos_start_offset)?;
+        let total_term_freq = term_info.total_term_freq as usize;
+        let mut deltas = Vec::with_capacity(total_term_freq);
+        let mut block = [0u32; BLOCK_SIZE];
+        for _ in 0..total_term_freq / BLOCK_SIZE {
+            pfor_decode(&mut input, &mut block)?;
+            deltas.extend_from_slice(&block);
+        }
+        if let Some(last_pos_block_offset) = term_info.last_pos_block_offset {
+            let offset = term_info
+                .pos_start_offset
+                .checked_add(last_pos_block_offset)
+                .ok_or_else(|| input.corruption("Invalid offset of the last positions"))?;
+            input = pos_file.input_at(offset)?;
+        }
+        read_vint_positions(
+            &mut input,
+            total_term_freq % BLOCK_SIZE,
+            has_payloads,
+            has_offsets,
+            &mut deltas,
+        )?;
+        // The positions are delta encoded within each document.
+        let mut positions = Vec::with_capacity(total_term_freq);
+        let mut deltas = deltas.into_iter();
+        for &term_freq in term_freqs {
+            let mut position = 0u32;
+            for delta in deltas.by_ref().take(term_freq as usize) {
+                position = position
+                    .checked_add(delta)
+                    .ok_or_else(|| input.corruption("Position overflow"))?;
+                positions.push(position);
+            }
+        }
+        Ok(positions)
+    }
+}
+
+fn read_vint_positions(
+    input: &mut DataInput,
+    num_positions: usize,
+    has_payloads: bool,
+    has_offsets: bool,
+    deltas: &mut Vec<u32>,
+) -> crate::Result<()> {
+    let mut payload_len = 0;
+    for _ in 0..num_positions {
+        let delta = if has_payloads {
+            let code = input.read_vint()?;
+            if code & 1 != 0 {
+                payload_len = input.read_vint()? as usize;
+            }
+            input.skip(payload_len)?;
+            code >> 1
+        } else {
+            input.read_vint()?
+        };
+        if has_offsets {
+            let code = input.read_vint()?;
+            if code & 1 != 0 {
+                let _offset_len = input.read_vint()?;
+            }
+        }
+        deltas.push(delta);
+    }
+    Ok(())
+}
+
+/// The postings of a term of a Lucene segment, decoded in memory.
+pub struct LucenePostings {
+    docs: Vec<DocId>,
+    term_freqs: Vec<u32>,
+    /// The positions of all the documents, empty if they were not read.
+    positions: Vec<u32>,
+    cursor: usize,
+    /// The index of the first position of the current document in `positions`.
+    positions_start: usize,
+}
+
+impl DocSet for LucenePostings {
+    fn advance(&mut self) -> DocId {
+        if self.cursor < self.docs.len() {
+            self.positions_start += self.term_freqs[self.cursor] as usize;
+            self.cursor += 1;
+        }
+        self.doc()
+    }
+
+    fn doc(&self) -> DocId {
+        self.docs.get(self.cursor).copied().unwrap_or(TERMINATED)
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.docs.len() as u32
+    }
+}
+
+impl Postings for LucenePostings {
+    fn term_freq(&self) -> u32 {
+        self.term_freqs.get(self.cursor).copied().unwrap_or(0)
+    }
+
+    fn positions_with_offset(&mut self, offset: u32, output: &mut Vec<u32>) {
+        output.clear();
+        let positions_end = self.positions_start + self.term_freq() as usize;
+        if let Some(positions) = self.positions.get(self.positions_start..positions_end) {
+            output.extend(positions.iter().map(|&position| position + offset));
+        }
+    }
+}
+
+#[cfg(all(test, feature = "mmap"))]
+pub(crate) mod tests {
+    use super::super::data_input::tests::DataOutput;
+    use super::super::packed_ints::tests::{for_delta_encode, pfor_encode};
+    use super::super::packed_ints::BLOCK_SIZE;
+    use super::super::terms::LuceneTermInfo;
+    use super::PostingsFormat;
+    use crate::DocId;
+
+    /// Writes the postings of a term, given as its documents and their positions, like the
+    /// `Lucene90PostingsWriter`, and returns its term info.
+    ///
+    /// The positions have a payload of one byte when `with_payloads` is true.
+    pub fn write_postings(
+        format: PostingsFormat,
+        postings: &[(DocId, Vec<u32>)],
+        with_payloads: bool,
+        doc_output: &mut DataOutput,
+        pos_output: &mut DataOutput,
+    ) -> LuceneTermInfo {
+        let total_term_freq: usize = postings.iter().map(|(_, positions)| positions.len()).sum();
+        let mut term_info = LuceneTermInfo {
+            doc_freq: postings.len() as u32,
+            total_term_freq: total_term_freq as u64,
+            doc_start_offset: doc_output.data.len() as u64,
+            pos_start_offset: pos_output.data.len() as u64,
+            ..LuceneTermInfo::default()
+        };
+        if postings.len() == 1 {
+            term_info.singleton_doc = Some(postings[0].0);
+        } else {
+            let mut last_doc = 0;
+            let blocks = postings.chunks(BLOCK_SIZE);
+            for block in blocks {
+                if block.len() < BLOCK_SIZE {
+                    for (doc, positions) in block {
+                        let delta = doc - last_doc;
+                        if positions.len() == 1 {
+                            doc_output.write_vlong(u64::from(delta << 1 | 1));
+                        } else {
+                            doc_output.write_vlong(u64::from(delta << 1));
+                            doc_output.write_vlong(positions.len() as u64);
+                        }
+                        last_doc = *doc;
+                    }
+                    break;
+                }
+                let mut deltas = [0u32; BLOCK_SIZE];
+                let mut term_freqs = [0u32; BLOCK_SIZE];
+                for (i, (doc, positions)) in block.iter().enumerate() {
+                    deltas[i] = doc - last_doc;
+                    term_freqs[i] = positions.len() as u32;
+                    last_doc = *doc;
+                }
+                match format {
+                    PostingsFormat::Lucene90 => pfor_encode(&deltas, doc_output),
+                    PostingsFormat::Lucene99 => for_delta_encode(&deltas, doc_output),
+                }
+                pfor_encode(&term_freqs, doc_output);
+            }
+        }
+        let position_deltas: Vec<u32> = postings
+            .iter()
+            .flat_map(|(_, positions)| {
+                let mut last_position = 0;
+                positions.iter().map(move |&position| {
+                    let delta = position - last_position;
+                    last_position = position;
+                    delta
+                })
+            })
+            .collect();
+        let mut blocks = position_deltas.chunks_exact(BLOCK_SIZE);
+        for block in blocks.by_ref() {
+            pfor_encode(block.try_into().unwrap(), pos_output);
+        }
+        if total_term_freq > BLOCK_SIZE {
+            term_info.last_pos_block_offset =
+                Some(pos_output.data.len() as u64 - term_info.pos_start_offset);
+        }
+        for (i, &delta) in blocks.remainder().iter().enumerate() {
+            if with_payloads {
+                // The payload length is only written when it changes.
+                if i == 0 {
+                    pos_output.write_vlong(u64::from(delta << 1 | 1));
+                    pos_output.write_vlong(1);
+                } else {
+                    pos_output.write_vlong(u64::from(delta << 1));
+                }
+                pos_output.data.push(42);
+            } else {
+                pos_output.write_vlong(u64::from(delta));
+            }
+        }
+        term_info
+    }
+}
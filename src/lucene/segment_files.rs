@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use common::OwnedBytes;
+
+use super::data_input::{LuceneFile, ID_LEN};
+use crate::TantivyError;
+
+const COMPOUND_DATA_EXTENSION: &str = "cfs";
+const COMPOUND_DATA_CODEC: &str = "Lucene90CompoundData";
+const COMPOUND_ENTRIES_EXTENSION: &str = "cfe";
+const COMPOUND_ENTRIES_CODEC: &str = "Lucene90CompoundEntries";
+const COMPOUND_VERSION: u32 = 0;
+
+/// Returns the name of a file of a segment, e.g. `_0_Lucene90_0.tim`.
+pub(crate) fn segment_file_name(segment_name: &str, suffix: &str, extension: &str) -> String {
+    if suffix.is_empty() {
+        format!("{segment_name}.{extension}")
+    } else {
+        format!("{segment_name}_{suffix}.{extension}")
+    }
+}
+
+/// Opens the files of a segment, either from the directory of the index, or from the compound
+/// file of the segment, written by the `Lucene90CompoundFormat`.
+pub(crate) struct SegmentFiles {
+    directory_path: PathBuf,
+    segment_name: String,
+    segment_id: [u8; ID_LEN],
+    /// The compound file, and the ranges of its sub-files, by file name without the segment
+    /// name.
+    compound_file: Option<(OwnedBytes, HashMap<String, Range<usize>>)>,
+}
+
+impl SegmentFiles {
+    pub fn open(
+        directory_path: &Path,
+        segment_name: &str,
+        segment_id: [u8; ID_LEN],
+        is_compound_file: bool,
+    ) -> crate::Result<SegmentFiles> {
+        let mut segment_files = SegmentFiles {
+            directory_path: directory_path.to_path_buf(),
+            segment_name: segment_name.to_string(),
+            segment_id,
+            compound_file: None,
+        };
+        if is_compound_file {
+            segment_files.compound_file = Some(segment_files.open_compound_file()?);
+        }
+        Ok(segment_files)
+    }
+
+    fn open_compound_file(&self) -> crate::Result<(OwnedBytes, HashMap<String, Range<usize>>)> {
+        let entries_file = self.open_directory_file("", COMPOUND_ENTRIES_EXTENSION)?;
+        let mut input = entries_file.input();
+        input.read_segment_header(
+            COMPOUND_ENTRIES_CODEC,
+            COMPOUND_VERSION..=COMPOUND_VERSION,
+            self.segment_id,
+            "",
+        )?;
+        let data_file = self.open_directory_file("", COMPOUND_DATA_EXTENSION)?;
+        data_file.input().read_segment_header(
+            COMPOUND_DATA_CODEC,
+            COMPOUND_VERSION..=COMPOUND_VERSION,
+            self.segment_id,
+            "",
+        )?;
+        let num_entries = input.read_vint()?;
+        let mut entries = HashMap::new();
+        for _ in 0..num_entries {
+            let name = input.read_string()?;
+            let offset = input.read_i64()?;
+            let len = input.read_i64()?;
+            let range = usize::try_from(offset)
+                .ok()
+                .zip(usize::try_from(len).ok())
+                .and_then(|(offset, len)| Some(offset..offset.checked_add(len)?))
+                .filter(|range| range.end <= data_file.data().len())
+                .ok_or_else(|| input.corruption(&format!("Invalid range of {name:?}")))?;
+            entries.insert(name, range);
+        }
+        Ok((data_file.data().clone(), entries))
+    }
+
+    fn open_directory_file(&self, suffix: &str, extension: &str) -> crate::Result<LuceneFile> {
+        let file_name = segment_file_name(&self.segment_name, suffix, extension);
+        let data = fs::read(self.directory_path.join(&file_name))?;
+        LuceneFile::open(PathBuf::from(file_name), OwnedBytes::new(data))
+    }
+
+    /// Opens a file of the segment, from its compound file if it has one.
+    pub fn open_file(&self, suffix: &str, extension: &str) -> crate::Result<LuceneFile> {
+        let Some((data, entries)) = &self.compound_file else {
+            return self.open_directory_file(suffix, extension);
+        };
+        let file_name = segment_file_name(&self.segment_name, suffix, extension);
+        let entry_name = &file_name[self.segment_name.len()..];
+        let range = entries.get(entry_name).ok_or_else(|| {
+            TantivyError::InvalidArgument(format!(
+                "The compound file of the segment {:?} does not contain {file_name:?}",
+                self.segment_name
+            ))
+        })?;
+        let compound_path = segment_file_name(&self.segment_name, "", COMPOUND_DATA_EXTENSION);
+        LuceneFile::open(
+            PathBuf::from(format!("{compound_path}/{file_name}")),
+            data.slice(range.clone()),
+        )
+    }
+
+    /// Opens a file written after the segment, such as its deletes, which is never in the
+    /// compound file.
+    pub fn open_generation_file(
+        &self,
+        generation: u64,
+        extension: &str,
+    ) -> crate::Result<LuceneFile> {
+        self.open_directory_file(&super::to_radix_36(generation), extension)
+    }
+}
+
+#[cfg(all(test, feature = "mmap"))]
+pub(crate) mod tests {
+    use std::path::Path;
+
+    use super::super::data_input::tests::DataOutput;
+    use super::segment_file_name;
+
+    /// Packs files, given by name and content, in the compound file of a segment.
+    pub fn write_compound_file(
+        directory_path: &Path,
+        segment_name: &str,
+        segment_id: [u8; 16],
+        files: &[(String, Vec<u8>)],
+    ) {
+        let mut data = DataOutput::default();
+        data.write_index_header("Lucene90CompoundData", 0, segment_id, "");
+        let mut entries = DataOutput::default();
+        entries.write_index_header("Lucene90CompoundEntries", 0, segment_id, "");
+        entries.write_vlong(files.len() as u64);
+        for (file_name, content) in files {
+            // Lucene aligns the sub-files on 8 bytes.
+            while data.data.len() % 8 != 0 {
+                data.data.push(0);
+            }
+            entries.write_string(&file_name[segment_name.len()..]);
+            entries.write_i64(data.data.len() as i64);
+            entries.write_i64(content.len() as i64);
+            data.data.extend_from_slice(content);
+        }
+        std::fs::write(
+            directory_path.join(segment_file_name(segment_name, "", "cfs")),
+            data.finish(),
+        )
+        .unwrap();
+        std::fs::write(
+            directory_path.join(segment_file_name(segment_name, "", "cfe")),
+            entries.finish(),
+        )
+        .unwrap();
+    }
+}
@@ -0,0 +1,675 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::Arc;
+
+use columnar::{BytesColumn, Column};
+use common::{BitSet, ReadOnlyBitSet};
+
+use super::doc_values::DocValuesReader;
+use super::field_infos::{read_field_infos, LuceneDocValuesType, LuceneFieldInfo};
+use super::postings::{LucenePostings, PostingsFormat, PostingsReader};
+use super::segment_files::SegmentFiles;
+use super::stored_fields::StoredFieldsReader;
+use super::terms::{read_block_tree_terms, FieldTerms, LuceneTermInfo, LuceneTermStreamer};
+use super::{to_radix_36, LuceneSegment};
+use crate::error::DataCorruption;
+use crate::fastfield::AliveBitSet;
+use crate::schema::{IndexRecordOption, NamedFieldDocument};
+use crate::{DocId, TantivyError};
+
+const FIELD_INFOS_EXTENSION: &str = "fnm";
+const LIVE_DOCS_EXTENSION: &str = "liv";
+const LIVE_DOCS_CODEC: &str = "Lucene90LiveDocs";
+const LIVE_DOCS_VERSION: u32 = 0;
+const DOC_VALUES_FORMAT: &str = "Lucene90";
+
+/// Reads the documents of a [`LuceneSegment`]: its inverted index, its stored fields and its
+/// doc values, exposed with the types of tantivy.
+///
+/// The segments must have been written by the `Lucene90` family of codecs, up to Lucene 9.11,
+/// with the default postings and doc values formats. The doc values are decoded in memory.
+pub struct LuceneSegmentReader {
+    segment: LuceneSegment,
+    field_infos: Vec<LuceneFieldInfo>,
+    inverted_indexes: HashMap<u32, (FieldTerms, Arc<PostingsReader>)>,
+    stored_fields: StoredFieldsReader,
+    doc_values: HashMap<u32, Arc<DocValuesReader>>,
+    alive_bitset: Option<AliveBitSet>,
+}
+
+impl LuceneSegmentReader {
+    pub(crate) fn open(
+        directory_path: &Path,
+        segment: &LuceneSegment,
+    ) -> crate::Result<LuceneSegmentReader> {
+        let segment_id = segment.id();
+        let files = SegmentFiles::open(
+            directory_path,
+            segment.name(),
+            segment_id,
+            segment.is_compound_file(),
+        )?;
+        // The files written after the segment are never in its compound file.
+        let generation_files =
+            SegmentFiles::open(directory_path, segment.name(), segment_id, false)?;
+        let field_infos = match segment.field_infos_gen {
+            Some(generation) => read_field_infos(
+                &files.open_generation_file(generation, FIELD_INFOS_EXTENSION)?,
+                segment_id,
+                &to_radix_36(generation),
+            )?,
+            None => read_field_infos(&files.open_file("", FIELD_INFOS_EXTENSION)?, segment_id, "")?,
+        };
+
+        let mut postings_formats: BTreeMap<String, (PostingsFormat, bool)> = BTreeMap::new();
+        for field_info in &field_infos {
+            if field_info.index_options().index_record_option().is_none() {
+                continue;
+            }
+            let (name, suffix) = field_info.postings_format().ok_or_else(|| {
+                TantivyError::InvalidArgument(format!(
+                    "The postings format of the field {:?} is unknown",
+                    field_info.name()
+                ))
+            })?;
+            let format = PostingsFormat::from_name(name).ok_or_else(|| {
+                TantivyError::InvalidArgument(format!(
+                    "The postings format {name:?} of the field {:?} is not supported",
+                    field_info.name()
+                ))
+            })?;
+            let has_positions = field_info.index_options().has_positions();
+            let entry = postings_formats
+                .entry(suffix)
+                .or_insert((format, has_positions));
+            entry.1 |= has_positions;
+        }
+        let mut inverted_indexes = HashMap::new();
+        for (suffix, (format, has_positions)) in postings_formats {
+            let postings_reader = Arc::new(PostingsReader::open(
+                &files,
+                segment_id,
+                &suffix,
+                format,
+                has_positions,
+            )?);
+            let fields_terms =
+                read_block_tree_terms(&files, segment_id, &suffix, format, &field_infos)?;
+            for (number, terms) in fields_terms {
+                inverted_indexes.insert(number, (terms, postings_reader.clone()));
+            }
+        }
+
+        let stored_fields = StoredFieldsReader::open(&files, segment_id, segment.max_doc())?;
+
+        let mut doc_values_readers: BTreeMap<String, Arc<DocValuesReader>> = BTreeMap::new();
+        let mut doc_values = HashMap::new();
+        for field_info in &field_infos {
+            if field_info.doc_values_type() == LuceneDocValuesType::None {
+                continue;
+            }
+            let (_, suffix) = field_info
+                .doc_values_format()
+                .filter(|(name, _)| *name == DOC_VALUES_FORMAT)
+                .ok_or_else(|| {
+                    TantivyError::InvalidArgument(format!(
+                        "The doc values format of the field {:?} is not supported",
+                        field_info.name()
+                    ))
+                })?;
+            // The doc values updated after the segment was written are in the files of their
+            // generation.
+            let (files, suffix) = match u64::try_from(field_info.doc_values_gen()) {
+                Ok(generation) => (
+                    &generation_files,
+                    format!("{}_{suffix}", to_radix_36(generation)),
+                ),
+                Err(_) => (&files, suffix),
+            };
+            let reader = match doc_values_readers.get(&suffix) {
+                Some(reader) => reader.clone(),
+                None => {
+                    let reader = Arc::new(DocValuesReader::open(
+                        files,
+                        segment_id,
+                        &suffix,
+                        &field_infos,
+                        segment.max_doc(),
+                    )?);
+                    doc_values_readers.insert(suffix, reader.clone());
+                    reader
+                }
+            };
+            doc_values.insert(field_info.number(), reader);
+        }
+
+        let mut segment_reader = LuceneSegmentReader {
+            segment: segment.clone(),
+            field_infos,
+            inverted_indexes,
+            stored_fields,
+            doc_values,
+            alive_bitset: None,
+        };
+        segment_reader.alive_bitset = segment_reader.read_alive_bitset(&generation_files)?;
+        Ok(segment_reader)
+    }
+
+    /// Reads the hard deletes from the `.liv` file of the segment, and the soft deletes from
+    /// the doc values of the soft deletes field.
+    fn read_alive_bitset(&self, files: &SegmentFiles) -> crate::Result<Option<AliveBitSet>> {
+        let max_doc = self.segment.max_doc();
+        let mut alive_docs = BitSet::with_max_value_and_full(max_doc);
+        let mut num_deleted_docs = 0u32;
+        if let Some(generation) = self.segment.del_gen {
+            let live_docs_file = files.open_generation_file(generation, LIVE_DOCS_EXTENSION)?;
+            let mut input = live_docs_file.input();
+            input.read_segment_header(
+                LIVE_DOCS_CODEC,
+                LIVE_DOCS_VERSION..=LIVE_DOCS_VERSION,
+                self.segment.id(),
+                &to_radix_36(generation),
+            )?;
+            for word_start in (0..u64::from(max_doc)).step_by(64) {
+                let word = input.read_i64()? as u64;
+                for doc in word_start..(word_start + 64).min(u64::from(max_doc)) {
+                    let doc = doc as DocId;
+                    if word >> (doc % 64) & 1 == 0 {
+                        alive_docs.remove(doc);
+                        num_deleted_docs += 1;
+                    }
+                }
+            }
+            if num_deleted_docs != self.segment.num_hard_deleted_docs {
+                return Err(input.corruption("The live docs do not match the deletes count"));
+            }
+        }
+        for field_info in &self.field_infos {
+            if !field_info.is_soft_deletes_field() {
+                continue;
+            }
+            let Some(doc_values) = self.doc_values.get(&field_info.number()) else {
+                continue;
+            };
+            for doc in doc_values.docs_with_values(field_info.number())? {
+                if alive_docs.contains(doc) {
+                    alive_docs.remove(doc);
+                    num_deleted_docs += 1;
+                }
+            }
+        }
+        if num_deleted_docs != self.segment.num_deleted_docs() {
+            return Err(DataCorruption::new(
+                self.segment.segment_info_path(),
+                format!(
+                    "{num_deleted_docs} documents are deleted, instead of {}",
+                    self.segment.num_deleted_docs()
+                ),
+            )
+            .into());
+        }
+        if num_deleted_docs == 0 {
+            return Ok(None);
+        }
+        Ok(Some(AliveBitSet::from(ReadOnlyBitSet::from(&alive_docs))))
+    }
+
+    /// Returns the segment read.
+    pub fn segment(&self) -> &LuceneSegment {
+        &self.segment
+    }
+
+    /// Returns the number of documents of the segment, deleted documents included.
+    pub fn max_doc(&self) -> DocId {
+        self.segment.max_doc()
+    }
+
+    /// Returns the number of documents of the segment which are not deleted.
+    pub fn num_docs(&self) -> crate::Result<DocId> {
+        self.segment.num_docs()
+    }
+
+    /// Returns the fields of the segment.
+    pub fn field_infos(&self) -> &[LuceneFieldInfo] {
+        &self.field_infos
+    }
+
+    /// Returns the field named `field_name`, if the segment has it.
+    pub fn field_info(&self, field_name: &str) -> Option<&LuceneFieldInfo> {
+        self.field_infos
+            .iter()
+            .find(|field_info| field_info.name() == field_name)
+    }
+
+    /// Returns the bitset of the documents which are not deleted, or `None` if no document
+    /// of the segment is deleted.
+    pub fn alive_bitset(&self) -> Option<&AliveBitSet> {
+        self.alive_bitset.as_ref()
+    }
+
+    /// Returns the inverted index of a field, or `None` if the field is not indexed.
+    pub fn inverted_index(&self, field_name: &str) -> Option<LuceneInvertedIndexReader<'_>> {
+        let field_info = self.field_info(field_name)?;
+        let (terms, postings_reader) = self.inverted_indexes.get(&field_info.number())?;
+        Some(LuceneInvertedIndexReader {
+            terms,
+            postings_reader,
+            max_doc: self.max_doc(),
+        })
+    }
+
+    /// Returns the stored fields of a document.
+    pub fn doc(&self, doc_id: DocId) -> crate::Result<NamedFieldDocument> {
+        if doc_id >= self.max_doc() {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The document {doc_id} is out of the segment {:?}",
+                self.segment.name()
+            )));
+        }
+        self.stored_fields.document(doc_id, &self.field_infos)
+    }
+
+    /// Returns the column of the numeric or sorted numeric doc values of a field, or `None` if
+    /// the field has no such doc values.
+    pub fn i64_column(&self, field_name: &str) -> crate::Result<Option<Column<i64>>> {
+        let Some((number, doc_values)) = self.doc_values_reader(field_name) else {
+            return Ok(None);
+        };
+        doc_values.numeric_column(number)
+    }
+
+    /// Returns the column of the sorted, sorted set or binary doc values of a field, or `None`
+    /// if the field has no such doc values.
+    ///
+    /// The binary doc values are sorted and deduplicated in the dictionary of the column.
+    pub fn bytes_column(&self, field_name: &str) -> crate::Result<Option<BytesColumn>> {
+        let Some((number, doc_values)) = self.doc_values_reader(field_name) else {
+            return Ok(None);
+        };
+        doc_values.bytes_column(number)
+    }
+
+    fn doc_values_reader(&self, field_name: &str) -> Option<(u32, &DocValuesReader)> {
+        let number = self.field_info(field_name)?.number();
+        Some((number, self.doc_values.get(&number)?))
+    }
+}
+
+/// The inverted index of a field of a [`LuceneSegmentReader`].
+pub struct LuceneInvertedIndexReader<'a> {
+    terms: &'a FieldTerms,
+    postings_reader: &'a PostingsReader,
+    max_doc: DocId,
+}
+
+impl<'a> LuceneInvertedIndexReader<'a> {
+    /// Returns the number of terms of the field.
+    pub fn num_terms(&self) -> u64 {
+        self.terms.num_terms
+    }
+
+    /// Returns the number of documents with at least a term of the field.
+    pub fn doc_count(&self) -> u32 {
+        self.terms.doc_count
+    }
+
+    /// Returns the sum of the document frequencies of the terms of the field.
+    pub fn sum_doc_freq(&self) -> u64 {
+        self.terms.sum_doc_freq
+    }
+
+    /// Returns the number of tokens of the field, over all the documents.
+    pub fn total_num_tokens(&self) -> u64 {
+        self.terms.sum_total_term_freq
+    }
+
+    /// Returns a streamer over the terms of the field, in sorted order.
+    pub fn terms(&self) -> LuceneTermStreamer<'a> {
+        self.terms.stream()
+    }
+
+    /// Returns the term info of a term, or `None` if the field does not have the term.
+    pub fn get_term_info(&self, term: &[u8]) -> crate::Result<Option<LuceneTermInfo>> {
+        self.terms.get(term)
+    }
+
+    /// Returns the number of documents containing a term.
+    pub fn doc_freq(&self, term: &[u8]) -> crate::Result<u32> {
+        Ok(self
+            .get_term_info(term)?
+            .map(|term_info| term_info.doc_freq)
+            .unwrap_or(0))
+    }
+
+    /// Returns the postings of a term, given its term info.
+    ///
+    /// The positions are decoded if `option` requests them and the field has positions.
+    pub fn read_postings_from_terminfo(
+        &self,
+        term_info: &LuceneTermInfo,
+        option: IndexRecordOption,
+    ) -> crate::Result<LucenePostings> {
+        self.postings_reader.read_postings(
+            self.terms,
+            term_info,
+            self.max_doc,
+            option.has_positions(),
+        )
+    }
+
+    /// Returns the postings of a term, or `None` if the field does not have the term.
+    pub fn read_postings(
+        &self,
+        term: &[u8],
+        option: IndexRecordOption,
+    ) -> crate::Result<Option<LucenePostings>> {
+        let Some(term_info) = self.get_term_info(term)? else {
+            return Ok(None);
+        };
+        Ok(Some(self.read_postings_from_terminfo(&term_info, option)?))
+    }
+}
+
+#[cfg(all(test, feature = "mmap"))]
+mod tests {
+    use std::path::Path;
+
+    use super::super::data_input::tests::DataOutput;
+    use super::super::doc_values::tests::{write_doc_values, TestDocValues};
+    use super::super::field_infos::tests::{write_field_infos, TestField};
+    use super::super::postings::tests::write_postings;
+    use super::super::postings::PostingsFormat;
+    use super::super::segment_files::segment_file_name;
+    use super::super::segment_files::tests::write_compound_file;
+    use super::super::stored_fields::tests::{write_stored_fields, TestValue};
+    use super::super::terms::tests::{write_block_tree_terms, TestCompression, TestEntry};
+    use super::super::tests::{write_segment_info, write_segments, TestSegment};
+    use super::super::{LuceneDocValuesType, LuceneIndex, LuceneIndexOptions};
+    use crate::docset::{DocSet, TERMINATED};
+    use crate::postings::Postings;
+    use crate::schema::{IndexRecordOption, Value};
+    use crate::DocId;
+
+    const MAX_DOC: u32 = 300;
+    const SEGMENT_ID: [u8; 16] = [1u8; 16];
+
+    fn field(
+        name: &'static str,
+        index_options: LuceneIndexOptions,
+        doc_values_type: LuceneDocValuesType,
+    ) -> TestField {
+        TestField {
+            name,
+            index_options,
+            has_payloads: false,
+            doc_values_type,
+            is_soft_deletes_field: false,
+        }
+    }
+
+    fn body_positions(doc: DocId) -> Vec<u32> {
+        (0..doc % 3 + 1).map(|i| i * 2 + doc % 5).collect()
+    }
+
+    /// Writes a segment of 300 documents, with an `id` field indexed and stored, a `body` field
+    /// indexed with positions and payloads, and fields with doc values. The documents 3 and 20
+    /// are hard deleted, and the documents 10 and 20 are soft deleted.
+    fn write_segment(directory_path: &Path, format: PostingsFormat, is_compound_file: bool) {
+        let (format_name, postings_suffix) = match format {
+            PostingsFormat::Lucene90 => ("Lucene90", "Lucene90_0"),
+            PostingsFormat::Lucene99 => ("Lucene99", "Lucene99_0"),
+        };
+        let fields = [
+            field("id", LuceneIndexOptions::Docs, LuceneDocValuesType::None),
+            TestField {
+                has_payloads: true,
+                ..field(
+                    "body",
+                    LuceneIndexOptions::DocsAndFreqsAndPositions,
+                    LuceneDocValuesType::None,
+                )
+            },
+            field(
+                "price",
+                LuceneIndexOptions::None,
+                LuceneDocValuesType::Numeric,
+            ),
+            field(
+                "tags",
+                LuceneIndexOptions::None,
+                LuceneDocValuesType::SortedSet,
+            ),
+            TestField {
+                is_soft_deletes_field: true,
+                ..field(
+                    "soft_deletes",
+                    LuceneIndexOptions::None,
+                    LuceneDocValuesType::Numeric,
+                )
+            },
+        ];
+        let mut files = vec![(
+            segment_file_name("_0", "", "fnm"),
+            write_field_infos(SEGMENT_ID, &fields, format_name),
+        )];
+
+        let mut doc_output = DataOutput::default();
+        doc_output.write_index_header(&format.codec("Doc"), 0, SEGMENT_ID, postings_suffix);
+        let mut pos_output = DataOutput::default();
+        pos_output.write_index_header(&format.codec("Pos"), 0, SEGMENT_ID, postings_suffix);
+        let id_terms: Vec<TestEntry> = (0..MAX_DOC)
+            .map(|doc| {
+                let term_info = write_postings(
+                    format,
+                    &[(doc, vec![0])],
+                    false,
+                    &mut doc_output,
+                    &mut DataOutput::default(),
+                );
+                TestEntry::Term(format!("{doc:03}").into_bytes(), term_info)
+            })
+            .collect();
+        let body_postings: [(&str, Vec<(DocId, Vec<u32>)>); 3] = [
+            (
+                "common",
+                (0..MAX_DOC).map(|doc| (doc, body_positions(doc))).collect(),
+            ),
+            ("rare", vec![(5, vec![1]), (150, vec![0, 4])]),
+            ("single", vec![(7, vec![3, 8])]),
+        ];
+        let body_terms = body_postings
+            .iter()
+            .map(|(term, postings)| {
+                let term_info =
+                    write_postings(format, postings, true, &mut doc_output, &mut pos_output);
+                TestEntry::Term(term.as_bytes().to_vec(), term_info)
+            })
+            .collect();
+        let (terms, terms_meta) = write_block_tree_terms(
+            SEGMENT_ID,
+            postings_suffix,
+            &[
+                (0, &fields[0], vec![id_terms]),
+                (1, &fields[1], vec![body_terms]),
+            ],
+            TestCompression::Lz4,
+            1,
+        );
+        files.push((segment_file_name("_0", postings_suffix, "tim"), terms));
+        files.push((segment_file_name("_0", postings_suffix, "tmd"), terms_meta));
+        files.push((
+            segment_file_name("_0", postings_suffix, "doc"),
+            doc_output.finish(),
+        ));
+        files.push((
+            segment_file_name("_0", postings_suffix, "pos"),
+            pos_output.finish(),
+        ));
+
+        let documents: Vec<Vec<(u32, TestValue)>> = (0..MAX_DOC)
+            .map(|doc| {
+                vec![
+                    (0, TestValue::Str(format!("{doc:03}"))),
+                    (2, TestValue::Long(i64::from(doc) * 10)),
+                ]
+            })
+            .collect();
+        for (extension, data) in
+            write_stored_fields(SEGMENT_ID, &documents, &[100, 200], 1024, false)
+        {
+            files.push((segment_file_name("_0", "", extension), data));
+        }
+
+        let doc_values = [
+            (
+                2,
+                TestDocValues::Numeric((0..MAX_DOC).map(|doc| Some(i64::from(doc) * 10)).collect()),
+            ),
+            (
+                3,
+                TestDocValues::SortedSet(
+                    (0..MAX_DOC)
+                        .map(|doc| match doc {
+                            0 => vec!["red".to_string(), "blue".to_string()],
+                            1 => vec!["green".to_string()],
+                            _ => Vec::new(),
+                        })
+                        .collect(),
+                ),
+            ),
+            (
+                4,
+                TestDocValues::Numeric(
+                    (0..MAX_DOC)
+                        .map(|doc| [10, 20].contains(&doc).then_some(1))
+                        .collect(),
+                ),
+            ),
+        ];
+        for (extension, data) in write_doc_values(SEGMENT_ID, &doc_values, MAX_DOC) {
+            files.push((segment_file_name("_0", "Lucene90_0", extension), data));
+        }
+
+        if is_compound_file {
+            write_compound_file(directory_path, "_0", SEGMENT_ID, &files);
+        } else {
+            for (file_name, data) in files {
+                std::fs::write(directory_path.join(file_name), data).unwrap();
+            }
+        }
+
+        let mut live_docs = DataOutput::default();
+        live_docs.write_index_header("Lucene90LiveDocs", 0, SEGMENT_ID, "1");
+        for word_start in (0..MAX_DOC).step_by(64) {
+            let mut word = u64::MAX;
+            for deleted_doc in [3, 20] {
+                if (word_start..word_start + 64).contains(&deleted_doc) {
+                    word &= !(1 << (deleted_doc - word_start));
+                }
+            }
+            live_docs.write_i64(word as i64);
+        }
+        std::fs::write(directory_path.join("_0_1.liv"), live_docs.finish()).unwrap();
+
+        write_segment_info(
+            directory_path,
+            "_0",
+            SEGMENT_ID,
+            MAX_DOC as i32,
+            is_compound_file,
+        );
+        write_segments(
+            directory_path,
+            1,
+            &[TestSegment {
+                name: "_0",
+                id: SEGMENT_ID,
+                del_gen: 1,
+                num_deleted_docs: 2,
+                num_soft_deleted_docs: 1,
+            }],
+        );
+    }
+
+    #[test]
+    fn test_lucene_segment_reader() -> crate::Result<()> {
+        for (format, is_compound_file) in [
+            (PostingsFormat::Lucene90, false),
+            (PostingsFormat::Lucene99, true),
+        ] {
+            let tempdir = tempfile::TempDir::new().unwrap();
+            write_segment(tempdir.path(), format, is_compound_file);
+            let index = LuceneIndex::open(tempdir.path())?;
+            assert_eq!(index.num_docs()?, 297);
+            let segment_readers = index.segment_readers()?;
+            assert_eq!(segment_readers.len(), 1);
+            let reader = &segment_readers[0];
+            assert_eq!((reader.max_doc(), reader.num_docs()?), (300, 297));
+            let alive_bitset = reader.alive_bitset().unwrap();
+            for doc in [3, 10, 20] {
+                assert!(alive_bitset.is_deleted(doc));
+            }
+            assert_eq!(alive_bitset.num_alive_docs(), 297);
+
+            let document = reader.doc(42)?;
+            assert_eq!(document.0["id"], [Value::Str("042".to_string())]);
+            assert_eq!(document.0["price"], [Value::I64(420)]);
+            assert!(reader.doc(300).is_err());
+
+            let id = reader.inverted_index("id").unwrap();
+            assert_eq!(id.doc_freq(b"042")?, 1);
+            assert_eq!(id.doc_freq(b"300")?, 0);
+            let mut postings = id.read_postings(b"042", IndexRecordOption::Basic)?.unwrap();
+            assert_eq!(postings.doc(), 42);
+            assert_eq!(postings.advance(), TERMINATED);
+            let mut terms = id.terms();
+            let mut num_terms = 0;
+            while terms.advance()? {
+                assert_eq!(terms.key(), format!("{num_terms:03}").as_bytes());
+                num_terms += 1;
+            }
+            assert_eq!(num_terms, 300);
+
+            let body = reader.inverted_index("body").unwrap();
+            let mut postings = body
+                .read_postings(b"common", IndexRecordOption::WithFreqsAndPositions)?
+                .unwrap();
+            let mut positions = Vec::new();
+            for doc in 0..MAX_DOC {
+                assert_eq!(postings.doc(), doc);
+                assert_eq!(postings.term_freq(), doc % 3 + 1);
+                postings.positions(&mut positions);
+                assert_eq!(positions, body_positions(doc));
+                postings.advance();
+            }
+            assert_eq!(postings.doc(), TERMINATED);
+            let mut postings = body
+                .read_postings(b"rare", IndexRecordOption::WithFreqs)?
+                .unwrap();
+            assert_eq!((postings.doc(), postings.term_freq()), (5, 1));
+            assert_eq!(postings.advance(), 150);
+            assert_eq!(postings.term_freq(), 2);
+            let mut postings = body
+                .read_postings(b"single", IndexRecordOption::WithFreqsAndPositions)?
+                .unwrap();
+            postings.positions(&mut positions);
+            assert_eq!((postings.doc(), positions), (7, vec![3, 8]));
+            assert!(body
+                .read_postings(b"missing", IndexRecordOption::Basic)?
+                .is_none());
+            assert!(reader.inverted_index("price").is_none());
+
+            let price = reader.i64_column("price")?.unwrap();
+            assert_eq!(price.first(7), Some(70));
+            let tags = reader.bytes_column("tags")?.unwrap();
+            let mut tag = Vec::new();
+            let ords: Vec<u64> = tags.term_ords(0).collect();
+            assert_eq!(ords, [0, 2]);
+            assert!(tags.ord_to_bytes(ords[1], &mut tag)?);
+            assert_eq!(tag, b"red");
+            assert!(reader.i64_column("id")?.is_none());
+            assert!(reader.bytes_column("price")?.is_none());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,748 @@
+use super::compression::{inflate_with_dictionary, lz4_decompress};
+use super::data_input::{DataInput, LuceneFile, ID_LEN};
+use super::field_infos::LuceneFieldInfo;
+use super::packed_ints::{DirectMonotonicMeta, DirectMonotonicReader};
+use super::segment_files::SegmentFiles;
+use crate::schema::{NamedFieldDocument, Value};
+use crate::DocId;
+
+const FIELDS_EXTENSION: &str = "fdt";
+const FAST_DATA_CODEC: &str = "Lucene90StoredFieldsFastData";
+const HIGH_DATA_CODEC: &str = "Lucene90StoredFieldsHighData";
+const META_EXTENSION: &str = "fdm";
+const META_CODEC: &str = "Lucene90FieldsIndexMeta";
+const INDEX_EXTENSION: &str = "fdx";
+const INDEX_CODEC: &str = "Lucene90FieldsIndexIdx";
+const MAX_VERSION: u32 = 1;
+
+const TYPE_BITS: u32 = 3;
+const STRING: u64 = 0;
+const BYTE_ARR: u64 = 1;
+const NUMERIC_INT: u64 = 2;
+const NUMERIC_FLOAT: u64 = 3;
+const NUMERIC_LONG: u64 = 4;
+const NUMERIC_DOUBLE: u64 = 5;
+
+/// Number of integers of the blocks of `StoredFieldsInts`.
+const INTS_BLOCK_SIZE: usize = 128;
+
+/// How the chunks of documents are compressed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CompressionMode {
+    /// `BEST_SPEED`, Lucene's `LZ4WithPresetDictCompressionMode`.
+    Lz4WithPresetDict,
+    /// `BEST_COMPRESSION`, Lucene's `DeflateWithPresetDictCompressionMode`.
+    DeflateWithPresetDict,
+}
+
+/// Reads the stored fields of a segment, written by the `Lucene90StoredFieldsFormat`.
+///
+/// The documents are stored in compressed chunks, located through the doc ids and the offsets
+/// of the first documents of the chunks, in the `.fdx` file.
+pub(crate) struct StoredFieldsReader {
+    fields_file: LuceneFile,
+    compression_mode: CompressionMode,
+    chunk_size: usize,
+    /// The first doc id of each chunk, followed by `max_doc`.
+    chunk_docs: DirectMonotonicReader,
+    /// The offset of each chunk in the `.fdt` file, followed by the end of the last chunk.
+    chunk_offsets: DirectMonotonicReader,
+}
+
+impl StoredFieldsReader {
+    pub fn open(
+        files: &SegmentFiles,
+        segment_id: [u8; ID_LEN],
+        max_doc: u32,
+    ) -> crate::Result<StoredFieldsReader> {
+        let meta_file = files.open_file("", META_EXTENSION)?;
+        let mut meta = meta_file.input();
+        let version = meta.read_segment_header(META_CODEC, 0..=MAX_VERSION, segment_id, "")?;
+        let fields_file = files.open_file("", FIELDS_EXTENSION)?;
+        let mut fields_input = fields_file.input();
+        let compression_mode = match fields_input.peek_codec()?.as_str() {
+            FAST_DATA_CODEC => CompressionMode::Lz4WithPresetDict,
+            HIGH_DATA_CODEC => CompressionMode::DeflateWithPresetDict,
+            codec => {
+                return Err(
+                    fields_input.corruption(&format!("Unknown stored fields codec {codec:?}"))
+                )
+            }
+        };
+        let codec = match compression_mode {
+            CompressionMode::Lz4WithPresetDict => FAST_DATA_CODEC,
+            CompressionMode::DeflateWithPresetDict => HIGH_DATA_CODEC,
+        };
+        fields_input.read_segment_header(codec, version..=version, segment_id, "")?;
+        let chunk_size = meta.read_vint()? as usize;
+        if chunk_size == 0 {
+            return Err(meta.corruption("Invalid chunk size"));
+        }
+
+        // The metadata of the `FieldsIndexWriter`.
+        let index_file = files.open_file("", INDEX_EXTENSION)?;
+        index_file
+            .input()
+            .read_segment_header(INDEX_CODEC, version..=version, segment_id, "")?;
+        if meta.read_i32()? as i64 != i64::from(max_doc) {
+            return Err(meta.corruption("The stored fields do not match the number of documents"));
+        }
+        let block_shift = meta.read_i32()? as u32;
+        let num_chunks_plus_one = u64::try_from(meta.read_i32()?)
+            .map_err(|_| meta.corruption("Invalid number of chunks"))?;
+        let docs_start = meta.read_i64()? as u64;
+        let docs_meta = DirectMonotonicMeta::read(&mut meta, num_chunks_plus_one, block_shift)?;
+        let docs_end = meta.read_i64()? as u64;
+        let offsets_meta = DirectMonotonicMeta::read(&mut meta, num_chunks_plus_one, block_shift)?;
+        let offsets_end = meta.read_i64()? as u64;
+        let _max_pointer = meta.read_i64()?;
+        let _num_chunks = meta.read_vlong()?;
+        let _num_dirty_chunks = meta.read_vlong()?;
+        let _num_dirty_docs = meta.read_vlong()?;
+        let slice_len = |start: u64, end: u64| {
+            end.checked_sub(start)
+                .ok_or_else(|| meta.corruption("Invalid fields index"))
+        };
+        let chunk_docs =
+            docs_meta.reader(index_file.slice(docs_start, slice_len(docs_start, docs_end)?)?);
+        let chunk_offsets =
+            offsets_meta.reader(index_file.slice(docs_end, slice_len(docs_end, offsets_end)?)?);
+        if chunk_docs.num_values() == 0
+            || chunk_docs.get(chunk_docs.num_values() - 1) != i64::from(max_doc)
+        {
+            return Err(meta.corruption("The stored fields do not match the number of documents"));
+        }
+        Ok(StoredFieldsReader {
+            fields_file,
+            compression_mode,
+            chunk_size,
+            chunk_docs,
+            chunk_offsets,
+        })
+    }
+
+    /// Returns the stored fields of a document, named after `field_infos`.
+    pub fn document(
+        &self,
+        doc: DocId,
+        field_infos: &[LuceneFieldInfo],
+    ) -> crate::Result<NamedFieldDocument> {
+        let num_chunks = self.chunk_docs.num_values() - 1;
+        // The last chunk whose first doc id is lower or equal to `doc`.
+        let chunk = partition_point(num_chunks, |chunk| {
+            self.chunk_docs.get(chunk) <= i64::from(doc)
+        })
+        .checked_sub(1)
+        .filter(|_| i64::from(doc) < self.chunk_docs.get(num_chunks))
+        .ok_or_else(|| {
+            self.fields_file
+                .input()
+                .corruption(&format!("Invalid doc id {doc}"))
+        })?;
+        let mut input = self
+            .fields_file
+            .input_at(self.chunk_offsets.get(chunk) as u64)?;
+        let doc_base = input.read_vint()?;
+        let token = input.read_vint()?;
+        let num_docs = token >> 2;
+        let is_sliced = token & 1 != 0;
+        if i64::from(doc_base) != self.chunk_docs.get(chunk)
+            || i64::from(doc_base) + i64::from(num_docs) != self.chunk_docs.get(chunk + 1)
+        {
+            return Err(input.corruption("Invalid chunk of documents"));
+        }
+        let num_stored_fields = read_stored_fields_ints(&mut input, num_docs as usize)?;
+        let lengths = read_stored_fields_ints(&mut input, num_docs as usize)?;
+        let doc_in_chunk = (doc - doc_base) as usize;
+        let doc_start: usize = lengths[..doc_in_chunk]
+            .iter()
+            .map(|&len| len as usize)
+            .sum();
+        let doc_end = doc_start + lengths[doc_in_chunk] as usize;
+        let total_len: usize = lengths.iter().map(|&len| len as usize).sum();
+
+        let mut data = Vec::with_capacity(total_len);
+        if is_sliced {
+            // The chunks larger than twice the chunk size are compressed in slices.
+            while data.len() < total_len {
+                let slice_len = self.chunk_size.min(total_len - data.len());
+                self.decompress(&mut input, slice_len, &mut data)?;
+            }
+        } else {
+            self.decompress(&mut input, total_len, &mut data)?;
+        }
+
+        let mut input = DataInput::new(self.fields_file.path(), &data[doc_start..doc_end]);
+        let mut document = NamedFieldDocument(Default::default());
+        for _ in 0..num_stored_fields[doc_in_chunk] {
+            let code = input.read_vlong()?;
+            let number = code >> TYPE_BITS;
+            let value = match code & ((1 << TYPE_BITS) - 1) {
+                STRING => Value::Str(input.read_string()?),
+                BYTE_ARR => Value::Bytes(input.read_bytes_ref()?.to_vec()),
+                NUMERIC_INT => Value::I64(i64::from(input.read_zint()?)),
+                NUMERIC_FLOAT => Value::F64(f64::from(read_zfloat(&mut input)?)),
+                NUMERIC_LONG => Value::I64(read_tlong(&mut input)?),
+                NUMERIC_DOUBLE => Value::F64(read_zdouble(&mut input)?),
+                _ => return Err(input.corruption("Invalid type of stored field")),
+            };
+            let field_info = field_infos
+                .iter()
+                .find(|field_info| u64::from(field_info.number()) == number)
+                .ok_or_else(|| input.corruption(&format!("Unknown field number {number}")))?;
+            document
+                .0
+                .entry(field_info.name().to_string())
+                .or_default()
+                .push(value);
+        }
+        if input.offset() != doc_end - doc_start {
+            return Err(input.corruption("Invalid length of document"));
+        }
+        Ok(document)
+    }
+
+    /// Decompresses `len` bytes, compressed in a dictionary followed by blocks which are
+    /// compressed with this dictionary.
+    fn decompress(
+        &self,
+        input: &mut DataInput,
+        len: usize,
+        output: &mut Vec<u8>,
+    ) -> crate::Result<()> {
+        let start = output.len();
+        let dictionary_len = input.read_vint()? as usize;
+        let block_len = input.read_vint()? as usize;
+        if dictionary_len > len || (block_len == 0 && dictionary_len < len) {
+            return Err(input.corruption("Invalid compressed blocks"));
+        }
+        match self.compression_mode {
+            CompressionMode::Lz4WithPresetDict => {
+                // The compressed lengths of the dictionary and of the blocks, which are not
+                // needed to decompress all the blocks.
+                let _dictionary_compressed_len = input.read_vint()?;
+                let num_blocks = if dictionary_len == len {
+                    0
+                } else {
+                    (len - dictionary_len + block_len - 1) / block_len
+                };
+                for _ in 0..num_blocks {
+                    let _block_compressed_len = input.read_vint()?;
+                }
+                let mut dictionary = Vec::with_capacity(dictionary_len + block_len);
+                lz4_decompress(input, dictionary_len, &mut dictionary)?;
+                output.extend_from_slice(&dictionary);
+                for block_ord in 0..num_blocks {
+                    let block_start = dictionary_len + block_ord * block_len;
+                    let mut buffer = dictionary.clone();
+                    lz4_decompress(input, block_len.min(len - block_start), &mut buffer)?;
+                    output.extend_from_slice(&buffer[dictionary_len..]);
+                }
+            }
+            CompressionMode::DeflateWithPresetDict => {
+                inflate_block(input, &[], output)?;
+                if output.len() - start != dictionary_len {
+                    return Err(input.corruption("Invalid length of the dictionary"));
+                }
+                let dictionary = output[start..].to_vec();
+                while output.len() - start < len {
+                    let block_start = output.len();
+                    inflate_block(input, &dictionary, output)?;
+                    if output.len() == block_start {
+                        return Err(input.corruption("Empty compressed block"));
+                    }
+                }
+            }
+        }
+        if output.len() - start != len {
+            return Err(input.corruption("Invalid length of the decompressed data"));
+        }
+        Ok(())
+    }
+}
+
+fn inflate_block(
+    input: &mut DataInput,
+    dictionary: &[u8],
+    output: &mut Vec<u8>,
+) -> crate::Result<()> {
+    let compressed_len = input.read_vint()? as usize;
+    if compressed_len == 0 {
+        return Ok(());
+    }
+    let compressed = input.read_bytes(compressed_len)?;
+    inflate_with_dictionary(input, compressed, dictionary, output)
+}
+
+/// Returns the first index in `0..len` for which `predicate` is false, `predicate` being true
+/// and then false over the range.
+fn partition_point(len: u64, predicate: impl Fn(u64) -> bool) -> u64 {
+    let (mut low, mut high) = (0, len);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if predicate(mid) {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+/// Reads `count` integers written by Lucene's `StoredFieldsInts.writeInts()`: a single
+/// variable length integer if they are all equal, or blocks of 128 integers packed in lanes of
+/// 8, 16 or 32 bits, followed by the remaining integers.
+fn read_stored_fields_ints(input: &mut DataInput, count: usize) -> crate::Result<Vec<u32>> {
+    if count == 1 {
+        return Ok(vec![input.read_vint()?]);
+    }
+    let bits_per_value = u32::from(input.read_byte()?);
+    if bits_per_value == 0 {
+        return Ok(vec![input.read_vint()?; count]);
+    }
+    if ![8, 16, 32].contains(&bits_per_value) {
+        return Err(input.corruption(&format!("Invalid number of bits {bits_per_value}")));
+    }
+    let mut values = vec![0u32; count];
+    let lanes_per_long = (64 / bits_per_value) as usize;
+    let num_longs = INTS_BLOCK_SIZE / lanes_per_long;
+    let mask = (1u64 << bits_per_value) - 1;
+    let mut block_start = 0;
+    while block_start + INTS_BLOCK_SIZE <= count {
+        for i in 0..num_longs {
+            let long = input.read_i64()? as u64;
+            for lane in 0..lanes_per_long {
+                let shift = bits_per_value as usize * (lanes_per_long - 1 - lane);
+                values[block_start + lane * num_longs + i] = ((long >> shift) & mask) as u32;
+            }
+        }
+        block_start += INTS_BLOCK_SIZE;
+    }
+    for value in &mut values[block_start..] {
+        *value = match bits_per_value {
+            8 => u32::from(input.read_byte()?),
+            16 => u32::from(input.read_u16()?),
+            _ => input.read_i32()? as u32,
+        };
+    }
+    Ok(values)
+}
+
+/// Reads a `float` written by `Lucene90CompressingStoredFieldsWriter.writeZFloat()`.
+fn read_zfloat(input: &mut DataInput) -> crate::Result<f32> {
+    let header = input.read_byte()?;
+    if header == 0xff {
+        Ok(f32::from_bits(input.read_i32()? as u32))
+    } else if header & 0x80 != 0 {
+        // A small integer in `[-1, 125]`.
+        Ok(f32::from(header & 0x7f) - 1.0)
+    } else {
+        let bits = u32::from(header) << 24
+            | u32::from(input.read_u16()?) << 8
+            | u32::from(input.read_byte()?);
+        Ok(f32::from_bits(bits))
+    }
+}
+
+/// Reads a `double` written by `Lucene90CompressingStoredFieldsWriter.writeZDouble()`.
+fn read_zdouble(input: &mut DataInput) -> crate::Result<f64> {
+    let header = input.read_byte()?;
+    if header == 0xff {
+        Ok(f64::from_bits(input.read_i64()? as u64))
+    } else if header == 0xfe {
+        Ok(f64::from(f32::from_bits(input.read_i32()? as u32)))
+    } else if header & 0x80 != 0 {
+        // A small integer in `[-1, 124]`.
+        Ok(f64::from(header & 0x7f) - 1.0)
+    } else {
+        let bits = u64::from(header) << 56
+            | u64::from(input.read_i32()? as u32) << 24
+            | u64::from(input.read_u16()?) << 8
+            | u64::from(input.read_byte()?);
+        Ok(f64::from_bits(bits))
+    }
+}
+
+/// Reads a `long` written by `Lucene90CompressingStoredFieldsWriter.writeTLong()`, which
+/// compacts the multiples of a second, an hour or a day, as timestamps often are.
+fn read_tlong(input: &mut DataInput) -> crate::Result<i64> {
+    const SECOND: i64 = 1000;
+    const HOUR: i64 = 60 * 60 * SECOND;
+    const DAY: i64 = 24 * HOUR;
+    let header = input.read_byte()?;
+    let mut bits = u64::from(header & 0x1f);
+    if header & 0x20 != 0 {
+        bits |= input.read_vlong()? << 5;
+    }
+    let value = (bits >> 1) as i64 ^ -((bits & 1) as i64);
+    let multiplier = match header & 0xc0 {
+        0x40 => SECOND,
+        0x80 => HOUR,
+        0xc0 => DAY,
+        _ => 1,
+    };
+    Ok(value.wrapping_mul(multiplier))
+}
+
+#[cfg(all(test, feature = "mmap"))]
+pub(crate) mod tests {
+    use std::path::PathBuf;
+
+    use common::OwnedBytes;
+
+    use super::super::compression::tests::lz4_literals;
+    use super::super::data_input::tests::DataOutput;
+    use super::super::data_input::LuceneFile;
+    use super::super::field_infos::tests::{write_field_infos, TestField};
+    use super::super::field_infos::{read_field_infos, LuceneDocValuesType, LuceneIndexOptions};
+    use super::super::packed_ints::tests::direct_monotonic_write;
+    use super::super::segment_files::SegmentFiles;
+    use super::StoredFieldsReader;
+    use crate::schema::Value;
+
+    /// A stored value of a test document, by field number.
+    pub enum TestValue {
+        Str(String),
+        Bytes(&'static [u8]),
+        Int(i32),
+        Float(f32),
+        Long(i64),
+        Double(f64),
+    }
+
+    fn write_zint(output: &mut DataOutput, value: i32) {
+        output.write_vlong(((value << 1) ^ (value >> 31)) as u32 as u64);
+    }
+
+    fn write_zfloat(output: &mut DataOutput, value: f32) {
+        let bits = value.to_bits();
+        if value == (value as i32) as f32 && (-1.0..=125.0).contains(&value) && bits != 1 << 31 {
+            output.data.push(0x80 | (1 + value as i32) as u8);
+        } else if bits >> 31 == 0 {
+            output.data.push((bits >> 24) as u8);
+            output
+                .data
+                .extend_from_slice(&((bits >> 8) as u16).to_le_bytes());
+            output.data.push(bits as u8);
+        } else {
+            output.data.push(0xff);
+            output.write_i32(bits as i32);
+        }
+    }
+
+    fn write_zdouble(output: &mut DataOutput, value: f64) {
+        let bits = value.to_bits();
+        if value == (value as i32) as f64 && (-1.0..=124.0).contains(&value) && bits != 1 << 63 {
+            output.data.push(0x80 | (1 + value as i32) as u8);
+        } else if f64::from(value as f32) == value {
+            output.data.push(0xfe);
+            output.write_i32((value as f32).to_bits() as i32);
+        } else if bits >> 63 == 0 {
+            output.data.push((bits >> 56) as u8);
+            output.write_i32((bits >> 24) as i32);
+            output
+                .data
+                .extend_from_slice(&((bits >> 8) as u16).to_le_bytes());
+            output.data.push(bits as u8);
+        } else {
+            output.data.push(0xff);
+            output.write_i64(bits as i64);
+        }
+    }
+
+    fn write_tlong(output: &mut DataOutput, value: i64) {
+        let (encoding, value) = if value % 86_400_000 == 0 {
+            (0xc0, value / 86_400_000)
+        } else if value % 3_600_000 == 0 {
+            (0x80, value / 3_600_000)
+        } else if value % 1000 == 0 {
+            (0x40, value / 1000)
+        } else {
+            (0, value)
+        };
+        let zig_zag = ((value << 1) ^ (value >> 63)) as u64;
+        let mut header = encoding | (zig_zag & 0x1f) as u8;
+        if zig_zag >> 5 != 0 {
+            header |= 0x20;
+        }
+        output.data.push(header);
+        if zig_zag >> 5 != 0 {
+            output.write_vlong(zig_zag >> 5);
+        }
+    }
+
+    fn write_stored_fields_ints(output: &mut DataOutput, values: &[u32]) {
+        if values.len() == 1 {
+            output.write_vlong(u64::from(values[0]));
+            return;
+        }
+        if values.iter().all(|&value| value == values[0]) {
+            output.data.push(0);
+            output.write_vlong(u64::from(values[0]));
+            return;
+        }
+        let max = values.iter().fold(0, |max, &value| max | value);
+        let bits_per_value = if max <= 0xff {
+            8
+        } else if max <= 0xffff {
+            16
+        } else {
+            32
+        };
+        output.data.push(bits_per_value as u8);
+        let lanes_per_long = 64 / bits_per_value;
+        let num_longs = 128 / lanes_per_long;
+        let mut blocks = values.chunks_exact(128);
+        for block in blocks.by_ref() {
+            for i in 0..num_longs {
+                let long = (0..lanes_per_long).fold(0u64, |long, lane| {
+                    long | u64::from(block[lane * num_longs + i])
+                        << (bits_per_value * (lanes_per_long - 1 - lane))
+                });
+                output.write_i64(long as i64);
+            }
+        }
+        for &value in blocks.remainder() {
+            match bits_per_value {
+                8 => output.data.push(value as u8),
+                16 => output.data.extend_from_slice(&(value as u16).to_le_bytes()),
+                _ => output.write_i32(value as i32),
+            }
+        }
+    }
+
+    /// Compresses `data` with the framing of Lucene's `LZ4WithPresetDictCompressionMode`, or of
+    /// its `DeflateWithPresetDictCompressionMode`, with literals or stored blocks only.
+    fn compress(data: &[u8], use_deflate: bool, output: &mut DataOutput) {
+        let len = data.len();
+        let dictionary_len = len / if use_deflate { 60 } else { 160 };
+        let block_len = (len - dictionary_len + 9) / 10;
+        output.write_vlong(dictionary_len as u64);
+        output.write_vlong(block_len as u64);
+        let mut pieces = vec![&data[..dictionary_len]];
+        pieces.extend(data[dictionary_len..].chunks(block_len.max(1)));
+        if use_deflate {
+            for piece in pieces {
+                if piece.is_empty() {
+                    output.write_vlong(0);
+                    continue;
+                }
+                // A single stored block.
+                let mut stored = vec![1];
+                stored.extend_from_slice(&(piece.len() as u16).to_le_bytes());
+                stored.extend_from_slice(&(!(piece.len() as u16)).to_le_bytes());
+                stored.extend_from_slice(piece);
+                output.write_vlong(stored.len() as u64);
+                output.data.extend_from_slice(&stored);
+            }
+        } else {
+            let compressed: Vec<Vec<u8>> = pieces.iter().map(|piece| lz4_literals(piece)).collect();
+            for piece in &compressed {
+                output.write_vlong(piece.len() as u64);
+            }
+            for piece in compressed {
+                output.data.extend_from_slice(&piece);
+            }
+        }
+    }
+
+    /// Writes the `.fdt`, `.fdm` and `.fdx` files of documents, split in chunks of the given
+    /// numbers of documents, like the `Lucene90CompressingStoredFieldsWriter`.
+    pub fn write_stored_fields(
+        segment_id: [u8; 16],
+        documents: &[Vec<(u32, TestValue)>],
+        chunk_lens: &[usize],
+        chunk_size: usize,
+        use_deflate: bool,
+    ) -> Vec<(&'static str, Vec<u8>)> {
+        let mut fields = DataOutput::default();
+        let codec = if use_deflate {
+            "Lucene90StoredFieldsHighData"
+        } else {
+            "Lucene90StoredFieldsFastData"
+        };
+        fields.write_index_header(codec, 1, segment_id, "");
+        let mut chunk_docs = vec![0];
+        let mut chunk_offsets = Vec::new();
+        let mut doc_base = 0;
+        for &chunk_len in chunk_lens {
+            chunk_offsets.push(fields.data.len() as i64);
+            let chunk = &documents[doc_base..doc_base + chunk_len];
+            let mut data = DataOutput::default();
+            let mut lengths = Vec::new();
+            for document in chunk {
+                let start = data.data.len();
+                for (number, value) in document {
+                    let value_type = match value {
+                        TestValue::Str(_) => 0,
+                        TestValue::Bytes(_) => 1,
+                        TestValue::Int(_) => 2,
+                        TestValue::Float(_) => 3,
+                        TestValue::Long(_) => 4,
+                        TestValue::Double(_) => 5,
+                    };
+                    data.write_vlong(u64::from(*number) << 3 | value_type);
+                    match value {
+                        TestValue::Str(text) => data.write_string(text),
+                        TestValue::Bytes(bytes) => {
+                            data.write_vlong(bytes.len() as u64);
+                            data.data.extend_from_slice(bytes);
+                        }
+                        TestValue::Int(value) => write_zint(&mut data, *value),
+                        TestValue::Float(value) => write_zfloat(&mut data, *value),
+                        TestValue::Long(value) => write_tlong(&mut data, *value),
+                        TestValue::Double(value) => write_zdouble(&mut data, *value),
+                    }
+                }
+                lengths.push((data.data.len() - start) as u32);
+            }
+            let is_sliced = data.data.len() >= 2 * chunk_size;
+            fields.write_vlong(doc_base as u64);
+            fields.write_vlong((chunk_len as u64) << 2 | is_sliced as u64);
+            let num_stored_fields: Vec<u32> =
+                chunk.iter().map(|document| document.len() as u32).collect();
+            write_stored_fields_ints(&mut fields, &num_stored_fields);
+            write_stored_fields_ints(&mut fields, &lengths);
+            if is_sliced {
+                for slice in data.data.chunks(chunk_size) {
+                    compress(slice, use_deflate, &mut fields);
+                }
+            } else {
+                compress(&data.data, use_deflate, &mut fields);
+            }
+            doc_base += chunk_len;
+            chunk_docs.push(doc_base as i64);
+        }
+        chunk_offsets.push(fields.data.len() as i64);
+
+        let mut meta = DataOutput::default();
+        meta.write_index_header("Lucene90FieldsIndexMeta", 1, segment_id, "");
+        meta.write_vlong(chunk_size as u64);
+        let mut index = DataOutput::default();
+        index.write_index_header("Lucene90FieldsIndexIdx", 1, segment_id, "");
+        let block_shift = 2;
+        meta.write_i32(doc_base as i32);
+        meta.write_i32(block_shift as i32);
+        meta.write_i32(chunk_docs.len() as i32);
+        for values in [&chunk_docs, &chunk_offsets] {
+            meta.write_i64(index.data.len() as i64);
+            let (values_meta, values_data) = direct_monotonic_write(values, block_shift);
+            meta.data.extend_from_slice(&values_meta.data);
+            index.data.extend_from_slice(&values_data);
+        }
+        meta.write_i64(index.data.len() as i64);
+        meta.write_i64(fields.data.len() as i64);
+        meta.write_vlong(chunk_lens.len() as u64);
+        meta.write_vlong(0);
+        meta.write_vlong(0);
+        vec![
+            ("fdt", fields.finish()),
+            ("fdm", meta.finish()),
+            ("fdx", index.finish()),
+        ]
+    }
+
+    #[test]
+    fn test_stored_fields() -> crate::Result<()> {
+        let fields: Vec<TestField> = ["id", "body", "number"]
+            .into_iter()
+            .map(|name| TestField {
+                name,
+                index_options: LuceneIndexOptions::None,
+                has_payloads: false,
+                doc_values_type: LuceneDocValuesType::None,
+                is_soft_deletes_field: false,
+            })
+            .collect();
+        let segment_id = [5u8; 16];
+        let field_infos = read_field_infos(
+            &LuceneFile::open(
+                PathBuf::from("_0.fnm"),
+                OwnedBytes::new(write_field_infos(segment_id, &fields, "Lucene90")),
+            )?,
+            segment_id,
+            "",
+        )?;
+        let numbers = [
+            TestValue::Int(-7),
+            TestValue::Int(i32::MAX),
+            TestValue::Float(3.0),
+            TestValue::Float(-2.5),
+            TestValue::Float(1e10),
+            TestValue::Long(-86_400_000 * 3),
+            TestValue::Long(7_200_000),
+            TestValue::Long(5000),
+            TestValue::Long(i64::MIN + 1),
+            TestValue::Double(12.0),
+            TestValue::Double(0.5),
+            TestValue::Double(std::f64::consts::PI),
+            TestValue::Double(-std::f64::consts::E),
+        ];
+        let expected_numbers = [
+            Value::I64(-7),
+            Value::I64(i64::from(i32::MAX)),
+            Value::F64(3.0),
+            Value::F64(-2.5),
+            Value::F64(1e10),
+            Value::I64(-86_400_000 * 3),
+            Value::I64(7_200_000),
+            Value::I64(5000),
+            Value::I64(i64::MIN + 1),
+            Value::F64(12.0),
+            Value::F64(0.5),
+            Value::F64(std::f64::consts::PI),
+            Value::F64(-std::f64::consts::E),
+        ];
+        let mut documents: Vec<Vec<(u32, TestValue)>> = (0..300)
+            .map(|doc| {
+                let body: &'static [u8] = if doc % 3 == 0 { b"" } else { &[7u8; 300] };
+                vec![
+                    (
+                        0,
+                        TestValue::Str(["zero", "one", "two"][doc % 3].to_string()),
+                    ),
+                    (1, TestValue::Bytes(body)),
+                ]
+            })
+            .collect();
+        documents[150] = numbers
+            .into_iter()
+            .map(|value| (2, value))
+            .chain([(0, TestValue::Str("numbers".to_string()))])
+            .collect();
+        documents[299] = Vec::new();
+        for use_deflate in [false, true] {
+            let tempdir = tempfile::TempDir::new().unwrap();
+            let files = write_stored_fields(
+                segment_id,
+                &documents,
+                &[1, 129, 20, 150],
+                4096,
+                use_deflate,
+            );
+            for (extension, data) in files {
+                std::fs::write(tempdir.path().join(format!("_0.{extension}")), data).unwrap();
+            }
+            let files = SegmentFiles::open(tempdir.path(), "_0", segment_id, false)?;
+            let reader = StoredFieldsReader::open(&files, segment_id, 300)?;
+            for doc in [0, 1, 2, 129, 130, 149, 151, 298] {
+                let document = reader.document(doc, &field_infos)?;
+                assert_eq!(
+                    document.0["id"],
+                    [Value::Str(
+                        ["zero", "one", "two"][doc as usize % 3].to_string()
+                    )]
+                );
+                let body = if doc % 3 == 0 { vec![] } else { vec![7u8; 300] };
+                assert_eq!(document.0["body"], [Value::Bytes(body)]);
+            }
+            let document = reader.document(150, &field_infos)?;
+            assert_eq!(document.0["id"], [Value::Str("numbers".to_string())]);
+            assert_eq!(document.0["number"], expected_numbers);
+            assert!(reader.document(299, &field_infos)?.0.is_empty());
+            assert!(reader.document(300, &field_infos).is_err());
+            assert!(StoredFieldsReader::open(&files, segment_id, 301).is_err());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,777 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+
+use super::compression::{lowercase_ascii_decompress, lz4_decompress};
+use super::data_input::{DataInput, LuceneFile, ID_LEN};
+use super::field_infos::{LuceneFieldInfo, LuceneIndexOptions};
+use super::packed_ints::BLOCK_SIZE;
+use super::postings::PostingsFormat;
+use super::segment_files::SegmentFiles;
+use crate::DocId;
+
+const TERMS_EXTENSION: &str = "tim";
+const TERMS_CODEC: &str = "BlockTreeTermsDict";
+const TERMS_META_EXTENSION: &str = "tmd";
+const TERMS_META_CODEC: &str = "BlockTreeTermsMeta";
+/// `Lucene90BlockTreeTermsReader.VERSION_MSB_VLONG_OUTPUT`: since Lucene 9.10, the outputs of
+/// the index of the terms are written with their most significant bits first.
+const TERMS_VERSION_MSB_VLONG_OUTPUT: u32 = 1;
+const FST_CODEC: &str = "FST";
+
+/// The statistics of a term of a Lucene segment, and the location of its postings.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LuceneTermInfo {
+    /// Number of documents containing the term.
+    pub doc_freq: u32,
+    /// Number of occurrences of the term, or `doc_freq` if the term frequencies are not
+    /// indexed.
+    pub total_term_freq: u64,
+    pub(crate) doc_start_offset: u64,
+    pub(crate) pos_start_offset: u64,
+    pub(crate) pay_start_offset: u64,
+    /// The doc id of the terms present in a single document, which have no doc postings.
+    pub(crate) singleton_doc: Option<DocId>,
+    /// The offset of the positions which are not in a block of 128 positions, relative to
+    /// `pos_start_offset`, if the term has more than 128 positions.
+    pub(crate) last_pos_block_offset: Option<u64>,
+}
+
+/// The terms of a field, in the block tree terms dictionary of Lucene.
+///
+/// The terms sharing a prefix are grouped in blocks, whose entries are either the suffixes of
+/// the terms or sub-blocks for longer prefixes. Lucene finds the block of a term with an FST
+/// index; the terms are found here by walking from the root block instead.
+#[derive(Clone)]
+pub(crate) struct FieldTerms {
+    terms_file: Arc<LuceneFile>,
+    root_block_offset: u64,
+    pub num_terms: u64,
+    pub sum_total_term_freq: u64,
+    pub sum_doc_freq: u64,
+    pub doc_count: u32,
+    pub index_options: LuceneIndexOptions,
+    pub has_payloads: bool,
+}
+
+/// Reads the `.tmd` and `.tim` files of a postings format, written by the
+/// `Lucene90BlockTreeTermsWriter`, and returns the terms of their fields, by field number.
+pub(crate) fn read_block_tree_terms(
+    files: &SegmentFiles,
+    segment_id: [u8; ID_LEN],
+    suffix: &str,
+    postings_format: PostingsFormat,
+    field_infos: &[LuceneFieldInfo],
+) -> crate::Result<HashMap<u32, FieldTerms>> {
+    let meta_file = files.open_file(suffix, TERMS_META_EXTENSION)?;
+    let mut input = meta_file.input();
+    let version = input.read_segment_header(
+        TERMS_META_CODEC,
+        0..=TERMS_VERSION_MSB_VLONG_OUTPUT,
+        segment_id,
+        suffix,
+    )?;
+    input.read_segment_header(&postings_format.codec("Terms"), 0..=0, segment_id, suffix)?;
+    if input.read_vint()? as usize != BLOCK_SIZE {
+        return Err(input.corruption("Invalid postings block size"));
+    }
+    let terms_file = files.open_file(suffix, TERMS_EXTENSION)?;
+    terms_file
+        .input()
+        .read_segment_header(TERMS_CODEC, version..=version, segment_id, suffix)?;
+    let terms_file = Arc::new(terms_file);
+    let num_fields = input.read_vint()?;
+    let mut fields = HashMap::new();
+    for _ in 0..num_fields {
+        let number = input.read_vint()?;
+        let num_terms = input.read_vlong()?;
+        let root_code = input.read_bytes_ref()?;
+        let field_info = field_infos
+            .iter()
+            .find(|field_info| field_info.number() == number)
+            .ok_or_else(|| input.corruption(&format!("Unknown field number {number}")))?;
+        let index_options = field_info.index_options();
+        let sum_total_term_freq = input.read_vlong()?;
+        let sum_doc_freq = if index_options.has_freqs() {
+            input.read_vlong()?
+        } else {
+            sum_total_term_freq
+        };
+        let doc_count = input.read_vint()?;
+        let _min_term = input.read_bytes_ref()?;
+        let _max_term = input.read_bytes_ref()?;
+        let _index_start_offset = input.read_vlong()?;
+        skip_fst_metadata(&mut input)?;
+        let mut root_code_input = DataInput::new(meta_file.path(), root_code);
+        let root_code = if version >= TERMS_VERSION_MSB_VLONG_OUTPUT {
+            read_msb_vlong(&mut root_code_input)?
+        } else {
+            root_code_input.read_vlong()?
+        };
+        fields.insert(
+            number,
+            FieldTerms {
+                terms_file: terms_file.clone(),
+                // The 2 lowest bits are flags of the block.
+                root_block_offset: root_code >> 2,
+                num_terms,
+                sum_total_term_freq,
+                sum_doc_freq,
+                doc_count,
+                index_options,
+                has_payloads: field_info.has_payloads(),
+            },
+        );
+    }
+    let _index_len = input.read_i64()?;
+    let _terms_len = input.read_i64()?;
+    Ok(fields)
+}
+
+/// Skips the metadata of the FST index of the terms of a field.
+fn skip_fst_metadata(input: &mut DataInput) -> crate::Result<()> {
+    input.read_header(FST_CODEC, 6..=9)?;
+    // The output of the empty string.
+    if input.read_byte()? == 1 {
+        let len = input.read_vint()? as usize;
+        input.skip(len)?;
+    }
+    let _input_type = input.read_byte()?;
+    let _start_node = input.read_vlong()?;
+    let _num_bytes = input.read_vlong()?;
+    Ok(())
+}
+
+fn read_msb_vlong(input: &mut DataInput) -> crate::Result<u64> {
+    let mut value = 0u64;
+    for _ in 0..10 {
+        let byte = input.read_byte()?;
+        value = (value << 7) | u64::from(byte & 0x7f);
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(input.corruption("Invalid variable length integer"))
+}
+
+enum BlockEntry {
+    Term(LuceneTermInfo),
+    SubBlock(u64),
+}
+
+/// A decoded block of the terms dictionary.
+struct Block {
+    suffixes: Vec<u8>,
+    entries: Vec<(Range<usize>, BlockEntry)>,
+    is_last_in_floor: bool,
+    /// Large blocks are split in floor blocks sharing the same prefix, written one after the
+    /// other.
+    end_offset: u64,
+}
+
+impl Block {
+    fn suffix(&self, entry_ord: usize) -> &[u8] {
+        &self.suffixes[self.entries[entry_ord].0.clone()]
+    }
+}
+
+impl FieldTerms {
+    /// Decodes the block starting at `offset` in the terms file, as Lucene's
+    /// `SegmentTermsEnumFrame.loadBlock()`.
+    fn read_block(&self, offset: u64) -> crate::Result<Block> {
+        let mut input = self.terms_file.input_at(offset)?;
+        let code = input.read_vint()?;
+        let num_entries = code >> 1;
+        let is_last_in_floor = code & 1 != 0;
+
+        let code = input.read_vlong()?;
+        let is_leaf_block = code & 0x04 != 0;
+        let num_suffix_bytes = (code >> 3) as usize;
+        let suffixes = match code & 0x03 {
+            0 => input.read_bytes(num_suffix_bytes)?.to_vec(),
+            1 => lowercase_ascii_decompress(&mut input, num_suffix_bytes)?,
+            2 => {
+                let mut suffixes = Vec::new();
+                lz4_decompress(&mut input, num_suffix_bytes, &mut suffixes)?;
+                suffixes
+            }
+            _ => return Err(input.corruption("Invalid compression of the suffixes")),
+        };
+        let code = input.read_vint()? as usize;
+        let num_suffix_length_bytes = code >> 1;
+        let suffix_lengths = if code & 1 != 0 {
+            vec![input.read_byte()?; num_suffix_length_bytes]
+        } else {
+            input.read_bytes(num_suffix_length_bytes)?.to_vec()
+        };
+        let num_stats_bytes = input.read_vint()? as usize;
+        let stats = input.read_bytes(num_stats_bytes)?;
+        let num_metadata_bytes = input.read_vint()? as usize;
+        let metadata = input.read_bytes(num_metadata_bytes)?;
+        let end_offset = input.offset() as u64;
+
+        let path = self.terms_file.path();
+        let mut suffix_lengths = DataInput::new(path, &suffix_lengths);
+        let mut stats = DataInput::new(path, stats);
+        let mut metadata = DataInput::new(path, metadata);
+        let mut singleton_run_len = 0;
+        // The metadata of the terms of a block are delta encoded.
+        let mut term_info = LuceneTermInfo::default();
+        let mut suffix_start = 0;
+        let mut entries = Vec::with_capacity(num_entries as usize);
+        for _ in 0..num_entries {
+            let (suffix_len, is_sub_block) = if is_leaf_block {
+                (suffix_lengths.read_vint()? as usize, false)
+            } else {
+                let code = suffix_lengths.read_vint()? as usize;
+                (code >> 1, code & 1 != 0)
+            };
+            let suffix = suffix_start..suffix_start + suffix_len;
+            if suffix.end > suffixes.len() {
+                return Err(input.corruption("Invalid suffix length"));
+            }
+            suffix_start = suffix.end;
+            if is_sub_block {
+                let sub_block_offset = offset
+                    .checked_sub(suffix_lengths.read_vlong()?)
+                    .ok_or_else(|| input.corruption("Invalid sub-block offset"))?;
+                entries.push((suffix, BlockEntry::SubBlock(sub_block_offset)));
+                continue;
+            }
+            if singleton_run_len > 0 {
+                singleton_run_len -= 1;
+                term_info.doc_freq = 1;
+                term_info.total_term_freq = 1;
+            } else {
+                let token = stats.read_vint()?;
+                if token & 1 != 0 {
+                    // A run of terms present once in a single document.
+                    singleton_run_len = token >> 1;
+                    term_info.doc_freq = 1;
+                    term_info.total_term_freq = 1;
+                } else {
+                    term_info.doc_freq = token >> 1;
+                    term_info.total_term_freq = u64::from(term_info.doc_freq);
+                    if self.index_options.has_freqs() {
+                        term_info.total_term_freq += stats.read_vlong()?;
+                    }
+                }
+            }
+            self.decode_term_metadata(&mut metadata, &mut term_info)?;
+            entries.push((suffix, BlockEntry::Term(term_info.clone())));
+        }
+        Ok(Block {
+            suffixes,
+            entries,
+            is_last_in_floor,
+            end_offset,
+        })
+    }
+
+    /// Decodes the metadata of a term, delta encoded from the previous term of its block, as
+    /// Lucene's `Lucene90PostingsReader.decodeTerm()`.
+    fn decode_term_metadata(
+        &self,
+        input: &mut DataInput,
+        term_info: &mut LuceneTermInfo,
+    ) -> crate::Result<()> {
+        let code = input.read_vlong()?;
+        if code & 1 == 0 {
+            term_info.doc_start_offset += code >> 1;
+            term_info.singleton_doc = if term_info.doc_freq == 1 {
+                Some(input.read_vint()?)
+            } else {
+                None
+            };
+        } else {
+            // The doc id of a singleton term, delta encoded from the previous singleton term.
+            let delta = (code >> 1) as i64;
+            let delta = (delta >> 1) ^ -(delta & 1);
+            term_info.singleton_doc = term_info
+                .singleton_doc
+                .filter(|_| term_info.doc_freq == 1)
+                .and_then(|doc| DocId::try_from(i64::from(doc) + delta).ok());
+            if term_info.singleton_doc.is_none() {
+                return Err(input.corruption("Invalid singleton doc id"));
+            }
+        }
+        if self.index_options.has_positions() {
+            term_info.pos_start_offset += input.read_vlong()?;
+            if self.index_options.has_offsets() || self.has_payloads {
+                term_info.pay_start_offset += input.read_vlong()?;
+            }
+            term_info.last_pos_block_offset = if term_info.total_term_freq > BLOCK_SIZE as u64 {
+                Some(input.read_vlong()?)
+            } else {
+                None
+            };
+        }
+        if term_info.doc_freq as usize > BLOCK_SIZE {
+            let _skip_offset = input.read_vlong()?;
+        }
+        Ok(())
+    }
+
+    /// Returns the term info of a term, if the field contains it.
+    pub fn get(&self, term: &[u8]) -> crate::Result<Option<LuceneTermInfo>> {
+        let mut block_offset = self.root_block_offset;
+        let mut prefix_len = 0;
+        'blocks: loop {
+            let block = self.read_block(block_offset)?;
+            let remaining = &term[prefix_len..];
+            for (entry_ord, (_, entry)) in block.entries.iter().enumerate() {
+                let suffix = block.suffix(entry_ord);
+                match entry {
+                    BlockEntry::Term(term_info) => {
+                        if suffix == remaining {
+                            return Ok(Some(term_info.clone()));
+                        }
+                    }
+                    // All the terms starting with the prefix of a sub-block are in the
+                    // sub-block.
+                    BlockEntry::SubBlock(sub_block_offset) => {
+                        if remaining.starts_with(suffix) {
+                            block_offset = *sub_block_offset;
+                            prefix_len += suffix.len();
+                            continue 'blocks;
+                        }
+                    }
+                }
+            }
+            if block.is_last_in_floor {
+                return Ok(None);
+            }
+            block_offset = block.end_offset;
+        }
+    }
+
+    /// Returns a streamer over the terms of the field, in increasing order.
+    pub fn stream(&self) -> LuceneTermStreamer<'_> {
+        LuceneTermStreamer {
+            terms: self,
+            stack: Vec::new(),
+            key: Vec::new(),
+            term_info: LuceneTermInfo::default(),
+            started: false,
+        }
+    }
+}
+
+/// A block being visited by a `LuceneTermStreamer`.
+struct Frame {
+    block: Block,
+    next_entry_ord: usize,
+    prefix_len: usize,
+}
+
+/// Streams the terms of a field of a Lucene segment, in increasing order, as tantivy's
+/// `TermStreamer`.
+pub struct LuceneTermStreamer<'a> {
+    terms: &'a FieldTerms,
+    stack: Vec<Frame>,
+    key: Vec<u8>,
+    term_info: LuceneTermInfo,
+    started: bool,
+}
+
+impl<'a> LuceneTermStreamer<'a> {
+    /// Advances to the next term, returning false once all the terms were visited.
+    pub fn advance(&mut self) -> crate::Result<bool> {
+        if !self.started {
+            self.started = true;
+            let block = self.terms.read_block(self.terms.root_block_offset)?;
+            self.stack.push(Frame {
+                block,
+                next_entry_ord: 0,
+                prefix_len: 0,
+            });
+        }
+        while let Some(frame) = self.stack.last_mut() {
+            if frame.next_entry_ord == frame.block.entries.len() {
+                if frame.block.is_last_in_floor {
+                    self.stack.pop();
+                } else {
+                    frame.block = self.terms.read_block(frame.block.end_offset)?;
+                    frame.next_entry_ord = 0;
+                }
+                continue;
+            }
+            let entry_ord = frame.next_entry_ord;
+            frame.next_entry_ord += 1;
+            self.key.truncate(frame.prefix_len);
+            self.key.extend_from_slice(frame.block.suffix(entry_ord));
+            match &frame.block.entries[entry_ord].1 {
+                BlockEntry::Term(term_info) => {
+                    self.term_info = term_info.clone();
+                    return Ok(true);
+                }
+                BlockEntry::SubBlock(sub_block_offset) => {
+                    let block = self.terms.read_block(*sub_block_offset)?;
+                    let prefix_len = self.key.len();
+                    self.stack.push(Frame {
+                        block,
+                        next_entry_ord: 0,
+                        prefix_len,
+                    });
+                }
+            }
+        }
+        self.key.clear();
+        Ok(false)
+    }
+
+    /// Returns the current term.
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+
+    /// Returns the term info of the current term.
+    pub fn value(&self) -> &LuceneTermInfo {
+        &self.term_info
+    }
+}
+
+#[cfg(all(test, feature = "mmap"))]
+pub(crate) mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use common::OwnedBytes;
+
+    use super::super::compression::tests::{lowercase_ascii_compress, lz4_literals};
+    use super::super::data_input::tests::DataOutput;
+    use super::super::field_infos::tests::{write_field_infos, TestField};
+    use super::super::field_infos::{read_field_infos, LuceneDocValuesType, LuceneIndexOptions};
+    use super::super::postings::PostingsFormat;
+    use super::super::segment_files::SegmentFiles;
+    use super::{read_block_tree_terms, LuceneTermInfo};
+    use crate::lucene::data_input::LuceneFile;
+
+    /// An entry of a block of a test terms dictionary.
+    pub enum TestEntry {
+        Term(Vec<u8>, LuceneTermInfo),
+        /// A sub-block, split in floor blocks.
+        SubBlock(Vec<u8>, Vec<Vec<TestEntry>>),
+    }
+
+    /// How the suffixes of the blocks are compressed.
+    #[derive(Clone, Copy)]
+    pub enum TestCompression {
+        None,
+        LowercaseAscii,
+        Lz4,
+    }
+
+    /// Writes the floor blocks of a prefix, after their sub-blocks, like the
+    /// `Lucene90BlockTreeTermsWriter`, and returns the offset of the first floor block.
+    fn write_blocks(
+        output: &mut DataOutput,
+        floor_blocks: &[Vec<TestEntry>],
+        compression: TestCompression,
+        field: &TestField,
+    ) -> u64 {
+        let mut sub_block_offsets = HashMap::new();
+        for entries in floor_blocks {
+            for entry in entries {
+                if let TestEntry::SubBlock(suffix, sub_blocks) = entry {
+                    let offset = write_blocks(output, sub_blocks, compression, field);
+                    sub_block_offsets.insert(suffix.clone(), offset);
+                }
+            }
+        }
+        let first_offset = output.data.len() as u64;
+        for (floor_ord, entries) in floor_blocks.iter().enumerate() {
+            let offset = output.data.len() as u64;
+            let is_leaf = entries
+                .iter()
+                .all(|entry| matches!(entry, TestEntry::Term(..)));
+            let mut suffixes = Vec::new();
+            let mut suffix_lengths = DataOutput::default();
+            let mut stats = DataOutput::default();
+            let mut metadata = DataOutput::default();
+            let mut previous = LuceneTermInfo::default();
+            let mut singleton_run_len = 0;
+            for entry in entries {
+                match entry {
+                    TestEntry::Term(suffix, term_info) => {
+                        suffixes.extend_from_slice(suffix);
+                        let len = suffix.len() as u64;
+                        suffix_lengths.write_vlong(if is_leaf { len } else { len << 1 });
+                        if term_info.doc_freq == 1 && term_info.total_term_freq == 1 {
+                            singleton_run_len += 1;
+                        } else {
+                            write_singleton_run(&mut stats, &mut singleton_run_len);
+                            stats.write_vlong(u64::from(term_info.doc_freq) << 1);
+                            if field.index_options.has_freqs() {
+                                stats.write_vlong(
+                                    term_info.total_term_freq - u64::from(term_info.doc_freq),
+                                );
+                            }
+                        }
+                        match (term_info.singleton_doc, previous.singleton_doc) {
+                            (Some(doc), Some(previous_doc))
+                                if term_info.doc_start_offset == previous.doc_start_offset =>
+                            {
+                                let delta = i64::from(doc) - i64::from(previous_doc);
+                                let zig_zag = ((delta << 1) ^ (delta >> 63)) as u64;
+                                metadata.write_vlong((zig_zag << 1) | 1);
+                            }
+                            _ => {
+                                let delta = term_info.doc_start_offset - previous.doc_start_offset;
+                                metadata.write_vlong(delta << 1);
+                                if let Some(doc) = term_info.singleton_doc {
+                                    metadata.write_vlong(u64::from(doc));
+                                }
+                            }
+                        }
+                        if field.index_options.has_positions() {
+                            metadata.write_vlong(
+                                term_info.pos_start_offset - previous.pos_start_offset,
+                            );
+                            if field.index_options.has_offsets() || field.has_payloads {
+                                metadata.write_vlong(
+                                    term_info.pay_start_offset - previous.pay_start_offset,
+                                );
+                            }
+                            if let Some(offset) = term_info.last_pos_block_offset {
+                                metadata.write_vlong(offset);
+                            }
+                        }
+                        if term_info.doc_freq > 128 {
+                            // The offset of the skip data, which is not read.
+                            metadata.write_vlong(0);
+                        }
+                        previous = term_info.clone();
+                    }
+                    TestEntry::SubBlock(suffix, _) => {
+                        suffixes.extend_from_slice(suffix);
+                        suffix_lengths.write_vlong(((suffix.len() as u64) << 1) | 1);
+                        suffix_lengths.write_vlong(offset - sub_block_offsets[suffix]);
+                    }
+                }
+            }
+            write_singleton_run(&mut stats, &mut singleton_run_len);
+            let is_last_in_floor = floor_ord + 1 == floor_blocks.len();
+            output.write_vlong(((entries.len() as u64) << 1) | is_last_in_floor as u64);
+            let (compression_code, compressed) = match compression {
+                TestCompression::None => (0, suffixes.clone()),
+                TestCompression::LowercaseAscii => (1, lowercase_ascii_compress(&suffixes)),
+                TestCompression::Lz4 => (2, lz4_literals(&suffixes)),
+            };
+            output.write_vlong(
+                ((suffixes.len() as u64) << 3) | ((is_leaf as u64) << 2) | compression_code,
+            );
+            output.data.extend_from_slice(&compressed);
+            output.write_vlong((suffix_lengths.data.len() as u64) << 1);
+            output.data.extend_from_slice(&suffix_lengths.data);
+            output.write_vlong(stats.data.len() as u64);
+            output.data.extend_from_slice(&stats.data);
+            output.write_vlong(metadata.data.len() as u64);
+            output.data.extend_from_slice(&metadata.data);
+        }
+        first_offset
+    }
+
+    fn write_singleton_run(stats: &mut DataOutput, singleton_run_len: &mut u64) {
+        if *singleton_run_len > 0 {
+            stats.write_vlong(((*singleton_run_len - 1) << 1) | 1);
+            *singleton_run_len = 0;
+        }
+    }
+
+    /// Writes the `.tim` and `.tmd` files of the terms of fields, given by field number and
+    /// root block entries, and returns their contents.
+    pub fn write_block_tree_terms(
+        segment_id: [u8; 16],
+        suffix: &str,
+        fields: &[(u32, &TestField, Vec<Vec<TestEntry>>)],
+        compression: TestCompression,
+        version: u32,
+    ) -> (Vec<u8>, Vec<u8>) {
+        let mut terms = DataOutput::default();
+        terms.write_index_header("BlockTreeTermsDict", version, segment_id, suffix);
+        let mut meta = DataOutput::default();
+        meta.write_index_header("BlockTreeTermsMeta", version, segment_id, suffix);
+        let postings_format = &suffix[..suffix.find('_').unwrap()];
+        meta.write_index_header(
+            &format!("{postings_format}PostingsWriterTerms"),
+            0,
+            segment_id,
+            suffix,
+        );
+        meta.write_vlong(128);
+        meta.write_vlong(fields.len() as u64);
+        for (number, field, root_blocks) in fields {
+            let root_offset = write_blocks(&mut terms, root_blocks, compression, field);
+            meta.write_vlong(u64::from(*number));
+            meta.write_vlong(42);
+            let mut root_code = DataOutput::default();
+            let code = (root_offset << 2) | 0b10 | (root_blocks.len() > 1) as u64;
+            if version >= 1 {
+                let mut bytes = Vec::new();
+                let mut value = code;
+                loop {
+                    bytes.push((value & 0x7f) as u8);
+                    value >>= 7;
+                    if value == 0 {
+                        break;
+                    }
+                }
+                bytes.reverse();
+                let last = bytes.len() - 1;
+                for byte in &mut bytes[..last] {
+                    *byte |= 0x80;
+                }
+                root_code.data = bytes;
+            } else {
+                root_code.write_vlong(code);
+            }
+            // The floor data of the root code is not read.
+            root_code.data.extend_from_slice(&[1, b'm', 3]);
+            meta.write_vlong(root_code.data.len() as u64);
+            meta.data.extend_from_slice(&root_code.data);
+            meta.write_vlong(100);
+            if field.index_options.has_freqs() {
+                meta.write_vlong(50);
+            }
+            meta.write_vlong(7);
+            meta.write_string("a");
+            meta.write_string("z");
+            meta.write_vlong(0);
+            // The metadata of the FST index, with an output for the empty string.
+            meta.data.extend_from_slice(&0x3fd7_6c17u32.to_be_bytes());
+            meta.write_string("FST");
+            meta.data.extend_from_slice(&8u32.to_be_bytes());
+            meta.data.push(1);
+            meta.write_vlong(2);
+            meta.data.extend_from_slice(&[0, 0]);
+            meta.data.push(2);
+            meta.write_vlong(12);
+            meta.write_vlong(40);
+        }
+        meta.write_i64(0);
+        meta.write_i64(0);
+        (terms.finish(), meta.finish())
+    }
+
+    fn term(suffix: &str, doc_freq: u32, total_term_freq: u64, doc_start_offset: u64) -> TestEntry {
+        let singleton_doc = (doc_freq == 1).then_some(suffix.len() as u32);
+        TestEntry::Term(
+            suffix.as_bytes().to_vec(),
+            LuceneTermInfo {
+                doc_freq,
+                total_term_freq,
+                doc_start_offset,
+                singleton_doc,
+                ..LuceneTermInfo::default()
+            },
+        )
+    }
+
+    fn test_tree() -> Vec<Vec<TestEntry>> {
+        vec![
+            vec![
+                TestEntry::SubBlock(
+                    b"ap".to_vec(),
+                    vec![vec![
+                        term("", 2, 4, 10),
+                        term("ple", 1, 2, 10),
+                        term("ricot", 3, 6, 20),
+                    ]],
+                ),
+                TestEntry::SubBlock(
+                    b"ban".to_vec(),
+                    vec![vec![
+                        term("al", 4, 8, 30),
+                        TestEntry::SubBlock(
+                            b"d".to_vec(),
+                            vec![
+                                vec![term("", 1, 1, 40), term("ana", 2, 4, 40)],
+                                vec![term("s", 5, 10, 50)],
+                            ],
+                        ),
+                    ]],
+                ),
+                term("cherry", 200, 400, 60),
+            ],
+            vec![
+                term("mango", 1, 1, 70),
+                term("melon", 1, 1, 70),
+                term("mint", 1, 1, 70),
+            ],
+        ]
+    }
+
+    #[test]
+    fn test_block_tree_terms() -> crate::Result<()> {
+        let field = TestField {
+            name: "fruit",
+            index_options: LuceneIndexOptions::DocsAndFreqs,
+            has_payloads: false,
+            doc_values_type: LuceneDocValuesType::None,
+            is_soft_deletes_field: false,
+        };
+        let segment_id = [3u8; 16];
+        let expected_terms = [
+            "ap", "apple", "apricot", "banal", "band", "bandana", "bands", "cherry", "mango",
+            "melon", "mint",
+        ];
+        for (compression, version) in [
+            (TestCompression::None, 0),
+            (TestCompression::LowercaseAscii, 1),
+            (TestCompression::Lz4, 1),
+        ] {
+            let tempdir = tempfile::TempDir::new().unwrap();
+            let field_infos = write_field_infos(segment_id, &[field], "Lucene90");
+            let (terms, meta) = write_block_tree_terms(
+                segment_id,
+                "Lucene90_0",
+                &[(0, &field, test_tree())],
+                compression,
+                version,
+            );
+            std::fs::write(tempdir.path().join("_0_Lucene90_0.tim"), terms).unwrap();
+            std::fs::write(tempdir.path().join("_0_Lucene90_0.tmd"), meta).unwrap();
+            let files = SegmentFiles::open(tempdir.path(), "_0", segment_id, false)?;
+            let field_infos = read_field_infos(
+                &LuceneFile::open(PathBuf::from("_0.fnm"), OwnedBytes::new(field_infos))?,
+                segment_id,
+                "",
+            )?;
+            let fields = read_block_tree_terms(
+                &files,
+                segment_id,
+                "Lucene90_0",
+                PostingsFormat::Lucene90,
+                &field_infos,
+            )?;
+            let terms = &fields[&0];
+            assert_eq!((terms.num_terms, terms.sum_total_term_freq), (42, 100));
+            assert_eq!((terms.sum_doc_freq, terms.doc_count), (50, 7));
+
+            let mut streamer = terms.stream();
+            let mut streamed_terms = Vec::new();
+            while streamer.advance()? {
+                let key = String::from_utf8(streamer.key().to_vec()).unwrap();
+                assert_eq!(terms.get(streamer.key())?.as_ref(), Some(streamer.value()));
+                streamed_terms.push(key);
+            }
+            assert_eq!(streamed_terms, expected_terms);
+            assert!(!streamer.advance()?);
+
+            let bandana = terms.get(b"bandana")?.unwrap();
+            assert_eq!((bandana.doc_freq, bandana.total_term_freq), (2, 4));
+            assert_eq!(bandana.doc_start_offset, 40);
+            let apple = terms.get(b"apple")?.unwrap();
+            assert_eq!((apple.doc_freq, apple.singleton_doc), (1, Some(3)));
+            let mint = terms.get(b"mint")?.unwrap();
+            assert_eq!((mint.total_term_freq, mint.singleton_doc), (1, Some(4)));
+            for missing in [
+                "", "a", "apples", "ban", "bandanas", "cherr", "zebra", "mel",
+            ] {
+                assert!(terms.get(missing.as_bytes())?.is_none(), "{missing}");
+            }
+        }
+        Ok(())
+    }
+}
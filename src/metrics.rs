@@ -0,0 +1,205 @@
+//! Observability hooks on the indexing and search operations.
+//!
+//! A [`MetricsRecorder`] set with
+//! [`Index::set_metrics_recorder()`](crate::Index::set_metrics_recorder) is called with the
+//! sizes and the durations of the main operations of the writers, readers and searchers of the
+//! index, e.g. to feed Prometheus histograms.
+//!
+//! With the `tracing` feature, these operations are also wrapped in `tracing` spans, at the
+//! `INFO` level, named after the operation: `tantivy.add_documents`, `tantivy.flush_segment`,
+//! `tantivy.commit`, `tantivy.merge`, `tantivy.reload_reader` and `tantivy.search`.
+//!
+//! ```rust
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! use tantivy::metrics::MetricsRecorder;
+//! use tantivy::schema::{Schema, TEXT};
+//! use tantivy::{doc, Index, Opstamp};
+//!
+//! #[derive(Default)]
+//! struct CommitCounter(AtomicUsize);
+//!
+//! impl MetricsRecorder for CommitCounter {
+//!     fn record_commit(&self, _opstamp: Opstamp, _elapsed: Duration) {
+//!         self.0.fetch_add(1, Ordering::Relaxed);
+//!     }
+//! }
+//!
+//! # fn main() -> tantivy::Result<()> {
+//! let mut schema_builder = Schema::builder();
+//! let title = schema_builder.add_text_field("title", TEXT);
+//! let mut index = Index::create_in_ram(schema_builder.build());
+//! let commit_counter = Arc::new(CommitCounter::default());
+//! index.set_metrics_recorder(commit_counter.clone());
+//! let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+//! index_writer.add_document(doc!(title => "The Old Man and the Sea"))?;
+//! index_writer.commit()?;
+//! assert_eq!(commit_counter.0.load(Ordering::Relaxed), 1);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::time::Duration;
+
+use crate::Opstamp;
+
+/// The phases of a search, see [`MetricsRecorder::record_query_phase()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum QueryPhase {
+    /// Building the [`Weight`](crate::query::Weight) of the query, which reads the statistics
+    /// of its terms.
+    Weight,
+    /// Collecting the matching documents of all of the segments, and merging the fruits of
+    /// the segments.
+    Collect,
+}
+
+/// Receives the metrics of the operations of an index.
+///
+/// All of the methods do nothing by default, so that implementations only override the
+/// metrics they are interested in. They are called on the threads running the operations,
+/// including the indexing and merging threads, and should therefore return quickly.
+pub trait MetricsRecorder: Send + Sync + 'static {
+    /// Called when a batch of documents is sent to the indexing workers, by
+    /// [`IndexWriter::add_document()`](crate::IndexWriter::add_document) or
+    /// [`IndexWriter::run()`](crate::IndexWriter::run).
+    fn record_add_documents(&self, _num_docs: usize) {}
+
+    /// Called when an indexing worker has flushed a new segment of `num_docs` documents.
+    fn record_segment_flush(&self, _num_docs: u32, _elapsed: Duration) {}
+
+    /// Called when a commit is persisted.
+    fn record_commit(&self, _opstamp: Opstamp, _elapsed: Duration) {}
+
+    /// Called when `num_segments` segments have been merged into a segment of `num_docs`
+    /// documents.
+    fn record_merge(&self, _num_segments: usize, _num_docs: u32, _elapsed: Duration) {}
+
+    /// Called when an [`IndexReader`](crate::IndexReader) has loaded a new searcher of
+    /// `num_segments` segments.
+    fn record_reader_reload(&self, _num_segments: usize, _elapsed: Duration) {}
+
+    /// Called at the end of each phase of a search.
+    fn record_query_phase(&self, _phase: QueryPhase, _elapsed: Duration) {}
+}
+
+/// Enters an `INFO` span with the given name and fields when the `tracing` feature is enabled,
+/// and does nothing otherwise. The span is exited when the returned guard is dropped.
+macro_rules! enter_span {
+    ($name:literal $(, $field:ident)* $(,)?) => {{
+        #[cfg(feature = "tracing")]
+        let span_guard = tracing::info_span!($name $(, $field)*).entered();
+        #[cfg(not(feature = "tracing"))]
+        let span_guard = {
+            $(let _ = &$field;)*
+            $crate::metrics::NoSpanGuard
+        };
+        span_guard
+    }};
+}
+
+pub(crate) use enter_span;
+
+/// The span guard when the `tracing` feature is disabled.
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct NoSpanGuard;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use super::{MetricsRecorder, QueryPhase};
+    use crate::collector::Count;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, TEXT};
+    use crate::{doc, Index, Opstamp, ReloadPolicy};
+
+    #[derive(Default)]
+    struct EventRecorder(Mutex<Vec<String>>);
+
+    impl EventRecorder {
+        fn take_events(&self) -> Vec<String> {
+            std::mem::take(&mut *self.0.lock().unwrap())
+        }
+
+        fn push(&self, event: String) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    impl MetricsRecorder for EventRecorder {
+        fn record_add_documents(&self, num_docs: usize) {
+            self.push(format!("add_documents {num_docs}"));
+        }
+
+        fn record_segment_flush(&self, num_docs: u32, _elapsed: Duration) {
+            self.push(format!("segment_flush {num_docs}"));
+        }
+
+        fn record_commit(&self, opstamp: Opstamp, _elapsed: Duration) {
+            self.push(format!("commit {opstamp}"));
+        }
+
+        fn record_merge(&self, num_segments: usize, num_docs: u32, _elapsed: Duration) {
+            self.push(format!("merge {num_segments} {num_docs}"));
+        }
+
+        fn record_reader_reload(&self, num_segments: usize, _elapsed: Duration) {
+            self.push(format!("reader_reload {num_segments}"));
+        }
+
+        fn record_query_phase(&self, phase: QueryPhase, _elapsed: Duration) {
+            self.push(format!("query_phase {phase:?}"));
+        }
+    }
+
+    #[test]
+    fn test_metrics_recorder() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let mut index = Index::create_in_ram(schema_builder.build());
+        let event_recorder = Arc::new(EventRecorder::default());
+        index.set_metrics_recorder(event_recorder.clone());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "a"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(title => "b"))?;
+        index_writer.add_document(doc!(title => "c"))?;
+        index_writer.commit()?;
+        assert_eq!(
+            event_recorder.take_events(),
+            vec![
+                "add_documents 1",
+                "segment_flush 1",
+                "commit 2",
+                "add_documents 1",
+                "add_documents 1",
+                "segment_flush 2",
+                "commit 7",
+            ]
+        );
+
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        assert_eq!(event_recorder.take_events(), vec!["merge 2 3"]);
+
+        reader.reload()?;
+        assert_eq!(reader.searcher().search(&AllQuery, &Count)?, 3);
+        assert_eq!(
+            event_recorder.take_events(),
+            vec![
+                "reader_reload 1",
+                "query_phase Weight",
+                "query_phase Collect"
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,242 @@
+//! Payloads are arbitrary bytes attached to the tokens of a text field during analysis,
+//! e.g. the confidence of a tagger for each token, which can then influence the scoring of
+//! the documents.
+//!
+//! They are only stored for text fields configured with
+//! [`TextFieldIndexing::set_payloads`](crate::schema::TextFieldIndexing::set_payloads),
+//! from the [`Token::payload`](crate::tokenizer::Token::payload) of the tokens, and can be
+//! read using [`SegmentReader::payloads`](crate::SegmentReader::payloads). Only the tokens
+//! with a non-empty payload are recorded.
+//!
+//! The payloads of a document are stored next to the postings, grouped by term, so that a
+//! scorer iterating over the postings of a term can look up the payloads of the term in the
+//! current document.
+//!
+//! Positions are the same as the positions recorded in the inverted index.
+mod reader;
+mod serializer;
+mod writer;
+
+use common::{read_u32_vint, write_u32_vint};
+
+pub use self::reader::{PayloadReader, PayloadReaders};
+pub use self::serializer::PayloadsSerializer;
+pub use self::writer::PayloadsWriter;
+
+/// The payloads of a term in a document, with the positions of the tokens they are attached
+/// to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TermPayloads {
+    text: String,
+    positions: Vec<u32>,
+    payloads: Vec<Vec<u8>>,
+}
+
+impl TermPayloads {
+    /// Returns the text of the term.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the positions of the occurrences of the term with a payload, in ascending
+    /// order.
+    pub fn positions(&self) -> &[u32] {
+        &self.positions
+    }
+
+    /// Returns the payloads of the occurrences of the term, in the same order as the
+    /// positions.
+    pub fn payloads(&self) -> &[Vec<u8>] {
+        &self.payloads
+    }
+}
+
+/// The payloads of a field of a document, grouped by term and sorted by the text of the terms.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DocPayloads {
+    terms: Vec<TermPayloads>,
+}
+
+impl DocPayloads {
+    /// Returns the payloads of each term, sorted by the text of the terms.
+    pub fn terms(&self) -> &[TermPayloads] {
+        &self.terms
+    }
+
+    /// Returns the payloads of the term with the given text, if it has payloads in the
+    /// document.
+    pub fn get(&self, text: &str) -> Option<&TermPayloads> {
+        self.terms
+            .binary_search_by(|term| term.text.as_str().cmp(text))
+            .ok()
+            .map(|ord| &self.terms[ord])
+    }
+
+    /// Returns true if none of the tokens of the field have a payload in the document.
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Serializes the payloads.
+    ///
+    /// The terms are encoded as their length and bytes, followed by their number of payloads
+    /// and, for each payload, the delta-encoded position and the length and bytes of the
+    /// payload.
+    fn serialize(&self, output: &mut Vec<u8>) {
+        if self.terms.is_empty() {
+            return;
+        }
+        write_vint(self.terms.len() as u32, output);
+        for term in &self.terms {
+            write_vint(term.text.len() as u32, output);
+            output.extend_from_slice(term.text.as_bytes());
+            write_vint(term.positions.len() as u32, output);
+            let mut previous_position = 0u32;
+            for (&position, payload) in term.positions.iter().zip(&term.payloads) {
+                write_vint(position - previous_position, output);
+                write_vint(payload.len() as u32, output);
+                output.extend_from_slice(payload);
+                previous_position = position;
+            }
+        }
+    }
+
+    /// Deserializes payloads serialized with [`DocPayloads::serialize`].
+    fn deserialize(mut data: &[u8]) -> crate::Result<DocPayloads> {
+        if data.is_empty() {
+            return Ok(DocPayloads::default());
+        }
+        let num_terms = read_u32_vint(&mut data) as usize;
+        let mut terms = Vec::with_capacity(num_terms);
+        for _ in 0..num_terms {
+            let text_len = read_u32_vint(&mut data) as usize;
+            let (text_bytes, rest) = data.split_at(text_len);
+            data = rest;
+            let text = String::from_utf8(text_bytes.to_vec()).map_err(|_| {
+                crate::TantivyError::DataCorruption(crate::error::DataCorruption::comment_only(
+                    "Payloads contain a term that is not valid UTF-8",
+                ))
+            })?;
+            let num_payloads = read_u32_vint(&mut data) as usize;
+            let mut positions = Vec::with_capacity(num_payloads);
+            let mut payloads = Vec::with_capacity(num_payloads);
+            let mut position = 0u32;
+            for _ in 0..num_payloads {
+                position += read_u32_vint(&mut data);
+                let payload_len = read_u32_vint(&mut data) as usize;
+                let (payload, rest) = data.split_at(payload_len);
+                data = rest;
+                positions.push(position);
+                payloads.push(payload.to_vec());
+            }
+            terms.push(TermPayloads {
+                text,
+                positions,
+                payloads,
+            });
+        }
+        Ok(DocPayloads { terms })
+    }
+}
+
+fn write_vint(val: u32, output: &mut Vec<u8>) {
+    // Writing to a `Vec` cannot fail.
+    write_u32_vint(val, output).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::TopDocs;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions, TEXT};
+    use crate::tokenizer::{
+        DelimitedPayloadFilter, LowerCaser, PayloadEncoding, TextAnalyzer, WhitespaceTokenizer,
+    };
+    use crate::{DocAddress, DocId, Index, Score, SegmentReader, Term};
+
+    #[test]
+    fn test_payloads() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("tagged")
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+                .set_payloads(true),
+        );
+        let tags = schema_builder.add_text_field("tags", text_options);
+        let title = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        index.tokenizers().register(
+            "tagged",
+            TextAnalyzer::builder(WhitespaceTokenizer)
+                .filter(DelimitedPayloadFilter::new('|', PayloadEncoding::F32))
+                .filter(LowerCaser)
+                .build(),
+        );
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc!(tags => "Cat|0.25 dog cat|0.5"))?;
+            index_writer.add_document(doc!(tags => "cat|1.5", title => "no payload"))?;
+            index_writer.commit()?;
+            index_writer.add_document(doc!(tags => "dog|1.0"))?;
+            index_writer.commit()?;
+        }
+        let check = |index: &Index, num_segments: usize| -> crate::Result<()> {
+            let searcher = index.reader()?.searcher();
+            assert_eq!(searcher.segment_readers().len(), num_segments);
+            let segment_reader = searcher.segment_reader(0);
+            assert!(segment_reader.payloads(0, title).is_err());
+
+            let payloads = segment_reader.payloads(0, tags)?;
+            assert_eq!(payloads.terms().len(), 1);
+            let cat = payloads.get("cat").unwrap();
+            assert_eq!(cat.positions(), &[0, 2]);
+            assert_eq!(
+                cat.payloads(),
+                &[
+                    0.25f32.to_le_bytes().to_vec(),
+                    0.5f32.to_le_bytes().to_vec()
+                ]
+            );
+            assert!(payloads.get("dog").is_none());
+            Ok(())
+        };
+        check(&index, 2)?;
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            let segment_ids = index.searchable_segment_ids()?;
+            index_writer.merge(&segment_ids).wait()?;
+            index_writer.wait_merging_threads()?;
+        }
+        check(&index, 1)?;
+
+        // Scoring the documents matching `cat` with the sum of the payloads of `cat`.
+        let searcher = index.reader()?.searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(tags, "cat"),
+            IndexRecordOption::WithFreqsAndPositions,
+        );
+        let collector =
+            TopDocs::with_limit(10).tweak_score(move |segment_reader: &SegmentReader| {
+                let segment_reader = segment_reader.clone();
+                move |doc: DocId, _score: Score| {
+                    let payloads = segment_reader.payloads(doc, tags).unwrap();
+                    payloads
+                        .get("cat")
+                        .map(|cat| {
+                            cat.payloads()
+                                .iter()
+                                .map(|payload| f32::from_le_bytes(payload[..].try_into().unwrap()))
+                                .sum::<f32>()
+                        })
+                        .unwrap_or(0.0)
+                }
+            });
+        let top_docs = searcher.search(&query, &collector)?;
+        assert_eq!(
+            top_docs,
+            vec![(1.5, DocAddress::new(0, 1)), (0.75, DocAddress::new(0, 0))]
+        );
+        Ok(())
+    }
+}
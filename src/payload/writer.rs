@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+use std::{io, iter};
+
+use super::{DocPayloads, PayloadsSerializer, TermPayloads};
+use crate::indexer::doc_id_mapping::DocIdMapping;
+use crate::schema::{Field, Schema};
+use crate::tokenizer::Token;
+use crate::DocId;
+
+/// The `PayloadsWriter` is in charge of buffering the serialized payloads
+/// of each document for each field storing payloads.
+pub struct PayloadsWriter {
+    payloads_buffers: Vec<Option<PayloadsBuffer>>,
+}
+
+#[derive(Default)]
+struct PayloadsBuffer {
+    // Start offset of the payloads of each document in `data`.
+    doc_offsets: Vec<usize>,
+    data: Vec<u8>,
+}
+
+impl PayloadsBuffer {
+    fn doc_data(&self, doc: DocId) -> &[u8] {
+        let start = self.doc_offsets[doc as usize];
+        let end = self
+            .doc_offsets
+            .get(doc as usize + 1)
+            .copied()
+            .unwrap_or(self.data.len());
+        &self.data[start..end]
+    }
+}
+
+impl PayloadsWriter {
+    /// Initialize with state for tracking the fields storing payloads
+    /// specified in the schema.
+    pub fn for_schema(schema: &Schema) -> PayloadsWriter {
+        let mut payloads_buffers: Vec<Option<PayloadsBuffer>> = iter::repeat_with(|| None)
+            .take(schema.num_fields())
+            .collect();
+        for (field, field_entry) in schema.fields() {
+            if field_entry.has_payloads() {
+                payloads_buffers[field.field_id() as usize] = Some(PayloadsBuffer::default());
+            }
+        }
+        PayloadsWriter { payloads_buffers }
+    }
+
+    /// The memory used inclusive childs
+    pub fn mem_usage(&self) -> usize {
+        self.payloads_buffers
+            .iter()
+            .flatten()
+            .map(|buffer| {
+                buffer.data.capacity()
+                    + buffer.doc_offsets.capacity() * std::mem::size_of::<usize>()
+            })
+            .sum()
+    }
+
+    /// Ensure that all documents in 0..max_doc have payloads associated with them
+    /// in each of the fields.
+    ///
+    /// Documents that have not been seen get empty payloads.
+    pub fn fill_up_to_max_doc(&mut self, max_doc: DocId) {
+        for buffer in self.payloads_buffers.iter_mut().flatten() {
+            buffer
+                .doc_offsets
+                .resize(max_doc as usize, buffer.data.len());
+        }
+    }
+
+    /// Records the payloads of the given document and field, given its tokens.
+    ///
+    /// The positions of the tokens are expected to be relative to the beginning of the field.
+    pub fn record(&mut self, doc: DocId, field: Field, tokens: &[Token]) {
+        let Some(buffer) = self
+            .payloads_buffers
+            .get_mut(field.field_id() as usize)
+            .and_then(Option::as_mut)
+        else {
+            return;
+        };
+        assert!(
+            buffer.doc_offsets.len() <= doc as usize,
+            "Cannot register the payloads of a given document twice"
+        );
+        buffer.doc_offsets.resize(doc as usize, buffer.data.len());
+        buffer.doc_offsets.push(buffer.data.len());
+        let mut terms: BTreeMap<&str, TermPayloads> = BTreeMap::new();
+        for token in tokens.iter().filter(|token| !token.payload.is_empty()) {
+            let term = terms
+                .entry(token.text.as_str())
+                .or_insert_with(|| TermPayloads {
+                    text: token.text.clone(),
+                    positions: Vec::new(),
+                    payloads: Vec::new(),
+                });
+            term.positions.push(token.position as u32);
+            term.payloads.push(token.payload.clone());
+        }
+        let doc_payloads = DocPayloads {
+            terms: terms.into_values().collect(),
+        };
+        doc_payloads.serialize(&mut buffer.data);
+    }
+
+    /// Serialize the payloads of all fields to the serializer.
+    pub fn serialize(
+        &self,
+        mut payloads_serializer: PayloadsSerializer,
+        doc_id_map: Option<&DocIdMapping>,
+    ) -> io::Result<()> {
+        for (field_id, buffer_opt) in self.payloads_buffers.iter().enumerate() {
+            let Some(buffer) = buffer_opt else {
+                continue;
+            };
+            let field = Field::from_field_id(field_id as u32);
+            let num_docs = buffer.doc_offsets.len() as DocId;
+            if let Some(doc_id_map) = doc_id_map {
+                let docs = doc_id_map
+                    .iter_old_doc_ids()
+                    .map(|old_doc| buffer.doc_data(old_doc));
+                payloads_serializer.serialize_field(field, docs)?;
+            } else {
+                let docs = (0..num_docs).map(|doc| buffer.doc_data(doc));
+                payloads_serializer.serialize_field(field, docs)?;
+            }
+        }
+        payloads_serializer.close()?;
+        Ok(())
+    }
+}
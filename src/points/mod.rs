@@ -0,0 +1,73 @@
+//! The points index stores, for each field with points enabled, the
+//! numeric values of the field sorted in ascending order together with
+//! the documents they belong to.
+//!
+//! Values are stored in their `u64` order-preserving representation, so
+//! that every numeric type (`u64`, `i64`, `f64`, `bool` and `DateTime`) can
+//! be handled the same way.
+//!
+//! The sorted values are split into blocks of [`POINTS_BLOCK_LEN`]
+//! points. The maximum value of each block is kept in memory by the
+//! reader, which makes it possible to locate the boundaries of a range
+//! with a two-level binary search, and to then read the matching documents
+//! sequentially.
+//!
+//! This makes range queries on wide ranges of high-cardinality numeric
+//! fields much cheaper than enumerating the terms of the inverted index.
+mod reader;
+mod serializer;
+mod writer;
+
+pub use self::reader::{PointsReader, PointsReaders};
+pub use self::serializer::PointsSerializer;
+pub use self::writer::PointsWriter;
+
+/// Number of points per block.
+pub const POINTS_BLOCK_LEN: usize = 128;
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{PointsReaders, PointsSerializer, PointsWriter};
+    use crate::directory::{Directory, RamDirectory, WritePtr};
+    use crate::schema::{NumericOptions, Schema};
+
+    #[test]
+    fn test_points_serialize_and_read() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let field = schema_builder.add_u64_field("val", NumericOptions::default().set_points());
+        let other = schema_builder.add_u64_field("other", NumericOptions::default());
+        let schema = schema_builder.build();
+
+        let path = Path::new("test_points");
+        let directory = RamDirectory::create();
+        {
+            let write: WritePtr = directory.open_write(path)?;
+            let serializer = PointsSerializer::from_write(write)?;
+            let mut writer = PointsWriter::for_schema(&schema);
+            for doc in 0..1_000u32 {
+                writer.record(doc, field, u64::from((doc * 7) % 1_000));
+                writer.record(doc, other, 1u64);
+            }
+            writer.serialize(serializer, None)?;
+        }
+        let points_readers = PointsReaders::open(directory.open_read(path)?)?;
+        assert!(points_readers.get_field(other)?.is_none());
+        let points_reader = points_readers.get_field(field)?.unwrap();
+        assert_eq!(points_reader.num_points(), 1_000);
+
+        let mut docs: Vec<u32> = points_reader.doc_ids_in_range(100..=299).collect();
+        docs.sort_unstable();
+        let mut expected: Vec<u32> = (0..1_000u32)
+            .filter(|doc| (100..=299).contains(&((doc * 7) % 1_000)))
+            .collect();
+        expected.sort_unstable();
+        assert_eq!(docs, expected);
+
+        assert_eq!(points_reader.doc_ids_in_range(1_000..=u64::MAX).count(), 0);
+        assert_eq!(points_reader.doc_ids_in_range(0..=u64::MAX).count(), 1_000);
+        assert_eq!(points_reader.doc_ids_in_range(999..=999).count(), 1);
+        Ok(())
+    }
+}
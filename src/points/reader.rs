@@ -0,0 +1,154 @@
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+use super::POINTS_BLOCK_LEN;
+use crate::directory::{CompositeFile, FileSlice, OwnedBytes};
+use crate::schema::Field;
+use crate::space_usage::PerFieldSpaceUsage;
+use crate::DocId;
+
+/// Reader for the points of all of the fields with a points index.
+#[derive(Clone)]
+pub struct PointsReaders {
+    data: Arc<CompositeFile>,
+}
+
+impl PointsReaders {
+    /// Creates a points reader.
+    pub fn open(file: FileSlice) -> crate::Result<PointsReaders> {
+        let data = CompositeFile::open(&file)?;
+        Ok(PointsReaders {
+            data: Arc::new(data),
+        })
+    }
+
+    /// Creates a points reader for a segment without any points.
+    pub fn empty() -> PointsReaders {
+        PointsReaders {
+            data: Arc::new(CompositeFile::empty()),
+        }
+    }
+
+    /// Returns the `PointsReader` for a specific field.
+    pub fn get_field(&self, field: Field) -> crate::Result<Option<PointsReader>> {
+        if let Some(file) = self.data.open_read(field) {
+            let points_reader = PointsReader::open(file)?;
+            Ok(Some(points_reader))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Return a break down of the space usage per field.
+    pub fn space_usage(&self) -> PerFieldSpaceUsage {
+        self.data.space_usage()
+    }
+}
+
+/// Reads the points of a given field.
+///
+/// See the [points module](crate::points) for the format.
+#[derive(Clone)]
+pub struct PointsReader {
+    num_points: u32,
+    values: OwnedBytes,
+    doc_ids: OwnedBytes,
+    block_maxs: Arc<[u64]>,
+}
+
+impl PointsReader {
+    /// Opens the points of a field.
+    pub fn open(file: FileSlice) -> crate::Result<PointsReader> {
+        let (header, body) = file.split(4);
+        let header = header.read_bytes()?;
+        let num_points = u32::from_le_bytes(header.as_slice().try_into().unwrap());
+        let num_points_usize = num_points as usize;
+        let (values, body) = body.split(num_points_usize * 8);
+        let (doc_ids, block_maxs) = body.split(num_points_usize * 4);
+        let block_maxs = block_maxs
+            .read_bytes()?
+            .as_slice()
+            .chunks_exact(8)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+            .collect();
+        Ok(PointsReader {
+            num_points,
+            values: values.read_bytes()?,
+            doc_ids: doc_ids.read_bytes()?,
+            block_maxs,
+        })
+    }
+
+    /// Returns the number of points of the field.
+    pub fn num_points(&self) -> u32 {
+        self.num_points
+    }
+
+    /// Returns the value of the point at the given ordinal.
+    #[inline]
+    fn value(&self, ord: usize) -> u64 {
+        let bytes = &self.values.as_slice()[ord * 8..ord * 8 + 8];
+        u64::from_le_bytes(bytes.try_into().unwrap())
+    }
+
+    /// Returns the doc id of the point at the given ordinal.
+    #[inline]
+    fn doc_id(&self, ord: usize) -> DocId {
+        let bytes = &self.doc_ids.as_slice()[ord * 4..ord * 4 + 4];
+        DocId::from_le_bytes(bytes.try_into().unwrap())
+    }
+
+    /// Returns the ordinal of the first point with a value `>= target`.
+    fn lower_bound(&self, target: u64) -> usize {
+        let block = self
+            .block_maxs
+            .partition_point(|&block_max| block_max < target);
+        if block == self.block_maxs.len() {
+            return self.num_points as usize;
+        }
+        let mut start = block * POINTS_BLOCK_LEN;
+        let mut end = (start + POINTS_BLOCK_LEN).min(self.num_points as usize);
+        while start < end {
+            let mid = start + (end - start) / 2;
+            if self.value(mid) < target {
+                start = mid + 1;
+            } else {
+                end = mid;
+            }
+        }
+        start
+    }
+
+    /// Returns the ordinal one past the last point with a value `<= target`.
+    fn upper_bound(&self, target: u64) -> usize {
+        if target == u64::MAX {
+            self.num_points as usize
+        } else {
+            self.lower_bound(target + 1)
+        }
+    }
+
+    /// Returns the doc ids of the points with a value within the given range.
+    ///
+    /// The doc ids are not sorted, and a doc id is returned once per matching value.
+    pub fn doc_ids_in_range(&self, range: RangeInclusive<u64>) -> impl Iterator<Item = DocId> + '_ {
+        let start = self.lower_bound(*range.start());
+        let end = self.upper_bound(*range.end()).max(start);
+        (start..end).map(move |ord| self.doc_id(ord))
+    }
+
+    /// Returns the points with a value within the given range, sorted by value.
+    pub fn points_in_range(
+        &self,
+        range: RangeInclusive<u64>,
+    ) -> impl Iterator<Item = (u64, DocId)> + '_ {
+        let start = self.lower_bound(*range.start());
+        let end = self.upper_bound(*range.end()).max(start);
+        (start..end).map(move |ord| (self.value(ord), self.doc_id(ord)))
+    }
+
+    /// Iterates over all of the points, sorted by value.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, DocId)> + '_ {
+        (0..self.num_points as usize).map(move |ord| (self.value(ord), self.doc_id(ord)))
+    }
+}
@@ -0,0 +1,56 @@
+use std::io;
+use std::io::Write;
+
+use super::POINTS_BLOCK_LEN;
+use crate::directory::{CompositeWrite, WritePtr};
+use crate::schema::Field;
+use crate::DocId;
+
+/// The points serializer is in charge of the serialization of
+/// the points of all fields with a points index.
+///
+/// For each field, the serialized data is:
+/// - the number of points as a `u32`,
+/// - the values, sorted in ascending order, as `u64`s,
+/// - the doc ids associated with the values, as `u32`s,
+/// - the maximum value of each block of [`POINTS_BLOCK_LEN`] points, as `u64`s.
+///
+/// All integers are encoded in little endian.
+pub struct PointsSerializer {
+    composite_write: CompositeWrite,
+}
+
+impl PointsSerializer {
+    /// Constructor
+    pub fn from_write(write: WritePtr) -> io::Result<PointsSerializer> {
+        let composite_write = CompositeWrite::wrap(write);
+        Ok(PointsSerializer { composite_write })
+    }
+
+    /// Serialize the points of the given field.
+    ///
+    /// `points` need to be sorted by value.
+    pub fn serialize_field(&mut self, field: Field, points: &[(u64, DocId)]) -> io::Result<()> {
+        debug_assert!(points.windows(2).all(|window| window[0].0 <= window[1].0));
+        let write = self.composite_write.for_field(field);
+        write.write_all(&(points.len() as u32).to_le_bytes())?;
+        for &(val, _) in points {
+            write.write_all(&val.to_le_bytes())?;
+        }
+        for &(_, doc) in points {
+            write.write_all(&doc.to_le_bytes())?;
+        }
+        for block in points.chunks(POINTS_BLOCK_LEN) {
+            let block_max = block[block.len() - 1].0;
+            write.write_all(&block_max.to_le_bytes())?;
+        }
+        write.flush()?;
+        Ok(())
+    }
+
+    /// Clean up / flush / close
+    pub fn close(self) -> io::Result<()> {
+        self.composite_write.close()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use std::{io, iter};
+
+use columnar::MonotonicallyMappableToU64;
+
+use super::PointsSerializer;
+use crate::indexer::doc_id_mapping::DocIdMapping;
+use crate::schema::{Field, Schema, Value, DATE_TIME_PRECISION_INDEXED};
+use crate::{DocId, Document};
+
+/// The `PointsWriter` is in charge of buffering the points of
+/// each field with a points index, until the segment is serialized.
+pub struct PointsWriter {
+    points_per_field: Vec<Option<Vec<(u64, DocId)>>>,
+}
+
+impl PointsWriter {
+    /// Initialize with state for tracking the fields with a points index
+    /// specified in the schema.
+    pub fn for_schema(schema: &Schema) -> PointsWriter {
+        let mut points_per_field: Vec<Option<Vec<(u64, DocId)>>> = iter::repeat_with(|| None)
+            .take(schema.num_fields())
+            .collect();
+        for (field, field_entry) in schema.fields() {
+            if field_entry.has_points() {
+                points_per_field[field.field_id() as usize] = Some(Vec::new());
+            }
+        }
+        PointsWriter { points_per_field }
+    }
+
+    /// The memory used inclusive childs
+    pub fn mem_usage(&self) -> usize {
+        self.points_per_field
+            .iter()
+            .flatten()
+            .map(|points| points.capacity() * std::mem::size_of::<(u64, DocId)>())
+            .sum()
+    }
+
+    /// Records the values of a document for all of the fields with a points index.
+    pub fn add_document(&mut self, doc: DocId, document: &Document) {
+        for field_value in document.field_values() {
+            if let Some(val) = value_to_point(field_value.value()) {
+                self.record(doc, field_value.field(), val);
+            }
+        }
+    }
+
+    /// Records a value, in its `u64` representation, for the given document and field.
+    ///
+    /// Values recorded for fields without a points index are ignored.
+    pub fn record(&mut self, doc: DocId, field: Field, val: u64) {
+        if let Some(points) = self
+            .points_per_field
+            .get_mut(field.field_id() as usize)
+            .and_then(Option::as_mut)
+        {
+            points.push((val, doc));
+        }
+    }
+
+    /// Serialize the points of all fields to the serializer.
+    pub fn serialize(
+        mut self,
+        mut points_serializer: PointsSerializer,
+        doc_id_map: Option<&DocIdMapping>,
+    ) -> io::Result<()> {
+        for (field_id, points_opt) in self.points_per_field.iter_mut().enumerate() {
+            let Some(points) = points_opt.as_mut() else {
+                continue;
+            };
+            if let Some(doc_id_map) = doc_id_map {
+                for (_, doc) in points.iter_mut() {
+                    *doc = doc_id_map.get_new_doc_id(*doc);
+                }
+            }
+            points.sort_unstable();
+            points_serializer.serialize_field(Field::from_field_id(field_id as u32), points)?;
+        }
+        points_serializer.close()?;
+        Ok(())
+    }
+}
+
+/// Returns the `u64` representation of a numeric value or a geo point, as stored in the points
+/// index.
+fn value_to_point(value: &Value) -> Option<u64> {
+    match value {
+        Value::U64(val) => Some(*val),
+        Value::I64(val) => Some(val.to_u64()),
+        Value::F64(val) => Some(val.to_u64()),
+        Value::Bool(val) => Some(val.to_u64()),
+        Value::Date(val) => Some(val.truncate(DATE_TIME_PRECISION_INDEXED).to_u64()),
+        Value::GeoPoint(geo_point) => Some(geo_point.to_u64()),
+        _ => None,
+    }
+}
@@ -2,6 +2,7 @@ use std::io;
 
 use common::VInt;
 
+use crate::codec::PostingsDecoder;
 use crate::directory::{FileSlice, OwnedBytes};
 use crate::fieldnorm::FieldNormReader;
 use crate::postings::compression::{BlockDecoder, VIntDecoder, COMPRESSION_BLOCK_SIZE};
@@ -31,6 +32,62 @@ pub struct BlockSegmentPostings {
     doc_freq: u32,
     data: OwnedBytes,
     pub(crate) skip_reader: SkipReader,
+    // Current block, if the posting list is serialized as a bitset.
+    // In that case, `data` is the bitset and `loaded_offset` the first
+    // document of the loaded block.
+    bitset_block: Option<BitsetBlock>,
+    // Decoder of the blocks, if the posting list is written by a postings format other than
+    // the default one. In that case, `data` is unused and the blocks are identified by their
+    // index.
+    postings_decoder: Option<Box<dyn PostingsDecoder>>,
+}
+
+/// Block of a posting list serialized as a bitset.
+///
+/// Such posting lists do not have a skip list: a block is made of the
+/// `COMPRESSION_BLOCK_SIZE` first documents of the bitset starting from `block_start`,
+/// which makes it possible to seek to any document directly.
+#[derive(Clone, Copy)]
+struct BitsetBlock {
+    block_start: DocId,
+    // `TERMINATED` if this is the last block.
+    last_doc_in_block: DocId,
+}
+
+impl BitsetBlock {
+    fn starting_at(bitset: &[u8], block_start: DocId) -> BitsetBlock {
+        let last_doc_in_block = bitset_docs(bitset, block_start)
+            .nth(COMPRESSION_BLOCK_SIZE - 1)
+            .unwrap_or(TERMINATED);
+        BitsetBlock {
+            block_start,
+            last_doc_in_block,
+        }
+    }
+}
+
+// Iterates over the documents of a bitset, stored as little-endian `u64` words,
+// starting from `start_doc`.
+fn bitset_docs(bitset: &[u8], start_doc: DocId) -> impl Iterator<Item = DocId> + '_ {
+    let start_word = start_doc as usize / 64;
+    bitset
+        .chunks_exact(8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        .enumerate()
+        .skip(start_word)
+        .flat_map(move |(word_id, mut word)| {
+            if word_id == start_word {
+                word &= u64::MAX << (start_doc % 64);
+            }
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros();
+                word &= word - 1;
+                Some(word_id as DocId * 64 + bit)
+            })
+        })
 }
 
 fn decode_bitpacked_block(
@@ -65,16 +122,31 @@ fn decode_vint_block(
     }
 }
 
-fn split_into_skips_and_postings(
-    doc_freq: u32,
-    mut bytes: OwnedBytes,
-) -> io::Result<(Option<OwnedBytes>, OwnedBytes)> {
+enum PostingsData {
+    Blocks {
+        skip_data: Option<OwnedBytes>,
+        postings_data: OwnedBytes,
+    },
+    Bitset(OwnedBytes),
+}
+
+fn split_into_skips_and_postings(doc_freq: u32, mut bytes: OwnedBytes) -> io::Result<PostingsData> {
     if doc_freq < COMPRESSION_BLOCK_SIZE as u32 {
-        return Ok((None, bytes));
+        return Ok(PostingsData::Blocks {
+            skip_data: None,
+            postings_data: bytes,
+        });
     }
     let skip_len = VInt::deserialize_u64(&mut bytes)? as usize;
+    if skip_len == 0 {
+        // Block encoded posting lists of this length always have a skip list.
+        return Ok(PostingsData::Bitset(bytes));
+    }
     let (skip_data, postings_data) = bytes.split(skip_len);
-    Ok((Some(skip_data), postings_data))
+    Ok(PostingsData::Blocks {
+        skip_data: Some(skip_data),
+        postings_data,
+    })
 }
 
 impl BlockSegmentPostings {
@@ -83,6 +155,37 @@ impl BlockSegmentPostings {
         data: FileSlice,
         record_option: IndexRecordOption,
         requested_option: IndexRecordOption,
+    ) -> io::Result<BlockSegmentPostings> {
+        Self::open_with_optional_decoder(doc_freq, data, None, record_option, requested_option)
+    }
+
+    /// Opens the posting list of a term with `doc_freq` documents, decoded by the
+    /// `postings_decoder` of a [`PostingsFormat`](crate::codec::PostingsFormat) other than the
+    /// default one.
+    ///
+    /// The block postings can only be reset on the posting lists of the same format.
+    pub fn open_with_decoder(
+        doc_freq: u32,
+        data: FileSlice,
+        postings_decoder: Box<dyn PostingsDecoder>,
+        record_option: IndexRecordOption,
+        requested_option: IndexRecordOption,
+    ) -> io::Result<BlockSegmentPostings> {
+        Self::open_with_optional_decoder(
+            doc_freq,
+            data,
+            Some(postings_decoder),
+            record_option,
+            requested_option,
+        )
+    }
+
+    fn open_with_optional_decoder(
+        doc_freq: u32,
+        data: FileSlice,
+        postings_decoder: Option<Box<dyn PostingsDecoder>>,
+        record_option: IndexRecordOption,
+        requested_option: IndexRecordOption,
     ) -> io::Result<BlockSegmentPostings> {
         let freq_reading_option = match (record_option, requested_option) {
             (IndexRecordOption::Basic, _) => FreqReadingOption::NoFreq,
@@ -91,12 +194,6 @@ impl BlockSegmentPostings {
         };
 
         let bytes = data.read_bytes()?;
-        let (skip_data_opt, postings_data) = split_into_skips_and_postings(doc_freq, bytes)?;
-        let skip_reader = match skip_data_opt {
-            Some(skip_data) => SkipReader::new(skip_data, doc_freq, record_option),
-            None => SkipReader::new(OwnedBytes::empty(), doc_freq, record_option),
-        };
-
         let mut block_segment_postings = BlockSegmentPostings {
             doc_decoder: BlockDecoder::with_val(TERMINATED),
             loaded_offset: usize::MAX,
@@ -104,10 +201,12 @@ impl BlockSegmentPostings {
             freq_reading_option,
             block_max_score_cache: None,
             doc_freq,
-            data: postings_data,
-            skip_reader,
+            data: OwnedBytes::empty(),
+            skip_reader: SkipReader::new(OwnedBytes::empty(), 0, record_option),
+            bitset_block: None,
+            postings_decoder,
         };
-        block_segment_postings.load_block();
+        block_segment_postings.reset(doc_freq, bytes)?;
         Ok(block_segment_postings)
     }
 
@@ -124,6 +223,16 @@ impl BlockSegmentPostings {
         if let Some(score) = self.block_max_score_cache {
             return score;
         }
+        if self.bitset_block.is_some() && self.block_is_loaded() {
+            // Bitset posting lists do not have term frequencies.
+            let bm25_scores = self
+                .docs()
+                .iter()
+                .map(|&doc| bm25_weight.score(fieldnorm_reader.fieldnorm_id(doc), 1));
+            let block_max_score = max_score(bm25_scores).unwrap_or(0.0);
+            self.block_max_score_cache = Some(block_max_score);
+            return block_max_score;
+        }
         if let Some(skip_reader_max_score) = self.skip_reader.block_max_score(bm25_weight) {
             // if we are on a full block, the skip reader should have the block max information
             // for us
@@ -150,6 +259,18 @@ impl BlockSegmentPostings {
         bm25_weight.max_score()
     }
 
+    /// Returns the `(fieldnorm_id, term_freq)` pair of the posting of the current block
+    /// with the highest BM25 impact, as computed when the posting list was serialized.
+    ///
+    /// Like `.block_max_score(..)`, it does not require the block to be loaded.
+    ///
+    /// Returns `None` if this information is not available: for posting lists without
+    /// term frequencies, for the last block of posting lists shorter than a single block,
+    /// and for the last block of posting lists serialized by older versions of tantivy.
+    pub fn block_wand_impact(&self) -> Option<(u8, u32)> {
+        self.skip_reader.block_wand_impact()
+    }
+
     pub(crate) fn freq_reading_option(&self) -> FreqReadingOption {
         self.freq_reading_option
     }
@@ -165,15 +286,29 @@ impl BlockSegmentPostings {
     //
     // This does not reset the positions list.
     pub(crate) fn reset(&mut self, doc_freq: u32, postings_data: OwnedBytes) -> io::Result<()> {
-        let (skip_data_opt, postings_data) =
-            split_into_skips_and_postings(doc_freq, postings_data)?;
-        self.data = postings_data;
         self.block_max_score_cache = None;
         self.loaded_offset = usize::MAX;
-        if let Some(skip_data) = skip_data_opt {
-            self.skip_reader.reset(skip_data, doc_freq);
-        } else {
-            self.skip_reader.reset(OwnedBytes::empty(), doc_freq);
+        if let Some(postings_decoder) = self.postings_decoder.as_mut() {
+            postings_decoder.reset(doc_freq, postings_data)?;
+            self.doc_freq = doc_freq;
+            self.load_block();
+            return Ok(());
+        }
+        match split_into_skips_and_postings(doc_freq, postings_data)? {
+            PostingsData::Blocks {
+                skip_data,
+                postings_data,
+            } => {
+                self.data = postings_data;
+                self.skip_reader
+                    .reset(skip_data.unwrap_or_else(OwnedBytes::empty), doc_freq);
+                self.bitset_block = None;
+            }
+            PostingsData::Bitset(bitset) => {
+                self.data = bitset;
+                self.skip_reader.reset(OwnedBytes::empty(), 0);
+                self.bitset_block = Some(BitsetBlock::starting_at(self.data.as_slice(), 0));
+            }
         }
         self.doc_freq = doc_freq;
         self.load_block();
@@ -253,6 +388,9 @@ impl BlockSegmentPostings {
     }
 
     pub(crate) fn position_offset(&self) -> u64 {
+        if let Some(postings_decoder) = self.postings_decoder.as_ref() {
+            return postings_decoder.position_offset();
+        }
         self.skip_reader.position_offset()
     }
 
@@ -263,21 +401,79 @@ impl BlockSegmentPostings {
     /// If all docs are smaller than target, the block loaded may be empty,
     /// or be the last an incomplete VInt block.
     pub(crate) fn shallow_seek(&mut self, target_doc: DocId) {
+        if let Some(bitset_block) = self.bitset_block {
+            if bitset_block.last_doc_in_block < target_doc {
+                self.bitset_block =
+                    Some(BitsetBlock::starting_at(self.data.as_slice(), target_doc));
+                self.block_max_score_cache = None;
+            }
+            return;
+        }
+        if let Some(postings_decoder) = self.postings_decoder.as_mut() {
+            let block_id = postings_decoder.block_id();
+            postings_decoder.seek(target_doc);
+            if postings_decoder.block_id() != block_id {
+                self.block_max_score_cache = None;
+            }
+            return;
+        }
         if self.skip_reader.seek(target_doc) {
             self.block_max_score_cache = None;
         }
     }
 
+    /// Returns the last document of the current block, or `TERMINATED` if the current
+    /// block is the last block.
+    ///
+    /// Like `.block_max_score(..)`, it does not require the block to be loaded.
+    pub(crate) fn last_doc_in_block(&self) -> DocId {
+        if let Some(bitset_block) = self.bitset_block {
+            return bitset_block.last_doc_in_block;
+        }
+        if let Some(postings_decoder) = self.postings_decoder.as_ref() {
+            return postings_decoder.last_doc_in_block();
+        }
+        self.skip_reader.last_doc_in_block()
+    }
+
+    fn block_offset(&self) -> usize {
+        if let Some(bitset_block) = self.bitset_block {
+            return bitset_block.block_start as usize;
+        }
+        if let Some(postings_decoder) = self.postings_decoder.as_ref() {
+            return postings_decoder.block_id();
+        }
+        self.skip_reader.byte_offset()
+    }
+
     pub(crate) fn block_is_loaded(&self) -> bool {
-        self.loaded_offset == self.skip_reader.byte_offset()
+        self.loaded_offset == self.block_offset()
     }
 
     pub(crate) fn load_block(&mut self) {
-        let offset = self.skip_reader.byte_offset();
+        let offset = self.block_offset();
         if self.loaded_offset == offset {
             return;
         }
         self.loaded_offset = offset;
+        if let Some(bitset_block) = self.bitset_block {
+            self.doc_decoder.fill(
+                bitset_docs(self.data.as_slice(), bitset_block.block_start),
+                TERMINATED,
+            );
+            return;
+        }
+        if let Some(postings_decoder) = self.postings_decoder.as_ref() {
+            let freqs_opt = if let FreqReadingOption::ReadFreq = self.freq_reading_option {
+                Some(self.freq_decoder.output_mut())
+            } else {
+                None
+            };
+            let len = postings_decoder.decode_block(self.doc_decoder.output_mut(), freqs_opt);
+            self.doc_decoder.set_output_len(len, TERMINATED);
+            self.freq_decoder.set_output_len(len, 1);
+            return;
+        }
         match self.skip_reader.block_info() {
             BlockInfo::BitPacked {
                 doc_num_bits,
@@ -324,7 +520,17 @@ impl BlockSegmentPostings {
     ///
     /// Returns false if and only if there is no remaining block.
     pub fn advance(&mut self) {
-        self.skip_reader.advance();
+        if let Some(bitset_block) = self.bitset_block {
+            let next_block_start = bitset_block.last_doc_in_block.saturating_add(1);
+            self.bitset_block = Some(BitsetBlock::starting_at(
+                self.data.as_slice(),
+                next_block_start,
+            ));
+        } else if let Some(postings_decoder) = self.postings_decoder.as_mut() {
+            postings_decoder.advance();
+        } else {
+            self.skip_reader.advance();
+        }
         self.block_max_score_cache = None;
         self.load_block();
     }
@@ -340,6 +546,8 @@ impl BlockSegmentPostings {
             doc_freq: 0,
             data: OwnedBytes::empty(),
             skip_reader: SkipReader::new(OwnedBytes::empty(), 0, IndexRecordOption::Basic),
+            bitset_block: None,
+            postings_decoder: None,
         }
     }
 }
@@ -354,7 +562,7 @@ mod tests {
     use crate::postings::compression::COMPRESSION_BLOCK_SIZE;
     use crate::postings::postings::Postings;
     use crate::postings::SegmentPostings;
-    use crate::schema::{IndexRecordOption, Schema, Term, INDEXED};
+    use crate::schema::{IndexRecordOption, Schema, Term, INDEXED, TEXT};
     use crate::DocId;
 
     #[test]
@@ -444,6 +652,60 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_bitset_block_segment_postings() -> crate::Result<()> {
+        for step in [1, 3, 8, 9] {
+            let docs: Vec<DocId> = (0..1_000).map(|i| i * step + 1).collect();
+            let block_postings = build_block_postings(&docs)?;
+            assert_eq!(block_postings.bitset_block.is_some(), step <= 8);
+            let mut docset = SegmentPostings::from_block_postings(block_postings.clone(), None);
+            for &doc in &docs {
+                assert_eq!(docset.doc(), doc);
+                assert_eq!(docset.term_freq(), 1);
+                docset.advance();
+            }
+            assert_eq!(docset.doc(), TERMINATED);
+            for target in [1, 2, 500, 501, 1_280, 2_999, 9_000] {
+                let expected = docs
+                    .iter()
+                    .cloned()
+                    .find(|&doc| doc >= target)
+                    .unwrap_or(TERMINATED);
+                let mut docset = SegmentPostings::from_block_postings(block_postings.clone(), None);
+                assert_eq!(docset.seek(target), expected);
+                let mut block_postings = block_postings.clone();
+                block_postings.shallow_seek(target);
+                assert!(block_postings.last_doc_in_block() >= expected);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_wand_impact_last_block() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for _ in 0..129 {
+            index_writer.add_document(doc!(text_field=>"a"))?;
+        }
+        index_writer.add_document(doc!(text_field=>"a a a"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let inverted_index = searcher.segment_reader(0).inverted_index(text_field)?;
+        let term_info = inverted_index
+            .get_term_info(&Term::from_field_text(text_field, "a"))?
+            .unwrap();
+        let mut block_postings = inverted_index
+            .read_block_postings_from_terminfo(&term_info, IndexRecordOption::WithFreqs)?;
+        assert_eq!(block_postings.block_wand_impact(), Some((1, 1)));
+        block_postings.shallow_seek(129);
+        assert!(!block_postings.block_is_loaded());
+        assert_eq!(block_postings.block_wand_impact(), Some((3, 3)));
+        Ok(())
+    }
+
     fn build_block_postings(docs: &[DocId]) -> crate::Result<BlockSegmentPostings> {
         let mut schema_builder = Schema::builder();
         let int_field = schema_builder.add_u64_field("id", INDEXED);
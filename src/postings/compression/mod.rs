@@ -49,9 +49,9 @@ impl BlockEncoder {
     }
 }
 
+/// Decodes the blocks with the SIMD kernels of `tantivy_bitpacker`, selected at runtime.
 #[derive(Clone)]
 pub struct BlockDecoder {
-    bitpacker: BitPacker4x,
     output: [u32; COMPRESSION_BLOCK_SIZE],
     pub output_len: usize,
 }
@@ -65,7 +65,6 @@ impl Default for BlockDecoder {
 impl BlockDecoder {
     pub fn with_val(val: u32) -> BlockDecoder {
         BlockDecoder {
-            bitpacker: BitPacker4x::new(),
             output: [val; COMPRESSION_BLOCK_SIZE],
             output_len: 0,
         }
@@ -78,14 +77,42 @@ impl BlockDecoder {
         num_bits: u8,
     ) -> usize {
         self.output_len = COMPRESSION_BLOCK_SIZE;
-        self.bitpacker
-            .decompress_sorted(offset, compressed_data, &mut self.output, num_bits)
+        tantivy_bitpacker::unpack_block_4x_sorted(
+            offset,
+            compressed_data,
+            num_bits,
+            &mut self.output,
+        )
     }
 
     pub fn uncompress_block_unsorted(&mut self, compressed_data: &[u8], num_bits: u8) -> usize {
         self.output_len = COMPRESSION_BLOCK_SIZE;
-        self.bitpacker
-            .decompress(compressed_data, &mut self.output, num_bits)
+        tantivy_bitpacker::unpack_block_4x(compressed_data, num_bits, &mut self.output)
+    }
+
+    /// Fills the output with (at most `COMPRESSION_BLOCK_SIZE`) values, and pads the rest
+    /// of the block with `padding`.
+    pub(crate) fn fill(&mut self, vals: impl Iterator<Item = u32>, padding: u32) {
+        let mut len = 0;
+        for (output, val) in self.output.iter_mut().zip(vals) {
+            *output = val;
+            len += 1;
+        }
+        self.output[len..].fill(padding);
+        self.output_len = len;
+    }
+
+    /// Returns the output block, so that it can be written in place. The number of values
+    /// written then needs to be set with `.set_output_len(..)`.
+    #[inline]
+    pub(crate) fn output_mut(&mut self) -> &mut [u32; COMPRESSION_BLOCK_SIZE] {
+        &mut self.output
+    }
+
+    /// Sets the number of values of the output, and pads the rest of the block with `padding`.
+    pub(crate) fn set_output_len(&mut self, len: usize, padding: u32) {
+        self.output[len..].fill(padding);
+        self.output_len = len;
     }
 
     #[inline]
@@ -1,5 +1,7 @@
 use stacker::{ArenaHashMap, MemoryArena};
 
+use crate::indexer::IndexingBufferSpill;
+
 /// IndexingContext contains all of the transient memory arenas
 /// required for building the inverted index.
 pub(crate) struct IndexingContext {
@@ -20,8 +22,25 @@ impl IndexingContext {
         }
     }
 
+    /// Create a new IndexingContext whose arenas spill to temporary files.
+    pub(crate) fn with_spill(
+        table_size: usize,
+        spill: &IndexingBufferSpill,
+    ) -> crate::Result<IndexingContext> {
+        let term_index = ArenaHashMap::with_capacity_and_arena(table_size, spill.spilling_arena()?);
+        Ok(IndexingContext {
+            arena: spill.spilling_arena()?,
+            term_index,
+        })
+    }
+
     /// Returns the memory usage for the inverted index memory arenas, in bytes.
     pub(crate) fn mem_usage(&self) -> usize {
         self.term_index.mem_usage() + self.arena.mem_usage()
     }
+
+    /// Returns the number of bytes of the arenas spilled to temporary files.
+    pub(crate) fn spilled_num_bytes(&self) -> usize {
+        self.term_index.spilled_num_bytes() + self.arena.spilled_num_bytes()
+    }
 }
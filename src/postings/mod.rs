@@ -25,6 +25,7 @@ pub(crate) use self::per_field_postings_writer::PerFieldPostingsWriter;
 pub use self::postings::Postings;
 pub(crate) use self::postings_writer::{serialize_postings, IndexingPosition, PostingsWriter};
 pub use self::segment_postings::SegmentPostings;
+pub(crate) use self::serializer::PostingsSerializer;
 pub use self::serializer::{FieldSerializer, InvertedIndexSerializer};
 pub(crate) use self::skip::{BlockInfo, SkipReader};
 pub use self::term_info::TermInfo;
@@ -39,15 +40,17 @@ pub(crate) enum FreqReadingOption {
 
 #[cfg(test)]
 pub mod tests {
+    use std::collections::BTreeMap;
     use std::mem;
 
     use super::{InvertedIndexSerializer, Postings};
+    use crate::collector::Count;
     use crate::core::{Index, SegmentComponent, SegmentReader};
     use crate::docset::{DocSet, TERMINATED};
     use crate::fieldnorm::FieldNormReader;
     use crate::indexer::operation::AddOperation;
     use crate::indexer::SegmentWriter;
-    use crate::query::Scorer;
+    use crate::query::{PhraseQuery, Query, Scorer, TermQuery};
     use crate::schema::{
         Field, IndexRecordOption, Schema, Term, TextFieldIndexing, TextOptions, INDEXED, TEXT,
     };
@@ -76,6 +79,137 @@ pub mod tests {
         Ok(())
     }
 
+    #[test]
+    pub fn test_offsets() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("default")
+                .set_index_option(IndexRecordOption::WithFreqsAndPositionsAndOffsets),
+        );
+        let title = schema_builder.add_text_field("title", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc!(title => "abc be abc", title => "Abc"))?;
+            for _ in 0..200 {
+                index_writer.add_document(doc!(title => "be abc"))?;
+            }
+            index_writer.commit()?;
+            index_writer.add_document(doc!(title => "c abc"))?;
+            index_writer.commit()?;
+        }
+        // Returns the number of documents for each list of positions and offsets.
+        let positions_and_offsets = |index: &Index| -> crate::Result<_> {
+            let searcher = index.reader()?.searcher();
+            let term = Term::from_field_text(title, "abc");
+            let mut counts: BTreeMap<(Vec<u32>, Vec<(u32, u32)>), usize> = BTreeMap::new();
+            let mut positions = Vec::new();
+            let mut offsets = Vec::new();
+            for segment_reader in searcher.segment_readers() {
+                let mut postings = segment_reader
+                    .inverted_index(title)?
+                    .read_postings(&term, IndexRecordOption::WithFreqsAndPositionsAndOffsets)?
+                    .unwrap();
+                while postings.doc() != TERMINATED {
+                    postings.offsets(&mut offsets);
+                    postings.positions(&mut positions);
+                    *counts
+                        .entry((positions.clone(), offsets.clone()))
+                        .or_default() += 1;
+                    postings.advance();
+                }
+                let mut postings = segment_reader
+                    .inverted_index(title)?
+                    .read_postings(&term, IndexRecordOption::WithFreqs)?
+                    .unwrap();
+                postings.offsets(&mut offsets);
+                assert!(offsets.is_empty());
+            }
+            Ok(counts)
+        };
+        let expected: BTreeMap<(Vec<u32>, Vec<(u32, u32)>), usize> = [
+            ((vec![0, 2, 4], vec![(0, 3), (7, 10), (10, 13)]), 1),
+            ((vec![1], vec![(3, 6)]), 200),
+            ((vec![1], vec![(2, 5)]), 1),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(positions_and_offsets(&index)?, expected);
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            let segment_ids = index.searchable_segment_ids()?;
+            index_writer.merge(&segment_ids).wait()?;
+            index_writer.wait_merging_threads()?;
+        }
+        assert_eq!(positions_and_offsets(&index)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_max_doc_freq() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+                .set_max_doc_freq(2),
+        );
+        let text = schema_builder.add_text_field("text", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        let the = Term::from_field_text(text, "the");
+        let cat = Term::from_field_text(text, "cat");
+        let dog = Term::from_field_text(text, "dog");
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "the cat the"))?;
+        index_writer.add_document(doc!(text => "the dog"))?;
+        index_writer.add_document(doc!(text => "the dog"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text => "the cat dog"))?;
+        index_writer.commit()?;
+
+        let read_postings = |term: &Term| -> crate::Result<(u32, Vec<u32>)> {
+            let searcher = index.reader()?.searcher();
+            let mut postings = searcher
+                .segment_reader(0)
+                .inverted_index(text)?
+                .read_postings(term, IndexRecordOption::WithFreqsAndPositions)?
+                .unwrap();
+            let mut positions = Vec::new();
+            postings.positions(&mut positions);
+            Ok((postings.term_freq(), positions))
+        };
+        let count = |query: &dyn Query| -> crate::Result<usize> {
+            index.reader()?.searcher().search(query, &Count)
+        };
+        let the_cat = PhraseQuery::new(vec![the.clone(), cat.clone()]);
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.doc_freq(&the)?, 4);
+        assert_eq!(searcher.doc_freq(&cat)?, 2);
+        assert_eq!(searcher.doc_freq(&dog)?, 3);
+        // Only the doc ids of `the` are indexed in the first segment.
+        assert_eq!(read_postings(&the)?, (1, vec![]));
+        assert_eq!(read_postings(&cat)?, (1, vec![1]));
+        assert_eq!(count(&the_cat)?, 1);
+
+        index_writer.delete_term(dog.clone());
+        index_writer.commit()?;
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        assert_eq!(searcher.doc_freq(&the)?, 1);
+        assert_eq!(searcher.doc_freq(&cat)?, 1);
+        // `the` does not get its positions back, even though its document frequency is now
+        // below the maximum.
+        assert_eq!(read_postings(&the)?, (1, vec![]));
+        assert_eq!(read_postings(&cat)?, (1, vec![1]));
+        let the_query = TermQuery::new(the, IndexRecordOption::WithFreqs);
+        assert_eq!(count(&the_query)?, 1);
+        assert_eq!(count(&the_cat)?, 0);
+        Ok(())
+    }
+
     #[test]
     pub fn test_skip_positions() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
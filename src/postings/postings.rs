@@ -5,7 +5,7 @@ use crate::docset::DocSet;
 /// For a given term, it is the list of doc ids of the doc
 /// containing the term. Optionally, for each document,
 /// it may also give access to the term frequency
-/// as well as the list of term positions.
+/// as well as the list of term positions and offsets.
 ///
 /// Its main implementation is `SegmentPostings`,
 /// but other implementations mocking `SegmentPostings` exist,
@@ -23,4 +23,16 @@ pub trait Postings: DocSet + 'static {
     fn positions(&mut self, output: &mut Vec<u32>) {
         self.positions_with_offset(0u32, output);
     }
+
+    /// Returns the `(offset_from, offset_to)` byte offsets of the tokens of the
+    /// occurrences of the term in the given document, in the same order as
+    /// the positions.
+    ///
+    /// The offsets of the values of a multivalued field are relative to the
+    /// concatenation of the values.
+    /// The output vector will be resized to the `term_freq`, or cleared if the
+    /// offsets are not available.
+    fn offsets(&mut self, output: &mut Vec<(u32, u32)>) {
+        output.clear();
+    }
 }
@@ -78,6 +78,9 @@ pub(crate) fn serialize_postings(
 pub(crate) struct IndexingPosition {
     pub num_tokens: u32,
     pub end_position: u32,
+    /// Byte offset added to the offsets of the tokens of the text being indexed,
+    /// so that the offsets of the values of a multivalued field do not overlap.
+    pub offset_base: u32,
 }
 
 /// The `PostingsWriter` is in charge of receiving documenting
@@ -94,6 +97,21 @@ pub(crate) trait PostingsWriter: Send + Sync {
     ///   information.
     fn subscribe(&mut self, doc: DocId, pos: u32, term: &Term, ctx: &mut IndexingContext);
 
+    /// Record that a document contains a term at a given position, for a token
+    /// spanning the given `(offset_from, offset_to)` byte offsets.
+    ///
+    /// The offsets are ignored if the postings writer does not record them.
+    fn subscribe_with_offsets(
+        &mut self,
+        doc: DocId,
+        pos: u32,
+        _offsets: (u32, u32),
+        term: &Term,
+        ctx: &mut IndexingContext,
+    ) {
+        self.subscribe(doc, pos, term, ctx);
+    }
+
     /// Serializes the postings on disk.
     /// The actual serialization format is handled by the `PostingsSerializer`.
     fn serialize(
@@ -131,7 +149,11 @@ pub(crate) trait PostingsWriter: Send + Sync {
             term_buffer.append_bytes(token.text.as_bytes());
             let start_position = indexing_position.end_position + token.position as u32;
             end_position = end_position.max(start_position + token.position_length as u32);
-            self.subscribe(doc_id, start_position, term_buffer, ctx);
+            let offsets = (
+                indexing_position.offset_base + token.offset_from as u32,
+                indexing_position.offset_base + token.offset_to as u32,
+            );
+            self.subscribe_with_offsets(doc_id, start_position, offsets, term_buffer, ctx);
             num_tokens += 1;
         });
 
@@ -181,6 +203,18 @@ impl<Rec: Recorder> SpecializedPostingsWriter<Rec> {
 impl<Rec: Recorder> PostingsWriter for SpecializedPostingsWriter<Rec> {
     #[inline]
     fn subscribe(&mut self, doc: DocId, position: u32, term: &Term, ctx: &mut IndexingContext) {
+        self.subscribe_with_offsets(doc, position, (0u32, 0u32), term, ctx);
+    }
+
+    #[inline]
+    fn subscribe_with_offsets(
+        &mut self,
+        doc: DocId,
+        position: u32,
+        offsets: (u32, u32),
+        term: &Term,
+        ctx: &mut IndexingContext,
+    ) {
         debug_assert!(term.serialized_term().len() >= 4);
         self.total_num_tokens += 1;
         let (term_index, arena) = (&mut ctx.term_index, &mut ctx.arena);
@@ -191,12 +225,12 @@ impl<Rec: Recorder> PostingsWriter for SpecializedPostingsWriter<Rec> {
                     recorder.close_doc(arena);
                     recorder.new_doc(doc, arena);
                 }
-                recorder.record_position(position, arena);
+                recorder.record_position_with_offsets(position, offsets, arena);
                 recorder
             } else {
                 let mut recorder = Rec::default();
                 recorder.new_doc(doc, arena);
-                recorder.record_position(position, arena);
+                recorder.record_position_with_offsets(position, offsets, arena);
                 recorder
             }
         });
@@ -56,6 +56,7 @@ impl<'a> Iterator for VInt32Reader<'a> {
 ///   * the document id
 ///   * the term frequency
 ///   * the term positions
+///   * the byte offsets of the tokens
 pub(crate) trait Recorder: Copy + Default + Send + Sync + 'static {
     /// Returns the current document
     fn current_doc(&self) -> u32;
@@ -65,6 +66,17 @@ pub(crate) trait Recorder: Copy + Default + Send + Sync + 'static {
     /// Record the position of a term. For each document,
     /// this method will be called `term_freq` times.
     fn record_position(&mut self, position: u32, arena: &mut MemoryArena);
+    /// Record the position of a term, along with the byte offsets of its token.
+    ///
+    /// The offsets are ignored by the recorders that do not record them.
+    fn record_position_with_offsets(
+        &mut self,
+        position: u32,
+        _offsets: (u32, u32),
+        arena: &mut MemoryArena,
+    ) {
+        self.record_position(position, arena);
+    }
     /// Close the document. It will help record the term frequency.
     fn close_doc(&mut self, arena: &mut MemoryArena);
     /// Pushes the postings information to the serializer.
@@ -305,6 +317,125 @@ impl Recorder for TfAndPositionRecorder {
     }
 }
 
+/// Recorder encoding term frequencies, positions, and the byte offsets of the tokens.
+#[derive(Clone, Copy)]
+pub struct TfPositionAndOffsetRecorder {
+    stack: ExpUnrolledLinkedList,
+    current_doc: DocId,
+    term_doc_freq: u32,
+}
+
+impl Default for TfPositionAndOffsetRecorder {
+    fn default() -> Self {
+        TfPositionAndOffsetRecorder {
+            stack: ExpUnrolledLinkedList::default(),
+            current_doc: u32::MAX,
+            term_doc_freq: 0u32,
+        }
+    }
+}
+
+impl Recorder for TfPositionAndOffsetRecorder {
+    #[inline]
+    fn current_doc(&self) -> DocId {
+        self.current_doc
+    }
+
+    #[inline]
+    fn new_doc(&mut self, doc: DocId, arena: &mut MemoryArena) {
+        self.current_doc = doc;
+        self.term_doc_freq += 1u32;
+        self.stack.writer(arena).write_u32_vint(doc);
+    }
+
+    #[inline]
+    fn record_position(&mut self, position: u32, arena: &mut MemoryArena) {
+        self.record_position_with_offsets(position, (0u32, 0u32), arena);
+    }
+
+    #[inline]
+    fn record_position_with_offsets(
+        &mut self,
+        position: u32,
+        (offset_from, offset_to): (u32, u32),
+        arena: &mut MemoryArena,
+    ) {
+        let mut writer = self.stack.writer(arena);
+        writer.write_u32_vint(position.wrapping_add(1u32));
+        writer.write_u32_vint(offset_from);
+        writer.write_u32_vint(offset_to.saturating_sub(offset_from));
+    }
+
+    #[inline]
+    fn close_doc(&mut self, arena: &mut MemoryArena) {
+        self.stack.writer(arena).write_u32_vint(POSITION_END);
+    }
+
+    fn serialize(
+        &self,
+        arena: &MemoryArena,
+        doc_id_map: Option<&DocIdMapping>,
+        serializer: &mut FieldSerializer<'_>,
+        buffer_lender: &mut BufferLender,
+    ) {
+        let (buffer_u8, buffer_positions) = buffer_lender.lend_all();
+        self.stack.read_to_end(arena, buffer_u8);
+        let mut u32_it = VInt32Reader::new(&buffer_u8[..]);
+        let mut offsets = Vec::new();
+        let mut doc_id_and_positions = vec![];
+        while let Some(doc) = u32_it.next() {
+            let mut prev_position_plus_one = 1u32;
+            buffer_positions.clear();
+            offsets.clear();
+            loop {
+                match u32_it.next() {
+                    Some(POSITION_END) | None => {
+                        break;
+                    }
+                    Some(position_plus_one) => {
+                        let delta_position = position_plus_one - prev_position_plus_one;
+                        buffer_positions.push(delta_position);
+                        prev_position_plus_one = position_plus_one;
+                        let offset_from = u32_it.next().unwrap_or(0u32);
+                        let len = u32_it.next().unwrap_or(0u32);
+                        offsets.push((offset_from, offset_from + len));
+                    }
+                }
+            }
+            if let Some(doc_id_map) = doc_id_map {
+                // this simple variant to remap may consume to much memory
+                doc_id_and_positions.push((
+                    doc_id_map.get_new_doc_id(doc),
+                    buffer_positions.to_vec(),
+                    offsets.clone(),
+                ));
+            } else {
+                serializer.write_doc_with_offsets(
+                    doc,
+                    buffer_positions.len() as u32,
+                    buffer_positions,
+                    &offsets,
+                );
+            }
+        }
+        if doc_id_map.is_some() {
+            doc_id_and_positions.sort_unstable_by_key(|&(doc_id, _, _)| doc_id);
+            for (doc_id, positions, offsets) in doc_id_and_positions {
+                serializer.write_doc_with_offsets(
+                    doc_id,
+                    positions.len() as u32,
+                    &positions,
+                    &offsets,
+                );
+            }
+        }
+    }
+
+    fn term_doc_freq(&self) -> Option<u32> {
+        Some(self.term_doc_freq)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
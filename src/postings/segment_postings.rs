@@ -17,6 +17,8 @@ pub struct SegmentPostings {
     pub(crate) block_cursor: BlockSegmentPostings,
     cur: usize,
     position_reader: Option<PositionReader>,
+    // If true, the offsets of each document are stored after its positions.
+    has_offsets: bool,
 }
 
 impl SegmentPostings {
@@ -26,6 +28,7 @@ impl SegmentPostings {
             block_cursor: BlockSegmentPostings::empty(),
             cur: 0,
             position_reader: None,
+            has_offsets: false,
         }
     }
 
@@ -65,18 +68,19 @@ impl SegmentPostings {
     #[cfg(test)]
     pub fn create_from_docs(docs: &[u32]) -> SegmentPostings {
         use crate::directory::FileSlice;
+        use crate::codec::PostingsEncoder;
         use crate::postings::serializer::PostingsSerializer;
         use crate::schema::IndexRecordOption;
         let mut buffer = Vec::new();
         {
             let mut postings_serializer =
-                PostingsSerializer::new(&mut buffer, 0.0, IndexRecordOption::Basic, None);
+                PostingsSerializer::new(0.0, IndexRecordOption::Basic, None);
             postings_serializer.new_term(docs.len() as u32);
             for &doc in docs {
                 postings_serializer.write_doc(doc, 1u32);
             }
             postings_serializer
-                .close_term(docs.len() as u32)
+                .close_term(docs.len() as u32, &mut buffer)
                 .expect("In memory Serialization should never fail.");
         }
         let block_segment_postings = BlockSegmentPostings::open(
@@ -97,6 +101,7 @@ impl SegmentPostings {
     ) -> SegmentPostings {
         use crate::directory::FileSlice;
         use crate::fieldnorm::FieldNormReader;
+        use crate::codec::PostingsEncoder;
         use crate::postings::serializer::PostingsSerializer;
         use crate::schema::IndexRecordOption;
         use crate::Score;
@@ -115,7 +120,6 @@ impl SegmentPostings {
             })
             .unwrap_or(0.0);
         let mut postings_serializer = PostingsSerializer::new(
-            &mut buffer,
             average_field_norm,
             IndexRecordOption::WithFreqs,
             fieldnorm_reader,
@@ -125,7 +129,7 @@ impl SegmentPostings {
             postings_serializer.write_doc(doc, tf);
         }
         postings_serializer
-            .close_term(doc_and_tfs.len() as u32)
+            .close_term(doc_and_tfs.len() as u32, &mut buffer)
             .unwrap();
         let block_segment_postings = BlockSegmentPostings::open(
             doc_and_tfs.len() as u32,
@@ -150,10 +154,36 @@ impl SegmentPostings {
             block_cursor: segment_block_postings,
             cur: 0, // cursor within the block
             position_reader,
+            has_offsets: false,
+        }
+    }
+
+    /// Reads a Segment postings, like [`SegmentPostings::from_block_postings`],
+    /// for a field recording the offsets of the tokens in addition to their positions.
+    pub(crate) fn from_block_postings_with_offsets(
+        segment_block_postings: BlockSegmentPostings,
+        position_reader: Option<PositionReader>,
+    ) -> SegmentPostings {
+        SegmentPostings {
+            has_offsets: true,
+            ..SegmentPostings::from_block_postings(segment_block_postings, position_reader)
         }
     }
 }
 
+// Returns the offset of the positions of the document at `cur` in the
+// positions stream.
+fn position_read_offset(block_cursor: &BlockSegmentPostings, cur: usize, has_offsets: bool) -> u64 {
+    let num_previous_positions = block_cursor.position_offset()
+        + (block_cursor.freqs()[..cur].iter().cloned().sum::<u32>() as u64);
+    if has_offsets {
+        // Each occurrence is encoded with its position and two values for its offsets.
+        num_previous_positions * 3
+    } else {
+        num_previous_positions
+    }
+}
+
 impl DocSet for SegmentPostings {
     // goes to the next element.
     // next needs to be called a first time to point to the correct element.
@@ -240,11 +270,7 @@ impl Postings for SegmentPostings {
     fn positions_with_offset(&mut self, offset: u32, output: &mut Vec<u32>) {
         let term_freq = self.term_freq() as usize;
         if let Some(position_reader) = self.position_reader.as_mut() {
-            let read_offset = self.block_cursor.position_offset()
-                + (self.block_cursor.freqs()[..self.cur]
-                    .iter()
-                    .cloned()
-                    .sum::<u32>() as u64);
+            let read_offset = position_read_offset(&self.block_cursor, self.cur, self.has_offsets);
             output.resize(term_freq, 0u32);
             position_reader.read(read_offset, &mut output[..]);
             let mut cum = offset;
@@ -256,6 +282,27 @@ impl Postings for SegmentPostings {
             output.clear();
         }
     }
+
+    /// The offsets are only available if the field records offsets, and
+    /// positions were requested.
+    fn offsets(&mut self, output: &mut Vec<(u32, u32)>) {
+        output.clear();
+        if !self.has_offsets {
+            return;
+        }
+        let term_freq = self.term_freq() as usize;
+        if let Some(position_reader) = self.position_reader.as_mut() {
+            let read_offset =
+                position_read_offset(&self.block_cursor, self.cur, true) + term_freq as u64;
+            let mut encoded_offsets = vec![0u32; term_freq * 2];
+            position_reader.read(read_offset, &mut encoded_offsets[..]);
+            let mut offset_from = 0u32;
+            output.extend(encoded_offsets.chunks_exact(2).map(|encoded_offset| {
+                offset_from = offset_from.wrapping_add(encoded_offset[0]);
+                (offset_from, offset_from + encoded_offset[1])
+            }));
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1,10 +1,12 @@
 use std::cmp::Ordering;
 use std::io::{self, Write};
+use std::sync::Arc;
 
 use common::{BinarySerializable, CountingWriter, VInt};
 use fail::fail_point;
 
 use super::TermInfo;
+use crate::codec::{Codec, PostingsEncoder, TermDictionaryEncoder};
 use crate::core::Segment;
 use crate::directory::{CompositeWrite, WritePtr};
 use crate::fieldnorm::FieldNormReader;
@@ -12,8 +14,7 @@ use crate::positions::PositionSerializer;
 use crate::postings::compression::{BlockEncoder, VIntEncoder, COMPRESSION_BLOCK_SIZE};
 use crate::postings::skip::SkipSerializer;
 use crate::query::Bm25Weight;
-use crate::schema::{Field, FieldEntry, FieldType, IndexRecordOption, Schema};
-use crate::termdict::TermDictionaryBuilder;
+use crate::schema::{Field, FieldEntry, FieldType, IndexRecordOption, Schema, TextFieldIndexing};
 use crate::{DocId, Score};
 
 /// `InvertedIndexSerializer` is in charge of serializing
@@ -52,10 +53,13 @@ pub struct InvertedIndexSerializer {
     postings_write: CompositeWrite<WritePtr>,
     positions_write: CompositeWrite<WritePtr>,
     schema: Schema,
+    codec: Arc<dyn Codec>,
 }
 
 impl InvertedIndexSerializer {
     /// Open a new `InvertedIndexSerializer` for the given segment
+    ///
+    /// The postings and the term dictionaries are encoded by the codec of the segment.
     pub fn open(segment: &mut Segment) -> crate::Result<InvertedIndexSerializer> {
         use crate::SegmentComponent::{Positions, Postings, Terms};
         let inv_index_serializer = InvertedIndexSerializer {
@@ -63,6 +67,7 @@ impl InvertedIndexSerializer {
             postings_write: CompositeWrite::wrap(segment.open_write(Postings)?),
             positions_write: CompositeWrite::wrap(segment.open_write(Positions)?),
             schema: segment.schema(),
+            codec: segment.codec()?,
         };
         Ok(inv_index_serializer)
     }
@@ -83,6 +88,7 @@ impl InvertedIndexSerializer {
         let positions_write = self.positions_write.for_field(field);
         let field_type: FieldType = (*field_entry.field_type()).clone();
         FieldSerializer::create(
+            self.codec.as_ref(),
             &field_type,
             total_num_tokens,
             term_dictionary_write,
@@ -104,15 +110,28 @@ impl InvertedIndexSerializer {
 /// The field serializer is in charge of
 /// the serialization of a specific field.
 pub struct FieldSerializer<'a> {
-    term_dictionary_builder: TermDictionaryBuilder<&'a mut CountingWriter<WritePtr>>,
-    postings_serializer: PostingsSerializer<&'a mut CountingWriter<WritePtr>>,
+    term_dictionary_encoder: Box<dyn TermDictionaryEncoder + 'a>,
+    // The key of the current term, inserted in the term dictionary with its term info once
+    // the term is closed.
+    current_term: Vec<u8>,
+    postings_encoder: Box<dyn PostingsEncoder>,
+    postings_write: CountingWriter<&'a mut CountingWriter<WritePtr>>,
     positions_serializer_opt: Option<PositionSerializer<&'a mut CountingWriter<WritePtr>>>,
+    // Buffer used to encode the positions and the offsets of a document,
+    // if the field records offsets.
+    offsets_buffer: Option<Vec<u32>>,
     current_term_info: TermInfo,
     term_open: bool,
+    // The terms with a larger document frequency are serialized without their term
+    // frequencies and positions.
+    max_doc_freq: Option<u32>,
+    // False if only the doc ids of the current term are serialized.
+    term_has_freqs_and_positions: bool,
 }
 
 impl<'a> FieldSerializer<'a> {
     fn create(
+        codec: &dyn Codec,
         field_type: &FieldType,
         total_num_tokens: u64,
         term_dictionary_write: &'a mut CountingWriter<WritePtr>,
@@ -124,29 +143,46 @@ impl<'a> FieldSerializer<'a> {
         let index_record_option = field_type
             .index_record_option()
             .unwrap_or(IndexRecordOption::Basic);
-        let term_dictionary_builder = TermDictionaryBuilder::create(term_dictionary_write)?;
+        let term_dictionary_encoder = codec
+            .term_dictionary_format()
+            .new_encoder(term_dictionary_write)?;
         let average_fieldnorm = fieldnorm_reader
             .as_ref()
             .map(|ff_reader| (total_num_tokens as Score / ff_reader.num_docs() as Score))
             .unwrap_or(0.0);
-        let postings_serializer = PostingsSerializer::new(
-            postings_write,
-            average_fieldnorm,
+        let postings_encoder = codec.postings_format().new_encoder(
             index_record_option,
             fieldnorm_reader,
+            average_fieldnorm,
         );
         let positions_serializer_opt = if index_record_option.has_positions() {
             Some(PositionSerializer::new(positions_write))
         } else {
             None
         };
+        let max_doc_freq = match field_type {
+            FieldType::Str(text_options) if index_record_option.has_freq() => text_options
+                .get_indexing_options()
+                .and_then(TextFieldIndexing::max_doc_freq),
+            _ => None,
+        };
+        let offsets_buffer = if index_record_option.has_offsets() {
+            Some(Vec::new())
+        } else {
+            None
+        };
 
         Ok(FieldSerializer {
-            term_dictionary_builder,
-            postings_serializer,
+            term_dictionary_encoder,
+            current_term: Vec::new(),
+            postings_encoder,
+            postings_write: CountingWriter::wrap(postings_write),
             positions_serializer_opt,
+            offsets_buffer,
             current_term_info: TermInfo::default(),
             term_open: false,
+            max_doc_freq,
+            term_has_freqs_and_positions: true,
         })
     }
 
@@ -157,7 +193,7 @@ impl<'a> FieldSerializer<'a> {
             } else {
                 0u64
             } as usize;
-        let addr = self.postings_serializer.written_bytes() as usize;
+        let addr = self.postings_write.written_bytes() as usize;
         TermInfo {
             doc_freq: 0,
             postings_range: addr..addr,
@@ -169,16 +205,44 @@ impl<'a> FieldSerializer<'a> {
     /// * term - the term. It needs to come after the previous term according to the lexicographical
     ///   order.
     /// * term_doc_freq - return the number of document containing the term.
+    ///
+    /// If the field has a maximum document frequency, and `term_doc_freq` exceeds it, only the
+    /// doc ids of the term are serialized.
     pub fn new_term(&mut self, term: &[u8], term_doc_freq: u32) -> io::Result<()> {
+        let has_freqs_and_positions = self
+            .max_doc_freq
+            .map(|max_doc_freq| term_doc_freq <= max_doc_freq)
+            .unwrap_or(true);
+        self.open_term(term, term_doc_freq, has_freqs_and_positions)
+    }
+
+    /// Starts the postings for a new term, of which only the doc ids are serialized.
+    ///
+    /// The term frequencies are all written as 1, and the term has no positions.
+    pub(crate) fn new_term_without_freqs_and_positions(
+        &mut self,
+        term: &[u8],
+        term_doc_freq: u32,
+    ) -> io::Result<()> {
+        self.open_term(term, term_doc_freq, false)
+    }
+
+    fn open_term(
+        &mut self,
+        term: &[u8],
+        term_doc_freq: u32,
+        has_freqs_and_positions: bool,
+    ) -> io::Result<()> {
         assert!(
             !self.term_open,
             "Called new_term, while the previous term was not closed."
         );
         self.term_open = true;
-        self.postings_serializer.clear();
+        self.term_has_freqs_and_positions = has_freqs_and_positions;
         self.current_term_info = self.current_term_info();
-        self.term_dictionary_builder.insert_key(term)?;
-        self.postings_serializer.new_term(term_doc_freq);
+        self.current_term.clear();
+        self.current_term.extend_from_slice(term);
+        self.postings_encoder.new_term(term_doc_freq);
         Ok(())
     }
 
@@ -192,11 +256,47 @@ impl<'a> FieldSerializer<'a> {
     /// Term frequencies and positions may be ignored by the serializer depending
     /// on the configuration of the field in the `Schema`.
     pub fn write_doc(&mut self, doc_id: DocId, term_freq: u32, position_deltas: &[u32]) {
+        self.write_doc_with_offsets(doc_id, term_freq, position_deltas, &[]);
+    }
+
+    /// Serialize the information that a document contains for the current term,
+    /// like [`FieldSerializer::write_doc`], as well as the `(offset_from, offset_to)`
+    /// byte offsets of the tokens of each occurrence.
+    ///
+    /// The offsets are ignored if the field does not record offsets. Otherwise,
+    /// there needs to be one pair of offsets per position.
+    pub fn write_doc_with_offsets(
+        &mut self,
+        doc_id: DocId,
+        term_freq: u32,
+        position_deltas: &[u32],
+        offsets: &[(u32, u32)],
+    ) {
         self.current_term_info.doc_freq += 1;
-        self.postings_serializer.write_doc(doc_id, term_freq);
+        if !self.term_has_freqs_and_positions {
+            self.postings_encoder.write_doc(doc_id, 1);
+            return;
+        }
+        self.postings_encoder.write_doc(doc_id, term_freq);
         if let Some(ref mut positions_serializer) = self.positions_serializer_opt.as_mut() {
             assert_eq!(term_freq as usize, position_deltas.len());
-            positions_serializer.write_positions_delta(position_deltas);
+            if let Some(offsets_buffer) = self.offsets_buffer.as_mut() {
+                assert_eq!(term_freq as usize, offsets.len());
+                // The offsets are written in the positions stream, right after
+                // the positions of the document: for each occurrence, the delta
+                // with the previous `offset_from` and the length of the token.
+                offsets_buffer.clear();
+                offsets_buffer.extend_from_slice(position_deltas);
+                let mut previous_offset_from = 0u32;
+                for &(offset_from, offset_to) in offsets {
+                    offsets_buffer.push(offset_from.wrapping_sub(previous_offset_from));
+                    offsets_buffer.push(offset_to.saturating_sub(offset_from));
+                    previous_offset_from = offset_from;
+                }
+                positions_serializer.write_positions_delta(offsets_buffer);
+            } else {
+                positions_serializer.write_positions_delta(position_deltas);
+            }
         }
     }
 
@@ -209,18 +309,23 @@ impl<'a> FieldSerializer<'a> {
             Err(io::Error::new(io::ErrorKind::Other, format!("{msg:?}")))
         });
         if self.term_open {
-            self.postings_serializer
-                .close_term(self.current_term_info.doc_freq)?;
+            self.postings_encoder
+                .close_term(self.current_term_info.doc_freq, &mut self.postings_write)?;
             self.current_term_info.postings_range.end =
-                self.postings_serializer.written_bytes() as usize;
+                self.postings_write.written_bytes() as usize;
 
-            if let Some(positions_serializer) = self.positions_serializer_opt.as_mut() {
+            // The terms without positions get an empty positions range.
+            if let Some(positions_serializer) = self
+                .positions_serializer_opt
+                .as_mut()
+                .filter(|_| self.term_has_freqs_and_positions)
+            {
                 positions_serializer.close_term()?;
                 self.current_term_info.positions_range.end =
                     positions_serializer.written_bytes() as usize;
             }
-            self.term_dictionary_builder
-                .insert_value(&self.current_term_info)?;
+            self.term_dictionary_encoder
+                .insert(&self.current_term, &self.current_term_info)?;
             self.term_open = false;
         }
         Ok(())
@@ -232,8 +337,7 @@ impl<'a> FieldSerializer<'a> {
         if let Some(positions_serializer) = self.positions_serializer_opt {
             positions_serializer.close()?;
         }
-        self.postings_serializer.close()?;
-        self.term_dictionary_builder.finish()?;
+        self.term_dictionary_encoder.finish()?;
         Ok(())
     }
 }
@@ -286,8 +390,14 @@ impl Block {
     }
 }
 
-pub struct PostingsSerializer<W: Write> {
-    output_write: CountingWriter<W>,
+/// Posting lists without term frequencies are serialized as a bitset if the term appears in at
+/// least one of every `BITSET_POSTINGS_MAX_SPARSITY` documents, up to its last document.
+///
+/// The bitset then takes at most one byte per document. Such posting lists are marked by
+/// an empty skip list.
+const BITSET_POSTINGS_MAX_SPARSITY: u64 = 8;
+
+pub(crate) struct PostingsSerializer {
     last_doc_id_encoded: u32,
 
     block_encoder: BlockEncoder,
@@ -299,21 +409,22 @@ pub struct PostingsSerializer<W: Write> {
     mode: IndexRecordOption,
     fieldnorm_reader: Option<FieldNormReader>,
 
+    // Doc ids of the current term, buffered if the posting list may be
+    // serialized as a bitset.
+    bitset_doc_ids: Vec<DocId>,
+
     bm25_weight: Option<Bm25Weight>,
     avg_fieldnorm: Score, /* Average number of term in the field for that segment.
                            * this value is used to compute the block wand information. */
 }
 
-impl<W: Write> PostingsSerializer<W> {
+impl PostingsSerializer {
     pub fn new(
-        write: W,
         avg_fieldnorm: Score,
         mode: IndexRecordOption,
         fieldnorm_reader: Option<FieldNormReader>,
-    ) -> PostingsSerializer<W> {
+    ) -> PostingsSerializer {
         PostingsSerializer {
-            output_write: CountingWriter::wrap(write),
-
             block_encoder: BlockEncoder::new(),
             block: Box::new(Block::new()),
 
@@ -324,36 +435,12 @@ impl<W: Write> PostingsSerializer<W> {
             mode,
 
             fieldnorm_reader,
+            bitset_doc_ids: Vec::new(),
             bm25_weight: None,
             avg_fieldnorm,
         }
     }
 
-    pub fn new_term(&mut self, term_doc_freq: u32) {
-        self.bm25_weight = None;
-
-        if !self.mode.has_freq() {
-            return;
-        }
-
-        let num_docs_in_segment: u64 =
-            if let Some(fieldnorm_reader) = self.fieldnorm_reader.as_ref() {
-                fieldnorm_reader.num_docs() as u64
-            } else {
-                return;
-            };
-
-        if num_docs_in_segment == 0 {
-            return;
-        }
-
-        self.bm25_weight = Some(Bm25Weight::for_one_term(
-            term_doc_freq as u64,
-            num_docs_in_segment,
-            self.avg_fieldnorm,
-        ));
-    }
-
     fn write_block(&mut self) {
         {
             // encode the doc ids
@@ -378,47 +465,105 @@ impl<W: Write> PostingsSerializer<W> {
                 let sum_freq = self.block.term_freqs().iter().cloned().sum();
                 self.skip_write.write_total_term_freq(sum_freq);
             }
-            let mut blockwand_params = (0u8, 0u32);
-            if let Some(bm25_weight) = self.bm25_weight.as_ref() {
-                if let Some(fieldnorm_reader) = self.fieldnorm_reader.as_ref() {
-                    let docs = self.block.doc_ids().iter().cloned();
-                    let term_freqs = self.block.term_freqs().iter().cloned();
-                    let fieldnorms = docs.map(|doc| fieldnorm_reader.fieldnorm_id(doc));
-                    blockwand_params = fieldnorms
-                        .zip(term_freqs)
-                        .max_by(
-                            |(left_fieldnorm_id, left_term_freq),
-                             (right_fieldnorm_id, right_term_freq)| {
-                                let left_score =
-                                    bm25_weight.tf_factor(*left_fieldnorm_id, *left_term_freq);
-                                let right_score =
-                                    bm25_weight.tf_factor(*right_fieldnorm_id, *right_term_freq);
-                                left_score
-                                    .partial_cmp(&right_score)
-                                    .unwrap_or(Ordering::Equal)
-                            },
-                        )
-                        .unwrap();
-                }
-            }
-            let (fieldnorm_id, term_freq) = blockwand_params;
+            let (fieldnorm_id, term_freq) = self.block_wand_params().unwrap_or((0u8, 0u32));
             self.skip_write.write_blockwand_max(fieldnorm_id, term_freq);
         }
         self.block.clear();
     }
 
-    pub fn write_doc(&mut self, doc_id: DocId, term_freq: u32) {
+    /// Returns the `(fieldnorm_id, term_freq)` pair of the posting of the current block
+    /// with the highest BM25 impact, or `None` if the impacts cannot be computed.
+    fn block_wand_params(&self) -> Option<(u8, u32)> {
+        let bm25_weight = self.bm25_weight.as_ref()?;
+        let fieldnorm_reader = self.fieldnorm_reader.as_ref()?;
+        let docs = self.block.doc_ids().iter().cloned();
+        let term_freqs = self.block.term_freqs().iter().cloned();
+        let fieldnorms = docs.map(|doc| fieldnorm_reader.fieldnorm_id(doc));
+        fieldnorms.zip(term_freqs).max_by(
+            |(left_fieldnorm_id, left_term_freq), (right_fieldnorm_id, right_term_freq)| {
+                let left_score = bm25_weight.tf_factor(*left_fieldnorm_id, *left_term_freq);
+                let right_score = bm25_weight.tf_factor(*right_fieldnorm_id, *right_term_freq);
+                left_score
+                    .partial_cmp(&right_score)
+                    .unwrap_or(Ordering::Equal)
+            },
+        )
+    }
+
+    /// Serializes the posting list of the current term as a bitset, if it is dense enough.
+    ///
+    /// It is encoded as an empty skip list, followed by the bitset of the documents as
+    /// little-endian `u64` words.
+    ///
+    /// Returns false if the posting list should be block encoded instead.
+    fn write_bitset(&mut self, output: &mut dyn io::Write) -> io::Result<bool> {
+        let Some(&last_doc) = self.bitset_doc_ids.last() else {
+            return Ok(false);
+        };
+        let num_docs = self.bitset_doc_ids.len() as u64;
+        if last_doc as u64 + 1 > num_docs * BITSET_POSTINGS_MAX_SPARSITY {
+            return Ok(false);
+        }
+        let mut words = vec![0u64; last_doc as usize / 64 + 1];
+        for &doc in &self.bitset_doc_ids {
+            words[doc as usize / 64] |= 1u64 << (doc % 64);
+        }
+        VInt(0).serialize(output)?;
+        for word in words {
+            output.write_all(&word.to_le_bytes())?;
+        }
+        self.bitset_doc_ids.clear();
+        Ok(true)
+    }
+}
+
+impl PostingsEncoder for PostingsSerializer {
+    fn new_term(&mut self, term_doc_freq: u32) {
+        self.block.clear();
+        self.last_doc_id_encoded = 0;
+        self.bm25_weight = None;
+
+        self.bitset_doc_ids.clear();
+
+        if !self.mode.has_freq() {
+            return;
+        }
+
+        let num_docs_in_segment: u64 =
+            if let Some(fieldnorm_reader) = self.fieldnorm_reader.as_ref() {
+                fieldnorm_reader.num_docs() as u64
+            } else {
+                return;
+            };
+
+        if num_docs_in_segment == 0 {
+            return;
+        }
+
+        self.bm25_weight = Some(Bm25Weight::for_one_term(
+            term_doc_freq as u64,
+            num_docs_in_segment,
+            self.avg_fieldnorm,
+        ));
+    }
+
+    fn write_doc(&mut self, doc_id: DocId, term_freq: u32) {
+        if self.mode == IndexRecordOption::Basic {
+            self.bitset_doc_ids.push(doc_id);
+        }
         self.block.append_doc(doc_id, term_freq);
         if self.block.is_full() {
             self.write_block();
         }
     }
 
-    fn close(mut self) -> io::Result<()> {
-        self.postings_write.flush()
-    }
-
-    pub fn close_term(&mut self, doc_freq: u32) -> io::Result<()> {
+    fn close_term(&mut self, doc_freq: u32, output: &mut dyn io::Write) -> io::Result<()> {
+        if doc_freq >= COMPRESSION_BLOCK_SIZE as u32 && self.write_bitset(output)? {
+            self.block.clear();
+            self.skip_write.clear();
+            self.postings_write.clear();
+            return Ok(());
+        }
         if !self.block.is_empty() {
             // we have doc ids waiting to be written
             // this happens when the number of doc ids is
@@ -438,33 +583,26 @@ impl<W: Write> PostingsSerializer<W> {
                     .block_encoder
                     .compress_vint_unsorted(self.block.term_freqs());
                 self.postings_write.write_all(block_encoded)?;
+                // If the posting list has a skip list, the block wand information of
+                // the last block is appended to it, so that its block max score does
+                // not have to be computed from the fieldnorms at search time.
+                if doc_freq >= COMPRESSION_BLOCK_SIZE as u32 {
+                    if let Some((fieldnorm_id, term_freq)) = self.block_wand_params() {
+                        self.skip_write.write_blockwand_max(fieldnorm_id, term_freq);
+                    }
+                }
             }
             self.block.clear();
         }
         if doc_freq >= COMPRESSION_BLOCK_SIZE as u32 {
             let skip_data = self.skip_write.data();
-            VInt(skip_data.len() as u64).serialize(&mut self.output_write)?;
-            self.output_write.write_all(skip_data)?;
+            VInt(skip_data.len() as u64).serialize(output)?;
+            output.write_all(skip_data)?;
         }
-        self.output_write.write_all(&self.postings_write[..])?;
+        output.write_all(&self.postings_write[..])?;
         self.skip_write.clear();
         self.postings_write.clear();
         self.bm25_weight = None;
         Ok(())
     }
-
-    /// Returns the number of bytes written in the postings write object
-    /// at this point.
-    /// When called before writing the postings of a term, this value is used as
-    /// start offset.
-    /// When called after writing the postings of a term, this value is used as a
-    /// end offset.
-    fn written_bytes(&self) -> u64 {
-        self.output_write.written_bytes()
-    }
-
-    fn clear(&mut self) {
-        self.block.clear();
-        self.last_doc_id_encoded = 0;
-    }
 }
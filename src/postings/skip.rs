@@ -77,6 +77,8 @@ pub(crate) struct SkipReader {
     remaining_docs: u32, // number of docs remaining, including the
     // documents in the current block.
     block_info: BlockInfo,
+    // Block wand information of the last VInt encoded block, if it was serialized.
+    vint_block_wand: Option<(u8, u32)>,
 
     position_offset: u64,
 }
@@ -113,6 +115,7 @@ impl SkipReader {
             owned_read: data,
             skip_info,
             block_info: BlockInfo::VInt { num_docs: doc_freq },
+            vint_block_wand: None,
             byte_offset: 0,
             remaining_docs: doc_freq,
             position_offset: 0u64,
@@ -132,6 +135,7 @@ impl SkipReader {
         self.last_doc_in_previous_block = 0u32;
         self.owned_read = data;
         self.block_info = BlockInfo::VInt { num_docs: doc_freq };
+        self.vint_block_wand = None;
         self.byte_offset = 0;
         self.remaining_docs = doc_freq;
         self.position_offset = 0u64;
@@ -142,16 +146,30 @@ impl SkipReader {
 
     // Returns the block max score for this block if available.
     //
-    // The block max score is available for all full bitpacked block,
-    // but no available for the last VInt encoded incomplete block.
+    // The block max score is available for all full bitpacked block.
+    // For the last VInt encoded incomplete block, it is only available if
+    // the posting list has a skip list, and was serialized with the block wand
+    // information of its last block.
     pub fn block_max_score(&self, bm25_weight: &Bm25Weight) -> Option<Score> {
+        self.block_wand_impact()
+            .map(|(fieldnorm_id, term_freq)| bm25_weight.score(fieldnorm_id, term_freq))
+    }
+
+    // Returns the `(fieldnorm_id, term_freq)` pair of the posting with the highest
+    // impact in this block, if available.
+    pub fn block_wand_impact(&self) -> Option<(u8, u32)> {
         match self.block_info {
             BlockInfo::BitPacked {
                 block_wand_fieldnorm_id,
                 block_wand_term_freq,
                 ..
-            } => Some(bm25_weight.score(block_wand_fieldnorm_id, block_wand_term_freq)),
-            BlockInfo::VInt { .. } => None,
+            } => Some((block_wand_fieldnorm_id, block_wand_term_freq)),
+            BlockInfo::VInt { num_docs } => {
+                if num_docs == 0 {
+                    return None;
+                }
+                self.vint_block_wand
+            }
         }
     }
 
@@ -197,7 +215,8 @@ impl SkipReader {
                     block_wand_term_freq,
                 };
             }
-            IndexRecordOption::WithFreqsAndPositions => {
+            IndexRecordOption::WithFreqsAndPositions
+            | IndexRecordOption::WithFreqsAndPositionsAndOffsets => {
                 let tf_num_bits = bytes[5];
                 let tf_sum = read_u32(&bytes[6..10]);
                 let block_wand_fieldnorm_id = bytes[10];
@@ -261,8 +280,25 @@ impl SkipReader {
             self.block_info = BlockInfo::VInt {
                 num_docs: self.remaining_docs,
             };
+            self.read_vint_block_wand();
         }
     }
+
+    // Reads the block wand information of the last VInt block, that follows the
+    // information of the bitpacked blocks.
+    //
+    // Posting lists serialized by older versions do not have it.
+    fn read_vint_block_wand(&mut self) {
+        if self.skip_info == IndexRecordOption::Basic || self.vint_block_wand.is_some() {
+            return;
+        }
+        let bytes = self.owned_read.as_slice();
+        if bytes.len() < 2 {
+            return;
+        }
+        self.vint_block_wand = Some((bytes[0], decode_block_wand_max_tf(bytes[1])));
+        self.owned_read.advance(2);
+    }
 }
 
 #[cfg(test)]
@@ -336,6 +372,28 @@ mod tests {
         assert_eq!(skip_reader.block_info(), BlockInfo::VInt { num_docs: 0u32 });
     }
 
+    #[test]
+    fn test_skip_with_last_block_wand() {
+        let buf = {
+            let mut skip_serializer = SkipSerializer::new();
+            skip_serializer.write_doc(1u32, 2u8);
+            skip_serializer.write_term_freq(3u8);
+            skip_serializer.write_blockwand_max(13u8, 3u32);
+            skip_serializer.write_blockwand_max(8u8, 300u32);
+            skip_serializer.data().to_owned()
+        };
+        let doc_freq = 3u32 + COMPRESSION_BLOCK_SIZE as u32;
+        let mut skip_reader =
+            SkipReader::new(OwnedBytes::new(buf), doc_freq, IndexRecordOption::WithFreqs);
+        assert_eq!(skip_reader.block_wand_impact(), Some((13u8, 3u32)));
+        skip_reader.advance();
+        assert_eq!(skip_reader.block_info(), BlockInfo::VInt { num_docs: 3u32 });
+        assert_eq!(skip_reader.block_wand_impact(), Some((8u8, u32::MAX)));
+        skip_reader.advance();
+        assert_eq!(skip_reader.block_info(), BlockInfo::VInt { num_docs: 0u32 });
+        assert_eq!(skip_reader.block_wand_impact(), None);
+    }
+
     #[test]
     fn test_skip_no_freq() {
         let buf = {
@@ -35,6 +35,10 @@ impl Weight for AllWeight {
         }
         Ok(Explanation::new("AllQuery", 1.0))
     }
+
+    fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
+        Ok(reader.num_docs())
+    }
 }
 
 /// Scorer associated with the `AllQuery` query.
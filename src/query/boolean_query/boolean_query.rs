@@ -1,5 +1,7 @@
 use super::boolean_weight::BooleanWeight;
-use crate::query::{EnableScoring, Occur, Query, SumWithCoordsCombiner, TermQuery, Weight};
+use crate::query::{
+    EnableScoring, Occur, Query, QueryLeaf, SumWithCoordsCombiner, TermQuery, Weight,
+};
 use crate::schema::{IndexRecordOption, Term};
 
 /// The boolean query returns a set of documents
@@ -160,6 +162,14 @@ impl Query for BooleanQuery {
             subquery.query_terms(visitor);
         }
     }
+
+    fn query_leaves<'a>(&'a self, visitor: &mut dyn FnMut(QueryLeaf<'a>)) {
+        for (occur, subquery) in &self.subqueries {
+            if *occur != Occur::MustNot {
+                subquery.query_leaves(visitor);
+            }
+        }
+    }
 }
 
 impl BooleanQuery {
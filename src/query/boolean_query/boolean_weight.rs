@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use common::BitSet;
+
 use crate::core::SegmentReader;
 use crate::docset::BUFFER_LEN;
 use crate::postings::FreqReadingOption;
@@ -104,6 +106,27 @@ impl<TScoreCombiner: ScoreCombiner> BooleanWeight<TScoreCombiner> {
         Ok(per_occur_scorers)
     }
 
+    /// Inserts the documents matching the clauses of the given occur in `bitset`.
+    fn fill_bitset(
+        &self,
+        occur: Occur,
+        reader: &SegmentReader,
+        bitset: &mut BitSet,
+    ) -> crate::Result<()> {
+        for (_, weight) in self
+            .weights
+            .iter()
+            .filter(|(clause_occur, _)| *clause_occur == occur)
+        {
+            weight.for_each_no_score(reader, &mut |docs| {
+                for &doc in docs {
+                    bitset.insert(doc);
+                }
+            })?;
+        }
+        Ok(())
+    }
+
     fn complex_scorer<TComplexScoreCombiner: ScoreCombiner>(
         &self,
         reader: &SegmentReader,
@@ -204,6 +227,64 @@ impl<TScoreCombiner: ScoreCombiner + Sync> Weight for BooleanWeight<TScoreCombin
         Ok(explanation)
     }
 
+    /// Counts the documents without scoring them.
+    ///
+    /// If some documents are excluded, the positive documents and the excluded documents are
+    /// gathered in two bitsets, and the excluded documents are removed from the positive ones
+    /// in a single pass, rather than seeking the excluded clauses for each positive document.
+    fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
+        if self.weights.len() == 1 {
+            let &(occur, ref weight) = &self.weights[0];
+            return if occur == Occur::MustNot {
+                Ok(0)
+            } else {
+                weight.count(reader)
+            };
+        }
+        let has_occur = |occur: Occur| {
+            self.weights
+                .iter()
+                .any(|(clause_occur, _)| *clause_occur == occur)
+        };
+        if !has_occur(Occur::MustNot) {
+            let mut scorer = self
+                .complex_scorer(reader, 1.0, DoNothingCombiner::default)
+                .map(|specialized_scorer| {
+                    into_box_scorer(specialized_scorer, DoNothingCombiner::default)
+                })?;
+            return Ok(if let Some(alive_bitset) = reader.alive_bitset() {
+                scorer.count(alive_bitset)
+            } else {
+                scorer.count_including_deleted()
+            });
+        }
+        let mut docs = BitSet::with_max_value(reader.max_doc());
+        if has_occur(Occur::Must) {
+            let must_scorers = self
+                .weights
+                .iter()
+                .filter(|(occur, _)| *occur == Occur::Must)
+                .map(|(_, weight)| weight.scorer(reader, 1.0))
+                .collect::<crate::Result<Vec<_>>>()?;
+            let mut intersection = intersect_scorers(must_scorers);
+            let mut buffer = [0u32; BUFFER_LEN];
+            for_each_docset_buffered(intersection.as_mut(), &mut buffer, |block: &[DocId]| {
+                for &doc in block {
+                    docs.insert(doc);
+                }
+            });
+        } else {
+            self.fill_bitset(Occur::Should, reader, &mut docs)?;
+        }
+        let mut excluded_docs = BitSet::with_max_value(reader.max_doc());
+        self.fill_bitset(Occur::MustNot, reader, &mut excluded_docs)?;
+        docs.difference_update(&excluded_docs);
+        if let Some(alive_bitset) = reader.alive_bitset() {
+            docs.intersect_update(alive_bitset.bitset());
+        }
+        Ok(docs.len() as u32)
+    }
+
     fn for_each(
         &self,
         reader: &SegmentReader,
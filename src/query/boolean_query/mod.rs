@@ -11,7 +11,7 @@ mod tests {
 
     use super::*;
     use crate::collector::tests::TEST_COLLECTOR_WITH_SCORE;
-    use crate::collector::TopDocs;
+    use crate::collector::{Count, DocSetCollector, TopDocs};
     use crate::query::score_combiner::SumWithCoordsCombiner;
     use crate::query::term_query::TermScorer;
     use crate::query::{
@@ -49,6 +49,32 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    pub fn test_boolean_count() -> crate::Result<()> {
+        let (index, text_field) = aux_test_helper()?;
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        let queries = [
+            "a", "+a +b", "a d", "+c -d", "a b -c", "+a -b -d", "(a d) -b", "+a b",
+        ];
+        let check_counts = |searcher: &crate::Searcher| -> crate::Result<()> {
+            for query_str in queries {
+                let query = query_parser.parse_query(query_str)?;
+                let expected = searcher.search(&query, &DocSetCollector)?.len();
+                assert_eq!(searcher.count(&query)?, expected, "{query_str}");
+                assert_eq!(searcher.search(&query, &Count)?, expected, "{query_str}");
+            }
+            Ok(())
+        };
+        check_counts(&index.reader()?.searcher())?;
+
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.delete_term(Term::from_field_text(text_field, "d"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.count(&query_parser.parse_query("+c -b")?)?, 1);
+        check_counts(&searcher)
+    }
+
     #[test]
     pub fn test_boolean_single_must_clause() -> crate::Result<()> {
         let (index, text_field) = aux_test_helper()?;
@@ -3,7 +3,7 @@ use std::fmt;
 use crate::docset::BUFFER_LEN;
 use crate::fastfield::AliveBitSet;
 use crate::query::explanation::does_not_match;
-use crate::query::{EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::query::{EnableScoring, Explanation, Query, QueryLeaf, Scorer, Weight};
 use crate::{DocId, DocSet, Score, SegmentReader, Term};
 
 /// `BoostQuery` is a wrapper over a query used to boost its score.
@@ -52,6 +52,10 @@ impl Query for BoostQuery {
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         self.query.query_terms(visitor)
     }
+
+    fn query_leaves<'a>(&'a self, visitor: &mut dyn FnMut(QueryLeaf<'a>)) {
+        self.query.query_leaves(visitor)
+    }
 }
 
 /// Weight associated to the BoostQuery.
@@ -0,0 +1,344 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use common::BitSet;
+use lru::LruCache;
+
+use crate::query::{
+    BitSetDocSet, ConstScorer, EnableScoring, Explanation, Query, QueryLeaf, Scorer, Weight,
+};
+use crate::store::CacheStats;
+use crate::{DocId, DocSet, Score, SegmentId, SegmentReader, TantivyError, Term, TERMINATED};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct FilterCacheKey {
+    segment_id: SegmentId,
+    fingerprint: String,
+}
+
+struct FilterCacheEntries {
+    lru: LruCache<FilterCacheKey, Arc<BitSet>>,
+    memory_budget: usize,
+    memory_usage: usize,
+    cache_hits: usize,
+    cache_misses: usize,
+}
+
+impl FilterCacheEntries {
+    fn evict_until_within_budget(&mut self) {
+        while self.memory_usage > self.memory_budget {
+            let Some((key, bitset)) = self.lru.pop_lru() else {
+                break;
+            };
+            self.memory_usage -= entry_num_bytes(&key, &bitset);
+        }
+    }
+}
+
+fn entry_num_bytes(key: &FilterCacheKey, bitset: &BitSet) -> usize {
+    let num_buckets = (bitset.max_value() as usize + 63) / 64;
+    num_buckets * 8 + key.fingerprint.len()
+}
+
+/// A cache of the documents matching filters, segment by segment.
+///
+/// The cache is shared by all of the [`CachedFilterQuery`] it is given to, and can be reused
+/// across searchers: a filter is only evaluated once per segment, as long as its bitset is not
+/// evicted. Filters are identified by the `Debug` representation of their query.
+///
+/// The cache is bounded by a memory budget, and the least recently used bitsets are evicted
+/// first. Bitsets of segments that are no longer searched are not dropped eagerly, and simply
+/// age out of the cache.
+#[derive(Clone)]
+pub struct FilterCache {
+    entries: Arc<Mutex<FilterCacheEntries>>,
+}
+
+impl FilterCache {
+    /// Creates a cache holding at most `memory_budget` bytes of bitsets.
+    pub fn with_memory_budget(memory_budget: usize) -> FilterCache {
+        FilterCache {
+            entries: Arc::new(Mutex::new(FilterCacheEntries {
+                lru: LruCache::unbounded(),
+                memory_budget,
+                memory_usage: 0,
+                cache_hits: 0,
+                cache_misses: 0,
+            })),
+        }
+    }
+
+    /// Returns the number of bytes used by the cached bitsets.
+    pub fn memory_usage(&self) -> usize {
+        self.entries.lock().unwrap().memory_usage
+    }
+
+    /// Returns the number of cached bitsets, and the hits and misses of the cache.
+    pub fn stats(&self) -> CacheStats {
+        let entries = self.entries.lock().unwrap();
+        CacheStats {
+            num_entries: entries.lru.len(),
+            cache_hits: entries.cache_hits,
+            cache_misses: entries.cache_misses,
+        }
+    }
+
+    /// Drops all of the cached bitsets.
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.lru.clear();
+        entries.memory_usage = 0;
+    }
+
+    fn get(&self, key: &FilterCacheKey) -> Option<Arc<BitSet>> {
+        let mut entries = self.entries.lock().unwrap();
+        let bitset_opt = entries.lru.get(key).cloned();
+        if bitset_opt.is_some() {
+            entries.cache_hits += 1;
+        } else {
+            entries.cache_misses += 1;
+        }
+        bitset_opt
+    }
+
+    fn put(&self, key: FilterCacheKey, bitset: Arc<BitSet>) {
+        let num_bytes = entry_num_bytes(&key, &bitset);
+        let mut entries = self.entries.lock().unwrap();
+        if num_bytes > entries.memory_budget {
+            return;
+        }
+        if let Some(previous) = entries.lru.put(key.clone(), bitset) {
+            entries.memory_usage -= entry_num_bytes(&key, &previous);
+        }
+        entries.memory_usage += num_bytes;
+        entries.evict_until_within_budget();
+    }
+}
+
+impl fmt::Debug for FilterCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let entries = self.entries.lock().unwrap();
+        f.debug_struct("FilterCache")
+            .field("memory_budget", &entries.memory_budget)
+            .field("memory_usage", &entries.memory_usage)
+            .finish()
+    }
+}
+
+/// `CachedFilterQuery` wraps a filter query, and caches the documents it matches in a
+/// [`FilterCache`].
+///
+/// The wrapped query is evaluated without scoring, and every matching document gets the same
+/// score. Since the bitsets survive deletes, the cached filters stay valid for all of the
+/// searchers of a segment.
+pub struct CachedFilterQuery {
+    query: Box<dyn Query>,
+    cache: FilterCache,
+}
+
+impl CachedFilterQuery {
+    /// Builds a cached filter query.
+    pub fn new(query: Box<dyn Query>, cache: FilterCache) -> CachedFilterQuery {
+        CachedFilterQuery { query, cache }
+    }
+}
+
+impl Clone for CachedFilterQuery {
+    fn clone(&self) -> Self {
+        CachedFilterQuery {
+            query: self.query.box_clone(),
+            cache: self.cache.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for CachedFilterQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CachedFilter({:?})", self.query)
+    }
+}
+
+impl Query for CachedFilterQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let disabled_scoring = match enable_scoring.searcher() {
+            Some(searcher) => EnableScoring::disabled_from_searcher(searcher),
+            None => EnableScoring::disabled_from_schema(enable_scoring.schema()),
+        };
+        let weight = self.query.weight(disabled_scoring)?;
+        Ok(Box::new(CachedFilterWeight {
+            weight,
+            fingerprint: format!("{:?}", self.query),
+            cache: self.cache.clone(),
+        }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.query.query_terms(visitor);
+    }
+
+    fn query_leaves<'a>(&'a self, visitor: &mut dyn FnMut(QueryLeaf<'a>)) {
+        self.query.query_leaves(visitor);
+    }
+}
+
+struct CachedFilterWeight {
+    weight: Box<dyn Weight>,
+    fingerprint: String,
+    cache: FilterCache,
+}
+
+impl CachedFilterWeight {
+    fn bitset(&self, reader: &SegmentReader) -> crate::Result<Arc<BitSet>> {
+        let key = FilterCacheKey {
+            segment_id: reader.segment_id(),
+            fingerprint: self.fingerprint.clone(),
+        };
+        if let Some(bitset) = self.cache.get(&key) {
+            return Ok(bitset);
+        }
+        let mut bitset = BitSet::with_max_value(reader.max_doc());
+        let mut scorer = self.weight.scorer(reader, 1.0)?;
+        let mut doc = scorer.doc();
+        while doc != TERMINATED {
+            bitset.insert(doc);
+            doc = scorer.advance();
+        }
+        let bitset = Arc::new(bitset);
+        self.cache.put(key, bitset.clone());
+        Ok(bitset)
+    }
+}
+
+impl Weight for CachedFilterWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let bitset = self.bitset(reader)?;
+        let docset = BitSetDocSet::from(BitSet::clone(&bitset));
+        Ok(Box::new(ConstScorer::new(docset, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Document #({doc}) does not match"
+            )));
+        }
+        Ok(Explanation::new("CachedFilter", 1.0))
+    }
+
+    fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
+        let bitset = self.bitset(reader)?;
+        if let Some(alive_bitset) = reader.alive_bitset() {
+            let mut docset = BitSetDocSet::from(BitSet::clone(&bitset));
+            Ok(docset.count(alive_bitset))
+        } else {
+            Ok(bitset.len() as u32)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CachedFilterQuery, FilterCache};
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{BooleanQuery, Occur, Query, RangeQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, INDEXED, STRING};
+    use crate::{doc, Index, Term};
+
+    #[test]
+    fn test_cached_filter_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING);
+        let timestamp = schema_builder.add_u64_field("timestamp", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for i in 0..100u64 {
+            let tenant_id = if i % 4 == 0 { "a" } else { "b" };
+            index_writer.add_document(doc!(tenant => tenant_id, timestamp => i))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let cache = FilterCache::with_memory_budget(1_000);
+        let tenant_filter = |tenant_id: &str| -> Box<dyn Query> {
+            Box::new(CachedFilterQuery::new(
+                Box::new(TermQuery::new(
+                    Term::from_field_text(tenant, tenant_id),
+                    IndexRecordOption::Basic,
+                )),
+                cache.clone(),
+            ))
+        };
+        let query = BooleanQuery::new(vec![
+            (Occur::Must, tenant_filter("a")),
+            (
+                Occur::Must,
+                Box::new(RangeQuery::new_u64("timestamp".to_string(), 0..50)),
+            ),
+        ]);
+        assert_eq!(searcher.search(&query, &Count)?, 13);
+        assert_eq!(searcher.search(&*tenant_filter("a"), &Count)?, 25);
+        let top_docs = searcher.search(&*tenant_filter("a"), &TopDocs::with_limit(3))?;
+        assert_eq!(top_docs.len(), 3);
+        assert_eq!(top_docs[0].0, 1.0);
+        let stats = cache.stats();
+        assert_eq!(
+            (stats.num_entries, stats.cache_hits, stats.cache_misses),
+            (1, 2, 1)
+        );
+        let term_query =
+            TermQuery::new(Term::from_field_text(tenant, "a"), IndexRecordOption::Basic);
+        assert_eq!(cache.memory_usage(), 16 + format!("{term_query:?}").len());
+
+        let explanation = tenant_filter("a").explain(&searcher, top_docs[0].1)?;
+        assert_eq!(explanation.value(), 1.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_cache_eviction() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for tenant_id in ["a", "b", "c"] {
+            index_writer.add_document(doc!(tenant => tenant_id))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let term_query = |tenant_id: &str| {
+            TermQuery::new(
+                Term::from_field_text(tenant, tenant_id),
+                IndexRecordOption::Basic,
+            )
+        };
+        let entry_num_bytes = 8 + format!("{:?}", term_query("a")).len();
+
+        // Only two bitsets fit in the budget.
+        let cache = FilterCache::with_memory_budget(2 * entry_num_bytes);
+        for tenant_id in ["a", "b", "a", "c"] {
+            let query = CachedFilterQuery::new(Box::new(term_query(tenant_id)), cache.clone());
+            assert_eq!(searcher.search(&query, &Count)?, 1);
+        }
+        let stats = cache.stats();
+        assert_eq!(
+            (stats.num_entries, stats.cache_hits, stats.cache_misses),
+            (2, 1, 3)
+        );
+        assert_eq!(cache.memory_usage(), 2 * entry_num_bytes);
+        // "b" was the least recently used filter.
+        let query = CachedFilterQuery::new(Box::new(term_query("a")), cache.clone());
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        let query = CachedFilterQuery::new(Box::new(term_query("b")), cache.clone());
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        assert_eq!(cache.stats().cache_hits, 2);
+
+        // Bitsets larger than the budget are never cached.
+        let cache = FilterCache::with_memory_budget(1);
+        let query = CachedFilterQuery::new(Box::new(term_query("a")), cache.clone());
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        assert_eq!(cache.stats().num_entries, 0);
+        assert_eq!(cache.memory_usage(), 0);
+        Ok(())
+    }
+}
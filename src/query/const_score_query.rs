@@ -1,7 +1,7 @@
 use std::fmt;
 
 use crate::docset::BUFFER_LEN;
-use crate::query::{EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::query::{EnableScoring, Explanation, Query, QueryLeaf, Scorer, Weight};
 use crate::{DocId, DocSet, Score, SegmentReader, TantivyError, Term};
 
 /// `ConstScoreQuery` is a wrapper over a query to provide a constant score.
@@ -49,6 +49,10 @@ impl Query for ConstScoreQuery {
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         self.query.query_terms(visitor);
     }
+
+    fn query_leaves<'a>(&'a self, visitor: &mut dyn FnMut(QueryLeaf<'a>)) {
+        self.query.query_leaves(visitor);
+    }
 }
 
 struct ConstWeight {
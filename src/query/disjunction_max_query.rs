@@ -1,4 +1,6 @@
-use crate::query::{BooleanWeight, DisjunctionMaxCombiner, EnableScoring, Occur, Query, Weight};
+use crate::query::{
+    BooleanWeight, DisjunctionMaxCombiner, EnableScoring, Occur, Query, QueryLeaf, Weight,
+};
 use crate::{Score, Term};
 
 /// The disjunction max query returns documents matching one or more wrapped queries,
@@ -108,6 +110,12 @@ impl Query for DisjunctionMaxQuery {
             disjunct.query_terms(visitor);
         }
     }
+
+    fn query_leaves<'a>(&'a self, visitor: &mut dyn FnMut(QueryLeaf<'a>)) {
+        for disjunct in &self.disjuncts {
+            disjunct.query_leaves(visitor);
+        }
+    }
 }
 
 impl DisjunctionMaxQuery {
@@ -2,7 +2,7 @@ use levenshtein_automata::{Distance, LevenshteinAutomatonBuilder, DFA};
 use once_cell::sync::OnceCell;
 use tantivy_fst::Automaton;
 
-use crate::query::{AutomatonWeight, EnableScoring, Query, Weight};
+use crate::query::{AutomatonWeight, EnableScoring, Query, QueryLeaf, Weight};
 use crate::schema::Term;
 use crate::TantivyError::InvalidArgument;
 
@@ -109,7 +109,17 @@ impl FuzzyTermQuery {
         }
     }
 
+    /// Returns the term of the query.
+    pub fn term(&self) -> &Term {
+        &self.term
+    }
+
     fn specialized_weight(&self) -> crate::Result<AutomatonWeight<DfaWrapper>> {
+        Ok(AutomatonWeight::new(self.term.field(), self.automaton()?))
+    }
+
+    /// Returns the automaton matching the terms within the distance of the term of the query.
+    pub(crate) fn automaton(&self) -> crate::Result<DfaWrapper> {
         static AUTOMATON_BUILDER: [[OnceCell<LevenshteinAutomatonBuilder>; 2]; 3] = [
             [OnceCell::new(), OnceCell::new()],
             [OnceCell::new(), OnceCell::new()],
@@ -140,10 +150,7 @@ impl FuzzyTermQuery {
         } else {
             automaton_builder.build_dfa(term_text)
         };
-        Ok(AutomatonWeight::new(
-            self.term.field(),
-            DfaWrapper(automaton),
-        ))
+        Ok(DfaWrapper(automaton))
     }
 }
 
@@ -151,6 +158,10 @@ impl Query for FuzzyTermQuery {
     fn weight(&self, _enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
         Ok(Box::new(self.specialized_weight()?))
     }
+
+    fn query_leaves<'a>(&'a self, visitor: &mut dyn FnMut(QueryLeaf<'a>)) {
+        visitor(QueryLeaf::Fuzzy(self));
+    }
 }
 
 #[cfg(test)]
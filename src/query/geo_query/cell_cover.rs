@@ -0,0 +1,275 @@
+use std::f64::consts::PI;
+use std::ops::RangeInclusive;
+
+use crate::schema::{dequantize_lat, dequantize_lon, interleave, GeoPoint, EARTH_RADIUS_METERS};
+
+/// Maximum number of ranges of `u64` values covering the area of a geo query.
+pub(crate) const MAX_COVER_RANGES: usize = 64;
+
+/// A rectangle in latitude and longitude, in degrees, with `min_lon <= max_lon`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct LatLonRect {
+    pub min_lat: f64,
+    pub max_lat: f64,
+    pub min_lon: f64,
+    pub max_lon: f64,
+}
+
+impl LatLonRect {
+    pub const WORLD: LatLonRect = LatLonRect {
+        min_lat: -90.0,
+        max_lat: 90.0,
+        min_lon: -180.0,
+        max_lon: 180.0,
+    };
+
+    pub fn contains(&self, point: &GeoPoint) -> bool {
+        (self.min_lat..=self.max_lat).contains(&point.lat)
+            && (self.min_lon..=self.max_lon).contains(&point.lon)
+    }
+
+    pub fn intersects(&self, other: &LatLonRect) -> bool {
+        self.min_lat <= other.max_lat
+            && other.min_lat <= self.max_lat
+            && self.min_lon <= other.max_lon
+            && other.min_lon <= self.max_lon
+    }
+
+    pub fn is_within(&self, other: &LatLonRect) -> bool {
+        other.min_lat <= self.min_lat
+            && self.max_lat <= other.max_lat
+            && other.min_lon <= self.min_lon
+            && self.max_lon <= other.max_lon
+    }
+
+    pub fn corners(&self) -> [GeoPoint; 4] {
+        [
+            GeoPoint {
+                lat: self.min_lat,
+                lon: self.min_lon,
+            },
+            GeoPoint {
+                lat: self.min_lat,
+                lon: self.max_lon,
+            },
+            GeoPoint {
+                lat: self.max_lat,
+                lon: self.min_lon,
+            },
+            GeoPoint {
+                lat: self.max_lat,
+                lon: self.max_lon,
+            },
+        ]
+    }
+}
+
+/// Returns the rectangles bounding the points within `distance` meters of `center`.
+///
+/// There are two rectangles when the circle crosses the antimeridian.
+pub(crate) fn circle_bounding_rects(center: &GeoPoint, distance: f64) -> Vec<LatLonRect> {
+    let angular_distance = distance / EARTH_RADIUS_METERS;
+    if angular_distance >= PI {
+        return vec![LatLonRect::WORLD];
+    }
+    let lat = center.lat.to_radians();
+    let min_lat = lat - angular_distance;
+    let max_lat = lat + angular_distance;
+    if min_lat <= -PI / 2.0 || max_lat >= PI / 2.0 {
+        // The circle contains a pole, hence points of all longitudes.
+        return vec![LatLonRect {
+            min_lat: min_lat.to_degrees().max(-90.0),
+            max_lat: max_lat.to_degrees().min(90.0),
+            min_lon: -180.0,
+            max_lon: 180.0,
+        }];
+    }
+    let delta_lon = (angular_distance.sin() / lat.cos())
+        .min(1.0)
+        .asin()
+        .to_degrees();
+    let (min_lat, max_lat) = (min_lat.to_degrees(), max_lat.to_degrees());
+    let min_lon = center.lon - delta_lon;
+    let max_lon = center.lon + delta_lon;
+    let rect = |min_lon: f64, max_lon: f64| LatLonRect {
+        min_lat,
+        max_lat,
+        min_lon,
+        max_lon,
+    };
+    if min_lon < -180.0 {
+        vec![rect(min_lon + 360.0, 180.0), rect(-180.0, max_lon)]
+    } else if max_lon > 180.0 {
+        vec![rect(min_lon, 180.0), rect(-180.0, max_lon - 360.0)]
+    } else {
+        vec![rect(min_lon, max_lon)]
+    }
+}
+
+/// The relation between a cell of the Z-order curve and the area of a geo query.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CellRelation {
+    /// No point of the cell is within the area.
+    Disjoint,
+    /// All of the points of the cell are likely within the area: the cell is not refined any
+    /// further.
+    Within,
+    /// Some points of the cell may be within the area.
+    Crosses,
+}
+
+/// A cell of the Z-order curve: the points of which the quantized latitude and longitude share
+/// their `level` most significant bits.
+#[derive(Clone, Copy)]
+struct Cell {
+    level: u32,
+    lat: u32,
+    lon: u32,
+}
+
+impl Cell {
+    fn side(&self) -> u64 {
+        1u64 << (32 - self.level)
+    }
+
+    fn rect(&self) -> LatLonRect {
+        LatLonRect {
+            min_lat: dequantize_lat(self.lat as u64),
+            max_lat: dequantize_lat(self.lat as u64 + self.side()),
+            min_lon: dequantize_lon(self.lon as u64),
+            max_lon: dequantize_lon(self.lon as u64 + self.side()),
+        }
+    }
+
+    /// Returns the `u64` values of the points of the cell.
+    fn range(&self) -> RangeInclusive<u64> {
+        let start = interleave(self.lat, self.lon);
+        let len_minus_one = u64::MAX.checked_shr(2 * self.level).unwrap_or(0);
+        start..=start + len_minus_one
+    }
+
+    fn children(&self) -> impl Iterator<Item = Cell> + '_ {
+        let half_side = (self.side() / 2) as u32;
+        [(0, 0), (0, 1), (1, 0), (1, 1)]
+            .into_iter()
+            .map(move |(lat, lon)| Cell {
+                level: self.level + 1,
+                lat: self.lat + lat * half_side,
+                lon: self.lon + lon * half_side,
+            })
+    }
+}
+
+/// Returns sorted, disjoint ranges of `u64` values covering all of the points of the area,
+/// given the relation of a cell to the area.
+///
+/// Cells crossing the area are refined breadth-first, as long as the number of ranges stays
+/// below `max_ranges`. The ranges may include points outside of the area, which need to be
+/// checked against the area.
+pub(crate) fn cover_ranges(
+    relation: impl Fn(&LatLonRect) -> CellRelation,
+    max_ranges: usize,
+) -> Vec<RangeInclusive<u64>> {
+    let mut ranges: Vec<RangeInclusive<u64>> = Vec::new();
+    let mut crossing_cells = vec![Cell {
+        level: 0,
+        lat: 0,
+        lon: 0,
+    }];
+    while let Some(cell) = crossing_cells.first() {
+        if cell.level == 32 || ranges.len() + 4 * crossing_cells.len() > max_ranges {
+            break;
+        }
+        let mut children_cells = Vec::new();
+        for cell in &crossing_cells {
+            for child in cell.children() {
+                match relation(&child.rect()) {
+                    CellRelation::Disjoint => {}
+                    CellRelation::Within => ranges.push(child.range()),
+                    CellRelation::Crosses => children_cells.push(child),
+                }
+            }
+        }
+        crossing_cells = children_cells;
+    }
+    ranges.extend(crossing_cells.iter().map(Cell::range));
+    ranges.sort_by_key(|range| *range.start());
+    let mut merged_ranges: Vec<RangeInclusive<u64>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        if let Some(last_range) = merged_ranges.last_mut() {
+            if last_range.end().checked_add(1) == Some(*range.start()) {
+                *last_range = *last_range.start()..=*range.end();
+                continue;
+            }
+        }
+        merged_ranges.push(range);
+    }
+    merged_ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{circle_bounding_rects, cover_ranges, CellRelation, LatLonRect};
+    use crate::schema::GeoPoint;
+
+    #[test]
+    fn test_circle_bounding_rects() {
+        let paris = GeoPoint::new(48.8566, 2.3522).unwrap();
+        let rects = circle_bounding_rects(&paris, 10_000.0);
+        assert_eq!(rects.len(), 1);
+        assert!((rects[0].max_lat - rects[0].min_lat - 0.18).abs() < 0.001);
+        assert!(rects[0].min_lon < 2.22 && rects[0].max_lon > 2.48);
+
+        let fiji = GeoPoint::new(-17.7, 179.9).unwrap();
+        let rects = circle_bounding_rects(&fiji, 100_000.0);
+        assert_eq!(rects.len(), 2);
+        assert_eq!(rects[0].max_lon, 180.0);
+        assert_eq!(rects[1].min_lon, -180.0);
+
+        let north_pole = GeoPoint::new(89.9, 0.0).unwrap();
+        let rects = circle_bounding_rects(&north_pole, 100_000.0);
+        assert_eq!(rects.len(), 1);
+        assert_eq!((rects[0].min_lon, rects[0].max_lon), (-180.0, 180.0));
+        assert_eq!(rects[0].max_lat, 90.0);
+    }
+
+    #[test]
+    fn test_cover_ranges() {
+        let area = LatLonRect {
+            min_lat: 10.0,
+            max_lat: 10.5,
+            min_lon: 20.0,
+            max_lon: 21.0,
+        };
+        let relation = |rect: &LatLonRect| {
+            if !rect.intersects(&area) {
+                CellRelation::Disjoint
+            } else if rect.is_within(&area) {
+                CellRelation::Within
+            } else {
+                CellRelation::Crosses
+            }
+        };
+        let ranges = cover_ranges(relation, 64);
+        assert!(ranges.len() <= 64);
+        assert!(ranges
+            .windows(2)
+            .all(|ranges| ranges[0].end() < ranges[1].start()));
+        let is_covered = |point: GeoPoint| {
+            let val = point.to_u64();
+            ranges.iter().any(|range| range.contains(&val))
+        };
+        for lat in 0..=10 {
+            for lon in 0..=10 {
+                let point = GeoPoint::new(10.0 + lat as f64 * 0.05, 20.0 + lon as f64 * 0.1);
+                assert!(is_covered(point.unwrap()));
+            }
+        }
+        assert!(!is_covered(GeoPoint::new(-10.0, 20.5).unwrap()));
+        assert!(!is_covered(GeoPoint::new(10.2, 25.0).unwrap()));
+        assert_eq!(
+            cover_ranges(|_| CellRelation::Crosses, 64),
+            vec![0..=u64::MAX]
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use super::cell_cover::{cover_ranges, CellRelation, LatLonRect, MAX_COVER_RANGES};
+use super::geo_weight::{GeoFilter, GeoWeight};
+use crate::query::{EnableScoring, Query, Weight};
+use crate::schema::{Field, FieldType, GeoPoint};
+use crate::TantivyError;
+
+/// `GeoBoundingBoxQuery` matches the documents with a point of a geo point field within a
+/// rectangle of latitudes and longitudes, e.g. the viewport of a map.
+///
+/// The rectangle is given by its top left (north west) and bottom right (south east) corners.
+/// If the longitude of the top left corner is greater than the one of the bottom right corner,
+/// the rectangle crosses the antimeridian.
+///
+/// The candidate points are selected as for a
+/// [`GeoDistanceQuery`](crate::query::GeoDistanceQuery), then checked against the rectangle.
+/// All of the matching documents get a score of 1.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::GeoBoundingBoxQuery;
+/// use tantivy::schema::{GeoPoint, GeoPointOptions, Schema};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let location = schema_builder.add_geo_point_field("location", GeoPointOptions::default());
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+/// // The Eiffel tower and the Tower of London.
+/// index_writer.add_document(doc!(location => GeoPoint { lat: 48.8584, lon: 2.2945 }))?;
+/// index_writer.add_document(doc!(location => GeoPoint { lat: 51.5081, lon: -0.0759 }))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = GeoBoundingBoxQuery::new(
+///     location,
+///     GeoPoint { lat: 48.9, lon: 2.2 },
+///     GeoPoint { lat: 48.8, lon: 2.5 },
+/// );
+/// assert_eq!(searcher.search(&query, &Count)?, 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct GeoBoundingBoxQuery {
+    field: Field,
+    top_left: GeoPoint,
+    bottom_right: GeoPoint,
+}
+
+impl GeoBoundingBoxQuery {
+    /// Creates a query matching the documents with a point within the rectangle of the given
+    /// top left and bottom right corners.
+    pub fn new(field: Field, top_left: GeoPoint, bottom_right: GeoPoint) -> GeoBoundingBoxQuery {
+        GeoBoundingBoxQuery {
+            field,
+            top_left,
+            bottom_right,
+        }
+    }
+
+    /// Returns the geo point field searched by the query.
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    /// Returns the top left corner of the rectangle.
+    pub fn top_left(&self) -> GeoPoint {
+        self.top_left
+    }
+
+    /// Returns the bottom right corner of the rectangle.
+    pub fn bottom_right(&self) -> GeoPoint {
+        self.bottom_right
+    }
+
+    /// Returns the rectangle, split in two at the antimeridian if it crosses it.
+    fn rects(&self) -> Vec<LatLonRect> {
+        let rect = |min_lon: f64, max_lon: f64| LatLonRect {
+            min_lat: self.bottom_right.lat,
+            max_lat: self.top_left.lat,
+            min_lon,
+            max_lon,
+        };
+        if self.top_left.lon <= self.bottom_right.lon {
+            vec![rect(self.top_left.lon, self.bottom_right.lon)]
+        } else {
+            vec![
+                rect(self.top_left.lon, 180.0),
+                rect(-180.0, self.bottom_right.lon),
+            ]
+        }
+    }
+}
+
+impl Query for GeoBoundingBoxQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let field_entry = enable_scoring.schema().get_field_entry(self.field);
+        if !matches!(field_entry.field_type(), FieldType::GeoPoint(_)) {
+            return Err(TantivyError::SchemaError(format!(
+                "GeoBoundingBoxQuery requires a geo point field, but {:?} is not one",
+                field_entry.name()
+            )));
+        }
+        if self.top_left.lat < self.bottom_right.lat {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The top left corner {} of the bounding box is below its bottom right corner {}",
+                self.top_left, self.bottom_right
+            )));
+        }
+        let rects = self.rects();
+        let relation = |cell_rect: &LatLonRect| {
+            if rects.iter().any(|rect| cell_rect.is_within(rect)) {
+                CellRelation::Within
+            } else if rects.iter().any(|rect| cell_rect.intersects(rect)) {
+                CellRelation::Crosses
+            } else {
+                CellRelation::Disjoint
+            }
+        };
+        let ranges = cover_ranges(relation, MAX_COVER_RANGES);
+        Ok(Box::new(GeoWeight::new(
+            self.field,
+            ranges,
+            GeoBoundingBoxFilter { rects },
+        )))
+    }
+}
+
+struct GeoBoundingBoxFilter {
+    rects: Vec<LatLonRect>,
+}
+
+impl GeoFilter for GeoBoundingBoxFilter {
+    fn matches(&self, point: &GeoPoint) -> bool {
+        self.rects.iter().any(|rect| rect.contains(point))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GeoBoundingBoxQuery;
+    use crate::collector::Count;
+    use crate::schema::{GeoPoint, GeoPointOptions, Schema, INDEXED};
+    use crate::Index;
+
+    #[test]
+    fn test_geo_bounding_box_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let location = schema_builder.add_geo_point_field("location", GeoPointOptions::default());
+        let indexed_location = schema_builder
+            .add_geo_point_field("indexed_location", GeoPointOptions::default().set_points());
+        let id = schema_builder.add_u64_field("id", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        // A grid of points every 0.5 degree, around the antimeridian.
+        let mut points = Vec::new();
+        for lat in -10..=10 {
+            for lon in -10..=10 {
+                let lon = lon as f64 / 2.0 + 180.25;
+                let lon = if lon > 180.0 { lon - 360.0 } else { lon };
+                points.push(GeoPoint {
+                    lat: lat as f64 / 2.0 + 0.25,
+                    lon,
+                });
+            }
+        }
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            for point in &points {
+                index_writer.add_document(
+                    doc!(location => *point, indexed_location => *point, id => 0u64),
+                )?;
+            }
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        for (top_left, bottom_right) in [
+            (
+                GeoPoint {
+                    lat: 2.0,
+                    lon: 177.0,
+                },
+                GeoPoint {
+                    lat: -1.0,
+                    lon: -178.0,
+                },
+            ),
+            (
+                GeoPoint {
+                    lat: 4.0,
+                    lon: 176.0,
+                },
+                GeoPoint {
+                    lat: 3.0,
+                    lon: 179.0,
+                },
+            ),
+            (
+                GeoPoint {
+                    lat: 90.0,
+                    lon: -180.0,
+                },
+                GeoPoint {
+                    lat: -90.0,
+                    lon: 180.0,
+                },
+            ),
+            (
+                GeoPoint { lat: 0.0, lon: 0.0 },
+                GeoPoint { lat: 0.0, lon: 0.0 },
+            ),
+        ] {
+            let query = GeoBoundingBoxQuery::new(location, top_left, bottom_right);
+            let expected = points
+                .iter()
+                .filter(|point| query.rects().iter().any(|rect| rect.contains(point)))
+                .count();
+            assert_eq!(searcher.search(&query, &Count)?, expected);
+            let indexed_query = GeoBoundingBoxQuery::new(indexed_location, top_left, bottom_right);
+            assert_eq!(searcher.search(&indexed_query, &Count)?, expected);
+        }
+        // 2 latitudes and 10 longitudes.
+        let query = GeoBoundingBoxQuery::new(
+            location,
+            GeoPoint {
+                lat: 1.0,
+                lon: 177.0,
+            },
+            GeoPoint {
+                lat: 0.0,
+                lon: -178.0,
+            },
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 2 * 10);
+
+        let invalid_query = GeoBoundingBoxQuery::new(
+            location,
+            GeoPoint { lat: 0.0, lon: 0.0 },
+            GeoPoint { lat: 1.0, lon: 1.0 },
+        );
+        assert!(searcher.search(&invalid_query, &Count).is_err());
+        let id_query = GeoBoundingBoxQuery::new(
+            id,
+            GeoPoint { lat: 1.0, lon: 0.0 },
+            GeoPoint { lat: 0.0, lon: 1.0 },
+        );
+        assert!(searcher.search(&id_query, &Count).is_err());
+        Ok(())
+    }
+}
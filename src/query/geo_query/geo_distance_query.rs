@@ -0,0 +1,302 @@
+use std::ops::RangeInclusive;
+
+use super::cell_cover::{
+    circle_bounding_rects, cover_ranges, CellRelation, LatLonRect, MAX_COVER_RANGES,
+};
+use super::geo_weight::{GeoFilter, GeoWeight};
+use crate::query::{EnableScoring, Query, Weight};
+use crate::schema::{Field, FieldType, GeoPoint};
+use crate::TantivyError;
+
+/// `GeoDistanceQuery` matches the documents with a point of a geo point field within a given
+/// distance of a center point.
+///
+/// The area of the circle is covered by a few ranges of cells of the Z-order curve the points
+/// are encoded with (see [`GeoPoint`]). The candidate points within these ranges are read from
+/// the points index of the field if it has one, or else from its fast field, and their
+/// great-circle distance to the center is then checked.
+///
+/// All of the matching documents get a score of 1.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::GeoDistanceQuery;
+/// use tantivy::schema::{GeoPoint, GeoPointOptions, Schema};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let location = schema_builder.add_geo_point_field("location", GeoPointOptions::default());
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+/// // The Eiffel tower, the Arc de Triomphe and the Tower of London.
+/// index_writer.add_document(doc!(location => GeoPoint { lat: 48.8584, lon: 2.2945 }))?;
+/// index_writer.add_document(doc!(location => GeoPoint { lat: 48.8738, lon: 2.2950 }))?;
+/// index_writer.add_document(doc!(location => GeoPoint { lat: 51.5081, lon: -0.0759 }))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let notre_dame = GeoPoint { lat: 48.8530, lon: 2.3499 };
+/// let query = GeoDistanceQuery::new(location, notre_dame, 10_000.0);
+/// assert_eq!(searcher.search(&query, &Count)?, 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct GeoDistanceQuery {
+    field: Field,
+    center: GeoPoint,
+    distance: f64,
+}
+
+impl GeoDistanceQuery {
+    /// Creates a query matching the documents with a point within `distance` meters of
+    /// `center`.
+    pub fn new(field: Field, center: GeoPoint, distance: f64) -> GeoDistanceQuery {
+        GeoDistanceQuery {
+            field,
+            center,
+            distance,
+        }
+    }
+
+    /// Returns the geo point field searched by the query.
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    /// Returns the center of the circle.
+    pub fn center(&self) -> GeoPoint {
+        self.center
+    }
+
+    /// Returns the radius of the circle, in meters.
+    pub fn distance(&self) -> f64 {
+        self.distance
+    }
+
+    /// Returns the ranges of `u64` values covering the circle.
+    fn cover_ranges(&self) -> Vec<RangeInclusive<u64>> {
+        if self.distance.is_nan() || self.distance < 0.0 {
+            return Vec::new();
+        }
+        let bounding_rects = circle_bounding_rects(&self.center, self.distance);
+        let relation = |cell_rect: &LatLonRect| {
+            let Some(bounding_rect) = bounding_rects
+                .iter()
+                .find(|bounding_rect| bounding_rect.intersects(cell_rect))
+            else {
+                return CellRelation::Disjoint;
+            };
+            // Checking the corners is only a heuristic, since the parallels are not great
+            // circles, to stop refining a cell. The points of the cell are all checked anyway.
+            if cell_rect.is_within(bounding_rect)
+                && cell_rect
+                    .corners()
+                    .iter()
+                    .all(|corner| self.center.distance(corner) <= self.distance)
+            {
+                CellRelation::Within
+            } else {
+                CellRelation::Crosses
+            }
+        };
+        cover_ranges(relation, MAX_COVER_RANGES)
+    }
+}
+
+impl Query for GeoDistanceQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let field_entry = enable_scoring.schema().get_field_entry(self.field);
+        if !matches!(field_entry.field_type(), FieldType::GeoPoint(_)) {
+            return Err(TantivyError::SchemaError(format!(
+                "GeoDistanceQuery requires a geo point field, but {:?} is not one",
+                field_entry.name()
+            )));
+        }
+        Ok(Box::new(GeoWeight::new(
+            self.field,
+            self.cover_ranges(),
+            GeoDistanceFilter {
+                center: self.center,
+                distance: self.distance,
+            },
+        )))
+    }
+}
+
+struct GeoDistanceFilter {
+    center: GeoPoint,
+    distance: f64,
+}
+
+impl GeoFilter for GeoDistanceFilter {
+    fn matches(&self, point: &GeoPoint) -> bool {
+        self.center.distance(point) <= self.distance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GeoDistanceQuery;
+    use crate::collector::{Count, DocSetCollector};
+    use crate::query::Query;
+    use crate::schema::{GeoPoint, GeoPointOptions, Schema};
+    use crate::{DocAddress, Index, Term};
+
+    fn test_geo_distance_query_aux(geo_point_options: GeoPointOptions) -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let location = schema_builder.add_geo_point_field("location", geo_point_options);
+        let id = schema_builder.add_u64_field("id", crate::schema::INDEXED);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        // A grid of points every 0.1 degree, around the antimeridian.
+        let mut points = Vec::new();
+        for lat in -20..=20 {
+            for lon in -20..=20 {
+                let lon = lon as f64 / 10.0 + 180.0;
+                let lon = if lon > 180.0 { lon - 360.0 } else { lon };
+                points.push(GeoPoint {
+                    lat: lat as f64 / 10.0,
+                    lon,
+                });
+            }
+        }
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            for (doc_id, point) in points.iter().enumerate() {
+                index_writer.add_document(doc!(location => *point, id => doc_id as u64))?;
+                if doc_id == 800 {
+                    index_writer.commit()?;
+                }
+            }
+            // A document without any point, and a document with two points.
+            index_writer.add_document(doc!(id => 10_000u64))?;
+            index_writer.add_document(doc!(
+                id => 10_001u64,
+                location => GeoPoint { lat: 45.0, lon: 0.0 },
+                location => GeoPoint { lat: 0.0, lon: 180.0 },
+            ))?;
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let check = |center: GeoPoint, distance: f64| -> crate::Result<()> {
+            let query = GeoDistanceQuery::new(location, center, distance);
+            let expected = points
+                .iter()
+                .filter(|point| center.distance(point) <= distance)
+                .count()
+                + usize::from(
+                    center.distance(&GeoPoint {
+                        lat: 0.0,
+                        lon: 180.0,
+                    }) <= distance,
+                );
+            assert_eq!(searcher.search(&query, &Count)?, expected);
+            Ok(())
+        };
+        check(
+            GeoPoint {
+                lat: 0.0,
+                lon: 180.0,
+            },
+            50_000.0,
+        )?;
+        check(
+            GeoPoint {
+                lat: 0.5,
+                lon: -179.7,
+            },
+            120_000.0,
+        )?;
+        check(
+            GeoPoint {
+                lat: 1.0,
+                lon: 179.0,
+            },
+            1.0,
+        )?;
+        check(
+            GeoPoint {
+                lat: -1.5,
+                lon: 178.5,
+            },
+            300_000.0,
+        )?;
+        check(
+            GeoPoint {
+                lat: 10.0,
+                lon: 0.0,
+            },
+            100_000.0,
+        )?;
+        check(
+            GeoPoint {
+                lat: 0.0,
+                lon: 180.0,
+            },
+            30_000_000.0,
+        )?;
+        check(
+            GeoPoint {
+                lat: 0.0,
+                lon: 180.0,
+            },
+            -1.0,
+        )?;
+
+        let query = GeoDistanceQuery::new(
+            location,
+            GeoPoint {
+                lat: 45.0,
+                lon: 0.0,
+            },
+            1.0,
+        );
+        let docs = searcher.search(&query, &DocSetCollector)?;
+        assert_eq!(docs.len(), 1);
+        let doc_address: DocAddress = docs.into_iter().next().unwrap();
+        let explanation = query.explain(&searcher, doc_address)?;
+        assert_eq!(explanation.value(), 1.0);
+        assert!(query
+            .explain(&searcher, DocAddress::new(doc_address.segment_ord, 0))
+            .is_err());
+
+        let id_query = GeoDistanceQuery::new(id, GeoPoint { lat: 0.0, lon: 0.0 }, 1.0);
+        assert!(searcher.search(&id_query, &Count).is_err());
+
+        // The points are still found after a merge and a deletion.
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.delete_term(Term::from_field_u64(id, 10_001));
+            let segment_ids = index.searchable_segment_ids()?;
+            index_writer.commit()?;
+            index_writer.merge(&segment_ids).wait()?;
+            index_writer.wait_merging_threads()?;
+        }
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let center = GeoPoint {
+            lat: 0.0,
+            lon: 180.0,
+        };
+        let query = GeoDistanceQuery::new(location, center, 50_000.0);
+        let expected = points
+            .iter()
+            .filter(|point| center.distance(point) <= 50_000.0)
+            .count();
+        assert_eq!(searcher.search(&query, &Count)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_geo_distance_query_fast_field() -> crate::Result<()> {
+        test_geo_distance_query_aux(GeoPointOptions::default())
+    }
+
+    #[test]
+    fn test_geo_distance_query_points() -> crate::Result<()> {
+        test_geo_distance_query_aux(GeoPointOptions::default().set_points())
+    }
+}
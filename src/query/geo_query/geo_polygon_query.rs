@@ -0,0 +1,252 @@
+use super::cell_cover::{cover_ranges, CellRelation, LatLonRect, MAX_COVER_RANGES};
+use super::geo_weight::{GeoFilter, GeoWeight};
+use crate::query::{EnableScoring, Query, Weight};
+use crate::schema::{Field, FieldType, GeoPoint};
+use crate::TantivyError;
+
+/// `GeoPolygonQuery` matches the documents with a point of a geo point field within a polygon.
+///
+/// The polygon is given by its vertices, and is implicitly closed. Its edges are straight
+/// lines in latitude and longitude, and it must not cross the antimeridian.
+///
+/// The candidate points are selected with the cells of the Z-order curve covering the
+/// bounding box of the polygon, then checked with a point-in-polygon test. All of the
+/// matching documents get a score of 1.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::GeoPolygonQuery;
+/// use tantivy::schema::{GeoPoint, GeoPointOptions, Schema};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let location = schema_builder.add_geo_point_field("location", GeoPointOptions::default());
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+/// index_writer.add_document(doc!(location => GeoPoint { lat: 1.0, lon: 1.0 }))?;
+/// index_writer.add_document(doc!(location => GeoPoint { lat: 3.0, lon: 2.0 }))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let triangle = vec![
+///     GeoPoint { lat: 0.0, lon: 0.0 },
+///     GeoPoint { lat: 4.0, lon: 0.0 },
+///     GeoPoint { lat: 0.0, lon: 4.0 },
+/// ];
+/// let query = GeoPolygonQuery::new(location, triangle);
+/// assert_eq!(searcher.search(&query, &Count)?, 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct GeoPolygonQuery {
+    field: Field,
+    vertices: Vec<GeoPoint>,
+}
+
+impl GeoPolygonQuery {
+    /// Creates a query matching the documents with a point within the polygon of the given
+    /// vertices.
+    pub fn new(field: Field, vertices: Vec<GeoPoint>) -> GeoPolygonQuery {
+        GeoPolygonQuery { field, vertices }
+    }
+
+    /// Returns the geo point field searched by the query.
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    /// Returns the vertices of the polygon.
+    pub fn vertices(&self) -> &[GeoPoint] {
+        &self.vertices
+    }
+}
+
+impl Query for GeoPolygonQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let field_entry = enable_scoring.schema().get_field_entry(self.field);
+        if !matches!(field_entry.field_type(), FieldType::GeoPoint(_)) {
+            return Err(TantivyError::SchemaError(format!(
+                "GeoPolygonQuery requires a geo point field, but {:?} is not one",
+                field_entry.name()
+            )));
+        }
+        if self.vertices.len() < 3 {
+            return Err(TantivyError::InvalidArgument(format!(
+                "A polygon needs at least 3 vertices, got {}",
+                self.vertices.len()
+            )));
+        }
+        let polygon = Polygon::new(self.vertices.clone());
+        let relation = |cell_rect: &LatLonRect| {
+            if !cell_rect.intersects(&polygon.bounding_rect) {
+                CellRelation::Disjoint
+            } else if cell_rect
+                .corners()
+                .iter()
+                .all(|corner| polygon.contains(corner))
+                && !polygon
+                    .vertices
+                    .iter()
+                    .any(|vertex| cell_rect.contains(vertex))
+            {
+                // This is only a heuristic to stop refining the cell, since an edge may still
+                // cross the cell. The points of the cell are all checked anyway.
+                CellRelation::Within
+            } else {
+                CellRelation::Crosses
+            }
+        };
+        let ranges = cover_ranges(relation, MAX_COVER_RANGES);
+        Ok(Box::new(GeoWeight::new(self.field, ranges, polygon)))
+    }
+}
+
+struct Polygon {
+    vertices: Vec<GeoPoint>,
+    bounding_rect: LatLonRect,
+}
+
+impl Polygon {
+    fn new(vertices: Vec<GeoPoint>) -> Polygon {
+        let mut bounding_rect = LatLonRect {
+            min_lat: f64::MAX,
+            max_lat: f64::MIN,
+            min_lon: f64::MAX,
+            max_lon: f64::MIN,
+        };
+        for vertex in &vertices {
+            bounding_rect.min_lat = bounding_rect.min_lat.min(vertex.lat);
+            bounding_rect.max_lat = bounding_rect.max_lat.max(vertex.lat);
+            bounding_rect.min_lon = bounding_rect.min_lon.min(vertex.lon);
+            bounding_rect.max_lon = bounding_rect.max_lon.max(vertex.lon);
+        }
+        Polygon {
+            vertices,
+            bounding_rect,
+        }
+    }
+
+    /// Returns true if the point is within the polygon, casting a ray from the point towards
+    /// the east and counting the edges it crosses.
+    fn contains(&self, point: &GeoPoint) -> bool {
+        if !self.bounding_rect.contains(point) {
+            return false;
+        }
+        let mut inside = false;
+        let mut previous = &self.vertices[self.vertices.len() - 1];
+        for vertex in &self.vertices {
+            if (vertex.lat > point.lat) != (previous.lat > point.lat) {
+                let crossing_lon = vertex.lon
+                    + (point.lat - vertex.lat) * (previous.lon - vertex.lon)
+                        / (previous.lat - vertex.lat);
+                if point.lon < crossing_lon {
+                    inside = !inside;
+                }
+            }
+            previous = vertex;
+        }
+        inside
+    }
+}
+
+impl GeoFilter for Polygon {
+    fn matches(&self, point: &GeoPoint) -> bool {
+        self.contains(point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GeoPolygonQuery, Polygon};
+    use crate::collector::Count;
+    use crate::schema::{GeoPoint, GeoPointOptions, Schema, INDEXED};
+    use crate::Index;
+
+    fn point(lat: f64, lon: f64) -> GeoPoint {
+        GeoPoint { lat, lon }
+    }
+
+    #[test]
+    fn test_polygon_contains() {
+        // An L shape.
+        let polygon = Polygon::new(vec![
+            point(0.0, 0.0),
+            point(0.0, 3.0),
+            point(1.0, 3.0),
+            point(1.0, 1.0),
+            point(3.0, 1.0),
+            point(3.0, 0.0),
+        ]);
+        assert!(polygon.contains(&point(0.5, 0.5)));
+        assert!(polygon.contains(&point(0.5, 2.5)));
+        assert!(polygon.contains(&point(2.5, 0.5)));
+        assert!(!polygon.contains(&point(2.0, 2.0)));
+        assert!(!polygon.contains(&point(-0.5, 0.5)));
+        assert!(!polygon.contains(&point(0.5, 3.5)));
+    }
+
+    #[test]
+    fn test_geo_polygon_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let location = schema_builder.add_geo_point_field("location", GeoPointOptions::default());
+        let indexed_location = schema_builder
+            .add_geo_point_field("indexed_location", GeoPointOptions::default().set_points());
+        let id = schema_builder.add_u64_field("id", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut points = Vec::new();
+        for lat in -20..=20 {
+            for lon in -20..=20 {
+                points.push(point(lat as f64 / 4.0 + 0.1, lon as f64 / 4.0 + 0.1));
+            }
+        }
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            for point in &points {
+                index_writer.add_document(
+                    doc!(location => *point, indexed_location => *point, id => 0u64),
+                )?;
+            }
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        for vertices in [
+            vec![point(0.0, 0.0), point(4.0, 0.0), point(0.0, 4.0)],
+            vec![
+                point(-3.0, -3.0),
+                point(-3.0, 3.0),
+                point(0.05, 0.0),
+                point(3.0, 3.0),
+                point(3.0, -3.0),
+            ],
+            vec![point(10.0, 10.0), point(11.0, 10.0), point(10.0, 11.0)],
+        ] {
+            let polygon = Polygon::new(vertices.clone());
+            let expected = points
+                .iter()
+                .filter(|point| polygon.contains(point))
+                .count();
+            let query = GeoPolygonQuery::new(location, vertices.clone());
+            assert_eq!(searcher.search(&query, &Count)?, expected);
+            let indexed_query = GeoPolygonQuery::new(indexed_location, vertices);
+            assert_eq!(searcher.search(&indexed_query, &Count)?, expected);
+        }
+        // A square of 4 by 4 points.
+        let square = vec![
+            point(0.0, 0.0),
+            point(1.0, 0.0),
+            point(1.0, 1.0),
+            point(0.0, 1.0),
+        ];
+        let query = GeoPolygonQuery::new(location, square);
+        assert_eq!(searcher.search(&query, &Count)?, 16);
+
+        let segment = GeoPolygonQuery::new(location, vec![point(0.0, 0.0), point(1.0, 1.0)]);
+        assert!(searcher.search(&segment, &Count).is_err());
+        let id_query =
+            GeoPolygonQuery::new(id, vec![point(0.0, 0.0), point(1.0, 0.0), point(1.0, 1.0)]);
+        assert!(searcher.search(&id_query, &Count).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+use std::ops::RangeInclusive;
+
+use common::BitSet;
+
+use crate::query::explanation::does_not_match;
+use crate::query::{BitSetDocSet, ConstScorer, Explanation, Scorer, Weight};
+use crate::schema::{Field, GeoPoint};
+use crate::{DocId, Score, SegmentReader};
+
+/// Checks whether the candidate points of a geo query are within its area.
+pub(crate) trait GeoFilter: Send + Sync + 'static {
+    fn matches(&self, point: &GeoPoint) -> bool;
+}
+
+/// `GeoWeight` matches the documents with a point within the ranges of `u64` values covering
+/// the area of a geo query, and accepted by its filter.
+pub(crate) struct GeoWeight<F> {
+    field: Field,
+    ranges: Vec<RangeInclusive<u64>>,
+    filter: F,
+}
+
+impl<F: GeoFilter> GeoWeight<F> {
+    pub fn new(field: Field, ranges: Vec<RangeInclusive<u64>>, filter: F) -> GeoWeight<F> {
+        GeoWeight {
+            field,
+            ranges,
+            filter,
+        }
+    }
+
+    fn matches(&self, val: u64) -> bool {
+        self.filter.matches(&GeoPoint::from_u64(val))
+    }
+
+    fn is_covered(&self, val: u64) -> bool {
+        let range_ord = self.ranges.partition_point(|range| *range.end() < val);
+        self.ranges
+            .get(range_ord)
+            .map_or(false, |range| range.contains(&val))
+    }
+}
+
+impl<F: GeoFilter> Weight for GeoWeight<F> {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let max_doc = reader.max_doc();
+        let mut doc_bitset = BitSet::with_max_value(max_doc);
+        if let Some(points_reader) = reader.points_readers().get_field(self.field)? {
+            for range in &self.ranges {
+                for (val, doc) in points_reader.points_in_range(range.clone()) {
+                    if self.matches(val) {
+                        doc_bitset.insert(doc);
+                    }
+                }
+            }
+        } else {
+            let field_name = reader.schema().get_field_name(self.field);
+            if let Some(column) = reader.fast_fields().column_opt::<u64>(field_name)? {
+                for doc in 0..max_doc {
+                    if column
+                        .values_for_doc(doc)
+                        .any(|val| self.is_covered(val) && self.matches(val))
+                    {
+                        doc_bitset.insert(doc);
+                    }
+                }
+            }
+        }
+        let doc_bitset = BitSetDocSet::from(doc_bitset);
+        Ok(Box::new(ConstScorer::new(doc_bitset, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("GeoWeight", 1.0))
+    }
+}
@@ -0,0 +1,14 @@
+//! Geo queries match the documents with a point of a geo point field within an area.
+//!
+//! The area is covered by ranges of cells of the Z-order curve the points are encoded with,
+//! which select the candidate points, either from the points index of the field or from its
+//! fast field. The candidate points are then checked against the exact area.
+mod cell_cover;
+mod geo_bounding_box_query;
+mod geo_distance_query;
+mod geo_polygon_query;
+mod geo_weight;
+
+pub use self::geo_bounding_box_query::GeoBoundingBoxQuery;
+pub use self::geo_distance_query::GeoDistanceQuery;
+pub use self::geo_polygon_query::GeoPolygonQuery;
@@ -0,0 +1,535 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::collector::{Collector, TopDocs};
+use crate::core::SegmentReader;
+use crate::query::explanation::does_not_match;
+use crate::query::{EnableScoring, Explanation, Query, QueryLeaf, Scorer, Weight};
+use crate::{DocAddress, DocId, DocSet, Score, Searcher, TantivyError, Term, TERMINATED};
+
+const DEFAULT_RANK_CONSTANT: Score = 60.0;
+const DEFAULT_RANK_WINDOW_SIZE: usize = 100;
+
+/// How a [`HybridQuery`] fuses the rankings of its sub queries.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Fusion {
+    /// Reciprocal rank fusion: a document is scored by the sum, over the rankings of the sub
+    /// queries, of `weight / (rank_constant + rank)`, with `rank` starting at 1.
+    ///
+    /// It only depends on the ranks, so that the scales of the scores of the sub queries do not
+    /// matter.
+    ReciprocalRank {
+        /// Dampens the advantage of the top ranks. It usually is 60.
+        rank_constant: Score,
+    },
+    /// A document is scored by the sum of the scores of the sub queries, each of them being
+    /// normalized to `[0, 1]` with the minimum and the maximum scores of its ranking, and
+    /// multiplied by the weight of the sub query.
+    WeightedScore,
+}
+
+impl Default for Fusion {
+    fn default() -> Fusion {
+        Fusion::ReciprocalRank {
+            rank_constant: DEFAULT_RANK_CONSTANT,
+        }
+    }
+}
+
+/// `HybridQuery` combines the rankings of several queries, typically a lexical query scored
+/// with BM25 and a [`KnnQuery`](crate::query::KnnQuery), into a single ranking.
+///
+/// The query matches the top [`rank_window_size`](HybridQuery::set_rank_window_size) documents
+/// of each sub query over all of the segments, and scores them by fusing their rankings with
+/// the [`Fusion`] method. When the weight is created, the scores of the rank window of each sub
+/// query are collected; the scorer of a segment then walks the scorers of the sub queries
+/// together, and maps the score of each sub query onto its rank, or its normalized score,
+/// within the window. The documents sharing a score share their rank.
+///
+/// Since the query is scored like any other query, it can be searched with any collector, and
+/// paginating with [`TopDocs::and_offset()`] returns consistent pages as long as the offset and
+/// the limit stay within the window.
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{HybridQuery, KnnQuery, QueryParser, VectorSimilarity};
+/// use tantivy::schema::{DenseVectorOptions, Schema, TEXT};
+/// use tantivy::{doc, DocAddress, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let embedding =
+///     schema_builder.add_dense_vector_field("embedding", DenseVectorOptions::new(2));
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+/// index_writer.add_document(doc!(title => "The old man and the sea", embedding => vec![1.0f32, 0.0]))?;
+/// index_writer.add_document(doc!(title => "Of mice and men", embedding => vec![0.1f32, 1.0]))?;
+/// index_writer.add_document(doc!(title => "The sea wolf", embedding => vec![0.0f32, 1.0]))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let lexical_query = QueryParser::for_index(&index, vec![title]).parse_query("sea")?;
+/// let vector_query = KnnQuery::new(embedding, vec![0.0, 1.0], VectorSimilarity::Cosine);
+/// let hybrid_query = HybridQuery::new(vec![
+///     (1.0, lexical_query),
+///     (1.0, Box::new(vector_query)),
+/// ]);
+/// let top_docs = searcher.search(&hybrid_query, &TopDocs::with_limit(2))?;
+/// // "The sea wolf" matches "sea" and has the most similar vector.
+/// assert_eq!(top_docs[0].1, DocAddress::new(0, 2));
+/// # Ok(())
+/// # }
+/// ```
+pub struct HybridQuery {
+    queries: Vec<(Score, Box<dyn Query>)>,
+    fusion: Fusion,
+    rank_window_size: usize,
+}
+
+impl Clone for HybridQuery {
+    fn clone(&self) -> HybridQuery {
+        HybridQuery {
+            queries: self
+                .queries
+                .iter()
+                .map(|(weight, query)| (*weight, query.box_clone()))
+                .collect(),
+            fusion: self.fusion,
+            rank_window_size: self.rank_window_size,
+        }
+    }
+}
+
+impl fmt::Debug for HybridQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HybridQuery")
+            .field("queries", &self.queries)
+            .field("fusion", &self.fusion)
+            .field("rank_window_size", &self.rank_window_size)
+            .finish()
+    }
+}
+
+impl HybridQuery {
+    /// Creates a `HybridQuery` fusing the rankings of the given queries, each with a weight.
+    pub fn new(queries: Vec<(Score, Box<dyn Query>)>) -> HybridQuery {
+        HybridQuery {
+            queries,
+            fusion: Fusion::default(),
+            rank_window_size: DEFAULT_RANK_WINDOW_SIZE,
+        }
+    }
+
+    /// Sets how the rankings are fused. It defaults to the reciprocal rank fusion, with a rank
+    /// constant of 60.
+    pub fn set_fusion(&mut self, fusion: Fusion) {
+        self.fusion = fusion;
+    }
+
+    /// Sets the number of top documents of each sub query that are fused. It defaults to 100.
+    pub fn set_rank_window_size(&mut self, rank_window_size: usize) {
+        self.rank_window_size = rank_window_size;
+    }
+
+    /// Returns the `limit` top documents of the fused ranking, after skipping the first
+    /// `offset` ones, with their fused score.
+    ///
+    /// The rank window is raised to `offset + limit` if it is smaller.
+    pub fn search(
+        &self,
+        searcher: &Searcher,
+        limit: usize,
+        offset: usize,
+    ) -> crate::Result<Vec<(Score, DocAddress)>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let mut query = self.clone();
+        query.rank_window_size = self.rank_window_size.max(offset + limit);
+        searcher.search(&query, &TopDocs::with_limit(limit).and_offset(offset))
+    }
+}
+
+impl Query for HybridQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let searcher = enable_scoring.searcher().ok_or_else(|| {
+            TantivyError::InvalidArgument(
+                "HybridQuery needs a searcher to rank the documents of its sub queries".to_string(),
+            )
+        })?;
+        // The documents are matched by their ranks, which need the scores of the sub queries
+        // even if the scores are not requested.
+        let sub_enable_scoring = if enable_scoring.is_scoring_enabled() {
+            enable_scoring
+        } else {
+            EnableScoring::enabled_from_searcher(searcher)
+        };
+        let mut sub_weights = Vec::with_capacity(self.queries.len());
+        let mut rank_windows = Vec::with_capacity(self.queries.len());
+        for (weight, query) in &self.queries {
+            let sub_weight = query.weight(sub_enable_scoring)?;
+            rank_windows.push(RankWindow::collect(
+                *weight,
+                sub_weight.as_ref(),
+                searcher,
+                self.rank_window_size,
+            )?);
+            sub_weights.push(sub_weight);
+        }
+        Ok(Box::new(HybridWeight {
+            sub_weights,
+            rank_windows: Arc::new(rank_windows),
+            fusion: self.fusion,
+        }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        for (_, query) in &self.queries {
+            query.query_terms(visitor);
+        }
+    }
+
+    fn query_leaves<'a>(&'a self, visitor: &mut dyn FnMut(QueryLeaf<'a>)) {
+        for (_, query) in &self.queries {
+            query.query_leaves(visitor);
+        }
+    }
+}
+
+/// The scores of the top documents of a sub query over all of the segments.
+struct RankWindow {
+    weight: Score,
+    /// Sorted in decreasing order.
+    scores: Vec<Score>,
+}
+
+impl RankWindow {
+    fn collect(
+        weight: Score,
+        sub_weight: &dyn Weight,
+        searcher: &Searcher,
+        rank_window_size: usize,
+    ) -> crate::Result<RankWindow> {
+        let scores = if rank_window_size == 0 {
+            Vec::new()
+        } else {
+            TopDocs::with_limit(rank_window_size)
+                .collect_segments(
+                    sub_weight,
+                    searcher.segment_readers(),
+                    searcher.index().search_executor(),
+                )?
+                .into_iter()
+                .map(|(score, _)| score)
+                .collect()
+        };
+        Ok(RankWindow { weight, scores })
+    }
+
+    /// Returns the rank, starting at 1, of a document scored `score` by the sub query, or
+    /// `None` if it is out of the window.
+    fn rank(&self, score: Score) -> Option<usize> {
+        let &min_score = self.scores.last()?;
+        if score < min_score {
+            return None;
+        }
+        Some(
+            self.scores
+                .partition_point(|&window_score| window_score > score)
+                + 1,
+        )
+    }
+
+    /// Returns the contribution of a document scored `score` by the sub query to its fused
+    /// score, or `None` if it is out of the window.
+    fn fused_score(&self, fusion: Fusion, score: Score) -> Option<Score> {
+        let rank = self.rank(score)?;
+        let fused_score = match fusion {
+            Fusion::ReciprocalRank { rank_constant } => 1.0 / (rank_constant + rank as Score),
+            Fusion::WeightedScore => {
+                let max_score = self.scores[0];
+                let min_score = self.scores[self.scores.len() - 1];
+                let score_range = max_score - min_score;
+                if score_range > 0.0 {
+                    (score.min(max_score) - min_score) / score_range
+                } else {
+                    1.0
+                }
+            }
+        };
+        Some(self.weight * fused_score)
+    }
+}
+
+struct HybridWeight {
+    sub_weights: Vec<Box<dyn Weight>>,
+    rank_windows: Arc<Vec<RankWindow>>,
+    fusion: Fusion,
+}
+
+impl Weight for HybridWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let scorers = self
+            .sub_weights
+            .iter()
+            .map(|sub_weight| sub_weight.scorer(reader, 1.0))
+            .collect::<crate::Result<Vec<_>>>()?;
+        Ok(Box::new(HybridScorer::new(
+            scorers,
+            self.rank_windows.clone(),
+            self.fusion,
+            boost,
+        )))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        let mut explanation = Explanation::new("HybridQuery", scorer.score());
+        explanation.add_context(format!("fusion={:?}", self.fusion));
+        for (sub_weight, rank_window) in self.sub_weights.iter().zip(self.rank_windows.iter()) {
+            let mut sub_scorer = sub_weight.scorer(reader, 1.0)?;
+            if sub_scorer.seek(doc) != doc {
+                continue;
+            }
+            let score = sub_scorer.score();
+            if let Some(rank) = rank_window.rank(score) {
+                let mut sub_explanation = sub_weight.explain(reader, doc)?;
+                sub_explanation.add_context(format!("rank={rank}"));
+                explanation.add_detail(sub_explanation);
+            }
+        }
+        Ok(explanation)
+    }
+}
+
+/// Walks the scorers of the sub queries together, matching the documents within the rank
+/// window of at least one of them.
+struct HybridScorer {
+    scorers: Vec<Box<dyn Scorer>>,
+    rank_windows: Arc<Vec<RankWindow>>,
+    fusion: Fusion,
+    boost: Score,
+    doc: DocId,
+    score: Score,
+}
+
+impl HybridScorer {
+    fn new(
+        scorers: Vec<Box<dyn Scorer>>,
+        rank_windows: Arc<Vec<RankWindow>>,
+        fusion: Fusion,
+        boost: Score,
+    ) -> HybridScorer {
+        let mut hybrid_scorer = HybridScorer {
+            scorers,
+            rank_windows,
+            fusion,
+            boost,
+            doc: 0,
+            score: 0.0,
+        };
+        hybrid_scorer.doc = hybrid_scorer.min_doc();
+        if hybrid_scorer.doc != TERMINATED && !hybrid_scorer.fuse_scores() {
+            hybrid_scorer.advance();
+        }
+        hybrid_scorer
+    }
+
+    fn min_doc(&self) -> DocId {
+        self.scorers
+            .iter()
+            .map(|scorer| scorer.doc())
+            .min()
+            .unwrap_or(TERMINATED)
+    }
+
+    /// Fuses the scores of the current document, and returns false if it is out of all of the
+    /// rank windows.
+    fn fuse_scores(&mut self) -> bool {
+        let mut fused_score: Option<Score> = None;
+        for (scorer, rank_window) in self.scorers.iter_mut().zip(self.rank_windows.iter()) {
+            if scorer.doc() != self.doc {
+                continue;
+            }
+            if let Some(score) = rank_window.fused_score(self.fusion, scorer.score()) {
+                *fused_score.get_or_insert(0.0) += score;
+            }
+        }
+        if let Some(fused_score) = fused_score {
+            self.score = self.boost * fused_score;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl DocSet for HybridScorer {
+    fn advance(&mut self) -> DocId {
+        while self.doc != TERMINATED {
+            for scorer in &mut self.scorers {
+                if scorer.doc() == self.doc {
+                    scorer.advance();
+                }
+            }
+            self.doc = self.min_doc();
+            if self.doc != TERMINATED && self.fuse_scores() {
+                break;
+            }
+        }
+        self.doc
+    }
+
+    fn doc(&self) -> DocId {
+        self.doc
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorers
+            .iter()
+            .map(|scorer| scorer.size_hint())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl Scorer for HybridScorer {
+    fn score(&mut self) -> Score {
+        self.score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Fusion, HybridQuery};
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{KnnQuery, Query, TermQuery, VectorSimilarity};
+    use crate::schema::{DenseVectorOptions, IndexRecordOption, Schema, FAST, TEXT};
+    use crate::{DocAddress, Index, Term};
+
+    #[test]
+    fn test_hybrid_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT);
+        let embedding =
+            schema_builder.add_dense_vector_field("embedding", DenseVectorOptions::new(1));
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer
+                .add_document(doc!(body => "apple apple apple", embedding => vec![0.0f32]))?;
+            index_writer.add_document(doc!(body => "apple", embedding => vec![3.0f32]))?;
+            index_writer.add_document(doc!(body => "banana", embedding => vec![4.0f32]))?;
+            index_writer.add_document(doc!(body => "cherry", embedding => vec![1.0f32]))?;
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let lexical_query: Box<dyn Query> = Box::new(TermQuery::new(
+            Term::from_field_text(body, "apple"),
+            IndexRecordOption::WithFreqs,
+        ));
+        let vector_query: Box<dyn Query> =
+            Box::new(KnnQuery::new(embedding, vec![4.0], VectorSimilarity::L2));
+        let doc_ids = |top_docs: Vec<(f32, DocAddress)>| -> Vec<u32> {
+            top_docs
+                .into_iter()
+                .map(|(_, doc_address)| doc_address.doc_id)
+                .collect()
+        };
+
+        // Lexical ranking: 0, 1. Vector ranking: 2, 1, 3, 0.
+        let mut hybrid_query = HybridQuery::new(vec![(1.0, lexical_query), (1.0, vector_query)]);
+        let top_docs = hybrid_query.search(&searcher, 10, 0)?;
+        assert!((top_docs[0].0 - (1.0 / 62.0 + 1.0 / 62.0)).abs() < 1e-6);
+        assert_eq!(doc_ids(top_docs), vec![1, 0, 2, 3]);
+        assert_eq!(doc_ids(hybrid_query.search(&searcher, 2, 1)?), vec![0, 2]);
+
+        hybrid_query.set_rank_window_size(1);
+        assert_eq!(doc_ids(hybrid_query.search(&searcher, 1, 0)?), vec![0]);
+        assert_eq!(
+            doc_ids(hybrid_query.search(&searcher, 3, 0)?),
+            vec![1, 0, 2]
+        );
+
+        hybrid_query.set_rank_window_size(100);
+        hybrid_query.set_fusion(Fusion::WeightedScore);
+        let top_docs = hybrid_query.search(&searcher, 10, 0)?;
+        // Doc 0 has the maximum lexical score and the minimum vector score, doc 2 the
+        // maximum vector score and no lexical score.
+        assert_eq!(top_docs[0].0, 1.0);
+        assert_eq!(top_docs[1].0, 1.0);
+        assert_eq!(doc_ids(top_docs), vec![0, 2, 1, 3]);
+
+        let weighted_query = HybridQuery::new(vec![(3.0, hybrid_query.queries[0].1.box_clone())]);
+        assert_eq!(
+            doc_ids(weighted_query.search(&searcher, 10, 0)?),
+            vec![0, 1]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_hybrid_query_collectors() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT);
+        let embedding =
+            schema_builder.add_dense_vector_field("embedding", DenseVectorOptions::new(1));
+        let id = schema_builder.add_u64_field("id", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            // Two segments: doc i has i + 1 times "apple" and the vector i.
+            for segment_docs in [0..5, 5..10] {
+                for i in segment_docs {
+                    let text = vec!["apple"; i + 1].join(" ");
+                    index_writer.add_document(
+                        doc!(body => text, embedding => vec![i as f32], id => i as u64),
+                    )?;
+                }
+                index_writer.commit()?;
+            }
+            index_writer.add_document(doc!(body => "banana", embedding => vec![-1.0f32]))?;
+            index_writer.delete_term(Term::from_field_text(body, "banana"));
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let lexical_query: Box<dyn Query> = Box::new(TermQuery::new(
+            Term::from_field_text(body, "apple"),
+            IndexRecordOption::WithFreqs,
+        ));
+        // The closest vectors to 0.0 are the ones of the docs with the fewest "apple".
+        let vector_query: Box<dyn Query> =
+            Box::new(KnnQuery::new(embedding, vec![0.0], VectorSimilarity::L2));
+        let mut hybrid_query = HybridQuery::new(vec![(1.0, lexical_query), (2.0, vector_query)]);
+        hybrid_query.set_rank_window_size(3);
+
+        // Lexical window: 9, 8, 7. Vector window: 0, 1, 2.
+        assert_eq!(searcher.search(&hybrid_query, &Count)?, 6);
+        let top_docs = searcher.search(&hybrid_query, &TopDocs::with_limit(10))?;
+        // The order of the segments is not deterministic, so the docs are identified by their id.
+        let ranking: Vec<u64> = top_docs
+            .iter()
+            .map(|(_, doc_address)| {
+                let segment_reader = searcher.segment_reader(doc_address.segment_ord);
+                let id_column = segment_reader.fast_fields().u64("id").unwrap();
+                id_column.first(doc_address.doc_id).unwrap()
+            })
+            .collect();
+        assert_eq!(ranking, vec![0, 1, 2, 9, 8, 7]);
+        assert!((top_docs[0].0 - 2.0 / 61.0).abs() < 1e-6);
+        assert!((top_docs[3].0 - 1.0 / 61.0).abs() < 1e-6);
+
+        // The pages are consistent with the whole ranking.
+        let page = searcher.search(&hybrid_query, &TopDocs::with_limit(2).and_offset(3))?;
+        assert_eq!(page, top_docs[3..5].to_vec());
+
+        let explanation = hybrid_query.explain(&searcher, top_docs[0].1)?;
+        assert!((explanation.value() - top_docs[0].0).abs() < 1e-6);
+        assert!(explanation.to_pretty_json().contains("rank=1"));
+        Ok(())
+    }
+}
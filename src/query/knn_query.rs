@@ -0,0 +1,611 @@
+use std::fmt;
+
+use columnar::Column;
+use serde::{Deserialize, Serialize};
+
+use crate::core::SegmentReader;
+use crate::docset::{DocSet, TERMINATED};
+use crate::query::explanation::does_not_match;
+use crate::query::{EmptyScorer, EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::schema::{dense_vector_from_bytes, Field, FieldType};
+use crate::{DocId, Score, TantivyError};
+
+const DEFAULT_EF_SEARCH: usize = 100;
+
+/// The similarity used by a [`KnnQuery`] to score a vector against the query vector.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VectorSimilarity {
+    /// The cosine of the angle between the vectors, mapped to `[0, 1]` as
+    /// `(1 + cosine) / 2`.
+    Cosine,
+    /// The dot product of the vectors. It matches the cosine similarity for normalized
+    /// vectors, and can be negative.
+    DotProduct,
+    /// The euclidean distance between the vectors, mapped to `(0, 1]` as
+    /// `1 / (1 + distance²)`.
+    L2,
+}
+
+impl VectorSimilarity {
+    pub(crate) fn score(&self, query_vector: &[f32], vector: &[f32]) -> Score {
+        match self {
+            VectorSimilarity::Cosine => {
+                let norms = norm(query_vector) * norm(vector);
+                let cosine = if norms > 0.0 {
+                    dot_product(query_vector, vector) / norms
+                } else {
+                    0.0
+                };
+                (1.0 + cosine) / 2.0
+            }
+            VectorSimilarity::DotProduct => dot_product(query_vector, vector),
+            VectorSimilarity::L2 => {
+                let squared_distance: f32 = query_vector
+                    .iter()
+                    .zip(vector)
+                    .map(|(left, right)| (left - right) * (left - right))
+                    .sum();
+                1.0 / (1.0 + squared_distance)
+            }
+        }
+    }
+}
+
+fn dot_product(left: &[f32], right: &[f32]) -> f32 {
+    left.iter()
+        .zip(right)
+        .map(|(left, right)| left * right)
+        .sum()
+}
+
+fn norm(vector: &[f32]) -> f32 {
+    dot_product(vector, vector).sqrt()
+}
+
+/// `KnnQuery` scores the documents by the similarity of the vectors of a dense vector field
+/// to a query vector.
+///
+/// It matches all of the documents with a vector. Collecting the top `k` documents, e.g. with
+/// [`TopDocs`](crate::collector::TopDocs), returns the `k` nearest neighbours of the query
+/// vector. A document with several vectors is scored by the most similar one.
+///
+/// By default, the vectors are all scanned and scored: the nearest neighbours are exact, at a
+/// cost proportional to the number of distinct vectors of the index. For a field with an
+/// [HNSW graph](crate::vector), [`KnnQuery::set_approximate_top_k`] instead only matches the
+/// approximate top `k` documents of each segment, found by searching the graph.
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{KnnQuery, VectorSimilarity};
+/// use tantivy::schema::{DenseVectorOptions, Schema};
+/// use tantivy::{doc, DocAddress, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let embedding =
+///     schema_builder.add_dense_vector_field("embedding", DenseVectorOptions::new(2));
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+/// index_writer.add_document(doc!(embedding => vec![1.0f32, 0.0]))?;
+/// index_writer.add_document(doc!(embedding => vec![0.0f32, 1.0]))?;
+/// index_writer.add_document(doc!(embedding => vec![0.7f32, 0.7]))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let knn_query = KnnQuery::new(embedding, vec![1.0, 0.1], VectorSimilarity::Cosine);
+/// let top_docs = searcher.search(&knn_query, &TopDocs::with_limit(2))?;
+/// let doc_addresses: Vec<DocAddress> = top_docs
+///     .into_iter()
+///     .map(|(_score, doc_address)| doc_address)
+///     .collect();
+/// assert_eq!(doc_addresses, vec![DocAddress::new(0, 0), DocAddress::new(0, 2)]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct KnnQuery {
+    field: Field,
+    query_vector: Vec<f32>,
+    similarity: VectorSimilarity,
+    approximate_top_k: Option<usize>,
+    ef_search: usize,
+}
+
+impl fmt::Debug for KnnQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "KnnQuery(field={:?}, similarity={:?}, dimension={})",
+            self.field,
+            self.similarity,
+            self.query_vector.len()
+        )
+    }
+}
+
+impl KnnQuery {
+    /// Creates a `KnnQuery` scoring the vectors of a dense vector field against the query
+    /// vector.
+    pub fn new(field: Field, query_vector: Vec<f32>, similarity: VectorSimilarity) -> KnnQuery {
+        KnnQuery {
+            field,
+            query_vector,
+            similarity,
+            approximate_top_k: None,
+            ef_search: DEFAULT_EF_SEARCH,
+        }
+    }
+
+    /// Only matches the approximate `k` nearest neighbours of each segment.
+    ///
+    /// They are searched in the HNSW graph of the segment if the field has one built with the
+    /// same similarity as the query, and found by scanning all of the vectors of the segment
+    /// otherwise. The deleted documents are skipped, but the other clauses of a boolean query
+    /// are not taken into account: intersecting the query with a filter may return less than
+    /// `k` documents.
+    pub fn set_approximate_top_k(&mut self, k: usize) {
+        self.approximate_top_k = Some(k);
+    }
+
+    /// Sets the number of candidates explored in the HNSW graph by an approximate search, at
+    /// least `k`. It defaults to 100.
+    ///
+    /// More candidates improve the recall, at the cost of a slower search.
+    pub fn set_ef_search(&mut self, ef_search: usize) {
+        self.ef_search = ef_search;
+    }
+}
+
+impl Query for KnnQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let schema = enable_scoring.schema();
+        let field_entry = schema.get_field_entry(self.field);
+        let FieldType::DenseVector(dense_vector_options) = field_entry.field_type() else {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not a dense vector field.",
+                field_entry.name()
+            )));
+        };
+        if dense_vector_options.dimension() != self.query_vector.len() {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The query vector has {} dimensions, but the vectors of field {:?} have {}.",
+                self.query_vector.len(),
+                field_entry.name(),
+                dense_vector_options.dimension()
+            )));
+        }
+        Ok(Box::new(KnnWeight {
+            field: self.field,
+            field_name: field_entry.name().to_string(),
+            query_vector: self.query_vector.clone(),
+            similarity: self.similarity,
+            approximate_top_k: self.approximate_top_k,
+            ef_search: self.ef_search,
+        }))
+    }
+}
+
+struct KnnWeight {
+    field: Field,
+    field_name: String,
+    query_vector: Vec<f32>,
+    similarity: VectorSimilarity,
+    approximate_top_k: Option<usize>,
+    ef_search: usize,
+}
+
+impl KnnWeight {
+    /// Returns the approximate `k` nearest neighbours of the segment, sorted by doc id.
+    fn top_k(&self, reader: &SegmentReader, k: usize, boost: Score) -> crate::Result<TopKScorer> {
+        let hnsw_reader = reader
+            .hnsw_readers()
+            .get_field(self.field)?
+            .filter(|hnsw_reader| hnsw_reader.similarity() == self.similarity);
+        let mut docs: Vec<(DocId, Score)> = if let Some(hnsw_reader) = hnsw_reader {
+            if hnsw_reader.is_quantized() {
+                // The similarities to the quantized vectors are approximate: more candidates
+                // are searched, then rescored against the full-precision vectors.
+                let candidates = hnsw_reader.search(
+                    &self.query_vector,
+                    self.ef_search.max(k),
+                    self.ef_search,
+                    |doc| !reader.is_deleted(doc),
+                );
+                self.rescore(reader, candidates, k)?
+            } else {
+                hnsw_reader.search(&self.query_vector, k, self.ef_search, |doc| {
+                    !reader.is_deleted(doc)
+                })
+            }
+        } else if let Some(mut knn_scorer) = self.knn_scorer(reader, 1.0)? {
+            let mut docs: Vec<(DocId, Score)> = Vec::new();
+            while knn_scorer.doc() != TERMINATED {
+                if !reader.is_deleted(knn_scorer.doc()) {
+                    docs.push((knn_scorer.doc(), knn_scorer.score()));
+                }
+                knn_scorer.advance();
+            }
+            docs.sort_by(|left, right| right.1.total_cmp(&left.1).then(left.0.cmp(&right.0)));
+            docs.truncate(k);
+            docs
+        } else {
+            Vec::new()
+        };
+        docs.sort_unstable_by_key(|&(doc, _)| doc);
+        for (_, score) in &mut docs {
+            *score *= boost;
+        }
+        Ok(TopKScorer { docs, cursor: 0 })
+    }
+
+    /// Scores the candidates with their full-precision vectors, and returns the top `k`.
+    fn rescore(
+        &self,
+        reader: &SegmentReader,
+        candidates: Vec<(DocId, Score)>,
+        k: usize,
+    ) -> crate::Result<Vec<(DocId, Score)>> {
+        let Some(bytes_column) = reader.fast_fields().bytes(&self.field_name)? else {
+            return Ok(Vec::new());
+        };
+        let mut vector_bytes: Vec<u8> = Vec::new();
+        let mut vector: Vec<f32> = Vec::with_capacity(self.query_vector.len());
+        let mut docs: Vec<(DocId, Score)> = Vec::with_capacity(candidates.len());
+        for (doc, _) in candidates {
+            let mut doc_score: Option<Score> = None;
+            for ord in bytes_column.term_ords(doc) {
+                vector_bytes.clear();
+                bytes_column.ord_to_bytes(ord, &mut vector_bytes)?;
+                dense_vector_from_bytes(&vector_bytes, &mut vector);
+                let score = self.similarity.score(&self.query_vector, &vector);
+                doc_score = Some(doc_score.map_or(score, |doc_score| doc_score.max(score)));
+            }
+            if let Some(doc_score) = doc_score {
+                docs.push((doc, doc_score));
+            }
+        }
+        docs.sort_by(|left, right| right.1.total_cmp(&left.1).then(left.0.cmp(&right.0)));
+        docs.truncate(k);
+        Ok(docs)
+    }
+
+    fn knn_scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Option<KnnScorer>> {
+        let Some(bytes_column) = reader.fast_fields().bytes(&self.field_name)? else {
+            return Ok(None);
+        };
+        // Identical vectors share the same ordinal, so each of them is only scored once.
+        let mut scores_by_ord: Vec<Score> = Vec::with_capacity(bytes_column.num_terms());
+        let mut vector: Vec<f32> = Vec::with_capacity(self.query_vector.len());
+        let mut stream = bytes_column.dictionary().stream()?;
+        while let Some((vector_bytes, _)) = stream.next() {
+            dense_vector_from_bytes(vector_bytes, &mut vector);
+            scores_by_ord.push(boost * self.similarity.score(&self.query_vector, &vector));
+        }
+        let mut knn_scorer = KnnScorer {
+            ords: bytes_column.ords().clone(),
+            scores_by_ord,
+            doc: 0,
+            max_doc: reader.max_doc(),
+            score: 0.0,
+        };
+        if knn_scorer.max_doc == 0 {
+            knn_scorer.doc = TERMINATED;
+        } else if !knn_scorer.score_doc() {
+            knn_scorer.advance();
+        }
+        Ok(Some(knn_scorer))
+    }
+}
+
+impl Weight for KnnWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        if let Some(k) = self.approximate_top_k {
+            Ok(Box::new(self.top_k(reader, k, boost)?))
+        } else if let Some(knn_scorer) = self.knn_scorer(reader, boost)? {
+            Ok(Box::new(knn_scorer))
+        } else {
+            Ok(Box::new(EmptyScorer))
+        }
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        let mut explanation = Explanation::new("KnnQuery", scorer.score());
+        explanation.add_context(format!("similarity={:?}", self.similarity));
+        Ok(explanation)
+    }
+}
+
+/// Scorer associated with the `KnnQuery` query.
+struct KnnScorer {
+    ords: Column<u64>,
+    scores_by_ord: Vec<Score>,
+    doc: DocId,
+    max_doc: DocId,
+    score: Score,
+}
+
+impl KnnScorer {
+    /// Scores the current document by its most similar vector.
+    ///
+    /// Returns false if the document does not have any vector.
+    fn score_doc(&mut self) -> bool {
+        let scores_by_ord = &self.scores_by_ord;
+        let score = self
+            .ords
+            .values_for_doc(self.doc)
+            .map(|ord| scores_by_ord[ord as usize])
+            .reduce(Score::max);
+        if let Some(score) = score {
+            self.score = score;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl DocSet for KnnScorer {
+    fn advance(&mut self) -> DocId {
+        loop {
+            if self.doc == TERMINATED || self.doc + 1 >= self.max_doc {
+                self.doc = TERMINATED;
+                return TERMINATED;
+            }
+            self.doc += 1;
+            if self.score_doc() {
+                return self.doc;
+            }
+        }
+    }
+
+    fn doc(&self) -> DocId {
+        self.doc
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.max_doc
+    }
+}
+
+impl Scorer for KnnScorer {
+    fn score(&mut self) -> Score {
+        self.score
+    }
+}
+
+/// Scorer matching the approximate top `k` documents of a segment.
+struct TopKScorer {
+    docs: Vec<(DocId, Score)>,
+    cursor: usize,
+}
+
+impl DocSet for TopKScorer {
+    fn advance(&mut self) -> DocId {
+        if self.cursor < self.docs.len() {
+            self.cursor += 1;
+        }
+        self.doc()
+    }
+
+    fn doc(&self) -> DocId {
+        self.docs
+            .get(self.cursor)
+            .map(|&(doc, _)| doc)
+            .unwrap_or(TERMINATED)
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.docs.len() as u32
+    }
+}
+
+impl Scorer for TopKScorer {
+    fn score(&mut self) -> Score {
+        self.docs[self.cursor].1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::ByteCount;
+
+    use super::{KnnQuery, VectorSimilarity};
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{AllQuery, BooleanQuery, Query, TermQuery};
+    use crate::schema::{
+        DenseVectorOptions, HnswOptions, IndexRecordOption, Schema, VectorQuantization, FAST,
+        INDEXED, STRING,
+    };
+    use crate::{DocAddress, Index, Term};
+
+    #[test]
+    fn test_knn_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let embedding = schema_builder
+            .add_dense_vector_field("embedding", DenseVectorOptions::new(3).set_stored());
+        let category = schema_builder.add_text_field("category", STRING);
+        let id = schema_builder.add_u64_field("id", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            for (doc_id, vector, doc_category) in [
+                (0u64, vec![1.0f32, 0.0, 0.0], "a"),
+                (1, vec![2.0, 2.0, 0.0], "b"),
+                (2, vec![0.0, 0.0, 1.0], "a"),
+                (3, vec![1.0, 0.0, 0.0], "b"),
+            ] {
+                index_writer.add_document(doc!(
+                    id => doc_id,
+                    embedding => vector,
+                    category => doc_category
+                ))?;
+            }
+            assert!(index_writer
+                .add_document(doc!(embedding => vec![1.0f32, 1.0]))
+                .is_err());
+            index_writer.commit()?;
+            // A segment without any vector.
+            index_writer.add_document(doc!(id => 4u64, category => "a"))?;
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let search = |query: &dyn Query| -> crate::Result<Vec<(f32, u64)>> {
+            let top_docs = searcher.search(query, &TopDocs::with_limit(10))?;
+            top_docs
+                .into_iter()
+                .map(|(score, doc_address)| {
+                    let id_reader = searcher
+                        .segment_reader(doc_address.segment_ord)
+                        .fast_fields()
+                        .u64("id")?;
+                    Ok((score, id_reader.first(doc_address.doc_id).unwrap()))
+                })
+                .collect()
+        };
+
+        let cosine_query = KnnQuery::new(embedding, vec![1.0, 0.0, 0.0], VectorSimilarity::Cosine);
+        let top_docs = search(&cosine_query)?;
+        assert_eq!(top_docs.len(), 4);
+        assert_eq!(top_docs[0].0, 1.0);
+        assert_eq!(top_docs[1].0, 1.0);
+        assert!((top_docs[2].0 - (1.0 + 0.5f32.sqrt()) / 2.0).abs() < 1e-6);
+        assert_eq!(top_docs[2].1, 1);
+        assert_eq!(top_docs[3], (0.5, 2));
+
+        let dot_product_query =
+            KnnQuery::new(embedding, vec![1.0, 0.0, 0.0], VectorSimilarity::DotProduct);
+        assert_eq!(search(&dot_product_query)?[0], (2.0, 1));
+
+        let l2_query = KnnQuery::new(embedding, vec![0.0, 0.0, 2.0], VectorSimilarity::L2);
+        assert_eq!(search(&l2_query)?[0], (0.5, 2));
+
+        let filtered_query = BooleanQuery::intersection(vec![
+            Box::new(l2_query.clone()),
+            Box::new(TermQuery::new(
+                Term::from_field_text(category, "b"),
+                IndexRecordOption::Basic,
+            )),
+        ]);
+        assert_eq!(search(&filtered_query)?.len(), 2);
+        assert_eq!(search(&filtered_query)?[0].1, 3);
+
+        let top_docs = searcher.search(&l2_query, &TopDocs::with_limit(1))?;
+        let doc_address: DocAddress = top_docs[0].1;
+        let explanation = l2_query.explain(&searcher, doc_address)?;
+        assert_eq!(explanation.value(), 0.5);
+        let doc = searcher.doc(doc_address)?;
+        assert_eq!(
+            doc.get_first(embedding)
+                .and_then(|value| value.as_dense_vector()),
+            Some(&[0.0f32, 0.0, 1.0][..])
+        );
+
+        assert!(search(&KnnQuery::new(embedding, vec![1.0], VectorSimilarity::L2)).is_err());
+        assert!(search(&KnnQuery::new(category, vec![1.0], VectorSimilarity::L2)).is_err());
+        assert_eq!(search(&AllQuery)?.len(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_knn_query_approximate() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let hnsw_options = HnswOptions::default()
+            .set_max_connections(4)
+            .set_similarity(VectorSimilarity::L2);
+        let embedding = schema_builder.add_dense_vector_field(
+            "embedding",
+            DenseVectorOptions::new(2).set_hnsw(hnsw_options),
+        );
+        let quantized_embedding = schema_builder.add_dense_vector_field(
+            "quantized_embedding",
+            DenseVectorOptions::new(2)
+                .set_hnsw(hnsw_options.set_quantization(VectorQuantization::Int8)),
+        );
+        let id = schema_builder.add_u64_field("id", FAST | INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for doc_id in 0..500u64 {
+            let angle = doc_id as f32 * 0.1;
+            let vector = vec![doc_id as f32 * angle.cos(), doc_id as f32 * angle.sin()];
+            index_writer.add_document(doc!(
+                id => doc_id,
+                embedding => vector.clone(),
+                quantized_embedding => vector,
+            ))?;
+            if doc_id == 250 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.add_document(doc!(id => 500u64))?;
+        index_writer.commit()?;
+
+        let search_ids = |query: &KnnQuery| -> crate::Result<Vec<u64>> {
+            let searcher = index.reader()?.searcher();
+            let top_docs = searcher.search(query, &TopDocs::with_limit(10))?;
+            top_docs
+                .into_iter()
+                .map(|(_score, doc_address)| {
+                    let id_reader = searcher
+                        .segment_reader(doc_address.segment_ord)
+                        .fast_fields()
+                        .u64("id")?;
+                    Ok(id_reader.first(doc_address.doc_id).unwrap())
+                })
+                .collect()
+        };
+        let query_vector = vec![300.0 * 30f32.cos(), 300.0 * 30f32.sin()];
+        let exact_query = KnnQuery::new(embedding, query_vector.clone(), VectorSimilarity::L2);
+        let exact_ids = search_ids(&exact_query)?;
+        assert_eq!(exact_ids[0], 300);
+        let mut approximate_query = exact_query.clone();
+        approximate_query.set_approximate_top_k(5);
+        // The top 5 documents of each of the two segments match.
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.search(&approximate_query, &Count)?, 10);
+        assert_eq!(search_ids(&approximate_query)?[..5], exact_ids[..5]);
+        let space_usage = searcher.space_usage()?;
+        let hnsw_usage = space_usage.segments()[0].hnsw();
+        let field_usage = |field| {
+            hnsw_usage
+                .fields()
+                .find(|(&usage_field, _)| usage_field == field)
+                .map(|(_, field_usage)| field_usage.total())
+                .unwrap()
+        };
+        assert!(field_usage(quantized_embedding) > ByteCount::from(0u64));
+        assert!(field_usage(quantized_embedding) < field_usage(embedding));
+
+        // The candidates found with the quantized vectors are rescored with the full-precision
+        // vectors.
+        let mut quantized_query = KnnQuery::new(
+            quantized_embedding,
+            query_vector.clone(),
+            VectorSimilarity::L2,
+        );
+        quantized_query.set_approximate_top_k(5);
+        let quantized_top_docs = searcher.search(&quantized_query, &TopDocs::with_limit(5))?;
+        let exact_top_docs = searcher.search(&exact_query, &TopDocs::with_limit(5))?;
+        assert_eq!(quantized_top_docs, exact_top_docs);
+
+        // Without a graph for the similarity of the query, the vectors are scanned.
+        let mut cosine_query =
+            KnnQuery::new(embedding, query_vector.clone(), VectorSimilarity::Cosine);
+        cosine_query.set_approximate_top_k(3);
+        assert_eq!(searcher.search(&cosine_query, &Count)?, 6);
+
+        index_writer.delete_term(Term::from_field_u64(id, 300));
+        index_writer.commit()?;
+        assert_eq!(search_ids(&approximate_query)?[..5], exact_ids[1..6]);
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+        let approximate_ids = search_ids(&approximate_query)?;
+        assert_eq!(approximate_ids, exact_ids[1..6]);
+        Ok(())
+    }
+}
@@ -4,16 +4,21 @@ mod bitset;
 mod bm25;
 mod boolean_query;
 mod boost_query;
+mod cached_filter_query;
 mod const_score_query;
 mod disjunction_max_query;
 mod empty_query;
 mod exclude;
 mod explanation;
 mod fuzzy_query;
+mod geo_query;
+mod hybrid_query;
 mod intersection;
+mod knn_query;
 mod more_like_this;
 mod phrase_prefix_query;
 mod phrase_query;
+pub(crate) mod profile;
 mod query;
 mod query_parser;
 mod range_query;
@@ -37,6 +42,7 @@ pub use self::bitset::BitSetDocSet;
 pub use self::bm25::{Bm25StatisticsProvider, Bm25Weight};
 pub use self::boolean_query::{BooleanQuery, BooleanWeight};
 pub use self::boost_query::{BoostQuery, BoostWeight};
+pub use self::cached_filter_query::{CachedFilterQuery, FilterCache};
 pub use self::const_score_query::{ConstScoreQuery, ConstScorer};
 pub use self::disjunction_max_query::DisjunctionMaxQuery;
 pub use self::empty_query::{EmptyQuery, EmptyScorer, EmptyWeight};
@@ -45,13 +51,21 @@ pub use self::explanation::Explanation;
 #[cfg(test)]
 pub(crate) use self::fuzzy_query::DfaWrapper;
 pub use self::fuzzy_query::FuzzyTermQuery;
+pub use self::geo_query::{GeoBoundingBoxQuery, GeoDistanceQuery, GeoPolygonQuery};
+pub use self::hybrid_query::{Fusion, HybridQuery};
 pub use self::intersection::{intersect_scorers, Intersection};
+pub use self::knn_query::{KnnQuery, VectorSimilarity};
 pub use self::more_like_this::{MoreLikeThisQuery, MoreLikeThisQueryBuilder};
 pub use self::phrase_prefix_query::PhrasePrefixQuery;
 pub use self::phrase_query::PhraseQuery;
-pub use self::query::{EnableScoring, Query, QueryClone};
-pub use self::query_parser::{QueryParser, QueryParserError};
-pub use self::range_query::{FastFieldRangeWeight, IPFastFieldRangeWeight, RangeQuery};
+pub use self::profile::{
+    QueryProfile, SearchProfile, SegmentCollectionProfile, SegmentQueryProfile,
+};
+pub use self::query::{EnableScoring, Query, QueryClone, QueryLeaf};
+pub use self::query_parser::{ElasticsearchQueryTranslator, QueryParser, QueryParserError};
+pub use self::range_query::{
+    FastFieldRangeWeight, IPFastFieldRangeWeight, PointsRangeWeight, RangeQuery,
+};
 pub use self::regex_query::RegexQuery;
 pub use self::reqopt_scorer::RequiredOptionalScorer;
 pub use self::score_combiner::{
@@ -1,6 +1,6 @@
 use super::PhraseWeight;
 use crate::query::bm25::Bm25Weight;
-use crate::query::{EnableScoring, Query, Weight};
+use crate::query::{EnableScoring, Query, QueryLeaf, Weight};
 use crate::schema::{Field, IndexRecordOption, Term};
 
 /// `PhraseQuery` matches a specific sequence of words.
@@ -146,4 +146,11 @@ impl Query for PhraseQuery {
             visitor(term, true);
         }
     }
+
+    fn query_leaves<'a>(&'a self, visitor: &mut dyn FnMut(QueryLeaf<'a>)) {
+        visitor(QueryLeaf::Phrase {
+            terms: &self.phrase_terms,
+            slop: self.slop,
+        });
+    }
 }
@@ -0,0 +1,345 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::docset::BUFFER_LEN;
+use crate::query::{BooleanQuery, EnableScoring, Explanation, Query, QueryLeaf, Scorer, Weight};
+use crate::schema::Term;
+use crate::{DocId, DocSet, Score, SegmentId, SegmentOrdinal, SegmentReader};
+
+/// The profile of a search, as returned by
+/// [`Searcher::search_with_profile`](crate::Searcher::search_with_profile).
+#[derive(Clone, Debug, Serialize)]
+pub struct SearchProfile {
+    /// The profile of the query.
+    pub query: QueryProfile,
+    /// The time spent collecting each segment, in the order of the segments of the searcher.
+    pub collection: Vec<SegmentCollectionProfile>,
+}
+
+/// The profile of a node of the query.
+///
+/// The clauses of a [`BooleanQuery`] are profiled as the children of its node. The timings of a
+/// node include the ones of its children.
+#[derive(Clone, Debug, Serialize)]
+pub struct QueryProfile {
+    /// The `Debug` representation of the query.
+    pub query: String,
+    /// The time spent creating the weight of the query.
+    pub weight_creation: Duration,
+    /// The profile of the query for each of the segments it was run on, in the order of the
+    /// segments of the searcher.
+    pub segments: Vec<SegmentQueryProfile>,
+    /// The profiles of the subqueries.
+    pub children: Vec<QueryProfile>,
+}
+
+/// The profile of a node of the query on one segment.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct SegmentQueryProfile {
+    /// The ordinal of the segment.
+    pub segment_ord: SegmentOrdinal,
+    /// The time spent creating the scorers.
+    pub scorer_creation: Duration,
+    /// The time spent advancing the scorers and computing the scores.
+    pub scoring: Duration,
+    /// The number of documents scored.
+    pub num_docs_scored: u64,
+    /// The number of documents jumped over when seeking the scorers.
+    pub num_docs_skipped: u64,
+}
+
+/// The time spent collecting one segment, including the scoring of its documents.
+#[derive(Clone, Debug, Serialize)]
+pub struct SegmentCollectionProfile {
+    /// The ordinal of the segment.
+    pub segment_ord: SegmentOrdinal,
+    /// The time spent collecting the segment.
+    pub collection: Duration,
+}
+
+#[derive(Default)]
+struct RecordedStats {
+    weight_creation: Duration,
+    segments: HashMap<SegmentId, SegmentQueryProfile>,
+}
+
+/// Records the profile of a node of the query while it runs.
+pub(crate) struct QueryProfileRecorder {
+    query: String,
+    children: Vec<Arc<QueryProfileRecorder>>,
+    stats: Mutex<RecordedStats>,
+}
+
+impl QueryProfileRecorder {
+    fn record_segment(&self, segment_id: SegmentId, record: impl FnOnce(&mut SegmentQueryProfile)) {
+        let mut stats = self.stats.lock().unwrap();
+        record(stats.segments.entry(segment_id).or_default());
+    }
+
+    /// Builds the profile of the node, with the segments ordered as in `segment_readers`.
+    pub fn profile(&self, segment_readers: &[SegmentReader]) -> QueryProfile {
+        let stats = self.stats.lock().unwrap();
+        let segments = segment_readers
+            .iter()
+            .enumerate()
+            .filter_map(|(segment_ord, segment_reader)| {
+                let segment_profile = stats.segments.get(&segment_reader.segment_id())?;
+                Some(SegmentQueryProfile {
+                    segment_ord: segment_ord as SegmentOrdinal,
+                    ..segment_profile.clone()
+                })
+            })
+            .collect();
+        QueryProfile {
+            query: self.query.clone(),
+            weight_creation: stats.weight_creation,
+            segments,
+            children: self
+                .children
+                .iter()
+                .map(|child| child.profile(segment_readers))
+                .collect(),
+        }
+    }
+}
+
+/// Wraps the nodes of the query to record their profile, and returns the wrapped query with the
+/// recorder of its root node.
+pub(crate) fn profile_query(query: &dyn Query) -> (Box<dyn Query>, Arc<QueryProfileRecorder>) {
+    let (profiled_query, children): (Box<dyn Query>, _) =
+        if let Some(boolean_query) = query.downcast_ref::<BooleanQuery>() {
+            let mut children = Vec::new();
+            let clauses = boolean_query
+                .clauses()
+                .iter()
+                .map(|(occur, subquery)| {
+                    let (profiled_subquery, child) = profile_query(subquery.as_ref());
+                    children.push(child);
+                    (*occur, profiled_subquery)
+                })
+                .collect();
+            (Box::new(BooleanQuery::new(clauses)), children)
+        } else {
+            (query.box_clone(), Vec::new())
+        };
+    let recorder = Arc::new(QueryProfileRecorder {
+        query: format!("{query:?}"),
+        children,
+        stats: Mutex::default(),
+    });
+    let profiled_query = ProfiledQuery {
+        query: profiled_query,
+        recorder: recorder.clone(),
+    };
+    (Box::new(profiled_query), recorder)
+}
+
+/// Records the profile of the wrapped query.
+struct ProfiledQuery {
+    query: Box<dyn Query>,
+    recorder: Arc<QueryProfileRecorder>,
+}
+
+impl Clone for ProfiledQuery {
+    fn clone(&self) -> Self {
+        ProfiledQuery {
+            query: self.query.box_clone(),
+            recorder: self.recorder.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for ProfiledQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Profiled({:?})", self.query)
+    }
+}
+
+impl Query for ProfiledQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let start = Instant::now();
+        let weight = self.query.weight(enable_scoring)?;
+        self.recorder.stats.lock().unwrap().weight_creation += start.elapsed();
+        Ok(Box::new(ProfiledWeight {
+            weight,
+            recorder: self.recorder.clone(),
+        }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.query.query_terms(visitor)
+    }
+
+    fn query_leaves<'a>(&'a self, visitor: &mut dyn FnMut(QueryLeaf<'a>)) {
+        self.query.query_leaves(visitor)
+    }
+}
+
+/// Records the time spent creating the scorers of the wrapped weight.
+///
+/// The scorers are consumed through their generic [`DocSet`] and [`Scorer`] methods, rather
+/// than through the specialized code paths of the weight, such as the pruning of the
+/// [`TopDocs`](crate::collector::TopDocs) collector.
+struct ProfiledWeight {
+    weight: Box<dyn Weight>,
+    recorder: Arc<QueryProfileRecorder>,
+}
+
+impl Weight for ProfiledWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let start = Instant::now();
+        let scorer = self.weight.scorer(reader, boost)?;
+        let scorer_creation = start.elapsed();
+        let segment_id = reader.segment_id();
+        self.recorder.record_segment(segment_id, |segment_profile| {
+            segment_profile.scorer_creation += scorer_creation;
+        });
+        Ok(Box::new(ProfiledScorer {
+            scorer,
+            recorder: self.recorder.clone(),
+            segment_id,
+            scoring: Duration::ZERO,
+            num_docs_scored: 0,
+            num_docs_skipped: 0,
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        self.weight.explain(reader, doc)
+    }
+}
+
+/// Records the time spent in the wrapped scorer, and the number of documents it scores and
+/// skips. The records are added to the profile when the scorer is dropped.
+struct ProfiledScorer {
+    scorer: Box<dyn Scorer>,
+    recorder: Arc<QueryProfileRecorder>,
+    segment_id: SegmentId,
+    scoring: Duration,
+    num_docs_scored: u64,
+    num_docs_skipped: u64,
+}
+
+impl DocSet for ProfiledScorer {
+    fn advance(&mut self) -> DocId {
+        let start = Instant::now();
+        let doc = self.scorer.advance();
+        self.scoring += start.elapsed();
+        doc
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        let start = Instant::now();
+        let current_doc = self.scorer.doc();
+        let doc = self.scorer.seek(target);
+        self.scoring += start.elapsed();
+        if target > current_doc + 1 {
+            self.num_docs_skipped += (target - current_doc - 1) as u64;
+        }
+        doc
+    }
+
+    fn fill_buffer(&mut self, buffer: &mut [DocId; BUFFER_LEN]) -> usize {
+        let start = Instant::now();
+        let num_docs = self.scorer.fill_buffer(buffer);
+        self.scoring += start.elapsed();
+        num_docs
+    }
+
+    fn doc(&self) -> DocId {
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+}
+
+impl Scorer for ProfiledScorer {
+    fn score(&mut self) -> Score {
+        let start = Instant::now();
+        let score = self.scorer.score();
+        self.scoring += start.elapsed();
+        self.num_docs_scored += 1;
+        score
+    }
+}
+
+impl Drop for ProfiledScorer {
+    fn drop(&mut self) {
+        self.recorder
+            .record_segment(self.segment_id, |segment_profile| {
+                segment_profile.scoring += self.scoring;
+                segment_profile.num_docs_scored += self.num_docs_scored;
+                segment_profile.num_docs_skipped += self.num_docs_skipped;
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::{Count, TopDocs};
+    use crate::query::QueryParser;
+    use crate::schema::{Schema, TEXT};
+    use crate::{doc, Index};
+
+    #[test]
+    fn test_search_with_profile() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "a b"))?;
+        index_writer.add_document(doc!(text => "a"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text => "b"))?;
+        index_writer.add_document(doc!(text => "a b c"))?;
+        index_writer.add_document(doc!(text => "c"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let query = QueryParser::for_index(&index, vec![text]).parse_query("+a b")?;
+
+        let (top_docs, profile) =
+            searcher.search_with_profile(query.as_ref(), &TopDocs::with_limit(10))?;
+        assert_eq!(
+            top_docs,
+            searcher.search(query.as_ref(), &TopDocs::with_limit(10))?
+        );
+        assert_eq!(profile.collection.len(), 2);
+        assert_eq!(profile.query.query, format!("{query:?}"));
+        assert_eq!(profile.query.children.len(), 2);
+        let num_docs_scored: u64 = profile
+            .query
+            .segments
+            .iter()
+            .map(|segment_profile| segment_profile.num_docs_scored)
+            .sum();
+        assert_eq!(num_docs_scored, 3);
+        let segment_ords: Vec<u32> = profile
+            .query
+            .segments
+            .iter()
+            .map(|segment_profile| segment_profile.segment_ord)
+            .collect();
+        assert_eq!(segment_ords, vec![0, 1]);
+        for child in &profile.query.children {
+            assert_eq!(child.segments.len(), 2);
+        }
+
+        // Without scoring, the documents are not scored.
+        let (count, profile) = searcher.search_with_profile(query.as_ref(), &Count)?;
+        assert_eq!(count, 3);
+        assert!(profile
+            .query
+            .segments
+            .iter()
+            .all(|segment_profile| segment_profile.num_docs_scored == 0));
+        assert!(serde_json::to_string(&profile)?.contains("weight_creation"));
+        Ok(())
+    }
+}
@@ -3,7 +3,7 @@ use std::fmt;
 use downcast_rs::impl_downcast;
 
 use super::bm25::Bm25StatisticsProvider;
-use super::Weight;
+use super::{FuzzyTermQuery, Weight};
 use crate::core::searcher::Searcher;
 use crate::query::Explanation;
 use crate::schema::Schema;
@@ -160,6 +160,34 @@ pub trait Query: QueryClone + Send + Sync + downcast_rs::Downcast + fmt::Debug {
     /// Note that there can be multiple instances of any given term
     /// in a query and deduplication must be handled by the visitor.
     fn query_terms<'a>(&'a self, _visitor: &mut dyn FnMut(&'a Term, bool)) {}
+
+    /// Extract the leaves of the query that can be matched against the tokens of a text,
+    /// and pass them to the given closure.
+    ///
+    /// Unlike [`Query::query_terms`], the terms of a phrase are reported together, and the
+    /// leaves of the excluded subqueries of a boolean query are not visited.
+    ///
+    /// By default, each term reported by [`Query::query_terms`] is reported as a
+    /// [`QueryLeaf::Term`].
+    fn query_leaves<'a>(&'a self, visitor: &mut dyn FnMut(QueryLeaf<'a>)) {
+        self.query_terms(&mut |term, _| visitor(QueryLeaf::Term(term)));
+    }
+}
+
+/// A leaf of a query, as visited by [`Query::query_leaves`].
+#[derive(Clone, Copy, Debug)]
+pub enum QueryLeaf<'a> {
+    /// A term.
+    Term(&'a Term),
+    /// A phrase, as its terms with their offset within the phrase, and its slop.
+    Phrase {
+        /// Terms of the phrase and their offset.
+        terms: &'a [(usize, Term)],
+        /// Slop of the phrase.
+        slop: u32,
+    },
+    /// A fuzzy term query.
+    Fuzzy(&'a FuzzyTermQuery),
 }
 
 /// Implements `box_clone`.
@@ -188,6 +216,10 @@ impl Query for Box<dyn Query> {
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         self.as_ref().query_terms(visitor);
     }
+
+    fn query_leaves<'a>(&'a self, visitor: &mut dyn FnMut(QueryLeaf<'a>)) {
+        self.as_ref().query_leaves(visitor);
+    }
 }
 
 impl QueryClone for Box<dyn Query> {
@@ -0,0 +1,809 @@
+use std::ops::Bound;
+
+use serde_json::{Map, Value};
+
+use super::query_parser::{QueryParser, QueryParserError};
+use crate::core::Index;
+use crate::query::{
+    AllQuery, BooleanQuery, BoostQuery, ConstScoreQuery, EmptyQuery, Occur, Query, RangeQuery,
+    RegexQuery, TermQuery, TermSetQuery,
+};
+use crate::schema::{Field, FieldType, IndexRecordOption, Schema, Term, Type};
+use crate::tokenizer::TokenizerManager;
+use crate::{DateTime, Score};
+
+/// Translates queries written in the Elasticsearch query DSL into tantivy queries, to ease the
+/// migration of applications built on Elasticsearch.
+///
+/// The following subset of the DSL is supported:
+/// * `bool`, with its `must`, `filter`, `should` and `must_not` clauses, and a
+///   `minimum_should_match` of `0` or `1`,
+/// * `term` and `terms`,
+/// * `match`, with the `or` and `and` operators, and `match_phrase`, with a `slop`,
+/// * `range`, with the `gt`, `gte`, `lt` and `lte` bounds,
+/// * `exists`, `prefix` and `wildcard`,
+/// * `match_all` and `match_none`.
+///
+/// All of them accept a `boost`. Any other query or parameter is rejected with a
+/// [`QueryParserError::UnsupportedQuery`] naming it, and malformed queries are rejected with a
+/// [`QueryParserError::SyntaxError`].
+///
+/// As in Elasticsearch, the values of the `term`, `terms`, `range`, `prefix` and `wildcard`
+/// queries are not analyzed, while the texts of the `match` and `match_phrase` queries are
+/// tokenized with the search tokenizer of the field. Dates are given either as RFC 3339 strings
+/// or as a number of milliseconds since the epoch, and bytes as base64 strings.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::ElasticsearchQueryTranslator;
+/// use tantivy::schema::{Schema, INDEXED, STRING, TEXT};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let status = schema_builder.add_text_field("status", STRING);
+/// let year = schema_builder.add_u64_field("year", INDEXED);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(title => "The Old Man and the Sea", status => "published", year => 1952u64))?;
+/// index_writer.add_document(doc!(title => "The Garden of Eden", status => "draft", year => 1986u64))?;
+/// index_writer.commit()?;
+///
+/// let translator = ElasticsearchQueryTranslator::for_index(&index);
+/// let query = translator.translate_str(
+///     r#"{
+///         "bool": {
+///             "must": { "match": { "title": "old sea" } },
+///             "filter": [
+///                 { "term": { "status": "published" } },
+///                 { "range": { "year": { "gte": 1950, "lt": 1960 } } }
+///             ]
+///         }
+///     }"#,
+/// )?;
+/// let searcher = index.reader()?.searcher();
+/// assert_eq!(searcher.search(&query, &Count)?, 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ElasticsearchQueryTranslator {
+    schema: Schema,
+    tokenizer_manager: TokenizerManager,
+    query_parser: QueryParser,
+}
+
+/// The parameters of a query, none of which may be left unsupported.
+struct Params<'a> {
+    query_type: &'a str,
+    params: Option<&'a Map<String, Value>>,
+}
+
+impl<'a> Params<'a> {
+    fn new(query_type: &'a str, params: &'a Value) -> Result<Params<'a>, QueryParserError> {
+        let params = params.as_object().ok_or_else(|| {
+            QueryParserError::SyntaxError(format!(
+                "The {query_type:?} query expects an object, got {params}"
+            ))
+        })?;
+        Ok(Params {
+            query_type,
+            params: Some(params),
+        })
+    }
+
+    fn empty(query_type: &'a str) -> Params<'a> {
+        Params {
+            query_type,
+            params: None,
+        }
+    }
+
+    fn keys(&self) -> impl Iterator<Item = &'a str> {
+        self.params
+            .into_iter()
+            .flat_map(|params| params.keys().map(String::as_str))
+    }
+
+    fn check_keys(&self, supported: &[&str]) -> Result<(), QueryParserError> {
+        if let Some(key) = self.keys().find(|key| !supported.contains(key)) {
+            return Err(QueryParserError::UnsupportedQuery(format!(
+                "The parameter {key:?} of the {:?} query is not supported",
+                self.query_type
+            )));
+        }
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> Option<&'a Value> {
+        self.params.and_then(|params| params.get(key))
+    }
+
+    fn invalid(&self, key: &str, expected: &str, value: &Value) -> QueryParserError {
+        QueryParserError::SyntaxError(format!(
+            "The parameter {key:?} of the {:?} query expects {expected}, got {value}",
+            self.query_type
+        ))
+    }
+
+    fn str(&self, key: &str) -> Result<Option<&'a str>, QueryParserError> {
+        self.get(key)
+            .map(|value| {
+                value
+                    .as_str()
+                    .ok_or_else(|| self.invalid(key, "a string", value))
+            })
+            .transpose()
+    }
+
+    fn u64(&self, key: &str) -> Result<Option<u64>, QueryParserError> {
+        self.get(key)
+            .map(|value| {
+                match value {
+                    Value::String(text) => text.parse().ok(),
+                    _ => value.as_u64(),
+                }
+                .ok_or_else(|| self.invalid(key, "a positive integer", value))
+            })
+            .transpose()
+    }
+
+    fn boost(&self) -> Result<Option<Score>, QueryParserError> {
+        self.get("boost")
+            .map(|value| match value.as_f64() {
+                Some(boost) if boost >= 0.0 => Ok(boost as Score),
+                _ => Err(self.invalid("boost", "a positive number", value)),
+            })
+            .transpose()
+    }
+}
+
+/// Returns the single entry of an object, such as the type and the parameters of a query.
+fn single_entry<'a>(
+    value: &'a Value,
+    context: &str,
+) -> Result<(&'a str, &'a Value), QueryParserError> {
+    match value.as_object() {
+        Some(object) if object.len() == 1 => {
+            let (key, value) = object.iter().next().unwrap();
+            Ok((key.as_str(), value))
+        }
+        _ => Err(QueryParserError::SyntaxError(format!(
+            "Expected {context} as an object with a single key, got {value}"
+        ))),
+    }
+}
+
+/// Splits the parameters of a query targeting a single field, given either in the short form
+/// `{"field": value}`, or in the long form `{"field": {value_key: value, ...}}`.
+fn field_params<'a>(
+    query_type: &'a str,
+    params: &'a Value,
+    value_key: &str,
+) -> Result<(&'a str, &'a Value, Params<'a>), QueryParserError> {
+    let (field_name, field_params) = single_entry(params, &format!("a {query_type:?} query"))?;
+    if !field_params.is_object() {
+        return Ok((field_name, field_params, Params::empty(query_type)));
+    }
+    let params = Params::new(query_type, field_params)?;
+    let value = params.get(value_key).ok_or_else(|| {
+        QueryParserError::SyntaxError(format!(
+            "The {query_type:?} query on {field_name:?} has no {value_key:?}"
+        ))
+    })?;
+    Ok((field_name, value, params))
+}
+
+fn scalar_to_string(value: &Value) -> Result<String, QueryParserError> {
+    match value {
+        Value::String(text) => Ok(text.clone()),
+        Value::Number(_) | Value::Bool(_) => Ok(value.to_string()),
+        _ => Err(QueryParserError::SyntaxError(format!(
+            "Expected a string, a number or a boolean, got {value}"
+        ))),
+    }
+}
+
+fn boosted(query: Box<dyn Query>, boost: Option<Score>) -> Box<dyn Query> {
+    match boost {
+        Some(boost) if boost != 1.0 => Box::new(BoostQuery::new(query, boost)),
+        _ => query,
+    }
+}
+
+fn wildcard_to_regex(pattern: &str) -> String {
+    let mut regex = String::with_capacity(pattern.len());
+    let mut buffer = [0u8; 4];
+    for c in pattern.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            _ => regex.push_str(&regex::escape(c.encode_utf8(&mut buffer))),
+        }
+    }
+    regex
+}
+
+impl ElasticsearchQueryTranslator {
+    /// Creates a translator for the given schema, tokenizing the texts of the `match` queries
+    /// with the tokenizers of the `tokenizer_manager`.
+    pub fn new(
+        schema: Schema,
+        tokenizer_manager: TokenizerManager,
+    ) -> ElasticsearchQueryTranslator {
+        let query_parser = QueryParser::new(schema.clone(), Vec::new(), tokenizer_manager.clone());
+        ElasticsearchQueryTranslator {
+            schema,
+            tokenizer_manager,
+            query_parser,
+        }
+    }
+
+    /// Creates a translator for the schema and the tokenizers of an index.
+    pub fn for_index(index: &Index) -> ElasticsearchQueryTranslator {
+        ElasticsearchQueryTranslator::new(index.schema(), index.tokenizers().clone())
+    }
+
+    /// Translates a query given as JSON text.
+    pub fn translate_str(&self, query: &str) -> Result<Box<dyn Query>, QueryParserError> {
+        let query: Value = serde_json::from_str(query)
+            .map_err(|err| QueryParserError::SyntaxError(format!("Invalid JSON: {err}")))?;
+        self.translate(&query)
+    }
+
+    /// Translates a query, that is the value of the `query` key of an Elasticsearch search
+    /// request.
+    pub fn translate(&self, query: &Value) -> Result<Box<dyn Query>, QueryParserError> {
+        let (query_type, params) = single_entry(query, "a query")?;
+        match query_type {
+            "bool" => self.translate_bool(params),
+            "term" => self.translate_term(params),
+            "terms" => self.translate_terms(params),
+            "match" => self.translate_match(params),
+            "match_phrase" => self.translate_match_phrase(params),
+            "range" => self.translate_range(params),
+            "exists" => self.translate_exists(params),
+            "prefix" | "wildcard" => self.translate_pattern(query_type, params),
+            "match_all" | "match_none" => {
+                let params = Params::new(query_type, params)?;
+                params.check_keys(&["boost"])?;
+                let query: Box<dyn Query> = if query_type == "match_all" {
+                    Box::new(AllQuery)
+                } else {
+                    Box::new(EmptyQuery)
+                };
+                Ok(boosted(query, params.boost()?))
+            }
+            _ => Err(QueryParserError::UnsupportedQuery(format!(
+                "The {query_type:?} query is not supported"
+            ))),
+        }
+    }
+
+    fn resolve_field<'a>(&self, full_path: &'a str) -> Result<(Field, &'a str), QueryParserError> {
+        self.query_parser
+            .split_full_path(full_path)
+            .ok_or_else(|| QueryParserError::FieldDoesNotExist(full_path.to_string()))
+    }
+
+    /// Returns the term of a value, which is not analyzed.
+    fn term_for_value(
+        &self,
+        field: Field,
+        json_path: &str,
+        value: &Value,
+    ) -> Result<Term, QueryParserError> {
+        let field_entry = self.schema.get_field_entry(field);
+        let text = scalar_to_string(value)?;
+        match (field_entry.field_type(), value) {
+            (FieldType::Str(str_options), _) if json_path.is_empty() => {
+                if str_options.get_indexing_options().is_none() {
+                    return Err(QueryParserError::FieldNotIndexed(
+                        field_entry.name().to_string(),
+                    ));
+                }
+                Ok(Term::from_field_text(field, &text))
+            }
+            (FieldType::Date(_), Value::Number(number)) if json_path.is_empty() => {
+                let timestamp_millis = number.as_i64().ok_or_else(|| {
+                    QueryParserError::SyntaxError(format!(
+                        "Expected a number of milliseconds since the epoch, got {number}"
+                    ))
+                })?;
+                Ok(Term::from_field_date(
+                    field,
+                    DateTime::from_timestamp_millis(timestamp_millis),
+                ))
+            }
+            _ => self
+                .query_parser
+                .compute_boundary_term(field, json_path, &text),
+        }
+    }
+
+    fn translate_clauses(
+        &self,
+        clauses: Option<&Value>,
+    ) -> Result<Vec<Box<dyn Query>>, QueryParserError> {
+        match clauses {
+            None => Ok(Vec::new()),
+            Some(Value::Array(clauses)) => clauses
+                .iter()
+                .map(|clause| self.translate(clause))
+                .collect(),
+            Some(clause) => Ok(vec![self.translate(clause)?]),
+        }
+    }
+
+    fn translate_bool(&self, params: &Value) -> Result<Box<dyn Query>, QueryParserError> {
+        let params = Params::new("bool", params)?;
+        params.check_keys(&[
+            "must",
+            "filter",
+            "should",
+            "must_not",
+            "minimum_should_match",
+            "boost",
+        ])?;
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        for query in self.translate_clauses(params.get("must"))? {
+            clauses.push((Occur::Must, query));
+        }
+        // Filters do not contribute to the score.
+        for query in self.translate_clauses(params.get("filter"))? {
+            clauses.push((Occur::Must, Box::new(ConstScoreQuery::new(query, 0.0))));
+        }
+        let should_queries = self.translate_clauses(params.get("should"))?;
+        let default_minimum_should_match =
+            u64::from(clauses.is_empty() && !should_queries.is_empty());
+        let minimum_should_match = params
+            .u64("minimum_should_match")?
+            .unwrap_or(default_minimum_should_match);
+        if minimum_should_match > 1 {
+            return Err(QueryParserError::UnsupportedQuery(format!(
+                "A minimum_should_match of {minimum_should_match} in a \"bool\" query, only 0 and \
+                 1 are supported"
+            )));
+        }
+        let should_clauses = should_queries
+            .into_iter()
+            .map(|query| (Occur::Should, query))
+            .collect::<Vec<_>>();
+        if minimum_should_match == 0 || clauses.is_empty() {
+            // Without required clauses, tantivy requires one of the optional clauses to match.
+            if minimum_should_match == 0 && clauses.is_empty() && !should_clauses.is_empty() {
+                clauses.push((
+                    Occur::Must,
+                    Box::new(ConstScoreQuery::new(Box::new(AllQuery), 0.0)),
+                ));
+            }
+            clauses.extend(should_clauses);
+        } else if should_clauses.is_empty() {
+            clauses.push((Occur::Must, Box::new(EmptyQuery)));
+        } else {
+            clauses.push((Occur::Must, Box::new(BooleanQuery::new(should_clauses))));
+        }
+        let must_not_queries = self.translate_clauses(params.get("must_not"))?;
+        if clauses.is_empty() {
+            let all_query: Box<dyn Query> = if must_not_queries.is_empty() {
+                Box::new(AllQuery)
+            } else {
+                Box::new(ConstScoreQuery::new(Box::new(AllQuery), 0.0))
+            };
+            clauses.push((Occur::Must, all_query));
+        }
+        for query in must_not_queries {
+            clauses.push((Occur::MustNot, query));
+        }
+        Ok(boosted(
+            Box::new(BooleanQuery::new(clauses)),
+            params.boost()?,
+        ))
+    }
+
+    fn translate_term(&self, params: &Value) -> Result<Box<dyn Query>, QueryParserError> {
+        let (field_name, value, params) = field_params("term", params, "value")?;
+        params.check_keys(&["value", "boost"])?;
+        let (field, json_path) = self.resolve_field(field_name)?;
+        let term = self.term_for_value(field, json_path, value)?;
+        let query = Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs));
+        Ok(boosted(query, params.boost()?))
+    }
+
+    fn translate_terms(&self, params: &Value) -> Result<Box<dyn Query>, QueryParserError> {
+        let params = Params::new("terms", params)?;
+        let field_names: Vec<&str> = params.keys().filter(|&key| key != "boost").collect();
+        let [field_name] = field_names[..] else {
+            return Err(QueryParserError::SyntaxError(format!(
+                "The \"terms\" query expects a single field, got {field_names:?}"
+            )));
+        };
+        let values = params
+            .get(field_name)
+            .and_then(Value::as_array)
+            .ok_or_else(|| {
+                QueryParserError::UnsupportedQuery(format!(
+                    "The \"terms\" query on {field_name:?} is only supported with an array of \
+                     values"
+                ))
+            })?;
+        let (field, json_path) = self.resolve_field(field_name)?;
+        let terms = values
+            .iter()
+            .map(|value| self.term_for_value(field, json_path, value))
+            .collect::<Result<Vec<Term>, QueryParserError>>()?;
+        Ok(boosted(Box::new(TermSetQuery::new(terms)), params.boost()?))
+    }
+
+    fn translate_match(&self, params: &Value) -> Result<Box<dyn Query>, QueryParserError> {
+        let (field_name, value, params) = field_params("match", params, "query")?;
+        params.check_keys(&["query", "operator", "boost"])?;
+        let occur = match params.str("operator")? {
+            None => Occur::Should,
+            Some(operator) if operator.eq_ignore_ascii_case("or") => Occur::Should,
+            Some(operator) if operator.eq_ignore_ascii_case("and") => Occur::Must,
+            Some(operator) => {
+                return Err(params.invalid("operator", "\"or\" or \"and\"", &operator.into()))
+            }
+        };
+        let (field, json_path) = self.resolve_field(field_name)?;
+        let field_entry = self.schema.get_field_entry(field);
+        let query: Box<dyn Query> = match field_entry.field_type() {
+            FieldType::Str(str_options) => {
+                let text = scalar_to_string(value)?;
+                let indexing_options = str_options.get_indexing_options().ok_or_else(|| {
+                    QueryParserError::FieldNotIndexed(field_entry.name().to_string())
+                })?;
+                let text_analyzer = self
+                    .tokenizer_manager
+                    .get(indexing_options.search_tokenizer())
+                    .ok_or_else(|| QueryParserError::UnknownTokenizer {
+                        field: field_entry.name().to_string(),
+                        tokenizer: indexing_options.search_tokenizer().to_string(),
+                    })?;
+                let mut term_queries: Vec<Box<dyn Query>> = Vec::new();
+                let mut token_stream = text_analyzer.token_stream(&text);
+                token_stream.process(&mut |token| {
+                    let term = Term::from_field_text(field, &token.text);
+                    term_queries.push(Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs)));
+                });
+                match term_queries.len() {
+                    0 => Box::new(EmptyQuery),
+                    1 => term_queries.pop().unwrap(),
+                    _ => Box::new(BooleanQuery::new(
+                        term_queries
+                            .into_iter()
+                            .map(|query| (occur, query))
+                            .collect(),
+                    )),
+                }
+            }
+            FieldType::JsonObject(_) => {
+                let text = scalar_to_string(value)?;
+                let literals = self
+                    .query_parser
+                    .compute_logical_ast_for_leaf(field, json_path, &text, 0, false)?;
+                self.query_parser.convert_literals_to_query(literals)
+            }
+            _ => {
+                let term = self.term_for_value(field, json_path, value)?;
+                Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs))
+            }
+        };
+        Ok(boosted(query, params.boost()?))
+    }
+
+    fn translate_match_phrase(&self, params: &Value) -> Result<Box<dyn Query>, QueryParserError> {
+        let (field_name, value, params) = field_params("match_phrase", params, "query")?;
+        params.check_keys(&["query", "slop", "boost"])?;
+        let slop = match params.u64("slop")? {
+            Some(slop) => u32::try_from(slop).map_err(|_| {
+                params.invalid("slop", "a slop fitting in 32 bits", &Value::from(slop))
+            })?,
+            None => 0,
+        };
+        let (field, json_path) = self.resolve_field(field_name)?;
+        let text = scalar_to_string(value)?;
+        let literals = self
+            .query_parser
+            .compute_logical_ast_for_leaf(field, json_path, &text, slop, false)?;
+        let query = self.query_parser.convert_literals_to_query(literals);
+        Ok(boosted(query, params.boost()?))
+    }
+
+    fn translate_range(&self, params: &Value) -> Result<Box<dyn Query>, QueryParserError> {
+        let (field_name, field_params) = single_entry(params, "a \"range\" query")?;
+        let params = Params::new("range", field_params)?;
+        params.check_keys(&["gt", "gte", "lt", "lte", "boost"])?;
+        let (field, json_path) = self.resolve_field(field_name)?;
+        let field_entry = self.schema.get_field_entry(field);
+        if field_entry.field_type().value_type() == Type::Json {
+            return Err(QueryParserError::UnsupportedQuery(
+                "Range queries are not supported on json fields".to_string(),
+            ));
+        }
+        let bound = |excluded: &str, included: &str| -> Result<Bound<Term>, QueryParserError> {
+            let excluded_value = params.get(excluded).filter(|value| !value.is_null());
+            let included_value = params.get(included).filter(|value| !value.is_null());
+            match (excluded_value, included_value) {
+                (Some(_), Some(_)) => Err(QueryParserError::SyntaxError(format!(
+                    "The \"range\" query on {field_name:?} has both {excluded:?} and {included:?}"
+                ))),
+                (Some(value), None) => Ok(Bound::Excluded(
+                    self.term_for_value(field, json_path, value)?,
+                )),
+                (None, Some(value)) => Ok(Bound::Included(
+                    self.term_for_value(field, json_path, value)?,
+                )),
+                (None, None) => Ok(Bound::Unbounded),
+            }
+        };
+        let lower_bound = bound("gt", "gte")?;
+        let upper_bound = bound("lt", "lte")?;
+        let query = Box::new(RangeQuery::new_term_bounds(
+            field_entry.name().to_string(),
+            field_entry.field_type().value_type(),
+            &lower_bound,
+            &upper_bound,
+        ));
+        Ok(boosted(query, params.boost()?))
+    }
+
+    fn translate_exists(&self, params: &Value) -> Result<Box<dyn Query>, QueryParserError> {
+        let params = Params::new("exists", params)?;
+        params.check_keys(&["field", "boost"])?;
+        let field_name = params.str("field")?.ok_or_else(|| {
+            QueryParserError::SyntaxError("The \"exists\" query has no \"field\"".to_string())
+        })?;
+        let (field, json_path) = self.resolve_field(field_name)?;
+        let field_entry = self.schema.get_field_entry(field);
+        let field_type = field_entry.field_type();
+        if !json_path.is_empty()
+            || matches!(
+                field_type.value_type(),
+                Type::Json | Type::DenseVector | Type::GeoPoint
+            )
+        {
+            return Err(QueryParserError::UnsupportedQuery(format!(
+                "The \"exists\" query is not supported on the {:?} field {field_name:?}",
+                field_type.value_type()
+            )));
+        }
+        if !field_type.is_indexed() && !field_type.is_fast() {
+            return Err(QueryParserError::FieldNotIndexed(field_name.to_string()));
+        }
+        let query = Box::new(RangeQuery::new_term_bounds(
+            field_entry.name().to_string(),
+            field_type.value_type(),
+            &Bound::Unbounded,
+            &Bound::Unbounded,
+        ));
+        Ok(boosted(query, params.boost()?))
+    }
+
+    /// Translates the `prefix` and `wildcard` queries, into a `RegexQuery`.
+    fn translate_pattern(
+        &self,
+        query_type: &str,
+        params: &Value,
+    ) -> Result<Box<dyn Query>, QueryParserError> {
+        let (field_name, value, params) = field_params(query_type, params, "value")?;
+        params.check_keys(&["value", "boost"])?;
+        let (field, json_path) = self.resolve_field(field_name)?;
+        let field_entry = self.schema.get_field_entry(field);
+        let FieldType::Str(str_options) = field_entry.field_type() else {
+            return Err(QueryParserError::UnsupportedQuery(format!(
+                "The {query_type:?} query is only supported on text fields"
+            )));
+        };
+        if !json_path.is_empty() {
+            return Err(QueryParserError::FieldDoesNotExist(field_name.to_string()));
+        }
+        if str_options.get_indexing_options().is_none() {
+            return Err(QueryParserError::FieldNotIndexed(field_name.to_string()));
+        }
+        let pattern = value.as_str().ok_or_else(|| {
+            QueryParserError::SyntaxError(format!(
+                "The {query_type:?} query expects a string, got {value}"
+            ))
+        })?;
+        let regex = if query_type == "prefix" {
+            format!("{}.*", regex::escape(pattern))
+        } else {
+            wildcard_to_regex(pattern)
+        };
+        let query = RegexQuery::from_pattern(&regex, field)
+            .map_err(|err| QueryParserError::SyntaxError(err.to_string()))?;
+        Ok(boosted(Box::new(query), params.boost()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::ElasticsearchQueryTranslator;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::QueryParserError;
+    use crate::schema::{Schema, FAST, INDEXED, STRING, TEXT};
+    use crate::{doc, DateTime, Index};
+
+    fn create_index() -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let year = schema_builder.add_i64_field("year", INDEXED | FAST);
+        let date = schema_builder.add_date_field("date", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(
+            title => "the quick brown fox",
+            tag => "Animal",
+            year => 2001i64,
+            date => DateTime::from_timestamp_secs(1_000),
+        ))?;
+        index_writer.add_document(doc!(
+            title => "the lazy dog",
+            tag => "Animal",
+            year => 2010i64,
+        ))?;
+        index_writer.add_document(doc!(title => "a brown paper bag", tag => "Object"))?;
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    #[test]
+    fn test_elasticsearch_queries() -> crate::Result<()> {
+        let index = create_index()?;
+        let searcher = index.reader()?.searcher();
+        let translator = ElasticsearchQueryTranslator::for_index(&index);
+        let count = |query: serde_json::Value| -> crate::Result<usize> {
+            searcher.search(&*translator.translate(&query)?, &Count)
+        };
+        assert_eq!(count(json!({"match_all": {}}))?, 3);
+        assert_eq!(count(json!({"match_none": {}}))?, 0);
+        assert_eq!(count(json!({"term": {"tag": "Animal"}}))?, 2);
+        assert_eq!(count(json!({"term": {"tag": "animal"}}))?, 0);
+        assert_eq!(
+            count(json!({"term": {"year": {"value": 2001, "boost": 2.0}}}))?,
+            1
+        );
+        assert_eq!(
+            count(json!({"terms": {"tag": ["Object", "Other"], "boost": 2}}))?,
+            1
+        );
+        assert_eq!(count(json!({"match": {"title": "brown dog"}}))?, 3);
+        assert_eq!(
+            count(json!({"match": {"title": {"query": "brown dog", "operator": "and"}}}))?,
+            0
+        );
+        assert_eq!(count(json!({"match": {"title": "BROWN"}}))?, 2);
+        assert_eq!(count(json!({"match_phrase": {"title": "quick brown"}}))?, 1);
+        assert_eq!(count(json!({"match_phrase": {"title": "quick fox"}}))?, 0);
+        assert_eq!(
+            count(json!({"match_phrase": {"title": {"query": "quick fox", "slop": 1}}}))?,
+            1
+        );
+        assert_eq!(
+            count(json!({"range": {"year": {"gte": 2001, "lt": 2010}}}))?,
+            1
+        );
+        assert_eq!(count(json!({"range": {"year": {"gt": 2001}}}))?, 1);
+        assert_eq!(count(json!({"range": {"date": {"lte": 1_000_000}}}))?, 1);
+        assert_eq!(
+            count(json!({"range": {"date": {"gt": "1970-01-01T00:16:39Z"}}}))?,
+            1
+        );
+        assert_eq!(count(json!({"exists": {"field": "year"}}))?, 2);
+        assert_eq!(count(json!({"exists": {"field": "date"}}))?, 1);
+        assert_eq!(count(json!({"prefix": {"tag": "Ani"}}))?, 2);
+        assert_eq!(count(json!({"prefix": {"title": {"value": "bro"}}}))?, 2);
+        assert_eq!(count(json!({"wildcard": {"title": "b?g"}}))?, 1);
+        assert_eq!(count(json!({"wildcard": {"tag": "*j*t"}}))?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_elasticsearch_bool_query() -> crate::Result<()> {
+        let index = create_index()?;
+        let searcher = index.reader()?.searcher();
+        let translator = ElasticsearchQueryTranslator::for_index(&index);
+        let count = |query: serde_json::Value| -> crate::Result<usize> {
+            searcher.search(&*translator.translate(&query)?, &Count)
+        };
+        assert_eq!(count(json!({"bool": {}}))?, 3);
+        assert_eq!(
+            count(json!({"bool": {"must_not": {"term": {"tag": "Object"}}}}))?,
+            2
+        );
+        assert_eq!(
+            count(json!({"bool": {
+                "filter": [{"term": {"tag": "Animal"}}],
+                "should": [{"match": {"title": "fox"}}]
+            }}))?,
+            2
+        );
+        assert_eq!(
+            count(json!({"bool": {
+                "filter": [{"term": {"tag": "Animal"}}],
+                "should": [{"match": {"title": "fox"}}],
+                "minimum_should_match": 1
+            }}))?,
+            1
+        );
+        assert_eq!(
+            count(json!({"bool": {
+                "should": [{"match": {"title": "fox"}}, {"match": {"title": "bag"}}],
+            }}))?,
+            2
+        );
+        assert_eq!(
+            count(json!({"bool": {
+                "should": [{"match": {"title": "fox"}}],
+                "minimum_should_match": "0"
+            }}))?,
+            3
+        );
+        assert_eq!(
+            count(json!({"bool": {
+                "must": {"match": {"title": "brown"}},
+                "must_not": [{"term": {"tag": "Object"}}]
+            }}))?,
+            1
+        );
+
+        // The filters do not contribute to the score.
+        let query = translator.translate(&json!({"bool": {
+            "must": {"match": {"title": "fox"}},
+            "filter": {"term": {"tag": "Animal"}}
+        }}))?;
+        let filtered_score = searcher.search(&*query, &TopDocs::with_limit(1))?[0].0;
+        let query = translator.translate(&json!({"match": {"title": "fox"}}))?;
+        let score = searcher.search(&*query, &TopDocs::with_limit(1))?[0].0;
+        assert_eq!(filtered_score, score);
+        Ok(())
+    }
+
+    #[test]
+    fn test_elasticsearch_unsupported_queries() -> crate::Result<()> {
+        let index = create_index()?;
+        let translator = ElasticsearchQueryTranslator::for_index(&index);
+        let error = |query: &str| translator.translate_str(query).err().unwrap();
+        assert_eq!(
+            error(r#"{"fuzzy": {"title": "fox"}}"#),
+            QueryParserError::UnsupportedQuery("The \"fuzzy\" query is not supported".to_string())
+        );
+        assert_eq!(
+            error(r#"{"match": {"title": {"query": "fox", "fuzziness": 1}}}"#),
+            QueryParserError::UnsupportedQuery(
+                "The parameter \"fuzziness\" of the \"match\" query is not supported".to_string()
+            )
+        );
+        assert!(matches!(
+            error(r#"{"bool": {"should": [], "minimum_should_match": 2}}"#),
+            QueryParserError::UnsupportedQuery(_)
+        ));
+        assert_eq!(
+            error(r#"{"term": {"missing": "fox"}}"#),
+            QueryParserError::FieldDoesNotExist("missing".to_string())
+        );
+        assert!(matches!(
+            error(r#"{"term": {"tag": "a", "title": "b"}}"#),
+            QueryParserError::SyntaxError(_)
+        ));
+        assert!(matches!(
+            error(r#"{"term": {"year": "two thousand"}}"#),
+            QueryParserError::ExpectedInt(_)
+        ));
+        assert!(matches!(
+            error(r#"{"range": {"year": {"gt": 1, "gte": 2}}}"#),
+            QueryParserError::SyntaxError(_)
+        ));
+        assert!(matches!(
+            error("{\"term\""),
+            QueryParserError::SyntaxError(_)
+        ));
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@ use std::fmt;
 use std::ops::Bound;
 
 use crate::query::Occur;
-use crate::schema::{Field, Term, Type};
+use crate::schema::{Field, GeoPoint, Term, Type};
 use crate::Score;
 
 #[derive(Clone)]
@@ -24,6 +24,11 @@ pub enum LogicalLiteral {
         value_type: Type,
         elements: Vec<Term>,
     },
+    GeoDistance {
+        field: Field,
+        center: GeoPoint,
+        distance: f64,
+    },
     All,
 }
 
@@ -122,6 +127,15 @@ impl fmt::Debug for LogicalLiteral {
                 }
                 write!(formatter, "]")
             }
+            LogicalLiteral::GeoDistance {
+                field,
+                center,
+                distance,
+            } => write!(
+                formatter,
+                "GeoDistance(field={}, center={center}, distance={distance}m)",
+                field.field_id()
+            ),
             LogicalLiteral::All => write!(formatter, "*"),
         }
     }
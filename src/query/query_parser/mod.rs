@@ -1,4 +1,6 @@
+mod elasticsearch;
 mod query_parser;
 
 pub mod logical_ast;
+pub use self::elasticsearch::ElasticsearchQueryTranslator;
 pub use self::query_parser::{QueryParser, QueryParserError};
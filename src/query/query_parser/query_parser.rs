@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::net::{AddrParseError, IpAddr};
 use std::num::{ParseFloatError, ParseIntError};
 use std::ops::Bound;
@@ -15,16 +16,16 @@ use crate::core::json_utils::{
 use crate::core::Index;
 use crate::query::range_query::{is_type_valid_for_fastfield_range_query, RangeQuery};
 use crate::query::{
-    AllQuery, BooleanQuery, BoostQuery, EmptyQuery, FuzzyTermQuery, Occur, PhrasePrefixQuery,
-    PhraseQuery, Query, TermQuery, TermSetQuery,
+    AllQuery, BooleanQuery, BoostQuery, EmptyQuery, FuzzyTermQuery, GeoDistanceQuery, Occur,
+    PhrasePrefixQuery, PhraseQuery, Query, TermQuery, TermSetQuery,
 };
 use crate::schema::{
-    Facet, FacetParseError, Field, FieldType, IndexRecordOption, IntoIpv6Addr, JsonObjectOptions,
-    Schema, Term, TextFieldIndexing, Type,
+    Facet, FacetParseError, Field, FieldType, GeoPoint, IndexRecordOption, IntoIpv6Addr,
+    JsonObjectOptions, JsonPathMapping, JsonPathTemplate, Schema, Term, TextFieldIndexing, Type,
 };
 use crate::time::format_description::well_known::Rfc3339;
 use crate::time::OffsetDateTime;
-use crate::tokenizer::{TextAnalyzer, TokenizerManager};
+use crate::tokenizer::{RawTokenizer, TextAnalyzer, TokenizerManager};
 use crate::{DateTime, Score};
 
 /// Possible error that may happen when parsing a query.
@@ -103,6 +104,9 @@ pub enum QueryParserError {
     /// The format for the ip field is invalid.
     #[error("The ip field is malformed: {0}")]
     IpFormatError(#[from] AddrParseError),
+    /// The format for the geo distance is invalid.
+    #[error("The geo distance is malformed: {0}")]
+    GeoDistanceFormatError(String),
 }
 
 /// Recursively remove empty clause from the AST
@@ -252,6 +256,27 @@ impl QueryParser {
         self.schema.find_field(full_path)
     }
 
+    /// Converts the literals computed for a leaf into a query, matching any of them.
+    pub(super) fn convert_literals_to_query(
+        &self,
+        literals: Vec<LogicalLiteral>,
+    ) -> Box<dyn Query> {
+        let mut queries: Vec<Box<dyn Query>> = literals
+            .into_iter()
+            .map(|literal| convert_literal_to_query(&self.fuzzy, literal))
+            .collect();
+        match queries.len() {
+            0 => Box::new(EmptyQuery),
+            1 => queries.pop().unwrap(),
+            _ => Box::new(BooleanQuery::new(
+                queries
+                    .into_iter()
+                    .map(|query| (Occur::Should, query))
+                    .collect(),
+            )),
+        }
+    }
+
     /// Creates a `QueryParser`, given
     ///  * an index
     ///  * a set of default fields used to search if no field is specifically defined
@@ -355,7 +380,7 @@ impl QueryParser {
         Ok(ast)
     }
 
-    fn compute_boundary_term(
+    pub(super) fn compute_boundary_term(
         &self,
         field: Field,
         json_path: &str,
@@ -366,7 +391,8 @@ impl QueryParser {
         let field_supports_ff_range_queries = field_type.is_fast()
             && is_type_valid_for_fastfield_range_query(field_type.value_type());
 
-        if !field_type.is_indexed() && !field_supports_ff_range_queries {
+        if !field_type.is_indexed() && !field_supports_ff_range_queries && !field_type.has_points()
+        {
             return Err(QueryParserError::FieldNotIndexed(
                 field_entry.name().to_string(),
             ));
@@ -403,13 +429,13 @@ impl QueryParser {
                     // This should have been seen earlier really.
                     QueryParserError::FieldNotIndexed(field_entry.name().to_string())
                 })?;
-                let text_analyzer =
-                    self.tokenizer_manager
-                        .get(option.tokenizer())
-                        .ok_or_else(|| QueryParserError::UnknownTokenizer {
-                            field: field_entry.name().to_string(),
-                            tokenizer: option.tokenizer().to_string(),
-                        })?;
+                let text_analyzer = self
+                    .tokenizer_manager
+                    .get(option.search_tokenizer())
+                    .ok_or_else(|| QueryParserError::UnknownTokenizer {
+                        field: field_entry.name().to_string(),
+                        tokenizer: option.search_tokenizer().to_string(),
+                    })?;
                 let mut terms: Vec<Term> = Vec::new();
                 let mut token_stream = text_analyzer.token_stream(phrase);
                 token_stream.process(&mut |token| {
@@ -443,10 +469,16 @@ impl QueryParser {
                 let ip_v6 = IpAddr::from_str(phrase)?.into_ipv6_addr();
                 Ok(Term::from_field_ip_addr(field, ip_v6))
             }
+            FieldType::DenseVector(_) => Err(QueryParserError::UnsupportedQuery(
+                "Range query are not supported on dense vector field.".to_string(),
+            )),
+            FieldType::GeoPoint(_) => Err(QueryParserError::UnsupportedQuery(
+                "Range query are not supported on geo point field.".to_string(),
+            )),
         }
     }
 
-    fn compute_logical_ast_for_leaf(
+    pub(super) fn compute_logical_ast_for_leaf(
         &self,
         field: Field,
         json_path: &str,
@@ -457,7 +489,8 @@ impl QueryParser {
         let field_entry = self.schema.get_field_entry(field);
         let field_type = field_entry.field_type();
         let field_name = field_entry.name();
-        if !field_type.is_indexed() {
+        // Geo point fields are not indexed, but are searched with a `GeoDistanceQuery`.
+        if !field_type.is_indexed() && !field_type.is_geo_point() {
             return Err(QueryParserError::FieldNotIndexed(field_name.to_string()));
         }
         if field_type.value_type() != Type::Json && !json_path.is_empty() {
@@ -499,12 +532,12 @@ impl QueryParser {
                 })?;
                 let text_analyzer = self
                     .tokenizer_manager
-                    .get(indexing_options.tokenizer())
+                    .get(indexing_options.search_tokenizer())
                     .ok_or_else(|| QueryParserError::UnknownTokenizer {
                         field: field_name.to_string(),
-                        tokenizer: indexing_options.tokenizer().to_string(),
+                        tokenizer: indexing_options.search_tokenizer().to_string(),
                     })?;
-                Ok(generate_literals_for_str(
+                generate_literals_for_str(
                     field_name,
                     field,
                     phrase,
@@ -512,9 +545,7 @@ impl QueryParser {
                     prefix,
                     indexing_options,
                     &text_analyzer,
-                )?
-                .into_iter()
-                .collect())
+                )
             }
             FieldType::JsonObject(ref json_options) => generate_literals_for_json_object(
                 field_name,
@@ -543,6 +574,18 @@ impl QueryParser {
                 let term = Term::from_field_ip_addr(field, ip_v6);
                 Ok(vec![LogicalLiteral::Term(term)])
             }
+            // Dense vector fields are never indexed.
+            FieldType::DenseVector(_) => {
+                Err(QueryParserError::FieldNotIndexed(field_name.to_string()))
+            }
+            FieldType::GeoPoint(_) => {
+                let (center, distance) = parse_geo_distance(phrase)?;
+                Ok(vec![LogicalLiteral::GeoDistance {
+                    field,
+                    center,
+                    distance,
+                }])
+            }
         }
     }
 
@@ -784,10 +827,41 @@ fn convert_literal_to_query(
             field, value_type, &lower, &upper,
         )),
         LogicalLiteral::Set { elements, .. } => Box::new(TermSetQuery::new(elements)),
+        LogicalLiteral::GeoDistance {
+            field,
+            center,
+            distance,
+        } => Box::new(GeoDistanceQuery::new(field, center, distance)),
         LogicalLiteral::All => Box::new(AllQuery),
     }
 }
 
+/// Parses a geo distance formatted as `lat,lon,distance`, the distance being in meters, or
+/// followed by a unit among `m`, `km` and `mi`.
+fn parse_geo_distance(phrase: &str) -> Result<(GeoPoint, f64), QueryParserError> {
+    let make_error = || {
+        QueryParserError::GeoDistanceFormatError(format!(
+            "expected `lat,lon,distance`, got {phrase:?}"
+        ))
+    };
+    let (center, distance) = phrase.rsplit_once(',').ok_or_else(make_error)?;
+    let center = GeoPoint::from_str(center)
+        .map_err(|err| QueryParserError::GeoDistanceFormatError(err.to_string()))?;
+    let distance = distance.trim();
+    let (distance, unit_in_meters) = if let Some(distance) = distance.strip_suffix("km") {
+        (distance, 1_000.0)
+    } else if let Some(distance) = distance.strip_suffix("mi") {
+        (distance, 1_609.344)
+    } else {
+        (distance.strip_suffix('m').unwrap_or(distance), 1.0)
+    };
+    let distance = f64::from_str(distance.trim()).map_err(|_| make_error())?;
+    if !distance.is_finite() || distance < 0.0 {
+        return Err(make_error());
+    }
+    Ok((center, distance * unit_in_meters))
+}
+
 fn generate_literals_for_str(
     field_name: &str,
     field: Field,
@@ -796,36 +870,104 @@ fn generate_literals_for_str(
     prefix: bool,
     indexing_options: &TextFieldIndexing,
     text_analyzer: &TextAnalyzer,
-) -> Result<Option<LogicalLiteral>, QueryParserError> {
+) -> Result<Vec<LogicalLiteral>, QueryParserError> {
     let mut terms: Vec<(usize, Term)> = Vec::new();
+    let mut position_lengths: Vec<usize> = Vec::new();
     let mut token_stream = text_analyzer.token_stream(phrase);
     token_stream.process(&mut |token| {
         let term = Term::from_field_text(field, &token.text);
         terms.push((token.position, term));
+        position_lengths.push(token.position_length);
     });
     if terms.len() <= 1 {
         if prefix {
             return Err(QueryParserError::PhrasePrefixRequiresAtLeastTwoTerms {
                 phrase: phrase.to_owned(),
-                tokenizer: indexing_options.tokenizer().to_owned(),
+                tokenizer: indexing_options.search_tokenizer().to_owned(),
             });
         }
         let term_literal_opt = terms
             .into_iter()
             .next()
             .map(|(_, term)| LogicalLiteral::Term(term));
-        return Ok(term_literal_opt);
+        return Ok(term_literal_opt.into_iter().collect());
     }
     if !indexing_options.index_option().has_positions() {
         return Err(QueryParserError::FieldDoesNotHavePositionsIndexed(
             field_name.to_string(),
         ));
     }
-    Ok(Some(LogicalLiteral::Phrase {
-        terms,
-        slop,
-        prefix,
-    }))
+    // Tokens spanning several positions come from a token graph, typically emitted by
+    // a `SynonymFilter`. Each path of the graph is searched as a separate phrase.
+    let paths = if position_lengths
+        .iter()
+        .any(|&position_length| position_length > 1)
+    {
+        token_graph_paths(&terms, &position_lengths)
+    } else {
+        vec![terms]
+    };
+    let literals = paths
+        .into_iter()
+        .map(|terms| {
+            if terms.len() == 1 {
+                let (_, term) = terms.into_iter().next().unwrap();
+                LogicalLiteral::Term(term)
+            } else {
+                LogicalLiteral::Phrase {
+                    terms,
+                    slop,
+                    prefix,
+                }
+            }
+        })
+        .collect();
+    Ok(literals)
+}
+
+/// Enumerates the paths of a token graph, where `terms[i]` goes from the position
+/// `terms[i].0` to the position `terms[i].0 + position_lengths[i]`.
+///
+/// Positions on which no term starts, e.g. because of a removed stop word, are skipped.
+fn token_graph_paths(
+    terms: &[(usize, Term)],
+    position_lengths: &[usize],
+) -> Vec<Vec<(usize, Term)>> {
+    let mut outgoing: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (term_ord, (position, _)) in terms.iter().enumerate() {
+        outgoing.entry(*position).or_default().push(term_ord);
+    }
+    let mut paths = Vec::new();
+    let Some(start_position) = outgoing.keys().next().copied() else {
+        return paths;
+    };
+    // Depth-first traversal of the graph, `stack` holding the position from which each term
+    // of `path` was picked, and the index of that term among the terms of that position.
+    let mut path: Vec<usize> = Vec::new();
+    let mut stack: Vec<(usize, usize)> = vec![(start_position, 0)];
+    while let Some(&(position, choice)) = stack.last() {
+        let Some(&term_ord) = outgoing[&position].get(choice) else {
+            stack.pop();
+            if let Some(last) = stack.last_mut() {
+                last.1 += 1;
+            }
+            continue;
+        };
+        path.truncate(stack.len() - 1);
+        path.push(term_ord);
+        let next_position = position + position_lengths[term_ord].max(1);
+        if let Some((&next_position, _)) = outgoing.range(next_position..).next() {
+            stack.push((next_position, 0));
+        } else {
+            paths.push(
+                path.iter()
+                    .map(|&term_ord| terms[term_ord].clone())
+                    .collect(),
+            );
+            stack.last_mut().unwrap().1 += 1;
+        }
+    }
+    paths
 }
 
 fn generate_literals_for_json_object(
@@ -840,12 +982,6 @@ fn generate_literals_for_json_object(
         // This should have been seen earlier really.
         QueryParserError::FieldNotIndexed(field_name.to_string())
     })?;
-    let text_analyzer = tokenizer_manager
-        .get(text_options.tokenizer())
-        .ok_or_else(|| QueryParserError::UnknownTokenizer {
-            field: field_name.to_string(),
-            tokenizer: text_options.tokenizer().to_string(),
-        })?;
     let index_record_option = text_options.index_option();
     let mut logical_literals = Vec::new();
     let mut term = Term::with_capacity(100);
@@ -855,9 +991,30 @@ fn generate_literals_for_json_object(
         json_options.is_expand_dots_enabled(),
         &mut term,
     );
+    // The path is searched the way it was indexed, as defined by its template.
+    let mapping = json_options
+        .template_for_path(json_term_writer.path())
+        .map(JsonPathTemplate::mapping)
+        .unwrap_or_default();
+    let text_analyzer = match mapping {
+        JsonPathMapping::Ignore => return Ok(Vec::new()),
+        JsonPathMapping::Raw => TextAnalyzer::from(RawTokenizer),
+        JsonPathMapping::Dynamic | JsonPathMapping::Date | JsonPathMapping::Text => {
+            tokenizer_manager
+                .get(text_options.search_tokenizer())
+                .ok_or_else(|| QueryParserError::UnknownTokenizer {
+                    field: field_name.to_string(),
+                    tokenizer: text_options.search_tokenizer().to_string(),
+                })?
+        }
+    };
     if let Some(term) = convert_to_fast_value_and_get_term(&mut json_term_writer, phrase) {
         logical_literals.push(LogicalLiteral::Term(term));
     }
+    if mapping == JsonPathMapping::Date {
+        // Strings which are not dates are not indexed on these paths.
+        return Ok(logical_literals);
+    }
     let terms = set_string_and_get_terms(&mut json_term_writer, phrase, &text_analyzer);
     drop(json_term_writer);
     if terms.len() <= 1 {
@@ -910,13 +1067,15 @@ mod test {
 
     use super::super::logical_ast::*;
     use super::{QueryParser, QueryParserError};
+    use crate::collector::Count;
     use crate::query::Query;
     use crate::schema::{
-        FacetOptions, Field, IndexRecordOption, Schema, Term, TextFieldIndexing, TextOptions, FAST,
-        INDEXED, STORED, STRING, TEXT,
+        FacetOptions, Field, GeoPointOptions, IndexRecordOption, JsonObjectOptions,
+        JsonPathMapping, JsonPathTemplate, Schema, Term, TextFieldIndexing, TextOptions,
+        AUTOCOMPLETE, FAST, INDEXED, STORED, STRING, TEXT,
     };
     use crate::tokenizer::{
-        LowerCaser, SimpleTokenizer, StopWordFilter, TextAnalyzer, TokenizerManager,
+        LowerCaser, SimpleTokenizer, StopWordFilter, SynonymFilter, TextAnalyzer, TokenizerManager,
     };
     use crate::Index;
 
@@ -929,7 +1088,8 @@ mod test {
             .set_indexing_options(text_field_indexing)
             .set_stored();
         schema_builder.add_text_field("title", TEXT);
-        schema_builder.add_text_field("text", TEXT);
+        let text = schema_builder.add_text_field("text", TEXT);
+        schema_builder.add_field_alias("content", text);
         schema_builder.add_i64_field("signed", INDEXED);
         schema_builder.add_u64_field("unsigned", INDEXED);
         schema_builder.add_text_field("notindexed_text", STORED);
@@ -942,11 +1102,13 @@ mod test {
         schema_builder.add_facet_field("facet", FacetOptions::default());
         schema_builder.add_bytes_field("bytes", INDEXED);
         schema_builder.add_bytes_field("bytes_not_indexed", STORED);
-        schema_builder.add_json_field("json", TEXT);
+        let json = schema_builder.add_json_field("json", TEXT);
+        schema_builder.add_field_alias("attributes", json);
         schema_builder.add_json_field("json_not_indexed", STORED);
         schema_builder.add_bool_field("bool", INDEXED);
         schema_builder.add_bool_field("notindexed_bool", STORED);
         schema_builder.add_u64_field("u64_ff", FAST);
+        schema_builder.add_geo_point_field("location", GeoPointOptions::default());
         schema_builder.build()
     }
 
@@ -1175,6 +1337,20 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_field_alias() {
+        test_parse_query_to_logical_ast_helper(
+            "content:hello",
+            r#"Term(field=1, type=Str, "hello")"#,
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "attributes.titi:hello",
+            "Term(field=14, type=Json, path=titi, type=Str, \"hello\")",
+            false,
+        );
+    }
+
     fn extract_query_term_json_path(query: &str) -> String {
         let LogicalAst::Leaf(literal) = parse_query_to_logical_ast(query, false).unwrap() else {
             panic!();
@@ -1533,6 +1709,38 @@ mod test {
         assert!(query_parser.parse_query("facet:\"/foo/bar\"").is_ok());
     }
 
+    #[test]
+    pub fn test_query_parser_geo_distance() {
+        test_parse_query_to_logical_ast_helper(
+            r#"location:"48.85,2.35,10km""#,
+            "GeoDistance(field=19, center=48.85,2.35, distance=10000m)",
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            r#"location:"-33.8, 151.2, 500""#,
+            "GeoDistance(field=19, center=-33.8,151.2, distance=500m)",
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            r#"location:"0,0,2mi""#,
+            "GeoDistance(field=19, center=0,0, distance=3218.688m)",
+            false,
+        );
+        let query_parser = make_query_parser();
+        for query in [
+            r#"location:"48.85,2.35""#,
+            r#"location:"95,2.35,10km""#,
+            r#"location:"48.85,2.35,-10m""#,
+            r#"location:"48.85,2.35,10 parsecs""#,
+        ] {
+            assert_matches!(
+                query_parser.parse_query(query),
+                Err(QueryParserError::GeoDistanceFormatError(_))
+            );
+        }
+        assert!(query_parser.parse_query("location:[0 TO 1]").is_err());
+    }
+
     #[test]
     pub fn test_query_parser_not_empty_but_no_tokens() {
         let query_parser = make_query_parser();
@@ -1780,4 +1988,116 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn test_query_parser_synonym_graph() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("synonyms")
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        );
+        let text = schema_builder.add_text_field("text", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        index.tokenizers().register(
+            "synonyms",
+            TextAnalyzer::builder(SimpleTokenizer)
+                .filter(LowerCaser)
+                .filter(SynonymFilter::from_rules(["new york, nyc"])?)
+                .build(),
+        );
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "New York city"))?;
+        index_writer.add_document(doc!(text => "NYC city"))?;
+        index_writer.add_document(doc!(text => "new jersey city"))?;
+        index_writer.add_document(doc!(text => "york city"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text]);
+
+        let query = query_parser.parse_query("\"nyc city\"")?;
+        assert_eq!(
+            format!("{query:?}"),
+            "BooleanQuery { subqueries: [(Should, PhraseQuery { field: Field(0), phrase_terms: \
+             [(0, Term(field=0, type=Str, \"new\")), (1, Term(field=0, type=Str, \"york\")), (2, \
+             Term(field=0, type=Str, \"city\"))], slop: 0 }), (Should, PhraseQuery { field: \
+             Field(0), phrase_terms: [(0, Term(field=0, type=Str, \"nyc\")), (2, Term(field=0, \
+             type=Str, \"city\"))], slop: 0 })] }"
+        );
+        let count = |query: &str| -> crate::Result<usize> {
+            searcher.search(query_parser.parse_query(query)?.as_ref(), &Count)
+        };
+        assert_eq!(count("\"nyc city\"")?, 2);
+        assert_eq!(count("\"new york city\"")?, 2);
+        assert_eq!(count("nyc")?, 2);
+        assert_eq!(count("\"new city\"")?, 0);
+        assert_eq!(count("\"york city\"")?, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_parser_json_templates() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let json_options = JsonObjectOptions::from(TEXT)
+            .add_template(JsonPathTemplate::new("*.ts", JsonPathMapping::Date))
+            .add_template(JsonPathTemplate::new("tags.*", JsonPathMapping::Raw))
+            .add_template(JsonPathTemplate::new("secret", JsonPathMapping::Ignore));
+        let json = schema_builder.add_json_field("json", json_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(json => json!({
+            "event": {"ts": "2023-01-01T00:00:00Z", "label": "Light Blue"},
+            "tags": {"color": "Light Blue"},
+            "secret": "hidden"
+        })))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![json]);
+        let count = |query: &str| -> crate::Result<usize> {
+            searcher.search(query_parser.parse_query(query)?.as_ref(), &Count)
+        };
+        assert_eq!(count("json.tags.color:\"Light Blue\"")?, 1);
+        assert_eq!(count("json.tags.color:light")?, 0);
+        assert_eq!(count("json.event.label:\"Light Blue\"")?, 1);
+        assert_eq!(count("json.event.label:light")?, 1);
+        assert_eq!(count("json.event.ts:\"2023-01-01T00:00:00Z\"")?, 1);
+        assert_eq!(count("json.secret:hidden")?, 0);
+        assert_eq!(
+            format!(
+                "{:?}",
+                query_parser.parse_query_to_logical_ast("json.tags.color:\"Light Blue\"")?
+            ),
+            r#"Term(field=0, type=Json, path=tags.color, type=Str, "Light Blue")"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_parser_autocomplete() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", AUTOCOMPLETE);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "The Quick Brown Fox"))?;
+        index_writer.add_document(doc!(title => "Quiet Brooks"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![title]);
+        let count = |query: &str| -> crate::Result<usize> {
+            searcher.search(query_parser.parse_query(query)?.as_ref(), &Count)
+        };
+        assert_eq!(count("qui")?, 2);
+        assert_eq!(count("quic")?, 1);
+        assert_eq!(count("\"quick bro\"")?, 1);
+        assert_eq!(count("\"qui bro\"")?, 2);
+        assert_eq!(count("\"bro qui\"")?, 0);
+        assert_eq!(count("fix")?, 0);
+        // The query is tokenized using the search tokenizer of the field, which does not
+        // produce n-grams.
+        assert_eq!(
+            format!("{:?}", query_parser.parse_query_to_logical_ast("Qui")?),
+            r#"Term(field=0, type=Str, "qui")"#
+        );
+        Ok(())
+    }
 }
@@ -5,10 +5,12 @@ use crate::schema::Type;
 mod fast_field_range_query;
 mod range_query;
 mod range_query_ip_fastfield;
+mod range_query_points;
 mod range_query_u64_fastfield;
 
 pub use self::range_query::{RangeQuery, RangeWeight};
 pub use self::range_query_ip_fastfield::IPFastFieldRangeWeight;
+pub use self::range_query_points::PointsRangeWeight;
 pub use self::range_query_u64_fastfield::FastFieldRangeWeight;
 
 // TODO is this correct?
@@ -16,7 +18,9 @@ pub(crate) fn is_type_valid_for_fastfield_range_query(typ: Type) -> bool {
     match typ {
         Type::U64 | Type::I64 | Type::F64 | Type::Bool | Type::Date => true,
         Type::IpAddr => true,
-        Type::Str | Type::Facet | Type::Bytes | Type::Json => false,
+        Type::Str | Type::Facet | Type::Bytes | Type::Json | Type::DenseVector | Type::GeoPoint => {
+            false
+        }
     }
 }
 
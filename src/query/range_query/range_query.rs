@@ -6,6 +6,7 @@ use columnar::MonotonicallyMappableToU128;
 use common::{BinarySerializable, BitSet};
 
 use super::map_bound;
+use super::range_query_points::PointsRangeWeight;
 use super::range_query_u64_fastfield::FastFieldRangeWeight;
 use crate::core::SegmentReader;
 use crate::error::TantivyError;
@@ -29,6 +30,10 @@ use crate::{DateTime, DocId, Score};
 /// `TermInfo` from the inverted index (posting list) and put them into a `BitSet`.
 /// Depending on the number of terms matched, this is a potentially expensive operation.
 ///
+/// ## Points
+/// For numerical fields with a points index, the matching documents are read from the points
+/// index, in which the values are sorted. This avoids enumerating the terms of the range.
+///
 /// ## IP fast field
 /// For IP fast fields a custom variant is used, by scanning the fast field. Unlike the default
 /// variant we can walk in a lazy fashion over it, since the fastfield is implicit orderered by
@@ -324,16 +329,17 @@ pub(crate) fn maps_to_u64_fastfield(typ: Type) -> bool {
     match typ {
         Type::U64 | Type::I64 | Type::F64 | Type::Bool | Type::Date => true,
         Type::IpAddr => false,
-        Type::Str | Type::Facet | Type::Bytes | Type::Json => false,
+        Type::Str | Type::Facet | Type::Bytes | Type::Json | Type::DenseVector | Type::GeoPoint => {
+            false
+        }
     }
 }
 
 impl Query for RangeQuery {
     fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
         let schema = enable_scoring.schema();
-        let field_type = schema
-            .get_field_entry(schema.get_field(&self.field)?)
-            .field_type();
+        let field = schema.get_field(&self.field)?;
+        let field_type = schema.get_field_entry(field).field_type();
         let value_type = field_type.value_type();
         if value_type != self.value_type {
             let err_msg = format!(
@@ -344,6 +350,19 @@ impl Query for RangeQuery {
             return Err(TantivyError::SchemaError(err_msg));
         }
 
+        if field_type.has_points() && maps_to_u64_fastfield(self.value_type) {
+            let parse_from_bytes = |data: &Vec<u8>| {
+                u64::from_be(BinarySerializable::deserialize(&mut &data[..]).unwrap())
+            };
+            let lower_bound = map_bound(&self.lower_bound, parse_from_bytes);
+            let upper_bound = map_bound(&self.upper_bound, parse_from_bytes);
+            return Ok(Box::new(PointsRangeWeight::new(
+                field,
+                lower_bound,
+                upper_bound,
+            )));
+        }
+
         if field_type.is_fast() && is_type_valid_for_fastfield_range_query(self.value_type) {
             if field_type.is_ip_addr() {
                 let parse_ip_from_bytes = |data: &Vec<u8>| {
@@ -0,0 +1,168 @@
+//! Range queries on fields with a points index.
+//!
+//! The matching documents are read from the points index, where values are sorted,
+//! instead of enumerating the terms or scanning the fast field.
+
+use std::ops::{Bound, RangeInclusive};
+
+use common::BitSet;
+
+use crate::query::explanation::does_not_match;
+use crate::query::{BitSetDocSet, ConstScorer, Explanation, Scorer, Weight};
+use crate::schema::Field;
+use crate::{DocId, Score, SegmentReader};
+
+/// `PointsRangeWeight` uses the points index to execute a range query.
+pub struct PointsRangeWeight {
+    field: Field,
+    lower_bound: Bound<u64>,
+    upper_bound: Bound<u64>,
+}
+
+impl PointsRangeWeight {
+    /// Create a new `PointsRangeWeight`, with bounds expressed in the `u64` representation
+    /// of the values.
+    pub fn new(field: Field, lower_bound: Bound<u64>, upper_bound: Bound<u64>) -> Self {
+        Self {
+            field,
+            lower_bound,
+            upper_bound,
+        }
+    }
+}
+
+impl Weight for PointsRangeWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let max_doc = reader.max_doc();
+        let mut doc_bitset = BitSet::with_max_value(max_doc);
+        if let (Some(points_reader), Some(range)) = (
+            reader.points_readers().get_field(self.field)?,
+            bound_to_value_range(&self.lower_bound, &self.upper_bound),
+        ) {
+            for doc in points_reader.doc_ids_in_range(range) {
+                doc_bitset.insert(doc);
+            }
+        }
+        let doc_bitset = BitSetDocSet::from(doc_bitset);
+        Ok(Box::new(ConstScorer::new(doc_bitset, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("PointsRangeWeight", 1.0))
+    }
+}
+
+/// Converts the bounds to an inclusive range, or `None` if no value can match.
+fn bound_to_value_range(
+    lower_bound: &Bound<u64>,
+    upper_bound: &Bound<u64>,
+) -> Option<RangeInclusive<u64>> {
+    let start = match *lower_bound {
+        Bound::Included(val) => val,
+        Bound::Excluded(val) => val.checked_add(1)?,
+        Bound::Unbounded => u64::MIN,
+    };
+    let end = match *upper_bound {
+        Bound::Included(val) => val,
+        Bound::Excluded(val) => val.checked_sub(1)?,
+        Bound::Unbounded => u64::MAX,
+    };
+    if start > end {
+        return None;
+    }
+    Some(start..=end)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use super::bound_to_value_range;
+    use crate::collector::Count;
+    use crate::query::RangeQuery;
+    use crate::schema::{NumericOptions, Schema, INDEXED};
+    use crate::Index;
+
+    #[test]
+    fn test_bound_to_value_range() {
+        assert_eq!(
+            bound_to_value_range(&Bound::Excluded(3), &Bound::Excluded(6)),
+            Some(4..=5)
+        );
+        assert_eq!(
+            bound_to_value_range(&Bound::Unbounded, &Bound::Unbounded),
+            Some(0..=u64::MAX)
+        );
+        assert_eq!(
+            bound_to_value_range(&Bound::Excluded(u64::MAX), &Bound::Unbounded),
+            None
+        );
+        assert_eq!(
+            bound_to_value_range(&Bound::Unbounded, &Bound::Excluded(0)),
+            None
+        );
+        assert_eq!(
+            bound_to_value_range(&Bound::Included(5), &Bound::Excluded(5)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_points_range_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let points_field =
+            schema_builder.add_i64_field("points", NumericOptions::default().set_points());
+        let indexed_field = schema_builder.add_i64_field("indexed", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            for val in -500i64..500 {
+                index_writer.add_document(doc!(points_field=>val, indexed_field=>val))?;
+                if val % 100 == 0 {
+                    index_writer.commit()?;
+                }
+            }
+            index_writer.commit()?;
+        }
+        let count_matches = |index: &Index| -> crate::Result<()> {
+            let searcher = index.reader()?.searcher();
+            for (lower, upper) in [
+                (Bound::Included(-120i64), Bound::Excluded(310i64)),
+                (Bound::Excluded(-500), Bound::Included(-499)),
+                (Bound::Unbounded, Bound::Included(0)),
+                (Bound::Included(10), Bound::Excluded(10)),
+            ] {
+                let points_query = RangeQuery::new_i64_bounds("points".to_string(), lower, upper);
+                let indexed_query = RangeQuery::new_i64_bounds("indexed".to_string(), lower, upper);
+                assert_eq!(
+                    searcher.search(&points_query, &Count)?,
+                    searcher.search(&indexed_query, &Count)?
+                );
+            }
+            Ok(())
+        };
+        count_matches(&index)?;
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.delete_term(crate::Term::from_field_i64(indexed_field, 7));
+            index_writer.commit()?;
+            let segment_ids = index.searchable_segment_ids()?;
+            index_writer.merge(&segment_ids).wait()?;
+            index_writer.wait_merging_threads()?;
+        }
+        assert_eq!(index.searchable_segment_ids()?.len(), 1);
+        count_matches(&index)?;
+        let searcher = index.reader()?.searcher();
+        let query = RangeQuery::new_i64_bounds(
+            "points".to_string(),
+            Bound::Included(0),
+            Bound::Excluded(10),
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 9);
+        Ok(())
+    }
+}
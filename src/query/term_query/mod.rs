@@ -9,7 +9,7 @@ pub use self::term_weight::TermWeight;
 #[cfg(test)]
 mod tests {
 
-    use crate::collector::TopDocs;
+    use crate::collector::{Count, TopDocs};
     use crate::docset::DocSet;
     use crate::postings::compression::COMPRESSION_BLOCK_SIZE;
     use crate::query::{EnableScoring, Query, QueryParser, Scorer, TermQuery};
@@ -77,6 +77,40 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_term_query_bitset_postings() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag_field = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            for i in 0..1_000 {
+                let mut doc = doc!();
+                if i % 3 != 0 {
+                    doc.add_text(tag_field, "common");
+                }
+                if i % 50 == 0 {
+                    doc.add_text(tag_field, "rare");
+                }
+                index_writer.add_document(doc)?;
+            }
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let common_query = TermQuery::new(
+            Term::from_field_text(tag_field, "common"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&common_query, &Count)?, 666);
+        let top_docs = searcher.search(&common_query, &TopDocs::with_limit(3))?;
+        assert_eq!(top_docs.len(), 3);
+        let query_parser = QueryParser::for_index(&index, vec![tag_field]);
+        let query = query_parser.parse_query("+common +rare")?;
+        let num_expected = (0..1_000).filter(|i| i % 3 != 0 && i % 50 == 0).count();
+        assert_eq!(searcher.search(&query, &Count)?, num_expected);
+        Ok(())
+    }
+
     #[test]
     pub fn test_term_weight() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
@@ -1,16 +1,27 @@
+mod nrt_segments;
+mod result_cache;
+mod segment_changes;
 mod warming;
 
 use std::convert::TryInto;
+use std::num::NonZeroUsize;
 use std::sync::atomic::AtomicU64;
 use std::sync::{atomic, Arc, Weak};
+use std::time::Instant;
 
 use arc_swap::ArcSwap;
-pub use warming::Warmer;
+pub(crate) use warming::warm_file_slice;
+pub use warming::{ComponentWarmer, GlobalOrdinalsWarmer, WarmUpComponents, Warmer};
 
+pub(crate) use self::nrt_segments::NrtSegments;
+pub(crate) use self::result_cache::{ResultCache, ResultCacheKey};
+use self::segment_changes::SegmentChangeCallbackList;
+pub use self::segment_changes::{SegmentChange, SegmentChangeCallback, SegmentChangeHandle};
 use self::warming::WarmingState;
 use crate::core::searcher::{SearcherGeneration, SearcherInner};
 use crate::directory::{Directory, WatchCallback, WatchHandle, META_LOCK};
-use crate::store::DOCSTORE_CACHE_CAPACITY;
+use crate::metrics::enter_span;
+use crate::store::{CacheStats, DOCSTORE_CACHE_CAPACITY};
 use crate::{Index, Inventory, Searcher, SegmentReader, TrackedObject};
 
 /// Defines when a new version of the index should be reloaded.
@@ -28,7 +39,10 @@ pub enum ReloadPolicy {
     Manual,
     /// The index is reloaded within milliseconds after a new commit is available.
     /// This is made possible by watching changes in the `meta.json` file.
-    OnCommit, // TODO add NEAR_REAL_TIME(target_ms)
+    ///
+    /// The [near real time](IndexReaderBuilder::near_real_time) readers are also reloaded after
+    /// each [refresh](crate::IndexWriter::refresh) of the index writer.
+    OnCommit,
 }
 
 /// [`IndexReader`] builder
@@ -38,6 +52,8 @@ pub enum ReloadPolicy {
 /// - [`Warmer`] implementations
 /// - number of warming threads, for parallelizing warming work
 /// - The cache size of the underlying doc store readers.
+/// - The size of the result cache.
+/// - Whether the segments which are not committed yet are searchable.
 #[derive(Clone)]
 pub struct IndexReaderBuilder {
     reload_policy: ReloadPolicy,
@@ -45,6 +61,8 @@ pub struct IndexReaderBuilder {
     warmers: Vec<Weak<dyn Warmer>>,
     num_warming_threads: usize,
     doc_store_cache_num_blocks: usize,
+    result_cache_num_entries: usize,
+    near_real_time: bool,
 }
 
 impl IndexReaderBuilder {
@@ -56,6 +74,8 @@ impl IndexReaderBuilder {
             warmers: Vec::new(),
             num_warming_threads: 1,
             doc_store_cache_num_blocks: DOCSTORE_CACHE_CAPACITY,
+            result_cache_num_entries: 0,
+            near_real_time: false,
         }
     }
 
@@ -71,17 +91,21 @@ impl IndexReaderBuilder {
             self.warmers,
             searcher_generation_inventory.clone(),
         )?;
+        let result_cache = NonZeroUsize::new(self.result_cache_num_entries)
+            .map(|num_entries| Arc::new(ResultCache::new(num_entries)));
         let inner_reader = InnerIndexReader::new(
             self.doc_store_cache_num_blocks,
+            result_cache,
             self.index,
+            self.near_real_time,
             warming_state,
             searcher_generation_inventory,
         )?;
         let inner_reader_arc = Arc::new(inner_reader);
-        let watch_handle_opt: Option<WatchHandle> = match self.reload_policy {
+        let mut watch_handles: Vec<WatchHandle> = Vec::new();
+        match self.reload_policy {
             ReloadPolicy::Manual => {
                 // No need to set anything...
-                None
             }
             ReloadPolicy::OnCommit => {
                 let inner_reader_arc_clone = inner_reader_arc.clone();
@@ -93,16 +117,18 @@ impl IndexReaderBuilder {
                         );
                     }
                 };
-                let watch_handle = inner_reader_arc
-                    .index
-                    .directory()
-                    .watch(WatchCallback::new(callback))?;
-                Some(watch_handle)
+                let callback = WatchCallback::new(callback);
+                if self.near_real_time {
+                    let nrt_segments = inner_reader_arc.index.nrt_segments();
+                    watch_handles.push(nrt_segments.watch(callback.clone()));
+                }
+                let watch_handle = inner_reader_arc.index.directory().watch(callback)?;
+                watch_handles.push(watch_handle);
             }
-        };
+        }
         Ok(IndexReader {
             inner: inner_reader_arc,
-            _watch_handle_opt: watch_handle_opt,
+            _watch_handles: watch_handles,
         })
     }
 
@@ -127,6 +153,36 @@ impl IndexReaderBuilder {
         self
     }
 
+    /// Sets the number of results kept by the result cache of the reader.
+    ///
+    /// The result cache is disabled by default. When enabled, the results of
+    /// [`Searcher::search_cached`] are kept until the reader reloads, the least recently used
+    /// ones being evicted first once the cache is full. This absorbs the workloads repeating the
+    /// same queries, like dashboards.
+    #[must_use]
+    pub fn result_cache_num_entries(
+        mut self,
+        result_cache_num_entries: usize,
+    ) -> IndexReaderBuilder {
+        self.result_cache_num_entries = result_cache_num_entries;
+        self
+    }
+
+    /// Makes the reader search the segments which are not committed yet.
+    ///
+    /// Such a near real time reader searches the segments of the last
+    /// [`IndexWriter::refresh`](crate::IndexWriter::refresh), or of the last commit if it is
+    /// more recent. The documents added before a refresh are thereby searchable without paying
+    /// for a commit, which syncs the `meta.json` file and the directory.
+    ///
+    /// The refreshed segments are only visible to the readers of the [`Index`] instance, or of
+    /// its clones, the index writer was created from. Defaults to `false`.
+    #[must_use]
+    pub fn near_real_time(mut self, near_real_time: bool) -> IndexReaderBuilder {
+        self.near_real_time = near_real_time;
+        self
+    }
+
     /// Set the [`Warmer`]s that are invoked when reloading searchable segments.
     #[must_use]
     pub fn warmers(mut self, warmers: Vec<Weak<dyn Warmer>>) -> IndexReaderBuilder {
@@ -155,17 +211,22 @@ impl TryInto<IndexReader> for IndexReaderBuilder {
 
 struct InnerIndexReader {
     doc_store_cache_num_blocks: usize,
+    result_cache: Option<Arc<ResultCache>>,
     index: Index,
+    near_real_time: bool,
     warming_state: WarmingState,
     searcher: arc_swap::ArcSwap<SearcherInner>,
     searcher_generation_counter: Arc<AtomicU64>,
     searcher_generation_inventory: Inventory<SearcherGeneration>,
+    segment_change_callbacks: SegmentChangeCallbackList,
 }
 
 impl InnerIndexReader {
     fn new(
         doc_store_cache_num_blocks: usize,
+        result_cache: Option<Arc<ResultCache>>,
         index: Index,
+        near_real_time: bool,
         warming_state: WarmingState,
         // The searcher_generation_inventory is not used as source, but as target to track the
         // loaded segments.
@@ -175,28 +236,47 @@ impl InnerIndexReader {
 
         let searcher = Self::create_searcher(
             &index,
+            near_real_time,
             doc_store_cache_num_blocks,
+            &result_cache,
             &warming_state,
             &searcher_generation_counter,
             &searcher_generation_inventory,
         )?;
         Ok(InnerIndexReader {
             doc_store_cache_num_blocks,
+            result_cache,
             index,
+            near_real_time,
             warming_state,
             searcher: ArcSwap::from(searcher),
             searcher_generation_counter,
             searcher_generation_inventory,
+            segment_change_callbacks: SegmentChangeCallbackList::default(),
         })
     }
     /// Opens the freshest segments [`SegmentReader`].
     ///
     /// This function acquires a lot to prevent GC from removing files
     /// as we are opening our index.
-    fn open_segment_readers(index: &Index) -> crate::Result<Vec<SegmentReader>> {
+    fn open_segment_readers(
+        index: &Index,
+        near_real_time: bool,
+    ) -> crate::Result<Vec<SegmentReader>> {
         // Prevents segment files from getting deleted while we are in the process of opening them
         let _meta_lock = index.directory().acquire_lock(&META_LOCK)?;
-        let searchable_segments = index.searchable_segments()?;
+        let nrt_segment_metas = if near_real_time {
+            index.nrt_segments().segment_metas()
+        } else {
+            None
+        };
+        let searchable_segments = match nrt_segment_metas {
+            Some(segment_metas) => segment_metas
+                .into_iter()
+                .map(|segment_meta| index.segment(segment_meta))
+                .collect(),
+            None => index.searchable_segments()?,
+        };
         let segment_readers = searchable_segments
             .iter()
             .map(SegmentReader::open)
@@ -217,12 +297,14 @@ impl InnerIndexReader {
 
     fn create_searcher(
         index: &Index,
+        near_real_time: bool,
         doc_store_cache_num_blocks: usize,
+        result_cache: &Option<Arc<ResultCache>>,
         warming_state: &WarmingState,
         searcher_generation_counter: &Arc<AtomicU64>,
         searcher_generation_inventory: &Inventory<SearcherGeneration>,
     ) -> crate::Result<Arc<SearcherInner>> {
-        let segment_readers = Self::open_segment_readers(index)?;
+        let segment_readers = Self::open_segment_readers(index, near_real_time)?;
         let searcher_generation = Self::track_segment_readers_in_inventory(
             &segment_readers,
             searcher_generation_counter,
@@ -236,6 +318,7 @@ impl InnerIndexReader {
             segment_readers,
             searcher_generation,
             doc_store_cache_num_blocks,
+            result_cache.clone(),
         )?);
 
         warming_state.warm_new_searcher_generation(&searcher.clone().into())?;
@@ -243,15 +326,34 @@ impl InnerIndexReader {
     }
 
     fn reload(&self) -> crate::Result<()> {
+        let _span_guard = enter_span!("tantivy.reload_reader");
+        let start = Instant::now();
         let searcher = Self::create_searcher(
             &self.index,
+            self.near_real_time,
             self.doc_store_cache_num_blocks,
+            &self.result_cache,
             &self.warming_state,
             &self.searcher_generation_counter,
             &self.searcher_generation_inventory,
         )?;
 
-        self.searcher.store(searcher);
+        let previous_searcher: Searcher = self.searcher.swap(searcher).into();
+        // The results of the previous searchers are not needed anymore.
+        if let Some(result_cache) = &self.result_cache {
+            result_cache.clear();
+        }
+        let segment_change = SegmentChange::between(
+            previous_searcher.generation().segments(),
+            self.searcher().generation().segments(),
+        );
+        if !segment_change.is_empty() {
+            self.segment_change_callbacks.broadcast(&segment_change);
+        }
+        if let Some(metrics_recorder) = self.index.metrics_recorder() {
+            let num_segments = self.searcher().segment_readers().len();
+            metrics_recorder.record_reader_reload(num_segments, start.elapsed());
+        }
 
         Ok(())
     }
@@ -270,7 +372,7 @@ impl InnerIndexReader {
 #[derive(Clone)]
 pub struct IndexReader {
     inner: Arc<InnerIndexReader>,
-    _watch_handle_opt: Option<WatchHandle>,
+    _watch_handles: Vec<WatchHandle>,
 }
 
 impl IndexReader {
@@ -302,4 +404,38 @@ impl IndexReader {
     pub fn searcher(&self) -> Searcher {
         self.inner.searcher()
     }
+
+    /// Registers a [`Warmer`], invoked with each new searcher before it is published by a reload.
+    ///
+    /// Like the warmers set with [`IndexReaderBuilder::warmers`], the reader only keeps a weak
+    /// reference to the warmer. The warmer is also invoked with the current searcher before this
+    /// method returns.
+    pub fn register_warmer(&self, warmer: Weak<dyn Warmer>) -> crate::Result<()> {
+        self.inner
+            .warming_state
+            .register_warmer(warmer, &self.inner.searcher())
+    }
+
+    /// Subscribes a callback, called with the [`SegmentChange`] of each reload changing the
+    /// segments searched by the reader.
+    ///
+    /// The callback is called in the thread reloading the reader, after the new searcher is
+    /// published. It stays subscribed as long as the returned handle, or one of its clones, is
+    /// alive.
+    pub fn subscribe_segment_changes(
+        &self,
+        callback: SegmentChangeCallback,
+    ) -> SegmentChangeHandle {
+        self.inner.segment_change_callbacks.subscribe(callback)
+    }
+
+    /// Returns the statistics of the result cache, or `None` if it is disabled.
+    ///
+    /// See [`IndexReaderBuilder::result_cache_num_entries`].
+    pub fn result_cache_stats(&self) -> Option<CacheStats> {
+        self.inner
+            .result_cache
+            .as_ref()
+            .map(|result_cache| result_cache.stats())
+    }
 }
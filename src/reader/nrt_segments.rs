@@ -0,0 +1,41 @@
+use std::sync::{Arc, RwLock};
+
+use crate::directory::{WatchCallback, WatchCallbackList, WatchHandle};
+use crate::{FutureResult, SegmentMeta};
+
+#[derive(Default)]
+struct InnerNrtSegments {
+    segment_metas: RwLock<Option<Vec<SegmentMeta>>>,
+    watch_router: WatchCallbackList,
+}
+
+/// The segments published by the [`IndexWriter`](crate::IndexWriter) of an index for its near
+/// real time readers.
+///
+/// These are the segments of the last refresh, including the segments which are not committed
+/// yet, until the next commit or rollback. Holding their `SegmentMeta`s prevents the garbage
+/// collection of their files.
+///
+/// See [`IndexReaderBuilder::near_real_time`](crate::IndexReaderBuilder::near_real_time).
+#[derive(Clone, Default)]
+pub(crate) struct NrtSegments(Arc<InnerNrtSegments>);
+
+impl NrtSegments {
+    /// Publishes the searchable segments, and notifies the near real time readers.
+    ///
+    /// Publishing `None` makes the readers fall back to the segments of the last commit.
+    pub fn publish(&self, segment_metas: Option<Vec<SegmentMeta>>) -> FutureResult<()> {
+        *self.0.segment_metas.write().unwrap() = segment_metas;
+        self.0.watch_router.broadcast()
+    }
+
+    /// Returns the last published segments.
+    pub fn segment_metas(&self) -> Option<Vec<SegmentMeta>> {
+        self.0.segment_metas.read().unwrap().clone()
+    }
+
+    /// Registers a callback, called when new segments are published.
+    pub fn watch(&self, watch_callback: WatchCallback) -> WatchHandle {
+        self.0.watch_router.subscribe(watch_callback)
+    }
+}
@@ -0,0 +1,155 @@
+use std::any::Any;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+use lru::LruCache;
+
+use crate::store::CacheStats;
+
+/// Identifies a search in the [`ResultCache`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct ResultCacheKey {
+    /// The generation of the searcher, changing with its segments and their deletes.
+    pub generation_id: u64,
+    /// The `Debug` representation of the query.
+    pub query: String,
+    /// The [`cache_key`](crate::collector::CacheableCollector::cache_key) of the collector.
+    pub collector: String,
+}
+
+struct Entries {
+    lru: LruCache<ResultCacheKey, Arc<dyn Any + Send + Sync>>,
+    cache_hits: usize,
+    cache_misses: usize,
+}
+
+/// A cache of the results of the searches, bounded by a number of entries, and shared by the
+/// searchers of an [`IndexReader`](crate::IndexReader).
+///
+/// The least recently used results are evicted first, and all of them are dropped when the
+/// reader reloads.
+pub(crate) struct ResultCache {
+    entries: std::sync::Mutex<Entries>,
+}
+
+impl ResultCache {
+    pub fn new(num_entries: NonZeroUsize) -> ResultCache {
+        ResultCache {
+            entries: std::sync::Mutex::new(Entries {
+                lru: LruCache::new(num_entries),
+                cache_hits: 0,
+                cache_misses: 0,
+            }),
+        }
+    }
+
+    pub fn get<T: Any + Clone>(&self, key: &ResultCacheKey) -> Option<T> {
+        let mut entries = self.entries.lock().unwrap();
+        let value_opt = entries
+            .lru
+            .get(key)
+            .and_then(|value| value.downcast_ref::<T>())
+            .cloned();
+        if value_opt.is_some() {
+            entries.cache_hits += 1;
+        } else {
+            entries.cache_misses += 1;
+        }
+        value_opt
+    }
+
+    pub fn put<T: Any + Send + Sync>(&self, key: ResultCacheKey, value: T) {
+        self.entries.lock().unwrap().lru.put(key, Arc::new(value));
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().lru.clear();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let entries = self.entries.lock().unwrap();
+        CacheStats {
+            num_entries: entries.lru.len(),
+            cache_hits: entries.cache_hits,
+            cache_misses: entries.cache_misses,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::AggregationCollector;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, FAST, STRING};
+    use crate::{doc, Index, ReloadPolicy, Term};
+
+    #[test]
+    fn test_result_cache() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STRING | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tag => "a"))?;
+        index_writer.add_document(doc!(tag => "b"))?;
+        index_writer.commit()?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .result_cache_num_entries(2)
+            .try_into()?;
+        let term_query =
+            |text: &str| TermQuery::new(Term::from_field_text(tag, text), IndexRecordOption::Basic);
+
+        let searcher = reader.searcher();
+        assert_eq!(searcher.search_cached(&term_query("a"), &Count)?, 1);
+        assert_eq!(searcher.search_cached(&term_query("a"), &Count)?, 1);
+        let top_docs = searcher.search_cached(&term_query("a"), &TopDocs::with_limit(1))?;
+        assert_eq!(top_docs.len(), 1);
+        let stats = reader.result_cache_stats().unwrap();
+        assert_eq!(
+            (stats.num_entries, stats.cache_hits, stats.cache_misses),
+            (2, 1, 2)
+        );
+        // The least recently used result is evicted.
+        assert_eq!(searcher.search_cached(&term_query("b"), &Count)?, 1);
+        assert_eq!(searcher.search_cached(&term_query("a"), &Count)?, 1);
+        let stats = reader.result_cache_stats().unwrap();
+        assert_eq!(
+            (stats.num_entries, stats.cache_hits, stats.cache_misses),
+            (2, 1, 4)
+        );
+
+        let aggregations: Aggregations =
+            serde_json::from_str(r#"{"tags": {"terms": {"field": "tag"}}}"#)?;
+        let collector = AggregationCollector::from_aggs(aggregations, Default::default());
+        let results = searcher.search_cached(&term_query("b"), &collector)?;
+        assert_eq!(
+            searcher.search_cached(&term_query("b"), &collector)?,
+            results
+        );
+        assert_eq!(reader.result_cache_stats().unwrap().cache_hits, 2);
+
+        // The results are dropped when the reader reloads.
+        index_writer.add_document(doc!(tag => "a"))?;
+        index_writer.commit()?;
+        reader.reload()?;
+        assert_eq!(reader.result_cache_stats().unwrap().num_entries, 0);
+        assert_eq!(
+            reader.searcher().search_cached(&term_query("a"), &Count)?,
+            2
+        );
+        // The previous searchers still get their own results.
+        assert_eq!(searcher.search_cached(&term_query("a"), &Count)?, 1);
+
+        // Without a result cache, the searches are not cached.
+        let reader = index.reader()?;
+        assert_eq!(
+            reader.searcher().search_cached(&term_query("a"), &Count)?,
+            2
+        );
+        assert!(reader.result_cache_stats().is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock, Weak};
+
+use crate::{Opstamp, SegmentId};
+
+/// Change of the set of segments searched by an [`IndexReader`](crate::IndexReader), emitted
+/// when a reload changes it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SegmentChange {
+    /// The segments searched after the reload, and not before.
+    pub added_segment_ids: Vec<SegmentId>,
+    /// The segments searched before the reload, and not after.
+    pub removed_segment_ids: Vec<SegmentId>,
+    /// The segments searched before and after the reload, with new deleted documents.
+    pub updated_segment_ids: Vec<SegmentId>,
+}
+
+impl SegmentChange {
+    /// Computes the change between two sets of segments, given with their delete opstamps.
+    pub(crate) fn between(
+        previous_segments: &BTreeMap<SegmentId, Option<Opstamp>>,
+        new_segments: &BTreeMap<SegmentId, Option<Opstamp>>,
+    ) -> SegmentChange {
+        let mut segment_change = SegmentChange::default();
+        for (segment_id, delete_opstamp) in new_segments {
+            match previous_segments.get(segment_id) {
+                None => segment_change.added_segment_ids.push(*segment_id),
+                Some(previous_delete_opstamp) if previous_delete_opstamp != delete_opstamp => {
+                    segment_change.updated_segment_ids.push(*segment_id)
+                }
+                Some(_) => {}
+            }
+        }
+        segment_change.removed_segment_ids = previous_segments
+            .keys()
+            .filter(|segment_id| !new_segments.contains_key(segment_id))
+            .copied()
+            .collect();
+        segment_change
+    }
+
+    /// Returns true if the set of segments did not change.
+    pub fn is_empty(&self) -> bool {
+        self.added_segment_ids.is_empty()
+            && self.removed_segment_ids.is_empty()
+            && self.updated_segment_ids.is_empty()
+    }
+}
+
+/// Cloneable wrapper for callbacks registered to receive `SegmentChange`s.
+#[derive(Clone)]
+pub struct SegmentChangeCallback(Arc<dyn Fn(&SegmentChange) + Sync + Send>);
+
+impl SegmentChangeCallback {
+    /// Wraps a `Fn(&SegmentChange)` to create a `SegmentChangeCallback`.
+    pub fn new<F: Fn(&SegmentChange) + Sync + Send + 'static>(op: F) -> Self {
+        SegmentChangeCallback(Arc::new(op))
+    }
+
+    fn call(&self, segment_change: &SegmentChange) {
+        self.0(segment_change)
+    }
+}
+
+/// Controls how long a segment change callback stays subscribed.
+///
+/// After all the clones of `SegmentChangeHandle` are dropped, the associated callback
+/// will not be called anymore.
+#[must_use = "This `SegmentChangeHandle` controls the lifetime of the subscription and should \
+              therefore be used."]
+#[derive(Clone)]
+pub struct SegmentChangeHandle(#[allow(dead_code)] Arc<SegmentChangeCallback>);
+
+/// Registers segment change callbacks and dispatches the changes to them.
+#[derive(Default)]
+pub(crate) struct SegmentChangeCallbackList {
+    callbacks: RwLock<Vec<Weak<SegmentChangeCallback>>>,
+}
+
+impl SegmentChangeCallbackList {
+    pub fn subscribe(&self, callback: SegmentChangeCallback) -> SegmentChangeHandle {
+        let callback_arc = Arc::new(callback);
+        self.callbacks
+            .write()
+            .unwrap()
+            .push(Arc::downgrade(&callback_arc));
+        SegmentChangeHandle(callback_arc)
+    }
+
+    /// Calls all of the live callbacks, in the calling thread.
+    pub fn broadcast(&self, segment_change: &SegmentChange) {
+        let callbacks: Vec<Arc<SegmentChangeCallback>> = {
+            let mut callbacks_wlock = self.callbacks.write().unwrap();
+            callbacks_wlock.retain(|callback| callback.strong_count() > 0);
+            callbacks_wlock.iter().filter_map(Weak::upgrade).collect()
+        };
+        for callback in callbacks {
+            callback.call(segment_change);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::{SegmentChange, SegmentChangeCallback};
+    use crate::schema::{Schema, STRING};
+    use crate::{doc, Index, ReloadPolicy, Term};
+
+    #[test]
+    fn test_segment_changes() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let segment_changes: Arc<Mutex<Vec<SegmentChange>>> = Arc::default();
+        let segment_changes_clone = segment_changes.clone();
+        let handle =
+            reader.subscribe_segment_changes(SegmentChangeCallback::new(move |segment_change| {
+                segment_changes_clone
+                    .lock()
+                    .unwrap()
+                    .push(segment_change.clone())
+            }));
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "a"))?;
+        index_writer.add_document(doc!(text => "b"))?;
+        index_writer.commit()?;
+        reader.reload()?;
+        index_writer.add_document(doc!(text => "c"))?;
+        index_writer.commit()?;
+        reader.reload()?;
+        let segment_ids = index.searchable_segment_ids()?;
+        // A reload without changes does not notify the callbacks.
+        reader.reload()?;
+        {
+            let segment_changes = segment_changes.lock().unwrap();
+            assert_eq!(segment_changes.len(), 2);
+            assert_eq!(segment_changes[0].added_segment_ids.len(), 1);
+            assert!(segment_changes[1]
+                .removed_segment_ids
+                .iter()
+                .chain(&segment_changes[1].updated_segment_ids)
+                .next()
+                .is_none());
+        }
+
+        index_writer.delete_term(Term::from_field_text(text, "a"));
+        index_writer.commit()?;
+        reader.reload()?;
+        assert_eq!(
+            segment_changes.lock().unwrap()[2].updated_segment_ids.len(),
+            1
+        );
+
+        index_writer.merge(&segment_ids).wait()?;
+        reader.reload()?;
+        let merge_change = segment_changes.lock().unwrap()[3].clone();
+        assert_eq!(merge_change.added_segment_ids.len(), 1);
+        let mut removed_segment_ids = merge_change.removed_segment_ids;
+        let mut expected_segment_ids = segment_ids;
+        removed_segment_ids.sort();
+        expected_segment_ids.sort();
+        assert_eq!(removed_segment_ids, expected_segment_ids);
+
+        // The callback is unsubscribed when its handle is dropped.
+        drop(handle);
+        index_writer.add_document(doc!(text => "d"))?;
+        index_writer.commit()?;
+        reader.reload()?;
+        assert_eq!(segment_changes.lock().unwrap().len(), 4);
+        Ok(())
+    }
+}
@@ -1,16 +1,22 @@
 use std::collections::HashSet;
+use std::io;
 use std::ops::Deref;
-use std::sync::{Arc, Mutex, Weak};
+use std::sync::{Arc, Mutex, RwLock, Weak};
 use std::thread::JoinHandle;
 use std::time::Duration;
 
-use crate::{Executor, Inventory, Searcher, SearcherGeneration, TantivyError};
+use crate::directory::FileSlice;
+use crate::schema::Field;
+use crate::{Executor, Inventory, Searcher, SearcherGeneration, SegmentId, TantivyError};
 
 pub const GC_INTERVAL: Duration = Duration::from_secs(1);
 
+const PAGE_SIZE: usize = 4_096;
+
 /// `Warmer` can be used to maintain segment-level state e.g. caches.
 ///
-/// They must be registered with the [`IndexReaderBuilder`](super::IndexReaderBuilder).
+/// They are registered with the [`IndexReaderBuilder`](super::IndexReaderBuilder), or with
+/// [`IndexReader::register_warmer`](super::IndexReader::register_warmer).
 pub trait Warmer: Sync + Send {
     /// Perform any warming work using the provided [`Searcher`].
     fn warm(&self, searcher: &Searcher) -> crate::Result<()>;
@@ -19,6 +25,169 @@ pub trait Warmer: Sync + Send {
     fn garbage_collect(&self, live_generations: &[&SearcherGeneration]);
 }
 
+/// Components of the segments loaded by
+/// [`SegmentReader::warm_up()`](crate::SegmentReader::warm_up).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WarmUpComponents {
+    pub(crate) term_dictionaries: Vec<Field>,
+    pub(crate) postings: Vec<Field>,
+    pub(crate) fast_fields: Vec<String>,
+    pub(crate) fieldnorms: Vec<Field>,
+    pub(crate) store_index: bool,
+}
+
+impl WarmUpComponents {
+    /// Loads the term dictionary of a field.
+    #[must_use]
+    pub fn term_dictionary(mut self, field: Field) -> WarmUpComponents {
+        self.term_dictionaries.push(field);
+        self
+    }
+
+    /// Loads the postings and the positions of a field.
+    #[must_use]
+    pub fn postings(mut self, field: Field) -> WarmUpComponents {
+        self.postings.push(field);
+        self
+    }
+
+    /// Loads the columns of a fast field.
+    #[must_use]
+    pub fn fast_field(mut self, field_name: impl ToString) -> WarmUpComponents {
+        self.fast_fields.push(field_name.to_string());
+        self
+    }
+
+    /// Loads the fieldnorms of a field.
+    #[must_use]
+    pub fn fieldnorms(mut self, field: Field) -> WarmUpComponents {
+        self.fieldnorms.push(field);
+        self
+    }
+
+    /// Loads the skip index of the doc store, used to find the block of a document.
+    #[must_use]
+    pub fn store_index(mut self) -> WarmUpComponents {
+        self.store_index = true;
+        self
+    }
+}
+
+/// Reads all of the bytes of a file slice, touching each of their pages so that they are read
+/// by the OS if the file is memory mapped.
+pub(crate) fn warm_file_slice(file_slice: &FileSlice) -> io::Result<()> {
+    let bytes = file_slice.read_bytes()?;
+    for page_start in (0..bytes.len()).step_by(PAGE_SIZE) {
+        // SAFETY: the byte is within the slice. The volatile read cannot be optimized away.
+        unsafe { std::ptr::read_volatile(&bytes.as_slice()[page_start]) };
+    }
+    Ok(())
+}
+
+/// A [`Warmer`] loading the given components of the new segments, each time an
+/// [`IndexReader`](crate::IndexReader) reloads, before the new searcher is published.
+///
+/// ```rust
+/// use std::sync::Arc;
+///
+/// use tantivy::schema::{Schema, FAST, TEXT};
+/// use tantivy::{doc, ComponentWarmer, Index, WarmUpComponents, Warmer};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let year = schema_builder.add_u64_field("year", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+///
+/// let components = WarmUpComponents::default()
+///     .term_dictionary(title)
+///     .fast_field("year")
+///     .store_index();
+/// let warmer: Arc<dyn Warmer> = Arc::new(ComponentWarmer::new(components));
+/// // The reader only keeps a weak reference to the warmer.
+/// let reader = index
+///     .reader_builder()
+///     .warmers(vec![Arc::downgrade(&warmer)])
+///     .try_into()?;
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+/// index_writer.add_document(doc!(title => "The Old Man and the Sea", year => 1952u64))?;
+/// index_writer.commit()?;
+/// reader.reload()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ComponentWarmer {
+    components: WarmUpComponents,
+    warmed_segment_ids: RwLock<HashSet<SegmentId>>,
+}
+
+impl ComponentWarmer {
+    /// Creates a warmer loading the given components.
+    pub fn new(components: WarmUpComponents) -> ComponentWarmer {
+        ComponentWarmer {
+            components,
+            warmed_segment_ids: Default::default(),
+        }
+    }
+}
+
+impl Warmer for ComponentWarmer {
+    fn warm(&self, searcher: &Searcher) -> crate::Result<()> {
+        for segment_reader in searcher.segment_readers() {
+            let segment_id = segment_reader.segment_id();
+            if self
+                .warmed_segment_ids
+                .read()
+                .unwrap()
+                .contains(&segment_id)
+            {
+                continue;
+            }
+            segment_reader.warm_up(&self.components)?;
+            self.warmed_segment_ids.write().unwrap().insert(segment_id);
+        }
+        Ok(())
+    }
+
+    fn garbage_collect(&self, live_generations: &[&SearcherGeneration]) {
+        let live_segment_ids: HashSet<SegmentId> = live_generations
+            .iter()
+            .flat_map(|searcher_generation| searcher_generation.segments().keys().copied())
+            .collect();
+        self.warmed_segment_ids
+            .write()
+            .unwrap()
+            .retain(|segment_id| live_segment_ids.contains(segment_id));
+    }
+}
+
+/// A [`Warmer`] building the [global ordinals](Searcher::global_ordinals) of the given `str`
+/// fast fields, each time an [`IndexReader`](crate::IndexReader) reloads, before the new searcher
+/// is published.
+pub struct GlobalOrdinalsWarmer {
+    field_names: Vec<String>,
+}
+
+impl GlobalOrdinalsWarmer {
+    /// Creates a warmer building the global ordinals of the given fields.
+    pub fn new(field_names: Vec<String>) -> GlobalOrdinalsWarmer {
+        GlobalOrdinalsWarmer { field_names }
+    }
+}
+
+impl Warmer for GlobalOrdinalsWarmer {
+    fn warm(&self, searcher: &Searcher) -> crate::Result<()> {
+        for field_name in &self.field_names {
+            searcher.global_ordinals(field_name)?;
+        }
+        Ok(())
+    }
+
+    // The global ordinals are dropped with their searcher.
+    fn garbage_collect(&self, _live_generations: &[&SearcherGeneration]) {}
+}
+
 /// Warming-related state with interior mutability.
 #[derive(Clone)]
 pub(crate) struct WarmingState(Arc<Mutex<WarmingStateInner>>);
@@ -50,6 +219,18 @@ impl WarmingState {
             .warm_new_searcher_generation(searcher, &self.0)
     }
 
+    /// Registers a new warmer, and [`Warmer::warm`]s it with the current searcher.
+    pub fn register_warmer(
+        &self,
+        warmer: Weak<dyn Warmer>,
+        searcher: &Searcher,
+    ) -> crate::Result<()> {
+        self.0
+            .lock()
+            .unwrap()
+            .register_warmer(warmer, searcher, &self.0)
+    }
+
     #[cfg(test)]
     fn gc_maybe(&self) -> bool {
         self.0.lock().unwrap().gc_maybe()
@@ -90,6 +271,22 @@ impl WarmingStateInner {
         Ok(())
     }
 
+    fn register_warmer(
+        &mut self,
+        warmer: Weak<dyn Warmer>,
+        searcher: &Searcher,
+        this: &Arc<Mutex<Self>>,
+    ) -> crate::Result<()> {
+        let Some(strong_warmer) = warmer.upgrade() else {
+            return Ok(());
+        };
+        self.warmers.push(warmer);
+        self.start_gc_thread_maybe(this)?;
+        self.warmed_generation_ids
+            .insert(searcher.generation().generation_id());
+        strong_warmer.warm(searcher)
+    }
+
     /// Attempt to upgrade the weak `Warmer` references, pruning those which cannot be upgraded.
     /// Return the strong references.
     fn pruned_warmers(&mut self) -> Vec<Arc<dyn Warmer>> {
@@ -176,10 +373,11 @@ mod tests {
     use std::sync::atomic::{self, AtomicUsize};
     use std::sync::{Arc, RwLock, Weak};
 
-    use super::Warmer;
+    use super::{ComponentWarmer, WarmUpComponents, Warmer};
     use crate::core::searcher::SearcherGeneration;
-    use crate::directory::RamDirectory;
-    use crate::schema::{Schema, INDEXED};
+    use crate::core::SegmentComponent;
+    use crate::directory::{FileKind, InstrumentedDirectory, RamDirectory};
+    use crate::schema::{Schema, FAST, INDEXED, STORED, TEXT};
     use crate::{Index, IndexSettings, ReloadPolicy, Searcher, SegmentId};
 
     #[derive(Default)]
@@ -337,4 +535,102 @@ mod tests {
     fn warming_four_threads() -> crate::Result<()> {
         test_warming(4)
     }
+
+    #[test]
+    fn test_register_warmer() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let field = schema_builder.add_u64_field("pk", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut writer = index.writer_for_tests()?;
+        writer.add_document(doc!(field => 1u64))?;
+        writer.commit()?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+
+        let warmer = Arc::new(TestWarmer::default());
+        reader.register_warmer(Arc::downgrade(&warmer) as Weak<dyn Warmer>)?;
+        // The current searcher is warmed right away.
+        warmer.verify(1, 0, segment_ids(&reader.searcher()));
+
+        writer.add_document(doc!(field => 2u64))?;
+        writer.commit()?;
+        let old_searcher = reader.searcher();
+        reader.reload()?;
+        let searcher = reader.searcher();
+        warmer.verify(
+            2,
+            0,
+            segment_ids(&old_searcher)
+                .union(&segment_ids(&searcher))
+                .copied()
+                .collect(),
+        );
+        drop(old_searcher);
+        assert!(reader.inner.warming_state.gc_maybe());
+        warmer.verify(2, 1, segment_ids(&searcher));
+        Ok(())
+    }
+
+    #[test]
+    fn test_component_warmer() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT | STORED);
+        let num = schema_builder.add_u64_field("num", FAST);
+        let directory = InstrumentedDirectory::new(RamDirectory::create());
+        let index = Index::create(
+            directory.clone(),
+            schema_builder.build(),
+            IndexSettings::default(),
+        )?;
+        let components = WarmUpComponents::default()
+            .term_dictionary(text)
+            .fast_field("num")
+            .fieldnorms(text)
+            .store_index();
+        let warmer = Arc::new(ComponentWarmer::new(components));
+        let weak_warmer: Weak<dyn Warmer> = Arc::downgrade(&warmer) as _;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .warmers(vec![weak_warmer])
+            .try_into()?;
+        let mut writer = index.writer_for_tests()?;
+        writer.add_document(doc!(text => "hello", num => 1u64))?;
+        writer.commit()?;
+
+        let bytes_read = |component| {
+            directory
+                .io_stats()
+                .get(&FileKind::Segment(component))
+                .map(|stats| stats.bytes_read)
+                .unwrap_or(0)
+        };
+        directory.reset_io_stats();
+        reader.reload()?;
+        for component in [
+            SegmentComponent::Terms,
+            SegmentComponent::FastFields,
+            SegmentComponent::FieldNorms,
+            SegmentComponent::Store,
+        ] {
+            assert!(bytes_read(component) > 0);
+        }
+
+        // The segments that were already warmed up are not warmed up again.
+        writer.add_document(doc!(text => "world", num => 2u64))?;
+        writer.commit()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        assert_eq!(
+            *warmer.warmed_segment_ids.read().unwrap(),
+            segment_ids(&searcher)
+        );
+        writer.merge(&index.searchable_segment_ids()?).wait()?;
+        reader.reload()?;
+        warmer.garbage_collect(&[reader.searcher().generation()]);
+        assert_eq!(warmer.warmed_segment_ids.read().unwrap().len(), 1);
+        Ok(())
+    }
 }
@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+/// Configuration of a completion field: the values of the field are suggested as the
+/// completions of a prefix by a [`CompletionSuggester`](crate::suggest::CompletionSuggester).
+///
+/// The suggestions are ranked by a weight, taken from a `u64` field of the document, and can
+/// be filtered by contexts, taken from the values of text fields of the document, e.g. a
+/// category or a language.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompletionOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    weight_field: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    context_fields: Vec<String>,
+}
+
+impl CompletionOptions {
+    /// Sets the `u64` field holding the weight of the suggestions of a document.
+    ///
+    /// The first value of the field is used, and the documents without a value get a weight
+    /// of 0.
+    #[must_use]
+    pub fn set_weight_field(mut self, field_name: &str) -> CompletionOptions {
+        self.weight_field = Some(field_name.to_string());
+        self
+    }
+
+    /// Returns the name of the field holding the weight of the suggestions.
+    pub fn weight_field(&self) -> Option<&str> {
+        self.weight_field.as_deref()
+    }
+
+    /// Adds a text field whose values are contexts of the suggestions of a document.
+    ///
+    /// The values are used as is, without being tokenized. A completion field can have at most
+    /// 255 context fields.
+    #[must_use]
+    pub fn add_context_field(mut self, field_name: &str) -> CompletionOptions {
+        self.context_fields.push(field_name.to_string());
+        self
+    }
+
+    /// Returns the names of the fields holding the contexts of the suggestions.
+    pub fn context_fields(&self) -> &[String] {
+        &self.context_fields
+    }
+}
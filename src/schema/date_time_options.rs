@@ -23,6 +23,12 @@ pub struct DateOptions {
     // compression on fast fields.
     #[serde(default)]
     precision: DateTimePrecision,
+    #[serde(default, skip_serializing_if = "is_false")]
+    points: bool,
+}
+
+fn is_false(val: &bool) -> bool {
+    !val
 }
 
 impl DateOptions {
@@ -90,6 +96,21 @@ impl DateOptions {
         self
     }
 
+    /// Returns true iff the values are indexed in the points index.
+    pub fn has_points(&self) -> bool {
+        self.points
+    }
+
+    /// Index the values in a dedicated points index, used to accelerate range queries.
+    ///
+    /// Like in the inverted index, values are indexed with the
+    /// [`DATE_TIME_PRECISION_INDEXED`] precision.
+    #[must_use]
+    pub fn set_points(mut self) -> DateOptions {
+        self.points = true;
+        self
+    }
+
     /// Sets the precision for this DateTime field on the fast field.
     /// Indexed precision is always [`DATE_TIME_PRECISION_INDEXED`].
     ///
@@ -154,6 +175,7 @@ impl<T: Into<DateOptions>> BitOr<T> for DateOptions {
             stored: self.stored | other.stored,
             fast: self.fast | other.fast,
             precision: self.precision,
+            points: self.points | other.points,
         }
     }
 }
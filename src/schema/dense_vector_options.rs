@@ -0,0 +1,226 @@
+use serde::{Deserialize, Serialize};
+
+use crate::query::VectorSimilarity;
+
+const DEFAULT_HNSW_MAX_CONNECTIONS: usize = 16;
+const DEFAULT_HNSW_EF_CONSTRUCTION: usize = 100;
+
+/// The quantization of the vectors of an HNSW graph.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VectorQuantization {
+    /// The vectors are stored with full precision, as `f32`.
+    #[default]
+    None,
+    /// Each value of a vector is quantized to a byte, given the range of the values of its
+    /// dimension in the segment, dividing the size of the graph by about 4.
+    ///
+    /// The graph is searched with the quantized vectors, then the candidates are rescored
+    /// against the full-precision vectors of the fast field.
+    Int8,
+}
+
+/// Define how the HNSW graph of a dense vector field is built.
+///
+/// See the [vector module](crate::vector) for details.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HnswOptions {
+    max_connections: usize,
+    ef_construction: usize,
+    similarity: VectorSimilarity,
+    #[serde(default)]
+    quantization: VectorQuantization,
+}
+
+impl Default for HnswOptions {
+    fn default() -> HnswOptions {
+        HnswOptions {
+            max_connections: DEFAULT_HNSW_MAX_CONNECTIONS,
+            ef_construction: DEFAULT_HNSW_EF_CONSTRUCTION,
+            similarity: VectorSimilarity::Cosine,
+            quantization: VectorQuantization::None,
+        }
+    }
+}
+
+impl HnswOptions {
+    /// Returns the maximum number of neighbours of a vector in the upper layers of the graph.
+    pub fn max_connections(&self) -> usize {
+        self.max_connections
+    }
+
+    /// Returns the number of candidate neighbours considered when inserting a vector.
+    pub fn ef_construction(&self) -> usize {
+        self.ef_construction
+    }
+
+    /// Returns the similarity the graph is built with.
+    pub fn similarity(&self) -> VectorSimilarity {
+        self.similarity
+    }
+
+    /// Returns the quantization of the vectors of the graph.
+    pub fn quantization(&self) -> VectorQuantization {
+        self.quantization
+    }
+
+    /// Sets the maximum number of neighbours of a vector in the upper layers of the graph,
+    /// twice as many being kept in the bottom layer. It defaults to 16.
+    ///
+    /// More connections improve the recall, at the cost of a larger graph.
+    #[must_use]
+    pub fn set_max_connections(mut self, max_connections: usize) -> HnswOptions {
+        self.max_connections = max_connections.max(2);
+        self
+    }
+
+    /// Sets the number of candidate neighbours considered when inserting a vector. It
+    /// defaults to 100.
+    ///
+    /// More candidates improve the quality of the graph, at the cost of a slower indexing.
+    #[must_use]
+    pub fn set_ef_construction(mut self, ef_construction: usize) -> HnswOptions {
+        self.ef_construction = ef_construction.max(1);
+        self
+    }
+
+    /// Sets the similarity the graph is built with. It defaults to the cosine similarity.
+    ///
+    /// Only the [`KnnQuery`](crate::query::KnnQuery) using the same similarity can search the
+    /// graph.
+    #[must_use]
+    pub fn set_similarity(mut self, similarity: VectorSimilarity) -> HnswOptions {
+        self.similarity = similarity;
+        self
+    }
+
+    /// Sets the quantization of the vectors of the graph. The vectors are not quantized by
+    /// default.
+    #[must_use]
+    pub fn set_quantization(mut self, quantization: VectorQuantization) -> HnswOptions {
+        self.quantization = quantization;
+        self
+    }
+}
+
+/// Define how a dense vector field should be handled by tantivy.
+///
+/// A dense vector field holds vectors of `f32` with a fixed number of dimensions, e.g.
+/// embeddings, which can be searched with a [`KnnQuery`](crate::query::KnnQuery).
+///
+/// The vectors are always recorded in a fast field column, as bytes. They are neither
+/// indexed nor normed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DenseVectorOptions {
+    dimension: usize,
+    #[serde(default)]
+    stored: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hnsw: Option<HnswOptions>,
+}
+
+impl DenseVectorOptions {
+    /// Creates the options of a dense vector field, given the number of dimensions of its
+    /// vectors.
+    pub fn new(dimension: usize) -> DenseVectorOptions {
+        DenseVectorOptions {
+            dimension,
+            stored: false,
+            hnsw: None,
+        }
+    }
+
+    /// Returns the number of dimensions of the vectors.
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    /// Returns true if the vectors are stored.
+    pub fn is_stored(&self) -> bool {
+        self.stored
+    }
+
+    /// Returns the options of the HNSW graph of the field, if it has one.
+    pub fn hnsw_options(&self) -> Option<&HnswOptions> {
+        self.hnsw.as_ref()
+    }
+
+    /// Builds an HNSW graph of the vectors of each segment, to search the approximate nearest
+    /// neighbours of a vector without scanning all of the vectors.
+    #[must_use]
+    pub fn set_hnsw(mut self, hnsw_options: HnswOptions) -> DenseVectorOptions {
+        self.hnsw = Some(hnsw_options);
+        self
+    }
+
+    /// Set the field as stored.
+    ///
+    /// Only the fields that are set as *stored* are
+    /// persisted into the Tantivy's store.
+    #[must_use]
+    pub fn set_stored(mut self) -> DenseVectorOptions {
+        self.stored = true;
+        self
+    }
+}
+
+/// Appends the bytes recorded in the fast field column for a vector: its values, as little
+/// endian `f32`.
+pub(crate) fn dense_vector_to_bytes(vector: &[f32], output: &mut Vec<u8>) {
+    output.reserve(std::mem::size_of_val(vector));
+    for value in vector {
+        output.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+/// Decodes the bytes recorded in the fast field column for a vector.
+pub(crate) fn dense_vector_from_bytes(bytes: &[u8], output: &mut Vec<f32>) {
+    output.clear();
+    output.extend(
+        bytes
+            .chunks_exact(std::mem::size_of::<f32>())
+            .map(|value_bytes| f32::from_le_bytes(value_bytes.try_into().unwrap())),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dense_vector_options_serialization() {
+        let options = DenseVectorOptions::new(3).set_stored();
+        let json = serde_json::to_string(&options).unwrap();
+        assert_eq!(json, r#"{"dimension":3,"stored":true}"#);
+        let options_deser: DenseVectorOptions = serde_json::from_str(r#"{"dimension":3}"#).unwrap();
+        assert_eq!(options_deser, DenseVectorOptions::new(3));
+        let options = DenseVectorOptions::new(3).set_hnsw(HnswOptions::default());
+        let json = serde_json::to_string(&options).unwrap();
+        assert_eq!(
+            json,
+            r#"{"dimension":3,"stored":false,"hnsw":{"max_connections":16,"ef_construction":100,"similarity":"cosine","quantization":"none"}}"#
+        );
+        let options_deser: DenseVectorOptions = serde_json::from_str(&json).unwrap();
+        assert_eq!(options_deser, options);
+        let options_deser: HnswOptions = serde_json::from_str(
+            r#"{"max_connections":16,"ef_construction":100,"similarity":"l2","quantization":"int8"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            options_deser,
+            HnswOptions::default()
+                .set_similarity(VectorSimilarity::L2)
+                .set_quantization(VectorQuantization::Int8)
+        );
+    }
+
+    #[test]
+    fn test_dense_vector_bytes() {
+        let mut bytes = Vec::new();
+        dense_vector_to_bytes(&[1.0, -0.5, 3.25], &mut bytes);
+        assert_eq!(bytes.len(), 12);
+        let mut vector = vec![7.0];
+        dense_vector_from_bytes(&bytes, &mut vector);
+        assert_eq!(vector, vec![1.0, -0.5, 3.25]);
+    }
+}
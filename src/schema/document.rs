@@ -128,6 +128,16 @@ impl Document {
         self.add_field_value(field, value.into());
     }
 
+    /// Add a dense vector field
+    pub fn add_dense_vector(&mut self, field: Field, vector: Vec<f32>) {
+        self.add_field_value(field, vector);
+    }
+
+    /// Add a geo point field
+    pub fn add_geo_point(&mut self, field: Field, geo_point: GeoPoint) {
+        self.add_field_value(field, geo_point);
+    }
+
     /// Add a JSON field
     pub fn add_json_object(
         &mut self,
@@ -227,6 +237,29 @@ impl Document {
     }
 }
 
+impl Document {
+    /// Deserializes a document, keeping only the values of the given fields.
+    ///
+    /// The values of the other fields are skipped without being decoded.
+    pub(crate) fn deserialize_with_fields(
+        reader: &mut &[u8],
+        fields: &[Field],
+    ) -> io::Result<Document> {
+        let num_field_values = VInt::deserialize(reader)?.val() as usize;
+        let mut field_values = Vec::new();
+        for _ in 0..num_field_values {
+            let field = Field::deserialize(reader)?;
+            if fields.contains(&field) {
+                let value = Value::deserialize(reader)?;
+                field_values.push(FieldValue::new(field, value));
+            } else {
+                crate::schema::value::skip_value(reader)?;
+            }
+        }
+        Ok(Document::from(field_values))
+    }
+}
+
 impl BinarySerializable for Document {
     fn serialize<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
         let field_values = self.field_values();
@@ -1,10 +1,13 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 use super::ip_options::IpAddrOptions;
 use crate::schema::bytes_options::BytesOptions;
+use crate::schema::field_type::ValueParsingError;
 use crate::schema::{
-    is_valid_field_name, DateOptions, FacetOptions, FieldType, JsonObjectOptions, NumericOptions,
-    TextOptions,
+    is_valid_field_name, CompletionOptions, DateOptions, DenseVectorOptions, FacetOptions,
+    FieldType, GeoPointOptions, JsonObjectOptions, NumericOptions, TextOptions, Value,
 };
 
 /// A `FieldEntry` represents a field and its configuration.
@@ -19,6 +22,12 @@ pub struct FieldEntry {
     name: String,
     #[serde(flatten)]
     field_type: FieldType,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default_value: Option<Value>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    metadata: BTreeMap<String, String>,
 }
 
 impl FieldEntry {
@@ -28,6 +37,9 @@ impl FieldEntry {
         FieldEntry {
             name: field_name,
             field_type,
+            aliases: Vec::new(),
+            default_value: None,
+            metadata: BTreeMap::new(),
         }
     }
 
@@ -76,6 +88,19 @@ impl FieldEntry {
         Self::new(field_name, FieldType::Bytes(bytes_options))
     }
 
+    /// Creates a field entry for a dense vector field
+    pub fn new_dense_vector(
+        field_name: String,
+        dense_vector_options: DenseVectorOptions,
+    ) -> FieldEntry {
+        Self::new(field_name, FieldType::DenseVector(dense_vector_options))
+    }
+
+    /// Creates a field entry for a geo point field
+    pub fn new_geo_point(field_name: String, geo_point_options: GeoPointOptions) -> FieldEntry {
+        Self::new(field_name, FieldType::GeoPoint(geo_point_options))
+    }
+
     /// Creates a field entry for a json field
     pub fn new_json(field_name: String, json_object_options: JsonObjectOptions) -> FieldEntry {
         Self::new(field_name, FieldType::JsonObject(json_object_options))
@@ -86,6 +111,56 @@ impl FieldEntry {
         &self.name
     }
 
+    /// Returns the aliases of the field.
+    ///
+    /// Aliases are alternative names the field can be referred to with,
+    /// for instance in queries or aggregations.
+    pub fn aliases(&self) -> &[String] {
+        &self.aliases
+    }
+
+    pub(crate) fn add_alias(&mut self, alias: String) {
+        self.aliases.push(alias);
+    }
+
+    /// Returns the user metadata attached to the field.
+    ///
+    /// This metadata is persisted with the schema, and is entirely unused by tantivy.
+    pub fn metadata(&self) -> &BTreeMap<String, String> {
+        &self.metadata
+    }
+
+    pub(crate) fn set_metadata(&mut self, key: String, value: String) {
+        self.metadata.insert(key, value);
+    }
+
+    /// Returns the value added to the documents that do not have any value for this
+    /// field, when they are indexed.
+    pub fn default_value(&self) -> Option<&Value> {
+        self.default_value.as_ref()
+    }
+
+    /// Sets the default value of the field, converting it to the type of the field.
+    pub(crate) fn set_default_value(
+        &mut self,
+        default_value: &Value,
+    ) -> Result<(), ValueParsingError> {
+        // Going through the json representation handles values that lose their exact
+        // type once serialized, like dates or negative integers.
+        let json = serde_json::to_value(default_value).expect("Values are serializable");
+        self.default_value = Some(self.field_type.value_from_json(json)?);
+        Ok(())
+    }
+
+    /// Converts the default value, possibly deserialized as a different type, to the
+    /// type of the field.
+    pub(crate) fn normalize_default_value(&mut self) -> Result<(), ValueParsingError> {
+        if let Some(default_value) = self.default_value.take() {
+            self.set_default_value(&default_value)?;
+        }
+        Ok(())
+    }
+
     /// Returns the field type
     pub fn field_type(&self) -> &FieldType {
         &self.field_type
@@ -103,6 +178,26 @@ impl FieldEntry {
         self.field_type.has_fieldnorms()
     }
 
+    /// Returns true if the field has a points index.
+    pub fn has_points(&self) -> bool {
+        self.field_type.has_points()
+    }
+
+    /// Returns true if the field stores term vectors.
+    pub fn has_term_vectors(&self) -> bool {
+        self.field_type.has_term_vectors()
+    }
+
+    /// Returns true if the field stores the payloads of its tokens.
+    pub fn has_payloads(&self) -> bool {
+        self.field_type.has_payloads()
+    }
+
+    /// Returns the completion options, if the field is a completion field.
+    pub fn completion_options(&self) -> Option<&CompletionOptions> {
+        self.field_type.completion_options()
+    }
+
     /// Returns true if the field is a fast field
     pub fn is_fast(&self) -> bool {
         self.field_type.is_fast()
@@ -121,6 +216,8 @@ impl FieldEntry {
             FieldType::Bytes(ref options) => options.is_stored(),
             FieldType::JsonObject(ref options) => options.is_stored(),
             FieldType::IpAddr(ref options) => options.is_stored(),
+            FieldType::DenseVector(ref options) => options.is_stored(),
+            FieldType::GeoPoint(ref options) => options.is_stored(),
         }
     }
 }
@@ -170,6 +267,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_json_serialization_with_aliases() {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body_text", TEXT);
+        schema_builder.add_field_alias("content", body);
+        let schema = schema_builder.build();
+        let field_entry = schema.get_field_entry(body);
+        let field_entry_json = serde_json::to_value(field_entry).unwrap();
+        assert_eq!(field_entry_json["aliases"], serde_json::json!(["content"]));
+        let deserialized_field_entry: FieldEntry =
+            serde_json::from_value(field_entry_json).unwrap();
+        assert_eq!(&deserialized_field_entry, field_entry);
+        assert_eq!(deserialized_field_entry.aliases(), &["content".to_string()]);
+    }
+
     #[test]
     fn test_json_deserialization() {
         let json_str = r#"{
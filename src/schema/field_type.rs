@@ -12,7 +12,8 @@ use super::IntoIpv6Addr;
 use crate::schema::bytes_options::BytesOptions;
 use crate::schema::facet_options::FacetOptions;
 use crate::schema::{
-    DateOptions, Facet, IndexRecordOption, JsonObjectOptions, NumericOptions, TextFieldIndexing,
+    CompletionOptions, DateOptions, DenseVectorOptions, Facet, GeoPoint, GeoPointOptions,
+    HnswOptions, IndexRecordOption, JsonObjectOptions, NumericOptions, TextFieldIndexing,
     TextOptions, Value,
 };
 use crate::time::format_description::well_known::Rfc3339;
@@ -70,9 +71,13 @@ pub enum Type {
     Json = b'j',
     /// IpAddr
     IpAddr = b'p',
+    /// `Vec<f32>` with a fixed dimension.
+    DenseVector = b'v',
+    /// `GeoPoint`
+    GeoPoint = b'g',
 }
 
-const ALL_TYPES: [Type; 10] = [
+const ALL_TYPES: [Type; 12] = [
     Type::Str,
     Type::U64,
     Type::I64,
@@ -83,6 +88,8 @@ const ALL_TYPES: [Type; 10] = [
     Type::Bytes,
     Type::Json,
     Type::IpAddr,
+    Type::DenseVector,
+    Type::GeoPoint,
 ];
 
 impl Type {
@@ -110,6 +117,8 @@ impl Type {
             Type::Bytes => "Bytes",
             Type::Json => "Json",
             Type::IpAddr => "IpAddr",
+            Type::DenseVector => "DenseVector",
+            Type::GeoPoint => "GeoPoint",
         }
     }
 
@@ -127,6 +136,8 @@ impl Type {
             b'b' => Some(Type::Bytes),
             b'j' => Some(Type::Json),
             b'p' => Some(Type::IpAddr),
+            b'v' => Some(Type::DenseVector),
+            b'g' => Some(Type::GeoPoint),
             _ => None,
         }
     }
@@ -159,6 +170,10 @@ pub enum FieldType {
     JsonObject(JsonObjectOptions),
     /// IpAddr field
     IpAddr(IpAddrOptions),
+    /// Dense vector field
+    DenseVector(DenseVectorOptions),
+    /// Geo point field
+    GeoPoint(GeoPointOptions),
 }
 
 impl FieldType {
@@ -175,6 +190,8 @@ impl FieldType {
             FieldType::Bytes(_) => Type::Bytes,
             FieldType::JsonObject(_) => Type::Json,
             FieldType::IpAddr(_) => Type::IpAddr,
+            FieldType::DenseVector(_) => Type::DenseVector,
+            FieldType::GeoPoint(_) => Type::GeoPoint,
         }
     }
 
@@ -188,6 +205,16 @@ impl FieldType {
         matches!(self, FieldType::Date(_))
     }
 
+    /// returns true if this is a dense vector field
+    pub fn is_dense_vector(&self) -> bool {
+        matches!(self, FieldType::DenseVector(_))
+    }
+
+    /// returns true if this is a geo point field
+    pub fn is_geo_point(&self) -> bool {
+        matches!(self, FieldType::GeoPoint(_))
+    }
+
     /// returns true if the field is indexed.
     pub fn is_indexed(&self) -> bool {
         match *self {
@@ -201,6 +228,7 @@ impl FieldType {
             FieldType::Bytes(ref bytes_options) => bytes_options.is_indexed(),
             FieldType::JsonObject(ref json_object_options) => json_object_options.is_indexed(),
             FieldType::IpAddr(ref ip_addr_options) => ip_addr_options.is_indexed(),
+            FieldType::DenseVector(_) | FieldType::GeoPoint(_) => false,
         }
     }
 
@@ -238,6 +266,7 @@ impl FieldType {
             FieldType::IpAddr(ref ip_addr_options) => ip_addr_options.is_fast(),
             FieldType::Facet(_) => true,
             FieldType::JsonObject(ref json_object_options) => json_object_options.is_fast(),
+            FieldType::DenseVector(_) | FieldType::GeoPoint(_) => true,
         }
     }
 
@@ -257,6 +286,64 @@ impl FieldType {
             FieldType::Bytes(ref bytes_options) => bytes_options.fieldnorms(),
             FieldType::JsonObject(ref _json_object_options) => false,
             FieldType::IpAddr(ref ip_addr_options) => ip_addr_options.fieldnorms(),
+            FieldType::DenseVector(_) | FieldType::GeoPoint(_) => false,
+        }
+    }
+
+    /// returns true if the field has a points index.
+    pub fn has_points(&self) -> bool {
+        match *self {
+            FieldType::U64(ref int_options)
+            | FieldType::I64(ref int_options)
+            | FieldType::F64(ref int_options)
+            | FieldType::Bool(ref int_options) => int_options.has_points(),
+            FieldType::Date(ref date_options) => date_options.has_points(),
+            FieldType::GeoPoint(ref geo_point_options) => geo_point_options.has_points(),
+            FieldType::Str(_)
+            | FieldType::Facet(_)
+            | FieldType::Bytes(_)
+            | FieldType::JsonObject(_)
+            | FieldType::IpAddr(_)
+            | FieldType::DenseVector(_) => false,
+        }
+    }
+
+    /// returns true if the field stores term vectors.
+    pub fn has_term_vectors(&self) -> bool {
+        match *self {
+            FieldType::Str(ref text_options) => text_options
+                .get_indexing_options()
+                .map(|options| options.term_vectors())
+                .unwrap_or(false),
+            _ => false,
+        }
+    }
+
+    /// Returns the options of the HNSW graph of the field, if it is a dense vector field with
+    /// an HNSW graph.
+    pub fn hnsw_options(&self) -> Option<&HnswOptions> {
+        match *self {
+            FieldType::DenseVector(ref dense_vector_options) => dense_vector_options.hnsw_options(),
+            _ => None,
+        }
+    }
+
+    /// Returns the completion options, if the field is a completion field.
+    pub fn completion_options(&self) -> Option<&CompletionOptions> {
+        match *self {
+            FieldType::Str(ref text_options) => text_options.get_completion_options(),
+            _ => None,
+        }
+    }
+
+    /// returns true if the field stores the payloads of its tokens.
+    pub fn has_payloads(&self) -> bool {
+        match *self {
+            FieldType::Str(ref text_options) => text_options
+                .get_indexing_options()
+                .map(|options| options.payloads())
+                .unwrap_or(false),
+            _ => false,
         }
     }
 
@@ -308,6 +395,7 @@ impl FieldType {
                     None
                 }
             }
+            FieldType::DenseVector(_) | FieldType::GeoPoint(_) => None,
         }
     }
 
@@ -408,6 +496,17 @@ impl FieldType {
 
                         Ok(Value::IpAddr(ip_addr.into_ipv6_addr()))
                     }
+                    FieldType::DenseVector(_) => Err(ValueParsingError::TypeError {
+                        expected: "an array of numbers",
+                        json: JsonValue::String(field_text),
+                    }),
+                    FieldType::GeoPoint(_) => match GeoPoint::from_str(&field_text) {
+                        Ok(geo_point) => Ok(Value::GeoPoint(geo_point)),
+                        Err(err) => Err(ValueParsingError::ParseError {
+                            error: err.to_string(),
+                            json: JsonValue::String(field_text),
+                        }),
+                    },
                 }
             }
             JsonValue::Number(field_val_num) => match self {
@@ -467,6 +566,14 @@ impl FieldType {
                     expected: "a string with an ip addr",
                     json: JsonValue::Number(field_val_num),
                 }),
+                FieldType::DenseVector(_) => Err(ValueParsingError::TypeError {
+                    expected: "an array of numbers",
+                    json: JsonValue::Number(field_val_num),
+                }),
+                FieldType::GeoPoint(_) => Err(ValueParsingError::TypeError {
+                    expected: "a geo point object or a `lat,lon` string",
+                    json: JsonValue::Number(field_val_num),
+                }),
             },
             JsonValue::Object(json_map) => match self {
                 FieldType::Str(_) => {
@@ -482,6 +589,20 @@ impl FieldType {
                     }
                 }
                 FieldType::JsonObject(_) => Ok(Value::JsonObject(json_map)),
+                FieldType::GeoPoint(_) => {
+                    let geo_point =
+                        serde_json::from_value::<GeoPoint>(JsonValue::Object(json_map.clone()))
+                            .ok()
+                            .and_then(|geo_point| GeoPoint::new(geo_point.lat, geo_point.lon));
+                    geo_point
+                        .map(Value::GeoPoint)
+                        .ok_or_else(|| ValueParsingError::ParseError {
+                            error: "expected an object with a `lat` within [-90, 90] and a `lon` \
+                                    within [-180, 180]"
+                                .to_string(),
+                            json: JsonValue::Object(json_map),
+                        })
+                }
                 _ => Err(ValueParsingError::TypeError {
                     expected: self.value_type().name(),
                     json: JsonValue::Object(json_map),
@@ -521,10 +642,33 @@ impl FieldType {
                     json: JsonValue::Null,
                 }),
             },
-            _ => Err(ValueParsingError::TypeError {
-                expected: self.value_type().name(),
-                json: json.clone(),
-            }),
+            JsonValue::Array(json_items) => match self {
+                FieldType::DenseVector(dense_vector_options) => {
+                    let vector: Vec<f32> = json_items
+                        .iter()
+                        .map(|json_item| json_item.as_f64().map(|value| value as f32))
+                        .collect::<Option<_>>()
+                        .ok_or_else(|| ValueParsingError::TypeError {
+                            expected: "an array of numbers",
+                            json: JsonValue::Array(json_items.clone()),
+                        })?;
+                    if vector.len() != dense_vector_options.dimension() {
+                        return Err(ValueParsingError::ParseError {
+                            error: format!(
+                                "expected a vector with {} dimensions, got {}",
+                                dense_vector_options.dimension(),
+                                vector.len()
+                            ),
+                            json: JsonValue::Array(json_items),
+                        });
+                    }
+                    Ok(Value::DenseVector(vector))
+                }
+                _ => Err(ValueParsingError::TypeError {
+                    expected: self.value_type().name(),
+                    json: JsonValue::Array(json_items),
+                }),
+            },
         }
     }
 }
@@ -535,11 +679,79 @@ mod tests {
 
     use super::FieldType;
     use crate::schema::field_type::ValueParsingError;
-    use crate::schema::{NumericOptions, Schema, TextOptions, Type, Value, COERCE, INDEXED};
+    use crate::schema::{
+        DenseVectorOptions, DocParsingError, GeoPoint, GeoPointOptions, NumericOptions, Schema,
+        TextOptions, Type, Value, COERCE, INDEXED,
+    };
     use crate::time::{Date, Month, PrimitiveDateTime, Time};
     use crate::tokenizer::{PreTokenizedString, Token};
     use crate::{DateTime, Document};
 
+    #[test]
+    fn test_dense_vector_from_json() {
+        let mut schema_builder = Schema::builder();
+        let embedding = schema_builder
+            .add_dense_vector_field("embedding", DenseVectorOptions::new(2).set_stored());
+        let schema = schema_builder.build();
+        let doc = schema.parse_document(r#"{"embedding": [1, 0.5]}"#).unwrap();
+        assert_eq!(
+            doc.get_all(embedding).collect::<Vec<_>>(),
+            vec![&Value::DenseVector(vec![1.0, 0.5])]
+        );
+        let doc_json = schema.to_json(&doc);
+        assert_eq!(doc_json, r#"{"embedding":[[1.0,0.5]]}"#);
+        let doc = schema.parse_document(&doc_json).unwrap();
+        assert_eq!(doc.get_all(embedding).count(), 1);
+        assert!(matches!(
+            schema.parse_document(r#"{"embedding": [1, 0.5, 2]}"#),
+            Err(DocParsingError::ValueError(
+                _,
+                ValueParsingError::ParseError { .. }
+            ))
+        ));
+        assert!(schema
+            .parse_document(r#"{"embedding": ["a", "b"]}"#)
+            .is_err());
+    }
+
+    #[test]
+    fn test_geo_point_from_json() {
+        let mut schema_builder = Schema::builder();
+        let location =
+            schema_builder.add_geo_point_field("location", GeoPointOptions::default().set_stored());
+        let schema = schema_builder.build();
+        let doc = schema
+            .parse_document(r#"{"location": [{"lat": 48.85, "lon": 2.35}, "-33.8,151.2"]}"#)
+            .unwrap();
+        assert_eq!(
+            doc.get_all(location).collect::<Vec<_>>(),
+            vec![
+                &Value::GeoPoint(GeoPoint {
+                    lat: 48.85,
+                    lon: 2.35
+                }),
+                &Value::GeoPoint(GeoPoint {
+                    lat: -33.8,
+                    lon: 151.2
+                })
+            ]
+        );
+        let doc_json = schema.to_json(&doc);
+        assert_eq!(
+            doc_json,
+            r#"{"location":[{"lat":48.85,"lon":2.35},{"lat":-33.8,"lon":151.2}]}"#
+        );
+        assert_eq!(schema.parse_document(&doc_json).unwrap(), doc);
+        for invalid_json in [
+            r#"{"location": {"lat": 91, "lon": 2.35}}"#,
+            r#"{"location": {"lat": 48.85}}"#,
+            r#"{"location": "48.85"}"#,
+            r#"{"location": 48.85}"#,
+        ] {
+            assert!(schema.parse_document(invalid_json).is_err());
+        }
+    }
+
     #[test]
     fn test_to_string_coercion() {
         let mut schema_builder = Schema::builder();
@@ -703,6 +915,8 @@ mod tests {
                     position: 0,
                     text: String::from("The"),
                     position_length: 1,
+                    keyword: false,
+                    payload: Vec::new(),
                 },
                 Token {
                     offset_from: 4,
@@ -710,6 +924,8 @@ mod tests {
                     position: 1,
                     text: String::from("Old"),
                     position_length: 1,
+                    keyword: false,
+                    payload: Vec::new(),
                 },
                 Token {
                     offset_from: 8,
@@ -717,6 +933,8 @@ mod tests {
                     position: 2,
                     text: String::from("Man"),
                     position_length: 1,
+                    keyword: false,
+                    payload: Vec::new(),
                 },
             ],
         });
@@ -0,0 +1,201 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// Mean radius of the earth, in meters.
+pub const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+const LAT_SCALE: f64 = (1u64 << 32) as f64 / 180.0;
+const LON_SCALE: f64 = (1u64 << 32) as f64 / 360.0;
+
+/// A point on the earth, given by its latitude and longitude in degrees.
+///
+/// In a geo point field, points are recorded in a fast field as a `u64`: the latitude and the
+/// longitude are each quantized to 32 bits, and their bits are interleaved along a Z-order curve,
+/// so that close points usually have close `u64` values. This makes it possible to cover an
+/// area of the earth with a small number of ranges of `u64` values.
+///
+/// The quantization error is below a centimeter.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeoPoint {
+    /// Latitude, in degrees, within `[-90, 90]`.
+    pub lat: f64,
+    /// Longitude, in degrees, within `[-180, 180]`.
+    pub lon: f64,
+}
+
+impl GeoPoint {
+    /// Creates a geo point, or returns `None` if the latitude is not within `[-90, 90]` or the
+    /// longitude is not within `[-180, 180]`.
+    pub fn new(lat: f64, lon: f64) -> Option<GeoPoint> {
+        if (-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon) {
+            Some(GeoPoint { lat, lon })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the great-circle distance to another point, in meters, computed with the
+    /// haversine formula.
+    pub fn distance(&self, other: &GeoPoint) -> f64 {
+        let lat1 = self.lat.to_radians();
+        let lat2 = other.lat.to_radians();
+        let half_delta_lat = (lat2 - lat1) / 2.0;
+        let half_delta_lon = (other.lon - self.lon).to_radians() / 2.0;
+        let a =
+            half_delta_lat.sin().powi(2) + lat1.cos() * lat2.cos() * half_delta_lon.sin().powi(2);
+        2.0 * EARTH_RADIUS_METERS * a.sqrt().min(1.0).asin()
+    }
+
+    /// Returns the `u64` representation of the point, as recorded in the fast field.
+    pub fn to_u64(&self) -> u64 {
+        interleave(quantize_lat(self.lat), quantize_lon(self.lon))
+    }
+
+    /// Returns the point of the given `u64` representation.
+    pub fn from_u64(val: u64) -> GeoPoint {
+        let (lat, lon) = deinterleave(val);
+        GeoPoint {
+            lat: dequantize_lat(lat as u64),
+            lon: dequantize_lon(lon as u64),
+        }
+    }
+}
+
+impl fmt::Display for GeoPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.lat, self.lon)
+    }
+}
+
+/// Error returned when parsing a geo point fails.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error(
+    "Invalid geo point {0:?}: expected `lat,lon` with a latitude within [-90, 90] and a longitude \
+     within [-180, 180]"
+)]
+pub struct GeoPointParseError(pub String);
+
+impl FromStr for GeoPoint {
+    type Err = GeoPointParseError;
+
+    /// Parses a geo point formatted as `lat,lon`.
+    fn from_str(text: &str) -> Result<GeoPoint, GeoPointParseError> {
+        let make_error = || GeoPointParseError(text.to_string());
+        let (lat, lon) = text.split_once(',').ok_or_else(make_error)?;
+        let lat = f64::from_str(lat.trim()).map_err(|_| make_error())?;
+        let lon = f64::from_str(lon.trim()).map_err(|_| make_error())?;
+        GeoPoint::new(lat, lon).ok_or_else(make_error)
+    }
+}
+
+pub(crate) fn quantize_lat(lat: f64) -> u32 {
+    ((lat + 90.0) * LAT_SCALE).clamp(0.0, u32::MAX as f64) as u32
+}
+
+pub(crate) fn quantize_lon(lon: f64) -> u32 {
+    ((lon + 180.0) * LON_SCALE).clamp(0.0, u32::MAX as f64) as u32
+}
+
+// The quantized values are taken as `u64`, so that the upper edge of the last cells of the
+// Z-order curve, `1 << 32`, can be dequantized.
+pub(crate) fn dequantize_lat(lat: u64) -> f64 {
+    lat as f64 / LAT_SCALE - 90.0
+}
+
+pub(crate) fn dequantize_lon(lon: u64) -> f64 {
+    lon as f64 / LON_SCALE - 180.0
+}
+
+/// Spreads the bits of a `u32` to the even bits of a `u64`.
+fn spread(val: u32) -> u64 {
+    let mut val = val as u64;
+    val = (val | (val << 16)) & 0x0000_FFFF_0000_FFFF;
+    val = (val | (val << 8)) & 0x00FF_00FF_00FF_00FF;
+    val = (val | (val << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    val = (val | (val << 2)) & 0x3333_3333_3333_3333;
+    (val | (val << 1)) & 0x5555_5555_5555_5555
+}
+
+/// Gathers the even bits of a `u64` into a `u32`.
+fn compact(val: u64) -> u32 {
+    let mut val = val & 0x5555_5555_5555_5555;
+    val = (val | (val >> 1)) & 0x3333_3333_3333_3333;
+    val = (val | (val >> 2)) & 0x0F0F_0F0F_0F0F_0F0F;
+    val = (val | (val >> 4)) & 0x00FF_00FF_00FF_00FF;
+    val = (val | (val >> 8)) & 0x0000_FFFF_0000_FFFF;
+    ((val | (val >> 16)) & 0x0000_0000_FFFF_FFFF) as u32
+}
+
+/// Interleaves the bits of the quantized latitude (odd bits) and longitude (even bits).
+pub(crate) fn interleave(lat: u32, lon: u32) -> u64 {
+    (spread(lat) << 1) | spread(lon)
+}
+
+/// Returns the quantized latitude and longitude of an interleaved value.
+pub(crate) fn deinterleave(val: u64) -> (u32, u32) {
+    (compact(val >> 1), compact(val))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::{deinterleave, interleave, GeoPoint};
+
+    #[test]
+    fn test_geo_point_interleave() {
+        assert_eq!(interleave(0, 1), 1);
+        assert_eq!(interleave(1, 0), 2);
+        assert_eq!(interleave(u32::MAX, u32::MAX), u64::MAX);
+        assert_eq!(
+            deinterleave(interleave(123_456, 987_654_321)),
+            (123_456, 987_654_321)
+        );
+    }
+
+    #[test]
+    fn test_geo_point_to_u64() {
+        for &(lat, lon) in &[
+            (48.8566, 2.3522),
+            (-33.8688, 151.2093),
+            (90.0, 180.0),
+            (-90.0, -180.0),
+        ] {
+            let point = GeoPoint::from_u64(GeoPoint::new(lat, lon).unwrap().to_u64());
+            assert!((point.lat - lat).abs() < 1e-7);
+            assert!((point.lon - lon).abs() < 1e-7);
+        }
+        // The order of the values follows the Z-order curve.
+        let south_west = GeoPoint::new(-10.0, -10.0).unwrap().to_u64();
+        let north_east = GeoPoint::new(10.0, 10.0).unwrap().to_u64();
+        assert!(south_west < north_east);
+    }
+
+    #[test]
+    fn test_geo_point_distance() {
+        let paris = GeoPoint::new(48.8566, 2.3522).unwrap();
+        let london = GeoPoint::new(51.5072, -0.1276).unwrap();
+        assert!((paris.distance(&london) - 343_500.0).abs() < 1_000.0);
+        assert_eq!(paris.distance(&paris), 0.0);
+        let north_pole = GeoPoint::new(90.0, 0.0).unwrap();
+        let south_pole = GeoPoint::new(-90.0, 0.0).unwrap();
+        assert!((north_pole.distance(&south_pole) - 20_015_115.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_geo_point_from_str() {
+        assert_eq!(
+            GeoPoint::from_str("48.85, 2.35"),
+            Ok(GeoPoint {
+                lat: 48.85,
+                lon: 2.35
+            })
+        );
+        assert!(GeoPoint::from_str("48.85").is_err());
+        assert!(GeoPoint::from_str("91,2").is_err());
+        assert!(GeoPoint::from_str("a,b").is_err());
+        assert!(GeoPoint::new(0.0, 180.5).is_none());
+    }
+}
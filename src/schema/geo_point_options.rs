@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+/// Define how a geo point field should be handled by tantivy.
+///
+/// The points are always recorded in a fast field column, in their `u64` representation
+/// (see [`GeoPoint`](crate::schema::GeoPoint)), and are searched with a
+/// [`GeoDistanceQuery`](crate::query::GeoDistanceQuery), a
+/// [`GeoBoundingBoxQuery`](crate::query::GeoBoundingBoxQuery) or a
+/// [`GeoPolygonQuery`](crate::query::GeoPolygonQuery). They are not indexed in the inverted
+/// index.
+///
+/// The documents can be sorted by their distance to a point with
+/// [`TopDocs::order_by_geo_distance`](crate::collector::TopDocs::order_by_geo_distance).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeoPointOptions {
+    #[serde(default)]
+    stored: bool,
+    #[serde(default)]
+    points: bool,
+}
+
+impl GeoPointOptions {
+    /// Returns `true` if the geo point should be stored in the doc store.
+    pub fn is_stored(&self) -> bool {
+        self.stored
+    }
+
+    /// Returns true iff the points are indexed in the points index.
+    pub fn has_points(&self) -> bool {
+        self.points
+    }
+
+    /// Sets the field as stored
+    #[must_use]
+    pub fn set_stored(mut self) -> GeoPointOptions {
+        self.stored = true;
+        self
+    }
+
+    /// Index the points in the points index, used to accelerate geo queries.
+    ///
+    /// The candidate points of a geo query are then read from the sorted points rather than
+    /// by scanning the fast field of all documents.
+    #[must_use]
+    pub fn set_points(mut self) -> GeoPointOptions {
+        self.points = true;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GeoPointOptions;
+
+    #[test]
+    fn test_geo_point_options_serialization() {
+        let options = GeoPointOptions::default().set_stored().set_points();
+        let json = serde_json::to_string(&options).unwrap();
+        assert_eq!(json, r#"{"stored":true,"points":true}"#);
+        assert_eq!(
+            serde_json::from_str::<GeoPointOptions>("{}").unwrap(),
+            GeoPointOptions::default()
+        );
+    }
+}
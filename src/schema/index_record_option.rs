@@ -26,6 +26,13 @@ pub enum IndexRecordOption {
     /// Positions are required to run a [`PhraseQuery`](crate::query::PhraseQuery).
     #[serde(rename = "position")]
     WithFreqsAndPositions,
+    /// records the document id, the term frequency, the positions of
+    /// the occurrences in the document, and the byte offsets of the
+    /// tokens of these occurrences.
+    /// Offsets make it possible to highlight the occurrences of a term
+    /// without analyzing the text of the document again.
+    #[serde(rename = "offset")]
+    WithFreqsAndPositionsAndOffsets,
 }
 
 impl IndexRecordOption {
@@ -34,7 +41,9 @@ impl IndexRecordOption {
     pub fn has_freq(self) -> bool {
         match self {
             IndexRecordOption::Basic => false,
-            IndexRecordOption::WithFreqs | IndexRecordOption::WithFreqsAndPositions => true,
+            IndexRecordOption::WithFreqs
+            | IndexRecordOption::WithFreqsAndPositions
+            | IndexRecordOption::WithFreqsAndPositionsAndOffsets => true,
         }
     }
 
@@ -43,7 +52,19 @@ impl IndexRecordOption {
     pub fn has_positions(self) -> bool {
         match self {
             IndexRecordOption::Basic | IndexRecordOption::WithFreqs => false,
-            IndexRecordOption::WithFreqsAndPositions => true,
+            IndexRecordOption::WithFreqsAndPositions
+            | IndexRecordOption::WithFreqsAndPositionsAndOffsets => true,
+        }
+    }
+
+    /// Returns true if this option include encoding
+    /// the byte offsets of the tokens.
+    pub fn has_offsets(self) -> bool {
+        match self {
+            IndexRecordOption::Basic
+            | IndexRecordOption::WithFreqs
+            | IndexRecordOption::WithFreqsAndPositions => false,
+            IndexRecordOption::WithFreqsAndPositionsAndOffsets => true,
         }
     }
 
@@ -52,7 +73,14 @@ impl IndexRecordOption {
         use IndexRecordOption::*;
 
         match (other, self) {
-            (WithFreqsAndPositions, WithFreqsAndPositions) => WithFreqsAndPositions,
+            (WithFreqsAndPositionsAndOffsets, WithFreqsAndPositionsAndOffsets) => {
+                WithFreqsAndPositionsAndOffsets
+            }
+            (WithFreqsAndPositionsAndOffsets, WithFreqsAndPositions)
+            | (WithFreqsAndPositions, WithFreqsAndPositionsAndOffsets)
+            | (WithFreqsAndPositions, WithFreqsAndPositions) => WithFreqsAndPositions,
+            (WithFreqsAndPositionsAndOffsets, WithFreqs)
+            | (WithFreqs, WithFreqsAndPositionsAndOffsets) => WithFreqs,
             (WithFreqs, WithFreqs) => WithFreqs,
             (WithFreqsAndPositions, WithFreqs) => WithFreqs,
             (WithFreqs, WithFreqsAndPositions) => WithFreqs,
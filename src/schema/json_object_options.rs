@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 
 use super::text_options::{FastFieldTextOptions, TokenizerName};
 use crate::schema::flags::{FastFlag, SchemaFlagList, StoredFlag};
+use crate::schema::term::JSON_PATH_SEGMENT_SEP;
 use crate::schema::{TextFieldIndexing, TextOptions};
 
 /// The `JsonObjectOptions` make it possible to
@@ -42,6 +43,129 @@ pub struct JsonObjectOptions {
     /// `root.child.with.dot:hello`
     #[serde(default)]
     expand_dots_enabled: bool,
+    /// Templates overriding, for the json paths they match, how values are
+    /// indexed and whether they are recorded in the fast fields.
+    ///
+    /// Templates are tried in order, and the first matching template wins.
+    /// Paths that do not match any template follow the settings of the field.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    templates: Vec<JsonPathTemplate>,
+}
+
+/// Defines how the values of the json paths matched by a [`JsonPathTemplate`]
+/// are interpreted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonPathMapping {
+    /// The type is inferred from the json value, as for paths without a template.
+    #[default]
+    Dynamic,
+    /// Strings are parsed as RFC 3339 dates.
+    /// Strings that cannot be parsed are ignored.
+    Date,
+    /// Strings are never inferred as dates, and are indexed as a single,
+    /// untokenized term.
+    Raw,
+    /// Strings are never inferred as dates, and are indexed with the
+    /// tokenizer of the field.
+    Text,
+    /// Values are neither indexed nor recorded in the fast fields.
+    Ignore,
+}
+
+/// A template applying specific options to the json paths matching its pattern.
+///
+/// The pattern is matched against the json path, with segments separated by `.`
+/// (e.g. `user.address.city`), and does not include the name of the field.
+/// `*` matches any sequence of characters, including `.`.
+///
+/// For instance, `*.ts` matches `event.ts` and `event.start.ts`, while
+/// `tags.*` matches all of the paths nested under `tags`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonPathTemplate {
+    path: String,
+    #[serde(default)]
+    mapping: JsonPathMapping,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fast: Option<bool>,
+}
+
+impl JsonPathTemplate {
+    /// Creates a new template applying `mapping` to the json paths matching `path`.
+    pub fn new(path: &str, mapping: JsonPathMapping) -> JsonPathTemplate {
+        JsonPathTemplate {
+            path: path.to_string(),
+            mapping,
+            fast: None,
+        }
+    }
+
+    /// Overrides whether the values of the matching paths are recorded in the
+    /// fast fields.
+    ///
+    /// If not set, the fast field settings of the json field apply.
+    #[must_use]
+    pub fn set_fast(mut self, fast: bool) -> JsonPathTemplate {
+        self.fast = Some(fast);
+        self
+    }
+
+    /// Returns the path pattern of the template.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the mapping applied to the matching paths.
+    pub fn mapping(&self) -> JsonPathMapping {
+        self.mapping
+    }
+
+    /// Returns the fast field override of the template, if any.
+    pub fn fast(&self) -> Option<bool> {
+        self.fast
+    }
+
+    /// Returns true if the json path matches the pattern of the template.
+    ///
+    /// Segments of the path can either be separated by `.` or by the
+    /// internal json path separator.
+    pub fn matches(&self, json_path: &[u8]) -> bool {
+        glob_match(self.path.as_bytes(), json_path)
+    }
+}
+
+fn glob_match(pattern: &[u8], path: &[u8]) -> bool {
+    let (mut pattern_pos, mut path_pos) = (0, 0);
+    // Position of the last `*` in the pattern, and the position of the path it was
+    // matched against, so that we can backtrack on mismatch.
+    let mut backtrack: Option<(usize, usize)> = None;
+    while path_pos < path.len() {
+        match pattern.get(pattern_pos) {
+            Some(b'*') => {
+                backtrack = Some((pattern_pos, path_pos));
+                pattern_pos += 1;
+                continue;
+            }
+            Some(&pattern_byte)
+                if pattern_byte == path[path_pos]
+                    || (pattern_byte == b'.' && path[path_pos] == JSON_PATH_SEGMENT_SEP) =>
+            {
+                pattern_pos += 1;
+                path_pos += 1;
+                continue;
+            }
+            _ => {}
+        }
+        match backtrack {
+            Some((star_pos, star_path_pos)) => {
+                backtrack = Some((star_pos, star_path_pos + 1));
+                pattern_pos = star_pos + 1;
+                path_pos = star_path_pos + 1;
+            }
+            None => return false,
+        }
+    }
+    pattern[pattern_pos..].iter().all(|&byte| byte == b'*')
 }
 
 impl JsonObjectOptions {
@@ -55,9 +179,22 @@ impl JsonObjectOptions {
         self.indexing.is_some()
     }
 
-    /// Returns true if and only if the json object fields are
+    /// Returns true if and only if some of the json object fields are
     /// to be treated as fast fields.
+    ///
+    /// This is the case if the json object is set as fast, or if one of its
+    /// templates enables fast fields.
     pub fn is_fast(&self) -> bool {
+        self.is_fast_by_default()
+            || self
+                .templates
+                .iter()
+                .any(|template| template.fast == Some(true))
+    }
+
+    /// Returns true if the json paths not overridden by a template are to be
+    /// treated as fast fields.
+    pub(crate) fn is_fast_by_default(&self) -> bool {
         matches!(self.fast, FastFieldTextOptions::IsEnabled(true))
             || matches!(
                 &self.fast,
@@ -98,6 +235,27 @@ impl JsonObjectOptions {
         self
     }
 
+    /// Returns the templates of the json object.
+    pub fn templates(&self) -> &[JsonPathTemplate] {
+        &self.templates
+    }
+
+    /// Returns the first template matching the given json path, if any.
+    pub(crate) fn template_for_path(&self, json_path: &[u8]) -> Option<&JsonPathTemplate> {
+        self.templates
+            .iter()
+            .find(|template| template.matches(json_path))
+    }
+
+    /// Appends a template to the json object.
+    ///
+    /// Templates are tried in the order in which they were added.
+    #[must_use]
+    pub fn add_template(mut self, template: JsonPathTemplate) -> Self {
+        self.templates.push(template);
+        self
+    }
+
     /// Returns the text indexing options.
     ///
     /// If set to `Some` then both int and str values will be indexed.
@@ -157,6 +315,7 @@ impl From<StoredFlag> for JsonObjectOptions {
             indexing: None,
             fast: FastFieldTextOptions::default(),
             expand_dots_enabled: false,
+            templates: Vec::new(),
         }
     }
 }
@@ -168,6 +327,7 @@ impl From<FastFlag> for JsonObjectOptions {
             indexing: None,
             fast: FastFieldTextOptions::IsEnabled(true),
             expand_dots_enabled: false,
+            templates: Vec::new(),
         }
     }
 }
@@ -188,6 +348,7 @@ impl<T: Into<JsonObjectOptions>> BitOr<T> for JsonObjectOptions {
             stored: self.stored | other.stored,
             fast: self.fast | other.fast,
             expand_dots_enabled: self.expand_dots_enabled | other.expand_dots_enabled,
+            templates: self.templates.into_iter().chain(other.templates).collect(),
         }
     }
 }
@@ -210,6 +371,7 @@ impl From<TextOptions> for JsonObjectOptions {
             indexing: text_options.get_indexing_options().cloned(),
             fast: text_options.fast,
             expand_dots_enabled: false,
+            templates: Vec::new(),
         }
     }
 }
@@ -252,4 +414,40 @@ mod tests {
             assert!(json_options.is_fast());
         }
     }
+
+    #[test]
+    fn test_json_path_template_matches() {
+        let ts_template = JsonPathTemplate::new("*.ts", JsonPathMapping::Date);
+        assert!(ts_template.matches(b"event.ts"));
+        assert!(ts_template.matches(b"event\x01start\x01ts"));
+        assert!(!ts_template.matches(b"ts"));
+        assert!(!ts_template.matches(b"event.tsx"));
+        let tags_template = JsonPathTemplate::new("tags.*", JsonPathMapping::Raw);
+        assert!(tags_template.matches(b"tags.color"));
+        assert!(tags_template.matches(b"tags\x01a\x01b"));
+        assert!(!tags_template.matches(b"tags"));
+        assert!(!tags_template.matches(b"mytags.color"));
+        assert!(JsonPathTemplate::new("*", JsonPathMapping::Text).matches(b"any.path"));
+    }
+
+    #[test]
+    fn test_json_options_templates() {
+        let json_options: JsonObjectOptions = JsonObjectOptions::from(TEXT)
+            .add_template(JsonPathTemplate::new("*.ts", JsonPathMapping::Date).set_fast(true))
+            .add_template(JsonPathTemplate::new("*", JsonPathMapping::Text));
+        assert!(json_options.is_fast());
+        assert!(!json_options.is_fast_by_default());
+        assert_eq!(
+            json_options.template_for_path(b"a.ts").unwrap().mapping(),
+            JsonPathMapping::Date
+        );
+        assert_eq!(
+            json_options.template_for_path(b"a.b").unwrap().mapping(),
+            JsonPathMapping::Text
+        );
+        let json = serde_json::to_string(&json_options).unwrap();
+        assert!(json.contains(r#"{"path":"*.ts","mapping":"date","fast":true}"#));
+        let deser: JsonObjectOptions = serde_json::from_str(&json).unwrap();
+        assert_eq!(deser, json_options);
+    }
 }
@@ -115,9 +115,13 @@ mod field_type;
 mod field_value;
 
 mod bytes_options;
+mod completion_options;
 mod date_time_options;
+mod dense_vector_options;
 mod field;
 mod flags;
+mod geo_point;
+mod geo_point_options;
 mod index_record_option;
 mod ip_options;
 mod json_object_options;
@@ -129,9 +133,12 @@ mod value;
 use columnar::ColumnType;
 
 pub use self::bytes_options::BytesOptions;
+pub use self::completion_options::CompletionOptions;
 #[allow(deprecated)]
 pub use self::date_time_options::DatePrecision;
 pub use self::date_time_options::{DateOptions, DateTimePrecision, DATE_TIME_PRECISION_INDEXED};
+pub(crate) use self::dense_vector_options::{dense_vector_from_bytes, dense_vector_to_bytes};
+pub use self::dense_vector_options::{DenseVectorOptions, HnswOptions, VectorQuantization};
 pub use self::document::Document;
 pub(crate) use self::facet::FACET_SEP_BYTE;
 pub use self::facet::{Facet, FacetParseError};
@@ -141,16 +148,19 @@ pub use self::field_entry::FieldEntry;
 pub use self::field_type::{FieldType, Type};
 pub use self::field_value::FieldValue;
 pub use self::flags::{COERCE, FAST, INDEXED, STORED};
+pub(crate) use self::geo_point::{dequantize_lat, dequantize_lon, interleave};
+pub use self::geo_point::{GeoPoint, GeoPointParseError, EARTH_RADIUS_METERS};
+pub use self::geo_point_options::GeoPointOptions;
 pub use self::index_record_option::IndexRecordOption;
 pub use self::ip_options::{IntoIpv6Addr, IpAddrOptions};
-pub use self::json_object_options::JsonObjectOptions;
+pub use self::json_object_options::{JsonObjectOptions, JsonPathMapping, JsonPathTemplate};
 pub use self::named_field_document::NamedFieldDocument;
 #[allow(deprecated)]
 pub use self::numeric_options::IntOptions;
 pub use self::numeric_options::NumericOptions;
 pub use self::schema::{DocParsingError, Schema, SchemaBuilder};
 pub use self::term::{Term, ValueBytes, JSON_END_OF_PATH};
-pub use self::text_options::{TextFieldIndexing, TextOptions, STRING, TEXT};
+pub use self::text_options::{TextFieldIndexing, TextOptions, AUTOCOMPLETE, STRING, TEXT};
 pub use self::value::Value;
 
 /// Validator for a potential `field_name`.
@@ -173,6 +183,8 @@ pub(crate) fn value_type_to_column_type(typ: Type) -> Option<ColumnType> {
         Type::Facet => Some(ColumnType::Str),
         Type::Bytes => Some(ColumnType::Bytes),
         Type::IpAddr => Some(ColumnType::IpAddr),
+        Type::DenseVector => Some(ColumnType::Bytes),
+        Type::GeoPoint => Some(ColumnType::U64),
         Type::Json => None,
     }
 }
@@ -20,6 +20,8 @@ pub struct NumericOptions {
     stored: bool,
     #[serde(skip_serializing_if = "is_false")]
     coerce: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    points: bool,
 }
 
 fn is_false(val: &bool) -> bool {
@@ -41,6 +43,8 @@ struct NumericOptionsDeser {
     stored: bool,
     #[serde(default)]
     coerce: bool,
+    #[serde(default)]
+    points: bool,
 }
 
 impl From<NumericOptionsDeser> for NumericOptions {
@@ -51,6 +55,7 @@ impl From<NumericOptionsDeser> for NumericOptions {
             fast: deser.fast,
             stored: deser.stored,
             coerce: deser.coerce,
+            points: deser.points,
         }
     }
 }
@@ -81,6 +86,21 @@ impl NumericOptions {
         self.coerce
     }
 
+    /// Returns true iff the values are indexed in the points index.
+    pub fn has_points(&self) -> bool {
+        self.points
+    }
+
+    /// Index the values in a dedicated points index, used to accelerate range queries.
+    ///
+    /// The points index sorts all of the values of a segment, making it possible
+    /// for a range query to only visit the matching documents.
+    #[must_use]
+    pub fn set_points(mut self) -> Self {
+        self.points = true;
+        self
+    }
+
     /// Try to coerce values if they are not a number. Defaults to false.
     #[must_use]
     pub fn set_coerce(mut self) -> Self {
@@ -147,6 +167,7 @@ impl From<CoerceFlag> for NumericOptions {
             stored: false,
             fast: false,
             coerce: true,
+            points: false,
         }
     }
 }
@@ -159,6 +180,7 @@ impl From<FastFlag> for NumericOptions {
             stored: false,
             fast: true,
             coerce: false,
+            points: false,
         }
     }
 }
@@ -171,6 +193,7 @@ impl From<StoredFlag> for NumericOptions {
             stored: true,
             fast: false,
             coerce: false,
+            points: false,
         }
     }
 }
@@ -183,6 +206,7 @@ impl From<IndexedFlag> for NumericOptions {
             stored: false,
             fast: false,
             coerce: false,
+            points: false,
         }
     }
 }
@@ -198,6 +222,7 @@ impl<T: Into<NumericOptions>> BitOr<T> for NumericOptions {
             stored: self.stored | other.stored,
             fast: self.fast | other.fast,
             coerce: self.coerce | other.coerce,
+            points: self.points | other.points,
         }
     }
 }
@@ -232,6 +257,7 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: false,
+                points: false,
             }
         );
     }
@@ -251,6 +277,7 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: false,
+                points: false,
             }
         );
     }
@@ -271,6 +298,7 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: false,
+                points: false,
             }
         );
     }
@@ -292,6 +320,7 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: false,
+                points: false,
             }
         );
     }
@@ -314,6 +343,7 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: true,
+                points: false,
             }
         );
     }
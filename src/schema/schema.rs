@@ -2,7 +2,7 @@ use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::sync::Arc;
 
-use serde::de::{SeqAccess, Visitor};
+use serde::de::{self, SeqAccess, Visitor};
 use serde::ser::SerializeSeq;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{self, Value as JsonValue};
@@ -187,6 +187,33 @@ impl SchemaBuilder {
         self.add_field(field_entry)
     }
 
+    /// Adds a dense vector field to the schema.
+    ///
+    /// Dense vector fields are not indexed: their vectors are recorded in a fast field, and are
+    /// searched with a [`KnnQuery`](crate::query::KnnQuery).
+    pub fn add_dense_vector_field(
+        &mut self,
+        field_name: &str,
+        field_options: DenseVectorOptions,
+    ) -> Field {
+        let field_entry = FieldEntry::new_dense_vector(field_name.to_string(), field_options);
+        self.add_field(field_entry)
+    }
+
+    /// Adds a geo point field to the schema.
+    ///
+    /// Geo point fields are not indexed in the inverted index: their points are recorded in a
+    /// fast field, and are searched with geo queries, such as a
+    /// [`GeoDistanceQuery`](crate::query::GeoDistanceQuery).
+    pub fn add_geo_point_field(
+        &mut self,
+        field_name: &str,
+        field_options: GeoPointOptions,
+    ) -> Field {
+        let field_entry = FieldEntry::new_geo_point(field_name.to_string(), field_options);
+        self.add_field(field_entry)
+    }
+
     /// Adds a json object field to the schema.
     pub fn add_json_field<T: Into<JsonObjectOptions>>(
         &mut self,
@@ -198,14 +225,94 @@ impl SchemaBuilder {
     }
 
     /// Adds a field entry to the schema in build.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the default value of the field is invalid, or if a field or an alias with the
+    /// same name as the field or as one of its aliases already exists.
     pub fn add_field(&mut self, field_entry: FieldEntry) -> Field {
+        self.try_add_field(field_entry)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Same as [`SchemaBuilder::add_field()`], but returns an error instead of panicking, as
+    /// required when deserializing a schema.
+    fn try_add_field(&mut self, mut field_entry: FieldEntry) -> Result<Field, String> {
+        field_entry.normalize_default_value().map_err(|err| {
+            format!(
+                "Invalid default value for field {}: {err}",
+                field_entry.name()
+            )
+        })?;
         let field = Field::from_field_id(self.fields.len() as u32);
         let field_name = field_entry.name().to_string();
         if let Some(_previous_value) = self.fields_map.insert(field_name, field) {
-            panic!("Field already exists in schema {}", field_entry.name());
+            return Err(format!(
+                "Field already exists in schema {}",
+                field_entry.name()
+            ));
         };
+        for alias in field_entry.aliases() {
+            self.register_alias(alias.clone(), field)?;
+        }
         self.fields.push(field_entry);
-        field
+        Ok(field)
+    }
+
+    /// Registers an alias for a field.
+    ///
+    /// The field can then be referred to with the alias anywhere a field name is
+    /// resolved: `Schema::get_field`, the query parser, aggregations, documents parsed
+    /// from JSON, etc.
+    ///
+    /// This makes it possible to rename a field without reindexing: the new name
+    /// can be added as an alias of the old field.
+    ///
+    /// # Panics
+    ///
+    /// Panics when a field or an alias with the same name already exists,
+    /// or if `alias` is not a valid field name.
+    pub fn add_field_alias(&mut self, alias: &str, field: Field) {
+        assert!(is_valid_field_name(alias), "Invalid field alias {alias}");
+        if let Err(err) = self.register_alias(alias.to_string(), field) {
+            panic!("{err}");
+        }
+        self.fields[field.field_id() as usize].add_alias(alias.to_string());
+    }
+
+    /// Sets the default value of a field.
+    ///
+    /// When a document without any value for the field is indexed, the default value
+    /// is added to the document. It is then indexed, stored, and available as a fast
+    /// field like any other value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value cannot be converted to the type of the field.
+    pub fn set_default_value<T: Into<Value>>(&mut self, field: Field, default_value: T) {
+        let field_entry = &mut self.fields[field.field_id() as usize];
+        if let Err(err) = field_entry.set_default_value(&default_value.into()) {
+            panic!(
+                "Invalid default value for field {}: {err}",
+                field_entry.name()
+            );
+        }
+    }
+
+    /// Attaches a key-value pair to the metadata of a field.
+    ///
+    /// The metadata is persisted with the schema, and can be read back using
+    /// [`FieldEntry::metadata()`]. Setting the same key twice overwrites the previous value.
+    pub fn set_field_metadata(&mut self, field: Field, key: &str, value: &str) {
+        self.fields[field.field_id() as usize].set_metadata(key.to_string(), value.to_string());
+    }
+
+    fn register_alias(&mut self, alias: String, field: Field) -> Result<(), String> {
+        if self.fields_map.contains_key(&alias) {
+            return Err(format!("Field already exists in schema {alias}"));
+        }
+        self.fields_map.insert(alias, field);
+        Ok(())
     }
 
     /// Finalize the creation of a `Schema`
@@ -308,6 +415,21 @@ impl Schema {
         SchemaBuilder::default()
     }
 
+    /// Returns true if this schema only adds new fields to the `previous` schema.
+    ///
+    /// All of the fields of `previous` need to be present, unchanged and in the same order,
+    /// at the beginning of this schema. Such a schema can be used to open an index
+    /// created with the `previous` schema without reindexing it.
+    pub fn is_extension_of(&self, previous: &Schema) -> bool {
+        self.num_fields() >= previous.num_fields()
+            && self
+                .0
+                .fields
+                .iter()
+                .zip(previous.0.fields.iter())
+                .all(|(field_entry, previous_field_entry)| field_entry == previous_field_entry)
+    }
+
     /// Returns the field option associated with a given name.
     pub fn get_field(&self, field_name: &str) -> crate::Result<Field> {
         self.0
@@ -369,7 +491,11 @@ impl Schema {
                 let field_entry = self.get_field_entry(field);
                 let field_type = field_entry.field_type();
                 match json_value {
-                    JsonValue::Array(json_items) => {
+                    // The array of numbers given for a dense vector field is a single value.
+                    JsonValue::Array(json_items)
+                        if !(field_type.is_dense_vector()
+                            && json_items.first().map_or(false, JsonValue::is_number)) =>
+                    {
                         for json_item in json_items {
                             let value = field_type
                                 .value_from_json(json_item)
@@ -467,7 +593,7 @@ impl<'de> Deserialize<'de> for Schema {
                 };
 
                 while let Some(value) = seq.next_element()? {
-                    schema.add_field(value);
+                    schema.try_add_field(value).map_err(de::Error::custom)?;
                 }
 
                 Ok(schema.build())
@@ -510,6 +636,7 @@ mod tests {
     use crate::schema::field_type::ValueParsingError;
     use crate::schema::schema::DocParsingError::InvalidJson;
     use crate::schema::*;
+    use crate::DateTime;
 
     #[test]
     fn test_locate_splitting_dots() {
@@ -526,6 +653,25 @@ mod tests {
         assert!(schema.get_field_entry(field_str).is_indexed());
     }
 
+    #[test]
+    fn test_schema_is_extension_of() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        let schema = schema_builder.build();
+        schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_u64_field("count", FAST);
+        let extended_schema = schema_builder.build();
+        schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", STRING);
+        schema_builder.add_u64_field("count", FAST);
+        let modified_schema = schema_builder.build();
+        assert!(schema.is_extension_of(&schema));
+        assert!(extended_schema.is_extension_of(&schema));
+        assert!(!schema.is_extension_of(&extended_schema));
+        assert!(!modified_schema.is_extension_of(&schema));
+    }
+
     #[test]
     pub fn test_schema_serialization() {
         let mut schema_builder = Schema::builder();
@@ -1044,4 +1190,119 @@ mod tests {
         assert_eq!(schema.find_field("thiswouldbeareallylongfieldname"), None);
         assert_eq!(schema.find_field("baz.bar.foo"), None);
     }
+
+    #[test]
+    fn test_schema_default_values_serialization() {
+        let mut schema_builder = Schema::builder();
+        let rank = schema_builder.add_i64_field("rank", FAST);
+        let date = schema_builder.add_date_field("date", INDEXED);
+        let title = schema_builder.add_text_field("title", TEXT);
+        schema_builder.set_default_value(rank, -3i64);
+        schema_builder.set_default_value(date, DateTime::from_timestamp_secs(1_000));
+        let schema = schema_builder.build();
+        assert_eq!(
+            schema.get_field_entry(rank).default_value(),
+            Some(&Value::I64(-3))
+        );
+        assert!(schema.get_field_entry(title).default_value().is_none());
+        let schema_json = serde_json::to_string(&schema).unwrap();
+        let schema_deser: Schema = serde_json::from_str(&schema_json).unwrap();
+        assert_eq!(
+            schema_deser.get_field_entry(date).default_value(),
+            Some(&Value::Date(DateTime::from_timestamp_secs(1_000)))
+        );
+        assert_eq!(schema_deser, schema);
+    }
+
+    #[test]
+    fn test_schema_field_metadata_serialization() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let body = schema_builder.add_text_field("body", TEXT);
+        schema_builder.set_field_metadata(title, "owner", "search-team");
+        schema_builder.set_field_metadata(title, "since", "v1");
+        schema_builder.set_field_metadata(title, "since", "v2");
+        let schema = schema_builder.build();
+        let schema_json = serde_json::to_string(&schema).unwrap();
+        assert!(schema_json.contains(r#""metadata":{"owner":"search-team","since":"v2"}"#));
+        let schema_deser: Schema = serde_json::from_str(&schema_json).unwrap();
+        let title_metadata = schema_deser.get_field_entry(title).metadata();
+        assert_eq!(title_metadata.len(), 2);
+        assert_eq!(title_metadata["owner"], "search-team");
+        assert_eq!(title_metadata["since"], "v2");
+        assert!(schema_deser.get_field_entry(body).metadata().is_empty());
+        assert_eq!(schema_deser, schema);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid default value for field rank")]
+    fn test_schema_invalid_default_value_should_panic() {
+        let mut schema_builder = Schema::builder();
+        let rank = schema_builder.add_u64_field("rank", FAST);
+        schema_builder.set_default_value(rank, "high");
+    }
+
+    #[test]
+    fn test_schema_deserialization_invalid_default_value() {
+        let mut schema_builder = Schema::builder();
+        let rank = schema_builder.add_u64_field("rank", FAST);
+        schema_builder.set_default_value(rank, 3u64);
+        let schema_json = serde_json::to_string(&schema_builder.build()).unwrap();
+        assert!(serde_json::from_str::<Schema>(&schema_json).is_ok());
+        assert!(schema_json.contains(r#""default_value":3"#));
+        let invalid_schema_json =
+            schema_json.replace(r#""default_value":3"#, r#""default_value":"high""#);
+        let err = serde_json::from_str::<Schema>(&invalid_schema_json).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Invalid default value for field rank"));
+    }
+
+    #[test]
+    fn test_field_alias() {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body_text", TEXT | STORED);
+        let attributes = schema_builder.add_json_field("attributes", TEXT);
+        schema_builder.add_field_alias("content", body);
+        schema_builder.add_field_alias("attrs", attributes);
+        let schema = schema_builder.build();
+        assert_eq!(schema.num_fields(), 2);
+        assert_eq!(schema.get_field("content").unwrap(), body);
+        assert_eq!(schema.get_field_name(body), "body_text");
+        assert_eq!(
+            schema.find_field("attrs.color"),
+            Some((attributes, "color"))
+        );
+        let doc = schema.parse_document(r#"{"content": "hello"}"#).unwrap();
+        assert_eq!(doc.get_first(body).and_then(Value::as_text), Some("hello"));
+
+        let schema_json = serde_json::to_string(&schema).unwrap();
+        let deserialized_schema: Schema = serde_json::from_str(&schema_json).unwrap();
+        assert_eq!(deserialized_schema, schema);
+        assert_eq!(deserialized_schema.get_field("content").unwrap(), body);
+    }
+
+    #[test]
+    #[should_panic(expected = "Field already exists in schema title")]
+    fn test_field_alias_conflicting_with_field_should_panic() {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT);
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_field_alias("title", body);
+    }
+
+    #[test]
+    fn test_schema_deserialization_conflicting_alias() {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT);
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_field_alias("content", body);
+        let schema_json = serde_json::to_string(&schema_builder.build()).unwrap();
+        assert!(serde_json::from_str::<Schema>(&schema_json).is_ok());
+        let invalid_schema_json = schema_json.replace(r#"["content"]"#, r#"["title"]"#);
+        let err = serde_json::from_str::<Schema>(&invalid_schema_json).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Field already exists in schema title"));
+    }
 }
@@ -478,6 +478,7 @@ where B: AsRef<[u8]>
             Type::IpAddr => {
                 write_opt(f, self.as_ip_addr())?;
             }
+            Type::DenseVector | Type::GeoPoint => {}
         }
         Ok(())
     }
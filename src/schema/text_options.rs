@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 
 use super::flags::{CoerceFlag, FastFlag};
 use crate::schema::flags::{SchemaFlagList, StoredFlag};
-use crate::schema::IndexRecordOption;
+use crate::schema::{CompletionOptions, IndexRecordOption};
 
 /// Define how a text field should be handled by tantivy.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
@@ -21,6 +21,12 @@ pub struct TextOptions {
     #[serde(skip_serializing_if = "is_false")]
     /// coerce values into string if they are not of type string
     coerce: bool,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    copy_to: Vec<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    completion: Option<CompletionOptions>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -105,6 +111,16 @@ impl TextOptions {
         self.coerce
     }
 
+    /// Returns the names of the fields the text of this field is copied to.
+    pub fn copy_to(&self) -> &[String] {
+        &self.copy_to
+    }
+
+    /// Returns the completion options, if the field is a completion field.
+    pub fn get_completion_options(&self) -> Option<&CompletionOptions> {
+        self.completion.as_ref()
+    }
+
     /// Set the field as a fast field.
     ///
     /// Fast fields are designed for random access.
@@ -140,6 +156,32 @@ impl TextOptions {
         self
     }
 
+    /// Copies the text of this field into another text field at indexing time.
+    ///
+    /// The text is tokenized with the tokenizer of the target field, and indexed
+    /// as if it was a value of the target field. It is however neither stored nor
+    /// added to the fast fields of the target field.
+    ///
+    /// This is typically used to build a catch-all field, searching into a single
+    /// field instead of a disjunction over many fields.
+    ///
+    /// The target field needs to be an indexed text field.
+    #[must_use]
+    pub fn set_copy_to(mut self, field_name: &str) -> TextOptions {
+        self.copy_to.push(field_name.to_string());
+        self
+    }
+
+    /// Sets the field as a completion field, see [`crate::suggest`].
+    ///
+    /// The values of the field are recorded as is, so the field does not need to be
+    /// indexed.
+    #[must_use]
+    pub fn set_completion(mut self, completion_options: CompletionOptions) -> TextOptions {
+        self.completion = Some(completion_options);
+        self
+    }
+
     /// Sets the field as stored.
     #[must_use]
     pub fn set_stored(mut self) -> TextOptions {
@@ -162,6 +204,8 @@ const DEFAULT_TOKENIZER_NAME: &str = "default";
 
 const NO_TOKENIZER_NAME: &str = "raw";
 
+const AUTOCOMPLETE_TOKENIZER_NAME: &str = "autocomplete";
+
 impl Default for TokenizerName {
     fn default() -> Self {
         TokenizerName::from_static(DEFAULT_TOKENIZER_NAME)
@@ -186,9 +230,16 @@ impl TokenizerName {
 /// - The amount of information that should be stored about the presence of a term in a document.
 /// Essentially, should we store the term frequency and/or the positions (See
 /// [`IndexRecordOption`]).
-/// - The name of the `Tokenizer` that should be used to process the field.
+/// - The name of the `Tokenizer` that should be used to process the field, and optionally the
+///   name of a distinct `Tokenizer` to process the queries on the field.
 /// - Flag indicating, if fieldnorms should be stored (See [fieldnorm](crate::fieldnorm)). Defaults
 ///   to `true`.
+/// - Flag indicating, if term vectors should be stored (See [termvector](crate::termvector)).
+///   Defaults to `false`.
+/// - Flag indicating, if the payloads of the tokens should be stored (See
+///   [payload](crate::payload)). Defaults to `false`.
+/// - Optionally, the document frequency above which the terms of a segment are indexed without
+///   their term frequencies and positions.
 #[derive(Clone, PartialEq, Debug, Eq, Serialize, Deserialize)]
 pub struct TextFieldIndexing {
     #[serde(default)]
@@ -197,6 +248,14 @@ pub struct TextFieldIndexing {
     fieldnorms: bool,
     #[serde(default)]
     tokenizer: TokenizerName,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    search_tokenizer: Option<TokenizerName>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    term_vectors: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    payloads: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_doc_freq: Option<u32>,
 }
 
 pub(crate) fn default_fieldnorms() -> bool {
@@ -207,8 +266,12 @@ impl Default for TextFieldIndexing {
     fn default() -> TextFieldIndexing {
         TextFieldIndexing {
             tokenizer: TokenizerName::default(),
+            search_tokenizer: None,
             record: IndexRecordOption::default(),
             fieldnorms: default_fieldnorms(),
+            term_vectors: false,
+            payloads: false,
+            max_doc_freq: None,
         }
     }
 }
@@ -226,6 +289,27 @@ impl TextFieldIndexing {
         self.tokenizer.name()
     }
 
+    /// Sets the tokenizer to be used for the queries on a given field, if it should differ
+    /// from the tokenizer used at indexing time.
+    ///
+    /// For instance, a field indexed with edge n-grams for search-as-you-type is best
+    /// searched with a tokenizer which does not produce n-grams.
+    #[must_use]
+    pub fn set_search_tokenizer(mut self, tokenizer_name: &str) -> TextFieldIndexing {
+        self.search_tokenizer = Some(TokenizerName::from_name(tokenizer_name));
+        self
+    }
+
+    /// Returns the tokenizer that will be used for the queries on this field.
+    ///
+    /// Unless a search tokenizer has been set, this is the tokenizer used at indexing time.
+    pub fn search_tokenizer(&self) -> &str {
+        self.search_tokenizer
+            .as_ref()
+            .unwrap_or(&self.tokenizer)
+            .name()
+    }
+
     /// Sets fieldnorms
     #[must_use]
     pub fn set_fieldnorms(mut self, fieldnorms: bool) -> TextFieldIndexing {
@@ -238,6 +322,58 @@ impl TextFieldIndexing {
         self.fieldnorms
     }
 
+    /// Sets whether [term vectors](crate::termvector) should be stored.
+    ///
+    /// The term vector of a document lists the terms of the field in the document, with
+    /// their positions and offsets.
+    #[must_use]
+    pub fn set_term_vectors(mut self, term_vectors: bool) -> TextFieldIndexing {
+        self.term_vectors = term_vectors;
+        self
+    }
+
+    /// Returns true if and only if [term vectors](crate::termvector) are stored.
+    pub fn term_vectors(&self) -> bool {
+        self.term_vectors
+    }
+
+    /// Sets whether the [payloads](crate::payload) of the tokens should be stored.
+    #[must_use]
+    pub fn set_payloads(mut self, payloads: bool) -> TextFieldIndexing {
+        self.payloads = payloads;
+        self
+    }
+
+    /// Returns true if and only if the [payloads](crate::payload) of the tokens are stored.
+    pub fn payloads(&self) -> bool {
+        self.payloads
+    }
+
+    /// Sets the maximum number of documents of a segment a term can appear in to be indexed
+    /// with its term frequencies and positions.
+    ///
+    /// Only the doc ids of the terms appearing in more documents of a segment, typically
+    /// stopwords, are indexed in this segment, which reduces the size of the index without
+    /// having to maintain a list of stopwords. These terms still match the term queries,
+    /// with a term frequency of 1, but not the phrase queries.
+    ///
+    /// The document frequencies are computed when a segment is written, and again when
+    /// segments are merged. A term which lost its term frequencies and positions in one of
+    /// the merged segments does not get them back in the merged segment.
+    ///
+    /// This only applies to fields indexed with term frequencies.
+    #[must_use]
+    pub fn set_max_doc_freq(mut self, max_doc_freq: u32) -> TextFieldIndexing {
+        self.max_doc_freq = Some(max_doc_freq);
+        self
+    }
+
+    /// Returns the maximum number of documents of a segment a term can appear in
+    /// to be indexed with its term frequencies and positions, if any.
+    pub fn max_doc_freq(&self) -> Option<u32> {
+        self.max_doc_freq
+    }
+
     /// Sets which information should be indexed with the tokens.
     ///
     /// See [`IndexRecordOption`] for more detail.
@@ -259,24 +395,56 @@ impl TextFieldIndexing {
 pub const STRING: TextOptions = TextOptions {
     indexing: Some(TextFieldIndexing {
         tokenizer: TokenizerName::from_static(NO_TOKENIZER_NAME),
+        search_tokenizer: None,
         fieldnorms: true,
         record: IndexRecordOption::Basic,
+        term_vectors: false,
+        payloads: false,
+        max_doc_freq: None,
     }),
     stored: false,
     fast: FastFieldTextOptions::IsEnabled(false),
     coerce: false,
+    copy_to: Vec::new(),
+    completion: None,
 };
 
 /// The field will be tokenized and indexed.
 pub const TEXT: TextOptions = TextOptions {
     indexing: Some(TextFieldIndexing {
         tokenizer: TokenizerName::from_static(DEFAULT_TOKENIZER_NAME),
+        search_tokenizer: None,
+        fieldnorms: true,
+        record: IndexRecordOption::WithFreqsAndPositions,
+        term_vectors: false,
+        payloads: false,
+        max_doc_freq: None,
+    }),
+    stored: false,
+    coerce: false,
+    fast: FastFieldTextOptions::IsEnabled(false),
+    copy_to: Vec::new(),
+    completion: None,
+};
+
+/// The field will be tokenized and indexed for search-as-you-type: the prefixes of its
+/// words are indexed using the `autocomplete` tokenizer, so that a query on a prefix
+/// requires no expansion. The queries are tokenized using the `default` tokenizer.
+pub const AUTOCOMPLETE: TextOptions = TextOptions {
+    indexing: Some(TextFieldIndexing {
+        tokenizer: TokenizerName::from_static(AUTOCOMPLETE_TOKENIZER_NAME),
+        search_tokenizer: Some(TokenizerName::from_static(DEFAULT_TOKENIZER_NAME)),
         fieldnorms: true,
         record: IndexRecordOption::WithFreqsAndPositions,
+        term_vectors: false,
+        payloads: false,
+        max_doc_freq: None,
     }),
     stored: false,
     coerce: false,
     fast: FastFieldTextOptions::IsEnabled(false),
+    copy_to: Vec::new(),
+    completion: None,
 };
 
 impl<T: Into<TextOptions>> BitOr<T> for TextOptions {
@@ -289,6 +457,8 @@ impl<T: Into<TextOptions>> BitOr<T> for TextOptions {
             stored: self.stored | other.stored,
             fast: self.fast | other.fast,
             coerce: self.coerce | other.coerce,
+            copy_to: self.copy_to.into_iter().chain(other.copy_to).collect(),
+            completion: self.completion.or(other.completion),
         }
     }
 }
@@ -306,6 +476,8 @@ impl From<StoredFlag> for TextOptions {
             stored: true,
             fast: FastFieldTextOptions::default(),
             coerce: false,
+            copy_to: Vec::new(),
+            completion: None,
         }
     }
 }
@@ -317,6 +489,8 @@ impl From<CoerceFlag> for TextOptions {
             stored: false,
             fast: FastFieldTextOptions::default(),
             coerce: true,
+            copy_to: Vec::new(),
+            completion: None,
         }
     }
 }
@@ -328,6 +502,8 @@ impl From<FastFlag> for TextOptions {
             stored: false,
             fast: FastFieldTextOptions::IsEnabled(true),
             coerce: false,
+            copy_to: Vec::new(),
+            completion: None,
         }
     }
 }
@@ -363,6 +539,28 @@ mod tests {
                 if text_options.get_indexing_options().unwrap().tokenizer() == "default"));
     }
 
+    #[test]
+    fn test_search_tokenizer() {
+        let indexing = TextFieldIndexing::default().set_tokenizer("autocomplete");
+        assert_eq!(indexing.search_tokenizer(), "autocomplete");
+        assert_eq!(
+            serde_json::to_string(&indexing).unwrap(),
+            r#"{"record":"basic","fieldnorms":true,"tokenizer":"autocomplete"}"#
+        );
+        let indexing = indexing.set_search_tokenizer("default");
+        assert_eq!(indexing.tokenizer(), "autocomplete");
+        assert_eq!(indexing.search_tokenizer(), "default");
+        let json = serde_json::to_string(&indexing).unwrap();
+        assert_eq!(
+            json,
+            r#"{"record":"basic","fieldnorms":true,"tokenizer":"autocomplete","search_tokenizer":"default"}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<TextFieldIndexing>(&json).unwrap(),
+            indexing
+        );
+    }
+
     #[test]
     fn test_cmp_index_record_option() {
         assert!(IndexRecordOption::WithFreqsAndPositions > IndexRecordOption::WithFreqs);
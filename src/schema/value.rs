@@ -7,7 +7,7 @@ use serde::de::Visitor;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Map;
 
-use crate::schema::Facet;
+use crate::schema::{Facet, GeoPoint};
 use crate::tokenizer::PreTokenizedString;
 use crate::DateTime;
 
@@ -37,6 +37,10 @@ pub enum Value {
     JsonObject(serde_json::Map<String, serde_json::Value>),
     /// IpV6 Address. Internally there is no IpV4, it needs to be converted to `Ipv6Addr`.
     IpAddr(Ipv6Addr),
+    /// Dense vector of `f32`.
+    DenseVector(Vec<f32>),
+    /// Geo point, with its latitude and longitude in degrees.
+    GeoPoint(GeoPoint),
 }
 
 impl Eq for Value {}
@@ -63,6 +67,8 @@ impl Serialize for Value {
                     obj.serialize(serializer)
                 }
             }
+            Value::DenseVector(ref vector) => vector.serialize(serializer),
+            Value::GeoPoint(ref geo_point) => geo_point.serialize(serializer),
         }
     }
 }
@@ -224,6 +230,28 @@ impl Value {
             None
         }
     }
+
+    /// Returns the dense vector, provided the value is of the `DenseVector` type.
+    ///
+    /// Returns `None` if the value is not of type `DenseVector`.
+    pub fn as_dense_vector(&self) -> Option<&[f32]> {
+        if let Value::DenseVector(vector) = self {
+            Some(vector)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the geo point, provided the value is of the `GeoPoint` type.
+    ///
+    /// Returns `None` if the value is not of type `GeoPoint`.
+    pub fn as_geo_point(&self) -> Option<GeoPoint> {
+        if let Value::GeoPoint(geo_point) = self {
+            Some(*geo_point)
+        } else {
+            None
+        }
+    }
 }
 
 impl From<String> for Value {
@@ -292,6 +320,18 @@ impl From<Vec<u8>> for Value {
     }
 }
 
+impl From<Vec<f32>> for Value {
+    fn from(vector: Vec<f32>) -> Value {
+        Value::DenseVector(vector)
+    }
+}
+
+impl From<GeoPoint> for Value {
+    fn from(geo_point: GeoPoint) -> Value {
+        Value::GeoPoint(geo_point)
+    }
+}
+
 impl From<PreTokenizedString> for Value {
     fn from(pretokenized_string: PreTokenizedString) -> Value {
         Value::PreTokStr(pretokenized_string)
@@ -315,15 +355,17 @@ impl From<serde_json::Value> for Value {
     }
 }
 
+pub(crate) use self::binary_serialize::skip_value;
+
 mod binary_serialize {
     use std::io::{self, Read, Write};
     use std::net::Ipv6Addr;
 
     use columnar::MonotonicallyMappableToU128;
-    use common::{f64_to_u64, u64_to_f64, BinarySerializable};
+    use common::{f64_to_u64, u64_to_f64, BinarySerializable, VInt};
 
     use super::Value;
-    use crate::schema::Facet;
+    use crate::schema::{Facet, GeoPoint};
     use crate::tokenizer::PreTokenizedString;
     use crate::DateTime;
 
@@ -338,11 +380,64 @@ mod binary_serialize {
     const JSON_OBJ_CODE: u8 = 8;
     const BOOL_CODE: u8 = 9;
     const IP_CODE: u8 = 10;
+    const DENSE_VECTOR_CODE: u8 = 11;
+    const GEO_POINT_CODE: u8 = 12;
 
     // extended types
 
     const TOK_STR_CODE: u8 = 0;
 
+    /// Advances the reader past a serialized value, without decoding it.
+    pub(crate) fn skip_value(reader: &mut &[u8]) -> io::Result<()> {
+        let type_code = u8::deserialize(reader)?;
+        match type_code {
+            TEXT_CODE | HIERARCHICAL_FACET_CODE | BYTES_CODE => skip_len_prefixed(reader),
+            U64_CODE | I64_CODE | F64_CODE | DATE_CODE => skip_bytes(reader, 8),
+            BOOL_CODE => skip_bytes(reader, 1),
+            IP_CODE | GEO_POINT_CODE => skip_bytes(reader, 16),
+            DENSE_VECTOR_CODE => {
+                let dimension = VInt::deserialize(reader)?.val() as usize;
+                skip_bytes(reader, dimension * 4)
+            }
+            EXT_CODE => {
+                let ext_type_code = u8::deserialize(reader)?;
+                match ext_type_code {
+                    TOK_STR_CODE => skip_len_prefixed(reader),
+                    _ => Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("No extended field type is associated with code {ext_type_code:?}"),
+                    )),
+                }
+            }
+            JSON_OBJ_CODE => {
+                // The json object is parsed, but not materialized.
+                let mut de = serde_json::Deserializer::from_reader(reader);
+                <serde::de::IgnoredAny as serde::Deserialize>::deserialize(&mut de)?;
+                Ok(())
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("No field type is associated with code {type_code:?}"),
+            )),
+        }
+    }
+
+    fn skip_len_prefixed(reader: &mut &[u8]) -> io::Result<()> {
+        let len = VInt::deserialize(reader)?.val() as usize;
+        skip_bytes(reader, len)
+    }
+
+    fn skip_bytes(reader: &mut &[u8], num_bytes: usize) -> io::Result<()> {
+        if reader.len() < num_bytes {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Reached end of buffer while skipping a value",
+            ));
+        }
+        *reader = &reader[num_bytes..];
+        Ok(())
+    }
+
     impl BinarySerializable for Value {
         fn serialize<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
             match *self {
@@ -400,6 +495,19 @@ mod binary_serialize {
                     IP_CODE.serialize(writer)?;
                     ip.to_u128().serialize(writer)
                 }
+                Value::DenseVector(ref vector) => {
+                    DENSE_VECTOR_CODE.serialize(writer)?;
+                    VInt(vector.len() as u64).serialize(writer)?;
+                    for value in vector {
+                        value.serialize(writer)?;
+                    }
+                    Ok(())
+                }
+                Value::GeoPoint(ref geo_point) => {
+                    GEO_POINT_CODE.serialize(writer)?;
+                    geo_point.lat.serialize(writer)?;
+                    geo_point.lon.serialize(writer)
+                }
             }
         }
 
@@ -473,6 +581,18 @@ mod binary_serialize {
                     let value = u128::deserialize(reader)?;
                     Ok(Value::IpAddr(Ipv6Addr::from_u128(value)))
                 }
+                DENSE_VECTOR_CODE => {
+                    let dimension = VInt::deserialize(reader)?.val() as usize;
+                    let vector = (0..dimension)
+                        .map(|_| f32::deserialize(reader))
+                        .collect::<io::Result<_>>()?;
+                    Ok(Value::DenseVector(vector))
+                }
+                GEO_POINT_CODE => {
+                    let lat = f64::deserialize(reader)?;
+                    let lon = f64::deserialize(reader)?;
+                    Ok(Value::GeoPoint(GeoPoint { lat, lon }))
+                }
 
                 _ => Err(io::Error::new(
                     io::ErrorKind::InvalidData,
@@ -0,0 +1,254 @@
+use std::ops::Range;
+
+use super::DEFAULT_MAX_NUM_CHARS;
+
+/// A `Fragmenter` splits a text into the fragments snippets are made of.
+///
+/// It can be set on a [`SnippetGenerator`](crate::SnippetGenerator) with
+/// [`SnippetGenerator::set_fragmenter`](crate::SnippetGenerator::set_fragmenter). Without a
+/// fragmenter, the fragments are made of consecutive tokens, up to the maximum number of chars
+/// of the `SnippetGenerator`.
+pub trait Fragmenter: Send + Sync + 'static {
+    /// Returns the byte ranges of the fragments of the text.
+    ///
+    /// The ranges must be sorted, must not overlap, and must start and end at char
+    /// boundaries.
+    fn fragments(&self, text: &str) -> Vec<Range<usize>>;
+}
+
+/// `SentenceFragmenter` splits a text into fragments of whole sentences.
+///
+/// A sentence ends with `.`, `!`, `?` or `…` followed by a whitespace, with `。`, `！` or `？`,
+/// or with a blank line. Consecutive sentences are grouped into a fragment as long as the
+/// fragment is at most `max_num_chars` bytes long.
+///
+/// Sentences longer than `max_num_chars` are split on whitespaces, and words longer than
+/// `max_num_chars` between two chars, but never before a combining mark or after a zero width
+/// joiner, so that the graphemes are not cut in half.
+#[derive(Clone, Copy, Debug)]
+pub struct SentenceFragmenter {
+    max_num_chars: usize,
+}
+
+impl SentenceFragmenter {
+    /// Creates a `SentenceFragmenter` returning fragments of at most `max_num_chars` bytes.
+    pub fn new(max_num_chars: usize) -> SentenceFragmenter {
+        SentenceFragmenter { max_num_chars }
+    }
+}
+
+impl Default for SentenceFragmenter {
+    fn default() -> Self {
+        SentenceFragmenter::new(DEFAULT_MAX_NUM_CHARS)
+    }
+}
+
+impl Fragmenter for SentenceFragmenter {
+    fn fragments(&self, text: &str) -> Vec<Range<usize>> {
+        let mut fragments: Vec<Range<usize>> = Vec::new();
+        let pieces = sentences(text)
+            .into_iter()
+            .flat_map(|sentence| split_sentence(text, sentence, self.max_num_chars));
+        for piece in pieces {
+            match fragments.last_mut() {
+                Some(fragment) if piece.end - fragment.start <= self.max_num_chars => {
+                    fragment.end = piece.end;
+                }
+                _ => fragments.push(piece),
+            }
+        }
+        fragments
+    }
+}
+
+/// Returns true if the char ends a sentence when followed by a whitespace.
+fn is_sentence_terminator(c: char) -> bool {
+    matches!(c, '.' | '!' | '?' | '…')
+}
+
+/// Returns true if the char ends a sentence, whatever follows it.
+fn is_cjk_sentence_terminator(c: char) -> bool {
+    matches!(c, '。' | '！' | '？')
+}
+
+/// Returns true if the char closes a sentence after its terminator, e.g. in `"Stop!" he said`.
+fn is_closing_punctuation(c: char) -> bool {
+    matches!(c, '"' | '\'' | ')' | ']' | '»' | '”' | '’' | '」' | '』')
+}
+
+/// Returns true if the text must not be split between the two chars, because they belong to
+/// the same grapheme.
+fn is_grapheme_continuation(previous: char, next: char) -> bool {
+    previous == '\u{200D}'
+        || matches!(next,
+            '\u{0300}'..='\u{036F}'
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{200D}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{FE20}'..='\u{FE2F}'
+            | '\u{1F3FB}'..='\u{1F3FF}'
+            | '\u{E0100}'..='\u{E01EF}')
+}
+
+/// Returns the byte ranges of the sentences of the text, without their surrounding
+/// whitespaces.
+fn sentences(text: &str) -> Vec<Range<usize>> {
+    let mut sentences = Vec::new();
+    let mut sentence_start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        let mut sentence_end = None;
+        if is_sentence_terminator(c) || is_cjk_sentence_terminator(c) {
+            let mut end = offset + c.len_utf8();
+            while let Some(&(next_offset, next_c)) = chars.peek() {
+                if !is_sentence_terminator(next_c)
+                    && !is_cjk_sentence_terminator(next_c)
+                    && !is_closing_punctuation(next_c)
+                {
+                    break;
+                }
+                end = next_offset + next_c.len_utf8();
+                chars.next();
+            }
+            let followed_by_whitespace = chars
+                .peek()
+                .map_or(true, |&(_, next_c)| next_c.is_whitespace());
+            if followed_by_whitespace || is_cjk_sentence_terminator(c) {
+                sentence_end = Some(end);
+            }
+        } else if c == '\n'
+            && text[offset + 1..]
+                .trim_start_matches([' ', '\t', '\r'])
+                .starts_with('\n')
+        {
+            sentence_end = Some(offset);
+        }
+        if let Some(end) = sentence_end {
+            push_trimmed(text, sentence_start..end, &mut sentences);
+            sentence_start = end;
+        }
+    }
+    push_trimmed(text, sentence_start..text.len(), &mut sentences);
+    sentences
+}
+
+/// Pushes the range without its leading and trailing whitespaces, if it is not blank.
+fn push_trimmed(text: &str, range: Range<usize>, ranges: &mut Vec<Range<usize>>) {
+    let range_text = &text[range.clone()];
+    let trimmed = range_text.trim_start();
+    let start = range.start + range_text.len() - trimmed.len();
+    let end = start + trimmed.trim_end().len();
+    if start < end {
+        ranges.push(start..end);
+    }
+}
+
+/// Splits a sentence into pieces of at most `max_num_chars` bytes, on whitespaces if possible.
+fn split_sentence(text: &str, sentence: Range<usize>, max_num_chars: usize) -> Vec<Range<usize>> {
+    let mut pieces = Vec::new();
+    let mut start = sentence.start;
+    while sentence.end - start > max_num_chars {
+        let window_end = floor_char_boundary(text, start + max_num_chars);
+        let split = text[start..window_end]
+            .rfind(char::is_whitespace)
+            .filter(|&whitespace_offset| whitespace_offset > 0)
+            .map(|whitespace_offset| start + whitespace_offset)
+            .unwrap_or_else(|| split_word(text, start, window_end).min(sentence.end));
+        push_trimmed(text, start..split, &mut pieces);
+        start = split;
+        start += text[start..sentence.end].len() - text[start..sentence.end].trim_start().len();
+    }
+    push_trimmed(text, start..sentence.end, &mut pieces);
+    pieces
+}
+
+/// Returns the offset at which a word starting at `start` should be split to end before
+/// `window_end`, without cutting a grapheme in half.
+///
+/// If the first grapheme of the word is longer than the window, the word is split right after
+/// it.
+fn split_word(text: &str, start: usize, window_end: usize) -> usize {
+    let mut split = window_end;
+    while split > start {
+        let previous = text[..split].chars().next_back().unwrap();
+        let Some(next) = text[split..].chars().next() else {
+            return split;
+        };
+        if !is_grapheme_continuation(previous, next) {
+            return split;
+        }
+        split -= previous.len_utf8();
+    }
+    let mut chars = text[start..].char_indices();
+    let mut previous = chars.next().map(|(_, c)| c).unwrap_or_default();
+    for (offset, c) in chars {
+        if !is_grapheme_continuation(previous, c) {
+            return start + offset;
+        }
+        previous = c;
+    }
+    text.len()
+}
+
+fn floor_char_boundary(text: &str, mut offset: usize) -> usize {
+    offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Fragmenter, SentenceFragmenter};
+
+    fn fragments(text: &str, max_num_chars: usize) -> Vec<&str> {
+        SentenceFragmenter::new(max_num_chars)
+            .fragments(text)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect()
+    }
+
+    #[test]
+    fn test_sentence_fragmenter() {
+        let text = "Rust is fast. Is it safe? \"Yes!\" he said.\n\nA new paragraph";
+        assert_eq!(
+            fragments(text, 100),
+            vec!["Rust is fast. Is it safe? \"Yes!\" he said.\n\nA new paragraph"]
+        );
+        assert_eq!(
+            fragments(text, 30),
+            vec![
+                "Rust is fast. Is it safe?",
+                "\"Yes!\" he said.",
+                "A new paragraph"
+            ]
+        );
+        assert_eq!(
+            fragments("Version 1.5 is out", 100),
+            vec!["Version 1.5 is out"]
+        );
+        assert_eq!(fragments("错误。这是中文", 100), vec!["错误。这是中文"]);
+        assert_eq!(fragments("错误。这是中文", 12), vec!["错误。", "这是中文"]);
+        assert!(fragments(" \n ", 10).is_empty());
+    }
+
+    #[test]
+    fn test_sentence_fragmenter_long_sentence() {
+        assert_eq!(
+            fragments("a very long sentence without any end", 12),
+            vec!["a very long", "sentence", "without any", "end"]
+        );
+        // Words are only split between graphemes.
+        assert_eq!(fragments("abcdefgh", 3), vec!["abc", "def", "gh"]);
+        assert_eq!(fragments("ééé", 3), vec!["é", "é", "é"]);
+        assert_eq!(
+            fragments("e\u{301}e\u{301}e\u{301}", 4),
+            vec!["e\u{301}", "e\u{301}", "e\u{301}"]
+        );
+        assert_eq!(fragments("e\u{301}\u{301}", 2), vec!["e\u{301}\u{301}"]);
+    }
+}
@@ -0,0 +1,339 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{
+    fragment_snippet, phrase_field, score_tokens, split_into_fragments, term_score, tokenize,
+    FragmentCandidate, HighlightedPhrase, Snippet, DEFAULT_MAX_NUM_CHARS, DEFAULT_SNIPPET_POSTFIX,
+    DEFAULT_SNIPPET_PREFIX,
+};
+use crate::query::{FuzzyTermQuery, Query, QueryLeaf};
+use crate::schema::{Field, Value};
+use crate::tokenizer::TextAnalyzer;
+use crate::{Document, Score, Searcher, Term};
+
+const DEFAULT_MAX_NUM_FRAGMENTS: usize = 3;
+
+/// The terms and phrases of a query to highlight in a field.
+struct HighlightedField {
+    field: Field,
+    tokenizer: TextAnalyzer,
+    terms: BTreeMap<String, Score>,
+    phrases: Vec<HighlightedPhrase>,
+}
+
+/// The highlighted fragments of a field of a document.
+#[derive(Debug)]
+pub struct FieldHighlight {
+    field: Field,
+    fragments: Vec<Snippet>,
+}
+
+impl FieldHighlight {
+    /// Returns the field.
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    /// Returns the highlighted fragments of the field, in the order in which they appear in the
+    /// document.
+    ///
+    /// The fragments are empty if none of the terms of the query appear in the field.
+    pub fn fragments(&self) -> &[Snippet] {
+        &self.fragments
+    }
+}
+
+/// `Highlighter` produces the highlighted fragments of several fields of a document, for a
+/// given query.
+///
+/// Unlike [`SnippetGenerator`](crate::SnippetGenerator), it highlights the terms matched by the
+/// fuzzy term queries, and the terms of the phrase queries only where the phrase occurs.
+///
+/// # Example
+///
+/// ```rust
+/// # use tantivy::query::QueryParser;
+/// # use tantivy::schema::{Schema, TEXT};
+/// # use tantivy::{doc, Index};
+/// use tantivy::Highlighter;
+///
+/// # fn main() -> tantivy::Result<()> {
+/// #    let mut schema_builder = Schema::builder();
+/// #    let title = schema_builder.add_text_field("title", TEXT);
+/// #    let body = schema_builder.add_text_field("body", TEXT);
+/// #    let index = Index::create_in_ram(schema_builder.build());
+/// #    let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// #    let doc = doc!(
+/// #        title => "The old man and the sea",
+/// #        body => "He was an old man who fished alone in a skiff in the Gulf Stream"
+/// #    );
+/// #    index_writer.add_document(doc.clone())?;
+/// #    index_writer.commit()?;
+/// #    let searcher = index.reader()?.searcher();
+/// let query_parser = QueryParser::for_index(&index, vec![title, body]);
+/// let query = query_parser.parse_query("\"old man\" sea")?;
+/// let mut highlighter = Highlighter::create(&searcher, &*query, &[title, body])?;
+/// highlighter.set_tags("<em>", "</em>");
+/// let highlights = highlighter.highlight(&doc);
+/// assert_eq!(
+///     highlights[0].fragments()[0].to_html(),
+///     "The <em>old</em> <em>man</em> and the <em>sea</em>"
+/// );
+/// assert_eq!(
+///     highlights[1].fragments()[0].to_html(),
+///     "He was an <em>old</em> <em>man</em> who fished alone in a skiff in the Gulf Stream"
+/// );
+/// #    Ok(())
+/// # }
+/// ```
+pub struct Highlighter {
+    fields: Vec<HighlightedField>,
+    max_num_chars: usize,
+    max_num_fragments: usize,
+    pre_tag: String,
+    post_tag: String,
+}
+
+impl Highlighter {
+    /// Creates a highlighter for the given fields, extracting the terms and phrases to
+    /// highlight from the query.
+    ///
+    /// The terms matched by fuzzy term queries are looked up in the term dictionaries of the
+    /// segments of the searcher. The terms of the excluded subqueries are not highlighted.
+    pub fn create(
+        searcher: &Searcher,
+        query: &dyn Query,
+        fields: &[Field],
+    ) -> crate::Result<Highlighter> {
+        let mut terms: BTreeSet<&Term> = BTreeSet::new();
+        let mut phrases: Vec<(&[(usize, Term)], u32)> = Vec::new();
+        let mut fuzzy_queries: Vec<&FuzzyTermQuery> = Vec::new();
+        query.query_leaves(&mut |leaf| match leaf {
+            QueryLeaf::Term(term) => {
+                terms.insert(term);
+            }
+            QueryLeaf::Phrase {
+                terms: phrase_terms,
+                slop,
+            } => phrases.push((phrase_terms, slop)),
+            QueryLeaf::Fuzzy(fuzzy_query) => fuzzy_queries.push(fuzzy_query),
+        });
+        let mut fuzzy_terms: BTreeSet<Term> = BTreeSet::new();
+        for fuzzy_query in fuzzy_queries {
+            let field = fuzzy_query.term().field();
+            if !fields.contains(&field) {
+                continue;
+            }
+            let automaton = fuzzy_query.automaton()?;
+            for segment_reader in searcher.segment_readers() {
+                let inverted_index = segment_reader.inverted_index(field)?;
+                let mut term_stream = inverted_index.terms().search(&automaton).into_stream()?;
+                while term_stream.advance() {
+                    if let Ok(text) = std::str::from_utf8(term_stream.key()) {
+                        fuzzy_terms.insert(Term::from_field_text(field, text));
+                    }
+                }
+            }
+        }
+        terms.extend(fuzzy_terms.iter());
+
+        let mut highlighted_fields = Vec::with_capacity(fields.len());
+        for &field in fields {
+            let mut field_terms: BTreeMap<String, Score> = BTreeMap::new();
+            for term in terms.iter().filter(|term| term.field() == field) {
+                if let Some((text, score)) = term_score(searcher, term)? {
+                    field_terms.insert(text, score);
+                }
+            }
+            let mut field_phrases = Vec::new();
+            for &(phrase_terms, slop) in &phrases {
+                if phrase_field(phrase_terms) != Some(field) {
+                    continue;
+                }
+                if let Some(phrase) = HighlightedPhrase::create(searcher, phrase_terms, slop)? {
+                    field_phrases.push(phrase);
+                }
+            }
+            highlighted_fields.push(HighlightedField {
+                field,
+                tokenizer: searcher.index().tokenizer_for_field(field)?,
+                terms: field_terms,
+                phrases: field_phrases,
+            });
+        }
+        Ok(Highlighter {
+            fields: highlighted_fields,
+            max_num_chars: DEFAULT_MAX_NUM_CHARS,
+            max_num_fragments: DEFAULT_MAX_NUM_FRAGMENTS,
+            pre_tag: DEFAULT_SNIPPET_PREFIX.to_string(),
+            post_tag: DEFAULT_SNIPPET_POSTFIX.to_string(),
+        })
+    }
+
+    /// Sets the maximum number of chars of a fragment.
+    pub fn set_max_num_chars(&mut self, max_num_chars: usize) {
+        self.max_num_chars = max_num_chars;
+    }
+
+    /// Sets the maximum number of fragments per field.
+    ///
+    /// The fragments with the highest scores are kept.
+    pub fn set_max_num_fragments(&mut self, max_num_fragments: usize) {
+        self.max_num_fragments = max_num_fragments;
+    }
+
+    /// Sets the tags inserted before and after the highlighted terms by
+    /// [`Snippet::to_html`].
+    pub fn set_tags(&mut self, pre_tag: &str, post_tag: &str) {
+        self.pre_tag = pre_tag.to_string();
+        self.post_tag = post_tag.to_string();
+    }
+
+    /// Returns the highlighted fragments of each field of the highlighter, in the order in
+    /// which the fields were given.
+    ///
+    /// The values of a field are highlighted separately, and a fragment never spans several
+    /// values.
+    pub fn highlight(&self, doc: &Document) -> Vec<FieldHighlight> {
+        self.fields
+            .iter()
+            .map(|highlighted_field| self.highlight_field(highlighted_field, doc))
+            .collect()
+    }
+
+    fn highlight_field(
+        &self,
+        highlighted_field: &HighlightedField,
+        doc: &Document,
+    ) -> FieldHighlight {
+        let texts: Vec<&str> = doc
+            .get_all(highlighted_field.field)
+            .flat_map(Value::as_text)
+            .collect();
+        let mut fragments: Vec<(usize, FragmentCandidate)> = Vec::new();
+        for (value_ord, text) in texts.iter().enumerate() {
+            let scored_tokens = score_tokens(
+                tokenize(&highlighted_field.tokenizer, text),
+                |token| highlighted_field.terms.get(&token.text).copied(),
+                &highlighted_field.phrases,
+            );
+            let value_fragments = split_into_fragments(
+                scored_tokens.iter().map(|(token, score)| (token, *score)),
+                self.max_num_chars,
+            );
+            fragments.extend(
+                value_fragments
+                    .into_iter()
+                    .map(|fragment| (value_ord, fragment)),
+            );
+        }
+        // Sorting the fragments by decreasing score, and then by order of appearance.
+        fragments.sort_by(|(left_ord, left), (right_ord, right)| {
+            right
+                .score
+                .partial_cmp(&left.score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| (left_ord, left.start_offset).cmp(&(right_ord, right.start_offset)))
+        });
+        fragments.truncate(self.max_num_fragments);
+        fragments.sort_by_key(|(value_ord, fragment)| (*value_ord, fragment.start_offset));
+        let fragments = fragments
+            .iter()
+            .map(|(value_ord, fragment)| {
+                let mut snippet = fragment_snippet(fragment, texts[*value_ord]);
+                snippet.set_snippet_prefix_postfix(&self.pre_tag, &self.post_tag);
+                snippet
+            })
+            .collect();
+        FieldHighlight {
+            field: highlighted_field.field,
+            fragments,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Highlighter;
+    use crate::query::{
+        BooleanQuery, FuzzyTermQuery, Occur, PhraseQuery, Query, QueryParser, TermQuery,
+    };
+    use crate::schema::{IndexRecordOption, Schema, TEXT};
+    use crate::{Document, Index, Term};
+
+    #[test]
+    fn test_highlighter() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let doc = doc!(
+            title => "Rust in action",
+            body => "Rusty tools. The language rust is fast; fast rust is safe.",
+            body => "Another value about rust",
+        );
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc.clone())?;
+            index_writer.add_document(doc!(body => "slow language"))?;
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let phrase: Box<dyn Query> = Box::new(PhraseQuery::new(vec![
+            Term::from_field_text(body, "rust"),
+            Term::from_field_text(body, "is"),
+            Term::from_field_text(body, "safe"),
+        ]));
+        let fuzzy: Box<dyn Query> = Box::new(FuzzyTermQuery::new(
+            Term::from_field_text(title, "actoin"),
+            1,
+            true,
+        ));
+        let excluded: Box<dyn Query> = Box::new(TermQuery::new(
+            Term::from_field_text(body, "language"),
+            IndexRecordOption::Basic,
+        ));
+        let query = BooleanQuery::new(vec![
+            (Occur::Should, phrase),
+            (Occur::Should, fuzzy),
+            (Occur::MustNot, excluded),
+        ]);
+        let mut highlighter = Highlighter::create(&searcher, &query, &[body, title])?;
+        highlighter.set_tags("[", "]");
+        let highlights = highlighter.highlight(&doc);
+        assert_eq!(highlights.len(), 2);
+        assert_eq!(highlights[0].field(), body);
+        let body_fragments: Vec<String> = highlights[0]
+            .fragments()
+            .iter()
+            .map(|fragment| fragment.to_html())
+            .collect();
+        assert_eq!(
+            body_fragments,
+            vec!["Rusty tools. The language rust is fast; fast [rust] [is] [safe]"]
+        );
+        assert_eq!(highlights[1].field(), title);
+        assert_eq!(highlights[1].fragments().len(), 1);
+        assert_eq!(highlights[1].fragments()[0].to_html(), "Rust in [action]");
+
+        let query_parser = QueryParser::for_index(&index, vec![title, body]);
+        let query = query_parser.parse_query("rust")?;
+        let mut highlighter = Highlighter::create(&searcher, &*query, &[body, title])?;
+        highlighter.set_max_num_chars(20);
+        highlighter.set_max_num_fragments(2);
+        let highlights = highlighter.highlight(&doc);
+        let body_fragments: Vec<String> = highlights[0]
+            .fragments()
+            .iter()
+            .map(|fragment| fragment.to_html())
+            .collect();
+        assert_eq!(
+            body_fragments,
+            vec!["language <b>rust</b> is", "fast; fast <b>rust</b> is"]
+        );
+        assert!(highlighter.highlight(&Document::default())[0]
+            .fragments()
+            .is_empty());
+        Ok(())
+    }
+}
@@ -1,13 +1,19 @@
+mod fragmenter;
+mod highlighter;
+
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, BTreeSet};
 use std::ops::Range;
 
 use htmlescape::encode_minimal;
 
-use crate::query::Query;
-use crate::schema::{Field, Value};
+pub use self::fragmenter::{Fragmenter, SentenceFragmenter};
+pub use self::highlighter::{FieldHighlight, Highlighter};
+use crate::postings::Postings;
+use crate::query::{Query, QueryLeaf};
+use crate::schema::{Field, IndexRecordOption, Value};
 use crate::tokenizer::{TextAnalyzer, Token};
-use crate::{Document, Score, Searcher, Term};
+use crate::{DocAddress, DocId, DocSet, Document, Score, Searcher, SegmentReader, Term};
 
 const DEFAULT_MAX_NUM_CHARS: usize = 150;
 
@@ -39,19 +45,232 @@ impl FragmentCandidate {
 
     /// Updates `score` and `highlighted` fields of the objects.
     ///
-    /// taking the token and terms, the token is added to the fragment.
-    /// if the token is one of the terms, the score
+    /// taking the token and its score, the token is added to the fragment.
+    /// if the token has a score, i.e. it needs to be highlighted, the score
     /// and highlighted fields are updated in the fragment.
-    fn try_add_token(&mut self, token: &Token, terms: &BTreeMap<String, Score>) {
+    fn add_token(&mut self, token: &Token, score: Option<Score>) {
         self.stop_offset = token.offset_to;
 
-        if let Some(&score) = terms.get(&token.text.to_lowercase()) {
+        if let Some(score) = score {
             self.score += score;
             self.highlighted.push(token.offset_from..token.offset_to);
         }
     }
 }
 
+/// A phrase to highlight.
+#[derive(Clone, Debug)]
+struct HighlightedPhrase {
+    // The text of the terms of the phrase, with their offset within
+    // the phrase and their score, sorted by offset.
+    terms: Vec<(usize, String, Score)>,
+    slop: u32,
+}
+
+impl HighlightedPhrase {
+    /// Creates the phrase to highlight for the terms of a phrase query.
+    ///
+    /// Returns `None` if one of the terms does not appear in the index.
+    fn create(
+        searcher: &Searcher,
+        phrase_terms: &[(usize, Term)],
+        slop: u32,
+    ) -> crate::Result<Option<HighlightedPhrase>> {
+        let mut terms = Vec::with_capacity(phrase_terms.len());
+        for (offset, term) in phrase_terms {
+            let Some((text, score)) = term_score(searcher, term)? else {
+                return Ok(None);
+            };
+            terms.push((*offset, text, score));
+        }
+        Ok(Some(HighlightedPhrase { terms, slop }))
+    }
+
+    /// Returns the ordinals of the tokens belonging to an occurrence of the phrase,
+    /// with their score.
+    ///
+    /// The position of the first term of the phrase anchors its occurrences: the other terms
+    /// may be moved from their expected position by the slop of the phrase in total.
+    fn matching_tokens(
+        &self,
+        tokens: &[Token],
+        tokens_by_position: &BTreeMap<usize, Vec<usize>>,
+    ) -> Vec<(usize, Score)> {
+        let mut matching_tokens = Vec::new();
+        let Some(((first_offset, first_text, first_score), other_terms)) = self.terms.split_first()
+        else {
+            return matching_tokens;
+        };
+        let mut occurrence = Vec::with_capacity(self.terms.len());
+        for (ord, token) in tokens.iter().enumerate() {
+            if token.text != *first_text || token.position < *first_offset {
+                continue;
+            }
+            let phrase_position = token.position - first_offset;
+            let mut remaining_slop = self.slop as usize;
+            occurrence.clear();
+            occurrence.push((ord, *first_score));
+            for (offset, text, score) in other_terms {
+                let expected_position = phrase_position + offset;
+                let closest_token = tokens_by_position
+                    .range(
+                        expected_position.saturating_sub(remaining_slop)
+                            ..=expected_position + remaining_slop,
+                    )
+                    .flat_map(|(&position, ords)| ords.iter().map(move |&ord| (position, ord)))
+                    .filter(|&(_, ord)| tokens[ord].text == *text)
+                    .min_by_key(|&(position, _)| position.abs_diff(expected_position));
+                let Some((position, ord)) = closest_token else {
+                    occurrence.clear();
+                    break;
+                };
+                remaining_slop -= position.abs_diff(expected_position);
+                occurrence.push((ord, *score));
+            }
+            matching_tokens.extend_from_slice(&occurrence);
+        }
+        matching_tokens
+    }
+}
+
+/// Returns the field of the terms of a phrase.
+fn phrase_field(phrase_terms: &[(usize, Term)]) -> Option<Field> {
+    phrase_terms.first().map(|(_, term)| term.field())
+}
+
+/// Returns the text of the term and its score, if it appears in the index.
+///
+/// Rare terms get higher scores.
+fn term_score(searcher: &Searcher, term: &Term) -> crate::Result<Option<(String, Score)>> {
+    let term_value = term.value();
+    let Some(text) = term_value.as_str() else {
+        return Ok(None);
+    };
+    let doc_freq = searcher.doc_freq(term)?;
+    if doc_freq == 0 {
+        return Ok(None);
+    }
+    Ok(Some((text.to_string(), 1.0 / (1.0 + doc_freq as Score))))
+}
+
+fn tokenize(tokenizer: &TextAnalyzer, text: &str) -> Vec<Token> {
+    let mut tokens: Vec<Token> = Vec::new();
+    tokenizer
+        .token_stream(text)
+        .process(&mut |token: &Token| tokens.push(token.clone()));
+    tokens
+}
+
+/// Returns the tokens with the score of the tokens that need to be highlighted.
+///
+/// A token is highlighted if `term_score` returns a score for it, or if it belongs to an
+/// occurrence of one of the `phrases`.
+fn score_tokens(
+    tokens: Vec<Token>,
+    term_score: impl Fn(&Token) -> Option<Score>,
+    phrases: &[HighlightedPhrase],
+) -> Vec<(Token, Option<Score>)> {
+    let mut scores: Vec<Option<Score>> = tokens.iter().map(term_score).collect();
+    if !phrases.is_empty() {
+        let mut tokens_by_position: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (ord, token) in tokens.iter().enumerate() {
+            tokens_by_position
+                .entry(token.position)
+                .or_default()
+                .push(ord);
+        }
+        for phrase in phrases {
+            for (ord, score) in phrase.matching_tokens(&tokens, &tokens_by_position) {
+                let token_score = scores[ord].get_or_insert(score);
+                *token_score = token_score.max(score);
+            }
+        }
+    }
+    tokens.into_iter().zip(scores).collect()
+}
+
+/// Splits the tokens into fragments of at most `max_num_chars` characters, and returns the
+/// fragments with at least one highlighted token.
+fn split_into_fragments<'a>(
+    scored_tokens: impl Iterator<Item = (&'a Token, Option<Score>)>,
+    max_num_chars: usize,
+) -> Vec<FragmentCandidate> {
+    let mut fragment = FragmentCandidate::new(0);
+    let mut fragments: Vec<FragmentCandidate> = vec![];
+    for (next, score) in scored_tokens {
+        if (next.offset_to - fragment.start_offset) > max_num_chars {
+            if fragment.score > 0.0 {
+                fragments.push(fragment)
+            };
+            fragment = FragmentCandidate::new(next.offset_from);
+        }
+        fragment.add_token(next, score);
+    }
+    if fragment.score > 0.0 {
+        fragments.push(fragment)
+    }
+
+    fragments
+}
+
+/// Scores the fragments of the text returned by a [`Fragmenter`], and returns the fragments
+/// with at least one highlighted token.
+///
+/// The tokens overlapping two fragments are not highlighted.
+fn score_fragments<'a>(
+    fragments: Vec<Range<usize>>,
+    scored_tokens: impl Iterator<Item = (&'a Token, Option<Score>)>,
+) -> Vec<FragmentCandidate> {
+    let mut fragment_candidates: Vec<FragmentCandidate> = fragments
+        .iter()
+        .map(|fragment| FragmentCandidate {
+            stop_offset: fragment.end,
+            ..FragmentCandidate::new(fragment.start)
+        })
+        .collect();
+    for (token, score) in scored_tokens {
+        let Some(score) = score else {
+            continue;
+        };
+        let ord = fragments.partition_point(|fragment| fragment.end <= token.offset_from);
+        if let Some(fragment) = fragment_candidates.get_mut(ord) {
+            if fragment.start_offset <= token.offset_from && token.offset_to <= fragment.stop_offset
+            {
+                fragment.score += score;
+                fragment
+                    .highlighted
+                    .push(token.offset_from..token.offset_to);
+            }
+        }
+    }
+    fragment_candidates.retain(|fragment| fragment.score > 0.0);
+    fragment_candidates
+}
+
+/// Extends the fragment with the text following its last token, up to `max_num_chars`
+/// bytes from its start, stopping at the last whitespace if there is one.
+///
+/// This is used when the fragment was built out of the tokens recorded in the index, which
+/// leave out the punctuation and, for the offsets of the postings, the tokens surrounding the
+/// matching ones.
+fn extend_fragment(fragment: &mut FragmentCandidate, text: &str, max_num_chars: usize) {
+    let mut stop_offset = text.len().min(fragment.start_offset + max_num_chars);
+    while !text.is_char_boundary(stop_offset) {
+        stop_offset -= 1;
+    }
+    if stop_offset <= fragment.stop_offset {
+        return;
+    }
+    if stop_offset < text.len() {
+        if let Some(whitespace_offset) =
+            text[fragment.stop_offset..stop_offset].rfind(char::is_whitespace)
+        {
+            stop_offset = fragment.stop_offset + whitespace_offset;
+        }
+    }
+    fragment.stop_offset = stop_offset;
+}
+
 /// `Snippet`
 /// Contains a fragment of a document, and some highlighted parts inside it.
 #[derive(Debug)]
@@ -144,29 +363,117 @@ impl Snippet {
 ///
 /// Fragments must be valid in the sense that `&text[fragment.start..fragment.stop]`\
 /// has to be a valid string.
+///
+/// The terms of the `phrases` are only highlighted where the phrase occurs.
 fn search_fragments(
     tokenizer: &TextAnalyzer,
     text: &str,
     terms: &BTreeMap<String, Score>,
+    phrases: &[HighlightedPhrase],
     max_num_chars: usize,
 ) -> Vec<FragmentCandidate> {
-    let mut token_stream = tokenizer.token_stream(text);
-    let mut fragment = FragmentCandidate::new(0);
-    let mut fragments: Vec<FragmentCandidate> = vec![];
-    while let Some(next) = token_stream.next() {
-        if (next.offset_to - fragment.start_offset) > max_num_chars {
-            if fragment.score > 0.0 {
-                fragments.push(fragment)
+    let scored_tokens = score_tokens(
+        tokenize(tokenizer, text),
+        |token| terms.get(&token.text.to_lowercase()).copied(),
+        phrases,
+    );
+    split_into_fragments(
+        scored_tokens.iter().map(|(token, score)| (token, *score)),
+        max_num_chars,
+    )
+}
+
+/// Returns the tokens of a field of a document as recorded in the index, if the field stores
+/// term vectors or the offsets of its postings.
+///
+/// Term vectors hold all of the tokens of the document, while the postings only give the
+/// tokens of the `terms` read from the index.
+/// The offsets are translated to offsets in `text`, the `texts` of the field joined by a space.
+/// Returns `None` if the field records neither, or if the offsets do not match the text, e.g.
+/// if the stored text was not the indexed one.
+fn indexed_tokens<'a>(
+    segment_reader: &SegmentReader,
+    doc_id: DocId,
+    field: Field,
+    terms: impl Iterator<Item = &'a str>,
+    texts: &[&str],
+    text: &str,
+) -> crate::Result<Option<Vec<Token>>> {
+    let field_entry = segment_reader.schema().get_field_entry(field);
+    let mut tokens: Vec<Token> = Vec::new();
+    if field_entry.has_term_vectors() {
+        let term_vector = segment_reader.term_vector(doc_id, field)?;
+        for term in term_vector.terms() {
+            for (&position, &(offset_from, offset_to)) in
+                term.positions().iter().zip(term.offsets())
+            {
+                tokens.push(Token {
+                    offset_from,
+                    offset_to,
+                    position: position as usize,
+                    text: term.text().to_string(),
+                    ..Token::default()
+                });
+            }
+        }
+    } else if field_entry
+        .field_type()
+        .get_index_record_option()
+        .map_or(false, IndexRecordOption::has_offsets)
+    {
+        let inverted_index = segment_reader.inverted_index(field)?;
+        let mut positions: Vec<u32> = Vec::new();
+        let mut offsets: Vec<(u32, u32)> = Vec::new();
+        for text in terms.collect::<BTreeSet<&str>>() {
+            let term = Term::from_field_text(field, text);
+            let Some(mut postings) = inverted_index
+                .read_postings(&term, IndexRecordOption::WithFreqsAndPositionsAndOffsets)?
+            else {
+                continue;
             };
-            fragment = FragmentCandidate::new(next.offset_from);
+            if postings.seek(doc_id) != doc_id {
+                continue;
+            }
+            postings.positions(&mut positions);
+            postings.offsets(&mut offsets);
+            for (&position, &(offset_from, offset_to)) in positions.iter().zip(&offsets) {
+                tokens.push(Token {
+                    offset_from: offset_from as usize,
+                    offset_to: offset_to as usize,
+                    position: position as usize,
+                    text: text.to_string(),
+                    ..Token::default()
+                });
+            }
         }
-        fragment.try_add_token(next, terms);
+    } else {
+        return Ok(None);
     }
-    if fragment.score > 0.0 {
-        fragments.push(fragment)
+    // The offsets of a value are shifted by the length of the previous values in the index,
+    // and by the separating spaces in the joined text.
+    let mut value_starts: Vec<usize> = Vec::with_capacity(texts.len());
+    let mut value_start = 0;
+    for text in texts {
+        value_starts.push(value_start);
+        value_start += text.len();
     }
-
-    fragments
+    for token in &mut tokens {
+        let value_ord = value_starts
+            .partition_point(|&value_start| value_start <= token.offset_from)
+            .saturating_sub(1);
+        token.offset_from += value_ord;
+        token.offset_to += value_ord;
+    }
+    let text_matches = tokens.iter().all(|token| {
+        token.offset_from <= token.offset_to
+            && text.is_char_boundary(token.offset_from)
+            && text.is_char_boundary(token.offset_to)
+    });
+    if !text_matches {
+        return Ok(None);
+    }
+    tokens.sort_by_key(|token| (token.offset_from, token.position));
+    Ok(Some(tokens))
 }
 
 /// Returns a Snippet
@@ -174,25 +481,11 @@ fn search_fragments(
 /// Takes a vector of `FragmentCandidate`s and the text.
 /// Figures out the best fragment from it and creates a snippet.
 fn select_best_fragment_combination(fragments: &[FragmentCandidate], text: &str) -> Snippet {
-    let best_fragment_opt = fragments.iter().max_by(|left, right| {
-        let cmp_score = left
-            .score
-            .partial_cmp(&right.score)
-            .unwrap_or(Ordering::Equal);
-        if cmp_score == Ordering::Equal {
-            (right.start_offset, right.stop_offset).cmp(&(left.start_offset, left.stop_offset))
-        } else {
-            cmp_score
-        }
-    });
+    let best_fragment_opt = fragments
+        .iter()
+        .max_by(|left, right| cmp_fragments(left, right));
     if let Some(fragment) = best_fragment_opt {
-        let fragment_text = &text[fragment.start_offset..fragment.stop_offset];
-        let highlighted = fragment
-            .highlighted
-            .iter()
-            .map(|item| item.start - fragment.start_offset..item.end - fragment.start_offset)
-            .collect();
-        Snippet::new(fragment_text, highlighted)
+        fragment_snippet(fragment, text)
     } else {
         // When there are no fragments to chose from,
         // for now create an empty snippet.
@@ -200,6 +493,30 @@ fn select_best_fragment_combination(fragments: &[FragmentCandidate], text: &str)
     }
 }
 
+/// Compares fragments by score, and then prefers the fragments appearing first.
+fn cmp_fragments(left: &FragmentCandidate, right: &FragmentCandidate) -> Ordering {
+    let cmp_score = left
+        .score
+        .partial_cmp(&right.score)
+        .unwrap_or(Ordering::Equal);
+    if cmp_score == Ordering::Equal {
+        (right.start_offset, right.stop_offset).cmp(&(left.start_offset, left.stop_offset))
+    } else {
+        cmp_score
+    }
+}
+
+/// Creates a snippet from a fragment of the text.
+fn fragment_snippet(fragment: &FragmentCandidate, text: &str) -> Snippet {
+    let fragment_text = &text[fragment.start_offset..fragment.stop_offset];
+    let highlighted = fragment
+        .highlighted
+        .iter()
+        .map(|item| item.start - fragment.start_offset..item.end - fragment.start_offset)
+        .collect();
+    Snippet::new(fragment_text, highlighted)
+}
+
 /// Returns ranges that are collapsed into non-overlapped ranges.
 ///
 /// ## Examples
@@ -290,9 +607,11 @@ fn is_sorted(mut it: impl Iterator<Item = usize>) -> bool {
 /// ```
 pub struct SnippetGenerator {
     terms_text: BTreeMap<String, Score>,
+    phrases: Vec<HighlightedPhrase>,
     tokenizer: TextAnalyzer,
     field: Field,
     max_num_chars: usize,
+    fragmenter: Option<Box<dyn Fragmenter>>,
 }
 
 impl SnippetGenerator {
@@ -305,51 +624,71 @@ impl SnippetGenerator {
     ) -> Self {
         SnippetGenerator {
             terms_text,
+            phrases: Vec::new(),
             tokenizer,
             field,
             max_num_chars,
+            fragmenter: None,
         }
     }
     /// Creates a new snippet generator
+    ///
+    /// The terms of the phrase queries are only highlighted where the phrase occurs,
+    /// e.g. for `"new york"`, the standalone occurrences of `new` and `york` are not
+    /// highlighted.
     pub fn create(
         searcher: &Searcher,
         query: &dyn Query,
         field: Field,
     ) -> crate::Result<SnippetGenerator> {
         let mut terms: BTreeSet<&Term> = BTreeSet::new();
-        query.query_terms(&mut |term, _| {
-            if term.field() == field {
+        let mut phrases: Vec<(&[(usize, Term)], u32)> = Vec::new();
+        query.query_leaves(&mut |leaf| match leaf {
+            QueryLeaf::Term(term) if term.field() == field => {
                 terms.insert(term);
             }
+            QueryLeaf::Phrase {
+                terms: phrase_terms,
+                slop,
+            } if phrase_field(phrase_terms) == Some(field) => phrases.push((phrase_terms, slop)),
+            _ => {}
         });
         let mut terms_text: BTreeMap<String, Score> = Default::default();
         for term in terms {
-            let term_value = term.value();
-            let term_str = if let Some(term_str) = term_value.as_str() {
-                term_str
-            } else {
-                continue;
-            };
-            let doc_freq = searcher.doc_freq(term)?;
-            if doc_freq > 0 {
-                let score = 1.0 / (1.0 + doc_freq as Score);
-                terms_text.insert(term_str.to_string(), score);
+            if let Some((term_str, score)) = term_score(searcher, term)? {
+                terms_text.insert(term_str, score);
+            }
+        }
+        let mut highlighted_phrases = Vec::with_capacity(phrases.len());
+        for (phrase_terms, slop) in phrases {
+            if let Some(phrase) = HighlightedPhrase::create(searcher, phrase_terms, slop)? {
+                highlighted_phrases.push(phrase);
             }
         }
         let tokenizer = searcher.index().tokenizer_for_field(field)?;
         Ok(SnippetGenerator {
             terms_text,
+            phrases: highlighted_phrases,
             tokenizer,
             field,
             max_num_chars: DEFAULT_MAX_NUM_CHARS,
+            fragmenter: None,
         })
     }
 
     /// Sets a maximum number of chars.
+    ///
+    /// It is ignored if a [`Fragmenter`] is set.
     pub fn set_max_num_chars(&mut self, max_num_chars: usize) {
         self.max_num_chars = max_num_chars;
     }
 
+    /// Sets the [`Fragmenter`] splitting the texts into the fragments snippets are made of,
+    /// e.g. a [`SentenceFragmenter`] to get snippets of whole sentences.
+    pub fn set_fragmenter<F: Fragmenter>(&mut self, fragmenter: F) {
+        self.fragmenter = Some(Box::new(fragmenter));
+    }
+
     #[cfg(test)]
     pub fn terms_text(&self) -> &BTreeMap<String, Score> {
         &self.terms_text
@@ -368,10 +707,85 @@ impl SnippetGenerator {
         self.snippet(&text)
     }
 
+    /// Generates a snippet for the given `Document`, stored at `doc_address` in the index.
+    ///
+    /// If the field of the `SnippetGenerator` stores [term vectors](crate::termvector) or
+    /// the offsets of its postings, the matches are located in the text of the document from
+    /// the index rather than by analyzing the text again. This is cheaper, and the
+    /// highlighted matches are the ones that were indexed even if the tokenizer of the field
+    /// changed since.
+    ///
+    /// Without term vectors, only the highlighted terms are read from the index, and the
+    /// fragments are extended with the text following them.
+    /// If the field records neither, this is equivalent to
+    /// [`SnippetGenerator::snippet_from_doc`].
+    pub fn snippet_from_indexed_doc(
+        &self,
+        searcher: &Searcher,
+        doc_address: DocAddress,
+        doc: &Document,
+    ) -> crate::Result<Snippet> {
+        let texts: Vec<&str> = doc.get_all(self.field).flat_map(Value::as_text).collect();
+        let text = texts.join(" ");
+        let segment_reader = searcher.segment_reader(doc_address.segment_ord);
+        let terms = self.terms_text.keys().map(String::as_str).chain(
+            self.phrases
+                .iter()
+                .flat_map(|phrase| phrase.terms.iter().map(|(_, text, _)| text.as_str())),
+        );
+        let Some(tokens) = indexed_tokens(
+            segment_reader,
+            doc_address.doc_id,
+            self.field,
+            terms,
+            &texts,
+            &text,
+        )?
+        else {
+            return Ok(self.snippet(&text));
+        };
+        let scored_tokens = score_tokens(
+            tokens,
+            |token| self.terms_text.get(&token.text).copied(),
+            &self.phrases,
+        );
+        let scored_tokens = scored_tokens.iter().map(|(token, score)| (token, *score));
+        let fragment_candidates = if let Some(fragmenter) = &self.fragmenter {
+            score_fragments(fragmenter.fragments(&text), scored_tokens)
+        } else {
+            let mut fragment_candidates = split_into_fragments(scored_tokens, self.max_num_chars);
+            for fragment in &mut fragment_candidates {
+                extend_fragment(fragment, &text, self.max_num_chars);
+            }
+            fragment_candidates
+        };
+        Ok(select_best_fragment_combination(
+            &fragment_candidates[..],
+            &text,
+        ))
+    }
+
     /// Generates a snippet for the given text.
     pub fn snippet(&self, text: &str) -> Snippet {
-        let fragment_candidates =
-            search_fragments(&self.tokenizer, text, &self.terms_text, self.max_num_chars);
+        let fragment_candidates = if let Some(fragmenter) = &self.fragmenter {
+            let scored_tokens = score_tokens(
+                tokenize(&self.tokenizer, text),
+                |token| self.terms_text.get(&token.text.to_lowercase()).copied(),
+                &self.phrases,
+            );
+            score_fragments(
+                fragmenter.fragments(text),
+                scored_tokens.iter().map(|(token, score)| (token, *score)),
+            )
+        } else {
+            search_fragments(
+                &self.tokenizer,
+                text,
+                &self.terms_text,
+                &self.phrases,
+                self.max_num_chars,
+            )
+        };
         select_best_fragment_combination(&fragment_candidates[..], text)
     }
 }
@@ -383,10 +797,13 @@ mod tests {
     use maplit::btreemap;
 
     use super::{collapse_overlapped_ranges, search_fragments, select_best_fragment_combination};
-    use crate::query::QueryParser;
+    use crate::query::{QueryParser, TermQuery};
     use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions, TEXT};
-    use crate::tokenizer::{NgramTokenizer, SimpleTokenizer};
-    use crate::{Index, SnippetGenerator};
+    use crate::tokenizer::{
+        HtmlStripCharFilter, LowerCaser, NgramTokenizer, SimpleTokenizer, TextAnalyzer,
+        WhitespaceTokenizer,
+    };
+    use crate::{DocAddress, Index, SentenceFragmenter, SnippetGenerator, Term};
 
     const TEST_TEXT: &str = r#"Rust is a systems programming language sponsored by
 Mozilla which describes it as a "safe, concurrent, practical language", supporting functional and
@@ -408,7 +825,7 @@ Survey in 2016, 2017, and 2018."#;
             String::from("rust") => 1.0,
             String::from("language") => 0.9
         };
-        let fragments = search_fragments(&From::from(SimpleTokenizer), TEST_TEXT, &terms, 100);
+        let fragments = search_fragments(&From::from(SimpleTokenizer), TEST_TEXT, &terms, &[], 100);
         assert_eq!(fragments.len(), 7);
         {
             let first = &fragments[0];
@@ -435,7 +852,8 @@ Survey in 2016, 2017, and 2018."#;
                 String::from("rust") =>1.0,
                 String::from("language") => 0.9
             };
-            let fragments = search_fragments(&From::from(SimpleTokenizer), TEST_TEXT, &terms, 20);
+            let fragments =
+                search_fragments(&From::from(SimpleTokenizer), TEST_TEXT, &terms, &[], 20);
             {
                 let first = &fragments[0];
                 assert_eq!(first.score, 1.0);
@@ -449,7 +867,8 @@ Survey in 2016, 2017, and 2018."#;
                 String::from("rust") =>0.9,
                 String::from("language") => 1.0
             };
-            let fragments = search_fragments(&From::from(SimpleTokenizer), TEST_TEXT, &terms, 20);
+            let fragments =
+                search_fragments(&From::from(SimpleTokenizer), TEST_TEXT, &terms, &[], 20);
             // assert_eq!(fragments.len(), 7);
             {
                 let first = &fragments[0];
@@ -468,7 +887,7 @@ Survey in 2016, 2017, and 2018."#;
         let mut terms = BTreeMap::new();
         terms.insert(String::from("c"), 1.0);
 
-        let fragments = search_fragments(&From::from(SimpleTokenizer), text, &terms, 3);
+        let fragments = search_fragments(&From::from(SimpleTokenizer), text, &terms, &[], 3);
 
         assert_eq!(fragments.len(), 1);
         {
@@ -490,7 +909,7 @@ Survey in 2016, 2017, and 2018."#;
         let mut terms = BTreeMap::new();
         terms.insert(String::from("f"), 1.0);
 
-        let fragments = search_fragments(&From::from(SimpleTokenizer), text, &terms, 3);
+        let fragments = search_fragments(&From::from(SimpleTokenizer), text, &terms, &[], 3);
 
         assert_eq!(fragments.len(), 2);
         {
@@ -513,7 +932,7 @@ Survey in 2016, 2017, and 2018."#;
         terms.insert(String::from("f"), 1.0);
         terms.insert(String::from("a"), 0.9);
 
-        let fragments = search_fragments(&From::from(SimpleTokenizer), text, &terms, 7);
+        let fragments = search_fragments(&From::from(SimpleTokenizer), text, &terms, &[], 7);
 
         assert_eq!(fragments.len(), 2);
         {
@@ -535,7 +954,7 @@ Survey in 2016, 2017, and 2018."#;
         let mut terms = BTreeMap::new();
         terms.insert(String::from("z"), 1.0);
 
-        let fragments = search_fragments(&From::from(SimpleTokenizer), text, &terms, 3);
+        let fragments = search_fragments(&From::from(SimpleTokenizer), text, &terms, &[], 3);
 
         assert_eq!(fragments.len(), 0);
 
@@ -550,7 +969,7 @@ Survey in 2016, 2017, and 2018."#;
         let text = "a b c d";
 
         let terms = BTreeMap::new();
-        let fragments = search_fragments(&From::from(SimpleTokenizer), text, &terms, 3);
+        let fragments = search_fragments(&From::from(SimpleTokenizer), text, &terms, &[], 3);
         assert_eq!(fragments.len(), 0);
 
         let snippet = select_best_fragment_combination(&fragments[..], text);
@@ -651,6 +1070,124 @@ Survey in 2016, 2017, and 2018."#;
         Ok(())
     }
 
+    #[test]
+    fn test_snippet_generator_sentence_fragmenter() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc!(text_field => TEST_TEXT))?;
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        let query = query_parser.parse_query("rust designers")?;
+        let mut snippet_generator = SnippetGenerator::create(&searcher, &*query, text_field)?;
+        snippet_generator.set_fragmenter(SentenceFragmenter::new(200));
+        let snippet = snippet_generator.snippet(TEST_TEXT);
+        assert_eq!(
+            snippet.to_html(),
+            "<b>Rust</b> is syntactically similar to C++[according to whom?],\nbut its \
+             <b>designers</b> intend it to provide better memory safety while still \
+             maintaining\nperformance."
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_snippet_from_indexed_doc() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let indexing = TextFieldIndexing::default().set_tokenizer("evolving");
+        let term_vectors_field = schema_builder.add_text_field(
+            "term_vectors",
+            TextOptions::default().set_indexing_options(indexing.clone().set_term_vectors(true)),
+        );
+        let offsets_field = schema_builder.add_text_field(
+            "offsets",
+            TextOptions::default().set_indexing_options(
+                indexing.set_index_option(IndexRecordOption::WithFreqsAndPositionsAndOffsets),
+            ),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        index.tokenizers().register(
+            "evolving",
+            TextAnalyzer::builder(SimpleTokenizer)
+                .filter(LowerCaser)
+                .build(),
+        );
+        let doc = doc!(
+            term_vectors_field => "Learning Rust",
+            term_vectors_field => "rust, cargo and more rust.",
+            offsets_field => "Learning Rust",
+            offsets_field => "rust, cargo and more rust.",
+        );
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc.clone())?;
+            index_writer.commit()?;
+        }
+        // The punctuation is not removed anymore by the tokenizer.
+        index
+            .tokenizers()
+            .register("evolving", TextAnalyzer::from(WhitespaceTokenizer));
+        let searcher = index.reader()?.searcher();
+        let doc_address = DocAddress::new(0, 0);
+        for field in [term_vectors_field, offsets_field] {
+            let query = TermQuery::new(
+                Term::from_field_text(field, "rust"),
+                IndexRecordOption::Basic,
+            );
+            let mut snippet_generator = SnippetGenerator::create(&searcher, &query, field)?;
+            assert_eq!(
+                snippet_generator.snippet_from_doc(&doc).to_html(),
+                "Learning <b>Rust</b> rust, cargo and more rust."
+            );
+            let snippet =
+                snippet_generator.snippet_from_indexed_doc(&searcher, doc_address, &doc)?;
+            assert_eq!(
+                snippet.to_html(),
+                "Learning <b>Rust</b> <b>rust</b>, cargo and more <b>rust</b>."
+            );
+            snippet_generator.set_max_num_chars(20);
+            let snippet =
+                snippet_generator.snippet_from_indexed_doc(&searcher, doc_address, &doc)?;
+            assert_eq!(snippet.to_html(), "Learning <b>Rust</b> <b>rust</b>,");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_snippet_generator_phrase() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let text = "A new bridge in York. New York is big, and New   York is old.";
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc!(text_field => text))?;
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        let query = query_parser.parse_query("\"new york\" old")?;
+        let snippet_generator = SnippetGenerator::create(&searcher, &*query, text_field)?;
+        assert_eq!(
+            snippet_generator.snippet(text).to_html(),
+            "A new bridge in York. <b>New</b> <b>York</b> is big, and <b>New</b>   <b>York</b> is \
+             <b>old</b>"
+        );
+        let query = query_parser.parse_query("\"new york\"~2 -bridge")?;
+        let snippet_generator = SnippetGenerator::create(&searcher, &*query, text_field)?;
+        assert_eq!(
+            snippet_generator
+                .snippet("new big york. york is new")
+                .to_html(),
+            "<b>new</b> big <b>york</b>. york is new"
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_collapse_overlapped_ranges() {
         assert_eq!(&collapse_overlapped_ranges(&[0..1, 2..3,]), &[0..1, 2..3]);
@@ -672,6 +1209,7 @@ Survey in 2016, 2017, and 2018."#;
             &From::from(NgramTokenizer::all_ngrams(2, 2)),
             text,
             &terms,
+            &[],
             3,
         );
 
@@ -688,10 +1226,28 @@ Survey in 2016, 2017, and 2018."#;
         assert_eq!(snippet.to_html(), "<b>abc</b>");
     }
 
+    #[test]
+    fn test_snippet_with_char_filter() {
+        let text = "<p>Tantivy is <em>F</em>ast &amp; <b>small</b></p>";
+        let analyzer = TextAnalyzer::builder(SimpleTokenizer)
+            .char_filter(HtmlStripCharFilter)
+            .filter(LowerCaser)
+            .build();
+        let terms = btreemap! { String::from("fast") => 1.0, String::from("small") => 0.5 };
+        let fragments = search_fragments(&analyzer, text, &terms, &[], 100);
+        let snippet = select_best_fragment_combination(&fragments[..], text);
+        let highlighted: Vec<&str> = snippet
+            .highlighted()
+            .iter()
+            .map(|range| &snippet.fragment()[range.clone()])
+            .collect();
+        assert_eq!(highlighted, vec!["F</em>ast", "small"]);
+    }
+
     #[test]
     fn test_snippet_generator_custom_highlighted_elements() {
         let terms = btreemap! { String::from("rust") => 1.0, String::from("language") => 0.9 };
-        let fragments = search_fragments(&From::from(SimpleTokenizer), TEST_TEXT, &terms, 100);
+        let fragments = search_fragments(&From::from(SimpleTokenizer), TEST_TEXT, &terms, &[], 100);
         let mut snippet = select_best_fragment_combination(&fragments[..], TEST_TEXT);
         assert_eq!(
             snippet.to_html(),
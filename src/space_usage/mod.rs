@@ -7,7 +7,7 @@
 //! storage-level details into consideration. For example, if your file system block size is 4096
 //! bytes, we can under-count actual resultant space usage by up to 4095 bytes per file.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use common::ByteCount;
 use serde::{Deserialize, Serialize};
@@ -57,6 +57,20 @@ impl SearcherSpaceUsage {
     pub fn total(&self) -> ByteCount {
         self.total
     }
+
+    /// Space usage of each field, summed over all of the segments.
+    ///
+    /// The fields which do not use any space are omitted. The doc store is not broken down
+    /// per field, see [`SegmentSpaceUsage::store`].
+    pub fn fields(&self) -> BTreeMap<Field, FieldSpaceUsage> {
+        let mut fields: BTreeMap<Field, FieldSpaceUsage> = BTreeMap::new();
+        for segment in &self.segments {
+            for (field, field_space_usage) in segment.fields() {
+                fields.entry(field).or_default().add(&field_space_usage);
+            }
+        }
+        fields
+    }
 }
 
 /// Represents combined space usage for all of the large components comprising a segment.
@@ -69,6 +83,11 @@ pub struct SegmentSpaceUsage {
     positions: PerFieldSpaceUsage,
     fast_fields: PerFieldSpaceUsage,
     fieldnorms: PerFieldSpaceUsage,
+    points: PerFieldSpaceUsage,
+    term_vectors: PerFieldSpaceUsage,
+    payloads: PerFieldSpaceUsage,
+    completions: PerFieldSpaceUsage,
+    hnsw: PerFieldSpaceUsage,
 
     store: StoreSpaceUsage,
 
@@ -86,6 +105,11 @@ impl SegmentSpaceUsage {
         positions: PerFieldSpaceUsage,
         fast_fields: PerFieldSpaceUsage,
         fieldnorms: PerFieldSpaceUsage,
+        points: PerFieldSpaceUsage,
+        term_vectors: PerFieldSpaceUsage,
+        payloads: PerFieldSpaceUsage,
+        completions: PerFieldSpaceUsage,
+        hnsw: PerFieldSpaceUsage,
         store: StoreSpaceUsage,
         deletes: ByteCount,
     ) -> SegmentSpaceUsage {
@@ -94,6 +118,11 @@ impl SegmentSpaceUsage {
             + positions.total()
             + fast_fields.total()
             + fieldnorms.total()
+            + points.total()
+            + term_vectors.total()
+            + payloads.total()
+            + completions.total()
+            + hnsw.total()
             + store.total()
             + deletes;
         SegmentSpaceUsage {
@@ -103,6 +132,11 @@ impl SegmentSpaceUsage {
             positions,
             fast_fields,
             fieldnorms,
+            points,
+            term_vectors,
+            payloads,
+            completions,
+            hnsw,
             store,
             deletes,
             total,
@@ -125,6 +159,11 @@ impl SegmentSpaceUsage {
             SegmentComponent::Store => ComponentSpaceUsage::Store(self.store().clone()),
             SegmentComponent::TempStore => ComponentSpaceUsage::Store(self.store().clone()),
             Delete => Basic(self.deletes()),
+            Points => PerField(self.points().clone()),
+            TermVectors => PerField(self.term_vectors().clone()),
+            Payloads => PerField(self.payloads().clone()),
+            Completions => PerField(self.completions().clone()),
+            Hnsw => PerField(self.hnsw().clone()),
         }
     }
 
@@ -158,6 +197,31 @@ impl SegmentSpaceUsage {
         &self.fieldnorms
     }
 
+    /// Space usage for points
+    pub fn points(&self) -> &PerFieldSpaceUsage {
+        &self.points
+    }
+
+    /// Space usage for term vectors
+    pub fn term_vectors(&self) -> &PerFieldSpaceUsage {
+        &self.term_vectors
+    }
+
+    /// Space usage for payloads
+    pub fn payloads(&self) -> &PerFieldSpaceUsage {
+        &self.payloads
+    }
+
+    /// Space usage for completions
+    pub fn completions(&self) -> &PerFieldSpaceUsage {
+        &self.completions
+    }
+
+    /// Space usage for HNSW graphs
+    pub fn hnsw(&self) -> &PerFieldSpaceUsage {
+        &self.hnsw
+    }
+
     /// Space usage for stored documents
     pub fn store(&self) -> &StoreSpaceUsage {
         &self.store
@@ -172,6 +236,100 @@ impl SegmentSpaceUsage {
     pub fn total(&self) -> ByteCount {
         self.total
     }
+
+    /// Space usage of the given field in the components of this segment.
+    pub fn field(&self, field: Field) -> FieldSpaceUsage {
+        FieldSpaceUsage {
+            termdict: self.termdict.field_total(field),
+            postings: self.postings.field_total(field),
+            positions: self.positions.field_total(field),
+            fast_fields: self.fast_fields.field_total(field),
+            fieldnorms: self.fieldnorms.field_total(field),
+            points: self.points.field_total(field),
+            term_vectors: self.term_vectors.field_total(field),
+            payloads: self.payloads.field_total(field),
+            completions: self.completions.field_total(field),
+            hnsw: self.hnsw.field_total(field),
+        }
+    }
+
+    /// Space usage of each field in the components of this segment.
+    ///
+    /// The fields which do not use any space are omitted.
+    pub fn fields(&self) -> BTreeMap<Field, FieldSpaceUsage> {
+        let per_field_space_usages = [
+            &self.termdict,
+            &self.postings,
+            &self.positions,
+            &self.fast_fields,
+            &self.fieldnorms,
+            &self.points,
+            &self.term_vectors,
+            &self.payloads,
+            &self.completions,
+            &self.hnsw,
+        ];
+        per_field_space_usages
+            .iter()
+            .flat_map(|per_field_space_usage| per_field_space_usage.fields.keys().copied())
+            .map(|field| (field, self.field(field)))
+            .filter(|(_, field_space_usage)| field_space_usage.total() > 0)
+            .collect()
+    }
+}
+
+/// Represents the space usage of a field, broken down into the components of the segments.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldSpaceUsage {
+    /// Space usage of the term dictionary.
+    pub termdict: ByteCount,
+    /// Space usage of the postings lists.
+    pub postings: ByteCount,
+    /// Space usage of the positions.
+    pub positions: ByteCount,
+    /// Space usage of the fast field columns.
+    pub fast_fields: ByteCount,
+    /// Space usage of the field norms.
+    pub fieldnorms: ByteCount,
+    /// Space usage of the points.
+    pub points: ByteCount,
+    /// Space usage of the term vectors.
+    pub term_vectors: ByteCount,
+    /// Space usage of the payloads.
+    pub payloads: ByteCount,
+    /// Space usage of the completions.
+    pub completions: ByteCount,
+    /// Space usage of the HNSW graphs.
+    pub hnsw: ByteCount,
+}
+
+impl FieldSpaceUsage {
+    fn add(&mut self, other: &FieldSpaceUsage) {
+        self.termdict += other.termdict;
+        self.postings += other.postings;
+        self.positions += other.positions;
+        self.fast_fields += other.fast_fields;
+        self.fieldnorms += other.fieldnorms;
+        self.points += other.points;
+        self.term_vectors += other.term_vectors;
+        self.payloads += other.payloads;
+        self.completions += other.completions;
+        self.hnsw += other.hnsw;
+    }
+
+    /// Total space usage in bytes of this field.
+    pub fn total(&self) -> ByteCount {
+        self.termdict
+            + self.postings
+            + self.positions
+            + self.fast_fields
+            + self.fieldnorms
+            + self.points
+            + self.term_vectors
+            + self.payloads
+            + self.completions
+            + self.hnsw
+    }
 }
 
 /// Represents space usage for the Store for this segment.
@@ -238,6 +396,14 @@ impl PerFieldSpaceUsage {
     pub fn total(&self) -> ByteCount {
         self.total
     }
+
+    /// Bytes used by the given field in the represented file
+    pub fn field_total(&self, field: Field) -> ByteCount {
+        self.fields
+            .get(&field)
+            .map(FieldUsage::total)
+            .unwrap_or_default()
+    }
 }
 
 /// Represents space usage of a given field, breaking it down into the (field, index) pairs that
@@ -482,4 +648,41 @@ mod test {
         assert!(segment_space_usage.deletes() > 0);
         Ok(())
     }
+
+    #[test]
+    fn test_fields_space_usage() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT | STORED);
+        let num = schema_builder.add_u64_field("num", FAST);
+        let stored_only = schema_builder.add_text_field("stored_only", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "hello world", num => 1u64))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text => "hello", num => 2u64, stored_only => "x"))?;
+        index_writer.commit()?;
+
+        let searcher_space_usage = index.reader()?.searcher().space_usage()?;
+        assert_eq!(searcher_space_usage.segments().len(), 2);
+        let fields = searcher_space_usage.fields();
+        assert!(!fields.contains_key(&stored_only));
+
+        let text_space_usage = &fields[&text];
+        assert!(text_space_usage.termdict > 0);
+        assert!(text_space_usage.postings > 0);
+        assert!(text_space_usage.positions > 0);
+        assert!(text_space_usage.fieldnorms > 0);
+        assert_eq!(text_space_usage.fast_fields, 0);
+        let num_space_usage = &fields[&num];
+        assert!(num_space_usage.fast_fields > 0);
+        assert_eq!(num_space_usage.postings, 0);
+
+        let segments_text_total: u64 = searcher_space_usage
+            .segments()
+            .iter()
+            .map(|segment| segment.field(text).total().get_bytes())
+            .sum();
+        assert_eq!(text_space_usage.total(), segments_text_total);
+        Ok(())
+    }
 }
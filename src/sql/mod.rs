@@ -0,0 +1,701 @@
+//! A small SQL-like language, for the users who would rather think in SQL than in the syntax of
+//! the [`QueryParser`](crate::query::QueryParser).
+//!
+//! The statements have the form
+//!
+//! ```sql
+//! SELECT items [FROM index] [WHERE predicate] [GROUP BY field] [ORDER BY expr [ASC|DESC]]
+//! [LIMIT n]
+//! ```
+//!
+//! * The items are either `*`, selecting all the stored fields, or a list of stored fields and of
+//!   the aggregate functions `COUNT(*)`, `COUNT(field)`, `SUM(field)`, `AVG(field)`, `MIN(field)`
+//!   and `MAX(field)`, each optionally renamed with `AS name`. The aggregate functions are computed
+//!   with the [aggregations](crate::aggregation), over fast fields.
+//! * The predicate combines, with `AND`, `OR`, `NOT` and parentheses, the comparisons `=`, `!=`,
+//!   `<>`, `<`, `<=`, `>` and `>=`, `field [NOT] IN (values)`, `field [NOT] BETWEEN low AND high`,
+//!   `field [NOT] LIKE 'pattern'`, `field IS [NOT] NULL`, and the full text search `MATCH(field,
+//!   'text')`. As in SQL, the values are compared as they are, while the text of `MATCH` is
+//!   tokenized.
+//! * `GROUP BY` buckets the documents by the values of a fast field, with a terms aggregation.
+//! * `ORDER BY` sorts the documents by a fast field, or by `_score`, which is the default. The
+//!   groups are sorted by their key or by one of the aggregate functions, by descending `COUNT(*)`
+//!   by default.
+//! * Without `LIMIT`, all the documents, or up to 10 000 groups, are returned.
+//!
+//! The identifiers and the keywords are case insensitive, and the identifiers which are keywords
+//! or contain special characters can be written between double quotes. The strings are written
+//! between single quotes.
+//!
+//! ```rust
+//! use tantivy::schema::{Schema, FAST, STORED, STRING};
+//! use tantivy::sql::SqlQueryParser;
+//! use tantivy::{doc, Index};
+//!
+//! # fn main() -> tantivy::Result<()> {
+//! let mut schema_builder = Schema::builder();
+//! let author = schema_builder.add_text_field("author", STRING | STORED | FAST);
+//! let pages = schema_builder.add_u64_field("pages", STORED | FAST);
+//! let index = Index::create_in_ram(schema_builder.build());
+//! let mut index_writer = index.writer(15_000_000)?;
+//! index_writer.add_document(doc!(author => "Hemingway", pages => 127u64))?;
+//! index_writer.add_document(doc!(author => "Hemingway", pages => 247u64))?;
+//! index_writer.add_document(doc!(author => "Woolf", pages => 213u64))?;
+//! index_writer.commit()?;
+//!
+//! let searcher = index.reader()?.searcher();
+//! let sql_query_parser = SqlQueryParser::for_index(&index);
+//!
+//! let sql_query = sql_query_parser.parse("SELECT * WHERE pages > 200 ORDER BY pages LIMIT 1")?;
+//! let rows = sql_query.execute(&searcher)?;
+//! assert_eq!(rows.columns, ["author", "pages"]);
+//! assert_eq!(rows.rows, [[serde_json::json!("Woolf"), serde_json::json!(213)]]);
+//!
+//! let sql_query = sql_query_parser
+//!     .parse("SELECT author, COUNT(*), AVG(pages) AS average FROM books GROUP BY author")?;
+//! let rows = sql_query.execute(&searcher)?;
+//! assert_eq!(rows.columns, ["author", "count(*)", "average"]);
+//! assert_eq!(
+//!     rows.rows,
+//!     [
+//!         [serde_json::json!("Hemingway"), serde_json::json!(2), serde_json::json!(187.0)],
+//!         [serde_json::json!("Woolf"), serde_json::json!(1), serde_json::json!(213.0)],
+//!     ]
+//! );
+//! # Ok(())
+//! # }
+//! ```
+
+mod parser;
+
+use std::cmp::Reverse;
+
+use serde_json::{json, Value};
+
+use self::parser::{parse_statement, AggregateFunction, CompareOp, Expr, Predicate, Statement};
+use crate::aggregation::agg_req::Aggregations;
+use crate::aggregation::AggregationCollector;
+use crate::collector::{Count, TopDocs};
+use crate::query::{ElasticsearchQueryTranslator, Query, QueryParserError};
+use crate::schema::{Field, Schema, Type};
+use crate::tokenizer::TokenizerManager;
+use crate::{DocAddress, Index, Searcher, SegmentReader, TantivyError};
+
+/// The maximum number of groups returned by a statement with a `GROUP BY` and no `LIMIT`.
+const DEFAULT_GROUP_LIMIT: u32 = 10_000;
+
+/// The name of the terms aggregation of the `GROUP BY` clause.
+const GROUP_BY_AGGREGATION: &str = "group_by";
+
+/// The pseudo field to order the documents by their score.
+const SCORE: &str = "_score";
+
+/// Parses SQL-like statements into a [`SqlQuery`].
+///
+/// The predicates of the `WHERE` clause are translated into queries by an
+/// [`ElasticsearchQueryTranslator`], and have the same semantics.
+#[derive(Clone)]
+pub struct SqlQueryParser {
+    schema: Schema,
+    translator: ElasticsearchQueryTranslator,
+}
+
+/// A column of the result of an aggregation statement.
+#[derive(Clone, Debug)]
+enum AggregationColumn {
+    /// The key of the group.
+    Key,
+    /// The number of documents, in the group or in total.
+    DocCount,
+    /// The value of a metric aggregation.
+    Metric(String),
+}
+
+#[derive(Clone, Debug)]
+enum Plan {
+    Documents {
+        fields: Vec<Field>,
+        /// The fast field to order the documents by, and whether the order is descending.
+        order_by: Option<(String, bool)>,
+        limit: Option<usize>,
+    },
+    Aggregations {
+        grouped: bool,
+        aggregations: Aggregations,
+        columns: Vec<AggregationColumn>,
+        limit: Option<usize>,
+    },
+}
+
+/// A parsed statement, made of a query and of the way to collect its results.
+pub struct SqlQuery {
+    query: Box<dyn Query>,
+    columns: Vec<String>,
+    plan: Plan,
+}
+
+/// The result of a [`SqlQuery`], as rows of JSON values.
+///
+/// The fields without values are `null`, and the fields with several values are arrays.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SqlRows {
+    /// The names of the columns.
+    pub columns: Vec<String>,
+    /// The rows, with one value per column.
+    pub rows: Vec<Vec<Value>>,
+}
+
+fn unsupported(message: String) -> QueryParserError {
+    QueryParserError::UnsupportedQuery(message)
+}
+
+/// Translates a predicate into the equivalent Elasticsearch query.
+fn predicate_to_query_dsl(predicate: &Predicate) -> Result<Value, QueryParserError> {
+    let query = match predicate {
+        Predicate::And(predicates) => json!({"bool": {
+            "must": predicates.iter().map(predicate_to_query_dsl).collect::<Result<Vec<_>, _>>()?
+        }}),
+        Predicate::Or(predicates) => json!({"bool": {
+            "should": predicates.iter().map(predicate_to_query_dsl).collect::<Result<Vec<_>, _>>()?
+        }}),
+        Predicate::Not(predicate) => json!({"bool": {
+            "must_not": [predicate_to_query_dsl(predicate)?]
+        }}),
+        Predicate::Compare { field, op, value } => {
+            let range_op = match op {
+                CompareOp::Eq => return Ok(json!({"term": {field: value}})),
+                CompareOp::Ne => {
+                    return Ok(json!({"bool": {"must_not": [{"term": {field: value}}]}}))
+                }
+                CompareOp::Lt => "lt",
+                CompareOp::Le => "lte",
+                CompareOp::Gt => "gt",
+                CompareOp::Ge => "gte",
+            };
+            json!({"range": {field: {range_op: value}}})
+        }
+        Predicate::In { field, values } => json!({"terms": {field: values}}),
+        Predicate::Between { field, low, high } => {
+            json!({"range": {field: {"gte": low, "lte": high}}})
+        }
+        Predicate::Like { field, pattern } => {
+            if pattern.contains(['*', '?']) {
+                return Err(unsupported(format!(
+                    "The LIKE pattern {pattern:?} contains '*' or '?'"
+                )));
+            }
+            let wildcard = pattern.replace('%', "*").replace('_', "?");
+            json!({"wildcard": {field: wildcard}})
+        }
+        Predicate::IsNotNull { field } => json!({"exists": {"field": field}}),
+        Predicate::Match { field, text } => json!({"match": {field: text}}),
+    };
+    Ok(query)
+}
+
+impl SqlQueryParser {
+    /// Creates a parser for the given schema, tokenizing the texts of `MATCH` with the
+    /// tokenizers of the `tokenizer_manager`.
+    pub fn new(schema: Schema, tokenizer_manager: TokenizerManager) -> SqlQueryParser {
+        let translator = ElasticsearchQueryTranslator::new(schema.clone(), tokenizer_manager);
+        SqlQueryParser { schema, translator }
+    }
+
+    /// Creates a parser for the schema and the tokenizers of an index.
+    pub fn for_index(index: &Index) -> SqlQueryParser {
+        SqlQueryParser::new(index.schema(), index.tokenizers().clone())
+    }
+
+    /// Parses a statement.
+    pub fn parse(&self, sql: &str) -> Result<SqlQuery, QueryParserError> {
+        let statement = parse_statement(sql)?;
+        let query = match &statement.predicate {
+            Some(predicate) => self
+                .translator
+                .translate(&predicate_to_query_dsl(predicate)?)?,
+            None => self.translator.translate(&json!({"match_all": {}}))?,
+        };
+        let columns = statement
+            .select
+            .iter()
+            .map(|item| item.column_name())
+            .collect();
+        let has_aggregates = statement
+            .select
+            .iter()
+            .any(|item| matches!(item.expr, Expr::Aggregate { .. }));
+        if statement.group_by.is_some() || has_aggregates {
+            self.plan_aggregations(query, columns, statement)
+        } else {
+            self.plan_documents(query, columns, statement)
+        }
+    }
+
+    fn field(&self, field_name: &str) -> Result<Field, QueryParserError> {
+        self.schema
+            .get_field(field_name)
+            .map_err(|_| QueryParserError::FieldDoesNotExist(field_name.to_string()))
+    }
+
+    fn check_fast(&self, field_name: &str, clause: &str) -> Result<Field, QueryParserError> {
+        let field = self.field(field_name)?;
+        if !self.schema.get_field_entry(field).is_fast() {
+            return Err(unsupported(format!(
+                "The field {field_name:?} of the {clause} clause is not a fast field"
+            )));
+        }
+        Ok(field)
+    }
+
+    fn plan_documents(
+        &self,
+        query: Box<dyn Query>,
+        mut columns: Vec<String>,
+        statement: Statement,
+    ) -> Result<SqlQuery, QueryParserError> {
+        let fields = if statement.select.is_empty() {
+            let fields: Vec<Field> = self
+                .schema
+                .fields()
+                .filter(|(_, field_entry)| field_entry.is_stored())
+                .map(|(field, _)| field)
+                .collect();
+            columns = fields
+                .iter()
+                .map(|&field| self.schema.get_field_name(field).to_string())
+                .collect();
+            fields
+        } else {
+            statement
+                .select
+                .iter()
+                .map(|item| {
+                    let Expr::Field(field_name) = &item.expr else {
+                        unreachable!("The aggregate functions are planned as aggregations");
+                    };
+                    let field = self.field(field_name)?;
+                    if !self.schema.get_field_entry(field).is_stored() {
+                        return Err(unsupported(format!(
+                            "The field {field_name:?} is not stored, and cannot be selected"
+                        )));
+                    }
+                    Ok(field)
+                })
+                .collect::<Result<_, _>>()?
+        };
+        let order_by = match statement.order_by {
+            None => None,
+            Some((Expr::Field(field_name), true)) if field_name == SCORE => None,
+            Some((Expr::Field(field_name), descending)) if field_name != SCORE => {
+                let field = self.check_fast(&field_name, "ORDER BY")?;
+                let value_type = self.schema.get_field_entry(field).field_type().value_type();
+                if !matches!(
+                    value_type,
+                    Type::U64 | Type::I64 | Type::F64 | Type::Bool | Type::Date
+                ) {
+                    return Err(unsupported(format!(
+                        "Ordering by the {value_type:?} field {field_name:?}"
+                    )));
+                }
+                Some((field_name, descending))
+            }
+            Some((expr, descending)) => {
+                let order = if descending { "DESC" } else { "ASC" };
+                return Err(unsupported(format!(
+                    "ORDER BY {expr} {order} without GROUP BY"
+                )));
+            }
+        };
+        Ok(SqlQuery {
+            query,
+            columns,
+            plan: Plan::Documents {
+                fields,
+                order_by,
+                limit: statement.limit,
+            },
+        })
+    }
+
+    fn plan_aggregations(
+        &self,
+        query: Box<dyn Query>,
+        columns: Vec<String>,
+        statement: Statement,
+    ) -> Result<SqlQuery, QueryParserError> {
+        if statement.select.is_empty() {
+            return Err(unsupported("SELECT * with GROUP BY".to_string()));
+        }
+        let mut metrics = serde_json::Map::new();
+        let mut aggregation_columns = Vec::new();
+        for (ord, item) in statement.select.iter().enumerate() {
+            let column = match &item.expr {
+                Expr::Field(field_name) if statement.group_by.as_ref() == Some(field_name) => {
+                    AggregationColumn::Key
+                }
+                Expr::Field(field_name) => {
+                    return Err(unsupported(format!(
+                        "Selecting the field {field_name:?}, which is neither in the GROUP BY \
+                         clause nor in an aggregate function"
+                    )))
+                }
+                Expr::Aggregate { field: None, .. } => AggregationColumn::DocCount,
+                Expr::Aggregate {
+                    function,
+                    field: Some(field_name),
+                } => {
+                    self.check_fast(field_name, "SELECT")?;
+                    let metric = match function {
+                        AggregateFunction::Count => "value_count",
+                        AggregateFunction::Sum => "sum",
+                        AggregateFunction::Avg => "avg",
+                        AggregateFunction::Min => "min",
+                        AggregateFunction::Max => "max",
+                    };
+                    let name = format!("column_{ord}");
+                    metrics.insert(name.clone(), json!({ metric: {"field": field_name} }));
+                    AggregationColumn::Metric(name)
+                }
+            };
+            aggregation_columns.push(column);
+        }
+        let aggregations = if let Some(group_by) = &statement.group_by {
+            self.check_fast(group_by, "GROUP BY")?;
+            let order = match &statement.order_by {
+                None => None,
+                Some((expr, descending)) => {
+                    let direction = if *descending { "desc" } else { "asc" };
+                    // The expression is either the key, an aggregate function, or an alias.
+                    let position = statement.select.iter().position(|item| {
+                        item.expr == *expr
+                            || matches!(expr, Expr::Field(alias) if item.alias.as_ref() == Some(alias))
+                    });
+                    let target = match position.map(|ord| &aggregation_columns[ord]) {
+                        Some(AggregationColumn::Key) => "_key".to_string(),
+                        Some(AggregationColumn::DocCount) => "_count".to_string(),
+                        Some(AggregationColumn::Metric(name)) => name.clone(),
+                        None if *expr == Expr::Field(group_by.clone()) => "_key".to_string(),
+                        None => {
+                            return Err(unsupported(format!(
+                                "ORDER BY {expr}, which is not one of the selected items"
+                            )))
+                        }
+                    };
+                    Some(json!({ target: direction }))
+                }
+            };
+            let size = match statement.limit {
+                Some(limit) => u32::try_from(limit).unwrap_or(u32::MAX),
+                None => DEFAULT_GROUP_LIMIT,
+            };
+            let mut terms = json!({"field": group_by, "size": size});
+            if let Some(order) = order {
+                terms["order"] = order;
+            }
+            json!({ GROUP_BY_AGGREGATION: {"terms": terms, "aggs": metrics} })
+        } else {
+            if let Some((expr, _)) = &statement.order_by {
+                return Err(unsupported(format!(
+                    "ORDER BY {expr} with aggregate functions and without GROUP BY"
+                )));
+            }
+            Value::Object(metrics)
+        };
+        let aggregations: Aggregations = serde_json::from_value(aggregations)
+            .map_err(|err| QueryParserError::SyntaxError(err.to_string()))?;
+        Ok(SqlQuery {
+            query,
+            columns,
+            plan: Plan::Aggregations {
+                grouped: statement.group_by.is_some(),
+                aggregations,
+                columns: aggregation_columns,
+                limit: statement.limit,
+            },
+        })
+    }
+}
+
+fn to_json<T: serde::Serialize>(value: T) -> crate::Result<Value> {
+    serde_json::to_value(value).map_err(|err| TantivyError::InternalError(err.to_string()))
+}
+
+/// Returns the value of a column in a group, or in the aggregations of all the documents.
+fn aggregation_cell(column: &AggregationColumn, bucket: &Value, doc_count: &Value) -> Value {
+    match column {
+        AggregationColumn::Key => bucket
+            .get("key_as_string")
+            .or_else(|| bucket.get("key"))
+            .cloned()
+            .unwrap_or(Value::Null),
+        AggregationColumn::DocCount => doc_count.clone(),
+        AggregationColumn::Metric(name) => bucket[name.as_str()]["value"].clone(),
+    }
+}
+
+impl SqlQuery {
+    /// Returns the query matching the documents of the `WHERE` clause.
+    pub fn query(&self) -> &dyn Query {
+        self.query.as_ref()
+    }
+
+    /// Returns the aggregations computing the aggregate functions and the groups, if any.
+    pub fn aggregations(&self) -> Option<&Aggregations> {
+        match &self.plan {
+            Plan::Documents { .. } => None,
+            Plan::Aggregations { aggregations, .. } => Some(aggregations),
+        }
+    }
+
+    /// Runs the statement.
+    pub fn execute(&self, searcher: &Searcher) -> crate::Result<SqlRows> {
+        let rows = match &self.plan {
+            Plan::Documents {
+                fields,
+                order_by,
+                limit,
+            } => {
+                let limit = limit.unwrap_or(searcher.num_docs() as usize);
+                if limit == 0 {
+                    Vec::new()
+                } else {
+                    let doc_addresses = self.search_documents(searcher, order_by, limit)?;
+                    let mut rows = Vec::with_capacity(doc_addresses.len());
+                    for doc_address in doc_addresses {
+                        let doc = searcher.doc(doc_address)?;
+                        let row = fields
+                            .iter()
+                            .map(|&field| {
+                                let mut values = doc.get_all(field).collect::<Vec<_>>();
+                                match values.len() {
+                                    0 => Ok(Value::Null),
+                                    1 => to_json(values.pop().unwrap()),
+                                    _ => to_json(values),
+                                }
+                            })
+                            .collect::<crate::Result<Vec<_>>>()?;
+                        rows.push(row);
+                    }
+                    rows
+                }
+            }
+            Plan::Aggregations {
+                grouped,
+                aggregations,
+                columns,
+                limit,
+            } => {
+                let collector =
+                    AggregationCollector::from_aggs(aggregations.clone(), Default::default());
+                let (count, aggregation_results) =
+                    searcher.search(self.query.as_ref(), &(Count, collector))?;
+                let results = to_json(aggregation_results)?;
+                let mut rows: Vec<Vec<Value>> = if *grouped {
+                    let buckets = results[GROUP_BY_AGGREGATION]["buckets"]
+                        .as_array()
+                        .cloned()
+                        .unwrap_or_default();
+                    buckets
+                        .iter()
+                        .map(|bucket| {
+                            columns
+                                .iter()
+                                .map(|column| {
+                                    aggregation_cell(column, bucket, &bucket["doc_count"])
+                                })
+                                .collect()
+                        })
+                        .collect()
+                } else {
+                    let row = columns
+                        .iter()
+                        .map(|column| aggregation_cell(column, &results, &Value::from(count)))
+                        .collect();
+                    vec![row]
+                };
+                if let Some(limit) = limit {
+                    rows.truncate(*limit);
+                }
+                rows
+            }
+        };
+        Ok(SqlRows {
+            columns: self.columns.clone(),
+            rows,
+        })
+    }
+
+    fn search_documents(
+        &self,
+        searcher: &Searcher,
+        order_by: &Option<(String, bool)>,
+        limit: usize,
+    ) -> crate::Result<Vec<DocAddress>> {
+        let top_docs = TopDocs::with_limit(limit);
+        let Some((field_name, descending)) = order_by else {
+            let top_docs = searcher.search(self.query.as_ref(), &top_docs)?;
+            return Ok(top_docs
+                .into_iter()
+                .map(|(_, doc_address)| doc_address)
+                .collect());
+        };
+        // The fast field is read in its u64 representation, whose order is the order of the
+        // values. The documents without value come last.
+        let field_name = field_name.clone();
+        let descending = *descending;
+        let top_docs = top_docs.custom_score(move |segment_reader: &SegmentReader| {
+            let column_opt = segment_reader
+                .fast_fields()
+                .u64_lenient(&field_name)
+                .ok()
+                .flatten()
+                .map(|(column, _)| column);
+            move |doc| {
+                let value = column_opt.as_ref().and_then(|column| column.first(doc));
+                // The top documents have the highest scores.
+                value.map(|value| Reverse(if descending { !value } else { value }))
+            }
+        });
+        let top_docs = searcher.search(self.query.as_ref(), &top_docs)?;
+        Ok(top_docs
+            .into_iter()
+            .map(|(_, doc_address)| doc_address)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::SqlQueryParser;
+    use crate::query::QueryParserError;
+    use crate::schema::{Schema, FAST, INDEXED, STORED, STRING, TEXT};
+    use crate::{doc, Index};
+
+    fn create_index() -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let genre = schema_builder.add_text_field("genre", STRING | FAST);
+        let year = schema_builder.add_i64_field("year", INDEXED | STORED | FAST);
+        let price = schema_builder.add_f64_field("price", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(
+            title => "The Old Man and the Sea",
+            genre => "novel",
+            year => 1952i64,
+            price => 10.0,
+        ))?;
+        index_writer.add_document(
+            doc!(title => "A Moveable Feast", genre => "memoir", year => 1964i64, price => 12.0),
+        )?;
+        index_writer
+            .add_document(doc!(title => "The Garden of Eden", genre => "novel", year => 1986i64))?;
+        index_writer.add_document(doc!(title => "Islands in the Stream", genre => "novel"))?;
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    #[test]
+    fn test_sql_select() -> crate::Result<()> {
+        let index = create_index()?;
+        let searcher = index.reader()?.searcher();
+        let parser = SqlQueryParser::for_index(&index);
+        let rows = |sql: &str| -> crate::Result<Vec<Vec<serde_json::Value>>> {
+            Ok(parser.parse(sql)?.execute(&searcher)?.rows)
+        };
+        assert_eq!(
+            rows("SELECT year FROM books WHERE genre = 'novel' ORDER BY year DESC")?,
+            [[json!(1986)], [json!(1952)], [json!(null)]]
+        );
+        assert_eq!(
+            rows("SELECT year WHERE year IS NOT NULL ORDER BY year LIMIT 2")?,
+            [[json!(1952)], [json!(1964)]]
+        );
+        assert_eq!(
+            rows("SELECT year WHERE year BETWEEN 1960 AND 1990 AND NOT genre IN ('memoir')")?,
+            [[json!(1986)]]
+        );
+        assert_eq!(
+            rows("SELECT title WHERE MATCH(title, 'sea stream') ORDER BY year")?,
+            [
+                [json!("The Old Man and the Sea")],
+                [json!("Islands in the Stream")]
+            ]
+        );
+        assert_eq!(
+            rows("SELECT title WHERE genre LIKE 'mem%' OR year < 1960")?.len(),
+            2
+        );
+        assert_eq!(rows("SELECT year WHERE year IS NULL")?, [[json!(null)]]);
+        assert_eq!(rows("SELECT * LIMIT 0")?.len(), 0);
+        let sql_rows = parser
+            .parse("SELECT * WHERE year = 1964")?
+            .execute(&searcher)?;
+        assert_eq!(sql_rows.columns, ["title", "year"]);
+        assert_eq!(sql_rows.rows, [[json!("A Moveable Feast"), json!(1964)]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sql_aggregations() -> crate::Result<()> {
+        let index = create_index()?;
+        let searcher = index.reader()?.searcher();
+        let parser = SqlQueryParser::for_index(&index);
+        let sql_query = parser.parse(
+            "SELECT genre AS g, COUNT(*) AS n, SUM(price), MIN(year) GROUP BY genre ORDER BY n",
+        )?;
+        assert!(sql_query.aggregations().is_some());
+        let sql_rows = sql_query.execute(&searcher)?;
+        assert_eq!(sql_rows.columns, ["g", "n", "sum(price)", "min(year)"]);
+        assert_eq!(
+            sql_rows.rows,
+            [
+                [json!("memoir"), json!(1), json!(12.0), json!(1964.0)],
+                [json!("novel"), json!(3), json!(10.0), json!(1952.0)],
+            ]
+        );
+        let sql_rows = parser
+            .parse("SELECT genre GROUP BY genre ORDER BY genre DESC LIMIT 1")?
+            .execute(&searcher)?;
+        assert_eq!(sql_rows.rows, [[json!("novel")]]);
+        let sql_rows = parser
+            .parse("SELECT COUNT(*), COUNT(year), AVG(price) WHERE genre = 'novel'")?
+            .execute(&searcher)?;
+        assert_eq!(sql_rows.rows, [[json!(3), json!(2.0), json!(10.0)]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sql_unsupported() -> crate::Result<()> {
+        let index = create_index()?;
+        let parser = SqlQueryParser::for_index(&index);
+        let error = |sql: &str| parser.parse(sql).err().unwrap();
+        assert_eq!(
+            error("SELECT missing"),
+            QueryParserError::FieldDoesNotExist("missing".to_string())
+        );
+        assert!(matches!(
+            error("SELECT price"),
+            QueryParserError::UnsupportedQuery(_)
+        ));
+        assert!(matches!(
+            error("SELECT title ORDER BY title"),
+            QueryParserError::UnsupportedQuery(_)
+        ));
+        assert!(matches!(
+            error("SELECT title, COUNT(*) GROUP BY genre"),
+            QueryParserError::UnsupportedQuery(_)
+        ));
+        assert!(matches!(
+            error("SELECT COUNT(*) ORDER BY year"),
+            QueryParserError::UnsupportedQuery(_)
+        ));
+        assert!(matches!(
+            error("SELECT title WHERE title LIKE 'a*'"),
+            QueryParserError::UnsupportedQuery(_)
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,668 @@
+use serde_json::Value;
+
+use crate::query::QueryParserError;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// A bare identifier, which may be a keyword.
+    Word(String),
+    /// An identifier between double quotes, which is never a keyword.
+    QuotedIdent(String),
+    String(String),
+    Number(String),
+    Symbol(&'static str),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "{word}"),
+            Token::QuotedIdent(ident) => write!(f, "{ident:?}"),
+            Token::String(text) => write!(f, "'{text}'"),
+            Token::Number(number) => write!(f, "{number}"),
+            Token::Symbol(symbol) => write!(f, "{symbol}"),
+        }
+    }
+}
+
+const SYMBOLS: [&str; 12] = [
+    "<>", "!=", "<=", ">=", "(", ")", ",", "*", "=", "<", ">", ";",
+];
+
+fn tokenize(sql: &str) -> Result<Vec<Token>, QueryParserError> {
+    let mut tokens = Vec::new();
+    let mut chars = sql.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '\'' || c == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    // A doubled quote stands for the quote itself.
+                    Some((_, quote)) if quote == c => {
+                        if chars.peek().map(|&(_, next)| next) == Some(c) {
+                            chars.next();
+                            text.push(c);
+                        } else {
+                            break;
+                        }
+                    }
+                    Some((_, other)) => text.push(other),
+                    None => {
+                        return Err(QueryParserError::SyntaxError(format!(
+                            "Unterminated quote starting at offset {start}"
+                        )))
+                    }
+                }
+            }
+            tokens.push(if c == '\'' {
+                Token::String(text)
+            } else {
+                Token::QuotedIdent(text)
+            });
+        } else if c.is_ascii_digit() || c == '-' || c == '.' {
+            let mut end = start;
+            let mut previous = None;
+            while let Some(&(offset, c)) = chars.peek() {
+                let is_exponent_sign =
+                    (c == '-' || c == '+') && matches!(previous, Some('e') | Some('E'));
+                if c.is_ascii_alphanumeric() || c == '.' || is_exponent_sign || offset == start {
+                    end = offset + c.len_utf8();
+                    previous = Some(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            tokens.push(Token::Number(sql[start..end].to_string()));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some(&(offset, c)) = chars.peek() {
+                if c.is_alphanumeric() || c == '_' || c == '.' {
+                    end = offset + c.len_utf8();
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            tokens.push(Token::Word(sql[start..end].to_string()));
+        } else if let Some(symbol) = SYMBOLS
+            .iter()
+            .find(|symbol| sql[start..].starts_with(**symbol))
+        {
+            for _ in 0..symbol.len() {
+                chars.next();
+            }
+            tokens.push(Token::Symbol(symbol));
+        } else {
+            return Err(QueryParserError::SyntaxError(format!(
+                "Unexpected character {c:?} at offset {start}"
+            )));
+        }
+    }
+    Ok(tokens)
+}
+
+/// The aggregate functions of the `SELECT` clause.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl AggregateFunction {
+    fn from_name(name: &str) -> Option<AggregateFunction> {
+        match name.to_ascii_lowercase().as_str() {
+            "count" => Some(AggregateFunction::Count),
+            "sum" => Some(AggregateFunction::Sum),
+            "avg" => Some(AggregateFunction::Avg),
+            "min" => Some(AggregateFunction::Min),
+            "max" => Some(AggregateFunction::Max),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AggregateFunction::Count => "count",
+            AggregateFunction::Sum => "sum",
+            AggregateFunction::Avg => "avg",
+            AggregateFunction::Min => "min",
+            AggregateFunction::Max => "max",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Expr {
+    Field(String),
+    /// An aggregate function, over a field, or over all the documents for `COUNT(*)`.
+    Aggregate {
+        function: AggregateFunction,
+        field: Option<String>,
+    },
+}
+
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Field(field) => write!(f, "{field}"),
+            Expr::Aggregate { function, field } => {
+                write!(
+                    f,
+                    "{}({})",
+                    function.name(),
+                    field.as_deref().unwrap_or("*")
+                )
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SelectItem {
+    pub expr: Expr,
+    pub alias: Option<String>,
+}
+
+impl SelectItem {
+    pub fn column_name(&self) -> String {
+        self.alias.clone().unwrap_or_else(|| self.expr.to_string())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Predicate {
+    And(Vec<Predicate>),
+    Or(Vec<Predicate>),
+    Not(Box<Predicate>),
+    Compare {
+        field: String,
+        op: CompareOp,
+        value: Value,
+    },
+    In {
+        field: String,
+        values: Vec<Value>,
+    },
+    Between {
+        field: String,
+        low: Value,
+        high: Value,
+    },
+    Like {
+        field: String,
+        pattern: String,
+    },
+    IsNotNull {
+        field: String,
+    },
+    /// A full text search, as `MATCH(field, 'text')`.
+    Match {
+        field: String,
+        text: String,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Statement {
+    /// The selected items, empty for `SELECT *`.
+    pub select: Vec<SelectItem>,
+    pub predicate: Option<Predicate>,
+    pub group_by: Option<String>,
+    /// The expression to order by, and whether the order is descending.
+    pub order_by: Option<(Expr, bool)>,
+    pub limit: Option<usize>,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn error(&self, expected: &str) -> QueryParserError {
+        match self.tokens.get(self.position) {
+            Some(token) => {
+                QueryParserError::SyntaxError(format!("Expected {expected}, found {token}"))
+            }
+            None => QueryParserError::SyntaxError(format!("Expected {expected}, found the end")),
+        }
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let is_keyword = self.peek_keyword(keyword);
+        if is_keyword {
+            self.position += 1;
+        }
+        is_keyword
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), QueryParserError> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.error(keyword))
+        }
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        let is_symbol = matches!(self.peek(), Some(Token::Symbol(actual)) if *actual == symbol);
+        if is_symbol {
+            self.position += 1;
+        }
+        is_symbol
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), QueryParserError> {
+        if self.eat_symbol(symbol) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("'{symbol}'")))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, QueryParserError> {
+        match self.peek() {
+            Some(Token::Word(word)) if !is_reserved(word) => {
+                let word = word.clone();
+                self.position += 1;
+                Ok(word)
+            }
+            Some(Token::QuotedIdent(ident)) => {
+                let ident = ident.clone();
+                self.position += 1;
+                Ok(ident)
+            }
+            _ => Err(self.error("a field name")),
+        }
+    }
+
+    fn literal(&mut self) -> Result<Value, QueryParserError> {
+        match self.peek().cloned() {
+            Some(Token::String(text)) => {
+                self.position += 1;
+                Ok(Value::String(text))
+            }
+            Some(Token::Number(number)) => {
+                let value = serde_json::from_str::<Value>(&number)
+                    .ok()
+                    .filter(Value::is_number)
+                    .ok_or_else(|| {
+                        QueryParserError::SyntaxError(format!("Invalid number {number}"))
+                    })?;
+                self.position += 1;
+                Ok(value)
+            }
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("true") => {
+                self.position += 1;
+                Ok(Value::Bool(true))
+            }
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("false") => {
+                self.position += 1;
+                Ok(Value::Bool(false))
+            }
+            _ => Err(self.error("a string, a number or a boolean")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, QueryParserError> {
+        match self.peek().cloned() {
+            Some(Token::String(text)) => {
+                self.position += 1;
+                Ok(text)
+            }
+            _ => Err(self.error("a string")),
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, QueryParserError> {
+        if let Some(Token::Word(word)) = self.peek() {
+            let is_call = matches!(self.tokens.get(self.position + 1), Some(Token::Symbol("(")));
+            if let Some(function) = AggregateFunction::from_name(word).filter(|_| is_call) {
+                self.position += 2;
+                let field = if function == AggregateFunction::Count && self.eat_symbol("*") {
+                    None
+                } else {
+                    Some(self.ident()?)
+                };
+                self.expect_symbol(")")?;
+                return Ok(Expr::Aggregate { function, field });
+            }
+        }
+        Ok(Expr::Field(self.ident()?))
+    }
+
+    fn select_item(&mut self) -> Result<SelectItem, QueryParserError> {
+        let expr = self.expr()?;
+        let alias = if self.eat_keyword("as") {
+            Some(self.ident()?)
+        } else {
+            None
+        };
+        Ok(SelectItem { expr, alias })
+    }
+
+    fn or_predicate(&mut self) -> Result<Predicate, QueryParserError> {
+        let mut predicates = vec![self.and_predicate()?];
+        while self.eat_keyword("or") {
+            predicates.push(self.and_predicate()?);
+        }
+        Ok(if predicates.len() == 1 {
+            predicates.pop().unwrap()
+        } else {
+            Predicate::Or(predicates)
+        })
+    }
+
+    fn and_predicate(&mut self) -> Result<Predicate, QueryParserError> {
+        let mut predicates = vec![self.not_predicate()?];
+        while self.eat_keyword("and") {
+            predicates.push(self.not_predicate()?);
+        }
+        Ok(if predicates.len() == 1 {
+            predicates.pop().unwrap()
+        } else {
+            Predicate::And(predicates)
+        })
+    }
+
+    fn not_predicate(&mut self) -> Result<Predicate, QueryParserError> {
+        if self.eat_keyword("not") {
+            return Ok(Predicate::Not(Box::new(self.not_predicate()?)));
+        }
+        if self.eat_symbol("(") {
+            let predicate = self.or_predicate()?;
+            self.expect_symbol(")")?;
+            return Ok(predicate);
+        }
+        if self.peek_keyword("match")
+            && matches!(self.tokens.get(self.position + 1), Some(Token::Symbol("(")))
+        {
+            self.position += 2;
+            let field = self.ident()?;
+            self.expect_symbol(",")?;
+            let text = self.string()?;
+            self.expect_symbol(")")?;
+            return Ok(Predicate::Match { field, text });
+        }
+        let field = self.ident()?;
+        if self.eat_keyword("is") {
+            let negated = self.eat_keyword("not");
+            self.expect_keyword("null")?;
+            let predicate = Predicate::IsNotNull { field };
+            return Ok(negate(predicate, !negated));
+        }
+        let negated = self.eat_keyword("not");
+        let predicate = if self.eat_keyword("in") {
+            self.expect_symbol("(")?;
+            let mut values = vec![self.literal()?];
+            while self.eat_symbol(",") {
+                values.push(self.literal()?);
+            }
+            self.expect_symbol(")")?;
+            Predicate::In { field, values }
+        } else if self.eat_keyword("between") {
+            let low = self.literal()?;
+            self.expect_keyword("and")?;
+            let high = self.literal()?;
+            Predicate::Between { field, low, high }
+        } else if self.eat_keyword("like") {
+            let pattern = self.string()?;
+            Predicate::Like { field, pattern }
+        } else if negated {
+            return Err(self.error("IN, BETWEEN or LIKE"));
+        } else {
+            let op = match self.next() {
+                Some(Token::Symbol("=")) => CompareOp::Eq,
+                Some(Token::Symbol("!=")) | Some(Token::Symbol("<>")) => CompareOp::Ne,
+                Some(Token::Symbol("<")) => CompareOp::Lt,
+                Some(Token::Symbol("<=")) => CompareOp::Le,
+                Some(Token::Symbol(">")) => CompareOp::Gt,
+                Some(Token::Symbol(">=")) => CompareOp::Ge,
+                _ => {
+                    self.position -= 1;
+                    return Err(self.error("a comparison operator"));
+                }
+            };
+            let value = self.literal()?;
+            return Ok(Predicate::Compare { field, op, value });
+        };
+        Ok(negate(predicate, negated))
+    }
+
+    fn statement(&mut self) -> Result<Statement, QueryParserError> {
+        self.expect_keyword("select")?;
+        let mut select = Vec::new();
+        if !self.eat_symbol("*") {
+            select.push(self.select_item()?);
+            while self.eat_symbol(",") {
+                select.push(self.select_item()?);
+            }
+        }
+        if self.eat_keyword("from") {
+            // There is a single index, so the name of the table does not matter.
+            self.ident()?;
+        }
+        let predicate = if self.eat_keyword("where") {
+            Some(self.or_predicate()?)
+        } else {
+            None
+        };
+        let group_by = if self.eat_keyword("group") {
+            self.expect_keyword("by")?;
+            Some(self.ident()?)
+        } else {
+            None
+        };
+        let order_by = if self.eat_keyword("order") {
+            self.expect_keyword("by")?;
+            let expr = self.expr()?;
+            let descending = if self.eat_keyword("desc") {
+                true
+            } else {
+                self.eat_keyword("asc");
+                false
+            };
+            Some((expr, descending))
+        } else {
+            None
+        };
+        let limit = if self.eat_keyword("limit") {
+            match self.next() {
+                Some(Token::Number(number)) => Some(number.parse().map_err(|_| {
+                    QueryParserError::SyntaxError(format!("Invalid limit {number}"))
+                })?),
+                _ => {
+                    self.position -= 1;
+                    return Err(self.error("a limit"));
+                }
+            }
+        } else {
+            None
+        };
+        self.eat_symbol(";");
+        if self.peek().is_some() {
+            return Err(self.error("the end of the statement"));
+        }
+        Ok(Statement {
+            select,
+            predicate,
+            group_by,
+            order_by,
+            limit,
+        })
+    }
+}
+
+fn negate(predicate: Predicate, negated: bool) -> Predicate {
+    if negated {
+        Predicate::Not(Box::new(predicate))
+    } else {
+        predicate
+    }
+}
+
+const RESERVED: [&str; 17] = [
+    "select", "from", "where", "group", "order", "by", "limit", "as", "and", "or", "not", "in",
+    "between", "like", "is", "null", "match",
+];
+
+fn is_reserved(word: &str) -> bool {
+    RESERVED
+        .iter()
+        .any(|keyword| word.eq_ignore_ascii_case(keyword))
+}
+
+/// Parses a statement, of the form
+/// `SELECT items [FROM index] [WHERE predicate] [GROUP BY field] [ORDER BY expr [ASC|DESC]]
+/// [LIMIT n]`.
+pub(crate) fn parse_statement(sql: &str) -> Result<Statement, QueryParserError> {
+    let mut parser = Parser {
+        tokens: tokenize(sql)?,
+        position: 0,
+    };
+    parser.statement()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_statement() {
+        let statement = parse_statement(
+            "select title, count(*) AS \"num docs\" from books WHERE year >= 2000 and (tag IN \
+             ('a', 'b') OR NOT MATCH(title, 'it''s')) AND price is not null GROUP BY title ORDER \
+             BY count(*) desc LIMIT 5;",
+        )
+        .unwrap();
+        assert_eq!(
+            statement.select,
+            vec![
+                SelectItem {
+                    expr: Expr::Field("title".to_string()),
+                    alias: None,
+                },
+                SelectItem {
+                    expr: Expr::Aggregate {
+                        function: AggregateFunction::Count,
+                        field: None,
+                    },
+                    alias: Some("num docs".to_string()),
+                },
+            ]
+        );
+        assert_eq!(
+            statement.predicate,
+            Some(Predicate::And(vec![
+                Predicate::Compare {
+                    field: "year".to_string(),
+                    op: CompareOp::Ge,
+                    value: json!(2000),
+                },
+                Predicate::Or(vec![
+                    Predicate::In {
+                        field: "tag".to_string(),
+                        values: vec![json!("a"), json!("b")],
+                    },
+                    Predicate::Not(Box::new(Predicate::Match {
+                        field: "title".to_string(),
+                        text: "it's".to_string(),
+                    })),
+                ]),
+                Predicate::IsNotNull {
+                    field: "price".to_string(),
+                },
+            ]))
+        );
+        assert_eq!(statement.group_by, Some("title".to_string()));
+        assert_eq!(
+            statement.order_by,
+            Some((
+                Expr::Aggregate {
+                    function: AggregateFunction::Count,
+                    field: None,
+                },
+                true
+            ))
+        );
+        assert_eq!(statement.limit, Some(5));
+
+        let statement =
+            parse_statement("SELECT * WHERE price NOT BETWEEN -1.5 AND 2e3 AND flag = true")
+                .unwrap();
+        assert!(statement.select.is_empty());
+        assert_eq!(
+            statement.predicate,
+            Some(Predicate::And(vec![
+                Predicate::Not(Box::new(Predicate::Between {
+                    field: "price".to_string(),
+                    low: json!(-1.5),
+                    high: json!(2000.0),
+                })),
+                Predicate::Compare {
+                    field: "flag".to_string(),
+                    op: CompareOp::Eq,
+                    value: json!(true),
+                },
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_parse_statement_errors() {
+        let error = |sql: &str| match parse_statement(sql) {
+            Err(QueryParserError::SyntaxError(message)) => message,
+            other => panic!("Expected a syntax error, got {other:?}"),
+        };
+        assert_eq!(error("SELECT"), "Expected a field name, found the end");
+        assert_eq!(
+            error("SELECT a WHERE b == 1"),
+            "Expected a string, a number or a boolean, found ="
+        );
+        assert_eq!(
+            error("SELECT a LIMIT 1 2"),
+            "Expected the end of the statement, found 2"
+        );
+        assert_eq!(
+            error("SELECT a WHERE b = 'c"),
+            "Unterminated quote starting at offset 19"
+        );
+        assert_eq!(
+            error("SELECT a WHERE b ~ 1"),
+            "Unexpected character '~' at offset 17"
+        );
+    }
+}
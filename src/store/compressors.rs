@@ -81,6 +81,16 @@ pub struct ZstdCompressor {
 }
 
 impl ZstdCompressor {
+    /// Creates a zstd compressor with the given compression level.
+    ///
+    /// Higher levels give smaller blocks, at the cost of a slower compression.
+    /// Decompression speed is mostly unaffected by the level.
+    pub fn with_compression_level(compression_level: i32) -> ZstdCompressor {
+        ZstdCompressor {
+            compression_level: Some(compression_level),
+        }
+    }
+
     fn deser_from_str(val: &str) -> Result<ZstdCompressor, String> {
         if !val.starts_with("zstd") {
             return Err(format!("needs to start with zstd, but got {val}"));
@@ -143,6 +153,17 @@ impl Default for Compressor {
 }
 
 impl Compressor {
+    /// Returns true if the feature flag required by the compressor is enabled.
+    pub fn is_available(&self) -> bool {
+        match self {
+            Self::None => true,
+            Self::Lz4 => cfg!(feature = "lz4-compression"),
+            Self::Brotli => cfg!(feature = "brotli-compression"),
+            Self::Snappy => cfg!(feature = "snappy-compression"),
+            Self::Zstd(_) => cfg!(feature = "zstd-compression"),
+        }
+    }
+
     #[inline]
     pub(crate) fn compress_into(
         &self,
@@ -222,6 +243,20 @@ mod tests {
             ZstdCompressor::deser_from_str(&ZstdCompressor::default().ser_to_string()).unwrap(),
             ZstdCompressor::default()
         );
+        assert_eq!(
+            ZstdCompressor::with_compression_level(9).ser_to_string(),
+            "zstd(compression_level=9)"
+        );
+    }
+
+    #[test]
+    fn test_compressor_is_available() {
+        assert!(Compressor::None.is_available());
+        assert!(Compressor::default().is_available());
+        assert_eq!(
+            Compressor::Zstd(ZstdCompressor::default()).is_available(),
+            cfg!(feature = "zstd-compression")
+        );
     }
 
     #[test]
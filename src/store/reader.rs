@@ -14,7 +14,7 @@ use super::Decompressor;
 use crate::directory::FileSlice;
 use crate::error::DataCorruption;
 use crate::fastfield::AliveBitSet;
-use crate::schema::Document;
+use crate::schema::{Document, Field};
 use crate::space_usage::StoreSpaceUsage;
 use crate::store::index::Checkpoint;
 use crate::DocId;
@@ -139,6 +139,14 @@ impl StoreReader {
         })
     }
 
+    /// Returns the part of a store file holding its skip index, which is read when opening a
+    /// [`StoreReader`].
+    pub(crate) fn skip_index_file(store_file: FileSlice) -> io::Result<FileSlice> {
+        let (footer, data_and_offset) = DocStoreFooter::extract_footer(store_file)?;
+        let (_, offset_index_file) = data_and_offset.split(footer.offset as usize);
+        Ok(offset_index_file)
+    }
+
     pub(crate) fn block_checkpoints(&self) -> impl Iterator<Item = Checkpoint> + '_ {
         self.skip_index.checkpoints()
     }
@@ -203,6 +211,18 @@ impl StoreReader {
         Ok(Document::deserialize(&mut doc_bytes)?)
     }
 
+    /// Reads a given document, keeping only the values of the given fields.
+    ///
+    /// The block of the document still needs to be decompressed, but the values of
+    /// the other fields are skipped without being decoded.
+    pub fn get_with_fields(&self, doc_id: DocId, fields: &[Field]) -> crate::Result<Document> {
+        let doc_bytes = self.get_document_bytes(doc_id)?;
+        Ok(Document::deserialize_with_fields(
+            &mut doc_bytes.as_slice(),
+            fields,
+        )?)
+    }
+
     /// Returns raw bytes of a given document.
     ///
     /// Calling `.get(doc)` is relatively costly as it requires
@@ -215,6 +235,33 @@ impl StoreReader {
         Self::get_document_bytes_from_block(block, doc_id, &checkpoint)
     }
 
+    /// Reads the given documents, returned in the order of `doc_ids`.
+    ///
+    /// The documents are read in the order of their doc ids, so that each block holding some
+    /// of them is decompressed only once, regardless of the size of the cache.
+    pub fn get_many(&self, doc_ids: &[DocId]) -> crate::Result<Vec<Document>> {
+        let mut docs: Vec<Option<Document>> = vec![None; doc_ids.len()];
+        let mut current_block: Option<(Checkpoint, Block)> = None;
+        for pos in positions_by_doc_id(doc_ids) {
+            let doc_id = doc_ids[pos];
+            let (checkpoint, block) = match current_block.take() {
+                Some((checkpoint, block)) if checkpoint.doc_range.contains(&doc_id) => {
+                    (checkpoint, block)
+                }
+                _ => {
+                    let checkpoint = self.block_checkpoint(doc_id)?;
+                    let block = self.read_block(&checkpoint)?;
+                    (checkpoint, block)
+                }
+            };
+            let mut doc_bytes =
+                Self::get_document_bytes_from_block(block.clone(), doc_id, &checkpoint)?;
+            docs[pos] = Some(Document::deserialize(&mut doc_bytes)?);
+            current_block = Some((checkpoint, block));
+        }
+        Ok(docs.into_iter().flatten().collect())
+    }
+
     /// Advanced API.
     ///
     /// In most cases use [`get_document_bytes`](Self::get_document_bytes).
@@ -306,6 +353,13 @@ impl StoreReader {
     }
 }
 
+/// Returns the positions of the doc ids, sorted by doc id.
+fn positions_by_doc_id(doc_ids: &[DocId]) -> Vec<usize> {
+    let mut positions: Vec<usize> = (0..doc_ids.len()).collect();
+    positions.sort_by_key(|&pos| doc_ids[pos]);
+    positions
+}
+
 fn block_read_index(block: &[u8], doc_pos: u32) -> crate::Result<Range<usize>> {
     let doc_pos = doc_pos as usize;
     let size_of_u32 = std::mem::size_of::<u32>();
@@ -328,7 +382,6 @@ fn block_read_index(block: &[u8], doc_pos: u32) -> crate::Result<Range<usize>> {
     Ok(start_offset..end_offset)
 }
 
-#[cfg(feature = "quickwit")]
 impl StoreReader {
     /// Advanced API.
     ///
@@ -368,6 +421,44 @@ impl StoreReader {
         let mut doc_bytes = self.get_document_bytes_async(doc_id).await?;
         Ok(Document::deserialize(&mut doc_bytes)?)
     }
+
+    /// Fetches documents asynchronously. Async version of [`get_many`](Self::get_many).
+    pub async fn get_many_async(&self, doc_ids: &[DocId]) -> crate::Result<Vec<Document>> {
+        let mut docs: Vec<Option<Document>> = vec![None; doc_ids.len()];
+        let mut current_block: Option<(Checkpoint, Block)> = None;
+        for pos in positions_by_doc_id(doc_ids) {
+            let doc_id = doc_ids[pos];
+            let (checkpoint, block) = match current_block.take() {
+                Some((checkpoint, block)) if checkpoint.doc_range.contains(&doc_id) => {
+                    (checkpoint, block)
+                }
+                _ => {
+                    let checkpoint = self.block_checkpoint(doc_id)?;
+                    let block = self.read_block_async(&checkpoint).await?;
+                    (checkpoint, block)
+                }
+            };
+            let mut doc_bytes =
+                Self::get_document_bytes_from_block(block.clone(), doc_id, &checkpoint)?;
+            docs[pos] = Some(Document::deserialize(&mut doc_bytes)?);
+            current_block = Some((checkpoint, block));
+        }
+        Ok(docs.into_iter().flatten().collect())
+    }
+
+    /// Fetches a document asynchronously, keeping only the values of the given fields.
+    /// Async version of [`get_with_fields`](Self::get_with_fields).
+    pub async fn get_with_fields_async(
+        &self,
+        doc_id: DocId,
+        fields: &[Field],
+    ) -> crate::Result<Document> {
+        let doc_bytes = self.get_document_bytes_async(doc_id).await?;
+        Ok(Document::deserialize_with_fields(
+            &mut doc_bytes.as_slice(),
+            fields,
+        )?)
+    }
 }
 
 #[cfg(test)]
@@ -430,4 +521,82 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_store_get_many() -> crate::Result<()> {
+        let directory = RamDirectory::create();
+        let path = Path::new("store");
+        let writer = directory.open_write(path)?;
+        let schema = write_lorem_ipsum_store(writer, 500, Compressor::default(), BLOCK_SIZE, true);
+        let title = schema.get_field("title").unwrap();
+        let store_file = directory.open_read(path)?;
+        // Without a cache, each block is only read once.
+        let store = StoreReader::open(store_file, 0)?;
+        let docs = store.get_many(&[499, 0, 1, 499])?;
+        let titles: Vec<Option<&str>> =
+            docs.iter().map(|doc| get_text_field(doc, &title)).collect();
+        assert_eq!(
+            titles,
+            vec![
+                Some("Doc 499"),
+                Some("Doc 0"),
+                Some("Doc 1"),
+                Some("Doc 499")
+            ]
+        );
+        assert_eq!(store.cache_stats().cache_misses, 2);
+        assert!(store.get_many(&[])?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_get_with_fields() -> crate::Result<()> {
+        use crate::schema::{Facet, Schema, FAST, STORED};
+        use crate::Index;
+
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let body = schema_builder.add_text_field("body", STORED);
+        let attributes = schema_builder.add_json_field("attributes", STORED);
+        let data = schema_builder.add_bytes_field("data", STORED);
+        let facet = schema_builder.add_facet_field("facet", STORED);
+        let num = schema_builder.add_u64_field("num", STORED | FAST);
+        let url = schema_builder.add_text_field("url", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            let attributes_val = serde_json::json!({"color": "red", "sizes": [1, 2]});
+            index_writer.add_document(doc!(
+                title => "First title",
+                body => "a long body ".repeat(100),
+                attributes => attributes_val.as_object().unwrap().clone(),
+                data => vec![1u8, 2, 3],
+                facet => Facet::from("/a/b"),
+                num => 4u64,
+                url => "http://first",
+                title => "Second title",
+            ))?;
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let store_reader = searcher.segment_reader(0).get_store_reader(0)?;
+        let doc = store_reader.get_with_fields(0, &[title, url])?;
+        assert_eq!(doc.len(), 3);
+        let titles: Vec<&str> = doc
+            .get_all(title)
+            .flat_map(|value| value.as_text())
+            .collect();
+        assert_eq!(titles, vec!["First title", "Second title"]);
+        assert_eq!(get_text_field(&doc, &url), Some("http://first"));
+        assert!(doc.get_first(body).is_none());
+
+        let doc = searcher.doc_with_fields(crate::DocAddress::new(0, 0), &[num, data])?;
+        assert_eq!(doc.get_first(num).and_then(|value| value.as_u64()), Some(4));
+        assert_eq!(
+            doc.get_first(data).and_then(|value| value.as_bytes()),
+            Some(&[1u8, 2, 3][..])
+        );
+        assert_eq!(doc.len(), 2);
+        Ok(())
+    }
 }
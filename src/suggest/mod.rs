@@ -0,0 +1,394 @@
+//! Suggestions complete a prefix typed by a user with the values of a completion field, e.g.
+//! to autocomplete a search box without a separate service.
+//!
+//! They are only recorded for the text fields configured with
+//! [`TextOptions::set_completion`](crate::schema::TextOptions::set_completion), and are
+//! returned by a [`CompletionSuggester`]. Each value of the field is a suggestion for the
+//! prefixes of its text, ranked by the weight of the document and optionally filtered by its
+//! contexts, e.g. a category or a language.
+//!
+//! The completions of a segment are recorded in an FST when the segment is serialized. Their
+//! keys are made of:
+//! - a context prefix: a `0` byte for all of the completions, or the ordinal of the context field
+//!   plus one, followed by the value of the context and a `0` byte, for the completions of a
+//!   document with this context,
+//! - the lowercased text of the completion, a `0` byte, the text of the completion and a `0` byte,
+//! - the document, as a big endian `u32`.
+//!
+//! The weight of the completion is the output of the FST.
+//!
+//! ```rust
+//! use tantivy::schema::{CompletionOptions, Schema, TextOptions, FAST, STORED, STRING};
+//! use tantivy::suggest::CompletionSuggester;
+//! use tantivy::{doc, Index};
+//!
+//! # fn main() -> tantivy::Result<()> {
+//! let mut schema_builder = Schema::builder();
+//! let completion_options = CompletionOptions::default()
+//!     .set_weight_field("popularity")
+//!     .add_context_field("category");
+//! let title = schema_builder.add_text_field(
+//!     "title",
+//!     TextOptions::from(STORED).set_completion(completion_options),
+//! );
+//! let popularity = schema_builder.add_u64_field("popularity", FAST);
+//! let category = schema_builder.add_text_field("category", STRING);
+//! let index = Index::create_in_ram(schema_builder.build());
+//! let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+//! for (text, weight, context) in [
+//!     ("Harry Potter", 10u64, "books"),
+//!     ("Hamlet", 5, "books"),
+//!     ("Harry Potter", 20, "movies"),
+//! ] {
+//!     index_writer.add_document(doc!(title => text, popularity => weight, category => context))?;
+//! }
+//! index_writer.commit()?;
+//!
+//! let searcher = index.reader()?.searcher();
+//! let mut suggester = CompletionSuggester::new(title);
+//! let suggestions = suggester.suggest(&searcher, "ha")?;
+//! let texts: Vec<(&str, u64)> = suggestions
+//!     .iter()
+//!     .map(|suggestion| (suggestion.text(), suggestion.weight()))
+//!     .collect();
+//! assert_eq!(texts, vec![("Harry Potter", 20), ("Harry Potter", 10), ("Hamlet", 5)]);
+//!
+//! suggester.add_context(category, "books");
+//! let suggestions = suggester.suggest(&searcher, "ha")?;
+//! let texts: Vec<&str> = suggestions.iter().map(|suggestion| suggestion.text()).collect();
+//! assert_eq!(texts, vec!["Harry Potter", "Hamlet"]);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The [`TermSuggester`] suggests corrections of the misspelled terms of a query string
+//! instead, from the terms of the term dictionary of a text field, and the [`PhraseSuggester`]
+//! combines them into corrections of the whole query string.
+mod phrase_suggester;
+mod reader;
+mod serializer;
+mod term_suggester;
+mod writer;
+
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+pub use self::phrase_suggester::{Collate, PhraseSuggester, PhraseSuggestion};
+pub use self::reader::{CompletionReader, CompletionReaders};
+pub use self::serializer::CompletionsSerializer;
+pub use self::term_suggester::{TermSuggester, TermSuggestion, TermSuggestions};
+pub(crate) use self::writer::resolve_completion_fields;
+pub use self::writer::CompletionsWriter;
+use crate::schema::Field;
+use crate::{DocAddress, Searcher};
+
+const DEFAULT_LIMIT: usize = 10;
+
+/// The context prefix of the keys of all completions.
+const NO_CONTEXT: u8 = 0;
+
+/// Returns the context prefix of the keys of the completions with the given context.
+fn context_key_prefix(context_ord: usize, context: &str) -> Vec<u8> {
+    let mut key_prefix = Vec::with_capacity(context.len() + 2);
+    key_prefix.push(context_ord as u8 + 1);
+    key_prefix.extend(context.bytes().filter(|&byte| byte != 0));
+    key_prefix.push(0);
+    key_prefix
+}
+
+/// Returns the normalized text of a completion, or of a prefix to complete.
+fn completion_key_prefix(text: &str) -> Vec<u8> {
+    text.to_lowercase()
+        .bytes()
+        .filter(|&byte| byte != 0)
+        .collect()
+}
+
+/// A suggestion returned by a [`CompletionSuggester`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+    text: String,
+    weight: u64,
+    doc_address: DocAddress,
+}
+
+impl Suggestion {
+    /// Returns the text of the suggestion, i.e. the value of the completion field.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the weight of the suggestion.
+    pub fn weight(&self) -> u64 {
+        self.weight
+    }
+
+    /// Returns the address of the document the suggestion comes from.
+    pub fn doc_address(&self) -> DocAddress {
+        self.doc_address
+    }
+}
+
+/// `CompletionSuggester` suggests the values of a completion field starting with a prefix.
+///
+/// The prefix is matched case insensitively. The suggestions are sorted by decreasing weight,
+/// then by text and by document, and the suggestions of the deleted documents are ignored.
+pub struct CompletionSuggester {
+    field: Field,
+    limit: usize,
+    contexts: Vec<(Field, String)>,
+    skip_duplicates: bool,
+}
+
+impl CompletionSuggester {
+    /// Creates a `CompletionSuggester` for the given completion field.
+    pub fn new(field: Field) -> CompletionSuggester {
+        CompletionSuggester {
+            field,
+            limit: DEFAULT_LIMIT,
+            contexts: Vec::new(),
+            skip_duplicates: false,
+        }
+    }
+
+    /// Sets the maximum number of suggestions. It defaults to 10.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+    }
+
+    /// Only suggests the values of the documents having the value `context` for one of the
+    /// context fields of the completion field.
+    ///
+    /// When several contexts are added, the suggestions matching any of them are returned.
+    pub fn add_context(&mut self, context_field: Field, context: &str) {
+        self.contexts.push((context_field, context.to_string()));
+    }
+
+    /// If set, a text suggested by several documents is only returned once, for the document
+    /// with the highest weight.
+    pub fn set_skip_duplicates(&mut self, skip_duplicates: bool) {
+        self.skip_duplicates = skip_duplicates;
+    }
+
+    /// Returns the suggestions completing `prefix`, merged across the segments of the
+    /// searcher.
+    ///
+    /// Returns an error if the field is not a completion field, or if one of the contexts is
+    /// not a context field of the completion field.
+    pub fn suggest(&self, searcher: &Searcher, prefix: &str) -> crate::Result<Vec<Suggestion>> {
+        let schema = searcher.schema();
+        let field_entry = schema.get_field_entry(self.field);
+        let Some(completion_options) = field_entry.completion_options() else {
+            return Err(crate::TantivyError::SchemaError(format!(
+                "Field {:?} is not a completion field",
+                field_entry.name()
+            )));
+        };
+        let mut key_prefixes: Vec<Vec<u8>> = Vec::new();
+        for (context_field, context) in &self.contexts {
+            let context_field_name = schema.get_field_name(*context_field);
+            let Some(context_ord) = completion_options
+                .context_fields()
+                .iter()
+                .position(|field_name| field_name == context_field_name)
+            else {
+                return Err(crate::TantivyError::InvalidArgument(format!(
+                    "Field {context_field_name:?} is not a context field of the completion field \
+                     {:?}",
+                    field_entry.name()
+                )));
+            };
+            key_prefixes.push(context_key_prefix(context_ord, context));
+        }
+        if key_prefixes.is_empty() {
+            key_prefixes.push(vec![NO_CONTEXT]);
+        }
+        let completion_prefix = completion_key_prefix(prefix);
+        for key_prefix in &mut key_prefixes {
+            key_prefix.extend_from_slice(&completion_prefix);
+        }
+        let mut suggestions: Vec<Suggestion> = Vec::new();
+        for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+            let Some(completion_reader) =
+                segment_reader.completion_readers().get_field(self.field)?
+            else {
+                continue;
+            };
+            let alive_bitset = segment_reader.alive_bitset();
+            for key_prefix in &key_prefixes {
+                completion_reader.for_each_completion(key_prefix, |text, weight, doc| {
+                    if alive_bitset.map_or(true, |alive_bitset| alive_bitset.is_alive(doc)) {
+                        suggestions.push(Suggestion {
+                            text: text.to_string(),
+                            weight,
+                            doc_address: DocAddress::new(segment_ord as u32, doc),
+                        });
+                    }
+                });
+            }
+        }
+        suggestions.sort_by(|left, right| {
+            (Reverse(left.weight), &left.text, left.doc_address).cmp(&(
+                Reverse(right.weight),
+                &right.text,
+                right.doc_address,
+            ))
+        });
+        // A document may match several of the contexts.
+        suggestions.dedup();
+        if self.skip_duplicates {
+            let mut texts: HashSet<String> = HashSet::new();
+            suggestions.retain(|suggestion| texts.insert(suggestion.text.clone()));
+        }
+        suggestions.truncate(self.limit);
+        Ok(suggestions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CompletionSuggester;
+    use crate::schema::{CompletionOptions, Schema, TextOptions, FAST, STORED, STRING};
+    use crate::{Index, IndexSettings, IndexSortByField, Order, Term};
+
+    #[test]
+    fn test_completion_suggester() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let completion_options = CompletionOptions::default()
+            .set_weight_field("weight")
+            .add_context_field("category")
+            .add_context_field("lang");
+        let title = schema_builder.add_text_field(
+            "title",
+            TextOptions::from(STORED).set_completion(completion_options),
+        );
+        let weight = schema_builder.add_u64_field("weight", FAST);
+        let category = schema_builder.add_text_field("category", STRING);
+        let lang = schema_builder.add_text_field("lang", STRING);
+        let schema = schema_builder.build();
+        let index = Index::builder()
+            .schema(schema)
+            .settings(IndexSettings {
+                sort_by_field: Some(IndexSortByField {
+                    field: "weight".to_string(),
+                    order: Order::Asc,
+                }),
+                ..Default::default()
+            })
+            .create_in_ram()?;
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc!(
+                title => "Nirvana",
+                title => "Nevermind",
+                weight => 30u64,
+                category => "music",
+                lang => "en",
+            ))?;
+            index_writer.add_document(doc!(title => "Neige", weight => 10u64, lang => "fr"))?;
+            index_writer.commit()?;
+            index_writer.add_document(doc!(
+                title => "Never Let Me Go",
+                weight => 20u64,
+                category => "books",
+                lang => "en",
+            ))?;
+            index_writer.add_document(doc!(title => "nevermind", category => "music"))?;
+            index_writer.add_document(doc!(title => "Nevada", weight => 50u64))?;
+            index_writer.commit()?;
+        }
+        let check = |index: &Index| -> crate::Result<()> {
+            let searcher = index.reader()?.searcher();
+            let suggest = |suggester: &CompletionSuggester, prefix: &str| {
+                suggester.suggest(&searcher, prefix).map(|suggestions| {
+                    suggestions
+                        .iter()
+                        .map(|suggestion| (suggestion.text().to_string(), suggestion.weight()))
+                        .collect::<Vec<_>>()
+                })
+            };
+            let expected = |suggestions: &[(&str, u64)]| {
+                suggestions
+                    .iter()
+                    .map(|&(text, weight)| (text.to_string(), weight))
+                    .collect::<Vec<_>>()
+            };
+            let mut suggester = CompletionSuggester::new(title);
+            assert_eq!(
+                suggest(&suggester, "NEV")?,
+                expected(&[
+                    ("Nevada", 50),
+                    ("Nevermind", 30),
+                    ("Never Let Me Go", 20),
+                    ("nevermind", 0)
+                ])
+            );
+            assert_eq!(
+                suggest(&suggester, "never ")?,
+                expected(&[("Never Let Me Go", 20)])
+            );
+            assert!(suggest(&suggester, "nirvanas")?.is_empty());
+            suggester.set_limit(2);
+            assert_eq!(
+                suggest(&suggester, "n")?,
+                expected(&[("Nevada", 50), ("Nevermind", 30)])
+            );
+            suggester.set_limit(10);
+            suggester.add_context(category, "music");
+            assert_eq!(
+                suggest(&suggester, "ne")?,
+                expected(&[("Nevermind", 30), ("nevermind", 0)])
+            );
+            suggester.add_context(lang, "en");
+            assert_eq!(
+                suggest(&suggester, "ne")?,
+                expected(&[("Nevermind", 30), ("Never Let Me Go", 20), ("nevermind", 0)])
+            );
+            let mut suggester = CompletionSuggester::new(title);
+            suggester.add_context(lang, "fr");
+            assert_eq!(suggest(&suggester, "")?, expected(&[("Neige", 10)]));
+            suggester.add_context(weight, "10");
+            assert!(suggester.suggest(&searcher, "n").is_err());
+            assert!(CompletionSuggester::new(category)
+                .suggest(&searcher, "m")
+                .is_err());
+            Ok(())
+        };
+        check(&index)?;
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.delete_term(Term::from_field_text(category, "books"));
+            index_writer.commit()?;
+            let segment_ids = index.searchable_segment_ids()?;
+            index_writer.merge(&segment_ids).wait()?;
+            index_writer.add_document(doc!(
+                title => "Never Let Me Go",
+                weight => 20u64,
+                category => "books",
+                lang => "en",
+            ))?;
+            index_writer.commit()?;
+            index_writer.wait_merging_threads()?;
+        }
+        check(&index)?;
+
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc!(title => "Nevada", weight => 5u64))?;
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let mut suggester = CompletionSuggester::new(title);
+        assert_eq!(suggester.suggest(&searcher, "nevada")?.len(), 2);
+        suggester.set_skip_duplicates(true);
+        let suggestions = suggester.suggest(&searcher, "nevada")?;
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].weight(), 50);
+        let doc = searcher.doc(suggestions[0].doc_address())?;
+        assert_eq!(
+            doc.get_first(title).and_then(|value| value.as_text()),
+            Some("Nevada")
+        );
+        Ok(())
+    }
+}
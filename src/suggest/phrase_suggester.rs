@@ -0,0 +1,474 @@
+use std::collections::HashMap;
+
+use super::term_suggester::search_tokens;
+use super::TermSuggester;
+use crate::collector::Count;
+use crate::query::{BooleanQuery, Occur, PhraseQuery, Query, TermQuery};
+use crate::schema::{Field, IndexRecordOption};
+use crate::{Searcher, Term};
+
+const DEFAULT_LIMIT: usize = 5;
+const DEFAULT_MAX_ERRORS: usize = 2;
+const DEFAULT_NUM_TERM_CANDIDATES: usize = 5;
+const DEFAULT_BEAM_SIZE: usize = 10;
+const DEFAULT_REAL_WORD_ERROR_LIKELIHOOD: f64 = 0.95;
+/// The discount applied to the probability of a unigram when the preceding bigram
+/// does not appear in the index, as in the "stupid backoff" language model.
+const BACKOFF_DISCOUNT: f64 = 0.4;
+
+/// How a [`PhraseSuggester`] checks that a corrected phrase matches some document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Collate {
+    /// The corrected phrase matches the documents containing all of its terms.
+    AllTerms,
+    /// The corrected phrase matches the documents containing its terms as a phrase. This
+    /// requires the positions of the field to be indexed.
+    Phrase,
+}
+
+/// A correction of a query string returned by a [`PhraseSuggester`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct PhraseSuggestion {
+    text: String,
+    score: f64,
+}
+
+impl PhraseSuggestion {
+    /// Returns the corrected query string.
+    ///
+    /// The corrected terms replace the original terms, as indexed, and the rest of the query
+    /// string is left untouched.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the score of the correction, the logarithm of its likelihood.
+    ///
+    /// The higher the better.
+    pub fn score(&self) -> f64 {
+        self.score
+    }
+}
+
+/// A candidate for a term of the query string.
+struct Candidate {
+    text: String,
+    // The logarithm of the likelihood that the user meant this candidate.
+    channel_score: f64,
+    is_original: bool,
+}
+
+/// A partial correction of the query string, during the beam search.
+#[derive(Clone)]
+struct Correction {
+    candidate_ords: Vec<usize>,
+    score: f64,
+    num_errors: usize,
+}
+
+/// `PhraseSuggester` suggests corrections of a whole query string, to offer a "did you mean"
+/// to the user.
+///
+/// The candidates for each term of the query string are the term itself and the corrections
+/// of a [`TermSuggester`]. The combinations of candidates are ranked by:
+/// - the likelihood of each candidate: the original term if it appears in the index is considered
+///   right with a probability of 0.95 by default, the rest being shared by its corrections
+///   according to their distance,
+/// - the likelihood of the phrase according to a language model built from the document frequencies
+///   of the index. It only relies on the frequency of the terms, unless a shingle field is set, in
+///   which case the frequency of the pairs of consecutive terms is used as well.
+///
+/// Optionally, the corrections can be collated, i.e. only kept if they match some document.
+///
+/// ```rust
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::suggest::{Collate, PhraseSuggester};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let body = schema_builder.add_text_field("body", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+/// index_writer.add_document(doc!(body => "The quick brown fox"))?;
+/// index_writer.add_document(doc!(body => "A brown bear, a brow of a hill"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let mut phrase_suggester = PhraseSuggester::new(body);
+/// phrase_suggester.set_collate(Collate::Phrase);
+/// let suggestions = phrase_suggester.suggest(&searcher, "quick brwn fox")?;
+/// assert_eq!(suggestions[0].text(), "quick brown fox");
+/// # Ok(())
+/// # }
+/// ```
+pub struct PhraseSuggester {
+    field: Field,
+    shingle_field: Option<Field>,
+    shingle_separator: String,
+    term_suggester: TermSuggester,
+    limit: usize,
+    max_errors: usize,
+    beam_size: usize,
+    real_word_error_likelihood: f64,
+    collate: Option<Collate>,
+}
+
+impl PhraseSuggester {
+    /// Creates a `PhraseSuggester` for the given text field.
+    pub fn new(field: Field) -> PhraseSuggester {
+        let mut term_suggester = TermSuggester::new(field);
+        term_suggester.set_only_missing(false);
+        term_suggester.set_limit(DEFAULT_NUM_TERM_CANDIDATES);
+        PhraseSuggester {
+            field,
+            shingle_field: None,
+            shingle_separator: " ".to_string(),
+            term_suggester,
+            limit: DEFAULT_LIMIT,
+            max_errors: DEFAULT_MAX_ERRORS,
+            beam_size: DEFAULT_BEAM_SIZE,
+            real_word_error_likelihood: DEFAULT_REAL_WORD_ERROR_LIKELIHOOD,
+            collate: None,
+        }
+    }
+
+    /// Sets the field holding the shingles of the field, i.e. the same text tokenized with a
+    /// [`ShingleFilter`](crate::tokenizer::ShingleFilter) emitting pairs of tokens.
+    ///
+    /// The document frequencies of the shingles are used to rank the corrections.
+    pub fn set_shingle_field(&mut self, shingle_field: Field) {
+        self.shingle_field = Some(shingle_field);
+    }
+
+    /// Sets the separator between the tokens of a shingle. It defaults to a single space,
+    /// like [`ShingleFilter`](crate::tokenizer::ShingleFilter).
+    pub fn set_shingle_separator(&mut self, shingle_separator: &str) {
+        self.shingle_separator = shingle_separator.to_string();
+    }
+
+    /// Sets the maximum number of suggestions. It defaults to 5.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+    }
+
+    /// Sets the maximum number of corrected terms per suggestion. It defaults to 2.
+    pub fn set_max_errors(&mut self, max_errors: usize) {
+        self.max_errors = max_errors;
+    }
+
+    /// Sets the maximum Levenshtein distance of the corrections of a term, at most 2.
+    /// It defaults to 2.
+    pub fn set_max_distance(&mut self, max_distance: u8) {
+        self.term_suggester.set_max_distance(max_distance);
+    }
+
+    /// Sets the maximum number of corrections considered per term. It defaults to 5.
+    pub fn set_num_term_candidates(&mut self, num_term_candidates: usize) {
+        self.term_suggester.set_limit(num_term_candidates);
+    }
+
+    /// Sets the number of partial corrections kept after each term of the query string.
+    /// It defaults to 10.
+    ///
+    /// A larger beam finds better corrections of long query strings, at a higher cost.
+    pub fn set_beam_size(&mut self, beam_size: usize) {
+        self.beam_size = beam_size;
+    }
+
+    /// Sets the probability that a term of the query string that appears in the index is
+    /// what the user meant. It defaults to 0.95.
+    pub fn set_real_word_error_likelihood(&mut self, real_word_error_likelihood: f64) {
+        self.real_word_error_likelihood = real_word_error_likelihood;
+    }
+
+    /// Only keeps the suggestions matching some document.
+    pub fn set_collate(&mut self, collate: Collate) {
+        self.collate = Some(collate);
+    }
+
+    /// Returns the corrections of the query string, from the best to the worst.
+    ///
+    /// The query string itself is never suggested.
+    pub fn suggest(
+        &self,
+        searcher: &Searcher,
+        query_text: &str,
+    ) -> crate::Result<Vec<PhraseSuggestion>> {
+        let tokens = search_tokens(searcher, self.field, query_text)?;
+        if tokens.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut doc_freqs = DocFreqs::new(searcher);
+        let candidates: Vec<Vec<Candidate>> = tokens
+            .iter()
+            .map(|token| self.candidates(searcher, &mut doc_freqs, &token.text))
+            .collect::<crate::Result<_>>()?;
+        let mut corrections = vec![Correction {
+            candidate_ords: Vec::new(),
+            score: 0.0,
+            num_errors: 0,
+        }];
+        for (position, position_candidates) in candidates.iter().enumerate() {
+            let mut next_corrections = Vec::new();
+            for correction in &corrections {
+                for (candidate_ord, candidate) in position_candidates.iter().enumerate() {
+                    let num_errors = correction.num_errors + usize::from(!candidate.is_original);
+                    if num_errors > self.max_errors {
+                        continue;
+                    }
+                    let previous = correction
+                        .candidate_ords
+                        .last()
+                        .map(|&previous_ord| &candidates[position - 1][previous_ord].text[..]);
+                    let language_model_score =
+                        self.language_model_score(&mut doc_freqs, previous, &candidate.text)?;
+                    let mut candidate_ords = correction.candidate_ords.clone();
+                    candidate_ords.push(candidate_ord);
+                    next_corrections.push(Correction {
+                        candidate_ords,
+                        score: correction.score + candidate.channel_score + language_model_score,
+                        num_errors,
+                    });
+                }
+            }
+            next_corrections.sort_by(|left, right| right.score.total_cmp(&left.score));
+            next_corrections.truncate(self.beam_size);
+            corrections = next_corrections;
+        }
+        let mut suggestions = Vec::new();
+        for correction in corrections {
+            if correction.num_errors == 0 {
+                continue;
+            }
+            let terms: Vec<&str> = correction
+                .candidate_ords
+                .iter()
+                .zip(&candidates)
+                .map(|(&candidate_ord, position_candidates)| {
+                    &position_candidates[candidate_ord].text[..]
+                })
+                .collect();
+            if let Some(collate) = self.collate {
+                if !self.matches(searcher, collate, &terms)? {
+                    continue;
+                }
+            }
+            let mut text = String::with_capacity(query_text.len());
+            let mut offset = 0;
+            for (token, term) in tokens.iter().zip(&terms) {
+                text.push_str(&query_text[offset..token.offset_from]);
+                text.push_str(term);
+                offset = token.offset_to;
+            }
+            text.push_str(&query_text[offset..]);
+            suggestions.push(PhraseSuggestion {
+                text,
+                score: correction.score,
+            });
+            if suggestions.len() == self.limit {
+                break;
+            }
+        }
+        Ok(suggestions)
+    }
+
+    fn candidates(
+        &self,
+        searcher: &Searcher,
+        doc_freqs: &mut DocFreqs,
+        text: &str,
+    ) -> crate::Result<Vec<Candidate>> {
+        let is_real_word = doc_freqs.get(Term::from_field_text(self.field, text))? > 0;
+        let error_likelihood = if is_real_word {
+            1.0 - self.real_word_error_likelihood
+        } else {
+            1.0
+        };
+        let mut candidates = vec![Candidate {
+            text: text.to_string(),
+            channel_score: if is_real_word {
+                self.real_word_error_likelihood.ln()
+            } else {
+                0.0
+            },
+            is_original: true,
+        }];
+        for term_suggestion in self.term_suggester.suggest_term(searcher, text)? {
+            candidates.push(Candidate {
+                text: term_suggestion.text().to_string(),
+                channel_score: (error_likelihood / f64::from(term_suggestion.distance())).ln(),
+                is_original: false,
+            });
+        }
+        Ok(candidates)
+    }
+
+    /// Returns the logarithm of the probability of a term following the previous term,
+    /// according to a "stupid backoff" language model.
+    fn language_model_score(
+        &self,
+        doc_freqs: &mut DocFreqs,
+        previous: Option<&str>,
+        text: &str,
+    ) -> crate::Result<f64> {
+        if let (Some(shingle_field), Some(previous)) = (self.shingle_field, previous) {
+            let shingle = format!("{previous}{}{text}", self.shingle_separator);
+            let shingle_doc_freq = doc_freqs.get(Term::from_field_text(shingle_field, &shingle))?;
+            if shingle_doc_freq > 0 {
+                let previous_doc_freq =
+                    doc_freqs.get(Term::from_field_text(self.field, previous))?;
+                return Ok((shingle_doc_freq as f64 / previous_doc_freq.max(1) as f64)
+                    .min(1.0)
+                    .ln());
+            }
+            return Ok(BACKOFF_DISCOUNT.ln() + self.unigram_score(doc_freqs, text)?);
+        }
+        self.unigram_score(doc_freqs, text)
+    }
+
+    /// Returns the logarithm of the probability of a term, smoothed so that the terms
+    /// missing from the index are not ruled out.
+    fn unigram_score(&self, doc_freqs: &mut DocFreqs, text: &str) -> crate::Result<f64> {
+        let doc_freq = doc_freqs.get(Term::from_field_text(self.field, text))?;
+        Ok(((doc_freq + 1) as f64 / (doc_freqs.num_docs + 1) as f64).ln())
+    }
+
+    fn matches(
+        &self,
+        searcher: &Searcher,
+        collate: Collate,
+        terms: &[&str],
+    ) -> crate::Result<bool> {
+        let terms: Vec<Term> = terms
+            .iter()
+            .map(|term| Term::from_field_text(self.field, term))
+            .collect();
+        let query: Box<dyn Query> = match (collate, &terms[..]) {
+            (_, [term]) => Box::new(TermQuery::new(term.clone(), IndexRecordOption::Basic)),
+            (Collate::AllTerms, _) => Box::new(BooleanQuery::new(
+                terms
+                    .into_iter()
+                    .map(|term| {
+                        let term_query: Box<dyn Query> =
+                            Box::new(TermQuery::new(term, IndexRecordOption::Basic));
+                        (Occur::Must, term_query)
+                    })
+                    .collect(),
+            )),
+            (Collate::Phrase, _) => Box::new(PhraseQuery::new(terms)),
+        };
+        Ok(searcher.search(query.as_ref(), &Count)? > 0)
+    }
+}
+
+/// The document frequencies of the terms looked up while ranking the corrections.
+struct DocFreqs<'a> {
+    searcher: &'a Searcher,
+    num_docs: u64,
+    doc_freqs: HashMap<Term, u64>,
+}
+
+impl<'a> DocFreqs<'a> {
+    fn new(searcher: &'a Searcher) -> DocFreqs<'a> {
+        DocFreqs {
+            searcher,
+            num_docs: searcher.num_docs(),
+            doc_freqs: HashMap::new(),
+        }
+    }
+
+    fn get(&mut self, term: Term) -> crate::Result<u64> {
+        if let Some(&doc_freq) = self.doc_freqs.get(&term) {
+            return Ok(doc_freq);
+        }
+        let doc_freq = self.searcher.doc_freq(&term)?;
+        self.doc_freqs.insert(term, doc_freq);
+        Ok(doc_freq)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Collate, PhraseSuggester};
+    use crate::schema::{Schema, TextFieldIndexing, TextOptions, TEXT};
+    use crate::tokenizer::{LowerCaser, ShingleFilter, SimpleTokenizer, TextAnalyzer};
+    use crate::{Index, Searcher};
+
+    fn suggestion_texts(
+        phrase_suggester: &PhraseSuggester,
+        searcher: &Searcher,
+        query_text: &str,
+    ) -> crate::Result<Vec<String>> {
+        Ok(phrase_suggester
+            .suggest(searcher, query_text)?
+            .iter()
+            .map(|suggestion| suggestion.text().to_string())
+            .collect())
+    }
+
+    #[test]
+    fn test_phrase_suggester() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT);
+        let shingle_options = TextOptions::default()
+            .set_indexing_options(TextFieldIndexing::default().set_tokenizer("shingles"));
+        let body_shingles = schema_builder.add_text_field("body_shingles", shingle_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        index.tokenizers().register(
+            "shingles",
+            TextAnalyzer::builder(SimpleTokenizer)
+                .filter(LowerCaser)
+                .filter(ShingleFilter::new(2, 2)?.set_output_unigrams(false))
+                .build(),
+        );
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            for text in [
+                "new york city",
+                "the new york times",
+                "news from york",
+                "a new work of art",
+                "new work",
+            ] {
+                index_writer.add_document(doc!(body => text, body_shingles => text))?;
+            }
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+
+        let mut phrase_suggester = PhraseSuggester::new(body);
+        assert_eq!(
+            suggestion_texts(&phrase_suggester, &searcher, "Nwe Yorc")?[0],
+            "new york"
+        );
+        assert!(suggestion_texts(&phrase_suggester, &searcher, "")?.is_empty());
+
+        phrase_suggester.set_shingle_field(body_shingles);
+        assert_eq!(
+            suggestion_texts(&phrase_suggester, &searcher, "new yorc times!")?[0],
+            "new york times!"
+        );
+        assert!(!suggestion_texts(&phrase_suggester, &searcher, "new york")?
+            .contains(&"new york".to_string()));
+
+        phrase_suggester.set_max_errors(1);
+        assert!(suggestion_texts(&phrase_suggester, &searcher, "nwe yorc")?
+            .iter()
+            .all(|suggestion| suggestion.contains("nwe") || suggestion.contains("yorc")));
+
+        phrase_suggester.set_max_errors(2);
+        phrase_suggester.set_collate(Collate::Phrase);
+        assert_eq!(
+            suggestion_texts(&phrase_suggester, &searcher, "new yorc")?,
+            vec!["new york".to_string(), "new work".to_string()]
+        );
+        assert!(suggestion_texts(&phrase_suggester, &searcher, "the new yorc city")?.is_empty());
+        phrase_suggester.set_collate(Collate::AllTerms);
+        assert_eq!(
+            suggestion_texts(&phrase_suggester, &searcher, "new yorc city")?,
+            vec!["new york city".to_string()]
+        );
+        Ok(())
+    }
+}
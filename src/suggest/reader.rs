@@ -0,0 +1,127 @@
+use std::io;
+use std::sync::Arc;
+
+use tantivy_fst::raw::Fst;
+use tantivy_fst::{IntoStreamer, Map, Streamer};
+
+use crate::directory::{CompositeFile, FileSlice, OwnedBytes};
+use crate::schema::Field;
+use crate::space_usage::PerFieldSpaceUsage;
+use crate::DocId;
+
+/// Reader for the completions of all of the completion fields.
+#[derive(Clone)]
+pub struct CompletionReaders {
+    data: Arc<CompositeFile>,
+}
+
+impl CompletionReaders {
+    /// Creates a completion reader.
+    pub fn open(file: FileSlice) -> crate::Result<CompletionReaders> {
+        let data = CompositeFile::open(&file)?;
+        Ok(CompletionReaders {
+            data: Arc::new(data),
+        })
+    }
+
+    /// Creates a completion reader for a segment without any completions.
+    pub fn empty() -> CompletionReaders {
+        CompletionReaders {
+            data: Arc::new(CompositeFile::empty()),
+        }
+    }
+
+    /// Returns the `CompletionReader` for a specific field.
+    pub fn get_field(&self, field: Field) -> crate::Result<Option<CompletionReader>> {
+        if let Some(file) = self.data.open_read(field) {
+            let completion_reader = CompletionReader::open(file)?;
+            Ok(Some(completion_reader))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Return a break down of the space usage per field.
+    pub fn space_usage(&self) -> PerFieldSpaceUsage {
+        self.data.space_usage()
+    }
+}
+
+/// Reads the completions of a given field.
+#[derive(Clone)]
+pub struct CompletionReader {
+    fst: Arc<Map<OwnedBytes>>,
+}
+
+impl CompletionReader {
+    /// Opens the completions of a field.
+    pub fn open(file: FileSlice) -> crate::Result<CompletionReader> {
+        let bytes = file.read_bytes()?;
+        let fst = Fst::new(bytes).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Fst data is corrupted: {err:?}"),
+            )
+        })?;
+        Ok(CompletionReader {
+            fst: Arc::new(Map::from(fst)),
+        })
+    }
+
+    /// Returns the number of recorded completions, including the copies of the completions
+    /// recorded for each of their contexts.
+    pub fn len(&self) -> usize {
+        self.fst.len()
+    }
+
+    /// Returns true if no completion is recorded.
+    pub fn is_empty(&self) -> bool {
+        self.fst.is_empty()
+    }
+
+    /// Calls `callback` with the text, the weight and the document of the completions whose
+    /// key starts with `key_prefix`.
+    pub(crate) fn for_each_completion(
+        &self,
+        key_prefix: &[u8],
+        mut callback: impl FnMut(&str, u64, DocId),
+    ) {
+        let mut stream = self.fst.range().ge(key_prefix).into_stream();
+        while let Some((key, weight)) = stream.next() {
+            if !key.starts_with(key_prefix) {
+                break;
+            }
+            let Some((text, doc)) = parse_key(&key[key_prefix.len()..]) else {
+                continue;
+            };
+            callback(text, weight, doc);
+        }
+    }
+
+    /// Calls `callback` with the key without its document, the weight and the document of
+    /// all of the completions.
+    pub(crate) fn for_each_key(&self, mut callback: impl FnMut(&[u8], u64, DocId)) {
+        let mut stream = self.fst.stream();
+        while let Some((key, weight)) = stream.next() {
+            if key.len() < 4 {
+                continue;
+            }
+            let (key, doc_bytes) = key.split_at(key.len() - 4);
+            callback(
+                key,
+                weight,
+                DocId::from_be_bytes(doc_bytes.try_into().unwrap()),
+            );
+        }
+    }
+}
+
+/// Parses the end of a key, after its context prefix: the normalized text, the text and the
+/// document of the completion.
+fn parse_key(key: &[u8]) -> Option<(&str, DocId)> {
+    let (key, doc_bytes) = key.split_at(key.len().checked_sub(4)?);
+    let doc = DocId::from_be_bytes(doc_bytes.try_into().unwrap());
+    let separator = key.iter().position(|&byte| byte == 0)?;
+    let text = key.get(separator + 1..key.len().checked_sub(1)?)?;
+    Some((std::str::from_utf8(text).ok()?, doc))
+}
@@ -0,0 +1,51 @@
+use std::io;
+use std::io::Write;
+
+use crate::directory::{CompositeWrite, WritePtr};
+use crate::schema::Field;
+
+fn convert_fst_error(e: tantivy_fst::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e)
+}
+
+/// The completions serializer is in charge of
+/// the serialization of the completions of all completion fields.
+///
+/// For each field, the serialized data is an FST associating the key of each completion
+/// to its weight.
+pub struct CompletionsSerializer {
+    composite_write: CompositeWrite,
+}
+
+impl CompletionsSerializer {
+    /// Constructor
+    pub fn from_write(write: WritePtr) -> io::Result<CompletionsSerializer> {
+        let composite_write = CompositeWrite::wrap(write);
+        Ok(CompletionsSerializer { composite_write })
+    }
+
+    /// Serialize the completions of the given field, given the key and the weight of each
+    /// completion, sorted by key and without duplicates.
+    pub fn serialize_field(
+        &mut self,
+        field: Field,
+        completions: &[(Vec<u8>, u64)],
+    ) -> io::Result<()> {
+        let write = self.composite_write.for_field(field);
+        let mut fst_builder = tantivy_fst::MapBuilder::new(write).map_err(convert_fst_error)?;
+        for (key, weight) in completions {
+            fst_builder
+                .insert(key, *weight)
+                .map_err(convert_fst_error)?;
+        }
+        let write = fst_builder.into_inner().map_err(convert_fst_error)?;
+        write.flush()?;
+        Ok(())
+    }
+
+    /// Clean up / flush / close
+    pub fn close(self) -> io::Result<()> {
+        self.composite_write.close()?;
+        Ok(())
+    }
+}
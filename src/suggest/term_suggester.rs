@@ -0,0 +1,319 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use levenshtein_automata::Distance;
+
+use crate::query::FuzzyTermQuery;
+use crate::schema::{Field, FieldType};
+use crate::tokenizer::Token;
+use crate::{Searcher, Term};
+
+const DEFAULT_MAX_DISTANCE: u8 = 2;
+const DEFAULT_LIMIT: usize = 5;
+
+/// A correction of a term returned by a [`TermSuggester`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TermSuggestion {
+    text: String,
+    distance: u8,
+    doc_freq: u64,
+}
+
+impl TermSuggestion {
+    /// Returns the text of the suggested term.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the Levenshtein distance between the suggested term and the input term.
+    pub fn distance(&self) -> u8 {
+        self.distance
+    }
+
+    /// Returns the number of documents containing the suggested term, summed over the
+    /// segments of the searcher.
+    pub fn doc_freq(&self) -> u64 {
+        self.doc_freq
+    }
+}
+
+/// The corrections of a term of a query string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TermSuggestions {
+    text: String,
+    offsets: Range<usize>,
+    suggestions: Vec<TermSuggestion>,
+}
+
+impl TermSuggestions {
+    /// Returns the text of the term, as tokenized from the query string.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the byte offsets of the term in the query string.
+    pub fn offsets(&self) -> Range<usize> {
+        self.offsets.clone()
+    }
+
+    /// Returns the corrections of the term, from the best to the worst.
+    pub fn suggestions(&self) -> &[TermSuggestion] {
+        &self.suggestions
+    }
+}
+
+/// `TermSuggester` suggests corrections of misspelled terms, to offer a "did you mean" to
+/// the user.
+///
+/// The candidates are the terms of the field within a Levenshtein distance of the input term,
+/// read from the term dictionaries of the segments. They are ranked by increasing distance,
+/// then by decreasing document frequency.
+///
+/// ```rust
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::suggest::TermSuggester;
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let body = schema_builder.add_text_field("body", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+/// index_writer.add_document(doc!(body => "The quick brown fox"))?;
+/// index_writer.add_document(doc!(body => "The quick red fox"))?;
+/// index_writer.add_document(doc!(body => "A brow of a hill"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let term_suggester = TermSuggester::new(body);
+/// let term_suggestions = term_suggester.suggest(&searcher, "quack brwn fox")?;
+/// let corrections: Vec<(&str, Vec<&str>)> = term_suggestions
+///     .iter()
+///     .map(|term_suggestions| {
+///         let suggestions = term_suggestions
+///             .suggestions()
+///             .iter()
+///             .map(|suggestion| suggestion.text())
+///             .collect();
+///         (term_suggestions.text(), suggestions)
+///     })
+///     .collect();
+/// assert_eq!(
+///     corrections,
+///     vec![
+///         ("quack", vec!["quick"]),
+///         ("brwn", vec!["brown", "brow"]),
+///         ("fox", vec![]),
+///     ]
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub struct TermSuggester {
+    field: Field,
+    max_distance: u8,
+    limit: usize,
+    only_missing: bool,
+}
+
+impl TermSuggester {
+    /// Creates a `TermSuggester` for the given text field.
+    pub fn new(field: Field) -> TermSuggester {
+        TermSuggester {
+            field,
+            max_distance: DEFAULT_MAX_DISTANCE,
+            limit: DEFAULT_LIMIT,
+            only_missing: true,
+        }
+    }
+
+    /// Sets the maximum Levenshtein distance of the suggestions, at most 2. It defaults to 2.
+    pub fn set_max_distance(&mut self, max_distance: u8) {
+        self.max_distance = max_distance;
+    }
+
+    /// Sets the maximum number of suggestions per term. It defaults to 5.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+    }
+
+    /// If set, which is the default, corrections are only suggested for the terms that do
+    /// not appear in the index. Otherwise, the other terms close to the input term are
+    /// suggested as well.
+    pub fn set_only_missing(&mut self, only_missing: bool) {
+        self.only_missing = only_missing;
+    }
+
+    /// Returns the corrections of each term of the query string, tokenized with the search
+    /// tokenizer of the field.
+    pub fn suggest(
+        &self,
+        searcher: &Searcher,
+        query_text: &str,
+    ) -> crate::Result<Vec<TermSuggestions>> {
+        search_tokens(searcher, self.field, query_text)?
+            .into_iter()
+            .map(|token| {
+                let suggestions = self.suggest_term(searcher, &token.text)?;
+                Ok(TermSuggestions {
+                    text: token.text,
+                    offsets: token.offset_from..token.offset_to,
+                    suggestions,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the corrections of a term, given its text as indexed.
+    pub fn suggest_term(
+        &self,
+        searcher: &Searcher,
+        text: &str,
+    ) -> crate::Result<Vec<TermSuggestion>> {
+        let term = Term::from_field_text(self.field, text);
+        if self.only_missing && searcher.doc_freq(&term)? > 0 {
+            return Ok(Vec::new());
+        }
+        let automaton = FuzzyTermQuery::new(term, self.max_distance, true).automaton()?;
+        let mut doc_freqs: BTreeMap<Vec<u8>, u64> = BTreeMap::new();
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(self.field)?;
+            let mut term_stream = inverted_index.terms().search(&automaton).into_stream()?;
+            while term_stream.advance() {
+                *doc_freqs.entry(term_stream.key().to_vec()).or_default() +=
+                    term_stream.value().doc_freq as u64;
+            }
+        }
+        let mut suggestions: Vec<TermSuggestion> = doc_freqs
+            .into_iter()
+            .filter_map(|(term_bytes, doc_freq)| {
+                let suggestion_text = String::from_utf8(term_bytes).ok()?;
+                if suggestion_text == text {
+                    return None;
+                }
+                let Distance::Exact(distance) = automaton.0.eval(&suggestion_text) else {
+                    return None;
+                };
+                Some(TermSuggestion {
+                    text: suggestion_text,
+                    distance,
+                    doc_freq,
+                })
+            })
+            .collect();
+        suggestions.sort_by(|left, right| {
+            (left.distance, Reverse(left.doc_freq), &left.text).cmp(&(
+                right.distance,
+                Reverse(right.doc_freq),
+                &right.text,
+            ))
+        });
+        suggestions.truncate(self.limit);
+        Ok(suggestions)
+    }
+}
+
+/// Tokenizes a query string with the search tokenizer of the given text field.
+pub(crate) fn search_tokens(
+    searcher: &Searcher,
+    field: Field,
+    query_text: &str,
+) -> crate::Result<Vec<Token>> {
+    let field_entry = searcher.schema().get_field_entry(field);
+    let FieldType::Str(text_options) = field_entry.field_type() else {
+        return Err(crate::TantivyError::SchemaError(format!(
+            "{:?} is not a text field.",
+            field_entry.name()
+        )));
+    };
+    let indexing_options = text_options.get_indexing_options().ok_or_else(|| {
+        crate::TantivyError::SchemaError(format!(
+            "{:?} is not an indexed text field.",
+            field_entry.name()
+        ))
+    })?;
+    let tokenizer = searcher
+        .index()
+        .tokenizers()
+        .get(indexing_options.search_tokenizer())
+        .ok_or_else(|| {
+            crate::TantivyError::InvalidArgument(format!(
+                "No Tokenizer found for field {:?}",
+                field_entry.name()
+            ))
+        })?;
+    let mut tokens: Vec<Token> = Vec::new();
+    tokenizer
+        .token_stream(query_text)
+        .process(&mut |token: &Token| tokens.push(token.clone()));
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TermSuggester;
+    use crate::schema::{Schema, TEXT};
+    use crate::Index;
+
+    #[test]
+    fn test_term_suggester() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc!(body => "search engine"))?;
+            index_writer.add_document(doc!(body => "research"))?;
+            index_writer.commit()?;
+            index_writer.add_document(doc!(body => "search and rescue"))?;
+            index_writer.add_document(doc!(body => "seaweed starch"))?;
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let suggestions = |term_suggester: &TermSuggester, text: &str| {
+            term_suggester
+                .suggest_term(&searcher, text)
+                .map(|suggestions| {
+                    suggestions
+                        .iter()
+                        .map(|suggestion| {
+                            (
+                                suggestion.text().to_string(),
+                                suggestion.distance(),
+                                suggestion.doc_freq(),
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                })
+        };
+        let mut term_suggester = TermSuggester::new(body);
+        assert_eq!(
+            suggestions(&term_suggester, "serch")?,
+            vec![("search".to_string(), 1, 2), ("starch".to_string(), 2, 1)]
+        );
+        assert!(suggestions(&term_suggester, "search")?.is_empty());
+        term_suggester.set_only_missing(false);
+        assert_eq!(
+            suggestions(&term_suggester, "search")?,
+            vec![("starch".to_string(), 1, 1), ("research".to_string(), 2, 1)]
+        );
+        term_suggester.set_max_distance(1);
+        term_suggester.set_limit(1);
+        assert_eq!(
+            suggestions(&term_suggester, "serch")?,
+            vec![("search".to_string(), 1, 2)]
+        );
+        term_suggester.set_max_distance(3);
+        assert!(term_suggester.suggest_term(&searcher, "serch").is_err());
+
+        let term_suggestions = TermSuggester::new(body).suggest(&searcher, "Serch ENGNE")?;
+        assert_eq!(term_suggestions.len(), 2);
+        assert_eq!(term_suggestions[0].text(), "serch");
+        assert_eq!(term_suggestions[0].offsets(), 0..5);
+        assert_eq!(term_suggestions[1].text(), "engne");
+        assert_eq!(term_suggestions[1].offsets(), 6..11);
+        assert_eq!(term_suggestions[1].suggestions()[0].text(), "engine");
+        Ok(())
+    }
+}
@@ -0,0 +1,182 @@
+use std::io;
+
+use super::{completion_key_prefix, context_key_prefix, CompletionsSerializer, NO_CONTEXT};
+use crate::indexer::doc_id_mapping::DocIdMapping;
+use crate::schema::{Field, FieldType, Schema, Value};
+use crate::{DocId, Document};
+
+/// A completion field, with the fields holding the weight and the contexts of its
+/// suggestions.
+pub(crate) struct CompletionField {
+    pub(crate) field: Field,
+    pub(crate) weight_field: Option<Field>,
+    pub(crate) context_fields: Vec<Field>,
+}
+
+/// Resolves the weight and context fields of every completion field of the schema.
+///
+/// Returns an error if one of them does not exist, if a weight field is not a `u64` field,
+/// or if a context field is not a text field.
+pub(crate) fn resolve_completion_fields(schema: &Schema) -> crate::Result<Vec<CompletionField>> {
+    let schema_error = |field_name: &str, message: String| {
+        crate::TantivyError::SchemaError(format!("Completion field {field_name:?} {message}"))
+    };
+    let mut completion_fields = Vec::new();
+    for (field, field_entry) in schema.fields() {
+        let Some(completion_options) = field_entry.completion_options() else {
+            continue;
+        };
+        let weight_field = completion_options
+            .weight_field()
+            .map(|weight_field_name| {
+                let weight_field = schema.get_field(weight_field_name)?;
+                if !matches!(
+                    schema.get_field_entry(weight_field).field_type(),
+                    FieldType::U64(_)
+                ) {
+                    return Err(schema_error(
+                        field_entry.name(),
+                        format!(
+                            "has a weight field {weight_field_name:?} which is not a u64 field"
+                        ),
+                    ));
+                }
+                Ok(weight_field)
+            })
+            .transpose()?;
+        if completion_options.context_fields().len() > u8::MAX as usize {
+            return Err(schema_error(
+                field_entry.name(),
+                "has more than 255 context fields".to_string(),
+            ));
+        }
+        let context_fields = completion_options
+            .context_fields()
+            .iter()
+            .map(|context_field_name| {
+                let context_field = schema.get_field(context_field_name)?;
+                if !matches!(
+                    schema.get_field_entry(context_field).field_type(),
+                    FieldType::Str(_)
+                ) {
+                    return Err(schema_error(
+                        field_entry.name(),
+                        format!(
+                            "has a context field {context_field_name:?} which is not a text field"
+                        ),
+                    ));
+                }
+                Ok(context_field)
+            })
+            .collect::<crate::Result<_>>()?;
+        completion_fields.push(CompletionField {
+            field,
+            weight_field,
+            context_fields,
+        });
+    }
+    Ok(completion_fields)
+}
+
+/// The `CompletionsWriter` is in charge of buffering the completions of each document
+/// for each completion field.
+pub struct CompletionsWriter {
+    completion_fields: Vec<CompletionField>,
+    // The completions of each completion field, as the document, the key without the
+    // document and the weight of each completion.
+    completions: Vec<Vec<(DocId, Vec<u8>, u64)>>,
+}
+
+impl CompletionsWriter {
+    /// Initialize with state for tracking the completion fields specified in the schema.
+    pub fn for_schema(schema: &Schema) -> crate::Result<CompletionsWriter> {
+        let completion_fields = resolve_completion_fields(schema)?;
+        let completions = completion_fields.iter().map(|_| Vec::new()).collect();
+        Ok(CompletionsWriter {
+            completion_fields,
+            completions,
+        })
+    }
+
+    /// The memory used inclusive childs
+    pub fn mem_usage(&self) -> usize {
+        self.completions
+            .iter()
+            .map(|field_completions| {
+                field_completions.capacity() * std::mem::size_of::<(DocId, Vec<u8>, u64)>()
+                    + field_completions
+                        .iter()
+                        .map(|(_, key, _)| key.capacity())
+                        .sum::<usize>()
+            })
+            .sum()
+    }
+
+    /// Records the completions of the given document.
+    ///
+    /// Each value of a completion field is recorded once without context, and once for each
+    /// of the contexts of the document.
+    pub fn record(&mut self, doc: DocId, document: &Document) {
+        for (completion_field, field_completions) in
+            self.completion_fields.iter().zip(&mut self.completions)
+        {
+            let weight = completion_field
+                .weight_field
+                .and_then(|weight_field| document.get_first(weight_field))
+                .and_then(Value::as_u64)
+                .unwrap_or(0);
+            let mut context_prefixes: Vec<Vec<u8>> = vec![vec![NO_CONTEXT]];
+            for (context_ord, &context_field) in completion_field.context_fields.iter().enumerate()
+            {
+                context_prefixes.extend(
+                    document
+                        .get_all(context_field)
+                        .flat_map(Value::as_text)
+                        .map(|context| context_key_prefix(context_ord, context)),
+                );
+            }
+            for input in document
+                .get_all(completion_field.field)
+                .flat_map(Value::as_text)
+                .filter(|input| !input.is_empty())
+            {
+                for context_prefix in &context_prefixes {
+                    let mut key = context_prefix.clone();
+                    key.extend_from_slice(&completion_key_prefix(input));
+                    key.push(0);
+                    key.extend(input.bytes().filter(|&byte| byte != 0));
+                    key.push(0);
+                    field_completions.push((doc, key, weight));
+                }
+            }
+        }
+    }
+
+    /// Serialize the completions of all fields to the serializer.
+    pub fn serialize(
+        &self,
+        mut completions_serializer: CompletionsSerializer,
+        doc_id_map: Option<&DocIdMapping>,
+    ) -> io::Result<()> {
+        for (completion_field, field_completions) in
+            self.completion_fields.iter().zip(&self.completions)
+        {
+            let mut completions: Vec<(Vec<u8>, u64)> = field_completions
+                .iter()
+                .map(|(doc, key, weight)| {
+                    let doc = doc_id_map
+                        .map(|doc_id_map| doc_id_map.get_new_doc_id(*doc))
+                        .unwrap_or(*doc);
+                    let mut key = key.clone();
+                    key.extend_from_slice(&doc.to_be_bytes());
+                    (key, *weight)
+                })
+                .collect();
+            completions.sort_unstable();
+            completions.dedup_by(|right, left| left.0 == right.0);
+            completions_serializer.serialize_field(completion_field.field, &completions)?;
+        }
+        completions_serializer.close()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+use std::io;
+use std::ops::{Bound, Range};
+
+use tantivy_fst::Automaton;
+
+use crate::codec::TermDictionaryDecoder;
+use crate::postings::TermInfo;
+use crate::termdict::TermOrdinal;
+
+/// Term dictionary of a [`TermDictionaryFormat`](crate::codec::TermDictionaryFormat) other
+/// than the default one, accessed through its decoder.
+pub(crate) struct DecoderTermDictionary {
+    decoder: Box<dyn TermDictionaryDecoder>,
+}
+
+impl DecoderTermDictionary {
+    pub fn new(decoder: Box<dyn TermDictionaryDecoder>) -> Self {
+        DecoderTermDictionary { decoder }
+    }
+
+    pub fn num_terms(&self) -> usize {
+        self.decoder.num_terms()
+    }
+
+    pub fn term_ord(&self, key: &[u8]) -> io::Result<Option<TermOrdinal>> {
+        let term_ord = self.decoder.seek(key)?;
+        let mut term = Vec::with_capacity(key.len());
+        if self.decoder.ord_to_term(term_ord, &mut term)? && term == key {
+            Ok(Some(term_ord))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn ord_to_term(&self, term_ord: TermOrdinal, bytes: &mut Vec<u8>) -> io::Result<bool> {
+        bytes.clear();
+        self.decoder.ord_to_term(term_ord, bytes)
+    }
+
+    pub fn get(&self, key: &[u8]) -> io::Result<Option<TermInfo>> {
+        self.term_ord(key)?
+            .map(|term_ord| self.decoder.term_info_from_ord(term_ord))
+            .transpose()
+    }
+
+    pub fn search<A: Automaton>(&self, automaton: A) -> DecoderTermStreamerBuilder<'_, A> {
+        DecoderTermStreamerBuilder {
+            term_dict: self,
+            automaton,
+            lower: Bound::Unbounded,
+            upper: Bound::Unbounded,
+            backward: false,
+        }
+    }
+
+    /// Returns the ordinal of the first term strictly greater than `key`.
+    fn ord_after(&self, key: &[u8]) -> io::Result<TermOrdinal> {
+        Ok(match self.term_ord(key)? {
+            Some(term_ord) => term_ord + 1,
+            None => self.decoder.seek(key)?,
+        })
+    }
+}
+
+/// Range of the terms of a [`DecoderTermDictionary`] to stream.
+pub(crate) struct DecoderTermStreamerBuilder<'a, A> {
+    term_dict: &'a DecoderTermDictionary,
+    automaton: A,
+    lower: Bound<Vec<u8>>,
+    upper: Bound<Vec<u8>>,
+    backward: bool,
+}
+
+impl<'a, A: Automaton> DecoderTermStreamerBuilder<'a, A> {
+    pub fn ge(mut self, bound: &[u8]) -> Self {
+        self.lower = Bound::Included(bound.to_vec());
+        self
+    }
+
+    pub fn gt(mut self, bound: &[u8]) -> Self {
+        self.lower = Bound::Excluded(bound.to_vec());
+        self
+    }
+
+    pub fn le(mut self, bound: &[u8]) -> Self {
+        self.upper = Bound::Included(bound.to_vec());
+        self
+    }
+
+    pub fn lt(mut self, bound: &[u8]) -> Self {
+        self.upper = Bound::Excluded(bound.to_vec());
+        self
+    }
+
+    #[cfg_attr(feature = "quickwit", allow(dead_code))]
+    pub fn backward(mut self) -> Self {
+        self.backward = true;
+        self
+    }
+
+    pub fn into_stream(self) -> io::Result<DecoderTermStreamer<'a, A>> {
+        let term_dict = self.term_dict;
+        let start = match &self.lower {
+            Bound::Included(key) => term_dict.decoder.seek(key)?,
+            Bound::Excluded(key) => term_dict.ord_after(key)?,
+            Bound::Unbounded => 0,
+        };
+        let end = match &self.upper {
+            Bound::Included(key) => term_dict.ord_after(key)?,
+            Bound::Excluded(key) => term_dict.decoder.seek(key)?,
+            Bound::Unbounded => term_dict.num_terms() as TermOrdinal,
+        };
+        Ok(DecoderTermStreamer {
+            term_dict,
+            automaton: self.automaton,
+            term_ords: start..end.max(start),
+            backward: self.backward,
+            term_ord: 0,
+            current_key: Vec::new(),
+            current_value: TermInfo::default(),
+            buffer: Vec::new(),
+        })
+    }
+}
+
+/// Streams the terms of a [`DecoderTermDictionary`] by ordinal, skipping the terms which do not
+/// match the automaton.
+pub(crate) struct DecoderTermStreamer<'a, A> {
+    term_dict: &'a DecoderTermDictionary,
+    automaton: A,
+    term_ords: Range<TermOrdinal>,
+    backward: bool,
+    term_ord: TermOrdinal,
+    current_key: Vec<u8>,
+    current_value: TermInfo,
+    // Buffer of the candidate terms, so that the current key is kept once the stream ends.
+    buffer: Vec<u8>,
+}
+
+impl<'a, A: Automaton> DecoderTermStreamer<'a, A> {
+    fn is_match(&self, key: &[u8]) -> bool {
+        let mut state = self.automaton.start();
+        for &byte in key {
+            if !self.automaton.can_match(&state) {
+                return false;
+            }
+            state = self.automaton.accept(&state, byte);
+        }
+        self.automaton.is_match(&state)
+    }
+
+    pub fn advance(&mut self) -> bool {
+        loop {
+            let term_ord_opt = if self.backward {
+                self.term_ords.next_back()
+            } else {
+                self.term_ords.next()
+            };
+            let Some(term_ord) = term_ord_opt else {
+                return false;
+            };
+            let decoder = &self.term_dict.decoder;
+            self.buffer.clear();
+            if !decoder.ord_to_term(term_ord, &mut self.buffer).unwrap() {
+                return false;
+            }
+            if !self.is_match(&self.buffer) {
+                continue;
+            }
+            std::mem::swap(&mut self.current_key, &mut self.buffer);
+            self.term_ord = term_ord;
+            self.current_value = decoder.term_info_from_ord(term_ord).unwrap();
+            return true;
+        }
+    }
+
+    pub fn term_ord(&self) -> TermOrdinal {
+        self.term_ord
+    }
+
+    pub fn key(&self) -> &[u8] {
+        &self.current_key
+    }
+
+    pub fn value(&self) -> &TermInfo {
+        &self.current_value
+    }
+}
@@ -3,8 +3,9 @@ use tantivy_fst::raw::IndexedValue;
 use tantivy_fst::Streamer;
 
 use super::termdict::TermDictionary;
+use super::TermStreamer;
 use crate::postings::TermInfo;
-use crate::termdict::{TermOrdinal, TermStreamer};
+use crate::termdict::TermOrdinal;
 
 /// Given a list of sorted term streams,
 /// returns an iterator over sorted unique terms.
@@ -134,14 +134,4 @@ where A: Automaton
     pub fn value(&self) -> &TermInfo {
         &self.current_value
     }
-
-    /// Return the next `(key, value)` pair.
-    #[allow(clippy::should_implement_trait)]
-    pub fn next(&mut self) -> Option<(&[u8], &TermInfo)> {
-        if self.advance() {
-            Some((self.key(), self.value()))
-        } else {
-            None
-        }
-    }
 }
@@ -0,0 +1,223 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::io;
+use std::ops::Bound;
+use std::sync::Arc;
+
+use tantivy_fst::automaton::AlwaysMatch;
+use tantivy_fst::Automaton;
+
+use super::TermStreamer;
+use crate::postings::TermInfo;
+use crate::{InvertedIndexReader, SegmentOrdinal};
+
+/// The term dictionaries of a field in all of the segments of a
+/// [`Searcher`](crate::Searcher), streamed as one sorted dictionary.
+///
+/// See [`Searcher::merged_term_dictionary`](crate::Searcher::merged_term_dictionary).
+pub struct MergedTermDictionary {
+    inverted_indexes: Vec<Arc<InvertedIndexReader>>,
+}
+
+impl MergedTermDictionary {
+    pub(crate) fn new(inverted_indexes: Vec<Arc<InvertedIndexReader>>) -> MergedTermDictionary {
+        MergedTermDictionary { inverted_indexes }
+    }
+
+    /// A stream of all the sorted terms of the segments.
+    pub fn stream(&self) -> io::Result<MergedTermStreamer<'_>> {
+        self.search(AlwaysMatch)
+    }
+
+    /// A stream of the sorted terms of the segments matching the automaton, e.g. a
+    /// [`Regex`](tantivy_fst::Regex) or the automaton of a fuzzy term.
+    pub fn search<'a, A>(&'a self, automaton: A) -> io::Result<MergedTermStreamer<'a, A>>
+    where
+        A: Automaton + Clone + 'a,
+        A::State: Clone,
+    {
+        self.search_range(automaton, Bound::Unbounded, Bound::Unbounded)
+    }
+
+    /// A stream of the sorted terms of the segments matching the automaton, within the given
+    /// bounds.
+    pub fn search_range<'a, A>(
+        &'a self,
+        automaton: A,
+        lower_bound: Bound<&[u8]>,
+        upper_bound: Bound<&[u8]>,
+    ) -> io::Result<MergedTermStreamer<'a, A>>
+    where
+        A: Automaton + Clone + 'a,
+        A::State: Clone,
+    {
+        let mut streamers = Vec::with_capacity(self.inverted_indexes.len());
+        for inverted_index in &self.inverted_indexes {
+            let mut builder = inverted_index.terms().search(automaton.clone());
+            builder = match lower_bound {
+                Bound::Included(bound) => builder.ge(bound),
+                Bound::Excluded(bound) => builder.gt(bound),
+                Bound::Unbounded => builder,
+            };
+            builder = match upper_bound {
+                Bound::Included(bound) => builder.le(bound),
+                Bound::Excluded(bound) => builder.lt(bound),
+                Bound::Unbounded => builder,
+            };
+            streamers.push(builder.into_stream()?);
+        }
+        Ok(MergedTermStreamer::new(streamers))
+    }
+}
+
+/// A cursor over the sorted and deduplicated terms of the term dictionaries of several
+/// segments.
+pub struct MergedTermStreamer<'a, A = AlwaysMatch>
+where
+    A: Automaton,
+    A::State: Clone,
+{
+    streamers: Vec<TermStreamer<'a, A>>,
+    heap: BinaryHeap<Reverse<(Vec<u8>, usize)>>,
+    current_key: Vec<u8>,
+    current_term_infos: Vec<(SegmentOrdinal, TermInfo)>,
+}
+
+impl<'a, A> MergedTermStreamer<'a, A>
+where
+    A: Automaton,
+    A::State: Clone,
+{
+    fn new(mut streamers: Vec<TermStreamer<'a, A>>) -> Self {
+        let mut heap = BinaryHeap::with_capacity(streamers.len());
+        for (segment_ord, streamer) in streamers.iter_mut().enumerate() {
+            if streamer.advance() {
+                heap.push(Reverse((streamer.key().to_vec(), segment_ord)));
+            }
+        }
+        MergedTermStreamer {
+            streamers,
+            heap,
+            current_key: Vec::new(),
+            current_term_infos: Vec::new(),
+        }
+    }
+
+    /// Advances to the next term.
+    ///
+    /// Returns false once all of the terms have been streamed.
+    pub fn advance(&mut self) -> bool {
+        let Some(Reverse((key, _))) = self.heap.peek() else {
+            return false;
+        };
+        self.current_key.clear();
+        self.current_key.extend_from_slice(key);
+        self.current_term_infos.clear();
+        while let Some(Reverse((key, segment_ord))) = self.heap.peek() {
+            if key[..] != self.current_key[..] {
+                break;
+            }
+            let segment_ord = *segment_ord;
+            let Reverse((mut key, _)) = self.heap.pop().unwrap();
+            let streamer = &mut self.streamers[segment_ord];
+            self.current_term_infos
+                .push((segment_ord as SegmentOrdinal, streamer.value().clone()));
+            if streamer.advance() {
+                key.clear();
+                key.extend_from_slice(streamer.key());
+                self.heap.push(Reverse((key, segment_ord)));
+            }
+        }
+        self.current_term_infos
+            .sort_unstable_by_key(|(segment_ord, _)| *segment_ord);
+        true
+    }
+
+    /// Returns the current term.
+    ///
+    /// This method may be called if [`Self::advance`] has been called before and `true` was
+    /// returned.
+    pub fn key(&self) -> &[u8] {
+        &self.current_key
+    }
+
+    /// Returns the number of documents containing the current term, summed over the segments.
+    ///
+    /// Like the statistics used for BM25 scoring, this includes the deleted documents.
+    pub fn doc_freq(&self) -> u64 {
+        self.current_term_infos
+            .iter()
+            .map(|(_, term_info)| u64::from(term_info.doc_freq))
+            .sum()
+    }
+
+    /// Returns the `TermInfo` of the current term in each of the segments containing it,
+    /// sorted by segment ordinal.
+    pub fn segment_term_infos(&self) -> &[(SegmentOrdinal, TermInfo)] {
+        &self.current_term_infos
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use tantivy_fst::Regex;
+
+    use crate::schema::{Schema, STRING};
+    use crate::{doc, Index};
+
+    #[test]
+    fn test_merged_term_dictionary() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let color = schema_builder.add_text_field("color", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(color => "red"))?;
+        index_writer.add_document(doc!(color => "blue"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(color => "red"))?;
+        index_writer.add_document(doc!(color => "green"))?;
+        index_writer.add_document(doc!(color => "black"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let merged_term_dictionary = searcher.merged_term_dictionary(color)?;
+
+        let mut terms = Vec::new();
+        let mut stream = merged_term_dictionary.stream()?;
+        while stream.advance() {
+            terms.push((
+                String::from_utf8(stream.key().to_vec()).unwrap(),
+                stream.doc_freq(),
+                stream.segment_term_infos().len(),
+            ));
+        }
+        assert_eq!(
+            terms,
+            vec![
+                ("black".to_string(), 1, 1),
+                ("blue".to_string(), 1, 1),
+                ("green".to_string(), 1, 1),
+                ("red".to_string(), 2, 2),
+            ]
+        );
+
+        let regex = Regex::new("b.*").unwrap();
+        let mut stream = merged_term_dictionary.search(&regex)?;
+        let mut terms = Vec::new();
+        while stream.advance() {
+            terms.push(String::from_utf8(stream.key().to_vec()).unwrap());
+        }
+        assert_eq!(terms, vec!["black", "blue"]);
+
+        let mut stream = merged_term_dictionary.search_range(
+            &regex,
+            Bound::Excluded(&b"black"[..]),
+            Bound::Unbounded,
+        )?;
+        assert!(stream.advance());
+        assert_eq!(stream.key(), b"blue");
+        assert!(!stream.advance());
+        Ok(())
+    }
+}
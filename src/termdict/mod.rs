@@ -29,6 +29,9 @@ mod sstable_termdict;
 #[cfg(feature = "quickwit")]
 use sstable_termdict as termdict;
 
+mod decoder_termdict;
+mod merged_term_dictionary;
+mod streamer;
 #[cfg(test)]
 mod tests;
 
@@ -39,13 +42,17 @@ use std::io;
 
 use common::file_slice::FileSlice;
 use common::BinarySerializable;
+use tantivy_fst::automaton::AlwaysMatch;
 use tantivy_fst::Automaton;
 
+use self::decoder_termdict::DecoderTermDictionary;
+pub use self::merged_term_dictionary::{MergedTermDictionary, MergedTermStreamer};
+use self::streamer::TermStreamerBuilderImpl;
+pub use self::streamer::{TermMerger, TermStreamer, TermStreamerBuilder};
 use self::termdict::{
     TermDictionary as InnerTermDict, TermDictionaryBuilder as InnerTermDictBuilder,
-    TermStreamerBuilder,
 };
-pub use self::termdict::{TermMerger, TermStreamer};
+use crate::codec::TermDictionaryDecoder;
 use crate::postings::TermInfo;
 
 #[repr(u32)]
@@ -61,9 +68,14 @@ const CURRENT_TYPE: DictionaryType = DictionaryType::Fst;
 #[cfg(feature = "quickwit")]
 const CURRENT_TYPE: DictionaryType = DictionaryType::SSTable;
 
-// TODO in the future this should become an enum of supported dictionaries
-/// A TermDictionary wrapping either an FST based dictionary or a SSTable based one.
-pub struct TermDictionary(InnerTermDict);
+enum TermDictionaryImpl {
+    Default(InnerTermDict),
+    Decoder(DecoderTermDictionary),
+}
+
+/// A TermDictionary wrapping either an FST based dictionary or a SSTable based one, or the
+/// decoder of a [`TermDictionaryFormat`](crate::codec::TermDictionaryFormat).
+pub struct TermDictionary(TermDictionaryImpl);
 
 impl TermDictionary {
     /// Opens a `TermDictionary`.
@@ -82,23 +94,39 @@ impl TermDictionary {
             ));
         }
 
-        InnerTermDict::open(main_slice).map(TermDictionary)
+        InnerTermDict::open(main_slice)
+            .map(|dict| TermDictionary(TermDictionaryImpl::Default(dict)))
+    }
+
+    /// Creates the term dictionary of a
+    /// [`TermDictionaryFormat`](crate::codec::TermDictionaryFormat) other than the default one,
+    /// from its decoder.
+    pub fn from_decoder(decoder: Box<dyn TermDictionaryDecoder>) -> Self {
+        TermDictionary(TermDictionaryImpl::Decoder(DecoderTermDictionary::new(
+            decoder,
+        )))
     }
 
     /// Creates an empty term dictionary which contains no terms.
     pub fn empty() -> Self {
-        TermDictionary(InnerTermDict::empty())
+        TermDictionary(TermDictionaryImpl::Default(InnerTermDict::empty()))
     }
 
     /// Returns the number of terms in the dictionary.
     /// Term ordinals range from 0 to `num_terms() - 1`.
     pub fn num_terms(&self) -> usize {
-        self.0.num_terms()
+        match &self.0 {
+            TermDictionaryImpl::Default(dict) => dict.num_terms(),
+            TermDictionaryImpl::Decoder(dict) => dict.num_terms(),
+        }
     }
 
     /// Returns the ordinal associated with a given term.
     pub fn term_ord<K: AsRef<[u8]>>(&self, key: K) -> io::Result<Option<TermOrdinal>> {
-        self.0.term_ord(key)
+        match &self.0 {
+            TermDictionaryImpl::Default(dict) => dict.term_ord(key),
+            TermDictionaryImpl::Decoder(dict) => dict.term_ord(key.as_ref()),
+        }
     }
 
     /// Stores the term associated with a given term ordinal in
@@ -112,7 +140,10 @@ impl TermDictionary {
     /// Regardless of whether the term is found or not,
     /// the buffer may be modified.
     pub fn ord_to_term(&self, ord: TermOrdinal, bytes: &mut Vec<u8>) -> io::Result<bool> {
-        self.0.ord_to_term(ord, bytes)
+        match &self.0 {
+            TermDictionaryImpl::Default(dict) => dict.ord_to_term(ord, bytes),
+            TermDictionaryImpl::Decoder(dict) => dict.ord_to_term(ord, bytes),
+        }
     }
 
     // this isn't used, and has different prototype in Fst and SSTable
@@ -123,37 +154,58 @@ impl TermDictionary {
 
     /// Lookups the value corresponding to the key.
     pub fn get<K: AsRef<[u8]>>(&self, key: K) -> io::Result<Option<TermInfo>> {
-        self.0.get(key)
+        match &self.0 {
+            TermDictionaryImpl::Default(dict) => dict.get(key),
+            TermDictionaryImpl::Decoder(dict) => dict.get(key.as_ref()),
+        }
     }
 
     /// Returns a range builder, to stream all of the terms
     /// within an interval.
     pub fn range(&self) -> TermStreamerBuilder<'_> {
-        self.0.range()
+        TermStreamerBuilder(match &self.0 {
+            TermDictionaryImpl::Default(dict) => TermStreamerBuilderImpl::Default(dict.range()),
+            TermDictionaryImpl::Decoder(dict) => {
+                TermStreamerBuilderImpl::Decoder(dict.search(AlwaysMatch))
+            }
+        })
     }
 
     /// A stream of all the sorted terms.
     pub fn stream(&self) -> io::Result<TermStreamer<'_>> {
-        self.0.stream()
+        self.range().into_stream()
     }
 
     /// Returns a search builder, to stream all of the terms
     /// within the Automaton
     pub fn search<'a, A: Automaton + 'a>(&'a self, automaton: A) -> TermStreamerBuilder<'a, A>
     where A::State: Clone {
-        self.0.search(automaton)
+        TermStreamerBuilder(match &self.0 {
+            TermDictionaryImpl::Default(dict) => {
+                TermStreamerBuilderImpl::Default(dict.search(automaton))
+            }
+            TermDictionaryImpl::Decoder(dict) => {
+                TermStreamerBuilderImpl::Decoder(dict.search(automaton))
+            }
+        })
     }
 
     #[cfg(feature = "quickwit")]
     /// Lookups the value corresponding to the key.
     pub async fn get_async<K: AsRef<[u8]>>(&self, key: K) -> io::Result<Option<TermInfo>> {
-        self.0.get_async(key).await
+        match &self.0 {
+            TermDictionaryImpl::Default(dict) => dict.get_async(key).await,
+            TermDictionaryImpl::Decoder(dict) => dict.get(key.as_ref()),
+        }
     }
 
     #[cfg(feature = "quickwit")]
     #[doc(hidden)]
     pub async fn warm_up_dictionary(&self) -> io::Result<()> {
-        self.0.warm_up_dictionary().await
+        match &self.0 {
+            TermDictionaryImpl::Default(dict) => dict.warm_up_dictionary().await,
+            TermDictionaryImpl::Decoder(_) => Ok(()),
+        }
     }
 
     #[cfg(feature = "quickwit")]
@@ -164,7 +216,11 @@ impl TermDictionary {
         key_range: impl std::ops::RangeBounds<[u8]>,
         limit: Option<u64>,
     ) -> FileSlice {
-        self.0.file_slice_for_range(key_range, limit)
+        match &self.0 {
+            TermDictionaryImpl::Default(dict) => dict.file_slice_for_range(key_range, limit),
+            // The decoders are not warmed up by ranges.
+            TermDictionaryImpl::Decoder(_) => FileSlice::empty(),
+        }
     }
 }
 
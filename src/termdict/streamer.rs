@@ -0,0 +1,383 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::io;
+
+use itertools::Either;
+use tantivy_fst::automaton::AlwaysMatch;
+use tantivy_fst::Automaton;
+
+use super::decoder_termdict::{DecoderTermStreamer, DecoderTermStreamerBuilder};
+use super::termdict::{
+    TermMerger as InnerTermMerger, TermStreamer as InnerTermStreamer,
+    TermStreamerBuilder as InnerTermStreamerBuilder,
+};
+use super::TermOrdinal;
+use crate::postings::TermInfo;
+
+pub(super) enum TermStreamerBuilderImpl<'a, A>
+where
+    A: Automaton,
+    A::State: Clone,
+{
+    Default(InnerTermStreamerBuilder<'a, A>),
+    Decoder(DecoderTermStreamerBuilder<'a, A>),
+}
+
+/// `TermStreamerBuilder` is a helper object used to define
+/// a range of terms that should be streamed.
+pub struct TermStreamerBuilder<'a, A = AlwaysMatch>(pub(super) TermStreamerBuilderImpl<'a, A>)
+where
+    A: Automaton,
+    A::State: Clone;
+
+impl<'a, A> TermStreamerBuilder<'a, A>
+where
+    A: Automaton,
+    A::State: Clone,
+{
+    /// Limit the range to terms greater or equal to the bound
+    pub fn ge<T: AsRef<[u8]>>(self, bound: T) -> Self {
+        TermStreamerBuilder(match self.0 {
+            TermStreamerBuilderImpl::Default(builder) => {
+                TermStreamerBuilderImpl::Default(builder.ge(bound))
+            }
+            TermStreamerBuilderImpl::Decoder(builder) => {
+                TermStreamerBuilderImpl::Decoder(builder.ge(bound.as_ref()))
+            }
+        })
+    }
+
+    /// Limit the range to terms strictly greater than the bound
+    pub fn gt<T: AsRef<[u8]>>(self, bound: T) -> Self {
+        TermStreamerBuilder(match self.0 {
+            TermStreamerBuilderImpl::Default(builder) => {
+                TermStreamerBuilderImpl::Default(builder.gt(bound))
+            }
+            TermStreamerBuilderImpl::Decoder(builder) => {
+                TermStreamerBuilderImpl::Decoder(builder.gt(bound.as_ref()))
+            }
+        })
+    }
+
+    /// Limit the range to terms lesser or equal to the bound
+    pub fn le<T: AsRef<[u8]>>(self, bound: T) -> Self {
+        TermStreamerBuilder(match self.0 {
+            TermStreamerBuilderImpl::Default(builder) => {
+                TermStreamerBuilderImpl::Default(builder.le(bound))
+            }
+            TermStreamerBuilderImpl::Decoder(builder) => {
+                TermStreamerBuilderImpl::Decoder(builder.le(bound.as_ref()))
+            }
+        })
+    }
+
+    /// Limit the range to terms strictly lesser than the bound
+    pub fn lt<T: AsRef<[u8]>>(self, bound: T) -> Self {
+        TermStreamerBuilder(match self.0 {
+            TermStreamerBuilderImpl::Default(builder) => {
+                TermStreamerBuilderImpl::Default(builder.lt(bound))
+            }
+            TermStreamerBuilderImpl::Decoder(builder) => {
+                TermStreamerBuilderImpl::Decoder(builder.lt(bound.as_ref()))
+            }
+        })
+    }
+
+    #[cfg(not(feature = "quickwit"))]
+    /// Iterate over the range backwards.
+    pub fn backward(self) -> Self {
+        TermStreamerBuilder(match self.0 {
+            TermStreamerBuilderImpl::Default(builder) => {
+                TermStreamerBuilderImpl::Default(builder.backward())
+            }
+            TermStreamerBuilderImpl::Decoder(builder) => {
+                TermStreamerBuilderImpl::Decoder(builder.backward())
+            }
+        })
+    }
+
+    #[cfg(feature = "quickwit")]
+    /// Load no more data than what's required to to get `limit`
+    /// matching entries.
+    ///
+    /// The resulting [`TermStreamer`] can still return marginaly
+    /// more than `limit` elements.
+    pub fn limit(self, limit: u64) -> Self {
+        TermStreamerBuilder(match self.0 {
+            TermStreamerBuilderImpl::Default(builder) => {
+                TermStreamerBuilderImpl::Default(builder.limit(limit))
+            }
+            // The terms are read one by one from their ordinal.
+            decoder_builder @ TermStreamerBuilderImpl::Decoder(_) => decoder_builder,
+        })
+    }
+
+    /// Creates the stream corresponding to the range
+    /// of terms defined using the `TermStreamerBuilder`.
+    pub fn into_stream(self) -> io::Result<TermStreamer<'a, A>> {
+        Ok(TermStreamer(match self.0 {
+            TermStreamerBuilderImpl::Default(builder) => {
+                TermStreamerImpl::Default(builder.into_stream()?)
+            }
+            TermStreamerBuilderImpl::Decoder(builder) => {
+                TermStreamerImpl::Decoder(builder.into_stream()?)
+            }
+        }))
+    }
+
+    #[cfg(feature = "quickwit")]
+    /// See `into_stream(..)`
+    pub async fn into_stream_async(self) -> io::Result<TermStreamer<'a, A>> {
+        Ok(TermStreamer(match self.0 {
+            TermStreamerBuilderImpl::Default(builder) => {
+                TermStreamerImpl::Default(builder.into_stream_async().await?)
+            }
+            TermStreamerBuilderImpl::Decoder(builder) => {
+                TermStreamerImpl::Decoder(builder.into_stream()?)
+            }
+        }))
+    }
+}
+
+enum TermStreamerImpl<'a, A>
+where
+    A: Automaton,
+    A::State: Clone,
+{
+    Default(InnerTermStreamer<'a, A>),
+    Decoder(DecoderTermStreamer<'a, A>),
+}
+
+/// `TermStreamer` acts as a cursor over a range of terms of a segment.
+/// Terms are guaranteed to be sorted.
+pub struct TermStreamer<'a, A = AlwaysMatch>(TermStreamerImpl<'a, A>)
+where
+    A: Automaton,
+    A::State: Clone;
+
+impl<'a, A> TermStreamer<'a, A>
+where
+    A: Automaton,
+    A::State: Clone,
+{
+    /// Advance position the stream on the next item.
+    /// Before the first call to `.advance()`, the stream
+    /// is an uninitialized state.
+    pub fn advance(&mut self) -> bool {
+        match &mut self.0 {
+            TermStreamerImpl::Default(streamer) => streamer.advance(),
+            TermStreamerImpl::Decoder(streamer) => streamer.advance(),
+        }
+    }
+
+    /// Returns the `TermOrdinal` of the given term.
+    ///
+    /// May panic if the called as `.advance()` as never
+    /// been called before.
+    pub fn term_ord(&self) -> TermOrdinal {
+        match &self.0 {
+            TermStreamerImpl::Default(streamer) => streamer.term_ord(),
+            TermStreamerImpl::Decoder(streamer) => streamer.term_ord(),
+        }
+    }
+
+    /// Accesses the current key.
+    ///
+    /// `.key()` should return the key that was returned
+    /// by the `.next()` method.
+    ///
+    /// If the end of the stream as been reached, and `.next()`
+    /// has been called and returned `None`, `.key()` remains
+    /// the value of the last key encountered.
+    ///
+    /// Before any call to `.next()`, `.key()` returns an empty array.
+    pub fn key(&self) -> &[u8] {
+        match &self.0 {
+            TermStreamerImpl::Default(streamer) => streamer.key(),
+            TermStreamerImpl::Decoder(streamer) => streamer.key(),
+        }
+    }
+
+    /// Accesses the current value.
+    ///
+    /// Calling `.value()` after the end of the stream will return the
+    /// last `.value()` encountered.
+    ///
+    /// # Panics
+    ///
+    /// Calling `.value()` before the first call to `.advance()` returns
+    /// `V::default()`.
+    pub fn value(&self) -> &TermInfo {
+        match &self.0 {
+            TermStreamerImpl::Default(streamer) => streamer.value(),
+            TermStreamerImpl::Decoder(streamer) => streamer.value(),
+        }
+    }
+
+    /// Return the next `(key, value)` pair.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<(&[u8], &TermInfo)> {
+        if self.advance() {
+            Some((self.key(), self.value()))
+        } else {
+            None
+        }
+    }
+}
+
+struct HeapItem<'a> {
+    streamer: TermStreamer<'a>,
+    segment_ord: usize,
+}
+
+impl<'a> PartialEq for HeapItem<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.segment_ord == other.segment_ord
+    }
+}
+
+impl<'a> Eq for HeapItem<'a> {}
+
+impl<'a> PartialOrd for HeapItem<'a> {
+    fn partial_cmp(&self, other: &HeapItem<'a>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a> Ord for HeapItem<'a> {
+    fn cmp(&self, other: &HeapItem<'a>) -> Ordering {
+        (&other.streamer.key(), &other.segment_ord).cmp(&(&self.streamer.key(), &self.segment_ord))
+    }
+}
+
+/// Merges streams of terms of any format, used if some of the term dictionaries are not in the
+/// default format.
+struct HeapTermMerger<'a> {
+    heap: BinaryHeap<HeapItem<'a>>,
+    current_streamers: Vec<HeapItem<'a>>,
+}
+
+impl<'a> HeapTermMerger<'a> {
+    fn advance(&mut self) -> bool {
+        for mut heap_item in self.current_streamers.drain(..) {
+            if heap_item.streamer.advance() {
+                self.heap.push(heap_item);
+            }
+        }
+        let Some(head) = self.heap.pop() else {
+            return false;
+        };
+        self.current_streamers.push(head);
+        while let Some(next_streamer) = self.heap.peek() {
+            if self.current_streamers[0].streamer.key() != next_streamer.streamer.key() {
+                break;
+            }
+            let next_heap_it = self.heap.pop().unwrap(); // safe : we peeked beforehand
+            self.current_streamers.push(next_heap_it);
+        }
+        true
+    }
+}
+
+enum TermMergerImpl<'a> {
+    Default(InnerTermMerger<'a>),
+    Heap(HeapTermMerger<'a>),
+}
+
+/// Given a list of sorted term streams,
+/// returns an iterator over sorted unique terms.
+///
+/// The item yielded is actually a pair with
+/// - the term
+/// - a slice with the ordinal of the segments containing
+///   the term.
+pub struct TermMerger<'a>(TermMergerImpl<'a>);
+
+impl<'a> TermMerger<'a> {
+    /// Stream of merged term dictionary
+    pub fn new(streams: Vec<TermStreamer<'a>>) -> TermMerger<'a> {
+        let all_default = streams
+            .iter()
+            .all(|streamer| matches!(streamer.0, TermStreamerImpl::Default(_)));
+        if all_default {
+            let inner_streams = streams
+                .into_iter()
+                .filter_map(|streamer| match streamer.0 {
+                    TermStreamerImpl::Default(inner_streamer) => Some(inner_streamer),
+                    TermStreamerImpl::Decoder(_) => None,
+                })
+                .collect();
+            return TermMerger(TermMergerImpl::Default(InnerTermMerger::new(inner_streams)));
+        }
+        TermMerger(TermMergerImpl::Heap(HeapTermMerger {
+            heap: BinaryHeap::new(),
+            current_streamers: streams
+                .into_iter()
+                .enumerate()
+                .map(|(segment_ord, streamer)| HeapItem {
+                    streamer,
+                    segment_ord,
+                })
+                .collect(),
+        }))
+    }
+
+    /// Advance the term iterator to the next term.
+    /// Returns `true` if there is indeed another term
+    /// `false` if there is none.
+    pub fn advance(&mut self) -> bool {
+        match &mut self.0 {
+            TermMergerImpl::Default(merger) => merger.advance(),
+            TermMergerImpl::Heap(merger) => merger.advance(),
+        }
+    }
+
+    /// Iterator over `(segment ordinal, TermOrdinal)` pairs sorted by segment ordinal
+    ///
+    /// This method may be called
+    /// if [`Self::advance`] has been called before
+    /// and `true` was returned.
+    #[cfg(not(feature = "quickwit"))]
+    pub fn matching_segments<'b: 'a>(&'b self) -> impl 'b + Iterator<Item = (usize, TermOrdinal)> {
+        match &self.0 {
+            TermMergerImpl::Default(merger) => Either::Left(merger.matching_segments()),
+            TermMergerImpl::Heap(merger) => Either::Right(
+                merger
+                    .current_streamers
+                    .iter()
+                    .map(|heap_item| (heap_item.segment_ord, heap_item.streamer.term_ord())),
+            ),
+        }
+    }
+
+    /// Returns the current term.
+    ///
+    /// This method may be called if [`Self::advance`] has been called before
+    /// and `true` was returned.
+    pub fn key(&self) -> &[u8] {
+        match &self.0 {
+            TermMergerImpl::Default(merger) => merger.key(),
+            TermMergerImpl::Heap(merger) => merger.current_streamers[0].streamer.key(),
+        }
+    }
+
+    /// Iterator over `(segment ordinal, TermInfo)` pairs sorted by the ordinal.
+    ///
+    /// This method may be called if [`Self::advance`] has been called before
+    /// and `true` was returned.
+    pub fn current_segment_ords_and_term_infos<'b: 'a>(
+        &'b self,
+    ) -> impl 'b + Iterator<Item = (usize, TermInfo)> {
+        match &self.0 {
+            TermMergerImpl::Default(merger) => {
+                Either::Left(merger.current_segment_ords_and_term_infos())
+            }
+            TermMergerImpl::Heap(merger) => Either::Right(
+                merger
+                    .current_streamers
+                    .iter()
+                    .map(|heap_item| (heap_item.segment_ord, heap_item.streamer.value().clone())),
+            ),
+        }
+    }
+}
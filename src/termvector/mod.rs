@@ -0,0 +1,256 @@
+//! Term vectors store, for each document, the terms of a field with their
+//! positions and offsets.
+//!
+//! They are only available for text fields configured with
+//! [`TextFieldIndexing::set_term_vectors`](crate::schema::TextFieldIndexing::set_term_vectors),
+//! and can be read using [`SegmentReader::term_vector`](crate::SegmentReader::term_vector).
+//!
+//! Term vectors make it possible to highlight a document or to extract its
+//! most significant terms without having to re-analyze its stored text.
+//!
+//! Positions are the same as the positions recorded in the inverted index.
+//! Offsets are byte offsets in the original text. For multivalued fields,
+//! the offsets of a value are shifted by the total length of the previous values.
+mod reader;
+mod serializer;
+mod writer;
+
+use common::{write_u32_vint, VInt};
+
+pub use self::reader::{TermVectorReader, TermVectorReaders};
+pub use self::serializer::TermVectorsSerializer;
+pub use self::writer::TermVectorsWriter;
+
+/// A term of a [`TermVector`], with its positions and offsets in the document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TermVectorTerm {
+    text: String,
+    positions: Vec<u32>,
+    offsets: Vec<(usize, usize)>,
+}
+
+impl TermVectorTerm {
+    /// Returns the text of the term.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the number of occurrences of the term in the document.
+    pub fn term_freq(&self) -> u32 {
+        self.positions.len() as u32
+    }
+
+    /// Returns the positions of the occurrences of the term, in ascending order.
+    pub fn positions(&self) -> &[u32] {
+        &self.positions
+    }
+
+    /// Returns the `(offset_from, offset_to)` byte offsets of the occurrences of the term,
+    /// in the same order as the positions.
+    pub fn offsets(&self) -> &[(usize, usize)] {
+        &self.offsets
+    }
+}
+
+/// The term vector of a field of a document: the terms of the field,
+/// sorted in lexicographic order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TermVector {
+    terms: Vec<TermVectorTerm>,
+}
+
+impl TermVector {
+    /// Returns the terms of the term vector, sorted in lexicographic order.
+    pub fn terms(&self) -> &[TermVectorTerm] {
+        &self.terms
+    }
+
+    /// Returns the term with the given text, if it appears in the document.
+    pub fn get(&self, text: &str) -> Option<&TermVectorTerm> {
+        self.terms
+            .binary_search_by(|term| term.text.as_str().cmp(text))
+            .ok()
+            .map(|ord| &self.terms[ord])
+    }
+
+    /// Returns the number of distinct terms.
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    /// Returns true if the document does not have any term for the field.
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Serializes the term vector.
+    ///
+    /// The terms are encoded as their length and bytes, followed by their term frequency and,
+    /// for each occurrence, the delta-encoded position and offset, and the length of the token.
+    fn serialize(&self, output: &mut Vec<u8>) {
+        write_vint(self.terms.len() as u32, output);
+        for term in &self.terms {
+            write_vint(term.text.len() as u32, output);
+            output.extend_from_slice(term.text.as_bytes());
+            write_vint(term.term_freq(), output);
+            let mut previous_position = 0u32;
+            let mut previous_offset_from = 0usize;
+            for (&position, &(offset_from, offset_to)) in term.positions.iter().zip(&term.offsets) {
+                write_vint(position - previous_position, output);
+                write_vint((offset_from - previous_offset_from) as u32, output);
+                write_vint((offset_to - offset_from) as u32, output);
+                previous_position = position;
+                previous_offset_from = offset_from;
+            }
+        }
+    }
+
+    /// Deserializes a term vector serialized with [`TermVector::serialize`].
+    fn deserialize(mut data: &[u8]) -> crate::Result<TermVector> {
+        if data.is_empty() {
+            return Ok(TermVector::default());
+        }
+        let num_terms = read_vint(&mut data)? as usize;
+        // Each term takes at least two bytes, which bounds the capacity of corrupted data.
+        let mut terms = Vec::with_capacity(num_terms.min(data.len() / 2));
+        for _ in 0..num_terms {
+            let text_len = read_vint(&mut data)? as usize;
+            if text_len > data.len() {
+                return Err(corrupted("Term vector is truncated"));
+            }
+            let (text_bytes, rest) = data.split_at(text_len);
+            data = rest;
+            let text = String::from_utf8(text_bytes.to_vec())
+                .map_err(|_| corrupted("Term vector contains a term that is not valid UTF-8"))?;
+            let term_freq = read_vint(&mut data)? as usize;
+            // Each occurrence takes at least three bytes.
+            let capacity = term_freq.min(data.len() / 3);
+            let mut positions = Vec::with_capacity(capacity);
+            let mut offsets = Vec::with_capacity(capacity);
+            let mut position = 0u32;
+            let mut offset_from = 0usize;
+            for _ in 0..term_freq {
+                let overflow = || corrupted("Term vector contains an invalid position or offset");
+                position = position
+                    .checked_add(read_vint(&mut data)?)
+                    .ok_or_else(overflow)?;
+                offset_from = offset_from
+                    .checked_add(read_vint(&mut data)? as usize)
+                    .ok_or_else(overflow)?;
+                let offset_to = offset_from
+                    .checked_add(read_vint(&mut data)? as usize)
+                    .ok_or_else(overflow)?;
+                positions.push(position);
+                offsets.push((offset_from, offset_to));
+            }
+            terms.push(TermVectorTerm {
+                text,
+                positions,
+                offsets,
+            });
+        }
+        Ok(TermVector { terms })
+    }
+}
+
+fn corrupted(comment: &str) -> crate::TantivyError {
+    crate::TantivyError::DataCorruption(crate::error::DataCorruption::comment_only(comment))
+}
+
+fn read_vint(data: &mut &[u8]) -> crate::Result<u32> {
+    VInt::deserialize_u64(data)
+        .ok()
+        .and_then(|val| u32::try_from(val).ok())
+        .ok_or_else(|| corrupted("Term vector is truncated or contains an invalid vint"))
+}
+
+fn write_vint(val: u32, output: &mut Vec<u8>) {
+    // Writing to a `Vec` cannot fail.
+    write_u32_vint(val, output).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TermVector, TermVectorTerm};
+    use crate::schema::{Schema, TextFieldIndexing, TextOptions, TEXT};
+    use crate::{Document, Index, TantivyError};
+
+    #[test]
+    fn test_term_vectors() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("default")
+                .set_term_vectors(true),
+        );
+        let body = schema_builder.add_text_field("body", text_options);
+        let title = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            let mut doc = Document::default();
+            doc.add_text(body, "The quick fox and the dog");
+            doc.add_text(body, "the end");
+            doc.add_text(title, "Title");
+            index_writer.add_document(doc)?;
+            index_writer.add_document(doc!(title=>"no body"))?;
+            index_writer.commit()?;
+            index_writer.add_document(doc!(body=>"a dog"))?;
+            index_writer.commit()?;
+        }
+        let check = |index: &Index, num_segments: usize| -> crate::Result<()> {
+            let searcher = index.reader()?.searcher();
+            assert_eq!(searcher.segment_readers().len(), num_segments);
+            let segment_reader = searcher.segment_reader(0);
+            assert!(segment_reader.term_vector(0, title).is_err());
+
+            let term_vector = segment_reader.term_vector(0, body)?;
+            let texts: Vec<&str> = term_vector.terms().iter().map(|term| term.text()).collect();
+            assert_eq!(texts, vec!["and", "dog", "end", "fox", "quick", "the"]);
+            let the = term_vector.get("the").unwrap();
+            assert_eq!(the.term_freq(), 3);
+            assert_eq!(the.positions(), &[0, 4, 7]);
+            assert_eq!(the.offsets(), &[(0, 3), (18, 21), (25, 28)]);
+            assert!(term_vector.get("cat").is_none());
+
+            assert!(segment_reader.term_vector(1, body)?.is_empty());
+            Ok(())
+        };
+        check(&index, 2)?;
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            let segment_ids = index.searchable_segment_ids()?;
+            index_writer.merge(&segment_ids).wait()?;
+            index_writer.wait_merging_threads()?;
+        }
+        check(&index, 1)?;
+        let searcher = index.reader()?.searcher();
+        let term_vector = searcher.segment_reader(0).term_vector(2, body)?;
+        assert_eq!(term_vector.get("dog").unwrap().offsets(), &[(2, 5)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_corrupted_term_vector() {
+        let term_vector = TermVector {
+            terms: vec![TermVectorTerm {
+                text: "dog".to_string(),
+                positions: vec![1, 3],
+                offsets: vec![(2, 5), (10, 13)],
+            }],
+        };
+        let mut data = Vec::new();
+        term_vector.serialize(&mut data);
+        assert_eq!(TermVector::deserialize(&data).unwrap().terms(), term_vector.terms());
+        for len in 1..data.len() {
+            assert!(matches!(
+                TermVector::deserialize(&data[..len]),
+                Err(TantivyError::DataCorruption(_))
+            ));
+        }
+        assert!(matches!(
+            TermVector::deserialize(&[1, 255, 255, 255, 255, 255]),
+            Err(TantivyError::DataCorruption(_))
+        ));
+    }
+}
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use super::TermVector;
+use crate::directory::{CompositeFile, FileSlice, OwnedBytes};
+use crate::schema::Field;
+use crate::space_usage::PerFieldSpaceUsage;
+use crate::DocId;
+
+/// Reader for the term vectors of all of the fields storing term vectors.
+#[derive(Clone)]
+pub struct TermVectorReaders {
+    data: Arc<CompositeFile>,
+}
+
+impl TermVectorReaders {
+    /// Creates a term vector reader.
+    pub fn open(file: FileSlice) -> crate::Result<TermVectorReaders> {
+        let data = CompositeFile::open(&file)?;
+        Ok(TermVectorReaders {
+            data: Arc::new(data),
+        })
+    }
+
+    /// Creates a term vector reader for a segment without any term vectors.
+    pub fn empty() -> TermVectorReaders {
+        TermVectorReaders {
+            data: Arc::new(CompositeFile::empty()),
+        }
+    }
+
+    /// Returns the `TermVectorReader` for a specific field.
+    pub fn get_field(&self, field: Field) -> crate::Result<Option<TermVectorReader>> {
+        if let Some(file) = self.data.open_read(field) {
+            let term_vector_reader = TermVectorReader::open(file)?;
+            Ok(Some(term_vector_reader))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Return a break down of the space usage per field.
+    pub fn space_usage(&self) -> PerFieldSpaceUsage {
+        self.data.space_usage()
+    }
+}
+
+/// Reads the term vectors of a given field.
+#[derive(Clone)]
+pub struct TermVectorReader {
+    num_docs: u32,
+    data: OwnedBytes,
+    doc_offsets: OwnedBytes,
+}
+
+impl TermVectorReader {
+    /// Opens the term vectors of a field.
+    pub fn open(file: FileSlice) -> crate::Result<TermVectorReader> {
+        let (body, footer) = file.split_from_end(4);
+        let footer = footer.read_bytes()?;
+        let num_docs = u32::from_le_bytes(footer.as_slice().try_into().unwrap());
+        let (data, doc_offsets) = body.split_from_end((num_docs as usize + 1) * 8);
+        Ok(TermVectorReader {
+            num_docs,
+            data: data.read_bytes()?,
+            doc_offsets: doc_offsets.read_bytes()?,
+        })
+    }
+
+    /// Returns the number of documents.
+    pub fn num_docs(&self) -> u32 {
+        self.num_docs
+    }
+
+    fn doc_offset(&self, doc: DocId) -> usize {
+        let start = doc as usize * 8;
+        let bytes = &self.doc_offsets.as_slice()[start..start + 8];
+        u64::from_le_bytes(bytes.try_into().unwrap()) as usize
+    }
+
+    /// Returns the serialized term vector of the given document.
+    pub(crate) fn doc_bytes(&self, doc: DocId) -> &[u8] {
+        if doc >= self.num_docs {
+            return &[];
+        }
+        &self.data.as_slice()[self.doc_offset(doc)..self.doc_offset(doc + 1)]
+    }
+
+    /// Returns the term vector of the given document.
+    pub fn term_vector(&self, doc: DocId) -> crate::Result<TermVector> {
+        TermVector::deserialize(self.doc_bytes(doc))
+    }
+}
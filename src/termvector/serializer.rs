@@ -0,0 +1,54 @@
+use std::io;
+use std::io::Write;
+
+use crate::directory::{CompositeWrite, WritePtr};
+use crate::schema::Field;
+
+/// The term vectors serializer is in charge of
+/// the serialization of the term vectors of all fields.
+///
+/// For each field, the serialized data is:
+/// - the serialized term vector of each document,
+/// - the start offset of each document, followed by the end offset of the last document, as `u64`s,
+/// - the number of documents, as a `u32`.
+pub struct TermVectorsSerializer {
+    composite_write: CompositeWrite,
+}
+
+impl TermVectorsSerializer {
+    /// Constructor
+    pub fn from_write(write: WritePtr) -> io::Result<TermVectorsSerializer> {
+        let composite_write = CompositeWrite::wrap(write);
+        Ok(TermVectorsSerializer { composite_write })
+    }
+
+    /// Serialize the term vectors of the given field, given the serialized
+    /// term vector of each document.
+    pub fn serialize_field<'a>(
+        &mut self,
+        field: Field,
+        docs: impl Iterator<Item = &'a [u8]>,
+    ) -> io::Result<()> {
+        let write = self.composite_write.for_field(field);
+        let mut doc_offsets: Vec<u64> = vec![0];
+        let mut offset = 0u64;
+        for doc_data in docs {
+            write.write_all(doc_data)?;
+            offset += doc_data.len() as u64;
+            doc_offsets.push(offset);
+        }
+        for doc_offset in &doc_offsets {
+            write.write_all(&doc_offset.to_le_bytes())?;
+        }
+        let num_docs = (doc_offsets.len() - 1) as u32;
+        write.write_all(&num_docs.to_le_bytes())?;
+        write.flush()?;
+        Ok(())
+    }
+
+    /// Clean up / flush / close
+    pub fn close(self) -> io::Result<()> {
+        self.composite_write.close()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+use std::{io, iter};
+
+use super::{TermVector, TermVectorTerm, TermVectorsSerializer};
+use crate::indexer::doc_id_mapping::DocIdMapping;
+use crate::schema::{Field, Schema};
+use crate::tokenizer::Token;
+use crate::DocId;
+
+/// The `TermVectorsWriter` is in charge of buffering the serialized term vectors
+/// of each document for each field storing term vectors.
+pub struct TermVectorsWriter {
+    term_vectors_buffers: Vec<Option<TermVectorsBuffer>>,
+}
+
+#[derive(Default)]
+struct TermVectorsBuffer {
+    // Start offset of the term vector of each document in `data`.
+    doc_offsets: Vec<usize>,
+    data: Vec<u8>,
+}
+
+impl TermVectorsBuffer {
+    fn doc_data(&self, doc: DocId) -> &[u8] {
+        let start = self.doc_offsets[doc as usize];
+        let end = self
+            .doc_offsets
+            .get(doc as usize + 1)
+            .copied()
+            .unwrap_or(self.data.len());
+        &self.data[start..end]
+    }
+}
+
+impl TermVectorsWriter {
+    /// Initialize with state for tracking the fields storing term vectors
+    /// specified in the schema.
+    pub fn for_schema(schema: &Schema) -> TermVectorsWriter {
+        let mut term_vectors_buffers: Vec<Option<TermVectorsBuffer>> = iter::repeat_with(|| None)
+            .take(schema.num_fields())
+            .collect();
+        for (field, field_entry) in schema.fields() {
+            if field_entry.has_term_vectors() {
+                term_vectors_buffers[field.field_id() as usize] =
+                    Some(TermVectorsBuffer::default());
+            }
+        }
+        TermVectorsWriter {
+            term_vectors_buffers,
+        }
+    }
+
+    /// The memory used inclusive childs
+    pub fn mem_usage(&self) -> usize {
+        self.term_vectors_buffers
+            .iter()
+            .flatten()
+            .map(|buffer| {
+                buffer.data.capacity()
+                    + buffer.doc_offsets.capacity() * std::mem::size_of::<usize>()
+            })
+            .sum()
+    }
+
+    /// Ensure that all documents in 0..max_doc have a term vector associated with them
+    /// in each of the fields.
+    ///
+    /// Documents that have not been seen get an empty term vector.
+    pub fn fill_up_to_max_doc(&mut self, max_doc: DocId) {
+        for buffer in self.term_vectors_buffers.iter_mut().flatten() {
+            buffer
+                .doc_offsets
+                .resize(max_doc as usize, buffer.data.len());
+        }
+    }
+
+    /// Records the term vector of the given document and field, given its tokens.
+    ///
+    /// The positions and offsets of the tokens are expected to be relative to the
+    /// beginning of the field.
+    pub fn record(&mut self, doc: DocId, field: Field, tokens: &[Token]) {
+        let Some(buffer) = self
+            .term_vectors_buffers
+            .get_mut(field.field_id() as usize)
+            .and_then(Option::as_mut)
+        else {
+            return;
+        };
+        assert!(
+            buffer.doc_offsets.len() <= doc as usize,
+            "Cannot register a given term vector twice"
+        );
+        buffer.doc_offsets.resize(doc as usize, buffer.data.len());
+        buffer.doc_offsets.push(buffer.data.len());
+        let mut terms: BTreeMap<&str, TermVectorTerm> = BTreeMap::new();
+        for token in tokens {
+            let term = terms
+                .entry(token.text.as_str())
+                .or_insert_with(|| TermVectorTerm {
+                    text: token.text.clone(),
+                    positions: Vec::new(),
+                    offsets: Vec::new(),
+                });
+            term.positions.push(token.position as u32);
+            term.offsets.push((token.offset_from, token.offset_to));
+        }
+        let term_vector = TermVector {
+            terms: terms.into_values().collect(),
+        };
+        term_vector.serialize(&mut buffer.data);
+    }
+
+    /// Serialize the term vectors of all fields to the serializer.
+    pub fn serialize(
+        &self,
+        mut term_vectors_serializer: TermVectorsSerializer,
+        doc_id_map: Option<&DocIdMapping>,
+    ) -> io::Result<()> {
+        for (field_id, buffer_opt) in self.term_vectors_buffers.iter().enumerate() {
+            let Some(buffer) = buffer_opt else {
+                continue;
+            };
+            let field = Field::from_field_id(field_id as u32);
+            let num_docs = buffer.doc_offsets.len() as DocId;
+            if let Some(doc_id_map) = doc_id_map {
+                let docs = doc_id_map
+                    .iter_old_doc_ids()
+                    .map(|old_doc| buffer.doc_data(old_doc));
+                term_vectors_serializer.serialize_field(field, docs)?;
+            } else {
+                let docs = (0..num_docs).map(|doc| buffer.doc_data(doc));
+                term_vectors_serializer.serialize_field(field, docs)?;
+            }
+        }
+        term_vectors_serializer.close()?;
+        Ok(())
+    }
+}
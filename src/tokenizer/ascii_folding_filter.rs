@@ -5,19 +5,55 @@ use super::{Token, TokenFilter, TokenStream, Tokenizer};
 /// This class converts alphabetic, numeric, and symbolic Unicode characters
 /// which are not in the first 127 ASCII characters (the "Basic Latin" Unicode
 /// block) into their ASCII equivalents, if one exists.
-#[derive(Clone)]
-pub struct AsciiFoldingFilter;
+///
+/// Optionally, the original tokens can be emitted as well, right after their folded
+/// version and at the same position, so that both the folded and the exact spelling
+/// of a word match.
+///
+/// ```rust
+/// use tantivy::tokenizer::*;
+///
+/// let tokenizer = TextAnalyzer::builder(SimpleTokenizer)
+///     .filter(AsciiFoldingFilter::default().set_preserve_original(true))
+///     .build();
+///
+/// let mut stream = tokenizer.token_stream("résumé cv");
+/// assert_eq!(stream.next().unwrap().text, "resume");
+/// assert_eq!(stream.next().unwrap().text, "résumé");
+/// assert_eq!(stream.next().unwrap().text, "cv");
+/// assert!(stream.next().is_none());
+/// ```
+#[derive(Clone, Default)]
+pub struct AsciiFoldingFilter {
+    preserve_original: bool,
+}
+
+impl AsciiFoldingFilter {
+    /// Sets whether the original tokens should be emitted as well, when they are not
+    /// already ASCII.
+    #[must_use]
+    pub fn set_preserve_original(mut self, preserve_original: bool) -> AsciiFoldingFilter {
+        self.preserve_original = preserve_original;
+        self
+    }
+}
 
 impl TokenFilter for AsciiFoldingFilter {
     type Tokenizer<T: Tokenizer> = AsciiFoldingFilterWrapper<T>;
 
     fn transform<T: Tokenizer>(self, tokenizer: T) -> AsciiFoldingFilterWrapper<T> {
-        AsciiFoldingFilterWrapper(tokenizer)
+        AsciiFoldingFilterWrapper {
+            preserve_original: self.preserve_original,
+            inner: tokenizer,
+        }
     }
 }
 
 #[derive(Clone)]
-pub struct AsciiFoldingFilterWrapper<T>(T);
+pub struct AsciiFoldingFilterWrapper<T> {
+    preserve_original: bool,
+    inner: T,
+}
 
 impl<T: Tokenizer> Tokenizer for AsciiFoldingFilterWrapper<T> {
     type TokenStream<'a> = AsciiFoldingFilterTokenStream<T::TokenStream<'a>>;
@@ -25,7 +61,11 @@ impl<T: Tokenizer> Tokenizer for AsciiFoldingFilterWrapper<T> {
     fn token_stream<'a>(&self, text: &'a str) -> Self::TokenStream<'a> {
         AsciiFoldingFilterTokenStream {
             buffer: String::with_capacity(100),
-            tail: self.0.token_stream(text),
+            tail: self.inner.token_stream(text),
+            preserve_original: self.preserve_original,
+            original: Token::default(),
+            has_pending_original: false,
+            is_original: false,
         }
     }
 }
@@ -33,27 +73,52 @@ impl<T: Tokenizer> Tokenizer for AsciiFoldingFilterWrapper<T> {
 pub struct AsciiFoldingFilterTokenStream<T> {
     buffer: String,
     tail: T,
+    preserve_original: bool,
+    // The original version of the last folded token.
+    original: Token,
+    // True if `original` should be emitted on the next call to `advance`.
+    has_pending_original: bool,
+    // True if the current token is `original`.
+    is_original: bool,
 }
 
 impl<T: TokenStream> TokenStream for AsciiFoldingFilterTokenStream<T> {
     fn advance(&mut self) -> bool {
+        if self.has_pending_original {
+            self.has_pending_original = false;
+            self.is_original = true;
+            return true;
+        }
+        self.is_original = false;
         if !self.tail.advance() {
             return false;
         }
-        if !self.token_mut().text.is_ascii() {
+        if !self.tail.token().text.is_ascii() {
             // ignore its already ascii
             to_ascii(&self.tail.token().text, &mut self.buffer);
+            if self.preserve_original && self.buffer != self.tail.token().text {
+                self.original.clone_from(self.tail.token());
+                self.has_pending_original = true;
+            }
             mem::swap(&mut self.tail.token_mut().text, &mut self.buffer);
         }
         true
     }
 
     fn token(&self) -> &Token {
-        self.tail.token()
+        if self.is_original {
+            &self.original
+        } else {
+            self.tail.token()
+        }
     }
 
     fn token_mut(&mut self) -> &mut Token {
-        self.tail.token_mut()
+        if self.is_original {
+            &mut self.original
+        } else {
+            self.tail.token_mut()
+        }
     }
 }
 
@@ -1566,6 +1631,27 @@ mod tests {
         assert_eq!(&folding_helper("âäàéè"), &["aaaee"]);
     }
 
+    #[test]
+    fn test_ascii_folding_preserve_original() {
+        let mut tokens = Vec::new();
+        TextAnalyzer::builder(SimpleTokenizer)
+            .filter(AsciiFoldingFilter::default().set_preserve_original(true))
+            .build()
+            .token_stream("Ràmon Usagi 馬")
+            .process(&mut |token| {
+                tokens.push((token.text.clone(), token.position));
+            });
+        assert_eq!(
+            tokens,
+            vec![
+                ("Ramon".to_string(), 0),
+                ("Ràmon".to_string(), 0),
+                ("Usagi".to_string(), 1),
+                ("馬".to_string(), 2),
+            ]
+        );
+    }
+
     #[test]
     fn test_no_change() {
         assert_eq!(&folding_helper("Usagi"), &["Usagi"]);
@@ -1574,7 +1660,7 @@ mod tests {
     fn folding_helper(text: &str) -> Vec<String> {
         let mut tokens = Vec::new();
         TextAnalyzer::builder(SimpleTokenizer)
-            .filter(AsciiFoldingFilter)
+            .filter(AsciiFoldingFilter::default())
             .build()
             .token_stream(text)
             .process(&mut |token| {
@@ -1585,7 +1671,7 @@ mod tests {
 
     fn folding_using_raw_tokenizer_helper(text: &str) -> String {
         let mut token_stream = TextAnalyzer::builder(RawTokenizer)
-            .filter(AsciiFoldingFilter)
+            .filter(AsciiFoldingFilter::default())
             .build()
             .token_stream(text);
         token_stream.advance();
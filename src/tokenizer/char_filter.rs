@@ -0,0 +1,191 @@
+use std::ops::Range;
+
+use crate::tokenizer::{Token, TokenStream};
+
+/// `CharFilter`s transform a text before it is tokenized, e.g. to remove markup or to
+/// normalize some characters.
+///
+/// Character filters are appended to a [`TextAnalyzer`](super::TextAnalyzer) using
+/// [`TextAnalyzerBuilder::char_filter()`](super::TextAnalyzerBuilder::char_filter).
+/// They record how the filtered text relates to its input, so that the offsets of the
+/// tokens still point into the original text.
+pub trait CharFilter: 'static + Send + Sync {
+    /// Filters the text.
+    fn filter(&self, text: &str) -> FilteredText;
+}
+
+/// A chunk of the filtered text, and the range of the input text it was built from.
+#[derive(Clone, Debug)]
+struct Segment {
+    filtered: Range<usize>,
+    original: Range<usize>,
+    // True if the chunk was copied as is from the input text.
+    is_verbatim: bool,
+}
+
+/// The output of a [`CharFilter`]: the filtered text, and the mapping of its byte offsets
+/// to the byte offsets of the input text.
+///
+/// It is built by appending, in order, the chunks of the input text which are kept as is,
+/// and the replacements of the other chunks.
+#[derive(Clone, Debug, Default)]
+pub struct FilteredText {
+    text: String,
+    segments: Vec<Segment>,
+}
+
+impl FilteredText {
+    /// Appends `text`, copied from the input text at `original_offset`.
+    pub fn push_original(&mut self, text: &str, original_offset: usize) {
+        let original = original_offset..original_offset + text.len();
+        if let Some(last_segment) = self.segments.last_mut() {
+            if last_segment.is_verbatim && last_segment.original.end == original.start {
+                self.text.push_str(text);
+                last_segment.filtered.end = self.text.len();
+                last_segment.original.end = original.end;
+                return;
+            }
+        }
+        self.push_segment(text, original, true);
+    }
+
+    /// Appends `replacement` in place of the `original_range` of the input text.
+    ///
+    /// The replacement may be empty, to remove a chunk of the input text.
+    pub fn push_replacement(&mut self, replacement: &str, original_range: Range<usize>) {
+        self.push_segment(replacement, original_range, false);
+    }
+
+    fn push_segment(&mut self, text: &str, original: Range<usize>, is_verbatim: bool) {
+        let filtered = self.text.len()..self.text.len() + text.len();
+        self.text.push_str(text);
+        self.segments.push(Segment {
+            filtered,
+            original,
+            is_verbatim,
+        });
+    }
+
+    /// Returns the filtered text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Maps the offset of the start of a token of the filtered text to the input text.
+    ///
+    /// The start of a token following a removed chunk is mapped after that chunk, and
+    /// the start of a token within a replacement is mapped to the start of the replaced
+    /// chunk.
+    pub fn original_offset_from(&self, offset: usize) -> usize {
+        let segment_ord = self
+            .segments
+            .partition_point(|segment| segment.filtered.end <= offset);
+        match self.segments.get(segment_ord) {
+            Some(segment) if segment.is_verbatim => {
+                segment.original.start + offset - segment.filtered.start
+            }
+            Some(segment) => segment.original.start,
+            None => self.original_len(),
+        }
+    }
+
+    /// Maps the offset of the end of a token of the filtered text to the input text.
+    ///
+    /// The end of a token preceding a removed chunk is mapped before that chunk, and the
+    /// end of a token within a replacement is mapped to the end of the replaced chunk.
+    pub fn original_offset_to(&self, offset: usize) -> usize {
+        if offset == 0 {
+            return self
+                .segments
+                .first()
+                .map(|segment| segment.original.start)
+                .unwrap_or(0);
+        }
+        let segment_ord = self
+            .segments
+            .partition_point(|segment| segment.filtered.end < offset);
+        match self.segments.get(segment_ord) {
+            Some(segment) if segment.is_verbatim => {
+                segment.original.start + offset - segment.filtered.start
+            }
+            Some(segment) => segment.original.end,
+            None => self.original_len(),
+        }
+    }
+
+    fn original_len(&self) -> usize {
+        self.segments
+            .last()
+            .map(|segment| segment.original.end)
+            .unwrap_or(0)
+    }
+}
+
+/// Token stream of a `TextAnalyzer` with character filters: the tokens of the filtered
+/// text, with offsets mapped to the original text.
+pub(crate) struct CharFilteredTokenStream {
+    tokens: std::vec::IntoIter<Token>,
+    token: Token,
+}
+
+impl CharFilteredTokenStream {
+    pub(crate) fn new(token_stream: &mut dyn TokenStream, filtered_texts: &[FilteredText]) -> Self {
+        let mut tokens = Vec::new();
+        token_stream.process(&mut |token| {
+            let mut token = token.clone();
+            for filtered_text in filtered_texts.iter().rev() {
+                token.offset_from = filtered_text.original_offset_from(token.offset_from);
+                token.offset_to = filtered_text.original_offset_to(token.offset_to);
+            }
+            tokens.push(token);
+        });
+        CharFilteredTokenStream {
+            tokens: tokens.into_iter(),
+            token: Token::default(),
+        }
+    }
+}
+
+impl TokenStream for CharFilteredTokenStream {
+    fn advance(&mut self) -> bool {
+        if let Some(token) = self.tokens.next() {
+            self.token = token;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FilteredText;
+
+    #[test]
+    fn test_filtered_text_offsets() {
+        // "<b>a&amp;b</b> c" -> "a&b c"
+        let mut filtered_text = FilteredText::default();
+        filtered_text.push_replacement("", 0..3);
+        filtered_text.push_original("a", 3);
+        filtered_text.push_replacement("&", 4..9);
+        filtered_text.push_original("b", 9);
+        filtered_text.push_replacement("", 10..14);
+        filtered_text.push_original(" c", 14);
+        assert_eq!(filtered_text.text(), "a&b c");
+        assert_eq!(filtered_text.original_offset_from(0), 3);
+        assert_eq!(filtered_text.original_offset_to(1), 4);
+        assert_eq!(filtered_text.original_offset_from(1), 4);
+        assert_eq!(filtered_text.original_offset_to(2), 9);
+        assert_eq!(filtered_text.original_offset_to(3), 10);
+        assert_eq!(filtered_text.original_offset_from(4), 15);
+        assert_eq!(filtered_text.original_offset_to(5), 16);
+    }
+}
@@ -0,0 +1,215 @@
+//! # Example
+//! ```rust
+//! use tantivy::tokenizer::*;
+//!
+//! let tokenizer = TextAnalyzer::builder(SimpleTokenizer)
+//!   .filter(CjkBigramFilter)
+//!   .filter(LowerCaser)
+//!   .build();
+//!
+//! let mut stream = tokenizer.token_stream("北京大学 Tantivy");
+//! assert_eq!(stream.next().unwrap().text, "北京");
+//! assert_eq!(stream.next().unwrap().text, "京大");
+//! assert_eq!(stream.next().unwrap().text, "大学");
+//! assert_eq!(stream.next().unwrap().text, "tantivy");
+//! assert!(stream.next().is_none());
+//! ```
+use std::collections::VecDeque;
+
+use super::{Token, TokenFilter, TokenStream, Tokenizer};
+
+/// `CjkBigramFilter` splits the runs of Han, Hiragana, Katakana and Hangul characters of
+/// the tokens into overlapping bigrams, and leaves the other characters alone.
+///
+/// Chinese and Japanese do not separate words with whitespaces, so that tokenizers like the
+/// [`SimpleTokenizer`](super::SimpleTokenizer) return whole sentences as a single token.
+/// Indexing bigrams gives a reasonable recall without a morphological analyzer, and phrase
+/// queries on the bigrams match the original sequence of characters.
+///
+/// Each bigram gets its own position. A run made of a single character is emitted as is.
+#[derive(Clone)]
+pub struct CjkBigramFilter;
+
+impl TokenFilter for CjkBigramFilter {
+    type Tokenizer<T: Tokenizer> = CjkBigramFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> CjkBigramFilterWrapper<T> {
+        CjkBigramFilterWrapper(tokenizer)
+    }
+}
+
+#[derive(Clone)]
+pub struct CjkBigramFilterWrapper<T>(T);
+
+impl<T: Tokenizer> Tokenizer for CjkBigramFilterWrapper<T> {
+    type TokenStream<'a> = CjkBigramFilterStream<T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&self, text: &'a str) -> Self::TokenStream<'a> {
+        CjkBigramFilterStream {
+            tail: self.0.token_stream(text),
+            pending: VecDeque::new(),
+            token: Token::default(),
+            position_shift: 0,
+        }
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        // Han
+        0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0x20000..=0x2EBEF | 0x2F800..=0x2FA1F
+        // Hiragana and Katakana
+        | 0x3040..=0x30FF | 0x31F0..=0x31FF | 0xFF66..=0xFF9F
+        // Hangul
+        | 0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF)
+}
+
+pub struct CjkBigramFilterStream<T> {
+    tail: T,
+    // Tokens built, but not emitted yet.
+    pending: VecDeque<Token>,
+    token: Token,
+    // Number of positions inserted by the tokens split so far.
+    position_shift: usize,
+}
+
+impl<T: TokenStream> CjkBigramFilterStream<T> {
+    // Splits the current token of the tail, and appends the resulting tokens to the pending
+    // tokens.
+    fn process_token(&mut self) {
+        let token = self.tail.token();
+        let mut position = token.position + self.position_shift;
+        if !token.text.chars().any(is_cjk) {
+            self.pending.push_back(Token {
+                position,
+                ..token.clone()
+            });
+            return;
+        }
+        // The offsets of the characters can only be computed if the text of the token has
+        // not been changed by a previous filter.
+        let has_original_offsets = token.offset_to - token.offset_from == token.text.len();
+        let mut push = |start: usize, end: usize| {
+            let (offset_from, offset_to) = if has_original_offsets {
+                (token.offset_from + start, token.offset_from + end)
+            } else {
+                (token.offset_from, token.offset_to)
+            };
+            self.pending.push_back(Token {
+                offset_from,
+                offset_to,
+                position,
+                text: token.text[start..end].to_string(),
+                position_length: 1,
+                keyword: false,
+                payload: Vec::new(),
+            });
+            position += 1;
+        };
+        let mut chars = token.text.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            let c_is_cjk = is_cjk(c);
+            // Byte ranges of the characters of the run, for CJK runs.
+            let mut end = start + c.len_utf8();
+            let mut run_chars = Vec::new();
+            run_chars.push(start..end);
+            while let Some(&(next_start, next_c)) = chars.peek() {
+                if is_cjk(next_c) != c_is_cjk {
+                    break;
+                }
+                end = next_start + next_c.len_utf8();
+                if c_is_cjk {
+                    run_chars.push(next_start..end);
+                }
+                chars.next();
+            }
+            if c_is_cjk && run_chars.len() > 1 {
+                for bigram in run_chars.windows(2) {
+                    push(bigram[0].start, bigram[1].end);
+                }
+            } else {
+                push(start, end);
+            }
+        }
+        self.position_shift = position - token.position - 1;
+    }
+}
+
+impl<T: TokenStream> TokenStream for CjkBigramFilterStream<T> {
+    fn advance(&mut self) -> bool {
+        while self.pending.is_empty() {
+            if !self.tail.advance() {
+                return false;
+            }
+            self.process_token();
+        }
+        self.token = self.pending.pop_front().unwrap();
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CjkBigramFilter;
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{LowerCaser, SimpleTokenizer, TextAnalyzer, Token};
+
+    fn token_stream_helper(text: &str) -> Vec<Token> {
+        let analyzer = TextAnalyzer::builder(SimpleTokenizer)
+            .filter(CjkBigramFilter)
+            .build();
+        let mut token_stream = analyzer.token_stream(text);
+        let mut tokens: Vec<Token> = vec![];
+        let mut add_token = |token: &Token| {
+            tokens.push(token.clone());
+        };
+        token_stream.process(&mut add_token);
+        tokens
+    }
+
+    #[test]
+    fn test_cjk_bigram_filter() {
+        let tokens = token_stream_helper("hello 我爱北京 world");
+        assert_eq!(tokens.len(), 5);
+        assert_token(&tokens[0], 0, "hello", 0, 5);
+        assert_token(&tokens[1], 1, "我爱", 6, 12);
+        assert_token(&tokens[2], 2, "爱北", 9, 15);
+        assert_token(&tokens[3], 3, "北京", 12, 18);
+        assert_token(&tokens[4], 4, "world", 19, 24);
+    }
+
+    #[test]
+    fn test_cjk_bigram_filter_mixed_scripts() {
+        let tokens = token_stream_helper("iPhone用 한국어 猫");
+        assert_eq!(tokens.len(), 5);
+        assert_token(&tokens[0], 0, "iPhone", 0, 6);
+        assert_token(&tokens[1], 1, "用", 6, 9);
+        assert_token(&tokens[2], 2, "한국", 10, 16);
+        assert_token(&tokens[3], 3, "국어", 13, 19);
+        assert_token(&tokens[4], 4, "猫", 20, 23);
+    }
+
+    #[test]
+    fn test_cjk_bigram_filter_changed_text() {
+        // Offsets cannot be computed once the length of the text has changed.
+        let analyzer = TextAnalyzer::builder(SimpleTokenizer)
+            .filter(LowerCaser)
+            .filter(CjkBigramFilter)
+            .build();
+        let mut token_stream = analyzer.token_stream("İstanbul東京");
+        let token = token_stream.next().unwrap();
+        assert_eq!(token.text, "i\u{307}stanbul");
+        assert_eq!((token.offset_from, token.offset_to), (0, 15));
+        let token = token_stream.next().unwrap();
+        assert_token(token, 1, "東京", 0, 15);
+        assert!(token_stream.next().is_none());
+    }
+}
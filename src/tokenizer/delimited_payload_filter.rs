@@ -0,0 +1,165 @@
+//! # Example
+//! ```rust
+//! use tantivy::tokenizer::*;
+//!
+//! let tokenizer = TextAnalyzer::builder(WhitespaceTokenizer)
+//!   .filter(DelimitedPayloadFilter::new('|', PayloadEncoding::F32))
+//!   .build();
+//!
+//! let mut stream = tokenizer.token_stream("paris|0.9 hilton");
+//! let token = stream.next().unwrap();
+//! assert_eq!(token.text, "paris");
+//! assert_eq!(token.payload, 0.9f32.to_le_bytes());
+//! let token = stream.next().unwrap();
+//! assert_eq!(token.text, "hilton");
+//! assert!(token.payload.is_empty());
+//! assert!(stream.next().is_none());
+//! ```
+use serde::{Deserialize, Serialize};
+
+use super::{Token, TokenFilter, TokenStream, Tokenizer};
+
+/// How the text following the delimiter is encoded into the payload.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadEncoding {
+    /// The payload is the UTF-8 bytes of the text.
+    #[default]
+    Text,
+    /// The text is parsed as a float, and the payload is its little-endian `f32`
+    /// representation. Tokens for which the text is not a valid float get no payload.
+    F32,
+}
+
+/// `DelimitedPayloadFilter` splits the tokens of the form `text|payload` on a delimiter,
+/// keeps the text as the token, and attaches the payload to it.
+///
+/// Tokens without the delimiter are left unchanged.
+#[derive(Clone)]
+pub struct DelimitedPayloadFilter {
+    delimiter: char,
+    encoding: PayloadEncoding,
+}
+
+impl DelimitedPayloadFilter {
+    /// Creates a `DelimitedPayloadFilter` splitting the tokens on the last occurrence of
+    /// `delimiter`.
+    pub fn new(delimiter: char, encoding: PayloadEncoding) -> DelimitedPayloadFilter {
+        DelimitedPayloadFilter {
+            delimiter,
+            encoding,
+        }
+    }
+}
+
+impl TokenFilter for DelimitedPayloadFilter {
+    type Tokenizer<T: Tokenizer> = DelimitedPayloadFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> DelimitedPayloadFilterWrapper<T> {
+        DelimitedPayloadFilterWrapper {
+            config: self,
+            inner: tokenizer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DelimitedPayloadFilterWrapper<T> {
+    config: DelimitedPayloadFilter,
+    inner: T,
+}
+
+impl<T: Tokenizer> Tokenizer for DelimitedPayloadFilterWrapper<T> {
+    type TokenStream<'a> = DelimitedPayloadFilterStream<T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&self, text: &'a str) -> Self::TokenStream<'a> {
+        DelimitedPayloadFilterStream {
+            config: self.config.clone(),
+            tail: self.inner.token_stream(text),
+        }
+    }
+}
+
+pub struct DelimitedPayloadFilterStream<T> {
+    config: DelimitedPayloadFilter,
+    tail: T,
+}
+
+impl<T: TokenStream> TokenStream for DelimitedPayloadFilterStream<T> {
+    fn advance(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+        let token = self.tail.token_mut();
+        let Some(delimiter_offset) = token.text.rfind(self.config.delimiter) else {
+            return true;
+        };
+        let payload_text = &token.text[delimiter_offset + self.config.delimiter.len_utf8()..];
+        token.payload = match self.config.encoding {
+            PayloadEncoding::Text => payload_text.as_bytes().to_vec(),
+            PayloadEncoding::F32 => payload_text
+                .parse::<f32>()
+                .map(|val| val.to_le_bytes().to_vec())
+                .unwrap_or_default(),
+        };
+        // The offsets can only be narrowed if the text of the token has not been changed by
+        // a previous filter.
+        if token.offset_to - token.offset_from == token.text.len() {
+            token.offset_to = token.offset_from + delimiter_offset;
+        }
+        token.text.truncate(delimiter_offset);
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DelimitedPayloadFilter, PayloadEncoding};
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{TextAnalyzer, Token, WhitespaceTokenizer};
+
+    fn token_stream_helper(encoding: PayloadEncoding, text: &str) -> Vec<Token> {
+        let analyzer = TextAnalyzer::builder(WhitespaceTokenizer)
+            .filter(DelimitedPayloadFilter::new('|', encoding))
+            .build();
+        let mut token_stream = analyzer.token_stream(text);
+        let mut tokens: Vec<Token> = vec![];
+        let mut add_token = |token: &Token| {
+            tokens.push(token.clone());
+        };
+        token_stream.process(&mut add_token);
+        tokens
+    }
+
+    #[test]
+    fn test_delimited_payload_filter_text() {
+        let tokens = token_stream_helper(PayloadEncoding::Text, "a|NN|VB b café|ADJ c|");
+        assert_eq!(tokens.len(), 4);
+        assert_token(&tokens[0], 0, "a|NN", 0, 4);
+        assert_eq!(tokens[0].payload, b"VB");
+        assert_token(&tokens[1], 1, "b", 8, 9);
+        assert!(tokens[1].payload.is_empty());
+        assert_token(&tokens[2], 2, "café", 10, 15);
+        assert_eq!(tokens[2].payload, b"ADJ");
+        assert_token(&tokens[3], 3, "c", 20, 21);
+        assert!(tokens[3].payload.is_empty());
+    }
+
+    #[test]
+    fn test_delimited_payload_filter_f32() {
+        let tokens = token_stream_helper(PayloadEncoding::F32, "a|0.5 b|x");
+        assert_eq!(tokens.len(), 2);
+        assert_token(&tokens[0], 0, "a", 0, 1);
+        assert_eq!(tokens[0].payload, 0.5f32.to_le_bytes());
+        assert_token(&tokens[1], 1, "b", 6, 7);
+        assert!(tokens[1].payload.is_empty());
+    }
+}
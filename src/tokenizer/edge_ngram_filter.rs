@@ -0,0 +1,273 @@
+//! # Example
+//! ```rust
+//! use tantivy::tokenizer::*;
+//!
+//! let tokenizer = TextAnalyzer::builder(SimpleTokenizer)
+//!   .filter(LowerCaser)
+//!   .filter(EdgeNgramFilter::new(2, 4).unwrap())
+//!   .build();
+//!
+//! let mut stream = tokenizer.token_stream("Quick fox");
+//! assert_eq!(stream.next().unwrap().text, "qu");
+//! assert_eq!(stream.next().unwrap().text, "qui");
+//! assert_eq!(stream.next().unwrap().text, "quic");
+//! assert_eq!(stream.next().unwrap().text, "fo");
+//! assert_eq!(stream.next().unwrap().text, "fox");
+//! assert!(stream.next().is_none());
+//! ```
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Token, TokenFilter, TokenStream, Tokenizer};
+use crate::TantivyError;
+
+/// The edge of the tokens the n-grams are anchored to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeNgramSide {
+    /// The n-grams are the prefixes of the tokens.
+    #[default]
+    Front,
+    /// The n-grams are the suffixes of the tokens.
+    Back,
+}
+
+/// `EdgeNgramFilter` replaces each token with its prefixes (or suffixes) of `min_gram` to
+/// `max_gram` characters.
+///
+/// Indexing edge n-grams makes prefix matching, e.g. for search-as-you-type, as cheap as
+/// a regular term lookup. The n-grams are emitted from the shortest to the longest, at the
+/// position of the original token.
+///
+/// By default, tokens shorter than `min_gram` characters are removed, and tokens longer
+/// than `max_gram` characters are only indexed through their n-grams.
+#[derive(Clone)]
+pub struct EdgeNgramFilter {
+    min_gram: usize,
+    max_gram: usize,
+    side: EdgeNgramSide,
+    preserve_original: bool,
+}
+
+impl EdgeNgramFilter {
+    /// Creates an `EdgeNgramFilter` emitting the prefixes of `min_gram` to `max_gram`
+    /// characters of the tokens.
+    ///
+    /// Returns an error if `min_gram` is 0, or greater than `max_gram`.
+    pub fn new(min_gram: usize, max_gram: usize) -> crate::Result<EdgeNgramFilter> {
+        validate_gram_sizes(min_gram, max_gram)?;
+        Ok(EdgeNgramFilter {
+            min_gram,
+            max_gram,
+            side: EdgeNgramSide::Front,
+            preserve_original: false,
+        })
+    }
+
+    /// Sets the edge of the tokens the n-grams are anchored to.
+    #[must_use]
+    pub fn set_side(mut self, side: EdgeNgramSide) -> EdgeNgramFilter {
+        self.side = side;
+        self
+    }
+
+    /// Sets whether the tokens which are shorter than `min_gram` or longer than `max_gram`
+    /// should be emitted as well.
+    #[must_use]
+    pub fn set_preserve_original(mut self, preserve_original: bool) -> EdgeNgramFilter {
+        self.preserve_original = preserve_original;
+        self
+    }
+}
+
+pub(crate) fn validate_gram_sizes(min_gram: usize, max_gram: usize) -> crate::Result<()> {
+    if min_gram == 0 {
+        return Err(TantivyError::InvalidArgument(
+            "The minimum n-gram size should be at least 1".to_string(),
+        ));
+    }
+    if min_gram > max_gram {
+        return Err(TantivyError::InvalidArgument(format!(
+            "The minimum n-gram size ({min_gram}) should not be greater than the maximum n-gram \
+             size ({max_gram})"
+        )));
+    }
+    Ok(())
+}
+
+impl TokenFilter for EdgeNgramFilter {
+    type Tokenizer<T: Tokenizer> = EdgeNgramFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> EdgeNgramFilterWrapper<T> {
+        EdgeNgramFilterWrapper {
+            config: self,
+            inner: tokenizer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct EdgeNgramFilterWrapper<T> {
+    config: EdgeNgramFilter,
+    inner: T,
+}
+
+impl<T: Tokenizer> Tokenizer for EdgeNgramFilterWrapper<T> {
+    type TokenStream<'a> = EdgeNgramFilterStream<T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&self, text: &'a str) -> Self::TokenStream<'a> {
+        EdgeNgramFilterStream {
+            config: self.config.clone(),
+            tail: self.inner.token_stream(text),
+            pending: VecDeque::new(),
+            token: Token::default(),
+        }
+    }
+}
+
+pub struct EdgeNgramFilterStream<T> {
+    config: EdgeNgramFilter,
+    tail: T,
+    // Tokens built, but not emitted yet.
+    pending: VecDeque<Token>,
+    token: Token,
+}
+
+impl<T: TokenStream> EdgeNgramFilterStream<T> {
+    // Builds the n-grams of the current token of the tail, and appends them to the pending
+    // tokens.
+    fn process_token(&mut self) {
+        let token = self.tail.token();
+        // Byte offsets of the character boundaries, from the edge of the n-grams.
+        let boundaries: Vec<usize> = match self.config.side {
+            EdgeNgramSide::Front => token
+                .text
+                .char_indices()
+                .map(|(offset, c)| offset + c.len_utf8())
+                .collect(),
+            EdgeNgramSide::Back => token
+                .text
+                .char_indices()
+                .rev()
+                .map(|(offset, _)| offset)
+                .collect(),
+        };
+        let num_chars = boundaries.len();
+        if self.config.preserve_original
+            && (num_chars < self.config.min_gram || num_chars > self.config.max_gram)
+        {
+            self.pending.push_back(token.clone());
+        }
+        // The offsets of the n-grams can only be computed if the text of the token has not
+        // been changed by a previous filter.
+        let has_original_offsets = token.offset_to - token.offset_from == token.text.len();
+        let max_gram = self.config.max_gram.min(num_chars);
+        for gram_len in self.config.min_gram..=max_gram {
+            let boundary = boundaries[gram_len - 1];
+            let (start, end) = match self.config.side {
+                EdgeNgramSide::Front => (0, boundary),
+                EdgeNgramSide::Back => (boundary, token.text.len()),
+            };
+            let (offset_from, offset_to) = if has_original_offsets {
+                (token.offset_from + start, token.offset_from + end)
+            } else {
+                (token.offset_from, token.offset_to)
+            };
+            self.pending.push_back(Token {
+                offset_from,
+                offset_to,
+                position: token.position,
+                text: token.text[start..end].to_string(),
+                position_length: token.position_length,
+                keyword: false,
+                payload: token.payload.clone(),
+            });
+        }
+    }
+}
+
+impl<T: TokenStream> TokenStream for EdgeNgramFilterStream<T> {
+    fn advance(&mut self) -> bool {
+        while self.pending.is_empty() {
+            if !self.tail.advance() {
+                return false;
+            }
+            self.process_token();
+        }
+        self.token = self.pending.pop_front().unwrap();
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EdgeNgramFilter, EdgeNgramSide};
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{LowerCaser, SimpleTokenizer, TextAnalyzer, Token};
+
+    fn token_stream_helper(edge_ngram_filter: EdgeNgramFilter, text: &str) -> Vec<Token> {
+        let analyzer = TextAnalyzer::builder(SimpleTokenizer)
+            .filter(edge_ngram_filter)
+            .build();
+        let mut token_stream = analyzer.token_stream(text);
+        let mut tokens: Vec<Token> = vec![];
+        let mut add_token = |token: &Token| {
+            tokens.push(token.clone());
+        };
+        token_stream.process(&mut add_token);
+        tokens
+    }
+
+    #[test]
+    fn test_edge_ngram_filter() {
+        let tokens = token_stream_helper(EdgeNgramFilter::new(2, 3).unwrap(), "a hεllo fox");
+        assert_eq!(tokens.len(), 4);
+        assert_token(&tokens[0], 1, "hε", 2, 5);
+        assert_token(&tokens[1], 1, "hεl", 2, 6);
+        assert_token(&tokens[2], 2, "fo", 9, 11);
+        assert_token(&tokens[3], 2, "fox", 9, 12);
+    }
+
+    #[test]
+    fn test_edge_ngram_filter_back() {
+        let edge_ngram_filter = EdgeNgramFilter::new(2, 3)
+            .unwrap()
+            .set_side(EdgeNgramSide::Back)
+            .set_preserve_original(true);
+        let tokens = token_stream_helper(edge_ngram_filter, "a hεllo fox");
+        assert_eq!(tokens.len(), 6);
+        assert_token(&tokens[0], 0, "a", 0, 1);
+        assert_token(&tokens[1], 1, "hεllo", 2, 8);
+        assert_token(&tokens[2], 1, "lo", 6, 8);
+        assert_token(&tokens[3], 1, "llo", 5, 8);
+        assert_token(&tokens[4], 2, "ox", 10, 12);
+        assert_token(&tokens[5], 2, "fox", 9, 12);
+    }
+
+    #[test]
+    fn test_edge_ngram_filter_changed_text() {
+        let analyzer = TextAnalyzer::builder(SimpleTokenizer)
+            .filter(LowerCaser)
+            .filter(EdgeNgramFilter::new(1, 2).unwrap())
+            .build();
+        let mut token_stream = analyzer.token_stream("İs");
+        assert_token(token_stream.next().unwrap(), 0, "i", 0, 3);
+        assert_token(token_stream.next().unwrap(), 0, "i\u{307}", 0, 3);
+        assert!(token_stream.next().is_none());
+    }
+
+    #[test]
+    fn test_edge_ngram_filter_invalid_sizes() {
+        assert!(EdgeNgramFilter::new(0, 2).is_err());
+        assert!(EdgeNgramFilter::new(3, 2).is_err());
+    }
+}
@@ -0,0 +1,95 @@
+use super::edge_ngram_filter::EdgeNgramFilterStream;
+use super::raw_tokenizer::RawTokenStream;
+use super::{
+    EdgeNgramFilter, EdgeNgramSide, RawTokenizer, Token, TokenFilter, TokenStream, Tokenizer,
+};
+
+/// Tokenize the text into its prefixes (or suffixes) of `min_gram` to `max_gram` characters.
+///
+/// Contrary to the [`EdgeNgramFilter`], the text is not split into words first: this
+/// tokenizer is typically used to autocomplete short values such as titles or names.
+/// With this tokenizer, the `position` is always 0.
+///
+/// # Example
+///
+/// ```rust
+/// use tantivy::tokenizer::*;
+///
+/// let tokenizer = EdgeNgramTokenizer::new(2, 4)
+///     .unwrap()
+///     .set_side(EdgeNgramSide::Back);
+/// let mut stream = tokenizer.token_stream("hello");
+/// assert_eq!(stream.next().unwrap().text, "lo");
+/// assert_eq!(stream.next().unwrap().text, "llo");
+/// assert_eq!(stream.next().unwrap().text, "ello");
+/// assert!(stream.next().is_none());
+/// ```
+#[derive(Clone)]
+pub struct EdgeNgramTokenizer {
+    filter: EdgeNgramFilter,
+}
+
+impl EdgeNgramTokenizer {
+    /// Creates an `EdgeNgramTokenizer` emitting the prefixes of `min_gram` to `max_gram`
+    /// characters of the text.
+    ///
+    /// Returns an error if `min_gram` is 0, or greater than `max_gram`.
+    pub fn new(min_gram: usize, max_gram: usize) -> crate::Result<EdgeNgramTokenizer> {
+        Ok(EdgeNgramTokenizer {
+            filter: EdgeNgramFilter::new(min_gram, max_gram)?,
+        })
+    }
+
+    /// Sets the edge of the text the n-grams are anchored to.
+    #[must_use]
+    pub fn set_side(self, side: EdgeNgramSide) -> EdgeNgramTokenizer {
+        EdgeNgramTokenizer {
+            filter: self.filter.set_side(side),
+        }
+    }
+}
+
+/// TokenStream associate to the `EdgeNgramTokenizer`
+pub struct EdgeNgramTokenStream(EdgeNgramFilterStream<RawTokenStream>);
+
+impl Tokenizer for EdgeNgramTokenizer {
+    type TokenStream<'a> = EdgeNgramTokenStream;
+
+    fn token_stream(&self, text: &str) -> EdgeNgramTokenStream {
+        let tokenizer = self.filter.clone().transform(RawTokenizer);
+        EdgeNgramTokenStream(tokenizer.token_stream(text))
+    }
+}
+
+impl TokenStream for EdgeNgramTokenStream {
+    fn advance(&mut self) -> bool {
+        self.0.advance()
+    }
+
+    fn token(&self) -> &Token {
+        self.0.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.0.token_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EdgeNgramTokenizer;
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{TextAnalyzer, Token};
+
+    #[test]
+    fn test_edge_ngram_tokenizer() {
+        let analyzer = TextAnalyzer::from(EdgeNgramTokenizer::new(1, 3).unwrap());
+        let mut token_stream = analyzer.token_stream("hε y");
+        let mut tokens: Vec<Token> = vec![];
+        token_stream.process(&mut |token: &Token| tokens.push(token.clone()));
+        assert_eq!(tokens.len(), 3);
+        assert_token(&tokens[0], 0, "h", 0, 1);
+        assert_token(&tokens[1], 0, "hε", 0, 3);
+        assert_token(&tokens[2], 0, "hε ", 0, 4);
+    }
+}
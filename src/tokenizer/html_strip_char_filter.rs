@@ -0,0 +1,227 @@
+//! # Example
+//! ```rust
+//! use tantivy::tokenizer::*;
+//!
+//! let tokenizer = TextAnalyzer::builder(SimpleTokenizer)
+//!   .char_filter(HtmlStripCharFilter)
+//!   .build();
+//!
+//! let mut stream = tokenizer.token_stream("<p>Fish &amp; <b>chips</b></p>");
+//! let token = stream.next().unwrap();
+//! assert_eq!(token.text, "Fish");
+//! assert_eq!((token.offset_from, token.offset_to), (3, 7));
+//! let token = stream.next().unwrap();
+//! assert_eq!(token.text, "chips");
+//! assert_eq!((token.offset_from, token.offset_to), (17, 22));
+//! assert!(stream.next().is_none());
+//! ```
+use super::{CharFilter, FilteredText};
+
+/// `HtmlStripCharFilter` removes the HTML markup of the text.
+///
+/// Tags and comments are removed, as well as the content of the `script` and `style`
+/// elements. Block-level tags, e.g. `<p>` or `<br>`, are replaced by a line break so that
+/// they still separate the words. Character references, e.g. `&amp;` or `&#233;`, are
+/// decoded.
+#[derive(Clone)]
+pub struct HtmlStripCharFilter;
+
+const BLOCK_ELEMENTS: [&str; 35] = [
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "br",
+    "caption",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "td",
+    "th",
+    "tr",
+    "ul",
+];
+
+// Longest named character reference decoded by the filter, including the `&` and `;`.
+const MAX_CHAR_REF_LEN: usize = 10;
+
+fn decode_char_ref(char_ref: &str) -> Option<char> {
+    if let Some(code_point) = char_ref.strip_prefix('#') {
+        let code_point = if let Some(hex) = code_point
+            .strip_prefix('x')
+            .or_else(|| code_point.strip_prefix('X'))
+        {
+            u32::from_str_radix(hex, 16).ok()?
+        } else {
+            code_point.parse::<u32>().ok()?
+        };
+        return char::from_u32(code_point);
+    }
+    let c = match char_ref {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "copy" => '©',
+        "reg" => '®',
+        "hellip" => '…',
+        "ndash" => '–',
+        "mdash" => '—',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        _ => return None,
+    };
+    Some(c)
+}
+
+// Returns the length of the markup starting at the beginning of `text`, and whether it is
+// a block-level tag.
+fn markup_len(text: &str) -> Option<(usize, bool)> {
+    if text.starts_with("<!--") {
+        let len = text.find("-->").map(|pos| pos + 3).unwrap_or(text.len());
+        return Some((len, false));
+    }
+    let tag_start = text.as_bytes().get(1)?;
+    if !(tag_start.is_ascii_alphabetic() || matches!(tag_start, b'/' | b'!' | b'?')) {
+        return None;
+    }
+    let tag_len = text.find('>')? + 1;
+    let tag_name = text[1..tag_len - 1]
+        .trim_start_matches('/')
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .next()
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    let is_closing = text[1..].starts_with('/');
+    if !is_closing && (tag_name == "script" || tag_name == "style") {
+        // The content of these elements is not text.
+        let closing_tag = format!("</{tag_name}");
+        let len = text[tag_len..]
+            .to_ascii_lowercase()
+            .find(&closing_tag)
+            .and_then(|pos| {
+                let closing_tag_start = tag_len + pos;
+                text[closing_tag_start..]
+                    .find('>')
+                    .map(|closing_tag_len| closing_tag_start + closing_tag_len + 1)
+            })
+            .unwrap_or(text.len());
+        return Some((len, false));
+    }
+    Some((tag_len, BLOCK_ELEMENTS.contains(&tag_name.as_str())))
+}
+
+impl CharFilter for HtmlStripCharFilter {
+    fn filter(&self, text: &str) -> FilteredText {
+        let mut filtered_text = FilteredText::default();
+        let mut offset = 0;
+        while offset < text.len() {
+            let rest = &text[offset..];
+            if rest.starts_with('<') {
+                if let Some((len, is_block)) = markup_len(rest) {
+                    let replacement = if is_block { "\n" } else { "" };
+                    filtered_text.push_replacement(replacement, offset..offset + len);
+                    offset += len;
+                    continue;
+                }
+            } else if rest.starts_with('&') {
+                let char_ref = rest
+                    .char_indices()
+                    .take(MAX_CHAR_REF_LEN)
+                    .find(|&(_, c)| c == ';')
+                    .and_then(|(end, _)| Some((end, decode_char_ref(&rest[1..end])?)));
+                if let Some((end, c)) = char_ref {
+                    let mut buffer = [0u8; 4];
+                    filtered_text
+                        .push_replacement(c.encode_utf8(&mut buffer), offset..offset + end + 1);
+                    offset += end + 1;
+                    continue;
+                }
+            }
+            // Copies the text up to the next possible markup.
+            let len = rest[1..]
+                .find(['<', '&'])
+                .map(|pos| pos + 1)
+                .unwrap_or(rest.len());
+            filtered_text.push_original(&rest[..len], offset);
+            offset += len;
+        }
+        filtered_text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HtmlStripCharFilter;
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{CharFilter, SimpleTokenizer, TextAnalyzer, Token};
+
+    fn token_stream_helper(text: &str) -> Vec<Token> {
+        let analyzer = TextAnalyzer::builder(SimpleTokenizer)
+            .char_filter(HtmlStripCharFilter)
+            .build();
+        let mut token_stream = analyzer.token_stream(text);
+        let mut tokens: Vec<Token> = vec![];
+        let mut add_token = |token: &Token| {
+            tokens.push(token.clone());
+        };
+        token_stream.process(&mut add_token);
+        tokens
+    }
+
+    #[test]
+    fn test_html_strip_char_filter() {
+        let text = "<div>Hello <i>w</i>orld</div><p>fish &amp; chips &#233;t&#xE9;</p>";
+        assert_eq!(
+            HtmlStripCharFilter.filter(text).text(),
+            "\nHello world\n\nfish & chips été\n"
+        );
+        let tokens = token_stream_helper(text);
+        assert_eq!(tokens.len(), 5);
+        assert_token(&tokens[0], 0, "Hello", 5, 10);
+        assert_token(&tokens[1], 1, "world", 14, 23);
+        assert_token(&tokens[2], 2, "fish", 32, 36);
+        assert_token(&tokens[3], 3, "chips", 43, 48);
+        assert_token(&tokens[4], 4, "été", 49, 62);
+    }
+
+    #[test]
+    fn test_html_strip_char_filter_scripts_and_comments() {
+        let text = "a<!-- b --><SCRIPT>c < d</script>e<style>f</style> 1 < 2 &unknown;";
+        assert_eq!(
+            HtmlStripCharFilter.filter(text).text(),
+            "ae 1 < 2 &unknown;"
+        );
+        let tokens = token_stream_helper(text);
+        assert_eq!(tokens.len(), 4);
+        assert_token(&tokens[0], 0, "ae", 0, 34);
+        assert_token(&tokens[1], 1, "1", 51, 52);
+        assert_token(&tokens[3], 3, "unknown", 58, 65);
+    }
+}
@@ -0,0 +1,247 @@
+use std::mem;
+
+use icu_casemap::CaseMapper;
+use icu_normalizer::{ComposingNormalizer, DecomposingNormalizer};
+use icu_properties::{maps, GeneralCategory, Script};
+
+use super::{Token, TokenFilter, TokenStream, Tokenizer};
+
+/// `IcuFoldingFilter` normalizes the tokens with the Unicode algorithms of
+/// [ICU4X](https://github.com/unicode-org/icu4x), as a replacement of the
+/// [`LowerCaser`](super::LowerCaser) and of the [`AsciiFoldingFilter`](super::AsciiFoldingFilter)
+/// for the texts that are not in English. It requires the `icu` feature.
+///
+/// The tokens are normalized in the NFKC form, so that the compatibility characters like the
+/// ligatures or the full-width letters are replaced by their usual form, then case folded with
+/// the full case folding of Unicode, e.g. `ß` is folded to `ss` and the Greek final sigma `ς` to
+/// `σ`. Finally, the diacritics of the Latin, Greek and Cyrillic letters are removed, e.g. `é`
+/// becomes `e` and `ά` becomes `α`, while the marks of the other scripts, like the vowel signs
+/// of Devanagari, are kept.
+///
+/// ```rust
+/// use tantivy::tokenizer::*;
+///
+/// let tokenizer = TextAnalyzer::builder(SimpleTokenizer)
+///     .filter(IcuFoldingFilter::default())
+///     .build();
+/// let mut stream = tokenizer.token_stream("ΟΔΥΣΣΕΎΣ Straße ﬁnal");
+/// assert_eq!(stream.next().unwrap().text, "οδυσσευσ");
+/// assert_eq!(stream.next().unwrap().text, "strasse");
+/// assert_eq!(stream.next().unwrap().text, "final");
+/// assert!(stream.next().is_none());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct IcuFoldingFilter {
+    turkic: bool,
+    keep_diacritics: bool,
+}
+
+impl IcuFoldingFilter {
+    /// Sets whether the case folding follows the rules of Turkish and Azerbaijani, where `I`
+    /// is folded to the dotless `ı` and `İ` to `i`. Defaults to `false`, where `I` is folded to
+    /// `i`, and `İ` to `i` as well once its dot is removed.
+    #[must_use]
+    pub fn set_turkic(mut self, turkic: bool) -> IcuFoldingFilter {
+        self.turkic = turkic;
+        self
+    }
+
+    /// Sets whether the diacritics are kept. Defaults to `false`.
+    #[must_use]
+    pub fn set_keep_diacritics(mut self, keep_diacritics: bool) -> IcuFoldingFilter {
+        self.keep_diacritics = keep_diacritics;
+        self
+    }
+}
+
+impl TokenFilter for IcuFoldingFilter {
+    type Tokenizer<T: Tokenizer> = IcuFoldingFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> IcuFoldingFilterWrapper<T> {
+        IcuFoldingFilterWrapper {
+            inner: tokenizer,
+            filter: self,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct IcuFoldingFilterWrapper<T> {
+    inner: T,
+    filter: IcuFoldingFilter,
+}
+
+impl<T: Tokenizer> Tokenizer for IcuFoldingFilterWrapper<T> {
+    type TokenStream<'a> = IcuFoldingTokenStream<T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&self, text: &'a str) -> Self::TokenStream<'a> {
+        IcuFoldingTokenStream {
+            tail: self.inner.token_stream(text),
+            folder: IcuFolder {
+                turkic: self.filter.turkic,
+                keep_diacritics: self.filter.keep_diacritics,
+                nfkc: ComposingNormalizer::new_nfkc(),
+                nfc: ComposingNormalizer::new_nfc(),
+                nfkd: DecomposingNormalizer::new_nfkd(),
+                case_mapper: CaseMapper::new(),
+            },
+            buffer: String::new(),
+        }
+    }
+}
+
+struct IcuFolder {
+    turkic: bool,
+    keep_diacritics: bool,
+    nfkc: ComposingNormalizer,
+    nfc: ComposingNormalizer,
+    nfkd: DecomposingNormalizer,
+    case_mapper: CaseMapper,
+}
+
+impl IcuFolder {
+    /// Writes the folded version of text into output.
+    fn fold(&self, text: &str, output: &mut String) {
+        output.clear();
+        let normalized = self.nfkc.normalize(text);
+        let folded = if self.turkic {
+            self.case_mapper.fold_turkic_string(&normalized)
+        } else {
+            self.case_mapper.fold_string(&normalized)
+        };
+        if self.keep_diacritics {
+            // The case folding may denormalize the text.
+            output.push_str(&self.nfkc.normalize(&folded));
+            return;
+        }
+        let general_category = maps::general_category();
+        let script = maps::script();
+        let mut base_script = Script::Common;
+        let mut stripped = String::with_capacity(folded.len());
+        for c in self.nfkd.normalize(&folded).chars() {
+            if general_category.get(c) == GeneralCategory::NonspacingMark {
+                if matches!(
+                    base_script,
+                    Script::Latin | Script::Greek | Script::Cyrillic
+                ) {
+                    continue;
+                }
+            } else {
+                base_script = script.get(c);
+            }
+            stripped.push(c);
+        }
+        output.push_str(&self.nfc.normalize(&stripped));
+    }
+}
+
+pub struct IcuFoldingTokenStream<T> {
+    tail: T,
+    folder: IcuFolder,
+    buffer: String,
+}
+
+impl<T: TokenStream> TokenStream for IcuFoldingTokenStream<T> {
+    fn advance(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+        let text = &mut self.tail.token_mut().text;
+        if text.is_ascii() && !(self.folder.turkic && text.contains('I')) {
+            // fast track for ascii.
+            text.make_ascii_lowercase();
+        } else {
+            self.folder.fold(text, &mut self.buffer);
+            mem::swap(text, &mut self.buffer);
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IcuFoldingFilter;
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{LowerCaser, SimpleTokenizer, TextAnalyzer, Token, WhitespaceTokenizer};
+
+    fn folded_tokens(filter: IcuFoldingFilter, text: &str) -> Vec<String> {
+        let analyzer = TextAnalyzer::builder(WhitespaceTokenizer)
+            .filter(filter)
+            .build();
+        let mut token_stream = analyzer.token_stream(text);
+        let mut tokens = Vec::new();
+        token_stream.process(&mut |token: &Token| tokens.push(token.text.clone()));
+        tokens
+    }
+
+    #[test]
+    fn test_icu_folding_filter() {
+        let filter = IcuFoldingFilter::default();
+        // Greek: the final sigma and the accents are folded.
+        assert_eq!(
+            folded_tokens(filter.clone(), "ΟΔΥΣΣΕΎΣ Αθήνα"),
+            vec!["οδυσσευσ", "αθηνα"]
+        );
+        // Latin: full case folding and diacritics.
+        assert_eq!(
+            folded_tokens(filter.clone(), "Straße Tiếng Việt Ça"),
+            vec!["strasse", "tieng", "viet", "ca"]
+        );
+        // Cyrillic.
+        assert_eq!(folded_tokens(filter.clone(), "Ёлка"), vec!["елка"]);
+        // Compatibility characters: ligatures, full-width letters and digits.
+        assert_eq!(
+            folded_tokens(filter.clone(), "ﬁnal ＡＢＣ１２"),
+            vec!["final", "abc12"]
+        );
+        // The dotted capital I is folded to i.
+        assert_eq!(
+            folded_tokens(filter.clone(), "İSTANBUL Istanbul"),
+            vec!["istanbul", "istanbul"]
+        );
+        // The vowel signs of Devanagari are kept.
+        assert_eq!(folded_tokens(filter, "हिन्दी"), vec!["हिन्दी"]);
+    }
+
+    #[test]
+    fn test_icu_folding_filter_options() {
+        let turkic = IcuFoldingFilter::default().set_turkic(true);
+        assert_eq!(
+            folded_tokens(turkic, "ISPARTA İzmir"),
+            vec!["ısparta", "izmir"]
+        );
+        let keep_diacritics = IcuFoldingFilter::default().set_keep_diacritics(true);
+        assert_eq!(
+            folded_tokens(keep_diacritics, "Ça ΆΛΦΑ"),
+            vec!["ça", "άλφα"]
+        );
+    }
+
+    #[test]
+    fn test_icu_folding_filter_offsets() {
+        let analyzer = TextAnalyzer::builder(SimpleTokenizer)
+            .filter(IcuFoldingFilter::default())
+            .build();
+        let mut token_stream = analyzer.token_stream("Crème BRÛLÉE");
+        let mut tokens = Vec::new();
+        token_stream.process(&mut |token: &Token| tokens.push(token.clone()));
+        assert_eq!(tokens.len(), 2);
+        assert_token(&tokens[0], 0, "creme", 0, 6);
+        assert_token(&tokens[1], 1, "brulee", 7, 15);
+
+        // Contrary to the lower caser, the Greek final sigma is folded.
+        let lower_caser = TextAnalyzer::builder(SimpleTokenizer)
+            .filter(LowerCaser)
+            .build();
+        let mut token_stream = lower_caser.token_stream("ΟΔΥΣΣΕΎΣ");
+        assert_eq!(token_stream.next().unwrap().text, "οδυσσεύσ");
+    }
+}
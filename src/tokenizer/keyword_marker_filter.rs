@@ -0,0 +1,168 @@
+//! # Example
+//! ```rust
+//! use tantivy::tokenizer::*;
+//!
+//! let tokenizer = TextAnalyzer::builder(SimpleTokenizer)
+//!   .filter(LowerCaser)
+//!   .filter(KeywordMarkerFilter::from_words(vec!["apples".to_string()]))
+//!   .filter(Stemmer::new(Language::English))
+//!   .build();
+//!
+//! let mut stream = tokenizer.token_stream("Apples sells apples");
+//! assert_eq!(stream.next().unwrap().text, "apples");
+//! assert_eq!(stream.next().unwrap().text, "sell");
+//! assert_eq!(stream.next().unwrap().text, "apples");
+//! assert!(stream.next().is_none());
+//! ```
+use std::sync::Arc;
+
+use regex::Regex;
+use rustc_hash::FxHashSet;
+
+use super::{Token, TokenFilter, TokenStream, Tokenizer};
+use crate::TantivyError;
+
+/// `TokenFilter` that marks some tokens as keywords, so that the following stemmers leave
+/// them unchanged.
+///
+/// The tokens are matched on their text, as emitted by the previous filters: a
+/// `KeywordMarkerFilter` following a [`LowerCaser`](super::LowerCaser) should be given
+/// lowercased words.
+#[derive(Clone)]
+pub struct KeywordMarkerFilter {
+    matcher: Arc<KeywordMatcher>,
+}
+
+enum KeywordMatcher {
+    Words(FxHashSet<String>),
+    Pattern(Regex),
+}
+
+impl KeywordMatcher {
+    fn is_keyword(&self, text: &str) -> bool {
+        match self {
+            KeywordMatcher::Words(words) => words.contains(text),
+            KeywordMatcher::Pattern(regex) => regex.is_match(text),
+        }
+    }
+}
+
+impl KeywordMarkerFilter {
+    /// Creates a `KeywordMarkerFilter` marking the given words as keywords.
+    pub fn from_words<W: IntoIterator<Item = String>>(words: W) -> KeywordMarkerFilter {
+        KeywordMarkerFilter {
+            matcher: Arc::new(KeywordMatcher::Words(words.into_iter().collect())),
+        }
+    }
+
+    /// Creates a `KeywordMarkerFilter` marking the tokens matching entirely a regular
+    /// expression as keywords.
+    ///
+    /// Returns an error if the pattern is not a valid regular expression.
+    pub fn from_pattern(pattern: &str) -> crate::Result<KeywordMarkerFilter> {
+        let regex = Regex::new(&format!("^(?:{pattern})$"))
+            .map_err(|_| TantivyError::InvalidArgument(pattern.to_owned()))?;
+        Ok(KeywordMarkerFilter {
+            matcher: Arc::new(KeywordMatcher::Pattern(regex)),
+        })
+    }
+}
+
+impl TokenFilter for KeywordMarkerFilter {
+    type Tokenizer<T: Tokenizer> = KeywordMarkerFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> KeywordMarkerFilterWrapper<T> {
+        KeywordMarkerFilterWrapper {
+            matcher: self.matcher,
+            inner: tokenizer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct KeywordMarkerFilterWrapper<T> {
+    matcher: Arc<KeywordMatcher>,
+    inner: T,
+}
+
+impl<T: Tokenizer> Tokenizer for KeywordMarkerFilterWrapper<T> {
+    type TokenStream<'a> = KeywordMarkerFilterStream<T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&self, text: &'a str) -> Self::TokenStream<'a> {
+        KeywordMarkerFilterStream {
+            matcher: self.matcher.clone(),
+            tail: self.inner.token_stream(text),
+        }
+    }
+}
+
+pub struct KeywordMarkerFilterStream<T> {
+    matcher: Arc<KeywordMatcher>,
+    tail: T,
+}
+
+impl<T: TokenStream> TokenStream for KeywordMarkerFilterStream<T> {
+    fn advance(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+        let token = self.tail.token_mut();
+        if self.matcher.is_keyword(&token.text) {
+            token.keyword = true;
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeywordMarkerFilter;
+    use crate::tokenizer::{Language, LowerCaser, SimpleTokenizer, Stemmer, TextAnalyzer, Token};
+
+    fn token_stream_helper(keyword_marker_filter: KeywordMarkerFilter, text: &str) -> Vec<Token> {
+        let analyzer = TextAnalyzer::builder(SimpleTokenizer)
+            .filter(LowerCaser)
+            .filter(keyword_marker_filter)
+            .filter(Stemmer::new(Language::English))
+            .build();
+        let mut token_stream = analyzer.token_stream(text);
+        let mut tokens: Vec<Token> = vec![];
+        let mut add_token = |token: &Token| {
+            tokens.push(token.clone());
+        };
+        token_stream.process(&mut add_token);
+        tokens
+    }
+
+    #[test]
+    fn test_keyword_marker_filter_words() {
+        let keyword_marker_filter = KeywordMarkerFilter::from_words(vec!["apples".to_string()]);
+        let tokens = token_stream_helper(keyword_marker_filter, "Apples and pears");
+        let texts: Vec<&str> = tokens.iter().map(|token| token.text.as_str()).collect();
+        assert_eq!(texts, vec!["apples", "and", "pear"]);
+        assert!(tokens[0].keyword);
+        assert!(!tokens[2].keyword);
+    }
+
+    #[test]
+    fn test_keyword_marker_filter_pattern() {
+        let keyword_marker_filter = KeywordMarkerFilter::from_pattern("[a-z]+ings").unwrap();
+        let tokens = token_stream_helper(keyword_marker_filter, "Hastings housings running");
+        let texts: Vec<&str> = tokens.iter().map(|token| token.text.as_str()).collect();
+        assert_eq!(texts, vec!["hastings", "housings", "run"]);
+        // The pattern has to match the whole token.
+        let keyword_marker_filter = KeywordMarkerFilter::from_pattern("run").unwrap();
+        let tokens = token_stream_helper(keyword_marker_filter, "running run");
+        let texts: Vec<&str> = tokens.iter().map(|token| token.text.as_str()).collect();
+        assert_eq!(texts, vec!["run", "run"]);
+        assert!(KeywordMarkerFilter::from_pattern("(").is_err());
+    }
+}
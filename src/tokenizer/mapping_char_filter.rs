@@ -0,0 +1,186 @@
+//! # Example
+//! ```rust
+//! use tantivy::tokenizer::*;
+//!
+//! let tokenizer = TextAnalyzer::builder(WhitespaceTokenizer)
+//!   .char_filter(MappingCharFilter::new([(":)", "smile"), ("&", " and ")]).unwrap())
+//!   .build();
+//!
+//! let mut stream = tokenizer.token_stream("R&D :)");
+//! assert_eq!(stream.next().unwrap().text, "R");
+//! assert_eq!(stream.next().unwrap().text, "and");
+//! assert_eq!(stream.next().unwrap().text, "D");
+//! let token = stream.next().unwrap();
+//! assert_eq!(token.text, "smile");
+//! assert_eq!((token.offset_from, token.offset_to), (4, 6));
+//! assert!(stream.next().is_none());
+//! ```
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use super::{CharFilter, FilteredText};
+use crate::TantivyError;
+
+/// `MappingCharFilter` replaces the occurrences of some strings of the text.
+///
+/// At each position of the text, the longest string with a mapping is replaced.
+#[derive(Clone)]
+pub struct MappingCharFilter {
+    mappings: HashMap<String, String>,
+    // Length in bytes of the longest string to replace.
+    max_len: usize,
+}
+
+impl MappingCharFilter {
+    /// Creates a `MappingCharFilter` replacing the keys of the `mappings` by their value.
+    ///
+    /// Returns an error if one of the keys is empty.
+    pub fn new<K: Into<String>, V: Into<String>>(
+        mappings: impl IntoIterator<Item = (K, V)>,
+    ) -> crate::Result<MappingCharFilter> {
+        let mappings: HashMap<String, String> = mappings
+            .into_iter()
+            .map(|(key, value)| (key.into(), value.into()))
+            .collect();
+        if mappings.contains_key("") {
+            return Err(TantivyError::InvalidArgument(
+                "The strings replaced by a MappingCharFilter should not be empty".to_string(),
+            ));
+        }
+        let max_len = mappings.keys().map(String::len).max().unwrap_or(0);
+        Ok(MappingCharFilter { mappings, max_len })
+    }
+}
+
+impl CharFilter for MappingCharFilter {
+    fn filter(&self, text: &str) -> FilteredText {
+        let mut filtered_text = FilteredText::default();
+        // Start of the text copied verbatim, which has not been pushed yet.
+        let mut verbatim_start = 0;
+        let mut offset = 0;
+        while offset < text.len() {
+            let rest = &text[offset..];
+            let longest_match = rest
+                .char_indices()
+                .map(|(pos, c)| pos + c.len_utf8())
+                .take_while(|&len| len <= self.max_len)
+                .filter_map(|len| Some((len, self.mappings.get(&rest[..len])?)))
+                .last();
+            if let Some((len, replacement)) = longest_match {
+                filtered_text.push_original(&text[verbatim_start..offset], verbatim_start);
+                filtered_text.push_replacement(replacement, offset..offset + len);
+                offset += len;
+                verbatim_start = offset;
+            } else {
+                offset += rest.chars().next().map(char::len_utf8).unwrap_or(1);
+            }
+        }
+        filtered_text.push_original(&text[verbatim_start..], verbatim_start);
+        filtered_text
+    }
+}
+
+/// `PatternReplaceCharFilter` replaces the matches of a regular expression.
+///
+/// The replacement string can refer to the capture groups of the regular expression,
+/// using the syntax of [`Regex::replace()`].
+#[derive(Clone)]
+pub struct PatternReplaceCharFilter {
+    regex: Regex,
+    replacement: String,
+}
+
+impl PatternReplaceCharFilter {
+    /// Creates a `PatternReplaceCharFilter` replacing the matches of `pattern` by
+    /// `replacement`.
+    ///
+    /// Returns an error if the pattern is not a valid regular expression.
+    pub fn new(pattern: &str, replacement: &str) -> crate::Result<PatternReplaceCharFilter> {
+        let regex =
+            Regex::new(pattern).map_err(|_| TantivyError::InvalidArgument(pattern.to_owned()))?;
+        Ok(PatternReplaceCharFilter {
+            regex,
+            replacement: replacement.to_string(),
+        })
+    }
+}
+
+impl CharFilter for PatternReplaceCharFilter {
+    fn filter(&self, text: &str) -> FilteredText {
+        let mut filtered_text = FilteredText::default();
+        let mut verbatim_start = 0;
+        let mut replacement = String::new();
+        for captures in self.regex.captures_iter(text) {
+            let matched = captures.get(0).unwrap();
+            filtered_text.push_original(&text[verbatim_start..matched.start()], verbatim_start);
+            replacement.clear();
+            captures.expand(&self.replacement, &mut replacement);
+            filtered_text.push_replacement(&replacement, matched.range());
+            verbatim_start = matched.end();
+        }
+        filtered_text.push_original(&text[verbatim_start..], verbatim_start);
+        filtered_text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MappingCharFilter, PatternReplaceCharFilter};
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{CharFilter, SimpleTokenizer, TextAnalyzer, Token};
+
+    fn token_stream_helper(analyzer: TextAnalyzer, text: &str) -> Vec<Token> {
+        let mut token_stream = analyzer.token_stream(text);
+        let mut tokens: Vec<Token> = vec![];
+        let mut add_token = |token: &Token| {
+            tokens.push(token.clone());
+        };
+        token_stream.process(&mut add_token);
+        tokens
+    }
+
+    #[test]
+    fn test_mapping_char_filter() {
+        let mapping_char_filter =
+            MappingCharFilter::new([("ß", "ss"), ("æ", "ae"), ("ae", "x"), ("aes", "y")]).unwrap();
+        assert_eq!(
+            mapping_char_filter.filter("straße æsaes aer").text(),
+            "strasse aesy xr"
+        );
+        let analyzer = TextAnalyzer::builder(SimpleTokenizer)
+            .char_filter(mapping_char_filter)
+            .build();
+        let tokens = token_stream_helper(analyzer, "straße æsaes aer");
+        assert_eq!(tokens.len(), 3);
+        assert_token(&tokens[0], 0, "strasse", 0, 7);
+        assert_token(&tokens[1], 1, "aesy", 8, 14);
+        assert_token(&tokens[2], 2, "xr", 15, 18);
+        assert!(MappingCharFilter::new([("", "a")]).is_err());
+    }
+
+    #[test]
+    fn test_pattern_replace_char_filter() {
+        let analyzer = TextAnalyzer::builder(SimpleTokenizer)
+            .char_filter(PatternReplaceCharFilter::new(r"(\d+)-(\d+)", "$1$2").unwrap())
+            .build();
+        let tokens = token_stream_helper(analyzer, "call 555-1234 now");
+        assert_eq!(tokens.len(), 3);
+        assert_token(&tokens[0], 0, "call", 0, 4);
+        assert_token(&tokens[1], 1, "5551234", 5, 13);
+        assert_token(&tokens[2], 2, "now", 14, 17);
+        assert!(PatternReplaceCharFilter::new("(", "").is_err());
+    }
+
+    #[test]
+    fn test_chained_char_filters() {
+        let analyzer = TextAnalyzer::builder(SimpleTokenizer)
+            .char_filter(MappingCharFilter::new([("&", "and")]).unwrap())
+            .char_filter(PatternReplaceCharFilter::new("and", " + ").unwrap())
+            .build();
+        let tokens = token_stream_helper(analyzer, "R&D");
+        assert_eq!(tokens.len(), 2);
+        assert_token(&tokens[0], 0, "R", 0, 1);
+        assert_token(&tokens[1], 1, "D", 2, 3);
+    }
+}
@@ -52,6 +52,12 @@
 //! remove their inflection. This tokenizer is slower than the default one,
 //! but is recommended to improve recall.
 //!
+//! ## `autocomplete`
+//!
+//! Like `default`, but indexes the prefixes of up to 20 characters of the
+//! tokens. Fields using the [`AUTOCOMPLETE`](crate::schema::AUTOCOMPLETE) options
+//! match the queries on the beginning of their words, for search-as-you-type.
+//!
 //! # Custom tokenizer Library
 //! Avoid using tantivy as dependency and prefer `tantivy-tokenizer-api` instead.
 //!
@@ -123,40 +129,72 @@
 //! ```
 mod alphanum_only;
 mod ascii_folding_filter;
+mod char_filter;
+mod cjk_bigram_filter;
+mod delimited_payload_filter;
+mod edge_ngram_filter;
+mod edge_ngram_tokenizer;
 mod empty_tokenizer;
 mod facet_tokenizer;
+mod html_strip_char_filter;
+#[cfg(feature = "icu")]
+mod icu_folding_filter;
+mod keyword_marker_filter;
 mod lower_caser;
+mod mapping_char_filter;
 mod ngram_tokenizer;
+mod path_hierarchy_tokenizer;
 mod raw_tokenizer;
 mod regex_tokenizer;
 mod remove_long;
+mod shingle_filter;
 mod simple_tokenizer;
 mod split_compound_words;
 mod stemmer;
 mod stop_word_filter;
+mod synonym_filter;
+mod text_analyzer_config;
 mod tokenized_string;
 mod tokenizer;
 mod tokenizer_manager;
 mod whitespace_tokenizer;
+mod word_delimiter_filter;
 
 pub use tokenizer_api::{BoxTokenStream, Token, TokenFilter, TokenStream, Tokenizer};
 
 pub use self::alphanum_only::AlphaNumOnlyFilter;
 pub use self::ascii_folding_filter::AsciiFoldingFilter;
+pub use self::char_filter::{CharFilter, FilteredText};
+pub use self::cjk_bigram_filter::CjkBigramFilter;
+pub use self::delimited_payload_filter::{DelimitedPayloadFilter, PayloadEncoding};
+pub use self::edge_ngram_filter::{EdgeNgramFilter, EdgeNgramSide};
+pub use self::edge_ngram_tokenizer::EdgeNgramTokenizer;
 pub use self::facet_tokenizer::FacetTokenizer;
+pub use self::html_strip_char_filter::HtmlStripCharFilter;
+#[cfg(feature = "icu")]
+pub use self::icu_folding_filter::IcuFoldingFilter;
+pub use self::keyword_marker_filter::KeywordMarkerFilter;
 pub use self::lower_caser::LowerCaser;
+pub use self::mapping_char_filter::{MappingCharFilter, PatternReplaceCharFilter};
 pub use self::ngram_tokenizer::NgramTokenizer;
+pub use self::path_hierarchy_tokenizer::PathHierarchyTokenizer;
 pub use self::raw_tokenizer::RawTokenizer;
 pub use self::regex_tokenizer::RegexTokenizer;
 pub use self::remove_long::RemoveLongFilter;
+pub use self::shingle_filter::ShingleFilter;
 pub use self::simple_tokenizer::{SimpleTokenStream, SimpleTokenizer};
 pub use self::split_compound_words::SplitCompoundWords;
 pub use self::stemmer::{Language, Stemmer};
 pub use self::stop_word_filter::StopWordFilter;
+pub use self::synonym_filter::SynonymFilter;
+pub use self::text_analyzer_config::{
+    CharFilterConfig, TextAnalyzerConfig, TokenFilterConfig, TokenizerConfig,
+};
 pub use self::tokenized_string::{PreTokenizedStream, PreTokenizedString};
-pub use self::tokenizer::TextAnalyzer;
+pub use self::tokenizer::{TextAnalyzer, TextAnalyzerBuilder};
 pub use self::tokenizer_manager::TokenizerManager;
 pub use self::whitespace_tokenizer::WhitespaceTokenizer;
+pub use self::word_delimiter_filter::WordDelimiterFilter;
 
 /// Maximum authorized len (in bytes) for a token.
 ///
@@ -153,6 +153,8 @@ impl<'a> TokenStream for NgramTokenStream<'a> {
                 return false;
             }
             self.token.position = 0;
+            self.token.keyword = false;
+            self.token.payload.clear();
             self.token.offset_from = offset_from;
             self.token.offset_to = offset_to;
             self.token.text.clear();
@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Token, TokenStream, Tokenizer};
+
+/// Tokenize a path into the paths of its ancestors.
+///
+/// With the default delimiter `/`, `/usr/local/bin` is tokenized as `/usr`, `/usr/local`
+/// and `/usr/local/bin`, so that the documents under a given directory, or category
+/// breadcrumb, can be found with a plain term query on the path of that directory.
+///
+/// In reverse mode, the suffixes of the path are emitted instead, from the longest to the
+/// shortest: with the delimiter `.`, `www.site.co.uk` is tokenized as `www.site.co.uk`,
+/// `site.co.uk`, `co.uk` and `uk`.
+///
+/// With this tokenizer, the `position` is always 0.
+///
+/// # Example
+///
+/// ```rust
+/// use tantivy::tokenizer::*;
+///
+/// let tokenizer = PathHierarchyTokenizer::default();
+/// let mut stream = tokenizer.token_stream("/usr/local/bin");
+/// assert_eq!(stream.next().unwrap().text, "/usr");
+/// assert_eq!(stream.next().unwrap().text, "/usr/local");
+/// assert_eq!(stream.next().unwrap().text, "/usr/local/bin");
+/// assert!(stream.next().is_none());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PathHierarchyTokenizer {
+    delimiter: char,
+    reverse: bool,
+}
+
+impl Default for PathHierarchyTokenizer {
+    fn default() -> PathHierarchyTokenizer {
+        PathHierarchyTokenizer {
+            delimiter: '/',
+            reverse: false,
+        }
+    }
+}
+
+impl PathHierarchyTokenizer {
+    /// Sets the character separating the components of the paths.
+    #[must_use]
+    pub fn set_delimiter(mut self, delimiter: char) -> PathHierarchyTokenizer {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Sets whether the suffixes of the paths should be emitted, instead of their prefixes.
+    #[must_use]
+    pub fn set_reverse(mut self, reverse: bool) -> PathHierarchyTokenizer {
+        self.reverse = reverse;
+        self
+    }
+}
+
+/// TokenStream associate to the `PathHierarchyTokenizer`
+pub struct PathHierarchyTokenStream<'a> {
+    text: &'a str,
+    // Byte ranges of the tokens, in reverse order.
+    ranges: Vec<(usize, usize)>,
+    token: Token,
+}
+
+impl Tokenizer for PathHierarchyTokenizer {
+    type TokenStream<'a> = PathHierarchyTokenStream<'a>;
+
+    fn token_stream<'a>(&self, text: &'a str) -> PathHierarchyTokenStream<'a> {
+        let delimiter_offsets = text.match_indices(self.delimiter).map(|(offset, _)| offset);
+        let mut ranges: Vec<(usize, usize)> = if self.reverse {
+            std::iter::once(0)
+                .chain(delimiter_offsets.map(|offset| offset + self.delimiter.len_utf8()))
+                .filter(|&offset_from| offset_from < text.len())
+                .map(|offset_from| (offset_from, text.len()))
+                .collect()
+        } else {
+            delimiter_offsets
+                .chain(std::iter::once(text.len()))
+                .filter(|&offset_to| offset_to > 0)
+                .map(|offset_to| (0, offset_to))
+                .collect()
+        };
+        ranges.reverse();
+        PathHierarchyTokenStream {
+            text,
+            ranges,
+            token: Token::default(),
+        }
+    }
+}
+
+impl<'a> TokenStream for PathHierarchyTokenStream<'a> {
+    fn advance(&mut self) -> bool {
+        let Some((offset_from, offset_to)) = self.ranges.pop() else {
+            return false;
+        };
+        self.token.position = 0;
+        self.token.keyword = false;
+        self.token.payload.clear();
+        self.token.offset_from = offset_from;
+        self.token.offset_to = offset_to;
+        self.token.text.clear();
+        self.token.text.push_str(&self.text[offset_from..offset_to]);
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PathHierarchyTokenizer;
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{TextAnalyzer, Token};
+
+    fn token_stream_helper(tokenizer: PathHierarchyTokenizer, text: &str) -> Vec<Token> {
+        let analyzer = TextAnalyzer::from(tokenizer);
+        let mut token_stream = analyzer.token_stream(text);
+        let mut tokens: Vec<Token> = vec![];
+        let mut add_token = |token: &Token| {
+            tokens.push(token.clone());
+        };
+        token_stream.process(&mut add_token);
+        tokens
+    }
+
+    #[test]
+    fn test_path_hierarchy_tokenizer() {
+        let tokens = token_stream_helper(PathHierarchyTokenizer::default(), "/a/bc/d/");
+        assert_eq!(tokens.len(), 4);
+        assert_token(&tokens[0], 0, "/a", 0, 2);
+        assert_token(&tokens[1], 0, "/a/bc", 0, 5);
+        assert_token(&tokens[2], 0, "/a/bc/d", 0, 7);
+        assert_token(&tokens[3], 0, "/a/bc/d/", 0, 8);
+        let tokens = token_stream_helper(PathHierarchyTokenizer::default(), "a");
+        assert_eq!(tokens.len(), 1);
+        assert_token(&tokens[0], 0, "a", 0, 1);
+        assert!(token_stream_helper(PathHierarchyTokenizer::default(), "").is_empty());
+    }
+
+    #[test]
+    fn test_path_hierarchy_tokenizer_reverse() {
+        let tokenizer = PathHierarchyTokenizer::default()
+            .set_delimiter('.')
+            .set_reverse(true);
+        let tokens = token_stream_helper(tokenizer, "www.site.uk");
+        assert_eq!(tokens.len(), 3);
+        assert_token(&tokens[0], 0, "www.site.uk", 0, 11);
+        assert_token(&tokens[1], 0, "site.uk", 4, 11);
+        assert_token(&tokens[2], 0, "uk", 9, 11);
+    }
+}
@@ -18,6 +18,8 @@ impl Tokenizer for RawTokenizer {
             position: 0,
             text: text.to_string(),
             position_length: 1,
+            keyword: false,
+            payload: Vec::new(),
         };
         RawTokenStream {
             token,
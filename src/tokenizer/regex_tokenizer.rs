@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use regex::Regex;
 
 use super::{Token, TokenStream, Tokenizer};
@@ -44,18 +46,81 @@ use crate::TantivyError;
 /// }
 /// assert!(stream.next().is_none());
 /// ```
-
+///
+/// The tokenizer can also [split](RegexTokenizer::split) the text on the matches of the
+/// regex, or only emit a [capture group](RegexTokenizer::capture_group) of each match:
+///
+/// ```rust
+/// use tantivy::tokenizer::*;
+///
+/// let tokenizer = RegexTokenizer::split(r"\s*[|,]\s*").unwrap();
+/// let mut stream = tokenizer.token_stream("GET | /index.html,200");
+/// assert_eq!(stream.next().unwrap().text, "GET");
+/// assert_eq!(stream.next().unwrap().text, "/index.html");
+/// assert_eq!(stream.next().unwrap().text, "200");
+/// assert!(stream.next().is_none());
+///
+/// let tokenizer = RegexTokenizer::capture_group(r"user=(\w+)", 1).unwrap();
+/// let mut stream = tokenizer.token_stream("user=alice action=login user=bob");
+/// assert_eq!(stream.next().unwrap().text, "alice");
+/// assert_eq!(stream.next().unwrap().text, "bob");
+/// assert!(stream.next().is_none());
+/// ```
 #[derive(Clone)]
 pub struct RegexTokenizer {
     regex: Regex,
+    mode: RegexTokenizerMode,
+}
+
+#[derive(Clone, Copy)]
+enum RegexTokenizerMode {
+    // Each match is a token.
+    Match,
+    // The text between the matches are the tokens.
+    Split,
+    // The given capture group of each match is a token.
+    CaptureGroup(usize),
+}
+
+fn compile(regex_pattern: &str) -> crate::Result<Regex> {
+    Regex::new(regex_pattern).map_err(|_| TantivyError::InvalidArgument(regex_pattern.to_owned()))
 }
 
 impl RegexTokenizer {
     /// Creates a new RegexTokenizer.
     pub fn new(regex_pattern: &str) -> crate::Result<RegexTokenizer> {
-        Regex::new(regex_pattern)
-            .map_err(|_| TantivyError::InvalidArgument(regex_pattern.to_owned()))
-            .map(|regex| Self { regex })
+        compile(regex_pattern).map(|regex| Self {
+            regex,
+            mode: RegexTokenizerMode::Match,
+        })
+    }
+
+    /// Creates a `RegexTokenizer` emitting the non-empty chunks of text between the matches
+    /// of the regex.
+    ///
+    /// Empty matches of the regex do not split the text.
+    pub fn split(regex_pattern: &str) -> crate::Result<RegexTokenizer> {
+        compile(regex_pattern).map(|regex| Self {
+            regex,
+            mode: RegexTokenizerMode::Split,
+        })
+    }
+
+    /// Creates a `RegexTokenizer` emitting the capture group `group` of each match of the
+    /// regex, the group 0 being the whole match. Empty groups are not emitted.
+    ///
+    /// Returns an error if the regex does not have such a capture group.
+    pub fn capture_group(regex_pattern: &str, group: usize) -> crate::Result<RegexTokenizer> {
+        let regex = compile(regex_pattern)?;
+        if group >= regex.captures_len() {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The regex {regex_pattern:?} has no capture group {group}"
+            )));
+        }
+        Ok(Self {
+            regex,
+            mode: RegexTokenizerMode::CaptureGroup(group),
+        })
     }
 }
 
@@ -64,6 +129,7 @@ impl Tokenizer for RegexTokenizer {
     fn token_stream<'a>(&self, text: &'a str) -> RegexTokenStream<'a> {
         RegexTokenStream {
             regex: self.regex.clone(),
+            mode: self.mode,
             text,
             token: Token::default(),
             cursor: 0,
@@ -73,29 +139,88 @@ impl Tokenizer for RegexTokenizer {
 
 pub struct RegexTokenStream<'a> {
     regex: Regex,
+    mode: RegexTokenizerMode,
     text: &'a str,
     token: Token,
     cursor: usize,
 }
 
+impl<'a> RegexTokenStream<'a> {
+    // Removes the first `len` bytes of the remaining text.
+    fn consume(&mut self, len: usize) {
+        self.cursor += len;
+        self.text = &self.text[len..];
+    }
+
+    // Returns the first match of the regex in the remaining text which is not empty.
+    fn find_non_empty(&self) -> Option<Range<usize>> {
+        let mut search_from = 0;
+        loop {
+            let regex_match = self.regex.find_at(self.text, search_from)?;
+            if !regex_match.as_str().is_empty() {
+                return Some(regex_match.range());
+            }
+            let c = self.text[regex_match.end()..].chars().next()?;
+            search_from = regex_match.end() + c.len_utf8();
+        }
+    }
+
+    // Returns the byte range of the next token within the remaining text, and the length
+    // of the text to consume once the token is emitted.
+    fn next_token(&mut self) -> Option<(Range<usize>, usize)> {
+        match self.mode {
+            RegexTokenizerMode::Match => {
+                let regex_match = self.regex.find(self.text)?;
+                if regex_match.as_str().is_empty() {
+                    return None;
+                }
+                Some((regex_match.range(), regex_match.end()))
+            }
+            RegexTokenizerMode::Split => loop {
+                if self.text.is_empty() {
+                    return None;
+                }
+                let Some(separator) = self.find_non_empty() else {
+                    return Some((0..self.text.len(), self.text.len()));
+                };
+                if separator.start > 0 {
+                    return Some((0..separator.start, separator.end));
+                }
+                self.consume(separator.end);
+            },
+            RegexTokenizerMode::CaptureGroup(group) => loop {
+                let captures = self.regex.captures(self.text)?;
+                let regex_match = captures.get(0).unwrap();
+                if regex_match.as_str().is_empty() {
+                    return None;
+                }
+                match captures.get(group) {
+                    Some(group_match) if !group_match.as_str().is_empty() => {
+                        return Some((group_match.range(), regex_match.end()));
+                    }
+                    _ => self.consume(regex_match.end()),
+                }
+            },
+        }
+    }
+}
+
 impl<'a> TokenStream for RegexTokenStream<'a> {
     fn advance(&mut self) -> bool {
-        let Some(regex_match) = self.regex.find(self.text) else {
+        let Some((token_range, consumed_len)) = self.next_token() else {
             return false;
         };
-        if regex_match.as_str().is_empty() {
-            return false;
-        }
         self.token.text.clear();
-        self.token.text.push_str(regex_match.as_str());
+        self.token.text.push_str(&self.text[token_range.clone()]);
 
-        self.token.offset_from = self.cursor + regex_match.start();
-        self.cursor += regex_match.end();
-        self.token.offset_to = self.cursor;
+        self.token.offset_from = self.cursor + token_range.start;
+        self.token.offset_to = self.cursor + token_range.end;
 
         self.token.position = self.token.position.wrapping_add(1);
+        self.token.keyword = false;
+        self.token.payload.clear();
 
-        self.text = &self.text[regex_match.end()..];
+        self.consume(consumed_len);
         true
     }
 
@@ -145,8 +270,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_regex_tokenizer_split() {
+        let tokenizer = RegexTokenizer::split(r"[,;]\s*|x*").unwrap();
+        let tokens = tokens_helper(tokenizer, ",a, bé;;c");
+        assert_eq!(tokens.len(), 3);
+        assert_token(&tokens[0], 0, "a", 1, 2);
+        assert_token(&tokens[1], 1, "bé", 4, 7);
+        assert_token(&tokens[2], 2, "c", 9, 10);
+        let tokenizer = RegexTokenizer::split(",").unwrap();
+        assert!(tokens_helper(tokenizer, ",,").is_empty());
+    }
+
+    #[test]
+    fn test_regex_tokenizer_capture_group() {
+        let tokenizer = RegexTokenizer::capture_group(r"(\w+)=(\w*)", 2).unwrap();
+        let tokens = tokens_helper(tokenizer, "a=1 b= c=3");
+        assert_eq!(tokens.len(), 2);
+        assert_token(&tokens[0], 0, "1", 2, 3);
+        assert_token(&tokens[1], 1, "3", 9, 10);
+        assert!(RegexTokenizer::capture_group(r"(\w+)=(\w*)", 3).is_err());
+    }
+
     fn token_stream_helper(text: &str, pattern: &str) -> Vec<Token> {
-        let r = RegexTokenizer::new(pattern).unwrap();
+        tokens_helper(RegexTokenizer::new(pattern).unwrap(), text)
+    }
+
+    fn tokens_helper(r: RegexTokenizer, text: &str) -> Vec<Token> {
         let a = TextAnalyzer::from(r);
         let mut token_stream = a.token_stream(text);
         let mut tokens: Vec<Token> = vec![];
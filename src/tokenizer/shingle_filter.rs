@@ -0,0 +1,232 @@
+//! # Example
+//! ```rust
+//! use tantivy::tokenizer::*;
+//!
+//! let tokenizer = TextAnalyzer::builder(SimpleTokenizer)
+//!   .filter(ShingleFilter::new(2, 2).unwrap())
+//!   .build();
+//!
+//! let mut stream = tokenizer.token_stream("quick brown fox");
+//! assert_eq!(stream.next().unwrap().text, "quick");
+//! assert_eq!(stream.next().unwrap().text, "quick brown");
+//! assert_eq!(stream.next().unwrap().text, "brown");
+//! assert_eq!(stream.next().unwrap().text, "brown fox");
+//! assert_eq!(stream.next().unwrap().text, "fox");
+//! assert!(stream.next().is_none());
+//! ```
+use std::collections::VecDeque;
+
+use super::{Token, TokenFilter, TokenStream, Tokenizer};
+use crate::TantivyError;
+
+/// `ShingleFilter` emits shingles, i.e. word n-grams, made of consecutive tokens.
+///
+/// A shingle is emitted at the position of its first token, right after that token, and
+/// spans from the start of its first token to the end of its last token.
+///
+/// By default, the original tokens are emitted as well, and the tokens of a shingle are
+/// separated by a single space.
+#[derive(Clone)]
+pub struct ShingleFilter {
+    min_shingle_size: usize,
+    max_shingle_size: usize,
+    separator: String,
+    output_unigrams: bool,
+}
+
+impl ShingleFilter {
+    /// Creates a `ShingleFilter` emitting shingles of `min_shingle_size` to
+    /// `max_shingle_size` tokens.
+    ///
+    /// Returns an error if `min_shingle_size` is lower than 2, or greater than
+    /// `max_shingle_size`.
+    pub fn new(min_shingle_size: usize, max_shingle_size: usize) -> crate::Result<ShingleFilter> {
+        if min_shingle_size < 2 {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The minimum shingle size should be at least 2, got {min_shingle_size}"
+            )));
+        }
+        if min_shingle_size > max_shingle_size {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The minimum shingle size ({min_shingle_size}) should not be greater than the \
+                 maximum shingle size ({max_shingle_size})"
+            )));
+        }
+        Ok(ShingleFilter {
+            min_shingle_size,
+            max_shingle_size,
+            separator: " ".to_string(),
+            output_unigrams: true,
+        })
+    }
+
+    /// Sets the string inserted between the tokens of a shingle.
+    #[must_use]
+    pub fn set_separator(mut self, separator: &str) -> ShingleFilter {
+        self.separator = separator.to_string();
+        self
+    }
+
+    /// Sets whether the original tokens should be emitted as well.
+    #[must_use]
+    pub fn set_output_unigrams(mut self, output_unigrams: bool) -> ShingleFilter {
+        self.output_unigrams = output_unigrams;
+        self
+    }
+}
+
+impl TokenFilter for ShingleFilter {
+    type Tokenizer<T: Tokenizer> = ShingleFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> ShingleFilterWrapper<T> {
+        ShingleFilterWrapper {
+            config: self,
+            inner: tokenizer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ShingleFilterWrapper<T> {
+    config: ShingleFilter,
+    inner: T,
+}
+
+impl<T: Tokenizer> Tokenizer for ShingleFilterWrapper<T> {
+    type TokenStream<'a> = ShingleFilterStream<T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&self, text: &'a str) -> Self::TokenStream<'a> {
+        ShingleFilterStream {
+            config: self.config.clone(),
+            tail: self.inner.token_stream(text),
+            tail_exhausted: false,
+            window: VecDeque::new(),
+            pending: VecDeque::new(),
+            token: Token::default(),
+        }
+    }
+}
+
+pub struct ShingleFilterStream<T> {
+    config: ShingleFilter,
+    tail: T,
+    tail_exhausted: bool,
+    // The tokens of the longest shingle starting at the current token.
+    window: VecDeque<Token>,
+    // Tokens built, but not emitted yet.
+    pending: VecDeque<Token>,
+    token: Token,
+}
+
+impl<T: TokenStream> ShingleFilterStream<T> {
+    fn fill_window(&mut self) {
+        while !self.tail_exhausted && self.window.len() < self.config.max_shingle_size {
+            if self.tail.advance() {
+                self.window.push_back(self.tail.token().clone());
+            } else {
+                self.tail_exhausted = true;
+            }
+        }
+    }
+
+    // Builds the unigram and the shingles starting at the first token of the window,
+    // and removes that token from the window.
+    fn process_next(&mut self) {
+        let Some(first_token) = self.window.front() else {
+            return;
+        };
+        if self.config.output_unigrams {
+            self.pending.push_back(first_token.clone());
+        }
+        let mut text = first_token.text.clone();
+        for (num_tokens, token) in self.window.iter().enumerate().skip(1) {
+            text.push_str(&self.config.separator);
+            text.push_str(&token.text);
+            if num_tokens + 1 >= self.config.min_shingle_size {
+                self.pending.push_back(Token {
+                    offset_from: first_token.offset_from,
+                    offset_to: token.offset_to,
+                    position: first_token.position,
+                    text: text.clone(),
+                    position_length: 1,
+                    keyword: false,
+                    payload: Vec::new(),
+                });
+            }
+        }
+        self.window.pop_front();
+    }
+}
+
+impl<T: TokenStream> TokenStream for ShingleFilterStream<T> {
+    fn advance(&mut self) -> bool {
+        while self.pending.is_empty() {
+            self.fill_window();
+            if self.window.is_empty() {
+                return false;
+            }
+            self.process_next();
+        }
+        self.token = self.pending.pop_front().unwrap();
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ShingleFilter;
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{SimpleTokenizer, TextAnalyzer, Token};
+
+    fn token_stream_helper(shingle_filter: ShingleFilter, text: &str) -> Vec<Token> {
+        let analyzer = TextAnalyzer::builder(SimpleTokenizer)
+            .filter(shingle_filter)
+            .build();
+        let mut token_stream = analyzer.token_stream(text);
+        let mut tokens: Vec<Token> = vec![];
+        let mut add_token = |token: &Token| {
+            tokens.push(token.clone());
+        };
+        token_stream.process(&mut add_token);
+        tokens
+    }
+
+    #[test]
+    fn test_shingle_filter() {
+        let tokens = token_stream_helper(ShingleFilter::new(2, 3).unwrap(), "a quick fox");
+        assert_eq!(tokens.len(), 6);
+        assert_token(&tokens[0], 0, "a", 0, 1);
+        assert_token(&tokens[1], 0, "a quick", 0, 7);
+        assert_token(&tokens[2], 0, "a quick fox", 0, 11);
+        assert_token(&tokens[3], 1, "quick", 2, 7);
+        assert_token(&tokens[4], 1, "quick fox", 2, 11);
+        assert_token(&tokens[5], 2, "fox", 8, 11);
+    }
+
+    #[test]
+    fn test_shingle_filter_without_unigrams() {
+        let shingle_filter = ShingleFilter::new(3, 3)
+            .unwrap()
+            .set_separator("_")
+            .set_output_unigrams(false);
+        let tokens = token_stream_helper(shingle_filter.clone(), "a quick brown fox");
+        assert_eq!(tokens.len(), 2);
+        assert_token(&tokens[0], 0, "a_quick_brown", 0, 13);
+        assert_token(&tokens[1], 1, "quick_brown_fox", 2, 17);
+        assert!(token_stream_helper(shingle_filter, "a quick").is_empty());
+    }
+
+    #[test]
+    fn test_shingle_filter_invalid_sizes() {
+        assert!(ShingleFilter::new(1, 2).is_err());
+        assert!(ShingleFilter::new(3, 2).is_err());
+    }
+}
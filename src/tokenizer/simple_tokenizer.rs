@@ -39,6 +39,8 @@ impl<'a> TokenStream for SimpleTokenStream<'a> {
     fn advance(&mut self) -> bool {
         self.token.text.clear();
         self.token.position = self.token.position.wrapping_add(1);
+        self.token.keyword = false;
+        self.token.payload.clear();
         while let Some((offset_from, c)) = self.chars.next() {
             if c.is_alphanumeric() {
                 let offset_to = self.search_token_end();
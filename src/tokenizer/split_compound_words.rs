@@ -145,6 +145,7 @@ impl<T: TokenStream> SplitCompoundWordsTokenStream<T> {
                 text = head;
                 self.parts.push(Token {
                     text: tail.to_owned(),
+                    payload: token.payload.clone(),
                     ..*token
                 });
             }
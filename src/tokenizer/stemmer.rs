@@ -59,6 +59,9 @@ impl Language {
 /// `Stemmer` token filter. Several languages are supported, see [`Language`] for the available
 /// languages.
 /// Tokens are expected to be lowercased beforehand.
+///
+/// The tokens marked as keywords, e.g. by a
+/// [`KeywordMarkerFilter`](crate::tokenizer::KeywordMarkerFilter), are left unchanged.
 #[derive(Clone)]
 pub struct Stemmer {
     stemmer_algorithm: Algorithm,
@@ -122,6 +125,9 @@ impl<T: TokenStream> TokenStream for StemmerTokenStream<T> {
             return false;
         }
         let token = self.tail.token_mut();
+        if token.keyword {
+            return true;
+        }
         let stemmed_str = self.stemmer.stem(&token.text);
         match stemmed_str {
             Cow::Owned(stemmed_str) => token.text = stemmed_str,
@@ -0,0 +1,386 @@
+//! # Example
+//! ```rust
+//! use tantivy::tokenizer::*;
+//!
+//! let tokenizer = TextAnalyzer::builder(SimpleTokenizer)
+//!   .filter(LowerCaser)
+//!   .filter(SynonymFilter::from_rules(["new york, nyc"]).unwrap())
+//!   .build();
+//!
+//! let mut stream = tokenizer.token_stream("NYC city");
+//! let token = stream.next().unwrap();
+//! assert_eq!((token.text.as_str(), token.position, token.position_length), ("new", 0, 1));
+//! let token = stream.next().unwrap();
+//! assert_eq!((token.text.as_str(), token.position, token.position_length), ("nyc", 0, 2));
+//! let token = stream.next().unwrap();
+//! assert_eq!((token.text.as_str(), token.position, token.position_length), ("york", 1, 1));
+//! let token = stream.next().unwrap();
+//! assert_eq!((token.text.as_str(), token.position, token.position_length), ("city", 2, 1));
+//! assert!(stream.next().is_none());
+//! ```
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use super::{Token, TokenFilter, TokenStream, Tokenizer};
+use crate::TantivyError;
+
+/// Separates the words of a phrase in the keys of the synonym map.
+const WORD_SEPARATOR: char = '\0';
+
+/// `TokenFilter` that expands synonyms, including multi-word synonyms.
+///
+/// The filter is configured from a list of rules, using the same syntax as the
+/// Solr synonym files:
+/// - `new york, nyc, big apple` declares a list of equivalent phrases. Any of these phrases is
+///   expanded to all of them.
+/// - `usa, united states => united states of america` declares an explicit mapping. Any of the
+///   phrases on the left is replaced by all of the phrases on the right.
+/// - Empty lines and lines starting with `#` are ignored.
+///
+/// The words of a phrase are separated by whitespaces, and are compared to the
+/// text of the tokens as is: the rules need to be normalized the same way as the tokens
+/// reaching the filter (e.g. lowercased).
+///
+/// The filter emits a token graph: a multi-word synonym shifts the position of the following
+/// tokens, and single token alternatives spanning several positions have a
+/// `position_length` greater than 1. The query parser relies on this to generate one phrase
+/// query per path of the graph, so that `"nyc city"` matches a document containing
+/// `new york city`, and conversely. The filter should therefore be used both when indexing
+/// and when querying a field.
+///
+/// Alternatives of more than one word and of the same length share their intermediate
+/// positions.
+#[derive(Clone)]
+pub struct SynonymFilter {
+    rules: Arc<SynonymRules>,
+}
+
+struct SynonymRules {
+    // Maps the words of a phrase, joined by `WORD_SEPARATOR`, to the phrases it expands to.
+    synonyms: HashMap<String, Vec<Vec<String>>>,
+    max_phrase_len: usize,
+}
+
+fn parse_phrases(rule: &str, phrases: &str) -> crate::Result<Vec<Vec<String>>> {
+    phrases
+        .split(',')
+        .map(|phrase| {
+            let words: Vec<String> = phrase.split_whitespace().map(str::to_string).collect();
+            if words.is_empty() {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "Invalid synonym rule `{rule}`: empty phrase"
+                )));
+            }
+            Ok(words)
+        })
+        .collect()
+}
+
+impl SynonymFilter {
+    /// Creates a `SynonymFilter` from a list of rules.
+    ///
+    /// Returns an error if one of the rules contains an empty phrase.
+    pub fn from_rules<I, S>(rules: I) -> crate::Result<SynonymFilter>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut synonyms: HashMap<String, Vec<Vec<String>>> = HashMap::new();
+        for rule in rules {
+            let rule = rule.as_ref().trim();
+            if rule.is_empty() || rule.starts_with('#') {
+                continue;
+            }
+            let (inputs, outputs) = if let Some((inputs, outputs)) = rule.split_once("=>") {
+                (parse_phrases(rule, inputs)?, parse_phrases(rule, outputs)?)
+            } else {
+                let phrases = parse_phrases(rule, rule)?;
+                (phrases.clone(), phrases)
+            };
+            for input in &inputs {
+                let key = input.join(&WORD_SEPARATOR.to_string());
+                let expansions = synonyms.entry(key).or_default();
+                for output in &outputs {
+                    if !expansions.contains(output) {
+                        expansions.push(output.clone());
+                    }
+                }
+            }
+        }
+        let max_phrase_len = synonyms
+            .keys()
+            .map(|key| key.split(WORD_SEPARATOR).count())
+            .max()
+            .unwrap_or(0);
+        Ok(SynonymFilter {
+            rules: Arc::new(SynonymRules {
+                synonyms,
+                max_phrase_len,
+            }),
+        })
+    }
+}
+
+impl TokenFilter for SynonymFilter {
+    type Tokenizer<T: Tokenizer> = SynonymFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> SynonymFilterWrapper<T> {
+        SynonymFilterWrapper {
+            rules: self.rules,
+            inner: tokenizer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SynonymFilterWrapper<T> {
+    rules: Arc<SynonymRules>,
+    inner: T,
+}
+
+impl<T: Tokenizer> Tokenizer for SynonymFilterWrapper<T> {
+    type TokenStream<'a> = SynonymFilterStream<T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&self, text: &'a str) -> Self::TokenStream<'a> {
+        SynonymFilterStream {
+            rules: self.rules.clone(),
+            tail: self.inner.token_stream(text),
+            tail_exhausted: false,
+            lookahead: VecDeque::new(),
+            pending: VecDeque::new(),
+            token: Token::default(),
+            key: String::new(),
+            position_shift: 0,
+        }
+    }
+}
+
+pub struct SynonymFilterStream<T> {
+    rules: Arc<SynonymRules>,
+    tail: T,
+    tail_exhausted: bool,
+    // Tokens read from the tail, but not processed yet.
+    lookahead: VecDeque<Token>,
+    // Tokens processed, but not emitted yet.
+    pending: VecDeque<Token>,
+    token: Token,
+    key: String,
+    // Number of positions inserted (or removed) by the synonyms emitted so far.
+    position_shift: isize,
+}
+
+impl<T: TokenStream> SynonymFilterStream<T> {
+    fn fill_lookahead(&mut self) {
+        while !self.tail_exhausted && self.lookahead.len() < self.rules.max_phrase_len.max(1) {
+            if self.tail.advance() {
+                self.lookahead.push_back(self.tail.token().clone());
+            } else {
+                self.tail_exhausted = true;
+            }
+        }
+    }
+
+    fn shifted(&self, position: usize) -> usize {
+        (position as isize + self.position_shift) as usize
+    }
+
+    // Returns the number of tokens of the longest phrase with synonyms at the start of the
+    // lookahead, and leaves the key of that phrase in `self.key`.
+    fn longest_match(&mut self) -> Option<usize> {
+        let first_position = self.lookahead.front()?.position;
+        let mut longest_match: Option<(usize, usize)> = None;
+        self.key.clear();
+        for (i, token) in self.lookahead.iter().enumerate() {
+            if token.position != first_position + i {
+                break;
+            }
+            if i > 0 {
+                self.key.push(WORD_SEPARATOR);
+            }
+            self.key.push_str(&token.text);
+            if self.rules.synonyms.contains_key(&self.key) {
+                longest_match = Some((i + 1, self.key.len()));
+            }
+        }
+        let (num_tokens, key_len) = longest_match?;
+        self.key.truncate(key_len);
+        Some(num_tokens)
+    }
+
+    // Moves the next token, or the next synonym expansion, from the lookahead to the pending
+    // tokens.
+    fn process_next(&mut self) {
+        self.fill_lookahead();
+        let Some(num_tokens) = self.longest_match() else {
+            if let Some(mut token) = self.lookahead.pop_front() {
+                token.position = self.shifted(token.position);
+                self.pending.push_back(token);
+            }
+            return;
+        };
+        let matched: Vec<Token> = self.lookahead.drain(..num_tokens).collect();
+        let expansions = &self.rules.synonyms[&self.key];
+        let start_position = self.shifted(matched[0].position);
+        let offset_from = matched[0].offset_from;
+        let offset_to = matched[num_tokens - 1].offset_to;
+        let span = expansions.iter().map(Vec::len).max().unwrap_or(num_tokens);
+        let mut tokens: Vec<Token> = Vec::new();
+        for expansion in expansions {
+            let is_original = expansion.len() == num_tokens
+                && expansion
+                    .iter()
+                    .zip(&matched)
+                    .all(|(word, token)| word == &token.text);
+            for (i, word) in expansion.iter().enumerate() {
+                // The last token of a path spans up to the end of the longest path.
+                let position_length = if i + 1 == expansion.len() {
+                    span - i
+                } else {
+                    1
+                };
+                let token = if is_original {
+                    Token {
+                        position: start_position + i,
+                        position_length,
+                        ..matched[i].clone()
+                    }
+                } else {
+                    Token {
+                        offset_from,
+                        offset_to,
+                        position: start_position + i,
+                        text: word.clone(),
+                        position_length,
+                        keyword: false,
+                        payload: Vec::new(),
+                    }
+                };
+                tokens.push(token);
+            }
+        }
+        tokens.sort_by_key(|token| token.position);
+        self.pending.extend(tokens);
+        self.position_shift += span as isize - num_tokens as isize;
+    }
+}
+
+impl<T: TokenStream> TokenStream for SynonymFilterStream<T> {
+    fn advance(&mut self) -> bool {
+        if self.pending.is_empty() {
+            self.process_next();
+        }
+        if let Some(token) = self.pending.pop_front() {
+            self.token = token;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SynonymFilter;
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{LowerCaser, SimpleTokenizer, TextAnalyzer, Token};
+
+    fn token_stream_helper(rules: &[&str], text: &str) -> Vec<Token> {
+        let mut token_stream = TextAnalyzer::builder(SimpleTokenizer)
+            .filter(LowerCaser)
+            .filter(SynonymFilter::from_rules(rules).unwrap())
+            .build();
+        let mut token_stream = token_stream.token_stream(text);
+        let mut tokens = vec![];
+        let mut add_token = |token: &Token| {
+            tokens.push(token.clone());
+        };
+        token_stream.process(&mut add_token);
+        tokens
+    }
+
+    fn positions(tokens: &[Token]) -> Vec<(&str, usize, usize)> {
+        tokens
+            .iter()
+            .map(|token| (token.text.as_str(), token.position, token.position_length))
+            .collect()
+    }
+
+    #[test]
+    fn test_synonym_filter_single_word() {
+        let tokens = token_stream_helper(&["quick, fast"], "The quick fox");
+        assert_eq!(
+            positions(&tokens),
+            vec![
+                ("the", 0, 1),
+                ("quick", 1, 1),
+                ("fast", 1, 1),
+                ("fox", 2, 1)
+            ]
+        );
+        assert_token(&tokens[2], 1, "fast", 4, 9);
+    }
+
+    #[test]
+    fn test_synonym_filter_multi_word() {
+        let rules = ["# cities", "", "new york, nyc"];
+        let tokens = token_stream_helper(&rules, "new york city");
+        assert_eq!(
+            positions(&tokens),
+            vec![("new", 0, 1), ("nyc", 0, 2), ("york", 1, 1), ("city", 2, 1)]
+        );
+        assert_token(&tokens[1], 0, "nyc", 0, 8);
+        let tokens = token_stream_helper(&rules, "I love nyc");
+        assert_eq!(
+            positions(&tokens),
+            vec![
+                ("i", 0, 1),
+                ("love", 1, 1),
+                ("new", 2, 1),
+                ("nyc", 2, 2),
+                ("york", 3, 1)
+            ]
+        );
+        assert_token(&tokens[2], 2, "new", 7, 10);
+    }
+
+    #[test]
+    fn test_synonym_filter_explicit_mapping() {
+        let rules = ["united states of america, usa => us"];
+        let tokens = token_stream_helper(&rules, "the united states of america today");
+        assert_eq!(
+            positions(&tokens),
+            vec![("the", 0, 1), ("us", 1, 1), ("today", 2, 1)]
+        );
+        assert_token(&tokens[1], 1, "us", 4, 28);
+    }
+
+    #[test]
+    fn test_synonym_filter_longest_match() {
+        let rules = ["new, novel", "new york, nyc"];
+        let tokens = token_stream_helper(&rules, "new york new");
+        assert_eq!(
+            positions(&tokens),
+            vec![
+                ("new", 0, 1),
+                ("nyc", 0, 2),
+                ("york", 1, 1),
+                ("new", 2, 1),
+                ("novel", 2, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_synonym_filter_invalid_rule() {
+        assert!(SynonymFilter::from_rules(["a, , b"]).is_err());
+        assert!(SynonymFilter::from_rules(["a =>"]).is_err());
+    }
+}
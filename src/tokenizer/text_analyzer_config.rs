@@ -0,0 +1,598 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use tokenizer_api::{BoxTokenStream, Token, TokenFilter, TokenStream, Tokenizer};
+
+use crate::tokenizer::{
+    AlphaNumOnlyFilter, AsciiFoldingFilter, CjkBigramFilter, DelimitedPayloadFilter,
+    EdgeNgramFilter, EdgeNgramSide, EdgeNgramTokenizer, HtmlStripCharFilter, KeywordMarkerFilter,
+    Language, LowerCaser, MappingCharFilter, NgramTokenizer, PathHierarchyTokenizer,
+    PatternReplaceCharFilter, PayloadEncoding, RawTokenizer, RegexTokenizer, RemoveLongFilter,
+    ShingleFilter, SimpleTokenizer, SplitCompoundWords, Stemmer, StopWordFilter, SynonymFilter,
+    TextAnalyzer, WhitespaceTokenizer, WordDelimiterFilter,
+};
+use crate::TantivyError;
+
+/// Declarative description of a [`TextAnalyzer`]: an ordered list of character filters,
+/// a tokenizer, and an ordered list of token filters.
+///
+/// Contrary to a `TextAnalyzer`, a `TextAnalyzerConfig` can be (de)serialized, which
+/// makes it possible to define analyzers at runtime, e.g. from a configuration file.
+/// Analyzers registered with [`TokenizerManager::register_config()`] are persisted in
+/// the `meta.json` file of the index, and registered again when the index is opened.
+///
+/// ```rust
+/// use tantivy::tokenizer::TextAnalyzerConfig;
+///
+/// let config: TextAnalyzerConfig = serde_json::from_str(r#"{
+///     "tokenizer": {"type": "simple"},
+///     "filters": [
+///         {"type": "remove_long", "length_limit": 40},
+///         {"type": "lower_caser"},
+///         {"type": "stemmer", "language": "English"}
+///     ]
+/// }"#).unwrap();
+/// let analyzer = config.build().unwrap();
+/// let mut stream = analyzer.token_stream("Hello Happy Tax Payers");
+/// assert_eq!(stream.next().unwrap().text, "hello");
+/// assert_eq!(stream.next().unwrap().text, "happi");
+/// ```
+///
+/// [`TokenizerManager::register_config()`]: crate::tokenizer::TokenizerManager::register_config
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TextAnalyzerConfig {
+    /// The character filters applied to the text before it is tokenized, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub char_filters: Vec<CharFilterConfig>,
+    /// The tokenizer splitting the text into tokens.
+    pub tokenizer: TokenizerConfig,
+    /// The filters applied to the tokens, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filters: Vec<TokenFilterConfig>,
+}
+
+/// Description of a [`CharFilter`](crate::tokenizer::CharFilter), see [`TextAnalyzerConfig`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CharFilterConfig {
+    /// See [`HtmlStripCharFilter`].
+    HtmlStrip,
+    /// See [`MappingCharFilter`].
+    Mapping {
+        /// The replacements of the strings of the text.
+        mappings: BTreeMap<String, String>,
+    },
+    /// See [`PatternReplaceCharFilter`].
+    PatternReplace {
+        /// The regular expression matching the chunks of text to replace.
+        pattern: String,
+        /// The replacement of the matches.
+        replacement: String,
+    },
+}
+
+/// Description of a [`Tokenizer`], see [`TextAnalyzerConfig`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TokenizerConfig {
+    /// See [`SimpleTokenizer`].
+    Simple,
+    /// See [`WhitespaceTokenizer`].
+    Whitespace,
+    /// See [`RawTokenizer`].
+    Raw,
+    /// See [`NgramTokenizer`].
+    Ngram {
+        /// Minimum size of the n-grams.
+        min_gram: usize,
+        /// Maximum size of the n-grams.
+        max_gram: usize,
+        /// If true, only the prefixes of the text are emitted.
+        #[serde(default)]
+        prefix_only: bool,
+    },
+    /// See [`EdgeNgramTokenizer`].
+    EdgeNgram {
+        /// Minimum size of the n-grams.
+        min_gram: usize,
+        /// Maximum size of the n-grams.
+        max_gram: usize,
+        /// The edge of the text the n-grams are anchored to.
+        #[serde(default)]
+        side: EdgeNgramSide,
+    },
+    /// See [`PathHierarchyTokenizer`].
+    PathHierarchy(PathHierarchyTokenizer),
+    /// See [`RegexTokenizer`].
+    Regex {
+        /// The regular expression matching the tokens.
+        pattern: String,
+        /// If true, the tokens are the chunks of text between the matches instead.
+        #[serde(default, skip_serializing_if = "is_false")]
+        split: bool,
+        /// If set, only this capture group of the matches is emitted.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        group: Option<usize>,
+    },
+}
+
+/// Description of a [`TokenFilter`], see [`TextAnalyzerConfig`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TokenFilterConfig {
+    /// See [`LowerCaser`].
+    LowerCaser,
+    /// See [`AsciiFoldingFilter`].
+    AsciiFolding {
+        /// Whether the original tokens are emitted as well.
+        #[serde(default, skip_serializing_if = "is_false")]
+        preserve_original: bool,
+    },
+    /// See [`AlphaNumOnlyFilter`].
+    AlphaNumOnly,
+    /// See [`RemoveLongFilter`].
+    RemoveLong {
+        /// Tokens of this length in bytes or longer are removed.
+        length_limit: usize,
+    },
+    /// See [`Stemmer`].
+    Stemmer {
+        /// The language of the stemmer.
+        language: Language,
+    },
+    /// See [`StopWordFilter`].
+    ///
+    /// Exactly one of `language` and `words` should be set.
+    StopWords {
+        /// Removes the stop words of a language. Requires the `stopwords` feature.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        language: Option<Language>,
+        /// Removes the given words.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        words: Vec<String>,
+    },
+    /// See [`KeywordMarkerFilter`].
+    ///
+    /// Exactly one of `words` and `pattern` should be set.
+    KeywordMarker {
+        /// Marks the given words as keywords.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        words: Vec<String>,
+        /// Marks the tokens matching this regular expression as keywords.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pattern: Option<String>,
+    },
+    /// See [`SplitCompoundWords`].
+    SplitCompoundWords {
+        /// The words the compound words are made of.
+        dictionary: Vec<String>,
+    },
+    /// See [`SynonymFilter`].
+    Synonyms {
+        /// The synonym rules.
+        rules: Vec<String>,
+    },
+    /// See [`ShingleFilter`].
+    Shingle {
+        /// Minimum number of tokens of a shingle.
+        min_shingle_size: usize,
+        /// Maximum number of tokens of a shingle.
+        max_shingle_size: usize,
+        /// String inserted between the tokens of a shingle. Defaults to a space.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        separator: Option<String>,
+        /// Whether the original tokens are emitted as well. Defaults to true.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        output_unigrams: Option<bool>,
+    },
+    /// See [`WordDelimiterFilter`].
+    WordDelimiter(WordDelimiterFilter),
+    /// See [`CjkBigramFilter`].
+    CjkBigram,
+    /// See [`EdgeNgramFilter`].
+    EdgeNgram {
+        /// Minimum size of the n-grams.
+        min_gram: usize,
+        /// Maximum size of the n-grams.
+        max_gram: usize,
+        /// The edge of the tokens the n-grams are anchored to.
+        #[serde(default)]
+        side: EdgeNgramSide,
+        /// Whether the tokens outside of the n-gram size range are emitted as well.
+        #[serde(default)]
+        preserve_original: bool,
+    },
+    /// See [`DelimitedPayloadFilter`].
+    DelimitedPayload {
+        /// The character separating the text of the tokens from their payload.
+        delimiter: char,
+        /// How the payloads are encoded.
+        #[serde(default)]
+        encoding: PayloadEncoding,
+    },
+    /// See `IcuFoldingFilter`. Requires the `icu` feature.
+    IcuFolding {
+        /// Whether the case folding follows the rules of Turkish and Azerbaijani.
+        #[serde(default, skip_serializing_if = "is_false")]
+        turkic: bool,
+        /// Whether the diacritics are kept.
+        #[serde(default, skip_serializing_if = "is_false")]
+        keep_diacritics: bool,
+    },
+}
+
+fn is_false(val: &bool) -> bool {
+    !val
+}
+
+/// Makes it possible to append filters to a `TextAnalyzer`, whose tokenizer type is erased.
+#[derive(Clone)]
+struct DynTokenizer(TextAnalyzer);
+
+impl Tokenizer for DynTokenizer {
+    type TokenStream<'a> = DynTokenStream<'a>;
+
+    fn token_stream<'a>(&self, text: &'a str) -> DynTokenStream<'a> {
+        DynTokenStream(self.0.token_stream(text))
+    }
+}
+
+struct DynTokenStream<'a>(BoxTokenStream<'a>);
+
+impl<'a> TokenStream for DynTokenStream<'a> {
+    fn advance(&mut self) -> bool {
+        self.0.advance()
+    }
+
+    fn token(&self) -> &Token {
+        self.0.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.0.token_mut()
+    }
+}
+
+fn append_filter<F: TokenFilter>(analyzer: TextAnalyzer, filter: F) -> TextAnalyzer {
+    TextAnalyzer::builder(DynTokenizer(analyzer))
+        .filter(filter)
+        .build()
+}
+
+impl TokenizerConfig {
+    fn build(&self) -> crate::Result<TextAnalyzer> {
+        let analyzer = match self {
+            TokenizerConfig::Simple => TextAnalyzer::from(SimpleTokenizer),
+            TokenizerConfig::Whitespace => TextAnalyzer::from(WhitespaceTokenizer),
+            TokenizerConfig::Raw => TextAnalyzer::from(RawTokenizer),
+            TokenizerConfig::Ngram {
+                min_gram,
+                max_gram,
+                prefix_only,
+            } => {
+                if *min_gram == 0 || min_gram > max_gram {
+                    return Err(TantivyError::InvalidArgument(format!(
+                        "Invalid n-gram sizes: min_gram={min_gram}, max_gram={max_gram}"
+                    )));
+                }
+                TextAnalyzer::from(NgramTokenizer::new(*min_gram, *max_gram, *prefix_only))
+            }
+            TokenizerConfig::EdgeNgram {
+                min_gram,
+                max_gram,
+                side,
+            } => TextAnalyzer::from(EdgeNgramTokenizer::new(*min_gram, *max_gram)?.set_side(*side)),
+            TokenizerConfig::PathHierarchy(path_hierarchy_tokenizer) => {
+                TextAnalyzer::from(path_hierarchy_tokenizer.clone())
+            }
+            TokenizerConfig::Regex {
+                pattern,
+                split,
+                group,
+            } => {
+                let tokenizer = match (split, group) {
+                    (false, None) => RegexTokenizer::new(pattern)?,
+                    (true, None) => RegexTokenizer::split(pattern)?,
+                    (false, Some(group)) => RegexTokenizer::capture_group(pattern, *group)?,
+                    (true, Some(_)) => {
+                        return Err(TantivyError::InvalidArgument(
+                            "A regex tokenizer cannot both split the text and emit a capture group"
+                                .to_string(),
+                        ))
+                    }
+                };
+                TextAnalyzer::from(tokenizer)
+            }
+        };
+        Ok(analyzer)
+    }
+}
+
+impl TokenFilterConfig {
+    fn append_to(&self, analyzer: TextAnalyzer) -> crate::Result<TextAnalyzer> {
+        let analyzer = match self {
+            TokenFilterConfig::LowerCaser => append_filter(analyzer, LowerCaser),
+            TokenFilterConfig::AsciiFolding { preserve_original } => append_filter(
+                analyzer,
+                AsciiFoldingFilter::default().set_preserve_original(*preserve_original),
+            ),
+            TokenFilterConfig::AlphaNumOnly => append_filter(analyzer, AlphaNumOnlyFilter),
+            TokenFilterConfig::RemoveLong { length_limit } => {
+                append_filter(analyzer, RemoveLongFilter::limit(*length_limit))
+            }
+            TokenFilterConfig::Stemmer { language } => {
+                append_filter(analyzer, Stemmer::new(*language))
+            }
+            TokenFilterConfig::StopWords { language, words } => {
+                let stop_word_filter = match (language, words.is_empty()) {
+                    (Some(language), true) => stop_words_for_language(*language)?,
+                    (None, false) => StopWordFilter::remove(words.iter().cloned()),
+                    _ => {
+                        return Err(TantivyError::InvalidArgument(
+                            "Exactly one of `language` and `words` should be set for the \
+                             stop_words filter"
+                                .to_string(),
+                        ));
+                    }
+                };
+                append_filter(analyzer, stop_word_filter)
+            }
+            TokenFilterConfig::KeywordMarker { words, pattern } => {
+                let keyword_marker_filter = match (pattern, words.is_empty()) {
+                    (Some(pattern), true) => KeywordMarkerFilter::from_pattern(pattern)?,
+                    (None, false) => KeywordMarkerFilter::from_words(words.iter().cloned()),
+                    _ => {
+                        return Err(TantivyError::InvalidArgument(
+                            "Exactly one of `words` and `pattern` should be set for the \
+                             keyword_marker filter"
+                                .to_string(),
+                        ));
+                    }
+                };
+                append_filter(analyzer, keyword_marker_filter)
+            }
+            TokenFilterConfig::SplitCompoundWords { dictionary } => {
+                append_filter(analyzer, SplitCompoundWords::from_dictionary(dictionary)?)
+            }
+            TokenFilterConfig::Synonyms { rules } => {
+                append_filter(analyzer, SynonymFilter::from_rules(rules)?)
+            }
+            TokenFilterConfig::Shingle {
+                min_shingle_size,
+                max_shingle_size,
+                separator,
+                output_unigrams,
+            } => {
+                let mut shingle_filter = ShingleFilter::new(*min_shingle_size, *max_shingle_size)?;
+                if let Some(separator) = separator {
+                    shingle_filter = shingle_filter.set_separator(separator);
+                }
+                if let Some(output_unigrams) = output_unigrams {
+                    shingle_filter = shingle_filter.set_output_unigrams(*output_unigrams);
+                }
+                append_filter(analyzer, shingle_filter)
+            }
+            TokenFilterConfig::WordDelimiter(word_delimiter_filter) => {
+                append_filter(analyzer, word_delimiter_filter.clone())
+            }
+            TokenFilterConfig::CjkBigram => append_filter(analyzer, CjkBigramFilter),
+            TokenFilterConfig::EdgeNgram {
+                min_gram,
+                max_gram,
+                side,
+                preserve_original,
+            } => {
+                let edge_ngram_filter = EdgeNgramFilter::new(*min_gram, *max_gram)?
+                    .set_side(*side)
+                    .set_preserve_original(*preserve_original);
+                append_filter(analyzer, edge_ngram_filter)
+            }
+            TokenFilterConfig::DelimitedPayload {
+                delimiter,
+                encoding,
+            } => append_filter(analyzer, DelimitedPayloadFilter::new(*delimiter, *encoding)),
+            TokenFilterConfig::IcuFolding {
+                turkic,
+                keep_diacritics,
+            } => append_icu_folding_filter(analyzer, *turkic, *keep_diacritics)?,
+        };
+        Ok(analyzer)
+    }
+}
+
+#[cfg(feature = "stopwords")]
+fn stop_words_for_language(language: Language) -> crate::Result<StopWordFilter> {
+    StopWordFilter::new(language).ok_or_else(|| {
+        TantivyError::InvalidArgument(format!("No stop words available for {language:?}"))
+    })
+}
+
+#[cfg(not(feature = "stopwords"))]
+fn stop_words_for_language(_language: Language) -> crate::Result<StopWordFilter> {
+    Err(TantivyError::InvalidArgument(
+        "Stop words lists require the `stopwords` feature".to_string(),
+    ))
+}
+
+#[cfg(feature = "icu")]
+fn append_icu_folding_filter(
+    analyzer: TextAnalyzer,
+    turkic: bool,
+    keep_diacritics: bool,
+) -> crate::Result<TextAnalyzer> {
+    let icu_folding_filter = crate::tokenizer::IcuFoldingFilter::default()
+        .set_turkic(turkic)
+        .set_keep_diacritics(keep_diacritics);
+    Ok(append_filter(analyzer, icu_folding_filter))
+}
+
+#[cfg(not(feature = "icu"))]
+fn append_icu_folding_filter(
+    _analyzer: TextAnalyzer,
+    _turkic: bool,
+    _keep_diacritics: bool,
+) -> crate::Result<TextAnalyzer> {
+    Err(TantivyError::InvalidArgument(
+        "The icu_folding filter requires the `icu` feature".to_string(),
+    ))
+}
+
+impl TextAnalyzerConfig {
+    /// Builds the `TextAnalyzer` described by this configuration.
+    ///
+    /// Returns an error if the parameters of the tokenizer or of one of the filters
+    /// are invalid.
+    pub fn build(&self) -> crate::Result<TextAnalyzer> {
+        let mut analyzer = self.tokenizer.build()?;
+        for filter in &self.filters {
+            analyzer = filter.append_to(analyzer)?;
+        }
+        if self.char_filters.is_empty() {
+            return Ok(analyzer);
+        }
+        let mut builder = TextAnalyzer::builder(DynTokenizer(analyzer));
+        for char_filter in &self.char_filters {
+            builder = match char_filter {
+                CharFilterConfig::HtmlStrip => builder.char_filter(HtmlStripCharFilter),
+                CharFilterConfig::Mapping { mappings } => {
+                    builder.char_filter(MappingCharFilter::new(mappings.clone())?)
+                }
+                CharFilterConfig::PatternReplace {
+                    pattern,
+                    replacement,
+                } => builder.char_filter(PatternReplaceCharFilter::new(pattern, replacement)?),
+            };
+        }
+        Ok(builder.build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CharFilterConfig, TextAnalyzerConfig, TokenFilterConfig, TokenizerConfig};
+    use crate::tokenizer::{Token, WordDelimiterFilter};
+
+    fn tokens(config: &TextAnalyzerConfig, text: &str) -> Vec<String> {
+        let analyzer = config.build().unwrap();
+        let mut token_stream = analyzer.token_stream(text);
+        let mut tokens = vec![];
+        token_stream.process(&mut |token: &Token| tokens.push(token.text.clone()));
+        tokens
+    }
+
+    #[test]
+    fn test_text_analyzer_config_serialization() {
+        let config = TextAnalyzerConfig {
+            char_filters: vec![CharFilterConfig::HtmlStrip],
+            tokenizer: TokenizerConfig::Whitespace,
+            filters: vec![
+                TokenFilterConfig::WordDelimiter(
+                    WordDelimiterFilter::default().set_catenate_words(true),
+                ),
+                TokenFilterConfig::LowerCaser,
+                TokenFilterConfig::StopWords {
+                    language: None,
+                    words: vec!["the".to_string()],
+                },
+            ],
+        };
+        let config_json = serde_json::to_string(&config).unwrap();
+        assert!(config_json.starts_with(
+            r#"{"char_filters":[{"type":"html_strip"}],"tokenizer":{"type":"whitespace"},"filters":[{"type":"word_delimiter","generate_word_parts":true,"#
+        ));
+        let config_deser: TextAnalyzerConfig = serde_json::from_str(&config_json).unwrap();
+        assert_eq!(config_deser, config);
+        assert_eq!(
+            tokens(&config_deser, "The <b>Wi-Fi</b> router"),
+            vec!["wi", "wifi", "fi", "router"]
+        );
+    }
+
+    #[test]
+    fn test_text_analyzer_config_defaults() {
+        let config: TextAnalyzerConfig = serde_json::from_str(
+            r#"{
+                "tokenizer": {"type": "ngram", "min_gram": 2, "max_gram": 3, "prefix_only": true}
+            }"#,
+        )
+        .unwrap();
+        assert!(config.filters.is_empty());
+        assert_eq!(tokens(&config, "hello"), vec!["he", "hel"]);
+        let config: TextAnalyzerConfig = serde_json::from_str(
+            r#"{
+                "tokenizer": {"type": "simple"},
+                "filters": [
+                    {"type": "shingle", "min_shingle_size": 2, "max_shingle_size": 2},
+                    {"type": "word_delimiter", "preserve_original": true}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            tokens(&config, "quick fox"),
+            vec!["quick", "quick fox", "quick", "fox", "fox"]
+        );
+        let config: TextAnalyzerConfig = serde_json::from_str(
+            r#"{
+                "tokenizer": {"type": "simple"},
+                "filters": [{"type": "ascii_folding"}]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.filters,
+            vec![TokenFilterConfig::AsciiFolding {
+                preserve_original: false
+            }]
+        );
+        assert_eq!(
+            serde_json::to_string(&config.filters).unwrap(),
+            r#"[{"type":"ascii_folding"}]"#
+        );
+    }
+
+    #[test]
+    fn test_text_analyzer_config_invalid() {
+        let invalid_configs = [
+            r#"{"tokenizer": {"type": "ngram", "min_gram": 3, "max_gram": 2}}"#,
+            r#"{"tokenizer": {"type": "regex", "pattern": "("}}"#,
+            r#"{"tokenizer": {"type": "regex", "pattern": "a", "split": true, "group": 0}}"#,
+            r#"{"tokenizer": {"type": "regex", "pattern": "a", "group": 1}}"#,
+            r#"{"char_filters": [{"type": "pattern_replace", "pattern": "(", "replacement": ""}], "tokenizer": {"type": "raw"}}"#,
+            r#"{"tokenizer": {"type": "simple"}, "filters": [{"type": "stop_words"}]}"#,
+            r#"{"tokenizer": {"type": "simple"}, "filters": [{"type": "keyword_marker", "words": ["a"], "pattern": "b"}]}"#,
+            r#"{"tokenizer": {"type": "simple"}, "filters": [{"type": "synonyms", "rules": ["a,,b"]}]}"#,
+        ];
+        for invalid_config in invalid_configs {
+            let config: TextAnalyzerConfig = serde_json::from_str(invalid_config).unwrap();
+            assert!(config.build().is_err(), "{invalid_config}");
+        }
+        assert!(serde_json::from_str::<TextAnalyzerConfig>(
+            r#"{"tokenizer": {"type": "unknown"}}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_text_analyzer_config_icu_folding() {
+        let config: TextAnalyzerConfig = serde_json::from_str(
+            r#"{
+                "tokenizer": {"type": "simple"},
+                "filters": [{"type": "icu_folding", "turkic": true}]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.filters,
+            vec![TokenFilterConfig::IcuFolding {
+                turkic: true,
+                keep_diacritics: false
+            }]
+        );
+        if cfg!(feature = "icu") {
+            assert_eq!(tokens(&config, "ISPARTA Ελλάδα"), vec!["ısparta", "ελλαδα"]);
+        } else {
+            assert!(config.build().is_err());
+        }
+    }
+}
@@ -80,6 +80,8 @@ mod tests {
                     position: 0,
                     text: String::from("A"),
                     position_length: 1,
+                    keyword: false,
+                    payload: Vec::new(),
                 },
                 Token {
                     offset_from: 2,
@@ -87,6 +89,8 @@ mod tests {
                     position: 1,
                     text: String::from("a"),
                     position_length: 1,
+                    keyword: false,
+                    payload: Vec::new(),
                 },
             ],
         };
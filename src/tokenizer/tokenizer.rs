@@ -1,11 +1,19 @@
 /// The tokenizer module contains all of the tools used to process
 /// text in `tantivy`.
+use std::sync::Arc;
+
 use tokenizer_api::{BoxTokenStream, TokenFilter, Tokenizer};
 
+use crate::tokenizer::char_filter::CharFilteredTokenStream;
 use crate::tokenizer::empty_tokenizer::EmptyTokenizer;
+use crate::tokenizer::CharFilter;
 
 /// `TextAnalyzer` tokenizes an input text into tokens and modifies the resulting `TokenStream`.
+///
+/// The text can first be transformed by a sequence of [`CharFilter`]s. The offsets of the
+/// resulting tokens still refer to the original text.
 pub struct TextAnalyzer {
+    char_filters: Vec<Arc<dyn CharFilter>>,
     tokenizer: Box<dyn BoxableTokenizer>,
 }
 
@@ -29,6 +37,7 @@ impl<T: Tokenizer> BoxableTokenizer for T {
 impl Clone for TextAnalyzer {
     fn clone(&self) -> Self {
         TextAnalyzer {
+            char_filters: self.char_filters.clone(),
             tokenizer: self.tokenizer.box_clone(),
         }
     }
@@ -49,17 +58,32 @@ impl<T: Tokenizer + Clone> From<T> for TextAnalyzer {
 impl TextAnalyzer {
     /// Create a new TextAnalyzerBuilder
     pub fn builder<T: Tokenizer>(tokenizer: T) -> TextAnalyzerBuilder<T> {
-        TextAnalyzerBuilder { tokenizer }
+        TextAnalyzerBuilder {
+            char_filters: Vec::new(),
+            tokenizer,
+        }
     }
 
     /// Creates a token stream for a given `str`.
     pub fn token_stream<'a>(&self, text: &'a str) -> BoxTokenStream<'a> {
-        self.tokenizer.box_token_stream(text)
+        let Some((first_char_filter, char_filters)) = self.char_filters.split_first() else {
+            return self.tokenizer.box_token_stream(text);
+        };
+        let mut filtered_texts = vec![first_char_filter.filter(text)];
+        for char_filter in char_filters {
+            let filtered_text = char_filter.filter(filtered_texts.last().unwrap().text());
+            filtered_texts.push(filtered_text);
+        }
+        let mut token_stream = self
+            .tokenizer
+            .box_token_stream(filtered_texts.last().unwrap().text());
+        CharFilteredTokenStream::new(&mut *token_stream, &filtered_texts).into()
     }
 }
 
 /// Builder helper for [`TextAnalyzer`]
 pub struct TextAnalyzerBuilder<T> {
+    char_filters: Vec<Arc<dyn CharFilter>>,
     tokenizer: T,
 }
 
@@ -79,13 +103,34 @@ impl<T: Tokenizer> TextAnalyzerBuilder<T> {
     /// ```
     pub fn filter<F: TokenFilter>(self, token_filter: F) -> TextAnalyzerBuilder<F::Tokenizer<T>> {
         TextAnalyzerBuilder {
+            char_filters: self.char_filters,
             tokenizer: token_filter.transform(self.tokenizer),
         }
     }
 
+    /// Appends a character filter to the current builder.
+    ///
+    /// Character filters are applied to the text, in order, before it is tokenized.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tantivy::tokenizer::*;
+    ///
+    /// let html_analyzer = TextAnalyzer::builder(SimpleTokenizer)
+    ///     .char_filter(HtmlStripCharFilter)
+    ///     .filter(LowerCaser)
+    ///     .build();
+    /// ```
+    pub fn char_filter<C: CharFilter>(mut self, char_filter: C) -> TextAnalyzerBuilder<T> {
+        self.char_filters.push(Arc::new(char_filter));
+        self
+    }
+
     /// Finalize building the TextAnalyzer
     pub fn build(self) -> TextAnalyzer {
         TextAnalyzer {
+            char_filters: self.char_filters,
             tokenizer: Box::new(self.tokenizer),
         }
     }
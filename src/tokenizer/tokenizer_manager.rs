@@ -1,10 +1,11 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, RwLock};
 
 use crate::tokenizer::stemmer::Language;
 use crate::tokenizer::tokenizer::TextAnalyzer;
 use crate::tokenizer::{
-    LowerCaser, RawTokenizer, RemoveLongFilter, SimpleTokenizer, Stemmer, WhitespaceTokenizer,
+    EdgeNgramFilter, LowerCaser, RawTokenizer, RemoveLongFilter, SimpleTokenizer, Stemmer,
+    TextAnalyzerConfig, Token, WhitespaceTokenizer,
 };
 
 /// The tokenizer manager serves as a store for
@@ -20,9 +21,12 @@ use crate::tokenizer::{
 ///  resulting tokens. Stemming can improve the recall of your
 ///  search engine.
 /// * `whitespace` : Splits the text on whitespaces.
+/// * `autocomplete` : Like `default`, but indexes the prefixes of 1 to 20 characters of
+///   the tokens instead of the tokens themselves, for search-as-you-type.
 #[derive(Clone)]
 pub struct TokenizerManager {
     tokenizers: Arc<RwLock<HashMap<String, TextAnalyzer>>>,
+    configs: Arc<RwLock<BTreeMap<String, TextAnalyzerConfig>>>,
 }
 
 impl TokenizerManager {
@@ -30,6 +34,7 @@ impl TokenizerManager {
     pub fn new() -> Self {
         Self {
             tokenizers: Arc::new(RwLock::new(HashMap::new())),
+            configs: Arc::new(RwLock::new(BTreeMap::new())),
         }
     }
 
@@ -41,6 +46,41 @@ impl TokenizerManager {
             .write()
             .expect("Acquiring the lock should never fail")
             .insert(tokenizer_name.to_string(), boxed_tokenizer);
+        self.configs
+            .write()
+            .expect("Acquiring the lock should never fail")
+            .remove(tokenizer_name);
+    }
+
+    /// Builds the tokenizer described by a [`TextAnalyzerConfig`], and registers it
+    /// with a given name.
+    ///
+    /// Contrary to the tokenizers registered using [`TokenizerManager::register()`], the
+    /// configuration of the tokenizer is persisted in the index meta upon commit, and the
+    /// tokenizer is registered again when the index is opened.
+    ///
+    /// Returns an error if the tokenizer cannot be built from the configuration.
+    pub fn register_config(
+        &self,
+        tokenizer_name: &str,
+        config: TextAnalyzerConfig,
+    ) -> crate::Result<()> {
+        let text_analyzer = config.build()?;
+        self.register(tokenizer_name, text_analyzer);
+        self.configs
+            .write()
+            .expect("Acquiring the lock should never fail")
+            .insert(tokenizer_name.to_string(), config);
+        Ok(())
+    }
+
+    /// Returns the configurations of the tokenizers registered using
+    /// [`TokenizerManager::register_config()`].
+    pub fn configs(&self) -> BTreeMap<String, TextAnalyzerConfig> {
+        self.configs
+            .read()
+            .expect("Acquiring the lock should never fail")
+            .clone()
     }
 
     /// Accessing a tokenizer given its name.
@@ -51,6 +91,29 @@ impl TokenizerManager {
             .get(tokenizer_name)
             .cloned()
     }
+
+    /// Returns the tokens emitted by a tokenizer for a given text, with their positions and
+    /// offsets, e.g. to understand why a query does or does not match a document.
+    ///
+    /// Returns `None` if no tokenizer is registered with the given name.
+    ///
+    /// ```rust
+    /// use tantivy::tokenizer::TokenizerManager;
+    ///
+    /// let tokenizer_manager = TokenizerManager::default();
+    /// let tokens = tokenizer_manager.analyze("en_stem", "Running dogs").unwrap();
+    /// assert_eq!(tokens[0].text, "run");
+    /// assert_eq!((tokens[0].offset_from, tokens[0].offset_to), (0, 7));
+    /// assert_eq!(tokens[1].text, "dog");
+    /// assert_eq!(tokens[1].position, 1);
+    /// ```
+    pub fn analyze(&self, tokenizer_name: &str, text: &str) -> Option<Vec<Token>> {
+        let text_analyzer = self.get(tokenizer_name)?;
+        let mut token_stream = text_analyzer.token_stream(text);
+        let mut tokens = Vec::new();
+        token_stream.process(&mut |token: &Token| tokens.push(token.clone()));
+        Some(tokens)
+    }
 }
 
 impl Default for TokenizerManager {
@@ -75,6 +138,33 @@ impl Default for TokenizerManager {
                 .build(),
         );
         manager.register("whitespace", WhitespaceTokenizer);
+        manager.register(
+            "autocomplete",
+            TextAnalyzer::builder(SimpleTokenizer)
+                .filter(RemoveLongFilter::limit(40))
+                .filter(LowerCaser)
+                .filter(EdgeNgramFilter::new(1, 20).unwrap())
+                .build(),
+        );
         manager
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::TokenizerManager;
+    use crate::tokenizer::tests::assert_token;
+
+    #[test]
+    fn test_tokenizer_manager_analyze() {
+        let tokenizer_manager = TokenizerManager::default();
+        let tokens = tokenizer_manager
+            .analyze("default", "Hello, Wörld!")
+            .unwrap();
+        assert_eq!(tokens.len(), 2);
+        assert_token(&tokens[0], 0, "hello", 0, 5);
+        assert_token(&tokens[1], 1, "wörld", 7, 13);
+        assert_eq!(tokens[1].position_length, 1);
+        assert!(tokenizer_manager.analyze("unknown", "hello").is_none());
+    }
+}
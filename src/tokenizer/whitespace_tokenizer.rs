@@ -38,6 +38,8 @@ impl<'a> TokenStream for WhitespaceTokenStream<'a> {
     fn advance(&mut self) -> bool {
         self.token.text.clear();
         self.token.position = self.token.position.wrapping_add(1);
+        self.token.keyword = false;
+        self.token.payload.clear();
         while let Some((offset_from, c)) = self.chars.next() {
             if !c.is_ascii_whitespace() {
                 let offset_to = self.search_token_end();
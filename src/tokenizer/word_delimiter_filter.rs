@@ -0,0 +1,483 @@
+//! # Example
+//! ```rust
+//! use tantivy::tokenizer::*;
+//!
+//! let tokenizer = TextAnalyzer::builder(WhitespaceTokenizer)
+//!   .filter(WordDelimiterFilter::default().set_catenate_words(true))
+//!   .filter(LowerCaser)
+//!   .build();
+//!
+//! let mut stream = tokenizer.token_stream("PowerShot-2000");
+//! assert_eq!(stream.next().unwrap().text, "power");
+//! assert_eq!(stream.next().unwrap().text, "powershot");
+//! assert_eq!(stream.next().unwrap().text, "shot");
+//! assert_eq!(stream.next().unwrap().text, "2000");
+//! assert!(stream.next().is_none());
+//! ```
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Token, TokenFilter, TokenStream, Tokenizer};
+
+/// `WordDelimiterFilter` splits tokens into subwords, on punctuation, case changes and
+/// transitions between letters and digits.
+///
+/// For instance, `PowerShot-2000` is split into `Power`, `Shot` and `2000`.
+/// Optionally, consecutive subwords can be catenated back together, so that `PowerShot`
+/// or `PowerShot2000` are emitted as well.
+///
+/// The filter emits a token graph: the subwords of a token get consecutive positions,
+/// shifting the positions of the following tokens, and catenated tokens span the
+/// positions of their subwords. As a result, the filter should be used both when indexing
+/// and when querying a field. It should be applied before any filter changing the case of
+/// the tokens, like [`LowerCaser`](super::LowerCaser).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WordDelimiterFilter {
+    generate_word_parts: bool,
+    generate_number_parts: bool,
+    catenate_words: bool,
+    catenate_numbers: bool,
+    catenate_all: bool,
+    preserve_original: bool,
+    split_on_case_change: bool,
+    split_on_numerics: bool,
+    stem_english_possessive: bool,
+}
+
+impl Default for WordDelimiterFilter {
+    fn default() -> WordDelimiterFilter {
+        WordDelimiterFilter {
+            generate_word_parts: true,
+            generate_number_parts: true,
+            catenate_words: false,
+            catenate_numbers: false,
+            catenate_all: false,
+            preserve_original: false,
+            split_on_case_change: true,
+            split_on_numerics: true,
+            stem_english_possessive: true,
+        }
+    }
+}
+
+impl WordDelimiterFilter {
+    /// Sets whether the alphabetic subwords should be emitted. Defaults to `true`.
+    #[must_use]
+    pub fn set_generate_word_parts(mut self, generate_word_parts: bool) -> WordDelimiterFilter {
+        self.generate_word_parts = generate_word_parts;
+        self
+    }
+
+    /// Sets whether the numeric subwords should be emitted. Defaults to `true`.
+    #[must_use]
+    pub fn set_generate_number_parts(mut self, generate_number_parts: bool) -> WordDelimiterFilter {
+        self.generate_number_parts = generate_number_parts;
+        self
+    }
+
+    /// Sets whether runs of consecutive alphabetic subwords should be catenated,
+    /// e.g. `wi-fi` to `wifi`. Defaults to `false`.
+    #[must_use]
+    pub fn set_catenate_words(mut self, catenate_words: bool) -> WordDelimiterFilter {
+        self.catenate_words = catenate_words;
+        self
+    }
+
+    /// Sets whether runs of consecutive numeric subwords should be catenated,
+    /// e.g. `500-42` to `50042`. Defaults to `false`.
+    #[must_use]
+    pub fn set_catenate_numbers(mut self, catenate_numbers: bool) -> WordDelimiterFilter {
+        self.catenate_numbers = catenate_numbers;
+        self
+    }
+
+    /// Sets whether all of the subwords should be catenated, e.g. `wi-fi-4000` to
+    /// `wifi4000`. Defaults to `false`.
+    #[must_use]
+    pub fn set_catenate_all(mut self, catenate_all: bool) -> WordDelimiterFilter {
+        self.catenate_all = catenate_all;
+        self
+    }
+
+    /// Sets whether the original token should be emitted as well when it is split.
+    /// Defaults to `false`.
+    #[must_use]
+    pub fn set_preserve_original(mut self, preserve_original: bool) -> WordDelimiterFilter {
+        self.preserve_original = preserve_original;
+        self
+    }
+
+    /// Sets whether tokens should be split on case changes, e.g. `PowerShot` to `Power`
+    /// and `Shot`. Defaults to `true`.
+    #[must_use]
+    pub fn set_split_on_case_change(mut self, split_on_case_change: bool) -> WordDelimiterFilter {
+        self.split_on_case_change = split_on_case_change;
+        self
+    }
+
+    /// Sets whether tokens should be split on transitions between letters and digits,
+    /// e.g. `SD500` to `SD` and `500`. Defaults to `true`.
+    #[must_use]
+    pub fn set_split_on_numerics(mut self, split_on_numerics: bool) -> WordDelimiterFilter {
+        self.split_on_numerics = split_on_numerics;
+        self
+    }
+
+    /// Sets whether trailing `'s` should be removed, e.g. `O'Neil's` to `O` and `Neil`.
+    /// Defaults to `true`.
+    #[must_use]
+    pub fn set_stem_english_possessive(
+        mut self,
+        stem_english_possessive: bool,
+    ) -> WordDelimiterFilter {
+        self.stem_english_possessive = stem_english_possessive;
+        self
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharType {
+    Lower,
+    Upper,
+    Digit,
+    Delimiter,
+}
+
+impl CharType {
+    fn of(c: char) -> CharType {
+        if c.is_uppercase() {
+            CharType::Upper
+        } else if c.is_alphabetic() {
+            CharType::Lower
+        } else if c.is_numeric() {
+            CharType::Digit
+        } else {
+            CharType::Delimiter
+        }
+    }
+
+    fn is_alpha(self) -> bool {
+        matches!(self, CharType::Lower | CharType::Upper)
+    }
+}
+
+struct Subword {
+    range: Range<usize>,
+    is_numeric: bool,
+}
+
+impl WordDelimiterFilter {
+    fn split(&self, text: &str) -> Vec<Subword> {
+        let mut subwords: Vec<Subword> = Vec::new();
+        // Start of the current subword, and type and start of its last two characters.
+        let mut start: Option<usize> = None;
+        let mut prev: Option<(CharType, usize)> = None;
+        let mut prev_prev: Option<CharType> = None;
+        let mut is_numeric = true;
+        let mut close = |start: &mut Option<usize>, end: usize, is_numeric: &mut bool| {
+            if let Some(start) = start.take() {
+                subwords.push(Subword {
+                    range: start..end,
+                    is_numeric: *is_numeric,
+                });
+            }
+            *is_numeric = true;
+        };
+        for (offset, c) in text.char_indices() {
+            let char_type = CharType::of(c);
+            if char_type == CharType::Delimiter {
+                close(&mut start, offset, &mut is_numeric);
+                prev = None;
+                prev_prev = None;
+                continue;
+            }
+            if let Some((prev_type, prev_offset)) = prev {
+                if self.split_on_case_change
+                    && prev_type == CharType::Lower
+                    && char_type == CharType::Upper
+                {
+                    close(&mut start, offset, &mut is_numeric);
+                } else if self.split_on_case_change
+                    && prev_type == CharType::Upper
+                    && char_type == CharType::Lower
+                    && prev_prev == Some(CharType::Upper)
+                {
+                    // `XMLParser` is split before the last uppercase letter.
+                    close(&mut start, prev_offset, &mut is_numeric);
+                    start = Some(prev_offset);
+                    is_numeric = false;
+                } else if self.split_on_numerics
+                    && (prev_type.is_alpha() && char_type == CharType::Digit
+                        || prev_type == CharType::Digit && char_type.is_alpha())
+                {
+                    close(&mut start, offset, &mut is_numeric);
+                }
+            }
+            start.get_or_insert(offset);
+            is_numeric &= char_type == CharType::Digit;
+            prev_prev = prev.map(|(prev_type, _)| prev_type);
+            prev = Some((char_type, offset));
+        }
+        close(&mut start, text.len(), &mut is_numeric);
+        if self.stem_english_possessive && subwords.len() > 1 {
+            let last = &subwords[subwords.len() - 1].range;
+            let is_possessive = matches!(&text[last.clone()], "s" | "S")
+                && last.end == text.len()
+                && text[..last.start].ends_with(['\'', '\u{2019}']);
+            if is_possessive {
+                subwords.pop();
+            }
+        }
+        subwords
+    }
+
+    fn is_generated(&self, subword: &Subword) -> bool {
+        if subword.is_numeric {
+            self.generate_number_parts
+        } else {
+            self.generate_word_parts
+        }
+    }
+}
+
+impl TokenFilter for WordDelimiterFilter {
+    type Tokenizer<T: Tokenizer> = WordDelimiterFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> WordDelimiterFilterWrapper<T> {
+        WordDelimiterFilterWrapper {
+            config: self,
+            inner: tokenizer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct WordDelimiterFilterWrapper<T> {
+    config: WordDelimiterFilter,
+    inner: T,
+}
+
+impl<T: Tokenizer> Tokenizer for WordDelimiterFilterWrapper<T> {
+    type TokenStream<'a> = WordDelimiterFilterStream<T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&self, text: &'a str) -> Self::TokenStream<'a> {
+        WordDelimiterFilterStream {
+            config: self.config.clone(),
+            tail: self.inner.token_stream(text),
+            pending: VecDeque::new(),
+            token: Token::default(),
+            position_shift: 0,
+        }
+    }
+}
+
+pub struct WordDelimiterFilterStream<T> {
+    config: WordDelimiterFilter,
+    tail: T,
+    // Tokens built, but not emitted yet.
+    pending: VecDeque<Token>,
+    token: Token,
+    // Number of positions inserted by the tokens split so far.
+    position_shift: usize,
+}
+
+impl<T: TokenStream> WordDelimiterFilterStream<T> {
+    // Splits the current token of the tail, and appends the resulting tokens to the pending
+    // tokens.
+    fn process_token(&mut self) {
+        let token = self.tail.token();
+        let position = token.position + self.position_shift;
+        let subwords = self.config.split(&token.text);
+        if let [subword] = &subwords[..] {
+            if subword.range == (0..token.text.len()) {
+                self.pending.push_back(Token {
+                    position,
+                    ..token.clone()
+                });
+                return;
+            }
+        }
+        // The offsets of the subwords can only be computed if the text of the token has
+        // not been changed by a previous filter.
+        let has_original_offsets = token.offset_to - token.offset_from == token.text.len();
+        let make_token = |subwords: &[Subword], first_subword: usize| {
+            let text: String = subwords
+                .iter()
+                .map(|subword| &token.text[subword.range.clone()])
+                .collect();
+            let (offset_from, offset_to) = if has_original_offsets {
+                (
+                    token.offset_from + subwords[0].range.start,
+                    token.offset_from + subwords[subwords.len() - 1].range.end,
+                )
+            } else {
+                (token.offset_from, token.offset_to)
+            };
+            Token {
+                offset_from,
+                offset_to,
+                position: position + first_subword,
+                text,
+                position_length: subwords.len(),
+                keyword: false,
+                payload: token.payload.clone(),
+            }
+        };
+        let mut tokens: Vec<Token> = Vec::new();
+        if self.config.preserve_original {
+            tokens.push(Token {
+                position,
+                position_length: subwords.len().max(1),
+                keyword: false,
+                ..token.clone()
+            });
+        }
+        for (i, subword) in subwords.iter().enumerate() {
+            if self.config.is_generated(subword) {
+                tokens.push(make_token(std::slice::from_ref(subword), i));
+            }
+        }
+        let mut run_start = 0;
+        while run_start < subwords.len() {
+            let is_numeric = subwords[run_start].is_numeric;
+            let run_len = subwords[run_start..]
+                .iter()
+                .take_while(|subword| subword.is_numeric == is_numeric)
+                .count();
+            let catenate = if is_numeric {
+                self.config.catenate_numbers
+            } else {
+                self.config.catenate_words
+            };
+            if catenate && run_len > 1 {
+                tokens.push(make_token(
+                    &subwords[run_start..run_start + run_len],
+                    run_start,
+                ));
+            }
+            run_start += run_len;
+        }
+        if self.config.catenate_all && subwords.len() > 1 {
+            let catenated = make_token(&subwords, 0);
+            if !tokens.contains(&catenated) {
+                tokens.push(catenated);
+            }
+        }
+        tokens.sort_by_key(|token| token.position);
+        self.pending.extend(tokens);
+        self.position_shift += subwords.len().max(1) - 1;
+    }
+}
+
+impl<T: TokenStream> TokenStream for WordDelimiterFilterStream<T> {
+    fn advance(&mut self) -> bool {
+        while self.pending.is_empty() {
+            if !self.tail.advance() {
+                return false;
+            }
+            self.process_token();
+        }
+        self.token = self.pending.pop_front().unwrap();
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WordDelimiterFilter;
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{TextAnalyzer, Token, WhitespaceTokenizer};
+
+    fn token_stream_helper(filter: WordDelimiterFilter, text: &str) -> Vec<Token> {
+        let analyzer = TextAnalyzer::builder(WhitespaceTokenizer)
+            .filter(filter)
+            .build();
+        let mut token_stream = analyzer.token_stream(text);
+        let mut tokens: Vec<Token> = vec![];
+        let mut add_token = |token: &Token| {
+            tokens.push(token.clone());
+        };
+        token_stream.process(&mut add_token);
+        tokens
+    }
+
+    fn texts(filter: WordDelimiterFilter, text: &str) -> Vec<String> {
+        token_stream_helper(filter, text)
+            .into_iter()
+            .map(|token| token.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_word_delimiter_filter_split() {
+        let tokens =
+            token_stream_helper(WordDelimiterFilter::default(), "the PowerShot-2000 rocks");
+        assert_eq!(tokens.len(), 5);
+        assert_token(&tokens[0], 0, "the", 0, 3);
+        assert_token(&tokens[1], 1, "Power", 4, 9);
+        assert_token(&tokens[2], 2, "Shot", 9, 13);
+        assert_token(&tokens[3], 3, "2000", 14, 18);
+        assert_token(&tokens[4], 4, "rocks", 19, 24);
+        assert_eq!(
+            texts(
+                WordDelimiterFilter::default(),
+                "XMLParser iPod SD500 O'Neil's --"
+            ),
+            vec!["XML", "Parser", "i", "Pod", "SD", "500", "O", "Neil"]
+        );
+        let filter = WordDelimiterFilter::default()
+            .set_split_on_case_change(false)
+            .set_split_on_numerics(false)
+            .set_stem_english_possessive(false);
+        assert_eq!(
+            texts(filter, "PowerShot SD500 wi-fi's"),
+            vec!["PowerShot", "SD500", "wi", "fi", "s"]
+        );
+    }
+
+    #[test]
+    fn test_word_delimiter_filter_catenate() {
+        let filter = WordDelimiterFilter::default()
+            .set_catenate_words(true)
+            .set_catenate_numbers(true);
+        let tokens = token_stream_helper(filter, "wi-fi-500-42 router");
+        let positions: Vec<(&str, usize, usize)> = tokens
+            .iter()
+            .map(|token| (token.text.as_str(), token.position, token.position_length))
+            .collect();
+        assert_eq!(
+            positions,
+            vec![
+                ("wi", 0, 1),
+                ("wifi", 0, 2),
+                ("fi", 1, 1),
+                ("500", 2, 1),
+                ("50042", 2, 2),
+                ("42", 3, 1),
+                ("router", 4, 1),
+            ]
+        );
+        assert_token(&tokens[1], 0, "wifi", 0, 5);
+
+        let filter = WordDelimiterFilter::default()
+            .set_generate_word_parts(false)
+            .set_generate_number_parts(false)
+            .set_catenate_all(true)
+            .set_preserve_original(true);
+        assert_eq!(
+            texts(filter, "wi-fi-500 router"),
+            vec!["wi-fi-500", "wifi500", "router"]
+        );
+    }
+}
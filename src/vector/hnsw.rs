@@ -0,0 +1,485 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashSet};
+
+use super::MAX_LEVEL;
+use crate::query::VectorSimilarity;
+use crate::schema::{HnswOptions, VectorQuantization};
+use crate::{DocId, Score};
+
+/// A graph that can be searched with [`search_layer`].
+pub(crate) trait HnswGraph {
+    /// Writes the neighbours of a node in the layer at the given level to `output`.
+    fn neighbors(&self, node: u32, level: usize, output: &mut Vec<u32>);
+
+    /// Returns the similarity of the vector of a node to the query vector.
+    ///
+    /// `vector_buffer` can be used to decode the vector.
+    fn score(&self, query_vector: &[f32], node: u32, vector_buffer: &mut Vec<f32>) -> Score;
+}
+
+/// A node of the graph, with its similarity to the query vector.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Candidate {
+    pub score: Score,
+    pub node: u32,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Candidate) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Candidate) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    // Ties are broken in favor of the lowest node, for the search to be deterministic.
+    fn cmp(&self, other: &Candidate) -> Ordering {
+        self.score
+            .total_cmp(&other.score)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+/// Buffers reused across the searches of a graph.
+#[derive(Default)]
+pub(crate) struct SearchBuffers {
+    visited: HashSet<u32>,
+    neighbors: Vec<u32>,
+    vector: Vec<f32>,
+}
+
+/// Returns the (at most) `ef` nodes the most similar to the query vector found in the layer
+/// at the given level, starting from the entry points, from the most to the least similar.
+///
+/// Only the nodes for which `accept` returns true are returned. The other nodes are still
+/// traversed, so that the accepted nodes can be reached through them.
+pub(crate) fn search_layer<G: HnswGraph>(
+    graph: &G,
+    query_vector: &[f32],
+    entry_points: &[Candidate],
+    ef: usize,
+    level: usize,
+    accept: &dyn Fn(u32) -> bool,
+    buffers: &mut SearchBuffers,
+) -> Vec<Candidate> {
+    let SearchBuffers {
+        visited,
+        neighbors,
+        vector,
+    } = buffers;
+    visited.clear();
+    let mut candidates: BinaryHeap<Candidate> = BinaryHeap::new();
+    let mut results: BinaryHeap<Reverse<Candidate>> = BinaryHeap::new();
+    let push_result = |results: &mut BinaryHeap<Reverse<Candidate>>, candidate: Candidate| {
+        if accept(candidate.node) {
+            results.push(Reverse(candidate));
+            if results.len() > ef {
+                results.pop();
+            }
+        }
+    };
+    for &entry_point in entry_points {
+        if visited.insert(entry_point.node) {
+            candidates.push(entry_point);
+            push_result(&mut results, entry_point);
+        }
+    }
+    let worst_score = |results: &BinaryHeap<Reverse<Candidate>>| {
+        if results.len() >= ef {
+            results.peek().map(|Reverse(worst)| worst.score)
+        } else {
+            None
+        }
+    };
+    while let Some(candidate) = candidates.pop() {
+        if let Some(worst_score) = worst_score(&results) {
+            if candidate.score < worst_score {
+                break;
+            }
+        }
+        graph.neighbors(candidate.node, level, neighbors);
+        for &neighbor in neighbors.iter() {
+            if !visited.insert(neighbor) {
+                continue;
+            }
+            let score = graph.score(query_vector, neighbor, vector);
+            if let Some(worst_score) = worst_score(&results) {
+                if score <= worst_score {
+                    continue;
+                }
+            }
+            let candidate = Candidate {
+                score,
+                node: neighbor,
+            };
+            candidates.push(candidate);
+            push_result(&mut results, candidate);
+        }
+    }
+    let mut results: Vec<Candidate> = results
+        .into_iter()
+        .map(|Reverse(candidate)| candidate)
+        .collect();
+    results.sort_unstable_by(|left, right| right.cmp(left));
+    results
+}
+
+/// Returns the nodes the most similar to the query vector, from the most to the least
+/// similar, descending the layers of the graph from its entry point.
+pub(crate) fn search<G: HnswGraph>(
+    graph: &G,
+    query_vector: &[f32],
+    entry_point: u32,
+    max_level: usize,
+    ef: usize,
+    accept: &dyn Fn(u32) -> bool,
+) -> Vec<Candidate> {
+    let mut buffers = SearchBuffers::default();
+    let mut entry_points = vec![Candidate {
+        score: graph.score(query_vector, entry_point, &mut buffers.vector),
+        node: entry_point,
+    }];
+    for level in (1..=max_level).rev() {
+        entry_points = search_layer(
+            graph,
+            query_vector,
+            &entry_points,
+            1,
+            level,
+            &|_| true,
+            &mut buffers,
+        );
+    }
+    search_layer(
+        graph,
+        query_vector,
+        &entry_points,
+        ef,
+        0,
+        accept,
+        &mut buffers,
+    )
+}
+
+/// Builds the HNSW graph of the vectors of a field.
+///
+/// Vectors are added with their document, then inserted in the graph in the order they were
+/// added when calling [`HnswBuilder::build`].
+pub(crate) struct HnswBuilder {
+    options: HnswOptions,
+    dimension: usize,
+    docs: Vec<DocId>,
+    vectors: Vec<f32>,
+    levels: Vec<u8>,
+    // The neighbours of each inserted node, for each level up to its own.
+    neighbors: Vec<Vec<Vec<u32>>>,
+    entry_point: Option<u32>,
+    max_level: usize,
+}
+
+impl HnswBuilder {
+    pub fn new(options: HnswOptions, dimension: usize) -> HnswBuilder {
+        HnswBuilder {
+            options,
+            dimension,
+            docs: Vec::new(),
+            vectors: Vec::new(),
+            levels: Vec::new(),
+            neighbors: Vec::new(),
+            entry_point: None,
+            max_level: 0,
+        }
+    }
+
+    /// Adds a vector of a document.
+    pub fn add_vector(&mut self, doc: DocId, vector: &[f32]) {
+        debug_assert_eq!(vector.len(), self.dimension);
+        self.docs.push(doc);
+        self.vectors.extend_from_slice(vector);
+    }
+
+    pub fn similarity(&self) -> VectorSimilarity {
+        self.options.similarity()
+    }
+
+    pub fn quantization(&self) -> VectorQuantization {
+        self.options.quantization()
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.docs.len()
+    }
+
+    /// Returns the document of each node.
+    pub fn docs(&self) -> &[DocId] {
+        &self.docs
+    }
+
+    /// Returns the vectors of all of the nodes, concatenated.
+    pub fn vectors(&self) -> &[f32] {
+        &self.vectors
+    }
+
+    /// Returns the level of each node.
+    pub fn levels(&self) -> &[u8] {
+        &self.levels
+    }
+
+    pub fn entry_point(&self) -> Option<u32> {
+        self.entry_point
+    }
+
+    pub fn max_level(&self) -> usize {
+        self.max_level
+    }
+
+    /// Returns the neighbours of a node in the layer at the given level.
+    pub fn node_neighbors(&self, node: u32, level: usize) -> &[u32] {
+        self.neighbors[node as usize]
+            .get(level)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    fn vector(&self, node: u32) -> &[f32] {
+        let start = node as usize * self.dimension;
+        &self.vectors[start..start + self.dimension]
+    }
+
+    fn node_similarity(&self, left: u32, right: u32) -> Score {
+        self.similarity()
+            .score(self.vector(left), self.vector(right))
+    }
+
+    fn max_connections(&self, level: usize) -> usize {
+        if level == 0 {
+            2 * self.options.max_connections()
+        } else {
+            self.options.max_connections()
+        }
+    }
+
+    /// Inserts all of the vectors in the graph.
+    pub fn build(&mut self) {
+        let max_connections = self.options.max_connections();
+        self.levels = (0..self.num_nodes() as u32)
+            .map(|node| random_level(node, max_connections) as u8)
+            .collect();
+        self.neighbors = Vec::with_capacity(self.num_nodes());
+        let mut buffers = SearchBuffers::default();
+        for node in 0..self.num_nodes() as u32 {
+            self.insert(node, &mut buffers);
+        }
+    }
+
+    fn insert(&mut self, node: u32, buffers: &mut SearchBuffers) {
+        let level = self.levels[node as usize] as usize;
+        self.neighbors.push(vec![Vec::new(); level + 1]);
+        let Some(entry_point) = self.entry_point else {
+            self.entry_point = Some(node);
+            self.max_level = level;
+            return;
+        };
+        let query_vector = self.vector(node).to_vec();
+        let mut entry_points = vec![Candidate {
+            score: self.node_similarity(node, entry_point),
+            node: entry_point,
+        }];
+        for current_level in (level + 1..=self.max_level).rev() {
+            entry_points = search_layer(
+                self,
+                &query_vector,
+                &entry_points,
+                1,
+                current_level,
+                &|_| true,
+                buffers,
+            );
+        }
+        for current_level in (0..=level.min(self.max_level)).rev() {
+            let candidates = search_layer(
+                self,
+                &query_vector,
+                &entry_points,
+                self.options.ef_construction(),
+                current_level,
+                &|_| true,
+                buffers,
+            );
+            let selected = self.select_neighbors(&candidates, self.max_connections(current_level));
+            for &neighbor in &selected {
+                self.connect(neighbor, node, current_level);
+            }
+            self.neighbors[node as usize][current_level] = selected;
+            entry_points = candidates;
+        }
+        if level > self.max_level {
+            self.entry_point = Some(node);
+            self.max_level = level;
+        }
+    }
+
+    /// Adds a node to the neighbours of another one, pruning its neighbours if it has too many.
+    fn connect(&mut self, node: u32, new_neighbor: u32, level: usize) {
+        self.neighbors[node as usize][level].push(new_neighbor);
+        let max_connections = self.max_connections(level);
+        if self.neighbors[node as usize][level].len() <= max_connections {
+            return;
+        }
+        let mut candidates: Vec<Candidate> = self.neighbors[node as usize][level]
+            .iter()
+            .map(|&neighbor| Candidate {
+                score: self.node_similarity(node, neighbor),
+                node: neighbor,
+            })
+            .collect();
+        candidates.sort_unstable_by(|left, right| right.cmp(left));
+        self.neighbors[node as usize][level] = self.select_neighbors(&candidates, max_connections);
+    }
+
+    /// Selects the neighbours of a node among candidates sorted by decreasing similarity.
+    ///
+    /// A candidate is preferred if it is more similar to the node than to any of the already
+    /// selected neighbours, so that the neighbours point in diverse directions. The other
+    /// candidates only fill the remaining connections.
+    fn select_neighbors(&self, candidates: &[Candidate], max_connections: usize) -> Vec<u32> {
+        let mut selected: Vec<u32> = Vec::with_capacity(max_connections);
+        let mut discarded: Vec<u32> = Vec::new();
+        for candidate in candidates {
+            if selected.len() >= max_connections {
+                break;
+            }
+            let is_diverse = selected
+                .iter()
+                .all(|&neighbor| self.node_similarity(candidate.node, neighbor) < candidate.score);
+            if is_diverse {
+                selected.push(candidate.node);
+            } else {
+                discarded.push(candidate.node);
+            }
+        }
+        let num_missing = max_connections.saturating_sub(selected.len());
+        selected.extend(discarded.into_iter().take(num_missing));
+        selected
+    }
+}
+
+impl HnswGraph for HnswBuilder {
+    fn neighbors(&self, node: u32, level: usize, output: &mut Vec<u32>) {
+        output.clear();
+        output.extend_from_slice(self.node_neighbors(node, level));
+    }
+
+    fn score(&self, query_vector: &[f32], node: u32, _vector_buffer: &mut Vec<f32>) -> Score {
+        self.similarity().score(query_vector, self.vector(node))
+    }
+}
+
+/// Returns the level of a node, drawn from an exponential distribution with a deterministic
+/// seed, so that building the graph of the same vectors always yields the same graph.
+fn random_level(node: u32, max_connections: usize) -> usize {
+    // splitmix64
+    let mut hash = (node as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    hash ^= hash >> 31;
+    // Uniform in (0, 1].
+    let uniform = ((hash >> 11) + 1) as f64 / (1u64 << 53) as f64;
+    let level = -uniform.ln() / (max_connections as f64).ln();
+    (level as usize).min(MAX_LEVEL)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::{search, HnswBuilder};
+    use crate::query::VectorSimilarity;
+    use crate::schema::HnswOptions;
+
+    #[test]
+    fn test_hnsw_recall() {
+        let mut rng = StdRng::from_seed([3u8; 32]);
+        let dimension = 8;
+        let vectors: Vec<Vec<f32>> = (0..2_000)
+            .map(|_| (0..dimension).map(|_| rng.gen_range(-1.0..1.0)).collect())
+            .collect();
+        for similarity in [
+            VectorSimilarity::Cosine,
+            VectorSimilarity::DotProduct,
+            VectorSimilarity::L2,
+        ] {
+            let options = HnswOptions::default()
+                .set_max_connections(8)
+                .set_ef_construction(50)
+                .set_similarity(similarity);
+            let mut builder = HnswBuilder::new(options, dimension);
+            for (doc, vector) in vectors.iter().enumerate() {
+                builder.add_vector(doc as u32, vector);
+            }
+            builder.build();
+            assert!(builder.max_level() > 0);
+            let mut num_found = 0;
+            for _ in 0..20 {
+                let query: Vec<f32> = (0..dimension).map(|_| rng.gen_range(-1.0..1.0)).collect();
+                let mut exact: Vec<(f32, u32)> = vectors
+                    .iter()
+                    .enumerate()
+                    .map(|(doc, vector)| (similarity.score(&query, vector), doc as u32))
+                    .collect();
+                exact.sort_by(|left, right| right.0.total_cmp(&left.0));
+                let exact: HashSet<u32> = exact.iter().take(10).map(|&(_, doc)| doc).collect();
+                let approximate = search(
+                    &builder,
+                    &query,
+                    builder.entry_point().unwrap(),
+                    builder.max_level(),
+                    50,
+                    &|_| true,
+                );
+                num_found += approximate
+                    .iter()
+                    .take(10)
+                    .filter(|candidate| exact.contains(&candidate.node))
+                    .count();
+            }
+            assert!(num_found >= 180, "{similarity:?} recall: {num_found}/200");
+        }
+    }
+
+    #[test]
+    fn test_hnsw_filter() {
+        let options = HnswOptions::default().set_similarity(VectorSimilarity::L2);
+        let mut builder = HnswBuilder::new(options, 1);
+        for doc in 0..100u32 {
+            builder.add_vector(doc, &[doc as f32]);
+        }
+        builder.build();
+        let nodes: Vec<u32> = search(
+            &builder,
+            &[10.0],
+            builder.entry_point().unwrap(),
+            builder.max_level(),
+            3,
+            &|node| node % 2 == 1,
+        )
+        .into_iter()
+        .map(|candidate| candidate.node)
+        .collect();
+        assert_eq!(nodes, vec![9, 11, 7]);
+    }
+}
@@ -0,0 +1,37 @@
+//! The vector index stores, for each dense vector field with an HNSW graph,
+//! a hierarchical navigable small world graph of the vectors of the
+//! segment.
+//!
+//! Each vector of a document is a node of the graph. Nodes are assigned a
+//! random level, the number of nodes decreasing exponentially with the level,
+//! and are connected to their most similar neighbours in each layer up to
+//! their level. A search greedily descends the sparse upper layers from the
+//! entry point of the graph, then explores the bottom layer with a beam of
+//! `ef` candidates.
+//!
+//! This makes it possible to find the approximate nearest neighbours of a
+//! vector by scoring a small fraction of the vectors of the segment, rather
+//! than scanning all of them. See
+//! [`KnnQuery::set_approximate_top_k`](crate::query::KnnQuery::set_approximate_top_k).
+//!
+//! The graph is built when a segment is serialized, and rebuilt from the
+//! vectors of the merged segments on merge.
+//!
+//! The vectors of the graph can be quantized to a byte per dimension (see
+//! [`VectorQuantization`](crate::schema::VectorQuantization)), so that searching
+//! the graph reads 4 times less data. The graph is still built with the
+//! full-precision vectors, and the candidates found in the graph are rescored
+//! against the full-precision vectors of the fast field.
+mod hnsw;
+mod quantization;
+mod reader;
+mod serializer;
+mod writer;
+
+pub(crate) use self::hnsw::HnswBuilder;
+pub use self::reader::{HnswReader, HnswReaders};
+pub use self::serializer::HnswSerializer;
+pub use self::writer::HnswWriter;
+
+/// Maximum level of a node of the graph.
+const MAX_LEVEL: usize = 16;
@@ -0,0 +1,90 @@
+/// Quantizes each value of a vector to a byte, given the range of the values of its
+/// dimension among the vectors of the graph.
+///
+/// This divides the size of the vectors by 4, at the cost of an error of at most half of
+/// the `1 / 255` of the range of each dimension.
+pub(crate) struct ScalarQuantizer {
+    mins: Vec<f32>,
+    scales: Vec<f32>,
+}
+
+impl ScalarQuantizer {
+    /// Computes the range of each dimension of the given vectors, concatenated.
+    pub fn fit(vectors: &[f32], dimension: usize) -> ScalarQuantizer {
+        let mut mins = vec![f32::MAX; dimension];
+        let mut maxs = vec![f32::MIN; dimension];
+        for vector in vectors.chunks_exact(dimension) {
+            for ((value, min), max) in vector.iter().zip(&mut mins).zip(&mut maxs) {
+                *min = min.min(*value);
+                *max = max.max(*value);
+            }
+        }
+        if vectors.is_empty() {
+            mins.fill(0.0);
+            maxs.fill(0.0);
+        }
+        let scales = mins
+            .iter()
+            .zip(&maxs)
+            .map(|(min, max)| (max - min) / u8::MAX as f32)
+            .collect();
+        ScalarQuantizer { mins, scales }
+    }
+
+    /// Creates a quantizer given the minimum value and the scale of each dimension.
+    pub fn from_parts(mins: Vec<f32>, scales: Vec<f32>) -> ScalarQuantizer {
+        ScalarQuantizer { mins, scales }
+    }
+
+    pub fn mins(&self) -> &[f32] {
+        &self.mins
+    }
+
+    pub fn scales(&self) -> &[f32] {
+        &self.scales
+    }
+
+    /// Appends the quantized values of a vector to `output`.
+    pub fn quantize(&self, vector: &[f32], output: &mut Vec<u8>) {
+        output.extend(vector.iter().zip(self.mins.iter().zip(&self.scales)).map(
+            |(value, (min, scale))| {
+                if *scale > 0.0 {
+                    ((value - min) / scale).round().clamp(0.0, u8::MAX as f32) as u8
+                } else {
+                    0
+                }
+            },
+        ));
+    }
+
+    /// Decodes the quantized values of a vector.
+    pub fn dequantize(&self, codes: &[u8], output: &mut Vec<f32>) {
+        output.clear();
+        output.extend(
+            codes
+                .iter()
+                .zip(self.mins.iter().zip(&self.scales))
+                .map(|(&code, (min, scale))| min + code as f32 * scale),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScalarQuantizer;
+
+    #[test]
+    fn test_scalar_quantizer() {
+        let vectors = [0.0f32, 1.0, 2.0, 1.0, -3.0, 1.0];
+        let quantizer = ScalarQuantizer::fit(&vectors, 3);
+        assert_eq!(quantizer.mins(), &[0.0, -3.0, 1.0]);
+        let mut codes = Vec::new();
+        quantizer.quantize(&[0.6, 1.0, 1.0], &mut codes);
+        assert_eq!(codes, vec![153, 255, 0]);
+        let mut vector = Vec::new();
+        quantizer.dequantize(&codes, &mut vector);
+        for (value, expected) in vector.iter().zip([0.6, 1.0, 1.0]) {
+            assert!((value - expected).abs() < 0.01);
+        }
+    }
+}
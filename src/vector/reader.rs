@@ -0,0 +1,239 @@
+use std::io;
+use std::sync::Arc;
+
+use super::hnsw::{search, HnswGraph};
+use super::quantization::ScalarQuantizer;
+use super::serializer::{quantization_code, similarity_code, NO_ENTRY_POINT};
+use crate::directory::{CompositeFile, FileSlice, OwnedBytes};
+use crate::query::VectorSimilarity;
+use crate::schema::{dense_vector_from_bytes, Field, VectorQuantization};
+use crate::space_usage::PerFieldSpaceUsage;
+use crate::{DocId, Score};
+
+const HEADER_LEN: usize = 15;
+
+/// Reader for the HNSW graphs of all of the dense vector fields with an HNSW graph.
+#[derive(Clone)]
+pub struct HnswReaders {
+    data: Arc<CompositeFile>,
+}
+
+impl HnswReaders {
+    /// Creates an HNSW reader.
+    pub fn open(file: FileSlice) -> crate::Result<HnswReaders> {
+        let data = CompositeFile::open(&file)?;
+        Ok(HnswReaders {
+            data: Arc::new(data),
+        })
+    }
+
+    /// Creates an HNSW reader for a segment without any HNSW graph.
+    pub fn empty() -> HnswReaders {
+        HnswReaders {
+            data: Arc::new(CompositeFile::empty()),
+        }
+    }
+
+    /// Returns the `HnswReader` for a specific field.
+    pub fn get_field(&self, field: Field) -> crate::Result<Option<HnswReader>> {
+        if let Some(file) = self.data.open_read(field) {
+            let hnsw_reader = HnswReader::open(file)?;
+            Ok(Some(hnsw_reader))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Return a break down of the space usage per field.
+    pub fn space_usage(&self) -> PerFieldSpaceUsage {
+        self.data.space_usage()
+    }
+}
+
+/// Reads the HNSW graph of a given field.
+///
+/// See the [vector module](crate::vector) for details.
+#[derive(Clone)]
+pub struct HnswReader {
+    num_nodes: u32,
+    dimension: usize,
+    similarity: VectorSimilarity,
+    quantizer: Option<Arc<ScalarQuantizer>>,
+    entry_point: Option<u32>,
+    max_level: usize,
+    docs: OwnedBytes,
+    vectors: OwnedBytes,
+    offsets: OwnedBytes,
+    neighbors: OwnedBytes,
+}
+
+impl HnswReader {
+    /// Opens the HNSW graph of a field.
+    pub fn open(file: FileSlice) -> crate::Result<HnswReader> {
+        let (header, body) = file.split(HEADER_LEN);
+        let header = header.read_bytes()?;
+        let header = header.as_slice();
+        let num_nodes = u32::from_le_bytes(header[0..4].try_into().unwrap());
+        let dimension = u32::from_le_bytes(header[4..8].try_into().unwrap()) as usize;
+        let similarity = [
+            VectorSimilarity::Cosine,
+            VectorSimilarity::DotProduct,
+            VectorSimilarity::L2,
+        ]
+        .into_iter()
+        .find(|&similarity| similarity_code(similarity) == header[8])
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unknown vector similarity code {}", header[8]),
+            )
+        })?;
+        let quantization = [VectorQuantization::None, VectorQuantization::Int8]
+            .into_iter()
+            .find(|&quantization| quantization_code(quantization) == header[9])
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown vector quantization code {}", header[9]),
+                )
+            })?;
+        let entry_point = u32::from_le_bytes(header[10..14].try_into().unwrap());
+        let max_level = header[14] as usize;
+        let num_nodes_usize = num_nodes as usize;
+        let (docs, body) = body.split(num_nodes_usize * 4);
+        let (quantizer, vectors, body) = match quantization {
+            VectorQuantization::None => {
+                let (vectors, body) = body.split(num_nodes_usize * dimension * 4);
+                (None, vectors, body)
+            }
+            VectorQuantization::Int8 => {
+                let (ranges, body) = body.split(2 * dimension * 4);
+                let ranges = ranges.read_bytes()?;
+                let mut ranges_values = Vec::new();
+                dense_vector_from_bytes(ranges.as_slice(), &mut ranges_values);
+                let scales = ranges_values.split_off(dimension);
+                let quantizer = ScalarQuantizer::from_parts(ranges_values, scales);
+                let (vectors, body) = body.split(num_nodes_usize * dimension);
+                (Some(Arc::new(quantizer)), vectors, body)
+            }
+        };
+        // The levels of the nodes are implied by the offsets of their neighbours.
+        let (_levels, body) = body.split(num_nodes_usize);
+        let (offsets, neighbors) = body.split((num_nodes_usize + 1) * 8);
+        Ok(HnswReader {
+            num_nodes,
+            dimension,
+            similarity,
+            quantizer,
+            entry_point: Some(entry_point).filter(|&entry_point| entry_point != NO_ENTRY_POINT),
+            max_level,
+            docs: docs.read_bytes()?,
+            vectors: vectors.read_bytes()?,
+            offsets: offsets.read_bytes()?,
+            neighbors: neighbors.read_bytes()?,
+        })
+    }
+
+    /// Returns the number of vectors of the graph.
+    pub fn num_nodes(&self) -> u32 {
+        self.num_nodes
+    }
+
+    /// Returns the similarity the graph was built with.
+    pub fn similarity(&self) -> VectorSimilarity {
+        self.similarity
+    }
+
+    /// Returns true if the vectors of the graph are quantized, in which case the similarities
+    /// returned by [`HnswReader::search`] are approximate.
+    pub fn is_quantized(&self) -> bool {
+        self.quantizer.is_some()
+    }
+
+    /// Returns the document of the vector at the given node.
+    pub fn doc(&self, node: u32) -> DocId {
+        let start = node as usize * 4;
+        u32::from_le_bytes(self.docs.as_slice()[start..start + 4].try_into().unwrap())
+    }
+
+    fn read_u32(&self, offset: usize) -> u32 {
+        u32::from_le_bytes(
+            self.neighbors.as_slice()[offset..offset + 4]
+                .try_into()
+                .unwrap(),
+        )
+    }
+
+    /// Returns the (at most) `k` documents with the vectors the most similar to the query
+    /// vector, with their similarity, from the most to the least similar.
+    ///
+    /// `ef` is the number of candidates explored in the bottom layer of the graph: the higher,
+    /// the slower the search and the better the recall. Only the documents for which `accept`
+    /// returns true are returned.
+    pub fn search(
+        &self,
+        query_vector: &[f32],
+        k: usize,
+        ef: usize,
+        accept: impl Fn(DocId) -> bool,
+    ) -> Vec<(DocId, Score)> {
+        let Some(entry_point) = self.entry_point else {
+            return Vec::new();
+        };
+        debug_assert_eq!(query_vector.len(), self.dimension);
+        let candidates = search(
+            self,
+            query_vector,
+            entry_point,
+            self.max_level,
+            ef.max(k),
+            &|node| accept(self.doc(node)),
+        );
+        let mut docs: Vec<(DocId, Score)> = Vec::with_capacity(k);
+        for candidate in candidates {
+            if docs.len() >= k {
+                break;
+            }
+            let doc = self.doc(candidate.node);
+            // A document with several vectors is scored by the most similar one.
+            if docs.iter().all(|&(other_doc, _)| other_doc != doc) {
+                docs.push((doc, candidate.score));
+            }
+        }
+        docs
+    }
+}
+
+impl HnswGraph for HnswReader {
+    fn neighbors(&self, node: u32, level: usize, output: &mut Vec<u32>) {
+        output.clear();
+        let start = node as usize * 8;
+        let offsets = &self.offsets.as_slice()[start..start + 16];
+        let mut offset = u64::from_le_bytes(offsets[..8].try_into().unwrap()) as usize;
+        let end = u64::from_le_bytes(offsets[8..].try_into().unwrap()) as usize;
+        for _ in 0..level {
+            if offset >= end {
+                return;
+            }
+            offset += 4 * (1 + self.read_u32(offset) as usize);
+        }
+        if offset >= end {
+            return;
+        }
+        let num_neighbors = self.read_u32(offset) as usize;
+        output.extend((0..num_neighbors).map(|ord| self.read_u32(offset + 4 * (1 + ord))));
+    }
+
+    fn score(&self, query_vector: &[f32], node: u32, vector_buffer: &mut Vec<f32>) -> Score {
+        if let Some(quantizer) = &self.quantizer {
+            let start = node as usize * self.dimension;
+            let codes = &self.vectors.as_slice()[start..start + self.dimension];
+            quantizer.dequantize(codes, vector_buffer);
+        } else {
+            let start = node as usize * self.dimension * 4;
+            let bytes = &self.vectors.as_slice()[start..start + self.dimension * 4];
+            dense_vector_from_bytes(bytes, vector_buffer);
+        }
+        self.similarity.score(query_vector, vector_buffer)
+    }
+}
@@ -0,0 +1,114 @@
+use std::io;
+use std::io::Write;
+
+use super::quantization::ScalarQuantizer;
+use super::HnswBuilder;
+use crate::directory::{CompositeWrite, WritePtr};
+use crate::query::VectorSimilarity;
+use crate::schema::{Field, VectorQuantization};
+
+/// Marks an empty graph, without any entry point.
+pub(crate) const NO_ENTRY_POINT: u32 = u32::MAX;
+
+pub(crate) fn similarity_code(similarity: VectorSimilarity) -> u8 {
+    match similarity {
+        VectorSimilarity::Cosine => 0,
+        VectorSimilarity::DotProduct => 1,
+        VectorSimilarity::L2 => 2,
+    }
+}
+
+pub(crate) fn quantization_code(quantization: VectorQuantization) -> u8 {
+    match quantization {
+        VectorQuantization::None => 0,
+        VectorQuantization::Int8 => 1,
+    }
+}
+
+/// The HNSW serializer is in charge of the serialization of
+/// the HNSW graphs of all dense vector fields with an HNSW graph.
+///
+/// For each field, the serialized data is:
+/// - a header with the number of nodes as a `u32`, the dimension of the vectors as a `u32`, the
+///   similarity as a `u8`, the quantization as a `u8`, the entry point as a `u32` and the maximum
+///   level as a `u8`,
+/// - the doc id of each node, as `u32`s,
+/// - the vector of each node, as `f32`s, or for quantized vectors the minimum value and the scale
+///   of each dimension as `f32`s followed by the quantized vectors, as `u8`s,
+/// - the level of each node, as `u8`s,
+/// - the offset of the neighbours of each node, followed by the end offset, as `u64`s,
+/// - for each node and each level up to its own, the number of neighbours followed by the
+///   neighbours, as `u32`s.
+///
+/// All numbers are encoded in little endian.
+pub struct HnswSerializer {
+    composite_write: CompositeWrite,
+}
+
+impl HnswSerializer {
+    /// Constructor
+    pub fn from_write(write: WritePtr) -> io::Result<HnswSerializer> {
+        let composite_write = CompositeWrite::wrap(write);
+        Ok(HnswSerializer { composite_write })
+    }
+
+    /// Serialize the graph of the given field.
+    pub(crate) fn serialize_field(&mut self, field: Field, graph: &HnswBuilder) -> io::Result<()> {
+        let write = self.composite_write.for_field(field);
+        write.write_all(&(graph.num_nodes() as u32).to_le_bytes())?;
+        write.write_all(&(graph.dimension() as u32).to_le_bytes())?;
+        write.write_all(&[similarity_code(graph.similarity())])?;
+        write.write_all(&[quantization_code(graph.quantization())])?;
+        write.write_all(&graph.entry_point().unwrap_or(NO_ENTRY_POINT).to_le_bytes())?;
+        write.write_all(&[graph.max_level() as u8])?;
+        for doc in graph.docs() {
+            write.write_all(&doc.to_le_bytes())?;
+        }
+        match graph.quantization() {
+            VectorQuantization::None => {
+                for value in graph.vectors() {
+                    write.write_all(&value.to_le_bytes())?;
+                }
+            }
+            VectorQuantization::Int8 => {
+                let quantizer = ScalarQuantizer::fit(graph.vectors(), graph.dimension());
+                for value in quantizer.mins().iter().chain(quantizer.scales()) {
+                    write.write_all(&value.to_le_bytes())?;
+                }
+                let mut codes = Vec::with_capacity(graph.vectors().len());
+                if graph.dimension() > 0 {
+                    for vector in graph.vectors().chunks_exact(graph.dimension()) {
+                        quantizer.quantize(vector, &mut codes);
+                    }
+                }
+                write.write_all(&codes)?;
+            }
+        }
+        write.write_all(graph.levels())?;
+        let mut offset = 0u64;
+        for (node, &level) in graph.levels().iter().enumerate() {
+            write.write_all(&offset.to_le_bytes())?;
+            for current_level in 0..=level as usize {
+                offset += 4 * (1 + graph.node_neighbors(node as u32, current_level).len()) as u64;
+            }
+        }
+        write.write_all(&offset.to_le_bytes())?;
+        for (node, &level) in graph.levels().iter().enumerate() {
+            for current_level in 0..=level as usize {
+                let neighbors = graph.node_neighbors(node as u32, current_level);
+                write.write_all(&(neighbors.len() as u32).to_le_bytes())?;
+                for neighbor in neighbors {
+                    write.write_all(&neighbor.to_le_bytes())?;
+                }
+            }
+        }
+        write.flush()?;
+        Ok(())
+    }
+
+    /// Clean up / flush / close
+    pub fn close(self) -> io::Result<()> {
+        self.composite_write.close()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+use std::io;
+
+use super::{HnswBuilder, HnswSerializer};
+use crate::indexer::doc_id_mapping::DocIdMapping;
+use crate::schema::{Field, FieldType, HnswOptions, Schema, Value};
+use crate::{DocId, Document};
+
+/// The vectors of a dense vector field with an HNSW graph.
+struct FieldVectors {
+    field: Field,
+    options: HnswOptions,
+    dimension: usize,
+    docs: Vec<DocId>,
+    // The vectors of all of the documents, concatenated.
+    vectors: Vec<f32>,
+}
+
+/// The `HnswWriter` is in charge of buffering the vectors of each dense vector field with an
+/// HNSW graph, until the graphs are built when the segment is serialized.
+pub struct HnswWriter {
+    fields: Vec<FieldVectors>,
+}
+
+impl HnswWriter {
+    /// Initialize with state for tracking the dense vector fields with an HNSW graph
+    /// specified in the schema.
+    pub fn for_schema(schema: &Schema) -> HnswWriter {
+        let fields = schema
+            .fields()
+            .filter_map(|(field, field_entry)| {
+                let FieldType::DenseVector(dense_vector_options) = field_entry.field_type() else {
+                    return None;
+                };
+                Some(FieldVectors {
+                    field,
+                    options: *dense_vector_options.hnsw_options()?,
+                    dimension: dense_vector_options.dimension(),
+                    docs: Vec::new(),
+                    vectors: Vec::new(),
+                })
+            })
+            .collect();
+        HnswWriter { fields }
+    }
+
+    /// The memory used inclusive childs
+    pub fn mem_usage(&self) -> usize {
+        self.fields
+            .iter()
+            .map(|field_vectors| {
+                field_vectors.docs.capacity() * std::mem::size_of::<DocId>()
+                    + field_vectors.vectors.capacity() * std::mem::size_of::<f32>()
+            })
+            .sum()
+    }
+
+    /// Records the vectors of the given document.
+    ///
+    /// The dimension of the vectors is expected to have been checked beforehand.
+    pub fn record(&mut self, doc: DocId, document: &Document) {
+        for field_vectors in &mut self.fields {
+            for vector in document
+                .get_all(field_vectors.field)
+                .flat_map(Value::as_dense_vector)
+            {
+                field_vectors.docs.push(doc);
+                field_vectors.vectors.extend_from_slice(vector);
+            }
+        }
+    }
+
+    /// Builds the graphs of all fields and serializes them to the serializer.
+    pub fn serialize(
+        &self,
+        mut hnsw_serializer: HnswSerializer,
+        doc_id_map: Option<&DocIdMapping>,
+    ) -> io::Result<()> {
+        for field_vectors in &self.fields {
+            let dimension = field_vectors.dimension;
+            let mut nodes: Vec<(DocId, usize)> = field_vectors
+                .docs
+                .iter()
+                .enumerate()
+                .map(|(node, &doc)| {
+                    let doc = doc_id_map
+                        .map(|doc_id_map| doc_id_map.get_new_doc_id(doc))
+                        .unwrap_or(doc);
+                    (doc, node)
+                })
+                .collect();
+            nodes.sort_unstable();
+            let mut builder = HnswBuilder::new(field_vectors.options, dimension);
+            for (doc, node) in nodes {
+                let vector = &field_vectors.vectors[node * dimension..(node + 1) * dimension];
+                builder.add_vector(doc, vector);
+            }
+            builder.build();
+            hnsw_serializer.serialize_field(field_vectors.field, &builder)?;
+        }
+        hnsw_serializer.close()?;
+        Ok(())
+    }
+}
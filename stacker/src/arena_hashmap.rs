@@ -119,8 +119,12 @@ impl Default for ArenaHashMap {
 
 impl ArenaHashMap {
     pub fn with_capacity(table_size: usize) -> ArenaHashMap {
+        ArenaHashMap::with_capacity_and_arena(table_size, MemoryArena::default())
+    }
+
+    /// Creates a hashmap storing its keys and values in the given memory arena.
+    pub fn with_capacity_and_arena(table_size: usize, memory_arena: MemoryArena) -> ArenaHashMap {
         let table_size_power_of_2 = compute_previous_power_of_two(table_size);
-        let memory_arena = MemoryArena::default();
         let table = vec![KeyValue::default(); table_size_power_of_2];
 
         ArenaHashMap {
@@ -151,6 +155,11 @@ impl ArenaHashMap {
         self.table.len() * mem::size_of::<KeyValue>()
     }
 
+    /// Returns the number of bytes of the memory arena spilled to a file.
+    pub fn spilled_num_bytes(&self) -> usize {
+        self.memory_arena.spilled_num_bytes()
+    }
+
     #[inline]
     fn is_saturated(&self) -> bool {
         self.table.len() <= self.len * 2
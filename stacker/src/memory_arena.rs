@@ -22,6 +22,11 @@
 //!
 //! Instead, you store and access your data via `.write(...)` and `.read(...)`, which under the hood
 //! stores your object using `ptr::write_unaligned` and `ptr::read_unaligned`.
+//!
+//! With the `mmap` feature, the arena can spill its pages to a memory-mapped file once it holds
+//! a given number of bytes in RAM. See [`MemoryArena::with_spill_file`].
+#[cfg(feature = "mmap")]
+use std::fs::File;
 use std::{mem, ptr};
 
 const NUM_BITS_PAGE_ADDR: usize = 20;
@@ -92,6 +97,16 @@ pub fn load<Item: Copy + 'static>(data: &[u8]) -> Item {
 #[allow(clippy::new_without_default)]
 pub struct MemoryArena {
     pages: Vec<Page>,
+    num_spilled_pages: usize,
+    #[cfg(feature = "mmap")]
+    spill: Option<SpillFile>,
+}
+
+#[cfg(feature = "mmap")]
+struct SpillFile {
+    // Number of pages kept in RAM before spilling the new pages to the file.
+    num_resident_pages: usize,
+    file: File,
 }
 
 impl Default for MemoryArena {
@@ -99,24 +114,85 @@ impl Default for MemoryArena {
         let first_page = Page::new(0);
         MemoryArena {
             pages: vec![first_page],
+            num_spilled_pages: 0,
+            #[cfg(feature = "mmap")]
+            spill: None,
         }
     }
 }
 
 impl MemoryArena {
+    /// Creates an arena keeping up to `spill_threshold_num_bytes` bytes in RAM, whose following
+    /// pages are memory-mapped from `file`.
+    ///
+    /// The spilled pages are paged in and out by the operating system, so that the arena can grow
+    /// beyond the available memory, at the cost of some speed. The file is expected to be a
+    /// temporary file: its content is overwritten, and it is only read through the mappings.
+    ///
+    /// If a page cannot be mapped, e.g. because the disk is full, it is allocated in RAM instead.
+    #[cfg(feature = "mmap")]
+    pub fn with_spill_file(spill_threshold_num_bytes: usize, file: File) -> MemoryArena {
+        MemoryArena {
+            spill: Some(SpillFile {
+                num_resident_pages: (spill_threshold_num_bytes / PAGE_SIZE).max(1),
+                file,
+            }),
+            ..MemoryArena::default()
+        }
+    }
+
     fn add_page(&mut self) -> &mut Page {
         let new_page_id = self.pages.len();
-        self.pages.push(Page::new(new_page_id));
+        let new_page = self
+            .spill_page(new_page_id)
+            .unwrap_or_else(|| Page::new(new_page_id));
+        self.pages.push(new_page);
         &mut self.pages[new_page_id]
     }
 
+    #[cfg(feature = "mmap")]
+    fn spill_page(&mut self, page_id: usize) -> Option<Page> {
+        let spill = self.spill.as_ref()?;
+        if self.pages.len() - self.num_spilled_pages < spill.num_resident_pages {
+            return None;
+        }
+        let offset = (self.num_spilled_pages * PAGE_SIZE) as u64;
+        spill.file.set_len(offset + PAGE_SIZE as u64).ok()?;
+        // Safety: the file is owned by the arena, and the mapped ranges of its pages are
+        // disjoint.
+        let mmap = unsafe {
+            memmap2::MmapOptions::new()
+                .offset(offset)
+                .len(PAGE_SIZE)
+                .map_mut(&spill.file)
+        }
+        .ok()?;
+        self.num_spilled_pages += 1;
+        Some(Page {
+            page_id,
+            len: 0,
+            data: PageData::Mmap(mmap),
+        })
+    }
+
+    #[cfg(not(feature = "mmap"))]
+    fn spill_page(&mut self, _page_id: usize) -> Option<Page> {
+        None
+    }
+
     /// Returns an estimate in number of bytes
     /// of resident memory consumed by the `MemoryArena`.
     ///
     /// Internally, it counts a number of `1MB` pages
     /// and therefore delivers an upperbound.
+    /// The pages spilled to a file are not accounted.
     pub fn mem_usage(&self) -> usize {
-        self.pages.len() * PAGE_SIZE
+        (self.pages.len() - self.num_spilled_pages) * PAGE_SIZE
+    }
+
+    /// Returns the number of bytes of the pages spilled to a file.
+    pub fn spilled_num_bytes(&self) -> usize {
+        self.num_spilled_pages * PAGE_SIZE
     }
 
     #[inline]
@@ -163,7 +239,37 @@ impl MemoryArena {
 struct Page {
     page_id: usize,
     len: usize,
-    data: Box<[u8]>,
+    data: PageData,
+}
+
+enum PageData {
+    Heap(Box<[u8]>),
+    #[cfg(feature = "mmap")]
+    Mmap(memmap2::MmapMut),
+}
+
+impl std::ops::Deref for PageData {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        match self {
+            PageData::Heap(data) => data,
+            #[cfg(feature = "mmap")]
+            PageData::Mmap(mmap) => mmap,
+        }
+    }
+}
+
+impl std::ops::DerefMut for PageData {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        match self {
+            PageData::Heap(data) => data,
+            #[cfg(feature = "mmap")]
+            PageData::Mmap(mmap) => mmap,
+        }
+    }
 }
 
 impl Page {
@@ -171,7 +277,7 @@ impl Page {
         Page {
             page_id,
             len: 0,
-            data: vec![0u8; PAGE_SIZE].into_boxed_slice(),
+            data: PageData::Heap(vec![0u8; PAGE_SIZE].into_boxed_slice()),
         }
     }
 
@@ -258,4 +364,23 @@ mod tests {
         assert_eq!(arena.read::<MyTest>(addr_a), a);
         assert_eq!(arena.read::<MyTest>(addr_b), b);
     }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_arena_spill_file() {
+        use super::PAGE_SIZE;
+
+        let mut arena = MemoryArena::with_spill_file(2 * PAGE_SIZE, tempfile::tempfile().unwrap());
+        let mut addrs = Vec::new();
+        for i in 0..5u64 {
+            let addr = arena.allocate_space(PAGE_SIZE / 2 + 1);
+            arena.write_at(addr, i);
+            addrs.push(addr);
+        }
+        assert_eq!(arena.mem_usage(), 2 * PAGE_SIZE);
+        assert_eq!(arena.spilled_num_bytes(), 3 * PAGE_SIZE);
+        for (i, addr) in addrs.into_iter().enumerate() {
+            assert_eq!(arena.read::<u64>(addr), i as u64);
+        }
+    }
 }
@@ -0,0 +1,134 @@
+use std::fs;
+use std::path::Path;
+
+use tantivy::collector::Count;
+use tantivy::directory::{Directory, RamDirectory};
+use tantivy::query::{PhraseQuery, TermQuery};
+use tantivy::schema::IndexRecordOption;
+use tantivy::{doc, DocSet, Index, Searcher, Term, TERMINATED};
+
+/// Index written by tantivy 0.19 with the index format 5, in which the dense posting lists
+/// are block encoded.
+///
+/// It holds a single segment of 300 docs, where the doc `i` has `num = i`, the tag "all",
+/// the tag "even" if `i` is even, and the text "hello world" if `i % 3 == 0` or "goodbye"
+/// otherwise.
+const PATH_TO_INDEX_V5: &str = "tests/compat_tests_data/index_v5/";
+
+/// Loads the files of the index in RAM, so that the tests do not modify them.
+fn open_index(path: &str) -> tantivy::Result<Index> {
+    let directory = RamDirectory::create();
+    for entry in fs::read_dir(path)? {
+        let path = entry?.path();
+        let file_name = path.file_name().unwrap();
+        directory.atomic_write(Path::new(file_name), &fs::read(&path)?)?;
+    }
+    Index::open(directory)
+}
+
+fn tag_doc_ids(searcher: &Searcher, tag: &str) -> tantivy::Result<Vec<u32>> {
+    let tag_field = searcher.schema().get_field("tag").unwrap();
+    let mut doc_ids = Vec::new();
+    for segment_reader in searcher.segment_readers() {
+        let inverted_index = segment_reader.inverted_index(tag_field)?;
+        let term = Term::from_field_text(tag_field, tag);
+        let mut postings = inverted_index
+            .read_postings(&term, IndexRecordOption::Basic)?
+            .unwrap();
+        while postings.doc() != TERMINATED {
+            doc_ids.push(postings.doc());
+            postings.advance();
+        }
+    }
+    Ok(doc_ids)
+}
+
+fn assert_v5_docs(searcher: &Searcher, num_segments_copies: u64) -> tantivy::Result<()> {
+    let schema = searcher.schema();
+    let text = schema.get_field("text").unwrap();
+    let tag = schema.get_field("tag").unwrap();
+
+    let count_term = |field, text: &str| {
+        let query = TermQuery::new(
+            Term::from_field_text(field, text),
+            IndexRecordOption::WithFreqs,
+        );
+        searcher.search(&query, &Count)
+    };
+    assert_eq!(count_term(tag, "all")?, 300 * num_segments_copies as usize);
+    assert_eq!(count_term(tag, "even")?, 150 * num_segments_copies as usize);
+    assert_eq!(
+        count_term(text, "goodbye")?,
+        200 * num_segments_copies as usize
+    );
+    let phrase_query = PhraseQuery::new(vec![
+        Term::from_field_text(text, "hello"),
+        Term::from_field_text(text, "world"),
+    ]);
+    assert_eq!(
+        searcher.search(&phrase_query, &Count)?,
+        100 * num_segments_copies as usize
+    );
+
+    let mut num_sum = 0;
+    for segment_reader in searcher.segment_readers() {
+        let num_column = segment_reader.fast_fields().u64("num")?;
+        num_sum += num_column.values.iter().sum::<u64>();
+    }
+    assert_eq!(num_sum, 299 * 300 / 2 * num_segments_copies);
+    Ok(())
+}
+
+#[test]
+fn test_format_5() -> tantivy::Result<()> {
+    let index = open_index(PATH_TO_INDEX_V5)?;
+    let searcher = index.reader()?.searcher();
+    assert_v5_docs(&searcher, 1)?;
+
+    // The dense posting lists of the format 5 are block encoded.
+    assert_eq!(
+        tag_doc_ids(&searcher, "all")?,
+        (0..300).collect::<Vec<u32>>()
+    );
+    assert_eq!(
+        tag_doc_ids(&searcher, "even")?,
+        (0..300).step_by(2).collect::<Vec<u32>>()
+    );
+
+    let schema = index.schema();
+    let text = schema.get_field("text").unwrap();
+    let num = schema.get_field("num").unwrap();
+    let doc = searcher.doc(tantivy::DocAddress::new(0, 3))?;
+    assert_eq!(doc.get_first(text).unwrap().as_text(), Some("hello world"));
+    assert_eq!(doc.get_first(num).unwrap().as_u64(), Some(3));
+    Ok(())
+}
+
+#[test]
+fn test_format_5_merged_with_the_current_format() -> tantivy::Result<()> {
+    let index = open_index(PATH_TO_INDEX_V5)?;
+    let schema = index.schema();
+    let text = schema.get_field("text").unwrap();
+    let tag = schema.get_field("tag").unwrap();
+    let num = schema.get_field("num").unwrap();
+    let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+    for i in 0..300u64 {
+        let words = if i % 3 == 0 { "hello world" } else { "goodbye" };
+        let mut doc = doc!(text => words, tag => "all", num => i);
+        if i % 2 == 0 {
+            doc.add_text(tag, "even");
+        }
+        index_writer.add_document(doc)?;
+    }
+    index_writer.commit()?;
+    assert_v5_docs(&index.reader()?.searcher(), 2)?;
+
+    index_writer
+        .merge(&index.searchable_segment_ids()?)
+        .wait()?;
+    index_writer.wait_merging_threads()?;
+    let searcher = index.reader()?.searcher();
+    assert_eq!(searcher.segment_readers().len(), 1);
+    assert_v5_docs(&searcher, 2)?;
+    Ok(())
+}
@@ -0,0 +1,127 @@
+#![cfg(feature = "lucene")]
+
+use std::path::Path;
+
+use tantivy::lucene::{LuceneIndex, LuceneSegmentReader};
+use tantivy::schema::{IndexRecordOption, Value};
+use tantivy::{DocSet, Postings, TERMINATED};
+
+/// Indexes written by Lucene with `tests/lucene_tests_data/GenerateLuceneIndexes.java`, in a
+/// compound and in a non compound segment.
+const PATH_TO_LUCENE_9_8_INDEXES: &str = "tests/lucene_tests_data/lucene_9_8/";
+const PATH_TO_LUCENE_9_11_INDEXES: &str = "tests/lucene_tests_data/lucene_9_11/";
+
+fn assert_postings_format(reader: &LuceneSegmentReader, postings_format: &str) {
+    for field_name in ["id", "body"] {
+        let field_info = reader.field_info(field_name).unwrap();
+        assert_eq!(
+            field_info.attributes()["PerFieldPostingsFormat.format"],
+            postings_format
+        );
+    }
+}
+
+fn assert_lucene_index(
+    path: &Path,
+    postings_format: &str,
+    is_compound_file: bool,
+) -> tantivy::Result<()> {
+    let index = LuceneIndex::open(path)?;
+    assert_eq!(index.version().major, 9);
+    assert_eq!(index.num_docs()?, 299);
+    let segment_readers = index.segment_readers()?;
+    assert_eq!(segment_readers.len(), 1);
+    let reader = &segment_readers[0];
+    assert_eq!(reader.segment().is_compound_file(), is_compound_file);
+    assert_postings_format(reader, postings_format);
+    assert_eq!((reader.max_doc(), reader.num_docs()?), (300, 299));
+    assert!(reader.alive_bitset().unwrap().is_deleted(3));
+
+    let document = reader.doc(42)?;
+    assert_eq!(document.0["id"], [Value::Str("042".to_string())]);
+    assert_eq!(
+        document.0["body"],
+        [Value::Str("hello world hello".to_string())]
+    );
+    assert_eq!(document.0["price"], [Value::I64(420)]);
+
+    let id = reader.inverted_index("id").unwrap();
+    let mut terms = id.terms();
+    let mut num_terms = 0;
+    while terms.advance()? {
+        assert_eq!(terms.key(), format!("{num_terms:03}").as_bytes());
+        num_terms += 1;
+    }
+    assert_eq!(num_terms, 300);
+    let mut postings = id.read_postings(b"042", IndexRecordOption::Basic)?.unwrap();
+    assert_eq!(postings.doc(), 42);
+    assert_eq!(postings.advance(), TERMINATED);
+
+    let body = reader.inverted_index("body").unwrap();
+    let mut terms = body.terms();
+    let mut body_terms = Vec::new();
+    while terms.advance()? {
+        body_terms.push(String::from_utf8(terms.key().to_vec()).unwrap());
+    }
+    assert_eq!(body_terms, ["goodbye", "hello", "world"]);
+    // The posting list of "world" is made of full blocks followed by a tail.
+    let mut postings = body
+        .read_postings(b"world", IndexRecordOption::WithFreqs)?
+        .unwrap();
+    for doc in 0..300 {
+        assert_eq!((postings.doc(), postings.term_freq()), (doc, 1));
+        postings.advance();
+    }
+    assert_eq!(postings.doc(), TERMINATED);
+    let mut postings = body
+        .read_postings(b"hello", IndexRecordOption::WithFreqsAndPositions)?
+        .unwrap();
+    let mut positions = Vec::new();
+    for doc in (0..300).step_by(3) {
+        assert_eq!((postings.doc(), postings.term_freq()), (doc, 2));
+        postings.positions(&mut positions);
+        assert_eq!(positions, [0, 2]);
+        postings.advance();
+    }
+    assert_eq!(postings.doc(), TERMINATED);
+    assert_eq!(body.doc_freq(b"goodbye")?, 200);
+    assert!(body
+        .read_postings(b"missing", IndexRecordOption::Basic)?
+        .is_none());
+
+    let price = reader.i64_column("price")?.unwrap();
+    for doc in 0..300 {
+        assert_eq!(price.first(doc), Some(doc as i64 * 10));
+    }
+    let tags = reader.bytes_column("tags")?.unwrap();
+    let mut tag = Vec::new();
+    assert!(tags.ord_to_bytes(1, &mut tag)?);
+    assert_eq!(tag, b"even");
+    for doc in 0..300 {
+        let ords: Vec<u64> = tags.term_ords(doc).collect();
+        if doc % 2 == 0 {
+            assert_eq!(ords, [0, 1]);
+        } else {
+            assert_eq!(ords, [0]);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[ignore = "the indexes need to be written by Lucene 9.8 with GenerateLuceneIndexes.java"]
+fn test_lucene_9_8_indexes() -> tantivy::Result<()> {
+    let path = Path::new(PATH_TO_LUCENE_9_8_INDEXES);
+    assert_lucene_index(&path.join("compound"), "Lucene90", true)?;
+    assert_lucene_index(&path.join("non_compound"), "Lucene90", false)?;
+    Ok(())
+}
+
+#[test]
+#[ignore = "the indexes need to be written by Lucene 9.11 with GenerateLuceneIndexes.java"]
+fn test_lucene_9_11_indexes() -> tantivy::Result<()> {
+    let path = Path::new(PATH_TO_LUCENE_9_11_INDEXES);
+    assert_lucene_index(&path.join("compound"), "Lucene99", true)?;
+    assert_lucene_index(&path.join("non_compound"), "Lucene99", false)?;
+    Ok(())
+}
@@ -0,0 +1,74 @@
+import java.nio.file.Path;
+import java.nio.file.Paths;
+
+import org.apache.lucene.analysis.standard.StandardAnalyzer;
+import org.apache.lucene.document.Document;
+import org.apache.lucene.document.Field;
+import org.apache.lucene.document.NumericDocValuesField;
+import org.apache.lucene.document.SortedSetDocValuesField;
+import org.apache.lucene.document.StoredField;
+import org.apache.lucene.document.StringField;
+import org.apache.lucene.document.TextField;
+import org.apache.lucene.index.IndexWriter;
+import org.apache.lucene.index.IndexWriterConfig;
+import org.apache.lucene.index.NoMergePolicy;
+import org.apache.lucene.index.Term;
+import org.apache.lucene.store.FSDirectory;
+import org.apache.lucene.util.BytesRef;
+
+/**
+ * Writes the Lucene indexes read by `tests/lucene_tests.rs`.
+ *
+ * <p>Each index holds a single segment of 300 documents, where the document `i` has:
+ * <ul>
+ *   <li>an `id` string field, indexed and stored, with the value `i` on 3 digits,
+ *   <li>a `body` text field, indexed with positions and stored, with the value
+ *       "hello world hello" if `i % 3 == 0` or "goodbye world" otherwise,
+ *   <li>a `price` field, with the numeric doc value `i * 10` and stored,
+ *   <li>a `tags` field, with the sorted set doc values "all", and "even" if `i` is even.
+ * </ul>
+ * The document 3 is deleted.
+ *
+ * <p>The indexes are written once with Lucene 9.8, whose default postings format is the
+ * `Lucene90PostingsFormat`, and once with Lucene 9.11, whose default postings format is the
+ * `Lucene99PostingsFormat`:
+ * <pre>
+ * javac -cp lucene-core-9.8.0.jar GenerateLuceneIndexes.java
+ * java -cp lucene-core-9.8.0.jar:. GenerateLuceneIndexes lucene_9_8
+ * javac -cp lucene-core-9.11.1.jar GenerateLuceneIndexes.java
+ * java -cp lucene-core-9.11.1.jar:. GenerateLuceneIndexes lucene_9_11
+ * </pre>
+ */
+public class GenerateLuceneIndexes {
+  public static void main(String[] args) throws Exception {
+    Path outputPath = Paths.get(args[0]);
+    writeIndex(outputPath.resolve("compound"), true);
+    writeIndex(outputPath.resolve("non_compound"), false);
+  }
+
+  private static void writeIndex(Path path, boolean useCompoundFile) throws Exception {
+    IndexWriterConfig config = new IndexWriterConfig(new StandardAnalyzer());
+    config.setOpenMode(IndexWriterConfig.OpenMode.CREATE);
+    config.setUseCompoundFile(useCompoundFile);
+    config.setMergePolicy(NoMergePolicy.INSTANCE);
+    try (FSDirectory directory = FSDirectory.open(path);
+        IndexWriter writer = new IndexWriter(directory, config)) {
+      for (int i = 0; i < 300; i++) {
+        Document doc = new Document();
+        doc.add(new StringField("id", String.format("%03d", i), Field.Store.YES));
+        String body = i % 3 == 0 ? "hello world hello" : "goodbye world";
+        doc.add(new TextField("body", body, Field.Store.YES));
+        doc.add(new NumericDocValuesField("price", i * 10L));
+        doc.add(new StoredField("price", i * 10L));
+        doc.add(new SortedSetDocValuesField("tags", new BytesRef("all")));
+        if (i % 2 == 0) {
+          doc.add(new SortedSetDocValuesField("tags", new BytesRef("even")));
+        }
+        writer.addDocument(doc);
+      }
+      writer.commit();
+      writer.deleteDocuments(new Term("id", "003"));
+      writer.commit();
+    }
+  }
+}
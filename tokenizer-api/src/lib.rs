@@ -26,6 +26,22 @@ pub struct Token {
     pub text: String,
     /// Is the length expressed in term of number of original tokens.
     pub position_length: usize,
+    /// Marks the token as a keyword, which should not be modified by the stemming
+    /// token filters.
+    ///
+    /// Tokenizers reusing the same `Token` for all of their tokens should reset it.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub keyword: bool,
+    /// Arbitrary bytes attached to the token, stored in the index for the fields
+    /// configured to index payloads.
+    ///
+    /// Tokenizers reusing the same `Token` for all of their tokens should reset it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub payload: Vec<u8>,
+}
+
+fn is_false(val: &bool) -> bool {
+    !val
 }
 
 impl Default for Token {
@@ -36,6 +52,8 @@ impl Default for Token {
             position: usize::MAX,
             text: String::with_capacity(200),
             position_length: 1,
+            keyword: false,
+            payload: Vec::new(),
         }
     }
 }
@@ -146,6 +164,8 @@ mod test {
             offset_to: 3,
             text: "abc".to_string(),
             position_length: 1,
+            keyword: false,
+            payload: Vec::new(),
         };
         let t2 = t1.clone();
 